@@ -5,7 +5,9 @@
 
 mod state;
 
-use amm::{AmmAbi, Message, Operation, Parameters};
+use std::collections::BTreeSet;
+
+use amm::{AmmAbi, AmmResponse, Hop, Message, Operation, Parameters};
 use fungible::{Account, FungibleTokenAbi};
 use linera_sdk::{
     base::{AccountOwner, Amount, ApplicationId, ChainId, WithContractAbi},
@@ -47,9 +49,10 @@ impl Contract for AmmContract {
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
         if self.runtime.chain_id() == self.runtime.application_creator_chain_id() {
-            self.execute_order_local(operation).await;
+            self.execute_order_local(operation).await
         } else {
             self.execute_order_remote(operation).await;
+            AmmResponse::Ok
         }
     }
 
@@ -65,31 +68,25 @@ impl Contract for AmmContract {
                 owner,
                 input_token_idx,
                 input_amount,
+                min_output_amount,
             } => {
                 self.check_account_authentication(owner);
                 // It's assumed that the tokens have already been transferred here at this point
-                assert!(
-                    input_amount > Amount::ZERO,
-                    "You can't swap with zero tokens"
-                );
+                let output_amount = self.quote(input_token_idx, input_amount);
 
-                assert!(input_token_idx < 2, "Invalid token index");
+                let message_origin_account = self.get_message_origin_account(owner);
+                if min_output_amount
+                    .is_some_and(|min_output_amount| output_amount < min_output_amount)
+                {
+                    self.transfer(owner, input_amount, message_origin_account, input_token_idx);
+                    return;
+                }
 
                 let output_token_idx = 1 - input_token_idx;
-                let input_pool_balance = self.get_pool_balance(input_token_idx);
-                let output_pool_balance = self.get_pool_balance(output_token_idx);
-
-                let output_amount = self.calculate_output_amount(
-                    input_amount,
-                    input_pool_balance,
-                    output_pool_balance,
-                );
-
                 let amm_account = self.get_amm_account();
                 self.transfer(owner, input_amount, amm_account, input_token_idx);
 
                 let amm_app_owner = self.get_amm_app_owner();
-                let message_origin_account = self.get_message_origin_account(owner);
                 self.transfer(
                     amm_app_owner,
                     output_amount,
@@ -98,6 +95,34 @@ impl Contract for AmmContract {
                 );
             }
 
+            Message::Route {
+                owner,
+                input_token_idx,
+                input_amount,
+                hops,
+                min_output_amount,
+            } => {
+                self.check_account_authentication(owner);
+                // It's assumed that the tokens have already been transferred here at this point
+                let message_origin_account = self.get_message_origin_account(owner);
+                let (_, output_amount) = self.quote_route(input_token_idx, input_amount, &hops);
+                if min_output_amount
+                    .is_some_and(|min_output_amount| output_amount < min_output_amount)
+                {
+                    self.transfer(owner, input_amount, message_origin_account, input_token_idx);
+                    return;
+                }
+
+                let (token, amount) = self.swap_route(
+                    owner,
+                    input_token_idx,
+                    input_amount,
+                    hops,
+                    min_output_amount,
+                );
+                self.transfer_token(token, owner, amount, message_origin_account);
+            }
+
             Message::AddLiquidity {
                 owner,
                 max_token0_amount,
@@ -475,13 +500,54 @@ impl AmmContract {
         }
     }
 
-    async fn execute_order_local(&mut self, operation: Operation) {
+    async fn execute_order_local(&mut self, operation: Operation) -> AmmResponse {
         match operation {
             Operation::Swap {
-                owner: _,
-                input_token_idx: _,
-                input_amount: _,
-            } => panic!("Can't swap locally"),
+                owner,
+                input_token_idx,
+                input_amount,
+                min_output_amount,
+            } => {
+                self.check_account_authentication(owner);
+                let (token, amount) = self.swap_local(owner, input_token_idx, input_amount);
+                if let Some(min_output_amount) = min_output_amount {
+                    assert!(
+                        amount >= min_output_amount,
+                        "The swap returns less than the minimum output amount"
+                    );
+                }
+                AmmResponse::Output { token, amount }
+            }
+
+            Operation::Route {
+                owner,
+                input_token_idx,
+                input_amount,
+                hops,
+                min_output_amount,
+            } => {
+                self.check_account_authentication(owner);
+                self.check_route(&hops);
+                let (token, amount) = self.swap_route(
+                    owner,
+                    input_token_idx,
+                    input_amount,
+                    hops,
+                    min_output_amount,
+                );
+                AmmResponse::Output { token, amount }
+            }
+
+            Operation::Quote {
+                input_token_idx,
+                input_amount,
+            } => {
+                let amount = self.quote(input_token_idx, input_amount);
+                let token = self.fungible_id(1 - input_token_idx);
+                AmmResponse::Output { token, amount }
+            }
+
+            Operation::Tokens => AmmResponse::Tokens(self.runtime.application_parameters().tokens),
 
             Operation::AddLiquidity {
                 owner: _,
@@ -531,6 +597,7 @@ impl AmmContract {
                 self.runtime
                     .close_chain()
                     .expect("Application is not authorized to close the chain");
+                AmmResponse::Ok
             }
         }
     }
@@ -541,6 +608,7 @@ impl AmmContract {
                 owner,
                 input_token_idx,
                 input_amount,
+                min_output_amount,
             } => {
                 self.check_account_authentication(owner);
 
@@ -551,6 +619,7 @@ impl AmmContract {
                     owner,
                     input_token_idx,
                     input_amount,
+                    min_output_amount,
                 };
 
                 self.runtime
@@ -559,6 +628,36 @@ impl AmmContract {
                     .send_to(self.get_amm_chain_id());
             }
 
+            Operation::Route {
+                owner,
+                input_token_idx,
+                input_amount,
+                hops,
+                min_output_amount,
+            } => {
+                self.check_account_authentication(owner);
+                self.check_route(&hops);
+
+                let account_on_amm_chain = self.get_account_on_amm_chain(owner);
+                self.transfer(owner, input_amount, account_on_amm_chain, input_token_idx);
+
+                let message = Message::Route {
+                    owner,
+                    input_token_idx,
+                    input_amount,
+                    hops,
+                    min_output_amount,
+                };
+                self.runtime
+                    .prepare_message(message)
+                    .with_authentication()
+                    .send_to(self.get_amm_chain_id());
+            }
+
+            Operation::Quote { .. } => panic!("Can't quote remotely"),
+
+            Operation::Tokens => panic!("Can't query the tokens remotely"),
+
             Operation::AddLiquidity {
                 owner,
                 max_token0_amount,
@@ -616,6 +715,139 @@ impl AmmContract {
         }
     }
 
+    /// Returns the amount of the other token that a swap of `input_amount` would return.
+    fn quote(&mut self, input_token_idx: u32, input_amount: Amount) -> Amount {
+        assert!(
+            input_amount > Amount::ZERO,
+            "You can't swap with zero tokens"
+        );
+        assert!(input_token_idx < 2, "Invalid token index");
+
+        let input_pool_balance = self.get_pool_balance(input_token_idx);
+        let output_pool_balance = self.get_pool_balance(1 - input_token_idx);
+        self.calculate_output_amount(input_amount, input_pool_balance, output_pool_balance)
+    }
+
+    /// Returns the token and the amount that a route would return.
+    fn quote_route(
+        &mut self,
+        input_token_idx: u32,
+        input_amount: Amount,
+        hops: &[Hop],
+    ) -> (ApplicationId<FungibleTokenAbi>, Amount) {
+        let mut token = self.fungible_id(1 - input_token_idx);
+        let mut amount = self.quote(input_token_idx, input_amount);
+        for hop in hops {
+            self.check_hop(hop, token);
+            let quote = Operation::Quote {
+                input_token_idx: hop.input_token_idx,
+                input_amount: amount,
+            };
+            (token, amount) = self.call_amm(hop.pool, &quote);
+        }
+        (token, amount)
+    }
+
+    /// Swaps tokens from the `owner`'s account on this chain, and returns the output to the
+    /// same account.
+    fn swap_local(
+        &mut self,
+        owner: AccountOwner,
+        input_token_idx: u32,
+        input_amount: Amount,
+    ) -> (ApplicationId<FungibleTokenAbi>, Amount) {
+        let output_amount = self.quote(input_token_idx, input_amount);
+        let output_token_idx = 1 - input_token_idx;
+
+        let amm_account = self.get_amm_account();
+        self.transfer(owner, input_amount, amm_account, input_token_idx);
+
+        let amm_app_owner = self.get_amm_app_owner();
+        let account_on_amm_chain = self.get_account_on_amm_chain(owner);
+        self.transfer(
+            amm_app_owner,
+            output_amount,
+            account_on_amm_chain,
+            output_token_idx,
+        );
+
+        (self.fungible_id(output_token_idx), output_amount)
+    }
+
+    /// Swaps tokens through this AMM and then through each of the `hops`, from and to the
+    /// `owner`'s account on this chain.
+    fn swap_route(
+        &mut self,
+        owner: AccountOwner,
+        input_token_idx: u32,
+        input_amount: Amount,
+        hops: Vec<Hop>,
+        min_output_amount: Option<Amount>,
+    ) -> (ApplicationId<FungibleTokenAbi>, Amount) {
+        let (mut token, mut amount) = self.swap_local(owner, input_token_idx, input_amount);
+        let mut hops = hops.into_iter().peekable();
+        while let Some(hop) = hops.next() {
+            self.check_hop(&hop, token);
+            let is_last_hop = hops.peek().is_none();
+            let swap = Operation::Swap {
+                owner,
+                input_token_idx: hop.input_token_idx,
+                input_amount: amount,
+                min_output_amount: min_output_amount.filter(|_| is_last_hop),
+            };
+            (token, amount) = self.call_amm(hop.pool, &swap);
+        }
+        if let Some(min_output_amount) = min_output_amount {
+            assert!(
+                amount >= min_output_amount,
+                "The route returns less than the minimum output amount"
+            );
+        }
+        (token, amount)
+    }
+
+    /// Verifies that a route goes through each AMM at most once.
+    fn check_route(&mut self, hops: &[Hop]) {
+        let this_amm = self.runtime.application_id().forget_abi();
+        let mut pools = BTreeSet::from([this_amm]);
+        assert!(
+            hops.iter().all(|hop| pools.insert(hop.pool)),
+            "A route can't swap twice in the same AMM"
+        );
+    }
+
+    /// Verifies that the `hop` swaps the `token` returned by the previous swap of the route.
+    fn check_hop(&mut self, hop: &Hop, token: ApplicationId<FungibleTokenAbi>) {
+        assert!(hop.input_token_idx < 2, "Invalid token index");
+        let tokens = match self.runtime.call_application(
+            true,
+            hop.pool.with_abi::<AmmAbi>(),
+            &Operation::Tokens,
+        ) {
+            AmmResponse::Tokens(tokens) => tokens,
+            response => panic!("Unexpected response from AMM application: {response:?}"),
+        };
+        assert_eq!(
+            tokens[hop.input_token_idx as usize], token,
+            "A route can only swap the token returned by the previous swap"
+        );
+    }
+
+    /// Calls another AMM on this chain, and returns the output of the swap or the quote.
+    fn call_amm(
+        &mut self,
+        pool: ApplicationId,
+        operation: &Operation,
+    ) -> (ApplicationId<FungibleTokenAbi>, Amount) {
+        match self
+            .runtime
+            .call_application(true, pool.with_abi::<AmmAbi>(), operation)
+        {
+            AmmResponse::Output { token, amount } => (token, amount),
+            response => panic!("Unexpected response from AMM application: {response:?}"),
+        }
+    }
+
     fn calculate_output_amount(
         &mut self,
         input_amount: Amount,
@@ -676,6 +908,16 @@ impl AmmContract {
         token_idx: u32,
     ) {
         let token = self.fungible_id(token_idx);
+        self.transfer_token(token, source_owner, amount, target_account);
+    }
+
+    fn transfer_token(
+        &mut self,
+        token: ApplicationId<FungibleTokenAbi>,
+        source_owner: AccountOwner,
+        amount: Amount,
+        target_account: Account,
+    ) {
         let operation = fungible::Operation::Transfer {
             owner: source_owner,
             amount,
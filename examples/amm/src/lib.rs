@@ -3,9 +3,10 @@
 
 /*! The ABI for the Automated Market Maker (AMM) Example Application */
 
-use async_graphql::{scalar, Request, Response};
+use async_graphql::{scalar, InputObject, Request, Response};
+use fungible::FungibleTokenAbi;
 use linera_sdk::{
-    base::{AccountOwner, Amount, ContractAbi, ServiceAbi},
+    base::{AccountOwner, Amount, ApplicationId, ContractAbi, ServiceAbi},
     graphql::GraphQLMutationRoot,
 };
 pub use matching_engine::Parameters;
@@ -15,7 +16,7 @@ pub struct AmmAbi;
 
 impl ContractAbi for AmmAbi {
     type Operation = Operation;
-    type Response = ();
+    type Response = AmmResponse;
 }
 
 impl ServiceAbi for AmmAbi {
@@ -32,11 +33,44 @@ pub enum Operation {
     /// Swap that token amount for an amount of the other token,
     /// calculated based on the current AMM ratio
     /// Owner here is the user executing the Swap
+    /// If `min_output_amount` is set and the swap would return less than that when it is
+    /// executed on the AMM chain, the input tokens are refunded instead
+    /// When executed on the AMM chain itself, e.g. when called by another AMM routing a
+    /// swap, the tokens are taken from and returned to the owner's account on that chain
     Swap {
         owner: AccountOwner,
         input_token_idx: u32,
         input_amount: Amount,
+        min_output_amount: Option<Amount>,
     },
+    /// Route operation
+    /// Swaps the input token in this AMM, then swaps the output in each of the AMMs
+    /// listed in `hops`, in order, and returns the output of the last swap
+    /// All the AMMs must have been created on the same chain, and be different from each
+    /// other, and each swap must take the token returned by the previous one. If
+    /// `min_output_amount` is set and the last swap would return less than that,
+    /// the input tokens are refunded instead
+    /// When executed on the AMM chain itself, the tokens are taken from and returned to the
+    /// owner's account on that chain
+    Route {
+        owner: AccountOwner,
+        input_token_idx: u32,
+        input_amount: Amount,
+        hops: Vec<Hop>,
+        min_output_amount: Option<Amount>,
+    },
+    /// Quote operation
+    /// Returns the amount of the other token that a swap of `input_amount` of the input
+    /// token would return, without executing it
+    /// Can only be executed on the AMM chain
+    Quote {
+        input_token_idx: u32,
+        input_amount: Amount,
+    },
+    /// Tokens operation
+    /// Returns the two tokens of this AMM
+    /// Can only be executed on the AMM chain
+    Tokens,
     /// Add liquidity operation
     /// Given a maximum token0 and token1 amount that you're willing to add,
     /// add liquidity to the AMM such that you'll be adding AT MOST
@@ -74,12 +108,43 @@ pub enum Operation {
 
 scalar!(Operation);
 
+/// A swap in another AMM, as part of a route.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, InputObject)]
+pub struct Hop {
+    /// The AMM to swap the tokens in
+    pub pool: ApplicationId,
+    /// The index of the input token in that AMM
+    pub input_token_idx: u32,
+}
+
+/// The response to an operation.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub enum AmmResponse {
+    #[default]
+    Ok,
+    /// The tokens returned, or that would be returned, by a swap
+    Output {
+        token: ApplicationId<FungibleTokenAbi>,
+        amount: Amount,
+    },
+    /// The two tokens of the AMM
+    Tokens([ApplicationId<FungibleTokenAbi>; 2]),
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Message {
     Swap {
         owner: AccountOwner,
         input_token_idx: u32,
         input_amount: Amount,
+        min_output_amount: Option<Amount>,
+    },
+    Route {
+        owner: AccountOwner,
+        input_token_idx: u32,
+        input_amount: Amount,
+        hops: Vec<Hop>,
+        min_output_amount: Option<Amount>,
     },
     AddLiquidity {
         owner: AccountOwner,
@@ -0,0 +1,195 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration tests for routing swaps through several AMMs.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use amm::{AmmAbi, Hop, Operation, Parameters};
+use fungible::FungibleTokenAbi;
+use linera_sdk::{
+    base::{Account, AccountOwner, Amount, ApplicationId},
+    test::{ActiveChain, TestValidator},
+};
+
+/// The owner's balances of tokens A, B and C once the AMMs have been funded.
+fn initial_balances() -> [Amount; 3] {
+    [90, 80, 90].map(Amount::from_tokens)
+}
+
+/// The tokens and the AMMs of a test: one AMM swapping tokens A and B, and another one
+/// swapping tokens B and C, all created on the same chain.
+struct Pools {
+    chain: ActiveChain,
+    owner: AccountOwner,
+    tokens: [ApplicationId<FungibleTokenAbi>; 3],
+    amm_ab: ApplicationId<AmmAbi>,
+    amm_bc: ApplicationId<AmmAbi>,
+}
+
+impl Pools {
+    /// Creates the tokens, giving 100 of each to the chain owner, and the two AMMs, each
+    /// holding 10 of both its tokens.
+    async fn new() -> Self {
+        let (validator, bytecode_id) =
+            TestValidator::with_current_bytecode::<AmmAbi, Parameters, ()>().await;
+        let mut chain = validator.new_chain().await;
+        let owner = AccountOwner::from(chain.public_key());
+
+        let fungible_bytecode_id = chain
+            .publish_bytecodes_in::<FungibleTokenAbi, fungible::Parameters, fungible::InitialState>(
+                "../fungible",
+            )
+            .await;
+        let mut tokens = Vec::new();
+        for ticker_symbol in ["A", "B", "C"] {
+            let initial_state = fungible::InitialStateBuilder::default()
+                .with_account(owner, Amount::from_tokens(100))
+                .build();
+            let token = chain
+                .create_application(
+                    fungible_bytecode_id,
+                    fungible::Parameters::new(ticker_symbol),
+                    initial_state,
+                    vec![],
+                )
+                .await;
+            tokens.push(token);
+        }
+        let tokens: [_; 3] = tokens.try_into().unwrap();
+
+        let mut amms = Vec::new();
+        for pair in [[tokens[0], tokens[1]], [tokens[1], tokens[2]]] {
+            let amm = chain
+                .create_application(
+                    bytecode_id,
+                    Parameters { tokens: pair },
+                    (),
+                    pair.iter().map(|token| token.forget_abi()).collect(),
+                )
+                .await;
+            let pool = Account {
+                chain_id: chain.id(),
+                owner: AccountOwner::Application(amm.forget_abi()),
+            };
+            chain
+                .add_block(|block| {
+                    for token in pair {
+                        block.with_operation(
+                            token,
+                            fungible::Operation::Transfer {
+                                owner,
+                                amount: Amount::from_tokens(10),
+                                target_account: pool,
+                            },
+                        );
+                    }
+                })
+                .await;
+            amms.push(amm);
+        }
+
+        Pools {
+            chain,
+            owner,
+            tokens,
+            amm_ab: amms[0],
+            amm_bc: amms[1],
+        }
+    }
+
+    /// Routes 10 tokens A through the AMM swapping A and B, and then through `hops`.
+    fn route(&self, hops: Vec<Hop>, min_output_amount: Option<Amount>) -> Operation {
+        Operation::Route {
+            owner: self.owner,
+            input_token_idx: 0,
+            input_amount: Amount::from_tokens(10),
+            hops,
+            min_output_amount,
+        }
+    }
+
+    /// Returns the owner's balances of tokens A, B and C.
+    async fn balances(&self) -> [Amount; 3] {
+        let mut balances = [Amount::ZERO; 3];
+        for (balance, token) in balances.iter_mut().zip(self.tokens) {
+            *balance = fungible::query_account(token, &self.chain, self.owner)
+                .await
+                .unwrap_or_default();
+        }
+        balances
+    }
+}
+
+/// Tests that a route swaps A for B in the first AMM, and then that B for C in the second one.
+#[tokio::test]
+async fn test_route_through_two_amms() {
+    let pools = Pools::new().await;
+    let hops = vec![Hop {
+        pool: pools.amm_bc.forget_abi(),
+        input_token_idx: 0,
+    }];
+
+    let route = pools.route(hops, Some(Amount::from_tokens(3)));
+    pools
+        .chain
+        .add_block(|block| {
+            block.with_operation(pools.amm_ab, route);
+        })
+        .await;
+
+    // 10 A return 10 * 10 / (10 + 10) = 5 B, which return 5 * 10 / (10 + 5) C.
+    let output = Amount::from_attos(3_333_333_333_333_333_333);
+    assert_eq!(
+        pools.balances().await,
+        [
+            Amount::from_tokens(80),
+            Amount::from_tokens(80),
+            Amount::from_tokens(90).saturating_add(output),
+        ]
+    );
+}
+
+/// Tests that a route can't swap a token other than the one returned by the previous swap.
+#[tokio::test]
+async fn test_route_rejects_hop_with_other_input_token() {
+    let pools = Pools::new().await;
+    // The first swap returns B, but this hop swaps C.
+    let hops = vec![Hop {
+        pool: pools.amm_bc.forget_abi(),
+        input_token_idx: 1,
+    }];
+
+    let route = pools.route(hops, None);
+    let result = pools
+        .chain
+        .try_add_block(|block| {
+            block.with_operation(pools.amm_ab, route);
+        })
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(pools.balances().await, initial_balances());
+}
+
+/// Tests that a route fails if the last swap returns less than the minimum output amount.
+#[tokio::test]
+async fn test_route_enforces_min_output_amount() {
+    let pools = Pools::new().await;
+    let hops = vec![Hop {
+        pool: pools.amm_bc.forget_abi(),
+        input_token_idx: 0,
+    }];
+
+    // The first swap returns 5 B, but the last one only about 3.33 C.
+    let route = pools.route(hops, Some(Amount::from_tokens(4)));
+    let result = pools
+        .chain
+        .try_add_block(|block| {
+            block.with_operation(pools.amm_ab, route);
+        })
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(pools.balances().await, initial_balances());
+}
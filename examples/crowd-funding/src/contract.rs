@@ -6,7 +6,7 @@
 mod state;
 
 use crowd_funding::{CrowdFundingAbi, InstantiationArgument, Message, Operation};
-use fungible::{Account, FungibleTokenAbi};
+use fungible::{Account, FungibleClient, FungibleTokenAbi};
 use linera_sdk::{
     base::{AccountOwner, Amount, ApplicationId, WithContractAbi},
     views::{RootView, View},
@@ -41,6 +41,23 @@ impl Contract for CrowdFundingContract {
         // Validate that the application parameters were configured correctly.
         let _ = self.runtime.application_parameters();
 
+        let milestones = &argument.milestones;
+        assert!(
+            milestones.is_empty()
+                || milestones
+                    .iter()
+                    .map(|milestone| u32::from(milestone.percentage))
+                    .sum::<u32>()
+                    == 100,
+            "The percentages of the milestones must add up to 100"
+        );
+        assert!(
+            milestones
+                .windows(2)
+                .all(|pair| pair[0].release_time <= pair[1].release_time),
+            "Milestones must be sorted by release time"
+        );
+
         self.state.instantiation_argument.set(Some(argument));
 
         let deadline = self.instantiation_argument().deadline;
@@ -61,6 +78,19 @@ impl Contract for CrowdFundingContract {
             }
             Operation::Collect => self.collect_pledges(),
             Operation::Cancel => self.cancel_campaign().await,
+            Operation::ClaimRefund { owner } => {
+                self.check_account_authentication(owner);
+                let chain_id = self.runtime.chain_id();
+                if chain_id == self.runtime.application_creator_chain_id() {
+                    self.refund_pledge(owner, Account { chain_id, owner }).await;
+                } else {
+                    self.runtime
+                        .prepare_message(Message::ClaimRefund { owner })
+                        .with_authentication()
+                        .send_to(self.runtime.application_creator_chain_id());
+                }
+            }
+            Operation::ReleaseMilestones => self.release_milestones(),
         }
     }
 
@@ -75,6 +105,21 @@ impl Contract for CrowdFundingContract {
                 );
                 self.execute_pledge_with_account(owner, amount).await;
             }
+            Message::ClaimRefund { owner } => {
+                assert_eq!(
+                    self.runtime.chain_id(),
+                    self.runtime.application_creator_chain_id(),
+                    "Action can only be executed on the chain that created the crowd-funding \
+                    campaign"
+                );
+                self.check_account_authentication(owner);
+                let chain_id = self
+                    .runtime
+                    .message_id()
+                    .expect("Message ID has to be available when executing a message")
+                    .chain_id;
+                self.refund_pledge(owner, Account { chain_id, owner }).await;
+            }
         }
     }
 
@@ -90,6 +135,29 @@ impl CrowdFundingContract {
         self.runtime.application_parameters()
     }
 
+    /// Returns a client to call the fungible token application, forwarding the authenticated
+    /// signer.
+    fn fungible(&mut self) -> FungibleClient<'_, Self> {
+        let fungible_id = self.fungible_id();
+        FungibleClient::new(&mut self.runtime, fungible_id).with_authentication()
+    }
+
+    /// Verifies that the `owner` authorized the current operation or message.
+    fn check_account_authentication(&mut self, owner: AccountOwner) {
+        match owner {
+            AccountOwner::User(address) => assert_eq!(
+                self.runtime.authenticated_signer(),
+                Some(address),
+                "The requested refund is not correctly authenticated."
+            ),
+            AccountOwner::Application(id) => assert_eq!(
+                self.runtime.authenticated_caller_id(),
+                Some(id),
+                "The requested refund is not correctly authenticated."
+            ),
+        }
+    }
+
     /// Adds a pledge from a local account to the remote campaign chain.
     fn execute_pledge_with_transfer(&mut self, owner: AccountOwner, amount: Amount) {
         assert!(amount > Amount::ZERO, "Pledge is empty");
@@ -99,14 +167,8 @@ impl CrowdFundingContract {
         // TODO(#589): Simplify this when the messaging system guarantees atomic delivery
         // of all messages created in the same operation/message.
         let target_account = Account { chain_id, owner };
-        let call = fungible::Operation::Transfer {
-            owner,
-            amount,
-            target_account,
-        };
-        let fungible_id = self.fungible_id();
-        self.runtime
-            .call_application(/* authenticated by owner */ true, fungible_id, &call);
+        // The transfer is authenticated by the owner.
+        self.fungible().transfer(owner, amount, target_account);
         // Second, schedule the attribution of the funds to the (remote) campaign.
         self.runtime
             .prepare_message(Message::PledgeWithAccount { owner, amount })
@@ -152,9 +214,76 @@ impl CrowdFundingContract {
             Status::Cancelled => panic!("Crowd-funding campaign has been cancelled"),
         }
 
-        self.send_to(total, self.instantiation_argument().owner);
         self.state.pledges.clear();
         self.state.status.set(Status::Complete);
+        if self.instantiation_argument().milestones.is_empty() {
+            self.send_to(total, self.instantiation_argument().owner);
+        } else {
+            self.state.collected.set(total);
+            self.release_milestones();
+        }
+    }
+
+    /// Releases the collected pledges of the milestones whose release time has passed.
+    ///
+    /// The last milestone releases everything that is left, including any rounding remainder.
+    fn release_milestones(&mut self) {
+        assert!(
+            self.state.status.get().is_complete(),
+            "Crowd-funding campaign has not been completed"
+        );
+        let now = self.runtime.system_time();
+        let collected = *self.state.collected.get();
+        let milestones = self.instantiation_argument().milestones.clone();
+        let mut released = *self.state.released_milestones.get() as usize;
+
+        while let Some(milestone) = milestones.get(released) {
+            if milestone.release_time > now {
+                break;
+            }
+            released += 1;
+            let amount = if released == milestones.len() {
+                self.balance()
+            } else {
+                let attos = u128::from(collected) * u128::from(milestone.percentage) / 100;
+                Amount::from_attos(attos)
+            };
+            self.send_to(amount, self.instantiation_argument().owner);
+        }
+
+        self.state.released_milestones.set(released as u32);
+    }
+
+    /// Refunds the pledge of `owner` to the `target_account`, if the campaign has reached its
+    /// deadline without reaching its target.
+    async fn refund_pledge(&mut self, owner: AccountOwner, target_account: Account) {
+        assert!(
+            matches!(self.state.status.get(), Status::Active),
+            "Crowd-funding campaign is no longer active"
+        );
+        assert!(
+            self.runtime.system_time() >= self.instantiation_argument().deadline,
+            "Crowd-funding campaign has not reached its deadline yet"
+        );
+        assert!(
+            self.balance() < self.instantiation_argument().target,
+            "Crowd-funding campaign has reached its target"
+        );
+
+        let amount = self
+            .state
+            .pledges
+            .get(&owner)
+            .await
+            .expect("view access should not fail")
+            .expect("No pledge to refund");
+        self.state
+            .pledges
+            .remove(&owner)
+            .expect("view access should not fail");
+
+        let source = AccountOwner::Application(self.runtime.application_id().forget_abi());
+        self.fungible().transfer(source, amount, target_account);
     }
 
     /// Cancels the campaign if the deadline has passed, refunding all pledges.
@@ -193,13 +322,7 @@ impl CrowdFundingContract {
     /// Queries the token application to determine the total amount of tokens in custody.
     fn balance(&mut self) -> Amount {
         let owner = AccountOwner::Application(self.runtime.application_id().forget_abi());
-        let fungible_id = self.fungible_id();
-        let response = self.runtime.call_application(
-            true,
-            fungible_id,
-            &fungible::Operation::Balance { owner },
-        );
-        match response {
+        match self.fungible().balance(owner) {
             fungible::FungibleResponse::Balance(balance) => balance,
             response => panic!("Unexpected response from fungible token application: {response:?}"),
         }
@@ -211,13 +334,8 @@ impl CrowdFundingContract {
             chain_id: self.runtime.chain_id(),
             owner,
         };
-        let transfer = fungible::Operation::Transfer {
-            owner: AccountOwner::Application(self.runtime.application_id().forget_abi()),
-            amount,
-            target_account,
-        };
-        let fungible_id = self.fungible_id();
-        self.runtime.call_application(true, fungible_id, &transfer);
+        let source = AccountOwner::Application(self.runtime.application_id().forget_abi());
+        self.fungible().transfer(source, amount, target_account);
     }
 
     /// Calls into the Fungible Token application to receive tokens from the given account.
@@ -226,13 +344,7 @@ impl CrowdFundingContract {
             chain_id: self.runtime.chain_id(),
             owner: AccountOwner::Application(self.runtime.application_id().forget_abi()),
         };
-        let transfer = fungible::Operation::Transfer {
-            owner,
-            amount,
-            target_account,
-        };
-        let fungible_id = self.fungible_id();
-        self.runtime.call_application(true, fungible_id, &transfer);
+        self.fungible().transfer(owner, amount, target_account);
     }
 
     pub fn instantiation_argument(&self) -> &InstantiationArgument {
@@ -23,7 +23,7 @@ impl ServiceAbi for CrowdFundingAbi {
 }
 
 /// The instantiation data required to create a crowd-funding campaign.
-#[derive(Clone, Copy, Debug, Deserialize, Serialize, SimpleObject)]
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
 pub struct InstantiationArgument {
     /// The receiver of the pledges of a successful campaign.
     pub owner: AccountOwner,
@@ -31,6 +31,22 @@ pub struct InstantiationArgument {
     pub deadline: Timestamp,
     /// The funding target of the campaign.
     pub target: Amount,
+    /// The stages at which the pledges of a successful campaign are released to its owner. If
+    /// there are none, the pledges are released as soon as they are collected.
+    #[serde(default)]
+    pub milestones: Vec<Milestone>,
+}
+
+/// A stage of a successful campaign, at which part of the collected pledges is released to the
+/// campaign's owner.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct Milestone {
+    /// What the owner commits to having achieved by this stage.
+    pub description: String,
+    /// The time after which the pledges of this stage can be released.
+    pub release_time: Timestamp,
+    /// The percentage of the collected pledges released at this stage.
+    pub percentage: u8,
 }
 
 impl std::fmt::Display for InstantiationArgument {
@@ -52,6 +68,11 @@ pub enum Operation {
     Collect,
     /// Cancel the campaign and refund all pledges after the campaign has reached its deadline (campaign chain only).
     Cancel,
+    /// Claim back a pledge after the campaign has reached its deadline without reaching its target
+    /// (from an account on the current chain).
+    ClaimRefund { owner: AccountOwner },
+    /// Release the collected pledges of all the milestones that are due (campaign chain only).
+    ReleaseMilestones,
 }
 
 /// Messages that can be exchanged across chains from the same application instance.
@@ -59,4 +80,6 @@ pub enum Operation {
 pub enum Message {
     /// Pledge some tokens to the campaign (from an account on the receiver chain).
     PledgeWithAccount { owner: AccountOwner, amount: Amount },
+    /// Refund the pledge of an owner to its account on the sender chain.
+    ClaimRefund { owner: AccountOwner },
 }
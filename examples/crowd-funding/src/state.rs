@@ -33,6 +33,10 @@ pub struct CrowdFundingState {
     pub pledges: MapView<AccountOwner, Amount>,
     /// The instantiation data that determine the details the campaign.
     pub instantiation_argument: RegisterView<Option<InstantiationArgument>>,
+    /// The total of the pledges collected when the campaign was completed, if it has milestones.
+    pub collected: RegisterView<Amount>,
+    /// The number of milestones whose pledges have been released.
+    pub released_milestones: RegisterView<u32>,
 }
 
 #[allow(dead_code)]
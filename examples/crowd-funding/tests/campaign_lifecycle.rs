@@ -7,10 +7,10 @@
 
 use std::iter;
 
-use crowd_funding::{CrowdFundingAbi, InstantiationArgument, Operation};
+use crowd_funding::{CrowdFundingAbi, InstantiationArgument, Milestone, Operation};
 use fungible::FungibleTokenAbi;
 use linera_sdk::{
-    base::{AccountOwner, Amount, ApplicationId, Timestamp},
+    base::{AccountOwner, Amount, ApplicationId, TimeDelta, Timestamp},
     test::TestValidator,
 };
 
@@ -51,6 +51,7 @@ async fn collect_pledges() {
         owner: campaign_account,
         deadline: Timestamp::from(u64::MAX),
         target: target_amount,
+        milestones: vec![],
     };
     let campaign_id = campaign_chain
         .create_application(
@@ -155,6 +156,7 @@ async fn cancel_successful_campaign() {
         owner: campaign_account,
         deadline: Timestamp::from(10),
         target: target_amount,
+        milestones: vec![],
     };
     let campaign_id = campaign_chain
         .create_application(
@@ -199,11 +201,11 @@ async fn cancel_successful_campaign() {
         None
     );
 
+    validator.advance_time(TimeDelta::from_micros(20));
+
     campaign_chain
         .add_block(|block| {
-            block
-                .with_timestamp(Timestamp::from(20))
-                .with_operation(campaign_id, Operation::Cancel);
+            block.with_operation(campaign_id, Operation::Cancel);
         })
         .await;
 
@@ -223,3 +225,196 @@ async fn cancel_successful_campaign() {
         );
     }
 }
+
+/// Test claiming refunds from a campaign that failed.
+///
+/// Creates a campaign on a `campaign_chain` and sets up the fungible token to use with two
+/// backer chains. Both backers pledge, but the campaign doesn't reach its target before its
+/// deadline. Each backer then claims their pledge back from their own chain.
+#[tokio::test(flavor = "multi_thread")]
+async fn claim_refunds_of_failed_campaign() {
+    let initial_amount = Amount::from_tokens(100);
+    let target_amount = Amount::from_tokens(220);
+    let pledge_amount = Amount::from_tokens(75);
+
+    let (validator, bytecode_id) = TestValidator::with_current_bytecode::<
+        CrowdFundingAbi,
+        ApplicationId<FungibleTokenAbi>,
+        InstantiationArgument,
+    >()
+    .await;
+
+    let fungible_publisher_chain = validator.new_chain().await;
+    let mut campaign_chain = validator.new_chain().await;
+    let campaign_account = AccountOwner::from(campaign_chain.public_key());
+
+    let fungible_bytecode_id = fungible_publisher_chain
+        .publish_bytecodes_in("../fungible")
+        .await;
+
+    let (token_id, backers) = fungible::create_with_accounts(
+        &validator,
+        fungible_bytecode_id,
+        iter::repeat(initial_amount).take(2),
+    )
+    .await;
+
+    let campaign_state = InstantiationArgument {
+        owner: campaign_account,
+        deadline: Timestamp::from(10),
+        target: target_amount,
+        milestones: vec![],
+    };
+    let campaign_id = campaign_chain
+        .create_application(
+            bytecode_id,
+            token_id,
+            campaign_state,
+            vec![token_id.forget_abi()],
+        )
+        .await;
+
+    for (backer_chain, backer_account, _balance) in &backers {
+        backer_chain.register_application(campaign_id).await;
+        backer_chain
+            .add_block(|block| {
+                block.with_operation(
+                    campaign_id,
+                    Operation::Pledge {
+                        owner: *backer_account,
+                        amount: pledge_amount,
+                    },
+                );
+            })
+            .await;
+    }
+    campaign_chain.handle_received_messages().await;
+
+    validator.advance_time(TimeDelta::from_micros(20));
+
+    for (backer_chain, backer_account, _balance) in &backers {
+        backer_chain
+            .add_block(|block| {
+                block.with_operation(
+                    campaign_id,
+                    Operation::ClaimRefund {
+                        owner: *backer_account,
+                    },
+                );
+            })
+            .await;
+    }
+    campaign_chain.handle_received_messages().await;
+
+    for (backer_chain, backer_account, initial_amount) in backers {
+        backer_chain.handle_received_messages().await;
+        assert_eq!(
+            fungible::query_account(token_id, &backer_chain, backer_account).await,
+            Some(initial_amount),
+        );
+    }
+    assert_eq!(
+        fungible::query_account(token_id, &campaign_chain, campaign_account).await,
+        None
+    );
+}
+
+/// Test releasing the pledges of a successful campaign in stages.
+///
+/// Creates a campaign with two milestones, releasing 40% and 60% of the pledges. Collecting the
+/// pledges releases the first stage, and the second one is released once its time has come.
+#[tokio::test(flavor = "multi_thread")]
+async fn release_milestones() {
+    let initial_amount = Amount::from_tokens(100);
+    let target_amount = Amount::from_tokens(100);
+    let pledge_amount = Amount::from_tokens(50);
+
+    let (validator, bytecode_id) = TestValidator::with_current_bytecode::<
+        CrowdFundingAbi,
+        ApplicationId<FungibleTokenAbi>,
+        InstantiationArgument,
+    >()
+    .await;
+
+    let fungible_publisher_chain = validator.new_chain().await;
+    let mut campaign_chain = validator.new_chain().await;
+    let campaign_account = AccountOwner::from(campaign_chain.public_key());
+
+    let fungible_bytecode_id = fungible_publisher_chain
+        .publish_bytecodes_in("../fungible")
+        .await;
+
+    let (token_id, backers) = fungible::create_with_accounts(
+        &validator,
+        fungible_bytecode_id,
+        iter::repeat(initial_amount).take(2),
+    )
+    .await;
+
+    let milestones = vec![
+        Milestone {
+            description: "Prototype".to_owned(),
+            release_time: Timestamp::from(0),
+            percentage: 40,
+        },
+        Milestone {
+            description: "Release".to_owned(),
+            release_time: Timestamp::from(1_000),
+            percentage: 60,
+        },
+    ];
+    let campaign_state = InstantiationArgument {
+        owner: campaign_account,
+        deadline: Timestamp::from(u64::MAX),
+        target: target_amount,
+        milestones,
+    };
+    let campaign_id = campaign_chain
+        .create_application(
+            bytecode_id,
+            token_id,
+            campaign_state,
+            vec![token_id.forget_abi()],
+        )
+        .await;
+
+    for (backer_chain, backer_account, _balance) in &backers {
+        backer_chain.register_application(campaign_id).await;
+        backer_chain
+            .add_block(|block| {
+                block.with_operation(
+                    campaign_id,
+                    Operation::Pledge {
+                        owner: *backer_account,
+                        amount: pledge_amount,
+                    },
+                );
+            })
+            .await;
+    }
+    campaign_chain.handle_received_messages().await;
+
+    campaign_chain
+        .add_block(|block| {
+            block.with_operation(campaign_id, Operation::Collect);
+        })
+        .await;
+
+    assert_eq!(
+        fungible::query_account(token_id, &campaign_chain, campaign_account).await,
+        Some(Amount::from_tokens(40)),
+    );
+
+    validator.advance_time(TimeDelta::from_micros(1_000));
+
+    campaign_chain
+        .add_block(|block| {
+            block.with_operation(campaign_id, Operation::ReleaseMilestones);
+        })
+        .await;
+
+    assert_eq!(
+        fungible::query_account(token_id, &campaign_chain, campaign_account).await,
+        Some(Amount::from_tokens(100)),
+    );
+}
@@ -90,6 +90,33 @@ impl Contract for FungibleTokenContract {
                 self.claim(source_account, amount, target_account).await;
                 FungibleResponse::Ok
             }
+
+            Operation::Approve {
+                owner,
+                spender,
+                allowance,
+            } => {
+                self.check_account_authentication(owner);
+                self.state.approve(owner, spender, allowance);
+                FungibleResponse::Ok
+            }
+
+            Operation::Allowance { owner, spender } => {
+                let allowance = self.state.allowance(owner, spender).await;
+                FungibleResponse::Allowance(allowance)
+            }
+
+            Operation::TransferFrom {
+                source_account,
+                spender,
+                amount,
+                target_account,
+            } => {
+                self.check_account_authentication(spender);
+                self.transfer_from(source_account, spender, amount, target_account)
+                    .await;
+                FungibleResponse::Ok
+            }
         }
     }
 
@@ -117,6 +144,18 @@ impl Contract for FungibleTokenContract {
                 self.finish_transfer_to_account(amount, target_account, owner)
                     .await;
             }
+            Message::TransferFrom {
+                owner,
+                spender,
+                amount,
+                target_account,
+            } => {
+                self.check_account_authentication(spender);
+                self.state.spend_allowance(owner, spender, amount).await;
+                self.state.debit(owner, amount).await;
+                self.finish_transfer_to_account(amount, target_account, owner)
+                    .await;
+            }
         }
     }
 
@@ -164,6 +203,34 @@ impl FungibleTokenContract {
         }
     }
 
+    async fn transfer_from(
+        &mut self,
+        source_account: Account,
+        spender: AccountOwner,
+        amount: Amount,
+        target_account: Account,
+    ) {
+        if source_account.chain_id == self.runtime.chain_id() {
+            self.state
+                .spend_allowance(source_account.owner, spender, amount)
+                .await;
+            self.state.debit(source_account.owner, amount).await;
+            self.finish_transfer_to_account(amount, target_account, source_account.owner)
+                .await;
+        } else {
+            let message = Message::TransferFrom {
+                owner: source_account.owner,
+                spender,
+                amount,
+                target_account,
+            };
+            self.runtime
+                .prepare_message(message)
+                .with_authentication()
+                .send_to(source_account.chain_id);
+        }
+    }
+
     /// Executes the final step of a transfer where the tokens are sent to the destination.
     async fn finish_transfer_to_account(
         &mut self,
@@ -39,6 +39,19 @@ pub enum Message {
         /// Target account to transfer amount to
         target_account: Account,
     },
+
+    /// Withdraws from the given account on behalf of its owner, using the allowance approved
+    /// for the `spender`, and starts a transfer to the target account.
+    TransferFrom {
+        /// Account to withdraw from
+        owner: AccountOwner,
+        /// Owner spending the allowance
+        spender: AccountOwner,
+        /// Amount to be withdrawn
+        amount: Amount,
+        /// Target account to transfer amount to
+        target_account: Account,
+    },
 }
 
 /// Creates a fungible token application and distributes `initial_amounts` to new individual
@@ -136,3 +149,25 @@ pub async fn query_account(
             .expect("Account balance cannot be parsed as a number"),
     )
 }
+
+/// Queries the amount that `spender` may still transfer from the account owned by `owner` on
+/// a specific `chain`.
+#[cfg(all(any(test, feature = "test"), not(target_arch = "wasm32")))]
+pub async fn query_allowance(
+    application_id: ApplicationId<FungibleTokenAbi>,
+    chain: &ActiveChain,
+    owner: AccountOwner,
+    spender: AccountOwner,
+) -> Amount {
+    let query = format!(
+        "query {{ allowance(owner: {}, spender: {}) }}",
+        owner.to_value(),
+        spender.to_value()
+    );
+    let QueryOutcome { response, .. } = chain.graphql_query(application_id, query).await;
+    response["allowance"]
+        .as_str()
+        .expect("Allowance is missing from the response")
+        .parse()
+        .expect("Allowance cannot be parsed as a number")
+}
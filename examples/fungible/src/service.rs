@@ -8,7 +8,7 @@ mod state;
 use std::sync::Arc;
 
 use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
-use fungible::{Operation, Parameters};
+use fungible::{Operation, OwnerSpender, Parameters};
 use linera_sdk::{
     base::{AccountOwner, Amount, WithServiceAbi},
     graphql::GraphQLMutationRoot,
@@ -60,6 +60,24 @@ impl FungibleTokenService {
         &self.state.accounts
     }
 
+    async fn allowances(&self) -> &MapView<OwnerSpender, Amount> {
+        &self.state.allowances
+    }
+
+    /// The amount that `spender` may still transfer from the account of `owner`.
+    async fn allowance(
+        &self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+    ) -> Result<Amount, async_graphql::Error> {
+        Ok(self
+            .state
+            .allowances
+            .get(&OwnerSpender::new(owner, spender))
+            .await?
+            .unwrap_or_default())
+    }
+
     async fn ticker_symbol(&self) -> Result<String, async_graphql::Error> {
         Ok(self.runtime.application_parameters().ticker_symbol)
     }
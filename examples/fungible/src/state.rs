@@ -1,7 +1,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use fungible::InitialState;
+use fungible::{InitialState, OwnerSpender};
 use linera_sdk::{
     base::{AccountOwner, Amount},
     views::{linera_views, MapView, RootView, ViewStorageContext},
@@ -12,6 +12,7 @@ use linera_sdk::{
 #[view(context = "ViewStorageContext")]
 pub struct FungibleTokenState {
     pub accounts: MapView<AccountOwner, Amount>,
+    pub allowances: MapView<OwnerSpender, Amount>,
 }
 
 #[allow(dead_code)]
@@ -71,4 +72,47 @@ impl FungibleTokenState {
                 .expect("Failed insertion operation");
         }
     }
+
+    /// Obtains the amount that `spender` may still transfer from the account of `owner`.
+    pub(crate) async fn allowance(&self, owner: AccountOwner, spender: AccountOwner) -> Amount {
+        self.allowances
+            .get(&OwnerSpender::new(owner, spender))
+            .await
+            .expect("Failure in the retrieval")
+            .unwrap_or_default()
+    }
+
+    /// Sets the amount that `spender` may transfer from the account of `owner`.
+    pub(crate) fn approve(
+        &mut self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+        allowance: Amount,
+    ) {
+        let key = OwnerSpender::new(owner, spender);
+        if allowance == Amount::ZERO {
+            self.allowances
+                .remove(&key)
+                .expect("Failed to remove an empty allowance");
+        } else {
+            self.allowances
+                .insert(&key, allowance)
+                .expect("Failed insertion operation");
+        }
+    }
+
+    /// Tries to spend the requested `amount` from the allowance of `spender` on the account of
+    /// `owner`.
+    pub(crate) async fn spend_allowance(
+        &mut self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+        amount: Amount,
+    ) {
+        let mut allowance = self.allowance(owner, spender).await;
+        allowance
+            .try_sub_assign(amount)
+            .expect("Spender's allowance is insufficient for transfer");
+        self.approve(owner, spender, allowance);
+    }
 }
@@ -142,3 +142,99 @@ async fn test_bouncing_tokens() {
         Some(initial_amount),
     );
 }
+
+/// Test transferring tokens on behalf of their owner, from another microchain.
+///
+/// Creates the application on an `owner_chain`, initializing it with a single account with some
+/// tokens for that chain's owner, who approves an allowance for the owner of a `spender_chain`.
+/// The spender then pulls tokens from the owner's account into its own account on the
+/// `spender_chain`, and a second attempt exceeding the remaining allowance is rejected.
+#[tokio::test]
+async fn test_cross_chain_transfer_from() {
+    let initial_amount = Amount::from_tokens(20);
+    let allowance = Amount::from_tokens(10);
+    let transfer_amount = Amount::from_tokens(6);
+
+    let (validator, bytecode_id) =
+        TestValidator::with_current_bytecode::<FungibleTokenAbi, Parameters, InitialState>().await;
+    let mut owner_chain = validator.new_chain().await;
+    let owner_account = AccountOwner::from(owner_chain.public_key());
+
+    let initial_state = InitialStateBuilder::default().with_account(owner_account, initial_amount);
+    let params = Parameters::new("FUN");
+    let application_id = owner_chain
+        .create_application(bytecode_id, params, initial_state.build(), vec![])
+        .await;
+
+    let spender_chain = validator.new_chain().await;
+    let spender_account = AccountOwner::from(spender_chain.public_key());
+
+    spender_chain.register_application(application_id).await;
+
+    owner_chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Approve {
+                    owner: owner_account,
+                    spender: spender_account,
+                    allowance,
+                },
+            );
+        })
+        .await;
+
+    assert_eq!(
+        fungible::query_allowance(application_id, &owner_chain, owner_account, spender_account)
+            .await,
+        allowance,
+    );
+
+    let transfer_from = Operation::TransferFrom {
+        source_account: Account {
+            chain_id: owner_chain.id(),
+            owner: owner_account,
+        },
+        spender: spender_account,
+        amount: transfer_amount,
+        target_account: Account {
+            chain_id: spender_chain.id(),
+            owner: spender_account,
+        },
+    };
+
+    spender_chain
+        .add_block(|block| {
+            block.with_operation(application_id, transfer_from.clone());
+        })
+        .await;
+    owner_chain.handle_received_messages().await;
+    spender_chain.handle_received_messages().await;
+
+    assert_eq!(
+        fungible::query_account(application_id, &owner_chain, owner_account).await,
+        Some(initial_amount.saturating_sub(transfer_amount)),
+    );
+    assert_eq!(
+        fungible::query_account(application_id, &spender_chain, spender_account).await,
+        Some(transfer_amount),
+    );
+    assert_eq!(
+        fungible::query_allowance(application_id, &owner_chain, owner_account, spender_account)
+            .await,
+        allowance.saturating_sub(transfer_amount),
+    );
+
+    let certificate = spender_chain
+        .add_block(|block| {
+            block.with_operation(application_id, transfer_from);
+        })
+        .await;
+    let result = owner_chain
+        .try_add_block(|block| {
+            block.with_messages_from(&certificate);
+        })
+        .await;
+
+    assert!(result.is_err());
+}
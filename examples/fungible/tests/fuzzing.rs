@@ -0,0 +1,97 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Property-based tests for the Fungible Token application.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use fungible::{Account, FungibleTokenAbi, InitialState, Operation, Parameters};
+use linera_sdk::{
+    base::{AccountOwner, Amount, ApplicationId},
+    test::{
+        proptest::{prelude::*, sample::Index},
+        ActiveChain, FuzzedApplication, OperationFuzzer, TestValidator,
+    },
+};
+
+/// The number of tokens each account starts with.
+const INITIAL_TOKENS: u128 = 10;
+
+/// The number of accounts, each on its own chain.
+const ACCOUNTS: usize = 3;
+
+/// Random transfers between the accounts of a few chains.
+struct RandomTransfers;
+
+impl FuzzedApplication for RandomTransfers {
+    type Abi = FungibleTokenAbi;
+    /// The target account and the number of tokens to transfer.
+    type Action = (Index, u128);
+
+    fn actions(&self) -> impl Strategy<Value = Self::Action> {
+        (any::<Index>(), 0..2 * INITIAL_TOKENS)
+    }
+
+    async fn setup(&self) -> (Vec<ActiveChain>, ApplicationId<FungibleTokenAbi>) {
+        let (validator, bytecode_id) =
+            TestValidator::with_current_bytecode::<FungibleTokenAbi, Parameters, InitialState>()
+                .await;
+        let (application_id, accounts) = fungible::create_with_accounts(
+            &validator,
+            bytecode_id,
+            [Amount::from_tokens(INITIAL_TOKENS); ACCOUNTS],
+        )
+        .await;
+        let chains = accounts.into_iter().map(|(chain, _, _)| chain).collect();
+        (chains, application_id)
+    }
+
+    fn operation(
+        &self,
+        (target, tokens): &Self::Action,
+        chain_index: usize,
+        chains: &[ActiveChain],
+    ) -> Operation {
+        let target_chain = target.get(chains);
+        Operation::Transfer {
+            owner: AccountOwner::from(chains[chain_index].public_key()),
+            amount: Amount::from_tokens(*tokens),
+            target_account: Account {
+                chain_id: target_chain.id(),
+                owner: AccountOwner::from(target_chain.public_key()),
+            },
+        }
+    }
+
+    async fn check_invariants(
+        &self,
+        chains: &[ActiveChain],
+        application_id: ApplicationId<FungibleTokenAbi>,
+    ) -> Result<(), String> {
+        let mut total = Amount::ZERO;
+        for chain in chains {
+            let owner = AccountOwner::from(chain.public_key());
+            let balance = fungible::query_account(application_id, chain, owner).await;
+            total.saturating_add_assign(balance.unwrap_or_default());
+        }
+        let expected = Amount::from_tokens(INITIAL_TOKENS * ACCOUNTS as u128);
+        if total == expected {
+            Ok(())
+        } else {
+            Err(format!(
+                "the accounts hold {total} tokens instead of {expected}"
+            ))
+        }
+    }
+}
+
+/// Tests that random transfers between accounts on different chains never change the total
+/// supply of tokens.
+#[tokio::test]
+async fn test_transfers_conserve_supply() {
+    OperationFuzzer::default()
+        .with_cases(8)
+        .with_sequence_length(1..8)
+        .run(&RandomTransfers)
+        .await;
+}
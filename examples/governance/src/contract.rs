@@ -0,0 +1,315 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use fungible::{Account, FungibleClient, FungibleResponse};
+use governance::{
+    Action, GovernanceAbi, Message, Operation, Parameters, Proposal, ProposalStatus, Vote,
+};
+use linera_sdk::{
+    base::{AccountOwner, Amount, WithContractAbi},
+    views::{RootView, View},
+    Contract, ContractRuntime,
+};
+
+use self::state::GovernanceState;
+
+pub struct GovernanceContract {
+    state: GovernanceState,
+    runtime: ContractRuntime<Self>,
+}
+
+linera_sdk::contract!(GovernanceContract);
+
+impl WithContractAbi for GovernanceContract {
+    type Abi = GovernanceAbi;
+}
+
+impl Contract for GovernanceContract {
+    type Message = Message;
+    type InstantiationArgument = ();
+    type Parameters = Parameters;
+
+    async fn load(runtime: ContractRuntime<Self>) -> Self {
+        let state = GovernanceState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        GovernanceContract { state, runtime }
+    }
+
+    async fn instantiate(&mut self, _argument: ()) {
+        // Validate that the application parameters were configured correctly.
+        self.runtime.application_parameters();
+    }
+
+    async fn execute_operation(&mut self, operation: Operation) -> Self::Response {
+        let message = match operation {
+            Operation::Propose {
+                proposer,
+                title,
+                action,
+            } => {
+                self.check_account_authentication(proposer);
+                Message::Propose {
+                    proposer: self.local_account(proposer),
+                    title,
+                    action,
+                }
+            }
+            Operation::Vote {
+                voter,
+                proposal_id,
+                in_favor,
+                amount,
+            } => {
+                self.check_account_authentication(voter);
+                assert!(amount > Amount::ZERO, "Votes must have a positive weight");
+                let dao_account = self.dao_account();
+                self.transfer_tokens(voter, amount, dao_account);
+                Message::Vote {
+                    voter: self.local_account(voter),
+                    proposal_id,
+                    in_favor,
+                    amount,
+                }
+            }
+            Operation::Tally { proposal_id } => {
+                self.check_dao_chain();
+                self.tally(proposal_id).await;
+                return;
+            }
+            Operation::Execute { proposal_id } => {
+                self.check_dao_chain();
+                self.execute(proposal_id).await;
+                return;
+            }
+        };
+
+        let dao_chain_id = self.runtime.application_creator_chain_id();
+        if self.runtime.chain_id() == dao_chain_id {
+            self.execute_dao_message(message).await;
+        } else {
+            self.runtime
+                .prepare_message(message)
+                .with_authentication()
+                .send_to(dao_chain_id);
+        }
+    }
+
+    async fn execute_message(&mut self, message: Message) {
+        self.check_dao_chain();
+        self.execute_dao_message(message).await;
+    }
+
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
+
+impl GovernanceContract {
+    /// Verifies that an operation is authenticated for this local account.
+    fn check_account_authentication(&mut self, owner: AccountOwner) {
+        match owner {
+            AccountOwner::User(address) => {
+                assert_eq!(
+                    self.runtime.authenticated_signer(),
+                    Some(address),
+                    "The requested operation is not correctly authenticated."
+                )
+            }
+            AccountOwner::Application(id) => {
+                assert_eq!(
+                    self.runtime.authenticated_caller_id(),
+                    Some(id),
+                    "The requested operation is not correctly authenticated."
+                )
+            }
+        }
+    }
+
+    /// Verifies that this is the chain where the DAO was created.
+    fn check_dao_chain(&mut self) {
+        assert_eq!(
+            self.runtime.chain_id(),
+            self.runtime.application_creator_chain_id(),
+            "Proposals are only managed on the chain that created the DAO"
+        );
+    }
+
+    /// Returns the account of `owner` on the current chain.
+    fn local_account(&mut self, owner: AccountOwner) -> Account {
+        Account {
+            chain_id: self.runtime.chain_id(),
+            owner,
+        }
+    }
+
+    /// Returns the account holding the tokens voted with.
+    fn dao_account(&mut self) -> Account {
+        Account {
+            chain_id: self.runtime.application_creator_chain_id(),
+            owner: AccountOwner::Application(self.runtime.application_id().forget_abi()),
+        }
+    }
+
+    /// Transfers locally owned tokens to the `target_account`.
+    fn transfer_tokens(&mut self, owner: AccountOwner, amount: Amount, target_account: Account) {
+        let token = self.runtime.application_parameters().token;
+        FungibleClient::new(&mut self.runtime, token)
+            .with_authentication()
+            .transfer(owner, amount, target_account);
+    }
+
+    /// Returns the balance of the DAO's account, including the escrowed votes.
+    fn dao_balance(&mut self) -> Amount {
+        let owner = self.dao_account().owner;
+        let token = self.runtime.application_parameters().token;
+        match FungibleClient::new(&mut self.runtime, token).balance(owner) {
+            FungibleResponse::Balance(balance) => balance,
+            response => panic!("Unexpected response from fungible token application: {response:?}"),
+        }
+    }
+
+    /// Transfers escrowed tokens to the `target_account`.
+    fn release_tokens(&mut self, amount: Amount, target_account: Account) {
+        let owner = self.dao_account().owner;
+        self.transfer_tokens(owner, amount, target_account);
+    }
+
+    async fn proposal(&self, proposal_id: u64) -> Option<Proposal> {
+        self.state
+            .proposals
+            .get(&proposal_id)
+            .await
+            .expect("Failed to read the proposals")
+    }
+
+    fn set_proposal(&mut self, proposal_id: u64, proposal: Proposal) {
+        self.state
+            .proposals
+            .insert(&proposal_id, proposal)
+            .expect("Failed to update the proposals");
+    }
+
+    /// Decides a proposal whose voting period is over, and returns the escrowed tokens.
+    async fn tally(&mut self, proposal_id: u64) {
+        let mut proposal = self.proposal(proposal_id).await.expect("Unknown proposal");
+        assert_eq!(
+            proposal.status,
+            ProposalStatus::Open,
+            "Proposal was already tallied"
+        );
+        assert!(
+            self.runtime.system_time() > proposal.voting_deadline,
+            "The voting period is not over yet"
+        );
+        let quorum = self.runtime.application_parameters().quorum;
+        let total = proposal.votes_for.saturating_add(proposal.votes_against);
+        proposal.status = if total >= quorum && proposal.votes_for > proposal.votes_against {
+            ProposalStatus::Approved
+        } else {
+            ProposalStatus::Rejected
+        };
+        for vote in std::mem::take(&mut proposal.votes) {
+            self.state
+                .escrowed_votes
+                .get_mut()
+                .try_sub_assign(vote.amount)
+                .expect("Escrowed votes should include the votes of open proposals");
+            self.release_tokens(vote.amount, vote.voter);
+        }
+        self.set_proposal(proposal_id, proposal);
+    }
+
+    /// Executes the action of an approved proposal whose timelock has expired.
+    async fn execute(&mut self, proposal_id: u64) {
+        let mut proposal = self.proposal(proposal_id).await.expect("Unknown proposal");
+        assert_eq!(
+            proposal.status,
+            ProposalStatus::Approved,
+            "Only approved proposals can be executed"
+        );
+        let timelock = self.runtime.application_parameters().timelock;
+        assert!(
+            self.runtime.system_time() >= proposal.voting_deadline.saturating_add(timelock),
+            "The proposal's timelock has not expired yet"
+        );
+        if let Some(Action {
+            application_id,
+            operation,
+        }) = &proposal.action
+        {
+            // The action must not act on behalf of whoever executes the proposal.
+            self.runtime
+                .call_raw_application(false, *application_id, operation);
+            // The DAO's account is also its treasury, but the tokens of the votes on other
+            // proposals must stay there until these are tallied.
+            assert!(
+                self.dao_balance() >= *self.state.escrowed_votes.get(),
+                "The action must not spend the tokens voted with on open proposals"
+            );
+        }
+        proposal.status = ProposalStatus::Executed;
+        self.set_proposal(proposal_id, proposal);
+    }
+
+    /// Updates the proposals on the chain where the DAO was created.
+    async fn execute_dao_message(&mut self, message: Message) {
+        match message {
+            Message::Propose {
+                proposer,
+                title,
+                action,
+            } => {
+                let voting_period = self.runtime.application_parameters().voting_period;
+                let proposal = Proposal {
+                    proposer,
+                    title,
+                    action,
+                    voting_deadline: self.runtime.system_time().saturating_add(voting_period),
+                    votes_for: Amount::ZERO,
+                    votes_against: Amount::ZERO,
+                    votes: Vec::new(),
+                    status: ProposalStatus::Open,
+                };
+                let proposal_id = *self.state.next_proposal_id.get();
+                self.state.next_proposal_id.set(proposal_id + 1);
+                self.set_proposal(proposal_id, proposal);
+            }
+
+            Message::Vote {
+                voter,
+                proposal_id,
+                in_favor,
+                amount,
+            } => {
+                let now = self.runtime.system_time();
+                let Some(mut proposal) = self.proposal(proposal_id).await.filter(|proposal| {
+                    proposal.status == ProposalStatus::Open && now <= proposal.voting_deadline
+                }) else {
+                    // The vote came too late: Return the tokens.
+                    self.release_tokens(amount, voter);
+                    return;
+                };
+                if in_favor {
+                    proposal.votes_for.saturating_add_assign(amount);
+                } else {
+                    proposal.votes_against.saturating_add_assign(amount);
+                }
+                self.state
+                    .escrowed_votes
+                    .get_mut()
+                    .saturating_add_assign(amount);
+                proposal.votes.push(Vote {
+                    voter,
+                    in_favor,
+                    amount,
+                });
+                self.set_proposal(proposal_id, proposal);
+            }
+        }
+    }
+}
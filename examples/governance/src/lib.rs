@@ -0,0 +1,139 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/*! ABI of the Governance Example Application */
+
+use async_graphql::{Enum, InputObject, Request, Response, SimpleObject};
+use fungible::{Account, FungibleTokenAbi};
+use linera_sdk::{
+    base::{AccountOwner, Amount, ApplicationId, ContractAbi, ServiceAbi, TimeDelta, Timestamp},
+    graphql::GraphQLMutationRoot,
+};
+use serde::{Deserialize, Serialize};
+
+pub struct GovernanceAbi;
+
+impl ContractAbi for GovernanceAbi {
+    type Operation = Operation;
+    type Response = ();
+}
+
+impl ServiceAbi for GovernanceAbi {
+    type Query = Request;
+    type QueryResponse = Response;
+}
+
+/// The rules of the DAO.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Parameters {
+    /// The token that the votes are weighted with.
+    pub token: ApplicationId<FungibleTokenAbi>,
+    /// The minimum amount of tokens that must be voted with, for or against, for a proposal
+    /// to be approved.
+    pub quorum: Amount,
+    /// How long a proposal is open for voting.
+    pub voting_period: TimeDelta,
+    /// How long an approved proposal must wait after the end of the vote before its action
+    /// can be executed.
+    pub timelock: TimeDelta,
+}
+
+/// An operation.
+///
+/// Proposals and votes can be made from any chain: They are sent to the chain where the DAO
+/// was created, which keeps the proposals and holds the tokens voted with in escrow.
+#[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
+pub enum Operation {
+    /// Submits a proposal to the vote, with an optional action to execute if it is approved.
+    Propose {
+        proposer: AccountOwner,
+        title: String,
+        action: Option<Action>,
+    },
+    /// Votes for or against a proposal, with the given `amount` of tokens. The tokens are held
+    /// in escrow until the proposal is tallied.
+    Vote {
+        voter: AccountOwner,
+        proposal_id: u64,
+        in_favor: bool,
+        amount: Amount,
+    },
+    /// Decides a proposal whose voting period is over, and returns the tokens voted with.
+    ///
+    /// This must be executed on the chain where the DAO was created.
+    Tally { proposal_id: u64 },
+    /// Executes the action of an approved proposal, once its timelock has expired.
+    ///
+    /// This must be executed on the chain where the DAO was created.
+    Execute { proposal_id: u64 },
+}
+
+/// A message sent to the chain where the DAO was created.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Message {
+    /// A proposal was submitted.
+    Propose {
+        proposer: Account,
+        title: String,
+        action: Option<Action>,
+    },
+    /// The `amount` of tokens were transferred to the DAO, to vote on a proposal.
+    Vote {
+        voter: Account,
+        proposal_id: u64,
+        in_favor: bool,
+        amount: Amount,
+    },
+}
+
+/// A call to another application, made by the DAO when a proposal is executed.
+///
+/// The called application sees the DAO as the authenticated caller, e.g. to only let the DAO
+/// change its settings. It sees no authenticated signer, whoever executes the proposal.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject, InputObject)]
+#[graphql(input_name = "ActionInput")]
+pub struct Action {
+    /// The application to call.
+    pub application_id: ApplicationId,
+    /// The BCS-serialized operation of the called application.
+    pub operation: Vec<u8>,
+}
+
+/// The state of a proposal.
+#[derive(Clone, Copy, Debug, Deserialize, Enum, Eq, PartialEq, Serialize)]
+pub enum ProposalStatus {
+    /// The proposal can be voted on.
+    Open,
+    /// The proposal was approved, and its action was not executed yet.
+    Approved,
+    /// The proposal was rejected, or did not reach the quorum.
+    Rejected,
+    /// The proposal was approved, and its action was executed.
+    Executed,
+}
+
+/// A proposal submitted to the DAO.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct Proposal {
+    pub proposer: Account,
+    pub title: String,
+    pub action: Option<Action>,
+    /// The time after which votes are no longer accepted.
+    pub voting_deadline: Timestamp,
+    /// The tokens voted with in favor of the proposal.
+    pub votes_for: Amount,
+    /// The tokens voted with against the proposal.
+    pub votes_against: Amount,
+    /// The votes, whose tokens are held in escrow until the proposal is tallied.
+    pub votes: Vec<Vote>,
+    pub status: ProposalStatus,
+}
+
+/// A vote on a proposal.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct Vote {
+    /// The account that voted, and to which the tokens are returned.
+    pub voter: Account,
+    pub in_favor: bool,
+    pub amount: Amount,
+}
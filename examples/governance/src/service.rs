@@ -0,0 +1,51 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use std::sync::Arc;
+
+use async_graphql::{EmptySubscription, Request, Response, Schema};
+use governance::{Operation, Parameters};
+use linera_sdk::{
+    base::WithServiceAbi, graphql::GraphQLMutationRoot, views::View, Service, ServiceRuntime,
+};
+
+use self::state::GovernanceState;
+
+pub struct GovernanceService {
+    state: Arc<GovernanceState>,
+    runtime: Arc<ServiceRuntime<Self>>,
+}
+
+linera_sdk::service!(GovernanceService);
+
+impl WithServiceAbi for GovernanceService {
+    type Abi = governance::GovernanceAbi;
+}
+
+impl Service for GovernanceService {
+    type Parameters = Parameters;
+
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
+        let state = GovernanceState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        GovernanceService {
+            state: Arc::new(state),
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    async fn handle_query(&self, request: Request) -> Response {
+        let schema = Schema::build(
+            self.state.clone(),
+            Operation::mutation_root(self.runtime.clone()),
+            EmptySubscription,
+        )
+        .finish();
+        schema.execute(request).await
+    }
+}
@@ -0,0 +1,23 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use governance::Proposal;
+use linera_sdk::{
+    base::Amount,
+    views::{linera_views, GraphQLView, MapView, RegisterView, RootView, ViewStorageContext},
+};
+
+/// The application state.
+///
+/// Only the chain where the DAO was created has proposals.
+#[derive(RootView, GraphQLView)]
+#[view(context = "ViewStorageContext")]
+pub struct GovernanceState {
+    /// The ID of the next proposal.
+    pub next_proposal_id: RegisterView<u64>,
+    /// The proposals, by ID.
+    pub proposals: MapView<u64, Proposal>,
+    /// The tokens voted with on the proposals that were not tallied yet. They are held in the
+    /// DAO's account, but the actions of proposals must not spend them.
+    pub escrowed_votes: RegisterView<Amount>,
+}
@@ -0,0 +1,378 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration tests for the Governance application.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use governance::{Action, GovernanceAbi, Operation, Parameters};
+use linera_sdk::{
+    base::{AccountOwner, Amount, TimeDelta},
+    test::{QueryOutcome, TestValidator},
+};
+
+/// Test approving and executing a proposal with votes from other chains.
+///
+/// Two token holders vote on a proposal to increment a counter, which is executed by the DAO once
+/// the voting period and the timelock are over. The tokens voted with are returned when the
+/// proposal is tallied.
+#[tokio::test]
+async fn test_approve_and_execute_proposal() {
+    let initial_amount = Amount::from_tokens(10);
+    let voting_period = TimeDelta::from_secs(60);
+    let timelock = TimeDelta::from_secs(120);
+
+    let (validator, bytecode_id) =
+        TestValidator::with_current_bytecode::<GovernanceAbi, Parameters, ()>().await;
+    let mut dao_chain = validator.new_chain().await;
+
+    let fungible_bytecode_id = dao_chain
+        .publish_bytecodes_in::<fungible::FungibleTokenAbi, fungible::Parameters, fungible::InitialState>("../fungible")
+        .await;
+    let (token_id, accounts) = fungible::create_with_accounts(
+        &validator,
+        fungible_bytecode_id,
+        [initial_amount, initial_amount],
+    )
+    .await;
+    let (supporter_chain, supporter, _) = &accounts[0];
+    let (opponent_chain, opponent, _) = &accounts[1];
+
+    let counter_bytecode_id = dao_chain
+        .publish_bytecodes_in::<counter::CounterAbi, (), u64>("../counter")
+        .await;
+    let counter_id = dao_chain
+        .create_application(counter_bytecode_id, (), 0, vec![])
+        .await;
+
+    dao_chain.register_application(token_id).await;
+    let parameters = Parameters {
+        token: token_id,
+        quorum: Amount::from_tokens(5),
+        voting_period,
+        timelock,
+    };
+    let dao_id = dao_chain
+        .create_application(bytecode_id, parameters, (), vec![token_id.forget_abi()])
+        .await;
+    supporter_chain.register_application(dao_id).await;
+    opponent_chain.register_application(dao_id).await;
+
+    let action = Action {
+        application_id: counter_id.forget_abi(),
+        operation: bcs::to_bytes(&5u64).unwrap(),
+    };
+    supporter_chain
+        .add_block(|block| {
+            block.with_operation(
+                dao_id,
+                Operation::Propose {
+                    proposer: *supporter,
+                    title: "Increment the counter".to_owned(),
+                    action: Some(action),
+                },
+            );
+        })
+        .await;
+    dao_chain.handle_received_messages().await;
+
+    for (chain, voter, in_favor, amount) in [
+        (supporter_chain, *supporter, true, Amount::from_tokens(7)),
+        (opponent_chain, *opponent, false, Amount::from_tokens(3)),
+    ] {
+        chain
+            .add_block(|block| {
+                block.with_operation(
+                    dao_id,
+                    Operation::Vote {
+                        voter,
+                        proposal_id: 0,
+                        in_favor,
+                        amount,
+                    },
+                );
+            })
+            .await;
+    }
+    dao_chain.handle_received_messages().await;
+
+    let QueryOutcome { response, .. } = dao_chain
+        .graphql_query(
+            dao_id,
+            "query { proposals { entry(key: 0) { value { votesFor votesAgainst status } } } }",
+        )
+        .await;
+    let proposal = &response["proposals"]["entry"]["value"];
+    assert_eq!(
+        proposal["votesFor"].as_str(),
+        Some(Amount::from_tokens(7).to_string().as_str())
+    );
+    assert_eq!(
+        proposal["votesAgainst"].as_str(),
+        Some(Amount::from_tokens(3).to_string().as_str())
+    );
+    assert_eq!(proposal["status"].as_str(), Some("OPEN"));
+
+    // The proposal can't be tallied before the end of the vote.
+    assert!(dao_chain
+        .try_add_block(|block| {
+            block.with_operation(dao_id, Operation::Tally { proposal_id: 0 });
+        })
+        .await
+        .is_err());
+
+    validator.advance_time(voting_period);
+    validator.advance_time(TimeDelta::from_secs(1));
+    dao_chain
+        .add_block(|block| {
+            block.with_operation(dao_id, Operation::Tally { proposal_id: 0 });
+        })
+        .await;
+    supporter_chain.handle_received_messages().await;
+    opponent_chain.handle_received_messages().await;
+
+    assert_eq!(
+        fungible::query_account(token_id, supporter_chain, *supporter).await,
+        Some(initial_amount),
+    );
+    assert_eq!(
+        fungible::query_account(token_id, opponent_chain, *opponent).await,
+        Some(initial_amount),
+    );
+
+    // The proposal can't be executed before the end of the timelock.
+    assert!(dao_chain
+        .try_add_block(|block| {
+            block.with_operation(dao_id, Operation::Execute { proposal_id: 0 });
+        })
+        .await
+        .is_err());
+
+    validator.advance_time(timelock);
+    dao_chain
+        .add_block(|block| {
+            block.with_operation(dao_id, Operation::Execute { proposal_id: 0 });
+        })
+        .await;
+
+    let QueryOutcome { response, .. } =
+        dao_chain.graphql_query(counter_id, "query { value }").await;
+    assert_eq!(response["value"].as_u64(), Some(5));
+}
+
+/// Test that the action of a proposal is not authenticated by the signer of the block executing
+/// it.
+///
+/// The owner of the DAO chain holds tokens, and a proposal tries to transfer them away. Although
+/// the proposal is approved, executing it fails because the token application sees no
+/// authenticated signer.
+#[tokio::test]
+async fn test_executed_actions_have_no_authenticated_signer() {
+    let initial_amount = Amount::from_tokens(10);
+    let voting_period = TimeDelta::from_secs(60);
+    let timelock = TimeDelta::from_secs(120);
+
+    let (validator, bytecode_id) =
+        TestValidator::with_current_bytecode::<GovernanceAbi, Parameters, ()>().await;
+    let publisher_chain = validator.new_chain().await;
+    let fungible_bytecode_id = publisher_chain
+        .publish_bytecodes_in::<fungible::FungibleTokenAbi, fungible::Parameters, fungible::InitialState>("../fungible")
+        .await;
+    let (token_id, mut accounts) = fungible::create_with_accounts(
+        &validator,
+        fungible_bytecode_id,
+        [initial_amount, initial_amount],
+    )
+    .await;
+    let (mut dao_chain, dao_owner, _) = accounts.pop().unwrap();
+    let (supporter_chain, supporter, _) = &accounts[0];
+
+    let parameters = Parameters {
+        token: token_id,
+        quorum: Amount::from_tokens(5),
+        voting_period,
+        timelock,
+    };
+    let dao_id = dao_chain
+        .create_application(bytecode_id, parameters, (), vec![token_id.forget_abi()])
+        .await;
+    supporter_chain.register_application(dao_id).await;
+
+    let transfer = fungible::Operation::Transfer {
+        owner: dao_owner,
+        amount: initial_amount,
+        target_account: fungible::Account {
+            chain_id: supporter_chain.id(),
+            owner: *supporter,
+        },
+    };
+    let action = Action {
+        application_id: token_id.forget_abi(),
+        operation: bcs::to_bytes(&transfer).unwrap(),
+    };
+    supporter_chain
+        .add_block(|block| {
+            block.with_operation(
+                dao_id,
+                Operation::Propose {
+                    proposer: *supporter,
+                    title: "Transfer the tokens of the DAO chain's owner".to_owned(),
+                    action: Some(action),
+                },
+            );
+        })
+        .await;
+    dao_chain.handle_received_messages().await;
+    supporter_chain
+        .add_block(|block| {
+            block.with_operation(
+                dao_id,
+                Operation::Vote {
+                    voter: *supporter,
+                    proposal_id: 0,
+                    in_favor: true,
+                    amount: Amount::from_tokens(7),
+                },
+            );
+        })
+        .await;
+    dao_chain.handle_received_messages().await;
+
+    validator.advance_time(voting_period);
+    validator.advance_time(TimeDelta::from_secs(1));
+    dao_chain
+        .add_block(|block| {
+            block.with_operation(dao_id, Operation::Tally { proposal_id: 0 });
+        })
+        .await;
+    validator.advance_time(timelock);
+
+    assert!(dao_chain
+        .try_add_block(|block| {
+            block.with_operation(dao_id, Operation::Execute { proposal_id: 0 });
+        })
+        .await
+        .is_err());
+    assert_eq!(
+        fungible::query_account(token_id, &dao_chain, dao_owner).await,
+        Some(initial_amount),
+    );
+}
+
+/// Test that the action of a proposal can't spend the tokens voted with on another proposal.
+///
+/// A first proposal is approved, with an action transferring tokens out of the DAO's account.
+/// When it is executed, the only tokens in that account are the votes on a second proposal, so
+/// the execution fails.
+#[tokio::test]
+async fn test_actions_cannot_spend_escrowed_votes() {
+    let initial_amount = Amount::from_tokens(10);
+    let voting_period = TimeDelta::from_secs(60);
+    let timelock = TimeDelta::from_secs(120);
+
+    let (validator, bytecode_id) =
+        TestValidator::with_current_bytecode::<GovernanceAbi, Parameters, ()>().await;
+    let mut dao_chain = validator.new_chain().await;
+
+    let fungible_bytecode_id = dao_chain
+        .publish_bytecodes_in::<fungible::FungibleTokenAbi, fungible::Parameters, fungible::InitialState>("../fungible")
+        .await;
+    let (token_id, accounts) = fungible::create_with_accounts(
+        &validator,
+        fungible_bytecode_id,
+        [initial_amount, initial_amount],
+    )
+    .await;
+    let (supporter_chain, supporter, _) = &accounts[0];
+    let (voter_chain, voter, _) = &accounts[1];
+
+    dao_chain.register_application(token_id).await;
+    let parameters = Parameters {
+        token: token_id,
+        quorum: Amount::from_tokens(5),
+        voting_period,
+        timelock,
+    };
+    let dao_id = dao_chain
+        .create_application(bytecode_id, parameters, (), vec![token_id.forget_abi()])
+        .await;
+    supporter_chain.register_application(dao_id).await;
+    voter_chain.register_application(dao_id).await;
+    let dao_owner = AccountOwner::Application(dao_id.forget_abi());
+
+    let transfer = fungible::Operation::Transfer {
+        owner: dao_owner,
+        amount: Amount::from_tokens(3),
+        target_account: fungible::Account {
+            chain_id: supporter_chain.id(),
+            owner: *supporter,
+        },
+    };
+    let proposals = [
+        (
+            "Spend the treasury",
+            Some(Action {
+                application_id: token_id.forget_abi(),
+                operation: bcs::to_bytes(&transfer).unwrap(),
+            }),
+        ),
+        ("Do nothing", None),
+    ];
+    let votes = [
+        (supporter_chain, *supporter, Amount::from_tokens(7)),
+        (voter_chain, *voter, Amount::from_tokens(3)),
+    ];
+    for (proposal_id, ((title, action), (chain, voter, amount))) in
+        proposals.into_iter().zip(votes).enumerate()
+    {
+        supporter_chain
+            .add_block(|block| {
+                block.with_operation(
+                    dao_id,
+                    Operation::Propose {
+                        proposer: *supporter,
+                        title: title.to_owned(),
+                        action,
+                    },
+                );
+            })
+            .await;
+        dao_chain.handle_received_messages().await;
+        chain
+            .add_block(|block| {
+                block.with_operation(
+                    dao_id,
+                    Operation::Vote {
+                        voter,
+                        proposal_id: proposal_id as u64,
+                        in_favor: true,
+                        amount,
+                    },
+                );
+            })
+            .await;
+        dao_chain.handle_received_messages().await;
+
+        if proposal_id == 0 {
+            validator.advance_time(voting_period);
+            validator.advance_time(TimeDelta::from_secs(1));
+            dao_chain
+                .add_block(|block| {
+                    block.with_operation(dao_id, Operation::Tally { proposal_id: 0 });
+                })
+                .await;
+        }
+    }
+    validator.advance_time(timelock);
+
+    assert!(dao_chain
+        .try_add_block(|block| {
+            block.with_operation(dao_id, Operation::Execute { proposal_id: 0 });
+        })
+        .await
+        .is_err());
+    assert_eq!(
+        fungible::query_account(token_id, &dao_chain, dao_owner).await,
+        Some(Amount::from_tokens(3)),
+    );
+}
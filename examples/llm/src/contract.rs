@@ -3,9 +3,21 @@
 
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
-use linera_sdk::{base::WithContractAbi, Contract, ContractRuntime};
+mod state;
 
-pub struct LlmContract;
+use linera_sdk::{
+    base::{Owner, WithContractAbi},
+    views::{RootView, View},
+    Contract, ContractRuntime,
+};
+use llm::{ChatMessage, Operation, Role};
+
+use self::state::LlmState;
+
+pub struct LlmContract {
+    state: LlmState,
+    runtime: ContractRuntime<Self>,
+}
 
 linera_sdk::contract!(LlmContract);
 
@@ -16,19 +28,66 @@ impl WithContractAbi for LlmContract {
 impl Contract for LlmContract {
     type Message = ();
     type InstantiationArgument = ();
-    type Parameters = ();
+    type Parameters = llm::Parameters;
 
-    async fn load(_runtime: ContractRuntime<Self>) -> Self {
-        LlmContract
+    async fn load(runtime: ContractRuntime<Self>) -> Self {
+        let state = LlmState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        LlmContract { state, runtime }
     }
 
     async fn instantiate(&mut self, _value: ()) {}
 
-    async fn execute_operation(&mut self, _operation: ()) -> Self::Response {}
+    async fn execute_operation(&mut self, operation: Operation) -> Self::Response {
+        match operation {
+            Operation::Chat {
+                owner,
+                prompt,
+                response,
+            } => {
+                self.check_authentication(owner);
+                let conversation = self
+                    .state
+                    .conversations
+                    .load_entry_mut(&owner)
+                    .await
+                    .expect("Failed to load the conversation");
+                conversation.push(ChatMessage {
+                    role: Role::User,
+                    text: prompt,
+                });
+                conversation.push(ChatMessage {
+                    role: Role::Assistant,
+                    text: response,
+                });
+            }
+            Operation::ClearHistory { owner } => {
+                self.check_authentication(owner);
+                self.state
+                    .conversations
+                    .remove_entry(&owner)
+                    .expect("Failed to clear the conversation");
+            }
+        }
+    }
 
     async fn execute_message(&mut self, _message: ()) {
         panic!("Llm application doesn't support any cross-chain messages");
     }
 
-    async fn store(self) {}
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
+
+impl LlmContract {
+    /// Verifies that the operation was signed by `owner`.
+    fn check_authentication(&mut self, owner: Owner) {
+        assert_eq!(
+            self.runtime.authenticated_signer(),
+            Some(owner),
+            "The requested operation is not correctly authenticated."
+        );
+    }
 }
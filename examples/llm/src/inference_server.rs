@@ -0,0 +1,119 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A native inference server, which runs the model on a GPU when one is available.
+//!
+//! The application's service forwards its prompts to this server when it is created with the
+//! `Remote` backend. This is how a node service can complete prompts with hardware
+//! acceleration, since GPUs are not accessible from WebAssembly.
+//!
+//! Usage: `llm_inference_server <WEIGHTS_FILE> <TOKENIZER_FILE> [ADDRESS]`
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+#[cfg(not(target_arch = "wasm32"))]
+mod model;
+#[cfg(not(target_arch = "wasm32"))]
+mod token;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    env, fs,
+    io::{self, BufRead as _, BufReader, Write as _},
+    net::{TcpListener, TcpStream},
+    process,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use self::model::LocalModel;
+
+/// The address the server listens on by default.
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8081";
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> io::Result<()> {
+    let mut args = env::args().skip(1);
+    let (Some(weights), Some(tokenizer)) = (args.next(), args.next()) else {
+        eprintln!("Usage: llm_inference_server <WEIGHTS_FILE> <TOKENIZER_FILE> [ADDRESS]");
+        process::exit(2);
+    };
+    let address = args.next().unwrap_or_else(|| DEFAULT_ADDRESS.to_owned());
+
+    let model = LocalModel::new(fs::read(weights)?, fs::read(tokenizer)?);
+    println!("Running inference on {:?}", model.device());
+
+    let listener = TcpListener::bind(&address)?;
+    println!("Listening on http://{address}");
+    for stream in listener.incoming() {
+        if let Err(error) = answer(&model, stream?) {
+            eprintln!("Failed to answer a request: {error}");
+        }
+    }
+    Ok(())
+}
+
+/// Answers an HTTP request with the completion of its `prompt` query parameter.
+#[cfg(not(target_arch = "wasm32"))]
+fn answer(model: &LocalModel, mut stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Consume the headers, so that the client receives the whole response.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let (status, body) = match prompt(&request_line) {
+        Some(prompt) => match model.run_model(&prompt) {
+            Ok(completion) => ("200 OK", completion),
+            Err(error) => ("500 Internal Server Error", error.to_string()),
+        },
+        None => (
+            "400 Bad Request",
+            "Expected a `prompt` query parameter".to_owned(),
+        ),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Returns the decoded `prompt` query parameter of an HTTP GET request line.
+#[cfg(not(target_arch = "wasm32"))]
+fn prompt(request_line: &str) -> Option<String> {
+    let mut parts = request_line.split_whitespace();
+    if parts.next()? != "GET" {
+        return None;
+    }
+    let (_, query) = parts.next()?.split_once('?')?;
+    query.split('&').find_map(|parameter| {
+        let value = parameter.strip_prefix("prompt=")?;
+        percent_decode(value)
+    })
+}
+
+/// Decodes a percent-encoded query parameter value.
+#[cfg(not(target_arch = "wasm32"))]
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+    while let Some(byte) = input.next() {
+        match byte {
+            b'%' => {
+                let digits = [input.next()?, input.next()?];
+                let hex = std::str::from_utf8(&digits).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            b'+' => bytes.push(b' '),
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
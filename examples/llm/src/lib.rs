@@ -3,13 +3,14 @@
 
 /*! ABI of the LLM Example Application */
 
-use async_graphql::{Request, Response};
-use linera_sdk::base::{ContractAbi, ServiceAbi};
+use async_graphql::{Enum, Request, Response, SimpleObject};
+use linera_sdk::base::{ContractAbi, Owner, ServiceAbi};
+use serde::{Deserialize, Serialize};
 
 pub struct LlmAbi;
 
 impl ContractAbi for LlmAbi {
-    type Operation = ();
+    type Operation = Operation;
     type Response = ();
 }
 
@@ -17,3 +18,62 @@ impl ServiceAbi for LlmAbi {
     type Query = Request;
     type QueryResponse = Response;
 }
+
+/// An operation.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Operation {
+    /// Records a prompt of `owner` and the model's response in their conversation history.
+    Chat {
+        owner: Owner,
+        prompt: String,
+        response: String,
+    },
+    /// Clears the conversation history of `owner`.
+    ClearHistory { owner: Owner },
+}
+
+/// Who wrote a message of a conversation.
+#[derive(Clone, Copy, Debug, Deserialize, Enum, Eq, PartialEq, Serialize)]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+/// A message of a conversation with the model.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub text: String,
+}
+
+/// How the service completes prompts, if not with the default model.
+///
+/// By default, the service runs the 42M parameter TinyLlama by A. Karpathy, in the llama2.c
+/// format.
+pub type Parameters = Option<Backend>;
+
+/// How the service completes prompts.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum Backend {
+    /// Runs a model in the service.
+    Local(ModelSource),
+    /// Forwards the prompts to a remote inference API, without downloading any model.
+    ///
+    /// The prompt is appended to the `endpoint` URL as a `prompt` query parameter, and the
+    /// response body is the completion, as plain text.
+    Remote { endpoint: String },
+}
+
+/// Where to fetch a model and its tokenizer from.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ModelSource {
+    /// The URL of the model weights, in the GGUF, GGML or llama2.c format. GGUF files can
+    /// contain quantized weights, e.g. `q4_0` or `q8_0`.
+    pub weights_url: String,
+    /// The hex-encoded SHA3-256 hash of the weights, if it should be checked.
+    pub weights_hash: Option<String>,
+    /// The URL of the tokenizer, if not the default one.
+    pub tokenizer_url: Option<String>,
+    /// The hex-encoded SHA3-256 hash of the tokenizer, if it should be checked.
+    pub tokenizer_hash: Option<String>,
+}
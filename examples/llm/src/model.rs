@@ -0,0 +1,268 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Inference with a model loaded in memory, shared by the service and the inference server.
+
+use std::io::{Cursor, Seek, SeekFrom};
+
+use candle_core::{
+    quantized::{ggml_file, gguf_file},
+    Device, IndexOp, Tensor,
+};
+use candle_transformers::{
+    generation::LogitsProcessor,
+    models::{llama2_c, llama2_c::Llama, llama2_c_weights, quantized_llama::ModelWeights},
+};
+use log::{debug, info};
+use tokenizers::Tokenizer;
+
+use crate::token::TokenOutputStream;
+
+enum Model {
+    Llama {
+        model: Llama,
+        cache: llama2_c::Cache,
+    },
+    Qllama(ModelWeights),
+}
+
+impl Model {
+    fn forward(&mut self, input: &Tensor, index_pos: usize) -> Result<Tensor, candle_core::Error> {
+        match self {
+            Model::Llama {
+                model: llama,
+                cache,
+            } => llama.forward(input, index_pos, cache),
+            Model::Qllama(model) => model.forward(input, index_pos),
+        }
+    }
+}
+
+/// Model weights loaded in memory, shared by all the completions.
+enum LoadedModel {
+    /// Weights in the llama2.c format, from which a model with its own cache is built for each
+    /// completion.
+    Llama {
+        config: llama2_c::Config,
+        weights: llama2_c_weights::TransformerWeights,
+    },
+    /// Quantized weights loaded from a GGUF or GGML file. Each completion uses a copy, which
+    /// shares the weights but has its own cache.
+    Qllama(ModelWeights),
+}
+
+/// A model and its tokenizer, loaded on the device that runs the inference.
+pub struct LocalModel {
+    model: LoadedModel,
+    tokenizer: Vec<u8>,
+    device: Device,
+}
+
+impl LocalModel {
+    /// Loads the model weights, once for all the completions, on the best available device.
+    pub fn new(weights: Vec<u8>, tokenizer: Vec<u8>) -> Self {
+        let device = Self::select_device();
+        info!("Running inference on {device:?}");
+        let model = Self::load_model(weights, &device);
+        LocalModel {
+            model,
+            tokenizer,
+            device,
+        }
+    }
+
+    /// Returns the device that runs the inference.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Returns the device to run inference on.
+    ///
+    /// A CUDA or Metal GPU is used if `candle-core` was built with the corresponding `cuda` or
+    /// `metal` feature and such a device is available. Otherwise, the CPU is used. GPUs are
+    /// never available to the WebAssembly build of the service.
+    fn select_device() -> Device {
+        if candle_core::utils::cuda_is_available() {
+            match Device::new_cuda(0) {
+                Ok(device) => return device,
+                Err(error) => info!("Failed to use the CUDA device: {error}"),
+            }
+        }
+        if candle_core::utils::metal_is_available() {
+            match Device::new_metal(0) {
+                Ok(device) => return device,
+                Err(error) => info!("Failed to use the Metal device: {error}"),
+            }
+        }
+        Device::Cpu
+    }
+
+    fn try_load_gguf(
+        cursor: &mut Cursor<Vec<u8>>,
+        device: &Device,
+    ) -> Result<ModelWeights, candle_core::Error> {
+        debug!("trying to load model assuming gguf");
+        let model_contents = gguf_file::Content::read(cursor)?;
+        let mut total_size_in_bytes = 0;
+        for (_, tensor) in model_contents.tensor_infos.iter() {
+            let elem_count = tensor.shape.elem_count();
+            total_size_in_bytes +=
+                elem_count * tensor.ggml_dtype.type_size() / tensor.ggml_dtype.block_size();
+        }
+
+        debug!(
+            "loaded {:?} tensors ({}B) ",
+            model_contents.tensor_infos.len(),
+            total_size_in_bytes,
+        );
+
+        ModelWeights::from_gguf(model_contents, cursor, device)
+    }
+
+    fn try_load_ggml(
+        cursor: &mut Cursor<Vec<u8>>,
+        device: &Device,
+    ) -> Result<ModelWeights, candle_core::Error> {
+        debug!("trying to load model assuming ggml");
+        let model_contents = ggml_file::Content::read(cursor, device)?;
+        let mut total_size_in_bytes = 0;
+        for (_, tensor) in model_contents.tensors.iter() {
+            let elem_count = tensor.shape().elem_count();
+            total_size_in_bytes +=
+                elem_count * tensor.dtype().type_size() / tensor.dtype().block_size();
+        }
+
+        debug!(
+            "loaded {:?} tensors ({}B) ",
+            model_contents.tensors.len(),
+            total_size_in_bytes,
+        );
+
+        ModelWeights::from_ggml(model_contents, 1)
+    }
+
+    fn try_load_non_quantized(
+        cursor: &mut Cursor<Vec<u8>>,
+        device: &Device,
+    ) -> Result<LoadedModel, candle_core::Error> {
+        debug!("trying to load model assuming llama2.c");
+        let config = llama2_c::Config::from_reader(cursor)?;
+        debug!("{config:?}");
+        let weights = llama2_c_weights::TransformerWeights::from_reader(cursor, &config, device)?;
+        Ok(LoadedModel::Llama { config, weights })
+    }
+
+    /// Loads the model weights, once for all the completions.
+    fn load_model(model_weights: Vec<u8>, device: &Device) -> LoadedModel {
+        let mut cursor = Cursor::new(model_weights);
+        if let Ok(model) = Self::try_load_gguf(&mut cursor, device) {
+            return LoadedModel::Qllama(model);
+        }
+        cursor.seek(SeekFrom::Start(0)).expect("seeking to 0");
+        if let Ok(model) = Self::try_load_ggml(&mut cursor, device) {
+            return LoadedModel::Qllama(model);
+        }
+        cursor.seek(SeekFrom::Start(0)).expect("seeking to 0");
+        if let Ok(model) = Self::try_load_non_quantized(&mut cursor, device) {
+            return model;
+        }
+        // might need a 'model not supported variant'
+        panic!("model failed to be loaded")
+    }
+
+    /// Returns a model with an empty cache, using the loaded weights.
+    fn new_model(&self) -> Result<Model, candle_core::Error> {
+        match &self.model {
+            LoadedModel::Llama { config, weights } => {
+                let vb = weights.var_builder(config, &self.device)?;
+                let cache = llama2_c::Cache::new(true, config, vb.pp("rot"))?;
+                let llama = Llama::load(vb, config.clone())?;
+                Ok(Model::Llama {
+                    model: llama,
+                    cache,
+                })
+            }
+            LoadedModel::Qllama(model) => Ok(Model::Qllama(model.clone())),
+        }
+    }
+
+    /// Starts generating a completion of the prompt.
+    pub fn start_generation(&self, prompt_string: &str) -> Generation {
+        let model = self.new_model().expect("failed to create model");
+        let tokenizer = Tokenizer::from_bytes(&self.tokenizer).expect("failed to create tokenizer");
+        let tokens = tokenizer
+            .encode(prompt_string, true)
+            .unwrap()
+            .get_ids()
+            .to_vec();
+        Generation {
+            model,
+            tokenizer: TokenOutputStream::new(tokenizer),
+            logits_processor: LogitsProcessor::new(299792458, None, None),
+            tokens,
+            index: 0,
+            index_pos: 0,
+        }
+    }
+
+    /// Returns the whole completion of the prompt, up to its last full sentence.
+    pub fn run_model(&self, prompt_string: &str) -> Result<String, candle_core::Error> {
+        let mut generation = self.start_generation(prompt_string);
+        let mut output = String::new();
+        while !generation.is_finished() {
+            if let Some(text) = generation.next_token(&self.device)? {
+                output.push_str(&text);
+            }
+        }
+        if let Some(rest) = generation.decode_rest()? {
+            output.push_str(&rest);
+        }
+        Ok(output
+            .rsplit_once('.')
+            .map(|(before, _)| format!("{}.", before))
+            .unwrap_or_else(|| output.to_string()))
+    }
+}
+
+/// The state of a completion being generated, one token at a time.
+pub struct Generation {
+    model: Model,
+    tokenizer: TokenOutputStream,
+    logits_processor: LogitsProcessor,
+    tokens: Vec<u32>,
+    index: usize,
+    index_pos: usize,
+}
+
+impl Generation {
+    /// The number of positions after which the generation stops.
+    const MAX_POSITION: usize = 256;
+
+    /// Returns `true` if no more tokens will be generated.
+    pub fn is_finished(&self) -> bool {
+        self.index_pos >= Self::MAX_POSITION
+    }
+
+    /// Generates the next token, and returns the text it completes, if any.
+    pub fn next_token(&mut self, device: &Device) -> Result<Option<String>, candle_core::Error> {
+        let context_size = if self.index > 0 { 1 } else { self.tokens.len() };
+        let ctxt = &self.tokens[self.tokens.len().saturating_sub(context_size)..];
+        let input = Tensor::new(ctxt, device)?.unsqueeze(0)?;
+        let logits = self.model.forward(&input, self.index_pos)?;
+        let logits = logits.i((0, logits.dim(1)? - 1))?;
+
+        let start_at = self.tokens.len().saturating_sub(10);
+        candle_transformers::utils::apply_repeat_penalty(&logits, 0.5, &self.tokens[start_at..])?;
+        self.index_pos += ctxt.len();
+        self.index += 1;
+
+        let next_token = self.logits_processor.sample(&logits)?;
+        self.tokens.push(next_token);
+        self.tokenizer.next_token(next_token)
+    }
+
+    /// Returns the text of the tokens that were generated but not returned yet.
+    pub fn decode_rest(&self) -> Result<Option<String>, candle_core::Error> {
+        self.tokenizer.decode_rest()
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Completion of prompts by a remote inference API.
+
+use std::{fmt::Write as _, sync::Arc};
+
+use linera_sdk::ServiceRuntime;
+
+use crate::{streaming::StreamedCompletions, InferenceBackend, LlmService, TokenChunk};
+
+/// Forwards the prompts to a remote inference API, using the `fetch_url` system API.
+pub struct RemoteBackend {
+    runtime: Arc<ServiceRuntime<LlmService>>,
+    /// The URL of the API, to which the prompt is appended as a query parameter.
+    endpoint: String,
+    /// The completions waiting to be streamed.
+    completions: StreamedCompletions<String>,
+}
+
+impl RemoteBackend {
+    pub fn new(runtime: Arc<ServiceRuntime<LlmService>>, endpoint: String) -> Self {
+        RemoteBackend {
+            runtime,
+            endpoint,
+            completions: StreamedCompletions::default(),
+        }
+    }
+
+    /// Returns the URL to fetch the completion of `prompt` from.
+    fn url(&self, prompt: &str) -> String {
+        let separator = if self.endpoint.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        let mut url = format!("{}{separator}prompt=", self.endpoint);
+        for byte in prompt.bytes() {
+            if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                url.push(char::from(byte));
+            } else {
+                write!(url, "%{byte:02X}").unwrap();
+            }
+        }
+        url
+    }
+}
+
+impl InferenceBackend for RemoteBackend {
+    fn complete(&self, prompt: &str) -> async_graphql::Result<String> {
+        let response = self.runtime.fetch_url(&self.url(prompt));
+        Ok(String::from_utf8(response)?)
+    }
+
+    /// Fetches the whole completion, which is then returned as a single chunk.
+    fn start_streaming(&self, prompt: &str) -> async_graphql::Result<u64> {
+        let completion = self.complete(prompt)?;
+        Ok(self.completions.insert(completion))
+    }
+
+    fn next_tokens(&self, id: u64, _max_tokens: u32) -> async_graphql::Result<TokenChunk> {
+        let text = self.completions.take(id)?;
+        Ok(TokenChunk {
+            text,
+            finished: true,
+        })
+    }
+}
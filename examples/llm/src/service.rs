@@ -3,44 +3,58 @@
 
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
+mod model;
 mod random;
+mod remote;
+mod state;
+mod streaming;
 mod token;
 
-use std::{
-    io::{Cursor, Seek, SeekFrom},
-    sync::Arc,
-};
+use std::{fmt::Write as _, sync::Arc};
 
-use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Request, Response, Schema};
-use candle_core::{
-    quantized::{ggml_file, gguf_file},
-    Device, IndexOp, Tensor,
-};
-use candle_transformers::{
-    generation::LogitsProcessor,
-    models::{llama2_c, llama2_c::Llama, llama2_c_weights, quantized_llama::ModelWeights},
+use async_graphql::{Context, EmptySubscription, Object, Request, Response, Schema, SimpleObject};
+use linera_sdk::{
+    base::{Owner, WithServiceAbi},
+    views::View,
+    Service, ServiceRuntime,
 };
-use linera_sdk::{base::WithServiceAbi, Service, ServiceRuntime};
+use llm::{Backend, ChatMessage, ModelSource, Operation, Role};
 use log::{debug, info};
 use sha3::{Digest as _, Sha3_256};
-use tokenizers::Tokenizer;
 
-use crate::token::TokenOutputStream;
+use self::{
+    model::{Generation, LocalModel},
+    remote::RemoteBackend,
+    state::LlmState,
+    streaming::StreamedCompletions,
+};
+
+/// The URL of the model weights used by default.
+const WEIGHTS_URL: &str = "https://huggingface.co/karpathy/tinyllamas/resolve/main/stories42M.bin";
 
-/// The SHA3-256 hash of the model weights to use.
+/// The SHA3-256 hash of the model weights used by default.
 const WEIGHTS_HASH: &[u8] = &[
     0x23, 0x42, 0x71, 0xe1, 0xf8, 0x3b, 0x6e, 0xec, 0xf1, 0x9b, 0xa4, 0xb7, 0xf4, 0x52, 0x49, 0xe7,
     0xd9, 0xc6, 0x86, 0x57, 0xbc, 0xa0, 0x2d, 0xa3, 0x9b, 0xdb, 0xb1, 0x49, 0xcd, 0x53, 0x10, 0x01,
 ];
 
-/// The SHA3-256 hash of the tokenizer to use.
+/// The URL of the tokenizer used by default.
+const TOKENIZER_URL: &str =
+    "https://huggingface.co/spaces/lmz/candle-llama2/resolve/main/tokenizer.json";
+
+/// The SHA3-256 hash of the tokenizer used by default.
 const TOKENIZER_HASH: &[u8] = &[
     0x4a, 0x33, 0x4c, 0x71, 0x85, 0x96, 0xca, 0x0b, 0x0a, 0x03, 0x11, 0x56, 0x0a, 0x50, 0x25, 0xfd,
     0xfc, 0x36, 0x8f, 0x33, 0x64, 0x17, 0x2b, 0x74, 0x01, 0xbb, 0x89, 0xbf, 0x30, 0x99, 0x20, 0x0b,
 ];
 
+/// The number of previous messages of a conversation given to the model as context.
+const CONTEXT_MESSAGES: usize = 6;
+
 pub struct LlmService {
-    model_context: Arc<ModelContext>,
+    state: Arc<LlmState>,
+    runtime: Arc<ServiceRuntime<Self>>,
+    backend: Arc<dyn InferenceBackend>,
 }
 
 linera_sdk::service!(LlmService);
@@ -49,197 +63,262 @@ impl WithServiceAbi for LlmService {
     type Abi = llm::LlmAbi;
 }
 
-struct QueryRoot {}
+struct QueryRoot {
+    state: Arc<LlmState>,
+}
 
 #[Object]
 impl QueryRoot {
-    async fn prompt(&self, ctx: &Context<'_>, prompt: String) -> String {
-        let model_context = ctx.data::<Arc<ModelContext>>().unwrap();
-        model_context.run_model(&prompt).unwrap()
+    /// Returns the conversation of `owner` with the model, oldest message first.
+    async fn history(&self, owner: Owner) -> async_graphql::Result<Vec<ChatMessage>> {
+        let Some(conversation) = self.state.conversations.try_load_entry(&owner).await? else {
+            return Ok(Vec::new());
+        };
+        Ok(conversation.read(..).await?)
+    }
+
+    async fn prompt(&self, ctx: &Context<'_>, prompt: String) -> async_graphql::Result<String> {
+        let backend = ctx.data::<Arc<dyn InferenceBackend>>().unwrap();
+        backend.complete(&prompt)
+    }
+
+    /// Starts completing the prompt, and returns an ID to fetch the completion with
+    /// `nextTokens`, a few tokens at a time.
+    ///
+    /// The completion is kept in memory between queries, which requires running the node
+    /// service with `--long-lived-services`.
+    async fn start_prompt(&self, ctx: &Context<'_>, prompt: String) -> async_graphql::Result<u64> {
+        let backend = ctx.data::<Arc<dyn InferenceBackend>>().unwrap();
+        backend.start_streaming(&prompt)
+    }
+
+    /// Generates up to `max_tokens` more tokens of the completion started by `startPrompt`.
+    async fn next_tokens(
+        &self,
+        ctx: &Context<'_>,
+        id: u64,
+        #[graphql(default = 8)] max_tokens: u32,
+    ) -> async_graphql::Result<TokenChunk> {
+        let backend = ctx.data::<Arc<dyn InferenceBackend>>().unwrap();
+        backend.next_tokens(id, max_tokens)
     }
 }
 
-enum Model {
-    Llama {
-        model: Llama,
-        cache: llama2_c::Cache,
-    },
-    Qllama(ModelWeights),
+struct MutationRoot {
+    state: Arc<LlmState>,
+    runtime: Arc<ServiceRuntime<LlmService>>,
 }
 
-impl Model {
-    fn forward(&mut self, input: &Tensor, index_pos: usize) -> Result<Tensor, candle_core::Error> {
-        match self {
-            Model::Llama {
-                model: llama,
-                cache,
-            } => llama.forward(input, index_pos, cache),
-            Model::Qllama(model) => model.forward(input, index_pos),
-        }
+#[Object]
+impl MutationRoot {
+    /// Answers the prompt of `owner`, given the last messages of their conversation as
+    /// context, and records both in the conversation.
+    async fn chat(
+        &self,
+        ctx: &Context<'_>,
+        owner: Owner,
+        prompt: String,
+    ) -> async_graphql::Result<[u8; 0]> {
+        let history = match self.state.conversations.try_load_entry(&owner).await? {
+            Some(conversation) => {
+                let start = conversation.count().saturating_sub(CONTEXT_MESSAGES);
+                conversation.read(start..).await?
+            }
+            None => Vec::new(),
+        };
+        let backend = ctx.data::<Arc<dyn InferenceBackend>>().unwrap();
+        let response = backend.complete(&conversation_prompt(&history, &prompt))?;
+        let response = response
+            .split("User:")
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_owned();
+        self.runtime.schedule_operation(&Operation::Chat {
+            owner,
+            prompt,
+            response,
+        });
+        Ok([])
+    }
+
+    /// Clears the conversation of `owner`.
+    async fn clear_history(&self, owner: Owner) -> [u8; 0] {
+        self.runtime
+            .schedule_operation(&Operation::ClearHistory { owner });
+        []
+    }
+}
+
+/// Returns the text given to the model to continue a conversation with a new prompt.
+fn conversation_prompt(history: &[ChatMessage], prompt: &str) -> String {
+    let mut text = String::new();
+    for message in history {
+        let name = match message.role {
+            Role::User => "User",
+            Role::Assistant => "Assistant",
+        };
+        writeln!(text, "{name}: {}", message.text).unwrap();
     }
+    writeln!(text, "User: {prompt}").unwrap();
+    text.push_str("Assistant:");
+    text
+}
+
+/// A way for the service to complete prompts.
+trait InferenceBackend: Send + Sync {
+    /// Returns the completion of the prompt.
+    fn complete(&self, prompt: &str) -> async_graphql::Result<String>;
+
+    /// Starts completing the prompt, and returns an ID to fetch the completion with
+    /// [`InferenceBackend::next_tokens`].
+    fn start_streaming(&self, prompt: &str) -> async_graphql::Result<u64>;
+
+    /// Returns up to `max_tokens` more tokens of the completion with the given ID.
+    fn next_tokens(&self, id: u64, max_tokens: u32) -> async_graphql::Result<TokenChunk>;
 }
 
 struct ModelContext {
-    model: Vec<u8>,
-    tokenizer: Vec<u8>,
+    model: LocalModel,
+    /// The completions being streamed.
+    generations: StreamedCompletions<Generation>,
+}
+
+/// A part of a streamed completion.
+#[derive(SimpleObject)]
+struct TokenChunk {
+    /// The text generated since the previous chunk.
+    text: String,
+    /// Whether this is the last chunk of the completion.
+    finished: bool,
 }
 
 impl Service for LlmService {
-    type Parameters = ();
+    type Parameters = llm::Parameters;
 
     async fn new(runtime: ServiceRuntime<Self>) -> Self {
-        info!("Downloading model");
-        let raw_weights = runtime
-            .fetch_url("https://huggingface.co/karpathy/tinyllamas/resolve/main/stories42M.bin");
-        assert_eq!(
-            Sha3_256::digest(&raw_weights).as_slice(),
-            WEIGHTS_HASH,
-            "Incorrect model was fetched"
-        );
-        info!("Downloaded model weights: {} bytes", raw_weights.len());
-
-        info!("Downloading tokenizer");
-        let tokenizer_bytes = runtime.fetch_url(
-            "https://huggingface.co/spaces/lmz/candle-llama2/resolve/main/tokenizer.json",
-        );
-        assert_eq!(
-            Sha3_256::digest(&tokenizer_bytes).as_slice(),
-            TOKENIZER_HASH,
-            "Incorrect tokenizer was fetched"
-        );
-        info!("Downloaded tokenizer: {} bytes", tokenizer_bytes.len());
-
-        let model_context = Arc::new(ModelContext {
-            model: raw_weights,
-            tokenizer: tokenizer_bytes,
-        });
-        LlmService { model_context }
+        let state = LlmState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        let runtime = Arc::new(runtime);
+        let backend: Arc<dyn InferenceBackend> = match runtime.application_parameters() {
+            Some(Backend::Remote { endpoint }) => {
+                info!("Forwarding prompts to {endpoint}");
+                Arc::new(RemoteBackend::new(runtime.clone(), endpoint))
+            }
+            Some(Backend::Local(source)) => Arc::new(ModelContext::fetch(&runtime, Some(source))),
+            None => Arc::new(ModelContext::fetch(&runtime, None)),
+        };
+        LlmService {
+            state: Arc::new(state),
+            runtime,
+            backend,
+        }
     }
 
     async fn handle_query(&self, request: Request) -> Response {
         let query_string = &request.query;
         debug!("query: {}", query_string);
-        let schema = Schema::build(QueryRoot {}, EmptyMutation, EmptySubscription)
-            .data(self.model_context.clone())
-            .finish();
+        let schema = Schema::build(
+            QueryRoot {
+                state: self.state.clone(),
+            },
+            MutationRoot {
+                state: self.state.clone(),
+                runtime: self.runtime.clone(),
+            },
+            EmptySubscription,
+        )
+        .data(self.backend.clone())
+        .finish();
         schema.execute(request).await
     }
 }
 
-impl ModelContext {
-    fn try_load_gguf(cursor: &mut Cursor<Vec<u8>>) -> Result<ModelWeights, candle_core::Error> {
-        debug!("trying to load model assuming gguf");
-        let model_contents = gguf_file::Content::read(cursor)?;
-        let mut total_size_in_bytes = 0;
-        for (_, tensor) in model_contents.tensor_infos.iter() {
-            let elem_count = tensor.shape.elem_count();
-            total_size_in_bytes +=
-                elem_count * tensor.ggml_dtype.type_size() / tensor.ggml_dtype.block_size();
-        }
-
-        debug!(
-            "loaded {:?} tensors ({}B) ",
-            model_contents.tensor_infos.len(),
-            total_size_in_bytes,
-        );
+/// Decodes a hex-encoded SHA3-256 hash from the application parameters.
+fn decode_hash(hash: &Option<String>) -> Option<Vec<u8>> {
+    hash.as_ref()
+        .map(|hash| hex::decode(hash).expect("Invalid hex-encoded SHA3-256 hash"))
+}
 
-        ModelWeights::from_gguf(model_contents, cursor, &Device::Cpu)
-    }
+impl ModelContext {
+    /// Fetches the model and the tokenizer from `source`, or the default ones, and loads the
+    /// model.
+    fn fetch(runtime: &ServiceRuntime<LlmService>, source: Option<ModelSource>) -> Self {
+        let (weights_url, weights_hash) = match &source {
+            Some(source) => (
+                source.weights_url.as_str(),
+                decode_hash(&source.weights_hash),
+            ),
+            None => (WEIGHTS_URL, Some(WEIGHTS_HASH.to_vec())),
+        };
+        let (tokenizer_url, tokenizer_hash) = match &source {
+            Some(ModelSource {
+                tokenizer_url: Some(url),
+                tokenizer_hash,
+                ..
+            }) => (url.as_str(), decode_hash(tokenizer_hash)),
+            _ => (TOKENIZER_URL, Some(TOKENIZER_HASH.to_vec())),
+        };
 
-    fn try_load_ggml(cursor: &mut Cursor<Vec<u8>>) -> Result<ModelWeights, candle_core::Error> {
-        debug!("trying to load model assuming ggml");
-        let model_contents = ggml_file::Content::read(cursor, &Device::Cpu)?;
-        let mut total_size_in_bytes = 0;
-        for (_, tensor) in model_contents.tensors.iter() {
-            let elem_count = tensor.shape().elem_count();
-            total_size_in_bytes +=
-                elem_count * tensor.dtype().type_size() / tensor.dtype().block_size();
+        info!("Downloading model");
+        let raw_weights = runtime.fetch_url(weights_url);
+        if let Some(hash) = weights_hash {
+            assert_eq!(
+                Sha3_256::digest(&raw_weights).as_slice(),
+                hash,
+                "Incorrect model was fetched"
+            );
         }
+        info!("Downloaded model weights: {} bytes", raw_weights.len());
 
-        debug!(
-            "loaded {:?} tensors ({}B) ",
-            model_contents.tensors.len(),
-            total_size_in_bytes,
-        );
+        info!("Downloading tokenizer");
+        let tokenizer_bytes = runtime.fetch_url(tokenizer_url);
+        if let Some(hash) = tokenizer_hash {
+            assert_eq!(
+                Sha3_256::digest(&tokenizer_bytes).as_slice(),
+                hash,
+                "Incorrect tokenizer was fetched"
+            );
+        }
+        info!("Downloaded tokenizer: {} bytes", tokenizer_bytes.len());
 
-        ModelWeights::from_ggml(model_contents, 1)
+        ModelContext {
+            model: LocalModel::new(raw_weights, tokenizer_bytes),
+            generations: StreamedCompletions::default(),
+        }
     }
+}
 
-    fn try_load_non_quantized(cursor: &mut Cursor<Vec<u8>>) -> Result<Model, candle_core::Error> {
-        let config = llama2_c::Config::from_reader(cursor)?;
-        println!("{config:?}");
-        let weights =
-            llama2_c_weights::TransformerWeights::from_reader(cursor, &config, &Device::Cpu)?;
-        let vb = weights.var_builder(&config, &Device::Cpu)?;
-        let cache = llama2_c::Cache::new(true, &config, vb.pp("rot"))?;
-        let llama = Llama::load(vb, config.clone())?;
-        Ok(Model::Llama {
-            model: llama,
-            cache,
-        })
+impl InferenceBackend for ModelContext {
+    fn complete(&self, prompt: &str) -> async_graphql::Result<String> {
+        Ok(self.model.run_model(prompt)?)
     }
 
-    fn load_model(&self, model_weights: Vec<u8>) -> Model {
-        let mut cursor = Cursor::new(model_weights);
-        if let Ok(model) = Self::try_load_gguf(&mut cursor) {
-            return Model::Qllama(model);
-        }
-        cursor.seek(SeekFrom::Start(0)).expect("seeking to 0");
-        if let Ok(model) = Self::try_load_ggml(&mut cursor) {
-            return Model::Qllama(model);
-        }
-        cursor.seek(SeekFrom::Start(0)).expect("seeking to 0");
-        if let Ok(model) = Self::try_load_non_quantized(&mut cursor) {
-            return model;
-        }
-        // might need a 'model not supported variant'
-        panic!("model failed to be loaded")
+    fn start_streaming(&self, prompt_string: &str) -> async_graphql::Result<u64> {
+        let generation = self.model.start_generation(prompt_string);
+        Ok(self.generations.insert(generation))
     }
 
-    fn run_model(&self, prompt_string: &str) -> Result<String, candle_core::Error> {
-        let raw_weights = &self.model;
-        let tokenizer_bytes = &self.tokenizer;
-
-        let mut output = String::new();
-        let mut model = self.load_model(raw_weights.clone());
-
-        let tokenizer = Tokenizer::from_bytes(tokenizer_bytes).expect("failed to create tokenizer");
-        let mut logits_processor = LogitsProcessor::new(299792458, None, None);
-        let mut index_pos = 0;
-
-        let mut tokens = tokenizer
-            .encode(prompt_string, true)
-            .unwrap()
-            .get_ids()
-            .to_vec();
-        let mut tokenizer = TokenOutputStream::new(tokenizer);
-        for index in 0.. {
-            let context_size = if index > 0 { 1 } else { tokens.len() };
-            let ctxt = &tokens[tokens.len().saturating_sub(context_size)..];
-            let input = Tensor::new(ctxt, &Device::Cpu)?.unsqueeze(0)?;
-            if index_pos == 256 {
-                return Ok(output
-                    .rsplit_once('.')
-                    .map(|(before, _)| format!("{}.", before))
-                    .unwrap_or_else(|| output.to_string()));
+    fn next_tokens(&self, id: u64, max_tokens: u32) -> async_graphql::Result<TokenChunk> {
+        let mut generation = self.generations.take(id)?;
+        let mut text = String::new();
+        for _ in 0..max_tokens {
+            if generation.is_finished() {
+                break;
             }
-            let logits = model.forward(&input, index_pos)?;
-            let logits = logits.i((0, logits.dim(1)? - 1))?;
-
-            let start_at = tokens.len().saturating_sub(10);
-            candle_transformers::utils::apply_repeat_penalty(&logits, 0.5, &tokens[start_at..])?;
-            index_pos += ctxt.len();
-
-            let next_token = logits_processor.sample(&logits)?;
-            tokens.push(next_token);
-            if let Some(t) = tokenizer.next_token(next_token)? {
-                output.push_str(&t);
+            if let Some(token) = generation.next_token(self.model.device())? {
+                text.push_str(&token);
             }
         }
-        if let Some(rest) = tokenizer.decode_rest().unwrap() {
-            output.push_str(&rest);
-            output.insert_str(0, prompt_string);
+        let finished = generation.is_finished();
+        if finished {
+            if let Some(rest) = generation.decode_rest()? {
+                text.push_str(&rest);
+            }
+        } else {
+            self.generations.put_back(id, generation);
         }
-        Ok(output)
+        Ok(TokenChunk { text, finished })
     }
 }
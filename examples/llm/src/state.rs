@@ -0,0 +1,16 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_sdk::{
+    base::Owner,
+    views::{linera_views, CollectionView, LogView, RootView, ViewStorageContext},
+};
+use llm::ChatMessage;
+
+/// The application state.
+#[derive(RootView)]
+#[view(context = "ViewStorageContext")]
+pub struct LlmState {
+    /// The conversation of each user with the model, oldest message first.
+    pub conversations: CollectionView<Owner, LogView<ChatMessage>>,
+}
@@ -0,0 +1,69 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The completions kept in the service's memory while they are being streamed.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+/// The maximum number of completions kept in memory. Starting a new completion evicts the
+/// oldest one beyond this limit.
+const MAX_STREAMED_COMPLETIONS: usize = 16;
+
+/// Completions being streamed, by ID.
+pub struct StreamedCompletions<T> {
+    completions: Mutex<BTreeMap<u64, T>>,
+    next_id: AtomicU64,
+}
+
+impl<T> Default for StreamedCompletions<T> {
+    fn default() -> Self {
+        StreamedCompletions {
+            completions: Mutex::default(),
+            next_id: AtomicU64::default(),
+        }
+    }
+}
+
+impl<T> StreamedCompletions<T> {
+    /// Keeps a new completion, evicting the oldest ones if there are too many, and returns
+    /// its ID.
+    pub fn insert(&self, completion: T) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.put_back(id, completion);
+        id
+    }
+
+    /// Removes the completion with the given ID, to continue it.
+    pub fn take(&self, id: u64) -> async_graphql::Result<T> {
+        if let Some(completion) = self.completions.lock().unwrap().remove(&id) {
+            return Ok(completion);
+        }
+        if id >= self.next_id.load(Ordering::Relaxed) {
+            // IDs are only unique within an instance of the service, so this one was returned
+            // by an instance that no longer exists.
+            return Err(async_graphql::Error::new(format!(
+                "Unknown completion ID {id}: Streaming completions requires running the node \
+                service with `--long-lived-services`"
+            )));
+        }
+        Err(async_graphql::Error::new(format!(
+            "Completion {id} is finished, or was evicted by more recent completions"
+        )))
+    }
+
+    /// Puts back a completion that was taken with [`StreamedCompletions::take`], evicting the
+    /// oldest completions if there are too many.
+    pub fn put_back(&self, id: u64, completion: T) {
+        let mut completions = self.completions.lock().unwrap();
+        completions.insert(id, completion);
+        while completions.len() > MAX_STREAMED_COMPLETIONS {
+            completions.pop_first();
+        }
+    }
+}
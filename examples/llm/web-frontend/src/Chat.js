@@ -1,7 +1,7 @@
 import {useState} from "react";
 import {
     gql,
-    useLazyQuery,
+    useApolloClient,
 } from "@apollo/client";
 
 // eslint-disable-next-line
@@ -16,12 +16,51 @@ import {
     MessageInput, ConversationHeader, TypingIndicator,
 } from "@chatscope/chat-ui-kit-react";
 
-const PROMPT = gql`
-query Prompt($prompt: String) {
-  prompt(prompt: $prompt)
+const START_PROMPT = gql`
+query StartPrompt($prompt: String!) {
+  startPrompt(prompt: $prompt)
 }
 `;
 
+const NEXT_TOKENS = gql`
+query NextTokens($id: Int!) {
+  nextTokens(id: $id) {
+    text
+    finished
+  }
+}
+`;
+
+function incomingMessage(message) {
+    return {
+        props: {
+            model: {
+                message: message,
+                sender: "Linera Stories",
+                direction: "incoming",
+                position: "single"
+            }
+        }
+    };
+}
+
+// Fetches the completion of the prompt a few tokens at a time, and renders it as it arrives.
+async function streamCompletion(client, prompt, setMessages, setTypingIndicator) {
+    const fetchPolicy = "network-only";
+    const started = await client.query({query: START_PROMPT, variables: {prompt}, fetchPolicy});
+    const id = started.data.startPrompt;
+    let text = "";
+    setMessages(prevMessages => [...prevMessages, incomingMessage(text)]);
+    for (let finished = false; !finished;) {
+        const result = await client.query({query: NEXT_TOKENS, variables: {id}, fetchPolicy});
+        text += result.data.nextTokens.text;
+        finished = result.data.nextTokens.finished;
+        const current = text;
+        setMessages(prevMessages => [...prevMessages.slice(0, -1), incomingMessage(current)]);
+    }
+    setTypingIndicator(null);
+}
+
 function handleSend(message, messages, setMessages, setTypingIndicator, doPrompt) {
     setMessages([...messages, {
         props: {
@@ -53,28 +92,14 @@ function Chat({chainId}) {
     const [messages, setMessages] = useState(initial_messages);
     const [typingIndicator, setTypingIndicator] = useState(null);
 
-    const [doPrompt
-    ] = useLazyQuery(PROMPT, {
-        fetchPolicy: "network-only",
-        onCompleted: (data) => {
-            data.prompt.split("\n").forEach((s) => {
-                setMessages(prevMessages => [...prevMessages, {
-                    props: {
-                        model: {
-                            message: s,
-                            sender: "Linera Stories",
-                            direction: "incoming",
-                            position: "single"
-                        }
-                    }
-                }]);
+    const client = useApolloClient();
+    const doPrompt = ({variables}) => {
+        streamCompletion(client, variables.prompt, setMessages, setTypingIndicator)
+            .catch((error) => {
+                console.log(error);
+                setTypingIndicator(null);
             });
-            setTypingIndicator(null)
-        },
-        onError: (error) => {
-            console.log(error)
-        }
-    });
+    };
 
     return (
         <div>
@@ -6,9 +6,10 @@
 mod state;
 use std::cmp::min;
 
+use async_graphql::ComplexObject;
 use fungible::{Account, FungibleTokenAbi};
 use linera_sdk::{
-    base::{AccountOwner, Amount, ApplicationId, ChainId, WithContractAbi},
+    base::{AccountOwner, Amount, ApplicationId, ChainId, Timestamp, WithContractAbi},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
@@ -84,6 +85,28 @@ impl Contract for MatchingEngineContract {
                     self.execute_order_remote(order);
                 }
             }
+            Operation::ExpireOrders => {
+                assert_eq!(
+                    self.runtime.chain_id(),
+                    self.runtime.application_creator_chain_id(),
+                    "Orders can only be expired on the chain that created the matching engine"
+                );
+                let now = self.runtime.system_time();
+                let orders = self
+                    .state
+                    .orders
+                    .index_values()
+                    .await
+                    .expect("Failed to read existing orders");
+                for (order_id, key_book) in orders {
+                    if Self::is_expired(key_book.expiry, now) {
+                        if let Some(transfer) = self.modify_order(order_id, ModifyAmount::All).await
+                        {
+                            self.send_to(transfer);
+                        }
+                    }
+                }
+            }
             Operation::CloseChain => {
                 let order_ids = self
                     .state
@@ -139,8 +162,10 @@ impl MatchingEngineContract {
                 amount: _,
                 nature: _,
                 price: _,
+                expiry: _,
             } => *owner,
             Order::Cancel { owner, order_id: _ } => *owner,
+            Order::CancelAll { owner } => *owner,
             Order::Modify {
                 owner,
                 order_id: _,
@@ -215,8 +240,14 @@ impl MatchingEngineContract {
         self.runtime.call_application(true, token, &transfer);
     }
 
-    /// Execution of orders. There are three kinds:
+    /// Returns whether an order with the given `expiry` has expired at time `now`.
+    fn is_expired(expiry: Option<Timestamp>, now: Timestamp) -> bool {
+        expiry.is_some_and(|expiry| expiry <= now)
+    }
+
+    /// Execution of orders. There are four kinds:
     /// * Cancel for total cancellation
+    /// * CancelAll for total cancellation of all the orders of an owner
     /// * Modify where the order is partially cancelled
     /// * Insertion order where an order is inserted into the system. It goes into following steps:
     ///   - Transfer of tokens corresponding to the order in question so that it can be paid
@@ -224,6 +255,9 @@ impl MatchingEngineContract {
     ///   - Insertion of the order into the market and immediately uncrossing the market that
     ///     is making sure that at the end we have best bid < best ask.
     ///   - Creation of the corresponding orders and operation of the corresponding transfers
+    ///
+    ///   An order that has already expired when it is executed is not inserted, and the
+    ///   tokens are returned to the chain it came from.
     async fn execute_order_local(&mut self, order: Order, chain_id: ChainId) {
         match order {
             Order::Insert {
@@ -231,11 +265,17 @@ impl MatchingEngineContract {
                 amount,
                 nature,
                 price,
+                expiry,
             } => {
-                self.receive_from_account(&owner, &amount, &nature, &price);
                 let account = Account { chain_id, owner };
+                if Self::is_expired(expiry, self.runtime.system_time()) {
+                    let (amount, token_idx) = Self::get_amount_idx(&nature, &price, &amount);
+                    self.transfer(owner, amount, account, token_idx);
+                    return;
+                }
+                self.receive_from_account(&owner, &amount, &nature, &price);
                 let transfers = self
-                    .insert_and_uncross_market(&account, amount, nature, &price)
+                    .insert_and_uncross_market(&account, amount, nature, &price, expiry)
                     .await;
                 for transfer in transfers {
                     self.send_to(transfer);
@@ -245,6 +285,20 @@ impl MatchingEngineContract {
                 self.modify_order_check(order_id, ModifyAmount::All, &owner)
                     .await;
             }
+            Order::CancelAll { owner } => {
+                let order_ids = self
+                    .state
+                    .account_info
+                    .get(&owner)
+                    .await
+                    .expect("Failed to load account information")
+                    .unwrap_or_default()
+                    .orders;
+                for order_id in order_ids {
+                    self.modify_order_check(order_id, ModifyAmount::All, &owner)
+                        .await;
+                }
+            }
             Order::Modify {
                 owner,
                 order_id,
@@ -285,6 +339,7 @@ impl MatchingEngineContract {
             amount,
             nature,
             price,
+            expiry: _,
         } = order
         {
             // First, move the funds to the matching engine chain (under the same owner).
@@ -538,6 +593,10 @@ impl MatchingEngineContract {
     /// A price level is cleared starting from the oldest one till the
     /// new order is completely filled or there is no more liquidity
     /// providing order remaining to fill it.
+    ///
+    /// The orders that have expired at time `now` are not filled: they are
+    /// cancelled and what is left of them is returned to their owners.
+    #[allow(clippy::too_many_arguments)]
     async fn level_clearing(
         view: &mut LevelView,
         account: &Account,
@@ -546,6 +605,7 @@ impl MatchingEngineContract {
         nature: &OrderNature,
         price_level: Price,
         price_insert: Price,
+        now: Timestamp,
     ) -> Vec<(AccountOwner, OrderId)> {
         let mut remove_order = Vec::new();
         let orders = view
@@ -554,6 +614,25 @@ impl MatchingEngineContract {
             .await
             .expect("Failed to load iterator over orders");
         for order in orders {
+            if order.amount > Amount::ZERO && Self::is_expired(order.expiry, now) {
+                // The inserted order is a bid if this is a level of asks, and vice versa.
+                let refund = match nature {
+                    OrderNature::Bid => Transfer {
+                        account: order.account,
+                        amount: order.amount,
+                        token_idx: 1,
+                    },
+                    OrderNature::Ask => Transfer {
+                        account: order.account,
+                        amount: product_price_amount(price_level, order.amount),
+                        token_idx: 0,
+                    },
+                };
+                transfers.push(refund);
+                order.amount = Amount::ZERO;
+                remove_order.push((order.account.owner, order.order_id));
+                continue;
+            }
             let fill = min(order.amount, *amount);
             amount.try_sub_assign(fill).unwrap();
             order.amount.try_sub_assign(fill).unwrap();
@@ -587,6 +666,7 @@ impl MatchingEngineContract {
         nature: OrderNature,
         order_id: OrderId,
         price: Price,
+        expiry: Option<Timestamp>,
     ) {
         let account_info = self
             .state
@@ -599,6 +679,7 @@ impl MatchingEngineContract {
             price,
             nature,
             account,
+            expiry,
         };
         self.state
             .orders
@@ -619,6 +700,10 @@ impl MatchingEngineContract {
             .expect("account_info")
             .unwrap();
         account_info.orders.remove(&order_id);
+        self.state
+            .orders
+            .remove(&order_id)
+            .expect("Failed to remove order");
     }
 
     /// Removes a bunch of order_id
@@ -641,12 +726,14 @@ impl MatchingEngineContract {
         amount: Amount,
         nature: OrderNature,
         price: &Price,
+        expiry: Option<Timestamp>,
     ) -> Vec<Transfer> {
         // Bids are ordered from the highest bid (most preferable) to the smallest bid.
         // Asks are ordered from the smallest (most preferable) to the highest.
         // The prices have custom serialization so that they are in increasing order.
         // To reverse the order of the bids, we take the bitwise complement of the price.
         let order_id = self.get_new_order_id();
+        let now = self.runtime.system_time();
         let mut final_amount = amount;
         let mut transfers = Vec::new();
         match nature {
@@ -673,6 +760,7 @@ impl MatchingEngineContract {
                         &nature,
                         price_ask.to_price(),
                         *price,
+                        now,
                     )
                     .await;
                     if view.queue.count() == 0 {
@@ -692,9 +780,10 @@ impl MatchingEngineContract {
                         amount: final_amount,
                         account: *account,
                         order_id,
+                        expiry,
                     };
                     view.queue.push_back(order);
-                    self.insert_order(*account, OrderNature::Bid, order_id, *price)
+                    self.insert_order(*account, OrderNature::Bid, order_id, *price, expiry)
                         .await;
                 }
             }
@@ -721,6 +810,7 @@ impl MatchingEngineContract {
                         &nature,
                         price_bid.to_price(),
                         *price,
+                        now,
                     )
                     .await;
                     if view.queue.count() == 0 {
@@ -740,9 +830,10 @@ impl MatchingEngineContract {
                         amount: final_amount,
                         account: *account,
                         order_id,
+                        expiry,
                     };
                     view.queue.push_back(order);
-                    self.insert_order(*account, OrderNature::Ask, order_id, *price)
+                    self.insert_order(*account, OrderNature::Ask, order_id, *price, expiry)
                         .await;
                 }
             }
@@ -768,3 +859,7 @@ impl MatchingEngineContract {
             .expect("Failed to load `LevelView` for a bid price")
     }
 }
+
+/// This implementation is only nonempty in the service.
+#[ComplexObject]
+impl MatchingEngineState {}
@@ -6,7 +6,7 @@
 use async_graphql::{scalar, InputObject, Request, Response, SimpleObject};
 use fungible::FungibleTokenAbi;
 use linera_sdk::{
-    base::{AccountOwner, Amount, ApplicationId, ContractAbi, ServiceAbi},
+    base::{AccountOwner, Amount, ApplicationId, ContractAbi, ServiceAbi, Timestamp},
     graphql::GraphQLMutationRoot,
     views::{CustomSerialize, ViewError},
 };
@@ -146,12 +146,17 @@ pub enum Order {
         amount: Amount,
         nature: OrderNature,
         price: Price,
+        /// The time after which the order can no longer be filled, and what is left of it
+        /// is returned to its owner
+        expiry: Option<Timestamp>,
     },
     /// Cancelling of an order
     Cancel {
         owner: AccountOwner,
         order_id: OrderId,
     },
+    /// Cancelling of all the orders of an owner
+    CancelAll { owner: AccountOwner },
     /// Modifying order (only decreasing is allowed)
     Modify {
         owner: AccountOwner,
@@ -178,6 +183,9 @@ scalar!(Parameters);
 pub enum Operation {
     /// The order that is going to be executed on the chain of the order book.
     ExecuteOrder { order: Order },
+    /// Cancel all the expired orders, returning what is left of them to their owners.
+    /// Can only be executed on the chain of the order book.
+    ExpireOrders,
     /// Close this chain, and cancel all orders.
     /// Requires that this application is authorized to close the chain.
     CloseChain,
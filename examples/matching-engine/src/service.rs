@@ -7,11 +7,15 @@ mod state;
 
 use std::sync::Arc;
 
-use async_graphql::{EmptySubscription, Request, Response, Schema};
+use async_graphql::{ComplexObject, EmptySubscription, Request, Response, Schema, SimpleObject};
+use fungible::Account;
 use linera_sdk::{
-    base::WithServiceAbi, graphql::GraphQLMutationRoot, views::View, Service, ServiceRuntime,
+    base::{AccountOwner, Amount, Timestamp, WithServiceAbi},
+    graphql::GraphQLMutationRoot,
+    views::View,
+    Service, ServiceRuntime,
 };
-use matching_engine::{Operation, Parameters};
+use matching_engine::{Operation, OrderId, OrderNature, Parameters, Price};
 
 use crate::state::MatchingEngineState;
 
@@ -49,3 +53,62 @@ impl Service for MatchingEngineService {
         schema.execute(request).await
     }
 }
+
+/// An order that is still in the order book, as returned by the `openOrders` query.
+#[derive(Clone, Debug, SimpleObject)]
+pub struct OpenOrder {
+    /// The order_id
+    pub order_id: OrderId,
+    /// The nature of the order
+    pub nature: OrderNature,
+    /// The price of the order
+    pub price: Price,
+    /// The number of token1 that remains to be bought or sold
+    pub amount: Amount,
+    /// The account that receives the proceeds of the order
+    pub account: Account,
+    /// The time after which the order can no longer be filled
+    pub expiry: Option<Timestamp>,
+}
+
+#[ComplexObject]
+impl MatchingEngineState {
+    /// Returns the orders of `owner` that are still in the order book, with the amounts that
+    /// remain to be filled.
+    async fn open_orders(&self, owner: AccountOwner) -> async_graphql::Result<Vec<OpenOrder>> {
+        let Some(account_info) = self.account_info.get(&owner).await? else {
+            return Ok(Vec::new());
+        };
+        let mut open_orders = Vec::new();
+        for order_id in account_info.orders {
+            let Some(key_book) = self.orders.get(&order_id).await? else {
+                continue;
+            };
+            let level = match key_book.nature {
+                OrderNature::Bid => self.bids.try_load_entry(&key_book.price.to_bid()).await?,
+                OrderNature::Ask => self.asks.try_load_entry(&key_book.price.to_ask()).await?,
+            };
+            let Some(level) = level else {
+                continue;
+            };
+            let Some(entry) = level
+                .queue
+                .elements()
+                .await?
+                .into_iter()
+                .find(|entry| entry.order_id == order_id)
+            else {
+                continue;
+            };
+            open_orders.push(OpenOrder {
+                order_id,
+                nature: key_book.nature,
+                price: key_book.price,
+                amount: entry.amount,
+                account: key_book.account,
+                expiry: key_book.expiry,
+            });
+        }
+        Ok(open_orders)
+    }
+}
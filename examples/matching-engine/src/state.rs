@@ -6,7 +6,7 @@ use std::collections::BTreeSet;
 use async_graphql::SimpleObject;
 use fungible::Account;
 use linera_sdk::{
-    base::{AccountOwner, Amount},
+    base::{AccountOwner, Amount, Timestamp},
     views::{
         linera_views, CustomCollectionView, MapView, QueueView, RegisterView, RootView, View,
         ViewStorageContext,
@@ -24,6 +24,8 @@ pub struct OrderEntry {
     pub account: Account,
     /// The order_id (needed for possible cancel or modification)
     pub order_id: OrderId,
+    /// The time after which the order can no longer be filled
+    pub expiry: Option<Timestamp>,
 }
 
 /// This is the entry present in the state so that we can access
@@ -36,6 +38,8 @@ pub struct KeyBook {
     pub nature: OrderNature,
     /// The owner used for checks
     pub account: Account,
+    /// The time after which the order can no longer be filled
+    pub expiry: Option<Timestamp>,
 }
 
 /// The AccountInfo used for storing which order_id are owned by
@@ -59,6 +63,7 @@ pub struct LevelView {
 
 /// The matching engine containing the information.
 #[derive(RootView, SimpleObject)]
+#[graphql(complex)]
 #[view(context = "ViewStorageContext")]
 pub struct MatchingEngineState {
     ///The next_order_number contains the order_id so that
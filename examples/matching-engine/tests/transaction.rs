@@ -7,7 +7,7 @@
 
 use async_graphql::InputType;
 use linera_sdk::{
-    base::{AccountOwner, Amount, ApplicationId, ApplicationPermissions},
+    base::{AccountOwner, Amount, ApplicationId, ApplicationPermissions, TimeDelta},
     test::{ActiveChain, QueryOutcome, TestValidator},
 };
 use matching_engine::{
@@ -33,6 +33,28 @@ pub async fn get_orders(
     Some(values)
 }
 
+pub async fn get_open_orders(
+    application_id: ApplicationId<MatchingEngineAbi>,
+    chain: &ActiveChain,
+    account_owner: AccountOwner,
+) -> Vec<(OrderId, Amount)> {
+    let query = format!(
+        "query {{ openOrders(owner: {}) {{ orderId amount }} }}",
+        account_owner.to_value()
+    );
+    let QueryOutcome { response, .. } = chain.graphql_query(application_id, query).await;
+    response["openOrders"]
+        .as_array()
+        .expect("Failed to read open orders")
+        .iter()
+        .map(|order| {
+            let order_id = order["orderId"].as_u64().unwrap();
+            let amount = order["amount"].as_str().unwrap().parse().unwrap();
+            (order_id, amount)
+        })
+        .collect()
+}
+
 /// Test creating a matching engine, pushing some orders, canceling some and
 /// seeing how the transactions went.
 ///
@@ -151,6 +173,7 @@ async fn single_transaction() {
             amount: Amount::from_tokens(3),
             nature: OrderNature::Bid,
             price,
+            expiry: None,
         };
         let operation = Operation::ExecuteOrder { order };
         let bid_certificate = user_chain_a
@@ -203,6 +226,7 @@ async fn single_transaction() {
             amount: Amount::from_tokens(4),
             nature: OrderNature::Ask,
             price,
+            expiry: None,
         };
         let operation = Operation::ExecuteOrder { order };
         let ask_certificate = user_chain_b
@@ -310,3 +334,115 @@ async fn single_transaction() {
         );
     }
 }
+
+/// Test that an order is no longer filled once it has expired.
+///
+/// A single owner trades on the chain of the matching engine:
+/// * A bid for 3 token B at a price of 2, expiring after 10 seconds, commits 6 token A.
+/// * After 20 seconds, an ask for 2 token B at a price of 1 would match the bid, but the bid
+///   has expired: its 6 token A are returned instead, and the ask remains in the order book.
+/// * Cancelling all the orders of the owner returns the 2 token B of the ask.
+#[tokio::test]
+async fn expired_order() {
+    let (validator, bytecode_id) =
+        TestValidator::with_current_bytecode::<MatchingEngineAbi, Parameters, ()>().await;
+
+    let mut matching_chain = validator.new_chain().await;
+    let owner = AccountOwner::from(matching_chain.public_key());
+
+    let fungible_bytecode_id = matching_chain
+        .publish_bytecodes_in::<fungible::FungibleTokenAbi, fungible::Parameters, fungible::InitialState>("../fungible")
+        .await;
+    let mut tokens = Vec::new();
+    for ticker_symbol in ["A", "B"] {
+        let initial_state =
+            fungible::InitialStateBuilder::default().with_account(owner, Amount::from_tokens(10));
+        let token_id = matching_chain
+            .create_application(
+                fungible_bytecode_id,
+                fungible::Parameters::new(ticker_symbol),
+                initial_state.build(),
+                vec![],
+            )
+            .await;
+        tokens.push(token_id);
+    }
+    let (token_id_a, token_id_b) = (tokens[0], tokens[1]);
+
+    let matching_id = matching_chain
+        .create_application(
+            bytecode_id,
+            Parameters {
+                tokens: [token_id_a, token_id_b],
+            },
+            (),
+            vec![token_id_a.forget_abi(), token_id_b.forget_abi()],
+        )
+        .await;
+
+    let expiry = validator
+        .current_time()
+        .saturating_add(TimeDelta::from_secs(10));
+    let order = Order::Insert {
+        owner,
+        amount: Amount::from_tokens(3),
+        nature: OrderNature::Bid,
+        price: Price { price: 2 },
+        expiry: Some(expiry),
+    };
+    matching_chain
+        .add_block(|block| {
+            block.with_operation(matching_id, Operation::ExecuteOrder { order });
+        })
+        .await;
+
+    let open_orders = get_open_orders(matching_id, &matching_chain, owner).await;
+    assert_eq!(open_orders.len(), 1);
+    assert_eq!(open_orders[0].1, Amount::from_tokens(3));
+    assert_eq!(
+        fungible::query_account(token_id_a, &matching_chain, owner).await,
+        Some(Amount::from_tokens(4))
+    );
+
+    validator.advance_time(TimeDelta::from_secs(20));
+
+    let order = Order::Insert {
+        owner,
+        amount: Amount::from_tokens(2),
+        nature: OrderNature::Ask,
+        price: Price { price: 1 },
+        expiry: None,
+    };
+    matching_chain
+        .add_block(|block| {
+            block.with_operation(matching_id, Operation::ExecuteOrder { order });
+        })
+        .await;
+
+    let open_orders = get_open_orders(matching_id, &matching_chain, owner).await;
+    assert_eq!(open_orders.len(), 1);
+    assert_eq!(open_orders[0].1, Amount::from_tokens(2));
+    assert_eq!(
+        fungible::query_account(token_id_a, &matching_chain, owner).await,
+        Some(Amount::from_tokens(10))
+    );
+    assert_eq!(
+        fungible::query_account(token_id_b, &matching_chain, owner).await,
+        Some(Amount::from_tokens(8))
+    );
+
+    let order = Order::CancelAll { owner };
+    matching_chain
+        .add_block(|block| {
+            block.with_operation(matching_id, Operation::ExecuteOrder { order });
+        })
+        .await;
+
+    assert!(get_open_orders(matching_id, &matching_chain, owner)
+        .await
+        .is_empty());
+    assert_eq!(
+        fungible::query_account(token_id_b, &matching_chain, owner).await,
+        Some(Amount::from_tokens(10))
+    );
+}
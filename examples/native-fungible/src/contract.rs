@@ -89,6 +89,12 @@ impl Contract for NativeFungibleTokenContract {
                 );
                 FungibleResponse::Ok
             }
+
+            Operation::Approve { .. }
+            | Operation::Allowance { .. }
+            | Operation::TransferFrom { .. } => {
+                panic!("Allowances are not supported for the native token")
+            }
         }
     }
 
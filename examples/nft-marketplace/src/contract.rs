@@ -0,0 +1,347 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use fungible::{Account, FungibleClient};
+use linera_sdk::{
+    base::{AccountOwner, Amount, WithContractAbi},
+    views::{RootView, View},
+    Contract, ContractRuntime,
+};
+use nft_marketplace::{Listing, Message, NftMarketplaceAbi, Offer, Operation, Parameters};
+use non_fungible::TokenId;
+
+use self::state::NftMarketplaceState;
+
+pub struct NftMarketplaceContract {
+    state: NftMarketplaceState,
+    runtime: ContractRuntime<Self>,
+}
+
+linera_sdk::contract!(NftMarketplaceContract);
+
+impl WithContractAbi for NftMarketplaceContract {
+    type Abi = NftMarketplaceAbi;
+}
+
+impl Contract for NftMarketplaceContract {
+    type Message = Message;
+    type InstantiationArgument = ();
+    type Parameters = Parameters;
+
+    async fn load(runtime: ContractRuntime<Self>) -> Self {
+        let state = NftMarketplaceState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        NftMarketplaceContract { state, runtime }
+    }
+
+    async fn instantiate(&mut self, _argument: ()) {
+        // Validate that the application parameters were configured correctly.
+        self.runtime.application_parameters();
+    }
+
+    async fn execute_operation(&mut self, operation: Operation) -> Self::Response {
+        let message = match operation {
+            Operation::List {
+                seller,
+                token_id,
+                price,
+            } => {
+                self.check_account_authentication(seller);
+                let marketplace_account = self.marketplace_account();
+                self.transfer_nft(seller, token_id.clone(), marketplace_account);
+                Message::List {
+                    seller: self.local_account(seller),
+                    token_id,
+                    price,
+                }
+            }
+            Operation::CancelListing { seller, token_id } => {
+                self.check_account_authentication(seller);
+                Message::CancelListing {
+                    seller: self.local_account(seller),
+                    token_id,
+                }
+            }
+            Operation::Buy {
+                buyer,
+                token_id,
+                max_price,
+            } => {
+                self.check_account_authentication(buyer);
+                let marketplace_account = self.marketplace_account();
+                self.transfer_tokens(buyer, max_price, marketplace_account);
+                Message::Buy {
+                    buyer: self.local_account(buyer),
+                    token_id,
+                    max_price,
+                }
+            }
+            Operation::MakeOffer {
+                buyer,
+                token_id,
+                amount,
+            } => {
+                self.check_account_authentication(buyer);
+                let marketplace_account = self.marketplace_account();
+                self.transfer_tokens(buyer, amount, marketplace_account);
+                Message::MakeOffer {
+                    buyer: self.local_account(buyer),
+                    token_id,
+                    amount,
+                }
+            }
+            Operation::CancelOffer { buyer, token_id } => {
+                self.check_account_authentication(buyer);
+                Message::CancelOffer {
+                    buyer: self.local_account(buyer),
+                    token_id,
+                }
+            }
+            Operation::AcceptOffer {
+                seller,
+                token_id,
+                buyer,
+            } => {
+                self.check_account_authentication(seller);
+                Message::AcceptOffer {
+                    seller: self.local_account(seller),
+                    token_id,
+                    buyer,
+                }
+            }
+        };
+
+        let marketplace_chain_id = self.runtime.application_creator_chain_id();
+        if self.runtime.chain_id() == marketplace_chain_id {
+            self.execute_marketplace_message(message).await;
+        } else {
+            self.runtime
+                .prepare_message(message)
+                .with_authentication()
+                .send_to(marketplace_chain_id);
+        }
+    }
+
+    async fn execute_message(&mut self, message: Message) {
+        assert_eq!(
+            self.runtime.chain_id(),
+            self.runtime.application_creator_chain_id(),
+            "Marketplace messages must be sent to the chain that created the marketplace"
+        );
+        self.execute_marketplace_message(message).await;
+    }
+
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
+
+impl NftMarketplaceContract {
+    /// Verifies that an operation is authenticated for this local account.
+    fn check_account_authentication(&mut self, owner: AccountOwner) {
+        match owner {
+            AccountOwner::User(address) => {
+                assert_eq!(
+                    self.runtime.authenticated_signer(),
+                    Some(address),
+                    "The requested operation is not correctly authenticated."
+                )
+            }
+            AccountOwner::Application(id) => {
+                assert_eq!(
+                    self.runtime.authenticated_caller_id(),
+                    Some(id),
+                    "The requested operation is not correctly authenticated."
+                )
+            }
+        }
+    }
+
+    /// Returns the account of `owner` on the current chain.
+    fn local_account(&mut self, owner: AccountOwner) -> Account {
+        Account {
+            chain_id: self.runtime.chain_id(),
+            owner,
+        }
+    }
+
+    /// Returns the account holding the escrowed NFTs and tokens.
+    fn marketplace_account(&mut self) -> Account {
+        Account {
+            chain_id: self.runtime.application_creator_chain_id(),
+            owner: AccountOwner::Application(self.runtime.application_id().forget_abi()),
+        }
+    }
+
+    /// Transfers a locally owned NFT to the `target_account`.
+    fn transfer_nft(&mut self, owner: AccountOwner, token_id: TokenId, target_account: Account) {
+        let nft = self.runtime.application_parameters().nft;
+        let transfer = non_fungible::Operation::Transfer {
+            source_owner: owner,
+            token_id,
+            target_account,
+        };
+        self.runtime.call_application(true, nft, &transfer);
+    }
+
+    /// Transfers locally owned tokens to the `target_account`.
+    fn transfer_tokens(&mut self, owner: AccountOwner, amount: Amount, target_account: Account) {
+        if amount == Amount::ZERO {
+            return;
+        }
+        let token = self.runtime.application_parameters().token;
+        FungibleClient::new(&mut self.runtime, token)
+            .with_authentication()
+            .transfer(owner, amount, target_account);
+    }
+
+    /// Transfers an escrowed NFT to the `target_account`.
+    fn release_nft(&mut self, token_id: TokenId, target_account: Account) {
+        let owner = self.marketplace_account().owner;
+        self.transfer_nft(owner, token_id, target_account);
+    }
+
+    /// Transfers escrowed tokens to the `target_account`.
+    fn release_tokens(&mut self, amount: Amount, target_account: Account) {
+        let owner = self.marketplace_account().owner;
+        self.transfer_tokens(owner, amount, target_account);
+    }
+
+    async fn listing(&self, token_id: &TokenId) -> Option<Listing> {
+        self.state
+            .listings
+            .get(token_id)
+            .await
+            .expect("Failed to read the listings")
+    }
+
+    fn set_listing(&mut self, token_id: &TokenId, listing: Listing) {
+        self.state
+            .listings
+            .insert(token_id, listing)
+            .expect("Failed to update the listings");
+    }
+
+    fn remove_listing(&mut self, token_id: &TokenId) {
+        self.state
+            .listings
+            .remove(token_id)
+            .expect("Failed to update the listings");
+    }
+
+    /// Sells a listed NFT for `price`, and refunds all the remaining offers.
+    fn settle(&mut self, token_id: TokenId, listing: Listing, buyer: Account, price: Amount) {
+        self.remove_listing(&token_id);
+        self.release_tokens(price, listing.seller);
+        self.release_nft(token_id, buyer);
+        for offer in listing.offers {
+            if offer.buyer != buyer {
+                self.release_tokens(offer.amount, offer.buyer);
+            }
+        }
+    }
+
+    /// Updates the listings on the chain where the marketplace was created.
+    async fn execute_marketplace_message(&mut self, message: Message) {
+        match message {
+            Message::List {
+                seller,
+                token_id,
+                price,
+            } => {
+                if self.listing(&token_id).await.is_some() {
+                    // The NFT is already in escrow, so it must be returned to the seller
+                    // instead of rejecting the message.
+                    self.release_nft(token_id, seller);
+                    return;
+                }
+                let listing = Listing {
+                    seller,
+                    price,
+                    offers: Vec::new(),
+                };
+                self.set_listing(&token_id, listing);
+            }
+
+            Message::CancelListing { seller, token_id } => {
+                let listing = self.listing(&token_id).await.expect("NFT is not listed");
+                assert_eq!(listing.seller.owner, seller.owner, "NFT has another seller");
+                self.remove_listing(&token_id);
+                self.release_nft(token_id, listing.seller);
+                for offer in listing.offers {
+                    self.release_tokens(offer.amount, offer.buyer);
+                }
+            }
+
+            Message::Buy {
+                buyer,
+                token_id,
+                max_price,
+            } => match self.listing(&token_id).await {
+                Some(listing) if listing.price <= max_price => {
+                    let refund = max_price.saturating_sub(listing.price);
+                    let price = listing.price;
+                    let previous_offer = listing
+                        .offers
+                        .iter()
+                        .find(|offer| offer.buyer == buyer)
+                        .map(|offer| offer.amount);
+                    self.settle(token_id, listing, buyer, price);
+                    let refund = refund.saturating_add(previous_offer.unwrap_or_default());
+                    self.release_tokens(refund, buyer);
+                }
+                _ => self.release_tokens(max_price, buyer),
+            },
+
+            Message::MakeOffer {
+                buyer,
+                token_id,
+                amount,
+            } => {
+                let Some(mut listing) = self.listing(&token_id).await else {
+                    self.release_tokens(amount, buyer);
+                    return;
+                };
+                if let Some(index) = listing.offers.iter().position(|offer| offer.buyer == buyer) {
+                    let previous_offer = listing.offers.remove(index);
+                    self.release_tokens(previous_offer.amount, buyer);
+                }
+                listing.offers.push(Offer { buyer, amount });
+                self.set_listing(&token_id, listing);
+            }
+
+            Message::CancelOffer { buyer, token_id } => {
+                let mut listing = self.listing(&token_id).await.expect("NFT is not listed");
+                let index = listing
+                    .offers
+                    .iter()
+                    .position(|offer| offer.buyer == buyer)
+                    .expect("No offer to cancel");
+                let offer = listing.offers.remove(index);
+                self.set_listing(&token_id, listing);
+                self.release_tokens(offer.amount, buyer);
+            }
+
+            Message::AcceptOffer {
+                seller,
+                token_id,
+                buyer,
+            } => {
+                let listing = self.listing(&token_id).await.expect("NFT is not listed");
+                assert_eq!(listing.seller.owner, seller.owner, "NFT has another seller");
+                let offer = listing
+                    .offers
+                    .iter()
+                    .find(|offer| offer.buyer == buyer)
+                    .expect("No offer from this buyer")
+                    .amount;
+                self.settle(token_id, listing, buyer, offer);
+            }
+        }
+    }
+}
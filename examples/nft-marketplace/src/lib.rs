@@ -0,0 +1,137 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/*! ABI of the NFT Marketplace Example Application */
+
+use async_graphql::{Request, Response, SimpleObject};
+use fungible::{Account, FungibleTokenAbi};
+use linera_sdk::{
+    base::{AccountOwner, Amount, ApplicationId, ContractAbi, ServiceAbi},
+    graphql::GraphQLMutationRoot,
+};
+use non_fungible::{NonFungibleTokenAbi, TokenId};
+use serde::{Deserialize, Serialize};
+
+pub struct NftMarketplaceAbi;
+
+impl ContractAbi for NftMarketplaceAbi {
+    type Operation = Operation;
+    type Response = ();
+}
+
+impl ServiceAbi for NftMarketplaceAbi {
+    type Query = Request;
+    type QueryResponse = Response;
+}
+
+/// The applications that the marketplace trades with.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Parameters {
+    /// The application managing the NFTs that are traded.
+    pub nft: ApplicationId<NonFungibleTokenAbi>,
+    /// The fungible token used to pay for the NFTs.
+    pub token: ApplicationId<FungibleTokenAbi>,
+}
+
+/// An operation.
+///
+/// Operations can be executed on any chain. The NFTs and the payments are held in escrow by
+/// the marketplace on the chain where it was created, where the listings and offers are
+/// recorded.
+#[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
+pub enum Operation {
+    /// Puts a locally owned NFT up for sale, at the given `price`.
+    List {
+        seller: AccountOwner,
+        token_id: TokenId,
+        price: Amount,
+    },
+    /// Withdraws a listed NFT from sale, returning it to the seller and refunding all the
+    /// offers made for it.
+    CancelListing {
+        seller: AccountOwner,
+        token_id: TokenId,
+    },
+    /// Buys a listed NFT, paying up to `max_price` from a locally owned account. Any
+    /// difference with the listed price, or the whole payment if the NFT is no longer for
+    /// sale, is refunded.
+    Buy {
+        buyer: AccountOwner,
+        token_id: TokenId,
+        max_price: Amount,
+    },
+    /// Offers to buy a listed NFT for the given `amount`, replacing any previous offer from the
+    /// same account.
+    MakeOffer {
+        buyer: AccountOwner,
+        token_id: TokenId,
+        amount: Amount,
+    },
+    /// Withdraws an offer, refunding it.
+    CancelOffer {
+        buyer: AccountOwner,
+        token_id: TokenId,
+    },
+    /// Sells a listed NFT to the account that made an offer for it.
+    AcceptOffer {
+        seller: AccountOwner,
+        token_id: TokenId,
+        buyer: Account,
+    },
+}
+
+/// A message sent to the chain where the marketplace was created.
+///
+/// The accounts in the messages were authenticated on their own chains when the operation was
+/// executed.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Message {
+    /// An NFT was transferred to the marketplace, to be sold for `price`.
+    List {
+        seller: Account,
+        token_id: TokenId,
+        price: Amount,
+    },
+    /// The seller withdraws an NFT from sale.
+    CancelListing { seller: Account, token_id: TokenId },
+    /// Up to `max_price` tokens were transferred to the marketplace, to buy an NFT.
+    Buy {
+        buyer: Account,
+        token_id: TokenId,
+        max_price: Amount,
+    },
+    /// The `amount` of tokens were transferred to the marketplace, as an offer for an NFT.
+    MakeOffer {
+        buyer: Account,
+        token_id: TokenId,
+        amount: Amount,
+    },
+    /// The buyer withdraws an offer.
+    CancelOffer { buyer: Account, token_id: TokenId },
+    /// The seller accepts the offer of `buyer`.
+    AcceptOffer {
+        seller: Account,
+        token_id: TokenId,
+        buyer: Account,
+    },
+}
+
+/// An NFT for sale.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct Listing {
+    /// The account to pay, and to return the NFT to if the listing is cancelled.
+    pub seller: Account,
+    /// The price asked by the seller.
+    pub price: Amount,
+    /// The offers made for the NFT, in the order they were received.
+    pub offers: Vec<Offer>,
+}
+
+/// An offer to buy a listed NFT.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct Offer {
+    /// The account that made the offer, and that receives the NFT if it is accepted.
+    pub buyer: Account,
+    /// The amount held in escrow by the marketplace.
+    pub amount: Amount,
+}
@@ -0,0 +1,51 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use std::sync::Arc;
+
+use async_graphql::{EmptySubscription, Request, Response, Schema};
+use linera_sdk::{
+    base::WithServiceAbi, graphql::GraphQLMutationRoot, views::View, Service, ServiceRuntime,
+};
+use nft_marketplace::{Operation, Parameters};
+
+use self::state::NftMarketplaceState;
+
+pub struct NftMarketplaceService {
+    state: Arc<NftMarketplaceState>,
+    runtime: Arc<ServiceRuntime<Self>>,
+}
+
+linera_sdk::service!(NftMarketplaceService);
+
+impl WithServiceAbi for NftMarketplaceService {
+    type Abi = nft_marketplace::NftMarketplaceAbi;
+}
+
+impl Service for NftMarketplaceService {
+    type Parameters = Parameters;
+
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
+        let state = NftMarketplaceState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        NftMarketplaceService {
+            state: Arc::new(state),
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    async fn handle_query(&self, request: Request) -> Response {
+        let schema = Schema::build(
+            self.state.clone(),
+            Operation::mutation_root(self.runtime.clone()),
+            EmptySubscription,
+        )
+        .finish();
+        schema.execute(request).await
+    }
+}
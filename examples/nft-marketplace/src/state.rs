@@ -0,0 +1,16 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_sdk::views::{linera_views, GraphQLView, MapView, RootView, ViewStorageContext};
+use nft_marketplace::Listing;
+use non_fungible::TokenId;
+
+/// The application state.
+///
+/// Only the chain where the marketplace was created has listings.
+#[derive(RootView, GraphQLView)]
+#[view(context = "ViewStorageContext")]
+pub struct NftMarketplaceState {
+    /// The NFTs for sale, with the offers made for them.
+    pub listings: MapView<TokenId, Listing>,
+}
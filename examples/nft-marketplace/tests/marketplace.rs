@@ -0,0 +1,149 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration tests for the NFT Marketplace application.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use async_graphql::InputType;
+use base64::engine::{general_purpose::STANDARD_NO_PAD, Engine as _};
+use linera_sdk::{
+    base::{AccountOwner, Amount},
+    test::{QueryOutcome, TestValidator},
+};
+use nft_marketplace::{NftMarketplaceAbi, Operation, Parameters};
+use non_fungible::{Nft, NonFungibleTokenAbi};
+
+/// Test selling an NFT across microchains.
+///
+/// A seller mints an NFT on a `seller_chain` and lists it on the marketplace, which was created
+/// on a `marketplace_chain`. A buyer on a `buyer_chain` then buys it, paying more than the asked
+/// price. The seller receives the price, the buyer receives the NFT and the change, and the
+/// listing is removed.
+#[tokio::test]
+async fn test_buy_listed_nft() {
+    let price = Amount::from_tokens(5);
+    let max_price = Amount::from_tokens(7);
+    let initial_amount = Amount::from_tokens(10);
+
+    let (validator, bytecode_id) =
+        TestValidator::with_current_bytecode::<NftMarketplaceAbi, Parameters, ()>().await;
+    let mut marketplace_chain = validator.new_chain().await;
+    let seller_chain = validator.new_chain().await;
+    let seller = AccountOwner::from(seller_chain.public_key());
+
+    let fungible_bytecode_id = marketplace_chain
+        .publish_bytecodes_in::<fungible::FungibleTokenAbi, fungible::Parameters, fungible::InitialState>("../fungible")
+        .await;
+    let (token_id, accounts) =
+        fungible::create_with_accounts(&validator, fungible_bytecode_id, [initial_amount]).await;
+    let (buyer_chain, buyer, _) = &accounts[0];
+
+    let nft_bytecode_id = marketplace_chain
+        .publish_bytecodes_in::<NonFungibleTokenAbi, (), ()>("../non-fungible")
+        .await;
+    let nft_id = marketplace_chain
+        .create_application(nft_bytecode_id, (), (), vec![])
+        .await;
+
+    marketplace_chain.register_application(token_id).await;
+    let marketplace_id = marketplace_chain
+        .create_application(
+            bytecode_id,
+            Parameters {
+                nft: nft_id,
+                token: token_id,
+            },
+            (),
+            vec![nft_id.forget_abi(), token_id.forget_abi()],
+        )
+        .await;
+
+    seller_chain.register_application(nft_id).await;
+    seller_chain.register_application(marketplace_id).await;
+    buyer_chain.register_application(marketplace_id).await;
+
+    let name = "Lighthouse".to_owned();
+    let blob_hash = seller_chain
+        .publish_data_blob(b"A lighthouse".to_vec())
+        .await;
+    let nft_token_id = Nft::create_token_id(
+        &seller_chain.id(),
+        &nft_id.forget_abi(),
+        &name,
+        &seller,
+        &blob_hash,
+        0,
+    )
+    .expect("Failed to create the NFT's token ID");
+
+    seller_chain
+        .add_block(|block| {
+            block.with_operation(
+                nft_id,
+                non_fungible::Operation::Mint {
+                    minter: seller,
+                    name,
+                    blob_hash,
+                },
+            );
+            block.with_operation(
+                marketplace_id,
+                Operation::List {
+                    seller,
+                    token_id: nft_token_id.clone(),
+                    price,
+                },
+            );
+        })
+        .await;
+    marketplace_chain.handle_received_messages().await;
+
+    let QueryOutcome { response, .. } = marketplace_chain
+        .graphql_query(marketplace_id, "query { listings { value { price } } }")
+        .await;
+    assert_eq!(
+        response["listings"][0]["value"]["price"].as_str(),
+        Some(price.to_string().as_str())
+    );
+
+    buyer_chain
+        .add_block(|block| {
+            block.with_operation(
+                marketplace_id,
+                Operation::Buy {
+                    buyer: *buyer,
+                    token_id: nft_token_id.clone(),
+                    max_price,
+                },
+            );
+        })
+        .await;
+    marketplace_chain.handle_received_messages().await;
+    seller_chain.handle_received_messages().await;
+    buyer_chain.handle_received_messages().await;
+
+    assert_eq!(
+        fungible::query_account(token_id, &seller_chain, seller).await,
+        Some(price),
+    );
+    assert_eq!(
+        fungible::query_account(token_id, buyer_chain, *buyer).await,
+        Some(initial_amount.saturating_sub(price)),
+    );
+
+    let query = format!(
+        "query {{ ownedTokenIdsByOwner(owner: {}) }}",
+        buyer.to_value()
+    );
+    let QueryOutcome { response, .. } = buyer_chain.graphql_query(nft_id, query).await;
+    assert_eq!(
+        response["ownedTokenIdsByOwner"],
+        serde_json::json!([STANDARD_NO_PAD.encode(&nft_token_id.id)])
+    );
+
+    let QueryOutcome { response, .. } = marketplace_chain
+        .graphql_query(marketplace_id, "query { listings { key { id } } }")
+        .await;
+    assert_eq!(response["listings"], serde_json::json!([]));
+}
@@ -0,0 +1,163 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use linera_sdk::{
+    base::{Amount, ChannelName, TimeDelta, WithContractAbi},
+    views::{RootView, View},
+    Contract, ContractRuntime,
+};
+use price_oracle::{Message, Operation, OracleResponse, Parameters, PriceFeed, PriceOracleAbi};
+
+use self::state::PriceOracleState;
+
+/// The channel name the application uses to send the price updates to the subscribers.
+const PRICES_CHANNEL_NAME: &[u8] = b"prices";
+
+pub struct PriceOracleContract {
+    state: PriceOracleState,
+    runtime: ContractRuntime<Self>,
+}
+
+linera_sdk::contract!(PriceOracleContract);
+
+impl WithContractAbi for PriceOracleContract {
+    type Abi = PriceOracleAbi;
+}
+
+impl Contract for PriceOracleContract {
+    type Message = Message;
+    type InstantiationArgument = ();
+    type Parameters = Parameters;
+
+    async fn load(runtime: ContractRuntime<Self>) -> Self {
+        let state = PriceOracleState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        PriceOracleContract { state, runtime }
+    }
+
+    async fn instantiate(&mut self, _argument: ()) {
+        // Validate that the application parameters were configured correctly.
+        self.runtime.application_parameters();
+    }
+
+    async fn execute_operation(&mut self, operation: Operation) -> OracleResponse {
+        match operation {
+            Operation::Publish { pair, price } => {
+                self.publish(pair, price);
+                OracleResponse::Ok
+            }
+            Operation::Subscribe => {
+                let publisher_chain_id = self.runtime.application_creator_chain_id();
+                self.runtime
+                    .send_message(publisher_chain_id, Message::Subscribe);
+                OracleResponse::Ok
+            }
+            Operation::Unsubscribe => {
+                let publisher_chain_id = self.runtime.application_creator_chain_id();
+                self.runtime
+                    .send_message(publisher_chain_id, Message::Unsubscribe);
+                OracleResponse::Ok
+            }
+            Operation::LatestPrice { pair, max_age } => {
+                OracleResponse::Price(self.latest_price(&pair, max_age).await)
+            }
+        }
+    }
+
+    async fn execute_message(&mut self, message: Message) {
+        let origin_chain_id = self
+            .runtime
+            .message_id()
+            .expect("Message ID has to be available when executing a message")
+            .chain_id;
+        match message {
+            Message::Subscribe => self.runtime.subscribe(
+                origin_chain_id,
+                ChannelName::from(PRICES_CHANNEL_NAME.to_vec()),
+            ),
+            Message::Unsubscribe => self.runtime.unsubscribe(
+                origin_chain_id,
+                ChannelName::from(PRICES_CHANNEL_NAME.to_vec()),
+            ),
+            Message::Price { pair, feed } => {
+                assert_eq!(
+                    origin_chain_id,
+                    self.runtime.application_creator_chain_id(),
+                    "Prices can only be published by the chain that created the oracle"
+                );
+                let publisher = self.runtime.application_parameters().publisher;
+                assert_eq!(
+                    self.runtime.authenticated_signer(),
+                    Some(publisher),
+                    "Price updates must be signed by the publisher"
+                );
+                let previous = self
+                    .state
+                    .prices
+                    .get(&pair)
+                    .await
+                    .expect("Failed to read the prices");
+                // Messages from the publisher arrive in order, but ignore older prices anyway.
+                if previous.map_or(true, |previous| previous.timestamp <= feed.timestamp) {
+                    self.set_price(&pair, feed);
+                }
+            }
+        }
+    }
+
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
+
+impl PriceOracleContract {
+    /// Records a new price, and sends it to the subscribers.
+    fn publish(&mut self, pair: String, price: Amount) {
+        assert_eq!(
+            self.runtime.chain_id(),
+            self.runtime.application_creator_chain_id(),
+            "Prices can only be published on the chain that created the oracle"
+        );
+        let publisher = self.runtime.application_parameters().publisher;
+        assert_eq!(
+            self.runtime.authenticated_signer(),
+            Some(publisher),
+            "Only the publisher can publish prices"
+        );
+        let feed = PriceFeed {
+            price,
+            timestamp: self.runtime.system_time(),
+        };
+        self.set_price(&pair, feed);
+        self.runtime
+            .prepare_message(Message::Price { pair, feed })
+            .with_authentication()
+            .send_to(ChannelName::from(PRICES_CHANNEL_NAME.to_vec()));
+    }
+
+    /// Returns the latest price of `pair`, failing if it is older than `max_age`.
+    async fn latest_price(&mut self, pair: &str, max_age: TimeDelta) -> PriceFeed {
+        let feed = self
+            .state
+            .prices
+            .get(pair)
+            .await
+            .expect("Failed to read the prices")
+            .unwrap_or_else(|| panic!("No price for {pair}"));
+        let age = self.runtime.system_time().delta_since(feed.timestamp);
+        assert!(age <= max_age, "The price of {pair} is stale");
+        feed
+    }
+
+    fn set_price(&mut self, pair: &str, feed: PriceFeed) {
+        self.state
+            .prices
+            .insert(pair, feed)
+            .expect("Failed to update the prices");
+    }
+}
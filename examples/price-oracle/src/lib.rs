@@ -0,0 +1,76 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/*! ABI of the Price Oracle Example Application */
+
+use async_graphql::{Request, Response, SimpleObject};
+use linera_sdk::{
+    base::{Amount, ContractAbi, Owner, ServiceAbi, TimeDelta, Timestamp},
+    graphql::GraphQLMutationRoot,
+};
+use serde::{Deserialize, Serialize};
+
+pub struct PriceOracleAbi;
+
+impl ContractAbi for PriceOracleAbi {
+    type Operation = Operation;
+    type Response = OracleResponse;
+}
+
+impl ServiceAbi for PriceOracleAbi {
+    type Query = Request;
+    type QueryResponse = Response;
+}
+
+/// The parameters of the oracle.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Parameters {
+    /// The owner that signs the price updates, on the chain that created the oracle.
+    pub publisher: Owner,
+}
+
+/// An operation.
+#[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
+pub enum Operation {
+    /// Publishes the price of an asset pair, e.g. `"ETH/USD"`, and sends it to the subscribers.
+    ///
+    /// Only the publisher can execute this operation, on the chain that created the oracle.
+    Publish { pair: String, price: Amount },
+    /// Subscribes this chain to the price updates.
+    Subscribe,
+    /// Unsubscribes this chain from the price updates.
+    Unsubscribe,
+    /// Returns the latest known price of an asset pair, as an [`OracleResponse::Price`].
+    ///
+    /// Fails if there is no price for the pair, or if it was published more than `max_age`
+    /// ago. This is meant to be called by other applications, such as an AMM.
+    LatestPrice { pair: String, max_age: TimeDelta },
+}
+
+/// The response to an operation.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub enum OracleResponse {
+    #[default]
+    Ok,
+    Price(PriceFeed),
+}
+
+/// A message.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Message {
+    /// The origin chain wants to subscribe to the price updates.
+    Subscribe,
+    /// The origin chain wants to unsubscribe from the price updates.
+    Unsubscribe,
+    /// A new price was published, signed by the publisher.
+    Price { pair: String, feed: PriceFeed },
+}
+
+/// A published price.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, SimpleObject)]
+pub struct PriceFeed {
+    /// The price of one unit of the base asset, in the quote asset.
+    pub price: Amount,
+    /// When the price was published.
+    pub timestamp: Timestamp,
+}
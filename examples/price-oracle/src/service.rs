@@ -0,0 +1,51 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use std::sync::Arc;
+
+use async_graphql::{EmptySubscription, Request, Response, Schema};
+use linera_sdk::{
+    base::WithServiceAbi, graphql::GraphQLMutationRoot, views::View, Service, ServiceRuntime,
+};
+use price_oracle::{Operation, Parameters};
+
+use self::state::PriceOracleState;
+
+pub struct PriceOracleService {
+    state: Arc<PriceOracleState>,
+    runtime: Arc<ServiceRuntime<Self>>,
+}
+
+linera_sdk::service!(PriceOracleService);
+
+impl WithServiceAbi for PriceOracleService {
+    type Abi = price_oracle::PriceOracleAbi;
+}
+
+impl Service for PriceOracleService {
+    type Parameters = Parameters;
+
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
+        let state = PriceOracleState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        PriceOracleService {
+            state: Arc::new(state),
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    async fn handle_query(&self, request: Request) -> Response {
+        let schema = Schema::build(
+            self.state.clone(),
+            Operation::mutation_root(self.runtime.clone()),
+            EmptySubscription,
+        )
+        .finish();
+        schema.execute(request).await
+    }
+}
@@ -0,0 +1,15 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_sdk::views::{linera_views, GraphQLView, MapView, RootView, ViewStorageContext};
+use price_oracle::PriceFeed;
+
+/// The application state.
+///
+/// The chain that created the oracle and the subscribed chains all keep the latest prices.
+#[derive(RootView, GraphQLView)]
+#[view(context = "ViewStorageContext")]
+pub struct PriceOracleState {
+    /// The latest price of each asset pair.
+    pub prices: MapView<String, PriceFeed>,
+}
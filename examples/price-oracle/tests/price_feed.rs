@@ -0,0 +1,106 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration tests for the Price Oracle application.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use linera_sdk::{
+    base::{Amount, Owner, TimeDelta},
+    test::{QueryOutcome, TestValidator},
+};
+use price_oracle::{Operation, Parameters, PriceOracleAbi};
+
+/// Test publishing a price and reading it on a subscribed chain.
+///
+/// The publisher creates the oracle on its chain, and a `subscriber_chain` subscribes to it. The
+/// price published afterwards is received by the subscriber, where it can be read until it
+/// becomes stale.
+#[tokio::test]
+async fn test_publish_and_read_price() {
+    let price = Amount::from_tokens(3_000);
+    let max_age = TimeDelta::from_secs(60);
+
+    let (validator, bytecode_id) =
+        TestValidator::with_current_bytecode::<PriceOracleAbi, Parameters, ()>().await;
+    let mut publisher_chain = validator.new_chain().await;
+    let publisher = Owner::from(publisher_chain.public_key());
+    let application_id = publisher_chain
+        .create_application(bytecode_id, Parameters { publisher }, (), vec![])
+        .await;
+
+    let subscriber_chain = validator.new_chain().await;
+    subscriber_chain.register_application(application_id).await;
+    subscriber_chain
+        .add_block(|block| {
+            block.with_operation(application_id, Operation::Subscribe);
+        })
+        .await;
+    publisher_chain.handle_received_messages().await;
+
+    // Only the publisher can publish prices.
+    assert!(subscriber_chain
+        .try_add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Publish {
+                    pair: "ETH/USD".to_owned(),
+                    price: Amount::ONE,
+                },
+            );
+        })
+        .await
+        .is_err());
+
+    publisher_chain
+        .add_block(|block| {
+            block
+                .with_operation(
+                    application_id,
+                    Operation::Publish {
+                        pair: "ETH/USD".to_owned(),
+                        price,
+                    },
+                )
+                .with_timestamp(validator.current_time());
+        })
+        .await;
+    subscriber_chain.handle_received_messages().await;
+
+    let query = "query { prices { entry(key: \"ETH/USD\") { value { price } } } }";
+    let QueryOutcome { response, .. } = subscriber_chain.graphql_query(application_id, query).await;
+    assert_eq!(
+        response["prices"]["entry"]["value"]["price"].as_str(),
+        Some(price.to_string().as_str())
+    );
+
+    let latest_price = Operation::LatestPrice {
+        pair: "ETH/USD".to_owned(),
+        max_age,
+    };
+    subscriber_chain
+        .add_block(|block| {
+            block
+                .with_operation(application_id, latest_price)
+                .with_timestamp(validator.current_time());
+        })
+        .await;
+
+    validator.advance_time(TimeDelta::from_secs(120));
+
+    // The price is now stale.
+    assert!(subscriber_chain
+        .try_add_block(|block| {
+            block
+                .with_operation(
+                    application_id,
+                    Operation::LatestPrice {
+                        pair: "ETH/USD".to_owned(),
+                        max_age,
+                    },
+                )
+                .with_timestamp(validator.current_time());
+        })
+        .await
+        .is_err());
+}
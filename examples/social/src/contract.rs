@@ -10,7 +10,10 @@ use linera_sdk::{
     views::{RootView, View},
     Contract, ContractRuntime,
 };
-use social::{Comment, Key, Message, Operation, OwnPost, Post, SocialAbi};
+use social::{
+    Attachment, Comment, Key, Message, Operation, OwnPost, Post, SocialAbi, MAX_ATTACHMENTS,
+    MAX_ATTACHMENT_SIZE,
+};
 use state::SocialState;
 
 /// The channel name the application uses for cross-chain messages about new posts.
@@ -48,8 +51,13 @@ impl Contract for SocialContract {
         let (destination, message) = match operation {
             Operation::Subscribe { chain_id } => (chain_id.into(), Message::Subscribe),
             Operation::Unsubscribe { chain_id } => (chain_id.into(), Message::Unsubscribe),
-            Operation::Post { text, image_url } => {
-                self.execute_post_operation(text, image_url).await
+            Operation::Post {
+                text,
+                image_url,
+                attachments,
+            } => {
+                self.execute_post_operation(text, image_url, attachments.unwrap_or_default())
+                    .await
             }
             Operation::Like { key } => self.execute_like_operation(key).await,
             Operation::Comment { key, comment } => {
@@ -94,12 +102,26 @@ impl SocialContract {
         &mut self,
         text: String,
         image_url: Option<String>,
+        attachments: Vec<Attachment>,
     ) -> (Destination, Message) {
+        assert!(
+            attachments.len() <= MAX_ATTACHMENTS,
+            "A post can have at most {MAX_ATTACHMENTS} attachments"
+        );
+        for attachment in &attachments {
+            let size = self.runtime.read_data_blob(attachment.blob_hash).len();
+            assert!(
+                size <= MAX_ATTACHMENT_SIZE,
+                "Attachment {} is larger than {MAX_ATTACHMENT_SIZE} bytes",
+                attachment.name
+            );
+        }
         let timestamp = self.runtime.system_time();
         let post = OwnPost {
             timestamp,
             text,
             image_url,
+            attachments,
         };
         let index = self.state.own_posts.count() as u64;
         self.state.own_posts.push(post.clone());
@@ -142,6 +164,7 @@ impl SocialContract {
             key: key.clone(),
             text: post.text,
             image_url: post.image_url,
+            attachments: post.attachments,
             likes: 0,
             comments: vec![],
         };
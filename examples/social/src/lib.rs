@@ -8,6 +8,7 @@ use linera_sdk::{
     base::{ChainId, ContractAbi, ServiceAbi, Timestamp},
     graphql::GraphQLMutationRoot,
     views::{CustomSerialize, ViewError},
+    DataBlobHash,
 };
 use serde::{Deserialize, Serialize};
 
@@ -23,6 +24,12 @@ impl ServiceAbi for SocialAbi {
     type QueryResponse = Response;
 }
 
+/// The maximum number of attachments of a post.
+pub const MAX_ATTACHMENTS: usize = 4;
+
+/// The maximum size of an attachment, in bytes.
+pub const MAX_ATTACHMENT_SIZE: usize = 1 << 20;
+
 /// An operation that can be executed by the application.
 #[derive(Debug, Serialize, Deserialize, GraphQLMutationRoot)]
 pub enum Operation {
@@ -31,9 +38,12 @@ pub enum Operation {
     /// Request to be unsubscribed from another chain.
     Unsubscribe { chain_id: ChainId },
     /// Send a new post to everyone who subscribed to us.
+    ///
+    /// The attachments must have been published as data blobs.
     Post {
         text: String,
         image_url: Option<String>,
+        attachments: Option<Vec<Attachment>>,
     },
     /// Like a post
     Like { key: Key },
@@ -69,6 +79,8 @@ pub struct OwnPost {
     pub text: String,
     /// The posted Image_url(optional).
     pub image_url: Option<String>,
+    /// The files attached to the post.
+    pub attachments: Vec<Attachment>,
 }
 
 /// A post on the social app.
@@ -80,12 +92,24 @@ pub struct Post {
     pub text: String,
     /// The post's image_url(optional).
     pub image_url: Option<String>,
+    /// The files attached to the post.
+    pub attachments: Vec<Attachment>,
     /// The total number of likes
     pub likes: u32,
     /// Comments with there ChainId
     pub comments: Vec<Comment>,
 }
 
+/// A file attached to a post, e.g. an image.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, SimpleObject, InputObject)]
+#[graphql(input_name = "AttachmentInput")]
+pub struct Attachment {
+    /// The file name.
+    pub name: String,
+    /// The hash of the data blob with the file's content.
+    pub blob_hash: DataBlobHash,
+}
+
 /// A comment on a post
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, SimpleObject)]
 pub struct Comment {
@@ -7,9 +7,11 @@ mod state;
 
 use std::sync::Arc;
 
-use async_graphql::{EmptySubscription, Request, Response, Schema};
+use async_graphql::{EmptySubscription, MergedObject, Object, Request, Response, Schema};
+use base64::engine::{general_purpose::STANDARD, Engine as _};
 use linera_sdk::{
-    base::WithServiceAbi, graphql::GraphQLMutationRoot, views::View, Service, ServiceRuntime,
+    base::WithServiceAbi, graphql::GraphQLMutationRoot, views::View, DataBlobHash, Service,
+    ServiceRuntime,
 };
 use social::Operation;
 use state::SocialState;
@@ -40,7 +42,12 @@ impl Service for SocialService {
 
     async fn handle_query(&self, request: Request) -> Response {
         let schema = Schema::build(
-            self.state.clone(),
+            QueryRoot(
+                self.state.clone(),
+                AttachmentQuery {
+                    runtime: self.runtime.clone(),
+                },
+            ),
             Operation::mutation_root(self.runtime.clone()),
             EmptySubscription,
         )
@@ -48,3 +55,19 @@ impl Service for SocialService {
         schema.execute(request).await
     }
 }
+
+/// The application state, together with the queries that read the attachments.
+#[derive(MergedObject)]
+struct QueryRoot(Arc<SocialState>, AttachmentQuery);
+
+struct AttachmentQuery {
+    runtime: Arc<ServiceRuntime<SocialService>>,
+}
+
+#[Object]
+impl AttachmentQuery {
+    /// Returns the content of a post's attachment, encoded in base64.
+    async fn attachment(&self, blob_hash: DataBlobHash) -> String {
+        STANDARD.encode(self.runtime.read_data_blob(blob_hash))
+    }
+}
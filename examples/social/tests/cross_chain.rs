@@ -5,8 +5,9 @@
 
 #![cfg(not(target_arch = "wasm32"))]
 
+use base64::engine::{general_purpose::STANDARD, Engine as _};
 use linera_sdk::test::{QueryOutcome, TestValidator};
-use social::Operation;
+use social::{Attachment, Operation};
 
 /// Test posting messages across microchains.
 ///
@@ -46,6 +47,7 @@ async fn test_cross_chain_posting() {
                 Operation::Post {
                     text: "Linera is the new Mastodon".to_string(),
                     image_url: None,
+                    attachments: None,
                 },
             );
         })
@@ -66,3 +68,65 @@ async fn test_cross_chain_posting() {
     let author = response["receivedPosts"]["keys"][0]["author"].clone();
     assert_eq!(author, chain2.id().to_string());
 }
+
+/// Test posting a file attached to a post.
+///
+/// The file is published as a data blob on chain2 and attached to a post, which chain1 receives.
+/// Both chains can then retrieve the file's content with the service.
+#[tokio::test]
+async fn test_post_with_attachment() {
+    let (validator, bytecode_id) =
+        TestValidator::with_current_bytecode::<social::SocialAbi, (), ()>().await;
+    let mut chain1 = validator.new_chain().await;
+    let application_id = chain1.create_application(bytecode_id, (), (), vec![]).await;
+    let chain2 = validator.new_chain().await;
+
+    chain1
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Subscribe {
+                    chain_id: chain2.id(),
+                },
+            );
+        })
+        .await;
+    chain2.handle_received_messages().await;
+
+    let content = b"Hello, Linera!".to_vec();
+    let blob_hash = chain2.publish_data_blob(content.clone()).await;
+    chain2
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Post {
+                    text: "See the attached file".to_string(),
+                    image_url: None,
+                    attachments: Some(vec![Attachment {
+                        name: "hello.txt".to_string(),
+                        blob_hash,
+                    }]),
+                },
+            );
+        })
+        .await;
+    chain1.handle_received_messages().await;
+
+    let query = "query { receivedPosts { entries { value { attachments { name blobHash } } } } }";
+    let QueryOutcome { response, .. } = chain1.graphql_query(application_id, query).await;
+    let attachment = &response["receivedPosts"]["entries"][0]["value"]["attachments"][0];
+    assert_eq!(attachment["name"], "hello.txt");
+
+    let query = format!(
+        "query {{ attachment(blobHash: {}) }}",
+        attachment["blobHash"]
+    );
+    for chain in [&chain1, &chain2] {
+        let QueryOutcome { response, .. } =
+            chain.graphql_query(application_id, query.as_str()).await;
+        let encoded = response["attachment"]
+            .as_str()
+            .expect("Failed to read the attachment");
+        assert_eq!(STANDARD.decode(encoded).unwrap(), content);
+    }
+}
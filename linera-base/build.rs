@@ -6,6 +6,7 @@ fn main() {
         web: { all(target_arch = "wasm32", feature = "web") },
         chain: { all(target_arch = "wasm32", not(web)) },
         with_metrics: { all(not(target_arch = "wasm32"), feature = "metrics") },
+        with_opentelemetry: { all(not(target_arch = "wasm32"), feature = "opentelemetry") },
         with_testing: { any(test, feature = "test") },
 
         // the old version of `getrandom` we pin here is available on all targets, but
@@ -29,6 +29,13 @@ pub trait ContractAbi {
 
     /// The response type of an application call.
     type Response: Serialize + DeserializeOwned + Send + Sync + Debug + 'static;
+
+    /// The version of this ABI, to increase whenever the serialization of the types above
+    /// changes incompatibly.
+    ///
+    /// Applications calling or instantiating an application with this ABI check that the
+    /// version matches the one the application was compiled with.
+    const VERSION: u32 = 0;
 }
 // ANCHOR_END: contract_abi
 
@@ -40,6 +47,13 @@ pub trait ServiceAbi {
 
     /// The response type of the application's service.
     type QueryResponse: Serialize + DeserializeOwned + Send + Sync + Debug + 'static;
+
+    /// The version of this ABI, to increase whenever the serialization of the types above
+    /// changes incompatibly.
+    ///
+    /// Services querying an application with this ABI check that the version matches the
+    /// one the application's service was compiled with.
+    const VERSION: u32 = 0;
 }
 // ANCHOR_END: service_abi
 
@@ -55,6 +69,7 @@ where
 {
     type Operation = <<A as WithContractAbi>::Abi as ContractAbi>::Operation;
     type Response = <<A as WithContractAbi>::Abi as ContractAbi>::Response;
+    const VERSION: u32 = <<A as WithContractAbi>::Abi as ContractAbi>::VERSION;
 }
 
 /// Marker trait to help importing service types.
@@ -69,4 +84,5 @@ where
 {
     type Query = <<A as WithServiceAbi>::Abi as ServiceAbi>::Query;
     type QueryResponse = <<A as WithServiceAbi>::Abi as ServiceAbi>::QueryResponse;
+    const VERSION: u32 = <<A as WithServiceAbi>::Abi as ServiceAbi>::VERSION;
 }
@@ -0,0 +1,189 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A canonical JSON encoding of signed values, e.g. block proposals, so that signers written
+//! in other languages can reproduce the exact signing payload without a BCS implementation.
+//!
+//! The canonical JSON of a value is its human-readable serde representation, as used by the
+//! GraphQL and JSON APIs, written with the following rules:
+//! * There is no whitespace outside of strings.
+//! * The members of each object are sorted by the UTF-8 bytes of their keys.
+//! * Integers are written in decimal, without leading zeros, exponent or fractional part.
+//!   Floating-point numbers are not allowed.
+//! * In strings, `"` and `\` are escaped with a backslash, the control characters
+//!   `U+0008`, `U+0009`, `U+000A`, `U+000C` and `U+000D` as `\b`, `\t`, `\n`, `\f` and `\r`,
+//!   and the other control characters as `\u00xx` with lowercase hexadecimal digits. All
+//!   other characters are written as UTF-8, without escaping.
+//!
+//! In this representation, hashes, public keys, owners and chain IDs are lowercase
+//! hexadecimal strings, amounts are decimal strings such as `"1.5"`, byte vectors are arrays
+//! of integers, `None` is `null`, unit enum variants are strings and other enum variants are
+//! objects with the variant name as their only key.
+//!
+//! The signing payload of a value is the name of its type, followed by `::json::` and its
+//! canonical JSON, e.g. `ProposalContent::json::{"block":…}`. This prefix is distinct from the
+//! `TypeName::` prefix of the BCS signing payload, so the two payloads of a value never
+//! collide. Signatures are Ed25519 signatures of the payload bytes, and the hash of a value is
+//! the Keccak-256 hash of the payload bytes. A block proposal signed this way must declare the
+//! `CanonicalJson` signature scheme.
+
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::crypto::HasTypeName;
+
+/// An error encoding a value as canonical JSON.
+#[derive(Debug, Error)]
+pub enum CanonicalJsonError {
+    /// The value could not be serialized to JSON.
+    #[error("Failed to serialize the value to JSON: {0}")]
+    Serialization(#[from] serde_json::Error),
+    /// The value contains a floating-point number.
+    #[error("Floating-point numbers have no canonical JSON encoding")]
+    FloatingPoint,
+}
+
+/// Returns the canonical JSON encoding of `value`.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, CanonicalJsonError> {
+    let mut output = Vec::new();
+    write_value(&serde_json::to_value(value)?, &mut output)?;
+    Ok(output)
+}
+
+/// Returns the canonical JSON encoding of `value`, as a string.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, CanonicalJsonError> {
+    let bytes = to_vec(value)?;
+    Ok(String::from_utf8(bytes).expect("JSON should be valid UTF-8"))
+}
+
+/// Returns the bytes to sign or hash for `value`: its type name, followed by `::json::` and
+/// its canonical JSON encoding.
+pub fn signing_payload<T: Serialize + HasTypeName>(
+    value: &T,
+) -> Result<Vec<u8>, CanonicalJsonError> {
+    let mut payload = format!("{}::json::", T::type_name()).into_bytes();
+    payload.extend(to_vec(value)?);
+    Ok(payload)
+}
+
+fn write_value(value: &Value, output: &mut Vec<u8>) -> Result<(), CanonicalJsonError> {
+    match value {
+        Value::Null | Value::Bool(_) | Value::String(_) => {
+            serde_json::to_writer(&mut *output, value)?;
+        }
+        Value::Number(number) => {
+            if number.is_f64() {
+                return Err(CanonicalJsonError::FloatingPoint);
+            }
+            serde_json::to_writer(&mut *output, number)?;
+        }
+        Value::Array(items) => {
+            output.push(b'[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push(b',');
+                }
+                write_value(item, output)?;
+            }
+            output.push(b']');
+        }
+        Value::Object(members) => {
+            let mut members = members.iter().collect::<Vec<_>>();
+            members.sort_by(|(key1, _), (key2, _)| key1.as_bytes().cmp(key2.as_bytes()));
+            output.push(b'{');
+            for (index, (key, member)) in members.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(b',');
+                }
+                serde_json::to_writer(&mut *output, key)?;
+                output.push(b':');
+                write_value(member, output)?;
+            }
+            output.push(b'}');
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::{signing_payload, to_string, CanonicalJsonError};
+    use crate::{
+        crypto::{CryptoHash, KeyPair, Signature, TestString},
+        data_types::Amount,
+    };
+
+    #[derive(Serialize, Deserialize)]
+    enum Action {
+        Stop,
+        Send { amount: Amount, to: Option<u64> },
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Record {
+        zeta: Vec<u8>,
+        alpha: String,
+        actions: Vec<Action>,
+        labels: HashMap<String, i32>,
+    }
+
+    #[test]
+    fn test_canonical_encoding() {
+        let record = Record {
+            zeta: vec![0, 255],
+            alpha: "\"é\"\n\u{1}".to_owned(),
+            actions: vec![
+                Action::Stop,
+                Action::Send {
+                    amount: Amount::from_millis(1500),
+                    to: None,
+                },
+            ],
+            labels: [
+                ("b".to_owned(), -1),
+                ("B".to_owned(), 2),
+                ("a".to_owned(), 3),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        assert_eq!(
+            to_string(&record).unwrap(),
+            r#"{"actions":["Stop",{"Send":{"amount":"1.5","to":null}}],"alpha":"\"é\"\n\u0001","labels":{"B":2,"a":3,"b":-1},"zeta":[0,255]}"#
+        );
+    }
+
+    #[test]
+    fn test_floating_point_numbers_are_rejected() {
+        assert!(matches!(
+            to_string(&[1.5]),
+            Err(CanonicalJsonError::FloatingPoint)
+        ));
+    }
+
+    #[test]
+    fn test_signing_payload() {
+        let value = TestString::new("hello");
+        let payload = signing_payload(&value).unwrap();
+        assert_eq!(payload, b"TestString::json::\"hello\"");
+
+        let key_pair = KeyPair::generate();
+        let signature = Signature::new_canonical_json(&value, &key_pair).unwrap();
+        assert!(signature
+            .check_canonical_json(&value, key_pair.public())
+            .is_ok());
+        assert!(signature.check(&value, key_pair.public()).is_err());
+        assert!(signature
+            .check_canonical_json(&TestString::new("world"), key_pair.public())
+            .is_err());
+        assert_ne!(
+            CryptoHash::from_canonical_json(&value).unwrap(),
+            CryptoHash::new(&value)
+        );
+    }
+}
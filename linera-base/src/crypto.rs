@@ -24,7 +24,10 @@ use {
     std::ops::RangeInclusive,
 };
 
-use crate::doc_scalar;
+use crate::{
+    canonical_json::{self, CanonicalJsonError},
+    doc_scalar,
+};
 
 /// A signature key-pair.
 pub struct KeyPair(dalek::SigningKey);
@@ -441,6 +444,17 @@ impl CryptoHash {
         CryptoHash(hasher.0.finalize())
     }
 
+    /// Computes the hash of the [canonical JSON](crate::canonical_json) signing payload of
+    /// a value.
+    pub fn from_canonical_json<'de, T: BcsHashable<'de>>(
+        value: &T,
+    ) -> Result<Self, CanonicalJsonError> {
+        let payload = canonical_json::signing_payload(value)?;
+        let mut hasher = Keccak256::new();
+        hasher.update(&payload);
+        Ok(CryptoHash(hasher.finalize()))
+    }
+
     /// Reads the bytes of the hash value.
     pub fn as_bytes(&self) -> &B256 {
         &self.0
@@ -491,6 +505,42 @@ impl Signature {
             })
     }
 
+    /// Computes a signature of the [canonical JSON](crate::canonical_json) signing payload
+    /// of a value, as an external signer would.
+    pub fn new_canonical_json<'de, T>(
+        value: &T,
+        secret: &KeyPair,
+    ) -> Result<Self, CanonicalJsonError>
+    where
+        T: BcsSignable<'de>,
+    {
+        let message = canonical_json::signing_payload(value)?;
+        Ok(Signature(secret.0.sign(&message)))
+    }
+
+    /// Checks a signature of the [canonical JSON](crate::canonical_json) signing payload of
+    /// a value.
+    pub fn check_canonical_json<'de, T>(
+        &self,
+        value: &T,
+        author: PublicKey,
+    ) -> Result<(), CryptoError>
+    where
+        T: BcsSignable<'de> + fmt::Debug,
+    {
+        let invalid_signature = |error: String| CryptoError::InvalidSignature {
+            error,
+            type_name: T::type_name().to_string(),
+        };
+        let message = canonical_json::signing_payload(value)
+            .map_err(|error| invalid_signature(error.to_string()))?;
+        let public_key = dalek::VerifyingKey::from_bytes(&author.0)
+            .map_err(|error| invalid_signature(error.to_string()))?;
+        public_key
+            .verify(&message, &self.0)
+            .map_err(|error| invalid_signature(error.to_string()))
+    }
+
     /// Checks an optional signature.
     pub fn check_optional_signature<'de, T>(
         signature: Option<&Self>,
@@ -25,7 +25,7 @@ use custom_debug_derive::Debug;
 use linera_witty::{WitLoad, WitStore, WitType};
 #[cfg(with_metrics)]
 use prometheus::HistogramVec;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
 #[cfg(with_metrics)]
@@ -288,6 +288,85 @@ pub struct Resources {
     // TODO(#1533): Allow declaring calls to other applications instead of having to count them here.
 }
 
+/// Builds a [`Resources`] value by applying `$operation` to each pair of fields of `$left`
+/// and `$right`.
+macro_rules! zip_resources {
+    ($left:expr, $right:expr, |$a:ident, $b:ident| $operation:expr) => {
+        Resources {
+            fuel: {
+                let ($a, $b) = ($left.fuel, $right.fuel);
+                $operation
+            },
+            read_operations: {
+                let ($a, $b) = ($left.read_operations, $right.read_operations);
+                $operation
+            },
+            write_operations: {
+                let ($a, $b) = ($left.write_operations, $right.write_operations);
+                $operation
+            },
+            bytes_to_read: {
+                let ($a, $b) = ($left.bytes_to_read, $right.bytes_to_read);
+                $operation
+            },
+            bytes_to_write: {
+                let ($a, $b) = ($left.bytes_to_write, $right.bytes_to_write);
+                $operation
+            },
+            messages: {
+                let ($a, $b) = ($left.messages, $right.messages);
+                $operation
+            },
+            message_size: {
+                let ($a, $b) = ($left.message_size, $right.message_size);
+                $operation
+            },
+            storage_size_delta: {
+                let ($a, $b) = ($left.storage_size_delta, $right.storage_size_delta);
+                $operation
+            },
+        }
+    };
+}
+
+impl Resources {
+    /// Checked addition of each kind of resource.
+    pub fn try_add(self, other: Self) -> Result<Self, ArithmeticError> {
+        Ok(zip_resources!(self, other, |a, b| a
+            .checked_add(b)
+            .ok_or(ArithmeticError::Overflow)?))
+    }
+
+    /// Saturating addition of each kind of resource.
+    pub fn saturating_add(self, other: Self) -> Self {
+        zip_resources!(self, other, |a, b| a.saturating_add(b))
+    }
+
+    /// Checked subtraction of each kind of resource.
+    pub fn try_sub(self, other: Self) -> Result<Self, ArithmeticError> {
+        Ok(zip_resources!(self, other, |a, b| a
+            .checked_sub(b)
+            .ok_or(ArithmeticError::Underflow)?))
+    }
+
+    /// Saturating subtraction of each kind of resource.
+    pub fn saturating_sub(self, other: Self) -> Self {
+        zip_resources!(self, other, |a, b| a.saturating_sub(b))
+    }
+
+    /// Checked in-place addition of each kind of resource.
+    pub fn try_add_assign(&mut self, other: Self) -> Result<(), ArithmeticError> {
+        *self = self.try_add(other)?;
+        Ok(())
+    }
+
+    /// Checked in-place subtraction of each kind of resource.
+    pub fn try_sub_assign(&mut self, other: Self) -> Result<(), ArithmeticError> {
+        *self = self.try_sub(other)?;
+        Ok(())
+    }
+}
+
 /// A request to send a message.
 #[derive(Clone, Debug, Deserialize, Serialize, WitLoad, WitType)]
 #[cfg_attr(with_testing, derive(Eq, PartialEq, WitStore))]
@@ -301,6 +380,8 @@ pub struct SendMessageRequest<Message> {
     pub is_tracked: bool,
     /// The grant resources forwarded with the message.
     pub grant: Resources,
+    /// The time after which the receiver can no longer accept the message, if any.
+    pub expires_at: Option<Timestamp>,
     /// The message itself.
     pub message: Message,
 }
@@ -318,6 +399,7 @@ where
             authenticated: self.authenticated,
             is_tracked: self.is_tracked,
             grant: self.grant,
+            expires_at: self.expires_at,
             message,
         }
     }
@@ -331,6 +413,8 @@ pub enum ArithmeticError {
     Overflow,
     #[error("Number underflow")]
     Underflow,
+    #[error("Division by zero")]
+    DivisionByZero,
 }
 
 macro_rules! impl_wrapped_number {
@@ -413,6 +497,24 @@ macro_rules! impl_wrapped_number {
                 Ok(())
             }
 
+            /// Saturating in-place subtraction.
+            pub fn saturating_sub_assign(&mut self, other: Self) {
+                self.0 = self.0.saturating_sub(other.0);
+            }
+
+            /// Overflowing addition: returns the wrapped sum, and whether it overflowed.
+            pub fn overflowing_add(self, other: Self) -> (Self, bool) {
+                let (val, overflow) = self.0.overflowing_add(other.0);
+                (Self(val), overflow)
+            }
+
+            /// Overflowing subtraction: returns the wrapped difference, and whether it
+            /// underflowed.
+            pub fn overflowing_sub(self, other: Self) -> (Self, bool) {
+                let (val, underflow) = self.0.overflowing_sub(other.0);
+                (Self(val), underflow)
+            }
+
             /// Saturating multiplication.
             pub fn saturating_mul(&self, other: $wrapped) -> Self {
                 Self(self.0.saturating_mul(other))
@@ -429,6 +531,27 @@ macro_rules! impl_wrapped_number {
                 self.0 = self.0.checked_mul(other).ok_or(ArithmeticError::Overflow)?;
                 Ok(())
             }
+
+            /// Saturating in-place multiplication.
+            pub fn saturating_mul_assign(&mut self, other: $wrapped) {
+                self.0 = self.0.saturating_mul(other);
+            }
+
+            /// Overflowing multiplication: returns the wrapped product, and whether it
+            /// overflowed.
+            pub fn overflowing_mul(self, other: $wrapped) -> (Self, bool) {
+                let (val, overflow) = self.0.overflowing_mul(other);
+                (Self(val), overflow)
+            }
+
+            /// Checked division, rounding down.
+            pub fn try_div(self, other: $wrapped) -> Result<Self, ArithmeticError> {
+                let val = self
+                    .0
+                    .checked_div(other)
+                    .ok_or(ArithmeticError::DivisionByZero)?;
+                Ok(Self(val))
+            }
         }
 
         impl From<$name> for $wrapped {
@@ -784,6 +907,19 @@ pub enum OracleResponse {
     Assert,
     /// The block's validation round.
     Round(Option<u32>),
+    /// The response from an HTTP GET request.
+    Get(
+        #[debug(with = "hex_debug")]
+        #[serde(with = "serde_bytes")]
+        Vec<u8>,
+    ),
+    /// The response from a service query, and the fuel the service consumed to compute it.
+    ServiceWithFuel {
+        #[debug(with = "hex_debug")]
+        #[serde(with = "serde_bytes")]
+        response: Vec<u8>,
+        fuel: u64,
+    },
 }
 
 impl Display for OracleResponse {
@@ -797,6 +933,12 @@ impl Display for OracleResponse {
             OracleResponse::Assert => write!(f, "Assert")?,
             OracleResponse::Round(Some(round)) => write!(f, "Round:{round}")?,
             OracleResponse::Round(None) => write!(f, "Round:None")?,
+            OracleResponse::Get(bytes) => write!(f, "Get:{}", STANDARD_NO_PAD.encode(bytes))?,
+            OracleResponse::ServiceWithFuel { response, fuel } => write!(
+                f,
+                "ServiceWithFuel:{fuel}:{}",
+                STANDARD_NO_PAD.encode(response)
+            )?,
         };
 
         Ok(())
@@ -817,6 +959,18 @@ impl FromStr for OracleResponse {
                 STANDARD_NO_PAD.decode(string).context("Invalid base64")?,
             ));
         }
+        if let Some(string) = s.strip_prefix("Get:") {
+            return Ok(OracleResponse::Get(
+                STANDARD_NO_PAD.decode(string).context("Invalid base64")?,
+            ));
+        }
+        if let Some(string) = s.strip_prefix("ServiceWithFuel:") {
+            let (fuel, response) = string.split_once(':').context("Missing fuel")?;
+            return Ok(OracleResponse::ServiceWithFuel {
+                response: STANDARD_NO_PAD.decode(response).context("Invalid base64")?,
+                fuel: fuel.parse().context("Invalid fuel")?,
+            });
+        }
         if let Some(string) = s.strip_prefix("Blob:") {
             return Ok(OracleResponse::Blob(
                 BlobId::from_str(string).context("Invalid BlobId")?,
@@ -891,12 +1045,72 @@ impl AsRef<[u8]> for Bytecode {
     }
 }
 
+/// An error that an application's contract returns to the application that called it.
+///
+/// Unlike a panic, which aborts the whole transaction, the error is a response: the caller can
+/// match on its `kind` and decide how to proceed. The changes the called application made
+/// before returning the error are kept.
+///
+/// ```rust
+/// # use linera_base::data_types::ApplicationError;
+/// let error =
+///     ApplicationError::new("insufficient_balance", "Not enough tokens").with_data(&100_u64);
+///
+/// assert!(error.is("insufficient_balance"));
+/// assert_eq!(error.data::<u64>(), Some(100));
+/// ```
+#[derive(
+    Clone, Debug, Deserialize, Eq, PartialEq, Serialize, Error, WitLoad, WitStore, WitType,
+)]
+#[error("{kind}: {message}")]
+pub struct ApplicationError {
+    /// A short identifier of the kind of error, for callers to match on.
+    pub kind: String,
+    /// A human-readable description of the error.
+    pub message: String,
+    /// Additional BCS-serialized information about the error, if any.
+    #[serde(with = "serde_bytes")]
+    #[debug(with = "hex_debug")]
+    pub data: Vec<u8>,
+}
+
+impl ApplicationError {
+    /// Creates a new [`ApplicationError`] of the given `kind`.
+    pub fn new(kind: impl Into<String>, message: impl Into<String>) -> Self {
+        ApplicationError {
+            kind: kind.into(),
+            message: message.into(),
+            data: Vec::new(),
+        }
+    }
+
+    /// Attaches some additional information to this error.
+    pub fn with_data(mut self, data: &impl Serialize) -> Self {
+        self.data = bcs::to_bytes(data).expect("Failed to serialize error data");
+        self
+    }
+
+    /// Returns whether this error is of the given `kind`.
+    pub fn is(&self, kind: &str) -> bool {
+        self.kind == kind
+    }
+
+    /// Returns the additional information attached to this error, or `None` if there is none
+    /// or if it has another type.
+    pub fn data<T: DeserializeOwned>(&self) -> Option<T> {
+        bcs::from_bytes(&self.data).ok()
+    }
+}
+
 /// A type for errors happening during decompression.
 #[derive(Error, Debug)]
 pub enum DecompressionError {
     /// Compressed bytecode is invalid, and could not be decompressed.
     #[error("Bytecode could not be decompressed: {0}")]
     InvalidCompressedBytecode(#[from] io::Error),
+    /// The chunks of a bytecode do not reassemble into the bytecode they were split from.
+    #[error("Bytecode chunks do not match the hash of the bytecode")]
+    InvalidBytecodeChunks,
 }
 
 /// A compressed WebAssembly module's bytecode.
@@ -982,6 +1196,85 @@ impl CompressedBytecode {
     }
 }
 
+/// The magic number at the beginning of a bytecode blob that lists the chunks of a compressed
+/// bytecode instead of containing it. Compressed bytecode always starts with the magic number
+/// of a zstd frame, so the two cannot be confused.
+const BYTECODE_CHUNKS_MAGIC: [u8; 4] = *b"LBCH";
+
+/// The chunks of a compressed bytecode that is too large to be published in a single blob.
+///
+/// The chunks are published as data blobs, and the bytecode blob only lists them.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BytecodeChunks {
+    /// The hashes of the data blobs with the chunks, in order.
+    pub chunk_hashes: Vec<CryptoHash>,
+    /// The hash of the bytecode blob that would contain the whole compressed bytecode.
+    pub bytecode_hash: CryptoHash,
+}
+
+impl BytecodeChunks {
+    /// Returns the content of the bytecode blob of the given type for the compressed
+    /// bytecode, and the data blobs with its chunks. If the compressed bytecode fits in
+    /// `maximum_blob_size` bytes, the blob contains it and there are no chunks.
+    pub fn split(
+        blob_type: BlobType,
+        compressed_bytecode: CompressedBytecode,
+        maximum_blob_size: u64,
+    ) -> (BlobContent, Vec<Blob>) {
+        let content = BlobContent::new(blob_type, compressed_bytecode.compressed_bytes);
+        let chunk_size = usize::try_from(maximum_blob_size).unwrap_or(usize::MAX);
+        if content.bytes().len() <= chunk_size {
+            return (content, Vec::new());
+        }
+        let chunks = content
+            .bytes()
+            .chunks(chunk_size.max(1))
+            .map(Blob::new_data)
+            .collect::<Vec<_>>();
+        let bytecode_chunks = BytecodeChunks {
+            chunk_hashes: chunks.iter().map(|chunk| chunk.id().hash).collect(),
+            bytecode_hash: CryptoHash::new(&content),
+        };
+        let mut bytes = BYTECODE_CHUNKS_MAGIC.to_vec();
+        bytes.extend(bcs::to_bytes(&bytecode_chunks).expect("Serializing hashes should not fail"));
+        (BlobContent::new(blob_type, bytes), chunks)
+    }
+
+    /// Returns the chunks listed in the bytes of a bytecode blob, or `None` if the blob
+    /// contains the compressed bytecode itself.
+    pub fn from_blob_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.strip_prefix(&BYTECODE_CHUNKS_MAGIC)?;
+        bcs::from_bytes(bytes).ok()
+    }
+
+    /// Returns the IDs of the data blobs with the chunks, in order.
+    pub fn chunk_blob_ids(&self) -> impl Iterator<Item = BlobId> + '_ {
+        self.chunk_hashes
+            .iter()
+            .map(|hash| BlobId::new(*hash, BlobType::Data))
+    }
+
+    /// Reassembles the compressed bytecode of a bytecode blob of the given type from the
+    /// contents of its chunks, and checks that it matches the hash of the bytecode.
+    pub fn reassemble(
+        &self,
+        blob_type: BlobType,
+        chunks: impl IntoIterator<Item = BlobContent>,
+    ) -> Result<CompressedBytecode, DecompressionError> {
+        let compressed_bytes = chunks
+            .into_iter()
+            .flat_map(|chunk| chunk.into_bytes().into_vec())
+            .collect::<Vec<_>>();
+        let content = BlobContent::new(blob_type, compressed_bytes);
+        if CryptoHash::new(&content) != self.bytecode_hash {
+            return Err(DecompressionError::InvalidBytecodeChunks);
+        }
+        Ok(CompressedBytecode {
+            compressed_bytes: content.into_bytes().into_vec(),
+        })
+    }
+}
+
 impl<'a> BcsHashable<'a> for BlobContent {}
 
 /// A blob of binary data.
@@ -1205,7 +1498,10 @@ static BYTECODE_DECOMPRESSION_LATENCY: LazyLock<HistogramVec> = LazyLock::new(||
 mod tests {
     use std::str::FromStr;
 
-    use super::Amount;
+    use super::{
+        Amount, ArithmeticError, BlobContent, BlobType, BytecodeChunks, CompressedBytecode,
+        Resources,
+    };
 
     #[test]
     fn display_amount() {
@@ -1232,4 +1528,111 @@ mod tests {
             format!("{:~^+9.1}", Amount::from_str("12.34").unwrap())
         );
     }
+
+    #[test]
+    fn amount_arithmetic() {
+        let one = Amount::ONE;
+        assert_eq!(
+            Amount::MAX.overflowing_add(one),
+            (one.try_sub_one().unwrap(), true)
+        );
+        assert!(Amount::ZERO.overflowing_sub(one).1);
+        assert_eq!(one.overflowing_mul(3), (Amount::from_tokens(3), false));
+        assert!(Amount::MAX.overflowing_mul(2).1);
+        assert_eq!(
+            Amount::from_tokens(7).try_div(2).unwrap(),
+            Amount::from_millis(3500)
+        );
+        assert!(matches!(
+            one.try_div(0),
+            Err(ArithmeticError::DivisionByZero)
+        ));
+
+        let mut amount = one;
+        amount.saturating_sub_assign(Amount::from_tokens(2));
+        assert_eq!(amount, Amount::ZERO);
+        amount = one;
+        amount.saturating_mul_assign(u128::MAX);
+        assert_eq!(amount, Amount::MAX);
+    }
+
+    #[test]
+    fn resources_arithmetic() {
+        let resources = Resources {
+            fuel: 10,
+            messages: 2,
+            ..Resources::default()
+        };
+        let maximum = Resources {
+            fuel: u64::MAX,
+            ..Resources::default()
+        };
+        assert_eq!(
+            resources.try_add(resources).unwrap(),
+            Resources {
+                fuel: 20,
+                messages: 4,
+                ..Resources::default()
+            }
+        );
+        assert!(matches!(
+            resources.try_add(maximum),
+            Err(ArithmeticError::Overflow)
+        ));
+        assert_eq!(resources.saturating_add(maximum).fuel, u64::MAX);
+        assert!(matches!(
+            resources.try_sub(maximum),
+            Err(ArithmeticError::Underflow)
+        ));
+        assert_eq!(
+            resources.saturating_sub(maximum),
+            Resources {
+                messages: 2,
+                ..Resources::default()
+            }
+        );
+        let mut total = resources;
+        total.try_sub_assign(resources).unwrap();
+        assert_eq!(total, Resources::default());
+    }
+
+    #[test]
+    fn split_and_reassemble_bytecode() {
+        let compressed_bytecode = CompressedBytecode {
+            compressed_bytes: (0..=255).collect(),
+        };
+        let whole = BlobContent::new(
+            BlobType::ContractBytecode,
+            compressed_bytecode.compressed_bytes.clone(),
+        );
+
+        let (content, chunks) = BytecodeChunks::split(
+            BlobType::ContractBytecode,
+            compressed_bytecode.clone(),
+            1000,
+        );
+        assert_eq!(content, whole);
+        assert!(chunks.is_empty());
+        assert_eq!(BytecodeChunks::from_blob_bytes(content.bytes()), None);
+
+        let (content, chunks) =
+            BytecodeChunks::split(BlobType::ContractBytecode, compressed_bytecode.clone(), 100);
+        assert_eq!(content.blob_type(), BlobType::ContractBytecode);
+        assert_eq!(chunks.len(), 3);
+        let bytecode_chunks = BytecodeChunks::from_blob_bytes(content.bytes()).unwrap();
+        assert_eq!(
+            bytecode_chunks.chunk_blob_ids().collect::<Vec<_>>(),
+            chunks.iter().map(|chunk| chunk.id()).collect::<Vec<_>>()
+        );
+        let contents = chunks.iter().map(|chunk| chunk.content().clone());
+        assert_eq!(
+            bytecode_chunks
+                .reassemble(BlobType::ContractBytecode, contents.clone())
+                .unwrap(),
+            compressed_bytecode
+        );
+        assert!(bytecode_chunks
+            .reassemble(BlobType::ContractBytecode, contents.rev())
+            .is_err());
+    }
 }
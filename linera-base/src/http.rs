@@ -0,0 +1,65 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Types for the plain HTTP handlers of application services.
+//!
+//! Besides GraphQL, the node service forwards the requests to
+//! `/chains/<CHAIN_ID>/applications/<APPLICATION_ID>/<PATH>` to the service of the
+//! application as an [`HttpRequest`], and replies with the [`HttpResponse`] it returns.
+
+use serde::{Deserialize, Serialize};
+
+/// The method of an [`HttpRequest`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum HttpMethod {
+    /// A request that only reads the state of the application.
+    Get,
+    /// A request that may schedule operations, which are then executed in a new block.
+    Post,
+}
+
+/// A plain HTTP request to an application service.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HttpRequest {
+    /// The method of the request.
+    pub method: HttpMethod,
+    /// The path of the request, relative to the URL of the application.
+    pub path: String,
+    /// The JSON body of the request, or `null` if it had none.
+    #[serde(default)]
+    pub body: serde_json::Value,
+}
+
+/// The response of an application service to an [`HttpRequest`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HttpResponse {
+    /// The HTTP status code of the response.
+    pub status: u16,
+    /// The JSON body of the response.
+    pub body: serde_json::Value,
+}
+
+impl HttpResponse {
+    /// Creates a response with the given `status` code and JSON `body`.
+    pub fn new(status: u16, body: impl Into<serde_json::Value>) -> Self {
+        HttpResponse {
+            status,
+            body: body.into(),
+        }
+    }
+
+    /// Creates a successful response with the given JSON `body`.
+    pub fn ok(body: impl Into<serde_json::Value>) -> Self {
+        HttpResponse::new(200, body)
+    }
+
+    /// Creates a response reporting that no handler exists for the requested path.
+    pub fn not_found(path: &str) -> Self {
+        HttpResponse::new(404, format!("no handler for {path:?}"))
+    }
+
+    /// Creates a response reporting that the request was invalid.
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        HttpResponse::new(400, message.into())
+    }
+}
@@ -480,6 +480,18 @@ impl ChannelName {
     }
 }
 
+impl From<Vec<u8>> for StreamName {
+    fn from(name: Vec<u8>) -> Self {
+        StreamName(name)
+    }
+}
+
+impl From<&str> for StreamName {
+    fn from(name: &str) -> Self {
+        StreamName(name.as_bytes().to_vec())
+    }
+}
+
 impl StreamName {
     /// Turns the stream name into bytes.
     pub fn into_bytes(self) -> Vec<u8> {
@@ -14,6 +14,7 @@ use std::fmt;
 pub use async_trait::async_trait;
 
 pub mod abi;
+pub mod canonical_json;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod command;
 pub mod crypto;
@@ -21,6 +22,7 @@ pub mod data_types;
 pub mod dyn_convert;
 mod graphql;
 pub mod hashed;
+pub mod http;
 pub mod identifiers;
 mod limited_writer;
 pub mod ownership;
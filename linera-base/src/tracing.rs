@@ -3,7 +3,10 @@
 
 //! This module provides unified handling for tracing subscribers within Linera binaries.
 
+#[cfg(with_opentelemetry)]
+use std::sync::OnceLock;
 use std::{
+    collections::HashMap,
     env,
     fs::{File, OpenOptions},
     path::Path,
@@ -11,7 +14,9 @@ use std::{
 };
 
 use is_terminal::IsTerminal as _;
-use tracing::Subscriber;
+use tracing::{Span, Subscriber};
+#[cfg(with_opentelemetry)]
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
 use tracing_subscriber::{
     fmt::{
         self,
@@ -33,6 +38,10 @@ use tracing_subscriber::{
 /// The `LINERA_LOG_DIR` environment variable can be used to configure a directory to
 /// store log files. If it is set, a file named `log_name` with the `log` extension is
 /// created in the directory.
+///
+/// With the `opentelemetry` feature, spans are also exported with OTLP to the collector at
+/// `OTEL_EXPORTER_OTLP_ENDPOINT`, if that environment variable is set. The `log_name` is then
+/// used as the service name.
 pub fn init(log_name: &str) {
     let env_filter = tracing_subscriber::EnvFilter::builder()
         .with_default_directive(tracing_subscriber::filter::LevelFilter::INFO.into())
@@ -66,11 +75,93 @@ pub fn init(log_name: &str) {
         )
     });
 
-    tracing_subscriber::registry()
+    let registry = tracing_subscriber::registry()
         .with(env_filter)
         .with(maybe_log_file_layer)
-        .with(stderr_layer)
-        .init();
+        .with(stderr_layer);
+    #[cfg(with_opentelemetry)]
+    let registry = registry.with(prepare_opentelemetry_layer(log_name));
+    registry.init();
+}
+
+/// Returns the trace context of the current span, to be sent along with a request to another
+/// process so that its spans are part of the same trace.
+#[cfg(with_opentelemetry)]
+pub fn current_trace_context() -> HashMap<String, String> {
+    let context = Span::current().context();
+    let mut trace_context = HashMap::new();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut trace_context)
+    });
+    trace_context
+}
+
+/// Returns the trace context of the current span, which is always empty without the
+/// `opentelemetry` feature.
+#[cfg(not(with_opentelemetry))]
+pub fn current_trace_context() -> HashMap<String, String> {
+    HashMap::new()
+}
+
+/// Makes `span` a child of the remote span described by a `trace_context` received with a
+/// request.
+#[cfg(with_opentelemetry)]
+pub fn set_remote_parent(span: &Span, trace_context: &HashMap<String, String>) {
+    let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(trace_context)
+    });
+    span.set_parent(parent);
+}
+
+/// Makes `span` a child of the remote span described by a `trace_context`, which does nothing
+/// without the `opentelemetry` feature.
+#[cfg(not(with_opentelemetry))]
+pub fn set_remote_parent(_span: &Span, _trace_context: &HashMap<String, String>) {}
+
+/// The runtime of the OpenTelemetry exporter, which is independent from the runtimes of the
+/// binaries since tracing is initialized before they are created.
+#[cfg(with_opentelemetry)]
+static OPENTELEMETRY_RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+/// Prepares a layer exporting spans with OTLP, if the `OTEL_EXPORTER_OTLP_ENDPOINT`
+/// environment variable is set. Trace contexts are propagated in the W3C format.
+#[cfg(with_opentelemetry)]
+fn prepare_opentelemetry_layer<S>(service_name: &str) -> Option<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    use opentelemetry::{trace::TracerProvider as _, KeyValue};
+    use opentelemetry_sdk::{
+        propagation::TraceContextPropagator, runtime, trace::TracerProvider, Resource,
+    };
+
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT")?;
+
+    let runtime = OPENTELEMETRY_RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("opentelemetry")
+            .enable_all()
+            .build()
+            .expect("Failed to create the OpenTelemetry runtime")
+    });
+    let _guard = runtime.enter();
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .build()
+        .expect("Failed to create the OTLP span exporter");
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            service_name.to_owned(),
+        )]))
+        .build();
+    let tracer = provider.tracer("linera");
+    opentelemetry::global::set_tracer_provider(provider);
+
+    Some(tracing_opentelemetry::layer().with_tracer(tracer).boxed())
 }
 
 /// Opens a log file for writing.
@@ -131,3 +222,53 @@ fn fmt_span_from_str(events: &str) -> FmtSpan {
     }
     fmt_span
 }
+
+#[cfg(all(test, with_opentelemetry))]
+mod tests {
+    use opentelemetry::trace::{TraceContextExt as _, TraceId, TracerProvider as _};
+    use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::TracerProvider};
+    use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+    use tracing_subscriber::layer::SubscriberExt as _;
+
+    use super::*;
+
+    /// Runs `f` with a subscriber recording spans with OpenTelemetry, without exporting them.
+    fn with_opentelemetry(f: impl FnOnce()) {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let tracer = TracerProvider::builder().build().tracer("test");
+        let subscriber =
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+        tracing::subscriber::with_default(subscriber, f);
+    }
+
+    fn trace_id(span: &Span) -> TraceId {
+        span.context().span().span_context().trace_id()
+    }
+
+    #[test]
+    fn test_remote_span_continues_trace() {
+        with_opentelemetry(|| {
+            let client_span = tracing::info_span!("client");
+            let trace_context = client_span.in_scope(current_trace_context);
+            assert!(trace_context.contains_key("traceparent"));
+
+            let server_span = tracing::info_span!("server");
+            set_remote_parent(&server_span, &trace_context);
+            assert_eq!(trace_id(&server_span), trace_id(&client_span));
+
+            let other_span = tracing::info_span!("other");
+            assert_ne!(trace_id(&other_span), trace_id(&client_span));
+        });
+    }
+
+    #[test]
+    fn test_no_trace_context_outside_spans() {
+        with_opentelemetry(|| {
+            assert!(current_trace_context().is_empty());
+
+            let server_span = tracing::info_span!("server");
+            set_remote_parent(&server_span, &HashMap::new());
+            assert_ne!(trace_id(&server_span), TraceId::INVALID);
+        });
+    }
+}
@@ -3,6 +3,8 @@
 
 //! This module provides unified handling for tracing subscribers within Linera binaries.
 
+use std::collections::HashMap;
+
 use tracing_subscriber::{
     prelude::__tracing_subscriber_SubscriberExt as _, util::SubscriberInitExt as _,
 };
@@ -24,3 +26,10 @@ pub fn init() {
         )
         .init();
 }
+
+/// Returns the trace context of the current span, to be sent along with a request.
+///
+/// Spans are not exported from the browser, so the context is always empty.
+pub fn current_trace_context() -> HashMap<String, String> {
+    HashMap::new()
+}
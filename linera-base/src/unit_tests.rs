@@ -76,6 +76,7 @@ fn send_message_request_test_case() -> SendMessageRequest<Vec<u8>> {
             write_operations: 0,
             storage_size_delta: 0,
         },
+        expires_at: Some(Timestamp::from(1_000_000)),
         message: (0..=255).cycle().take(2_000).collect(),
     }
 }
@@ -7,7 +7,7 @@ use std::{collections::BTreeSet, fmt::Debug};
 use async_graphql::SimpleObject;
 use linera_base::{
     crypto::{BcsHashable, CryptoHash},
-    data_types::{BlockHeight, OracleResponse, Timestamp},
+    data_types::{Amount, BlockHeight, OracleResponse, Timestamp},
     hashed::Hashed,
     identifiers::{BlobId, BlobType, ChainId, MessageId, Owner},
 };
@@ -164,6 +164,7 @@ impl ConfirmedBlock {
             timestamp,
             authenticated_signer,
             previous_block_hash,
+            priority_fee,
         } = block;
         *chain_id == self.chain_id()
             && *epoch == self.epoch()
@@ -173,6 +174,7 @@ impl ConfirmedBlock {
             && *timestamp == self.block().header.timestamp
             && *authenticated_signer == self.block().header.authenticated_signer
             && *previous_block_hash == self.block().header.previous_block_hash
+            && *priority_fee == self.block().header.priority_fee
     }
 }
 
@@ -253,6 +255,7 @@ impl Serialize for Block {
             state_hash: self.header.state_hash,
             previous_block_hash: self.header.previous_block_hash,
             authenticated_signer: self.header.authenticated_signer,
+            priority_fee: self.header.priority_fee,
         };
         state.serialize_field("header", &header)?;
         state.serialize_field("body", &self.body)?;
@@ -284,6 +287,7 @@ impl<'de> Deserialize<'de> for Block {
             state_hash: inner.header.state_hash,
             previous_block_hash: inner.header.previous_block_hash,
             authenticated_signer: inner.header.authenticated_signer,
+            priority_fee: inner.header.priority_fee,
             bundles_hash,
             operations_hash,
             messages_hash,
@@ -320,6 +324,9 @@ pub struct BlockHeader {
     /// the default account of the chain is used. This value is also used as recipient of
     /// potential refunds for the message grants created by the operations.
     pub authenticated_signer: Option<Owner>,
+    /// The fee paid for each operation on top of the chain's base fee, credited to the admin
+    /// chain.
+    pub priority_fee: Amount,
 
     // Inputs to the block, chosen by the block proposer.
     /// Cryptographic hash of all the incoming bundles in the block.
@@ -368,6 +375,7 @@ impl Block {
             state_hash: outcome.state_hash,
             previous_block_hash: block.previous_block_hash,
             authenticated_signer: block.authenticated_signer,
+            priority_fee: block.priority_fee,
             bundles_hash,
             operations_hash,
             messages_hash,
@@ -540,6 +548,7 @@ impl From<Block> for ExecutedBlock {
                     state_hash,
                     previous_block_hash,
                     authenticated_signer,
+                    priority_fee,
                     bundles_hash: _,
                     operations_hash: _,
                     messages_hash: _,
@@ -565,6 +574,7 @@ impl From<Block> for ExecutedBlock {
             operations,
             authenticated_signer,
             previous_block_hash,
+            priority_fee,
         };
 
         let outcome = BlockExecutionOutcome {
@@ -590,6 +600,7 @@ struct SerializedHeader {
     state_hash: CryptoHash,
     previous_block_hash: Option<CryptoHash>,
     authenticated_signer: Option<Owner>,
+    priority_fee: Amount,
 }
 
 mod hashing {
@@ -8,15 +8,23 @@ use linera_base::{
     hashed::Hashed,
     identifiers::{BlobId, ChainId, MessageId},
 };
-use linera_execution::committee::{Epoch, ValidatorName};
+use linera_execution::{
+    committee::{Committee, Epoch, ValidatorName},
+    Message, SystemMessage,
+};
 use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize};
 
 use super::{generic::GenericCertificate, Certificate};
 use crate::{
     block::{Block, ConfirmedBlock, ConversionError},
     data_types::{Medium, MessageBundle},
+    ChainError,
 };
 
+#[cfg(test)]
+#[path = "../unit_tests/confirmed_tests.rs"]
+mod confirmed_tests;
+
 impl GenericCertificate<ConfirmedBlock> {
     /// Returns reference to the `ExecutedBlock` contained in this certificate.
     pub fn block(&self) -> &Block {
@@ -46,12 +54,71 @@ impl GenericCertificate<ConfirmedBlock> {
         self.block().requires_blob(blob_id)
     }
 
+    /// Returns the epoch and committee created by this block, if any.
+    pub fn created_committee(&self) -> Option<(Epoch, &Committee)> {
+        self.block().messages().iter().flatten().find_map(
+            |outgoing_message| match &outgoing_message.message {
+                Message::System(SystemMessage::CreateCommittee { epoch, committee }) => {
+                    Some((*epoch, committee))
+                }
+                _ => None,
+            },
+        )
+    }
+
+    /// Returns the epochs of the committees removed by this block, if it is an admin chain
+    /// block.
+    pub fn removed_committees(&self) -> impl Iterator<Item = Epoch> + '_ {
+        self.block()
+            .messages()
+            .iter()
+            .flatten()
+            .filter_map(|outgoing_message| match &outgoing_message.message {
+                Message::System(SystemMessage::RemoveCommittee { epoch }) => Some(*epoch),
+                _ => None,
+            })
+    }
+
     #[cfg(with_testing)]
     pub fn outgoing_message_count(&self) -> usize {
         self.block().messages().iter().map(Vec::len).sum()
     }
 }
 
+/// Follows a chain of committee changes, starting from the genesis committee.
+///
+/// Each certificate must be an admin chain block signed by the current committee that creates
+/// the committee of the next epoch. Returns the last epoch and its committee, which can then
+/// be used to check certificates from that epoch.
+pub fn verify_committee_changes(
+    admin_id: ChainId,
+    genesis_committee: Committee,
+    certificates: &[GenericCertificate<ConfirmedBlock>],
+) -> Result<(Epoch, Committee), ChainError> {
+    let mut epoch = Epoch::ZERO;
+    let mut committee = genesis_committee;
+    for certificate in certificates {
+        let invalid = || ChainError::InvalidCommitteeChange {
+            hash: certificate.hash(),
+            epoch,
+        };
+        let header = &certificate.block().header;
+        if header.chain_id != admin_id || header.epoch != epoch {
+            return Err(invalid());
+        }
+        certificate.check(&committee)?;
+        let next_epoch = epoch.try_add_one()?;
+        match certificate.created_committee() {
+            Some((created_epoch, created_committee)) if created_epoch == next_epoch => {
+                committee = created_committee.clone();
+                epoch = next_epoch;
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok((epoch, committee))
+}
+
 impl TryFrom<Certificate> for GenericCertificate<ConfirmedBlock> {
     type Error = ConversionError;
 
@@ -10,6 +10,7 @@ mod validated;
 
 use std::collections::BTreeSet;
 
+pub use confirmed::verify_committee_changes;
 pub use generic::GenericCertificate;
 use linera_base::{
     crypto::Signature,
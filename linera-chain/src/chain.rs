@@ -24,15 +24,16 @@ use linera_base::{
 };
 use linera_execution::{
     committee::{Committee, Epoch, ValidatorName},
-    system::OpenChainConfig,
+    system::{OpenChainConfig, SystemMessage, SystemOperation},
     ExecutionOutcome, ExecutionRuntimeContext, ExecutionStateView, Message, MessageContext,
-    Operation, OperationContext, Query, QueryContext, QueryOutcome, RawExecutionOutcome,
-    RawOutgoingMessage, ResourceController, ResourceTracker, ServiceRuntimeEndpoint,
-    TransactionTracker,
+    MessageKind, Operation, OperationContext, Query, QueryContext, QueryOutcome,
+    RawExecutionOutcome, RawOutgoingMessage, ResourceController, ResourceTracker,
+    ServiceRuntimeEndpoint, TransactionTracker,
 };
 use linera_views::{
     context::Context,
     log_view::LogView,
+    map_view::MapView,
     queue_view::QueueView,
     reentrant_collection_view::ReentrantCollectionView,
     register_view::RegisterView,
@@ -153,7 +154,7 @@ static STATE_HASH_COMPUTATION_LATENCY: LazyLock<HistogramVec> = LazyLock::new(||
 });
 
 /// The BCS-serialized size of an empty [`Block`].
-const EMPTY_BLOCK_SIZE: usize = 91;
+const EMPTY_BLOCK_SIZE: usize = 107;
 
 /// An origin, cursor and timestamp of a unskippable bundle in our inbox.
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
@@ -228,6 +229,12 @@ where
     pub outbox_counters: RegisterView<C, BTreeMap<BlockHeight, u32>>,
     /// Channels able to multicast messages to subscribers.
     pub channels: ReentrantCollectionView<C, ChannelFullName, ChannelStateView<C>>,
+    /// Hashes of the certified blocks that created each committee. Only the admin chain
+    /// creates committees.
+    #[graphql(skip)]
+    pub committee_certificates: MapView<C, Epoch, CryptoHash>,
+    /// The base fee charged for the next block, following the fullness of the previous blocks.
+    pub base_fee: RegisterView<C, Amount>,
 }
 
 /// Block-chaining state.
@@ -590,6 +597,13 @@ where
         Ok(true)
     }
 
+    /// Returns the base fee for the next block, which is never lower than the block price of
+    /// the current committee's policy.
+    pub fn next_block_base_fee(&self) -> Result<Amount, ChainError> {
+        let (_, committee) = self.current_committee()?;
+        Ok((*self.base_fee.get()).max(committee.policy().block))
+    }
+
     pub fn current_committee(&self) -> Result<(Epoch, &Committee), ChainError> {
         self.execution_state
             .system
@@ -714,10 +728,17 @@ where
             *self.execution_state.system.timestamp.get() <= block.timestamp,
             ChainError::InvalidBlockTimestamp
         );
-        self.execution_state.system.timestamp.set(block.timestamp);
         let (_, committee) = self.current_committee()?;
+        let policy = Arc::new(committee.policy().clone());
+        // The rent is charged for the time since the previous block, so this must happen before
+        // the timestamp is updated.
+        self.execution_state
+            .charge_storage_rent(&policy, block.timestamp)
+            .await
+            .with_execution_context(ChainExecutionContext::Block)?;
+        self.execution_state.system.timestamp.set(block.timestamp);
         let mut resource_controller = ResourceController {
-            policy: Arc::new(committee.policy().clone()),
+            policy,
             tracker: ResourceTracker::default(),
             account: block.authenticated_signer,
         };
@@ -744,8 +765,15 @@ where
             app_permissions.mandatory_applications.iter().cloned(),
         );
         for operation in &block.operations {
+            // Executing due callbacks only runs applications that scheduled them on this chain,
+            // and the callbacks of applications that may not execute operations are dropped.
+            let is_execute_callbacks = matches!(
+                operation,
+                Operation::System(SystemOperation::ExecuteCallbacks)
+            );
             ensure!(
-                app_permissions.can_execute_operations(&operation.application_id()),
+                is_execute_callbacks
+                    || app_permissions.can_execute_operations(&operation.application_id()),
                 ChainError::AuthorizedApplications(
                     app_permissions.execute_operations.clone().unwrap()
                 )
@@ -774,6 +802,9 @@ where
         let mut oracle_responses = Vec::new();
         let mut events = Vec::new();
         let mut messages = Vec::new();
+        let last_operation_index = (block.incoming_bundles.len() + block.operations.len())
+            .checked_sub(1)
+            .filter(|_| !block.operations.is_empty());
         for (txn_index, transaction) in block.transactions() {
             let chain_execution_context = match transaction {
                 Transaction::ReceiveMessages(_) => ChainExecutionContext::IncomingBundle(txn_index),
@@ -833,6 +864,15 @@ where
                         .await?
                         .track_operation(operation)
                         .with_execution_context(chain_execution_context)?;
+                    if last_operation_index == Some(txn_index as usize) {
+                        self.pay_priority_fees(
+                            block,
+                            &mut txn_tracker,
+                            &mut resource_controller,
+                            chain_execution_context,
+                        )
+                        .await?;
+                    }
                 }
             }
 
@@ -886,9 +926,13 @@ where
             resource_controller
                 .with_state(&mut self.execution_state)
                 .await?
-                .track_block()
+                .track_block(*self.base_fee.get())
                 .with_execution_context(ChainExecutionContext::Block)?;
         }
+        let base_fee = resource_controller
+            .policy
+            .next_base_fee(*self.base_fee.get(), resource_controller.tracker.block_size);
+        self.base_fee.set(base_fee);
 
         // Recompute the state hash.
         let state_hash = {
@@ -940,6 +984,47 @@ where
         Ok(outcome)
     }
 
+    /// Charges the priority fees of the block's operations, and credits them to the admin chain,
+    /// whose owners are the validators, with a message sent by the last operation.
+    async fn pay_priority_fees(
+        &mut self,
+        block: &ProposedBlock,
+        txn_tracker: &mut TransactionTracker,
+        resource_controller: &mut ResourceController<Option<Owner>>,
+        context: ChainExecutionContext,
+    ) -> Result<(), ChainError> {
+        if block.priority_fee.is_zero() {
+            return Ok(());
+        }
+        let admin_id = self
+            .execution_state
+            .system
+            .admin_id
+            .get()
+            .ok_or(ChainError::InactiveChain(block.chain_id))?;
+        let fees = resource_controller
+            .with_state(&mut self.execution_state)
+            .await?
+            .track_priority_fees(block.priority_fee)
+            .with_execution_context(context)?;
+        let message = RawOutgoingMessage {
+            destination: Destination::Recipient(admin_id),
+            authenticated: false,
+            grant: Amount::ZERO,
+            kind: MessageKind::Tracked,
+            expires_at: None,
+            message: SystemMessage::Credit {
+                target: None,
+                amount: fees,
+                source: None,
+            },
+        };
+        txn_tracker
+            .add_system_outcome(RawExecutionOutcome::default().with_message(message))
+            .with_execution_context(context)?;
+        Ok(())
+    }
+
     /// Executes a message as part of an incoming bundle in a block.
     #[expect(clippy::too_many_arguments)]
     async fn execute_message_in_block(
@@ -967,7 +1052,14 @@ where
             refund_grant_to: posted_message.refund_grant_to,
         };
         let mut grant = posted_message.grant;
-        match incoming_bundle.action {
+        // Expired messages are rejected regardless of the action chosen by the proposer.
+        let expired = posted_message.is_expired(block.timestamp);
+        let action = if expired {
+            MessageAction::Reject
+        } else {
+            incoming_bundle.action
+        };
+        match action {
             MessageAction::Accept => {
                 // Once a chain is closed, accepting incoming messages is not allowed.
                 ensure!(!self.is_closed(), ChainError::ClosedChain);
@@ -997,7 +1089,7 @@ where
                 // If rejecting a message fails, the entire block proposal should be
                 // scrapped.
                 ensure!(
-                    !posted_message.is_protected() || self.is_closed(),
+                    !posted_message.is_protected() || self.is_closed() || expired,
                     ChainError::CannotRejectMessage {
                         chain_id: block.chain_id,
                         origin: Box::new(incoming_bundle.origin.clone()),
@@ -1103,6 +1195,7 @@ where
             authenticated,
             grant,
             kind,
+            expires_at,
             message,
         } in raw_outcome.messages
         {
@@ -1123,6 +1216,7 @@ where
                 grant,
                 refund_grant_to,
                 kind,
+                expires_at,
                 message: lift(message),
             });
         }
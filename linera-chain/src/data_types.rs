@@ -75,6 +75,12 @@ pub struct ProposedBlock {
     /// Certified hash (see `Certificate` below) of the previous block in the
     /// chain, if any.
     pub previous_block_hash: Option<CryptoHash>,
+    /// The fee paid for each operation on top of the chain's base fee, to have this block
+    /// preferred when the chain is congested: validators handle the competing proposals that
+    /// are waiting for the chain by decreasing priority fee. The priority fees are credited to
+    /// the admin chain, whose owners are the validators.
+    #[debug(skip_if = Amount::is_zero)]
+    pub priority_fee: Amount,
 }
 
 impl ProposedBlock {
@@ -311,6 +317,19 @@ pub struct BlockProposal {
     pub signature: Signature,
     #[debug(skip_if = Option::is_none)]
     pub validated_block_certificate: Option<LiteCertificate<'static>>,
+    /// The encoding of the content that the signature signs.
+    pub signature_scheme: SignatureScheme,
+}
+
+/// The encoding of the [`ProposalContent`] that the signature of a [`BlockProposal`] signs.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SignatureScheme {
+    /// The BCS encoding, as signed by the Linera client.
+    #[default]
+    Bcs,
+    /// The [canonical JSON](linera_base::canonical_json) signing payload, which external
+    /// signers can produce without a BCS implementation.
+    CanonicalJson,
 }
 
 /// A posted message together with routing information.
@@ -329,6 +348,9 @@ pub struct OutgoingMessage {
     pub refund_grant_to: Option<Account>,
     /// The kind of message being sent.
     pub kind: MessageKind,
+    /// The time after which the receiver can no longer accept the message, if any.
+    #[debug(skip_if = Option::is_none)]
+    pub expires_at: Option<Timestamp>,
     /// The message itself.
     pub message: Message,
 }
@@ -349,6 +371,9 @@ pub struct PostedMessage {
     pub refund_grant_to: Option<Account>,
     /// The kind of message being sent.
     pub kind: MessageKind,
+    /// The time after which the receiver can no longer accept the message, if any.
+    #[debug(skip_if = Option::is_none)]
+    pub expires_at: Option<Timestamp>,
     /// The index of the message in the sending block.
     pub index: u32,
     /// The message itself.
@@ -382,6 +407,7 @@ impl OutgoingMessage {
             grant,
             refund_grant_to,
             kind,
+            expires_at,
             message,
         } = self;
         PostedMessage {
@@ -389,6 +415,7 @@ impl OutgoingMessage {
             grant,
             refund_grant_to,
             kind,
+            expires_at,
             index,
             message,
         }
@@ -620,6 +647,13 @@ impl PostedMessage {
     pub fn is_bouncing(&self) -> bool {
         matches!(self.kind, MessageKind::Bouncing)
     }
+
+    /// Returns whether the message can no longer be accepted in a block with the given
+    /// timestamp.
+    pub fn is_expired(&self, timestamp: Timestamp) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| timestamp > expires_at)
+    }
 }
 
 impl ExecutedBlock {
@@ -785,6 +819,7 @@ impl BlockProposal {
             owner: secret.public().into(),
             signature,
             validated_block_certificate: None,
+            signature_scheme: SignatureScheme::Bcs,
         }
     }
 
@@ -808,11 +843,19 @@ impl BlockProposal {
             owner: secret.public().into(),
             signature,
             validated_block_certificate: Some(lite_cert),
+            signature_scheme: SignatureScheme::Bcs,
         }
     }
 
+    /// Checks the signature of the proposal content, in the encoding given by the
+    /// [`SignatureScheme`] of the proposal.
     pub fn check_signature(&self) -> Result<(), CryptoError> {
-        self.signature.check(&self.content, self.public_key)
+        match self.signature_scheme {
+            SignatureScheme::Bcs => self.signature.check(&self.content, self.public_key),
+            SignatureScheme::CanonicalJson => self
+                .signature
+                .check_canonical_json(&self.content, self.public_key),
+        }
     }
 
     pub fn required_blob_ids(&self) -> impl Iterator<Item = BlobId> + '_ {
@@ -29,7 +29,7 @@ use linera_base::{
     data_types::{ArithmeticError, BlockHeight, Round, Timestamp},
     identifiers::{ApplicationId, BlobId, ChainId},
 };
-use linera_execution::ExecutionError;
+use linera_execution::{committee::Epoch, ExecutionError};
 use linera_views::views::ViewError;
 use rand_distr::WeightedError;
 use thiserror::Error;
@@ -167,6 +167,11 @@ pub enum ChainError {
     },
     #[error("Blobs not found: {0:?}")]
     BlobsNotFound(Vec<BlobId>),
+    #[error(
+        "Certificate {hash} is not an admin chain block of epoch {epoch:?} creating the \
+         next committee"
+    )]
+    InvalidCommitteeChange { hash: CryptoHash, epoch: Epoch },
 }
 
 impl From<ViewError> for ChainError {
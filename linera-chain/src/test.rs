@@ -36,6 +36,7 @@ pub fn make_child_block(parent: &Hashed<ConfirmedBlock>) -> ProposedBlock {
         height: parent_header.height.try_add_one().unwrap(),
         authenticated_signer: parent_header.authenticated_signer,
         timestamp: parent_header.timestamp,
+        priority_fee: parent_header.priority_fee,
     }
 }
 
@@ -50,6 +51,7 @@ pub fn make_first_block(chain_id: ChainId) -> ProposedBlock {
         height: BlockHeight::ZERO,
         authenticated_signer: None,
         timestamp: Timestamp::default(),
+        priority_fee: Amount::ZERO,
     }
 }
 
@@ -76,6 +78,9 @@ pub trait BlockTestExt: Sized {
     /// Returns the block with the specified epoch.
     fn with_epoch(self, epoch: impl Into<Epoch>) -> Self;
 
+    /// Returns the block with the specified priority fee per operation.
+    fn with_priority_fee(self, priority_fee: Amount) -> Self;
+
     /// Returns a block proposal in the first round in a default ownership configuration
     /// (`Round::MultiLeader(0)`) without any hashed certificate values or validated block.
     fn into_first_proposal(self, key_pair: &KeyPair) -> BlockProposal {
@@ -124,6 +129,11 @@ impl BlockTestExt for ProposedBlock {
         self
     }
 
+    fn with_priority_fee(mut self, priority_fee: Amount) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
     fn into_proposal_with_round(self, key_pair: &KeyPair, round: Round) -> BlockProposal {
         BlockProposal::new_initial(round, self, key_pair)
     }
@@ -163,6 +173,7 @@ impl<T: Into<Message>> MessageTestExt for T {
             grant: Amount::ZERO,
             refund_grant_to: None,
             kind,
+            expires_at: None,
             index,
             message: self.into(),
         }
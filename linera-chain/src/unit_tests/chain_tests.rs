@@ -13,7 +13,7 @@ use linera_base::{
         UserApplicationDescription,
     },
     hashed::Hashed,
-    identifiers::{ApplicationId, BytecodeId, ChainId, MessageId},
+    identifiers::{ApplicationId, BytecodeId, ChainId, Destination, MessageId},
     ownership::ChainOwnership,
 };
 use linera_execution::{
@@ -32,7 +32,7 @@ use linera_views::{
 
 use crate::{
     block::{Block, ConfirmedBlock},
-    data_types::{IncomingBundle, MessageAction, MessageBundle, Origin},
+    data_types::{IncomingBundle, Medium, MessageAction, MessageBundle, Origin},
     test::{make_child_block, make_first_block, BlockTestExt, MessageTestExt},
     ChainError, ChainExecutionContext, ChainStateView,
 };
@@ -112,7 +112,7 @@ async fn test_block_size_limit() {
     let mut chain = ChainStateView::new(chain_id).await;
 
     // The size of the executed valid block below.
-    let maximum_executed_block_size = 685;
+    let maximum_executed_block_size = 702;
 
     // Initialize the chain.
     let mut config = make_open_chain_config();
@@ -212,6 +212,7 @@ async fn test_application_permissions() -> anyhow::Result<()> {
 
     let register_app_message = SystemMessage::RegisterApplications {
         applications: vec![app_description],
+        call_policies: vec![],
     };
 
     // The OpenChain message must be included in the first block. Also register the app.
@@ -279,3 +280,153 @@ async fn test_application_permissions() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_expired_message_is_rejected() -> anyhow::Result<()> {
+    let time = Timestamp::from(0);
+    let message_id = make_admin_message_id(BlockHeight(3));
+    let chain_id = ChainId::child(message_id);
+    let mut chain = ChainStateView::new(chain_id).await;
+
+    let config = make_open_chain_config();
+    chain
+        .execute_init_message(message_id, &config, time, time)
+        .await?;
+    let open_chain_bundle = IncomingBundle {
+        origin: Origin::chain(admin_id()),
+        bundle: MessageBundle {
+            certificate_hash: CryptoHash::test_hash("certificate"),
+            height: BlockHeight(1),
+            transaction_index: 0,
+            timestamp: time,
+            messages: vec![Message::System(SystemMessage::OpenChain(config))
+                .to_posted(0, MessageKind::Protected)],
+        },
+        action: MessageAction::Accept,
+    };
+    let first_block = make_first_block(chain_id).with_incoming_bundle(open_chain_bundle);
+    let executed_block = chain
+        .execute_block(&first_block, time, None, None)
+        .await?
+        .with(first_block);
+    let value = Hashed::new(ConfirmedBlock::new(executed_block));
+
+    // A tracked message that expired before the block is accepted by the proposer.
+    let sender = ChainId::root(1);
+    let credit = Message::System(SystemMessage::Credit {
+        source: None,
+        target: None,
+        amount: Amount::ONE,
+    });
+    let mut posted_message = credit.clone().to_posted(0, MessageKind::Tracked);
+    posted_message.expires_at = Some(Timestamp::from(5));
+    let expired_bundle = IncomingBundle {
+        origin: Origin::chain(sender),
+        bundle: MessageBundle {
+            certificate_hash: CryptoHash::test_hash("sender certificate"),
+            height: BlockHeight(0),
+            transaction_index: 0,
+            timestamp: time,
+            messages: vec![posted_message],
+        },
+        action: MessageAction::Accept,
+    };
+    let block = make_child_block(&value)
+        .with_timestamp(10)
+        .with_incoming_bundle(expired_bundle);
+    let outcome = chain
+        .execute_block(&block, Timestamp::from(10), None, None)
+        .await?;
+
+    // The message is rejected instead, so it bounces back to the sender.
+    assert_matches!(
+        &outcome.messages[0][..],
+        [message] if message.kind == MessageKind::Bouncing
+            && message.message == credit
+            && message.has_destination(&Medium::Direct, sender)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_base_fee_and_priority_fees() -> anyhow::Result<()> {
+    let time = Timestamp::from(0);
+    let message_id = make_admin_message_id(BlockHeight(3));
+    let chain_id = ChainId::child(message_id);
+    let mut chain = ChainStateView::new(chain_id).await;
+
+    // Blocks of more than 500 bytes make the base fee rise.
+    let block_price = Amount::from_millis(1);
+    let mut config = make_open_chain_config();
+    config.committees.insert(
+        Epoch::ZERO,
+        Committee::new(
+            BTreeMap::from([(
+                ValidatorName(PublicKey::test_key(1)),
+                ValidatorState {
+                    network_address: PublicKey::test_key(1).to_string(),
+                    votes: 1,
+                },
+            )]),
+            ResourceControlPolicy {
+                block: block_price,
+                maximum_executed_block_size: 1_000,
+                base_fee_change_denominator: 8,
+                ..ResourceControlPolicy::default()
+            },
+        ),
+    );
+    chain
+        .execute_init_message(message_id, &config, time, time)
+        .await?;
+    assert_eq!(chain.next_block_base_fee()?, block_price);
+
+    let open_chain_bundle = IncomingBundle {
+        origin: Origin::chain(admin_id()),
+        bundle: MessageBundle {
+            certificate_hash: CryptoHash::test_hash("certificate"),
+            height: BlockHeight(1),
+            transaction_index: 0,
+            timestamp: time,
+            messages: vec![Message::System(SystemMessage::OpenChain(config))
+                .to_posted(0, MessageKind::Protected)],
+        },
+        action: MessageAction::Accept,
+    };
+    let first_block = make_first_block(chain_id).with_incoming_bundle(open_chain_bundle);
+    let executed_block = chain
+        .execute_block(&first_block, time, None, None)
+        .await?
+        .with(first_block);
+    let value = Hashed::new(ConfirmedBlock::new(executed_block));
+    let base_fee = chain.next_block_base_fee()?;
+    assert!(base_fee > block_price);
+
+    // The next block pays the raised base fee, and the priority fee for its operation, which is
+    // credited to the admin chain.
+    let balance = *chain.execution_state.system.balance.get();
+    let priority_fee = Amount::from_millis(10);
+    let block = make_child_block(&value)
+        .with_authenticated_signer(Some(PublicKey::test_key(0).into()))
+        .with_simple_transfer(ChainId::root(0), Amount::ONE)
+        .with_priority_fee(priority_fee);
+    let outcome = chain.execute_block(&block, time, None, None).await?;
+    let fee_message = outcome.messages[0].last().unwrap();
+    assert_eq!(fee_message.destination, Destination::Recipient(admin_id()));
+    assert_matches!(
+        &fee_message.message,
+        Message::System(SystemMessage::Credit {
+            target: None,
+            amount,
+            source: None,
+        }) if *amount == priority_fee
+    );
+    assert_eq!(
+        *chain.execution_state.system.balance.get(),
+        balance
+            .try_sub(Amount::ONE)?
+            .try_sub(base_fee)?
+            .try_sub(priority_fee)?
+    );
+    Ok(())
+}
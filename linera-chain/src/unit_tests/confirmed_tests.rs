@@ -0,0 +1,191 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{
+    crypto::{CryptoHash, KeyPair},
+    data_types::{Amount, Round},
+    hashed::Hashed,
+    identifiers::{ChainId, Destination},
+};
+use linera_execution::{
+    committee::{Committee, Epoch, ValidatorName},
+    system::SystemChannel,
+    Message, MessageKind, SystemMessage,
+};
+
+use super::verify_committee_changes;
+use crate::{
+    block::ConfirmedBlock,
+    data_types::{BlockExecutionOutcome, OutgoingMessage, Vote},
+    test::{make_first_block, BlockTestExt as _, VoteTestExt as _},
+    types::ConfirmedBlockCertificate,
+    ChainError,
+};
+
+/// Returns a certificate of a block of `chain_id` in `epoch` that creates the committee of
+/// `new_epoch`, signed by the only validator of the current committee.
+fn committee_change(
+    chain_id: ChainId,
+    epoch: Epoch,
+    new_epoch: Epoch,
+    new_committee: &Committee,
+    signer: &KeyPair,
+) -> ConfirmedBlockCertificate {
+    let block = make_first_block(chain_id).with_epoch(epoch);
+    let message = OutgoingMessage {
+        destination: Destination::Subscribers(SystemChannel::Admin.name()),
+        authenticated_signer: None,
+        grant: Amount::ZERO,
+        refund_grant_to: None,
+        kind: MessageKind::Protected,
+        expires_at: None,
+        message: Message::System(SystemMessage::CreateCommittee {
+            epoch: new_epoch,
+            committee: new_committee.clone(),
+        }),
+    };
+    let executed_block = BlockExecutionOutcome {
+        messages: vec![vec![message]],
+        state_hash: CryptoHash::test_hash("state"),
+        oracle_responses: vec![vec![]],
+        events: vec![vec![]],
+    }
+    .with(block);
+    let value = Hashed::new(ConfirmedBlock::new(executed_block));
+    Vote::new(value, Round::Fast, signer).into_certificate()
+}
+
+fn committee(key_pair: &KeyPair) -> Committee {
+    Committee::make_simple(vec![ValidatorName(key_pair.public())])
+}
+
+#[test]
+fn test_valid_committee_changes() {
+    let admin_id = ChainId::root(0);
+    let keys = [
+        KeyPair::generate(),
+        KeyPair::generate(),
+        KeyPair::generate(),
+    ];
+    let certificates = [
+        committee_change(
+            admin_id,
+            Epoch::ZERO,
+            Epoch::from(1),
+            &committee(&keys[1]),
+            &keys[0],
+        ),
+        committee_change(
+            admin_id,
+            Epoch::from(1),
+            Epoch::from(2),
+            &committee(&keys[2]),
+            &keys[1],
+        ),
+    ];
+
+    let (epoch, last_committee) =
+        verify_committee_changes(admin_id, committee(&keys[0]), &certificates).unwrap();
+    assert_eq!(epoch, Epoch::from(2));
+    assert_eq!(last_committee, committee(&keys[2]));
+
+    // Without changes, the genesis committee is still current.
+    let (epoch, last_committee) =
+        verify_committee_changes(admin_id, committee(&keys[0]), &[]).unwrap();
+    assert_eq!(epoch, Epoch::ZERO);
+    assert_eq!(last_committee, committee(&keys[0]));
+}
+
+#[test]
+fn test_invalid_committee_changes() {
+    let admin_id = ChainId::root(0);
+    let keys = [
+        KeyPair::generate(),
+        KeyPair::generate(),
+        KeyPair::generate(),
+    ];
+    let genesis_committee = committee(&keys[0]);
+    let first_change = committee_change(
+        admin_id,
+        Epoch::ZERO,
+        Epoch::from(1),
+        &committee(&keys[1]),
+        &keys[0],
+    );
+
+    // Signed by a validator that is not in the current committee.
+    let unsigned_change = committee_change(
+        admin_id,
+        Epoch::ZERO,
+        Epoch::from(1),
+        &committee(&keys[1]),
+        &keys[2],
+    );
+    assert!(
+        verify_committee_changes(admin_id, genesis_committee.clone(), &[unsigned_change]).is_err()
+    );
+
+    // Not a block of the admin chain.
+    let other_chain_change = committee_change(
+        ChainId::root(1),
+        Epoch::ZERO,
+        Epoch::from(1),
+        &committee(&keys[1]),
+        &keys[0],
+    );
+    assert!(matches!(
+        verify_committee_changes(admin_id, genesis_committee.clone(), &[other_chain_change]),
+        Err(ChainError::InvalidCommitteeChange { .. })
+    ));
+
+    // Skipping an epoch.
+    let skipping_change = committee_change(
+        admin_id,
+        Epoch::ZERO,
+        Epoch::from(2),
+        &committee(&keys[1]),
+        &keys[0],
+    );
+    assert!(matches!(
+        verify_committee_changes(admin_id, genesis_committee.clone(), &[skipping_change]),
+        Err(ChainError::InvalidCommitteeChange { .. })
+    ));
+
+    // A block from another epoch, although signed by the current validator.
+    let wrong_epoch_change = committee_change(
+        admin_id,
+        Epoch::from(1),
+        Epoch::from(2),
+        &committee(&keys[2]),
+        &keys[0],
+    );
+    assert!(matches!(
+        verify_committee_changes(
+            admin_id,
+            genesis_committee.clone(),
+            &[wrong_epoch_change.clone()]
+        ),
+        Err(ChainError::InvalidCommitteeChange { .. })
+    ));
+
+    // After the first change, the previous committee can no longer create committees.
+    assert!(verify_committee_changes(
+        admin_id,
+        genesis_committee.clone(),
+        &[first_change.clone(), wrong_epoch_change]
+    )
+    .is_err());
+
+    // Changes out of order.
+    let second_change = committee_change(
+        admin_id,
+        Epoch::from(1),
+        Epoch::from(2),
+        &committee(&keys[2]),
+        &keys[1],
+    );
+    assert!(
+        verify_committee_changes(admin_id, genesis_committee, &[second_change, first_change])
+            .is_err()
+    );
+}
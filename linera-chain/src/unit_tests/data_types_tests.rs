@@ -121,3 +121,40 @@ fn test_certificates() {
         .is_none());
     assert!(builder.append(v3.validator, v3.signature).is_err());
 }
+
+#[test]
+fn test_proposal_signed_as_canonical_json() {
+    let key_pair = KeyPair::generate();
+    let block =
+        make_first_block(ChainId::root(1)).with_simple_transfer(ChainId::root(2), Amount::ONE);
+    let mut proposal = BlockProposal::new_initial(Round::Fast, block, &key_pair);
+    assert!(proposal.check_signature().is_ok());
+
+    // An external signer signs the canonical JSON payload instead of the BCS bytes.
+    proposal.signature = Signature::new_canonical_json(&proposal.content, &key_pair).unwrap();
+    assert!(proposal.check_signature().is_err());
+    proposal.signature_scheme = SignatureScheme::CanonicalJson;
+    assert!(proposal.check_signature().is_ok());
+
+    proposal.content.round = Round::MultiLeader(1);
+    assert!(proposal.check_signature().is_err());
+}
+
+/// The canonical JSON signing payload of a proposal, as a test vector for external signers.
+/// If this test fails, the payload changed, and external signers must be updated.
+#[test]
+fn test_proposal_canonical_json_payload() {
+    let block = make_first_block(ChainId(CryptoHash::from([0, 0, 0, 1])))
+        .with_simple_transfer(ChainId(CryptoHash::from([0, 0, 0, 2])), Amount::ONE)
+        .with_authenticated_signer(Some(Owner(CryptoHash::from([0, 0, 0, 3]))));
+    let content = ProposalContent {
+        block,
+        round: Round::MultiLeader(0),
+        outcome: None,
+    };
+    let payload = linera_base::canonical_json::signing_payload(&content).unwrap();
+    assert_eq!(
+        String::from_utf8(payload).unwrap(),
+        r#"ProposalContent::json::{"block":{"authenticated_signer":"0000000000000000000000000000000000000000000000000000000000000003","chain_id":"0000000000000000000000000000000000000000000000000000000000000001","epoch":"0","height":0,"incoming_bundles":[],"operations":[{"System":{"Transfer":{"amount":"1.","owner":null,"recipient":{"Account":{"chain_id":"0000000000000000000000000000000000000000000000000000000000000002","owner":null}}}}}],"previous_block_hash":null,"priority_fee":"0.","timestamp":0},"outcome":null,"round":{"MultiLeader":0}}"#
+    );
+}
@@ -0,0 +1,193 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The operation mixes and latency reports of the `linera benchmark` command.
+
+use std::{fmt, str::FromStr};
+
+use linera_base::time::Duration;
+
+/// The maximal sum of the weights in an [`OperationMix`].
+const MAX_TOTAL_WEIGHT: u32 = 1000;
+
+/// A kind of operation submitted by the benchmark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BenchmarkOperation {
+    /// A transfer of native tokens to another benchmark chain.
+    NativeTransfer,
+    /// A transfer of fungible tokens to another benchmark chain.
+    FungibleTransfer,
+}
+
+impl FromStr for BenchmarkOperation {
+    type Err = ParseOperationMixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "native-transfer" => Ok(BenchmarkOperation::NativeTransfer),
+            "fungible-transfer" => Ok(BenchmarkOperation::FungibleTransfer),
+            _ => Err(ParseOperationMixError::UnknownOperation(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseOperationMixError {
+    #[error(
+        "unknown benchmark operation {0:?}: expected `native-transfer` or `fungible-transfer`"
+    )]
+    UnknownOperation(String),
+    #[error("invalid weight {0:?}: expected a positive integer")]
+    InvalidWeight(String),
+    #[error("the weights must add up to at most {MAX_TOTAL_WEIGHT}")]
+    TotalWeightTooLarge,
+    #[error("the operation mix is empty")]
+    Empty,
+}
+
+/// The relative frequencies of the operations submitted by the benchmark, parsed from a
+/// comma-separated list of operations with optional weights, e.g.
+/// `native-transfer=3,fungible-transfer=1`.
+///
+/// Operations are chosen in a fixed cycle whose length is the sum of the weights, so that
+/// the mix is followed exactly by any run of that many consecutive operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OperationMix {
+    cycle: Vec<BenchmarkOperation>,
+}
+
+impl OperationMix {
+    /// Returns a mix made of a single kind of operation.
+    pub fn single(operation: BenchmarkOperation) -> Self {
+        OperationMix {
+            cycle: vec![operation],
+        }
+    }
+
+    /// Returns the operation to submit at the given position.
+    pub fn operation(&self, index: usize) -> BenchmarkOperation {
+        self.cycle[index % self.cycle.len()]
+    }
+
+    /// Returns `true` if the mix includes the given kind of operation.
+    pub fn contains(&self, operation: BenchmarkOperation) -> bool {
+        self.cycle.contains(&operation)
+    }
+}
+
+impl FromStr for OperationMix {
+    type Err = ParseOperationMixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = Vec::new();
+        let mut total_weight = 0;
+        for entry in s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (operation, weight) = match entry.split_once('=') {
+                Some((operation, weight)) => {
+                    let weight = weight
+                        .trim()
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|weight| *weight > 0)
+                        .ok_or_else(|| ParseOperationMixError::InvalidWeight(weight.to_owned()))?;
+                    (operation.trim(), weight)
+                }
+                None => (entry, 1),
+            };
+            total_weight += weight;
+            if total_weight > MAX_TOTAL_WEIGHT {
+                return Err(ParseOperationMixError::TotalWeightTooLarge);
+            }
+            weights.push((operation.parse::<BenchmarkOperation>()?, weight));
+        }
+        if weights.is_empty() {
+            return Err(ParseOperationMixError::Empty);
+        }
+        // Interleave the operations, rather than submitting each kind in a row.
+        let max_weight = weights.iter().map(|(_, weight)| *weight).max().unwrap_or(0);
+        let cycle = (0..max_weight)
+            .flat_map(|round| {
+                weights
+                    .iter()
+                    .filter(move |(_, weight)| *weight > round)
+                    .map(|(operation, _)| *operation)
+            })
+            .collect();
+        Ok(OperationMix { cycle })
+    }
+}
+
+/// The throughput and latency of the blocks submitted by the benchmark.
+#[derive(Debug)]
+pub struct LatencyReport {
+    /// The latencies of the committed blocks, in increasing order.
+    latencies: Vec<Duration>,
+    transactions: usize,
+    failed_blocks: usize,
+    elapsed: Duration,
+}
+
+impl LatencyReport {
+    /// Creates a report from the latencies of the committed blocks, the number of
+    /// transactions they contain, the number of blocks that failed, and the total duration.
+    pub fn new(
+        mut latencies: Vec<Duration>,
+        transactions: usize,
+        failed_blocks: usize,
+        elapsed: Duration,
+    ) -> Self {
+        latencies.sort_unstable();
+        LatencyReport {
+            latencies,
+            transactions,
+            failed_blocks,
+            elapsed,
+        }
+    }
+
+    /// Returns the latency below which lies the given percentage of the blocks, or `None` if
+    /// no block was committed.
+    pub fn percentile(&self, percent: u32) -> Option<Duration> {
+        let last_index = self.latencies.len().checked_sub(1)?;
+        let index = (last_index * percent.min(100) as usize).div_ceil(100);
+        Some(self.latencies[index])
+    }
+
+    fn per_second(&self, count: usize) -> f64 {
+        count as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl fmt::Display for LatencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let blocks = self.latencies.len();
+        writeln!(
+            f,
+            "Committed {blocks} blocks with {} transactions in {} ms ({} failed)",
+            self.transactions,
+            self.elapsed.as_millis(),
+            self.failed_blocks,
+        )?;
+        writeln!(
+            f,
+            "Throughput: {:.1} blocks/s, {:.1} transactions/s",
+            self.per_second(blocks),
+            self.per_second(self.transactions),
+        )?;
+        write!(f, "Block latency:")?;
+        for percent in [50, 90, 99, 100] {
+            let latency = self.percentile(percent).unwrap_or_default();
+            let label = if percent == 100 {
+                "max".to_owned()
+            } else {
+                format!("p{percent}")
+            };
+            write!(f, " {label} {} ms", latency.as_millis())?;
+        }
+        Ok(())
+    }
+}
@@ -182,6 +182,7 @@ impl ChainListener {
                         Err(error) => warn!(%error, "Failed to process inbox."),
                         Ok((certs, None)) => {
                             info!("Done processing inbox. {} blocks created.", certs.len());
+                            timeout = Self::callback_timeout(&client, timeout).await;
                         }
                         Ok((certs, Some(new_timeout))) => {
                             info!(
@@ -208,6 +209,9 @@ impl ChainListener {
                             notification, error
                         );
                     }
+                    if let Reason::NewBlock { .. } = notification.reason {
+                        timeout = Self::callback_timeout(&client, timeout).await;
+                    }
                 }
             }
             Self::maybe_sleep(config.delay_after_ms).await;
@@ -267,6 +271,22 @@ impl ChainListener {
         Ok(())
     }
 
+    /// Returns the earlier of `timeout` and the time from which the next callback scheduled
+    /// by an application on the client's chain is due.
+    async fn callback_timeout<C>(client: &ContextChainClient<C>, timeout: Timestamp) -> Timestamp
+    where
+        C: ClientContext,
+    {
+        match client.next_callback_time().await {
+            Ok(Some(callback_time)) => timeout.min(callback_time),
+            Ok(None) => timeout,
+            Err(error) => {
+                warn!(%error, "Failed to read the callbacks scheduled on the chain.");
+                timeout
+            }
+        }
+    }
+
     async fn maybe_sleep(delay_ms: u64) {
         if delay_ms > 0 {
             linera_base::time::timer::sleep(Duration::from_millis(delay_ms)).await;
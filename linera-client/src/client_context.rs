@@ -9,7 +9,7 @@ use async_trait::async_trait;
 use futures::Future;
 use linera_base::{
     crypto::KeyPair,
-    data_types::{BlockHeight, Timestamp},
+    data_types::{Amount, BlockHeight, Timestamp},
     identifiers::{Account, ChainId},
     ownership::ChainOwnership,
     time::{Duration, Instant},
@@ -21,16 +21,19 @@ use linera_core::{
     join_set_ext::{JoinSet, JoinSetExt as _},
     node::CrossChainMessageDelivery,
 };
-use linera_rpc::node_provider::{NodeOptions, NodeProvider};
+use linera_rpc::{
+    grpc::GrpcCompression,
+    node_provider::{NodeOptions, NodeProvider},
+};
 use linera_storage::Storage;
 use thiserror_context::Context;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 #[cfg(feature = "benchmark")]
 use {
+    crate::benchmark::{BenchmarkOperation, LatencyReport, OperationMix},
     futures::{stream, StreamExt as _, TryStreamExt as _},
     linera_base::{
         crypto::PublicKey,
-        data_types::Amount,
         identifiers::{AccountOwner, ApplicationId, Owner},
     },
     linera_chain::data_types::{
@@ -85,9 +88,13 @@ where
     pub recv_timeout: Duration,
     pub retry_delay: Duration,
     pub max_retries: u32,
+    pub grpc_compression: GrpcCompression,
     pub chain_listeners: JoinSet,
     pub blanket_message_policy: BlanketMessagePolicy,
     pub restrict_chain_ids_to: Option<HashSet<ChainId>>,
+    pub priority_fee: Amount,
+    /// The chain used instead of the default chain of the wallet, if any.
+    pub default_chain: Option<ChainId>,
 }
 
 #[cfg_attr(not(web), async_trait)]
@@ -155,6 +162,7 @@ where
             recv_timeout: options.recv_timeout,
             retry_delay: options.retry_delay,
             max_retries: options.max_retries,
+            compression: options.grpc_compression,
         };
         let node_provider = NodeProvider::new(node_options);
         let delivery = CrossChainMessageDelivery::new(options.wait_for_outgoing_messages);
@@ -184,9 +192,12 @@ where
             recv_timeout: options.recv_timeout,
             retry_delay: options.retry_delay,
             max_retries: options.max_retries,
+            grpc_compression: options.grpc_compression,
             chain_listeners: JoinSet::default(),
             blanket_message_policy: options.blanket_message_policy,
             restrict_chain_ids_to: options.restrict_chain_ids_to,
+            priority_fee: options.priority_fee,
+            default_chain: options.default_chain,
         }
     }
 
@@ -203,6 +214,7 @@ where
             recv_timeout: send_recv_timeout,
             retry_delay,
             max_retries,
+            compression: GrpcCompression::None,
         };
         let node_provider = NodeProvider::new(node_options);
         let delivery = CrossChainMessageDelivery::new(true);
@@ -232,9 +244,12 @@ where
             recv_timeout: send_recv_timeout,
             retry_delay,
             max_retries,
+            grpc_compression: GrpcCompression::None,
             chain_listeners: JoinSet::default(),
             blanket_message_policy: BlanketMessagePolicy::Accept,
             restrict_chain_ids_to: None,
+            priority_fee: Amount::ZERO,
+            default_chain: None,
         }
     }
 
@@ -246,8 +261,8 @@ where
 
     /// Retrieve the default chain.
     pub fn default_chain(&self) -> ChainId {
-        self.wallet
-            .default_chain()
+        self.default_chain
+            .or_else(|| self.wallet.default_chain())
             .expect("No chain specified in wallet with no default chain")
     }
 
@@ -275,6 +290,7 @@ where
             self.blanket_message_policy,
             self.restrict_chain_ids_to.clone(),
         );
+        chain_client.options_mut().priority_fee = self.priority_fee;
         Ok(chain_client)
     }
 
@@ -288,6 +304,7 @@ where
             recv_timeout: self.recv_timeout,
             retry_delay: self.retry_delay,
             max_retries: self.max_retries,
+            compression: self.grpc_compression,
         }
     }
 
@@ -439,10 +456,20 @@ where
         ownership_config: ChainOwnershipConfig,
     ) -> Result<(), Error> {
         let chain_id = chain_id.unwrap_or_else(|| self.default_chain());
+        let ownership = ChainOwnership::try_from(ownership_config)?;
+        self.set_ownership(chain_id, ownership).await
+    }
+
+    /// Replaces the owners of a chain of the wallet, and how they work together proposing
+    /// blocks.
+    pub async fn set_ownership(
+        &mut self,
+        chain_id: ChainId,
+        ownership: ChainOwnership,
+    ) -> Result<(), Error> {
         let chain_client = self.make_chain_client(chain_id)?;
         info!("Changing ownership for chain {}", chain_id);
         let time_start = Instant::now();
-        let ownership = ChainOwnership::try_from(ownership_config)?;
 
         let certificate = self
             .apply_client_command(&chain_client, |chain_client| {
@@ -485,16 +512,46 @@ where
             .with_context(|| format!("failed to load service bytecode from {:?}", &service))?;
 
         info!("Publishing bytecode");
-        let (contract_blob, service_blob, bytecode_id) =
-            create_bytecode_blobs(contract_bytecode, service_bytecode).await;
+        let committee = chain_client.local_committee().await?;
+        let policy = committee.policy();
+        for (name, bytecode) in [
+            ("contract", &contract_bytecode),
+            ("service", &service_bytecode),
+        ] {
+            let size = bytecode.bytes.len() as u64;
+            // Warn when the bytecode takes more than 90% of the limit.
+            if size.saturating_mul(10) > policy.maximum_bytecode_size.saturating_mul(9) {
+                warn!(
+                    "The {name} bytecode has {size} bytes, close to the maximum bytecode size of \
+                    {} bytes",
+                    policy.maximum_bytecode_size
+                );
+            }
+        }
+        let maximum_blob_size = policy.maximum_blob_size;
+        let (contract_blob, service_blob, chunk_blobs, bytecode_id) =
+            create_bytecode_blobs(contract_bytecode, service_bytecode, maximum_blob_size).await;
+        if !chunk_blobs.is_empty() {
+            info!(
+                "The compressed bytecode exceeds the maximum blob size of {maximum_blob_size} \
+                bytes; publishing it in {} chunks",
+                chunk_blobs.len()
+            );
+        }
         let (bytecode_id, _) = self
             .apply_client_command(chain_client, |chain_client| {
                 let contract_blob = contract_blob.clone();
                 let service_blob = service_blob.clone();
+                let chunk_blobs = chunk_blobs.clone();
                 let chain_client = chain_client.clone();
                 async move {
                     chain_client
-                        .publish_bytecode_blobs(contract_blob, service_blob, bytecode_id)
+                        .publish_bytecode_blobs(
+                            contract_blob,
+                            service_blob,
+                            chunk_blobs,
+                            bytecode_id,
+                        )
                         .await
                         .context("Failed to publish bytecode")
                 }
@@ -744,29 +801,22 @@ where
         &mut self,
         key_pairs: &HashMap<ChainId, KeyPair>,
         transactions_per_block: usize,
+        operation_mix: &OperationMix,
         fungible_application_id: Option<ApplicationId>,
     ) -> Vec<RpcMessage> {
         let mut proposals = Vec::new();
         let mut next_recipient = self.wallet.last_chain().unwrap().chain_id;
-        let amount = Amount::from(1);
         for (&chain_id, key_pair) in key_pairs {
             let public_key = key_pair.public();
-            let operation = match fungible_application_id {
-                Some(application_id) => Self::fungible_transfer(
-                    application_id,
-                    next_recipient,
-                    public_key,
-                    public_key,
-                    amount,
-                ),
-                None => Operation::System(SystemOperation::Transfer {
-                    owner: None,
-                    recipient: Recipient::chain(next_recipient),
-                    amount,
-                }),
-            };
-            let operations = iter::repeat(operation)
-                .take(transactions_per_block)
+            let operations = (0..transactions_per_block)
+                .map(|index| {
+                    Self::benchmark_operation(
+                        operation_mix.operation(index),
+                        fungible_application_id,
+                        next_recipient,
+                        public_key,
+                    )
+                })
                 .collect();
             let chain = self.wallet.get(chain_id).expect("should have chain");
             let block = ProposedBlock {
@@ -778,6 +828,7 @@ where
                 height: chain.next_block_height,
                 authenticated_signer: Some(Owner::from(public_key)),
                 timestamp: chain.timestamp.max(Timestamp::now()),
+                priority_fee: Amount::ZERO,
             };
             trace!("Preparing block proposal: {:?}", block);
             let proposal = BlockProposal::new_initial(
@@ -791,6 +842,87 @@ where
         proposals
     }
 
+    /// Submits `blocks_per_chain` blocks on each benchmark chain using the client library,
+    /// waiting for each block to be confirmed before submitting the next one on the same
+    /// chain, and reports the throughput and the latency of the blocks.
+    pub async fn run_benchmark_load(
+        &mut self,
+        key_pairs: &HashMap<ChainId, KeyPair>,
+        blocks_per_chain: usize,
+        transactions_per_block: usize,
+        operation_mix: &OperationMix,
+        fungible_application_id: Option<ApplicationId>,
+        max_in_flight: usize,
+    ) -> Result<LatencyReport, Error> {
+        // Each chain sends its tokens to the next one.
+        let mut chain_ids = key_pairs.keys().copied().collect::<Vec<_>>();
+        chain_ids.sort();
+        let recipients = chain_ids.iter().cycle().skip(1);
+        let futures = chain_ids
+            .iter()
+            .zip(recipients)
+            .map(|(&chain_id, &recipient)| {
+                let chain_client = self
+                    .make_chain_client(chain_id)
+                    .expect("benchmark chains should be in the wallet");
+                let public_key = key_pairs[&chain_id].public();
+                async move {
+                    let mut latencies = Vec::new();
+                    let mut failed_blocks = 0;
+                    for block_index in 0..blocks_per_chain {
+                        let operations = (0..transactions_per_block)
+                            .map(|index| {
+                                Self::benchmark_operation(
+                                    operation_mix
+                                        .operation(block_index * transactions_per_block + index),
+                                    fungible_application_id,
+                                    recipient,
+                                    public_key,
+                                )
+                            })
+                            .collect();
+                        let start = Instant::now();
+                        match chain_client.execute_operations(operations, vec![]).await {
+                            Ok(ClientOutcome::Committed(_)) => latencies.push(start.elapsed()),
+                            Ok(ClientOutcome::WaitForTimeout(_)) => failed_blocks += 1,
+                            Err(error) => {
+                                error!(%chain_id, %error, "Failed to execute a benchmark block");
+                                failed_blocks += 1;
+                            }
+                        }
+                    }
+                    (chain_client, latencies, failed_blocks)
+                }
+            })
+            .collect::<Vec<_>>();
+        info!(
+            "Submitting {blocks_per_chain} blocks on each of {} chains",
+            chain_ids.len()
+        );
+        let time_start = Instant::now();
+        // We have to collect the futures to avoid a higher-ranked lifetime error:
+        // https://github.com/rust-lang/rust/issues/102211#issuecomment-1673201352
+        let results = stream::iter(futures)
+            .buffer_unordered(max_in_flight)
+            .collect::<Vec<_>>()
+            .await;
+        let elapsed = time_start.elapsed();
+        let mut latencies = Vec::new();
+        let mut failed_blocks = 0;
+        for (chain_client, chain_latencies, chain_failed_blocks) in results {
+            self.update_wallet_from_client(&chain_client).await?;
+            latencies.extend(chain_latencies);
+            failed_blocks += chain_failed_blocks;
+        }
+        let transactions = latencies.len() * transactions_per_block;
+        Ok(LatencyReport::new(
+            latencies,
+            transactions,
+            failed_blocks,
+            elapsed,
+        ))
+    }
+
     /// Tries to aggregate votes into certificates.
     pub fn make_benchmark_certificates_from_votes<T>(
         &self,
@@ -927,6 +1059,32 @@ where
         self.save_wallet().await.unwrap();
     }
 
+    /// Creates a benchmark operation, sending a token from the owner of `public_key` to the
+    /// same owner on the chain `recipient`.
+    fn benchmark_operation(
+        operation: BenchmarkOperation,
+        fungible_application_id: Option<ApplicationId>,
+        recipient: ChainId,
+        public_key: PublicKey,
+    ) -> Operation {
+        let amount = Amount::from(1);
+        match operation {
+            BenchmarkOperation::NativeTransfer => Operation::System(SystemOperation::Transfer {
+                owner: None,
+                recipient: Recipient::chain(recipient),
+                amount,
+            }),
+            BenchmarkOperation::FungibleTransfer => Self::fungible_transfer(
+                fungible_application_id
+                    .expect("fungible transfers require a fungible application ID"),
+                recipient,
+                public_key,
+                public_key,
+                amount,
+            ),
+        }
+    }
+
     /// Creates a fungible token transfer operation.
     fn fungible_transfer(
         application_id: ApplicationId,
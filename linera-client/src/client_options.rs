@@ -4,14 +4,14 @@
 use std::{
     collections::HashSet,
     env, fmt, iter,
-    num::{NonZeroU16, NonZeroUsize},
+    num::{NonZeroU16, NonZeroU32, NonZeroUsize},
     path::PathBuf,
 };
 
 use chrono::{DateTime, Utc};
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{Amount, ApplicationPermissions, TimeDelta},
+    data_types::{Amount, ApplicationPermissions, BlockHeight, TimeDelta},
     identifiers::{
         Account, ApplicationId, BytecodeId, ChainId, MessageId, Owner, UserApplicationId,
     },
@@ -20,17 +20,22 @@ use linera_base::{
 };
 use linera_core::{client::BlanketMessagePolicy, DEFAULT_GRACE_PERIOD};
 use linera_execution::{
-    committee::ValidatorName, ResourceControlPolicy, WasmRuntime, WithWasmDefault as _,
+    committee::ValidatorName, ApplicationCallPolicy, ExecutionRuntimeConfig,
+    NativeApplicationRegistry, PrivilegedSystemApi, ReentrancyMode, ResourceControlPolicy,
+    ServiceQueryLimits, WasmRuntime, WithWasmDefault as _,
 };
+use linera_rpc::grpc::GrpcCompression;
 use linera_views::store::CommonStoreConfig;
-
 #[cfg(feature = "fs")]
-use crate::config::GenesisConfig;
+use {crate::profile::Profile, age::secrecy::SecretString};
+
 use crate::{
     chain_listener::ChainListenerConfig,
-    config::WalletState,
+    config::{GenesisConfig, WalletState},
     persistent,
-    storage::{full_initialize_storage, run_with_storage, Runnable, StorageConfigNamespace},
+    storage::{
+        full_initialize_storage, run_with_storage, Runnable, StorageConfig, StorageConfigNamespace,
+    },
     util,
     wallet::Wallet,
 };
@@ -55,6 +60,20 @@ pub enum Error {
     Persistence(#[from] Box<dyn std::error::Error + Send + Sync>),
     #[error("config error: {0}")]
     Config(#[from] crate::config::Error),
+    #[error("the passphrases don't match")]
+    PassphraseMismatch,
+    #[error("the passphrase must not be empty")]
+    EmptyPassphrase,
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid profile name {0:?}: only letters, digits, '-' and '_' are allowed")]
+    InvalidProfileName(String),
+    #[error("profile {0} does not exist: create it with `linera profile create`")]
+    NonexistentProfile(String),
+    #[error("profile {0} already exists")]
+    ProfileAlreadyExists(String),
+    #[error("invalid payout on {position}: {reason}")]
+    InvalidPayout { position: String, reason: String },
 }
 
 #[cfg(feature = "fs")]
@@ -86,6 +105,12 @@ pub struct ClientOptions {
     #[arg(long, short = 'w')]
     pub with_wallet: Option<u32>,
 
+    /// Use the wallet, storage, faucet and validator of a named profile, created with
+    /// `linera profile create`. The LINERA_WALLET and LINERA_STORAGE environment variables are
+    /// ignored, but `--wallet` and `--storage` still take precedence.
+    #[arg(long, env = "LINERA_PROFILE")]
+    pub profile: Option<String>,
+
     /// Timeout for sending queries (milliseconds)
     #[arg(long = "send-timeout-ms", default_value = "4000", value_parser = util::parse_millis)]
     pub send_timeout: Duration,
@@ -134,6 +159,10 @@ pub struct ClientOptions {
     #[arg(long, default_value = "10")]
     pub max_retries: u32,
 
+    /// The compression of the gRPC requests sent to validators. Not supported on the web.
+    #[arg(long, default_value = "none")]
+    pub grpc_compression: GrpcCompression,
+
     /// Whether to wait until a quorum of validators has confirmed that all sent cross-chain
     /// messages have been delivered.
     #[arg(long)]
@@ -157,6 +186,11 @@ pub struct ClientOptions {
     #[arg(long, value_parser = util::parse_chain_set)]
     pub restrict_chain_ids_to: Option<HashSet<ChainId>>,
 
+    /// The fee paid for each operation on top of the chain's base fee, to have our blocks
+    /// preferred when the chain is congested. It is credited to the admin chain.
+    #[arg(long, default_value = "0")]
+    pub priority_fee: Amount,
+
     /// An additional delay, after reaching a quorum, to wait for additional validator signatures,
     /// as a fraction of time taken to reach quorum.
     #[arg(long, default_value_t = DEFAULT_GRACE_PERIOD)]
@@ -169,11 +203,39 @@ pub struct ClientOptions {
         value_parser = util::parse_millis
     )]
     pub blob_download_timeout: Duration,
+
+    /// The maximal amount of fuel an application service may consume to answer a query.
+    #[arg(long, default_value_t = ServiceQueryLimits::DEFAULT_MAX_FUEL)]
+    pub service_query_max_fuel: u64,
+
+    /// The maximal time an application service may take to answer a query, in milliseconds.
+    #[arg(
+        long = "service-query-timeout-ms",
+        default_value = "30000",
+        value_parser = util::parse_millis
+    )]
+    pub service_query_timeout: Duration,
+
+    /// The maximal size of the response of an application service to a query, in bytes.
+    #[arg(long, default_value_t = ServiceQueryLimits::DEFAULT_MAX_RESPONSE_BYTES)]
+    pub service_query_max_response_bytes: u64,
+
+    /// The chain used by commands that don't specify one, instead of the default chain of
+    /// the wallet. This is set by `linera console`.
+    #[arg(skip)]
+    pub default_chain: Option<ChainId>,
 }
 
 impl ClientOptions {
     pub fn init() -> Result<Self, Error> {
         let mut options = <ClientOptions as clap::Parser>::parse();
+        #[cfg(feature = "fs")]
+        if let Some(name) = options.profile.clone() {
+            let profile = Profile::read(&Self::config_dir()?, &name)?
+                .ok_or(Error::NonexistentProfile(name))?;
+            options.apply_profile(profile);
+            return Ok(options);
+        }
         let suffix = options
             .with_wallet
             .map(|n| format!("_{}", n))
@@ -197,6 +259,16 @@ impl ClientOptions {
         }
     }
 
+    fn execution_runtime_config(&self) -> ExecutionRuntimeConfig {
+        ExecutionRuntimeConfig {
+            service_query_limits: ServiceQueryLimits {
+                max_fuel: self.service_query_max_fuel,
+                max_duration: self.service_query_timeout,
+                max_response_bytes: self.service_query_max_response_bytes,
+            },
+        }
+    }
+
     pub async fn run_with_storage<R: Runnable>(&self, job: R) -> Result<R::Output, Error> {
         let genesis_config = self.wallet().await?.genesis_config().clone();
         let output = Box::pin(run_with_storage(
@@ -205,6 +277,33 @@ impl ClientOptions {
                 .await?,
             &genesis_config,
             self.wasm_runtime.with_wasm_default(),
+            self.execution_runtime_config(),
+            NativeApplicationRegistry::default(),
+            job,
+        ))
+        .await?;
+        Ok(output)
+    }
+
+    /// Runs the job on a fresh in-memory storage initialized with the given genesis
+    /// configuration, ignoring the wallet and the `--storage` option.
+    pub async fn run_with_memory_storage<R: Runnable>(
+        &self,
+        genesis_config: &GenesisConfig,
+        job: R,
+    ) -> Result<R::Output, Error> {
+        let storage_config = StorageConfigNamespace {
+            storage_config: StorageConfig::Memory,
+            namespace: "linera".to_string(),
+        };
+        let output = Box::pin(run_with_storage(
+            storage_config
+                .add_common_config(self.common_config())
+                .await?,
+            genesis_config,
+            self.wasm_runtime.with_wasm_default(),
+            self.execution_runtime_config(),
+            NativeApplicationRegistry::default(),
             job,
         ))
         .await?;
@@ -248,13 +347,79 @@ impl ClientOptions {
     }
 }
 
+/// The environment variable from which the passphrase of the wallet is read, instead of
+/// prompting the user for it.
+#[cfg(feature = "fs")]
+const WALLET_PASSWORD_ENV_VAR: &str = "LINERA_WALLET_PASSWORD";
+
+/// The environment variable from which the passphrase of exported chains is read, instead of
+/// prompting the user for it.
+#[cfg(feature = "fs")]
+const EXPORT_PASSWORD_ENV_VAR: &str = "LINERA_EXPORT_PASSWORD";
+
 #[cfg(feature = "fs")]
 impl ClientOptions {
     pub async fn wallet(&self) -> Result<WalletState<persistent::File<Wallet>>, Error> {
-        let wallet = persistent::File::read(&self.wallet_path()?)?;
+        let wallet = persistent::File::read_with_passphrase(&self.wallet_path()?, || {
+            Ok(Self::wallet_passphrase()?)
+        })?;
         Ok(WalletState::new(wallet))
     }
 
+    /// Returns the passphrase of an encrypted wallet, from the `LINERA_WALLET_PASSWORD`
+    /// environment variable if it is set, or prompting the user otherwise.
+    fn wallet_passphrase() -> std::io::Result<SecretString> {
+        if let Ok(passphrase) = env::var(WALLET_PASSWORD_ENV_VAR) {
+            return Ok(passphrase.into());
+        }
+        Ok(rpassword::prompt_password("Wallet passphrase: ")?.into())
+    }
+
+    /// Returns a new passphrase to encrypt the wallet with, from the `LINERA_WALLET_PASSWORD`
+    /// environment variable if it is set, or prompting the user to type it twice otherwise.
+    pub fn new_wallet_passphrase() -> Result<SecretString, Error> {
+        Self::new_passphrase(WALLET_PASSWORD_ENV_VAR, "New wallet passphrase: ")
+    }
+
+    /// Returns the passphrase of an exported chain, from the `LINERA_EXPORT_PASSWORD`
+    /// environment variable if it is set, or prompting the user otherwise.
+    pub fn export_passphrase() -> std::io::Result<SecretString> {
+        if let Ok(passphrase) = env::var(EXPORT_PASSWORD_ENV_VAR) {
+            return Ok(passphrase.into());
+        }
+        Ok(rpassword::prompt_password("Passphrase of the exported chain: ")?.into())
+    }
+
+    /// Returns a new passphrase to encrypt an exported chain with, from the
+    /// `LINERA_EXPORT_PASSWORD` environment variable if it is set, or prompting the user to
+    /// type it twice otherwise.
+    pub fn new_export_passphrase() -> Result<SecretString, Error> {
+        Self::new_passphrase(
+            EXPORT_PASSWORD_ENV_VAR,
+            "New passphrase of the exported chain: ",
+        )
+    }
+
+    /// Returns a new passphrase from the environment variable `env_var` if it is set, or
+    /// showing the `prompt` to the user and asking for confirmation otherwise.
+    fn new_passphrase(env_var: &str, prompt: &str) -> Result<SecretString, Error> {
+        let passphrase = match env::var(env_var) {
+            Ok(passphrase) => passphrase,
+            Err(_) => {
+                let passphrase = rpassword::prompt_password(prompt)?;
+                let confirmation = rpassword::prompt_password("Confirm the passphrase: ")?;
+                if passphrase != confirmation {
+                    return Err(Error::PassphraseMismatch);
+                }
+                passphrase
+            }
+        };
+        if passphrase.is_empty() {
+            return Err(Error::EmptyPassphrase);
+        }
+        Ok(passphrase.into())
+    }
+
     fn wallet_path(&self) -> Result<PathBuf, Error> {
         self.wallet_state_path
             .clone()
@@ -262,7 +427,18 @@ impl ClientOptions {
             .unwrap_or_else(|| Ok(self.config_path()?.join("wallet.json")))
     }
 
+    /// Returns the directory of the default wallet and storage: the directory of the
+    /// selected profile, if any, or the Linera configuration directory otherwise.
     fn config_path(&self) -> Result<PathBuf, Error> {
+        let config_dir = Self::config_dir()?;
+        match &self.profile {
+            Some(name) => Profile::directory(&config_dir, name),
+            None => Ok(config_dir),
+        }
+    }
+
+    /// Returns the Linera configuration directory, creating it if it doesn't exist.
+    pub fn config_dir() -> Result<PathBuf, Error> {
         let mut config_dir = dirs::config_dir().ok_or(Error::NoDefaultConfigurationDirectory)?;
         config_dir.push("linera");
         if !config_dir.exists() {
@@ -273,6 +449,33 @@ impl ClientOptions {
         Ok(config_dir)
     }
 
+    /// Uses the settings of the `profile` that were not overridden on the command line.
+    fn apply_profile(&mut self, profile: Profile) {
+        if self.wallet_state_path.is_none() {
+            self.wallet_state_path = profile.wallet;
+        }
+        if self.storage_config.is_none() {
+            self.storage_config = profile.storage;
+        }
+        if let ClientCommand::Wallet(WalletCommand::Init {
+            genesis_config_path: None,
+            faucet,
+            ..
+        }) = &mut self.command
+        {
+            if faucet.is_none() {
+                *faucet = profile.faucet;
+            }
+        }
+        if let ClientCommand::QueryValidator { address, .. }
+        | ClientCommand::SyncValidator { address, .. } = &mut self.command
+        {
+            if address.is_none() {
+                *address = profile.validator;
+            }
+        }
+    }
+
     pub fn create_wallet(
         &self,
         genesis_config: GenesisConfig,
@@ -323,6 +526,35 @@ pub enum ClientCommand {
 
         /// Amount to transfer
         amount: Amount,
+
+        /// Instead of signing and submitting the transfer, write the unsigned block proposal
+        /// to this file, so that it can be signed with `linera sign` on another machine. The
+        /// wallet does not need the chain's key.
+        #[arg(long, value_name = "PATH")]
+        offline: Option<PathBuf>,
+    },
+
+    /// Send the transfers listed in a file, in as few blocks as possible, and print the
+    /// outcome of each of them.
+    ///
+    /// The file is either a JSON array of `{"recipient": "<ACCOUNT>", "amount": "<AMOUNT>"}`
+    /// objects, if its extension is `json`, or a CSV file with one `<ACCOUNT>,<AMOUNT>` line
+    /// per transfer. Each printed line contains the recipient, the amount and the hash of the
+    /// block with the transfer, or `failed`, `unknown` or `skipped`. A block that fails is only
+    /// reported as `failed` once the chain was synchronized and doesn't contain it, or as
+    /// `unknown` if that could not be checked. Sending stops at the first block that fails.
+    TransferBatch {
+        /// Sending chain ID (must be one of our chains)
+        #[arg(long = "from")]
+        sender: Account,
+
+        /// The file listing the transfers.
+        #[arg(long)]
+        file: PathBuf,
+
+        /// The maximal number of transfers in each block.
+        #[arg(long, default_value = "100")]
+        max_transfers_per_block: usize,
     },
 
     /// Open (i.e. activate) a new chain deriving the UID from an existing one.
@@ -443,11 +675,25 @@ pub enum ClientCommand {
         chain_id: Option<ChainId>,
     },
 
+    /// Show the operations executed by a chain and the messages it received and sent.
+    QueryHistory {
+        /// The chain to show. If omitted, uses the default chain of the wallet.
+        chain_id: Option<ChainId>,
+
+        /// Only show the operations and messages of this application.
+        #[arg(long)]
+        application: Option<UserApplicationId>,
+
+        /// The height of the first block to show.
+        #[arg(long, default_value = "0")]
+        from_height: BlockHeight,
+    },
+
     /// Show the version and genesis config hash of a new validator, and print a warning if it is
     /// incompatible. Also print some information about the given chain while we are at it.
     QueryValidator {
-        /// The new validator's address.
-        address: String,
+        /// The new validator's address. If omitted, uses the validator of the profile.
+        address: Option<String>,
         /// The chain to query. If omitted, query the default chain of the wallet.
         chain_id: Option<ChainId>,
         /// The public key of the validator. If given, the signature of the chain query
@@ -465,8 +711,9 @@ pub enum ClientCommand {
 
     /// Synchronizes a validator with the local state of chains.
     SyncValidator {
-        /// The public address of the validator to synchronize.
-        address: String,
+        /// The public address of the validator to synchronize. If omitted, uses the validator of
+        /// the profile.
+        address: Option<String>,
 
         /// The chains to synchronize, or the default chain if empty.
         #[arg(long, num_args = 0..)]
@@ -499,6 +746,13 @@ pub enum ClientCommand {
         name: ValidatorName,
     },
 
+    /// Replace the key of a validator by the one announced in its key rotation (admin only)
+    RotateValidatorKey {
+        /// Path to the key rotation written by `linera-server rotate-key`.
+        #[arg(long = "rotation")]
+        rotation_path: PathBuf,
+    },
+
     /// Deprecates all committees except the last one.
     FinalizeCommittee,
 
@@ -532,6 +786,10 @@ pub enum ClientCommand {
         #[arg(long)]
         byte_stored: Option<Amount>,
 
+        /// Set the rent per byte stored per day.
+        #[arg(long)]
+        byte_stored_per_day: Option<Amount>,
+
         /// Set the base price of sending an operation from a block..
         #[arg(long)]
         operation: Option<Amount>,
@@ -580,6 +838,57 @@ pub enum ClientCommand {
         /// Set the maximum write data per block.
         #[arg(long)]
         maximum_bytes_written_per_block: Option<u64>,
+
+        /// Set the maximum size of the linear memory of an application instance, in bytes.
+        #[arg(long)]
+        maximum_wasm_memory_size: Option<u64>,
+
+        /// Set the maximum number of elements in the tables of an application instance.
+        #[arg(long)]
+        maximum_wasm_table_elements: Option<u64>,
+
+        /// Set the maximum number of bytes that an application can store on a chain.
+        #[arg(long)]
+        maximum_bytes_stored_per_application: Option<u64>,
+
+        /// Set how long the storage rent can remain unpaid before applications can no longer
+        /// increase their storage, in milliseconds. The largest application state is evicted if
+        /// it remains unpaid for twice as long, and another one after each further period.
+        #[arg(long = "storage-rent-grace-period-ms", value_parser = util::parse_millis_delta)]
+        storage_rent_grace_period: Option<TimeDelta>,
+
+        /// Set the fuel consumed by instructions that don't belong to any other class.
+        #[arg(long)]
+        instruction_fuel: Option<u32>,
+
+        /// Set the fuel consumed by structural instructions that perform no computation.
+        #[arg(long)]
+        structural_instruction_fuel: Option<u32>,
+
+        /// Set the fuel consumed by memory loads and stores.
+        #[arg(long)]
+        memory_access_fuel: Option<u32>,
+
+        /// Set the fuel consumed by function calls.
+        #[arg(long)]
+        call_fuel: Option<u32>,
+
+        /// Set the fuel consumed for each page of memory allocated by an application.
+        #[arg(long)]
+        memory_grow_page_fuel: Option<u32>,
+
+        /// Set how fast the base fee of each chain follows the fullness of its blocks.
+        #[arg(long)]
+        base_fee_change_denominator: Option<u64>,
+
+        /// Set the maximum number of callbacks that an application can have scheduled on a
+        /// chain at the same time.
+        #[arg(long)]
+        maximum_scheduled_callbacks_per_application: Option<u64>,
+
+        /// Set the maximum number of due callbacks executed by one operation.
+        #[arg(long)]
+        maximum_callbacks_per_operation: Option<u64>,
     },
 
     /// Send one transfer per chain in bulk mode
@@ -606,6 +915,20 @@ pub enum ClientCommand {
         /// If none is specified, the benchmark uses the native token.
         #[arg(long)]
         fungible_application_id: Option<linera_base::identifiers::ApplicationId>,
+
+        /// The operations to put in the blocks, as a comma-separated list of
+        /// `native-transfer` and `fungible-transfer`, each optionally followed by `=<WEIGHT>`,
+        /// e.g. `native-transfer=3,fungible-transfer=1`. By default, the benchmark only
+        /// transfers fungible tokens if a fungible application ID is specified, and native
+        /// tokens otherwise.
+        #[arg(long)]
+        operation_mix: Option<crate::benchmark::OperationMix>,
+
+        /// Submit this many blocks on each chain through the client library, one after the
+        /// other, and report the throughput and latency percentiles. By default, one block
+        /// per chain is broadcast directly to the validators.
+        #[arg(long)]
+        blocks_per_chain: Option<usize>,
     },
 
     /// Create genesis configuration for a Linera deployment.
@@ -663,6 +986,10 @@ pub enum ClientCommand {
         #[arg(long, default_value = "0")]
         byte_stored_price: Amount,
 
+        /// Set the rent per byte stored per day.
+        #[arg(long, default_value = "0")]
+        byte_stored_per_day_price: Amount,
+
         /// Set the base price of sending an operation from a block..
         #[arg(long, default_value = "0")]
         operation_price: Amount,
@@ -712,6 +1039,58 @@ pub enum ClientCommand {
         #[arg(long)]
         maximum_bytes_written_per_block: Option<u64>,
 
+        /// Set the maximum size of the linear memory of an application instance, in bytes.
+        #[arg(long)]
+        maximum_wasm_memory_size: Option<u64>,
+
+        /// Set the maximum number of elements in the tables of an application instance.
+        #[arg(long)]
+        maximum_wasm_table_elements: Option<u64>,
+
+        /// Set the maximum number of bytes that an application can store on a chain.
+        #[arg(long)]
+        maximum_bytes_stored_per_application: Option<u64>,
+
+        /// Set how long the storage rent can remain unpaid before applications can no longer
+        /// increase their storage, in milliseconds. The largest application state is evicted if
+        /// it remains unpaid for twice as long, and another one after each further period.
+        #[arg(long = "storage-rent-grace-period-ms", value_parser = util::parse_millis_delta)]
+        storage_rent_grace_period: Option<TimeDelta>,
+
+        /// Set the fuel consumed by instructions that don't belong to any other class.
+        #[arg(long, default_value = "1")]
+        instruction_fuel: u32,
+
+        /// Set the fuel consumed by structural instructions that perform no computation.
+        #[arg(long, default_value = "0")]
+        structural_instruction_fuel: u32,
+
+        /// Set the fuel consumed by memory loads and stores.
+        #[arg(long, default_value = "1")]
+        memory_access_fuel: u32,
+
+        /// Set the fuel consumed by function calls.
+        #[arg(long, default_value = "1")]
+        call_fuel: u32,
+
+        /// Set the fuel consumed for each page of memory allocated by an application.
+        #[arg(long, default_value = "0")]
+        memory_grow_page_fuel: u32,
+
+        /// Set how fast the base fee of each chain follows the fullness of its blocks. Zero
+        /// keeps the base fee at the block price.
+        #[arg(long, default_value = "0")]
+        base_fee_change_denominator: u64,
+
+        /// Set the maximum number of callbacks that an application can have scheduled on a
+        /// chain at the same time.
+        #[arg(long, default_value = "16")]
+        maximum_scheduled_callbacks_per_application: u64,
+
+        /// Set the maximum number of due callbacks executed by one operation.
+        #[arg(long, default_value = "10")]
+        maximum_callbacks_per_operation: u64,
+
         /// Force this wallet to generate keys using a PRNG and a given seed. USE FOR
         /// TESTING ONLY.
         #[arg(long)]
@@ -722,24 +1101,55 @@ pub enum ClientCommand {
         network_name: Option<String>,
     },
 
-    /// Watch the network for notifications.
+    /// Watch chains for new blocks and incoming messages, and print them as they arrive.
     Watch {
-        /// The chain ID to watch.
-        chain_id: Option<ChainId>,
+        /// The chain IDs to watch. Defaults to the default chain of the wallet.
+        chain_ids: Vec<ChainId>,
 
         /// Show all notifications from all validators.
         #[arg(long)]
         raw: bool,
+
+        /// Print each new block or incoming message bundle as a line of JSON.
+        #[arg(long)]
+        json: bool,
     },
 
+    /// Start an interactive console running `linera` commands.
+    ///
+    /// Commands are typed without the `linera` prefix and share the global options of the
+    /// console. Chain IDs, application IDs and command names can be completed with the Tab
+    /// key. `use <CHAIN_ID>` selects the chain used by commands that don't specify one, and
+    /// `exit` leaves the console.
+    Console,
+
     /// Run a GraphQL service to explore and extend the chains of the wallet.
+    ///
+    /// Common actions are also available as plain HTTP and JSON requests:
+    /// `GET /api/v1/chains/<CHAIN_ID>/balance[?owner=<OWNER>]`,
+    /// `POST /api/v1/chains/<CHAIN_ID>/operations` with a JSON operation, and
+    /// `GET /api/v1/chains/<CHAIN_ID>/blocks/<HEIGHT>`.
+    ///
+    /// Applications can also handle plain HTTP requests with JSON bodies, sent to
+    /// `/chains/<CHAIN_ID>/applications/<APPLICATION_ID>/<PATH>`.
     Service {
+        #[command(subcommand)]
+        command: Option<ServiceCommand>,
+
         #[command(flatten)]
         config: ChainListenerConfig,
 
         /// The port on which to run the server
         #[arg(long, default_value = "8080")]
         port: NonZeroU16,
+
+        #[command(flatten)]
+        webhooks: WebhookConfig,
+
+        /// Answer the `storageStats` query, which reads the whole state of a chain. Only enable
+        /// this if the node service is not exposed to untrusted users
+        #[arg(long)]
+        storage_stats: bool,
     },
 
     /// Run a GraphQL service that exposes a faucet where users can claim tokens.
@@ -761,6 +1171,10 @@ pub enum ClientCommand {
         #[arg(long)]
         limit_rate_until: Option<DateTime<Utc>>,
 
+        /// Limits on the claims accepted by the faucet.
+        #[command(flatten)]
+        limits: FaucetLimitConfig,
+
         /// Configuration for the faucet chain listener.
         #[command(flatten)]
         config: ChainListenerConfig,
@@ -826,6 +1240,9 @@ pub enum ClientCommand {
         /// The list of required dependencies of application, if any.
         #[arg(long, num_args(0..))]
         required_application_ids: Option<Vec<UserApplicationId>>,
+
+        #[clap(flatten)]
+        call_policy_config: ApplicationCallPolicyConfig,
     },
 
     /// Create an application, and publish the required bytecode.
@@ -876,6 +1293,36 @@ pub enum ClientCommand {
         requester_chain_id: Option<ChainId>,
     },
 
+    /// Replace the bytecode of an application created on this chain.
+    UpgradeApplication {
+        /// The ID of the application to upgrade.
+        application_id: UserApplicationId,
+
+        /// The ID of the new bytecode.
+        bytecode_id: BytecodeId,
+
+        /// The chain on which the application was created. The default chain of the wallet
+        /// is used otherwise.
+        #[arg(long)]
+        creator_chain_id: Option<ChainId>,
+    },
+
+    /// Restrict which applications may call an application, and which privileged system APIs
+    /// it may use. Only the owner who created the application can do this, on its creator
+    /// chain. Other chains adopt the policy when the application is registered there again.
+    SetApplicationCallPolicy {
+        /// The ID of the application to restrict.
+        application_id: UserApplicationId,
+
+        /// The chain on which the application was created. The default chain of the wallet
+        /// is used otherwise.
+        #[arg(long)]
+        creator_chain_id: Option<ChainId>,
+
+        #[clap(flatten)]
+        call_policy_config: ApplicationCallPolicyConfig,
+    },
+
     /// Create an unassigned key-pair.
     Keygen,
 
@@ -900,14 +1347,75 @@ pub enum ClientCommand {
         chain_id: Option<ChainId>,
     },
 
+    /// Sign a block proposal created with `linera transfer --offline`, using a key of the
+    /// wallet. This does not connect to the network, so it can run on an air-gapped machine.
+    Sign {
+        /// The file containing the unsigned block proposal.
+        proposal: PathBuf,
+
+        /// The file to write the signed block proposal to.
+        #[arg(long)]
+        output: PathBuf,
+
+        /// Sign without asking for confirmation after showing the content of the block.
+        #[arg(long)]
+        yes: bool,
+    },
+
+    /// Submit a block proposal signed with `linera sign` to the validators, and print the hash
+    /// of the confirmed block.
+    Submit {
+        /// The file containing the signed block proposal.
+        proposal: PathBuf,
+    },
+
+    /// Export the certificates of some chains from the local storage, together with the blobs
+    /// they require, so that they can be verified with `linera audit`.
+    ExportCertificates {
+        /// The chains to export. If none are given, all the chains of the wallet are exported.
+        chain_ids: Vec<ChainId>,
+
+        /// The file to write the certificates to.
+        #[arg(long)]
+        output: PathBuf,
+    },
+
+    /// Verify exported certificates offline: check their signatures and hashes, and re-execute
+    /// their blocks from the genesis state to check their outcomes. This does not connect to
+    /// the network and does not use the wallet.
+    Audit {
+        /// The file containing the certificates, as written by `linera export-certificates`.
+        certificates: PathBuf,
+
+        /// The genesis configuration of the network the certificates are from.
+        #[arg(long = "genesis")]
+        genesis_config_path: PathBuf,
+    },
+
     /// Show the contents of the wallet.
     #[command(subcommand)]
     Wallet(WalletCommand),
 
+    /// Move chains between wallets.
+    #[command(subcommand)]
+    Chain(ChainCommand),
+
+    /// Set up chains shared by several owners, step by step.
+    ///
+    /// Each future owner creates a key with `new-key` and sends the printed owner to whoever
+    /// opens the chain with `open`, or to an existing owner who runs `add-owner`. Each new
+    /// owner then runs `accept` with the chain ID to start proposing blocks on it.
+    #[command(subcommand)]
+    MultiOwner(MultiOwnerCommand),
+
     /// Manage Linera projects.
     #[command(subcommand)]
     Project(ProjectCommand),
 
+    /// Manage the named profiles selected with `--profile`.
+    #[command(subcommand)]
+    Profile(ProfileCommand),
+
     /// Manage a local Linera Network.
     #[command(subcommand)]
     Net(NetCommand),
@@ -998,6 +1506,70 @@ pub enum DatabaseToolCommand {
         #[arg(long = "storage")]
         storage_config: String,
     },
+
+    /// List the chains that have a state in the database
+    #[command(name = "list_chain_ids")]
+    ListChainIds {
+        /// Storage configuration for the blockchain history.
+        #[arg(long = "storage")]
+        storage_config: String,
+    },
+
+    /// Register the chains of a database written before the chains were indexed, so that they
+    /// are listed and retransmit their pending messages. The validator or client using the
+    /// database must be stopped.
+    #[command(name = "backfill_chain_indices")]
+    BackfillChainIndices {
+        /// Storage configuration for the blockchain history.
+        #[arg(long = "storage")]
+        storage_config: String,
+    },
+
+    /// Delete the whole state of a chain, e.g. after it was closed. The validator or client
+    /// using the database must be stopped.
+    #[command(name = "delete_chain")]
+    DeleteChain {
+        /// Storage configuration for the blockchain history.
+        #[arg(long = "storage")]
+        storage_config: String,
+
+        /// The chain whose state is deleted.
+        #[arg(long = "chain")]
+        chain_id: ChainId,
+    },
+
+    /// Write the raw keys and values of the state of a chain to a JSON file, as pairs of
+    /// hexadecimal strings
+    #[command(name = "export_chain")]
+    ExportChain {
+        /// Storage configuration for the blockchain history.
+        #[arg(long = "storage")]
+        storage_config: String,
+
+        /// The chain whose state is exported.
+        #[arg(long = "chain")]
+        chain_id: ChainId,
+
+        /// The file to write the entries to.
+        #[arg(long)]
+        output: PathBuf,
+    },
+
+    /// Print the keys and values of the state of a chain, decoding the layout of its views
+    #[command(name = "inspect")]
+    Inspect {
+        /// Storage configuration for the blockchain history.
+        #[arg(long = "storage")]
+        storage_config: String,
+
+        /// The chain to inspect.
+        #[arg(long = "chain")]
+        chain_id: ChainId,
+
+        /// Only print the entries whose raw keys start with this prefix, in hexadecimal.
+        #[arg(long)]
+        prefix: Option<String>,
+    },
 }
 
 impl DatabaseToolCommand {
@@ -1010,6 +1582,11 @@ impl DatabaseToolCommand {
             DatabaseToolCommand::Initialize { storage_config } => storage_config,
             DatabaseToolCommand::ListNamespaces { storage_config } => storage_config,
             DatabaseToolCommand::ListBlobIds { storage_config } => storage_config,
+            DatabaseToolCommand::ListChainIds { storage_config } => storage_config,
+            DatabaseToolCommand::BackfillChainIndices { storage_config } => storage_config,
+            DatabaseToolCommand::DeleteChain { storage_config, .. } => storage_config,
+            DatabaseToolCommand::ExportChain { storage_config, .. } => storage_config,
+            DatabaseToolCommand::Inspect { storage_config, .. } => storage_config,
         };
         Ok(storage_config.parse::<StorageConfigNamespace>()?)
     }
@@ -1053,9 +1630,10 @@ pub enum NetCommand {
         #[arg(long)]
         testing_prng_seed: Option<u64>,
 
-        /// Start the local network on a local Kubernetes deployment.
+        /// Start the local network on a local Kubernetes deployment. Its validators use the
+        /// storage of the Helm chart, so `--storage-backend` is not supported.
         #[cfg(feature = "kubernetes")]
-        #[arg(long)]
+        #[arg(long, conflicts_with = "storage_backend")]
         kubernetes: bool,
 
         /// If this is not set, we'll build the binaries from within the Docker container
@@ -1075,6 +1653,13 @@ pub enum NetCommand {
         #[arg(long, default_value = "linera:latest")]
         docker_image_name: String,
 
+        /// Instead of deploying the network, write the configuration of the validators,
+        /// their Helm values for the chart `kubernetes/linera-validator` and the initial
+        /// wallet to the given directory.
+        #[cfg(feature = "kubernetes")]
+        #[arg(long, requires = "kubernetes")]
+        kubernetes_manifests: Option<PathBuf>,
+
         /// Run with a specific path where the wallet and validator input files are.
         /// If none, then a temporary directory is created.
         #[arg(long)]
@@ -1085,6 +1670,15 @@ pub enum NetCommand {
         #[arg(long)]
         storage: Option<String>,
 
+        /// The storage backend of the validators, if `--storage` is not set.
+        #[arg(long, default_value = "service", conflicts_with = "storage")]
+        storage_backend: NetStorageBackend,
+
+        /// Save the data of the storage service to this file, so that the network survives
+        /// restarts. Only used by the `service` storage backend.
+        #[arg(long, conflicts_with = "storage")]
+        storage_file: Option<PathBuf>,
+
         /// External protocol used, either grpc or grpcs.
         #[arg(long, default_value = "grpc")]
         external_protocol: String,
@@ -1108,6 +1702,19 @@ pub enum NetCommand {
     Helper,
 }
 
+/// The storage backend of the validators started by `linera net up`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetStorageBackend {
+    /// A linera-storage-service started on a random free port.
+    Service,
+    /// A ScyllaDB instance listening on `localhost:9042`.
+    #[value(name = "scylladb")]
+    ScyllaDb,
+    /// DynamoDB, emulated by a local LocalStack instance.
+    #[value(name = "dynamodb")]
+    DynamoDb,
+}
+
 #[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ResourceControlPolicyConfig {
     Default,
@@ -1190,6 +1797,320 @@ pub enum WalletCommand {
 
     /// Forgets the specified chain, including the associated key pair.
     ForgetChain { chain_id: ChainId },
+
+    /// Encrypt the wallet with a new passphrase, or change the passphrase of an encrypted
+    /// wallet.
+    ///
+    /// The passphrase is read from the `LINERA_WALLET_PASSWORD` environment variable if it is
+    /// set, and the user is prompted for it otherwise. The same applies to the commands using
+    /// an encrypted wallet.
+    Encrypt,
+}
+
+/// Limits on the claims accepted by a faucet, protecting it from being drained.
+#[derive(Clone, Debug, clap::Args)]
+pub struct FaucetLimitConfig {
+    /// The maximal number of claims granted to each IP address per claim window.
+    #[arg(long)]
+    pub max_claims_per_ip: Option<NonZeroU32>,
+
+    /// The maximal number of claims granted to each owner per claim window.
+    #[arg(long)]
+    pub max_claims_per_owner: Option<NonZeroU32>,
+
+    /// The duration of the window in which claims are counted, in seconds.
+    #[arg(
+        long = "claim-window-secs",
+        default_value = "86400",
+        value_parser = util::parse_secs
+    )]
+    pub claim_window: Duration,
+
+    /// Read the address of clients from the `X-Forwarded-For` header. Only use this behind a
+    /// reverse proxy setting that header.
+    #[arg(long)]
+    pub trust_forwarded_for: bool,
+
+    /// A URL to which each claim is posted before being granted, e.g. to verify a captcha.
+    /// The request is a JSON object with the `owner`, the client's `ip` address and the
+    /// `verificationToken` passed to the `claim` mutation. The claim is only granted if the
+    /// response has a success status.
+    #[arg(long)]
+    pub verification_webhook: Option<String>,
+
+    /// A file to which every grant is also appended as a JSON line, e.g. to be analyzed by
+    /// other tools. The grants are always recorded in the storage, where they still count
+    /// toward the limits when the faucet is restarted. If the storage has no grants yet, those
+    /// of the file are imported into it.
+    #[arg(long)]
+    pub grants_path: Option<PathBuf>,
+
+    /// The maximal amount of tokens granted per budget period.
+    #[arg(long)]
+    pub budget_per_period: Option<Amount>,
+
+    /// The duration of a budget period, in seconds. Periods start at multiples of this
+    /// duration since the Unix epoch.
+    #[arg(
+        long = "budget-period-secs",
+        default_value = "86400",
+        value_parser = util::parse_secs
+    )]
+    pub budget_period: Duration,
+}
+
+/// HTTP endpoints notified of the events of the chains of the wallet.
+#[derive(Clone, Debug, Default, clap::Args)]
+pub struct WebhookConfig {
+    /// Post a JSON notification to a URL whenever an event happens on a chain of the wallet,
+    /// e.g. `--webhook new-block=https://example.com/blocks`. The events are `new-block`,
+    /// `new-incoming-bundle`, and `application-event:<APPLICATION_ID>` for the events
+    /// emitted by an application. Can be repeated.
+    #[arg(
+        long = "webhook",
+        value_name = "EVENT=URL",
+        requires = "webhook_secret"
+    )]
+    pub webhooks: Vec<Webhook>,
+
+    /// The secret used to sign the webhook notifications: the hex-encoded HMAC-SHA256 of the
+    /// `X-Linera-Timestamp` header, a dot and the request body is sent in the
+    /// `X-Linera-Signature` header.
+    #[arg(long, env = "LINERA_WEBHOOK_SECRET")]
+    pub webhook_secret: Option<String>,
+}
+
+/// A URL notified of the events of a given kind.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Webhook {
+    pub event: WebhookEvent,
+    pub url: String,
+}
+
+/// The kinds of events that webhooks can be notified of.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WebhookEvent {
+    /// A new block was added to the chain.
+    NewBlock,
+    /// The chain received new messages from another chain.
+    NewIncomingBundle,
+    /// The given application emitted an event in a new block of the chain.
+    ApplicationEvent(UserApplicationId),
+}
+
+impl std::str::FromStr for Webhook {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (event, url) = s
+            .split_once('=')
+            .ok_or_else(|| "expected a webhook of the form EVENT=URL".to_owned())?;
+        Ok(Webhook {
+            event: event.parse()?,
+            url: url.to_owned(),
+        })
+    }
+}
+
+impl std::str::FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "new-block" => Ok(WebhookEvent::NewBlock),
+            "new-incoming-bundle" => Ok(WebhookEvent::NewIncomingBundle),
+            _ => {
+                let application_id = s.strip_prefix("application-event:").ok_or_else(|| {
+                    format!(
+                        "unknown webhook event {s:?}; expected `new-block`, \
+                        `new-incoming-bundle` or `application-event:<APPLICATION_ID>`"
+                    )
+                })?;
+                let application_id = application_id
+                    .parse()
+                    .map_err(|error| format!("invalid application ID: {error}"))?;
+                Ok(WebhookEvent::ApplicationEvent(application_id))
+            }
+        }
+    }
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WebhookEvent::NewBlock => write!(f, "new-block"),
+            WebhookEvent::NewIncomingBundle => write!(f, "new-incoming-bundle"),
+            WebhookEvent::ApplicationEvent(application_id) => {
+                write!(f, "application-event:{application_id}")
+            }
+        }
+    }
+}
+
+#[derive(Clone, clap::Subcommand)]
+pub enum ChainCommand {
+    /// Export a chain of the wallet to a new file, to use it from another wallet.
+    ///
+    /// The file contains the key pair of the chain. It is encrypted with a passphrase, read
+    /// from the `LINERA_EXPORT_PASSWORD` environment variable if it is set, or typed by the user
+    /// otherwise.
+    Export {
+        /// The chain to export.
+        chain_id: ChainId,
+
+        /// The file to create.
+        #[arg(long)]
+        output: PathBuf,
+
+        /// Write the key pair of the chain unencrypted. The file must then be kept secret.
+        #[arg(long)]
+        plaintext: bool,
+
+        /// Remove the chain from this wallet once it is exported.
+        #[arg(long)]
+        forget: bool,
+    },
+
+    /// Import a chain exported from another wallet of the same network.
+    ///
+    /// If the wallet already follows the chain without its keys, it is replaced. The
+    /// passphrase of an encrypted file is read from the `LINERA_EXPORT_PASSWORD` environment
+    /// variable if it is set, or typed by the user otherwise.
+    Import {
+        /// The file created by `linera chain export`.
+        path: PathBuf,
+    },
+}
+
+#[derive(Clone, clap::Subcommand)]
+pub enum MultiOwnerCommand {
+    /// Create a key for a chain owned by several wallets, and print the owner to send to the
+    /// person opening the chain or adding owners to it.
+    NewKey,
+
+    /// Open a new multi-owner chain, and print its ID.
+    ///
+    /// A new key of this wallet is added to the given owners with weight 100, so that the new
+    /// chain can be used from this wallet right away.
+    Open {
+        /// Chain ID (must be one of our chains).
+        #[arg(long = "from")]
+        chain_id: Option<ChainId>,
+
+        #[clap(flatten)]
+        ownership_config: ChainOwnershipConfig,
+
+        #[clap(flatten)]
+        application_permissions_config: ApplicationPermissionsConfig,
+
+        /// The initial balance of the new chain. This is subtracted from the parent chain's
+        /// balance.
+        #[arg(long = "initial-balance", default_value = "0")]
+        balance: Amount,
+    },
+
+    /// Add a regular owner to a chain of the wallet, keeping the other owners and the round
+    /// configuration.
+    AddOwner {
+        /// The new owner, as printed by `linera multi-owner new-key`.
+        owner: Owner,
+
+        /// The chain to add the owner to. If omitted, uses the default chain of the wallet.
+        #[arg(long)]
+        chain_id: Option<ChainId>,
+
+        /// The weight of the new owner, which determines how often it is the round leader.
+        #[arg(long, default_value = "100")]
+        weight: u64,
+    },
+
+    /// Remove an owner from a chain of the wallet, keeping the other owners and the round
+    /// configuration.
+    RemoveOwner {
+        /// The owner to remove.
+        owner: Owner,
+
+        /// The chain to remove the owner from. If omitted, uses the default chain of the
+        /// wallet.
+        #[arg(long)]
+        chain_id: Option<ChainId>,
+    },
+
+    /// Start using a chain that a key created with `linera multi-owner new-key` was made an
+    /// owner of, and print that owner.
+    Accept {
+        /// The chain to use, as printed by `linera multi-owner open`.
+        chain_id: ChainId,
+    },
+}
+
+#[derive(Clone, clap::Subcommand)]
+pub enum ServiceCommand {
+    /// Print the GraphQL schema of the node service and of each application, in the schema
+    /// definition language.
+    Schema {
+        #[command(flatten)]
+        options: ServiceSchemaOptions,
+    },
+
+    /// Write the GraphQL schema of the node service and of each application to a directory,
+    /// together with TypeScript definitions of their types for web frontends.
+    ///
+    /// Each schema is written to `<NAME>.graphql` and `<NAME>.ts`, where the name is
+    /// `node_service` or the application ID.
+    Codegen {
+        #[command(flatten)]
+        options: ServiceSchemaOptions,
+
+        /// The directory to write the files to.
+        #[arg(long)]
+        output_dir: PathBuf,
+    },
+}
+
+#[derive(Clone, Debug, clap::Args)]
+pub struct ServiceSchemaOptions {
+    /// Only export the schema of this application, instead of the node service's and those
+    /// of all the applications of the chain.
+    #[arg(long = "app")]
+    pub application_id: Option<UserApplicationId>,
+
+    /// The chain whose applications are queried. The default chain of the wallet is used
+    /// otherwise.
+    #[arg(long = "chain")]
+    pub chain_id: Option<ChainId>,
+}
+
+#[derive(Clone, clap::Subcommand)]
+pub enum ProfileCommand {
+    /// Create a new profile.
+    ///
+    /// Unless other paths are specified, the wallet and the storage of the profile are kept in
+    /// its own directory, so that `linera --profile <NAME> wallet init` creates new ones.
+    Create {
+        /// The name of the profile.
+        name: String,
+
+        /// The wallet of the profile.
+        #[arg(long)]
+        wallet: Option<PathBuf>,
+
+        /// The storage configuration of the profile.
+        #[arg(long)]
+        storage: Option<String>,
+
+        /// The address of the faucet used by `linera wallet init` with this profile.
+        #[arg(long)]
+        faucet: Option<String>,
+
+        /// The public address of the validator used by `linera query-validator` and
+        /// `linera sync-validator` with this profile.
+        #[arg(long)]
+        validator: Option<String>,
+    },
+
+    /// List the existing profiles.
+    List,
 }
 
 #[derive(Clone, clap::Parser)]
@@ -1246,6 +2167,11 @@ pub enum ProjectCommand {
         /// The list of required dependencies of application, if any.
         #[arg(long, num_args(0..))]
         required_application_ids: Option<Vec<UserApplicationId>>,
+
+        /// Publish the bytecode as built, without optimizing it for size with `wasm-opt`
+        /// and stripping its custom sections.
+        #[arg(long)]
+        no_optimize: bool,
     },
 }
 
@@ -1382,3 +2308,28 @@ impl From<ApplicationPermissionsConfig> for ApplicationPermissions {
         }
     }
 }
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct ApplicationCallPolicyConfig {
+    /// If present, only the specified applications may call the application. Otherwise any
+    /// application may call it.
+    #[arg(long, num_args(0..))]
+    pub allowed_callers: Option<Vec<ApplicationId>>,
+    /// If present, the application may only use the specified privileged system APIs.
+    /// Otherwise it may use all of them.
+    #[arg(long, value_enum, num_args(0..))]
+    pub allowed_system_apis: Option<Vec<PrivilegedSystemApi>>,
+    /// How calls into the application are handled while it is executing.
+    #[arg(long, value_enum, default_value = "reject-calls")]
+    pub reentrancy: ReentrancyMode,
+}
+
+impl From<ApplicationCallPolicyConfig> for ApplicationCallPolicy {
+    fn from(config: ApplicationCallPolicyConfig) -> ApplicationCallPolicy {
+        ApplicationCallPolicy {
+            callers: config.allowed_callers,
+            system_apis: config.allowed_system_apis,
+            reentrancy: config.reentrancy,
+        }
+    }
+}
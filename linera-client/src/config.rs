@@ -55,6 +55,14 @@ pub struct ValidatorConfig {
 pub struct ValidatorServerConfig {
     pub validator: ValidatorConfig,
     pub key: KeyPair,
+    /// The key used before the last key rotation, still signing for the chains whose
+    /// committee doesn't include the new key yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_key: Option<KeyPair>,
+    /// The end of the grace period of the last key rotation, after which the previous key is
+    /// not used anymore. Without it, the previous key is used until the rotation is ended.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_key_valid_until: Option<Timestamp>,
     pub internal_network: ValidatorInternalNetworkConfig,
 }
 
@@ -151,6 +159,20 @@ impl WalletState<persistent::File<Wallet>> {
     pub fn read_from_file(path: &std::path::Path) -> Result<Self, Error> {
         Ok(Self::new(persistent::File::read(path)?))
     }
+
+    /// Returns whether the wallet file is encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.wallet.is_encrypted()
+    }
+
+    /// Saves the wallet file encrypted with the `passphrase`, from now on.
+    pub async fn encrypt(&mut self, passphrase: age::secrecy::SecretString) -> Result<(), Error> {
+        self.wallet.set_passphrase(Some(passphrase));
+        self.wallet
+            .persist()
+            .await
+            .map_err(|e| Error::Persistence(Box::new(e)))
+    }
 }
 
 #[cfg(with_indexed_db)]
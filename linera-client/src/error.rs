@@ -24,6 +24,20 @@ pub(crate) enum Inner {
     NonexistentKeypair(linera_base::identifiers::ChainId),
     #[error("error on the local node: {0}")]
     LocalNode(#[from] linera_core::local_node::LocalNodeError),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(
+        "the exported chain belongs to another network: its genesis configuration hash is \
+        {exported}, but the wallet's is {expected}"
+    )]
+    GenesisConfigMismatch {
+        exported: linera_base::crypto::CryptoHash,
+        expected: linera_base::crypto::CryptoHash,
+    },
+    #[error("the wallet already has the keys of chain {0}")]
+    ChainAlreadyOwned(linera_base::identifiers::ChainId),
 }
 
 thiserror_context::impl_context!(Error(Inner));
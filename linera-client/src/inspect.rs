@@ -0,0 +1,257 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decoding of the raw keys and values of a chain state, for `linera storage inspect`.
+//!
+//! The keys follow the layout of the views: each field of a derived view is prefixed by the
+//! tag `MIN_VIEW_TAG` and the index of the field, as a 4-byte integer. A register is stored
+//! at its base key; maps and sets append the serialized index to it; logs and queues store
+//! their count or range under a first tag, and their entries under a second tag; collections
+//! store their indices under a first tag and their subviews under a second one; and hashed
+//! views store the wrapped view under a first tag and its hash under a second one.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Debug},
+    ops::Range,
+};
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{Amount, ApplicationPermissions, BlockHeight, Timestamp},
+    identifiers::{ChainDescription, ChainId},
+    ownership::ChainOwnership,
+};
+use linera_chain::data_types::ChainAndHeight;
+use linera_execution::committee::{Committee, Epoch, ValidatorName};
+use linera_views::views::MIN_VIEW_TAG;
+use serde::de::DeserializeOwned;
+
+/// Decodes a serialized value, or returns `None` if it has an unexpected format.
+type Decode = fn(&[u8]) -> Option<String>;
+
+/// How the keys and values of a view are laid out.
+#[derive(Clone, Copy)]
+enum Layout {
+    /// A view derived from a struct, with the names and layouts of its fields.
+    Struct(&'static [(&'static str, Layout)]),
+    /// A view wrapped with its hash.
+    Hashed(&'static Layout),
+    /// A register view, with the decoder of its value, if known.
+    Register(Option<Decode>),
+    /// A log view, with the decoder of its entries, if known.
+    Log(Option<Decode>),
+    /// A queue view.
+    Queue,
+    /// A map view or a set view.
+    Map,
+    /// A collection of views.
+    Collection,
+    /// A view whose layout is not decoded.
+    Opaque,
+}
+
+const CHAIN_STATE: &[(&str, Layout)] = &[
+    ("execution_state", Layout::Struct(EXECUTION_STATE)),
+    (
+        "execution_state_hash",
+        Layout::Register(Some(debug::<Option<CryptoHash>>)),
+    ),
+    ("tip_state", Layout::Register(None)),
+    ("manager", Layout::Opaque),
+    ("pending_validated_blobs", Layout::Opaque),
+    ("pending_proposed_blobs", Layout::Collection),
+    ("confirmed_log", Layout::Log(Some(debug::<CryptoHash>))),
+    ("received_log", Layout::Log(Some(debug::<ChainAndHeight>))),
+    (
+        "received_certificate_trackers",
+        Layout::Register(Some(debug::<HashMap<ValidatorName, u64>>)),
+    ),
+    ("inboxes", Layout::Collection),
+    ("unskippable_bundles", Layout::Queue),
+    ("removed_unskippable_bundles", Layout::Map),
+    ("outboxes", Layout::Collection),
+    (
+        "outbox_counters",
+        Layout::Register(Some(debug::<BTreeMap<BlockHeight, u32>>)),
+    ),
+    ("channels", Layout::Collection),
+    ("committee_certificates", Layout::Map),
+    ("base_fee", Layout::Register(Some(debug::<Amount>))),
+];
+
+const EXECUTION_STATE: &[(&str, Layout)] = &[
+    ("system", Layout::Struct(SYSTEM_EXECUTION_STATE)),
+    ("users", Layout::Hashed(&Layout::Collection)),
+];
+
+const SYSTEM_EXECUTION_STATE: &[(&str, Layout)] = &[
+    (
+        "description",
+        Layout::Hashed(&Layout::Register(Some(debug::<Option<ChainDescription>>))),
+    ),
+    (
+        "epoch",
+        Layout::Hashed(&Layout::Register(Some(debug::<Option<Epoch>>))),
+    ),
+    (
+        "admin_id",
+        Layout::Hashed(&Layout::Register(Some(debug::<Option<ChainId>>))),
+    ),
+    ("subscriptions", Layout::Hashed(&Layout::Map)),
+    (
+        "committees",
+        Layout::Hashed(&Layout::Register(Some(debug::<BTreeMap<Epoch, Committee>>))),
+    ),
+    (
+        "ownership",
+        Layout::Hashed(&Layout::Register(Some(debug::<ChainOwnership>))),
+    ),
+    (
+        "balance",
+        Layout::Hashed(&Layout::Register(Some(debug::<Amount>))),
+    ),
+    ("balances", Layout::Hashed(&Layout::Map)),
+    (
+        "timestamp",
+        Layout::Hashed(&Layout::Register(Some(debug::<Timestamp>))),
+    ),
+    ("registry", Layout::Opaque),
+    (
+        "closed",
+        Layout::Hashed(&Layout::Register(Some(debug::<bool>))),
+    ),
+    (
+        "application_permissions",
+        Layout::Hashed(&Layout::Register(Some(debug::<ApplicationPermissions>))),
+    ),
+    ("used_blobs", Layout::Hashed(&Layout::Map)),
+    (
+        "bytes_stored",
+        Layout::Hashed(&Layout::Register(Some(debug::<u64>))),
+    ),
+    ("bytes_stored_by_application", Layout::Hashed(&Layout::Map)),
+    (
+        "storage_rent_debt",
+        Layout::Hashed(&Layout::Register(Some(debug::<Amount>))),
+    ),
+    (
+        "storage_rent_due_by",
+        Layout::Hashed(&Layout::Register(Some(debug::<Option<Timestamp>>))),
+    ),
+    ("scheduled_callbacks", Layout::Hashed(&Layout::Map)),
+    (
+        "next_callback_index",
+        Layout::Hashed(&Layout::Register(Some(debug::<u64>))),
+    ),
+    ("scheduled_callback_counts", Layout::Hashed(&Layout::Map)),
+    ("latest_events", Layout::Hashed(&Layout::Map)),
+];
+
+fn debug<T: DeserializeOwned + Debug>(bytes: &[u8]) -> Option<String> {
+    let value = bcs::from_bytes::<T>(bytes).ok()?;
+    Some(format!("{value:?}"))
+}
+
+/// An entry of a chain state, with its key decoded into a path.
+pub struct ChainStateEntry {
+    /// The path of the entry in the chain state, e.g. `tip_state` or `confirmed_log[3]`.
+    /// The parts of the key that could not be decoded are shown in hexadecimal, and fields
+    /// of unknown views by their index, e.g. `#3`.
+    pub path: String,
+    /// The value, decoded if its type is known, or in hexadecimal otherwise.
+    pub value: String,
+}
+
+impl ChainStateEntry {
+    /// Decodes a raw entry of the state of a chain.
+    pub fn decode(key: &[u8], value: &[u8]) -> Self {
+        let mut path = String::new();
+        let value = decode_entry(Layout::Struct(CHAIN_STATE), key, value, &mut path);
+        ChainStateEntry { path, value }
+    }
+}
+
+impl fmt::Display for ChainStateEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.path, self.value)
+    }
+}
+
+/// Appends the decoded `key` of a view with the given `layout` to `path`, and returns the
+/// decoded `value`.
+fn decode_entry(layout: Layout, key: &[u8], value: &[u8], path: &mut String) -> String {
+    let tag = key.first().and_then(|tag| tag.checked_sub(MIN_VIEW_TAG));
+    match (layout, tag) {
+        (Layout::Struct(fields), Some(0)) if key.len() >= 5 => {
+            let index = i32::from_le_bytes(key[1..5].try_into().unwrap());
+            let field = usize::try_from(index)
+                .ok()
+                .and_then(|index| fields.get(index));
+            if !path.is_empty() {
+                path.push('.');
+            }
+            let Some((name, layout)) = field else {
+                // A field that is missing from the layouts above.
+                path.push_str(&format!("#{index}"));
+                return decode_entry(Layout::Opaque, &key[5..], value, path);
+            };
+            path.push_str(name);
+            return decode_entry(*layout, &key[5..], value, path);
+        }
+        (Layout::Hashed(layout), Some(0)) => {
+            return decode_entry(*layout, &key[1..], value, path);
+        }
+        (Layout::Hashed(_), Some(1)) if key.len() == 1 => {
+            path.push_str(".hash");
+            return debug::<CryptoHash>(value).unwrap_or_else(|| hex(value));
+        }
+        (Layout::Register(decode), _) if key.is_empty() => {
+            return decode
+                .and_then(|decode| decode(value))
+                .unwrap_or_else(|| hex(value));
+        }
+        (Layout::Log(_), Some(0)) if key.len() == 1 => {
+            path.push_str(".count");
+            return debug::<u64>(value).unwrap_or_else(|| hex(value));
+        }
+        (Layout::Log(decode), Some(1)) if key.len() == 9 => {
+            let index = u64::from_le_bytes(key[1..].try_into().unwrap());
+            path.push_str(&format!("[{index}]"));
+            return decode
+                .and_then(|decode| decode(value))
+                .unwrap_or_else(|| hex(value));
+        }
+        (Layout::Queue, Some(0)) if key.len() == 1 => {
+            path.push_str(".stored_indices");
+            return debug::<Range<u64>>(value).unwrap_or_else(|| hex(value));
+        }
+        (Layout::Queue, Some(1)) if key.len() == 9 => {
+            let index = u64::from_le_bytes(key[1..].try_into().unwrap());
+            path.push_str(&format!("[{index}]"));
+            return hex(value);
+        }
+        (Layout::Map, _) if !key.is_empty() => {
+            path.push_str(&format!("[{}]", hex(key)));
+            return hex(value);
+        }
+        (Layout::Collection, Some(0)) => {
+            path.push_str(&format!(".indices[{}]", hex(&key[1..])));
+            return hex(value);
+        }
+        (Layout::Collection, Some(1)) => {
+            path.push_str(&format!(".subviews/{}", hex(&key[1..])));
+            return hex(value);
+        }
+        _ => {}
+    }
+    if !key.is_empty() {
+        path.push('/');
+        path.push_str(&hex(key));
+    }
+    hex(value)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
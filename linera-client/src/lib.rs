@@ -6,12 +6,19 @@
 #![recursion_limit = "256"]
 #![deny(clippy::large_futures)]
 
+#[cfg(feature = "benchmark")]
+pub mod benchmark;
 pub mod chain_listener;
 pub mod client_context;
 pub mod client_options;
 pub mod config;
 mod error;
+pub mod inspect;
+#[cfg(feature = "fs")]
+pub mod payouts;
 pub mod persistent;
+#[cfg(feature = "fs")]
+pub mod profile;
 pub mod storage;
 pub mod util;
 pub mod wallet;
@@ -0,0 +1,149 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Batches of transfers read from a file, as sent by `linera transfer-batch`.
+//!
+//! A JSON file contains an array of `{"recipient": "<ACCOUNT>", "amount": "<AMOUNT>"}`
+//! objects. Any other file is read as CSV, with one `<ACCOUNT>,<AMOUNT>` line per transfer;
+//! empty lines, lines starting with `#` and a `recipient,amount` header are ignored.
+
+use std::{ops::Range, path::Path};
+
+use linera_base::{
+    data_types::Amount,
+    identifiers::{Account, Owner},
+};
+use linera_execution::{
+    system::{Recipient, SystemOperation},
+    Operation,
+};
+use serde::Deserialize;
+
+use crate::client_options::Error;
+
+/// A transfer of a batch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Payout {
+    /// The account receiving the tokens.
+    pub recipient: Account,
+    /// The amount to transfer.
+    pub amount: Amount,
+}
+
+/// A payout as written in a JSON file.
+#[derive(Deserialize)]
+struct PayoutRecord {
+    recipient: String,
+    amount: Amount,
+}
+
+impl Payout {
+    /// Reads the payouts listed in a JSON file if its extension is `json`, or in a CSV file
+    /// otherwise.
+    pub fn read(path: &Path) -> Result<Vec<Payout>, Error> {
+        let contents = fs_err::read_to_string(path)?;
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            Self::parse_json(&contents)
+        } else {
+            Self::parse_csv(&contents)
+        }
+    }
+
+    /// Parses the payouts of a CSV file.
+    pub fn parse_csv(contents: &str) -> Result<Vec<Payout>, Error> {
+        let mut payouts = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let position = format!("line {}", index + 1);
+            let Some((recipient, amount)) = line.split_once(',') else {
+                return Err(Error::InvalidPayout {
+                    position,
+                    reason: "expected `<ACCOUNT>,<AMOUNT>`".to_owned(),
+                });
+            };
+            let (recipient, amount) = (recipient.trim(), amount.trim());
+            if payouts.is_empty() && recipient == "recipient" && amount == "amount" {
+                continue;
+            }
+            let amount = amount.parse().map_err(|error| Error::InvalidPayout {
+                position: position.clone(),
+                reason: format!("invalid amount: {error}"),
+            })?;
+            payouts.push(Self::new(recipient, amount, position)?);
+        }
+        Ok(payouts)
+    }
+
+    /// Parses the payouts of a JSON file.
+    pub fn parse_json(contents: &str) -> Result<Vec<Payout>, Error> {
+        let records: Vec<PayoutRecord> = serde_json::from_str(contents)?;
+        records
+            .into_iter()
+            .enumerate()
+            .map(|(index, record)| {
+                let position = format!("entry {}", index + 1);
+                Self::new(&record.recipient, record.amount, position)
+            })
+            .collect()
+    }
+
+    /// Returns the operation making this transfer from the given owner's account, or from the
+    /// chain's balance.
+    pub fn operation(&self, owner: Option<Owner>) -> Operation {
+        Operation::System(SystemOperation::Transfer {
+            owner,
+            recipient: Recipient::Account(self.recipient),
+            amount: self.amount,
+        })
+    }
+
+    fn new(recipient: &str, amount: Amount, position: String) -> Result<Payout, Error> {
+        let recipient = recipient.parse().map_err(|error| Error::InvalidPayout {
+            position: position.clone(),
+            reason: format!("invalid recipient: {error}"),
+        })?;
+        if amount == Amount::ZERO {
+            return Err(Error::InvalidPayout {
+                position,
+                reason: "the amount must not be zero".to_owned(),
+            });
+        }
+        Ok(Payout { recipient, amount })
+    }
+}
+
+/// Splits the payouts into consecutive batches of at most `max_transfers` transfers, whose
+/// operations take at most `max_size` bytes. Each transfer is counted twice, since its block
+/// also contains the message crediting the recipient.
+pub fn batches(
+    payouts: &[Payout],
+    owner: Option<Owner>,
+    max_transfers: usize,
+    max_size: u64,
+) -> Vec<Range<usize>> {
+    let max_transfers = max_transfers.max(1);
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut size = 0;
+    for (index, payout) in payouts.iter().enumerate() {
+        let operation_size = bcs::serialized_size(&payout.operation(owner))
+            .expect("transfer operations should be serializable");
+        let transfer_size = 2 * operation_size as u64;
+        if index > start && (index - start == max_transfers || size + transfer_size > max_size) {
+            batches.push(start..index);
+            start = index;
+            size = 0;
+        }
+        size += transfer_size;
+    }
+    if start < payouts.len() {
+        batches.push(start..payouts.len());
+    }
+    batches
+}
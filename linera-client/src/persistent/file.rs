@@ -2,10 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-    io::{self, BufRead as _, Write as _},
+    io::{Read as _, Write as _},
+    iter,
     path::Path,
 };
 
+use age::secrecy::SecretString;
 use fs4::FileExt as _;
 use thiserror_context::Context;
 
@@ -20,8 +22,17 @@ enum ErrorInner {
     IoError(#[from] std::io::Error),
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
+    #[error("encryption error: {0}")]
+    EncryptError(#[from] age::EncryptError),
+    #[error("decryption error: {0}")]
+    DecryptError(#[from] age::DecryptError),
+    #[error("the file is encrypted, but no passphrase was provided")]
+    MissingPassphrase,
 }
 
+/// The beginning of files encrypted with `age`.
+const ENCRYPTED_FILE_HEADER: &[u8] = b"age-encryption.org/v1";
+
 thiserror_context::impl_context!(Error(ErrorInner));
 
 /// Utility: run a fallible cleanup function if an operation failed, attaching the
@@ -79,10 +90,14 @@ impl Drop for Lock {
 /// An exclusive lock is taken using `flock(2)` to ensure that concurrent updates cannot
 /// happen, and writes are saved to a staging file before being moved over the old file,
 /// an operation that is atomic on all UNIXes.
+///
+/// If the file has a passphrase, its contents are encrypted with `age`, using a key derived
+/// from the passphrase.
 pub struct File<T> {
     _lock: Lock,
     path: std::path::PathBuf,
     value: T,
+    passphrase: Option<SecretString>,
     dirty: Dirty,
 }
 
@@ -126,20 +141,36 @@ impl<T: serde::Serialize + serde::de::DeserializeOwned> File<T> {
             .with_context(|| format!("locking path {}", path.display()))?,
             path: path.into(),
             value,
+            passphrase: None,
             dirty: Dirty::new(true),
         };
         Ok(this)
     }
 
-    /// Reads the value from a file at `path`, returning an error if it does not exist.
+    /// Reads the value from a file at `path`, returning an error if it does not exist or if
+    /// it is encrypted.
     pub fn read(path: &Path) -> Result<Self, Error> {
-        Self::read_or_create(path, || {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("path does not exist: {}", path.display()),
-            )
-            .into())
-        })
+        Self::read_with_passphrase(path, || Err(ErrorInner::MissingPassphrase.into()))
+    }
+
+    /// Reads the value from a file at `path`, returning an error if it does not exist. If the
+    /// file is encrypted, the `passphrase` function is called to decrypt it, and the file
+    /// remains encrypted with the same passphrase when saved.
+    pub fn read_with_passphrase(
+        path: &Path,
+        passphrase: impl FnOnce() -> Result<SecretString, Error>,
+    ) -> Result<Self, Error> {
+        Self::open(
+            path,
+            || {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("path does not exist: {}", path.display()),
+                )
+                .into())
+            },
+            passphrase,
+        )
     }
 
     /// Reads the value from a file at `path`, calling the `value` function to create it
@@ -147,23 +178,51 @@ impl<T: serde::Serialize + serde::de::DeserializeOwned> File<T> {
     pub fn read_or_create(
         path: &Path,
         value: impl FnOnce() -> Result<T, Error>,
+    ) -> Result<Self, Error> {
+        Self::open(path, value, || Err(ErrorInner::MissingPassphrase.into()))
+    }
+
+    fn open(
+        path: &Path,
+        value: impl FnOnce() -> Result<T, Error>,
+        passphrase: impl FnOnce() -> Result<SecretString, Error>,
     ) -> Result<Self, Error> {
         let lock = Lock::new(open_options().read(true).open(path)?)?;
-        let mut reader = io::BufReader::new(&lock.0);
-        let file_is_empty = reader.fill_buf()?.is_empty();
+        let mut contents = Vec::new();
+        (&lock.0).read_to_end(&mut contents)?;
+        let file_is_empty = contents.is_empty();
+
+        let (value, passphrase) = if file_is_empty {
+            (value()?, None)
+        } else if is_encrypted(&contents) {
+            let passphrase = passphrase()?;
+            let plaintext = decrypt(&contents, &passphrase)?;
+            (serde_json::from_slice(&plaintext)?, Some(passphrase))
+        } else {
+            (serde_json::from_slice(&contents)?, None)
+        };
 
         Ok(Self {
-            value: if file_is_empty {
-                value()?
-            } else {
-                serde_json::from_reader(reader)?
-            },
+            value,
+            passphrase,
             dirty: Dirty::new(file_is_empty),
             path: path.into(),
             _lock: lock,
         })
     }
 
+    /// Returns whether the file is saved encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.passphrase.is_some()
+    }
+
+    /// Sets the passphrase used to encrypt the file when it is saved, or saves it unencrypted
+    /// if `passphrase` is `None`.
+    pub fn set_passphrase(&mut self, passphrase: Option<SecretString>) {
+        self.passphrase = passphrase;
+        *self.dirty = true;
+    }
+
     fn save(&mut self) -> Result<(), Error> {
         let mut temp_file_path = self.path.clone();
         temp_file_path.set_extension("json.new");
@@ -172,11 +231,15 @@ impl<T: serde::Serialize + serde::de::DeserializeOwned> File<T> {
 
         let remove_temp_file = || fs_err::remove_file(&temp_file_path);
 
-        serde_json::to_writer_pretty(&mut temp_file_writer, &self.value)
+        let mut contents = serde_json::to_vec_pretty(&self.value)
             .map_err(Error::from)
             .or_cleanup(remove_temp_file)?;
+        if let Some(passphrase) = &self.passphrase {
+            contents = encrypt(&contents, passphrase).or_cleanup(remove_temp_file)?;
+        }
         temp_file_writer
-            .flush()
+            .write_all(&contents)
+            .and_then(|()| temp_file_writer.flush())
             .map_err(Error::from)
             .or_cleanup(remove_temp_file)?;
         fs_err::rename(&temp_file_path, &self.path)?;
@@ -185,6 +248,35 @@ impl<T: serde::Serialize + serde::de::DeserializeOwned> File<T> {
     }
 }
 
+/// Returns whether the `contents` of a file were encrypted by [`encrypt`].
+pub(crate) fn is_encrypted(contents: &[u8]) -> bool {
+    contents.starts_with(ENCRYPTED_FILE_HEADER)
+}
+
+/// Encrypts `plaintext` with a key derived from the `passphrase`.
+///
+/// The key is derived with the scrypt work factor of `age`, which is calibrated to take about a
+/// second on this machine.
+pub(crate) fn encrypt(plaintext: &[u8], passphrase: &SecretString) -> Result<Vec<u8>, Error> {
+    let recipient = age::scrypt::Recipient::new(passphrase.clone());
+    let encryptor = age::Encryptor::with_recipients(iter::once(&recipient as &dyn age::Recipient))?;
+    let mut ciphertext = Vec::new();
+    let mut writer = encryptor.wrap_output(&mut ciphertext)?;
+    writer.write_all(plaintext)?;
+    writer.finish()?;
+    Ok(ciphertext)
+}
+
+/// Decrypts the `ciphertext` produced by [`encrypt`] with the same `passphrase`.
+pub(crate) fn decrypt(ciphertext: &[u8], passphrase: &SecretString) -> Result<Vec<u8>, Error> {
+    let decryptor = age::Decryptor::new(ciphertext)?;
+    let identity = age::scrypt::Identity::new(passphrase.clone());
+    let mut reader = decryptor.decrypt(iter::once(&identity as &dyn age::Identity))?;
+    let mut plaintext = Vec::new();
+    reader.read_to_end(&mut plaintext)?;
+    Ok(plaintext)
+}
+
 impl<T: serde::Serialize + serde::de::DeserializeOwned + Send> Persist for File<T> {
     type Error = Error;
 
@@ -0,0 +1,94 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Named client profiles.
+//!
+//! A profile bundles the wallet, the storage, the faucet and the validator used to connect to
+//! one Linera network, so that users can switch between networks with
+//! `linera --profile <NAME>`. Each profile is a directory `profiles/<NAME>` of the Linera
+//! configuration directory, holding a `profile.json` file with its settings. The wallet and the
+//! storage default to files in that directory.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::client_options::Error;
+
+/// The name of the file holding the settings of a profile, in its directory.
+const PROFILE_FILE_NAME: &str = "profile.json";
+
+/// The settings of a profile.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Profile {
+    /// The wallet of the profile, if not in the directory of the profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet: Option<PathBuf>,
+    /// The storage configuration of the profile, if not in the directory of the profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<String>,
+    /// The address of the faucet used to initialize the wallet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faucet: Option<String>,
+    /// The public address of the validator, i.e. of its proxy, that is queried and
+    /// synchronized when no other address is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator: Option<String>,
+}
+
+impl Profile {
+    /// Returns the directory of the profile `name` in the configuration directory
+    /// `config_dir`.
+    pub fn directory(config_dir: &Path, name: &str) -> Result<PathBuf, Error> {
+        let is_valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !is_valid {
+            return Err(Error::InvalidProfileName(name.to_owned()));
+        }
+        Ok(config_dir.join("profiles").join(name))
+    }
+
+    /// Reads the settings of the profile `name`, or returns `None` if it doesn't exist.
+    pub fn read(config_dir: &Path, name: &str) -> Result<Option<Self>, Error> {
+        let path = Self::directory(config_dir, name)?.join(PROFILE_FILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs_err::read(&path)?;
+        Ok(Some(serde_json::from_slice(&contents)?))
+    }
+
+    /// Creates the profile `name` with these settings. Fails if it already exists.
+    pub fn create(&self, config_dir: &Path, name: &str) -> Result<(), Error> {
+        let directory = Self::directory(config_dir, name)?;
+        let path = directory.join(PROFILE_FILE_NAME);
+        if path.exists() {
+            return Err(Error::ProfileAlreadyExists(name.to_owned()));
+        }
+        fs_err::create_dir_all(&directory)?;
+        fs_err::write(&path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Returns the names of the profiles in the configuration directory `config_dir`, in
+    /// alphabetical order.
+    pub fn list(config_dir: &Path) -> Result<Vec<String>, Error> {
+        let profiles_dir = config_dir.join("profiles");
+        if !profiles_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names = Vec::new();
+        for entry in fs_err::read_dir(&profiles_dir)? {
+            let entry = entry?;
+            if entry.path().join(PROFILE_FILE_NAME).exists() {
+                if let Some(name) = entry.file_name().to_str() {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
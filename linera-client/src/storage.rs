@@ -4,10 +4,12 @@
 use std::{fmt, str::FromStr};
 
 use async_trait::async_trait;
-use linera_base::identifiers::BlobId;
-use linera_execution::WasmRuntime;
+use linera_base::identifiers::{BlobId, ChainId};
+use linera_execution::{ExecutionRuntimeConfig, NativeApplicationRegistry, WasmRuntime};
 #[cfg(with_storage)]
-use linera_storage::list_all_blob_ids;
+use linera_storage::{
+    delete_chain_namespace, list_all_blob_ids, list_chain_ids, read_chain_state_entries,
+};
 use linera_storage::{DbStorage, Storage};
 #[cfg(feature = "storage-service")]
 use linera_storage_service::{
@@ -15,7 +17,10 @@ use linera_storage_service::{
     common::{ServiceStoreConfig, ServiceStoreInternalConfig},
 };
 #[cfg(feature = "dynamodb")]
-use linera_views::dynamo_db::{get_config, DynamoDbStore, DynamoDbStoreConfig};
+use linera_views::dynamo_db::{
+    get_config, DynamoDbConsistency, DynamoDbFailover, DynamoDbRetryConfig, DynamoDbStore,
+    DynamoDbStoreConfig, ReadConsistency,
+};
 #[cfg(with_storage)]
 use linera_views::store::LocalAdminKeyValueStore as _;
 use linera_views::{
@@ -105,6 +110,10 @@ pub enum StorageConfig {
     DynamoDb {
         /// Whether to use the localstack system
         use_localstack: bool,
+        /// The regions of the replicas of the global tables, by order of preference
+        regions: Vec<String>,
+        /// The other options of the store
+        options: DynamoDbOptions,
     },
     /// The ScyllaDb description
     #[cfg(feature = "scylladb")]
@@ -114,6 +123,99 @@ pub enum StorageConfig {
     },
 }
 
+/// The options of the DynamoDB storage, given as `KEY=VALUE` parts of its description.
+#[cfg(feature = "dynamodb")]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(any(test), derive(Eq, PartialEq))]
+pub struct DynamoDbOptions {
+    /// The failover of the reads to the other regions
+    pub failover: DynamoDbFailover,
+    /// The consistency of the reads
+    pub consistency: DynamoDbConsistency,
+    /// The retries of the throttled requests
+    pub retries: DynamoDbRetryConfig,
+}
+
+#[cfg(feature = "dynamodb")]
+impl DynamoDbOptions {
+    /// Sets the option `key` to `value`.
+    fn set(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        let invalid = || Error::Format(format!("Invalid value {value:?} of DynamoDB option {key}"));
+        let consistency = || match value {
+            "eventual" => Ok(ReadConsistency::Eventual),
+            "strong" => Ok(ReadConsistency::Strong),
+            _ => Err(invalid()),
+        };
+        match key {
+            "read_failover" => self.failover.reads = value.parse().map_err(|_| invalid())?,
+            "failover_cooldown" => {
+                let seconds = value.parse().map_err(|_| invalid())?;
+                self.failover.cooldown = std::time::Duration::from_secs(seconds);
+            }
+            "get_item_consistency" => self.consistency.get_item = consistency()?,
+            "query_consistency" => self.consistency.query = consistency()?,
+            "max_attempts" => self.retries.max_attempts = value.parse().map_err(|_| invalid())?,
+            "initial_backoff_ms" => {
+                let millis = value.parse().map_err(|_| invalid())?;
+                self.retries.initial_backoff = std::time::Duration::from_millis(millis);
+            }
+            "max_backoff_ms" => {
+                let millis = value.parse().map_err(|_| invalid())?;
+                self.retries.max_backoff = std::time::Duration::from_millis(millis);
+            }
+            _ => {
+                return Err(Error::Format(format!(
+                    "Unknown DynamoDB option {key}. Expected one of read_failover, \
+                    failover_cooldown, get_item_consistency, query_consistency, max_attempts, \
+                    initial_backoff_ms or max_backoff_ms"
+                )))
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "dynamodb")]
+impl fmt::Display for DynamoDbOptions {
+    /// Writes the options that differ from their default, each preceded by `:`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let default = DynamoDbOptions::default();
+        if self.failover.reads != default.failover.reads {
+            write!(f, ":read_failover={}", self.failover.reads)?;
+        }
+        if self.failover.cooldown != default.failover.cooldown {
+            write!(f, ":failover_cooldown={}", self.failover.cooldown.as_secs())?;
+        }
+        let consistency = |consistency: ReadConsistency| match consistency {
+            ReadConsistency::Eventual => "eventual",
+            ReadConsistency::Strong => "strong",
+        };
+        if self.consistency.get_item != default.consistency.get_item {
+            let value = consistency(self.consistency.get_item);
+            write!(f, ":get_item_consistency={value}")?;
+        }
+        if self.consistency.query != default.consistency.query {
+            let value = consistency(self.consistency.query);
+            write!(f, ":query_consistency={value}")?;
+        }
+        if self.retries.max_attempts != default.retries.max_attempts {
+            write!(f, ":max_attempts={}", self.retries.max_attempts)?;
+        }
+        if self.retries.initial_backoff != default.retries.initial_backoff {
+            let millis = self.retries.initial_backoff.as_millis();
+            write!(f, ":initial_backoff_ms={millis}")?;
+        }
+        if self.retries.max_backoff != default.retries.max_backoff {
+            write!(
+                f,
+                ":max_backoff_ms={}",
+                self.retries.max_backoff.as_millis()
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// The `root_key` used at startup before the `clone_with_root_key`.
 const ROOT_KEY: &[u8] = &[0];
 
@@ -237,7 +339,7 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
         }
         #[cfg(feature = "dynamodb")]
         if let Some(s) = input.strip_prefix(DYNAMO_DB) {
-            let mut parts = s.splitn(2, ':');
+            let mut parts = s.split(':');
             let namespace = parts
                 .next()
                 .ok_or_else(|| {
@@ -252,11 +354,38 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
                 Some(unknown) => {
                     return Err(Error::Format(format!(
                         "Invalid DynamoDB endpoint {unknown:?}. \
-                        Expected {DYNAMO_DB}TABLE:[env|localstack]"
+                        Expected {DYNAMO_DB}TABLE:[env|localstack][:REGION,...][:KEY=VALUE]..."
                     )));
                 }
             };
-            let storage_config = StorageConfig::DynamoDb { use_localstack };
+            let mut parts = parts.peekable();
+            let regions = match parts.next_if(|part| !part.contains('=')) {
+                None => Vec::new(),
+                Some(regions) => {
+                    let regions = regions.split(',').map(str::to_string).collect::<Vec<_>>();
+                    if regions.iter().any(String::is_empty) {
+                        return Err(Error::Format(format!(
+                            "Invalid DynamoDB regions {regions:?}. \
+                            Expected {DYNAMO_DB}TABLE:[env|localstack][:REGION,...][:KEY=VALUE]..."
+                        )));
+                    }
+                    regions
+                }
+            };
+            let mut options = DynamoDbOptions::default();
+            for part in parts {
+                let (key, value) = part.split_once('=').ok_or_else(|| {
+                    Error::Format(format!(
+                        "Invalid DynamoDB option {part:?}. Expected KEY=VALUE"
+                    ))
+                })?;
+                options.set(key, value)?;
+            }
+            let storage_config = StorageConfig::DynamoDb {
+                use_localstack,
+                regions,
+                options,
+            };
             return Ok(StorageConfigNamespace {
                 storage_config,
                 namespace,
@@ -365,9 +494,17 @@ impl StorageConfigNamespace {
                 Ok(StoreConfig::RocksDb(config, namespace))
             }
             #[cfg(feature = "dynamodb")]
-            StorageConfig::DynamoDb { use_localstack } => {
+            StorageConfig::DynamoDb {
+                use_localstack,
+                regions,
+                options,
+            } => {
                 let aws_config = get_config(*use_localstack).await?;
-                let config = DynamoDbStoreConfig::new(aws_config, common_config);
+                let config = DynamoDbStoreConfig::new(aws_config, common_config)
+                    .with_regions(regions.clone())
+                    .with_failover(options.failover)
+                    .with_consistency(options.consistency)
+                    .with_retries(options.retries);
                 Ok(StoreConfig::DynamoDb(config, namespace))
             }
             #[cfg(feature = "scylladb")]
@@ -399,10 +536,20 @@ impl fmt::Display for StorageConfigNamespace {
                 write!(f, "rocksdb:{}:{}:{}", path.display(), spawn_mode, namespace)
             }
             #[cfg(feature = "dynamodb")]
-            StorageConfig::DynamoDb { use_localstack } => match use_localstack {
-                true => write!(f, "dynamodb:{}:localstack", namespace),
-                false => write!(f, "dynamodb:{}:env", namespace),
-            },
+            StorageConfig::DynamoDb {
+                use_localstack,
+                regions,
+                options,
+            } => {
+                match use_localstack {
+                    true => write!(f, "dynamodb:{}:localstack", namespace)?,
+                    false => write!(f, "dynamodb:{}:env", namespace)?,
+                }
+                if !regions.is_empty() {
+                    write!(f, ":{}", regions.join(","))?;
+                }
+                write!(f, "{options}")
+            }
             #[cfg(feature = "scylladb")]
             StorageConfig::ScyllaDb { uri } => {
                 write!(f, "scylladb:tcp:{}:{}", uri, namespace)
@@ -592,6 +739,152 @@ impl StoreConfig {
             }
         }
     }
+
+    /// Lists the chains that have a state in the storage.
+    pub async fn list_chain_ids(self) -> Result<Vec<ChainId>, ViewError> {
+        match self {
+            StoreConfig::Memory(_, _) => Err(ViewError::StoreError {
+                backend: "memory".to_string(),
+                error: "list_chain_ids is not supported for the memory storage".to_string(),
+            }),
+            #[cfg(feature = "storage-service")]
+            StoreConfig::Service(config, namespace) => {
+                let store =
+                    ServiceStoreClient::maybe_create_and_connect(&config, &namespace, ROOT_KEY)
+                        .await?;
+                list_chain_ids(&store).await
+            }
+            #[cfg(feature = "rocksdb")]
+            StoreConfig::RocksDb(config, namespace) => {
+                let store =
+                    RocksDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
+                list_chain_ids(&store).await
+            }
+            #[cfg(feature = "dynamodb")]
+            StoreConfig::DynamoDb(config, namespace) => {
+                let store =
+                    DynamoDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
+                list_chain_ids(&store).await
+            }
+            #[cfg(feature = "scylladb")]
+            StoreConfig::ScyllaDb(config, namespace) => {
+                let store =
+                    ScyllaDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
+                list_chain_ids(&store).await
+            }
+        }
+    }
+
+    /// Registers the chains of a storage written before the chains were indexed. Returns the
+    /// chains that were registered.
+    pub async fn backfill_chain_indices(self) -> Result<Vec<ChainId>, ViewError> {
+        match self {
+            StoreConfig::Memory(_, _) => Err(ViewError::StoreError {
+                backend: "memory".to_string(),
+                error: "backfill_chain_indices is not supported for the memory storage".to_string(),
+            }),
+            #[cfg(feature = "storage-service")]
+            StoreConfig::Service(config, namespace) => {
+                let storage =
+                    DbStorage::<ServiceStoreClient, _>::new(config, &namespace, ROOT_KEY, None)
+                        .await?;
+                storage.backfill_chain_indices().await
+            }
+            #[cfg(feature = "rocksdb")]
+            StoreConfig::RocksDb(config, namespace) => {
+                let storage =
+                    DbStorage::<RocksDbStore, _>::new(config, &namespace, ROOT_KEY, None).await?;
+                storage.backfill_chain_indices().await
+            }
+            #[cfg(feature = "dynamodb")]
+            StoreConfig::DynamoDb(config, namespace) => {
+                let storage =
+                    DbStorage::<DynamoDbStore, _>::new(config, &namespace, ROOT_KEY, None).await?;
+                storage.backfill_chain_indices().await
+            }
+            #[cfg(feature = "scylladb")]
+            StoreConfig::ScyllaDb(config, namespace) => {
+                let storage =
+                    DbStorage::<ScyllaDbStore, _>::new(config, &namespace, ROOT_KEY, None).await?;
+                storage.backfill_chain_indices().await
+            }
+        }
+    }
+
+    /// Deletes the whole state of a chain.
+    pub async fn delete_chain_namespace(self, chain_id: ChainId) -> Result<(), ViewError> {
+        match self {
+            StoreConfig::Memory(_, _) => Err(ViewError::StoreError {
+                backend: "memory".to_string(),
+                error: "delete_chain_namespace is not supported for the memory storage".to_string(),
+            }),
+            #[cfg(feature = "storage-service")]
+            StoreConfig::Service(config, namespace) => {
+                let store =
+                    ServiceStoreClient::maybe_create_and_connect(&config, &namespace, ROOT_KEY)
+                        .await?;
+                delete_chain_namespace(&store, chain_id).await
+            }
+            #[cfg(feature = "rocksdb")]
+            StoreConfig::RocksDb(config, namespace) => {
+                let store =
+                    RocksDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
+                delete_chain_namespace(&store, chain_id).await
+            }
+            #[cfg(feature = "dynamodb")]
+            StoreConfig::DynamoDb(config, namespace) => {
+                let store =
+                    DynamoDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
+                delete_chain_namespace(&store, chain_id).await
+            }
+            #[cfg(feature = "scylladb")]
+            StoreConfig::ScyllaDb(config, namespace) => {
+                let store =
+                    ScyllaDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
+                delete_chain_namespace(&store, chain_id).await
+            }
+        }
+    }
+
+    /// Reads the raw entries of the state of a chain whose keys start with `key_prefix`.
+    pub async fn read_chain_state_entries(
+        self,
+        chain_id: ChainId,
+        key_prefix: &[u8],
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewError> {
+        match self {
+            StoreConfig::Memory(_, _) => Err(ViewError::StoreError {
+                backend: "memory".to_string(),
+                error: "read_chain_state_entries is not supported for the memory storage"
+                    .to_string(),
+            }),
+            #[cfg(feature = "storage-service")]
+            StoreConfig::Service(config, namespace) => {
+                let store =
+                    ServiceStoreClient::maybe_create_and_connect(&config, &namespace, ROOT_KEY)
+                        .await?;
+                read_chain_state_entries(&store, chain_id, key_prefix).await
+            }
+            #[cfg(feature = "rocksdb")]
+            StoreConfig::RocksDb(config, namespace) => {
+                let store =
+                    RocksDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
+                read_chain_state_entries(&store, chain_id, key_prefix).await
+            }
+            #[cfg(feature = "dynamodb")]
+            StoreConfig::DynamoDb(config, namespace) => {
+                let store =
+                    DynamoDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
+                read_chain_state_entries(&store, chain_id, key_prefix).await
+            }
+            #[cfg(feature = "scylladb")]
+            StoreConfig::ScyllaDb(config, namespace) => {
+                let store =
+                    ScyllaDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
+                read_chain_state_entries(&store, chain_id, key_prefix).await
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -612,6 +905,8 @@ pub async fn run_with_storage<Job>(
     config: StoreConfig,
     genesis_config: &GenesisConfig,
     wasm_runtime: Option<WasmRuntime>,
+    execution_runtime_config: ExecutionRuntimeConfig,
+    native_applications: NativeApplicationRegistry,
     job: Job,
 ) -> Result<Job::Output, Error>
 where
@@ -622,7 +917,9 @@ where
             let store_config = MemoryStoreConfig::new(config.common_config.max_stream_queries);
             let mut storage =
                 DbStorage::<MemoryStore, _>::new(store_config, &namespace, ROOT_KEY, wasm_runtime)
-                    .await?;
+                    .await?
+                    .with_execution_runtime_config(execution_runtime_config)
+                    .with_native_applications(native_applications);
             genesis_config.initialize_storage(&mut storage).await?;
             Ok(job.run(storage).await)
         }
@@ -630,28 +927,36 @@ where
         StoreConfig::Service(config, namespace) => {
             let storage =
                 DbStorage::<ServiceStoreClient, _>::new(config, &namespace, ROOT_KEY, wasm_runtime)
-                    .await?;
+                    .await?
+                    .with_execution_runtime_config(execution_runtime_config)
+                    .with_native_applications(native_applications);
             Ok(job.run(storage).await)
         }
         #[cfg(feature = "rocksdb")]
         StoreConfig::RocksDb(config, namespace) => {
             let storage =
                 DbStorage::<RocksDbStore, _>::new(config, &namespace, ROOT_KEY, wasm_runtime)
-                    .await?;
+                    .await?
+                    .with_execution_runtime_config(execution_runtime_config)
+                    .with_native_applications(native_applications);
             Ok(job.run(storage).await)
         }
         #[cfg(feature = "dynamodb")]
         StoreConfig::DynamoDb(config, namespace) => {
             let storage =
                 DbStorage::<DynamoDbStore, _>::new(config, &namespace, ROOT_KEY, wasm_runtime)
-                    .await?;
+                    .await?
+                    .with_execution_runtime_config(execution_runtime_config)
+                    .with_native_applications(native_applications);
             Ok(job.run(storage).await)
         }
         #[cfg(feature = "scylladb")]
         StoreConfig::ScyllaDb(config, namespace) => {
             let storage =
                 DbStorage::<ScyllaDbStore, _>::new(config, &namespace, ROOT_KEY, wasm_runtime)
-                    .await?;
+                    .await?
+                    .with_execution_runtime_config(execution_runtime_config)
+                    .with_native_applications(native_applications);
             Ok(job.run(storage).await)
         }
     }
@@ -791,7 +1096,9 @@ fn test_aws_storage_config_from_str() {
         StorageConfigNamespace::from_str("dynamodb:table").unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::DynamoDb {
-                use_localstack: false
+                use_localstack: false,
+                regions: Vec::new(),
+                options: DynamoDbOptions::default(),
             },
             namespace: "table".to_string()
         }
@@ -800,7 +1107,9 @@ fn test_aws_storage_config_from_str() {
         StorageConfigNamespace::from_str("dynamodb:table:env").unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::DynamoDb {
-                use_localstack: false
+                use_localstack: false,
+                regions: Vec::new(),
+                options: DynamoDbOptions::default(),
             },
             namespace: "table".to_string()
         }
@@ -809,15 +1118,79 @@ fn test_aws_storage_config_from_str() {
         StorageConfigNamespace::from_str("dynamodb:table:localstack").unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::DynamoDb {
-                use_localstack: true
+                use_localstack: true,
+                regions: Vec::new(),
+                options: DynamoDbOptions::default(),
+            },
+            namespace: "table".to_string()
+        }
+    );
+    assert_eq!(
+        StorageConfigNamespace::from_str("dynamodb:table:env:us-east-1,eu-west-1").unwrap(),
+        StorageConfigNamespace {
+            storage_config: StorageConfig::DynamoDb {
+                use_localstack: false,
+                regions: vec!["us-east-1".to_string(), "eu-west-1".to_string()],
+                options: DynamoDbOptions::default(),
+            },
+            namespace: "table".to_string()
+        }
+    );
+    let options = DynamoDbOptions {
+        failover: DynamoDbFailover {
+            reads: false,
+            cooldown: std::time::Duration::from_secs(30),
+        },
+        consistency: DynamoDbConsistency {
+            get_item: ReadConsistency::Strong,
+            query: ReadConsistency::Eventual,
+        },
+        retries: DynamoDbRetryConfig {
+            max_attempts: 5,
+            initial_backoff: std::time::Duration::from_millis(50),
+            ..DynamoDbRetryConfig::default()
+        },
+    };
+    let input = "dynamodb:table:env:us-east-1,eu-west-1:read_failover=false:failover_cooldown=30:\
+        get_item_consistency=strong:max_attempts=5:initial_backoff_ms=50";
+    let config = StorageConfigNamespace::from_str(input).unwrap();
+    assert_eq!(
+        config,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::DynamoDb {
+                use_localstack: false,
+                regions: vec!["us-east-1".to_string(), "eu-west-1".to_string()],
+                options: options.clone(),
             },
             namespace: "table".to_string()
         }
     );
+    assert_eq!(config.to_string(), input);
+    assert_eq!(
+        StorageConfigNamespace::from_str("dynamodb:table:localstack:query_consistency=strong")
+            .unwrap()
+            .storage_config,
+        StorageConfig::DynamoDb {
+            use_localstack: true,
+            regions: Vec::new(),
+            options: DynamoDbOptions {
+                consistency: DynamoDbConsistency {
+                    get_item: ReadConsistency::Eventual,
+                    query: ReadConsistency::Strong,
+                },
+                ..DynamoDbOptions::default()
+            },
+        }
+    );
     assert!(StorageConfigNamespace::from_str("dynamodb").is_err());
     assert!(StorageConfigNamespace::from_str("dynamodb:").is_err());
     assert!(StorageConfigNamespace::from_str("dynamodb:1").is_err());
     assert!(StorageConfigNamespace::from_str("dynamodb:wrong:endpoint").is_err());
+    assert!(StorageConfigNamespace::from_str("dynamodb:table:env:").is_err());
+    assert!(StorageConfigNamespace::from_str("dynamodb:table:env:eu-west-1:unknown=1").is_err());
+    assert!(StorageConfigNamespace::from_str("dynamodb:table:env:read_failover=maybe").is_err());
+    assert!(StorageConfigNamespace::from_str("dynamodb:table:env:max_attempts=-1").is_err());
+    assert!(StorageConfigNamespace::from_str("dynamodb:table:env:eu-west-1:us-east-1").is_err());
 }
 
 #[cfg(feature = "scylladb")]
@@ -0,0 +1,54 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::time::Duration;
+
+use crate::benchmark::{BenchmarkOperation, LatencyReport, OperationMix};
+
+#[test]
+fn test_operation_mix() {
+    use BenchmarkOperation::{FungibleTransfer, NativeTransfer};
+
+    let mix = "native-transfer=3, fungible-transfer"
+        .parse::<OperationMix>()
+        .unwrap();
+    let operations = (0..8).map(|index| mix.operation(index)).collect::<Vec<_>>();
+    assert_eq!(
+        operations,
+        [
+            NativeTransfer,
+            FungibleTransfer,
+            NativeTransfer,
+            NativeTransfer,
+            NativeTransfer,
+            FungibleTransfer,
+            NativeTransfer,
+            NativeTransfer,
+        ]
+    );
+    assert!(mix.contains(FungibleTransfer));
+    assert_eq!(
+        "native-transfer".parse::<OperationMix>().unwrap(),
+        OperationMix::single(NativeTransfer)
+    );
+
+    assert!("".parse::<OperationMix>().is_err());
+    assert!("burn".parse::<OperationMix>().is_err());
+    assert!("native-transfer=0".parse::<OperationMix>().is_err());
+    assert!("native-transfer=1001".parse::<OperationMix>().is_err());
+}
+
+#[test]
+fn test_latency_percentiles() {
+    let latencies = (1..=100).rev().map(Duration::from_millis).collect();
+    let report = LatencyReport::new(latencies, 200, 0, Duration::from_secs(2));
+    assert_eq!(report.percentile(50), Some(Duration::from_millis(51)));
+    assert_eq!(report.percentile(99), Some(Duration::from_millis(100)));
+    assert_eq!(report.percentile(100), Some(Duration::from_millis(100)));
+    assert_eq!(report.percentile(0), Some(Duration::from_millis(1)));
+    let summary = report.to_string();
+    assert!(summary.contains("100.0 transactions/s"), "{summary}");
+
+    let empty = LatencyReport::new(Vec::new(), 0, 3, Duration::from_secs(1));
+    assert_eq!(empty.percentile(50), None);
+}
@@ -0,0 +1,227 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{
+    crypto::{CryptoHash, PublicKey},
+    data_types::{Amount, ApplicationPermissions, BlockHeight, Timestamp},
+    identifiers::{
+        AccountOwner, ApplicationId, BlobId, BlobType, BytecodeId, ChainDescription, ChainId,
+        ChannelName, GenericApplicationId, MessageId, StreamId, StreamName,
+    },
+    ownership::ChainOwnership,
+};
+use linera_chain::ChainStateView;
+use linera_execution::{
+    committee::{Epoch, ValidatorName},
+    system::{CallbackKey, ScheduledCallback},
+    ChannelSubscription, ExecutionRuntimeConfig, ExecutionStateView, SystemExecutionStateView,
+    TestExecutionRuntimeContext,
+};
+use linera_views::{
+    context::{Context as _, MemoryContext},
+    memory::TEST_MEMORY_MAX_STREAM_QUERIES,
+    random::generate_test_namespace,
+    store::KeyValueIterable as _,
+    views::{RootView as _, View as _},
+};
+
+use crate::inspect::ChainStateEntry;
+
+#[tokio::test]
+async fn test_decode_chain_state_entries() -> anyhow::Result<()> {
+    let chain_id = ChainId::root(0);
+    let extra = TestExecutionRuntimeContext::new(chain_id, ExecutionRuntimeConfig::default());
+    let namespace = generate_test_namespace();
+    let context =
+        MemoryContext::new_for_testing(TEST_MEMORY_MAX_STREAM_QUERIES, &namespace, &[], extra);
+    let mut chain = ChainStateView::load(context.clone()).await?;
+    let hash = CryptoHash::test_hash("block");
+    chain
+        .execution_state
+        .system
+        .balance
+        .set(Amount::from_tokens(3));
+    chain.confirmed_log.push(hash);
+    chain.outbox_counters.get_mut().insert(BlockHeight(1), 2);
+    chain.save().await?;
+
+    let mut entries = Vec::new();
+    let key_values = context.find_key_values_by_prefix(&[]).await?;
+    for entry in key_values.into_iterator_owned() {
+        let (key, value) = entry?;
+        entries.push(ChainStateEntry::decode(&key, &value).to_string());
+    }
+    let expected = [
+        format!(
+            "execution_state.system.balance = {:?}",
+            Amount::from_tokens(3)
+        ),
+        "confirmed_log.count = 1".to_string(),
+        format!("confirmed_log[0] = {hash:?}"),
+        "outbox_counters = {BlockHeight(1): 2}".to_string(),
+    ];
+    for entry in expected {
+        assert!(entries.contains(&entry), "{entry:?} not in {entries:#?}");
+    }
+    Ok(())
+}
+
+/// Writes every field of a chain state that holds data directly, and checks that all of them
+/// are in the layouts of the decoder. The views are destructured without `..`, so that adding
+/// a field to them fails to compile until it is written here.
+#[tokio::test]
+async fn test_all_chain_state_fields_are_decoded() -> anyhow::Result<()> {
+    let chain_id = ChainId::root(0);
+    let extra = TestExecutionRuntimeContext::new(chain_id, ExecutionRuntimeConfig::default());
+    let namespace = generate_test_namespace();
+    let context =
+        MemoryContext::new_for_testing(TEST_MEMORY_MAX_STREAM_QUERIES, &namespace, &[], extra);
+    let mut chain = ChainStateView::load(context.clone()).await?;
+    let hash = CryptoHash::test_hash("hash");
+    let owner = PublicKey::test_key(0).into();
+    let application_id = ApplicationId {
+        bytecode_id: BytecodeId::new(hash, hash),
+        creation: MessageId {
+            chain_id,
+            height: BlockHeight::ZERO,
+            index: 0,
+        },
+    };
+
+    let ChainStateView {
+        execution_state,
+        execution_state_hash,
+        tip_state,
+        manager: _,
+        pending_validated_blobs: _,
+        pending_proposed_blobs: _,
+        confirmed_log,
+        received_log: _,
+        received_certificate_trackers,
+        inboxes: _,
+        unskippable_bundles: _,
+        removed_unskippable_bundles: _,
+        outboxes: _,
+        outbox_counters,
+        channels: _,
+        committee_certificates,
+        base_fee,
+    } = &mut chain;
+    execution_state_hash.set(Some(hash));
+    tip_state.get_mut().next_block_height = BlockHeight(1);
+    confirmed_log.push(hash);
+    received_certificate_trackers
+        .get_mut()
+        .insert(ValidatorName(PublicKey::test_key(0)), 1);
+    outbox_counters.get_mut().insert(BlockHeight(1), 2);
+    committee_certificates.insert(&Epoch::ZERO, hash)?;
+    base_fee.set(Amount::ONE);
+
+    let ExecutionStateView { system, users: _ } = execution_state;
+    let SystemExecutionStateView {
+        description,
+        epoch,
+        admin_id,
+        subscriptions,
+        committees,
+        ownership,
+        balance,
+        balances,
+        timestamp,
+        registry: _,
+        closed,
+        application_permissions,
+        used_blobs,
+        bytes_stored,
+        bytes_stored_by_application,
+        storage_rent_debt,
+        storage_rent_due_by,
+        scheduled_callbacks,
+        next_callback_index,
+        scheduled_callback_counts,
+        latest_events,
+    } = system;
+    description.set(Some(ChainDescription::Root(0)));
+    epoch.set(Some(Epoch::ZERO));
+    admin_id.set(Some(chain_id));
+    subscriptions.insert(&ChannelSubscription {
+        chain_id,
+        name: ChannelName::from(b"channel".to_vec()),
+    })?;
+    committees.set(Default::default());
+    ownership.set(ChainOwnership::single(owner));
+    balance.set(Amount::ONE);
+    balances.insert(&AccountOwner::User(owner), Amount::ONE)?;
+    timestamp.set(Timestamp::from(1));
+    closed.set(true);
+    application_permissions.set(ApplicationPermissions::default());
+    used_blobs.insert(&BlobId::new(hash, BlobType::Data))?;
+    bytes_stored.set(1);
+    bytes_stored_by_application.insert(&application_id, 1)?;
+    storage_rent_debt.set(Amount::ONE);
+    storage_rent_due_by.set(Some(Timestamp::from(1)));
+    scheduled_callbacks.insert(
+        &CallbackKey {
+            timestamp: Timestamp::from(1),
+            index: 0,
+        },
+        ScheduledCallback {
+            application_id,
+            argument: Vec::new(),
+        },
+    )?;
+    next_callback_index.set(1);
+    scheduled_callback_counts.insert(&application_id, 1)?;
+    let stream_id = StreamId {
+        application_id: GenericApplicationId::User(application_id),
+        stream_name: StreamName(b"stream".to_vec()),
+    };
+    latest_events.insert(&(stream_id, b"key".to_vec()), b"value".to_vec())?;
+    chain.save().await?;
+
+    let mut paths = Vec::new();
+    let key_values = context.find_key_values_by_prefix(&[]).await?;
+    for entry in key_values.into_iterator_owned() {
+        let (key, value) = entry?;
+        let entry = ChainStateEntry::decode(&key, &value);
+        assert!(!entry.path.contains('#'), "unknown field in {entry}");
+        paths.push(entry.path);
+    }
+    for field in [
+        "execution_state_hash",
+        "tip_state",
+        "confirmed_log",
+        "received_certificate_trackers",
+        "outbox_counters",
+        "committee_certificates",
+        "base_fee",
+        "execution_state.system.description",
+        "execution_state.system.epoch",
+        "execution_state.system.admin_id",
+        "execution_state.system.subscriptions",
+        "execution_state.system.committees",
+        "execution_state.system.ownership",
+        "execution_state.system.balance",
+        "execution_state.system.balances",
+        "execution_state.system.timestamp",
+        "execution_state.system.closed",
+        "execution_state.system.application_permissions",
+        "execution_state.system.used_blobs",
+        "execution_state.system.bytes_stored",
+        "execution_state.system.bytes_stored_by_application",
+        "execution_state.system.storage_rent_debt",
+        "execution_state.system.storage_rent_due_by",
+        "execution_state.system.scheduled_callbacks",
+        "execution_state.system.next_callback_index",
+        "execution_state.system.scheduled_callback_counts",
+        "execution_state.system.latest_events",
+    ] {
+        assert!(
+            paths.iter().any(|path| path == field
+                || path.starts_with(&format!("{field}."))
+                || path.starts_with(&format!("{field}["))),
+            "{field:?} not in {paths:#?}"
+        );
+    }
+    Ok(())
+}
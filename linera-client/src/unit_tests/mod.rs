@@ -1,7 +1,14 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "benchmark")]
+mod benchmark;
 mod chain_listener;
+mod inspect;
+#[cfg(feature = "fs")]
+mod payouts;
+#[cfg(feature = "fs")]
+mod profile;
 mod util;
 #[cfg(feature = "fs")]
 mod wallet;
@@ -0,0 +1,83 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{
+    data_types::Amount,
+    identifiers::{Account, ChainId},
+};
+
+use crate::{
+    client_options::Error,
+    payouts::{self, Payout},
+};
+
+fn payout(index: u32, tokens: u128) -> Payout {
+    Payout {
+        recipient: Account::chain(ChainId::root(index)),
+        amount: Amount::from_tokens(tokens),
+    }
+}
+
+#[test]
+fn test_parse_payouts() -> anyhow::Result<()> {
+    let csv = format!(
+        "recipient,amount\n\
+         # First payouts\n\
+         {},1\n\
+         \n\
+         {} , 2.5\n",
+        ChainId::root(1),
+        ChainId::root(2),
+    );
+    let expected = vec![
+        payout(1, 1),
+        Payout {
+            recipient: Account::chain(ChainId::root(2)),
+            amount: Amount::from_millis(2500),
+        },
+    ];
+    assert_eq!(Payout::parse_csv(&csv)?, expected);
+
+    let json = format!(
+        r#"[{{"recipient": "{}", "amount": "1"}}, {{"recipient": "{}", "amount": "2.5"}}]"#,
+        ChainId::root(1),
+        ChainId::root(2),
+    );
+    assert_eq!(Payout::parse_json(&json)?, expected);
+
+    let invalid = format!("{}\n{},0\n", ChainId::root(1), ChainId::root(2));
+    assert!(matches!(
+        Payout::parse_csv(&invalid),
+        Err(Error::InvalidPayout { position, .. }) if position == "line 1"
+    ));
+    assert!(matches!(
+        Payout::parse_csv(invalid.lines().nth(1).unwrap()),
+        Err(Error::InvalidPayout { reason, .. }) if reason.contains("zero")
+    ));
+    assert!(matches!(
+        Payout::parse_json(r#"[{"recipient": "nobody", "amount": "1"}]"#),
+        Err(Error::InvalidPayout { position, .. }) if position == "entry 1"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_payout_batches() {
+    let payouts = (0..5).map(|index| payout(index, 1)).collect::<Vec<_>>();
+    assert_eq!(
+        payouts::batches(&payouts, None, 2, u64::MAX),
+        vec![0..2, 2..4, 4..5]
+    );
+    assert!(payouts::batches(&[], None, 2, u64::MAX).is_empty());
+
+    let transfer_size = 2 * bcs::serialized_size(&payouts[0].operation(None)).unwrap() as u64;
+    assert_eq!(
+        payouts::batches(&payouts, None, 100, 3 * transfer_size),
+        vec![0..3, 3..5]
+    );
+    // Transfers larger than the limit are still sent, one per block.
+    assert_eq!(
+        payouts::batches(&payouts[..2], None, 100, 0),
+        vec![0..1, 1..2]
+    );
+}
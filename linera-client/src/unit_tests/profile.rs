@@ -0,0 +1,38 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{client_options::Error, profile::Profile};
+
+#[test]
+fn test_profiles() -> anyhow::Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let config_dir = tmp_dir.path();
+    assert!(Profile::list(config_dir)?.is_empty());
+    assert!(Profile::read(config_dir, "testnet")?.is_none());
+
+    let profile = Profile {
+        wallet: None,
+        storage: Some("rocksdb:testnet.db".to_owned()),
+        faucet: Some("https://faucet.testnet.example".to_owned()),
+        validator: Some("grpcs:validator.testnet.example:443".to_owned()),
+    };
+    profile.create(config_dir, "testnet")?;
+    Profile::default().create(config_dir, "devnet")?;
+    assert!(matches!(
+        profile.create(config_dir, "testnet"),
+        Err(Error::ProfileAlreadyExists(_))
+    ));
+    assert_eq!(Profile::list(config_dir)?, ["devnet", "testnet"]);
+
+    let read_profile = Profile::read(config_dir, "testnet")?.unwrap();
+    assert_eq!(read_profile.wallet, None);
+    assert_eq!(read_profile.storage, profile.storage);
+    assert_eq!(read_profile.faucet, profile.faucet);
+    assert_eq!(read_profile.validator, profile.validator);
+
+    assert!(matches!(
+        Profile::directory(config_dir, "../escape"),
+        Err(Error::InvalidProfileName(_))
+    ));
+    Ok(())
+}
@@ -65,6 +65,7 @@ async fn test_save_wallet_with_pending_blobs() -> anyhow::Result<()> {
             timestamp: clock.current_time(),
             authenticated_signer: None,
             previous_block_hash: None,
+            priority_fee: Amount::ZERO,
         },
         blobs: vec![Blob::new_data(b"blob".to_vec())],
     });
@@ -72,3 +73,107 @@ async fn test_save_wallet_with_pending_blobs() -> anyhow::Result<()> {
     context.save_wallet().await?;
     Ok(())
 }
+
+/// Tests that an encrypted wallet can only be read back with its passphrase.
+#[test]
+fn test_encrypted_wallet_file() -> anyhow::Result<()> {
+    use age::secrecy::SecretString;
+
+    use crate::persistent::{self, Persist as _};
+
+    let tmp_dir = tempfile::tempdir()?;
+    let wallet_path = tmp_dir.path().join("wallet.json");
+    let passphrase = || Ok(SecretString::from("correct horse".to_owned()));
+
+    let mut file = persistent::File::new(&wallet_path, 42u64)?;
+    file.set_passphrase(Some(SecretString::from("correct horse".to_owned())));
+    futures::executor::block_on(file.persist())?;
+    drop(file);
+
+    let contents = fs_err::read(&wallet_path)?;
+    assert!(!contents.windows(2).any(|window| window == b"42"));
+    assert!(persistent::File::<u64>::read(&wallet_path).is_err());
+    let wrong_passphrase = || Ok(SecretString::from("wrong".to_owned()));
+    assert!(persistent::File::<u64>::read_with_passphrase(&wallet_path, wrong_passphrase).is_err());
+
+    let file = persistent::File::<u64>::read_with_passphrase(&wallet_path, passphrase)?;
+    assert!(file.is_encrypted());
+    assert_eq!(*file, 42);
+    Ok(())
+}
+
+/// Tests that a chain can be moved to another wallet of the same network.
+#[test_log::test(tokio::test)]
+async fn test_export_and_import_chain() -> anyhow::Result<()> {
+    use age::secrecy::SecretString;
+
+    use crate::wallet::ExportedChain;
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let storage_builder = MemoryStorageBuilder::default();
+    let builder = TestBuilder::new(storage_builder, 4, 1).await?;
+    let genesis_config = make_genesis_config(&builder);
+    let mut other_genesis_config = genesis_config.clone();
+    other_genesis_config.network_name = "other network".to_string();
+
+    let mut source = Wallet::new(genesis_config.clone(), None);
+    let chain = UserChain::make_initial(&mut rng, ChainDescription::Root(0), Default::default());
+    let chain_id = chain.chain_id;
+    source.insert(chain);
+
+    let tmp_dir = tempfile::tempdir()?;
+    let path = tmp_dir.path().join("chain.json");
+    let no_passphrase = || -> Result<SecretString, crate::Error> { panic!("not encrypted") };
+    source.export_chain(chain_id)?.write(&path, None)?;
+    // Existing files are not overwritten.
+    assert!(source.export_chain(chain_id)?.write(&path, None).is_err());
+
+    let mut other_network = Wallet::new(other_genesis_config, None);
+    assert!(other_network
+        .import_chain(ExportedChain::read(&path, no_passphrase)?)
+        .is_err());
+
+    let mut destination = Wallet::new(genesis_config, None);
+    destination.insert(UserChain::make_other(chain_id, Default::default()));
+    assert_eq!(
+        destination.import_chain(ExportedChain::read(&path, no_passphrase)?)?,
+        chain_id
+    );
+    assert_eq!(destination.owned_chain_ids(), vec![chain_id]);
+    assert!(destination
+        .import_chain(ExportedChain::read(&path, no_passphrase)?)
+        .is_err());
+    Ok(())
+}
+
+/// Tests that exported chains can be encrypted, and are only read back with the same
+/// passphrase.
+#[test_log::test(tokio::test)]
+async fn test_export_encrypted_chain() -> anyhow::Result<()> {
+    use age::secrecy::SecretString;
+
+    use crate::wallet::ExportedChain;
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let storage_builder = MemoryStorageBuilder::default();
+    let builder = TestBuilder::new(storage_builder, 4, 1).await?;
+    let mut source = Wallet::new(make_genesis_config(&builder), None);
+    let chain = UserChain::make_initial(&mut rng, ChainDescription::Root(0), Default::default());
+    let chain_id = chain.chain_id;
+    source.insert(chain);
+
+    let tmp_dir = tempfile::tempdir()?;
+    let path = tmp_dir.path().join("chain.json");
+    let passphrase = SecretString::from("correct horse".to_owned());
+    source
+        .export_chain(chain_id)?
+        .write(&path, Some(&passphrase))?;
+    assert!(!String::from_utf8_lossy(&fs_err::read(&path)?).contains("key_pair"));
+
+    let wrong_passphrase = || Ok(SecretString::from("wrong".to_owned()));
+    assert!(ExportedChain::read(&path, wrong_passphrase).is_err());
+    let exported = ExportedChain::read(&path, || Ok(passphrase.clone()))?;
+    assert_eq!(exported.chain.chain_id, chain_id);
+    assert!(exported.chain.key_pair.is_some());
+    Ok(())
+}
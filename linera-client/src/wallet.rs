@@ -5,7 +5,11 @@ use std::{
     collections::{BTreeMap, HashMap},
     iter::IntoIterator,
 };
+#[cfg(feature = "fs")]
+use std::{io::Write as _, path::Path};
 
+#[cfg(feature = "fs")]
+use age::secrecy::SecretString;
 use linera_base::{
     crypto::{CryptoHash, CryptoRng, KeyPair},
     data_types::{BlockHeight, Timestamp},
@@ -20,6 +24,8 @@ use linera_storage::Storage;
 use rand::Rng as _;
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "fs")]
+use crate::persistent;
 use crate::{config::GenesisConfig, error, Error};
 
 #[derive(Serialize, Deserialize)]
@@ -184,6 +190,41 @@ impl Wallet {
         );
     }
 
+    /// Returns a copy of the chain `chain_id`, including its key pair and its state, to be
+    /// imported into another wallet.
+    pub fn export_chain(&self, chain_id: ChainId) -> Result<ExportedChain, Error> {
+        let chain = self
+            .chains
+            .get(&chain_id)
+            .ok_or(error::Inner::NonexistentChain(chain_id))?;
+        Ok(ExportedChain {
+            genesis_config_hash: self.genesis_config.hash(),
+            chain: chain.copy(),
+        })
+    }
+
+    /// Adds a chain exported from another wallet of the same network. A chain that is already
+    /// in the wallet is only replaced if the wallet doesn't have its keys.
+    pub fn import_chain(&mut self, exported: ExportedChain) -> Result<ChainId, Error> {
+        let expected = self.genesis_config.hash();
+        ensure!(
+            exported.genesis_config_hash == expected,
+            error::Inner::GenesisConfigMismatch {
+                exported: exported.genesis_config_hash,
+                expected,
+            }
+        );
+        let chain_id = exported.chain.chain_id;
+        ensure!(
+            self.chains
+                .get(&chain_id)
+                .map_or(true, |chain| chain.key_pair.is_none()),
+            error::Inner::ChainAlreadyOwned(chain_id)
+        );
+        self.insert(exported.chain);
+        Ok(chain_id)
+    }
+
     pub fn genesis_admin_chain(&self) -> ChainId {
         self.genesis_config.admin_id
     }
@@ -243,4 +284,59 @@ impl UserChain {
             pending_proposal: None,
         }
     }
+
+    /// Returns a copy of this chain, including its key pair.
+    pub fn copy(&self) -> Self {
+        Self {
+            chain_id: self.chain_id,
+            key_pair: self.key_pair.as_ref().map(KeyPair::copy),
+            block_hash: self.block_hash,
+            timestamp: self.timestamp,
+            next_block_height: self.next_block_height,
+            pending_proposal: self.pending_proposal.clone(),
+        }
+    }
+}
+
+/// A chain exported from a wallet, with everything needed to keep using it from another
+/// wallet of the same network.
+#[derive(Serialize, Deserialize)]
+pub struct ExportedChain {
+    /// The hash of the genesis configuration of the network of the chain.
+    pub genesis_config_hash: CryptoHash,
+    /// The chain, including its key pair.
+    pub chain: UserChain,
+}
+
+#[cfg(feature = "fs")]
+impl ExportedChain {
+    /// Writes the exported chain to a new file at `path`, encrypted with the `passphrase` if
+    /// there is one. Since it contains the key pair of the chain, on Unix the file is only
+    /// readable by the current user.
+    pub fn write(&self, path: &Path, passphrase: Option<&SecretString>) -> Result<(), Error> {
+        let mut contents = serde_json::to_vec_pretty(self)?;
+        if let Some(passphrase) = passphrase {
+            contents = persistent::file::encrypt(&contents, passphrase)?;
+        }
+        let mut options = fs_err::OpenOptions::new();
+        #[cfg(target_family = "unix")]
+        fs_err::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.write(true).create_new(true).open(path)?;
+        file.write_all(&contents)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Reads an exported chain from the file at `path`. If the file is encrypted, the
+    /// `passphrase` function is called to decrypt it.
+    pub fn read(
+        path: &Path,
+        passphrase: impl FnOnce() -> Result<SecretString, Error>,
+    ) -> Result<Self, Error> {
+        let mut contents = fs_err::read(path)?;
+        if persistent::file::is_encrypted(&contents) {
+            contents = persistent::file::decrypt(&contents, &passphrase()?)?;
+        }
+        Ok(serde_json::from_slice(&contents)?)
+    }
 }
@@ -0,0 +1,245 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline verification of exported confirmed-block certificates.
+//!
+//! A [`CertificateArchive`] contains the certificates of one or several chains, together with
+//! the blobs they need. [`audit_certificates`] checks the signatures of every certificate
+//! against the known committees, checks that the blocks of each chain are correctly chained,
+//! and then re-executes all the blocks on top of the given storage, which should only contain
+//! the genesis state. Re-execution makes sure that the recorded outcome of each block (its
+//! messages, events, oracle responses and state hash) is the one the protocol produces.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    num::NonZeroUsize,
+};
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{Blob, BlockHeight},
+    identifiers::ChainId,
+};
+use linera_chain::{types::ConfirmedBlockCertificate, ChainError};
+use linera_execution::committee::{Committee, Epoch};
+use linera_storage::Storage;
+use linera_views::views::ViewError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::worker::{WorkerError, WorkerState};
+
+/// The number of chain workers to keep in memory while re-executing blocks.
+const AUDIT_CHAIN_WORKER_LIMIT: usize = 100;
+
+/// A set of confirmed-block certificates and the blobs they require, as exported from a node.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CertificateArchive {
+    /// The certificates, in any order.
+    pub certificates: Vec<ConfirmedBlockCertificate>,
+    /// The blobs read or published by the certified blocks.
+    pub blobs: Vec<Blob>,
+}
+
+/// The result of a successful audit.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AuditReport {
+    /// The number of certificates that were verified.
+    pub certificate_count: usize,
+    /// The height following the last verified block, for each chain.
+    pub next_block_heights: BTreeMap<ChainId, BlockHeight>,
+    /// The execution state hash after the last verified block, for each chain.
+    pub state_hashes: BTreeMap<ChainId, CryptoHash>,
+}
+
+/// An error found while auditing certificates.
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error("Certificate {hash} is from epoch {epoch}, whose committee is unknown")]
+    UnknownEpoch { hash: CryptoHash, epoch: Epoch },
+    #[error("Certificate {hash} of the admin chain is from epoch {epoch}, which was removed")]
+    RemovedEpoch { hash: CryptoHash, epoch: Epoch },
+    #[error(
+        "Certificate {hash} creates the committee of epoch {epoch}, which does not follow the \
+        block's epoch or differs from the known committee of that epoch"
+    )]
+    InvalidCommitteeCreation { hash: CryptoHash, epoch: Epoch },
+    #[error("Certificate {hash} removes the committee of epoch {epoch}, which is not known")]
+    InvalidCommitteeRemoval { hash: CryptoHash, epoch: Epoch },
+    #[error("Certificate {hash} is not correctly signed: {error}")]
+    InvalidSignatures { hash: CryptoHash, error: ChainError },
+    #[error(
+        "Certificate {hash} for block {height} of chain {chain_id} does not follow the previous \
+        certificate of that chain in the archive"
+    )]
+    InvalidBlockChaining {
+        hash: CryptoHash,
+        chain_id: ChainId,
+        height: BlockHeight,
+    },
+    #[error("Re-executing certificate {hash} failed: {error}")]
+    Verification {
+        hash: CryptoHash,
+        error: Box<WorkerError>,
+    },
+    #[error(transparent)]
+    ViewError(#[from] ViewError),
+}
+
+/// Verifies the given certificates offline, re-executing their blocks on top of `storage`.
+///
+/// The `storage` must contain the genesis state of the network and nothing else. The
+/// `committees` are the ones known to be trusted, usually only the genesis committee: the
+/// committees created by admin chain blocks in the archive are added as they are verified.
+///
+/// Like the admin chain itself, the audit only accepts a new committee for the epoch that
+/// follows the one of the block creating it, and rejects the admin chain blocks of a removed
+/// epoch. The blocks of other chains may have been signed before they received the removal,
+/// so their signatures are still checked against removed committees: re-executing them then
+/// makes sure that each chain still trusted the epoch of its blocks.
+pub async fn audit_certificates<S>(
+    storage: S,
+    admin_id: ChainId,
+    mut committees: BTreeMap<Epoch, Committee>,
+    archive: CertificateArchive,
+) -> Result<AuditReport, AuditError>
+where
+    S: Storage + Clone + Send + Sync + 'static,
+{
+    let CertificateArchive {
+        mut certificates,
+        blobs,
+    } = archive;
+    certificates.sort_by_key(|certificate| {
+        let header = &certificate.block().header;
+        (header.chain_id, header.height)
+    });
+
+    // Committee changes must be known before checking the blocks of later epochs, so the admin
+    // chain is checked first.
+    let (admin_certificates, other_certificates): (Vec<_>, Vec<_>) = certificates
+        .iter()
+        .partition(|certificate| certificate.block().header.chain_id == admin_id);
+    let mut removed_epochs = BTreeSet::new();
+    for certificate in admin_certificates.into_iter().chain(other_certificates) {
+        let hash = certificate.hash();
+        let epoch = certificate.block().header.epoch;
+        let is_admin_chain = certificate.block().header.chain_id == admin_id;
+        if is_admin_chain && removed_epochs.contains(&epoch) {
+            return Err(AuditError::RemovedEpoch { hash, epoch });
+        }
+        let committee = committees
+            .get(&epoch)
+            .ok_or(AuditError::UnknownEpoch { hash, epoch })?;
+        certificate
+            .check(committee)
+            .map_err(|error| AuditError::InvalidSignatures { hash, error })?;
+        if !is_admin_chain {
+            continue;
+        }
+        if let Some((created_epoch, created_committee)) = certificate.created_committee() {
+            let is_next_epoch = epoch.try_add_one().ok() == Some(created_epoch);
+            let is_consistent = committees
+                .get(&created_epoch)
+                .map_or(true, |known_committee| known_committee == created_committee);
+            if !is_next_epoch || !is_consistent || removed_epochs.contains(&created_epoch) {
+                return Err(AuditError::InvalidCommitteeCreation {
+                    hash,
+                    epoch: created_epoch,
+                });
+            }
+            committees.insert(created_epoch, created_committee.clone());
+        }
+        for removed_epoch in certificate.removed_committees() {
+            if !committees.contains_key(&removed_epoch) || !removed_epochs.insert(removed_epoch) {
+                return Err(AuditError::InvalidCommitteeRemoval {
+                    hash,
+                    epoch: removed_epoch,
+                });
+            }
+        }
+    }
+
+    let mut previous: Option<&ConfirmedBlockCertificate> = None;
+    for certificate in &certificates {
+        let header = &certificate.block().header;
+        let follows_previous = match previous {
+            Some(previous) if previous.block().header.chain_id == header.chain_id => {
+                header.previous_block_hash == Some(previous.hash())
+                    && previous.block().header.height.try_add_one().ok() == Some(header.height)
+            }
+            _ => true,
+        };
+        if !follows_previous {
+            return Err(AuditError::InvalidBlockChaining {
+                hash: certificate.hash(),
+                chain_id: header.chain_id,
+                height: header.height,
+            });
+        }
+        previous = Some(certificate);
+    }
+
+    storage.write_blobs(&blobs).await?;
+    let worker = WorkerState::new(
+        "Auditor".to_string(),
+        None,
+        storage,
+        NonZeroUsize::new(AUDIT_CHAIN_WORKER_LIMIT).expect("Chain worker limit should not be 0"),
+    );
+    let mut report = AuditReport {
+        certificate_count: certificates.len(),
+        ..AuditReport::default()
+    };
+    for certificate in certificates {
+        let hash = certificate.hash();
+        let response = worker
+            .fully_handle_certificate_with_notifications(certificate, &())
+            .await
+            .map_err(|error| AuditError::Verification {
+                hash,
+                error: Box::new(error),
+            })?;
+        let info = response.info;
+        report
+            .next_block_heights
+            .insert(info.chain_id, info.next_block_height);
+        if let Some(state_hash) = info.state_hash {
+            report.state_hashes.insert(info.chain_id, state_hash);
+        }
+    }
+    Ok(report)
+}
+
+/// Collects the certificates of the given chains from `storage`, with the blobs they require,
+/// so that they can be audited elsewhere.
+pub async fn export_certificates<S>(
+    storage: &S,
+    chain_ids: impl IntoIterator<Item = ChainId>,
+) -> Result<CertificateArchive, ViewError>
+where
+    S: Storage,
+{
+    let mut archive = CertificateArchive::default();
+    let mut blob_ids = BTreeSet::new();
+    for chain_id in chain_ids {
+        let chain = storage.load_chain(chain_id).await?;
+        let hashes = chain.confirmed_log.read(..).await?;
+        for certificate in storage.read_certificates(hashes).await? {
+            blob_ids.extend(certificate.block().required_blob_ids());
+            archive.certificates.push(certificate);
+        }
+    }
+    let blob_ids = blob_ids.into_iter().collect::<Vec<_>>();
+    let mut missing_blob_ids = Vec::new();
+    for (blob_id, maybe_blob) in blob_ids.iter().zip(storage.read_blobs(&blob_ids).await?) {
+        match maybe_blob {
+            Some(blob) => archive.blobs.push(blob),
+            None => missing_blob_ids.push(*blob_id),
+        }
+    }
+    if !missing_blob_ids.is_empty() {
+        return Err(ViewError::BlobsNotFound(missing_blob_ids));
+    }
+    Ok(archive)
+}
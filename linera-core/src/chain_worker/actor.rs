@@ -4,7 +4,7 @@
 //! An actor that runs a chain worker.
 
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashSet, VecDeque},
     fmt,
     sync::{Arc, RwLock},
 };
@@ -12,7 +12,7 @@ use std::{
 use custom_debug_derive::Debug;
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{Blob, BlockHeight, Timestamp, UserApplicationDescription},
+    data_types::{Amount, Blob, BlockHeight, Timestamp, UserApplicationDescription},
     hashed::Hashed,
     identifiers::{BlobId, ChainId, UserApplicationId},
 };
@@ -33,7 +33,7 @@ use super::{config::ChainWorkerConfig, state::ChainWorkerState, DeliveryNotifier
 use crate::{
     data_types::{ChainInfoQuery, ChainInfoResponse},
     value_cache::ValueCache,
-    worker::{NetworkActions, WorkerError},
+    worker::{CrossChainUpdateOutcome, NetworkActions, WorkerError},
 };
 
 /// A request for the [`ChainWorkerActor`].
@@ -125,7 +125,7 @@ where
         origin: Origin,
         bundles: Vec<(Epoch, MessageBundle)>,
         #[debug(skip)]
-        callback: oneshot::Sender<Result<Option<(BlockHeight, NetworkActions)>, WorkerError>>,
+        callback: oneshot::Sender<Result<Option<CrossChainUpdateOutcome>, WorkerError>>,
     },
 
     /// Handle cross-chain request to confirm that the recipient was updated.
@@ -161,6 +161,12 @@ where
         new_trackers: BTreeMap<ValidatorName, u64>,
         callback: oneshot::Sender<Result<(), WorkerError>>,
     },
+
+    /// Get the cross-chain requests for the outgoing messages that were not acknowledged yet.
+    RetransmitPendingDeliveries {
+        #[debug(skip)]
+        callback: oneshot::Sender<Result<NetworkActions, WorkerError>>,
+    },
 }
 
 /// The actor worker type.
@@ -252,7 +258,24 @@ where
     ) {
         trace!("Starting `ChainWorkerActor`");
 
-        while let Some(request) = incoming_requests.recv().await {
+        // The requests that were received while looking for competing block proposals.
+        let mut pending_requests = VecDeque::new();
+        loop {
+            let request = match pending_requests.pop_front() {
+                Some(request) => request,
+                None => match incoming_requests.recv().await {
+                    Some(request) => request,
+                    None => break,
+                },
+            };
+            let request = if let ChainWorkerRequest::HandleBlockProposal { .. } = &request {
+                while let Ok(request) = incoming_requests.try_recv() {
+                    pending_requests.push_back(request);
+                }
+                Self::take_highest_priority_proposal(request, &mut pending_requests)
+            } else {
+                request
+            };
             // TODO(#2237): Spawn concurrent tasks for read-only operations
             trace!("Handling `ChainWorkerRequest`: {request:?}");
 
@@ -359,6 +382,9 @@ where
                             .await,
                     )
                     .is_ok(),
+                ChainWorkerRequest::RetransmitPendingDeliveries { callback } => callback
+                    .send(self.worker.retransmit_pending_deliveries().await)
+                    .is_ok(),
             };
 
             if !responded {
@@ -373,6 +399,47 @@ where
 
         trace!("`ChainWorkerActor` finished");
     }
+
+    /// Returns the block proposal with the highest priority fee among `proposal` and the
+    /// `pending_requests`, so that competing proposals are handled by decreasing priority
+    /// fee instead of in order of arrival. If another proposal is returned, `proposal` takes
+    /// its place in the queue. Proposals with equal fees keep their order.
+    fn take_highest_priority_proposal(
+        proposal: ChainWorkerRequest<StorageClient::Context>,
+        pending_requests: &mut VecDeque<ChainWorkerRequest<StorageClient::Context>>,
+    ) -> ChainWorkerRequest<StorageClient::Context> {
+        let Some(mut highest_fee) = proposal.priority_fee() else {
+            return proposal;
+        };
+        let mut highest_index = None;
+        for (index, request) in pending_requests.iter().enumerate() {
+            if let Some(fee) = request.priority_fee() {
+                if fee > highest_fee {
+                    highest_fee = fee;
+                    highest_index = Some(index);
+                }
+            }
+        }
+        match highest_index {
+            Some(index) => std::mem::replace(&mut pending_requests[index], proposal),
+            None => proposal,
+        }
+    }
+}
+
+impl<Context> ChainWorkerRequest<Context>
+where
+    Context: linera_views::context::Context + Clone + Send + Sync + 'static,
+{
+    /// Returns the priority fee of a block proposal.
+    fn priority_fee(&self) -> Option<Amount> {
+        match self {
+            ChainWorkerRequest::HandleBlockProposal { proposal, .. } => {
+                Some(proposal.content.block.priority_fee)
+            }
+            _ => None,
+        }
+    }
 }
 
 /// Writes an option as `Some(..)` or `None`.
@@ -5,7 +5,7 @@
 
 use std::sync::Arc;
 
-use linera_base::{crypto::KeyPair, time::Duration};
+use linera_base::{crypto::KeyPair, data_types::Timestamp, time::Duration};
 
 /// Configuration parameters for the [`ChainWorkerState`][`super::state::ChainWorkerState`].
 #[derive(Clone, Default)]
@@ -13,6 +13,12 @@ pub struct ChainWorkerConfig {
     /// The signature key pair of the validator. The key may be missing for replicas
     /// without voting rights (possibly with a partial view of chains).
     pub key_pair: Option<Arc<KeyPair>>,
+    /// The previous key pair of the validator, after a key rotation. It is used for chains
+    /// whose current committee doesn't include the new key yet.
+    pub previous_key_pair: Option<Arc<KeyPair>>,
+    /// The end of the grace period of the key rotation, after which the previous key pair is
+    /// not used anymore.
+    pub previous_key_valid_until: Timestamp,
     /// Whether inactive chains are allowed in storage.
     pub allow_inactive_chains: bool,
     /// Whether new messages from deprecated epochs are allowed.
@@ -31,6 +37,18 @@ impl ChainWorkerConfig {
         self
     }
 
+    /// Configures the `previous_key_pair` in this [`ChainWorkerConfig`], and the end of its
+    /// grace period.
+    pub fn with_previous_key_pair(
+        mut self,
+        key_pair: impl Into<Option<KeyPair>>,
+        valid_until: Timestamp,
+    ) -> Self {
+        self.previous_key_pair = key_pair.into().map(Arc::new);
+        self.previous_key_valid_until = valid_until;
+        self
+    }
+
     /// Gets a reference to the [`KeyPair`], if available.
     pub fn key_pair(&self) -> Option<&KeyPair> {
         self.key_pair.as_ref().map(Arc::as_ref)
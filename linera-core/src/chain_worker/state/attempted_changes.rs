@@ -35,9 +35,12 @@ use tracing::{debug, instrument, trace, warn};
 use super::{check_block_epoch, ChainWorkerConfig, ChainWorkerState};
 use crate::{
     data_types::ChainInfoResponse,
-    worker::{NetworkActions, Notification, Reason, WorkerError},
+    worker::{CrossChainUpdateOutcome, NetworkActions, Notification, Reason, WorkerError},
 };
 
+/// The number of certificates read at a time when backfilling the committee certificates.
+const BACKFILL_CERTIFICATES_BATCH_SIZE: usize = 100;
+
 /// Wrapper type that tracks if the changes to the `chain` state should be rolled back when
 /// dropped.
 pub struct ChainWorkerStateWithAttemptedChanges<'state, StorageClient>
@@ -93,7 +96,7 @@ where
             .already_validated_block(certificate.inner().height)?
         {
             return Ok((
-                ChainInfoResponse::new(&self.state.chain, self.state.config.key_pair()),
+                ChainInfoResponse::new(&self.state.chain, self.state.key_pair().as_deref()),
                 actions,
             ));
         }
@@ -114,7 +117,7 @@ where
                 },
             })
         }
-        let info = ChainInfoResponse::new(&self.state.chain, self.state.config.key_pair());
+        let info = ChainInfoResponse::new(&self.state.chain, self.state.key_pair().as_deref());
         self.save().await?;
         Ok((info, actions))
     }
@@ -135,6 +138,7 @@ where
             owner,
             validated_block_certificate,
             signature: _,
+            signature_scheme: _,
         } = proposal;
 
         let chain = &self.state.chain;
@@ -196,9 +200,15 @@ where
             .state
             .get_required_blobs(proposal.required_blob_ids())
             .await?;
-        let key_pair = self.state.config.key_pair();
+        let key_pair = self.state.key_pair();
         let manager = &mut self.state.chain.manager;
-        match manager.create_vote(proposal, executed_block, key_pair, local_time, blobs)? {
+        match manager.create_vote(
+            proposal,
+            executed_block,
+            key_pair.as_deref(),
+            local_time,
+            blobs,
+        )? {
             // Cache the value we voted on, so the client doesn't have to send it again.
             Some(Either::Left(vote)) => {
                 self.state
@@ -248,7 +258,7 @@ where
         if already_committed_block || should_skip_validated_block()? {
             // If we just processed the same pending block, return the chain info unchanged.
             return Ok((
-                ChainInfoResponse::new(&self.state.chain, self.state.config.key_pair()),
+                ChainInfoResponse::new(&self.state.chain, self.state.key_pair().as_deref()),
                 actions,
                 true,
             ));
@@ -277,13 +287,14 @@ where
             .filter_map(|(blob_id, maybe_blob)| Some((blob_id, maybe_blob?)))
             .collect();
         let old_round = self.state.chain.manager.current_round();
+        let key_pair = self.state.key_pair();
         self.state.chain.manager.create_final_vote(
             certificate,
-            self.state.config.key_pair(),
+            key_pair.as_deref(),
             self.state.storage.clock().current_time(),
             blobs,
         )?;
-        let info = ChainInfoResponse::new(&self.state.chain, self.state.config.key_pair());
+        let info = ChainInfoResponse::new(&self.state.chain, self.state.key_pair().as_deref());
         self.save().await?;
         let round = self.state.chain.manager.current_round();
         if round > old_round {
@@ -312,7 +323,7 @@ where
         }
         if tip.next_block_height > block_height {
             // Block was already confirmed.
-            let info = ChainInfoResponse::new(&self.state.chain, self.state.config.key_pair());
+            let info = ChainInfoResponse::new(&self.state.chain, self.state.key_pair().as_deref());
             let actions = self.state.create_network_actions().await?;
             return Ok((info, actions));
         }
@@ -361,25 +372,26 @@ where
             .map(|blobs| blobs.into_values().collect::<Vec<_>>());
 
         if let Ok(blobs) = &blobs_result {
+            // This also updates the blob states with the certificate hash, in the same batch, so
+            // that they are rolled back with the certificate after a crash.
             self.state
                 .storage
                 .write_blobs_and_certificate(blobs, &certificate)
                 .await?;
+        } else {
+            // Record the certificate in the states of the blobs that don't have one yet.
+            let blob_state = BlobState {
+                last_used_by: certificate.hash(),
+                chain_id: executed_block.block.chain_id,
+                block_height,
+                epoch: executed_block.block.epoch,
+            };
+            let blob_ids = required_blob_ids.into_iter().collect::<Vec<_>>();
+            self.state
+                .storage
+                .maybe_write_blob_states(&blob_ids, blob_state, false)
+                .await?;
         }
-
-        // Update the blob state with last used certificate hash.
-        let blob_state = BlobState {
-            last_used_by: certificate.hash(),
-            chain_id: executed_block.block.chain_id,
-            block_height,
-            epoch: executed_block.block.epoch,
-        };
-        let overwrite = blobs_result.is_ok(); // Overwrite only if we wrote the certificate.
-        let blob_ids = required_blob_ids.into_iter().collect::<Vec<_>>();
-        self.state
-            .storage
-            .maybe_write_blob_states(&blob_ids, blob_state, overwrite)
-            .await?;
         blobs_result?;
 
         // Execute the block and update inboxes.
@@ -414,7 +426,13 @@ where
         tip.num_operations += executed_block.block.operations.len() as u32;
         tip.num_outgoing_messages += executed_block.outcome.messages.len() as u32;
         self.state.chain.confirmed_log.push(certificate.hash());
-        let info = ChainInfoResponse::new(&self.state.chain, self.state.config.key_pair());
+        if let Some((epoch, _)) = certificate.created_committee() {
+            self.state
+                .chain
+                .committee_certificates
+                .insert(&epoch, certificate.hash())?;
+        }
+        let info = ChainInfoResponse::new(&self.state.chain, self.state.key_pair().as_deref());
         self.state.track_newly_created_chains(&executed_block);
         let mut actions = self.state.create_network_actions().await?;
         trace!(
@@ -431,6 +449,10 @@ where
         });
         // Persist chain.
         self.save().await?;
+        self.state
+            .storage
+            .clear_write_ahead_log(executed_block.block.chain_id)
+            .await?;
 
         self.state
             .block_values
@@ -473,7 +495,7 @@ where
         &mut self,
         origin: Origin,
         bundles: Vec<(Epoch, MessageBundle)>,
-    ) -> Result<Option<(BlockHeight, NetworkActions)>, WorkerError> {
+    ) -> Result<Option<CrossChainUpdateOutcome>, WorkerError> {
         // Only process certificates with relevant heights and epochs.
         let next_height_to_receive = self
             .state
@@ -485,6 +507,11 @@ where
             .chain
             .last_anticipated_block_height(&origin)
             .await?;
+        let last_received_height = bundles
+            .iter()
+            .map(|(_, bundle)| bundle.height)
+            .filter(|height| *height < next_height_to_receive)
+            .max();
         let helper = CrossChainUpdateHelper::new(&self.state.config, &self.state.chain);
         let recipient = self.state.chain_id();
         let bundles = helper.select_message_bundles(
@@ -495,7 +522,9 @@ where
             bundles,
         )?;
         let Some(last_updated_height) = bundles.last().map(|bundle| bundle.height) else {
-            return Ok(None);
+            // The sender may be retransmitting bundles because our confirmation was lost.
+            return Ok(last_received_height
+                .map(|height| CrossChainUpdateOutcome::AlreadyReceived { height }));
         };
         // Process the received messages in certificates.
         let local_time = self.state.storage.clock().current_time();
@@ -532,7 +561,10 @@ where
         };
         // Save the chain.
         self.save().await?;
-        Ok(Some((last_updated_height, actions)))
+        Ok(Some(CrossChainUpdateOutcome::Received {
+            height: last_updated_height,
+            actions,
+        }))
     }
 
     /// Handles the cross-chain request confirming that the recipient was updated.
@@ -580,15 +612,15 @@ where
 
     /// Attempts to vote for a leader timeout, if possible.
     pub(super) async fn vote_for_leader_timeout(&mut self) -> Result<(), WorkerError> {
+        let key_pair = self.state.key_pair();
         let chain = &mut self.state.chain;
         if let Some(epoch) = chain.execution_state.system.epoch.get() {
             let chain_id = chain.chain_id();
             let height = chain.tip_state.get().next_block_height;
-            let key_pair = self.state.config.key_pair();
             let local_time = self.state.storage.clock().current_time();
             if chain
                 .manager
-                .vote_timeout(chain_id, height, *epoch, key_pair, local_time)
+                .vote_timeout(chain_id, height, *epoch, key_pair.as_deref(), local_time)
             {
                 self.save().await?;
             }
@@ -598,6 +630,7 @@ where
 
     /// Votes for falling back to a public chain.
     pub(super) async fn vote_for_fallback(&mut self) -> Result<(), WorkerError> {
+        let key_pair = self.state.key_pair();
         let chain = &mut self.state.chain;
         if let (Some(epoch), Some(entry)) = (
             chain.execution_state.system.epoch.get(),
@@ -612,10 +645,9 @@ where
             if elapsed >= chain.ownership().timeout_config.fallback_duration {
                 let chain_id = chain.chain_id();
                 let height = chain.tip_state.get().next_block_height;
-                let key_pair = self.state.config.key_pair();
                 if chain
                     .manager
-                    .vote_fallback(chain_id, height, *epoch, key_pair)
+                    .vote_fallback(chain_id, height, *epoch, key_pair.as_deref())
                 {
                     self.save().await?;
                 }
@@ -624,6 +656,39 @@ where
         Ok(())
     }
 
+    /// Records the certificates that created the committees of the admin chain before they
+    /// were tracked, by reading its confirmed blocks once.
+    pub(super) async fn backfill_committee_certificates(&mut self) -> Result<(), WorkerError> {
+        let chain = &self.state.chain;
+        let system = &chain.execution_state.system;
+        let Some(epoch) = *system.epoch.get() else {
+            return Ok(());
+        };
+        // Every epoch after the genesis one was created by a block of the admin chain.
+        if *system.admin_id.get() != Some(chain.chain_id())
+            || chain.committee_certificates.count().await? >= epoch.0 as usize
+        {
+            return Ok(());
+        }
+        let hashes = chain.confirmed_log.read(..).await?;
+        for hashes in hashes.chunks(BACKFILL_CERTIFICATES_BATCH_SIZE) {
+            let certificates = self
+                .state
+                .storage
+                .read_certificates(hashes.to_vec())
+                .await?;
+            for certificate in certificates {
+                if let Some((epoch, _)) = certificate.created_committee() {
+                    self.state
+                        .chain
+                        .committee_certificates
+                        .insert(&epoch, certificate.hash())?;
+                }
+            }
+        }
+        self.save().await
+    }
+
     pub(super) async fn handle_pending_blob(
         &mut self,
         blob: Blob,
@@ -659,7 +724,7 @@ where
         self.save().await?;
         Ok(ChainInfoResponse::new(
             &self.state.chain,
-            self.state.config.key_pair(),
+            self.state.key_pair().as_deref(),
         ))
     }
 
@@ -678,7 +743,7 @@ where
             None => None,
         };
 
-        self.state.chain.save().await?;
+        self.state.storage.save_chain(&mut self.state.chain).await?;
         self.succeeded = true;
         Ok(())
     }
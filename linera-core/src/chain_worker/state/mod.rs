@@ -13,7 +13,7 @@ use std::{
 };
 
 use linera_base::{
-    crypto::CryptoHash,
+    crypto::{CryptoHash, KeyPair},
     data_types::{Blob, BlockHeight, UserApplicationDescription},
     ensure,
     hashed::Hashed,
@@ -44,7 +44,7 @@ use super::{ChainWorkerConfig, DeliveryNotifier};
 use crate::{
     data_types::{ChainInfoQuery, ChainInfoResponse, CrossChainRequest},
     value_cache::ValueCache,
-    worker::{NetworkActions, WorkerError},
+    worker::{CrossChainUpdateOutcome, NetworkActions, WorkerError},
 };
 
 /// The state of the chain worker.
@@ -107,6 +107,32 @@ where
         }
     }
 
+    /// Returns the key pair to sign votes and chain information with: the current key of the
+    /// validator, unless it rotated its key, the chain's committee only includes the previous
+    /// one and the grace period of the rotation is not over.
+    fn key_pair(&self) -> Option<Arc<KeyPair>> {
+        let key_pair = self.config.key_pair.clone();
+        let Some(previous_key_pair) = &self.config.previous_key_pair else {
+            return key_pair;
+        };
+        if self.storage.clock().current_time() >= self.config.previous_key_valid_until {
+            return key_pair;
+        }
+        let Ok((_, committee)) = self.chain.current_committee() else {
+            return key_pair;
+        };
+        let is_member = |key_pair: &KeyPair| {
+            committee
+                .validators()
+                .contains_key(&ValidatorName(key_pair.public()))
+        };
+        if key_pair.as_deref().is_some_and(is_member) || !is_member(previous_key_pair) {
+            key_pair
+        } else {
+            Some(previous_key_pair.clone())
+        }
+    }
+
     /// Returns a read-only view of the [`ChainStateView`].
     ///
     /// The returned view holds a lock on the chain state, which prevents the worker from changing
@@ -230,7 +256,7 @@ where
             NetworkActions::default()
         };
 
-        let info = ChainInfoResponse::new(&self.chain, self.config.key_pair());
+        let info = ChainInfoResponse::new(&self.chain, self.key_pair().as_deref());
         Ok((info, actions))
     }
 
@@ -262,7 +288,7 @@ where
         &mut self,
         origin: Origin,
         bundles: Vec<(Epoch, MessageBundle)>,
-    ) -> Result<Option<(BlockHeight, NetworkActions)>, WorkerError> {
+    ) -> Result<Option<CrossChainUpdateOutcome>, WorkerError> {
         ChainWorkerStateWithAttemptedChanges::new(self)
             .await
             .process_cross_chain_update(origin, bundles)
@@ -285,6 +311,12 @@ where
         &mut self,
         query: ChainInfoQuery,
     ) -> Result<(ChainInfoResponse, NetworkActions), WorkerError> {
+        if query.request_committee_certificates {
+            ChainWorkerStateWithAttemptedChanges::new(&mut *self)
+                .await
+                .backfill_committee_certificates()
+                .await?;
+        }
         if query.request_leader_timeout {
             ChainWorkerStateWithAttemptedChanges::new(&mut *self)
                 .await
@@ -420,6 +452,14 @@ where
     }
 
     /// Loads pending cross-chain requests.
+    /// Returns the cross-chain requests for all outgoing messages that were not acknowledged
+    /// yet.
+    pub(super) async fn retransmit_pending_deliveries(
+        &self,
+    ) -> Result<NetworkActions, WorkerError> {
+        self.create_network_actions().await
+    }
+
     async fn create_network_actions(&self) -> Result<NetworkActions, WorkerError> {
         let mut heights_by_recipient = BTreeMap::<_, BTreeMap<_, _>>::new();
         let mut targets = self.chain.outboxes.indices().await?;
@@ -166,11 +166,29 @@ where
         chain
             .remove_bundles_from_inboxes(block.timestamp, &block.incoming_bundles)
             .await?;
-        let outcome = if let Some(outcome) = outcome {
-            outcome.clone()
-        } else {
-            Box::pin(chain.execute_block(block, local_time, round.multi_leader(), None)).await?
-        };
+        // A retry carries the outcome from an earlier round. Re-execute the block against its
+        // recorded oracle responses instead of querying the oracles again, and check that we
+        // agree on the result.
+        let replaying_oracle_responses = outcome
+            .as_ref()
+            .map(|outcome| outcome.oracle_responses.clone());
+        let verified_outcome = Box::pin(chain.execute_block(
+            block,
+            local_time,
+            round.multi_leader(),
+            replaying_oracle_responses,
+        ))
+        .await?;
+        if let Some(outcome) = outcome {
+            ensure!(
+                *outcome == verified_outcome,
+                WorkerError::IncorrectOutcome {
+                    submitted: Box::new(outcome.clone()),
+                    computed: Box::new(verified_outcome),
+                }
+            );
+        }
+        let outcome = verified_outcome;
 
         let executed_block = outcome.with(block.clone());
         ensure!(
@@ -210,6 +228,12 @@ where
                 }
             );
         }
+        if query.request_committee_certificates {
+            let mut hashes = chain.committee_certificates.index_values().await?;
+            hashes.sort_by_key(|(epoch, _)| *epoch);
+            info.requested_committee_certificate_hashes =
+                hashes.into_iter().map(|(_, hash)| hash).collect();
+        }
         if query.request_pending_message_bundles {
             let mut messages = Vec::new();
             let pairs = chain.inboxes.try_load_all_entries().await?;
@@ -263,7 +287,7 @@ where
         if query.request_manager_values {
             info.manager.add_values(&chain.manager);
         }
-        Ok(ChainInfoResponse::new(info, self.0.config.key_pair()))
+        Ok(ChainInfoResponse::new(info, self.0.key_pair().as_deref()))
     }
 }
 
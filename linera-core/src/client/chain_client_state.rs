@@ -34,6 +34,10 @@ pub struct ChainClientState {
     ///
     /// This is always at the same height as `next_block_height`.
     pending_proposal: Option<PendingProposal>,
+    /// Whether a proposal that the local node recorded for the next block may still be adopted
+    /// as our pending proposal. This is only the case until we first try to propose a block,
+    /// e.g. after the client was restarted and lost track of a proposal it had already signed.
+    may_recover_proposal: bool,
     /// Known key pairs from present and past identities.
     known_key_pairs: BTreeMap<Owner, KeyPair>,
 
@@ -60,6 +64,7 @@ impl ChainClientState {
             timestamp,
             next_block_height,
             pending_proposal,
+            may_recover_proposal: true,
             client_mutex: Arc::default(),
         }
     }
@@ -81,6 +86,7 @@ impl ChainClientState {
     }
 
     pub(super) fn set_pending_proposal(&mut self, block: ProposedBlock, blobs: Vec<Blob>) {
+        self.may_recover_proposal = false;
         if block.height == self.next_block_height {
             let blobs = Vec::from_iter(blobs);
             assert_eq!(
@@ -117,7 +123,7 @@ impl ChainClientState {
     pub(super) fn update_from_info(&mut self, info: &ChainInfo) {
         if info.next_block_height > self.next_block_height {
             self.next_block_height = info.next_block_height;
-            self.clear_pending_proposal();
+            self.pending_proposal = None;
             self.block_hash = info.block_hash;
             self.timestamp = info.timestamp;
         }
@@ -125,6 +131,13 @@ impl ChainClientState {
 
     pub(super) fn clear_pending_proposal(&mut self) {
         self.pending_proposal = None;
+        self.may_recover_proposal = false;
+    }
+
+    /// Returns whether a proposal recorded by the local node may be adopted, and makes sure
+    /// this is only attempted once.
+    pub(super) fn take_may_recover_proposal(&mut self) -> bool {
+        std::mem::take(&mut self.may_recover_proposal)
     }
 
     pub(super) fn client_mutex(&self) -> Arc<Mutex<()>> {
@@ -23,7 +23,7 @@ use futures::{
     stream::{self, AbortHandle, FusedStream, FuturesUnordered, StreamExt},
 };
 #[cfg(not(target_arch = "wasm32"))]
-use linera_base::data_types::Bytecode;
+use linera_base::data_types::{Bytecode, BytecodeChunks};
 #[cfg(with_metrics)]
 use linera_base::prometheus_util::MeasureLatency as _;
 use linera_base::{
@@ -43,7 +43,7 @@ use linera_base::{
 use linera_chain::{
     data_types::{
         BlockProposal, ChainAndHeight, ExecutedBlock, IncomingBundle, LiteVote, MessageAction,
-        ProposedBlock,
+        ProposalContent, ProposedBlock,
     },
     manager::LockingBlock,
     types::{
@@ -58,8 +58,8 @@ use linera_execution::{
         AdminOperation, OpenChainConfig, Recipient, SystemChannel, SystemOperation,
         CREATE_APPLICATION_MESSAGE_INDEX, OPEN_CHAIN_MESSAGE_INDEX,
     },
-    ExecutionError, Operation, Query, QueryOutcome, QueryResponse, SystemExecutionError,
-    SystemQuery, SystemResponse,
+    ApplicationCallPolicy, ExecutionError, Operation, Query, QueryOutcome, QueryResponse,
+    SystemExecutionError, SystemQuery, SystemResponse,
 };
 use linera_storage::{Clock as _, Storage};
 use linera_views::views::ViewError;
@@ -314,6 +314,7 @@ impl<P, S: Storage + Clone> Client<P, S> {
                 cross_chain_message_delivery: self.cross_chain_message_delivery,
                 grace_period: self.grace_period,
                 blob_download_timeout: self.blob_download_timeout,
+                priority_fee: Amount::ZERO,
             },
         }
     }
@@ -517,6 +518,9 @@ pub struct ChainClientOptions {
     pub grace_period: f64,
     /// The delay when downloading a blob, after which we try a second validator.
     pub blob_download_timeout: Duration,
+    /// The fee paid for each operation on top of the base fee, to have our blocks preferred
+    /// when the chain is congested.
+    pub priority_fee: Amount,
 }
 
 /// Client to operate a chain by interacting with validators and the given local storage
@@ -618,6 +622,9 @@ pub enum ChainClientError {
         chain_id: ChainId,
         target_next_block_height: BlockHeight,
     },
+
+    #[error("Chain {0:?} cannot propose a block before the current round times out")]
+    WaitingForRoundTimeout(ChainId),
 }
 
 impl From<Infallible> for ChainClientError {
@@ -733,19 +740,34 @@ enum CheckCertificateResult {
 }
 
 /// Creates a compressed Contract, Service and bytecode.
+///
+/// A compressed bytecode larger than `maximum_blob_size` is split into chunks, which are
+/// returned as data blobs to be published before the bytecode blobs.
 #[cfg(not(target_arch = "wasm32"))]
 pub async fn create_bytecode_blobs(
     contract: Bytecode,
     service: Bytecode,
-) -> (Blob, Blob, BytecodeId) {
+    maximum_blob_size: u64,
+) -> (Blob, Blob, Vec<Blob>, BytecodeId) {
     let (compressed_contract, compressed_service) =
         tokio::task::spawn_blocking(move || (contract.compress(), service.compress()))
             .await
             .expect("Compression should not panic");
-    let contract_blob = Blob::new_contract_bytecode(compressed_contract);
-    let service_blob = Blob::new_service_bytecode(compressed_service);
+    let (contract_content, mut chunk_blobs) = BytecodeChunks::split(
+        BlobType::ContractBytecode,
+        compressed_contract,
+        maximum_blob_size,
+    );
+    let (service_content, service_chunk_blobs) = BytecodeChunks::split(
+        BlobType::ServiceBytecode,
+        compressed_service,
+        maximum_blob_size,
+    );
+    chunk_blobs.extend(service_chunk_blobs);
+    let contract_blob = Blob::new(contract_content);
+    let service_blob = Blob::new(service_content);
     let bytecode_id = BytecodeId::new(contract_blob.id().hash, service_blob.id().hash);
-    (contract_blob, service_blob, bytecode_id)
+    (contract_blob, service_blob, chunk_blobs, bytecode_id)
 }
 
 impl<P, S> ChainClient<P, S>
@@ -2047,6 +2069,323 @@ where
         self.execute_operations(vec![operation], vec![]).await
     }
 
+    /// Executes `operations` on this chain, then `other_operations` on the `other` chain,
+    /// which can use the messages sent by the first block.
+    ///
+    /// This is not a two-phase commit: the first block is committed before the second one is
+    /// proposed. If the second block cannot be committed, the first one is undone by later
+    /// blocks: the other chain rejects the messages it sent, so that the tracked ones bounce
+    /// back, and this chain accepts the bounced messages in a block with the `compensation`
+    /// operations. Other incoming messages are left in the inbox. If one of these blocks has to
+    /// wait for a round timeout, the outcome is [`AtomicOperationsOutcome::RollbackPending`].
+    #[instrument(
+        level = "trace",
+        skip(other, operations, other_operations, compensation)
+    )]
+    pub async fn execute_operations_with_compensation(
+        &self,
+        operations: Vec<Operation>,
+        other: &ChainClient<P, S>,
+        other_operations: Vec<Operation>,
+        compensation: Vec<Operation>,
+    ) -> Result<ClientOutcome<AtomicOperationsOutcome>, ChainClientError> {
+        // Check that we can propose blocks on both chains before committing anything.
+        self.prepare_chain().await?;
+        other.prepare_chain().await?;
+        other.identity().await?;
+
+        let first = match self.execute_operations(operations, vec![]).await? {
+            ClientOutcome::Committed(certificate) => certificate,
+            ClientOutcome::WaitForTimeout(timeout) => {
+                return Ok(ClientOutcome::WaitForTimeout(timeout))
+            }
+        };
+        let error = match other
+            .execute_operations_after(first.clone(), other_operations)
+            .await
+        {
+            Ok(second) => {
+                return Ok(ClientOutcome::Committed(
+                    AtomicOperationsOutcome::Committed { first, second },
+                ))
+            }
+            Err(error) => error,
+        };
+        warn!(%error, "Second block failed; rolling back the first one.");
+
+        let mut rollback = Vec::new();
+        let mut bounced_bundles_hash = None;
+        match other.reject_message_bundles_from(first.hash()).await? {
+            ClientOutcome::Committed(Either::Left(second)) => {
+                // The second block was committed after all, so there is nothing to undo.
+                return Ok(ClientOutcome::Committed(
+                    AtomicOperationsOutcome::Committed { first, second },
+                ));
+            }
+            ClientOutcome::Committed(Either::Right(Some(certificate))) => {
+                self.receive_certificate_and_update_validators(certificate.clone())
+                    .await?;
+                bounced_bundles_hash = Some(certificate.hash());
+                rollback.push(certificate);
+            }
+            ClientOutcome::Committed(Either::Right(None)) => {}
+            ClientOutcome::WaitForTimeout(timeout) => {
+                return Ok(ClientOutcome::Committed(
+                    AtomicOperationsOutcome::RollbackPending {
+                        first,
+                        rollback,
+                        error: Box::new(error),
+                        chain_id: other.chain_id,
+                        timeout,
+                    },
+                ));
+            }
+        }
+        match self
+            .accept_message_bundles_from(bounced_bundles_hash, compensation)
+            .await?
+        {
+            ClientOutcome::Committed(certificate) => rollback.extend(certificate),
+            ClientOutcome::WaitForTimeout(timeout) => {
+                return Ok(ClientOutcome::Committed(
+                    AtomicOperationsOutcome::RollbackPending {
+                        first,
+                        rollback,
+                        error: Box::new(error),
+                        chain_id: self.chain_id,
+                        timeout,
+                    },
+                ));
+            }
+        }
+        Ok(ClientOutcome::Committed(
+            AtomicOperationsOutcome::RolledBack {
+                first,
+                rollback,
+                error: Box::new(error),
+            },
+        ))
+    }
+
+    /// Receives the messages sent by the given certificate and executes the operations in a
+    /// block that accepts them.
+    async fn execute_operations_after(
+        &self,
+        certificate: ConfirmedBlockCertificate,
+        operations: Vec<Operation>,
+    ) -> Result<ConfirmedBlockCertificate, ChainClientError> {
+        self.receive_certificate_and_update_validators(certificate)
+            .await?;
+        match self.execute_operations(operations, vec![]).await? {
+            ClientOutcome::Committed(certificate) => Ok(certificate),
+            ClientOutcome::WaitForTimeout(_) => {
+                Err(ChainClientError::WaitingForRoundTimeout(self.chain_id))
+            }
+        }
+    }
+
+    /// Creates a block with the given operations that accepts the incoming bundles from the
+    /// block with the given certificate hash, if any, and leaves the other incoming bundles in
+    /// the inbox.
+    ///
+    /// The pending block is processed first. Returns the certificate of the new block, or `None`
+    /// if there is nothing to execute.
+    #[instrument(level = "trace", skip(operations))]
+    async fn accept_message_bundles_from(
+        &self,
+        certificate_hash: Option<CryptoHash>,
+        operations: Vec<Operation>,
+    ) -> Result<ClientOutcome<Option<ConfirmedBlockCertificate>>, ChainClientError> {
+        let mutex = self.state().client_mutex();
+        let _guard = mutex.lock_owned().await;
+        if let ClientOutcome::WaitForTimeout(timeout) =
+            self.process_pending_block_without_prepare().await?
+        {
+            return Ok(ClientOutcome::WaitForTimeout(timeout));
+        }
+        let incoming_bundles = match certificate_hash {
+            Some(certificate_hash) => self
+                .pending_message_bundles()
+                .await?
+                .into_iter()
+                .filter(|incoming_bundle| {
+                    incoming_bundle.bundle.certificate_hash == certificate_hash
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        if incoming_bundles.is_empty() && operations.is_empty() {
+            return Ok(ClientOutcome::Committed(None));
+        }
+        let identity = self.identity().await?;
+        self.new_pending_block(incoming_bundles, operations, vec![], identity)
+            .await?;
+        self.process_pending_block_without_prepare().await
+    }
+
+    /// Creates a block that rejects the incoming bundles from the block with the given
+    /// certificate hash, so that their tracked messages bounce back to the sender. Other
+    /// incoming bundles are handled as usual.
+    ///
+    /// The pending block is processed first. If it gets committed and accepts the bundles,
+    /// nothing is rejected and its certificate is returned on the left. Otherwise, the
+    /// certificate of the block rejecting the bundles, if any, is returned on the right.
+    #[instrument(level = "trace")]
+    async fn reject_message_bundles_from(
+        &self,
+        certificate_hash: CryptoHash,
+    ) -> Result<
+        ClientOutcome<Either<ConfirmedBlockCertificate, Option<ConfirmedBlockCertificate>>>,
+        ChainClientError,
+    > {
+        let mutex = self.state().client_mutex();
+        let _guard = mutex.lock_owned().await;
+        match self.process_pending_block_without_prepare().await? {
+            ClientOutcome::Committed(Some(certificate))
+                if certificate
+                    .block()
+                    .body
+                    .incoming_bundles
+                    .iter()
+                    .any(|incoming_bundle| {
+                        incoming_bundle.bundle.certificate_hash == certificate_hash
+                            && incoming_bundle.action == MessageAction::Accept
+                    }) =>
+            {
+                return Ok(ClientOutcome::Committed(Either::Left(certificate)));
+            }
+            ClientOutcome::Committed(_) => {}
+            ClientOutcome::WaitForTimeout(timeout) => {
+                return Ok(ClientOutcome::WaitForTimeout(timeout));
+            }
+        }
+        let mut incoming_bundles = self.pending_message_bundles().await?;
+        let mut found = false;
+        for incoming_bundle in &mut incoming_bundles {
+            if incoming_bundle.bundle.certificate_hash == certificate_hash
+                && !incoming_bundle.bundle.is_protected()
+            {
+                incoming_bundle.action = MessageAction::Reject;
+                found = true;
+            }
+        }
+        if !found {
+            return Ok(ClientOutcome::Committed(Either::Right(None)));
+        }
+        let identity = self.identity().await?;
+        self.new_pending_block(incoming_bundles, vec![], vec![], identity)
+            .await?;
+        Ok(match self.process_pending_block_without_prepare().await? {
+            ClientOutcome::Committed(certificate) => {
+                ClientOutcome::Committed(Either::Right(certificate))
+            }
+            ClientOutcome::WaitForTimeout(timeout) => ClientOutcome::WaitForTimeout(timeout),
+        })
+    }
+
+    /// Creates a block with the given operations, to be signed by `signer` elsewhere, e.g. on
+    /// a machine without network access, and returns the content of the proposal to sign.
+    ///
+    /// Unlike [`Self::execute_operations`], this does not need the key pair of `signer`, and
+    /// does not set a pending proposal for the chain.
+    #[instrument(level = "trace", skip(operations))]
+    pub async fn prepare_unsigned_proposal(
+        &self,
+        operations: Vec<Operation>,
+        signer: Owner,
+    ) -> Result<ProposalContent, ChainClientError> {
+        self.prepare_chain().await?;
+        let info = self.chain_info_with_manager_values().await?;
+        ensure!(
+            info.manager.ownership.verify_owner(&signer),
+            ChainClientError::BlockProposalError("The signer is not an owner of the chain")
+        );
+        let incoming_bundles = self.pending_message_bundles().await?;
+        let (previous_block_hash, height, timestamp) = {
+            let state = self.state();
+            (
+                state.block_hash(),
+                state.next_block_height(),
+                self.next_timestamp(&incoming_bundles, state.timestamp()),
+            )
+        };
+        let block = ProposedBlock {
+            epoch: self.epoch().await?,
+            chain_id: self.chain_id,
+            incoming_bundles,
+            operations,
+            previous_block_hash,
+            height,
+            authenticated_signer: Some(signer),
+            timestamp,
+            priority_fee: self.options.priority_fee,
+        };
+        let round = match Self::round_for_new_proposal(&info, &signer, &block, true)? {
+            Either::Left(round) => round.multi_leader(),
+            Either::Right(_) => None,
+        };
+        let (executed_block, _) = self
+            .stage_block_execution_and_discard_failing_messages(block, round)
+            .await?;
+        let round = match Self::round_for_new_proposal(
+            &info,
+            &signer,
+            &executed_block.block,
+            executed_block.outcome.has_oracle_responses(),
+        )? {
+            Either::Left(round) => round,
+            Either::Right(_) => {
+                return Err(ChainClientError::BlockProposalError(
+                    "The signer cannot propose a block in the current round",
+                ))
+            }
+        };
+        Ok(ProposalContent {
+            block: executed_block.block,
+            round,
+            outcome: None,
+        })
+    }
+
+    /// Submits a block proposal that was signed elsewhere, e.g. for the content returned by
+    /// [`Self::prepare_unsigned_proposal`], and returns the certificate of the confirmed block.
+    #[instrument(level = "trace", skip(proposal))]
+    pub async fn submit_signed_proposal(
+        &self,
+        proposal: BlockProposal,
+    ) -> Result<ConfirmedBlockCertificate, ChainClientError> {
+        ensure!(
+            proposal.content.block.chain_id == self.chain_id,
+            ChainClientError::BlockProposalError("The proposal is for another chain")
+        );
+        let mutex = self.state().client_mutex();
+        let _guard = mutex.lock_owned().await;
+        self.prepare_chain().await?;
+        let round = proposal.content.round;
+        let (executed_block, _) = self
+            .stage_block_execution(proposal.content.block.clone(), round.multi_leader())
+            .await?;
+        self.client
+            .local_node
+            .handle_block_proposal(proposal.clone())
+            .await?;
+        let committee = self.local_committee().await?;
+        let proposal = Box::new(proposal);
+        let certificate = if round.is_fast() {
+            let hashed_value = Hashed::new(ConfirmedBlock::new(executed_block));
+            self.submit_block_proposal(&committee, proposal, hashed_value)
+                .await?
+        } else {
+            let hashed_value = Hashed::new(ValidatedBlock::new(executed_block));
+            let certificate = self
+                .submit_block_proposal(&committee, proposal, hashed_value)
+                .await?;
+            self.finalize_block(&committee, certificate).await?
+        };
+        self.update_validators(Some(&committee)).await?;
+        Ok(certificate)
+    }
+
     /// Executes a new block.
     ///
     /// This must be preceded by a call to `prepare_chain()`.
@@ -2131,6 +2470,7 @@ where
             height,
             authenticated_signer: Some(identity),
             timestamp,
+            priority_fee: self.options.priority_fee,
         };
         // Make sure every incoming message succeeds and otherwise remove them.
         // Also, compute the final certified hash while we're at it.
@@ -2311,6 +2651,7 @@ where
                 AccountOwner::Application(_) => None,
             }),
             timestamp,
+            priority_fee: Amount::ZERO,
         };
         match self
             .stage_block_execution_and_discard_failing_messages(block, None)
@@ -2348,6 +2689,15 @@ where
         Ok(balance)
     }
 
+    /// Reads the base fee that the next block of this chain will be charged, on top of which
+    /// each operation pays the priority fee from our options.
+    ///
+    /// Does not process the inbox or attempt to synchronize with validators.
+    #[instrument(level = "trace")]
+    pub async fn local_base_fee(&self) -> Result<Amount, ChainClientError> {
+        Ok(self.chain_info().await?.base_fee)
+    }
+
     /// Reads the local balance of a user account.
     ///
     /// Does not process the inbox or attempt to synchronize with validators.
@@ -2389,6 +2739,26 @@ where
         ))
     }
 
+    /// Returns the time from which the earliest callback scheduled by an application on this
+    /// chain is due, if any.
+    ///
+    /// Does not process the inbox or attempt to synchronize with validators.
+    #[instrument(level = "trace")]
+    pub async fn next_callback_time(&self) -> Result<Option<Timestamp>, ChainClientError> {
+        let chain = self.chain_state_view().await?;
+        Ok(chain.execution_state.system.next_callback_time().await?)
+    }
+
+    /// Returns whether a callback scheduled by an application on this chain is due according to
+    /// the local clock.
+    async fn has_due_callbacks(&self) -> Result<bool, ChainClientError> {
+        let now = self.storage_client().clock().current_time();
+        Ok(self
+            .next_callback_time()
+            .await?
+            .is_some_and(|time| time <= now))
+    }
+
     /// Requests a `RegisterApplications` message from another chain so the application can be used
     /// on this one.
     #[instrument(level = "trace")]
@@ -2445,6 +2815,17 @@ where
         Ok(info)
     }
 
+    /// Downloads the certificates of the given chain, which need not be in the wallet, that
+    /// are missing from the local node.
+    #[instrument(level = "trace")]
+    pub async fn synchronize_chain_from_validators(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<Box<ChainInfo>, ChainClientError> {
+        let nodes = self.validator_nodes().await?;
+        self.synchronize_chain_state(&nodes, chain_id).await
+    }
+
     /// Processes the last pending block
     #[instrument(level = "trace")]
     pub async fn process_pending_block(
@@ -2460,6 +2841,7 @@ where
         &self,
     ) -> Result<ClientOutcome<Option<ConfirmedBlockCertificate>>, ChainClientError> {
         let info = self.request_leader_timeout_if_needed().await?;
+        self.recover_pending_proposal(&info).await?;
 
         // If there is a validated block in the current round, finalize it.
         if info.manager.has_locking_block_in_current_round()
@@ -2561,6 +2943,49 @@ where
         Ok(ClientOutcome::Committed(Some(certificate)))
     }
 
+    /// Adopts the proposal that the local node recorded for the next block as our pending
+    /// proposal, if it was signed with one of our keys and we don't have one already.
+    ///
+    /// The local node stores every proposal before it is sent to the validators. This lets a
+    /// restarted client retry the same block instead of signing a conflicting one.
+    async fn recover_pending_proposal(&self, info: &ChainInfo) -> Result<(), ChainClientError> {
+        let block = {
+            let mut state = self.state_mut();
+            if !state.take_may_recover_proposal() || state.pending_proposal().is_some() {
+                return Ok(());
+            }
+            match &info.manager.requested_proposed {
+                Some(proposal)
+                    if proposal.validated_block_certificate.is_none()
+                        && proposal.content.block.height == state.next_block_height()
+                        && state.known_key_pairs().contains_key(&proposal.owner) =>
+                {
+                    proposal.content.block.clone()
+                }
+                _ => return Ok(()),
+            }
+        };
+        let blob_ids = block.published_blob_ids().into_iter().collect::<Vec<_>>();
+        let Some(blobs) = self
+            .client
+            .local_node
+            .get_pending_blobs(&blob_ids, self.chain_id)
+            .await?
+        else {
+            warn!(
+                "Cannot recover the proposal for block {} of chain {}: missing blobs",
+                block.height, self.chain_id
+            );
+            return Ok(());
+        };
+        info!(
+            "Recovered the proposal for block {} of chain {}",
+            block.height, self.chain_id
+        );
+        self.state_mut().set_pending_proposal(block, blobs);
+        Ok(())
+    }
+
     /// Checks that the current height and hash match the `ChainClientState`. Then requests a
     /// leader timeout certificate if the current round has timed out. Returns the chain info for
     /// the (possibly new) current round.
@@ -2847,21 +3272,54 @@ where
         contract: Bytecode,
         service: Bytecode,
     ) -> Result<ClientOutcome<(BytecodeId, ConfirmedBlockCertificate)>, ChainClientError> {
-        let (contract_blob, service_blob, bytecode_id) =
-            create_bytecode_blobs(contract, service).await;
-        self.publish_bytecode_blobs(contract_blob, service_blob, bytecode_id)
+        let maximum_blob_size = self.local_committee().await?.policy().maximum_blob_size;
+        let (contract_blob, service_blob, chunk_blobs, bytecode_id) =
+            create_bytecode_blobs(contract, service, maximum_blob_size).await;
+        self.publish_bytecode_blobs(contract_blob, service_blob, chunk_blobs, bytecode_id)
             .await
     }
 
     /// Publishes some bytecode.
+    ///
+    /// The chunks of a bytecode that is too large for a single blob are published first, in
+    /// as many blocks as the limit on published blobs per block requires.
     #[cfg(not(target_arch = "wasm32"))]
-    #[instrument(level = "trace", skip(contract_blob, service_blob, bytecode_id))]
+    #[instrument(
+        level = "trace",
+        skip(contract_blob, service_blob, chunk_blobs, bytecode_id)
+    )]
     pub async fn publish_bytecode_blobs(
         &self,
         contract_blob: Blob,
         service_blob: Blob,
+        chunk_blobs: Vec<Blob>,
         bytecode_id: BytecodeId,
     ) -> Result<ClientOutcome<(BytecodeId, ConfirmedBlockCertificate)>, ChainClientError> {
+        if !chunk_blobs.is_empty() {
+            let maximum_published_blobs = self
+                .local_committee()
+                .await?
+                .policy()
+                .maximum_published_blobs;
+            let chunks_per_block = usize::try_from(maximum_published_blobs)
+                .unwrap_or(usize::MAX)
+                .max(1);
+            for blobs in chunk_blobs.chunks(chunks_per_block) {
+                let operations = blobs
+                    .iter()
+                    .map(|blob| {
+                        Operation::System(SystemOperation::PublishDataBlob {
+                            blob_hash: blob.id().hash,
+                        })
+                    })
+                    .collect();
+                if let ClientOutcome::WaitForTimeout(timeout) =
+                    self.execute_operations(operations, blobs.to_vec()).await?
+                {
+                    return Ok(ClientOutcome::WaitForTimeout(timeout));
+                }
+            }
+        }
         self.execute_operations(
             vec![Operation::System(SystemOperation::PublishBytecode {
                 bytecode_id,
@@ -2948,12 +3406,44 @@ where
         instantiation_argument: Vec<u8>,
         required_application_ids: Vec<UserApplicationId>,
     ) -> Result<ClientOutcome<(UserApplicationId, ConfirmedBlockCertificate)>, ChainClientError>
+    {
+        self.create_application_with_call_policy_untyped(
+            bytecode_id,
+            parameters,
+            instantiation_argument,
+            required_application_ids,
+            ApplicationCallPolicy::default(),
+        )
+        .await
+    }
+
+    /// Creates an application by instantiating some bytecode, restricting its callers and
+    /// system APIs according to the `call_policy`.
+    #[instrument(
+        level = "trace",
+        skip(
+            self,
+            bytecode_id,
+            parameters,
+            instantiation_argument,
+            required_application_ids
+        )
+    )]
+    pub async fn create_application_with_call_policy_untyped(
+        &self,
+        bytecode_id: BytecodeId,
+        parameters: Vec<u8>,
+        instantiation_argument: Vec<u8>,
+        required_application_ids: Vec<UserApplicationId>,
+        call_policy: ApplicationCallPolicy,
+    ) -> Result<ClientOutcome<(UserApplicationId, ConfirmedBlockCertificate)>, ChainClientError>
     {
         self.execute_operation(Operation::System(SystemOperation::CreateApplication {
             bytecode_id,
             parameters,
             instantiation_argument,
             required_application_ids,
+            call_policy,
         }))
         .await?
         .try_map(|certificate| {
@@ -2970,6 +3460,37 @@ where
         })
     }
 
+    /// Replaces the bytecode of an application created on this chain.
+    #[instrument(level = "trace")]
+    pub async fn upgrade_application(
+        &self,
+        application_id: UserApplicationId,
+        bytecode_id: BytecodeId,
+    ) -> Result<ClientOutcome<ConfirmedBlockCertificate>, ChainClientError> {
+        self.execute_operation(Operation::System(SystemOperation::UpgradeApplication {
+            application_id,
+            bytecode_id,
+        }))
+        .await
+    }
+
+    /// Replaces the restrictions on the callers and system APIs of an application created on
+    /// this chain.
+    #[instrument(level = "trace")]
+    pub async fn set_application_call_policy(
+        &self,
+        application_id: UserApplicationId,
+        policy: ApplicationCallPolicy,
+    ) -> Result<ClientOutcome<ConfirmedBlockCertificate>, ChainClientError> {
+        self.execute_operation(Operation::System(
+            SystemOperation::SetApplicationCallPolicy {
+                application_id,
+                policy,
+            },
+        ))
+        .await
+    }
+
     /// Creates a new committee and starts using it (admin chains only).
     #[instrument(level = "trace", skip(committee))]
     pub async fn stage_new_committee(
@@ -3015,7 +3536,8 @@ where
     }
 
     /// Creates blocks without any operations to process all incoming messages. This may require
-    /// several blocks.
+    /// several blocks. If callbacks scheduled by applications are due, the first block also
+    /// executes them.
     ///
     /// If not all certificates could be processed due to a timeout, the timestamp for when to retry
     /// is returned, too.
@@ -3027,12 +3549,20 @@ where
         let _latency = metrics::PROCESS_INBOX_WITHOUT_PREPARE_LATENCY.measure_latency();
 
         let mut certificates = Vec::new();
+        let mut callbacks_executed = false;
         loop {
             let incoming_bundles = self.pending_message_bundles().await?;
-            if incoming_bundles.is_empty() {
+            let mut operations = Vec::new();
+            if !callbacks_executed && self.has_due_callbacks().await? {
+                // Callbacks scheduled by this block are only executed by later calls, so that
+                // this loop terminates.
+                callbacks_executed = true;
+                operations.push(Operation::System(SystemOperation::ExecuteCallbacks));
+            }
+            if incoming_bundles.is_empty() && operations.is_empty() {
                 return Ok((certificates, None));
             }
-            match self.execute_block(vec![], vec![]).await {
+            match self.execute_block(operations, vec![]).await {
                 Ok(ExecuteBlockOutcome::Executed(certificate))
                 | Ok(ExecuteBlockOutcome::Conflict(certificate)) => certificates.push(certificate),
                 Ok(ExecuteBlockOutcome::WaitForTimeout(timeout)) => {
@@ -3475,6 +4005,33 @@ where
     }
 }
 
+/// The outcome of [`ChainClient::execute_operations_with_compensation`].
+#[derive(Debug)]
+pub enum AtomicOperationsOutcome {
+    /// The blocks with the operations were committed on both chains.
+    Committed {
+        first: ConfirmedBlockCertificate,
+        second: ConfirmedBlockCertificate,
+    },
+    /// The second block failed, so the first one was undone by the `rollback` blocks.
+    RolledBack {
+        first: ConfirmedBlockCertificate,
+        rollback: Vec<ConfirmedBlockCertificate>,
+        error: Box<ChainClientError>,
+    },
+    /// The second block failed, and only the `rollback` blocks were committed so far to undo
+    /// the first one: the next one is pending on `chain_id` until the round times out. It is
+    /// proposed again when the pending block of that chain is processed, e.g. with
+    /// [`ChainClient::process_pending_block`].
+    RollbackPending {
+        first: ConfirmedBlockCertificate,
+        rollback: Vec<ConfirmedBlockCertificate>,
+        error: Box<ChainClientError>,
+        chain_id: ChainId,
+        timeout: RoundTimeout,
+    },
+}
+
 /// The outcome of trying to commit a list of incoming messages and operations to the chain.
 #[derive(Debug)]
 enum ExecuteBlockOutcome {
@@ -91,6 +91,9 @@ pub struct ChainInfoQuery {
     /// Include a vote to switch to fallback mode, if appropriate.
     #[debug(skip_if = Not::not)]
     pub request_fallback: bool,
+    /// Query the hashes of the certificates that created each committee.
+    #[debug(skip_if = Not::not)]
+    pub request_committee_certificates: bool,
 }
 
 impl ChainInfoQuery {
@@ -106,6 +109,7 @@ impl ChainInfoQuery {
             request_manager_values: false,
             request_leader_timeout: false,
             request_fallback: false,
+            request_committee_certificates: false,
         }
     }
 
@@ -153,6 +157,11 @@ impl ChainInfoQuery {
         self.request_fallback = true;
         self
     }
+
+    pub fn with_committee_certificates(mut self) -> Self {
+        self.request_committee_certificates = true;
+        self
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -170,6 +179,8 @@ pub struct ChainInfo {
     pub manager: Box<ChainManagerInfo>,
     /// The current balance.
     pub chain_balance: Amount,
+    /// The base fee charged for the next block.
+    pub base_fee: Amount,
     /// The last block hash, if any.
     #[debug(skip_if = Option::is_none)]
     pub block_hash: Option<CryptoHash>,
@@ -197,6 +208,10 @@ pub struct ChainInfo {
     /// The response to `request_received_certificates_excluding_first_n`
     #[debug(skip_if = Vec::is_empty)]
     pub requested_received_log: Vec<ChainAndHeight>,
+    /// The hashes of the admin chain certificates that created each committee after the
+    /// genesis one, by increasing epoch (if requested).
+    #[debug(skip_if = Vec::is_empty)]
+    pub requested_committee_certificate_hashes: Vec<CryptoHash>,
 }
 
 impl ChainInfo {
@@ -277,6 +292,9 @@ where
             description: *system_state.description.get(),
             manager: Box::new(ChainManagerInfo::from(&view.manager)),
             chain_balance: *system_state.balance.get(),
+            base_fee: view
+                .next_block_base_fee()
+                .unwrap_or_else(|_| *view.base_fee.get()),
             block_hash: tip_state.block_hash,
             next_block_height: tip_state.next_block_height,
             timestamp: *view.execution_state.system.timestamp.get(),
@@ -287,6 +305,7 @@ where
             requested_sent_certificate_hashes: Vec::new(),
             count_received_log: view.received_log.count(),
             requested_received_log: Vec::new(),
+            requested_committee_certificate_hashes: Vec::new(),
         }
     }
 }
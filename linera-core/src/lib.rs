@@ -7,6 +7,7 @@
 #![recursion_limit = "256"]
 #![deny(clippy::large_futures)]
 
+pub mod audit;
 pub mod chain_worker;
 pub mod client;
 pub mod data_types;
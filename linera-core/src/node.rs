@@ -262,6 +262,16 @@ pub enum NodeError {
     EmptyBlobsNotFound,
     #[error("Local error handling validator response")]
     ResponseHandlingError { error: String },
+    #[error(
+        "Incompatible RPC protocol versions: this node supports versions {min_version} to \
+        {version}, but the validator supports versions {remote_min_version} to {remote_version}"
+    )]
+    IncompatibleProtocolVersion {
+        min_version: u32,
+        version: u32,
+        remote_min_version: u32,
+        remote_version: u32,
+    },
 }
 
 impl From<tonic::Status> for NodeError {
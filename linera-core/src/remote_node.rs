@@ -308,6 +308,30 @@ impl<N: ValidatorNode> RemoteNode<N> {
         self.node.download_certificates(hashes).await
     }
 
+    /// Downloads the admin chain certificates that created each committee after the genesis
+    /// one, by increasing epoch. They can be checked with [`verify_committee_changes`].
+    ///
+    /// [`verify_committee_changes`]: linera_chain::types::verify_committee_changes
+    #[instrument(level = "trace")]
+    pub async fn download_committee_certificates(
+        &self,
+        admin_id: ChainId,
+    ) -> Result<Vec<ConfirmedBlockCertificate>, NodeError> {
+        let query = ChainInfoQuery::new(admin_id).with_committee_certificates();
+        let info = self.handle_chain_info_query(query).await?;
+        let hashes = info.requested_committee_certificate_hashes;
+        let certificates = self.download_certificates(hashes.clone()).await?;
+        if !certificates
+            .iter()
+            .map(ConfirmedBlockCertificate::hash)
+            .eq(hashes)
+        {
+            warn!("Validator sent certificates that don't match the committee changes.");
+            return Err(NodeError::InvalidChainInfoResponse);
+        }
+        Ok(certificates)
+    }
+
     #[instrument(level = "trace", skip(validators))]
     async fn download_blob(
         validators: &[Self],
@@ -5,8 +5,10 @@
 #[path = "./wasm_client_tests.rs"]
 mod wasm;
 
+use std::collections::BTreeMap;
+
 use assert_matches::assert_matches;
-use futures::StreamExt;
+use futures::{future::Either, StreamExt};
 use linera_base::{
     crypto::*,
     data_types::*,
@@ -14,7 +16,7 @@ use linera_base::{
     ownership::{ChainOwnership, TimeoutConfig},
 };
 use linera_chain::{
-    data_types::{IncomingBundle, Medium, MessageBundle, Origin, PostedMessage},
+    data_types::{BlockProposal, IncomingBundle, Medium, MessageBundle, Origin, PostedMessage},
     manager::LockingBlock,
     types::Timeout,
     ChainError, ChainExecutionContext,
@@ -39,9 +41,10 @@ use crate::test_utils::ScyllaDbStorageBuilder;
 #[cfg(feature = "storage-service")]
 use crate::test_utils::ServiceStorageBuilder;
 use crate::{
+    audit::{self, AuditError},
     client::{
-        BlanketMessagePolicy, ChainClient, ChainClientError, ClientOutcome, MessageAction,
-        MessagePolicy,
+        AtomicOperationsOutcome, BlanketMessagePolicy, ChainClient, ChainClientError,
+        ClientOutcome, MessageAction, MessagePolicy,
     },
     local_node::LocalNodeError,
     node::{
@@ -130,6 +133,57 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_offline_signed_transfer<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let mut builder = TestBuilder::new(storage_builder, 4, 1)
+        .await?
+        .with_policy(ResourceControlPolicy::fuel_and_block());
+    let sender = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+    let key_pair = sender.key_pair().await?;
+    let owner = Owner::from(key_pair.public());
+    let transfer = Operation::System(SystemOperation::Transfer {
+        owner: None,
+        recipient: Recipient::chain(ChainId::root(2)),
+        amount: Amount::from_tokens(3),
+    });
+    let content = sender
+        .prepare_unsigned_proposal(vec![transfer], owner)
+        .await?;
+    assert_eq!(content.block.authenticated_signer, Some(owner));
+    // Preparing the proposal does not change the chain.
+    assert_eq!(sender.next_block_height(), BlockHeight::ZERO);
+    assert!(sender.pending_proposal().is_none());
+
+    // A proposal signed with a key that does not own the chain is rejected.
+    let forged_proposal =
+        BlockProposal::new_initial(content.round, content.block.clone(), &KeyPair::generate());
+    assert!(sender
+        .submit_signed_proposal(forged_proposal)
+        .await
+        .is_err());
+
+    let proposal = BlockProposal::new_initial(content.round, content.block, &key_pair);
+    let certificate = sender.submit_signed_proposal(proposal).await?;
+    assert_eq!(sender.next_block_height(), BlockHeight::from(1));
+    assert_eq!(sender.local_balance().await?, Amount::from_millis(999));
+    assert_eq!(
+        builder
+            .check_that_validators_have_certificate(sender.chain_id, BlockHeight::ZERO, 3)
+            .await
+            .unwrap(),
+        certificate
+    );
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
@@ -444,6 +498,49 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[test_log::test(tokio::test)]
+async fn test_recover_pending_proposal_after_restart<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let mut builder = TestBuilder::new(storage_builder, 4, 1).await?;
+    let sender = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+
+    // The proposal is signed and handled by the local node, but doesn't reach a quorum.
+    builder.set_fault_type([0, 1], FaultType::Offline).await;
+    assert!(sender.burn(None, Amount::ONE).await.is_err());
+    builder.set_fault_type([0, 1], FaultType::Honest).await;
+
+    // A restarted client uses the same storage but has lost its pending proposal. It must
+    // retry the same block rather than propose a new one.
+    let restarted = builder.make_client_with_storage(
+        sender.storage_client(),
+        sender.chain_id(),
+        sender.key_pair().await?,
+        None,
+        BlockHeight::ZERO,
+    );
+    assert!(restarted.pending_proposal().is_none());
+    let certificate = restarted
+        .process_pending_block()
+        .await?
+        .unwrap()
+        .expect("the recorded proposal should be committed");
+    assert_eq!(
+        certificate.block().body.operations,
+        vec![Operation::System(SystemOperation::Transfer {
+            owner: None,
+            recipient: Recipient::Burn,
+            amount: Amount::ONE,
+        })]
+    );
+    assert_eq!(restarted.local_balance().await?, Amount::from_tokens(3));
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
@@ -2229,6 +2326,243 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
+#[test_log::test(tokio::test)]
+async fn test_atomic_operations_on_two_chains<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let mut builder = TestBuilder::new(storage_builder, 4, 1).await?;
+    let client1 = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+    let client2 = builder.add_root_chain(2, Amount::ZERO).await?;
+    let transfer = |recipient: ChainId, amount: Amount| {
+        Operation::System(SystemOperation::Transfer {
+            owner: None,
+            recipient: Recipient::chain(recipient),
+            amount,
+        })
+    };
+
+    // The second chain forwards the tokens it receives from the first one.
+    let outcome = client1
+        .execute_operations_with_compensation(
+            vec![transfer(client2.chain_id(), Amount::ONE)],
+            &client2,
+            vec![transfer(ChainId::root(3), Amount::ONE)],
+            vec![],
+        )
+        .await?;
+    assert_matches!(
+        outcome,
+        ClientOutcome::Committed(AtomicOperationsOutcome::Committed { .. })
+    );
+    assert_eq!(client1.local_balance().await?, Amount::from_tokens(3));
+    assert_eq!(client2.local_balance().await?, Amount::ZERO);
+
+    // The second chain cannot forward more than it receives, so the transfer bounces back.
+    let outcome = client1
+        .execute_operations_with_compensation(
+            vec![transfer(client2.chain_id(), Amount::ONE)],
+            &client2,
+            vec![transfer(ChainId::root(3), Amount::from_tokens(2))],
+            vec![],
+        )
+        .await?;
+    assert_matches!(
+        outcome,
+        ClientOutcome::Committed(AtomicOperationsOutcome::RolledBack { rollback, .. })
+            if rollback.len() == 2
+    );
+    assert_eq!(client1.local_balance().await?, Amount::from_tokens(3));
+    assert_eq!(client2.local_balance().await?, Amount::ZERO);
+    Ok(())
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
+#[test_log::test(tokio::test)]
+async fn test_reject_message_bundles_after_pending_block_is_committed<B>(
+    storage_builder: B,
+) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let mut builder = TestBuilder::new(storage_builder, 4, 1).await?;
+    let client1 = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+    let client2 = builder.add_root_chain(2, Amount::ZERO).await?;
+    let first = client1
+        .transfer_to_account(None, Amount::ONE, Account::chain(client2.chain_id()))
+        .await?
+        .unwrap();
+    client2
+        .receive_certificate_and_update_validators(first.clone())
+        .await?;
+
+    // The block accepting the transfer can't be committed while half the validators are
+    // offline, so it remains pending.
+    builder.set_fault_type([0, 1], FaultType::Offline).await;
+    assert!(client2.burn(None, Amount::from_attos(1)).await.is_err());
+    assert!(client2.pending_proposal().is_some());
+
+    // Once they are back, the pending block is committed instead of rejecting the transfer.
+    builder.set_fault_type([0, 1], FaultType::Honest).await;
+    let outcome = client2.reject_message_bundles_from(first.hash()).await?;
+    let ClientOutcome::Committed(Either::Left(certificate)) = outcome else {
+        panic!("Expected the pending block to be committed, got {outcome:?}");
+    };
+    assert_eq!(certificate.block().header.height, BlockHeight::ZERO);
+    assert!(client2.pending_proposal().is_none());
+    assert_eq!(
+        client2.local_balance().await?,
+        Amount::ONE.saturating_sub(Amount::from_attos(1))
+    );
+    client1.synchronize_from_validators().await?;
+    client1.process_inbox().await?;
+    assert_eq!(client1.local_balance().await?, Amount::from_tokens(3));
+    Ok(())
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
+#[test_log::test(tokio::test)]
+async fn test_audit_exported_certificates<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let mut builder = TestBuilder::new(storage_builder, 4, 1).await?;
+    let sender = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+    let receiver = builder.add_root_chain(2, Amount::ZERO).await?;
+    for _ in 0..2 {
+        sender
+            .execute_operation(Operation::System(SystemOperation::Transfer {
+                owner: None,
+                recipient: Recipient::chain(receiver.chain_id()),
+                amount: Amount::ONE,
+            }))
+            .await?
+            .unwrap();
+    }
+    assert_eq!(receiver.process_inbox().await?.0.len(), 1);
+
+    let mut archive =
+        audit::export_certificates(&sender.storage_client(), [sender.chain_id()]).await?;
+    let receiver_archive =
+        audit::export_certificates(&receiver.storage_client(), [receiver.chain_id()]).await?;
+    archive.certificates.extend(receiver_archive.certificates);
+    archive.blobs.extend(receiver_archive.blobs);
+    let committees = BTreeMap::from([(Epoch::ZERO, builder.initial_committee.clone())]);
+
+    let report = audit::audit_certificates(
+        builder.make_storage().await?,
+        builder.admin_id(),
+        committees.clone(),
+        archive.clone(),
+    )
+    .await?;
+    assert_eq!(report.certificate_count, 3);
+    assert_eq!(
+        report.next_block_heights,
+        BTreeMap::from([
+            (sender.chain_id(), BlockHeight::from(2)),
+            (receiver.chain_id(), BlockHeight::from(1)),
+        ])
+    );
+    assert_eq!(
+        report.state_hashes.get(&receiver.chain_id()),
+        receiver.chain_info().await?.state_hash.as_ref()
+    );
+
+    // Without the committee, the signatures cannot be checked.
+    let result = audit::audit_certificates(
+        builder.make_storage().await?,
+        builder.admin_id(),
+        BTreeMap::new(),
+        archive.clone(),
+    )
+    .await;
+    assert_matches!(result, Err(AuditError::UnknownEpoch { epoch, .. }) if epoch == Epoch::ZERO);
+
+    // A gap in a chain's blocks is detected.
+    let first_hash = archive
+        .certificates
+        .iter()
+        .find(|certificate| certificate.block().header.chain_id == sender.chain_id())
+        .map(|certificate| certificate.hash());
+    archive
+        .certificates
+        .retain(|certificate| Some(certificate.hash()) != first_hash);
+    let result = audit::audit_certificates(
+        builder.make_storage().await?,
+        builder.admin_id(),
+        committees,
+        archive,
+    )
+    .await;
+    assert_matches!(result, Err(AuditError::Verification { .. }));
+    Ok(())
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
+#[test_log::test(tokio::test)]
+async fn test_audit_committee_changes<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let mut builder = TestBuilder::new(storage_builder, 4, 1).await?;
+    let admin = builder.add_root_chain(0, Amount::from_tokens(3)).await?;
+    let user = builder.add_root_chain(1, Amount::ZERO).await?;
+    let validators = builder.initial_committee.validators().clone();
+
+    // Create the committee of epoch 1 and remove the one of epoch 0.
+    let committee = Committee::new(validators.clone(), ResourceControlPolicy::only_fuel());
+    admin.stage_new_committee(committee.clone()).await?.unwrap();
+    admin.finalize_committee().await?.unwrap();
+    user.synchronize_from_validators().await?;
+    user.process_inbox().await?;
+    assert_eq!(user.epoch().await?, Epoch::from(1));
+
+    let mut archive =
+        audit::export_certificates(&admin.storage_client(), [admin.chain_id()]).await?;
+    let user_archive =
+        audit::export_certificates(&user.storage_client(), [user.chain_id()]).await?;
+    archive.certificates.extend(user_archive.certificates);
+    archive.blobs.extend(user_archive.blobs);
+    let genesis_committees = BTreeMap::from([(Epoch::ZERO, builder.initial_committee.clone())]);
+
+    let report = audit::audit_certificates(
+        builder.make_storage().await?,
+        builder.admin_id(),
+        genesis_committees.clone(),
+        archive.clone(),
+    )
+    .await?;
+    assert_eq!(
+        report.next_block_heights.get(&admin.chain_id()),
+        Some(&BlockHeight::from(2))
+    );
+
+    // A trusted committee for epoch 1 that differs from the created one is a mismatch.
+    let mut committees = genesis_committees;
+    committees.insert(
+        Epoch::from(1),
+        Committee::new(validators, ResourceControlPolicy::default()),
+    );
+    let result = audit::audit_certificates(
+        builder.make_storage().await?,
+        builder.admin_id(),
+        committees,
+        archive,
+    )
+    .await;
+    assert_matches!(
+        result,
+        Err(AuditError::InvalidCommitteeCreation { epoch, .. }) if epoch == Epoch::from(1)
+    );
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
@@ -894,6 +894,19 @@ where
         // the rest by asking validators.
         let storage = self.make_storage().await?;
         self.chain_client_storages.push(storage.clone());
+        Ok(self.make_client_with_storage(storage, chain_id, key_pair, block_hash, block_height))
+    }
+
+    /// Creates a client that uses the given local storage, e.g. to simulate a client that is
+    /// restarted and loses its in-memory state.
+    pub fn make_client_with_storage(
+        &self,
+        storage: B::Storage,
+        chain_id: ChainId,
+        key_pair: KeyPair,
+        block_hash: Option<CryptoHash>,
+        block_height: BlockHeight,
+    ) -> ChainClient<NodeProvider<B::Storage>, B::Storage> {
         let provider = self.make_node_provider();
         let builder = Arc::new(Client::new(
             provider,
@@ -907,7 +920,7 @@ where
             DEFAULT_GRACE_PERIOD,
             Duration::from_secs(1),
         ));
-        Ok(builder.create_chain_client(
+        builder.create_chain_client(
             chain_id,
             vec![key_pair],
             self.admin_id,
@@ -915,7 +928,7 @@ where
             Timestamp::from(0),
             block_height,
             None,
-        ))
+        )
     }
 
     /// Tries to find a (confirmation) certificate for the given chain_id and block height.
@@ -183,6 +183,72 @@ where
     Ok(())
 }
 
+#[cfg_attr(feature = "wasmer", test_case(WasmRuntime::Wasmer ; "wasmer"))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmRuntime::Wasmtime ; "wasmtime"))]
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn test_memory_create_application_with_chunked_bytecode(
+    wasm_runtime: WasmRuntime,
+) -> anyhow::Result<()> {
+    run_test_create_application_with_chunked_bytecode(MemoryStorageBuilder::with_wasm_runtime(
+        wasm_runtime,
+    ))
+    .await
+}
+
+async fn run_test_create_application_with_chunked_bytecode<B>(
+    storage_builder: B,
+) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let (contract_path, service_path) =
+        linera_execution::wasm_test::get_example_bytecode_paths("counter")?;
+    let contract_bytecode = Bytecode::load_from_file(contract_path).await?;
+    let service_bytecode = Bytecode::load_from_file(service_path).await?;
+    let contract_compressed_len = contract_bytecode.compress().compressed_bytes.len();
+
+    // The compressed bytecode is split into chunks, which are published two per block.
+    let mut policy = ResourceControlPolicy::default();
+    policy.maximum_blob_size = (contract_compressed_len as u64).div_ceil(4);
+    policy.maximum_published_blobs = 2;
+    let mut builder = TestBuilder::new(storage_builder, 4, 1)
+        .await?
+        .with_policy(policy);
+    let publisher = builder.add_root_chain(0, Amount::ONE).await?;
+    let creator = builder.add_root_chain(1, Amount::ONE).await?;
+
+    let (bytecode_id, _cert) = publisher
+        .publish_bytecode(contract_bytecode, service_bytecode)
+        .await
+        .unwrap()
+        .unwrap();
+    let bytecode_id = bytecode_id.with_abi::<counter::CounterAbi, (), u64>();
+    assert!(publisher.chain_info().await?.next_block_height.0 > 2);
+
+    creator.synchronize_from_validators().await.unwrap();
+    creator.process_inbox().await.unwrap();
+
+    let (application_id, _) = creator
+        .create_application(bytecode_id, &(), &10, vec![])
+        .await
+        .unwrap()
+        .unwrap();
+    creator
+        .execute_operation(Operation::user(application_id, &5_u64)?)
+        .await
+        .unwrap();
+
+    let query = Request::new("{ value }");
+    let outcome = creator
+        .query_user_application(application_id, &query)
+        .await
+        .unwrap();
+    let expected = async_graphql::Value::from_json(json!({"value": 15})).unwrap();
+    assert_eq!(outcome.response.data, expected);
+
+    Ok(())
+}
+
 #[cfg_attr(feature = "wasmer", test_case(WasmRuntime::Wasmer ; "wasmer"))]
 #[cfg_attr(feature = "wasmtime", test_case(WasmRuntime::Wasmtime ; "wasmtime"))]
 #[test_log::test(tokio::test(flavor = "multi_thread"))]
@@ -360,12 +426,12 @@ where
         panic!("Unexpected oracle responses: {:?}", responses);
     };
     if cfg!(feature = "unstable-oracles") {
-        let [OracleResponse::Service(json)] = &responses[..] else {
-            assert_eq!(&responses[..], &[]);
+        let [OracleResponse::ServiceWithFuel { response, fuel }] = &responses[..] else {
             panic!("Unexpected oracle responses: {:?}", responses);
         };
-        let response_json = serde_json::from_slice::<serde_json::Value>(json).unwrap();
+        let response_json = serde_json::from_slice::<serde_json::Value>(response).unwrap();
         assert_eq!(response_json["data"], json!({"value": 10}));
+        assert!(*fuel > 0);
     } else {
         assert!(responses.is_empty());
     }
@@ -589,7 +655,7 @@ where
             ..
         } = &messages[1][0];
         assert_matches!(
-            message, Message::System(SystemMessage::RegisterApplications { applications })
+            message, Message::System(SystemMessage::RegisterApplications { applications, .. })
             if applications.len() == 1 && matches!(
                 applications[0], UserApplicationDescription{ bytecode_id: b_id, .. }
                 if b_id == bytecode_id.forget_abi()
@@ -608,7 +674,7 @@ where
     let messages = &certs[0].block().body.incoming_bundles;
     assert!(messages.iter().any(|msg| matches!(
         &msg.bundle.messages[0].message,
-        Message::System(SystemMessage::RegisterApplications { applications })
+        Message::System(SystemMessage::RegisterApplications { applications, .. })
         if applications.iter().any(|app| app.bytecode_id == bytecode_id.forget_abi())
     )));
     assert!(messages
@@ -33,8 +33,8 @@ use linera_execution::{
     committee::Epoch,
     system::{SystemMessage, SystemOperation},
     test_utils::SystemExecutionState,
-    Message, MessageKind, Operation, OperationContext, ResourceController, TransactionTracker,
-    WasmContractModule, WasmRuntime,
+    ApplicationCallPolicy, Message, MessageKind, Operation, OperationContext, ResourceController,
+    TransactionTracker, WasmContractModule, WasmRuntime,
 };
 use linera_storage::{DbStorage, Storage};
 #[cfg(feature = "dynamodb")]
@@ -192,6 +192,7 @@ where
         parameters: parameters_bytes.clone(),
         instantiation_argument: initial_value_bytes.clone(),
         required_application_ids: vec![],
+        call_policy: ApplicationCallPolicy::default(),
     };
     let application_id = UserApplicationId {
         bytecode_id,
@@ -234,6 +235,7 @@ where
                 grant: Amount::ZERO,
                 refund_grant_to: None,
                 kind: MessageKind::Protected,
+                expires_at: None,
                 message: Message::System(SystemMessage::ApplicationCreated),
             }]],
             events: vec![Vec::new()],
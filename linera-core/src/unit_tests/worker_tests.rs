@@ -8,7 +8,7 @@
 mod wasm;
 
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, VecDeque},
     iter,
     num::NonZeroUsize,
     sync::{Arc, Mutex},
@@ -294,6 +294,7 @@ where
                             grant: Amount::ZERO,
                             refund_grant_to: None,
                             kind: MessageKind::Bouncing,
+                            expires_at: None,
                             message: posted_message.message.clone(),
                         }]
                     } else {
@@ -351,6 +352,7 @@ fn direct_outgoing_message(
         grant: Amount::ZERO,
         refund_grant_to: None,
         kind,
+        expires_at: None,
         message: Message::System(message),
     }
 }
@@ -366,6 +368,7 @@ fn channel_outgoing_message(
         grant: Amount::ZERO,
         refund_grant_to: None,
         kind,
+        expires_at: None,
         message: Message::System(message),
     }
 }
@@ -460,6 +463,57 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_competing_block_proposals_are_ordered_by_priority_fee<B>(
+    mut storage_builder: B,
+) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let sender_key_pair = KeyPair::generate();
+    let (_, worker) = init_worker_with_chains(
+        storage_builder.build().await?,
+        vec![
+            (
+                ChainDescription::Root(1),
+                sender_key_pair.public().into(),
+                Amount::from_tokens(5),
+            ),
+            (
+                ChainDescription::Root(2),
+                PublicKey::test_key(2).into(),
+                Amount::ZERO,
+            ),
+        ],
+    )
+    .await;
+    let make_proposal = |priority_fee| {
+        make_first_block(ChainId::root(1))
+            .with_simple_transfer(ChainId::root(2), Amount::ONE)
+            .with_priority_fee(priority_fee)
+            .into_first_proposal(&sender_key_pair)
+    };
+    let low_fee = Amount::from_millis(1);
+    let high_fee = Amount::from_millis(2);
+    // Start the chain worker, so that both proposals are waiting before the first one is
+    // handled.
+    drop(worker.chain_state_view(ChainId::root(1)).await?);
+    let (low_fee_result, high_fee_result) = tokio::join!(
+        worker.handle_block_proposal(make_proposal(low_fee)),
+        worker.handle_block_proposal(make_proposal(high_fee)),
+    );
+    high_fee_result?;
+    assert!(low_fee_result.is_err());
+    let chain = worker.chain_state_view(ChainId::root(1)).await?;
+    let vote = chain.manager.confirmed_vote().unwrap();
+    assert_eq!(vote.value().inner().block().header.priority_fee, high_fee);
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
 #[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
@@ -1161,6 +1215,91 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_handle_block_proposal_after_key_rotation<B>(
+    mut storage_builder: B,
+) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let sender_key_pair = KeyPair::generate();
+    let (_committee, worker) = init_worker_with_chains(
+        storage_builder.build().await?,
+        vec![(
+            ChainDescription::Root(1),
+            sender_key_pair.public().into(),
+            Amount::from_tokens(5),
+        )],
+    )
+    .await;
+    // The validator rotated its key, but the committee of the chain only has the old one.
+    let old_key_pair = worker.chain_worker_config.key_pair().unwrap().copy();
+    let old_name = ValidatorName(old_key_pair.public());
+    let new_key_pair = KeyPair::generate();
+    let new_name = ValidatorName(new_key_pair.public());
+    let worker = worker
+        .with_key_pair(Some(Arc::new(new_key_pair)))
+        .await
+        .with_previous_key_pair(Some(old_key_pair), Timestamp::from(u64::MAX));
+    let block_proposal = make_first_block(ChainId::root(1))
+        .with_simple_transfer(ChainId::root(2), Amount::from_tokens(5))
+        .with_authenticated_signer(Some(sender_key_pair.public().into()))
+        .into_first_proposal(&sender_key_pair);
+
+    let (chain_info_response, _actions) = worker.handle_block_proposal(block_proposal).await?;
+    chain_info_response.check(&old_name)?;
+    assert!(chain_info_response.check(&new_name).is_err());
+    let chain = worker.chain_state_view(ChainId::root(1)).await?;
+    assert_eq!(chain.manager.validated_vote().unwrap().validator, old_name);
+    Ok(())
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_previous_key_is_not_used_after_grace_period<B>(
+    mut storage_builder: B,
+) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let sender_key_pair = KeyPair::generate();
+    let (_committee, worker) = init_worker_with_chains(
+        storage_builder.build().await?,
+        vec![(
+            ChainDescription::Root(1),
+            sender_key_pair.public().into(),
+            Amount::from_tokens(5),
+        )],
+    )
+    .await;
+    // The grace period of the key rotation is over, although the committee of the chain
+    // still only has the old key.
+    let old_key_pair = worker.chain_worker_config.key_pair().unwrap().copy();
+    let old_name = ValidatorName(old_key_pair.public());
+    let new_key_pair = KeyPair::generate();
+    let new_name = ValidatorName(new_key_pair.public());
+    let worker = worker
+        .with_key_pair(Some(Arc::new(new_key_pair)))
+        .await
+        .with_previous_key_pair(Some(old_key_pair), storage_builder.clock().current_time());
+    let block_proposal = make_first_block(ChainId::root(1))
+        .with_simple_transfer(ChainId::root(2), Amount::from_tokens(5))
+        .with_authenticated_signer(Some(sender_key_pair.public().into()))
+        .into_first_proposal(&sender_key_pair);
+
+    let (chain_info_response, _actions) = worker.handle_block_proposal(block_proposal).await?;
+    chain_info_response.check(&new_name)?;
+    assert!(chain_info_response.check(&old_name).is_err());
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
 #[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
@@ -1557,6 +1696,7 @@ where
                 grant: Amount::ZERO,
                 refund_grant_to: None,
                 kind: MessageKind::Tracked,
+                expires_at: None,
                 index: 0,
                 message: Message::System(SystemMessage::Credit { amount, .. }),
             }] if amount == Amount::from_tokens(995)),
@@ -1694,6 +1834,7 @@ where
             grant: Amount::ZERO,
             refund_grant_to: None,
             kind: MessageKind::Tracked,
+            expires_at: None,
             index: 0,
             message: Message::System(SystemMessage::Credit { amount, .. })
         }] if amount == Amount::ONE),
@@ -1773,6 +1914,7 @@ where
             grant: Amount::ZERO,
             refund_grant_to: None,
             kind: MessageKind::Tracked,
+            expires_at: None,
             index: 0,
             message: Message::System(SystemMessage::Credit { amount, .. })
         }] if amount == Amount::from_tokens(10)),
@@ -1874,6 +2016,129 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_handle_repeated_cross_chain_request<B>(mut storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let storage = storage_builder.build().await?;
+    let sender_key_pair = KeyPair::generate();
+    let (committee, worker) = init_worker(
+        storage, /* is_client */ true, /* has_long_lived_services */ false,
+    );
+    let certificate = make_simple_transfer_certificate(
+        ChainDescription::Root(1),
+        &sender_key_pair,
+        ChainId::root(2),
+        Amount::from_tokens(10),
+        Vec::new(),
+        &committee,
+        Amount::ZERO,
+        &worker,
+        None,
+    )
+    .await;
+    worker
+        .handle_cross_chain_request(update_recipient_direct(ChainId::root(2), &certificate))
+        .await?;
+    // If the confirmation got lost, the sender retransmits the bundles. They are confirmed
+    // again, but not received twice.
+    let actions = worker
+        .handle_cross_chain_request(update_recipient_direct(ChainId::root(2), &certificate))
+        .await?;
+    assert_matches!(
+        actions.cross_chain_requests.as_slice(),
+        [CrossChainRequest::ConfirmUpdatedRecipient {
+            sender,
+            recipient,
+            latest_heights,
+        }] if *sender == ChainId::root(1)
+            && *recipient == ChainId::root(2)
+            && *latest_heights == vec![(Medium::Direct, BlockHeight::ZERO)]
+    );
+    assert!(actions.notifications.is_empty());
+    let chain = worker.chain_state_view(ChainId::root(2)).await?;
+    assert_eq!(chain.received_log.count(), 1);
+    Ok(())
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_retransmit_pending_deliveries<B>(mut storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let sender_key_pair = KeyPair::generate();
+    let (committee, worker) = init_worker_with_chains(
+        storage_builder.build().await?,
+        vec![
+            (
+                ChainDescription::Root(1),
+                sender_key_pair.public().into(),
+                Amount::from_tokens(5),
+            ),
+            (
+                ChainDescription::Root(2),
+                PublicKey::test_key(2).into(),
+                Amount::ZERO,
+            ),
+        ],
+    )
+    .await;
+    let certificate = make_simple_transfer_certificate(
+        ChainDescription::Root(1),
+        &sender_key_pair,
+        ChainId::root(2),
+        Amount::ONE,
+        Vec::new(),
+        &committee,
+        Amount::from_tokens(4),
+        &worker,
+        None,
+    )
+    .await;
+    // The cross-chain requests of the certificate are lost.
+    worker
+        .handle_confirmed_certificate(certificate.clone(), None)
+        .await?;
+
+    // The unacknowledged messages are sent again, unless the sender is filtered out.
+    let actions = worker
+        .retransmit_pending_deliveries(|chain_id| chain_id != ChainId::root(1))
+        .await?;
+    assert!(actions.cross_chain_requests.is_empty());
+    let actions = worker.retransmit_pending_deliveries(|_| true).await?;
+    assert_matches!(
+        actions.cross_chain_requests.as_slice(),
+        [CrossChainRequest::UpdateRecipient {
+            sender,
+            recipient,
+            ..
+        }] if *sender == ChainId::root(1) && *recipient == ChainId::root(2)
+    );
+
+    // Once the recipient acknowledged them, there is nothing left to retransmit.
+    let mut requests = VecDeque::from(actions.cross_chain_requests);
+    while let Some(request) = requests.pop_front() {
+        requests.extend(
+            worker
+                .handle_cross_chain_request(request)
+                .await?
+                .cross_chain_requests,
+        );
+    }
+    let actions = worker.retransmit_pending_deliveries(|_| true).await?;
+    assert!(actions.cross_chain_requests.is_empty());
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
 #[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
@@ -3682,6 +3947,65 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_retry_proposal_outcome_is_verified<B>(mut storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let storage = storage_builder.build().await?;
+    let chain_id = ChainId::root(0);
+    let key_pairs = generate_key_pairs(2);
+    let owner0 = Owner::from(key_pairs[0].public());
+    let owner1 = Owner::from(key_pairs[1].public());
+    let balances = vec![(ChainDescription::Root(0), owner0, Amount::from_tokens(2))];
+    let (committee, worker) = init_worker_with_chains(storage, balances).await;
+
+    // Add another owner and configure multi-leader rounds.
+    let block0 = make_first_block(chain_id).with_operation(SystemOperation::ChangeOwnership {
+        super_owners: Vec::new(),
+        owners: vec![(owner0, 100), (owner1, 100)],
+        multi_leader_rounds: 3,
+        open_multi_leader_rounds: false,
+        timeout_config: TimeoutConfig::default(),
+    });
+    let (executed_block0, _) = worker.stage_block_execution(block0, None).await?;
+    let value0 = Hashed::new(ConfirmedBlock::new(executed_block0));
+    let certificate0 = make_certificate(&committee, &worker, value0.clone());
+    worker
+        .fully_handle_certificate_with_notifications(certificate0, &())
+        .await?;
+
+    // A retry whose outcome does not match the block is rejected, even with a certificate.
+    let block1 = make_child_block(&value0)
+        .with_simple_transfer(ChainId::root(1), Amount::ONE)
+        .with_authenticated_signer(Some(owner1));
+    let (executed_block1, _) = worker.stage_block_execution(block1, None).await?;
+    let mut wrong_executed_block1 = executed_block1.clone();
+    wrong_executed_block1.outcome.state_hash = CryptoHash::test_hash("wrong state");
+    let wrong_value1 = Hashed::new(ValidatedBlock::new(wrong_executed_block1));
+    let wrong_certificate1 =
+        make_certificate_with_round(&committee, &worker, wrong_value1, Round::MultiLeader(0));
+    let proposal =
+        BlockProposal::new_retry(Round::MultiLeader(1), wrong_certificate1, &key_pairs[1]);
+    let result = worker.handle_block_proposal(proposal).await;
+    assert_matches!(result, Err(WorkerError::IncorrectOutcome { .. }));
+
+    // With the correct outcome, the validator re-executes the block and votes for it.
+    let value1 = Hashed::new(ValidatedBlock::new(executed_block1));
+    let certificate1 =
+        make_certificate_with_round(&committee, &worker, value1.clone(), Round::MultiLeader(0));
+    let proposal = BlockProposal::new_retry(Round::MultiLeader(1), certificate1, &key_pairs[1]);
+    let (response, _) = worker.handle_block_proposal(proposal).await?;
+    let vote = response.info.manager.pending.as_ref().unwrap();
+    assert_eq!(vote.value, LiteValue::new(&value1));
+    assert_eq!(vote.round, Round::MultiLeader(1));
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
 #[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
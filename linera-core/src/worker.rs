@@ -15,7 +15,8 @@ use linera_base::crypto::PublicKey;
 use linera_base::{
     crypto::{CryptoError, CryptoHash, KeyPair},
     data_types::{
-        ArithmeticError, Blob, BlockHeight, DecompressionError, Round, UserApplicationDescription,
+        ArithmeticError, Blob, BlockHeight, DecompressionError, Round, Timestamp,
+        UserApplicationDescription,
     },
     doc_scalar,
     hashed::Hashed,
@@ -47,10 +48,13 @@ use tracing::{error, instrument, trace, warn, Instrument as _};
 #[cfg(with_metrics)]
 use {
     linera_base::prometheus_util::{
-        bucket_interval, register_histogram_vec, register_int_counter_vec,
+        bucket_interval, register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
+    },
+    prometheus::{HistogramVec, IntCounterVec, IntGaugeVec},
+    std::sync::{
+        atomic::{AtomicU64, Ordering},
+        LazyLock,
     },
-    prometheus::{HistogramVec, IntCounterVec},
-    std::sync::LazyLock,
 };
 
 use crate::{
@@ -94,6 +98,22 @@ static NUM_BLOCKS: LazyLock<IntCounterVec> = LazyLock::new(|| {
     register_int_counter_vec("num_blocks", "Number of blocks added to chains", &[])
 });
 
+/// The greatest next block height among the chains handled by this worker. This is not broken
+/// down by chain: a label per chain would make the number of series grow with the number of
+/// chains ever seen by the validator.
+#[cfg(with_metrics)]
+static MAX_CHAIN_BLOCK_HEIGHT: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec(
+        "max_chain_block_height",
+        "Greatest height of the next block among the chains handled by this worker",
+        &[],
+    )
+});
+
+/// The value last reported by [`MAX_CHAIN_BLOCK_HEIGHT`], which gauges cannot update atomically.
+#[cfg(with_metrics)]
+static MAX_CHAIN_BLOCK_HEIGHT_VALUE: AtomicU64 = AtomicU64::new(0);
+
 #[cfg(with_metrics)]
 static CERTIFICATES_SIGNED: LazyLock<IntCounterVec> = LazyLock::new(|| {
     register_int_counter_vec(
@@ -119,6 +139,19 @@ impl NetworkActions {
     }
 }
 
+/// The result of adding the message bundles of a cross-chain update to an inbox.
+#[derive(Debug)]
+pub enum CrossChainUpdateOutcome {
+    /// New bundles were received, up to the given height.
+    Received {
+        height: BlockHeight,
+        actions: NetworkActions,
+    },
+    /// The bundles up to the given height had all been received already, e.g. because the
+    /// sender retransmitted them without having received our confirmation.
+    AlreadyReceived { height: BlockHeight },
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 /// Notification that a chain has a new certified block or a new message.
 pub struct Notification {
@@ -347,6 +380,21 @@ where
         self
     }
 
+    /// Configures the key the validator used before rotating to its current one. Until
+    /// `valid_until`, it keeps signing with it for the chains whose committee doesn't include
+    /// the current key yet.
+    #[instrument(level = "trace", skip(self, key_pair))]
+    pub fn with_previous_key_pair(
+        mut self,
+        key_pair: Option<KeyPair>,
+        valid_until: Timestamp,
+    ) -> Self {
+        self.chain_worker_config = self
+            .chain_worker_config
+            .with_previous_key_pair(key_pair, valid_until);
+        self
+    }
+
     #[instrument(level = "trace", skip(self, tracked_chains))]
     /// Configures the subset of chains that this worker is tracking.
     pub fn with_tracked_chains(
@@ -563,7 +611,22 @@ where
             .await?;
 
         #[cfg(with_metrics)]
-        NUM_BLOCKS.with_label_values(&[]).inc();
+        {
+            NUM_BLOCKS.with_label_values(&[]).inc();
+            let height = response.info.next_block_height.0;
+            MAX_CHAIN_BLOCK_HEIGHT_VALUE.fetch_max(height, Ordering::Relaxed);
+            // Set the gauge again if a concurrent update raised the maximum in the meantime, so
+            // that the last write is never a stale one.
+            loop {
+                let max = MAX_CHAIN_BLOCK_HEIGHT_VALUE.load(Ordering::Relaxed);
+                MAX_CHAIN_BLOCK_HEIGHT
+                    .with_label_values(&[])
+                    .set(i64::try_from(max).unwrap_or(i64::MAX));
+                if MAX_CHAIN_BLOCK_HEIGHT_VALUE.load(Ordering::Relaxed) == max {
+                    break;
+                }
+            }
+        }
 
         Ok((response, actions))
     }
@@ -607,7 +670,7 @@ where
         origin: Origin,
         recipient: ChainId,
         bundles: Vec<(Epoch, MessageBundle)>,
-    ) -> Result<Option<(BlockHeight, NetworkActions)>, WorkerError> {
+    ) -> Result<Option<CrossChainUpdateOutcome>, WorkerError> {
         self.query_chain_worker(recipient, move |callback| {
             ChainWorkerRequest::ProcessCrossChainUpdate {
                 origin,
@@ -981,29 +1044,37 @@ where
                 recipient,
                 bundle_vecs,
             } => {
-                let mut height_by_origin = Vec::new();
+                let mut latest_heights = Vec::new();
                 let mut actions = NetworkActions::default();
+                let mut notifications = Vec::new();
                 for (medium, bundles) in bundle_vecs {
                     let origin = Origin { sender, medium };
-                    if let Some((height, new_actions)) = self
+                    match self
                         .process_cross_chain_update(origin.clone(), recipient, bundles)
                         .await?
                     {
-                        actions.extend(new_actions);
-                        height_by_origin.push((origin, height));
+                        Some(CrossChainUpdateOutcome::Received {
+                            height,
+                            actions: new_actions,
+                        }) => {
+                            actions.extend(new_actions);
+                            latest_heights.push((origin.medium.clone(), height));
+                            notifications.push(Notification {
+                                chain_id: recipient,
+                                reason: Reason::NewIncomingBundle { origin, height },
+                            });
+                        }
+                        // Confirm again, in case the previous confirmation was lost.
+                        Some(CrossChainUpdateOutcome::AlreadyReceived { height }) => {
+                            latest_heights.push((origin.medium, height));
+                        }
+                        None => {}
                     }
                 }
-                if height_by_origin.is_empty() {
+                if latest_heights.is_empty() {
                     return Ok(NetworkActions::default());
                 }
-                let mut latest_heights = Vec::new();
-                for (origin, height) in height_by_origin {
-                    latest_heights.push((origin.medium.clone(), height));
-                    actions.notifications.push(Notification {
-                        chain_id: recipient,
-                        reason: Reason::NewIncomingBundle { origin, height },
-                    });
-                }
+                actions.notifications.extend(notifications);
                 actions
                     .cross_chain_requests
                     .push(CrossChainRequest::ConfirmUpdatedRecipient {
@@ -1034,6 +1105,60 @@ where
         }
     }
 
+    /// Returns the cross-chain requests retransmitting the outgoing messages that were not
+    /// acknowledged yet, for the chains selected by `filter`.
+    ///
+    /// This scans the whole index of chains with pending deliveries, and moves the selected
+    /// chains to the partition of the storage of this worker. This is meant to be done at
+    /// startup, and [`WorkerState::retransmit_partition_pending_deliveries`] afterwards.
+    #[instrument(level = "trace", skip_all)]
+    pub async fn retransmit_pending_deliveries<F>(
+        &self,
+        filter: F,
+    ) -> Result<NetworkActions, WorkerError>
+    where
+        F: Fn(ChainId) -> bool + Send + Sync,
+    {
+        let chain_ids = self.storage.read_pending_deliveries(filter).await?;
+        self.retransmit_chain_deliveries(chain_ids).await
+    }
+
+    /// Returns the cross-chain requests retransmitting the outgoing messages that were not
+    /// acknowledged yet, for the chains selected by `filter` in the partition of the storage of
+    /// this worker.
+    #[instrument(level = "trace", skip_all)]
+    pub async fn retransmit_partition_pending_deliveries<F>(
+        &self,
+        filter: F,
+    ) -> Result<NetworkActions, WorkerError>
+    where
+        F: Fn(ChainId) -> bool + Send + Sync,
+    {
+        let chain_ids = self
+            .storage
+            .read_partition_pending_deliveries(filter)
+            .await?;
+        self.retransmit_chain_deliveries(chain_ids).await
+    }
+
+    /// Returns the cross-chain requests retransmitting the outgoing messages of the given
+    /// chains that were not acknowledged yet.
+    async fn retransmit_chain_deliveries(
+        &self,
+        chain_ids: Vec<ChainId>,
+    ) -> Result<NetworkActions, WorkerError> {
+        let mut actions = NetworkActions::default();
+        for chain_id in chain_ids {
+            let new_actions = self
+                .query_chain_worker(chain_id, |callback| {
+                    ChainWorkerRequest::RetransmitPendingDeliveries { callback }
+                })
+                .await?;
+            actions.extend(new_actions);
+        }
+        Ok(actions)
+    }
+
     /// Updates the received certificate trackers to at least the given values.
     pub async fn update_received_certificate_trackers(
         &self,
@@ -3,12 +3,16 @@
 
 use std::collections::HashSet;
 
-use linera_base::{data_types::UserApplicationDescription, identifiers::UserApplicationId};
+use linera_base::{
+    data_types::UserApplicationDescription,
+    identifiers::{BytecodeId, Owner, UserApplicationId},
+};
 use linera_views::{
     context::Context,
     map_view::HashedMapView,
-    views::{ClonableView, HashableView},
+    views::{ClonableView, HashableView, ViewError},
 };
+use serde::{Deserialize, Serialize};
 #[cfg(with_testing)]
 use {
     linera_views::context::{create_test_memory_context, MemoryContext},
@@ -22,10 +26,135 @@ use crate::SystemExecutionError;
 #[path = "unit_tests/applications_tests.rs"]
 mod applications_tests;
 
+/// The restrictions an application places on who may call it and on the system APIs it may use.
+///
+/// A policy is set on the chain that created the application, by the owner who created it, and
+/// is shared with the other chains along with the application's description.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ApplicationCallPolicy {
+    /// If this is `None`, any application may call this one. If it is `Some`, only the
+    /// specified applications may call it.
+    pub callers: Option<Vec<UserApplicationId>>,
+    /// If this is `None`, the application may use all the privileged system APIs. If it is
+    /// `Some`, it may only use the specified ones.
+    pub system_apis: Option<Vec<PrivilegedSystemApi>>,
+    /// How calls into the application are handled while it is executing.
+    pub reentrancy: ReentrancyMode,
+}
+
+impl ApplicationCallPolicy {
+    /// Returns whether this policy doesn't restrict anything.
+    pub fn is_unrestricted(&self) -> bool {
+        self.callers.is_none()
+            && self.system_apis.is_none()
+            && self.reentrancy == ReentrancyMode::default()
+    }
+
+    /// Returns whether the application with ID `caller_id` may call the application.
+    pub fn can_be_called_by(&self, caller_id: &UserApplicationId) -> bool {
+        self.callers
+            .as_ref()
+            .map_or(true, |callers| callers.contains(caller_id))
+    }
+
+    /// Returns whether the application's service may be queried while the application is on
+    /// the call stack.
+    pub fn allows_reentrant_queries(&self) -> bool {
+        self.reentrancy == ReentrancyMode::RejectCalls
+    }
+
+    /// Returns whether the application may use the `system_api`.
+    pub fn can_use(&self, system_api: PrivilegedSystemApi) -> bool {
+        self.system_apis
+            .as_ref()
+            .map_or(true, |system_apis| system_apis.contains(&system_api))
+    }
+}
+
+/// How the runtime handles calls into an application that is already on the call stack.
+///
+/// Reentrant contract calls are always rejected, since an application's contract can't be
+/// entered again before it returns.
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize, clap::ValueEnum,
+)]
+pub enum ReentrancyMode {
+    /// Reentrant contract calls are rejected, but the application's service may be queried.
+    #[default]
+    RejectCalls,
+    /// Queries to the application's service are rejected too, so that other applications
+    /// can't read its state in the middle of its execution.
+    RejectCallsAndQueries,
+}
+
+/// The system APIs whose use can be restricted by an [`ApplicationCallPolicy`].
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+    clap::ValueEnum,
+)]
+pub enum PrivilegedSystemApi {
+    /// Transferring tokens.
+    Transfer,
+    /// Claiming tokens from an account on another chain.
+    Claim,
+    /// Opening a new chain.
+    OpenChain,
+    /// Closing the current chain.
+    CloseChain,
+    /// Changing the application permissions of the current chain.
+    ChangeApplicationPermissions,
+    /// Creating a new application.
+    CreateApplication,
+}
+
+/// A call policy of an application, as shared with other chains.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ApplicationCallPolicyUpdate {
+    /// The application the policy applies to.
+    pub application_id: UserApplicationId,
+    /// The number of times the policy had been set on the application's creator chain.
+    pub version: u32,
+    /// The policy itself.
+    pub policy: ApplicationCallPolicy,
+}
+
+/// A version of the bytecode of an upgraded application, as shared with other chains.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ApplicationUpgrade {
+    /// The upgraded application.
+    pub application_id: UserApplicationId,
+    /// The number of times the application had been upgraded on its creator chain.
+    pub version: u32,
+    /// The bytecode of this version.
+    pub bytecode_id: BytecodeId,
+}
+
 #[derive(Debug, ClonableView, HashableView)]
 pub struct ApplicationRegistryView<C> {
     /// The applications that are known by the chain.
     pub known_applications: HashedMapView<C, UserApplicationId, UserApplicationDescription>,
+    /// The bytecode that replaced the original bytecode of upgraded applications.
+    pub upgraded_bytecodes: HashedMapView<C, UserApplicationId, BytecodeId>,
+    /// The upgraded applications whose migration entrypoint hasn't been executed yet, with
+    /// the version of the bytecode that last used their state.
+    pub pending_migrations: HashedMapView<C, UserApplicationId, u32>,
+    /// The call policies of the applications that were given one, with the number of times
+    /// it was set on the application's creator chain.
+    pub call_policies: HashedMapView<C, UserApplicationId, (u32, ApplicationCallPolicy)>,
+    /// The number of times each upgraded application was upgraded, i.e. the version of its
+    /// current bytecode. Applications that were never upgraded have version 0.
+    pub versions: HashedMapView<C, UserApplicationId, u32>,
+    /// The signers who created applications on this chain, and are allowed to upgrade them.
+    pub owners: HashedMapView<C, UserApplicationId, Owner>,
 }
 
 #[cfg(with_testing)]
@@ -62,12 +191,28 @@ where
         Ok(id)
     }
 
+    /// Forgets an application, e.g. after its state was evicted from this chain.
+    ///
+    /// The application can be registered again, with an empty state, by a later
+    /// registration message.
+    pub fn unregister_application(&mut self, id: UserApplicationId) -> Result<(), ViewError> {
+        self.known_applications.remove(&id)?;
+        self.upgraded_bytecodes.remove(&id)?;
+        self.versions.remove(&id)?;
+        self.pending_migrations.remove(&id)?;
+        self.call_policies.remove(&id)?;
+        self.owners.remove(&id)
+    }
+
     /// Registers a newly created application.
+    ///
+    /// Only the `owner`, if any, is allowed to upgrade the application later.
     pub async fn register_new_application(
         &mut self,
         application_id: UserApplicationId,
         parameters: Vec<u8>,
         required_application_ids: Vec<UserApplicationId>,
+        owner: Option<Owner>,
     ) -> Result<(), SystemExecutionError> {
         // Make sure that referenced applications ids have been registered.
         for required_id in &required_application_ids {
@@ -86,6 +231,9 @@ where
         };
         self.known_applications
             .insert(&application_id, description)?;
+        if let Some(owner) = owner {
+            self.owners.insert(&application_id, owner)?;
+        }
         Ok(())
     }
 
@@ -100,6 +248,198 @@ where
             .ok_or_else(|| SystemExecutionError::UnknownApplicationId(Box::new(id)))
     }
 
+    /// Retrieves the description of the code that an application currently runs.
+    ///
+    /// This is the application's description, with the bytecode replaced by the latest upgrade,
+    /// if any.
+    pub async fn describe_application_code(
+        &self,
+        id: UserApplicationId,
+    ) -> Result<UserApplicationDescription, SystemExecutionError> {
+        let mut description = self.describe_application(id).await?;
+        if let Some(bytecode_id) = self.upgraded_bytecodes.get(&id).await? {
+            description.bytecode_id = bytecode_id;
+        }
+        Ok(description)
+    }
+
+    /// Replaces the bytecode of an existing application.
+    ///
+    /// The migration entrypoint of the new contract will be executed the next time the
+    /// application is used. If the application is upgraded again before that, only the last
+    /// contract migrates the state, from the version that last used it.
+    pub async fn upgrade_application(
+        &mut self,
+        id: UserApplicationId,
+        bytecode_id: BytecodeId,
+    ) -> Result<(), SystemExecutionError> {
+        let version = self.application_version(id).await?;
+        self.apply_upgrade(ApplicationUpgrade {
+            application_id: id,
+            version: version + 1,
+            bytecode_id,
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Applies an upgrade made on another chain, unless this chain already runs the same or
+    /// a later version of the application.
+    ///
+    /// Returns whether the bytecode was replaced.
+    pub async fn apply_upgrade(
+        &mut self,
+        upgrade: ApplicationUpgrade,
+    ) -> Result<bool, SystemExecutionError> {
+        let ApplicationUpgrade {
+            application_id: id,
+            version,
+            bytecode_id,
+        } = upgrade;
+        self.describe_application(id).await?;
+        let old_version = self.application_version(id).await?;
+        if version <= old_version {
+            return Ok(false);
+        }
+        self.upgraded_bytecodes.insert(&id, bytecode_id)?;
+        self.versions.insert(&id, version)?;
+        if !self.pending_migrations.contains_key(&id).await? {
+            self.pending_migrations.insert(&id, old_version)?;
+        }
+        Ok(true)
+    }
+
+    /// Returns the latest upgrades known by this chain of the given applications, so that
+    /// the chains they are shared with run the same code.
+    pub async fn describe_upgrades(
+        &self,
+        ids: impl IntoIterator<Item = UserApplicationId>,
+    ) -> Result<Vec<ApplicationUpgrade>, SystemExecutionError> {
+        let mut upgrades = Vec::new();
+        for id in ids {
+            if let Some(bytecode_id) = self.upgraded_bytecodes.get(&id).await? {
+                upgrades.push(ApplicationUpgrade {
+                    application_id: id,
+                    version: self.application_version(id).await?,
+                    bytecode_id,
+                });
+            }
+        }
+        Ok(upgrades)
+    }
+
+    /// Returns the number of times an application was upgraded.
+    pub async fn application_version(
+        &self,
+        id: UserApplicationId,
+    ) -> Result<u32, SystemExecutionError> {
+        Ok(self.versions.get(&id).await?.unwrap_or_default())
+    }
+
+    /// Marks the migration of an upgraded application as executed.
+    ///
+    /// Returns the version of the bytecode whose state must be migrated, if a migration was
+    /// pending.
+    pub async fn take_pending_migration(
+        &mut self,
+        id: UserApplicationId,
+    ) -> Result<Option<u32>, SystemExecutionError> {
+        let old_version = self.pending_migrations.get(&id).await?;
+        if old_version.is_some() {
+            self.pending_migrations.remove(&id)?;
+        }
+        Ok(old_version)
+    }
+
+    /// Returns whether the state of an upgraded application hasn't been migrated yet.
+    pub async fn has_pending_migration(
+        &self,
+        id: UserApplicationId,
+    ) -> Result<bool, SystemExecutionError> {
+        Ok(self.pending_migrations.contains_key(&id).await?)
+    }
+
+    /// Retrieves the call policy of an application.
+    pub async fn call_policy(
+        &self,
+        id: UserApplicationId,
+    ) -> Result<ApplicationCallPolicy, SystemExecutionError> {
+        Ok(self
+            .call_policies
+            .get(&id)
+            .await?
+            .map(|(_, policy)| policy)
+            .unwrap_or_default())
+    }
+
+    /// Replaces the call policy of an existing application created on this chain.
+    pub async fn set_call_policy(
+        &mut self,
+        id: UserApplicationId,
+        policy: ApplicationCallPolicy,
+    ) -> Result<(), SystemExecutionError> {
+        let version = self.call_policy_version(id).await?;
+        self.apply_call_policy(ApplicationCallPolicyUpdate {
+            application_id: id,
+            version: version + 1,
+            policy,
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Applies a call policy set on another chain, unless this chain already knows the same
+    /// or a later version of it.
+    ///
+    /// Returns whether the policy was replaced.
+    pub async fn apply_call_policy(
+        &mut self,
+        update: ApplicationCallPolicyUpdate,
+    ) -> Result<bool, SystemExecutionError> {
+        let ApplicationCallPolicyUpdate {
+            application_id: id,
+            version,
+            policy,
+        } = update;
+        self.describe_application(id).await?;
+        if version <= self.call_policy_version(id).await? {
+            return Ok(false);
+        }
+        self.call_policies.insert(&id, (version, policy))?;
+        Ok(true)
+    }
+
+    /// Returns the call policies known by this chain of the given applications, so that the
+    /// chains they are shared with enforce the same restrictions.
+    pub async fn describe_call_policies(
+        &self,
+        ids: impl IntoIterator<Item = UserApplicationId>,
+    ) -> Result<Vec<ApplicationCallPolicyUpdate>, SystemExecutionError> {
+        let mut updates = Vec::new();
+        for id in ids {
+            if let Some((version, policy)) = self.call_policies.get(&id).await? {
+                updates.push(ApplicationCallPolicyUpdate {
+                    application_id: id,
+                    version,
+                    policy,
+                });
+            }
+        }
+        Ok(updates)
+    }
+
+    /// Returns the number of times the call policy of an application was set.
+    async fn call_policy_version(
+        &self,
+        id: UserApplicationId,
+    ) -> Result<u32, SystemExecutionError> {
+        Ok(self
+            .call_policies
+            .get(&id)
+            .await?
+            .map_or(0, |(version, _)| version))
+    }
+
     /// Retrieves the recursive dependencies of applications and apply a topological sort.
     pub async fn find_dependencies(
         &self,
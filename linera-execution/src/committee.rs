@@ -6,13 +6,17 @@ use std::{borrow::Cow, collections::BTreeMap, str::FromStr};
 
 use async_graphql::InputObject;
 use linera_base::{
-    crypto::{CryptoError, PublicKey},
+    crypto::{BcsSignable, CryptoError, KeyPair, PublicKey, Signature},
     data_types::ArithmeticError,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::policy::ResourceControlPolicy;
 
+#[cfg(test)]
+#[path = "unit_tests/committee_tests.rs"]
+mod committee_tests;
+
 /// A number identifying the configuration of the chain (aka the committee).
 #[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash, Default, Debug)]
 pub struct Epoch(pub u32);
@@ -99,6 +103,59 @@ pub struct ValidatorState {
     pub votes: u64,
 }
 
+/// The announcement by a validator that it replaces its key `old_name` with `new_name`,
+/// signed with both keys.
+///
+/// Administrators apply it by moving the voting power and network address of the validator
+/// to the new key in the next committee. Until all chains migrated to that committee, and
+/// at most for the grace period chosen by the validator, the old key remains valid: the
+/// validator keeps signing with it for the chains of the previous committees.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ValidatorKeyRotation {
+    /// The current key of the validator.
+    pub old_name: ValidatorName,
+    /// The key replacing it.
+    pub new_name: ValidatorName,
+    /// The signature of the rotation by the current key.
+    pub signature: Signature,
+    /// The signature of the rotation by the new key, proving that the validator holds it.
+    pub new_signature: Signature,
+}
+
+/// The content signed in a [`ValidatorKeyRotation`].
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyRotationStatement {
+    old_name: ValidatorName,
+    new_name: ValidatorName,
+}
+
+impl<'de> BcsSignable<'de> for KeyRotationStatement {}
+
+impl ValidatorKeyRotation {
+    /// Creates the announcement that the validator with key `old_key` now uses `new_key`.
+    pub fn new(old_key: &KeyPair, new_key: &KeyPair) -> Self {
+        let old_name = ValidatorName(old_key.public());
+        let new_name = ValidatorName(new_key.public());
+        let statement = KeyRotationStatement { old_name, new_name };
+        ValidatorKeyRotation {
+            old_name,
+            new_name,
+            signature: Signature::new(&statement, old_key),
+            new_signature: Signature::new(&statement, new_key),
+        }
+    }
+
+    /// Checks that the rotation was signed by both the old and the new key.
+    pub fn check(&self) -> Result<(), CryptoError> {
+        let statement = KeyRotationStatement {
+            old_name: self.old_name,
+            new_name: self.new_name,
+        };
+        self.signature.check(&statement, self.old_name.0)?;
+        self.new_signature.check(&statement, self.new_name.0)
+    }
+}
+
 /// A set of validators (identified by their public keys) and their voting rights.
 #[derive(Eq, PartialEq, Hash, Clone, Debug, Default, InputObject)]
 pub struct Committee {
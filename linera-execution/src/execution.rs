@@ -8,8 +8,10 @@ use std::{
 
 use futures::{stream::FuturesOrdered, FutureExt, StreamExt, TryStreamExt};
 use linera_base::{
-    data_types::{Amount, BlockHeight, Timestamp},
-    identifiers::{Account, AccountOwner, ChainId, Destination, Owner},
+    data_types::{Amount, ArithmeticError, BlockHeight, Timestamp},
+    identifiers::{
+        Account, AccountOwner, ChainId, Destination, GenericApplicationId, Owner, StreamId,
+    },
 };
 use linera_views::{
     context::Context,
@@ -20,9 +22,7 @@ use linera_views::{
 use linera_views_derive::CryptoHashView;
 #[cfg(with_testing)]
 use {
-    crate::{
-        ResourceControlPolicy, ResourceTracker, TestExecutionRuntimeContext, UserContractCode,
-    },
+    crate::{ResourceTracker, TestExecutionRuntimeContext, UserContractCode},
     linera_base::data_types::Blob,
     linera_views::context::MemoryContext,
     std::sync::Arc,
@@ -30,11 +30,16 @@ use {
 
 use super::{runtime::ServiceRuntimeRequest, ExecutionRequest};
 use crate::{
-    resources::ResourceController, system::SystemExecutionStateView, ContractSyncRuntime,
-    ExecutionError, ExecutionOutcome, ExecutionRuntimeConfig, ExecutionRuntimeContext, Message,
-    MessageContext, MessageKind, Operation, OperationContext, Query, QueryContext, QueryOutcome,
-    RawExecutionOutcome, RawOutgoingMessage, ServiceSyncRuntime, SystemMessage, TransactionTracker,
-    UserApplicationDescription, UserApplicationId,
+    resources::ResourceController,
+    system::{
+        ScheduledCallback, SystemExecutionStateView, SystemOperation, EVICTED_APPLICATIONS_STREAM,
+        FAILED_CALLBACKS_STREAM,
+    },
+    ContractSyncRuntime, ExecutionError, ExecutionOutcome, ExecutionRuntimeConfig,
+    ExecutionRuntimeContext, Message, MessageContext, MessageKind, Operation, OperationContext,
+    Query, QueryContext, QueryOutcome, RawExecutionOutcome, RawOutgoingMessage,
+    ResourceControlPolicy, ServiceQueryLimits, ServiceSyncRuntime, SystemMessage,
+    TransactionTracker, UserApplicationDescription, UserApplicationId,
 };
 
 /// A view accessing the execution state of a chain.
@@ -130,6 +135,7 @@ pub enum UserAction {
     Instantiate(OperationContext, Vec<u8>),
     Operation(OperationContext, Vec<u8>),
     Message(MessageContext, Vec<u8>),
+    Callback(OperationContext, Vec<u8>),
 }
 
 impl UserAction {
@@ -139,6 +145,7 @@ impl UserAction {
             Instantiate(context, _) => context.authenticated_signer,
             Operation(context, _) => context.authenticated_signer,
             Message(context, _) => context.authenticated_signer,
+            Callback(context, _) => context.authenticated_signer,
         }
     }
 
@@ -147,6 +154,7 @@ impl UserAction {
             UserAction::Instantiate(context, _) => context.height,
             UserAction::Operation(context, _) => context.height,
             UserAction::Message(context, _) => context.height,
+            UserAction::Callback(context, _) => context.height,
         }
     }
 
@@ -155,6 +163,7 @@ impl UserAction {
             UserAction::Instantiate(context, _) => context.round,
             UserAction::Operation(context, _) => context.round,
             UserAction::Message(context, _) => context.round,
+            UserAction::Callback(context, _) => context.round,
         }
     }
 }
@@ -164,6 +173,73 @@ where
     C: Context + Clone + Send + Sync + 'static,
     C::Extra: ExecutionRuntimeContext,
 {
+    /// Charges the storage rent of this chain up to `timestamp`. If the rent is still unpaid
+    /// one grace period after it became overdue, the application with the largest state on
+    /// this chain is evicted, and its share of the debt is written off. The remaining
+    /// applications are given another grace period.
+    pub async fn charge_storage_rent(
+        &mut self,
+        policy: &ResourceControlPolicy,
+        timestamp: Timestamp,
+    ) -> Result<(), ExecutionError> {
+        self.system.charge_storage_rent(policy, timestamp)?;
+        if !self.system.must_evict_for_storage_rent(policy, timestamp) {
+            return Ok(());
+        }
+        let bytes_stored = *self.system.bytes_stored.get();
+        let largest = self
+            .system
+            .bytes_stored_by_application
+            .index_values()
+            .await?
+            .into_iter()
+            .reduce(|largest, next| if next.1 > largest.1 { next } else { largest });
+        let mut debt = Amount::ZERO;
+        if let Some((application_id, bytes)) = largest {
+            self.evict_application(application_id, timestamp).await?;
+            let remaining_bytes = bytes_stored.saturating_sub(bytes);
+            if remaining_bytes > 0 {
+                let remaining_debt = u128::from(*self.system.storage_rent_debt.get())
+                    .checked_mul(u128::from(remaining_bytes))
+                    .ok_or(ArithmeticError::Overflow)?
+                    / u128::from(bytes_stored);
+                debt = Amount::from_attos(remaining_debt);
+            }
+        }
+        self.system.storage_rent_debt.set(debt);
+        let due_by = (!debt.is_zero()).then_some(timestamp);
+        self.system.storage_rent_due_by.set(due_by);
+        Ok(())
+    }
+
+    /// Deletes the state of an application and unregisters it from this chain, so that it
+    /// can't run with an empty state. The eviction is recorded on the
+    /// [`EVICTED_APPLICATIONS_STREAM`], where the application's users can read it.
+    async fn evict_application(
+        &mut self,
+        application_id: UserApplicationId,
+        timestamp: Timestamp,
+    ) -> Result<(), ExecutionError> {
+        tracing::info!(
+            ?application_id,
+            "Evicting an application for unpaid storage rent"
+        );
+        self.users.remove_entry(&application_id)?;
+        self.system.record_bytes_stored(application_id, 0).await?;
+        self.system
+            .registry
+            .unregister_application(application_id)?;
+        let stream_id = StreamId {
+            application_id: GenericApplicationId::System,
+            stream_name: EVICTED_APPLICATIONS_STREAM.into(),
+        };
+        let key = bcs::to_bytes(&application_id)?;
+        self.system
+            .latest_events
+            .insert(&(stream_id, key), bcs::to_bytes(&timestamp)?)?;
+        Ok(())
+    }
+
     #[expect(clippy::too_many_arguments)]
     async fn run_user_action(
         &mut self,
@@ -176,7 +252,9 @@ where
         txn_tracker: &mut TransactionTracker,
         resource_controller: &mut ResourceController<Option<Owner>>,
     ) -> Result<(), ExecutionError> {
-        let ExecutionRuntimeConfig {} = self.context().extra().execution_runtime_config();
+        let ExecutionRuntimeConfig {
+            service_query_limits: _,
+        } = self.context().extra().execution_runtime_config();
         self.run_user_action_with_runtime(
             application_id,
             chain_id,
@@ -252,6 +330,69 @@ where
         Ok(())
     }
 
+    /// Runs a due callback. If it fails, its changes are discarded and the failure is
+    /// recorded as an event on the [`FAILED_CALLBACKS_STREAM`], instead of failing the block.
+    async fn run_callback(
+        &mut self,
+        context: OperationContext,
+        local_time: Timestamp,
+        callback: ScheduledCallback,
+        txn_tracker: &mut TransactionTracker,
+        resource_controller: &mut ResourceController<Option<Owner>>,
+    ) -> Result<(), ExecutionError> {
+        let application_id = callback.application_id;
+        // The application may have lost the permission to execute operations on this chain
+        // since it scheduled the callback.
+        let is_permitted = self
+            .system
+            .application_permissions
+            .get()
+            .can_execute_operations(&application_id.into());
+        let result = if is_permitted {
+            let snapshot = self.clone_unchecked()?;
+            let mut callback_tracker = txn_tracker.fork();
+            let result = self
+                .run_user_action(
+                    application_id,
+                    context.chain_id,
+                    local_time,
+                    UserAction::Callback(context, callback.argument.clone()),
+                    context.refund_grant_to(),
+                    None,
+                    &mut callback_tracker,
+                    resource_controller,
+                )
+                .await;
+            match result {
+                Ok(()) => {
+                    txn_tracker.merge(callback_tracker);
+                    Ok(())
+                }
+                // Storage errors are not the application's fault, and must not make validators
+                // disagree on the outcome of the block.
+                Err(error @ ExecutionError::ViewError(_)) => return Err(error),
+                Err(error) => {
+                    *self = snapshot;
+                    txn_tracker.merge_oracle_responses(callback_tracker);
+                    Err(error)
+                }
+            }
+        } else {
+            Err(ExecutionError::UnauthorizedApplication(application_id))
+        };
+        if let Err(error) = result {
+            tracing::debug!(%error, ?application_id, "Dropping a failed callback");
+            let mut outcome = RawExecutionOutcome::default();
+            outcome.events.push((
+                FAILED_CALLBACKS_STREAM.into(),
+                Vec::new(),
+                bcs::to_bytes(&callback)?,
+            ));
+            txn_tracker.add_system_outcome(outcome)?;
+        }
+        Ok(())
+    }
+
     /// Schedules application registration messages when needed.
     ///
     /// Ensures that the outgoing messages in `results` are preceded by a system message that
@@ -292,16 +433,43 @@ where
                     )
                     .await?;
 
-                Ok::<_, ExecutionError>(RawOutgoingMessage {
+                // The recipient also adopts the upgrades and call policies known by this chain,
+                // so that both chains run the same code with the same restrictions.
+                let upgrades = self
+                    .system
+                    .registry
+                    .describe_upgrades(applications.iter().map(UserApplicationId::from))
+                    .await?;
+                let call_policies = self
+                    .system
+                    .registry
+                    .describe_call_policies(applications.iter().map(UserApplicationId::from))
+                    .await?;
+                let mut messages = vec![RawOutgoingMessage {
                     destination: destination.clone(),
                     authenticated: false,
                     grant: Amount::ZERO,
                     kind: MessageKind::Simple,
-                    message: SystemMessage::RegisterApplications { applications },
-                })
+                    expires_at: None,
+                    message: SystemMessage::RegisterApplications {
+                        applications,
+                        call_policies,
+                    },
+                }];
+                if !upgrades.is_empty() {
+                    messages.push(RawOutgoingMessage {
+                        destination: destination.clone(),
+                        authenticated: false,
+                        grant: Amount::ZERO,
+                        kind: MessageKind::Simple,
+                        expires_at: None,
+                        message: SystemMessage::UpgradeApplications(upgrades),
+                    });
+                }
+                Ok::<_, ExecutionError>(messages)
             })
             .collect::<FuturesOrdered<_>>()
-            .try_collect::<Vec<_>>()
+            .try_concat()
             .await?;
 
         let system_outcome = RawExecutionOutcome {
@@ -330,6 +498,28 @@ where
     ) -> Result<(), ExecutionError> {
         assert_eq!(context.chain_id, self.context().extra().chain_id());
         match operation {
+            Operation::System(SystemOperation::ExecuteCallbacks) => {
+                let now = *self.system.timestamp.get();
+                // Callbacks are not authenticated by the signer of the block that executes them.
+                let context = OperationContext {
+                    authenticated_signer: None,
+                    ..context
+                };
+                let limit =
+                    usize::try_from(resource_controller.policy.maximum_callbacks_per_operation)
+                        .unwrap_or(usize::MAX);
+                let callbacks = self.system.take_due_callbacks(now, limit).await?;
+                for callback in callbacks {
+                    self.run_callback(
+                        context,
+                        local_time,
+                        callback,
+                        txn_tracker,
+                        resource_controller,
+                    )
+                    .await?;
+                }
+            }
             Operation::System(op) => {
                 let new_application = self
                     .system
@@ -428,6 +618,7 @@ where
                     authenticated: true,
                     grant,
                     kind: MessageKind::Bouncing,
+                    expires_at: None,
                     message,
                 });
                 txn_tracker.add_system_outcome(outcome)?;
@@ -446,6 +637,7 @@ where
                     authenticated: true,
                     grant,
                     kind: MessageKind::Bouncing,
+                    expires_at: None,
                     message: bytes,
                 });
                 txn_tracker.add_user_outcome(application_id, outcome)?;
@@ -468,6 +660,7 @@ where
             authenticated: false,
             grant: Amount::ZERO,
             kind: MessageKind::Tracked,
+            expires_at: None,
             message: SystemMessage::Credit {
                 amount,
                 source: context.authenticated_signer.map(AccountOwner::User),
@@ -495,21 +688,29 @@ where
                 application_id,
                 bytes,
             } => {
-                let ExecutionRuntimeConfig {} = self.context().extra().execution_runtime_config();
+                let ExecutionRuntimeConfig {
+                    service_query_limits,
+                } = self.context().extra().execution_runtime_config();
                 let outcome = match endpoint {
                     Some(endpoint) => {
                         self.query_user_application_with_long_lived_service(
                             application_id,
                             context,
                             bytes,
+                            service_query_limits,
                             &mut endpoint.incoming_execution_requests,
                             &mut endpoint.runtime_request_sender,
                         )
                         .await?
                     }
                     None => {
-                        self.query_user_application(application_id, context, bytes)
-                            .await?
+                        self.query_user_application(
+                            application_id,
+                            context,
+                            bytes,
+                            service_query_limits,
+                        )
+                        .await?
                     }
                 };
                 Ok(outcome.into())
@@ -522,13 +723,15 @@ where
         application_id: UserApplicationId,
         context: QueryContext,
         query: Vec<u8>,
+        limits: ServiceQueryLimits,
     ) -> Result<QueryOutcome<Vec<u8>>, ExecutionError> {
         let (execution_state_sender, mut execution_state_receiver) =
             futures::channel::mpsc::unbounded();
         let (code, description) = self.load_service(application_id).await?;
 
         let service_runtime_task = linera_base::task::Blocking::spawn(move |mut codes| {
-            let mut runtime = ServiceSyncRuntime::new(execution_state_sender, context);
+            let mut runtime =
+                ServiceSyncRuntime::new(execution_state_sender, context).with_query_limits(limits);
 
             async move {
                 let code = codes.next().await.expect("we send this immediately below");
@@ -552,6 +755,7 @@ where
         application_id: UserApplicationId,
         context: QueryContext,
         query: Vec<u8>,
+        limits: ServiceQueryLimits,
         incoming_execution_requests: &mut futures::channel::mpsc::UnboundedReceiver<
             ExecutionRequest,
         >,
@@ -565,6 +769,7 @@ where
                 application_id,
                 context,
                 query,
+                limits,
                 callback: outcome_sender,
             })
             .expect("Service runtime thread should only stop when `request_sender` is dropped");
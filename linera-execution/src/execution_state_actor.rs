@@ -13,7 +13,7 @@ use linera_base::prometheus_util::{bucket_latencies, register_histogram_vec, Mea
 use linera_base::{
     data_types::{Amount, ApplicationPermissions, BlobContent, Timestamp},
     hex_debug, hex_vec_debug,
-    identifiers::{Account, AccountOwner, BlobId, MessageId, Owner},
+    identifiers::{Account, AccountOwner, BlobId, MessageId, Owner, StreamId},
     ownership::ChainOwnership,
 };
 use linera_views::{batch::Batch, context::Context, views::View};
@@ -25,9 +25,9 @@ use reqwest::{header::CONTENT_TYPE, Client};
 use crate::{
     system::{CreateApplicationResult, OpenChainConfig, Recipient},
     util::RespondExt,
-    BytecodeId, ExecutionError, ExecutionRuntimeContext, ExecutionStateView, RawExecutionOutcome,
-    RawOutgoingMessage, SystemExecutionError, SystemMessage, UserApplicationDescription,
-    UserApplicationId, UserContractCode, UserServiceCode,
+    ApplicationCallPolicy, BytecodeId, ExecutionError, ExecutionRuntimeContext, ExecutionStateView,
+    RawExecutionOutcome, RawOutgoingMessage, SystemExecutionError, SystemMessage,
+    UserApplicationDescription, UserApplicationId, UserContractCode, UserServiceCode,
 };
 
 #[cfg(with_metrics)]
@@ -66,11 +66,22 @@ where
         #[cfg(with_metrics)]
         let _latency = LOAD_CONTRACT_LATENCY.measure_latency();
         let description = self.system.registry.describe_application(id).await?;
-        let code = self
+        let code_description = self.system.registry.describe_application_code(id).await?;
+        let mut code = self
             .context()
             .extra()
-            .get_user_contract(&description)
+            .get_user_contract(&code_description)
             .await?;
+        let fuel_schedule = self
+            .system
+            .current_committee()
+            .map(|(_, committee)| committee.policy().fuel_schedule)
+            .unwrap_or_default();
+        // The shared code is left as it is, since chains in other epochs may use other
+        // schedules. The modules compiled for each schedule are cached by the runtimes.
+        if let Some(metered_code) = code.with_fuel_schedule(&fuel_schedule)? {
+            code = metered_code;
+        }
         Ok((code, description))
     }
 
@@ -80,11 +91,16 @@ where
     ) -> Result<(UserServiceCode, UserApplicationDescription), ExecutionError> {
         #[cfg(with_metrics)]
         let _latency = LOAD_SERVICE_LATENCY.measure_latency();
+        // The service would read the state with the layout of the new bytecode.
+        if self.system.registry.has_pending_migration(id).await? {
+            return Err(ExecutionError::PendingMigration(Box::new(id)));
+        }
         let description = self.system.registry.describe_application(id).await?;
+        let code_description = self.system.registry.describe_application_code(id).await?;
         let code = self
             .context()
             .extra()
-            .get_user_service(&description)
+            .get_user_service(&code_description)
             .await?;
         Ok((code, description))
     }
@@ -101,6 +117,16 @@ where
             #[cfg(not(web))]
             LoadService { id, callback } => callback.respond(self.load_service(id).await?),
 
+            TakePendingMigration { id, callback } => {
+                let pending = self.system.registry.take_pending_migration(id).await?;
+                callback.respond(pending);
+            }
+
+            CallPolicy { id, callback } => {
+                let policy = self.system.registry.call_policy(id).await?;
+                callback.respond(policy);
+            }
+
             ChainBalance { callback } => {
                 let balance = *self.system.balance.get();
                 callback.respond(balance);
@@ -178,6 +204,32 @@ where
                 callback.respond(timestamp);
             }
 
+            RecordEvent {
+                stream_id,
+                key,
+                value,
+                callback,
+            } => {
+                let key = (stream_id, key);
+                let previous_value_size = self
+                    .system
+                    .latest_events
+                    .get(&key)
+                    .await?
+                    .map(|previous_value| previous_value.len());
+                self.system.latest_events.insert(&key, value)?;
+                callback.respond(previous_value_size);
+            }
+
+            ReadEvent {
+                stream_id,
+                key,
+                callback,
+            } => {
+                let value = self.system.latest_events.get(&(stream_id, key)).await?;
+                callback.respond(value);
+            }
+
             ChainOwnership { callback } => {
                 let ownership = self.system.ownership.get().clone();
                 callback.respond(ownership);
@@ -248,11 +300,26 @@ where
             WriteBatch {
                 id,
                 batch,
+                maximum_bytes_stored,
                 callback,
             } => {
                 let mut view = self.users.try_load_entry_mut(&id).await?;
-                view.write_batch(batch).await?;
-                callback.respond(());
+                let size_before = u64::from(view.total_size().sum());
+                let size_after = u64::from(view.total_size_after(&batch).await?.sum());
+                // Applications over the quota, e.g. after it was lowered, can still free storage.
+                if size_after > size_before && size_after > maximum_bytes_stored {
+                    callback.respond(Err(ExecutionError::StorageQuotaExceeded {
+                        application_id: Box::new(id),
+                        limit: maximum_bytes_stored,
+                    }));
+                } else if size_after > size_before && self.system.is_storage_rent_overdue() {
+                    callback.respond(Err(ExecutionError::StorageRentOverdue));
+                } else {
+                    view.write_batch(batch).await?;
+                    drop(view);
+                    self.system.record_bytes_stored(id, size_after).await?;
+                    callback.respond(Ok((size_before, size_after)));
+                }
             }
 
             OpenChain {
@@ -275,6 +342,25 @@ where
                 callback.respond(messages)
             }
 
+            ScheduleCallback {
+                application_id,
+                timestamp,
+                argument,
+                maximum_scheduled_callbacks,
+                callback,
+            } => {
+                let result = self
+                    .system
+                    .schedule_callback(
+                        application_id,
+                        timestamp,
+                        argument,
+                        maximum_scheduled_callbacks,
+                    )
+                    .await;
+                callback.respond(result.map_err(ExecutionError::from));
+            }
+
             CloseChain {
                 application_id,
                 callback,
@@ -319,14 +405,32 @@ where
                         bytecode_id,
                         parameters,
                         required_application_ids,
+                        // Applications created by other applications can't be upgraded.
+                        None,
                     )
                     .await?;
                 callback.respond(Ok(create_application_result));
             }
 
-            FetchUrl { url, callback } => {
-                let bytes = reqwest::get(url).await?.bytes().await?.to_vec();
-                callback.respond(bytes);
+            FetchUrl {
+                url,
+                max_response_bytes,
+                callback,
+            } => {
+                let mut response = reqwest::get(&url).await?;
+                let mut bytes = Vec::new();
+                let mut result = Ok(());
+                while let Some(chunk) = response.chunk().await? {
+                    if (bytes.len() + chunk.len()) as u64 > max_response_bytes {
+                        result = Err(ExecutionError::FetchedResponseTooLarge {
+                            url,
+                            limit: max_response_bytes,
+                        });
+                        break;
+                    }
+                    bytes.extend_from_slice(&chunk);
+                }
+                callback.respond(result.map(|()| bytes));
             }
 
             HttpPost {
@@ -379,6 +483,18 @@ pub enum ExecutionRequest {
         callback: Sender<(UserServiceCode, UserApplicationDescription)>,
     },
 
+    TakePendingMigration {
+        id: UserApplicationId,
+        #[debug(skip)]
+        callback: Sender<Option<u32>>,
+    },
+
+    CallPolicy {
+        id: UserApplicationId,
+        #[debug(skip)]
+        callback: Sender<ApplicationCallPolicy>,
+    },
+
     ChainBalance {
         #[debug(skip)]
         callback: Sender<Amount>,
@@ -428,6 +544,24 @@ pub enum ExecutionRequest {
         callback: Sender<Timestamp>,
     },
 
+    RecordEvent {
+        stream_id: StreamId,
+        #[debug(with = hex_debug)]
+        key: Vec<u8>,
+        #[debug(with = hex_debug)]
+        value: Vec<u8>,
+        #[debug(skip)]
+        callback: Sender<Option<usize>>,
+    },
+
+    ReadEvent {
+        stream_id: StreamId,
+        #[debug(with = hex_debug)]
+        key: Vec<u8>,
+        #[debug(skip)]
+        callback: Sender<Option<Vec<u8>>>,
+    },
+
     ChainOwnership {
         #[debug(skip)]
         callback: Sender<ChainOwnership>,
@@ -482,8 +616,9 @@ pub enum ExecutionRequest {
     WriteBatch {
         id: UserApplicationId,
         batch: Batch,
+        maximum_bytes_stored: u64,
         #[debug(skip)]
-        callback: Sender<()>,
+        callback: Sender<Result<(u64, u64), ExecutionError>>,
     },
 
     OpenChain {
@@ -496,6 +631,16 @@ pub enum ExecutionRequest {
         callback: Sender<[RawOutgoingMessage<SystemMessage, Amount>; 2]>,
     },
 
+    ScheduleCallback {
+        application_id: UserApplicationId,
+        timestamp: Timestamp,
+        #[debug(with = hex_debug)]
+        argument: Vec<u8>,
+        maximum_scheduled_callbacks: u64,
+        #[debug(skip)]
+        callback: Sender<Result<(), ExecutionError>>,
+    },
+
     CloseChain {
         application_id: UserApplicationId,
         #[debug(skip)]
@@ -520,8 +665,9 @@ pub enum ExecutionRequest {
 
     FetchUrl {
         url: String,
+        max_response_bytes: u64,
         #[debug(skip)]
-        callback: Sender<Vec<u8>>,
+        callback: Sender<Result<Vec<u8>, ExecutionError>>,
     },
 
     HttpPost {
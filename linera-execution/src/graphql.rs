@@ -102,4 +102,14 @@ impl<C: Send + Sync + Context> SystemExecutionStateView<C> {
     async fn _timestamp(&self) -> &Timestamp {
         self.timestamp.get()
     }
+
+    #[graphql(derived(name = "bytes_stored"))]
+    async fn _bytes_stored(&self) -> u64 {
+        *self.bytes_stored.get()
+    }
+
+    #[graphql(derived(name = "storage_rent_debt"))]
+    async fn _storage_rent_debt(&self) -> &Amount {
+        self.storage_rent_debt.get()
+    }
 }
@@ -12,6 +12,7 @@ pub mod committee;
 mod execution;
 mod execution_state_actor;
 mod graphql;
+mod native;
 mod policy;
 mod resources;
 mod runtime;
@@ -36,8 +37,8 @@ use linera_base::{
     abi::Abi,
     crypto::{BcsHashable, CryptoHash},
     data_types::{
-        Amount, ApplicationPermissions, ArithmeticError, Blob, BlockHeight, DecompressionError,
-        Resources, SendMessageRequest, Timestamp, UserApplicationDescription,
+        Amount, ApplicationError, ApplicationPermissions, ArithmeticError, Blob, BlockHeight,
+        DecompressionError, Resources, SendMessageRequest, Timestamp, UserApplicationDescription,
     },
     doc_scalar, hex_debug,
     identifiers::{
@@ -46,6 +47,7 @@ use linera_base::{
     },
     ownership::ChainOwnership,
     task,
+    time::Duration,
 };
 use linera_views::{batch::Batch, views::ViewError};
 use serde::{Deserialize, Serialize};
@@ -57,16 +59,22 @@ pub use crate::applications::ApplicationRegistry;
 use crate::runtime::ContractSyncRuntime;
 #[cfg(all(with_testing, with_wasm_runtime))]
 pub use crate::wasm::test as wasm_test;
+#[cfg(all(with_wasm_runtime, with_fs))]
+pub use crate::wasm::{enable_module_disk_cache, warm_up_module_caches};
 #[cfg(with_wasm_runtime)]
 pub use crate::wasm::{
     ContractEntrypoints, ContractSystemApi, ServiceEntrypoints, ServiceSystemApi, SystemApiData,
     ViewSystemApi, WasmContractModule, WasmExecutionError, WasmServiceModule,
 };
 pub use crate::{
-    applications::ApplicationRegistryView,
+    applications::{
+        ApplicationCallPolicy, ApplicationCallPolicyUpdate, ApplicationRegistryView,
+        ApplicationUpgrade, PrivilegedSystemApi, ReentrancyMode,
+    },
     execution::{ExecutionStateView, ServiceRuntimeEndpoint},
     execution_state_actor::ExecutionRequest,
-    policy::ResourceControlPolicy,
+    native::NativeApplicationRegistry,
+    policy::{FuelSchedule, ResourceControlPolicy},
     resources::{ResourceController, ResourceTracker},
     runtime::{
         ContractSyncRuntimeHandle, ServiceRuntimeRequest, ServiceSyncRuntime,
@@ -83,6 +91,8 @@ pub use crate::{
 const MAX_EVENT_KEY_LEN: usize = 64;
 /// The maximum length of a stream name.
 const MAX_STREAM_NAME_LEN: usize = 64;
+/// The maximum length of an event value in bytes.
+const MAX_EVENT_VALUE_LEN: usize = 64 * 1024;
 
 /// An implementation of [`UserContractModule`].
 #[derive(Clone)]
@@ -104,6 +114,21 @@ pub trait UserContractModule: dyn_clone::DynClone + Any + task::Post + Send + Sy
         &self,
         runtime: ContractSyncRuntimeHandle,
     ) -> Result<UserContractInstance, ExecutionError>;
+
+    /// Returns a version of this module that consumes fuel according to `fuel_schedule`, or
+    /// `None` if this module can be used as is. Implementations should cache the versions they
+    /// compile, since this is called every time the contract is loaded.
+    fn with_fuel_schedule(
+        &self,
+        _fuel_schedule: &FuelSchedule,
+    ) -> Result<Option<UserContractCode>, ExecutionError> {
+        Ok(None)
+    }
+
+    /// Returns the version of the contract ABI declared by this module, if any.
+    fn abi_version(&self) -> Option<u32> {
+        None
+    }
 }
 
 impl<T: UserContractModule + Send + Sync + 'static> From<T> for UserContractCode {
@@ -120,6 +145,11 @@ pub trait UserServiceModule: dyn_clone::DynClone + Any + task::Post + Send + Syn
         &self,
         runtime: ServiceSyncRuntimeHandle,
     ) -> Result<UserServiceInstance, ExecutionError>;
+
+    /// Returns the version of the service ABI declared by this module, if any.
+    fn abi_version(&self) -> Option<u32> {
+        None
+    }
 }
 
 impl<T: UserServiceModule + Send + Sync + 'static> From<T> for UserServiceCode {
@@ -137,6 +167,10 @@ impl UserServiceCode {
     ) -> Result<UserServiceInstance, ExecutionError> {
         self.0.instantiate(runtime)
     }
+
+    fn abi_version(&self) -> Option<u32> {
+        self.0.abi_version()
+    }
 }
 
 impl UserContractCode {
@@ -146,6 +180,17 @@ impl UserContractCode {
     ) -> Result<UserContractInstance, ExecutionError> {
         self.0.instantiate(runtime)
     }
+
+    fn with_fuel_schedule(
+        &self,
+        fuel_schedule: &FuelSchedule,
+    ) -> Result<Option<UserContractCode>, ExecutionError> {
+        self.0.with_fuel_schedule(fuel_schedule)
+    }
+
+    fn abi_version(&self) -> Option<u32> {
+        self.0.abi_version()
+    }
 }
 
 #[cfg(web)]
@@ -197,6 +242,8 @@ pub enum ExecutionError {
     SystemError(SystemExecutionError),
     #[error("User application reported an error: {0}")]
     UserError(String),
+    #[error("User application returned an error: {0}")]
+    ApplicationError(ApplicationError),
     #[cfg(any(with_wasmer, with_wasmtime))]
     #[error(transparent)]
     WasmError(#[from] WasmExecutionError),
@@ -205,8 +252,22 @@ pub enum ExecutionError {
     #[error("The given promise is invalid or was polled once already")]
     InvalidPromise,
 
-    #[error("Attempted to perform a reentrant call to application {0}")]
-    ReentrantCall(UserApplicationId),
+    #[error(
+        "Attempted to perform a reentrant call to application {callee_id}, which is already \
+        on the call stack {call_stack:?}"
+    )]
+    ReentrantCall {
+        callee_id: Box<UserApplicationId>,
+        call_stack: Vec<UserApplicationId>,
+    },
+    #[error(
+        "Attempted to query the service of application {callee_id}, which is on the call stack \
+        {call_stack:?} and doesn't allow reentrant queries"
+    )]
+    ReentrantQuery {
+        callee_id: Box<UserApplicationId>,
+        call_stack: Vec<UserApplicationId>,
+    },
     #[error(
         "Application {caller_id} attempted to perform a cross-application to {callee_id} call \
         from `finalize`"
@@ -215,6 +276,25 @@ pub enum ExecutionError {
         caller_id: Box<UserApplicationId>,
         callee_id: Box<UserApplicationId>,
     },
+    #[error(
+        "Application {application_id} implements version {actual} of its ABI, but version \
+        {expected} was expected"
+    )]
+    AbiVersionMismatch {
+        application_id: Box<UserApplicationId>,
+        expected: u32,
+        actual: u32,
+    },
+    #[error("Application {caller_id:?} is not allowed to call application {callee_id:?}")]
+    UnauthorizedApplicationCall {
+        caller_id: Box<UserApplicationId>,
+        callee_id: Box<UserApplicationId>,
+    },
+    #[error("Application {application_id:?} is not allowed to use the {system_api:?} system API")]
+    UnauthorizedSystemApi {
+        application_id: Box<UserApplicationId>,
+        system_api: PrivilegedSystemApi,
+    },
     #[error("Attempt to write to storage from a contract")]
     ServiceWriteAttempt,
     #[error("Failed to load bytecode from storage {0:?}")]
@@ -222,6 +302,11 @@ pub enum ExecutionError {
     // TODO(#2927): support dynamic loading of modules on the Web
     #[error("Unsupported dynamic application load: {0:?}")]
     UnsupportedDynamicApplicationLoad(Box<UserApplicationId>),
+    #[error(
+        "Application {0:?} can't be queried until a block using it migrates its state to its \
+        upgraded bytecode"
+    )]
+    PendingMigration(Box<UserApplicationId>),
 
     #[error("Excessive number of bytes read from storage")]
     ExcessiveRead,
@@ -229,8 +314,34 @@ pub enum ExecutionError {
     ExcessiveWrite,
     #[error("Block execution required too much fuel")]
     MaximumFuelExceeded,
+    #[error(
+        "Application instance reported {remaining} units of remaining fuel, more than its \
+        initial {initial}"
+    )]
+    InvalidRemainingFuel { initial: u64, remaining: u64 },
+    #[error("Service query required more than the maximum fuel of {0}")]
+    ServiceQueryFuelLimitExceeded(u64),
+    #[error("Application instance exceeded the maximum memory size of {0} bytes")]
+    WasmMemoryLimitExceeded(u64),
+    #[error("Application instance exceeded the maximum of {0} table elements")]
+    WasmTableLimitExceeded(u64),
+    #[error("Service query took longer than the maximum duration of {0:?}")]
+    ServiceQueryTimedOut(Duration),
+    #[error("Service query response of {size} bytes exceeds the limit of {limit} bytes")]
+    ServiceQueryResponseTooLarge { size: u64, limit: u64 },
+    #[error("Response fetched from {url} exceeds the limit of {limit} bytes")]
+    FetchedResponseTooLarge { url: String, limit: u64 },
+    #[error("Application {0:?} scheduled an operation while answering a query from a contract")]
+    OperationScheduledByContractQuery(Box<UserApplicationId>),
     #[error("Serialized size of the executed block exceeds limit")]
     ExecutedBlockTooLarge,
+    #[error("Application {application_id:?} would store more than the maximum of {limit} bytes")]
+    StorageQuotaExceeded {
+        application_id: Box<UserApplicationId>,
+        limit: u64,
+    },
+    #[error("Applications cannot increase their storage until the overdue storage rent is paid")]
+    StorageRentOverdue,
     #[error("Runtime failed to respond to application")]
     MissingRuntimeResponse,
     #[error("Bytecode ID {0:?} is invalid")]
@@ -261,6 +372,8 @@ pub enum ExecutionError {
     EventKeyTooLong,
     #[error("Stream names can be at most {MAX_STREAM_NAME_LEN} bytes.")]
     StreamNameTooLong,
+    #[error("Event values can be at most {MAX_EVENT_VALUE_LEN} bytes.")]
+    EventValueTooLong,
     #[error("Blob exceeds size limit")]
     BlobTooLarge,
     #[error("Bytecode exceeds size limit")]
@@ -307,6 +420,9 @@ pub trait UserContract {
     ) -> Result<(), ExecutionError>;
 
     /// Applies an operation from the current block.
+    ///
+    /// An [`ExecutionError::ApplicationError`] is the application's response to its caller,
+    /// which the caller can handle.
     fn execute_operation(
         &mut self,
         context: OperationContext,
@@ -320,8 +436,19 @@ pub trait UserContract {
         message: Vec<u8>,
     ) -> Result<(), ExecutionError>;
 
+    /// Executes a callback that the application scheduled, once its time has come.
+    fn execute_callback(
+        &mut self,
+        context: OperationContext,
+        argument: Vec<u8>,
+    ) -> Result<(), ExecutionError>;
+
     /// Finishes execution of the current transaction.
     fn finalize(&mut self, context: FinalizeContext) -> Result<(), ExecutionError>;
+
+    /// Migrates the application state after its bytecode was upgraded, from the layout used by
+    /// the application's version `old_version`.
+    fn migrate(&mut self, old_version: u32) -> Result<(), ExecutionError>;
 }
 
 /// The public entry points provided by the service part of an application.
@@ -353,7 +480,40 @@ impl ApplicationCallOutcome {
 
 /// Configuration options for the execution runtime available to applications.
 #[derive(Clone, Copy, Default)]
-pub struct ExecutionRuntimeConfig {}
+pub struct ExecutionRuntimeConfig {
+    /// The limits enforced when running application services to answer queries.
+    pub service_query_limits: ServiceQueryLimits,
+}
+
+/// Limits on the resources used by an application service to answer a single query.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ServiceQueryLimits {
+    /// The maximum amount of fuel that the query may consume.
+    pub max_fuel: u64,
+    /// The maximum time that the query may run for.
+    pub max_duration: Duration,
+    /// The maximum size of the query's response, in bytes.
+    pub max_response_bytes: u64,
+}
+
+impl ServiceQueryLimits {
+    /// The default maximum amount of fuel for a query.
+    pub const DEFAULT_MAX_FUEL: u64 = 10_000_000_000;
+    /// The default maximum duration of a query.
+    pub const DEFAULT_MAX_DURATION: Duration = Duration::from_secs(30);
+    /// The default maximum size of a query's response.
+    pub const DEFAULT_MAX_RESPONSE_BYTES: u64 = 16 << 20;
+}
+
+impl Default for ServiceQueryLimits {
+    fn default() -> Self {
+        ServiceQueryLimits {
+            max_fuel: Self::DEFAULT_MAX_FUEL,
+            max_duration: Self::DEFAULT_MAX_DURATION,
+            max_response_bytes: Self::DEFAULT_MAX_RESPONSE_BYTES,
+        }
+    }
+}
 
 /// Requirements for the `extra` field in our state views (and notably the
 /// [`ExecutionStateView`]).
@@ -621,6 +781,12 @@ pub trait BaseRuntime {
         payload: Vec<u8>,
     ) -> Result<Vec<u8>, ExecutionError>;
 
+    /// Fetches a blob of bytes from an arbitrary URL.
+    ///
+    /// In contracts, the response is recorded in the block as an oracle response, so that
+    /// validators use it instead of fetching the URL again when the block is confirmed.
+    fn fetch_url(&mut self, url: &str) -> Result<Vec<u8>, ExecutionError>;
+
     /// Ensures that the current time at block validation is `< timestamp`. Note that block
     /// validation happens at or after the block timestamp, but isn't necessarily the same.
     ///
@@ -643,11 +809,51 @@ pub trait ServiceRuntime: BaseRuntime {
         argument: Vec<u8>,
     ) -> Result<Vec<u8>, ExecutionError>;
 
-    /// Fetches blob of bytes from an arbitrary URL.
-    fn fetch_url(&mut self, url: &str) -> Result<Vec<u8>, ExecutionError>;
+    /// Checks that the application implements the expected `version` of its service ABI,
+    /// if its bytecode declares one.
+    fn assert_abi_version(
+        &mut self,
+        application_id: UserApplicationId,
+        version: u32,
+    ) -> Result<(), ExecutionError>;
 
     /// Schedules an operation to be included in the block proposed after execution.
     fn schedule_operation(&mut self, operation: Vec<u8>) -> Result<(), ExecutionError>;
+
+    /// Reads the value of the latest event emitted by an application on this chain, on the
+    /// stream `name` with the given `key`.
+    fn read_event(
+        &mut self,
+        application_id: UserApplicationId,
+        name: StreamName,
+        key: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, ExecutionError>;
+
+    /// Returns the amount of execution fuel remaining before the query is aborted.
+    fn remaining_fuel(&mut self) -> Result<u64, ExecutionError>;
+
+    /// Consumes some of the execution fuel.
+    fn consume_fuel(&mut self, fuel: u64) -> Result<(), ExecutionError>;
+
+    /// Returns how much longer the query may run before it is aborted, if its duration is
+    /// limited.
+    fn remaining_query_time(&mut self) -> Result<Option<Duration>, ExecutionError>;
+
+    /// Checks that a new application instance with the given initial linear memory (in Wasm
+    /// pages) and table sizes is within the limits.
+    fn check_wasm_instance_size(
+        &mut self,
+        memory_pages: u64,
+        table_elements: u64,
+    ) -> Result<(), ExecutionError>;
+
+    /// Checks that an instance with `memory_pages` pages of linear memory may grow by
+    /// `additional_pages`, and consumes the fuel for the new pages.
+    fn track_wasm_memory_growth(
+        &mut self,
+        memory_pages: u64,
+        additional_pages: u64,
+    ) -> Result<(), ExecutionError>;
 }
 
 pub trait ContractRuntime: BaseRuntime {
@@ -671,6 +877,22 @@ pub trait ContractRuntime: BaseRuntime {
     /// Consumes some of the execution fuel.
     fn consume_fuel(&mut self, fuel: u64) -> Result<(), ExecutionError>;
 
+    /// Checks that a new application instance with the given initial linear memory (in Wasm
+    /// pages) and table sizes is within the limits.
+    fn check_wasm_instance_size(
+        &mut self,
+        memory_pages: u64,
+        table_elements: u64,
+    ) -> Result<(), ExecutionError>;
+
+    /// Checks that an instance with `memory_pages` pages of linear memory may grow by
+    /// `additional_pages`, and consumes the fuel for the new pages.
+    fn track_wasm_memory_growth(
+        &mut self,
+        memory_pages: u64,
+        additional_pages: u64,
+    ) -> Result<(), ExecutionError>;
+
     /// Schedules a message to be sent.
     fn send_message(&mut self, message: SendMessageRequest<Vec<u8>>) -> Result<(), ExecutionError>;
 
@@ -698,6 +920,10 @@ pub trait ContractRuntime: BaseRuntime {
 
     /// Calls another application. Forwarded sessions will now be visible to
     /// `callee_id` (but not to the caller any more).
+    ///
+    /// If the callee responds with an [`ExecutionError::ApplicationError`], the call is
+    /// finished and the error is returned, so that the caller can handle it. Any other error
+    /// aborts the transaction.
     fn try_call_application(
         &mut self,
         authenticated: bool,
@@ -705,7 +931,17 @@ pub trait ContractRuntime: BaseRuntime {
         argument: Vec<u8>,
     ) -> Result<Vec<u8>, ExecutionError>;
 
-    /// Adds a new item to an event stream.
+    /// Checks that the application implements the expected `version` of its contract ABI,
+    /// if its bytecode declares one.
+    fn assert_abi_version(
+        &mut self,
+        application_id: UserApplicationId,
+        version: u32,
+    ) -> Result<(), ExecutionError>;
+
+    /// Adds a new item to an event stream. The value replaces the one of the latest event
+    /// emitted on the stream with the same key, which services can read. Storing it is charged
+    /// like a write to the application's state.
     fn emit(
         &mut self,
         name: StreamName,
@@ -744,6 +980,18 @@ pub trait ContractRuntime: BaseRuntime {
 
     /// Returns the round in which this block was validated.
     fn validation_round(&mut self) -> Result<Option<u32>, ExecutionError>;
+
+    /// Schedules a callback of the current application, executed with the `argument` in the
+    /// first block of this chain that executes the due callbacks at or after `timestamp`.
+    ///
+    /// Scheduling is charged like an operation with the `argument` as its bytes. An application
+    /// can have at most [`ResourceControlPolicy::maximum_scheduled_callbacks_per_application`]
+    /// callbacks scheduled on a chain at the same time.
+    fn schedule_callback(
+        &mut self,
+        timestamp: Timestamp,
+        argument: Vec<u8>,
+    ) -> Result<(), ExecutionError>;
 }
 
 /// An operation to be executed in a block.
@@ -850,6 +1098,8 @@ pub struct RawOutgoingMessage<Message, Grant = Resources> {
     pub grant: Grant,
     /// The kind of outgoing message being sent.
     pub kind: MessageKind,
+    /// The time after which the receiver can no longer accept the message, if any.
+    pub expires_at: Option<Timestamp>,
     /// The message itself.
     pub message: Message,
 }
@@ -861,6 +1111,7 @@ impl<Message> From<SendMessageRequest<Message>> for RawOutgoingMessage<Message,
             authenticated,
             grant,
             is_tracked,
+            expires_at,
             message,
         } = request;
 
@@ -875,6 +1126,7 @@ impl<Message> From<SendMessageRequest<Message>> for RawOutgoingMessage<Message,
             authenticated,
             grant,
             kind,
+            expires_at,
             message,
         }
     }
@@ -992,6 +1244,7 @@ impl<Message> RawOutgoingMessage<Message, Resources> {
             authenticated,
             grant,
             kind,
+            expires_at,
             message,
         } = self;
         Ok(RawOutgoingMessage {
@@ -999,6 +1252,7 @@ impl<Message> RawOutgoingMessage<Message, Resources> {
             authenticated,
             grant: policy.total_price(&grant)?,
             kind,
+            expires_at,
             message,
         })
     }
@@ -0,0 +1,82 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Native implementations of applications, used in place of their WebAssembly bytecode.
+//!
+//! Validators may register a native Rust implementation for a specific bytecode, so that
+//! frequently used applications don't pay the cost of running in a WebAssembly virtual machine.
+//! A native implementation must behave exactly like the bytecode it replaces, including the
+//! amount of fuel it consumes, otherwise the validator will disagree with the rest of the
+//! committee on the outcome of blocks. The [`check_native_equivalence`] helper is provided to
+//! test that, for the resource control policy of the committee. Since the fuel consumed by the
+//! bytecode depends on the policy's fuel schedule, a registration must be tested again, and
+//! updated if needed, before the committee changes its schedule.
+//!
+//! [`check_native_equivalence`]: crate::test_utils::check_native_equivalence
+
+use std::{collections::HashMap, sync::Arc};
+
+use linera_base::{crypto::CryptoHash, identifiers::BytecodeId};
+
+use crate::{UserContractCode, UserServiceCode};
+
+/// A set of native application implementations, indexed by the hash of the bytecode they
+/// replace.
+#[derive(Clone, Default)]
+pub struct NativeApplicationRegistry {
+    contracts: Arc<HashMap<CryptoHash, UserContractCode>>,
+    services: Arc<HashMap<CryptoHash, UserServiceCode>>,
+}
+
+impl NativeApplicationRegistry {
+    /// Registers a native `contract` to use instead of the contract bytecode with the hash
+    /// `contract_blob_hash`.
+    pub fn register_contract(
+        &mut self,
+        contract_blob_hash: CryptoHash,
+        contract: impl Into<UserContractCode>,
+    ) -> &mut Self {
+        Arc::make_mut(&mut self.contracts).insert(contract_blob_hash, contract.into());
+        self
+    }
+
+    /// Registers a native `service` to use instead of the service bytecode with the hash
+    /// `service_blob_hash`.
+    pub fn register_service(
+        &mut self,
+        service_blob_hash: CryptoHash,
+        service: impl Into<UserServiceCode>,
+    ) -> &mut Self {
+        Arc::make_mut(&mut self.services).insert(service_blob_hash, service.into());
+        self
+    }
+
+    /// Registers a native `contract` and `service` to use instead of the bytecodes referenced
+    /// by `bytecode_id`.
+    pub fn register_application(
+        &mut self,
+        bytecode_id: BytecodeId,
+        contract: impl Into<UserContractCode>,
+        service: impl Into<UserServiceCode>,
+    ) -> &mut Self {
+        self.register_contract(bytecode_id.contract_blob_hash, contract)
+            .register_service(bytecode_id.service_blob_hash, service)
+    }
+
+    /// Returns the native contract to use for the bytecodes referenced by `bytecode_id`, if
+    /// one was registered.
+    pub fn contract(&self, bytecode_id: &BytecodeId) -> Option<UserContractCode> {
+        self.contracts.get(&bytecode_id.contract_blob_hash).cloned()
+    }
+
+    /// Returns the native service to use for the bytecodes referenced by `bytecode_id`, if
+    /// one was registered.
+    pub fn service(&self, bytecode_id: &BytecodeId) -> Option<UserServiceCode> {
+        self.services.get(&bytecode_id.service_blob_hash).cloned()
+    }
+
+    /// Returns `true` if no native implementations were registered.
+    pub fn is_empty(&self) -> bool {
+        self.contracts.is_empty() && self.services.is_empty()
+    }
+}
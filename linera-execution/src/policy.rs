@@ -7,7 +7,10 @@ use std::fmt;
 
 use async_graphql::InputObject;
 use linera_base::{
-    data_types::{Amount, ArithmeticError, BlobContent, CompressedBytecode, Resources},
+    data_types::{
+        Amount, ArithmeticError, BlobContent, BytecodeChunks, CompressedBytecode, Resources,
+        TimeDelta,
+    },
     ensure,
     identifiers::BlobType,
 };
@@ -15,10 +18,17 @@ use serde::{Deserialize, Serialize};
 
 use crate::ExecutionError;
 
+/// The size of a page of WebAssembly linear memory, in bytes.
+const WASM_PAGE_SIZE: u64 = 1 << 16;
+
+/// The number of microseconds in a day, the period for which the storage rent is priced.
+const MICROS_PER_DAY: u128 = 24 * 60 * 60 * 1_000_000;
+
 /// A collection of prices and limits associated with block execution.
 #[derive(Eq, PartialEq, Hash, Clone, Debug, Serialize, Deserialize, InputObject)]
 pub struct ResourceControlPolicy {
-    /// The base price for creating a new block.
+    /// The base price for creating a new block. This is also the lowest possible base fee of a
+    /// chain.
     pub block: Amount,
     /// The price per unit of fuel (aka gas) for VM execution.
     pub fuel_unit: Amount,
@@ -31,8 +41,10 @@ pub struct ResourceControlPolicy {
     /// The price of writing a byte
     pub byte_written: Amount,
     /// The price of increasing storage by a byte.
-    // TODO(#1536): This is not fully supported.
     pub byte_stored: Amount,
+    /// The price of keeping a byte stored for one day. The rent is charged from the chain
+    /// balance with each block.
+    pub byte_stored_per_day: Amount,
     /// The base price of adding an operation to a block.
     pub operation: Amount,
     /// The additional price for each byte in the argument of a user operation.
@@ -61,6 +73,79 @@ pub struct ResourceControlPolicy {
     pub maximum_bytes_read_per_block: u64,
     /// The maximum data to write per block
     pub maximum_bytes_written_per_block: u64,
+    /// The maximum size of the linear memory of an application instance, in bytes.
+    pub maximum_wasm_memory_size: u64,
+    /// The maximum number of elements in the tables of an application instance.
+    pub maximum_wasm_table_elements: u64,
+    /// The maximum number of bytes that an application can store on a chain.
+    pub maximum_bytes_stored_per_application: u64,
+    /// How long the storage rent of a chain can remain unpaid before its applications can no
+    /// longer increase their storage. If it is still unpaid after the same period again, the
+    /// largest application state of the chain is evicted, and then another one after each
+    /// further period.
+    pub storage_rent_grace_period: TimeDelta,
+    /// The amount of fuel consumed by each class of WebAssembly instructions.
+    pub fuel_schedule: FuelSchedule,
+    /// How fast the base fee of a chain follows the fullness of its blocks: after each block, it
+    /// changes by at most this fraction of itself. Zero keeps the base fee at the block price.
+    pub base_fee_change_denominator: u64,
+    /// The maximum number of callbacks that an application can have scheduled on a chain at
+    /// the same time.
+    pub maximum_scheduled_callbacks_per_application: u64,
+    /// The maximum number of due callbacks executed by one operation.
+    pub maximum_callbacks_per_operation: u64,
+}
+
+/// The amount of fuel consumed by each class of WebAssembly instructions.
+///
+/// The schedule is used to instrument contract bytecode with fuel metering, so contracts consume
+/// the same amount of fuel with every runtime.
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug, Serialize, Deserialize, InputObject)]
+pub struct FuelSchedule {
+    /// The fuel consumed by instructions that don't belong to any other class.
+    pub instruction: u32,
+    /// The fuel consumed by structural instructions that perform no computation
+    /// (`nop`, `drop`, `block`, `loop`, `unreachable`, `else` and `end`).
+    pub structural_instruction: u32,
+    /// The fuel consumed by memory loads and stores.
+    pub memory_access: u32,
+    /// The fuel consumed by direct and indirect function calls.
+    pub call: u32,
+    /// The fuel consumed for each page of memory allocated with `memory.grow`. This is charged
+    /// by the host, in both runtimes.
+    pub memory_grow_page: u32,
+}
+
+impl Default for FuelSchedule {
+    fn default() -> Self {
+        Self {
+            instruction: 1,
+            structural_instruction: 0,
+            memory_access: 1,
+            call: 1,
+            memory_grow_page: 0,
+        }
+    }
+}
+
+impl fmt::Display for FuelSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let FuelSchedule {
+            instruction,
+            structural_instruction,
+            memory_access,
+            call,
+            memory_grow_page,
+        } = self;
+        write!(
+            f,
+            "{instruction} per instruction, \
+            {structural_instruction} per structural instruction, \
+            {memory_access} per memory access, \
+            {call} per call, \
+            {memory_grow_page} per page of memory growth",
+        )
+    }
 }
 
 impl fmt::Display for ResourceControlPolicy {
@@ -73,6 +158,7 @@ impl fmt::Display for ResourceControlPolicy {
             byte_read,
             byte_written,
             byte_stored,
+            byte_stored_per_day,
             operation,
             operation_byte,
             message,
@@ -85,7 +171,16 @@ impl fmt::Display for ResourceControlPolicy {
             maximum_block_proposal_size,
             maximum_bytes_read_per_block,
             maximum_bytes_written_per_block,
+            maximum_wasm_memory_size,
+            maximum_wasm_table_elements,
+            maximum_bytes_stored_per_application,
+            storage_rent_grace_period,
+            fuel_schedule,
+            base_fee_change_denominator,
+            maximum_scheduled_callbacks_per_application,
+            maximum_callbacks_per_operation,
         } = self;
+        let storage_rent_grace_period_ms = storage_rent_grace_period.as_micros() / 1_000;
         write!(
             f,
             "Resource control policy:\n\
@@ -96,6 +191,7 @@ impl fmt::Display for ResourceControlPolicy {
             {byte_read:.2} cost per byte read\n\
             {byte_written:.2} cost per byte written\n\
             {byte_stored:.2} cost per byte stored\n\
+            {byte_stored_per_day:.2} rent per byte stored per day\n\
             {operation:.2} per operation\n\
             {operation_byte:.2} per byte in the argument of an operation\n\
             {message:.2} per outgoing messages\n\
@@ -107,7 +203,15 @@ impl fmt::Display for ResourceControlPolicy {
             {maximum_bytecode_size} maximum size of service and contract bytecode\n\
             {maximum_block_proposal_size} maximum size of a block proposal\n\
             {maximum_bytes_read_per_block} maximum number bytes read per block\n\
-            {maximum_bytes_written_per_block} maximum number bytes written per block",
+            {maximum_bytes_written_per_block} maximum number bytes written per block\n\
+            {maximum_wasm_memory_size} maximum size of the memory of an application instance\n\
+            {maximum_wasm_table_elements} maximum number of table elements of an application instance\n\
+            {maximum_bytes_stored_per_application} maximum number of bytes stored by an application\n\
+            {storage_rent_grace_period_ms} ms grace period for unpaid storage rent\n\
+            fuel schedule: {fuel_schedule}\n\
+            {base_fee_change_denominator} base fee change denominator\n\
+            {maximum_scheduled_callbacks_per_application} maximum number of callbacks scheduled by an application\n\
+            {maximum_callbacks_per_operation} maximum number of callbacks executed per operation",
         )
     }
 }
@@ -122,6 +226,7 @@ impl Default for ResourceControlPolicy {
             byte_read: Amount::default(),
             byte_written: Amount::default(),
             byte_stored: Amount::default(),
+            byte_stored_per_day: Amount::default(),
             operation: Amount::default(),
             operation_byte: Amount::default(),
             message: Amount::default(),
@@ -134,6 +239,14 @@ impl Default for ResourceControlPolicy {
             maximum_block_proposal_size: u64::MAX,
             maximum_bytes_read_per_block: u64::MAX,
             maximum_bytes_written_per_block: u64::MAX,
+            maximum_wasm_memory_size: u64::MAX,
+            maximum_wasm_table_elements: u64::MAX,
+            maximum_bytes_stored_per_application: u64::MAX,
+            storage_rent_grace_period: TimeDelta::ZERO,
+            fuel_schedule: FuelSchedule::default(),
+            base_fee_change_denominator: 0,
+            maximum_scheduled_callbacks_per_application: 16,
+            maximum_callbacks_per_operation: 10,
         }
     }
 }
@@ -143,6 +256,38 @@ impl ResourceControlPolicy {
         self.block
     }
 
+    /// Returns the base fee for the next block of a chain, given its current base fee and the
+    /// size of its last executed block.
+    ///
+    /// The base fee rises while blocks are more than half of the maximum size, and falls
+    /// otherwise, but never below the block price.
+    pub fn next_base_fee(&self, base_fee: Amount, block_size: u64) -> Amount {
+        if self.base_fee_change_denominator == 0 {
+            return self.block;
+        }
+        let base_fee = base_fee.max(self.block);
+        let target = (self.maximum_executed_block_size / 2).max(1);
+        let change = u128::from(base_fee).saturating_mul(u128::from(block_size.abs_diff(target)))
+            / u128::from(target)
+            / u128::from(self.base_fee_change_denominator);
+        if block_size > target {
+            base_fee.saturating_add(Amount::from_attos(change.max(1)))
+        } else {
+            base_fee
+                .saturating_sub(Amount::from_attos(change))
+                .max(self.block)
+        }
+    }
+
+    /// Returns the priority fees paid for the given number of operations.
+    pub fn priority_fees(
+        &self,
+        fee_per_operation: Amount,
+        operations: u32,
+    ) -> Result<Amount, ArithmeticError> {
+        fee_per_operation.try_mul(u128::from(operations))
+    }
+
     pub fn total_price(&self, resources: &Resources) -> Result<Amount, ArithmeticError> {
         let mut amount = Amount::ZERO;
         amount.try_add_assign(self.fuel_price(resources.fuel)?)?;
@@ -180,12 +325,25 @@ impl ResourceControlPolicy {
         self.byte_written.try_mul(count as u128)
     }
 
-    // TODO(#1536): This is not fully implemented.
-    #[allow(dead_code)]
     pub(crate) fn bytes_stored_price(&self, count: u64) -> Result<Amount, ArithmeticError> {
         self.byte_stored.try_mul(count as u128)
     }
 
+    /// Returns the rent for keeping the given number of bytes stored during `duration`.
+    pub fn storage_rent(&self, bytes: u64, duration: TimeDelta) -> Result<Amount, ArithmeticError> {
+        let rent_per_day = self.byte_stored_per_day.try_mul(u128::from(bytes))?;
+        let rent = u128::from(rent_per_day)
+            .checked_mul(u128::from(duration.as_micros()))
+            .ok_or(ArithmeticError::Overflow)?
+            / MICROS_PER_DAY;
+        Ok(Amount::from_attos(rent))
+    }
+
+    /// Returns the maximum number of Wasm pages of linear memory of an application instance.
+    pub(crate) fn maximum_wasm_memory_pages(&self) -> u64 {
+        self.maximum_wasm_memory_size / WASM_PAGE_SIZE
+    }
+
     pub(crate) fn fuel_price(&self, fuel: u64) -> Result<Amount, ArithmeticError> {
         self.fuel_unit.try_mul(u128::from(fuel))
     }
@@ -204,6 +362,10 @@ impl ResourceControlPolicy {
         );
         match content.blob_type() {
             BlobType::ContractBytecode | BlobType::ServiceBytecode => {
+                // The size of a bytecode split into chunks is checked once it is reassembled.
+                if BytecodeChunks::from_blob_bytes(content.bytes()).is_some() {
+                    return Ok(());
+                }
                 ensure!(
                     CompressedBytecode::decompressed_size_at_most(
                         content.bytes(),
@@ -267,6 +429,7 @@ impl ResourceControlPolicy {
             read_operation: Amount::from_micros(10),
             write_operation: Amount::from_micros(20),
             byte_stored: Amount::from_nanos(10),
+            byte_stored_per_day: Amount::ZERO,
             message_byte: Amount::from_nanos(100),
             operation_byte: Amount::from_nanos(10),
             operation: Amount::from_micros(10),
@@ -279,6 +442,17 @@ impl ResourceControlPolicy {
             maximum_block_proposal_size: 13_000_000,
             maximum_bytes_read_per_block: 100_000_000,
             maximum_bytes_written_per_block: 10_000_000,
+            maximum_wasm_memory_size: 256 << 20,
+            maximum_wasm_table_elements: 100_000,
+            maximum_bytes_stored_per_application: 100_000_000,
+            storage_rent_grace_period: TimeDelta::from_secs(30 * 24 * 60 * 60),
+            fuel_schedule: FuelSchedule {
+                memory_grow_page: 10_000,
+                ..FuelSchedule::default()
+            },
+            base_fee_change_denominator: 8,
+            maximum_scheduled_callbacks_per_application: 16,
+            maximum_callbacks_per_operation: 10,
         }
     }
 }
@@ -47,7 +47,7 @@ pub struct ResourceTracker {
     /// The number of bytes written.
     pub bytes_written: u64,
     /// The change in the number of bytes being stored by user applications.
-    pub bytes_stored: i32,
+    pub bytes_stored: i64,
     /// The number of operations executed.
     pub operations: u32,
     /// The total size of the arguments of user operations.
@@ -84,15 +84,14 @@ where
     /// Operates a 3-way merge by transferring the difference between `initial`
     /// and `other` to `self`.
     pub fn merge_balance(&mut self, initial: Amount, other: Amount) -> Result<(), ExecutionError> {
-        if other <= initial {
-            self.account
-                .try_sub_assign(initial.try_sub(other).expect("other <= initial"))
+        match other.try_sub(initial) {
+            Ok(gain) => self.account.try_add_assign(gain)?,
+            Err(_) => self
+                .account
+                .try_sub_assign(initial.saturating_sub(other))
                 .map_err(|_| SystemExecutionError::InsufficientFundingForFees {
                     balance: self.balance().unwrap_or(Amount::MAX),
-                })?;
-        } else {
-            self.account
-                .try_add_assign(other.try_sub(initial).expect("other > initial"))?;
+                })?,
         }
         Ok(())
     }
@@ -118,6 +117,13 @@ where
             )
     }
 
+    /// Obtains the number of bytes that can still be read in the current block.
+    pub(crate) fn remaining_bytes_read(&self) -> u64 {
+        self.policy
+            .maximum_bytes_read_per_block
+            .saturating_sub(self.tracker.as_ref().bytes_read)
+    }
+
     /// Tracks the allocation of a grant.
     pub fn track_grant(&mut self, grant: Amount) -> Result<(), ExecutionError> {
         self.tracker.as_mut().grants.try_add_assign(grant)?;
@@ -125,14 +131,25 @@ where
     }
 
     /// Tracks the creation of a block.
-    pub fn track_block(&mut self) -> Result<(), ExecutionError> {
+    pub fn track_block(&mut self, base_fee: Amount) -> Result<(), ExecutionError> {
         self.tracker.as_mut().blocks = self
             .tracker
             .as_mut()
             .blocks
             .checked_add(1)
             .ok_or(ArithmeticError::Overflow)?;
-        self.update_balance(self.policy.block)
+        self.update_balance(base_fee.max(self.policy.block))
+    }
+
+    /// Tracks the priority fee paid for each operation executed so far, and returns the total.
+    pub fn track_priority_fees(
+        &mut self,
+        fee_per_operation: Amount,
+    ) -> Result<Amount, ExecutionError> {
+        let operations = self.tracker.as_ref().operations;
+        let fees = self.policy.priority_fees(fee_per_operation, operations)?;
+        self.update_balance(fees)?;
+        Ok(fees)
     }
 
     /// Tracks the execution of an operation in block.
@@ -200,6 +217,41 @@ where
         self.update_balance(self.policy.fuel_price(fuel)?)
     }
 
+    /// Checks the initial linear memory (in Wasm pages) and table sizes of a new application
+    /// instance.
+    pub(crate) fn check_wasm_instance_size(
+        &self,
+        memory_pages: u64,
+        table_elements: u64,
+    ) -> Result<(), ExecutionError> {
+        ensure!(
+            memory_pages <= self.policy.maximum_wasm_memory_pages(),
+            ExecutionError::WasmMemoryLimitExceeded(self.policy.maximum_wasm_memory_size)
+        );
+        ensure!(
+            table_elements <= self.policy.maximum_wasm_table_elements,
+            ExecutionError::WasmTableLimitExceeded(self.policy.maximum_wasm_table_elements)
+        );
+        Ok(())
+    }
+
+    /// Tracks the growth of the linear memory of an application instance from `memory_pages` by
+    /// `additional_pages`, charging the fuel for the new pages.
+    pub(crate) fn track_wasm_memory_growth(
+        &mut self,
+        memory_pages: u64,
+        additional_pages: u64,
+    ) -> Result<(), ExecutionError> {
+        ensure!(
+            memory_pages.saturating_add(additional_pages)
+                <= self.policy.maximum_wasm_memory_pages(),
+            ExecutionError::WasmMemoryLimitExceeded(self.policy.maximum_wasm_memory_size)
+        );
+        let fuel =
+            additional_pages.saturating_mul(u64::from(self.policy.fuel_schedule.memory_grow_page));
+        self.track_fuel(fuel)
+    }
+
     /// Tracks a read operation.
     pub(crate) fn track_read_operations(&mut self, count: u32) -> Result<(), ExecutionError> {
         self.tracker.as_mut().read_operations = self
@@ -252,16 +304,17 @@ where
         Ok(())
     }
 
-    /// Tracks a change in the number of bytes stored.
-    // TODO(#1536): This is not fully implemented.
-    #[allow(dead_code)]
-    pub(crate) fn track_stored_bytes(&mut self, delta: i32) -> Result<(), ExecutionError> {
+    /// Tracks a change in the number of bytes stored. Only increases are charged.
+    pub(crate) fn track_stored_bytes(&mut self, delta: i64) -> Result<(), ExecutionError> {
         self.tracker.as_mut().bytes_stored = self
             .tracker
             .as_mut()
             .bytes_stored
             .checked_add(delta)
             .ok_or(ArithmeticError::Overflow)?;
+        if let Ok(increase) = u64::try_from(delta) {
+            self.update_balance(self.policy.bytes_stored_price(increase)?)?;
+        }
         Ok(())
     }
 }
@@ -281,7 +334,9 @@ where
         if delta == 0 {
             return Ok(());
         }
-        let new_len = old_len + delta;
+        let new_len = old_len
+            .checked_add(delta)
+            .ok_or(ExecutionError::ExecutedBlockTooLarge)?;
         // ULEB128 uses one byte per 7 bits of the number. It always uses at least one byte.
         let old_size = ((usize::BITS - old_len.leading_zeros()) / 7).max(1);
         let new_size = ((usize::BITS - new_len.leading_zeros()) / 7).max(1);
@@ -366,7 +421,7 @@ impl BalanceHolder for Sources<'_> {
             if source.try_sub_assign(other).is_ok() {
                 return Ok(());
             }
-            other.try_sub_assign(**source).expect("*source < other");
+            other.saturating_sub_assign(**source);
             **source = Amount::ZERO;
         }
         if other > Amount::ZERO {
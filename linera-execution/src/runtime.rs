@@ -17,10 +17,11 @@ use linera_base::{
     },
     ensure,
     identifiers::{
-        Account, AccountOwner, ApplicationId, BlobId, BlobType, ChainId, ChannelName, MessageId,
-        Owner, StreamName,
+        Account, AccountOwner, ApplicationId, BlobId, BlobType, ChainId, ChannelName,
+        GenericApplicationId, MessageId, Owner, StreamId, StreamName,
     },
     ownership::ChainOwnership,
+    time::{Duration, Instant},
 };
 use linera_views::batch::Batch;
 use oneshot::Receiver;
@@ -31,11 +32,12 @@ use crate::{
     resources::ResourceController,
     system::CreateApplicationResult,
     util::{ReceiverExt, UnboundedSenderExt},
-    BaseRuntime, BytecodeId, ContractRuntime, ExecutionError, FinalizeContext, MessageContext,
-    Operation, OperationContext, QueryContext, QueryOutcome, RawExecutionOutcome, ServiceRuntime,
-    TransactionTracker, UserApplicationDescription, UserApplicationId, UserContractCode,
-    UserContractInstance, UserServiceCode, UserServiceInstance, MAX_EVENT_KEY_LEN,
-    MAX_STREAM_NAME_LEN,
+    ApplicationCallPolicy, BaseRuntime, BytecodeId, ContractRuntime, ExecutionError,
+    FinalizeContext, MessageContext, Operation, OperationContext, PrivilegedSystemApi,
+    QueryContext, QueryOutcome, RawExecutionOutcome, ResourceControlPolicy, ServiceQueryLimits,
+    ServiceRuntime, TransactionTracker, UserApplicationDescription, UserApplicationId,
+    UserContractCode, UserContractInstance, UserServiceCode, UserServiceInstance,
+    MAX_EVENT_KEY_LEN, MAX_EVENT_VALUE_LEN, MAX_STREAM_NAME_LEN,
 };
 
 #[cfg(test)]
@@ -50,6 +52,7 @@ pub type ContractSyncRuntime = SyncRuntime<UserContractInstance>;
 pub struct ServiceSyncRuntime {
     runtime: SyncRuntime<UserServiceInstance>,
     current_context: QueryContext,
+    query_limits: ServiceQueryLimits,
 }
 
 #[derive(Debug)]
@@ -106,6 +109,9 @@ pub struct SyncRuntimeInternal<UserInstance> {
     refund_grant_to: Option<Account>,
     /// Controller to track fuel and storage consumption.
     resource_controller: ResourceController,
+    /// The limits of the service query being executed, and when it started.
+    #[debug(skip_if = Option::is_none)]
+    service_query: Option<(ServiceQueryLimits, Instant)>,
 }
 
 /// The runtime status of an application.
@@ -117,6 +123,8 @@ struct ApplicationStatus {
     id: UserApplicationId,
     /// The parameters from the application description.
     parameters: Vec<u8>,
+    /// The restrictions on the callers and system APIs of the application.
+    call_policy: Arc<ApplicationCallPolicy>,
     /// The authenticated signer for the execution thread, if any.
     signer: Option<Owner>,
     /// The current execution outcome of the application.
@@ -128,6 +136,14 @@ struct ApplicationStatus {
 struct LoadedApplication<Instance> {
     instance: Arc<Mutex<Instance>>,
     parameters: Vec<u8>,
+    /// The version of the bytecode whose state must be migrated before the application is first
+    /// executed, if any.
+    pending_migration: Arc<Mutex<Option<u32>>>,
+    /// The version of the contract or service ABI declared by the application's bytecode, if
+    /// any.
+    abi_version: Option<u32>,
+    /// The restrictions on the callers and system APIs of the application.
+    call_policy: Arc<ApplicationCallPolicy>,
 }
 
 impl<Instance> LoadedApplication<Instance> {
@@ -136,7 +152,59 @@ impl<Instance> LoadedApplication<Instance> {
         LoadedApplication {
             instance: Arc::new(Mutex::new(instance)),
             parameters: description.parameters,
+            pending_migration: Arc::default(),
+            abi_version: None,
+            call_policy: Arc::default(),
+        }
+    }
+
+    /// Records the version of the contract or service ABI declared by the application's
+    /// bytecode.
+    fn with_abi_version(mut self, abi_version: Option<u32>) -> Self {
+        self.abi_version = abi_version;
+        self
+    }
+
+    /// Records the restrictions on the callers and system APIs of the application.
+    fn with_call_policy(mut self, call_policy: ApplicationCallPolicy) -> Self {
+        self.call_policy = Arc::new(call_policy);
+        self
+    }
+
+    /// Checks that the application with ID `application_id` implements the expected `version`
+    /// of its ABI, if its bytecode declares one.
+    fn check_abi_version(
+        &self,
+        application_id: UserApplicationId,
+        version: u32,
+    ) -> Result<(), ExecutionError> {
+        if let Some(actual) = self.abi_version {
+            ensure!(
+                actual == version,
+                ExecutionError::AbiVersionMismatch {
+                    application_id: Box::new(application_id),
+                    expected: version,
+                    actual,
+                }
+            );
         }
+        Ok(())
+    }
+
+    /// Records the version of the bytecode whose state must be migrated before the application
+    /// is first executed, if any.
+    fn with_pending_migration(mut self, old_version: Option<u32>) -> Self {
+        self.pending_migration = Arc::new(Mutex::new(old_version));
+        self
+    }
+
+    /// Returns the version whose state must be migrated, if any, so that the migration runs only
+    /// once.
+    fn take_pending_migration(&self) -> Option<u32> {
+        self.pending_migration
+            .lock()
+            .expect("Pending migration lock should not be poisoned")
+            .take()
     }
 }
 
@@ -147,6 +215,9 @@ impl<Instance> Clone for LoadedApplication<Instance> {
         LoadedApplication {
             instance: self.instance.clone(),
             parameters: self.parameters.clone(),
+            pending_migration: self.pending_migration.clone(),
+            abi_version: self.abi_version,
+            call_policy: self.call_policy.clone(),
         }
     }
 }
@@ -315,6 +386,7 @@ impl<UserInstance> SyncRuntimeInternal<UserInstance> {
             resource_controller,
             transaction_tracker,
             scheduled_operations: Vec::new(),
+            service_query: None,
         }
     }
 
@@ -370,14 +442,51 @@ impl<UserInstance> SyncRuntimeInternal<UserInstance> {
 
     /// Ensures that a call to `application_id` is not-reentrant.
     ///
-    /// Returns an error if there already is an entry for `application_id` in the call stack.
+    /// Returns an error listing the current call stack if there already is an entry for
+    /// `application_id` in it.
     fn check_for_reentrancy(
         &mut self,
         application_id: UserApplicationId,
     ) -> Result<(), ExecutionError> {
         ensure!(
             !self.active_applications.contains(&application_id),
-            ExecutionError::ReentrantCall(application_id)
+            ExecutionError::ReentrantCall {
+                callee_id: Box::new(application_id),
+                call_stack: self
+                    .call_stack
+                    .iter()
+                    .map(|application| application.id)
+                    .collect(),
+            }
+        );
+        Ok(())
+    }
+
+    /// Ensures that the service of `application_id` may be queried.
+    ///
+    /// Returns an error if the application is on the call stack and its call policy doesn't
+    /// allow its service to be queried while it is executing.
+    fn check_for_reentrant_query(
+        &self,
+        application_id: UserApplicationId,
+    ) -> Result<(), ExecutionError> {
+        let Some(application) = self
+            .call_stack
+            .iter()
+            .find(|application| application.id == application_id)
+        else {
+            return Ok(());
+        };
+        ensure!(
+            application.call_policy.allows_reentrant_queries(),
+            ExecutionError::ReentrantQuery {
+                callee_id: Box::new(application_id),
+                call_stack: self
+                    .call_stack
+                    .iter()
+                    .map(|application| application.id)
+                    .collect(),
+            }
         );
         Ok(())
     }
@@ -406,11 +515,28 @@ impl SyncRuntimeInternal<UserContractInstance> {
                     .send_request(|callback| ExecutionRequest::LoadContract { id, callback })?
                     .recv_response()?;
 
+                let pending_migration = self
+                    .execution_state_sender
+                    .send_request(|callback| ExecutionRequest::TakePendingMigration {
+                        id,
+                        callback,
+                    })?
+                    .recv_response()?;
+                let call_policy = self
+                    .execution_state_sender
+                    .send_request(|callback| ExecutionRequest::CallPolicy { id, callback })?
+                    .recv_response()?;
+                let abi_version = code.abi_version();
                 let instance = code.instantiate(this)?;
 
                 self.applications_to_finalize.push(id);
                 Ok(entry
-                    .insert(LoadedApplication::new(instance, description))
+                    .insert(
+                        LoadedApplication::new(instance, description)
+                            .with_pending_migration(pending_migration)
+                            .with_abi_version(abi_version)
+                            .with_call_policy(call_policy),
+                    )
                     .clone())
             }
             hash_map::Entry::Occupied(entry) => Ok(entry.get().clone()),
@@ -423,7 +549,14 @@ impl SyncRuntimeInternal<UserContractInstance> {
         this: ContractSyncRuntimeHandle,
         authenticated: bool,
         callee_id: UserApplicationId,
-    ) -> Result<(Arc<Mutex<UserContractInstance>>, OperationContext), ExecutionError> {
+    ) -> Result<
+        (
+            Arc<Mutex<UserContractInstance>>,
+            OperationContext,
+            Option<u32>,
+        ),
+        ExecutionError,
+    > {
         self.check_for_reentrancy(callee_id)?;
 
         ensure!(
@@ -440,6 +573,14 @@ impl SyncRuntimeInternal<UserContractInstance> {
         let caller = self.current_application();
         let caller_id = caller.id;
         let caller_signer = caller.signer;
+        ensure!(
+            application.call_policy.can_be_called_by(&caller_id),
+            ExecutionError::UnauthorizedApplicationCall {
+                caller_id: Box::new(caller_id),
+                callee_id: Box::new(callee_id),
+            }
+        );
+        let pending_migration = application.take_pending_migration();
         // Make the call to user code.
         let authenticated_signer = match caller_signer {
             Some(signer) if authenticated => Some(signer),
@@ -458,11 +599,26 @@ impl SyncRuntimeInternal<UserContractInstance> {
             caller_id: authenticated_caller_id,
             id: callee_id,
             parameters: application.parameters,
+            call_policy: application.call_policy,
             // Allow further nested calls to be authenticated if this one is.
             signer: authenticated_signer,
             outcome: RawExecutionOutcome::default(),
         });
-        Ok((application.instance, callee_context))
+        Ok((application.instance, callee_context, pending_migration))
+    }
+
+    /// Ensures that the call policy of the current application allows it to use the
+    /// `system_api`.
+    fn check_system_api(&mut self, system_api: PrivilegedSystemApi) -> Result<(), ExecutionError> {
+        let application = self.current_application();
+        ensure!(
+            application.call_policy.can_use(system_api),
+            ExecutionError::UnauthorizedSystemApi {
+                application_id: Box::new(application.id),
+                system_api,
+            }
+        );
+        Ok(())
     }
 
     /// Cleans up the runtime after the execution of a call to a different contract.
@@ -528,14 +684,89 @@ impl SyncRuntimeInternal<UserServiceInstance> {
                     .send_request(|callback| ExecutionRequest::LoadService { id, callback })?
                     .recv_response()?;
 
+                let abi_version = code.abi_version();
                 let instance = code.instantiate(this)?;
                 Ok(entry
-                    .insert(LoadedApplication::new(instance, description))
+                    .insert(
+                        LoadedApplication::new(instance, description).with_abi_version(abi_version),
+                    )
                     .clone())
             }
             hash_map::Entry::Occupied(entry) => Ok(entry.get().clone()),
         }
     }
+
+    /// Resets the tracked resources before running a query that must respect the `limits`.
+    fn start_query(&mut self, limits: ServiceQueryLimits) {
+        self.resource_controller = ResourceController {
+            policy: Arc::new(ResourceControlPolicy {
+                maximum_fuel_per_block: limits.max_fuel,
+                ..ResourceControlPolicy::default()
+            }),
+            ..ResourceController::default()
+        };
+        self.service_query = Some((limits, Instant::now()));
+    }
+
+    /// Returns how much longer the current query may run, if there is one.
+    fn remaining_query_time(&self) -> Option<Duration> {
+        let (limits, start) = self.service_query.as_ref()?;
+        Some(limits.max_duration.saturating_sub(start.elapsed()))
+    }
+
+    /// Returns an error if the current query has been running for longer than allowed.
+    fn check_query_duration(&self) -> Result<(), ExecutionError> {
+        if let Some((limits, start)) = &self.service_query {
+            ensure!(
+                start.elapsed() <= limits.max_duration,
+                ExecutionError::ServiceQueryTimedOut(limits.max_duration)
+            );
+        }
+        Ok(())
+    }
+
+    /// Enforces the limits of the current query on its `result`.
+    ///
+    /// If the query failed because it exceeded one of its limits, the error is replaced with
+    /// one describing that limit, regardless of how the application's code reported it.
+    fn enforce_query_limits(
+        &mut self,
+        result: Result<Vec<u8>, ExecutionError>,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        let Some((limits, start)) = self.service_query.take() else {
+            return result;
+        };
+        match result {
+            Ok(response) => {
+                // A response computed after the deadline is rejected, e.g. if the application
+                // was blocked in a system API call when it expired.
+                ensure!(
+                    start.elapsed() <= limits.max_duration,
+                    ExecutionError::ServiceQueryTimedOut(limits.max_duration)
+                );
+                let size = response.len() as u64;
+                ensure!(
+                    size <= limits.max_response_bytes,
+                    ExecutionError::ServiceQueryResponseTooLarge {
+                        size,
+                        limit: limits.max_response_bytes,
+                    }
+                );
+                Ok(response)
+            }
+            Err(error) => {
+                ensure!(
+                    self.resource_controller.remaining_fuel() > 0,
+                    ExecutionError::ServiceQueryFuelLimitExceeded(limits.max_fuel)
+                );
+                ensure!(
+                    start.elapsed() <= limits.max_duration,
+                    ExecutionError::ServiceQueryTimedOut(limits.max_duration)
+                );
+                Err(error)
+            }
+        }
+    }
 }
 
 impl<UserInstance> SyncRuntime<UserInstance> {
@@ -714,6 +945,10 @@ impl<UserInstance> BaseRuntime for SyncRuntimeHandle<UserInstance> {
         self.inner().http_post(url, content_type, payload)
     }
 
+    fn fetch_url(&mut self, url: &str) -> Result<Vec<u8>, ExecutionError> {
+        self.inner().fetch_url(url)
+    }
+
     fn assert_before(&mut self, timestamp: Timestamp) -> Result<(), ExecutionError> {
         self.inner().assert_before(timestamp)
     }
@@ -912,12 +1147,11 @@ impl<UserInstance> BaseRuntime for SyncRuntimeInternal<UserInstance> {
         let id = self.application_id()?;
         let state = self.view_user_states.entry(id).or_default();
         let keys = state.find_keys_queries.wait(*promise)?;
-        let mut read_size = 0;
-        for key in &keys {
-            read_size += key.len();
-        }
-        self.resource_controller
-            .track_bytes_read(read_size as u64)?;
+        let read_size = keys
+            .iter()
+            .try_fold(0u64, |size, key| size.checked_add(key.len() as u64))
+            .ok_or(ArithmeticError::Overflow)?;
+        self.resource_controller.track_bytes_read(read_size)?;
         Ok(keys)
     }
 
@@ -945,12 +1179,14 @@ impl<UserInstance> BaseRuntime for SyncRuntimeInternal<UserInstance> {
         let id = self.application_id()?;
         let state = self.view_user_states.entry(id).or_default();
         let key_values = state.find_key_values_queries.wait(*promise)?;
-        let mut read_size = 0;
-        for (key, value) in &key_values {
-            read_size += key.len() + value.len();
-        }
-        self.resource_controller
-            .track_bytes_read(read_size as u64)?;
+        let read_size = key_values
+            .iter()
+            .try_fold(0u64, |size, (key, value)| {
+                size.checked_add(key.len() as u64)?
+                    .checked_add(value.len() as u64)
+            })
+            .ok_or(ArithmeticError::Overflow)?;
+        self.resource_controller.track_bytes_read(read_size)?;
         Ok(key_values)
     }
 
@@ -959,14 +1195,18 @@ impl<UserInstance> BaseRuntime for SyncRuntimeInternal<UserInstance> {
         application_id: ApplicationId,
         query: Vec<u8>,
     ) -> Result<Vec<u8>, ExecutionError> {
+        // Validators replaying a block use the responses recorded by its proposer.
         ensure!(
             cfg!(feature = "unstable-oracles"),
             ExecutionError::UnstableOracle
         );
-        let response =
+        self.check_for_reentrant_query(application_id)?;
+        let (response, fuel) =
             if let Some(response) = self.transaction_tracker.next_replayed_oracle_response()? {
                 match response {
-                    OracleResponse::Service(bytes) => bytes,
+                    OracleResponse::ServiceWithFuel { response, fuel } => (response, fuel),
+                    // Recorded before the fuel of queries was charged.
+                    OracleResponse::Service(response) => (response, 0),
                     _ => return Err(ExecutionError::OracleResponseMismatch),
                 }
             } else {
@@ -976,17 +1216,42 @@ impl<UserInstance> BaseRuntime for SyncRuntimeInternal<UserInstance> {
                     local_time: self.local_time,
                 };
                 let sender = self.execution_state_sender.clone();
+                // The query may not use more fuel than the caller has left.
+                let limits = ServiceQueryLimits {
+                    max_fuel: self.resource_controller.remaining_fuel(),
+                    ..ServiceQueryLimits::default()
+                };
 
+                let mut service_runtime =
+                    ServiceSyncRuntime::new(sender, context).with_query_limits(limits);
                 let QueryOutcome {
                     response,
                     operations,
-                } = ServiceSyncRuntime::new(sender, context).run_query(application_id, query)?;
+                } = service_runtime.run_query(application_id, query)?;
+                let fuel = service_runtime.query_fuel();
 
-                self.scheduled_operations.extend(operations);
-                response
+                if self.service_query.is_some() {
+                    self.scheduled_operations.extend(operations);
+                } else {
+                    // Queries made by contracts are read-only.
+                    ensure!(
+                        operations.is_empty(),
+                        ExecutionError::OperationScheduledByContractQuery(Box::new(application_id))
+                    );
+                }
+                (response, fuel)
             };
+        // Validators replaying the block don't run the service, so the fuel it consumed is
+        // charged from the recorded response, the same way as its size, and all validators agree
+        // on the fees.
+        self.resource_controller.track_fuel(fuel)?;
+        self.resource_controller
+            .track_bytes_read(response.len() as u64)?;
         self.transaction_tracker
-            .add_oracle_response(OracleResponse::Service(response.clone()));
+            .add_oracle_response(OracleResponse::ServiceWithFuel {
+                response: response.clone(),
+                fuel,
+            });
         Ok(response)
     }
 
@@ -1022,6 +1287,38 @@ impl<UserInstance> BaseRuntime for SyncRuntimeInternal<UserInstance> {
         Ok(bytes)
     }
 
+    fn fetch_url(&mut self, url: &str) -> Result<Vec<u8>, ExecutionError> {
+        // The responses fetched by services are not part of any block.
+        ensure!(
+            self.service_query.is_some() || cfg!(feature = "unstable-oracles"),
+            ExecutionError::UnstableOracle
+        );
+        let bytes =
+            if let Some(response) = self.transaction_tracker.next_replayed_oracle_response()? {
+                match response {
+                    OracleResponse::Get(bytes) => bytes,
+                    _ => return Err(ExecutionError::OracleResponseMismatch),
+                }
+            } else {
+                let url = url.to_string();
+                let max_response_bytes = self.resource_controller.remaining_bytes_read();
+                self.execution_state_sender
+                    .send_request(|callback| ExecutionRequest::FetchUrl {
+                        url,
+                        max_response_bytes,
+                        callback,
+                    })?
+                    .recv_response()??
+            };
+        // The response is charged the same way on replay, so that all validators agree on the
+        // fees.
+        self.resource_controller
+            .track_bytes_read(bytes.len() as u64)?;
+        self.transaction_tracker
+            .add_oracle_response(OracleResponse::Get(bytes.clone()));
+        Ok(bytes)
+    }
+
     fn assert_before(&mut self, timestamp: Timestamp) -> Result<(), ExecutionError> {
         if !self
             .transaction_tracker
@@ -1116,10 +1413,14 @@ impl ContractSyncRuntime {
         let mut this_guard = this.inner();
 
         if let hash_map::Entry::Vacant(entry) = this_guard.loaded_applications.entry(id) {
-            entry.insert(LoadedApplication::new(
-                code.instantiate(runtime_handle)?,
-                description,
-            ));
+            let pending_migration = this_guard
+                .execution_state_sender
+                .send_request(|callback| ExecutionRequest::TakePendingMigration { id, callback })?
+                .recv_response()?;
+            entry.insert(
+                LoadedApplication::new(code.instantiate(runtime_handle)?, description)
+                    .with_pending_migration(pending_migration),
+            );
             this_guard.applications_to_finalize.push(id);
         }
 
@@ -1168,6 +1469,7 @@ impl ContractSyncRuntimeHandle {
                 code.execute_operation(context, operation).map(|_| ())
             }
             UserAction::Message(context, message) => code.execute_message(context, message),
+            UserAction::Callback(context, argument) => code.execute_callback(context, argument),
         })?;
         self.finalize(finalize_context)?;
         Ok(())
@@ -1206,6 +1508,7 @@ impl ContractSyncRuntimeHandle {
                 caller_id: None,
                 id: application_id,
                 parameters: application.parameters.clone(),
+                call_policy: application.call_policy.clone(),
                 signer,
                 outcome: RawExecutionOutcome::default(),
             };
@@ -1215,12 +1518,16 @@ impl ContractSyncRuntimeHandle {
             application
         };
 
-        closure(
-            &mut contract
+        {
+            let mut instance = contract
                 .instance
                 .try_lock()
-                .expect("Application should not be already executing"),
-        )?;
+                .expect("Application should not be already executing");
+            if let Some(old_version) = contract.take_pending_migration() {
+                instance.migrate(old_version)?;
+            }
+            closure(&mut instance)?;
+        }
 
         let mut runtime = self.inner();
         let application_status = runtime.pop_application();
@@ -1269,6 +1576,26 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         this.resource_controller.track_fuel(fuel)
     }
 
+    fn check_wasm_instance_size(
+        &mut self,
+        memory_pages: u64,
+        table_elements: u64,
+    ) -> Result<(), ExecutionError> {
+        self.inner()
+            .resource_controller
+            .check_wasm_instance_size(memory_pages, table_elements)
+    }
+
+    fn track_wasm_memory_growth(
+        &mut self,
+        memory_pages: u64,
+        additional_pages: u64,
+    ) -> Result<(), ExecutionError> {
+        self.inner()
+            .resource_controller
+            .track_wasm_memory_growth(memory_pages, additional_pages)
+    }
+
     fn send_message(&mut self, message: SendMessageRequest<Vec<u8>>) -> Result<(), ExecutionError> {
         let mut this = self.inner();
         let application = this.current_application_mut();
@@ -1303,6 +1630,7 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         amount: Amount,
     ) -> Result<(), ExecutionError> {
         let mut this = self.inner();
+        this.check_system_api(PrivilegedSystemApi::Transfer)?;
         let current_application = this.current_application();
         let application_id = current_application.id;
         let signer = current_application.signer;
@@ -1331,6 +1659,7 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         amount: Amount,
     ) -> Result<(), ExecutionError> {
         let mut this = self.inner();
+        this.check_system_api(PrivilegedSystemApi::Claim)?;
         let current_application = this.current_application();
         let application_id = current_application.id;
         let signer = current_application.signer;
@@ -1358,18 +1687,35 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         callee_id: UserApplicationId,
         argument: Vec<u8>,
     ) -> Result<Vec<u8>, ExecutionError> {
-        let (contract, context) =
+        let (contract, context, pending_migration) =
             self.inner()
                 .prepare_for_call(self.clone(), authenticated, callee_id)?;
 
-        let value = contract
+        let mut instance = contract
             .try_lock()
-            .expect("Applications should not have reentrant calls")
-            .execute_operation(context, argument)?;
+            .expect("Applications should not have reentrant calls");
+        if let Some(old_version) = pending_migration {
+            instance.migrate(old_version)?;
+        }
+        let result = instance.execute_operation(context, argument);
+        drop(instance);
 
-        self.inner().finish_call()?;
+        // An application error is the callee's response, so the call is finished normally.
+        if let Ok(_) | Err(ExecutionError::ApplicationError(_)) = &result {
+            self.inner().finish_call()?;
+        }
 
-        Ok(value)
+        result
+    }
+
+    fn assert_abi_version(
+        &mut self,
+        application_id: UserApplicationId,
+        version: u32,
+    ) -> Result<(), ExecutionError> {
+        self.inner()
+            .load_contract_instance(self.clone(), application_id)?
+            .check_abi_version(application_id, version)
     }
 
     fn emit(
@@ -1387,8 +1733,39 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
             name.0.len() <= MAX_STREAM_NAME_LEN,
             ExecutionError::StreamNameTooLong
         );
+        ensure!(
+            value.len() <= MAX_EVENT_VALUE_LEN,
+            ExecutionError::EventValueTooLong
+        );
+        // The latest value of each stream and key is stored in the chain state.
+        let entry_size = name.0.len() + key.len() + value.len();
+        let value_size = value.len();
+        this.resource_controller.track_write_operations(1)?;
+        this.resource_controller
+            .track_bytes_written(entry_size as u64)?;
         let application = this.current_application_mut();
-        application.outcome.events.push((name, key, value));
+        application
+            .outcome
+            .events
+            .push((name.clone(), key.clone(), value.clone()));
+        let stream_id = StreamId {
+            application_id: GenericApplicationId::User(application.id),
+            stream_name: name,
+        };
+        let previous_value_size = this
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::RecordEvent {
+                stream_id,
+                key,
+                value,
+                callback,
+            })?
+            .recv_response()?;
+        let delta = match previous_value_size {
+            Some(previous_value_size) => value_size as i64 - previous_value_size as i64,
+            None => entry_size as i64,
+        };
+        this.resource_controller.track_stored_bytes(delta)?;
         Ok(())
     }
 
@@ -1399,6 +1776,7 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         balance: Amount,
     ) -> Result<(MessageId, ChainId), ExecutionError> {
         let mut this = self.inner();
+        this.check_system_api(PrivilegedSystemApi::OpenChain)?;
         let message_id = MessageId {
             chain_id: this.chain_id,
             height: this.height,
@@ -1424,6 +1802,7 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
 
     fn close_chain(&mut self) -> Result<(), ExecutionError> {
         let mut this = self.inner();
+        this.check_system_api(PrivilegedSystemApi::CloseChain)?;
         let application_id = this.current_application().id;
         this.execution_state_sender
             .send_request(|callback| ExecutionRequest::CloseChain {
@@ -1438,6 +1817,7 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         application_permissions: ApplicationPermissions,
     ) -> Result<(), ExecutionError> {
         let mut this = self.inner();
+        this.check_system_api(PrivilegedSystemApi::ChangeApplicationPermissions)?;
         let application_id = this.current_application().id;
         this.execution_state_sender
             .send_request(|callback| ExecutionRequest::ChangeApplicationPermissions {
@@ -1455,6 +1835,8 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         argument: Vec<u8>,
         required_application_ids: Vec<UserApplicationId>,
     ) -> Result<UserApplicationId, ExecutionError> {
+        self.inner()
+            .check_system_api(PrivilegedSystemApi::CreateApplication)?;
         let chain_id = self.inner().chain_id;
         let height = self.block_height()?;
         let index = self.inner().transaction_tracker.next_message_index();
@@ -1490,7 +1872,7 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
             .transaction_tracker
             .add_system_outcome(outcome)?;
 
-        let (contract, context) = self.inner().prepare_for_call(self.clone(), true, app_id)?;
+        let (contract, context, _) = self.inner().prepare_for_call(self.clone(), true, app_id)?;
 
         contract
             .try_lock()
@@ -1515,13 +1897,22 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         )?;
         this.resource_controller
             .track_bytes_written(batch.size() as u64)?;
-        this.execution_state_sender
+        let maximum_bytes_stored = this
+            .resource_controller
+            .policy
+            .maximum_bytes_stored_per_application;
+        let (size_before, size_after) = this
+            .execution_state_sender
             .send_request(|callback| ExecutionRequest::WriteBatch {
                 id,
                 batch,
+                maximum_bytes_stored,
                 callback,
             })?
-            .recv_response()?;
+            .recv_response()??;
+        let delta = i64::try_from(i128::from(size_after) - i128::from(size_before))
+            .map_err(|_| ArithmeticError::Overflow)?;
+        this.resource_controller.track_stored_bytes(delta)?;
         Ok(())
     }
 
@@ -1540,6 +1931,33 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
             .add_oracle_response(OracleResponse::Round(round));
         Ok(round)
     }
+
+    fn schedule_callback(
+        &mut self,
+        timestamp: Timestamp,
+        argument: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        let mut this = self.inner();
+        let application_id = this.current_application().id;
+        // Scheduling a callback costs as much as the operation that would execute it.
+        this.resource_controller.track_operation(&Operation::User {
+            application_id,
+            bytes: argument.clone(),
+        })?;
+        let maximum_scheduled_callbacks = this
+            .resource_controller
+            .policy
+            .maximum_scheduled_callbacks_per_application;
+        this.execution_state_sender
+            .send_request(|callback| ExecutionRequest::ScheduleCallback {
+                application_id,
+                timestamp,
+                argument,
+                maximum_scheduled_callbacks,
+                callback,
+            })?
+            .recv_response()?
+    }
 }
 
 impl ServiceSyncRuntime {
@@ -1564,9 +1982,16 @@ impl ServiceSyncRuntime {
         ServiceSyncRuntime {
             runtime,
             current_context: context,
+            query_limits: ServiceQueryLimits::default(),
         }
     }
 
+    /// Configures the limits enforced on each query executed by this runtime.
+    pub fn with_query_limits(mut self, limits: ServiceQueryLimits) -> Self {
+        self.query_limits = limits;
+        self
+    }
+
     /// Loads a service into the runtime's memory.
     pub(crate) fn preload_service(
         &self,
@@ -1600,10 +2025,12 @@ impl ServiceSyncRuntime {
                 application_id,
                 context,
                 query,
+                limits,
                 callback,
             } = request;
 
             self.prepare_for_query(context);
+            self.query_limits = limits;
 
             let _ = callback.send(self.run_query(application_id, query));
         }
@@ -1630,8 +2057,11 @@ impl ServiceSyncRuntime {
         application_id: UserApplicationId,
         query: Vec<u8>,
     ) -> Result<QueryOutcome<Vec<u8>>, ExecutionError> {
+        let limits = self.query_limits;
         let this = self.handle_mut();
-        let response = this.try_query_application(application_id, query)?;
+        this.inner().start_query(limits);
+        let result = this.try_query_application(application_id, query);
+        let response = this.inner().enforce_query_limits(result)?;
         let operations = mem::take(&mut this.inner().scheduled_operations);
 
         Ok(QueryOutcome {
@@ -1640,6 +2070,11 @@ impl ServiceSyncRuntime {
         })
     }
 
+    /// Returns the fuel consumed by the last query executed by this runtime.
+    pub(crate) fn query_fuel(&mut self) -> u64 {
+        self.handle_mut().inner().resource_controller.tracker.fuel
+    }
+
     /// Obtains the [`SyncRuntimeHandle`] stored in this [`ServiceSyncRuntime`].
     fn handle_mut(&mut self) -> &mut ServiceSyncRuntimeHandle {
         self.runtime.0.as_mut().expect(
@@ -1657,6 +2092,7 @@ impl ServiceRuntime for ServiceSyncRuntimeHandle {
     ) -> Result<Vec<u8>, ExecutionError> {
         let (query_context, service) = {
             let mut this = self.inner();
+            this.check_query_duration()?;
 
             // Load the application.
             let application = this.load_service_instance(self.clone(), queried_id)?;
@@ -1670,6 +2106,7 @@ impl ServiceRuntime for ServiceSyncRuntimeHandle {
                 caller_id: None,
                 id: queried_id,
                 parameters: application.parameters,
+                call_policy: application.call_policy,
                 signer: None,
                 outcome: RawExecutionOutcome::default(),
             });
@@ -1683,13 +2120,15 @@ impl ServiceRuntime for ServiceSyncRuntimeHandle {
         Ok(response)
     }
 
-    /// Get a blob of bytes from an arbitrary URL.
-    fn fetch_url(&mut self, url: &str) -> Result<Vec<u8>, ExecutionError> {
-        let this = self.inner();
-        let url = url.to_string();
-        this.execution_state_sender
-            .send_request(|callback| ExecutionRequest::FetchUrl { url, callback })?
-            .recv_response()
+    fn assert_abi_version(
+        &mut self,
+        application_id: UserApplicationId,
+        version: u32,
+    ) -> Result<(), ExecutionError> {
+        let mut this = self.inner();
+        this.check_query_duration()?;
+        this.load_service_instance(self.clone(), application_id)?
+            .check_abi_version(application_id, version)
     }
 
     fn schedule_operation(&mut self, operation: Vec<u8>) -> Result<(), ExecutionError> {
@@ -1703,6 +2142,61 @@ impl ServiceRuntime for ServiceSyncRuntimeHandle {
 
         Ok(())
     }
+
+    fn read_event(
+        &mut self,
+        application_id: UserApplicationId,
+        name: StreamName,
+        key: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, ExecutionError> {
+        let stream_id = StreamId {
+            application_id: GenericApplicationId::User(application_id),
+            stream_name: name,
+        };
+        self.inner()
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::ReadEvent {
+                stream_id,
+                key,
+                callback,
+            })?
+            .recv_response()
+    }
+
+    fn remaining_fuel(&mut self) -> Result<u64, ExecutionError> {
+        Ok(self.inner().resource_controller.remaining_fuel())
+    }
+
+    fn consume_fuel(&mut self, fuel: u64) -> Result<(), ExecutionError> {
+        let mut this = self.inner();
+        this.check_query_duration()?;
+        this.resource_controller.track_fuel(fuel)
+    }
+
+    fn remaining_query_time(&mut self) -> Result<Option<Duration>, ExecutionError> {
+        Ok(self.inner().remaining_query_time())
+    }
+
+    fn check_wasm_instance_size(
+        &mut self,
+        memory_pages: u64,
+        table_elements: u64,
+    ) -> Result<(), ExecutionError> {
+        self.inner()
+            .resource_controller
+            .check_wasm_instance_size(memory_pages, table_elements)
+    }
+
+    fn track_wasm_memory_growth(
+        &mut self,
+        memory_pages: u64,
+        additional_pages: u64,
+    ) -> Result<(), ExecutionError> {
+        let mut this = self.inner();
+        this.check_query_duration()?;
+        this.resource_controller
+            .track_wasm_memory_growth(memory_pages, additional_pages)
+    }
 }
 
 /// A request to the service runtime actor.
@@ -1711,6 +2205,7 @@ pub enum ServiceRuntimeRequest {
         application_id: UserApplicationId,
         context: QueryContext,
         query: Vec<u8>,
+        limits: ServiceQueryLimits,
         callback: oneshot::Sender<Result<QueryOutcome<Vec<u8>>, ExecutionError>>,
     },
 }
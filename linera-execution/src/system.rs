@@ -19,18 +19,20 @@ use custom_debug_derive::Debug;
 use linera_base::{
     crypto::CryptoHash,
     data_types::{
-        Amount, ApplicationPermissions, ArithmeticError, BlobContent, OracleResponse, Timestamp,
+        Amount, ApplicationPermissions, ArithmeticError, BlobContent, BytecodeChunks,
+        CompressedBytecode, OracleResponse, Timestamp,
     },
     ensure, hex_debug,
     identifiers::{
         Account, AccountOwner, BlobId, BlobType, BytecodeId, ChainDescription, ChainId, MessageId,
-        Owner,
+        Owner, StreamId,
     },
     ownership::{ChainOwnership, TimeoutConfig},
 };
 use linera_views::{
+    common::CustomSerialize,
     context::Context,
-    map_view::HashedMapView,
+    map_view::{HashedCustomMapView, HashedMapView},
     register_view::HashedRegisterView,
     set_view::HashedSetView,
     views::{ClonableView, HashableView, View, ViewError},
@@ -44,10 +46,11 @@ use {linera_base::prometheus_util::register_int_counter_vec, prometheus::IntCoun
 use crate::test_utils::SystemExecutionState;
 use crate::{
     committee::{Committee, Epoch},
-    ApplicationRegistryView, ChannelName, ChannelSubscription, Destination,
-    ExecutionRuntimeContext, MessageContext, MessageKind, OperationContext, QueryContext,
-    QueryOutcome, RawExecutionOutcome, RawOutgoingMessage, TransactionTracker,
-    UserApplicationDescription, UserApplicationId,
+    ApplicationCallPolicy, ApplicationCallPolicyUpdate, ApplicationRegistryView,
+    ApplicationUpgrade, ChannelName, ChannelSubscription, Destination, ExecutionRuntimeContext,
+    MessageContext, MessageKind, OperationContext, QueryContext, QueryOutcome, RawExecutionOutcome,
+    RawOutgoingMessage, ResourceControlPolicy, TransactionTracker, UserApplicationDescription,
+    UserApplicationId,
 };
 
 /// The relative index of the `OpenChain` message created by the `OpenChain` operation.
@@ -98,6 +101,73 @@ pub struct SystemExecutionStateView<C> {
     pub application_permissions: HashedRegisterView<C, ApplicationPermissions>,
     /// Blobs that have been used or published on this chain.
     pub used_blobs: HashedSetView<C, BlobId>,
+    /// The number of bytes that user applications hold in storage on this chain, i.e. the sum
+    /// of `bytes_stored_by_application`.
+    pub bytes_stored: HashedRegisterView<C, u64>,
+    /// The number of bytes that each user application holds in storage on this chain.
+    pub bytes_stored_by_application: HashedMapView<C, UserApplicationId, u64>,
+    /// The storage rent that could not be paid from the chain balance yet.
+    pub storage_rent_debt: HashedRegisterView<C, Amount>,
+    /// If some storage rent is unpaid, the time from which applications can no longer increase
+    /// their storage. The largest application state is evicted one grace period later.
+    pub storage_rent_due_by: HashedRegisterView<C, Option<Timestamp>>,
+    /// The callbacks scheduled by applications, ordered by the time from which they are due.
+    pub scheduled_callbacks: HashedCustomMapView<C, CallbackKey, ScheduledCallback>,
+    /// The number of callbacks scheduled so far, used to order callbacks due at the same time.
+    pub next_callback_index: HashedRegisterView<C, u64>,
+    /// The number of callbacks each application has scheduled that were not executed yet.
+    pub scheduled_callback_counts: HashedMapView<C, UserApplicationId, u32>,
+    /// The value of the latest event emitted on each stream of this chain with each key.
+    pub latest_events: HashedMapView<C, (StreamId, Vec<u8>), Vec<u8>>,
+}
+
+/// The name of the system event stream on which the callbacks that failed are recorded.
+pub const FAILED_CALLBACKS_STREAM: &str = "failed_callbacks";
+
+/// The name of the system event stream on which the applications evicted for unpaid storage
+/// rent are recorded. The key of each event is the application ID and its value the time of
+/// the eviction, both BCS-serialized.
+pub const EVICTED_APPLICATIONS_STREAM: &str = "evicted_applications";
+
+/// A callback scheduled by an application, executed by the first
+/// [`SystemOperation::ExecuteCallbacks`] in a block whose timestamp is not earlier than the
+/// scheduled one.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+pub struct ScheduledCallback {
+    /// The application to call back.
+    pub application_id: UserApplicationId,
+    /// The argument passed to the callback entrypoint of the application.
+    #[serde(with = "serde_bytes")]
+    #[debug(with = "hex_debug")]
+    pub argument: Vec<u8>,
+}
+
+/// The key of a [`ScheduledCallback`]: the time from which it is due, and the number of
+/// callbacks scheduled before it on the chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CallbackKey {
+    pub timestamp: Timestamp,
+    pub index: u64,
+}
+
+// Serialize keys so that the lexicographic order of the serialized keys is the chronological
+// order, then the order in which the callbacks were scheduled.
+impl CustomSerialize for CallbackKey {
+    fn to_custom_bytes(&self) -> Result<Vec<u8>, ViewError> {
+        let data = (
+            self.timestamp.micros().to_be_bytes(),
+            self.index.to_be_bytes(),
+        );
+        Ok(bcs::to_bytes(&data)?)
+    }
+
+    fn from_custom_bytes(short_key: &[u8]) -> Result<Self, ViewError> {
+        let (time_bytes, index_bytes) = bcs::from_bytes(short_key)?;
+        Ok(Self {
+            timestamp: Timestamp::from(u64::from_be_bytes(time_bytes)),
+            index: u64::from_be_bytes(index_bytes),
+        })
+    }
 }
 
 /// The configuration for a new chain.
@@ -183,6 +253,9 @@ pub enum SystemOperation {
         instantiation_argument: Vec<u8>,
         #[debug(skip_if = Vec::is_empty)]
         required_application_ids: Vec<UserApplicationId>,
+        /// The restrictions on the callers and system APIs of the application.
+        #[debug(skip_if = ApplicationCallPolicy::is_unrestricted)]
+        call_policy: ApplicationCallPolicy,
     },
     /// Requests a message from another chain to register a user application on this chain.
     RequestApplication {
@@ -191,6 +264,27 @@ pub enum SystemOperation {
     },
     /// Operations that are only allowed on the admin chain.
     Admin(AdminOperation),
+    /// Replaces the bytecode of an application created on this chain. The migration
+    /// entrypoint of the new contract is executed the next time the application is used.
+    /// The block must be signed by the owner who created the application. Other chains
+    /// adopt the upgrade when they receive messages of the application from a chain that
+    /// knows it, or when they request the application again.
+    UpgradeApplication {
+        application_id: UserApplicationId,
+        bytecode_id: BytecodeId,
+    },
+    /// Replaces the restrictions on the callers and system APIs of an application created on
+    /// this chain. The block must be signed by the owner who created the application. Other
+    /// chains adopt the new policy when the application is registered or requested again.
+    SetApplicationCallPolicy {
+        application_id: UserApplicationId,
+        policy: ApplicationCallPolicy,
+    },
+    /// Executes the callbacks scheduled by applications that are due at the timestamp of the
+    /// block, in the order of their scheduled times, up to the
+    /// [`ResourceControlPolicy::maximum_callbacks_per_operation`] of them. A callback that fails
+    /// is dropped.
+    ExecuteCallbacks,
 }
 
 /// Operations that are only allowed on the admin chain.
@@ -248,10 +342,16 @@ pub enum SystemMessage {
     /// Applications must be registered after their dependencies.
     RegisterApplications {
         applications: Vec<UserApplicationDescription>,
+        /// The call policies of the applications that have one. Older versions than the
+        /// recipient's are ignored.
+        call_policies: Vec<ApplicationCallPolicyUpdate>,
     },
     /// Requests a `RegisterApplication` message from the target chain to register the specified
     /// application on the sender chain.
     RequestApplication(UserApplicationId),
+    /// Shares the latest upgrades of some registered applications, so that the recipient runs
+    /// the same bytecode as the sender. Older versions than the recipient's are ignored.
+    UpgradeApplications(Vec<ApplicationUpgrade>),
 }
 
 /// A query to the system state.
@@ -411,9 +511,23 @@ pub enum SystemExecutionError {
     UnknownApplicationId(Box<UserApplicationId>),
     #[error("Chain is not active yet.")]
     InactiveChain,
+    #[error("Application {0:?} can only be upgraded on the chain that created it")]
+    UpgradeOnNonCreatorChain(Box<UserApplicationId>),
+    #[error("Application {0:?} can only be upgraded by the owner who created it")]
+    UnauthorizedApplicationUpgrade(Box<UserApplicationId>),
+    #[error("The call policy of application {0:?} can only be set on the chain that created it")]
+    CallPolicyOnNonCreatorChain(Box<UserApplicationId>),
+    #[error("The call policy of application {0:?} can only be set by the owner who created it")]
+    UnauthorizedCallPolicyChange(Box<UserApplicationId>),
+    #[error("Application {0:?} already has the maximum number of scheduled callbacks")]
+    TooManyScheduledCallbacks(Box<UserApplicationId>),
 
     #[error("Blobs not found: {0:?}")]
     BlobsNotFound(Vec<BlobId>),
+    #[error("The chunks listed by the bytecode blob {0} do not reassemble into its bytecode")]
+    InvalidBytecodeChunks(BlobId),
+    #[error("The bytecode reassembled from the chunks of the blob {0} is invalid or too large")]
+    ChunkedBytecodeTooLarge(BlobId),
     #[error("Oracle response mismatch")]
     OracleResponseMismatch,
     #[error("No recorded response for oracle query")]
@@ -449,6 +563,172 @@ where
         Some((*epoch, committee))
     }
 
+    /// Charges the rent for the bytes stored on this chain between the previous block and
+    /// `timestamp` to the chain balance.
+    ///
+    /// Rent that cannot be paid is added to the storage rent debt, which is paid first at the
+    /// next blocks. From the time the balance ran out, the chain is given the grace period of
+    /// the `policy` before its applications can no longer increase their storage. Since the
+    /// rent of an idle chain is only charged at its next block, that time may be in the past.
+    pub fn charge_storage_rent(
+        &mut self,
+        policy: &ResourceControlPolicy,
+        timestamp: Timestamp,
+    ) -> Result<(), ArithmeticError> {
+        let elapsed = timestamp.delta_since(*self.timestamp.get());
+        let rent = policy.storage_rent(*self.bytes_stored.get(), elapsed)?;
+        let mut debt = self.storage_rent_debt.get().try_add(rent)?;
+        if debt.is_zero() {
+            return Ok(());
+        }
+        let balance = self.balance.get_mut();
+        let payment = debt.min(*balance);
+        balance.try_sub_assign(payment)?;
+        debt.try_sub_assign(payment)?;
+        self.storage_rent_debt.set(debt);
+        if debt.is_zero() {
+            self.storage_rent_due_by.set(None);
+        } else if self.storage_rent_due_by.get().is_none() {
+            // There was no debt before, so the unpaid part of the rent is the last part of the
+            // elapsed time.
+            let unpaid_micros = u128::from(debt)
+                .checked_mul(u128::from(elapsed.as_micros()))
+                .ok_or(ArithmeticError::Overflow)?
+                .checked_div(u128::from(rent))
+                .unwrap_or_default();
+            let unpaid_since =
+                timestamp.saturating_sub_micros(u64::try_from(unpaid_micros).unwrap_or(u64::MAX));
+            let due_by = unpaid_since.saturating_add(policy.storage_rent_grace_period);
+            self.storage_rent_due_by.set(Some(due_by));
+        }
+        Ok(())
+    }
+
+    /// Returns whether the storage rent has been unpaid for longer than the grace period.
+    /// Applications cannot increase their storage until it is paid.
+    pub fn is_storage_rent_overdue(&self) -> bool {
+        self.storage_rent_due_by
+            .get()
+            .is_some_and(|due_by| *self.timestamp.get() >= due_by)
+    }
+
+    /// Returns whether the storage rent has still not been paid at `timestamp`, one grace
+    /// period after it became overdue, so that the application states must be evicted.
+    pub fn must_evict_for_storage_rent(
+        &self,
+        policy: &ResourceControlPolicy,
+        timestamp: Timestamp,
+    ) -> bool {
+        self.storage_rent_due_by.get().is_some_and(|due_by| {
+            timestamp >= due_by.saturating_add(policy.storage_rent_grace_period)
+        })
+    }
+
+    /// Records that the application holds `bytes` bytes in storage, and updates the total
+    /// number of bytes stored on this chain.
+    pub async fn record_bytes_stored(
+        &mut self,
+        application_id: UserApplicationId,
+        bytes: u64,
+    ) -> Result<(), ViewError> {
+        let previous_bytes = self
+            .bytes_stored_by_application
+            .get(&application_id)
+            .await?
+            .unwrap_or_default();
+        let bytes_stored = self.bytes_stored.get_mut();
+        *bytes_stored = bytes_stored
+            .saturating_sub(previous_bytes)
+            .saturating_add(bytes);
+        if bytes == 0 {
+            self.bytes_stored_by_application.remove(&application_id)?;
+        } else {
+            self.bytes_stored_by_application
+                .insert(&application_id, bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Schedules a callback of the application, due from the given `timestamp`, unless it
+    /// already has `maximum_scheduled_callbacks` of them.
+    pub async fn schedule_callback(
+        &mut self,
+        application_id: UserApplicationId,
+        timestamp: Timestamp,
+        argument: Vec<u8>,
+        maximum_scheduled_callbacks: u64,
+    ) -> Result<(), SystemExecutionError> {
+        let count = self
+            .scheduled_callback_counts
+            .get_mut_or_default(&application_id)
+            .await?;
+        ensure!(
+            u64::from(*count) < maximum_scheduled_callbacks,
+            SystemExecutionError::TooManyScheduledCallbacks(Box::new(application_id))
+        );
+        *count += 1;
+        let index = self.next_callback_index.get_mut();
+        let key = CallbackKey {
+            timestamp,
+            index: *index,
+        };
+        *index = index.checked_add(1).ok_or(ArithmeticError::Overflow)?;
+        let callback = ScheduledCallback {
+            application_id,
+            argument,
+        };
+        self.scheduled_callbacks.insert(&key, callback)?;
+        Ok(())
+    }
+
+    /// Removes and returns at most `limit` callbacks that are due at the given time, in the
+    /// order in which they must be executed.
+    pub async fn take_due_callbacks(
+        &mut self,
+        now: Timestamp,
+        limit: usize,
+    ) -> Result<Vec<ScheduledCallback>, SystemExecutionError> {
+        let mut due = Vec::new();
+        self.scheduled_callbacks
+            .for_each_index_value_while(|key, callback| {
+                if key.timestamp > now || due.len() >= limit {
+                    return Ok(false);
+                }
+                due.push((key, callback.into_owned()));
+                Ok(true)
+            })
+            .await?;
+        let mut callbacks = Vec::with_capacity(due.len());
+        for (key, callback) in due {
+            self.scheduled_callbacks.remove(&key)?;
+            let application_id = &callback.application_id;
+            if let Some(count) = self
+                .scheduled_callback_counts
+                .get_mut(application_id)
+                .await?
+            {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    self.scheduled_callback_counts.remove(application_id)?;
+                }
+            }
+            callbacks.push(callback);
+        }
+        Ok(callbacks)
+    }
+
+    /// Returns the time from which the earliest scheduled callback is due, if any.
+    pub async fn next_callback_time(&self) -> Result<Option<Timestamp>, ViewError> {
+        let mut next = None;
+        self.scheduled_callbacks
+            .for_each_index_while(|key| {
+                next = Some(key.timestamp);
+                Ok(false)
+            })
+            .await?;
+        Ok(next)
+    }
+
     /// Executes the sender's side of an operation and returns a list of actions to be
     /// taken.
     pub async fn execute_operation(
@@ -551,6 +831,7 @@ where
                             authenticated: false,
                             grant: Amount::ZERO,
                             kind: MessageKind::Protected,
+                            expires_at: None,
                             message: SystemMessage::CreateCommittee { epoch, committee },
                         };
                         outcome.messages.push(message);
@@ -565,6 +846,7 @@ where
                             authenticated: false,
                             grant: Amount::ZERO,
                             kind: MessageKind::Protected,
+                            expires_at: None,
                             message: SystemMessage::RemoveCommittee { epoch },
                         };
                         outcome.messages.push(message);
@@ -596,6 +878,7 @@ where
                     authenticated: false,
                     grant: Amount::ZERO,
                     kind: MessageKind::Protected,
+                    expires_at: None,
                     message: SystemMessage::Subscribe {
                         id: context.chain_id,
                         subscription,
@@ -618,6 +901,7 @@ where
                     authenticated: false,
                     grant: Amount::ZERO,
                     kind: MessageKind::Protected,
+                    expires_at: None,
                     message: SystemMessage::Unsubscribe {
                         id: context.chain_id,
                         subscription,
@@ -640,6 +924,7 @@ where
                 parameters,
                 instantiation_argument,
                 required_application_ids,
+                call_policy,
             } => {
                 let next_message_id = context.next_message_id(txn_tracker.next_message_index());
                 let CreateApplicationResult {
@@ -652,8 +937,12 @@ where
                         bytecode_id,
                         parameters,
                         required_application_ids,
+                        context.authenticated_signer,
                     )
                     .await?;
+                if !call_policy.is_unrestricted() {
+                    self.registry.set_call_policy(app_id, call_policy).await?;
+                }
                 self.record_bytecode_blobs(blobs_to_register, txn_tracker)
                     .await?;
                 outcome.messages.push(message);
@@ -668,6 +957,7 @@ where
                     authenticated: false,
                     grant: Amount::ZERO,
                     kind: MessageKind::Simple,
+                    expires_at: None,
                     message: SystemMessage::RequestApplication(application_id),
                 };
                 outcome.messages.push(message);
@@ -675,10 +965,49 @@ where
             PublishDataBlob { blob_hash } => {
                 self.blob_published(&BlobId::new(blob_hash, BlobType::Data))?;
             }
+            UpgradeApplication {
+                application_id,
+                bytecode_id,
+            } => {
+                ensure!(
+                    application_id.creation.chain_id == context.chain_id,
+                    SystemExecutionError::UpgradeOnNonCreatorChain(Box::new(application_id))
+                );
+                let owner = self.registry.owners.get(&application_id).await?;
+                ensure!(
+                    owner.is_some() && owner == context.authenticated_signer,
+                    SystemExecutionError::UnauthorizedApplicationUpgrade(Box::new(application_id))
+                );
+                self.check_and_record_bytecode_blobs(&bytecode_id, txn_tracker)
+                    .await?;
+                self.registry
+                    .upgrade_application(application_id, bytecode_id)
+                    .await?;
+            }
+            SetApplicationCallPolicy {
+                application_id,
+                policy,
+            } => {
+                ensure!(
+                    application_id.creation.chain_id == context.chain_id,
+                    SystemExecutionError::CallPolicyOnNonCreatorChain(Box::new(application_id))
+                );
+                let owner = self.registry.owners.get(&application_id).await?;
+                ensure!(
+                    owner.is_some() && owner == context.authenticated_signer,
+                    SystemExecutionError::UnauthorizedCallPolicyChange(Box::new(application_id))
+                );
+                self.registry
+                    .set_call_policy(application_id, policy)
+                    .await?;
+            }
             ReadBlob { blob_id } => {
                 self.read_blob_content(blob_id).await?;
                 self.blob_used(Some(txn_tracker), blob_id).await?;
             }
+            ExecuteCallbacks => {
+                // The callbacks are user actions, executed by the `ExecutionStateView`.
+            }
         }
 
         txn_tracker.add_system_outcome(outcome)?;
@@ -724,6 +1053,7 @@ where
                     authenticated: false,
                     grant: Amount::ZERO,
                     kind: MessageKind::Tracked,
+                    expires_at: None,
                     message: SystemMessage::Credit {
                         amount,
                         source,
@@ -766,6 +1096,7 @@ where
             authenticated: true,
             grant: Amount::ZERO,
             kind: MessageKind::Simple,
+            expires_at: None,
             message: SystemMessage::Withdraw {
                 amount,
                 owner: source,
@@ -843,6 +1174,7 @@ where
                             authenticated: false,
                             grant: Amount::ZERO,
                             kind: MessageKind::Tracked,
+                            expires_at: None,
                             message: SystemMessage::Credit {
                                 amount,
                                 source: Some(owner),
@@ -868,26 +1200,65 @@ where
             RemoveCommittee { epoch } => {
                 self.committees.get_mut().remove(&epoch);
             }
-            RegisterApplications { applications } => {
+            RegisterApplications {
+                applications,
+                call_policies,
+            } => {
                 for application in applications {
                     self.check_and_record_bytecode_blobs(&application.bytecode_id, txn_tracker)
                         .await?;
                     self.registry.register_application(application).await?;
                 }
+                for update in call_policies {
+                    self.registry.apply_call_policy(update).await?;
+                }
             }
             RequestApplication(application_id) => {
                 let applications = self
                     .registry
                     .describe_applications_with_dependencies(vec![application_id])
                     .await?;
+                let upgrades = self
+                    .registry
+                    .describe_upgrades(applications.iter().map(UserApplicationId::from))
+                    .await?;
+                let call_policies = self
+                    .registry
+                    .describe_call_policies(applications.iter().map(UserApplicationId::from))
+                    .await?;
+                let destination = Destination::Recipient(context.message_id.chain_id);
                 let message = RawOutgoingMessage {
-                    destination: Destination::Recipient(context.message_id.chain_id),
+                    destination: destination.clone(),
                     authenticated: false,
                     grant: Amount::ZERO,
                     kind: MessageKind::Simple,
-                    message: SystemMessage::RegisterApplications { applications },
+                    expires_at: None,
+                    message: SystemMessage::RegisterApplications {
+                        applications,
+                        call_policies,
+                    },
                 };
                 outcome.messages.push(message);
+                if !upgrades.is_empty() {
+                    let message = RawOutgoingMessage {
+                        destination,
+                        authenticated: false,
+                        grant: Amount::ZERO,
+                        kind: MessageKind::Simple,
+                        expires_at: None,
+                        message: SystemMessage::UpgradeApplications(upgrades),
+                    };
+                    outcome.messages.push(message);
+                }
+            }
+            UpgradeApplications(upgrades) => {
+                for upgrade in upgrades {
+                    let bytecode_id = upgrade.bytecode_id;
+                    if self.registry.apply_upgrade(upgrade).await? {
+                        self.check_and_record_bytecode_blobs(&bytecode_id, txn_tracker)
+                            .await?;
+                    }
+                }
             }
             // These messages are executed immediately when cross-chain requests are received.
             Subscribe { .. } | Unsubscribe { .. } | OpenChain(_) => {}
@@ -978,6 +1349,7 @@ where
             authenticated: false,
             grant: Amount::ZERO,
             kind: MessageKind::Protected,
+            expires_at: None,
             message: SystemMessage::OpenChain(config),
         };
         let subscription = ChannelSubscription {
@@ -989,6 +1361,7 @@ where
             authenticated: false,
             grant: Amount::ZERO,
             kind: MessageKind::Protected,
+            expires_at: None,
             message: SystemMessage::Subscribe {
                 id: child_id,
                 subscription,
@@ -1010,6 +1383,7 @@ where
                     authenticated: false,
                     grant: Amount::ZERO,
                     kind: MessageKind::Protected,
+                    expires_at: None,
                     message: SystemMessage::Unsubscribe { id, subscription },
                 };
                 messages.push(message);
@@ -1027,6 +1401,7 @@ where
         bytecode_id: BytecodeId,
         parameters: Vec<u8>,
         required_application_ids: Vec<UserApplicationId>,
+        owner: Option<Owner>,
     ) -> Result<CreateApplicationResult, SystemExecutionError> {
         let id = UserApplicationId {
             bytecode_id,
@@ -1034,19 +1409,16 @@ where
         };
         let mut blobs_to_register = vec![];
         for application in required_application_ids.iter().chain(iter::once(&id)) {
-            let (contract_bytecode_blob_id, service_bytecode_blob_id) =
-                self.check_bytecode_blobs(&application.bytecode_id).await?;
             // We only remember to register the blobs that aren't recorded in `used_blobs`
             // already.
-            if !self.used_blobs.contains(&contract_bytecode_blob_id).await? {
-                blobs_to_register.push(contract_bytecode_blob_id);
-            }
-            if !self.used_blobs.contains(&service_bytecode_blob_id).await? {
-                blobs_to_register.push(service_bytecode_blob_id);
+            for blob_id in self.check_bytecode_blobs(&application.bytecode_id).await? {
+                if !self.used_blobs.contains(&blob_id).await? {
+                    blobs_to_register.push(blob_id);
+                }
             }
         }
         self.registry
-            .register_new_application(id, parameters, required_application_ids)
+            .register_new_application(id, parameters, required_application_ids, owner)
             .await?;
         // Send a message to ourself to increment the message ID.
         let message = RawOutgoingMessage {
@@ -1054,6 +1426,7 @@ where
             authenticated: false,
             grant: Amount::ZERO,
             kind: MessageKind::Protected,
+            expires_at: None,
             message: SystemMessage::ApplicationCreated,
         };
 
@@ -1112,10 +1485,12 @@ where
         }
     }
 
+    /// Checks that the bytecode blobs exist, together with their chunks if they are too large
+    /// for a single blob, and returns all their IDs.
     async fn check_bytecode_blobs(
         &mut self,
         bytecode_id: &BytecodeId,
-    ) -> Result<(BlobId, BlobId), SystemExecutionError> {
+    ) -> Result<Vec<BlobId>, SystemExecutionError> {
         let contract_bytecode_blob_id =
             BlobId::new(bytecode_id.contract_blob_hash, BlobType::ContractBytecode);
 
@@ -1145,7 +1520,56 @@ where
             SystemExecutionError::BlobsNotFound(missing_blobs)
         );
 
-        Ok((contract_bytecode_blob_id, service_bytecode_blob_id))
+        let mut blob_ids = vec![contract_bytecode_blob_id, service_bytecode_blob_id];
+        for blob_id in [contract_bytecode_blob_id, service_bytecode_blob_id] {
+            blob_ids.extend(self.check_bytecode_chunks(blob_id).await?);
+        }
+        Ok(blob_ids)
+    }
+
+    /// If the bytecode blob lists the chunks of a bytecode that was too large for a single
+    /// blob, checks that they reassemble into that bytecode, within the size limit, and
+    /// returns their blob IDs.
+    async fn check_bytecode_chunks(
+        &mut self,
+        blob_id: BlobId,
+    ) -> Result<Vec<BlobId>, SystemExecutionError> {
+        let content = self.read_blob_content(blob_id).await?;
+        let Some(bytecode_chunks) = BytecodeChunks::from_blob_bytes(content.bytes()) else {
+            return Ok(Vec::new());
+        };
+
+        let chunk_blob_ids = bytecode_chunks.chunk_blob_ids().collect::<Vec<_>>();
+        let mut chunks = Vec::new();
+        let mut missing_blobs = Vec::new();
+        for chunk_blob_id in &chunk_blob_ids {
+            match self.read_blob_content(*chunk_blob_id).await {
+                Ok(chunk) => chunks.push(chunk),
+                Err(SystemExecutionError::BlobsNotFound(blob_ids)) => {
+                    missing_blobs.extend(blob_ids)
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        ensure!(
+            missing_blobs.is_empty(),
+            SystemExecutionError::BlobsNotFound(missing_blobs)
+        );
+
+        let compressed_bytecode = bytecode_chunks
+            .reassemble(blob_id.blob_type, chunks)
+            .map_err(|_| SystemExecutionError::InvalidBytecodeChunks(blob_id))?;
+        if let Some((_, committee)) = self.current_committee() {
+            ensure!(
+                CompressedBytecode::decompressed_size_at_most(
+                    &compressed_bytecode.compressed_bytes,
+                    committee.policy().maximum_bytecode_size
+                )
+                .unwrap_or(false),
+                SystemExecutionError::ChunkedBytecodeTooLarge(blob_id)
+            );
+        }
+        Ok(chunk_blob_ids)
     }
 
     async fn record_bytecode_blobs(
@@ -1164,12 +1588,7 @@ where
         bytecode_id: &BytecodeId,
         txn_tracker: &mut TransactionTracker,
     ) -> Result<(), SystemExecutionError> {
-        let (contract_bytecode_blob_id, service_bytecode_blob_id) =
-            self.check_bytecode_blobs(bytecode_id).await?;
-        self.record_bytecode_blobs(
-            vec![contract_bytecode_blob_id, service_bytecode_blob_id],
-            txn_tracker,
-        )
-        .await
+        let blob_ids = self.check_bytecode_blobs(bytecode_id).await?;
+        self.record_bytecode_blobs(blob_ids, txn_tracker).await
     }
 }
@@ -33,6 +33,7 @@ use crate::{
 pub struct MockApplication {
     expected_calls: Arc<Mutex<VecDeque<ExpectedCall>>>,
     active_instances: Arc<AtomicUsize>,
+    abi_version: Arc<Mutex<Option<u32>>>,
 }
 
 /// A mocked implementation of a user application instance.
@@ -53,6 +54,12 @@ impl MockApplication {
             .push_back(expected_call);
     }
 
+    /// Makes the [`MockApplication`] declare the given `version` of its contract and service
+    /// ABIs.
+    pub fn set_abi_version(&self, version: u32) {
+        *self.abi_version.lock().expect("Mutex is poisoned") = Some(version);
+    }
+
     /// Creates a new [`MockApplicationInstance`], forwarding the configured expected calls.
     pub fn create_mock_instance<Runtime>(
         &self,
@@ -100,6 +107,10 @@ impl Debug for MockApplication {
                 "active_instances",
                 &self.active_instances.load(Ordering::Acquire),
             )
+            .field(
+                "abi_version",
+                &self.abi_version.lock().ok().and_then(|version| *version),
+            )
             .finish()
     }
 }
@@ -108,6 +119,7 @@ impl PartialEq for MockApplication {
     fn eq(&self, other: &Self) -> bool {
         Arc::ptr_eq(&self.expected_calls, &other.expected_calls)
             && Arc::ptr_eq(&self.active_instances, &other.active_instances)
+            && Arc::ptr_eq(&self.abi_version, &other.abi_version)
     }
 }
 
@@ -148,11 +160,23 @@ type ExecuteMessageHandler = Box<
         + Send
         + Sync,
 >;
+type ExecuteCallbackHandler = Box<
+    dyn FnOnce(
+            &mut ContractSyncRuntimeHandle,
+            OperationContext,
+            Vec<u8>,
+        ) -> Result<(), ExecutionError>
+        + Send
+        + Sync,
+>;
 type FinalizeHandler = Box<
     dyn FnOnce(&mut ContractSyncRuntimeHandle, FinalizeContext) -> Result<(), ExecutionError>
         + Send
         + Sync,
 >;
+type MigrateHandler = Box<
+    dyn FnOnce(&mut ContractSyncRuntimeHandle, u32) -> Result<(), ExecutionError> + Send + Sync,
+>;
 type HandleQueryHandler = Box<
     dyn FnOnce(
             &mut ServiceSyncRuntimeHandle,
@@ -172,8 +196,12 @@ pub enum ExpectedCall {
     ExecuteOperation(#[debug(skip)] ExecuteOperationHandler),
     /// An expected call to [`UserContract::execute_message`].
     ExecuteMessage(#[debug(skip)] ExecuteMessageHandler),
+    /// An expected call to [`UserContract::execute_callback`].
+    ExecuteCallback(#[debug(skip)] ExecuteCallbackHandler),
     /// An expected call to [`UserContract::finalize`].
     Finalize(#[debug(skip)] FinalizeHandler),
+    /// An expected call to [`UserContract::migrate`].
+    Migrate(#[debug(skip)] MigrateHandler),
     /// An expected call to [`UserService::handle_query`].
     HandleQuery(#[debug(skip)] HandleQueryHandler),
 }
@@ -184,7 +212,9 @@ impl Display for ExpectedCall {
             ExpectedCall::Instantiate(_) => "instantiate",
             ExpectedCall::ExecuteOperation(_) => "execute_operation",
             ExpectedCall::ExecuteMessage(_) => "execute_message",
+            ExpectedCall::ExecuteCallback(_) => "execute_callback",
             ExpectedCall::Finalize(_) => "finalize",
+            ExpectedCall::Migrate(_) => "migrate",
             ExpectedCall::HandleQuery(_) => "handle_query",
         };
 
@@ -240,6 +270,22 @@ impl ExpectedCall {
         ExpectedCall::ExecuteMessage(Box::new(handler))
     }
 
+    /// Creates an [`ExpectedCall`] to the [`MockApplicationInstance`]'s
+    /// [`UserContract::execute_callback`] implementation, which is handled by the provided
+    /// `handler`.
+    pub fn execute_callback(
+        handler: impl FnOnce(
+                &mut ContractSyncRuntimeHandle,
+                OperationContext,
+                Vec<u8>,
+            ) -> Result<(), ExecutionError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        ExpectedCall::ExecuteCallback(Box::new(handler))
+    }
+
     /// Creates an [`ExpectedCall`] to the [`MockApplicationInstance`]'s [`UserContract::finalize`]
     /// implementation, which is handled by the provided `handler`.
     pub fn finalize(
@@ -257,6 +303,17 @@ impl ExpectedCall {
         Self::finalize(|_, _| Ok(()))
     }
 
+    /// Creates an [`ExpectedCall`] to the [`MockApplicationInstance`]'s [`UserContract::migrate`]
+    /// implementation, which is handled by the provided `handler`.
+    pub fn migrate(
+        handler: impl FnOnce(&mut ContractSyncRuntimeHandle, u32) -> Result<(), ExecutionError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        ExpectedCall::Migrate(Box::new(handler))
+    }
+
     /// Creates an [`ExpectedCall`] to the [`MockApplicationInstance`]'s
     /// [`UserService::handle_query`] implementation, which is handled by the provided `handler`.
     pub fn handle_query(
@@ -280,6 +337,10 @@ impl UserContractModule for MockApplication {
     ) -> Result<Box<dyn UserContract + 'static>, ExecutionError> {
         Ok(Box::new(self.create_mock_instance(runtime)))
     }
+
+    fn abi_version(&self) -> Option<u32> {
+        *self.abi_version.lock().expect("Mutex is poisoned")
+    }
 }
 
 impl UserServiceModule for MockApplication {
@@ -289,6 +350,10 @@ impl UserServiceModule for MockApplication {
     ) -> Result<Box<dyn UserService + 'static>, ExecutionError> {
         Ok(Box::new(self.create_mock_instance(runtime)))
     }
+
+    fn abi_version(&self) -> Option<u32> {
+        *self.abi_version.lock().expect("Mutex is poisoned")
+    }
 }
 
 impl<Runtime> MockApplicationInstance<Runtime> {
@@ -347,6 +412,22 @@ impl UserContract for MockApplicationInstance<ContractSyncRuntimeHandle> {
         }
     }
 
+    fn execute_callback(
+        &mut self,
+        context: OperationContext,
+        argument: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        match self.next_expected_call() {
+            Some(ExpectedCall::ExecuteCallback(handler)) => {
+                handler(&mut self.runtime, context, argument)
+            }
+            Some(unexpected_call) => panic!(
+                "Expected a call to `execute_callback`, got a call to `{unexpected_call}` instead."
+            ),
+            None => panic!("Unexpected call to `execute_callback`"),
+        }
+    }
+
     fn finalize(&mut self, context: FinalizeContext) -> Result<(), ExecutionError> {
         match self.next_expected_call() {
             Some(ExpectedCall::Finalize(handler)) => handler(&mut self.runtime, context),
@@ -356,6 +437,16 @@ impl UserContract for MockApplicationInstance<ContractSyncRuntimeHandle> {
             None => panic!("Unexpected call to `finalize`"),
         }
     }
+
+    fn migrate(&mut self, old_version: u32) -> Result<(), ExecutionError> {
+        match self.next_expected_call() {
+            Some(ExpectedCall::Migrate(handler)) => handler(&mut self.runtime, old_version),
+            Some(unexpected_call) => {
+                panic!("Expected a call to `migrate`, got a call to `{unexpected_call}` instead.")
+            }
+            None => panic!("Unexpected call to `migrate`"),
+        }
+    }
 }
 
 impl UserService for MockApplicationInstance<ServiceSyncRuntimeHandle> {
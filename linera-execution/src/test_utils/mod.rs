@@ -6,6 +6,7 @@
 #![allow(unused_imports)]
 
 mod mock_application;
+mod native_equivalence;
 mod system_execution_state;
 
 use std::{collections::BTreeMap, sync::Arc, thread, vec};
@@ -26,6 +27,7 @@ use serde::{Deserialize, Serialize};
 
 pub use self::{
     mock_application::{ExpectedCall, MockApplication, MockApplicationInstance},
+    native_equivalence::{check_native_equivalence, ApplicationCode},
     system_execution_state::SystemExecutionState,
 };
 use crate::{
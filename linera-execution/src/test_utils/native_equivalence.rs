@@ -0,0 +1,193 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers to check that a native application behaves exactly like its bytecode.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::ensure;
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{Amount, BlockHeight, Timestamp},
+    identifiers::{ChainDescription, ChainId},
+};
+use linera_views::{context::Context as _, views::CryptoHashView as _};
+
+use super::{create_dummy_user_application_description, SystemExecutionState};
+use crate::{
+    committee::{Committee, Epoch},
+    ExecutionOutcome, ExecutionRuntimeContext as _, Operation, OperationContext, Query,
+    QueryContext, QueryOutcome, ResourceControlPolicy, ResourceController, TransactionTracker,
+    UserContractCode, UserServiceCode,
+};
+
+/// An implementation of an application, with its contract and its service.
+pub struct ApplicationCode {
+    pub contract: UserContractCode,
+    pub service: UserServiceCode,
+}
+
+/// Everything observable by the rest of the chain after running an application.
+#[derive(Debug, PartialEq)]
+struct Trace {
+    operation_outcomes: Vec<Result<Vec<ExecutionOutcome>, String>>,
+    query_outcomes: Vec<Result<QueryOutcome, String>>,
+    fuel: u64,
+    state_hash: CryptoHash,
+}
+
+/// Checks that the `native` implementation of an application is equivalent to its `reference`
+/// implementation, usually compiled from the bytecode it replaces.
+///
+/// Both implementations execute the same serialized `operations` and then answer the same
+/// serialized `queries`, each on a fresh chain whose committee uses the `policy`. They must
+/// produce the same outcomes, consume the same amount of fuel and leave the chain in the same
+/// state.
+///
+/// The fuel consumed by the bytecode depends on the [`FuelSchedule`](crate::FuelSchedule) of the
+/// `policy`, while a native implementation consumes fuel on its own terms. So the check only
+/// holds for that schedule, and must be repeated with the new policy before the committee's
+/// schedule changes.
+pub async fn check_native_equivalence(
+    reference: ApplicationCode,
+    native: ApplicationCode,
+    policy: &ResourceControlPolicy,
+    operations: &[Vec<u8>],
+    queries: &[Vec<u8>],
+) -> anyhow::Result<()> {
+    let expected = trace(reference, policy, operations, queries).await?;
+    let actual = trace(native, policy, operations, queries).await?;
+
+    for (index, (expected, actual)) in expected
+        .operation_outcomes
+        .iter()
+        .zip(&actual.operation_outcomes)
+        .enumerate()
+    {
+        ensure!(
+            expected == actual,
+            "Operation #{index} diverged: expected {expected:?}, got {actual:?}"
+        );
+    }
+    for (index, (expected, actual)) in expected
+        .query_outcomes
+        .iter()
+        .zip(&actual.query_outcomes)
+        .enumerate()
+    {
+        ensure!(
+            expected == actual,
+            "Query #{index} diverged: expected {expected:?}, got {actual:?}"
+        );
+    }
+    ensure!(
+        expected.fuel == actual.fuel,
+        "Fuel consumption diverged: expected {}, got {}",
+        expected.fuel,
+        actual.fuel
+    );
+    ensure!(
+        expected.state_hash == actual.state_hash,
+        "Chain state diverged: expected hash {}, got {}",
+        expected.state_hash,
+        actual.state_hash
+    );
+    Ok(())
+}
+
+/// Runs the `operations` and then the `queries` with the `application` on a fresh chain, whose
+/// committee uses the `policy`.
+async fn trace(
+    application: ApplicationCode,
+    policy: &ResourceControlPolicy,
+    operations: &[Vec<u8>],
+    queries: &[Vec<u8>],
+) -> anyhow::Result<Trace> {
+    let chain_id = ChainId::root(0);
+    let state = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        balance: Amount::from_tokens(1_000),
+        epoch: Some(Epoch::ZERO),
+        committees: BTreeMap::from([(
+            Epoch::ZERO,
+            Committee::new(BTreeMap::new(), policy.clone()),
+        )]),
+        ..Default::default()
+    };
+    let mut view = state.into_view().await;
+    let (description, contract_blob, service_blob) = create_dummy_user_application_description(0);
+    let application_id = view
+        .system
+        .registry
+        .register_application(description)
+        .await?;
+    let extra = view.context().extra();
+    extra
+        .user_contracts()
+        .insert(application_id, application.contract);
+    extra
+        .user_services()
+        .insert(application_id, application.service);
+    extra.add_blobs([contract_blob, service_blob]).await?;
+
+    let mut controller = ResourceController {
+        policy: Arc::new(policy.clone()),
+        tracker: Default::default(),
+        account: None,
+    };
+
+    let mut operation_outcomes = Vec::with_capacity(operations.len());
+    for (index, bytes) in operations.iter().enumerate() {
+        let context = OperationContext {
+            chain_id,
+            height: BlockHeight(0),
+            round: Some(0),
+            index: Some(index as u32),
+            authenticated_signer: None,
+            authenticated_caller_id: None,
+        };
+        let operation = Operation::User {
+            application_id,
+            bytes: bytes.clone(),
+        };
+        let mut txn_tracker = TransactionTracker::new(0, Some(Vec::new()));
+        let outcome = view
+            .execute_operation(
+                context,
+                Timestamp::from(0),
+                operation,
+                &mut txn_tracker,
+                &mut controller,
+            )
+            .await
+            .and_then(|()| txn_tracker.destructure())
+            .map(|(outcomes, _, _)| outcomes)
+            .map_err(|error| error.to_string());
+        operation_outcomes.push(outcome);
+    }
+
+    let mut query_outcomes = Vec::with_capacity(queries.len());
+    for bytes in queries {
+        let context = QueryContext {
+            chain_id,
+            next_block_height: BlockHeight(0),
+            local_time: Timestamp::from(0),
+        };
+        let query = Query::User {
+            application_id,
+            bytes: bytes.clone(),
+        };
+        let outcome = view
+            .query_application(context, query, None)
+            .await
+            .map_err(|error| error.to_string());
+        query_outcomes.push(outcome);
+    }
+
+    Ok(Trace {
+        operation_outcomes,
+        query_outcomes,
+        fuel: controller.tracker.fuel,
+        state_hash: view.crypto_hash().await?,
+    })
+}
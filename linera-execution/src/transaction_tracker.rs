@@ -130,6 +130,36 @@ impl TransactionTracker {
         Ok((outcomes, oracle_responses, next_message_index))
     }
 
+    /// Returns a tracker for an action that may fail without failing the transaction. It
+    /// replays the same oracle responses, and its outcomes are only added to this tracker by
+    /// [`TransactionTracker::merge`].
+    pub(crate) fn fork(&self) -> Self {
+        TransactionTracker {
+            replaying_oracle_responses: self.replaying_oracle_responses.clone(),
+            oracle_responses: Vec::new(),
+            outcomes: Vec::new(),
+            next_message_index: self.next_message_index,
+        }
+    }
+
+    /// Adds the oracle responses and outcomes of a tracker returned by
+    /// [`TransactionTracker::fork`] to this one.
+    pub(crate) fn merge(&mut self, fork: TransactionTracker) {
+        self.replaying_oracle_responses = fork.replaying_oracle_responses;
+        self.oracle_responses.extend(fork.oracle_responses);
+        self.outcomes.extend(fork.outcomes);
+        self.next_message_index = fork.next_message_index;
+    }
+
+    /// Adds only the oracle responses of a tracker returned by [`TransactionTracker::fork`]
+    /// to this one, discarding its outcomes, for an action that failed. The block still
+    /// records them, so that the failed action consumes the same responses when the block is
+    /// replayed.
+    pub(crate) fn merge_oracle_responses(&mut self, fork: TransactionTracker) {
+        self.replaying_oracle_responses = fork.replaying_oracle_responses;
+        self.oracle_responses.extend(fork.oracle_responses);
+    }
+
     pub(crate) fn outcomes_mut(&mut self) -> &mut Vec<ExecutionOutcome> {
         &mut self.outcomes
     }
@@ -8,7 +8,8 @@ use linera_base::{
 };
 
 use super::{
-    ApplicationRegistry, ApplicationRegistryView, UserApplicationDescription, UserApplicationId,
+    ApplicationRegistry, ApplicationRegistryView, ApplicationUpgrade, UserApplicationDescription,
+    UserApplicationId,
 };
 
 fn message_id(index: u32) -> MessageId {
@@ -87,3 +88,110 @@ async fn test_topological_sort_with_loop() {
         Vec::from_iter([2, 3, 1, 0].into_iter().map(app_id))
     );
 }
+
+#[tokio::test]
+async fn test_upgrade_application() {
+    let mut view = ApplicationRegistryView::new().await;
+    view.import(registry([(0, vec![])])).unwrap();
+    let new_bytecode_id = BytecodeId::new(
+        CryptoHash::test_hash("new contract"),
+        CryptoHash::test_hash("new service"),
+    );
+
+    assert!(view
+        .upgrade_application(app_id(1), new_bytecode_id)
+        .await
+        .is_err());
+    assert_eq!(view.take_pending_migration(app_id(0)).await.unwrap(), None);
+    assert_eq!(view.application_version(app_id(0)).await.unwrap(), 0);
+
+    view.upgrade_application(app_id(0), new_bytecode_id)
+        .await
+        .unwrap();
+    let description = view.describe_application_code(app_id(0)).await.unwrap();
+    assert_eq!(description.bytecode_id, new_bytecode_id);
+    assert_eq!(
+        view.describe_application(app_id(0)).await.unwrap(),
+        app_description(0, vec![])
+    );
+    assert_eq!(view.application_version(app_id(0)).await.unwrap(), 1);
+    assert_eq!(
+        view.take_pending_migration(app_id(0)).await.unwrap(),
+        Some(0)
+    );
+    assert_eq!(view.take_pending_migration(app_id(0)).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_upgrade_application_twice_before_migration() {
+    let mut view = ApplicationRegistryView::new().await;
+    view.import(registry([(0, vec![])])).unwrap();
+    let bytecode_id = |name: &str| {
+        BytecodeId::new(
+            CryptoHash::test_hash(format!("{name} contract")),
+            CryptoHash::test_hash(format!("{name} service")),
+        )
+    };
+
+    view.upgrade_application(app_id(0), bytecode_id("first"))
+        .await
+        .unwrap();
+    assert_eq!(
+        view.take_pending_migration(app_id(0)).await.unwrap(),
+        Some(0)
+    );
+
+    view.upgrade_application(app_id(0), bytecode_id("second"))
+        .await
+        .unwrap();
+    view.upgrade_application(app_id(0), bytecode_id("third"))
+        .await
+        .unwrap();
+    let description = view.describe_application_code(app_id(0)).await.unwrap();
+    assert_eq!(description.bytecode_id, bytecode_id("third"));
+    assert_eq!(view.application_version(app_id(0)).await.unwrap(), 3);
+    // The last contract migrates the state written by the first upgrade.
+    assert_eq!(
+        view.take_pending_migration(app_id(0)).await.unwrap(),
+        Some(1)
+    );
+}
+
+#[tokio::test]
+async fn test_apply_upgrade_from_other_chain() {
+    let mut view = ApplicationRegistryView::new().await;
+    view.import(registry([(0, vec![]), (1, vec![])])).unwrap();
+    let bytecode_id = |name: &str| {
+        BytecodeId::new(
+            CryptoHash::test_hash(format!("{name} contract")),
+            CryptoHash::test_hash(format!("{name} service")),
+        )
+    };
+    let upgrade = |version, name| ApplicationUpgrade {
+        application_id: app_id(0),
+        version,
+        bytecode_id: bytecode_id(name),
+    };
+
+    assert!(view.apply_upgrade(upgrade(2, "second")).await.unwrap());
+    assert_eq!(view.application_version(app_id(0)).await.unwrap(), 2);
+    assert_eq!(
+        view.take_pending_migration(app_id(0)).await.unwrap(),
+        Some(0)
+    );
+
+    // Older and already known versions are ignored.
+    assert!(!view.apply_upgrade(upgrade(1, "first")).await.unwrap());
+    assert!(!view.apply_upgrade(upgrade(2, "second")).await.unwrap());
+    let description = view.describe_application_code(app_id(0)).await.unwrap();
+    assert_eq!(description.bytecode_id, bytecode_id("second"));
+    assert_eq!(view.take_pending_migration(app_id(0)).await.unwrap(), None);
+
+    // Only upgraded applications are shared.
+    assert_eq!(
+        view.describe_upgrades([app_id(0), app_id(1)])
+            .await
+            .unwrap(),
+        vec![upgrade(2, "second")]
+    );
+}
@@ -0,0 +1,29 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::crypto::KeyPair;
+
+use super::ValidatorKeyRotation;
+
+#[test]
+fn key_rotation_is_signed_by_both_keys() {
+    let old_key = KeyPair::generate();
+    let new_key = KeyPair::generate();
+    let rotation = ValidatorKeyRotation::new(&old_key, &new_key);
+    rotation.check().unwrap();
+
+    // A validator can't claim a key that it doesn't hold.
+    let other_key = KeyPair::generate();
+    let claimed = ValidatorKeyRotation {
+        new_signature: ValidatorKeyRotation::new(&old_key, &other_key).new_signature,
+        ..rotation.clone()
+    };
+    assert!(claimed.check().is_err());
+
+    // Nor rotate the key of another validator.
+    let forged = ValidatorKeyRotation {
+        signature: ValidatorKeyRotation::new(&other_key, &new_key).signature,
+        ..rotation
+    };
+    assert!(forged.check().is_err());
+}
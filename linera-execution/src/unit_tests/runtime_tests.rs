@@ -121,6 +121,7 @@ async fn test_write_batch() {
         let ExecutionRequest::WriteBatch {
             id,
             batch,
+            maximum_bytes_stored: _,
             callback,
         } = request
         else {
@@ -131,7 +132,7 @@ async fn test_write_batch() {
         assert_eq!(batch, expected_batch);
 
         callback
-            .send(())
+            .send(Ok((0, 0)))
             .expect("Failed to notify that writing the batch finished");
     });
 
@@ -197,6 +198,7 @@ fn create_dummy_application() -> ApplicationStatus {
         caller_id: None,
         id: create_dummy_application_id(),
         parameters: vec![],
+        call_policy: Arc::default(),
         signer: None,
         outcome: RawExecutionOutcome::default(),
     }
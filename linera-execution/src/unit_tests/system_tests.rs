@@ -2,13 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use linera_base::{
-    data_types::{Blob, BlockHeight, Bytecode},
-    identifiers::ApplicationId,
+    data_types::{Blob, BlockHeight, Bytecode, TimeDelta},
+    identifiers::{ApplicationId, GenericApplicationId},
 };
 use linera_views::context::MemoryContext;
 
 use super::*;
-use crate::{ExecutionOutcome, ExecutionStateView, TestExecutionRuntimeContext};
+use crate::{
+    test_utils::{create_dummy_message_context, RegisterMockApplication},
+    ExecutionOutcome, ExecutionStateView, TestExecutionRuntimeContext,
+};
 
 /// Returns an execution state view and a matching operation context, for epoch 1, with root
 /// chain 0 as the admin ID and one empty committee.
@@ -50,6 +53,7 @@ async fn application_message_index() -> anyhow::Result<()> {
         parameters: vec![],
         instantiation_argument: vec![],
         required_application_ids: vec![],
+        call_policy: ApplicationCallPolicy::default(),
     };
     let mut txn_tracker = TransactionTracker::default();
     view.context()
@@ -81,6 +85,291 @@ async fn application_message_index() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Publishes a bytecode with the given contract and service to the test storage of the `view`.
+async fn publish_bytecode(
+    view: &ExecutionStateView<MemoryContext<TestExecutionRuntimeContext>>,
+    name: &str,
+) -> anyhow::Result<BytecodeId> {
+    let contract = Bytecode::new(format!("{name} contract").into_bytes());
+    let service = Bytecode::new(format!("{name} service").into_bytes());
+    let contract_blob = Blob::new_contract_bytecode(contract.compress());
+    let service_blob = Blob::new_service_bytecode(service.compress());
+    let bytecode_id = BytecodeId::new(contract_blob.id().hash, service_blob.id().hash);
+    view.context()
+        .extra()
+        .add_blobs([contract_blob, service_blob])
+        .await?;
+    Ok(bytecode_id)
+}
+
+/// Tests that only the owner who created an application can upgrade it, and that the
+/// upgrade is shared with the chains that request the application.
+#[tokio::test]
+async fn application_upgrades_are_authorized_and_shared() -> anyhow::Result<()> {
+    let (mut view, context) = new_view_and_context().await;
+    let owner = Owner(CryptoHash::test_hash("owner"));
+    let other = Owner(CryptoHash::test_hash("other"));
+    let bytecode_id = publish_bytecode(&view, "old").await?;
+    let new_bytecode_id = publish_bytecode(&view, "new").await?;
+
+    let operation = SystemOperation::CreateApplication {
+        bytecode_id,
+        parameters: vec![],
+        instantiation_argument: vec![],
+        required_application_ids: vec![],
+        call_policy: ApplicationCallPolicy::default(),
+    };
+    let context = OperationContext {
+        authenticated_signer: Some(owner),
+        ..context
+    };
+    let (application_id, _) = view
+        .system
+        .execute_operation(context, operation, &mut TransactionTracker::default())
+        .await?
+        .expect("an application should be created");
+
+    let upgrade = SystemOperation::UpgradeApplication {
+        application_id,
+        bytecode_id: new_bytecode_id,
+    };
+    for signer in [None, Some(other)] {
+        let context = OperationContext {
+            authenticated_signer: signer,
+            ..context
+        };
+        let result = view
+            .system
+            .execute_operation(context, upgrade.clone(), &mut TransactionTracker::default())
+            .await;
+        assert_matches::assert_matches!(
+            result,
+            Err(SystemExecutionError::UnauthorizedApplicationUpgrade(_))
+        );
+    }
+    view.system
+        .execute_operation(context, upgrade, &mut TransactionTracker::default())
+        .await?;
+    assert_eq!(
+        view.system
+            .registry
+            .application_version(application_id)
+            .await?,
+        1
+    );
+
+    // Another chain requesting the application also receives its upgrade.
+    let mut txn_tracker = TransactionTracker::default();
+    let outcome = view
+        .system
+        .execute_message(
+            create_dummy_message_context(None),
+            SystemMessage::RequestApplication(application_id),
+            &mut txn_tracker,
+        )
+        .await?;
+    let messages = outcome
+        .messages
+        .into_iter()
+        .map(|message| message.message)
+        .collect::<Vec<_>>();
+    let expected_upgrade = ApplicationUpgrade {
+        application_id,
+        version: 1,
+        bytecode_id: new_bytecode_id,
+    };
+    assert_eq!(
+        messages[1],
+        SystemMessage::UpgradeApplications(vec![expected_upgrade.clone()])
+    );
+
+    let (mut other_view, _) = new_view_and_context().await;
+    publish_bytecode(&other_view, "old").await?;
+    publish_bytecode(&other_view, "new").await?;
+    for message in messages {
+        other_view
+            .system
+            .execute_message(
+                create_dummy_message_context(None),
+                message,
+                &mut TransactionTracker::default(),
+            )
+            .await?;
+    }
+    let registry = &other_view.system.registry;
+    assert_eq!(registry.application_version(application_id).await?, 1);
+    assert_eq!(
+        registry
+            .describe_application_code(application_id)
+            .await?
+            .bytecode_id,
+        new_bytecode_id
+    );
+
+    // An older version is ignored.
+    let stale_upgrade = ApplicationUpgrade {
+        version: 0,
+        bytecode_id,
+        ..expected_upgrade
+    };
+    other_view
+        .system
+        .execute_message(
+            create_dummy_message_context(None),
+            SystemMessage::UpgradeApplications(vec![stale_upgrade]),
+            &mut TransactionTracker::default(),
+        )
+        .await?;
+    assert_eq!(
+        other_view
+            .system
+            .registry
+            .describe_application_code(application_id)
+            .await?
+            .bytecode_id,
+        new_bytecode_id
+    );
+
+    Ok(())
+}
+
+/// Tests that only the owner who created an application can set its call policy, and that the
+/// policy is shared with the chains that request the application.
+#[tokio::test]
+async fn call_policies_are_authorized_and_shared() -> anyhow::Result<()> {
+    let (mut view, context) = new_view_and_context().await;
+    let owner = Owner(CryptoHash::test_hash("owner"));
+    let other = Owner(CryptoHash::test_hash("other"));
+    let bytecode_id = publish_bytecode(&view, "app").await?;
+
+    let operation = SystemOperation::CreateApplication {
+        bytecode_id,
+        parameters: vec![],
+        instantiation_argument: vec![],
+        required_application_ids: vec![],
+        call_policy: ApplicationCallPolicy::default(),
+    };
+    let context = OperationContext {
+        authenticated_signer: Some(owner),
+        ..context
+    };
+    let (application_id, _) = view
+        .system
+        .execute_operation(context, operation, &mut TransactionTracker::default())
+        .await?
+        .expect("an application should be created");
+
+    let policy = ApplicationCallPolicy {
+        callers: Some(vec![]),
+        ..ApplicationCallPolicy::default()
+    };
+    let set_policy = SystemOperation::SetApplicationCallPolicy {
+        application_id,
+        policy: policy.clone(),
+    };
+    for signer in [None, Some(other)] {
+        let context = OperationContext {
+            authenticated_signer: signer,
+            ..context
+        };
+        let result = view
+            .system
+            .execute_operation(
+                context,
+                set_policy.clone(),
+                &mut TransactionTracker::default(),
+            )
+            .await;
+        assert_matches::assert_matches!(
+            result,
+            Err(SystemExecutionError::UnauthorizedCallPolicyChange(_))
+        );
+    }
+    view.system
+        .execute_operation(context, set_policy, &mut TransactionTracker::default())
+        .await?;
+
+    // Another chain requesting the application also receives its policy.
+    let outcome = view
+        .system
+        .execute_message(
+            create_dummy_message_context(None),
+            SystemMessage::RequestApplication(application_id),
+            &mut TransactionTracker::default(),
+        )
+        .await?;
+    let expected_update = ApplicationCallPolicyUpdate {
+        application_id,
+        version: 1,
+        policy: policy.clone(),
+    };
+    let registration = outcome.messages[0].message.clone();
+    assert_matches::assert_matches!(
+        &registration,
+        SystemMessage::RegisterApplications { call_policies, .. }
+            if *call_policies == vec![expected_update.clone()]
+    );
+
+    let (mut other_view, other_context) = new_view_and_context().await;
+    publish_bytecode(&other_view, "app").await?;
+    other_view
+        .system
+        .execute_message(
+            create_dummy_message_context(None),
+            registration,
+            &mut TransactionTracker::default(),
+        )
+        .await?;
+    let registry = &other_view.system.registry;
+    assert_eq!(registry.call_policy(application_id).await?, policy);
+
+    // An older version is ignored.
+    let stale_update = ApplicationCallPolicyUpdate {
+        version: 0,
+        policy: ApplicationCallPolicy::default(),
+        ..expected_update
+    };
+    assert!(
+        !other_view
+            .system
+            .registry
+            .apply_call_policy(stale_update)
+            .await?
+    );
+    assert_eq!(
+        other_view
+            .system
+            .registry
+            .call_policy(application_id)
+            .await?,
+        policy
+    );
+
+    // The other chain can't change the policy, even with the owner's signature.
+    let context = OperationContext {
+        chain_id: ChainId::root(1),
+        authenticated_signer: Some(owner),
+        ..other_context
+    };
+    let result = other_view
+        .system
+        .execute_operation(
+            context,
+            SystemOperation::SetApplicationCallPolicy {
+                application_id,
+                policy: ApplicationCallPolicy::default(),
+            },
+            &mut TransactionTracker::default(),
+        )
+        .await;
+    assert_matches::assert_matches!(
+        result,
+        Err(SystemExecutionError::CallPolicyOnNonCreatorChain(_))
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn open_chain_message_index() {
     let (mut view, context) = new_view_and_context().await;
@@ -134,3 +423,159 @@ async fn empty_accounts_are_removed() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Tests that the storage rent is charged to the chain balance, and that unpaid rent becomes
+/// overdue one grace period after the balance ran out, even if no block was made since.
+#[tokio::test]
+async fn storage_rent_is_charged_and_becomes_overdue() -> anyhow::Result<()> {
+    const DAY: u64 = 24 * 60 * 60;
+    let policy = ResourceControlPolicy {
+        byte_stored_per_day: Amount::from_micros(1),
+        storage_rent_grace_period: TimeDelta::from_secs(2 * DAY),
+        ..ResourceControlPolicy::default()
+    };
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        balance: Amount::from_millis(3),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+    view.system.bytes_stored.set(1_000);
+
+    // One day of rent for 1000 bytes costs 0.001.
+    let day1 = Timestamp::from(0).saturating_add(TimeDelta::from_secs(DAY));
+    view.system.charge_storage_rent(&policy, day1)?;
+    view.system.timestamp.set(day1);
+    assert_eq!(*view.system.balance.get(), Amount::from_millis(2));
+    assert_eq!(*view.system.storage_rent_debt.get(), Amount::ZERO);
+
+    // After three more days, the balance only covers two of them: it ran out on day 3.
+    let day4 = day1.saturating_add(TimeDelta::from_secs(3 * DAY));
+    view.system.charge_storage_rent(&policy, day4)?;
+    view.system.timestamp.set(day4);
+    assert_eq!(*view.system.balance.get(), Amount::ZERO);
+    assert_eq!(*view.system.storage_rent_debt.get(), Amount::from_millis(1));
+    assert_eq!(
+        *view.system.storage_rent_due_by.get(),
+        Some(day4.saturating_add(TimeDelta::from_secs(DAY)))
+    );
+    assert!(!view.system.is_storage_rent_overdue());
+
+    // Once the grace period has passed, the rent is overdue.
+    let day5 = day4.saturating_add(TimeDelta::from_secs(DAY));
+    view.system.charge_storage_rent(&policy, day5)?;
+    view.system.timestamp.set(day5);
+    assert_eq!(*view.system.storage_rent_debt.get(), Amount::from_millis(2));
+    assert!(view.system.is_storage_rent_overdue());
+
+    // Paying the debt ends it.
+    view.system.balance.set(Amount::from_millis(5));
+    view.system.charge_storage_rent(&policy, day5)?;
+    assert_eq!(*view.system.balance.get(), Amount::from_millis(3));
+    assert_eq!(*view.system.storage_rent_debt.get(), Amount::ZERO);
+    assert!(!view.system.is_storage_rent_overdue());
+
+    Ok(())
+}
+
+/// Tests that the rent is charged at every block for the bytes that the applications hold, and
+/// that the application with the largest state is evicted if the rent is still unpaid one grace
+/// period after it became overdue.
+#[tokio::test]
+async fn unpaid_storage_rent_evicts_application_states() -> anyhow::Result<()> {
+    const DAY: u64 = 24 * 60 * 60;
+    let policy = ResourceControlPolicy {
+        byte_stored_per_day: Amount::from_micros(1),
+        storage_rent_grace_period: TimeDelta::from_secs(DAY),
+        ..ResourceControlPolicy::default()
+    };
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+    let mut sizes = Vec::new();
+    for value_len in [999, 99] {
+        let (application_id, _application) = view.register_mock_application().await?;
+        let mut state = view.users.try_load_entry_mut(&application_id).await?;
+        state.insert(vec![0], vec![0; value_len]).await?;
+        let size = u64::from(state.total_size().sum());
+        drop(state);
+        sizes.push((application_id, size));
+    }
+    let [(large_id, large_size), (small_id, small_size)] = sizes[..] else {
+        unreachable!();
+    };
+
+    // Recording the size of an application's state again doesn't count it twice.
+    for (application_id, size) in [
+        (large_id, large_size),
+        (small_id, small_size),
+        (large_id, large_size),
+    ] {
+        view.system
+            .record_bytes_stored(application_id, size)
+            .await?;
+    }
+    let total_size = large_size + small_size;
+    assert_eq!(*view.system.bytes_stored.get(), total_size);
+
+    // The balance runs out immediately, so the rent is overdue after the first day.
+    let day1 = Timestamp::from(0).saturating_add(TimeDelta::from_secs(DAY));
+    view.charge_storage_rent(&policy, day1).await?;
+    view.system.timestamp.set(day1);
+    assert_eq!(
+        *view.system.storage_rent_debt.get(),
+        policy.storage_rent(total_size, TimeDelta::from_secs(DAY))?
+    );
+    assert!(view.system.is_storage_rent_overdue());
+    assert_eq!(view.users.indices().await?.len(), 2);
+
+    // One grace period after the rent became overdue, the largest state is evicted, and its
+    // share of the debt is written off.
+    let day2 = day1.saturating_add(TimeDelta::from_secs(DAY));
+    view.charge_storage_rent(&policy, day2).await?;
+    view.system.timestamp.set(day2);
+    let debt = policy.storage_rent(total_size, TimeDelta::from_secs(2 * DAY))?;
+    let remaining_debt = u128::from(debt) * u128::from(small_size) / u128::from(total_size);
+    assert_eq!(view.users.indices().await?, vec![small_id]);
+    assert_eq!(*view.system.bytes_stored.get(), small_size);
+    // The evicted application is no longer registered, and its eviction is recorded.
+    assert_eq!(
+        view.system.registry.known_applications.indices().await?,
+        vec![small_id]
+    );
+    let stream_id = StreamId {
+        application_id: GenericApplicationId::System,
+        stream_name: EVICTED_APPLICATIONS_STREAM.into(),
+    };
+    let eviction_time = view
+        .system
+        .latest_events
+        .get(&(stream_id, bcs::to_bytes(&large_id)?))
+        .await?;
+    assert_eq!(eviction_time, Some(bcs::to_bytes(&day2)?));
+    assert_eq!(
+        *view.system.storage_rent_debt.get(),
+        Amount::from_attos(remaining_debt)
+    );
+    assert_eq!(*view.system.storage_rent_due_by.get(), Some(day2));
+
+    // The remaining state is evicted after another grace period.
+    let day3 = day2.saturating_add(TimeDelta::from_secs(DAY));
+    view.charge_storage_rent(&policy, day3).await?;
+    assert!(view.users.indices().await?.is_empty());
+    assert!(view
+        .system
+        .bytes_stored_by_application
+        .indices()
+        .await?
+        .is_empty());
+    assert_eq!(*view.system.bytes_stored.get(), 0);
+    assert_eq!(*view.system.storage_rent_debt.get(), Amount::ZERO);
+    assert_eq!(*view.system.storage_rent_due_by.get(), None);
+
+    Ok(())
+}
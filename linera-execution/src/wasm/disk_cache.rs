@@ -0,0 +1,468 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A persistent cache of compiled WebAssembly modules, stored on disk.
+//!
+//! Compiling a module is expensive, so a directory can be configured where compiled modules are
+//! persisted and reused after a restart. Entries are indexed by a hash of the bytecode they were
+//! compiled from and of the runtime and engine version that compiled them, so that artifacts
+//! from another version are never loaded. They are grouped by the kind of module, because each
+//! runtime and engine configuration produces different artifacts. The bytecode is stored next to
+//! the compiled artifact, so that the in-memory caches can be warmed up when starting.
+//!
+//! Each compiled artifact is stored after its checksum, which is verified when loading it.
+//! Entries that fail the check are removed and compiled again. The checksum is not keyed, so it
+//! only detects files that were truncated or damaged by accident: anyone able to write to the
+//! directory can make the node load arbitrary machine code. The directory must therefore only be
+//! writable by the node.
+//!
+//! The least recently used entries are evicted when the total size of the directory exceeds the
+//! configured limit. Temporary files left behind by an interrupted write are removed at the same
+//! time.
+
+use linera_base::data_types::Bytecode;
+#[cfg(with_fs)]
+use {
+    linera_base::crypto::{BcsHashable, CryptoHash},
+    serde::{Deserialize, Serialize},
+    std::{
+        fs, io,
+        path::{Path, PathBuf},
+        sync::{Mutex, OnceLock},
+        time::{Duration, SystemTime},
+    },
+    tracing::warn,
+};
+
+/// The extension of the files storing compiled modules.
+#[cfg(with_fs)]
+const MODULE_EXTENSION: &str = "module";
+
+/// The extension of the files storing the bytecode a module was compiled from.
+#[cfg(with_fs)]
+const BYTECODE_EXTENSION: &str = "wasm";
+
+/// The prefix of the extension of the files being written, followed by the ID of the process
+/// writing them.
+#[cfg(with_fs)]
+const TEMPORARY_EXTENSION_PREFIX: &str = "tmp-";
+
+/// How long a temporary file can remain before it is considered left behind by an interrupted
+/// write and removed.
+#[cfg(with_fs)]
+const STALE_TEMPORARY_FILE_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// The number of bytes of the checksum stored before each compiled artifact.
+#[cfg(with_fs)]
+const CHECKSUM_LENGTH: usize = std::mem::size_of::<CryptoHash>();
+
+/// The disk cache, if one was enabled.
+#[cfg(with_fs)]
+static DISK_CACHE: OnceLock<DiskCache> = OnceLock::new();
+
+/// A directory storing compiled modules.
+#[cfg(with_fs)]
+struct DiskCache {
+    directory: PathBuf,
+    max_size: u64,
+    /// Serializes writes and evictions done by this process.
+    write_lock: Mutex<()>,
+}
+
+/// The values from which the name of an entry in the cache is derived.
+#[cfg(with_fs)]
+#[derive(Serialize, Deserialize)]
+struct EntryKey {
+    kind: String,
+    engine: String,
+    bytecode: Bytecode,
+}
+
+#[cfg(with_fs)]
+impl<'de> BcsHashable<'de> for EntryKey {}
+
+/// The compiled artifact of an entry, hashed to detect accidentally corrupted files.
+#[cfg(with_fs)]
+#[derive(Serialize, Deserialize)]
+struct Artifact<'a>(&'a [u8]);
+
+#[cfg(with_fs)]
+impl<'de> BcsHashable<'de> for Artifact<'de> {}
+
+/// Persists compiled modules in `directory`, keeping its total size under `max_size` bytes.
+///
+/// Returns an error if the directory can't be created, or if a disk cache was already enabled.
+#[cfg(with_fs)]
+pub fn enable_module_disk_cache(directory: PathBuf, max_size: u64) -> io::Result<()> {
+    DISK_CACHE
+        .set(DiskCache::new(directory, max_size)?)
+        .map_err(|_| io::Error::other("The module disk cache was already enabled"))
+}
+
+/// Returns the module of the given `kind` compiled from `bytecode` by the `engine`, which
+/// identifies the runtime and its version.
+///
+/// If the module was persisted on disk, it is loaded with `deserialize`. Otherwise it is built with
+/// `compile` and persisted using `serialize`. Failing to use the disk cache is not an error, and
+/// falls back to compiling the module.
+#[cfg_attr(not(with_fs), allow(unused_variables))]
+pub fn load_or_compile<Module>(
+    kind: &str,
+    engine: &str,
+    bytecode: &Bytecode,
+    compile: impl FnOnce() -> anyhow::Result<Module>,
+    serialize: impl FnOnce(&Module) -> anyhow::Result<Vec<u8>>,
+    deserialize: impl FnOnce(Vec<u8>) -> anyhow::Result<Module>,
+) -> anyhow::Result<Module> {
+    #[cfg(with_fs)]
+    if let Some(cache) = DISK_CACHE.get() {
+        return cache.load_or_compile(kind, engine, bytecode, compile, serialize, deserialize);
+    }
+
+    compile()
+}
+
+/// Returns the bytecodes and compiled artifacts of all the modules of the given `kind` that are
+/// persisted on disk and were compiled by the `engine`.
+#[cfg(with_fs)]
+pub fn persisted_modules(kind: &str, engine: &str) -> Vec<(Bytecode, Vec<u8>)> {
+    DISK_CACHE
+        .get()
+        .map_or_else(Vec::new, |cache| cache.persisted_modules(kind, engine))
+}
+
+#[cfg(with_fs)]
+impl DiskCache {
+    /// Creates a cache in `directory`, keeping its total size under `max_size` bytes.
+    fn new(directory: PathBuf, max_size: u64) -> io::Result<Self> {
+        fs::create_dir_all(&directory)?;
+        Ok(DiskCache {
+            directory,
+            max_size,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    /// See [`load_or_compile`].
+    fn load_or_compile<Module>(
+        &self,
+        kind: &str,
+        engine: &str,
+        bytecode: &Bytecode,
+        compile: impl FnOnce() -> anyhow::Result<Module>,
+        serialize: impl FnOnce(&Module) -> anyhow::Result<Vec<u8>>,
+        deserialize: impl FnOnce(Vec<u8>) -> anyhow::Result<Module>,
+    ) -> anyhow::Result<Module> {
+        let entry = self.entry_path(kind, engine, bytecode);
+
+        if let Some(artifact) = self.load(&entry) {
+            match deserialize(artifact) {
+                Ok(module) => return Ok(module),
+                Err(error) => warn!(
+                    "Failed to load compiled module {}: {error}",
+                    entry.display()
+                ),
+            }
+        }
+
+        let module = compile()?;
+        match serialize(&module) {
+            Ok(artifact) => {
+                if let Err(error) = self.store(&entry, bytecode, &artifact) {
+                    warn!(
+                        "Failed to store compiled module {}: {error}",
+                        entry.display()
+                    );
+                }
+            }
+            Err(error) => warn!("Failed to serialize compiled module: {error}"),
+        }
+        Ok(module)
+    }
+
+    /// See [`persisted_modules`].
+    fn persisted_modules(&self, kind: &str, engine: &str) -> Vec<(Bytecode, Vec<u8>)> {
+        let Ok(entries) = fs::read_dir(self.directory.join(kind)) else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == MODULE_EXTENSION))
+            .filter_map(|path| {
+                let bytecode =
+                    Bytecode::new(fs::read(path.with_extension(BYTECODE_EXTENSION)).ok()?);
+                // Entries compiled by another engine, or whose bytecode doesn't match their name,
+                // are skipped.
+                let entry = self.entry_path(kind, engine, &bytecode);
+                if entry.with_extension(MODULE_EXTENSION) != path {
+                    return None;
+                }
+                let artifact = self.load(&entry)?;
+                Some((bytecode, artifact))
+            })
+            .collect()
+    }
+
+    /// Returns the path of the entry for a module of the given `kind` compiled from `bytecode` by
+    /// the `engine`, without an extension.
+    fn entry_path(&self, kind: &str, engine: &str, bytecode: &Bytecode) -> PathBuf {
+        let hash = CryptoHash::new(&EntryKey {
+            kind: kind.to_owned(),
+            engine: engine.to_owned(),
+            bytecode: bytecode.clone(),
+        });
+        self.directory.join(kind).join(hash.to_string())
+    }
+
+    /// Reads and checks the compiled artifact of an `entry`, marking it as recently used.
+    ///
+    /// A corrupt entry is removed.
+    fn load(&self, entry: &Path) -> Option<Vec<u8>> {
+        let path = entry.with_extension(MODULE_EXTENSION);
+        let mut contents = fs::read(&path).ok()?;
+        if contents.len() < CHECKSUM_LENGTH
+            || contents[..CHECKSUM_LENGTH]
+                != CryptoHash::new(&Artifact(&contents[CHECKSUM_LENGTH..])).as_bytes()[..]
+        {
+            warn!("Removing corrupt compiled module {}", path.display());
+            let _ = fs::remove_file(&path);
+            let _ = fs::remove_file(entry.with_extension(BYTECODE_EXTENSION));
+            return None;
+        }
+        if let Ok(file) = fs::File::options().append(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(contents.split_off(CHECKSUM_LENGTH))
+    }
+
+    /// Writes an `entry` with its `bytecode` and compiled `artifact`, then evicts old entries if
+    /// the cache is too large.
+    fn store(&self, entry: &Path, bytecode: &Bytecode, artifact: &[u8]) -> io::Result<()> {
+        let _guard = self
+            .write_lock
+            .lock()
+            .expect("Module disk cache lock should not be poisoned");
+        if let Some(parent) = entry.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut contents = Vec::with_capacity(CHECKSUM_LENGTH + artifact.len());
+        contents.extend_from_slice(CryptoHash::new(&Artifact(artifact)).as_bytes());
+        contents.extend_from_slice(artifact);
+        // The artifact is written last, so that an entry is only visible once it's complete.
+        Self::write_atomically(&entry.with_extension(BYTECODE_EXTENSION), bytecode.as_ref())?;
+        Self::write_atomically(&entry.with_extension(MODULE_EXTENSION), &contents)?;
+        self.evict()
+    }
+    /// Writes `contents` to a temporary file and then moves it to `path`, so that other
+    /// processes sharing the directory never see a partially written file.
+    fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+        let temporary_path = path.with_extension(format!(
+            "{TEMPORARY_EXTENSION_PREFIX}{}",
+            std::process::id()
+        ));
+        fs::write(&temporary_path, contents)?;
+        fs::rename(temporary_path, path)
+    }
+
+    /// Removes the least recently used entries until the total size is under the limit, and the
+    /// stale temporary files.
+    fn evict(&self) -> io::Result<()> {
+        let mut entries = Vec::new();
+        let mut total_size = 0;
+
+        for kind_directory in fs::read_dir(&self.directory)? {
+            let kind_directory = kind_directory?;
+            if !kind_directory.file_type()?.is_dir() {
+                continue;
+            }
+            for file in fs::read_dir(kind_directory.path())? {
+                let path = file?.path();
+                let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+                    continue;
+                };
+                if extension.starts_with(TEMPORARY_EXTENSION_PREFIX) {
+                    // Files being written by other processes sharing the directory are recent.
+                    let is_stale = fs::metadata(&path)
+                        .and_then(|metadata| metadata.modified())
+                        .is_ok_and(|modified| {
+                            modified.elapsed().unwrap_or_default() > STALE_TEMPORARY_FILE_AGE
+                        });
+                    if is_stale {
+                        let _ = fs::remove_file(&path);
+                    }
+                } else if extension == MODULE_EXTENSION {
+                    let metadata = fs::metadata(&path)?;
+                    let bytecode_size = fs::metadata(path.with_extension(BYTECODE_EXTENSION))
+                        .map_or(0, |metadata| metadata.len());
+                    let size = metadata.len() + bytecode_size;
+                    total_size += size;
+                    entries.push((metadata.modified()?, size, path));
+                }
+            }
+        }
+
+        entries.sort_unstable_by_key(|(last_used, _, _)| *last_used);
+
+        for (_, size, path) in entries {
+            if total_size <= self.max_size {
+                break;
+            }
+            fs::remove_file(&path)?;
+            let _ = fs::remove_file(path.with_extension(BYTECODE_EXTENSION));
+            total_size -= size;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, with_fs))]
+mod tests {
+    use std::{
+        cell::Cell,
+        fs,
+        time::{Duration, SystemTime},
+    };
+
+    use linera_base::data_types::Bytecode;
+
+    use super::{DiskCache, MODULE_EXTENSION, STALE_TEMPORARY_FILE_AGE};
+
+    /// Compiles the `bytecode` into a fake module, counting the compilations, using the
+    /// artifacts persisted in the `cache`.
+    fn load_or_compile(
+        cache: &DiskCache,
+        engine: &str,
+        bytecode: &Bytecode,
+        compilations: &Cell<usize>,
+    ) -> Vec<u8> {
+        cache
+            .load_or_compile(
+                "test-module",
+                engine,
+                bytecode,
+                || {
+                    compilations.set(compilations.get() + 1);
+                    Ok([&b"compiled:"[..], &bytecode.bytes].concat())
+                },
+                |module| Ok(module.clone()),
+                Ok,
+            )
+            .expect("Compiling the fake module should not fail")
+    }
+
+    /// Tests that a persisted module is read back instead of being compiled again.
+    #[test]
+    fn reads_persisted_modules() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let cache = DiskCache::new(directory.path().to_owned(), u64::MAX)?;
+        let bytecode = Bytecode::new(b"bytecode".to_vec());
+        let compilations = Cell::new(0);
+
+        let module = load_or_compile(&cache, "engine-1", &bytecode, &compilations);
+        assert_eq!(module, b"compiled:bytecode");
+        assert_eq!(
+            load_or_compile(&cache, "engine-1", &bytecode, &compilations),
+            module
+        );
+        assert_eq!(compilations.get(), 1);
+
+        // The cache of another process sharing the directory reads the same modules.
+        let cache = DiskCache::new(directory.path().to_owned(), u64::MAX)?;
+        assert_eq!(
+            cache.persisted_modules("test-module", "engine-1"),
+            vec![(bytecode.clone(), module.clone())]
+        );
+        assert_eq!(
+            load_or_compile(&cache, "engine-1", &bytecode, &compilations),
+            module
+        );
+        assert_eq!(compilations.get(), 1);
+        Ok(())
+    }
+
+    /// Tests that modules persisted by another engine version are not loaded.
+    #[test]
+    fn ignores_modules_of_other_engine_versions() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let cache = DiskCache::new(directory.path().to_owned(), u64::MAX)?;
+        let bytecode = Bytecode::new(b"bytecode".to_vec());
+        let compilations = Cell::new(0);
+
+        load_or_compile(&cache, "engine-1", &bytecode, &compilations);
+        assert!(cache
+            .persisted_modules("test-module", "engine-2")
+            .is_empty());
+        load_or_compile(&cache, "engine-2", &bytecode, &compilations);
+        assert_eq!(compilations.get(), 2);
+        assert_eq!(cache.persisted_modules("test-module", "engine-2").len(), 1);
+        Ok(())
+    }
+
+    /// Tests that corrupt modules are removed and compiled again.
+    #[test]
+    fn recompiles_corrupt_modules() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let cache = DiskCache::new(directory.path().to_owned(), u64::MAX)?;
+        let bytecode = Bytecode::new(b"bytecode".to_vec());
+        let compilations = Cell::new(0);
+
+        let module = load_or_compile(&cache, "engine-1", &bytecode, &compilations);
+        let path = cache
+            .entry_path("test-module", "engine-1", &bytecode)
+            .with_extension(MODULE_EXTENSION);
+        let mut contents = fs::read(&path)?;
+        *contents.last_mut().unwrap() ^= 1;
+        fs::write(&path, contents)?;
+
+        assert!(cache
+            .persisted_modules("test-module", "engine-1")
+            .is_empty());
+        assert!(!path.exists());
+        assert_eq!(
+            load_or_compile(&cache, "engine-1", &bytecode, &compilations),
+            module
+        );
+        assert_eq!(compilations.get(), 2);
+
+        // Truncated files are rejected too.
+        fs::write(&path, b"short")?;
+        assert_eq!(
+            load_or_compile(&cache, "engine-1", &bytecode, &compilations),
+            module
+        );
+        assert_eq!(compilations.get(), 3);
+        assert_eq!(
+            cache.persisted_modules("test-module", "engine-1"),
+            vec![(bytecode, module)]
+        );
+        Ok(())
+    }
+
+    /// Tests that temporary files left behind by interrupted writes are removed, but not those
+    /// that may still be being written.
+    #[test]
+    fn removes_stale_temporary_files() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let cache = DiskCache::new(directory.path().to_owned(), u64::MAX)?;
+        let kind_directory = directory.path().join("test-module");
+        fs::create_dir_all(&kind_directory)?;
+        let stale_path = kind_directory.join("stale.tmp-1");
+        let recent_path = kind_directory.join("recent.tmp-2");
+        fs::write(&stale_path, b"partial")?;
+        fs::write(&recent_path, b"partial")?;
+        fs::File::options()
+            .append(true)
+            .open(&stale_path)?
+            .set_modified(SystemTime::now() - STALE_TEMPORARY_FILE_AGE - Duration::from_secs(1))?;
+
+        let bytecode = Bytecode::new(b"bytecode".to_vec());
+        load_or_compile(&cache, "engine-1", &bytecode, &Cell::new(0));
+
+        assert!(!stale_path.exists());
+        assert!(recent_path.exists());
+        Ok(())
+    }
+}
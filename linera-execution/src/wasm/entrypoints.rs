@@ -3,15 +3,18 @@
 
 //! Wasm entrypoints for contracts and services.
 
+use linera_base::data_types::ApplicationError;
 use linera_witty::wit_import;
 
 /// WIT entrypoints for application contracts.
 #[wit_import(package = "linera:app")]
 pub trait ContractEntrypoints {
     fn instantiate(argument: Vec<u8>);
-    fn execute_operation(operation: Vec<u8>) -> Vec<u8>;
+    fn execute_operation(operation: Vec<u8>) -> Result<Vec<u8>, ApplicationError>;
     fn execute_message(message: Vec<u8>);
+    fn execute_callback(argument: Vec<u8>);
     fn finalize();
+    fn migrate(old_version: u32);
 }
 
 /// WIT entrypoints for application services.
@@ -0,0 +1,155 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Enforcement of the limits on the linear memory and tables of application instances.
+//!
+//! The initial sizes are read from the bytecode and checked when an instance is created. Every
+//! `memory.grow` instruction is instrumented to first call the `track-memory-growth` function of
+//! the system API, which enforces the maximum memory size and charges fuel for the new pages.
+//! Both runtimes execute the same instrumented bytecode, so they fail at the same point.
+
+use linera_base::data_types::Bytecode;
+use wasm_instrument::{
+    gas_metering,
+    parity_wasm::{
+        self,
+        elements::{External, Module},
+    },
+};
+
+/// The name of the system API function called before the linear memory grows.
+const TRACK_MEMORY_GROWTH: &str = "track-memory-growth";
+
+/// The initial size of the linear memory and tables of a module's instances.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct InstanceSize {
+    /// The initial number of Wasm pages of linear memory.
+    pub memory_pages: u64,
+    /// The initial number of elements in all tables.
+    pub table_elements: u64,
+}
+
+impl InstanceSize {
+    /// Reads the initial memory and table sizes declared or imported by the `bytecode`.
+    pub fn of(bytecode: &Bytecode) -> anyhow::Result<Self> {
+        let module = parity_wasm::deserialize_buffer::<Module>(&bytecode.bytes)?;
+        let mut memory_limits = module
+            .memory_section()
+            .map(|section| section.entries())
+            .unwrap_or_default()
+            .iter()
+            .map(|memory| memory.limits())
+            .collect::<Vec<_>>();
+        let mut table_limits = module
+            .table_section()
+            .map(|section| section.entries())
+            .unwrap_or_default()
+            .iter()
+            .map(|table| table.limits())
+            .collect::<Vec<_>>();
+        for import in module
+            .import_section()
+            .map(|section| section.entries())
+            .unwrap_or_default()
+        {
+            match import.external() {
+                External::Memory(memory) => memory_limits.push(memory.limits()),
+                External::Table(table) => table_limits.push(table.limits()),
+                External::Function(_) | External::Global(_) => {}
+            }
+        }
+        Ok(InstanceSize {
+            memory_pages: memory_limits
+                .iter()
+                .map(|limits| u64::from(limits.initial()))
+                .sum(),
+            table_elements: table_limits
+                .iter()
+                .map(|limits| u64::from(limits.initial()))
+                .sum(),
+        })
+    }
+}
+
+/// Instruments the `bytecode` so that every `memory.grow` instruction first calls the
+/// `track-memory-growth` function of the `system_api` interface with the number of new pages.
+pub fn add_memory_tracking(bytecode: &Bytecode, system_api: &str) -> anyhow::Result<Bytecode> {
+    let instrumented_module = gas_metering::inject(
+        parity_wasm::deserialize_buffer(&bytecode.bytes)?,
+        gas_metering::host_function::Injector::new(system_api, TRACK_MEMORY_GROWTH),
+        &MemoryGrowthRules,
+    )
+    .map_err(|_| anyhow::anyhow!("failed to instrument module"))?;
+
+    Ok(Bytecode::new(instrumented_module.into_bytes()?))
+}
+
+/// Metering rules that only report the number of pages requested by `memory.grow`.
+struct MemoryGrowthRules;
+
+impl gas_metering::Rules for MemoryGrowthRules {
+    fn instruction_cost(&self, _instruction: &parity_wasm::elements::Instruction) -> Option<u32> {
+        Some(0)
+    }
+
+    fn memory_grow_cost(&self) -> gas_metering::MemoryGrowCost {
+        gas_metering::MemoryGrowCost::Linear(std::num::NonZeroU32::MIN)
+    }
+
+    fn call_per_local_cost(&self) -> u32 {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::data_types::Bytecode;
+    use wasm_instrument::parity_wasm::{self, elements::Module};
+
+    use super::{add_memory_tracking, InstanceSize};
+
+    #[test]
+    fn reads_initial_memory_and_table_sizes() {
+        let wat = r#"
+            (module
+                (import "env" "table" (table 3 funcref))
+                (memory 17)
+                (table 5 10 funcref)
+            )
+        "#;
+        let bytecode = Bytecode::new(wasmer::wat2wasm(wat.as_bytes()).unwrap().into_owned());
+
+        assert_eq!(
+            InstanceSize::of(&bytecode).unwrap(),
+            InstanceSize {
+                memory_pages: 17,
+                table_elements: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn tracks_memory_growth_with_the_system_api() {
+        let wat = r#"
+            (module
+                (memory 1)
+                (func (export "grow") (param i32) (result i32)
+                    (memory.grow (local.get 0))
+                )
+            )
+        "#;
+        let bytecode = Bytecode::new(wasmer::wat2wasm(wat.as_bytes()).unwrap().into_owned());
+
+        let tracked_bytecode = add_memory_tracking(&bytecode, "system-api").unwrap();
+        let module = parity_wasm::deserialize_buffer::<Module>(&tracked_bytecode.bytes).unwrap();
+        let imports = module.import_section().unwrap().entries();
+
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].module(), "system-api");
+        assert_eq!(imports[0].field(), "track-memory-growth");
+        assert_eq!(
+            InstanceSize::of(&tracked_bytecode).unwrap(),
+            InstanceSize::of(&bytecode).unwrap()
+        );
+    }
+}
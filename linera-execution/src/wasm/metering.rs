@@ -0,0 +1,87 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Instrumentation of the bytecode with fuel metering.
+//!
+//! Contracts are instrumented to call the `consume-fuel` function of the system API according to
+//! the [`FuelSchedule`] of the committee. Both runtimes execute the same instrumented bytecode,
+//! so they consume the same amount of fuel.
+
+use linera_base::data_types::Bytecode;
+use wasm_instrument::{gas_metering, parity_wasm};
+
+use super::limits::add_memory_tracking;
+use crate::FuelSchedule;
+
+/// Instruments the contract `bytecode` so that it consumes fuel according to the
+/// `fuel_schedule`.
+pub fn add_metering(bytecode: &Bytecode, fuel_schedule: &FuelSchedule) -> anyhow::Result<Bytecode> {
+    inject_metering(bytecode, fuel_schedule, "linera:app/contract-system-api")
+}
+
+/// Instruments the service `bytecode` so that it consumes fuel according to the default
+/// [`FuelSchedule`].
+///
+/// Wasmer has no equivalent of the epoch interruption of Wasmtime, so this is also how the
+/// deadlines of queries are enforced: the injected calls to `consume-fuel` happen at least
+/// once per basic block and check the elapsed time, so a service that runs past its deadline
+/// traps at its next call, even in a loop without system API calls.
+#[cfg(with_wasmer)]
+pub fn add_service_metering(bytecode: &Bytecode) -> anyhow::Result<Bytecode> {
+    inject_metering(
+        bytecode,
+        &FuelSchedule::default(),
+        "linera:app/service-system-api",
+    )
+}
+
+/// Instruments the `bytecode` to call the `track-memory-growth` and `consume-fuel` functions of
+/// the `system_api` interface.
+fn inject_metering(
+    bytecode: &Bytecode,
+    fuel_schedule: &FuelSchedule,
+    system_api: &str,
+) -> anyhow::Result<Bytecode> {
+    let tracked_bytecode = add_memory_tracking(bytecode, system_api)?;
+    let instrumented_module = gas_metering::inject(
+        parity_wasm::deserialize_buffer(&tracked_bytecode.bytes)?,
+        gas_metering::host_function::Injector::new(system_api, "consume-fuel"),
+        fuel_schedule,
+    )
+    .map_err(|_| anyhow::anyhow!("failed to instrument module"))?;
+
+    Ok(Bytecode::new(instrumented_module.into_bytes()?))
+}
+
+impl gas_metering::Rules for FuelSchedule {
+    /// Calculates the fuel cost of a WebAssembly instruction.
+    ///
+    /// The default schedule follows the hardcoded [rules in the Wasmtime runtime
+    /// engine](https://docs.rs/wasmtime/5.0.0/wasmtime/struct.Store.html#method.add_fuel).
+    fn instruction_cost(&self, instruction: &parity_wasm::elements::Instruction) -> Option<u32> {
+        use parity_wasm::elements::Instruction::*;
+
+        Some(match instruction {
+            Nop | Drop | Block(_) | Loop(_) | Unreachable | Else | End => {
+                self.structural_instruction
+            }
+            I32Load(..) | I64Load(..) | F32Load(..) | F64Load(..) | I32Load8S(..)
+            | I32Load8U(..) | I32Load16S(..) | I32Load16U(..) | I64Load8S(..) | I64Load8U(..)
+            | I64Load16S(..) | I64Load16U(..) | I64Load32S(..) | I64Load32U(..) | I32Store(..)
+            | I64Store(..) | F32Store(..) | F64Store(..) | I32Store8(..) | I32Store16(..)
+            | I64Store8(..) | I64Store16(..) | I64Store32(..) => self.memory_access,
+            Call(_) | CallIndirect(..) => self.call,
+            _ => self.instruction,
+        })
+    }
+
+    /// New memory pages are charged by the host when their growth is tracked, using
+    /// [`FuelSchedule::memory_grow_page`].
+    fn memory_grow_cost(&self) -> gas_metering::MemoryGrowCost {
+        gas_metering::MemoryGrowCost::Free
+    }
+
+    fn call_per_local_cost(&self) -> u32 {
+        0
+    }
+}
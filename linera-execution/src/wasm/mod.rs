@@ -10,7 +10,11 @@
 
 #![cfg(with_wasm_runtime)]
 
+#[cfg(not(web))]
+mod disk_cache;
 mod entrypoints;
+mod limits;
+mod metering;
 mod module_cache;
 mod sanitizer;
 #[macro_use]
@@ -20,7 +24,13 @@ mod wasmer;
 #[cfg(with_wasmtime)]
 mod wasmtime;
 
-use linera_base::data_types::Bytecode;
+use std::sync::Mutex;
+
+use linera_base::{
+    crypto::{BcsHashable, CryptoHash},
+    data_types::Bytecode,
+};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 #[cfg(with_wasmer)]
 use wasmer::{WasmerContractInstance, WasmerServiceInstance};
@@ -33,13 +43,16 @@ use {
     std::sync::LazyLock,
 };
 
-use self::sanitizer::sanitize;
+#[cfg(with_fs)]
+pub use self::disk_cache::enable_module_disk_cache;
 pub use self::{
     entrypoints::{ContractEntrypoints, ServiceEntrypoints},
     system_api::{ContractSystemApi, ServiceSystemApi, SystemApiData, ViewSystemApi},
 };
+use self::{limits::InstanceSize, module_cache::ModuleCache, sanitizer::sanitize};
 use crate::{
-    ContractSyncRuntimeHandle, ExecutionError, ServiceSyncRuntimeHandle, UserContractInstance,
+    ContractRuntime as _, ContractSyncRuntimeHandle, ExecutionError, FuelSchedule,
+    ServiceRuntime as _, ServiceSyncRuntimeHandle, UserContractCode, UserContractInstance,
     UserContractModule, UserServiceInstance, UserServiceModule, WasmRuntime,
 };
 
@@ -70,9 +83,31 @@ pub enum WasmContractModule {
     Wasmer {
         engine: ::wasmer::Engine,
         module: ::wasmer::Module,
+        /// The bytecode without the fuel metering instrumentation.
+        bytecode: Bytecode,
+        /// The hash of the `bytecode`, under which its compiled modules are cached.
+        bytecode_hash: CryptoHash,
+        /// The schedule used to instrument the `module` with fuel metering.
+        fuel_schedule: FuelSchedule,
+        /// The version of the contract ABI declared by the bytecode, if any.
+        abi_version: Option<u32>,
+        /// The initial memory and table sizes of the instances.
+        instance_size: InstanceSize,
     },
     #[cfg(with_wasmtime)]
-    Wasmtime { module: ::wasmtime::Module },
+    Wasmtime {
+        module: ::wasmtime::Module,
+        /// The bytecode without the fuel metering instrumentation.
+        bytecode: Bytecode,
+        /// The hash of the `bytecode`, under which its compiled modules are cached.
+        bytecode_hash: CryptoHash,
+        /// The schedule used to instrument the `module` with fuel metering.
+        fuel_schedule: FuelSchedule,
+        /// The version of the contract ABI declared by the bytecode, if any.
+        abi_version: Option<u32>,
+        /// The initial memory and table sizes of the instances.
+        instance_size: InstanceSize,
+    },
 }
 
 impl WasmContractModule {
@@ -91,11 +126,11 @@ impl WasmContractModule {
         match runtime {
             #[cfg(with_wasmer)]
             WasmRuntime::Wasmer | WasmRuntime::WasmerWithSanitizer => {
-                Self::from_wasmer(contract_bytecode).await
+                Self::from_wasmer(contract_bytecode, FuelSchedule::default()).await
             }
             #[cfg(with_wasmtime)]
             WasmRuntime::Wasmtime | WasmRuntime::WasmtimeWithSanitizer => {
-                Self::from_wasmtime(contract_bytecode).await
+                Self::from_wasmtime(contract_bytecode, FuelSchedule::default()).await
             }
         }
     }
@@ -120,33 +155,238 @@ impl WasmContractModule {
 impl UserContractModule for WasmContractModule {
     fn instantiate(
         &self,
-        runtime: ContractSyncRuntimeHandle,
+        mut runtime: ContractSyncRuntimeHandle,
     ) -> Result<UserContractInstance, ExecutionError> {
         #[cfg(with_metrics)]
         let _instantiation_latency = CONTRACT_INSTANTIATION_LATENCY.measure_latency();
 
+        let instance_size = self.instance_size();
+        runtime
+            .check_wasm_instance_size(instance_size.memory_pages, instance_size.table_elements)?;
         let instance: UserContractInstance = match self {
             #[cfg(with_wasmtime)]
-            WasmContractModule::Wasmtime { module } => {
-                Box::new(WasmtimeContractInstance::prepare(module, runtime)?)
-            }
+            WasmContractModule::Wasmtime { module, .. } => Box::new(
+                WasmtimeContractInstance::prepare(module, runtime, instance_size)?,
+            ),
             #[cfg(with_wasmer)]
-            WasmContractModule::Wasmer { engine, module } => Box::new(
-                WasmerContractInstance::prepare(engine.clone(), module, runtime)?,
+            WasmContractModule::Wasmer { engine, module, .. } => Box::new(
+                WasmerContractInstance::prepare(engine.clone(), module, runtime, instance_size)?,
             ),
         };
 
         Ok(instance)
     }
+
+    #[cfg_attr(web, allow(unused_variables))]
+    fn with_fuel_schedule(
+        &self,
+        fuel_schedule: &FuelSchedule,
+    ) -> Result<Option<UserContractCode>, ExecutionError> {
+        match self {
+            #[cfg(all(with_wasmer, not(web)))]
+            WasmContractModule::Wasmer {
+                bytecode,
+                bytecode_hash,
+                fuel_schedule: current_fuel_schedule,
+                ..
+            } if current_fuel_schedule != fuel_schedule => {
+                let module = Self::from_wasmer_with_schedule(
+                    bytecode.clone(),
+                    *bytecode_hash,
+                    *fuel_schedule,
+                )?;
+                Ok(Some(module.into()))
+            }
+            #[cfg(with_wasmtime)]
+            WasmContractModule::Wasmtime {
+                bytecode,
+                bytecode_hash,
+                fuel_schedule: current_fuel_schedule,
+                ..
+            } if current_fuel_schedule != fuel_schedule => {
+                let module = Self::from_wasmtime_with_schedule(
+                    bytecode.clone(),
+                    *bytecode_hash,
+                    *fuel_schedule,
+                )?;
+                Ok(Some(module.into()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn abi_version(&self) -> Option<u32> {
+        match self {
+            #[cfg(with_wasmer)]
+            WasmContractModule::Wasmer { abi_version, .. } => *abi_version,
+            #[cfg(with_wasmtime)]
+            WasmContractModule::Wasmtime { abi_version, .. } => *abi_version,
+        }
+    }
+}
+
+impl WasmContractModule {
+    /// Returns the initial memory and table sizes of the instances of this module.
+    fn instance_size(&self) -> InstanceSize {
+        match self {
+            #[cfg(with_wasmer)]
+            WasmContractModule::Wasmer { instance_size, .. } => *instance_size,
+            #[cfg(with_wasmtime)]
+            WasmContractModule::Wasmtime { instance_size, .. } => *instance_size,
+        }
+    }
+}
+
+/// The key of a compiled contract module in the in-memory caches: the hash of the contract's
+/// bytecode, and the [`FuelSchedule`] with which its fuel metering was instrumented.
+type ContractCacheKey = (CryptoHash, FuelSchedule);
+
+/// A contract bytecode, hashed to index its compiled modules in the in-memory caches.
+#[derive(Serialize, Deserialize)]
+struct ContractBytecode<'a>(&'a [u8]);
+
+impl<'de> BcsHashable<'de> for ContractBytecode<'de> {}
+
+/// Returns the hash under which the modules compiled from a contract `bytecode` are cached.
+fn contract_bytecode_hash(bytecode: &Bytecode) -> CryptoHash {
+    CryptoHash::new(&ContractBytecode(&bytecode.bytes))
+}
+
+/// A compiled contract module in the in-memory caches, with the metadata read from its
+/// bytecode.
+#[derive(Clone)]
+struct CachedContract<Module> {
+    module: Module,
+    /// The version of the contract ABI declared by the bytecode, if any.
+    abi_version: Option<u32>,
+    /// The initial memory and table sizes of the instances.
+    instance_size: InstanceSize,
+}
+
+impl<Module: Clone> CachedContract<Module> {
+    /// Returns the module compiled from the contract `bytecode` with the `fuel_schedule`,
+    /// from the `cache` if possible.
+    ///
+    /// Otherwise the bytecode is instrumented and compiled with `compile`, without holding the
+    /// lock of the `cache`, so that other contracts can be loaded in the meantime.
+    fn load(
+        cache: &Mutex<ModuleCache<Self, ContractCacheKey>>,
+        bytecode: &Bytecode,
+        bytecode_hash: CryptoHash,
+        fuel_schedule: FuelSchedule,
+        compile: impl FnOnce(&Bytecode, &FuelSchedule) -> anyhow::Result<Module>,
+    ) -> anyhow::Result<Self> {
+        let key = (bytecode_hash, fuel_schedule);
+        if let Some(cached) = Self::lock(cache).get(&key) {
+            return Ok(cached);
+        }
+        let cached = CachedContract {
+            abi_version: read_contract_abi_version(bytecode),
+            instance_size: InstanceSize::of(bytecode)?,
+            module: compile(bytecode, &fuel_schedule)?,
+        };
+        let bytecode_size = bytecode.as_ref().len() as u64;
+        Self::lock(cache).insert_with_size(key, bytecode_size, cached.clone());
+        Ok(cached)
+    }
+
+    /// Adds a module persisted in the disk cache under the `source` returned by
+    /// [`contract_module_source`] to the in-memory `cache`.
+    ///
+    /// Returns `false` if the entry could not be read.
+    #[cfg(with_fs)]
+    fn warm_up(
+        cache: &mut ModuleCache<Self, ContractCacheKey>,
+        source: Bytecode,
+        deserialize: impl FnOnce() -> anyhow::Result<Module>,
+    ) -> bool {
+        let Ok((bytecode, fuel_schedule)) =
+            bcs::from_bytes::<(Bytecode, FuelSchedule)>(&source.bytes)
+        else {
+            return false;
+        };
+        let Ok(instance_size) = InstanceSize::of(&bytecode) else {
+            return false;
+        };
+        let Ok(module) = deserialize() else {
+            return false;
+        };
+        let cached = CachedContract {
+            module,
+            abi_version: read_contract_abi_version(&bytecode),
+            instance_size,
+        };
+        let key = (contract_bytecode_hash(&bytecode), fuel_schedule);
+        cache.insert_with_size(key, bytecode.as_ref().len() as u64, cached);
+        true
+    }
+
+    /// Locks a `cache` of compiled contract modules.
+    fn lock(
+        cache: &Mutex<ModuleCache<Self, ContractCacheKey>>,
+    ) -> std::sync::MutexGuard<'_, ModuleCache<Self, ContractCacheKey>> {
+        cache
+            .lock()
+            .expect("Contract module cache should not be poisoned")
+    }
+}
+
+/// Returns what identifies a contract module compiled from the `bytecode` with the
+/// `fuel_schedule` in the disk cache: the bytecode and the schedule, so that the in-memory
+/// caches can be warmed up from it.
+#[cfg(not(web))]
+fn contract_module_source(bytecode: &Bytecode, fuel_schedule: &FuelSchedule) -> Bytecode {
+    Bytecode::new(bcs::to_bytes(&(bytecode, fuel_schedule)).expect("Serialization should not fail"))
+}
+
+/// The name of the custom section in which the SDK records the version of a contract's ABI.
+const CONTRACT_ABI_VERSION_SECTION: &str = "linera:contract-abi-version";
+
+/// The name of the custom section in which the SDK records the version of a service's ABI.
+const SERVICE_ABI_VERSION_SECTION: &str = "linera:service-abi-version";
+
+/// Reads the version of the contract ABI recorded in the `bytecode`, if any.
+fn read_contract_abi_version(bytecode: &Bytecode) -> Option<u32> {
+    read_abi_version(bytecode, CONTRACT_ABI_VERSION_SECTION)
+}
+
+/// Reads the version of the service ABI recorded in the `bytecode`, if any.
+fn read_service_abi_version(bytecode: &Bytecode) -> Option<u32> {
+    read_abi_version(bytecode, SERVICE_ABI_VERSION_SECTION)
+}
+
+/// Reads the ABI version recorded in the custom section `section_name` of the `bytecode`.
+fn read_abi_version(bytecode: &Bytecode, section_name: &str) -> Option<u32> {
+    wasmparser::Parser::new(0)
+        .parse_all(&bytecode.bytes)
+        .map_while(Result::ok)
+        .find_map(|payload| match payload {
+            wasmparser::Payload::CustomSection(section) if section.name() == section_name => {
+                Some(u32::from_le_bytes(section.data().try_into().ok()?))
+            }
+            _ => None,
+        })
 }
 
 /// A user service in a compiled WebAssembly module.
 #[derive(Clone)]
 pub enum WasmServiceModule {
     #[cfg(with_wasmer)]
-    Wasmer { module: ::wasmer::Module },
+    Wasmer {
+        module: ::wasmer::Module,
+        /// The version of the service ABI declared by the bytecode, if any.
+        abi_version: Option<u32>,
+        /// The initial memory and table sizes of the instances.
+        instance_size: InstanceSize,
+    },
     #[cfg(with_wasmtime)]
-    Wasmtime { module: ::wasmtime::Module },
+    Wasmtime {
+        module: ::wasmtime::Module,
+        /// The version of the service ABI declared by the bytecode, if any.
+        abi_version: Option<u32>,
+        /// The initial memory and table sizes of the instances.
+        instance_size: InstanceSize,
+    },
 }
 
 impl WasmServiceModule {
@@ -187,24 +427,42 @@ impl WasmServiceModule {
 impl UserServiceModule for WasmServiceModule {
     fn instantiate(
         &self,
-        runtime: ServiceSyncRuntimeHandle,
+        mut runtime: ServiceSyncRuntimeHandle,
     ) -> Result<UserServiceInstance, ExecutionError> {
         #[cfg(with_metrics)]
         let _instantiation_latency = SERVICE_INSTANTIATION_LATENCY.measure_latency();
 
-        let instance: UserServiceInstance = match self {
+        let instance_size = match self {
             #[cfg(with_wasmtime)]
-            WasmServiceModule::Wasmtime { module } => {
-                Box::new(WasmtimeServiceInstance::prepare(module, runtime)?)
-            }
+            WasmServiceModule::Wasmtime { instance_size, .. } => *instance_size,
             #[cfg(with_wasmer)]
-            WasmServiceModule::Wasmer { module } => {
-                Box::new(WasmerServiceInstance::prepare(module, runtime)?)
-            }
+            WasmServiceModule::Wasmer { instance_size, .. } => *instance_size,
         };
+        runtime
+            .check_wasm_instance_size(instance_size.memory_pages, instance_size.table_elements)?;
+        let instance: UserServiceInstance =
+            match self {
+                #[cfg(with_wasmtime)]
+                WasmServiceModule::Wasmtime { module, .. } => Box::new(
+                    WasmtimeServiceInstance::prepare(module, runtime, instance_size)?,
+                ),
+                #[cfg(with_wasmer)]
+                WasmServiceModule::Wasmer { module, .. } => Box::new(
+                    WasmerServiceInstance::prepare(module, runtime, instance_size)?,
+                ),
+            };
 
         Ok(instance)
     }
+
+    fn abi_version(&self) -> Option<u32> {
+        match self {
+            #[cfg(with_wasmer)]
+            WasmServiceModule::Wasmer { abi_version, .. } => *abi_version,
+            #[cfg(with_wasmtime)]
+            WasmServiceModule::Wasmtime { abi_version, .. } => *abi_version,
+        }
+    }
 }
 
 #[cfg(web)]
@@ -221,6 +479,8 @@ const _: () = {
                 if #[cfg(with_wasmer)] {
                     Ok(Self::Wasmer {
                         module: value.try_into()?,
+                        abi_version: None,
+                        instance_size: InstanceSize::default(),
                     })
                 } else {
                     Err(value)
@@ -233,7 +493,7 @@ const _: () = {
         fn from(module: WasmServiceModule) -> JsValue {
             match module {
                 #[cfg(with_wasmer)]
-                WasmServiceModule::Wasmer { module } => ::wasmer::Module::clone(&module).into(),
+                WasmServiceModule::Wasmer { module, .. } => ::wasmer::Module::clone(&module).into(),
             }
         }
     }
@@ -248,6 +508,11 @@ const _: () = {
                     Ok(Self::Wasmer {
                         module: value.try_into()?,
                         engine: Default::default(),
+                        bytecode: Bytecode::new(Vec::new()),
+                        bytecode_hash: contract_bytecode_hash(&Bytecode::new(Vec::new())),
+                        fuel_schedule: FuelSchedule::default(),
+                        abi_version: None,
+                        instance_size: InstanceSize::default(),
                     })
                 } else {
                     Err(value)
@@ -260,7 +525,7 @@ const _: () = {
         fn from(module: WasmContractModule) -> JsValue {
             match module {
                 #[cfg(with_wasmer)]
-                WasmContractModule::Wasmer { module, engine: _ } => {
+                WasmContractModule::Wasmer { module, .. } => {
                     ::wasmer::Module::clone(&module).into()
                 }
             }
@@ -268,6 +533,25 @@ const _: () = {
     }
 };
 
+/// Loads the compiled modules persisted by the disk cache into the in-memory module caches, so
+/// that applications don't need to be compiled again when they are first executed.
+///
+/// Returns the number of modules that were loaded.
+#[cfg(with_fs)]
+pub async fn warm_up_module_caches() -> usize {
+    #[allow(unused_mut)]
+    let mut loaded_modules = 0;
+    #[cfg(with_wasmer)]
+    {
+        loaded_modules += wasmer::warm_up_caches().await;
+    }
+    #[cfg(with_wasmtime)]
+    {
+        loaded_modules += wasmtime::warm_up_caches().await;
+    }
+    loaded_modules
+}
+
 /// Errors that can occur when executing a user application in a WebAssembly module.
 #[cfg(any(with_wasmer, with_wasmtime))]
 #[derive(Debug, Error)]
@@ -353,3 +637,31 @@ pub mod test {
         Ok((contract, service))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use linera_base::data_types::Bytecode;
+
+    use super::{read_contract_abi_version, read_service_abi_version};
+
+    /// Tests that the version of the contract ABI is read from its custom section.
+    #[test]
+    fn reads_contract_abi_version() {
+        let wat = r#"(module (@custom "linera:contract-abi-version" "\2a\00\00\00"))"#;
+        let bytecode = Bytecode::new(wasmer::wat2wasm(wat.as_bytes()).unwrap().into_owned());
+        assert_eq!(read_contract_abi_version(&bytecode), Some(42));
+        assert_eq!(read_service_abi_version(&bytecode), None);
+
+        let bytecode = Bytecode::new(wasmer::wat2wasm(b"(module)").unwrap().into_owned());
+        assert_eq!(read_contract_abi_version(&bytecode), None);
+    }
+
+    /// Tests that the version of the service ABI is read from its own custom section.
+    #[test]
+    fn reads_service_abi_version() {
+        let wat = r#"(module (@custom "linera:service-abi-version" "\07\00\00\00"))"#;
+        let bytecode = Bytecode::new(wasmer::wat2wasm(wat.as_bytes()).unwrap().into_owned());
+        assert_eq!(read_service_abi_version(&bytecode), Some(7));
+        assert_eq!(read_contract_abi_version(&bytecode), None);
+    }
+}
@@ -7,6 +7,8 @@
 //! estimate the total memory usage by the cache, since it's currently not possible to determine
 //! the size of a generic `Module`.
 
+use std::hash::Hash;
+
 use linera_base::data_types::Bytecode;
 use lru::LruCache;
 
@@ -15,14 +17,16 @@ const DEFAULT_MAX_CACHE_SIZE: u64 = 512 /* MiB */ * 1024 /* KiB */ * 1024 /* byt
 
 /// A cache of compiled WebAssembly modules.
 ///
-/// The cache prioritizes entries based on their [`Metadata`].
-pub struct ModuleCache<Module> {
-    modules: LruCache<Bytecode, Module>,
+/// The cache prioritizes entries based on their [`Metadata`]. Entries are indexed by their
+/// bytecode, unless another `Key` is used, in which case the size of their bytecode is provided
+/// when inserting them.
+pub struct ModuleCache<Module, Key = Bytecode> {
+    modules: LruCache<Key, (Module, u64)>,
     total_size: u64,
     max_size: u64,
 }
 
-impl<Module> Default for ModuleCache<Module> {
+impl<Module, Key: Hash + Eq> Default for ModuleCache<Module, Key> {
     fn default() -> Self {
         ModuleCache {
             modules: LruCache::unbounded(),
@@ -49,31 +53,42 @@ impl<Module: Clone> ModuleCache<Module> {
         }
     }
 
-    /// Returns a `Module` for the requested `bytecode` if it's in the cache.
-    pub fn get(&mut self, bytecode: &Bytecode) -> Option<Module> {
-        self.modules.get(bytecode).cloned()
-    }
-
     /// Inserts a `bytecode` and its compiled `module` in the cache.
     pub fn insert(&mut self, bytecode: Bytecode, module: Module) {
         let bytecode_size = bytecode.as_ref().len() as u64;
+        self.insert_with_size(bytecode, bytecode_size, module);
+    }
+}
+
+impl<Module: Clone, Key: Hash + Eq> ModuleCache<Module, Key> {
+    /// Returns the `Module` cached under the `key`, if there is one.
+    pub fn get(&mut self, key: &Key) -> Option<Module> {
+        self.modules.get(key).map(|(module, _)| module.clone())
+    }
+
+    /// Inserts a `module` compiled from a bytecode of `bytecode_size` bytes in the cache, under
+    /// the `key`.
+    pub fn insert_with_size(&mut self, key: Key, bytecode_size: u64, module: Module) {
+        if let Some((_module, old_size)) = self.modules.pop(&key) {
+            self.total_size -= old_size;
+        }
 
         if self.total_size + bytecode_size > self.max_size {
-            self.reduce_size_to(self.max_size - bytecode_size);
+            self.reduce_size_to(self.max_size.saturating_sub(bytecode_size));
         }
 
-        self.modules.put(bytecode, module);
+        self.modules.put(key, (module, bytecode_size));
+        self.total_size += bytecode_size;
     }
 
     /// Evicts entries from the cache so that the total size of cached bytecodes is less than
     /// `new_size`.
     fn reduce_size_to(&mut self, new_size: u64) {
         while self.total_size > new_size {
-            let (bytecode, _module) = self
+            let (_key, (_module, bytecode_size)) = self
                 .modules
                 .pop_lru()
                 .expect("Empty cache should have a `total_size` of zero");
-            let bytecode_size = bytecode.as_ref().len() as u64;
 
             self.total_size -= bytecode_size;
         }
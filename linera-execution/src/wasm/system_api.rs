@@ -5,7 +5,10 @@ use std::{any::Any, collections::HashMap, marker::PhantomData};
 
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{Amount, ApplicationPermissions, BlockHeight, SendMessageRequest, Timestamp},
+    data_types::{
+        Amount, ApplicationError, ApplicationPermissions, BlockHeight, SendMessageRequest,
+        Timestamp,
+    },
     identifiers::{
         Account, AccountOwner, ApplicationId, ChainId, ChannelName, MessageId, Owner, StreamName,
     },
@@ -26,15 +29,19 @@ pub struct SystemApiData<Runtime> {
     runtime: Runtime,
     active_promises: HashMap<u32, Box<dyn Any + Send + Sync>>,
     promise_counter: u32,
+    /// The number of Wasm pages of linear memory of the instance.
+    memory_pages: u64,
 }
 
 impl<Runtime> SystemApiData<Runtime> {
-    /// Creates a new [`SystemApiData`] using the provided `runtime` to execute the system APIs.
-    pub fn new(runtime: Runtime) -> Self {
+    /// Creates a new [`SystemApiData`] using the provided `runtime` to execute the system APIs,
+    /// for an instance that starts with `memory_pages` pages of linear memory.
+    pub fn new(runtime: Runtime, memory_pages: u64) -> Self {
         SystemApiData {
             runtime,
             active_promises: HashMap::new(),
             promise_counter: 0,
+            memory_pages,
         }
     }
 
@@ -336,17 +343,34 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
-    /// Calls another application.
+    /// Calls another application. Returns the error the callee responded with, if any.
     fn try_call_application(
         caller: &mut Caller,
         authenticated: bool,
         callee_id: ApplicationId,
         argument: Vec<u8>,
-    ) -> Result<Vec<u8>, RuntimeError> {
+    ) -> Result<Result<Vec<u8>, ApplicationError>, RuntimeError> {
+        match caller.user_data_mut().runtime.try_call_application(
+            authenticated,
+            callee_id,
+            argument,
+        ) {
+            Ok(response) => Ok(Ok(response)),
+            Err(ExecutionError::ApplicationError(error)) => Ok(Err(error)),
+            Err(error) => Err(RuntimeError::Custom(error.into())),
+        }
+    }
+
+    /// Checks that the application implements the expected `version` of its contract ABI.
+    fn assert_abi_version(
+        caller: &mut Caller,
+        application_id: ApplicationId,
+        version: u32,
+    ) -> Result<(), RuntimeError> {
         caller
             .user_data_mut()
             .runtime
-            .try_call_application(authenticated, callee_id, argument)
+            .assert_abi_version(application_id, version)
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
@@ -391,6 +415,16 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Fetches a blob of bytes from a given URL. The response is recorded in the block as an
+    /// oracle response.
+    fn fetch_url(caller: &mut Caller, url: String) -> Result<Vec<u8>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .fetch_url(&url)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Rejects the transaction if the current time at block validation is `>= timestamp`. Note
     /// that block validation happens at or after the block timestamp, but isn't necessarily the
     /// same.
@@ -421,14 +455,13 @@ where
     }
 
     /// Logs a `message` with the provided information `level`.
-    fn log(_caller: &mut Caller, message: String, level: log::Level) -> Result<(), RuntimeError> {
-        match level {
-            log::Level::Trace => tracing::trace!("{message}"),
-            log::Level::Debug => tracing::debug!("{message}"),
-            log::Level::Info => tracing::info!("{message}"),
-            log::Level::Warn => tracing::warn!("{message}"),
-            log::Level::Error => tracing::error!("{message}"),
-        }
+    fn log(caller: &mut Caller, message: String, level: log::Level) -> Result<(), RuntimeError> {
+        let application_id = caller
+            .user_data_mut()
+            .runtime
+            .application_id()
+            .map_err(|error| RuntimeError::Custom(error.into()))?;
+        log_application_message(application_id, &message, level);
         Ok(())
     }
 
@@ -444,6 +477,20 @@ where
             .map_err(|e| RuntimeError::Custom(e.into()))
     }
 
+    /// Accounts for the growth of the linear memory by some pages.
+    ///
+    /// This is called by the instrumentation of `memory.grow`, and fails if the instance would
+    /// exceed its maximum memory size.
+    fn track_memory_growth(caller: &mut Caller, pages: u64) -> Result<(), RuntimeError> {
+        let mut data = caller.user_data_mut();
+        let memory_pages = data.memory_pages;
+        data.runtime_mut()
+            .track_wasm_memory_growth(memory_pages, pages)
+            .map_err(|e| RuntimeError::Custom(e.into()))?;
+        data.memory_pages = memory_pages.saturating_add(pages);
+        Ok(())
+    }
+
     /// Returns the round in which this block was validated.
     fn validation_round(caller: &mut Caller) -> Result<Option<u32>, RuntimeError> {
         caller
@@ -452,6 +499,20 @@ where
             .validation_round()
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
+
+    /// Schedules a callback of the application with the `argument`, executed once the chain
+    /// reaches the `timestamp`.
+    fn schedule_callback(
+        caller: &mut Caller,
+        timestamp: Timestamp,
+        argument: Vec<u8>,
+    ) -> Result<(), RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime_mut()
+            .schedule_callback(timestamp, argument)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
 }
 
 /// An implementation of the system API made available to services.
@@ -581,6 +642,19 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Checks that the application implements the expected `version` of its service ABI.
+    fn assert_abi_version(
+        caller: &mut Caller,
+        application_id: ApplicationId,
+        version: u32,
+    ) -> Result<(), RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .assert_abi_version(application_id, version)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Fetches a blob of bytes from a given URL.
     fn fetch_url(caller: &mut Caller, url: String) -> Result<Vec<u8>, RuntimeError> {
         caller
@@ -646,16 +720,56 @@ where
     }
 
     /// Logs a `message` with the provided information `level`.
-    fn log(_caller: &mut Caller, message: String, level: log::Level) -> Result<(), RuntimeError> {
-        match level {
-            log::Level::Trace => tracing::trace!("{message}"),
-            log::Level::Debug => tracing::debug!("{message}"),
-            log::Level::Info => tracing::info!("{message}"),
-            log::Level::Warn => tracing::warn!("{message}"),
-            log::Level::Error => tracing::error!("{message}"),
-        }
+    fn log(caller: &mut Caller, message: String, level: log::Level) -> Result<(), RuntimeError> {
+        let application_id = caller
+            .user_data_mut()
+            .runtime
+            .application_id()
+            .map_err(|error| RuntimeError::Custom(error.into()))?;
+        log_application_message(application_id, &message, level);
+        Ok(())
+    }
+
+    /// Consume some fuel.
+    ///
+    /// This is intended for the metering instrumentation, which limits how much computation a
+    /// single query may perform.
+    fn consume_fuel(caller: &mut Caller, fuel: u64) -> Result<(), RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime_mut()
+            .consume_fuel(fuel)
+            .map_err(|e| RuntimeError::Custom(e.into()))
+    }
+
+    /// Accounts for the growth of the linear memory by some pages.
+    ///
+    /// This is called by the instrumentation of `memory.grow`, and fails if the instance would
+    /// exceed its maximum memory size.
+    fn track_memory_growth(caller: &mut Caller, pages: u64) -> Result<(), RuntimeError> {
+        let mut data = caller.user_data_mut();
+        let memory_pages = data.memory_pages;
+        data.runtime_mut()
+            .track_wasm_memory_growth(memory_pages, pages)
+            .map_err(|e| RuntimeError::Custom(e.into()))?;
+        data.memory_pages = memory_pages.saturating_add(pages);
         Ok(())
     }
+
+    /// Reads the value of the latest event emitted by an application on this chain, on the
+    /// stream `name` with the given `key`.
+    fn read_event(
+        caller: &mut Caller,
+        application_id: ApplicationId,
+        name: StreamName,
+        key: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime_mut()
+            .read_event(application_id, name, key)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
 }
 
 /// An implementation of the system API used to access the view storage for both contracts and
@@ -813,13 +927,35 @@ where
         caller: &mut Caller,
         operations: Vec<WriteOperation>,
     ) -> Result<(), RuntimeError> {
-        WriteBatch::write_batch(&mut caller.user_data_mut().runtime, Batch { operations })
-            .map_err(|error| RuntimeError::Custom(error.into()))
+        WriteBatch::write_batch(
+            &mut caller.user_data_mut().runtime,
+            Batch {
+                operations,
+                ..Batch::default()
+            },
+        )
+        .map_err(|error| RuntimeError::Custom(error.into()))
     }
 }
 
 // TODO(#1977): Remove once the WIT interface does not include `write-batch` in the service system
 // API
+/// Forwards a log `message` from an application to [`tracing`].
+///
+/// The event is emitted inside an `application` span recording the application ID, so that
+/// the logs of a single application can be selected with a filter such as
+/// `RUST_LOG='linera_execution[application{id=<ID>}]=debug'`.
+fn log_application_message(application_id: ApplicationId, message: &str, level: log::Level) {
+    let _span = tracing::info_span!("application", id = %application_id).entered();
+    match level {
+        log::Level::Trace => tracing::trace!("{message}"),
+        log::Level::Debug => tracing::debug!("{message}"),
+        log::Level::Info => tracing::info!("{message}"),
+        log::Level::Warn => tracing::warn!("{message}"),
+        log::Level::Error => tracing::error!("{message}"),
+    }
+}
+
 /// An extension trait to separate the behavior between the contract runtime and the service
 /// runtime.
 pub trait WriteBatch {
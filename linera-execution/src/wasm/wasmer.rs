@@ -3,25 +3,32 @@
 
 //! Code specific to the usage of the [Wasmer](https://wasmer.io/) runtime.
 
-use std::{marker::Unpin, sync::LazyLock};
+use std::{
+    marker::Unpin,
+    sync::{LazyLock, Mutex},
+};
 
-use linera_base::data_types::Bytecode;
+use linera_base::{crypto::CryptoHash, data_types::Bytecode};
 use linera_witty::{
     wasmer::{EntrypointInstance, InstanceBuilder},
     ExportTo,
 };
-use tokio::sync::Mutex;
-use wasm_instrument::{gas_metering, parity_wasm};
 
 use super::{
+    contract_bytecode_hash,
+    limits::InstanceSize,
+    metering::{add_metering, add_service_metering},
     module_cache::ModuleCache,
+    read_service_abi_version,
     system_api::{ContractSystemApi, ServiceSystemApi, SystemApiData, ViewSystemApi, WriteBatch},
     ContractEntrypoints, ServiceEntrypoints, WasmExecutionError,
 };
+#[cfg(not(web))]
+use super::{contract_module_source, disk_cache};
 use crate::{
-    wasm::{WasmContractModule, WasmServiceModule},
-    ContractRuntime, ExecutionError, FinalizeContext, MessageContext, OperationContext,
-    QueryContext, ServiceRuntime,
+    wasm::{CachedContract, ContractCacheKey, WasmContractModule, WasmServiceModule},
+    ContractRuntime, ExecutionError, FinalizeContext, FuelSchedule, MessageContext,
+    OperationContext, QueryContext, ServiceRuntime,
 };
 
 /// An [`Engine`] instance configured to run application services.
@@ -37,12 +44,29 @@ static SERVICE_ENGINE: LazyLock<wasmer::Engine> = LazyLock::new(|| {
     }
 });
 
+/// The kind under which compiled contract modules are persisted in the disk cache.
+#[cfg(not(web))]
+const CONTRACT_MODULE_KIND: &str = "wasmer-contract";
+
+/// The kind under which compiled service modules are persisted in the disk cache.
+#[cfg(not(web))]
+const SERVICE_MODULE_KIND: &str = "wasmer-instrumented-service";
+
+/// The version of the engine compiling the modules persisted in the disk cache.
+#[cfg(not(web))]
+const ENGINE_VERSION: &str = wasmer::VERSION;
+
 /// A cache of compiled contract modules, with their respective [`wasmer::Engine`] instances.
-static CONTRACT_CACHE: LazyLock<Mutex<ModuleCache<CachedContractModule>>> =
-    LazyLock::new(Mutex::default);
+///
+/// The cache is indexed by the hash of the bytecode and by the [`FuelSchedule`] of its fuel
+/// metering, so that modules compiled with different schedules are kept apart.
+static CONTRACT_CACHE: LazyLock<
+    Mutex<ModuleCache<CachedContract<CachedContractModule>, ContractCacheKey>>,
+> = LazyLock::new(Mutex::default);
 
 /// A cache of compiled service modules.
-static SERVICE_CACHE: LazyLock<Mutex<ModuleCache<wasmer::Module>>> = LazyLock::new(Mutex::default);
+static SERVICE_CACHE: LazyLock<tokio::sync::Mutex<ModuleCache<wasmer::Module>>> =
+    LazyLock::new(tokio::sync::Mutex::default);
 
 /// Type representing a running [Wasmer](https://wasmer.io/) contract.
 pub(crate) struct WasmerContractInstance<Runtime> {
@@ -58,14 +82,45 @@ pub struct WasmerServiceInstance<Runtime> {
 
 impl WasmContractModule {
     /// Creates a new [`WasmContractModule`] using Wasmer with the provided bytecodes.
-    pub async fn from_wasmer(contract_bytecode: Bytecode) -> Result<Self, WasmExecutionError> {
-        let mut contract_cache = CONTRACT_CACHE.lock().await;
-        let (engine, module) = contract_cache
-            .get_or_insert_with(contract_bytecode, CachedContractModule::new)
-            .map_err(WasmExecutionError::LoadContractModule)?
+    pub async fn from_wasmer(
+        contract_bytecode: Bytecode,
+        fuel_schedule: FuelSchedule,
+    ) -> Result<Self, WasmExecutionError> {
+        let bytecode_hash = contract_bytecode_hash(&contract_bytecode);
+        Self::from_wasmer_with_schedule(contract_bytecode, bytecode_hash, fuel_schedule)
+    }
+
+    /// Creates a new [`WasmContractModule`] using Wasmer with the provided bytecodes, whose
+    /// hash is `bytecode_hash`, consuming fuel according to the `fuel_schedule`.
+    pub(super) fn from_wasmer_with_schedule(
+        contract_bytecode: Bytecode,
+        bytecode_hash: CryptoHash,
+        fuel_schedule: FuelSchedule,
+    ) -> Result<Self, WasmExecutionError> {
+        let CachedContract {
+            module: cached_module,
+            abi_version,
+            instance_size,
+        } = CachedContract::load(
+            &CONTRACT_CACHE,
+            &contract_bytecode,
+            bytecode_hash,
+            fuel_schedule,
+            CachedContractModule::new,
+        )
+        .map_err(WasmExecutionError::LoadContractModule)?;
+        let (engine, module) = cached_module
             .create_execution_instance()
             .map_err(WasmExecutionError::LoadContractModule)?;
-        Ok(WasmContractModule::Wasmer { engine, module })
+        Ok(WasmContractModule::Wasmer {
+            engine,
+            module,
+            bytecode: contract_bytecode,
+            bytecode_hash,
+            fuel_schedule,
+            abi_version,
+            instance_size,
+        })
     }
 }
 
@@ -78,8 +133,9 @@ where
         contract_engine: wasmer::Engine,
         contract_module: &wasmer::Module,
         runtime: Runtime,
+        instance_size: InstanceSize,
     ) -> Result<Self, WasmExecutionError> {
-        let system_api_data = SystemApiData::new(runtime);
+        let system_api_data = SystemApiData::new(runtime, instance_size.memory_pages);
         let mut instance_builder = InstanceBuilder::new(contract_engine, system_api_data);
 
         ContractSystemApi::export_to(&mut instance_builder)?;
@@ -94,16 +150,87 @@ where
 impl WasmServiceModule {
     /// Creates a new [`WasmServiceModule`] using Wasmer with the provided bytecodes.
     pub async fn from_wasmer(service_bytecode: Bytecode) -> Result<Self, WasmExecutionError> {
+        let abi_version = read_service_abi_version(&service_bytecode);
+        let instance_size =
+            InstanceSize::of(&service_bytecode).map_err(WasmExecutionError::LoadServiceModule)?;
         let mut service_cache = SERVICE_CACHE.lock().await;
         let module = service_cache
-            .get_or_insert_with(service_bytecode, |bytecode| {
-                wasmer::Module::new(&*SERVICE_ENGINE, bytecode).map_err(anyhow::Error::from)
-            })
+            .get_or_insert_with(service_bytecode, compile_service_module)
             .map_err(WasmExecutionError::LoadServiceModule)?;
-        Ok(WasmServiceModule::Wasmer { module })
+        Ok(WasmServiceModule::Wasmer {
+            module,
+            abi_version,
+            instance_size,
+        })
     }
 }
 
+/// Compiles a service `bytecode` instrumented with memory tracking and fuel metering, reusing the module persisted
+/// in the disk cache if there is one.
+fn compile_service_module(bytecode: Bytecode) -> anyhow::Result<wasmer::Module> {
+    #[cfg(not(web))]
+    {
+        disk_cache::load_or_compile(
+            SERVICE_MODULE_KIND,
+            ENGINE_VERSION,
+            &bytecode,
+            || {
+                let metered_bytecode = add_service_metering(&bytecode)?;
+                Ok(wasmer::Module::new(&*SERVICE_ENGINE, metered_bytecode)?)
+            },
+            |module| Ok(module.serialize()?.to_vec()),
+            // SAFETY: Wasmer only rejects artifacts from incompatible versions, and can't check
+            // that the artifact is a valid module. This relies on the disk cache directory being
+            // trusted, i.e. only writable by this node, as required by `--wasm-module-cache-dir`.
+            |artifact| Ok(unsafe { wasmer::Module::deserialize(&*SERVICE_ENGINE, artifact) }?),
+        )
+    }
+
+    #[cfg(web)]
+    {
+        let metered_bytecode = add_service_metering(&bytecode)?;
+        wasmer::Module::new(&*SERVICE_ENGINE, metered_bytecode).map_err(anyhow::Error::from)
+    }
+}
+
+/// Loads the modules persisted in the disk cache into the in-memory caches.
+///
+/// Returns the number of modules that were loaded.
+#[cfg(with_fs)]
+pub(super) async fn warm_up_caches() -> usize {
+    let mut loaded_modules = 0;
+
+    {
+        let mut contract_cache = CONTRACT_CACHE
+            .lock()
+            .expect("Contract module cache should not be poisoned");
+        for (source, artifact) in
+            disk_cache::persisted_modules(CONTRACT_MODULE_KIND, ENGINE_VERSION)
+        {
+            // SAFETY: See `CachedContractModule::new`.
+            let deserialize = || {
+                let engine = wasmer::Engine::default();
+                let module = unsafe { wasmer::Module::deserialize(&engine, artifact) }?;
+                Ok(CachedContractModule(module))
+            };
+            if CachedContract::warm_up(&mut contract_cache, source, deserialize) {
+                loaded_modules += 1;
+            }
+        }
+    }
+
+    let mut service_cache = SERVICE_CACHE.lock().await;
+    for (bytecode, artifact) in disk_cache::persisted_modules(SERVICE_MODULE_KIND, ENGINE_VERSION) {
+        // SAFETY: See `compile_service_module`.
+        if let Ok(module) = unsafe { wasmer::Module::deserialize(&*SERVICE_ENGINE, artifact) } {
+            service_cache.insert(bytecode, module);
+            loaded_modules += 1;
+        }
+    }
+
+    loaded_modules
+}
+
 impl<Runtime> WasmerServiceInstance<Runtime>
 where
     Runtime: ServiceRuntime + WriteBatch + Clone + Unpin + 'static,
@@ -112,8 +239,9 @@ where
     pub fn prepare(
         service_module: &wasmer::Module,
         runtime: Runtime,
+        instance_size: InstanceSize,
     ) -> Result<Self, WasmExecutionError> {
-        let system_api_data = SystemApiData::new(runtime);
+        let system_api_data = SystemApiData::new(runtime, instance_size.memory_pages);
         let mut instance_builder = InstanceBuilder::new(SERVICE_ENGINE.clone(), system_api_data);
 
         ServiceSystemApi::export_to(&mut instance_builder)?;
@@ -145,9 +273,10 @@ where
         _context: OperationContext,
         operation: Vec<u8>,
     ) -> Result<Vec<u8>, ExecutionError> {
-        Ok(ContractEntrypoints::new(&mut self.instance)
+        ContractEntrypoints::new(&mut self.instance)
             .execute_operation(operation)
-            .map_err(WasmExecutionError::from)?)
+            .map_err(WasmExecutionError::from)?
+            .map_err(ExecutionError::ApplicationError)
     }
 
     fn execute_message(
@@ -161,12 +290,30 @@ where
         Ok(())
     }
 
+    fn execute_callback(
+        &mut self,
+        _context: OperationContext,
+        argument: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        ContractEntrypoints::new(&mut self.instance)
+            .execute_callback(argument)
+            .map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
+
     fn finalize(&mut self, _context: FinalizeContext) -> Result<(), ExecutionError> {
         ContractEntrypoints::new(&mut self.instance)
             .finalize()
             .map_err(WasmExecutionError::from)?;
         Ok(())
     }
+
+    fn migrate(&mut self, old_version: u32) -> Result<(), ExecutionError> {
+        ContractEntrypoints::new(&mut self.instance)
+            .migrate(old_version)
+            .map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
 }
 
 impl<Runtime: 'static> crate::UserService for WasmerServiceInstance<Runtime> {
@@ -202,54 +349,36 @@ impl From<wasmer::RuntimeError> for ExecutionError {
 #[derive(Clone)]
 pub struct CachedContractModule(wasmer::Module);
 
-pub fn add_metering(bytecode: Bytecode) -> anyhow::Result<Bytecode> {
-    struct WasmtimeRules;
-
-    impl gas_metering::Rules for WasmtimeRules {
-        /// Calculates the fuel cost of a WebAssembly [`Operator`].
-        ///
-        /// The rules try to follow the hardcoded [rules in the Wasmtime runtime
-        /// engine](https://docs.rs/wasmtime/5.0.0/wasmtime/struct.Store.html#method.add_fuel).
-        fn instruction_cost(
-            &self,
-            instruction: &parity_wasm::elements::Instruction,
-        ) -> Option<u32> {
-            use parity_wasm::elements::Instruction::*;
-
-            Some(match instruction {
-                Nop | Drop | Block(_) | Loop(_) | Unreachable | Else | End => 0,
-                _ => 1,
-            })
-        }
-
-        fn memory_grow_cost(&self) -> gas_metering::MemoryGrowCost {
-            gas_metering::MemoryGrowCost::Free
-        }
-
-        fn call_per_local_cost(&self) -> u32 {
-            0
-        }
-    }
-
-    let instrumented_module = gas_metering::inject(
-        parity_wasm::deserialize_buffer(&bytecode.bytes)?,
-        gas_metering::host_function::Injector::new(
-            "linera:app/contract-system-api",
-            "consume-fuel",
-        ),
-        &WasmtimeRules,
-    )
-    .map_err(|_| anyhow::anyhow!("failed to instrument module"))?;
-
-    Ok(Bytecode::new(instrumented_module.into_bytes()?))
-}
-
 impl CachedContractModule {
-    /// Creates a new [`CachedContractModule`] by compiling a `contract_bytecode`.
-    pub fn new(contract_bytecode: Bytecode) -> Result<Self, anyhow::Error> {
+    /// Creates a new [`CachedContractModule`] by compiling a `contract_bytecode`, after
+    /// instrumenting it with fuel metering according to the `fuel_schedule`.
+    pub fn new(
+        contract_bytecode: &Bytecode,
+        fuel_schedule: &FuelSchedule,
+    ) -> Result<Self, anyhow::Error> {
+        #[cfg(not(web))]
+        let module = disk_cache::load_or_compile(
+            CONTRACT_MODULE_KIND,
+            ENGINE_VERSION,
+            &contract_module_source(contract_bytecode, fuel_schedule),
+            || {
+                Ok(wasmer::Module::new(
+                    &Self::create_compilation_engine(),
+                    add_metering(contract_bytecode, fuel_schedule)?,
+                )?)
+            },
+            |module| Ok(module.serialize()?.to_vec()),
+            // SAFETY: Wasmer only rejects artifacts from incompatible versions, and can't check
+            // that the artifact is a valid module. This relies on the disk cache directory being
+            // trusted, i.e. only writable by this node, as required by `--wasm-module-cache-dir`.
+            |artifact| {
+                Ok(unsafe { wasmer::Module::deserialize(&wasmer::Engine::default(), artifact) }?)
+            },
+        )?;
+        #[cfg(web)]
         let module = wasmer::Module::new(
             &Self::create_compilation_engine(),
-            add_metering(contract_bytecode)?,
+            add_metering(contract_bytecode, fuel_schedule)?,
         )?;
         Ok(CachedContractModule(module))
     }
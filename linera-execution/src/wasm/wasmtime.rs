@@ -3,42 +3,89 @@
 
 //! Code specific to the usage of the [Wasmtime](https://wasmtime.dev/) runtime.
 
-use std::sync::LazyLock;
+use std::{
+    hash::{Hash as _, Hasher},
+    sync::{LazyLock, Mutex},
+    thread,
+    time::Duration,
+};
 
-use linera_base::data_types::Bytecode;
+use linera_base::{crypto::CryptoHash, data_types::Bytecode};
 use linera_witty::{wasmtime::EntrypointInstance, ExportTo, Instance};
-use tokio::sync::Mutex;
+use sha3::{Digest as _, Sha3_256};
 use wasmtime::{AsContextMut, Config, Engine, Linker, Module, Store};
 
 use super::{
+    contract_bytecode_hash, contract_module_source, disk_cache,
+    limits::{add_memory_tracking, InstanceSize},
+    metering::add_metering,
     module_cache::ModuleCache,
+    read_service_abi_version,
     system_api::{ContractSystemApi, ServiceSystemApi, SystemApiData, ViewSystemApi, WriteBatch},
     ContractEntrypoints, ServiceEntrypoints, WasmExecutionError,
 };
 use crate::{
-    wasm::{WasmContractModule, WasmServiceModule},
-    ContractRuntime, ExecutionError, FinalizeContext, MessageContext, OperationContext,
-    QueryContext, ServiceRuntime,
+    wasm::{CachedContract, ContractCacheKey, WasmContractModule, WasmServiceModule},
+    ContractRuntime, ExecutionError, FinalizeContext, FuelSchedule, MessageContext,
+    OperationContext, QueryContext, ServiceRuntime,
 };
 
 /// An [`Engine`] instance configured to run application contracts.
+///
+/// Contracts consume fuel through the metering instrumentation of their bytecode, like with
+/// Wasmer, so the built-in fuel consumption of Wasmtime is disabled.
 static CONTRACT_ENGINE: LazyLock<Engine> = LazyLock::new(|| {
     let mut config = Config::default();
-    config
-        .consume_fuel(true)
-        .cranelift_nan_canonicalization(true);
+    config.cranelift_nan_canonicalization(true);
 
     Engine::new(&config).expect("Failed to create Wasmtime `Engine` for contracts")
 });
 
 /// An [`Engine`] instance configured to run application services.
-static SERVICE_ENGINE: LazyLock<Engine> = LazyLock::new(Engine::default);
+///
+/// Services are interrupted when their query's deadline expires: a background thread
+/// increments the epoch of the engine every [`EPOCH_TICK`], and each query sets the epoch at
+/// which it traps.
+static SERVICE_ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    let mut config = Config::default();
+    config.consume_fuel(true).epoch_interruption(true);
+
+    let engine = Engine::new(&config).expect("Failed to create Wasmtime `Engine` for services");
+    let ticking_engine = engine.clone();
+    thread::Builder::new()
+        .name("wasmtime-service-epochs".to_owned())
+        .spawn(move || loop {
+            thread::sleep(EPOCH_TICK);
+            ticking_engine.increment_epoch();
+        })
+        .expect("Failed to spawn the thread incrementing the Wasmtime epochs");
+    engine
+});
+
+/// The interval at which the epoch of the [`SERVICE_ENGINE`] is incremented, i.e. the
+/// precision with which the deadlines of queries are enforced.
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// The number of epochs after which a query without a deadline is interrupted, which is never
+/// reached in practice, but doesn't overflow when added to the current epoch.
+const MAX_EPOCH_DEADLINE: u64 = u64::MAX / 2;
+
+/// The kind under which compiled contract modules are persisted in the disk cache.
+const CONTRACT_MODULE_KIND: &str = "wasmtime-metered-contract";
+
+/// The kind under which compiled service modules are persisted in the disk cache.
+const SERVICE_MODULE_KIND: &str = "wasmtime-interruptible-service";
 
 /// A cache of compiled contract modules.
-static CONTRACT_CACHE: LazyLock<Mutex<ModuleCache<Module>>> = LazyLock::new(Mutex::default);
+///
+/// The cache is indexed by the hash of the bytecode and by the [`FuelSchedule`] of its fuel
+/// metering, so that modules compiled with different schedules are kept apart.
+static CONTRACT_CACHE: LazyLock<Mutex<ModuleCache<CachedContract<Module>, ContractCacheKey>>> =
+    LazyLock::new(Mutex::default);
 
 /// A cache of compiled service modules.
-static SERVICE_CACHE: LazyLock<Mutex<ModuleCache<Module>>> = LazyLock::new(Mutex::default);
+static SERVICE_CACHE: LazyLock<tokio::sync::Mutex<ModuleCache<Module>>> =
+    LazyLock::new(tokio::sync::Mutex::default);
 
 /// Type representing a running [Wasmtime](https://wasmtime.dev/) contract.
 ///
@@ -50,15 +97,20 @@ where
 {
     /// The Wasm module instance.
     instance: EntrypointInstance<SystemApiData<Runtime>>,
+}
+
+/// Type representing a running [Wasmtime](https://wasmtime.dev/) service.
+pub struct WasmtimeServiceInstance<Runtime> {
+    /// The Wasm module instance.
+    instance: EntrypointInstance<SystemApiData<Runtime>>,
 
     /// The starting amount of fuel.
     initial_fuel: u64,
 }
 
-// TODO(#1967): Remove once fuel consumption is instrumented in the bytecode
-impl<Runtime> WasmtimeContractInstance<Runtime>
+impl<Runtime> WasmtimeServiceInstance<Runtime>
 where
-    Runtime: ContractRuntime,
+    Runtime: ServiceRuntime,
 {
     fn configure_initial_fuel(&mut self) -> Result<(), ExecutionError> {
         let runtime = &mut self.instance.user_data_mut().runtime_mut();
@@ -74,6 +126,22 @@ where
         Ok(())
     }
 
+    /// Makes the instance trap once the query's deadline has expired.
+    fn configure_deadline(&mut self) -> Result<(), ExecutionError> {
+        let runtime = &mut self.instance.user_data_mut().runtime_mut();
+        let epochs = match runtime.remaining_query_time()? {
+            Some(remaining_time) => {
+                let epochs = remaining_time.as_nanos().div_ceil(EPOCH_TICK.as_nanos());
+                u64::try_from(epochs)
+                    .unwrap_or(MAX_EPOCH_DEADLINE)
+                    .min(MAX_EPOCH_DEADLINE)
+            }
+            None => MAX_EPOCH_DEADLINE,
+        };
+        self.instance.as_context_mut().set_epoch_deadline(epochs);
+        Ok(())
+    }
+
     fn persist_remaining_fuel(&mut self) -> Result<(), ExecutionError> {
         let remaining_fuel = self
             .instance
@@ -82,28 +150,61 @@ where
             .expect("Failed to read remaining fuel");
         let runtime = &mut self.instance.user_data_mut().runtime_mut();
 
-        assert!(self.initial_fuel >= remaining_fuel);
+        let consumed_fuel = self.initial_fuel.checked_sub(remaining_fuel).ok_or(
+            ExecutionError::InvalidRemainingFuel {
+                initial: self.initial_fuel,
+                remaining: remaining_fuel,
+            },
+        )?;
 
-        runtime.consume_fuel(self.initial_fuel - remaining_fuel)
+        runtime.consume_fuel(consumed_fuel)
     }
 }
 
-/// Type representing a running [Wasmtime](https://wasmtime.dev/) service.
-pub struct WasmtimeServiceInstance<Runtime> {
-    /// The Wasm module instance.
-    instance: EntrypointInstance<SystemApiData<Runtime>>,
-}
-
 impl WasmContractModule {
     /// Creates a new [`WasmContractModule`] using Wasmtime with the provided bytecodes.
-    pub async fn from_wasmtime(contract_bytecode: Bytecode) -> Result<Self, WasmExecutionError> {
-        let mut contract_cache = CONTRACT_CACHE.lock().await;
-        let module = contract_cache
-            .get_or_insert_with(contract_bytecode, |bytecode| {
-                Module::new(&CONTRACT_ENGINE, bytecode)
-            })
-            .map_err(WasmExecutionError::LoadContractModule)?;
-        Ok(WasmContractModule::Wasmtime { module })
+    pub async fn from_wasmtime(
+        contract_bytecode: Bytecode,
+        fuel_schedule: FuelSchedule,
+    ) -> Result<Self, WasmExecutionError> {
+        let bytecode_hash = contract_bytecode_hash(&contract_bytecode);
+        Self::from_wasmtime_with_schedule(contract_bytecode, bytecode_hash, fuel_schedule)
+    }
+
+    /// Creates a new [`WasmContractModule`] using Wasmtime with the provided bytecodes, whose
+    /// hash is `bytecode_hash`, consuming fuel according to the `fuel_schedule`.
+    pub(super) fn from_wasmtime_with_schedule(
+        contract_bytecode: Bytecode,
+        bytecode_hash: CryptoHash,
+        fuel_schedule: FuelSchedule,
+    ) -> Result<Self, WasmExecutionError> {
+        let CachedContract {
+            module,
+            abi_version,
+            instance_size,
+        } = CachedContract::load(
+            &CONTRACT_CACHE,
+            &contract_bytecode,
+            bytecode_hash,
+            fuel_schedule,
+            |bytecode, fuel_schedule| {
+                compile_module(
+                    CONTRACT_MODULE_KIND,
+                    &CONTRACT_ENGINE,
+                    &contract_module_source(bytecode, fuel_schedule),
+                    || add_metering(bytecode, fuel_schedule),
+                )
+            },
+        )
+        .map_err(WasmExecutionError::LoadContractModule)?;
+        Ok(WasmContractModule::Wasmtime {
+            module,
+            bytecode: contract_bytecode,
+            bytecode_hash,
+            fuel_schedule,
+            abi_version,
+            instance_size,
+        })
     }
 }
 
@@ -112,13 +213,17 @@ where
     Runtime: ContractRuntime + WriteBatch + 'static,
 {
     /// Prepares a runtime instance to call into the Wasm contract.
-    pub fn prepare(contract_module: &Module, runtime: Runtime) -> Result<Self, WasmExecutionError> {
+    pub fn prepare(
+        contract_module: &Module,
+        runtime: Runtime,
+        instance_size: InstanceSize,
+    ) -> Result<Self, WasmExecutionError> {
         let mut linker = Linker::new(&CONTRACT_ENGINE);
 
         ContractSystemApi::export_to(&mut linker)?;
         ViewSystemApi::export_to(&mut linker)?;
 
-        let user_data = SystemApiData::new(runtime);
+        let user_data = SystemApiData::new(runtime, instance_size.memory_pages);
         let mut store = Store::new(&CONTRACT_ENGINE, user_data);
         let instance = linker
             .instantiate(&mut store, contract_module)
@@ -126,7 +231,6 @@ where
 
         Ok(Self {
             instance: EntrypointInstance::new(instance, store),
-            initial_fuel: 0,
         })
     }
 }
@@ -134,35 +238,132 @@ where
 impl WasmServiceModule {
     /// Creates a new [`WasmServiceModule`] using Wasmtime with the provided bytecodes.
     pub async fn from_wasmtime(service_bytecode: Bytecode) -> Result<Self, WasmExecutionError> {
+        let abi_version = read_service_abi_version(&service_bytecode);
+        let instance_size =
+            InstanceSize::of(&service_bytecode).map_err(WasmExecutionError::LoadServiceModule)?;
         let mut service_cache = SERVICE_CACHE.lock().await;
         let module = service_cache
             .get_or_insert_with(service_bytecode, |bytecode| {
-                Module::new(&SERVICE_ENGINE, bytecode)
+                compile_module(SERVICE_MODULE_KIND, &SERVICE_ENGINE, &bytecode, || {
+                    add_memory_tracking(&bytecode, "linera:app/service-system-api")
+                })
             })
             .map_err(WasmExecutionError::LoadServiceModule)?;
-        Ok(WasmServiceModule::Wasmtime { module })
+        Ok(WasmServiceModule::Wasmtime {
+            module,
+            abi_version,
+            instance_size,
+        })
     }
 }
 
+/// Compiles the bytecode returned by `instrument` with the `engine`, reusing the module
+/// persisted in the disk cache under `kind` and `source` if there is one.
+fn compile_module(
+    kind: &str,
+    engine: &Engine,
+    source: &Bytecode,
+    instrument: impl FnOnce() -> anyhow::Result<Bytecode>,
+) -> anyhow::Result<Module> {
+    disk_cache::load_or_compile(
+        kind,
+        &engine_version(engine),
+        source,
+        || Module::new(engine, instrument()?),
+        Module::serialize,
+        // SAFETY: Wasmtime only rejects artifacts from incompatible engines, and can't check that
+        // the artifact is a valid module. This relies on the disk cache directory being trusted,
+        // i.e. only writable by this node, as required by `--wasm-module-cache-dir`.
+        |artifact| unsafe { Module::deserialize(engine, artifact) },
+    )
+}
+
+/// Returns the version of the `engine` under which its modules are persisted in the disk cache.
+///
+/// This changes with the version of Wasmtime and with the settings that affect the compiled
+/// artifacts.
+fn engine_version(engine: &Engine) -> String {
+    let mut hasher = StableHasher(Sha3_256::new());
+    engine.precompile_compatibility_hash().hash(&mut hasher);
+    linera_base::hex::encode(hasher.0.finalize())
+}
+
+/// A [`Hasher`] computing a SHA-3 digest, which unlike [`std::hash::DefaultHasher`] is the same
+/// across Rust versions and processes.
+struct StableHasher(Sha3_256);
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        let digest = self.0.clone().finalize();
+        u64::from_le_bytes(digest[..8].try_into().expect("SHA-3 digests have 32 bytes"))
+    }
+}
+
+/// Loads the modules persisted in the disk cache into the in-memory caches.
+///
+/// Returns the number of modules that were loaded.
+#[cfg(with_fs)]
+pub(super) async fn warm_up_caches() -> usize {
+    let mut loaded_modules = 0;
+
+    {
+        let mut contract_cache = CONTRACT_CACHE
+            .lock()
+            .expect("Contract module cache should not be poisoned");
+        for (source, artifact) in
+            disk_cache::persisted_modules(CONTRACT_MODULE_KIND, &engine_version(&CONTRACT_ENGINE))
+        {
+            // SAFETY: See `compile_module`.
+            let deserialize = || unsafe { Module::deserialize(&CONTRACT_ENGINE, artifact) };
+            if CachedContract::warm_up(&mut contract_cache, source, deserialize) {
+                loaded_modules += 1;
+            }
+        }
+    }
+
+    let mut service_cache = SERVICE_CACHE.lock().await;
+    for (bytecode, artifact) in
+        disk_cache::persisted_modules(SERVICE_MODULE_KIND, &engine_version(&SERVICE_ENGINE))
+    {
+        // SAFETY: See `compile_module`.
+        if let Ok(module) = unsafe { Module::deserialize(&SERVICE_ENGINE, artifact) } {
+            service_cache.insert(bytecode, module);
+            loaded_modules += 1;
+        }
+    }
+
+    loaded_modules
+}
+
 impl<Runtime> WasmtimeServiceInstance<Runtime>
 where
     Runtime: ServiceRuntime + WriteBatch + 'static,
 {
     /// Prepares a runtime instance to call into the Wasm service.
-    pub fn prepare(service_module: &Module, runtime: Runtime) -> Result<Self, WasmExecutionError> {
+    pub fn prepare(
+        service_module: &Module,
+        runtime: Runtime,
+        instance_size: InstanceSize,
+    ) -> Result<Self, WasmExecutionError> {
         let mut linker = Linker::new(&SERVICE_ENGINE);
 
         ServiceSystemApi::export_to(&mut linker)?;
         ViewSystemApi::export_to(&mut linker)?;
 
-        let user_data = SystemApiData::new(runtime);
+        let user_data = SystemApiData::new(runtime, instance_size.memory_pages);
         let mut store = Store::new(&SERVICE_ENGINE, user_data);
+        store.set_epoch_deadline(MAX_EPOCH_DEADLINE);
         let instance = linker
             .instantiate(&mut store, service_module)
             .map_err(WasmExecutionError::LoadServiceModule)?;
 
         Ok(Self {
             instance: EntrypointInstance::new(instance, store),
+            initial_fuel: 0,
         })
     }
 }
@@ -176,10 +377,9 @@ where
         _context: OperationContext,
         argument: Vec<u8>,
     ) -> Result<(), ExecutionError> {
-        self.configure_initial_fuel()?;
-        let result = ContractEntrypoints::new(&mut self.instance).instantiate(argument);
-        self.persist_remaining_fuel()?;
-        result.map_err(WasmExecutionError::from)?;
+        ContractEntrypoints::new(&mut self.instance)
+            .instantiate(argument)
+            .map_err(WasmExecutionError::from)?;
         Ok(())
     }
 
@@ -188,10 +388,10 @@ where
         _context: OperationContext,
         operation: Vec<u8>,
     ) -> Result<Vec<u8>, ExecutionError> {
-        self.configure_initial_fuel()?;
-        let result = ContractEntrypoints::new(&mut self.instance).execute_operation(operation);
-        self.persist_remaining_fuel()?;
-        Ok(result.map_err(WasmExecutionError::from)?)
+        ContractEntrypoints::new(&mut self.instance)
+            .execute_operation(operation)
+            .map_err(WasmExecutionError::from)?
+            .map_err(ExecutionError::ApplicationError)
     }
 
     fn execute_message(
@@ -199,18 +399,34 @@ where
         _context: MessageContext,
         message: Vec<u8>,
     ) -> Result<(), ExecutionError> {
-        self.configure_initial_fuel()?;
-        let result = ContractEntrypoints::new(&mut self.instance).execute_message(message);
-        self.persist_remaining_fuel()?;
-        result.map_err(WasmExecutionError::from)?;
+        ContractEntrypoints::new(&mut self.instance)
+            .execute_message(message)
+            .map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
+
+    fn execute_callback(
+        &mut self,
+        _context: OperationContext,
+        argument: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        ContractEntrypoints::new(&mut self.instance)
+            .execute_callback(argument)
+            .map_err(WasmExecutionError::from)?;
         Ok(())
     }
 
     fn finalize(&mut self, _context: FinalizeContext) -> Result<(), ExecutionError> {
-        self.configure_initial_fuel()?;
-        let result = ContractEntrypoints::new(&mut self.instance).finalize();
-        self.persist_remaining_fuel()?;
-        result.map_err(WasmExecutionError::from)?;
+        ContractEntrypoints::new(&mut self.instance)
+            .finalize()
+            .map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
+
+    fn migrate(&mut self, old_version: u32) -> Result<(), ExecutionError> {
+        ContractEntrypoints::new(&mut self.instance)
+            .migrate(old_version)
+            .map_err(WasmExecutionError::from)?;
         Ok(())
     }
 }
@@ -224,8 +440,10 @@ where
         _context: QueryContext,
         argument: Vec<u8>,
     ) -> Result<Vec<u8>, ExecutionError> {
-        Ok(ServiceEntrypoints::new(&mut self.instance)
-            .handle_query(argument)
-            .map_err(WasmExecutionError::from)?)
+        self.configure_initial_fuel()?;
+        self.configure_deadline()?;
+        let result = ServiceEntrypoints::new(&mut self.instance).handle_query(argument);
+        self.persist_remaining_fuel()?;
+        Ok(result.map_err(WasmExecutionError::from)?)
     }
 }
@@ -5,6 +5,7 @@
 
 use std::{
     collections::{BTreeMap, BTreeSet},
+    sync::Arc,
     vec,
 };
 
@@ -13,21 +14,23 @@ use assert_matches::assert_matches;
 use linera_base::{
     crypto::CryptoHash,
     data_types::{
-        Amount, Blob, BlockHeight, CompressedBytecode, Timestamp, UserApplicationDescription,
+        Amount, Blob, BlockHeight, CompressedBytecode, OracleResponse, Timestamp,
+        UserApplicationDescription,
     },
     identifiers::{
         Account, AccountOwner, ApplicationId, BytecodeId, ChainDescription, ChainId, MessageId,
-        Owner,
+        Owner, StreamName,
     },
     ownership::ChainOwnership,
 };
 use linera_execution::{
+    system::{ScheduledCallback, SystemOperation, FAILED_CALLBACKS_STREAM},
     test_utils::{
         create_dummy_message_context, create_dummy_operation_context, test_accounts_strategy,
         ExpectedCall, RegisterMockApplication, SystemExecutionState,
     },
     BaseRuntime, ContractRuntime, ExecutionError, ExecutionOutcome, Message, MessageContext,
-    Operation, OperationContext, ResourceController, SystemExecutionError,
+    Operation, OperationContext, ResourceControlPolicy, ResourceController, SystemExecutionError,
     SystemExecutionStateView, TestExecutionRuntimeContext, TransactionTracker,
 };
 use linera_views::context::MemoryContext;
@@ -620,6 +623,511 @@ async fn test_read_balance_owners_system_api(
     .unwrap();
 }
 
+/// Tests that a callback scheduled with the contract system API is executed by the first
+/// `ExecuteCallbacks` operation in a block whose timestamp is not earlier than the scheduled one.
+#[tokio::test]
+async fn test_schedule_callback_system_api() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+    let due_time = Timestamp::from(1_000);
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.schedule_callback(due_time, b"callback".to_vec())?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = OperationContext {
+        authenticated_signer: Some(Owner(CryptoHash::test_hash("signer"))),
+        ..create_dummy_operation_context()
+    };
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        operation,
+        &mut TransactionTracker::new(0, None),
+        &mut controller,
+    )
+    .await?;
+    assert_eq!(view.system.next_callback_time().await?, Some(due_time));
+    // Scheduling the callback is charged like an operation.
+    assert_eq!(controller.tracker.operations, 1);
+
+    // The callback is not due yet, so the application is not called.
+    view.system.timestamp.set(Timestamp::from(999));
+    view.execute_operation(
+        context,
+        Timestamp::from(999),
+        SystemOperation::ExecuteCallbacks.into(),
+        &mut TransactionTracker::new(0, None),
+        &mut controller,
+    )
+    .await?;
+    assert_eq!(view.system.next_callback_time().await?, Some(due_time));
+
+    application.expect_call(ExpectedCall::execute_callback(
+        |_runtime, context, argument| {
+            assert_eq!(context.authenticated_signer, None);
+            assert_eq!(argument, b"callback");
+            Ok(())
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    view.system.timestamp.set(due_time);
+    view.execute_operation(
+        context,
+        due_time,
+        SystemOperation::ExecuteCallbacks.into(),
+        &mut TransactionTracker::new(0, None),
+        &mut controller,
+    )
+    .await?;
+    assert_eq!(view.system.next_callback_time().await?, None);
+
+    Ok(())
+}
+
+/// Tests that a failing callback is dropped together with its changes and recorded as an event,
+/// without preventing the other due callbacks from running.
+#[tokio::test]
+async fn test_failed_callback_is_dropped() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+    let due_time = Timestamp::from(0);
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.schedule_callback(due_time, b"fail".to_vec())?;
+            runtime.schedule_callback(due_time, b"succeed".to_vec())?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::User {
+            application_id,
+            bytes: vec![],
+        },
+        &mut TransactionTracker::new(0, None),
+        &mut controller,
+    )
+    .await?;
+
+    application.expect_call(ExpectedCall::execute_callback(
+        move |runtime, _context, argument| {
+            assert_eq!(argument, b"fail");
+            runtime.schedule_callback(due_time, b"discarded".to_vec())?;
+            Err(ExecutionError::UserError("Callback failed".to_owned()))
+        },
+    ));
+    application.expect_call(ExpectedCall::execute_callback(
+        |_runtime, _context, argument| {
+            assert_eq!(argument, b"succeed");
+            Ok(())
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let mut txn_tracker = TransactionTracker::new(0, None);
+    view.execute_operation(
+        context,
+        due_time,
+        SystemOperation::ExecuteCallbacks.into(),
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    // The callback scheduled by the failed callback was discarded.
+    assert_eq!(view.system.next_callback_time().await?, None);
+
+    let failed_callback = ScheduledCallback {
+        application_id,
+        argument: b"fail".to_vec(),
+    };
+    let expected_event = (
+        FAILED_CALLBACKS_STREAM.into(),
+        Vec::new(),
+        bcs::to_bytes(&failed_callback)?,
+    );
+    let (outcomes, _, _) = txn_tracker.destructure()?;
+    assert!(outcomes.iter().any(|outcome| matches!(
+        outcome,
+        ExecutionOutcome::System(outcome) if outcome.events == [expected_event.clone()]
+    )));
+
+    Ok(())
+}
+
+/// Tests that storing the latest event of a stream is charged, and that event values are
+/// bounded.
+#[tokio::test]
+async fn test_emitted_events_are_charged() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        |runtime, _context, _operation| {
+            let name = StreamName(b"stream".to_vec());
+            runtime.emit(name.clone(), b"key".to_vec(), b"value".to_vec())?;
+            runtime.emit(name.clone(), b"key".to_vec(), b"longer value".to_vec())?;
+            assert_matches!(
+                runtime.emit(name, b"key".to_vec(), vec![0; 64 * 1024 + 1]),
+                Err(ExecutionError::EventValueTooLong)
+            );
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::User {
+            application_id,
+            bytes: vec![],
+        },
+        &mut TransactionTracker::new(0, None),
+        &mut controller,
+    )
+    .await?;
+
+    assert_eq!(controller.tracker.write_operations, 2);
+    assert_eq!(
+        controller.tracker.bytes_written,
+        ("stream".len() + "key".len()) as u64 * 2 + ("value".len() + "longer value".len()) as u64
+    );
+    // The second event only replaces the value of the first one.
+    assert_eq!(
+        controller.tracker.bytes_stored,
+        ("stream".len() + "key".len() + "longer value".len()) as i64
+    );
+
+    Ok(())
+}
+
+/// Tests that the oracle responses of a failed callback are still recorded, so that replaying the
+/// block gives the next callback its own responses.
+#[tokio::test]
+async fn test_failed_callback_records_oracle_responses() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+    let due_time = Timestamp::from(0);
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.schedule_callback(due_time, b"fail".to_vec())?;
+            runtime.schedule_callback(due_time, b"succeed".to_vec())?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::User {
+            application_id,
+            bytes: vec![],
+        },
+        &mut TransactionTracker::new(0, None),
+        &mut controller,
+    )
+    .await?;
+
+    application.expect_call(ExpectedCall::execute_callback(
+        |runtime, _context, _argument| {
+            runtime.validation_round()?;
+            Err(ExecutionError::UserError("Callback failed".to_owned()))
+        },
+    ));
+    application.expect_call(ExpectedCall::execute_callback(
+        |runtime, _context, _argument| {
+            runtime.validation_round()?;
+            Ok(())
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let mut txn_tracker = TransactionTracker::new(0, None);
+    view.execute_operation(
+        context,
+        due_time,
+        SystemOperation::ExecuteCallbacks.into(),
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+
+    let (_, oracle_responses, _) = txn_tracker.destructure()?;
+    assert_matches!(
+        oracle_responses.as_slice(),
+        [OracleResponse::Round(_), OracleResponse::Round(_)]
+    );
+
+    Ok(())
+}
+
+/// Tests that an application can only have a bounded number of callbacks scheduled at the same
+/// time, and that each `ExecuteCallbacks` operation executes a bounded number of them.
+#[tokio::test]
+async fn test_scheduled_callbacks_are_bounded() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+    let due_time = Timestamp::from(0);
+    let policy = ResourceControlPolicy {
+        maximum_scheduled_callbacks_per_application: 3,
+        maximum_callbacks_per_operation: 2,
+        ..ResourceControlPolicy::default()
+    };
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            for _ in 0..3 {
+                runtime.schedule_callback(due_time, vec![])?;
+            }
+            assert_matches!(
+                runtime.schedule_callback(due_time, vec![]),
+                Err(ExecutionError::SystemError(
+                    SystemExecutionError::TooManyScheduledCallbacks(id)
+                )) if *id == application_id
+            );
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController {
+        policy: Arc::new(policy),
+        ..ResourceController::default()
+    };
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::User {
+            application_id,
+            bytes: vec![],
+        },
+        &mut TransactionTracker::new(0, None),
+        &mut controller,
+    )
+    .await?;
+
+    for _ in 0..2 {
+        application.expect_call(ExpectedCall::execute_callback(
+            |_runtime, _context, _argument| Ok(()),
+        ));
+        application.expect_call(ExpectedCall::default_finalize());
+    }
+    view.execute_operation(
+        context,
+        due_time,
+        SystemOperation::ExecuteCallbacks.into(),
+        &mut TransactionTracker::new(0, None),
+        &mut controller,
+    )
+    .await?;
+    // The remaining callbacks are left for later operations.
+    assert_eq!(view.system.next_callback_time().await?, Some(due_time));
+
+    Ok(())
+}
+
+/// Tests that the contract system API to fetch a URL uses the recorded oracle response when
+/// replaying a block, and charges it as bytes read.
+#[cfg(feature = "unstable-oracles")]
+#[tokio::test]
+async fn test_fetch_url_system_api_replays_oracle_response() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+    let recorded_bytes = b"recorded response".to_vec();
+    let expected_bytes = recorded_bytes.clone();
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            assert_eq!(
+                runtime.fetch_url("http://localhost:1/unreachable")?,
+                expected_bytes
+            );
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut txn_tracker =
+        TransactionTracker::new(0, Some(vec![OracleResponse::Get(recorded_bytes.clone())]));
+
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        operation,
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+
+    assert_eq!(controller.tracker.bytes_read, recorded_bytes.len() as u64);
+    let (_, oracle_responses, _) = txn_tracker.destructure()?;
+    assert_eq!(oracle_responses, vec![OracleResponse::Get(recorded_bytes)]);
+
+    Ok(())
+}
+
+/// Tests that contracts can't fetch URLs unless unstable oracles are enabled.
+#[cfg(not(feature = "unstable-oracles"))]
+#[tokio::test]
+async fn test_fetch_url_system_api_requires_unstable_oracles() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            assert_matches!(
+                runtime.fetch_url("http://localhost:1/unreachable"),
+                Err(ExecutionError::UnstableOracle)
+            );
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut txn_tracker =
+        TransactionTracker::new(0, Some(vec![OracleResponse::Get(b"recorded".to_vec())]));
+
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        operation,
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Tests that a contract can only call an application with the version of the ABI that the
+/// application declares.
+#[tokio::test]
+async fn test_assert_abi_version_system_api() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (caller_id, caller_application) = view.register_mock_application().await?;
+    let (target_id, target_application) = view.register_mock_application().await?;
+    target_application.set_abi_version(3);
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.assert_abi_version(target_id, 3)?;
+            assert_matches!(
+                runtime.assert_abi_version(target_id, 2),
+                Err(ExecutionError::AbiVersionMismatch {
+                    application_id,
+                    expected: 2,
+                    actual: 3,
+                }) if *application_id == target_id
+            );
+            Ok(vec![])
+        },
+    ));
+    caller_application.expect_call(ExpectedCall::default_finalize());
+    target_application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id: caller_id,
+        bytes: vec![],
+    };
+
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        operation,
+        &mut TransactionTracker::new(0, None),
+        &mut controller,
+    )
+    .await?;
+
+    Ok(())
+}
+
 /// A test helper representing a transfer endpoint.
 #[derive(Clone, Copy, Debug)]
 enum TransferTestEndpoint {
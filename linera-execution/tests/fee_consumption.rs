@@ -9,12 +9,12 @@ use std::{sync::Arc, vec};
 
 use linera_base::{
     crypto::{CryptoHash, PublicKey},
-    data_types::{Amount, BlockHeight, Timestamp},
+    data_types::{Amount, BlockHeight, TimeDelta, Timestamp},
     identifiers::{Account, AccountOwner, ChainDescription, ChainId, MessageId, Owner},
 };
 use linera_execution::{
     test_utils::{ExpectedCall, RegisterMockApplication, SystemExecutionState},
-    ContractRuntime, ExecutionError, ExecutionOutcome, Message, MessageContext,
+    ContractRuntime, ExecutionError, ExecutionOutcome, FuelSchedule, Message, MessageContext,
     RawExecutionOutcome, ResourceControlPolicy, ResourceController, TransactionTracker,
 };
 use test_case::test_case;
@@ -139,6 +139,7 @@ async fn test_fee_consumption(
         byte_read: Amount::from_tokens(11),
         byte_written: Amount::from_tokens(13),
         byte_stored: Amount::from_tokens(17),
+        byte_stored_per_day: Amount::from_tokens(79),
         operation: Amount::from_tokens(19),
         operation_byte: Amount::from_tokens(23),
         message: Amount::from_tokens(29),
@@ -151,6 +152,14 @@ async fn test_fee_consumption(
         maximum_block_proposal_size: 53,
         maximum_bytes_read_per_block: 59,
         maximum_bytes_written_per_block: 61,
+        maximum_wasm_memory_size: 71,
+        maximum_wasm_table_elements: 73,
+        maximum_bytes_stored_per_application: 83,
+        storage_rent_grace_period: TimeDelta::from_micros(89),
+        fuel_schedule: FuelSchedule::default(),
+        base_fee_change_denominator: 67,
+        maximum_scheduled_callbacks_per_application: 97,
+        maximum_callbacks_per_operation: 101,
     };
 
     let consumed_fees = spends
@@ -0,0 +1,183 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for native implementations of applications.
+
+use linera_base::{crypto::CryptoHash, identifiers::BytecodeId};
+use linera_execution::{
+    test_utils::{check_native_equivalence, ApplicationCode},
+    BaseRuntime, ContractRuntime, ContractSyncRuntimeHandle, ExecutionError, FinalizeContext,
+    MessageContext, NativeApplicationRegistry, OperationContext, QueryContext,
+    ResourceControlPolicy, ServiceRuntime, ServiceSyncRuntimeHandle, UserContract,
+    UserContractInstance, UserContractModule, UserService, UserServiceInstance, UserServiceModule,
+};
+use linera_views::batch::Batch;
+
+/// The key where [`KeyValueContract`] stores the last operation it executed.
+const VALUE_KEY: &[u8] = b"value";
+
+/// A native application storing the last operation it executed, and returning it when queried.
+#[derive(Clone)]
+struct KeyValueApplication {
+    /// The fuel consumed by each operation.
+    fuel_per_operation: u64,
+}
+
+struct KeyValueContract {
+    runtime: ContractSyncRuntimeHandle,
+    fuel_per_operation: u64,
+}
+
+struct KeyValueService {
+    runtime: ServiceSyncRuntimeHandle,
+}
+
+impl KeyValueApplication {
+    fn code(fuel_per_operation: u64) -> ApplicationCode {
+        let application = KeyValueApplication { fuel_per_operation };
+        ApplicationCode {
+            contract: application.clone().into(),
+            service: application.into(),
+        }
+    }
+}
+
+impl UserContractModule for KeyValueApplication {
+    fn instantiate(
+        &self,
+        runtime: ContractSyncRuntimeHandle,
+    ) -> Result<UserContractInstance, ExecutionError> {
+        Ok(Box::new(KeyValueContract {
+            runtime,
+            fuel_per_operation: self.fuel_per_operation,
+        }))
+    }
+}
+
+impl UserServiceModule for KeyValueApplication {
+    fn instantiate(
+        &self,
+        runtime: ServiceSyncRuntimeHandle,
+    ) -> Result<UserServiceInstance, ExecutionError> {
+        Ok(Box::new(KeyValueService { runtime }))
+    }
+}
+
+impl UserContract for KeyValueContract {
+    fn instantiate(
+        &mut self,
+        _context: OperationContext,
+        _argument: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn execute_operation(
+        &mut self,
+        _context: OperationContext,
+        operation: Vec<u8>,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        self.runtime.consume_fuel(self.fuel_per_operation)?;
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(VALUE_KEY.to_vec(), operation);
+        self.runtime.write_batch(batch)?;
+        Ok(Vec::new())
+    }
+
+    fn execute_message(
+        &mut self,
+        _context: MessageContext,
+        _message: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn execute_callback(
+        &mut self,
+        _context: OperationContext,
+        _argument: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn finalize(&mut self, _context: FinalizeContext) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn migrate(&mut self, _old_version: u32) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+}
+
+impl UserService for KeyValueService {
+    fn handle_query(
+        &mut self,
+        _context: QueryContext,
+        _argument: Vec<u8>,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        self.runtime.consume_fuel(1)?;
+        Ok(self
+            .runtime
+            .read_value_bytes(VALUE_KEY.to_vec())?
+            .unwrap_or_default())
+    }
+}
+
+/// Tests that native implementations are looked up by the hash of the bytecode they replace.
+#[test]
+fn test_native_application_registry() {
+    let bytecode_id = BytecodeId::new(
+        CryptoHash::test_hash("contract"),
+        CryptoHash::test_hash("service"),
+    );
+    let other_bytecode_id = BytecodeId::new(
+        CryptoHash::test_hash("other contract"),
+        CryptoHash::test_hash("service"),
+    );
+    let application = KeyValueApplication {
+        fuel_per_operation: 10,
+    };
+
+    let mut registry = NativeApplicationRegistry::default();
+    assert!(registry.is_empty());
+    registry.register_application(bytecode_id, application.clone(), application);
+
+    assert!(registry.contract(&bytecode_id).is_some());
+    assert!(registry.service(&bytecode_id).is_some());
+    assert!(registry.contract(&other_bytecode_id).is_none());
+    assert!(registry.service(&other_bytecode_id).is_some());
+}
+
+/// Tests that two implementations behaving in the same way are considered equivalent.
+#[tokio::test]
+async fn test_equivalent_native_application() -> anyhow::Result<()> {
+    let operations = [b"first".to_vec(), b"second".to_vec()];
+    let queries = [Vec::new()];
+
+    check_native_equivalence(
+        KeyValueApplication::code(10),
+        KeyValueApplication::code(10),
+        &ResourceControlPolicy::default(),
+        &operations,
+        &queries,
+    )
+    .await
+}
+
+/// Tests that a native implementation consuming a different amount of fuel is rejected.
+#[tokio::test]
+async fn test_native_application_with_different_fuel() {
+    let operations = [b"first".to_vec(), b"second".to_vec()];
+
+    let error = check_native_equivalence(
+        KeyValueApplication::code(10),
+        KeyValueApplication::code(11),
+        &ResourceControlPolicy::default(),
+        &operations,
+        &[],
+    )
+    .await
+    .expect_err("Fuel consumption should diverge");
+
+    assert!(error.to_string().starts_with("Fuel consumption diverged"));
+}
@@ -3,18 +3,21 @@
 
 #![allow(clippy::field_reassign_with_default)]
 
-use std::{collections::BTreeMap, vec};
+use std::{collections::BTreeMap, thread, time::Duration, vec};
 
+use assert_matches::assert_matches;
 use linera_base::{
     data_types::Amount,
-    identifiers::{AccountOwner, ChainDescription},
+    identifiers::{
+        AccountOwner, ChainDescription, ChainId, GenericApplicationId, StreamId, StreamName,
+    },
 };
 use linera_execution::{
     test_utils::{
         create_dummy_query_context, test_accounts_strategy, ExpectedCall, RegisterMockApplication,
         SystemExecutionState,
     },
-    BaseRuntime, Query,
+    BaseRuntime, ExecutionError, ExecutionRuntimeConfig, Query, ServiceQueryLimits, ServiceRuntime,
 };
 use test_strategy::proptest;
 
@@ -183,3 +186,296 @@ async fn test_read_balance_owners_system_api(
 
     view.query_application(context, query, None).await.unwrap();
 }
+
+/// Tests that a query consuming more fuel than allowed is aborted.
+#[tokio::test]
+async fn test_service_query_fuel_limit() {
+    let limits = ServiceQueryLimits {
+        max_fuel: 1_000,
+        ..ServiceQueryLimits::default()
+    };
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view_with(
+        ChainId::root(0),
+        ExecutionRuntimeConfig {
+            service_query_limits: limits,
+        },
+    )
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await.unwrap();
+
+    application.expect_call(ExpectedCall::handle_query(
+        move |runtime, _context, _query| {
+            runtime.consume_fuel(600)?;
+            runtime.consume_fuel(600)?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    let result = view.query_application(context, query, None).await;
+
+    assert_matches!(
+        result,
+        Err(ExecutionError::ServiceQueryFuelLimitExceeded(1_000))
+    );
+}
+
+/// Tests that a query with a response larger than allowed is rejected.
+#[tokio::test]
+async fn test_service_query_response_size_limit() {
+    let limits = ServiceQueryLimits {
+        max_response_bytes: 10,
+        ..ServiceQueryLimits::default()
+    };
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view_with(
+        ChainId::root(0),
+        ExecutionRuntimeConfig {
+            service_query_limits: limits,
+        },
+    )
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await.unwrap();
+
+    application.expect_call(ExpectedCall::handle_query(|_runtime, _context, _query| {
+        Ok(vec![0; 11])
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    let result = view.query_application(context, query, None).await;
+
+    assert_matches!(
+        result,
+        Err(ExecutionError::ServiceQueryResponseTooLarge {
+            size: 11,
+            limit: 10
+        })
+    );
+}
+
+/// Tests that an upgraded application can't be queried until its state is migrated.
+#[tokio::test]
+async fn test_query_before_migration_is_rejected() {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, _application) = view.register_mock_application().await.unwrap();
+    view.system
+        .registry
+        .pending_migrations
+        .insert(&application_id, 0)
+        .unwrap();
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    let result = view.query_application(context, query, None).await;
+
+    assert_matches!(
+        result,
+        Err(ExecutionError::PendingMigration(id)) if *id == application_id
+    );
+}
+
+/// Tests that services can read the latest event emitted with a key on a stream.
+#[tokio::test]
+async fn test_read_event_system_api() {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await.unwrap();
+    let stream_id = StreamId {
+        application_id: GenericApplicationId::User(application_id),
+        stream_name: StreamName::from("prices"),
+    };
+    view.system
+        .latest_events
+        .insert(&(stream_id, b"key".to_vec()), b"value".to_vec())
+        .unwrap();
+
+    application.expect_call(ExpectedCall::handle_query(
+        move |runtime, _context, _query| {
+            let name = StreamName::from("prices");
+            assert_eq!(
+                runtime
+                    .read_event(application_id, name.clone(), b"key".to_vec())
+                    .unwrap(),
+                Some(b"value".to_vec())
+            );
+            assert_eq!(
+                runtime
+                    .read_event(application_id, name, b"other".to_vec())
+                    .unwrap(),
+                None
+            );
+            assert_eq!(
+                runtime
+                    .read_event(application_id, StreamName::from("volumes"), b"key".to_vec())
+                    .unwrap(),
+                None
+            );
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await.unwrap();
+}
+
+/// Tests that a query running past its deadline is interrupted the next time it consumes
+/// fuel, as the metering instrumentation does in every loop.
+#[tokio::test]
+async fn test_service_query_running_past_deadline_is_interrupted() {
+    let limits = ServiceQueryLimits {
+        max_duration: Duration::from_millis(20),
+        ..ServiceQueryLimits::default()
+    };
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view_with(
+        ChainId::root(0),
+        ExecutionRuntimeConfig {
+            service_query_limits: limits,
+        },
+    )
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await.unwrap();
+
+    application.expect_call(ExpectedCall::handle_query(|runtime, _context, _query| {
+        let remaining_time = runtime.remaining_query_time()?;
+        assert!(remaining_time.is_some_and(|time| time <= Duration::from_millis(20)));
+        loop {
+            runtime.consume_fuel(1)?;
+        }
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    let result = view.query_application(context, query, None).await;
+
+    assert_matches!(result, Err(ExecutionError::ServiceQueryTimedOut(_)));
+}
+
+/// Tests that a response computed after the query's deadline is rejected.
+#[tokio::test]
+async fn test_service_query_late_response_is_rejected() {
+    let limits = ServiceQueryLimits {
+        max_duration: Duration::from_millis(10),
+        ..ServiceQueryLimits::default()
+    };
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view_with(
+        ChainId::root(0),
+        ExecutionRuntimeConfig {
+            service_query_limits: limits,
+        },
+    )
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await.unwrap();
+
+    application.expect_call(ExpectedCall::handle_query(|_runtime, _context, _query| {
+        thread::sleep(Duration::from_millis(50));
+        Ok(vec![])
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    let result = view.query_application(context, query, None).await;
+
+    assert_matches!(result, Err(ExecutionError::ServiceQueryTimedOut(_)));
+}
+
+/// Tests that a service can only query an application with the version of the ABI that the
+/// application declares.
+#[tokio::test]
+async fn test_assert_abi_version_system_api() {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (caller_id, caller_application) = view.register_mock_application().await.unwrap();
+    let (target_id, target_application) = view.register_mock_application().await.unwrap();
+    target_application.set_abi_version(3);
+
+    caller_application.expect_call(ExpectedCall::handle_query(
+        move |runtime, _context, _query| {
+            runtime.assert_abi_version(target_id, 3)?;
+            assert_matches!(
+                runtime.assert_abi_version(target_id, 2),
+                Err(ExecutionError::AbiVersionMismatch {
+                    application_id,
+                    expected: 2,
+                    actual: 3,
+                }) if *application_id == target_id
+            );
+            Ok(vec![])
+        },
+    ));
+    caller_application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id: caller_id,
+        bytes: vec![],
+    };
+
+    view.query_application(context, query, None).await.unwrap();
+}
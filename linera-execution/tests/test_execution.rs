@@ -3,7 +3,7 @@
 
 #![allow(clippy::field_reassign_with_default)]
 
-use std::{collections::BTreeMap, vec};
+use std::{collections::BTreeMap, sync::Arc, vec};
 
 use anyhow::Context as _;
 use assert_matches::assert_matches;
@@ -11,7 +11,8 @@ use futures::{stream, StreamExt, TryStreamExt};
 use linera_base::{
     crypto::PublicKey,
     data_types::{
-        Amount, ApplicationPermissions, BlockHeight, Resources, SendMessageRequest, Timestamp,
+        Amount, ApplicationError, ApplicationPermissions, BlockHeight, Resources,
+        SendMessageRequest, Timestamp,
     },
     identifiers::{
         Account, AccountOwner, ChainDescription, ChainId, Destination, MessageId, Owner,
@@ -26,10 +27,11 @@ use linera_execution::{
         create_dummy_user_application_registrations, ExpectedCall, RegisterMockApplication,
         SystemExecutionState,
     },
-    BaseRuntime, ContractRuntime, ExecutionError, ExecutionOutcome, ExecutionRuntimeContext,
-    Message, MessageKind, Operation, OperationContext, Query, QueryContext, QueryOutcome,
-    QueryResponse, RawExecutionOutcome, RawOutgoingMessage, ResourceControlPolicy,
-    ResourceController, SystemOperation, TransactionTracker,
+    ApplicationCallPolicy, BaseRuntime, ContractRuntime, ExecutionError, ExecutionOutcome,
+    ExecutionRuntimeContext, Message, MessageKind, Operation, OperationContext,
+    PrivilegedSystemApi, Query, QueryContext, QueryOutcome, QueryResponse, RawExecutionOutcome,
+    RawOutgoingMessage, ReentrancyMode, ResourceControlPolicy, ResourceController, SystemOperation,
+    TransactionTracker,
 };
 use linera_views::{batch::Batch, context::Context, views::View};
 use test_case::test_case;
@@ -247,6 +249,84 @@ async fn test_simple_user_operation() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests that an error returned by a called application is handed to the caller, which can
+/// handle it and continue, while the callee's changes are kept.
+#[tokio::test]
+async fn test_application_error_is_returned_to_caller() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application) = view.register_mock_application().await?;
+    let (target_id, target_application) = view.register_mock_application().await?;
+    let error =
+        ApplicationError::new("insufficient_balance", "Not enough tokens").with_data(&7_u64);
+
+    caller_application.expect_call({
+        let error = error.clone();
+        ExpectedCall::execute_operation(move |runtime, _context, _operation| {
+            let result = runtime.try_call_application(false, target_id, vec![]);
+            assert_matches!(
+                result,
+                Err(ExecutionError::ApplicationError(returned)) if returned == error
+            );
+            // The call is finished, so the target can be called again.
+            assert_eq!(
+                runtime.try_call_application(false, target_id, vec![])?,
+                vec![1]
+            );
+            Ok(vec![])
+        })
+    });
+    target_application.expect_call({
+        let error = error.clone();
+        ExpectedCall::execute_operation(move |runtime, _context, _operation| {
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(vec![], vec![1]);
+            runtime.write_batch(batch)?;
+            Err(ExecutionError::ApplicationError(error))
+        })
+    });
+    target_application.expect_call(ExpectedCall::execute_operation(
+        |runtime, _context, _operation| Ok(runtime.read_value_bytes(vec![])?.unwrap_or_default()),
+    ));
+    target_application.expect_call(ExpectedCall::default_finalize());
+    caller_application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::User {
+            application_id: caller_id,
+            bytes: vec![],
+        },
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+
+    // Returned by the operation of a block, the error fails it.
+    target_application.expect_call(ExpectedCall::execute_operation(
+        move |_runtime, _context, _operation| Err(ExecutionError::ApplicationError(error)),
+    ));
+    let result = view
+        .execute_operation(
+            context,
+            Timestamp::from(0),
+            Operation::User {
+                application_id: target_id,
+                bytes: vec![],
+            },
+            &mut TransactionTracker::new(0, Some(Vec::new())),
+            &mut controller,
+        )
+        .await;
+    assert_matches!(result, Err(ExecutionError::ApplicationError(_)));
+    Ok(())
+}
+
 /// A cross-application call to start or end a session.
 ///
 /// Here a session is a test scenario where the transaction is prevented from succeeding while
@@ -552,6 +632,7 @@ async fn test_sending_message_from_finalize() -> anyhow::Result<()> {
         authenticated: false,
         is_tracked: false,
         grant: Resources::default(),
+        expires_at: None,
         message: b"first".to_vec(),
     };
 
@@ -587,6 +668,7 @@ async fn test_sending_message_from_finalize() -> anyhow::Result<()> {
         authenticated: false,
         is_tracked: false,
         grant: Resources::default(),
+        expires_at: None,
         message: b"second".to_vec(),
     };
     let third_message = SendMessageRequest {
@@ -594,6 +676,7 @@ async fn test_sending_message_from_finalize() -> anyhow::Result<()> {
         authenticated: false,
         is_tracked: false,
         grant: Resources::default(),
+        expires_at: None,
         message: b"third".to_vec(),
     };
     let fourth_message = SendMessageRequest {
@@ -601,6 +684,7 @@ async fn test_sending_message_from_finalize() -> anyhow::Result<()> {
         authenticated: false,
         is_tracked: false,
         grant: Resources::default(),
+        expires_at: None,
         message: b"fourth".to_vec(),
     };
 
@@ -649,7 +733,11 @@ async fn test_sending_message_from_finalize() -> anyhow::Result<()> {
         authenticated: false,
         grant: Amount::ZERO,
         kind: MessageKind::Simple,
-        message: SystemMessage::RegisterApplications { applications },
+        expires_at: None,
+        message: SystemMessage::RegisterApplications {
+            applications,
+            call_policies: vec![],
+        },
     };
     let account = Account {
         chain_id: ChainId::root(0),
@@ -911,6 +999,583 @@ async fn test_cross_application_error() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests that a cross-application call back into an application that is already on the call
+/// stack is rejected, and that the error reports the call stack.
+#[tokio::test]
+async fn test_reentrant_call_is_rejected() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application) = view.register_mock_application().await?;
+    let (target_id, target_application) = view.register_mock_application().await?;
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.try_call_application(/* authenticated */ false, target_id, vec![])?;
+            Ok(vec![])
+        },
+    ));
+    target_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _argument| {
+            runtime.try_call_application(/* authenticated */ false, caller_id, vec![])
+        },
+    ));
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    assert_matches!(
+        view.execute_operation(
+            context,
+            Timestamp::from(0),
+            Operation::User {
+                application_id: caller_id,
+                bytes: vec![],
+            },
+            &mut TransactionTracker::new(0, Some(Vec::new())),
+            &mut controller,
+        )
+        .await,
+        Err(ExecutionError::ReentrantCall { callee_id, call_stack })
+            if *callee_id == caller_id && call_stack == vec![caller_id, target_id]
+    );
+
+    Ok(())
+}
+
+/// Tests that an application can only be called by the callers allowed by its call policy.
+#[tokio::test]
+async fn test_call_policy_restricts_callers() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application) = view.register_mock_application().await?;
+    let (target_id, _target_application) = view.register_mock_application().await?;
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    view.system
+        .registry
+        .set_call_policy(
+            target_id,
+            ApplicationCallPolicy {
+                callers: Some(vec![]),
+                system_apis: None,
+                reentrancy: ReentrancyMode::RejectCalls,
+            },
+        )
+        .await?;
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.try_call_application(/* authenticated */ false, target_id, vec![])?;
+            Ok(vec![])
+        },
+    ));
+
+    assert_matches!(
+        view.execute_operation(
+            context,
+            Timestamp::from(0),
+            Operation::User {
+                application_id: caller_id,
+                bytes: vec![],
+            },
+            &mut TransactionTracker::new(0, Some(Vec::new())),
+            &mut controller,
+        )
+        .await,
+        Err(ExecutionError::UnauthorizedApplicationCall {
+            caller_id: rejected_caller_id,
+            callee_id,
+        }) if *rejected_caller_id == caller_id && *callee_id == target_id
+    );
+
+    Ok(())
+}
+
+/// Tests that an application can only use the privileged system APIs allowed by its call policy.
+#[tokio::test]
+async fn test_call_policy_restricts_system_apis() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    state.balance = Amount::from_tokens(5);
+    let mut view = state.into_view().await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    view.system
+        .registry
+        .set_call_policy(
+            application_id,
+            ApplicationCallPolicy {
+                callers: None,
+                system_apis: Some(vec![PrivilegedSystemApi::CloseChain]),
+                reentrancy: ReentrancyMode::RejectCalls,
+            },
+        )
+        .await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            assert_matches!(
+                runtime.transfer(None, Account::chain(ChainId::root(1)), Amount::ONE),
+                Err(ExecutionError::UnauthorizedSystemApi {
+                    application_id: rejected_id,
+                    system_api: PrivilegedSystemApi::Transfer,
+                }) if *rejected_id == application_id
+            );
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::User {
+            application_id,
+            bytes: vec![],
+        },
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+    assert_eq!(*view.system.balance.get(), Amount::from_tokens(5));
+
+    Ok(())
+}
+
+/// Tests that the bytes stored by applications are tracked and limited by the quota.
+#[tokio::test]
+async fn test_storage_quota() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(vec![0; 10], vec![1; 40]);
+            runtime.write_batch(batch)?;
+
+            // Exceeding the quota fails, and nothing is written.
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(vec![2; 10], vec![3; 50]);
+            assert_matches!(
+                runtime.write_batch(batch),
+                Err(ExecutionError::StorageQuotaExceeded {
+                    application_id: rejected_id,
+                    limit: 100,
+                }) if *rejected_id == application_id
+            );
+            assert!(runtime.contains_key(vec![0; 10])?);
+            assert!(!runtime.contains_key(vec![2; 10])?);
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController {
+        policy: Arc::new(ResourceControlPolicy {
+            maximum_bytes_stored_per_application: 100,
+            ..ResourceControlPolicy::default()
+        }),
+        ..ResourceController::default()
+    };
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::User {
+            application_id,
+            bytes: vec![],
+        },
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+
+    assert_eq!(*view.system.bytes_stored.get(), 50);
+    assert_eq!(controller.tracker.bytes_stored, 50);
+    let state = view.users.try_load_entry(&application_id).await?.unwrap();
+    assert_eq!(state.total_size().sum(), 50);
+    assert_eq!(state.get(&[2; 10]).await?, None);
+
+    Ok(())
+}
+
+/// Tests that applications can't increase their storage while the storage rent is overdue, and
+/// that the rejected writes are not applied.
+#[tokio::test]
+async fn test_storage_rent_overdue() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+    view.system
+        .storage_rent_due_by
+        .set(Some(Timestamp::from(0)));
+
+    let (application_id, application) = view.register_mock_application().await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(vec![0; 10], vec![1; 40]);
+            assert_matches!(
+                runtime.write_batch(batch),
+                Err(ExecutionError::StorageRentOverdue)
+            );
+            assert!(!runtime.contains_key(vec![0; 10])?);
+
+            // Freeing storage is still allowed.
+            let mut batch = Batch::new();
+            batch.delete_key(vec![0; 10]);
+            runtime.write_batch(batch)?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::User {
+            application_id,
+            bytes: vec![],
+        },
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+
+    assert_eq!(*view.system.bytes_stored.get(), 0);
+
+    Ok(())
+}
+
+/// Tests that a contract can query another application's service, and that the response and
+/// the service's fuel are recorded as an oracle response, and charged again on replay.
+#[cfg(feature = "unstable-oracles")]
+#[tokio::test]
+async fn test_contract_queries_service() -> anyhow::Result<()> {
+    use linera_base::data_types::OracleResponse;
+    use linera_execution::ServiceRuntime as _;
+
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application) = view.register_mock_application().await?;
+    let (oracle_id, oracle_application) = view.register_mock_application().await?;
+
+    for _ in 0..2 {
+        caller_application.expect_call(ExpectedCall::execute_operation(
+            move |runtime, _context, _operation| {
+                let response = runtime.query_service(oracle_id, b"price".to_vec())?;
+                assert_eq!(response, b"42");
+                Ok(vec![])
+            },
+        ));
+        caller_application.expect_call(ExpectedCall::default_finalize());
+    }
+    // The service is only queried when the block is first executed.
+    oracle_application.expect_call(ExpectedCall::handle_query(|runtime, _context, query| {
+        assert_eq!(query, b"price");
+        runtime.consume_fuel(100)?;
+        Ok(b"42".to_vec())
+    }));
+
+    let context = create_dummy_operation_context();
+    let operation = Operation::User {
+        application_id: caller_id,
+        bytes: vec![],
+    };
+    let mut controller = ResourceController::default();
+    let mut txn_tracker = TransactionTracker::new(0, None);
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        operation.clone(),
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    let (_, oracle_responses, _) = txn_tracker.destructure()?;
+    assert_eq!(
+        oracle_responses,
+        vec![OracleResponse::ServiceWithFuel {
+            response: b"42".to_vec(),
+            fuel: 100,
+        }]
+    );
+    assert_eq!(controller.tracker.fuel, 100);
+
+    let mut txn_tracker = TransactionTracker::new(0, Some(oracle_responses.clone()));
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        operation,
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    let (_, replayed_responses, _) = txn_tracker.destructure()?;
+    assert_eq!(replayed_responses, oracle_responses);
+    assert_eq!(controller.tracker.fuel, 200);
+
+    Ok(())
+}
+
+/// Tests that a service queried by a contract cannot schedule operations.
+#[cfg(feature = "unstable-oracles")]
+#[tokio::test]
+async fn test_contract_query_cannot_schedule_operations() -> anyhow::Result<()> {
+    use linera_execution::ServiceRuntime as _;
+
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application) = view.register_mock_application().await?;
+    let (oracle_id, oracle_application) = view.register_mock_application().await?;
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            assert_matches!(
+                runtime.query_service(oracle_id, vec![]),
+                Err(ExecutionError::OperationScheduledByContractQuery(id))
+                    if *id == oracle_id
+            );
+            Ok(vec![])
+        },
+    ));
+    caller_application.expect_call(ExpectedCall::default_finalize());
+    oracle_application.expect_call(ExpectedCall::handle_query(|runtime, _context, _query| {
+        runtime.schedule_operation(b"operation".to_vec())?;
+        Ok(vec![])
+    }));
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::User {
+            application_id: caller_id,
+            bytes: vec![],
+        },
+        &mut TransactionTracker::new(0, None),
+        &mut controller,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Tests that a replayed service query can't use more fuel than the block allows, since the
+/// recorded fuel is charged without running the service.
+#[cfg(feature = "unstable-oracles")]
+#[tokio::test]
+async fn test_replayed_contract_query_fuel_is_limited() -> anyhow::Result<()> {
+    use linera_base::data_types::OracleResponse;
+
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application) = view.register_mock_application().await?;
+    let (oracle_id, _oracle_application) = view.register_mock_application().await?;
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.query_service(oracle_id, b"price".to_vec())?;
+            Ok(vec![])
+        },
+    ));
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController {
+        policy: Arc::new(ResourceControlPolicy {
+            maximum_fuel_per_block: 1_000,
+            ..ResourceControlPolicy::default()
+        }),
+        ..ResourceController::default()
+    };
+    let oracle_responses = vec![OracleResponse::ServiceWithFuel {
+        response: b"42".to_vec(),
+        fuel: 1_001,
+    }];
+    let result = view
+        .execute_operation(
+            context,
+            Timestamp::from(0),
+            Operation::User {
+                application_id: caller_id,
+                bytes: vec![],
+            },
+            &mut TransactionTracker::new(0, Some(oracle_responses)),
+            &mut controller,
+        )
+        .await;
+    assert_matches!(result, Err(ExecutionError::MaximumFuelExceeded));
+
+    Ok(())
+}
+
+/// Tests that contracts can't query services unless unstable oracles are enabled, since
+/// validators replaying the block use the response recorded by the proposer.
+#[cfg(not(feature = "unstable-oracles"))]
+#[tokio::test]
+async fn test_contract_query_requires_unstable_oracles() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application) = view.register_mock_application().await?;
+    let (oracle_id, _oracle_application) = view.register_mock_application().await?;
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            assert_matches!(
+                runtime.query_service(oracle_id, b"price".to_vec()),
+                Err(ExecutionError::UnstableOracle)
+            );
+            Ok(vec![])
+        },
+    ));
+    caller_application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    let mut txn_tracker = TransactionTracker::new(0, None);
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::User {
+            application_id: caller_id,
+            bytes: vec![],
+        },
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    let (_, oracle_responses, _) = txn_tracker.destructure()?;
+    assert!(oracle_responses.is_empty());
+
+    Ok(())
+}
+
+/// Tests that, by default, the service of an application on the call stack can be queried by
+/// the applications it called.
+#[cfg(feature = "unstable-oracles")]
+#[tokio::test]
+async fn test_reentrant_query_is_allowed_by_default() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application) = view.register_mock_application().await?;
+    let (target_id, target_application) = view.register_mock_application().await?;
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.try_call_application(/* authenticated */ false, target_id, vec![])?;
+            Ok(vec![])
+        },
+    ));
+    target_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _argument| {
+            let response = runtime.query_service(caller_id, b"balance".to_vec())?;
+            assert_eq!(response, b"10");
+            Ok(vec![])
+        },
+    ));
+    caller_application.expect_call(ExpectedCall::handle_query(|_runtime, _context, _query| {
+        Ok(b"10".to_vec())
+    }));
+    caller_application.expect_call(ExpectedCall::default_finalize());
+    target_application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::User {
+            application_id: caller_id,
+            bytes: vec![],
+        },
+        &mut TransactionTracker::new(0, None),
+        &mut controller,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Tests that the service of an application on the call stack can't be queried if its call
+/// policy rejects reentrant queries.
+#[cfg(feature = "unstable-oracles")]
+#[tokio::test]
+async fn test_reentrant_query_is_rejected_by_call_policy() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (caller_id, caller_application) = view.register_mock_application().await?;
+    let (target_id, target_application) = view.register_mock_application().await?;
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    view.system
+        .registry
+        .set_call_policy(
+            caller_id,
+            ApplicationCallPolicy {
+                callers: None,
+                system_apis: None,
+                reentrancy: ReentrancyMode::RejectCallsAndQueries,
+            },
+        )
+        .await?;
+
+    caller_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.try_call_application(/* authenticated */ false, target_id, vec![])?;
+            Ok(vec![])
+        },
+    ));
+    target_application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _argument| runtime.query_service(caller_id, b"balance".to_vec()),
+    ));
+
+    assert_matches!(
+        view.execute_operation(
+            context,
+            Timestamp::from(0),
+            Operation::User {
+                application_id: caller_id,
+                bytes: vec![],
+            },
+            &mut TransactionTracker::new(0, None),
+            &mut controller,
+        )
+        .await,
+        Err(ExecutionError::ReentrantQuery { callee_id, call_stack })
+            if *callee_id == caller_id && call_stack == vec![caller_id, target_id]
+    );
+
+    Ok(())
+}
+
 /// Tests if an application is scheduled to be registered together with any messages it sends to
 /// other chains.
 #[tokio::test]
@@ -927,6 +1592,7 @@ async fn test_simple_message() -> anyhow::Result<()> {
         authenticated: false,
         is_tracked: false,
         grant: Resources::default(),
+        expires_at: None,
         message: b"msg".to_vec(),
     };
 
@@ -969,8 +1635,10 @@ async fn test_simple_message() -> anyhow::Result<()> {
         authenticated: false,
         grant: Amount::ZERO,
         kind: MessageKind::Simple,
+        expires_at: None,
         message: SystemMessage::RegisterApplications {
             applications: vec![application_description],
+            call_policies: vec![],
         },
     };
     let account = Account {
@@ -1025,6 +1693,7 @@ async fn test_message_from_cross_application_call() -> anyhow::Result<()> {
         authenticated: false,
         is_tracked: false,
         grant: Resources::default(),
+        expires_at: None,
         message: b"msg".to_vec(),
     };
 
@@ -1065,8 +1734,10 @@ async fn test_message_from_cross_application_call() -> anyhow::Result<()> {
         authenticated: false,
         grant: Amount::ZERO,
         kind: MessageKind::Simple,
+        expires_at: None,
         message: SystemMessage::RegisterApplications {
             applications: vec![target_description],
+            call_policies: vec![],
         },
     };
     let account = Account {
@@ -1136,6 +1807,7 @@ async fn test_message_from_deeper_call() -> anyhow::Result<()> {
         authenticated: false,
         is_tracked: false,
         grant: Resources::default(),
+        expires_at: None,
         message: b"msg".to_vec(),
     };
 
@@ -1175,8 +1847,10 @@ async fn test_message_from_deeper_call() -> anyhow::Result<()> {
         authenticated: false,
         grant: Amount::ZERO,
         kind: MessageKind::Simple,
+        expires_at: None,
         message: SystemMessage::RegisterApplications {
             applications: vec![target_description],
+            call_policies: vec![],
         },
     };
     view.update_execution_outcomes_with_app_registrations(&mut txn_tracker)
@@ -1253,6 +1927,7 @@ async fn test_multiple_messages_from_different_applications() -> anyhow::Result<
         authenticated: false,
         is_tracked: false,
         grant: Resources::default(),
+        expires_at: None,
         message: b"first".to_vec(),
     };
 
@@ -1291,6 +1966,7 @@ async fn test_multiple_messages_from_different_applications() -> anyhow::Result<
         authenticated: false,
         is_tracked: false,
         grant: Resources::default(),
+        expires_at: None,
         message: b"second".to_vec(),
     };
 
@@ -1343,8 +2019,10 @@ async fn test_multiple_messages_from_different_applications() -> anyhow::Result<
         authenticated: false,
         grant: Amount::ZERO,
         kind: MessageKind::Simple,
+        expires_at: None,
         message: SystemMessage::RegisterApplications {
             applications: vec![sending_target_description.clone(), caller_description],
+            call_policies: vec![],
         },
     };
 
@@ -1354,8 +2032,10 @@ async fn test_multiple_messages_from_different_applications() -> anyhow::Result<
         authenticated: false,
         grant: Amount::ZERO,
         kind: MessageKind::Simple,
+        expires_at: None,
         message: SystemMessage::RegisterApplications {
             applications: vec![sending_target_description],
+            call_policies: vec![],
         },
     };
 
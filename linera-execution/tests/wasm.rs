@@ -5,15 +5,17 @@
 
 use std::sync::Arc;
 
+use assert_matches::assert_matches;
 use linera_base::{
     data_types::{Amount, BlockHeight, Timestamp},
     identifiers::{Account, ChainDescription, ChainId},
 };
 use linera_execution::{
     test_utils::{create_dummy_user_application_description, SystemExecutionState},
-    ExecutionOutcome, ExecutionRuntimeConfig, ExecutionRuntimeContext, Operation, OperationContext,
-    Query, QueryContext, QueryOutcome, QueryResponse, RawExecutionOutcome, ResourceControlPolicy,
-    ResourceController, ResourceTracker, TransactionTracker, WasmContractModule, WasmRuntime,
+    ExecutionError, ExecutionOutcome, ExecutionRuntimeConfig, ExecutionRuntimeContext,
+    FuelSchedule, Operation, OperationContext, Query, QueryContext, QueryOutcome, QueryResponse,
+    RawExecutionOutcome, ResourceControlPolicy, ResourceController, ResourceTracker,
+    TransactionTracker, UserContractModule as _, WasmContractModule, WasmRuntime,
     WasmServiceModule,
 };
 use linera_views::{context::Context as _, views::View};
@@ -26,8 +28,8 @@ use test_case::test_case;
 /// To update the bytecode files, run `linera-execution/update_wasm_fixtures.sh`.
 #[cfg_attr(with_wasmer, test_case(WasmRuntime::Wasmer, 90_090; "wasmer"))]
 #[cfg_attr(with_wasmer, test_case(WasmRuntime::WasmerWithSanitizer, 90_622; "wasmer_with_sanitizer"))]
-#[cfg_attr(with_wasmtime, test_case(WasmRuntime::Wasmtime, 90_454; "wasmtime"))]
-#[cfg_attr(with_wasmtime, test_case(WasmRuntime::WasmtimeWithSanitizer, 90_454; "wasmtime_with_sanitizer"))]
+#[cfg_attr(with_wasmtime, test_case(WasmRuntime::Wasmtime, 90_090; "wasmtime"))]
+#[cfg_attr(with_wasmtime, test_case(WasmRuntime::WasmtimeWithSanitizer, 90_622; "wasmtime_with_sanitizer"))]
 #[test_log::test(tokio::test(flavor = "multi_thread"))]
 async fn test_fuel_for_counter_wasm_application(
     wasm_runtime: WasmRuntime,
@@ -156,3 +158,175 @@ async fn test_fuel_for_counter_wasm_application(
     assert!(operations.is_empty());
     Ok(())
 }
+
+/// Tests that the "counter" example application consumes the same amount of fuel with Wasmer and
+/// Wasmtime, with the default and with a custom [`FuelSchedule`].
+#[cfg(all(with_wasmer, with_wasmtime))]
+#[test_case(WasmRuntime::Wasmer, WasmRuntime::Wasmtime; "without_sanitizer")]
+#[test_case(WasmRuntime::WasmerWithSanitizer, WasmRuntime::WasmtimeWithSanitizer; "with_sanitizer")]
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn test_fuel_schedule_is_applied_by_all_runtimes(
+    wasmer: WasmRuntime,
+    wasmtime: WasmRuntime,
+) -> anyhow::Result<()> {
+    let custom_schedule = FuelSchedule {
+        instruction: 3,
+        structural_instruction: 1,
+        memory_access: 5,
+        call: 7,
+        memory_grow_page: 11,
+    };
+
+    let default_fuel = counter_fuel(wasmer, FuelSchedule::default()).await?;
+    assert_eq!(
+        counter_fuel(wasmtime, FuelSchedule::default()).await?,
+        default_fuel
+    );
+    let custom_fuel = counter_fuel(wasmer, custom_schedule).await?;
+    assert_eq!(counter_fuel(wasmtime, custom_schedule).await?, custom_fuel);
+    assert!(custom_fuel > default_fuel);
+    Ok(())
+}
+
+/// Tests that the pages allocated by the "counter" example application consume the fuel of
+/// [`FuelSchedule::memory_grow_page`], with all runtimes.
+#[cfg_attr(with_wasmer, test_case(WasmRuntime::Wasmer; "wasmer"))]
+#[cfg_attr(with_wasmer, test_case(WasmRuntime::WasmerWithSanitizer; "wasmer_with_sanitizer"))]
+#[cfg_attr(with_wasmtime, test_case(WasmRuntime::Wasmtime; "wasmtime"))]
+#[cfg_attr(with_wasmtime, test_case(WasmRuntime::WasmtimeWithSanitizer; "wasmtime_with_sanitizer"))]
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn test_memory_growth_consumes_fuel(wasm_runtime: WasmRuntime) -> anyhow::Result<()> {
+    let memory_grow_page = 1_000;
+    let schedule = FuelSchedule {
+        memory_grow_page,
+        ..FuelSchedule::default()
+    };
+
+    let default_fuel = counter_fuel(wasm_runtime, FuelSchedule::default()).await?;
+    let fuel = counter_fuel(wasm_runtime, schedule).await?;
+    let memory_growth_fuel = fuel - default_fuel;
+    assert!(memory_growth_fuel > 0);
+    assert_eq!(memory_growth_fuel % u64::from(memory_grow_page), 0);
+    Ok(())
+}
+
+/// Returns the fuel consumed by an increment of the "counter" example application with the
+/// `wasm_runtime`, when instructions cost fuel according to the `fuel_schedule`.
+async fn counter_fuel(
+    wasm_runtime: WasmRuntime,
+    fuel_schedule: FuelSchedule,
+) -> anyhow::Result<u64> {
+    let state = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..Default::default()
+    };
+    let mut view = state
+        .into_view_with(ChainId::root(0), ExecutionRuntimeConfig::default())
+        .await;
+    let (app_desc, contract_blob, service_blob) = create_dummy_user_application_description(1);
+    let app_id = view.system.registry.register_application(app_desc).await?;
+
+    let contract =
+        WasmContractModule::from_file("tests/fixtures/counter_contract.wasm", wasm_runtime).await?;
+    let contract = contract
+        .with_fuel_schedule(&fuel_schedule)?
+        .unwrap_or_else(|| contract.into());
+    view.context()
+        .extra()
+        .user_contracts()
+        .insert(app_id, contract);
+    view.context()
+        .extra()
+        .add_blobs([contract_blob, service_blob])
+        .await?;
+
+    let context = OperationContext {
+        chain_id: ChainId::root(0),
+        height: BlockHeight(0),
+        round: Some(0),
+        index: Some(0),
+        authenticated_signer: None,
+        authenticated_caller_id: None,
+    };
+    *view.system.balance.get_mut() = Amount::from_tokens(1);
+    let mut controller = ResourceController {
+        policy: Arc::new(ResourceControlPolicy {
+            fuel_unit: Amount::from_attos(1),
+            ..ResourceControlPolicy::default()
+        }),
+        tracker: ResourceTracker::default(),
+        account: None,
+    };
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        Operation::user_without_abi(app_id, &5_u64).unwrap(),
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+    Ok(controller.tracker.fuel)
+}
+
+/// Tests that an application whose linear memory exceeds the limit of the policy fails to
+/// execute operations, with all runtimes.
+#[cfg_attr(with_wasmer, test_case(WasmRuntime::Wasmer; "wasmer"))]
+#[cfg_attr(with_wasmer, test_case(WasmRuntime::WasmerWithSanitizer; "wasmer_with_sanitizer"))]
+#[cfg_attr(with_wasmtime, test_case(WasmRuntime::Wasmtime; "wasmtime"))]
+#[cfg_attr(with_wasmtime, test_case(WasmRuntime::WasmtimeWithSanitizer; "wasmtime_with_sanitizer"))]
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn test_wasm_memory_limit_is_enforced(wasm_runtime: WasmRuntime) -> anyhow::Result<()> {
+    let state = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..Default::default()
+    };
+    let mut view = state
+        .into_view_with(ChainId::root(0), ExecutionRuntimeConfig::default())
+        .await;
+    let (app_desc, contract_blob, service_blob) = create_dummy_user_application_description(1);
+    let app_id = view.system.registry.register_application(app_desc).await?;
+
+    let contract =
+        WasmContractModule::from_file("tests/fixtures/counter_contract.wasm", wasm_runtime).await?;
+    view.context()
+        .extra()
+        .user_contracts()
+        .insert(app_id, contract.into());
+    view.context()
+        .extra()
+        .add_blobs([contract_blob, service_blob])
+        .await?;
+
+    let context = OperationContext {
+        chain_id: ChainId::root(0),
+        height: BlockHeight(0),
+        round: Some(0),
+        index: Some(0),
+        authenticated_signer: None,
+        authenticated_caller_id: None,
+    };
+    let maximum_wasm_memory_size = 1 << 16;
+    let mut controller = ResourceController {
+        policy: Arc::new(ResourceControlPolicy {
+            maximum_wasm_memory_size,
+            ..ResourceControlPolicy::default()
+        }),
+        tracker: ResourceTracker::default(),
+        account: None,
+    };
+    let result = view
+        .execute_operation(
+            context,
+            Timestamp::from(0),
+            Operation::user_without_abi(app_id, &5_u64).unwrap(),
+            &mut TransactionTracker::new(0, Some(Vec::new())),
+            &mut controller,
+        )
+        .await;
+    assert_matches!(
+        result,
+        Err(ExecutionError::WasmMemoryLimitExceeded(limit)) if limit == maximum_wasm_memory_size
+    );
+    assert_eq!(controller.tracker.fuel, 0);
+    Ok(())
+}
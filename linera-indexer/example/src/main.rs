@@ -1,10 +1,14 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-//! An example of an indexer with the operations plugin.
+//! An example of an indexer with the operations and applications plugins.
 
 use linera_indexer::{common::IndexerError, plugin::Plugin, rocks_db::RocksDbRunner};
-use linera_indexer_plugins::operations::OperationsPlugin;
+use linera_indexer_plugins::{applications::ApplicationsPlugin, operations::OperationsPlugin};
+
+/// The environment variable holding the token required to register application ABIs. ABIs
+/// cannot be registered if it is not set.
+const ABI_TOKEN_ENV_VAR: &str = "LINERA_INDEXER_ABI_TOKEN";
 
 #[tokio::main]
 async fn main() -> Result<(), IndexerError> {
@@ -20,5 +24,10 @@ async fn main() -> Result<(), IndexerError> {
     runner
         .add_plugin(OperationsPlugin::load(runner.store.clone()).await?)
         .await?;
+    let mut applications = ApplicationsPlugin::load(runner.store.clone()).await?;
+    if let Ok(token) = std::env::var(ABI_TOKEN_ENV_VAR) {
+        applications = applications.with_abi_token(token);
+    }
+    runner.add_plugin(applications).await?;
     runner.run().await
 }
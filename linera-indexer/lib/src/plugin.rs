@@ -40,27 +40,48 @@ where
     fn route(&self, app: Router) -> Router;
 }
 
-async fn handler<Q: ObjectType + 'static>(
-    schema: axum::extract::Extension<Schema<Q, EmptyMutation, EmptySubscription>>,
+async fn handler<Q: ObjectType + 'static, M: ObjectType + 'static>(
+    schema: axum::extract::Extension<Schema<Q, M, EmptySubscription>>,
     req: async_graphql_axum::GraphQLRequest,
 ) -> async_graphql_axum::GraphQLResponse {
     schema.execute(req.into_inner()).await.into()
 }
 
-fn schema<Q: ObjectType + 'static>(query: Q) -> Schema<Q, EmptyMutation, EmptySubscription> {
-    Schema::new(query, EmptyMutation, EmptySubscription)
+fn schema<Q: ObjectType + 'static, M: ObjectType + 'static>(
+    query: Q,
+    mutation: M,
+) -> Schema<Q, M, EmptySubscription> {
+    Schema::new(query, mutation, EmptySubscription)
 }
 
 pub fn sdl<Q: ObjectType + 'static>(query: Q) -> String {
-    schema(query).sdl()
+    sdl_with_mutation(query, EmptyMutation)
 }
 
 pub fn route<Q: ObjectType + 'static>(name: &str, query: Q, app: axum::Router) -> axum::Router {
+    route_with_mutation(name, query, EmptyMutation, app)
+}
+
+/// Produces the GraphQL schema of a plugin accepting mutations.
+pub fn sdl_with_mutation<Q: ObjectType + 'static, M: ObjectType + 'static>(
+    query: Q,
+    mutation: M,
+) -> String {
+    schema(query, mutation).sdl()
+}
+
+/// Registers a plugin accepting mutations to an Axum router.
+pub fn route_with_mutation<Q: ObjectType + 'static, M: ObjectType + 'static>(
+    name: &str,
+    query: Q,
+    mutation: M,
+    app: axum::Router,
+) -> axum::Router {
     app.route(
         &format!("/{}", name),
-        axum::routing::get(crate::common::graphiql).post(handler::<Q>),
+        axum::routing::get(crate::common::graphiql).post(handler::<Q, M>),
     )
-    .layer(axum::extract::Extension(schema(query)))
+    .layer(axum::extract::Extension(schema(query, mutation)))
     .layer(tower_http::cors::CorsLayer::permissive())
 }
 
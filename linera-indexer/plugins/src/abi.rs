@@ -0,0 +1,446 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decoding of the operations, messages and events of user applications, given a description of
+//! their ABI.
+//!
+//! The types of an application are described by a `serde-reflection` [`Registry`], e.g. as
+//! produced by tracing its operation, message and event types with a `serde_reflection::Tracer`.
+//! Their BCS encoding is then decoded into JSON, where enum variants are objects with a single
+//! key, byte arrays are hexadecimal strings, and 128-bit integers are decimal strings.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
+use thiserror::Error;
+
+/// The maximal nesting of named types, as in BCS.
+const MAX_CONTAINER_DEPTH: usize = 500;
+
+/// The description of the types of an application.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ApplicationAbi {
+    /// The formats of the named types used by the application.
+    #[serde(default)]
+    pub types: Registry,
+    /// The format of the operations of the application, if known.
+    #[serde(default)]
+    pub operation: Option<Format>,
+    /// The format of the messages of the application, if known.
+    #[serde(default)]
+    pub message: Option<Format>,
+    /// The format of the values of the events emitted by the application, by stream name.
+    #[serde(default)]
+    pub events: BTreeMap<String, Format>,
+}
+
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+    #[error("{0} trailing bytes after the value")]
+    TrailingBytes(usize),
+    #[error("unknown type {0:?}")]
+    UnknownType(String),
+    #[error("unknown variant {0}")]
+    UnknownVariant(u32),
+    #[error("invalid tag {0}")]
+    InvalidTag(u8),
+    #[error("invalid length")]
+    InvalidLength,
+    #[error("invalid UTF-8 string")]
+    InvalidString,
+    #[error("format {0} is not supported by BCS")]
+    UnsupportedFormat(&'static str),
+    #[error("types are nested more than {MAX_CONTAINER_DEPTH} times")]
+    TooDeep,
+}
+
+impl ApplicationAbi {
+    /// Decodes an operation of the application, or returns `None` if its format is unknown.
+    pub fn decode_operation(&self, bytes: &[u8]) -> Option<Result<Value, DecodeError>> {
+        Some(self.decode(self.operation.as_ref()?, bytes))
+    }
+
+    /// Decodes a message of the application, or returns `None` if its format is unknown.
+    pub fn decode_message(&self, bytes: &[u8]) -> Option<Result<Value, DecodeError>> {
+        Some(self.decode(self.message.as_ref()?, bytes))
+    }
+
+    /// Decodes the value of an event emitted on the stream `stream_name`, or returns `None` if
+    /// its format is unknown.
+    pub fn decode_event(
+        &self,
+        stream_name: &str,
+        bytes: &[u8],
+    ) -> Option<Result<Value, DecodeError>> {
+        Some(self.decode(self.events.get(stream_name)?, bytes))
+    }
+
+    /// Decodes a value with the given format, which must use all the bytes.
+    pub fn decode(&self, format: &Format, bytes: &[u8]) -> Result<Value, DecodeError> {
+        let mut decoder = Decoder {
+            types: &self.types,
+            input: bytes,
+            depth: 0,
+        };
+        let value = decoder.format(format)?;
+        if !decoder.input.is_empty() {
+            return Err(DecodeError::TrailingBytes(decoder.input.len()));
+        }
+        Ok(value)
+    }
+}
+
+/// A BCS decoder into JSON values.
+struct Decoder<'a> {
+    types: &'a Registry,
+    input: &'a [u8],
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.input.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.take(N)?.try_into().expect("N bytes were taken"))
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Reads a ULEB128-encoded length or variant index, which BCS requires to be canonical
+    /// and to fit in 32 bits.
+    fn uleb128(&mut self) -> Result<u32, DecodeError> {
+        let mut value = 0u64;
+        for shift in (0..32).step_by(7) {
+            let byte = self.byte()?;
+            let digit = byte & 0x7f;
+            value |= u64::from(digit) << shift;
+            if byte & 0x80 == 0 {
+                if shift > 0 && digit == 0 {
+                    return Err(DecodeError::InvalidLength);
+                }
+                return u32::try_from(value).map_err(|_| DecodeError::InvalidLength);
+            }
+        }
+        Err(DecodeError::InvalidLength)
+    }
+
+    /// Reads the length of a sequence, map, string or byte array. Their elements use at least
+    /// one byte each, except zero-sized values, which must not fill sequences longer than the
+    /// rest of the input: this bounds the memory used for invalid inputs.
+    fn len(&mut self) -> Result<usize, DecodeError> {
+        let len = self.uleb128()? as usize;
+        if len > self.input.len() {
+            return Err(DecodeError::InvalidLength);
+        }
+        Ok(len)
+    }
+
+    fn format(&mut self, format: &Format) -> Result<Value, DecodeError> {
+        Ok(match format {
+            Format::Variable(_) => return Err(DecodeError::UnsupportedFormat("Variable")),
+            Format::TypeName(name) => {
+                let container = self
+                    .types
+                    .get(name)
+                    .ok_or_else(|| DecodeError::UnknownType(name.clone()))?;
+                if self.depth == MAX_CONTAINER_DEPTH {
+                    return Err(DecodeError::TooDeep);
+                }
+                self.depth += 1;
+                let value = self.container(container);
+                self.depth -= 1;
+                value?
+            }
+            Format::Unit => Value::Null,
+            Format::Bool => match self.byte()? {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                tag => return Err(DecodeError::InvalidTag(tag)),
+            },
+            Format::I8 => i8::from_le_bytes(self.array()?).into(),
+            Format::I16 => i16::from_le_bytes(self.array()?).into(),
+            Format::I32 => i32::from_le_bytes(self.array()?).into(),
+            Format::I64 => i64::from_le_bytes(self.array()?).into(),
+            Format::I128 => i128::from_le_bytes(self.array()?).to_string().into(),
+            Format::U8 => self.byte()?.into(),
+            Format::U16 => u16::from_le_bytes(self.array()?).into(),
+            Format::U32 => u32::from_le_bytes(self.array()?).into(),
+            Format::U64 => u64::from_le_bytes(self.array()?).into(),
+            Format::U128 => u128::from_le_bytes(self.array()?).to_string().into(),
+            Format::F32 => return Err(DecodeError::UnsupportedFormat("F32")),
+            Format::F64 => return Err(DecodeError::UnsupportedFormat("F64")),
+            Format::Char => return Err(DecodeError::UnsupportedFormat("Char")),
+            Format::Str => {
+                let len = self.len()?;
+                let bytes = self.take(len)?;
+                std::str::from_utf8(bytes)
+                    .map_err(|_| DecodeError::InvalidString)?
+                    .into()
+            }
+            Format::Bytes => {
+                let len = self.len()?;
+                hex::encode(self.take(len)?).into()
+            }
+            Format::Option(format) => match self.byte()? {
+                0 => Value::Null,
+                1 => self.format(format)?,
+                tag => return Err(DecodeError::InvalidTag(tag)),
+            },
+            Format::Seq(format) => {
+                let len = self.len()?;
+                self.values(std::iter::repeat(&**format).take(len))?
+            }
+            Format::Map { key, value } => {
+                let len = self.len()?;
+                let mut entries = Vec::new();
+                for _ in 0..len {
+                    entries.push((self.format(key)?, self.format(value)?));
+                }
+                if entries.iter().all(|(key, _)| key.is_string()) {
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key.as_str().unwrap().to_owned(), value))
+                        .collect::<Map<_, _>>()
+                        .into()
+                } else {
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| Value::Array(vec![key, value]))
+                        .collect()
+                }
+            }
+            Format::Tuple(formats) => self.values(formats)?,
+            Format::TupleArray { content, size } => {
+                self.values(std::iter::repeat(&**content).take(*size))?
+            }
+        })
+    }
+
+    fn values<'f>(
+        &mut self,
+        formats: impl IntoIterator<Item = &'f Format>,
+    ) -> Result<Value, DecodeError> {
+        formats
+            .into_iter()
+            .map(|format| self.format(format))
+            .collect()
+    }
+
+    fn fields(&mut self, fields: &[Named<Format>]) -> Result<Value, DecodeError> {
+        let mut object = Map::new();
+        for field in fields {
+            object.insert(field.name.clone(), self.format(&field.value)?);
+        }
+        Ok(object.into())
+    }
+
+    fn container(&mut self, container: &ContainerFormat) -> Result<Value, DecodeError> {
+        match container {
+            ContainerFormat::UnitStruct => Ok(Value::Null),
+            ContainerFormat::NewTypeStruct(format) => self.format(format),
+            ContainerFormat::TupleStruct(formats) => self.values(formats),
+            ContainerFormat::Struct(fields) => self.fields(fields),
+            ContainerFormat::Enum(variants) => {
+                let index = self.uleb128()?;
+                let variant = variants
+                    .get(&index)
+                    .ok_or(DecodeError::UnknownVariant(index))?;
+                let value = match &variant.value {
+                    VariantFormat::Variable(_) => {
+                        return Err(DecodeError::UnsupportedFormat("Variable"))
+                    }
+                    VariantFormat::Unit => return Ok(Value::String(variant.name.clone())),
+                    VariantFormat::NewType(format) => self.format(format)?,
+                    VariantFormat::Tuple(formats) => self.values(formats)?,
+                    VariantFormat::Struct(fields) => self.fields(fields)?,
+                };
+                Ok(Value::Object(Map::from_iter([(
+                    variant.name.clone(),
+                    value,
+                )])))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use serde_reflection::{
+        ContainerFormat, Format, Named, Registry, Samples, Tracer, TracerConfig,
+    };
+
+    use super::{ApplicationAbi, DecodeError, MAX_CONTAINER_DEPTH};
+
+    #[derive(Serialize, Deserialize)]
+    enum Operation {
+        Transfer { to: String, amount: u128 },
+        Burn(u64),
+        Swap(i8, bool),
+        Pause,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Batch {
+        operations: Vec<Operation>,
+        memo: Option<String>,
+        limits: BTreeMap<String, i128>,
+        pairs: BTreeMap<u8, u16>,
+        checksum: [u8; 2],
+    }
+
+    fn batch_abi() -> (ApplicationAbi, Format) {
+        let mut tracer = Tracer::new(TracerConfig::default());
+        let samples = Samples::new();
+        tracer.trace_simple_type::<Operation>().unwrap();
+        let (format, _) = tracer.trace_type::<Batch>(&samples).unwrap();
+        let abi = ApplicationAbi {
+            types: tracer.registry().unwrap(),
+            ..ApplicationAbi::default()
+        };
+        (abi, format)
+    }
+
+    #[test]
+    fn test_decode() {
+        let (abi, format) = batch_abi();
+        let batch = Batch {
+            operations: vec![
+                Operation::Transfer {
+                    to: "alice".to_owned(),
+                    amount: u128::MAX,
+                },
+                Operation::Burn(7),
+                Operation::Swap(-1, true),
+                Operation::Pause,
+            ],
+            memo: None,
+            limits: BTreeMap::from([("daily".to_owned(), -3)]),
+            pairs: BTreeMap::from([(1, 2)]),
+            checksum: [0xab, 0xcd],
+        };
+        let bytes = bcs::to_bytes(&batch).unwrap();
+        assert_eq!(
+            abi.decode(&format, &bytes).unwrap(),
+            json!({
+                "operations": [
+                    {"Transfer": {"to": "alice", "amount": u128::MAX.to_string()}},
+                    {"Burn": 7},
+                    {"Swap": [-1, true]},
+                    "Pause",
+                ],
+                "memo": null,
+                "limits": {"daily": "-3"},
+                "pairs": [[1, 2]],
+                "checksum": [0xab, 0xcd],
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_bytes() {
+        let abi = ApplicationAbi::default();
+        let bytes = bcs::to_bytes(&bcs::to_bytes(&"hi").unwrap()).unwrap();
+        assert_eq!(abi.decode(&Format::Bytes, &bytes).unwrap(), json!("026869"));
+    }
+
+    #[test]
+    fn test_decode_invalid_input() {
+        let (abi, format) = batch_abi();
+        let mut bytes = bcs::to_bytes(&Batch {
+            operations: vec![Operation::Burn(1)],
+            memo: Some("memo".to_owned()),
+            limits: BTreeMap::new(),
+            pairs: BTreeMap::new(),
+            checksum: [0, 0],
+        })
+        .unwrap();
+        assert!(abi.decode(&format, &bytes).is_ok());
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(matches!(
+            abi.decode(&format, truncated),
+            Err(DecodeError::UnexpectedEnd)
+        ));
+        bytes.push(0);
+        assert!(matches!(
+            abi.decode(&format, &bytes),
+            Err(DecodeError::TrailingBytes(1))
+        ));
+        // One operation, with the unknown variant index 4.
+        assert!(matches!(
+            abi.decode(&format, &[1, 4]),
+            Err(DecodeError::UnknownVariant(4))
+        ));
+        // The option tag of the memo is 2.
+        assert!(matches!(
+            abi.decode(&format, &[0, 2]),
+            Err(DecodeError::InvalidTag(2))
+        ));
+        // The number of operations is not encoded canonically.
+        assert!(matches!(
+            abi.decode(&format, &[0x80, 0x00]),
+            Err(DecodeError::InvalidLength)
+        ));
+        // The number of operations exceeds the size of the input.
+        assert!(matches!(
+            abi.decode(&format, &[0xff, 0xff, 0x03]),
+            Err(DecodeError::InvalidLength)
+        ));
+        assert!(matches!(
+            abi.decode(&Format::Str, &[2, 0xff, 0xfe]),
+            Err(DecodeError::InvalidString)
+        ));
+        assert!(matches!(
+            abi.decode(&Format::TypeName("Unknown".to_owned()), &[]),
+            Err(DecodeError::UnknownType(_))
+        ));
+        assert!(matches!(
+            abi.decode(&Format::F64, &[0; 8]),
+            Err(DecodeError::UnsupportedFormat("F64"))
+        ));
+    }
+
+    #[test]
+    fn test_decode_depth_limit() {
+        // A linked list: `struct Node { next: Option<Node> }`.
+        let node = Format::TypeName("Node".to_owned());
+        let types = Registry::from([(
+            "Node".to_owned(),
+            ContainerFormat::Struct(vec![Named {
+                name: "next".to_owned(),
+                value: Format::Option(Box::new(node.clone())),
+            }]),
+        )]);
+        let abi = ApplicationAbi {
+            types,
+            ..ApplicationAbi::default()
+        };
+        let list = |len: usize| {
+            let mut bytes = vec![1; len - 1];
+            bytes.push(0);
+            bytes
+        };
+        assert!(abi.decode(&node, &list(MAX_CONTAINER_DEPTH)).is_ok());
+        assert!(matches!(
+            abi.decode(&node, &list(MAX_CONTAINER_DEPTH + 1)),
+            Err(DecodeError::TooDeep)
+        ));
+    }
+}
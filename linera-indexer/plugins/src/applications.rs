@@ -0,0 +1,713 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A plugin indexing the operations, outgoing messages and events of each user application,
+//! and decoding them with the ABIs registered for the applications.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_graphql::{Enum, Json, SimpleObject};
+use axum::Router;
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::BlockHeight,
+    hashed::Hashed,
+    identifiers::{ApplicationId, ChainId, GenericApplicationId},
+};
+use linera_chain::types::ConfirmedBlock;
+use linera_execution::{Message, Operation};
+use linera_indexer::{
+    common::IndexerError,
+    plugin::{load, route_with_mutation, sdl_with_mutation, Plugin},
+};
+use linera_views::{
+    collection_view::CollectionView,
+    context::{Context, ViewContext},
+    map_view::MapView,
+    store::KeyValueStore,
+    views::{RootView, View},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::abi::ApplicationAbi;
+
+/// Whether an application entry is an operation or a message.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Enum)]
+pub enum EntryKind {
+    Operation,
+    Message,
+}
+
+/// An operation or an outgoing message of an application, as stored by the plugin.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ApplicationEntry {
+    kind: EntryKind,
+    chain_id: ChainId,
+    height: BlockHeight,
+    block: CryptoHash,
+    /// The index of the transaction in the block, counting the incoming bundles first.
+    transaction_index: u32,
+    bytes: Vec<u8>,
+}
+
+/// An event emitted by an application, as stored by the plugin.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct EventEntry {
+    chain_id: ChainId,
+    height: BlockHeight,
+    block: CryptoHash,
+    /// The index of the transaction in the block, counting the incoming bundles first.
+    transaction_index: u32,
+    stream_name: Vec<u8>,
+    key: Vec<u8>,
+    value: Vec<u8>,
+}
+
+/// An operation or an outgoing message of an application, decoded with its ABI if possible.
+#[derive(SimpleObject)]
+pub struct DecodedEntry {
+    /// The position of the entry among those of the application.
+    index: u64,
+    kind: EntryKind,
+    chain_id: ChainId,
+    height: BlockHeight,
+    block: CryptoHash,
+    /// The index of the transaction in the block, counting the incoming bundles first.
+    transaction_index: u32,
+    /// The serialized entry, in hexadecimal.
+    bytes: String,
+    /// The entry decoded to JSON, if the ABI of the application is registered.
+    content: Option<Json<Value>>,
+    /// The reason why the entry could not be decoded with the registered ABI.
+    decode_error: Option<String>,
+}
+
+/// An event emitted by an application, decoded with its ABI if possible.
+#[derive(SimpleObject)]
+pub struct DecodedEvent {
+    /// The position of the event among those of the application.
+    index: u64,
+    chain_id: ChainId,
+    height: BlockHeight,
+    block: CryptoHash,
+    /// The index of the transaction in the block, counting the incoming bundles first.
+    transaction_index: u32,
+    /// The name of the stream the event was emitted on, decoded as UTF-8.
+    stream_name: String,
+    /// The key of the event, in hexadecimal.
+    key: String,
+    /// The serialized value of the event, in hexadecimal.
+    value: String,
+    /// The value decoded to JSON, if the ABI of the application has a format for the stream.
+    content: Option<Json<Value>>,
+    /// The reason why the value could not be decoded with the registered ABI.
+    decode_error: Option<String>,
+}
+
+/// The indices of some entries or events of an application, grouped by a label, so that
+/// those with a given label are found without scanning the others.
+#[derive(View)]
+pub struct LabelIndex<C> {
+    /// The number of indices with each label.
+    count: MapView<C, String, u64>,
+    /// The indices with each label, in increasing order, by position.
+    indices: MapView<C, (String, u64), u64>,
+}
+
+impl<C> LabelIndex<C>
+where
+    C: Context + Send + Sync + 'static + Clone,
+{
+    /// Appends an index with the given label.
+    async fn push(&mut self, label: String, index: u64) -> Result<(), IndexerError> {
+        let position = self.count.get(&label).await?.unwrap_or(0);
+        self.indices.insert(&(label.clone(), position), index)?;
+        Ok(self.count.insert(&label, position + 1)?)
+    }
+
+    /// Returns at most `limit` indices with the given label, in downward order, starting with
+    /// the last one not greater than `from`.
+    async fn find(&self, label: &str, from: u64, limit: usize) -> Result<Vec<u64>, IndexerError> {
+        let label = label.to_owned();
+        let count = self.count.get(&label).await?.unwrap_or(0);
+        // Binary search for the number of indices not greater than `from`.
+        let (mut low, mut high) = (0, count);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.indices.get(&(label.clone(), middle)).await? {
+                Some(index) if index <= from => low = middle + 1,
+                _ => high = middle,
+            }
+        }
+        let mut result = Vec::new();
+        for position in (0..low).rev().take(limit) {
+            if let Some(index) = self.indices.get(&(label.clone(), position)).await? {
+                result.push(index);
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[derive(RootView)]
+pub struct Applications<C> {
+    /// The ABI registered for each application, in JSON.
+    abis: MapView<C, ApplicationId, String>,
+    /// The number of entries of each application.
+    count: MapView<C, ApplicationId, u64>,
+    /// The entries of each application, by index.
+    entries: MapView<C, (ApplicationId, u64), ApplicationEntry>,
+    /// The number of events of each application.
+    event_count: MapView<C, ApplicationId, u64>,
+    /// The events of each application, by index.
+    events: MapView<C, (ApplicationId, u64), EventEntry>,
+    /// The indices of the entries of each application, by the enum variant of their content
+    /// decoded with the registered ABI.
+    variants: CollectionView<C, ApplicationId, LabelIndex<C>>,
+    /// The indices of the events of each application, by stream name.
+    streams: CollectionView<C, ApplicationId, LabelIndex<C>>,
+    /// The height of the last block registered for each chain.
+    last_height: MapView<C, ChainId, BlockHeight>,
+}
+
+impl<C> Applications<C>
+where
+    C: Context + Send + Sync + 'static + Clone,
+{
+    /// Appends an entry to those of `application_id`, indexing it by enum variant if the
+    /// application's ABI is known.
+    async fn register_entry(
+        &mut self,
+        application_id: ApplicationId,
+        entry: ApplicationEntry,
+        abi: Option<&ApplicationAbi>,
+    ) -> Result<(), IndexerError> {
+        let index = self.count.get(&application_id).await?.unwrap_or(0);
+        if let Some(abi) = abi {
+            self.index_variant(application_id, index, &entry, abi)
+                .await?;
+        }
+        self.entries.insert(&(application_id, index), entry)?;
+        Ok(self.count.insert(&application_id, index + 1)?)
+    }
+
+    /// Records the enum variant of an entry's decoded content, if it is one.
+    async fn index_variant(
+        &mut self,
+        application_id: ApplicationId,
+        index: u64,
+        entry: &ApplicationEntry,
+        abi: &ApplicationAbi,
+    ) -> Result<(), IndexerError> {
+        let content = decode(index, entry.clone(), Some(abi)).content;
+        if let Some(variant) = variant_name(&content) {
+            let variant = variant.to_owned();
+            self.variants
+                .load_entry_mut(&application_id)
+                .await?
+                .push(variant, index)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Indexes the entries of `application_id` by enum variant again, with a new ABI.
+    async fn reindex_variants(
+        &mut self,
+        application_id: ApplicationId,
+        abi: &ApplicationAbi,
+    ) -> Result<(), IndexerError> {
+        self.variants.reset_entry_to_default(&application_id)?;
+        let count = self.count.get(&application_id).await?.unwrap_or(0);
+        for index in 0..count {
+            if let Some(entry) = self.entries.get(&(application_id, index)).await? {
+                self.index_variant(application_id, index, &entry, abi)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends an event to those of `application_id`.
+    async fn register_event(
+        &mut self,
+        application_id: ApplicationId,
+        event: EventEntry,
+    ) -> Result<(), IndexerError> {
+        let index = self.event_count.get(&application_id).await?.unwrap_or(0);
+        let stream_name = String::from_utf8_lossy(&event.stream_name).into_owned();
+        self.streams
+            .load_entry_mut(&application_id)
+            .await?
+            .push(stream_name, index)
+            .await?;
+        self.events.insert(&(application_id, index), event)?;
+        Ok(self.event_count.insert(&application_id, index + 1)?)
+    }
+
+    /// Returns the ABI registered for `application_id`, if any, reading it from `cache` if it
+    /// was already parsed.
+    async fn cached_abi<'a>(
+        &self,
+        cache: &'a mut BTreeMap<ApplicationId, Option<ApplicationAbi>>,
+        application_id: ApplicationId,
+    ) -> Result<Option<&'a ApplicationAbi>, IndexerError> {
+        if !cache.contains_key(&application_id) {
+            let abi = self.abi(application_id).await?;
+            cache.insert(application_id, abi);
+        }
+        Ok(cache[&application_id].as_ref())
+    }
+
+    /// Returns the ABI registered for `application_id`, if any.
+    async fn abi(
+        &self,
+        application_id: ApplicationId,
+    ) -> Result<Option<ApplicationAbi>, IndexerError> {
+        let Some(abi) = self.abis.get(&application_id).await? else {
+            return Ok(None);
+        };
+        match serde_json::from_str(&abi) {
+            Ok(abi) => Ok(Some(abi)),
+            Err(error) => {
+                warn!("Invalid ABI stored for application {application_id:?}: {error}");
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ApplicationsPlugin<C> {
+    state: Arc<Mutex<Applications<C>>>,
+    /// The token required to register ABIs. Registration is disabled without one.
+    abi_token: Option<Arc<str>>,
+}
+
+impl<C> ApplicationsPlugin<C> {
+    /// Allows registering ABIs with the `registerAbi` mutation, given the token.
+    pub fn with_abi_token(mut self, token: impl Into<Arc<str>>) -> Self {
+        self.abi_token = Some(token.into());
+        self
+    }
+
+    fn mutation(&self) -> ApplicationsMutation<C> {
+        ApplicationsMutation {
+            state: self.state.clone(),
+            abi_token: self.abi_token.clone(),
+        }
+    }
+}
+
+/// The mutations of the [`ApplicationsPlugin`].
+#[derive(Clone)]
+pub struct ApplicationsMutation<C> {
+    state: Arc<Mutex<Applications<C>>>,
+    abi_token: Option<Arc<str>>,
+}
+
+static NAME: &str = "applications";
+
+/// Implements `Plugin`
+#[async_trait::async_trait]
+impl<S> Plugin<S> for ApplicationsPlugin<ViewContext<(), S>>
+where
+    S: KeyValueStore + Clone + Send + Sync + 'static,
+    S::Error: From<bcs::Error> + Send + Sync + std::error::Error + 'static,
+{
+    fn name(&self) -> String {
+        NAME.to_string()
+    }
+
+    async fn load(store: S) -> Result<Self, IndexerError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            state: load(store, NAME).await?,
+            abi_token: None,
+        })
+    }
+
+    async fn register(&self, value: &Hashed<ConfirmedBlock>) -> Result<(), IndexerError> {
+        let mut plugin = self.state.lock().await;
+        let block = value.inner().block();
+        let chain_id = block.header.chain_id;
+        let height = block.header.height;
+        if let Some(last_height) = plugin.last_height.get(&chain_id).await? {
+            if last_height >= height {
+                return Ok(());
+            }
+        }
+        let make_entry = |kind, transaction_index: usize, bytes: &Vec<u8>| ApplicationEntry {
+            kind,
+            chain_id,
+            height,
+            block: value.hash(),
+            transaction_index: transaction_index as u32,
+            bytes: bytes.clone(),
+        };
+        // The ABIs of the applications with entries in the block, parsed once.
+        let mut abis = BTreeMap::new();
+        let num_bundles = block.body.incoming_bundles.len();
+        for (index, operation) in block.body.operations.iter().enumerate() {
+            if let Operation::User {
+                application_id,
+                bytes,
+            } = operation
+            {
+                let entry = make_entry(EntryKind::Operation, num_bundles + index, bytes);
+                let abi = plugin.cached_abi(&mut abis, *application_id).await?;
+                plugin.register_entry(*application_id, entry, abi).await?;
+            }
+        }
+        for (transaction_index, messages) in block.body.messages.iter().enumerate() {
+            for outgoing_message in messages {
+                if let Message::User {
+                    application_id,
+                    bytes,
+                } = &outgoing_message.message
+                {
+                    let entry = make_entry(EntryKind::Message, transaction_index, bytes);
+                    let abi = plugin.cached_abi(&mut abis, *application_id).await?;
+                    plugin.register_entry(*application_id, entry, abi).await?;
+                }
+            }
+        }
+        for (transaction_index, events) in block.body.events.iter().enumerate() {
+            for event in events {
+                if let GenericApplicationId::User(application_id) = event.stream_id.application_id {
+                    let event = EventEntry {
+                        chain_id,
+                        height,
+                        block: value.hash(),
+                        transaction_index: transaction_index as u32,
+                        stream_name: event.stream_id.stream_name.0.clone(),
+                        key: event.key.clone(),
+                        value: event.value.clone(),
+                    };
+                    plugin.register_event(application_id, event).await?;
+                }
+            }
+        }
+        plugin.last_height.insert(&chain_id, height)?;
+        info!("register application entries of {chain_id:?} at height {height}");
+        Ok(plugin.save().await?)
+    }
+
+    fn sdl(&self) -> String {
+        sdl_with_mutation(self.clone(), self.mutation())
+    }
+
+    fn route(&self, app: Router) -> Router {
+        route_with_mutation(&self.name(), self.clone(), self.mutation(), app)
+    }
+}
+
+/// Implements `ObjectType`
+#[async_graphql::Object(cache_control(no_cache))]
+impl<C> ApplicationsPlugin<C>
+where
+    C: Context + Send + Sync + 'static + Clone,
+{
+    /// Gets the entries of an application in downward order, from the given index or from
+    /// the last one, optionally only those whose decoded content is the given enum variant
+    pub async fn entries(
+        &self,
+        application_id: ApplicationId,
+        from: Option<u64>,
+        limit: Option<u32>,
+        variant: Option<String>,
+    ) -> Result<Vec<DecodedEntry>, IndexerError> {
+        let plugin = self.state.lock().await;
+        let count = plugin.count.get(&application_id).await?.unwrap_or(0);
+        let Some(last) = count.checked_sub(1) else {
+            return Ok(Vec::new());
+        };
+        let from = from.map_or(last, |from| from.min(last));
+        let abi = plugin.abi(application_id).await?;
+        let limit = limit.unwrap_or(20) as usize;
+        let indices = match &variant {
+            None => (0..=from).rev().take(limit).collect(),
+            Some(variant) => match plugin.variants.try_load_entry(&application_id).await? {
+                None => Vec::new(),
+                Some(variants) => variants.find(variant, from, limit).await?,
+            },
+        };
+        let mut result = Vec::new();
+        for index in indices {
+            if let Some(entry) = plugin.entries.get(&(application_id, index)).await? {
+                result.push(decode(index, entry, abi.as_ref()));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Gets the events of an application in downward order, from the given index or from
+    /// the last one, optionally only those of the given stream
+    pub async fn events(
+        &self,
+        application_id: ApplicationId,
+        from: Option<u64>,
+        limit: Option<u32>,
+        stream_name: Option<String>,
+    ) -> Result<Vec<DecodedEvent>, IndexerError> {
+        let plugin = self.state.lock().await;
+        let count = plugin.event_count.get(&application_id).await?.unwrap_or(0);
+        let Some(last) = count.checked_sub(1) else {
+            return Ok(Vec::new());
+        };
+        let from = from.map_or(last, |from| from.min(last));
+        let abi = plugin.abi(application_id).await?;
+        let limit = limit.unwrap_or(20) as usize;
+        let indices = match &stream_name {
+            None => (0..=from).rev().take(limit).collect(),
+            Some(stream_name) => match plugin.streams.try_load_entry(&application_id).await? {
+                None => Vec::new(),
+                Some(streams) => streams.find(stream_name, from, limit).await?,
+            },
+        };
+        let mut result = Vec::new();
+        for index in indices {
+            if let Some(event) = plugin.events.get(&(application_id, index)).await? {
+                result.push(decode_event(index, event, abi.as_ref()));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Gets the number of events registered for an application
+    pub async fn event_count(&self, application_id: ApplicationId) -> Result<u64, IndexerError> {
+        let plugin = self.state.lock().await;
+        Ok(plugin.event_count.get(&application_id).await?.unwrap_or(0))
+    }
+
+    /// Gets an entry of an application
+    pub async fn entry(
+        &self,
+        application_id: ApplicationId,
+        index: u64,
+    ) -> Result<Option<DecodedEntry>, IndexerError> {
+        let plugin = self.state.lock().await;
+        let Some(entry) = plugin.entries.get(&(application_id, index)).await? else {
+            return Ok(None);
+        };
+        let abi = plugin.abi(application_id).await?;
+        Ok(Some(decode(index, entry, abi.as_ref())))
+    }
+
+    /// Gets the number of entries registered for an application
+    pub async fn count(&self, application_id: ApplicationId) -> Result<u64, IndexerError> {
+        let plugin = self.state.lock().await;
+        Ok(plugin.count.get(&application_id).await?.unwrap_or(0))
+    }
+
+    /// Gets the ABI registered for an application
+    pub async fn abi(
+        &self,
+        application_id: ApplicationId,
+    ) -> Result<Option<Json<ApplicationAbi>>, IndexerError> {
+        let plugin = self.state.lock().await;
+        Ok(plugin.abi(application_id).await?.map(Json))
+    }
+}
+
+#[async_graphql::Object(cache_control(no_cache))]
+impl<C> ApplicationsMutation<C>
+where
+    C: Context + Send + Sync + 'static + Clone,
+{
+    /// Registers the ABI used to decode the operations, messages and events of an
+    /// application, replacing the previous one, if any. Requires the token configured by the
+    /// operator of the indexer
+    pub async fn register_abi(
+        &self,
+        application_id: ApplicationId,
+        abi: Json<ApplicationAbi>,
+        token: String,
+    ) -> async_graphql::Result<bool> {
+        let Some(abi_token) = &self.abi_token else {
+            return Err(async_graphql::Error::new(
+                "ABI registration is disabled on this indexer",
+            ));
+        };
+        if token.as_bytes() != abi_token.as_bytes() {
+            return Err(async_graphql::Error::new("Invalid ABI registration token"));
+        }
+        let mut plugin = self.state.lock().await;
+        plugin
+            .abis
+            .insert(&application_id, serde_json::to_string(&abi.0)?)?;
+        plugin.reindex_variants(application_id, &abi.0).await?;
+        plugin.save().await?;
+        Ok(true)
+    }
+}
+
+/// Decodes an entry with the ABI of its application.
+fn decode(index: u64, entry: ApplicationEntry, abi: Option<&ApplicationAbi>) -> DecodedEntry {
+    let decoded = abi.and_then(|abi| match entry.kind {
+        EntryKind::Operation => abi.decode_operation(&entry.bytes),
+        EntryKind::Message => abi.decode_message(&entry.bytes),
+    });
+    let (content, decode_error) = match decoded {
+        None => (None, None),
+        Some(Ok(value)) => (Some(Json(value)), None),
+        Some(Err(error)) => (None, Some(error.to_string())),
+    };
+    DecodedEntry {
+        index,
+        kind: entry.kind,
+        chain_id: entry.chain_id,
+        height: entry.height,
+        block: entry.block,
+        transaction_index: entry.transaction_index,
+        bytes: hex::encode(&entry.bytes),
+        content,
+        decode_error,
+    }
+}
+
+/// Decodes an event with the ABI of its application.
+fn decode_event(index: u64, event: EventEntry, abi: Option<&ApplicationAbi>) -> DecodedEvent {
+    let stream_name = String::from_utf8_lossy(&event.stream_name).into_owned();
+    let decoded = abi.and_then(|abi| abi.decode_event(&stream_name, &event.value));
+    let (content, decode_error) = match decoded {
+        None => (None, None),
+        Some(Ok(value)) => (Some(Json(value)), None),
+        Some(Err(error)) => (None, Some(error.to_string())),
+    };
+    DecodedEvent {
+        index,
+        chain_id: event.chain_id,
+        height: event.height,
+        block: event.block,
+        transaction_index: event.transaction_index,
+        stream_name,
+        key: hex::encode(&event.key),
+        value: hex::encode(&event.value),
+        content,
+        decode_error,
+    }
+}
+
+/// Returns the name of the enum variant of a decoded value, if it is one.
+fn variant_name(content: &Option<Json<Value>>) -> Option<&str> {
+    match &content.as_ref()?.0 {
+        Value::String(name) => Some(name),
+        Value::Object(object) if object.len() == 1 => object.keys().next().map(String::as_str),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::{
+        crypto::CryptoHash,
+        data_types::BlockHeight,
+        identifiers::{ApplicationId, BytecodeId, ChainId, MessageId},
+    };
+    use linera_views::{context::create_test_memory_context, views::View};
+    use serde::{Deserialize, Serialize};
+    use serde_reflection::{Tracer, TracerConfig};
+
+    use super::{ApplicationEntry, Applications, EntryKind, LabelIndex};
+    use crate::abi::ApplicationAbi;
+
+    #[derive(Serialize, Deserialize)]
+    enum Operation {
+        Burn(u64),
+        Pause,
+    }
+
+    fn operation_abi() -> ApplicationAbi {
+        let mut tracer = Tracer::new(TracerConfig::default());
+        let (format, _) = tracer.trace_simple_type::<Operation>().unwrap();
+        ApplicationAbi {
+            types: tracer.registry().unwrap(),
+            operation: Some(format),
+            ..ApplicationAbi::default()
+        }
+    }
+
+    fn operation_entry(operation: &Operation) -> ApplicationEntry {
+        ApplicationEntry {
+            kind: EntryKind::Operation,
+            chain_id: ChainId::root(0),
+            height: BlockHeight::ZERO,
+            block: CryptoHash::test_hash("block"),
+            transaction_index: 0,
+            bytes: bcs::to_bytes(operation).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_label_index_find() {
+        let mut index = LabelIndex::load(create_test_memory_context())
+            .await
+            .unwrap();
+        for (label, position) in [("a", 0), ("b", 1), ("a", 2), ("a", 3), ("b", 4)] {
+            index.push(label.to_owned(), position).await.unwrap();
+        }
+        assert_eq!(index.find("a", 4, 10).await.unwrap(), vec![3, 2, 0]);
+        assert_eq!(index.find("a", 2, 10).await.unwrap(), vec![2, 0]);
+        assert_eq!(index.find("a", 4, 2).await.unwrap(), vec![3, 2]);
+        assert_eq!(index.find("b", 3, 10).await.unwrap(), vec![1]);
+        assert_eq!(index.find("b", 0, 10).await.unwrap(), Vec::<u64>::new());
+        assert_eq!(index.find("c", 4, 10).await.unwrap(), Vec::<u64>::new());
+    }
+
+    #[tokio::test]
+    async fn test_entries_are_indexed_by_variant() {
+        let hash = CryptoHash::test_hash("bytecode");
+        let application_id = ApplicationId {
+            bytecode_id: BytecodeId::new(hash, hash),
+            creation: MessageId {
+                chain_id: ChainId::root(0),
+                height: BlockHeight::ZERO,
+                index: 0,
+            },
+        };
+        let mut applications = Applications::load(create_test_memory_context())
+            .await
+            .unwrap();
+
+        // Entries registered before the ABI are indexed when it is registered.
+        for operation in [Operation::Burn(1), Operation::Pause, Operation::Burn(2)] {
+            let entry = operation_entry(&operation);
+            applications
+                .register_entry(application_id, entry, None)
+                .await
+                .unwrap();
+        }
+        assert!(applications
+            .variants
+            .try_load_entry(&application_id)
+            .await
+            .unwrap()
+            .is_none());
+        let abi = operation_abi();
+        applications
+            .reindex_variants(application_id, &abi)
+            .await
+            .unwrap();
+
+        let entry = operation_entry(&Operation::Burn(3));
+        applications
+            .register_entry(application_id, entry, Some(&abi))
+            .await
+            .unwrap();
+
+        let variants = applications
+            .variants
+            .try_load_entry(&application_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(variants.find("Burn", 3, 10).await.unwrap(), vec![3, 2, 0]);
+        assert_eq!(variants.find("Pause", 3, 10).await.unwrap(), vec![1]);
+    }
+}
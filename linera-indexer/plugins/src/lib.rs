@@ -3,4 +3,6 @@
 
 //! Plugins for Linera indexer.
 
+pub mod abi;
+pub mod applications;
 pub mod operations;
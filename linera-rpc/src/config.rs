@@ -1,6 +1,8 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::path::PathBuf;
+
 use linera_base::identifiers::ChainId;
 use linera_execution::committee::ValidatorName;
 use serde::{Deserialize, Serialize};
@@ -33,6 +35,14 @@ pub struct CrossChainConfig {
     /// How many concurrent tasks to spawn for cross-chain message handling RPCs.
     #[arg(long = "cross-chain-max-tasks", default_value = "10")]
     pub(crate) max_concurrent_tasks: usize,
+
+    /// Interval between retransmissions of the cross-chain messages that were not acknowledged
+    /// by their recipients yet.
+    #[arg(
+        long = "cross-chain-retransmission-interval-ms",
+        default_value = "30000"
+    )]
+    pub(crate) retransmission_interval_ms: u64,
 }
 
 #[derive(Clone, Debug, clap::Parser)]
@@ -42,6 +52,29 @@ pub struct NotificationConfig {
     pub(crate) notification_queue_size: usize,
 }
 
+/// The certificates used to secure the gRPC connections of a validator.
+///
+/// Without a certificate, the public endpoint of the proxy uses a self-signed one. Using TLS on
+/// the internal network requires all three options: the proxy and the shards then use mutual
+/// TLS, and only accept connections from clients presenting a certificate signed by the client
+/// certificate authority.
+#[derive(Clone, Debug, Default, clap::Args)]
+pub struct TlsCertificateConfig {
+    /// Path to the PEM-encoded certificate chain presented by the gRPC servers, and by the
+    /// clients connecting to the internal network.
+    #[arg(long = "tls-cert-path", requires = "key_path")]
+    pub cert_path: Option<PathBuf>,
+
+    /// Path to the PEM-encoded private key of the certificate in `--tls-cert-path`.
+    #[arg(long = "tls-key-path", requires = "cert_path")]
+    pub key_path: Option<PathBuf>,
+
+    /// Path to the PEM-encoded certificate of the authority signing the certificates of the
+    /// proxy and the shards, used to authenticate both sides of internal connections.
+    #[arg(long = "tls-client-ca-path")]
+    pub client_ca_path: Option<PathBuf>,
+}
+
 pub type ShardId = usize;
 
 /// The network configuration of a shard.
@@ -138,6 +171,11 @@ impl ValidatorInternalNetworkConfig {
     pub fn proxy_address(&self) -> String {
         format!("{}://{}:{}", self.protocol.scheme(), self.host, self.port)
     }
+
+    /// Returns the address of a `shard`, with the scheme of the internal protocol.
+    pub fn shard_address(&self, shard: &ShardConfig) -> String {
+        format!("{}://{}:{}", self.protocol.scheme(), shard.host, shard.port)
+    }
 }
 
 impl ValidatorPublicNetworkConfig {
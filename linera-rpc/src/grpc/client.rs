@@ -28,13 +28,13 @@ use tonic::{Code, IntoRequest, Request, Status};
 use tracing::{debug, error, info, instrument, warn};
 #[cfg(not(web))]
 use {
-    super::GrpcProtoConversionError,
+    super::{GrpcCompression, GrpcProtoConversionError},
     crate::{mass_client, RpcMessage},
 };
 
 use super::{
     api::{self, validator_node_client::ValidatorNodeClient, SubscriptionRequest},
-    transport, GRPC_MAX_MESSAGE_SIZE,
+    protocol_version, trace_context, transport, GRPC_MAX_MESSAGE_SIZE,
 };
 use crate::{
     HandleConfirmedCertificateRequest, HandleLiteCertRequest, HandleTimeoutCertificateRequest,
@@ -59,6 +59,12 @@ impl GrpcClient {
         let client = ValidatorNodeClient::new(channel)
             .max_encoding_message_size(GRPC_MAX_MESSAGE_SIZE)
             .max_decoding_message_size(GRPC_MAX_MESSAGE_SIZE);
+        #[cfg(not(web))]
+        let client = GrpcCompression::ACCEPTED_ENCODINGS
+            .into_iter()
+            .fold(client, |client, encoding| {
+                client.accept_compressed(encoding)
+            });
         Self {
             address,
             client,
@@ -67,15 +73,36 @@ impl GrpcClient {
         }
     }
 
+    /// Compresses the requests sent to the validator with the given `compression`.
+    #[cfg(not(web))]
+    pub fn with_compression(mut self, compression: GrpcCompression) -> Self {
+        if let Some(encoding) = compression.encoding() {
+            self.client = self.client.send_compressed(encoding);
+        }
+        self
+    }
+
     pub fn create(address: String, node_options: NodeOptions) -> Self {
         let options = (&node_options).into();
         let channel = transport::create_channel(address.clone(), &options).unwrap();
-        Self::new(
+        let client = Self::new(
             address,
             channel,
             node_options.retry_delay,
             node_options.max_retries,
-        )
+        );
+        #[cfg(not(web))]
+        let client = client.with_compression(node_options.compression);
+        client
+    }
+
+    /// Wraps `message` in a request advertising the protocol versions supported by this node,
+    /// and carrying the trace context of the current span.
+    fn request<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        protocol_version::add_protocol_version(&mut request);
+        trace_context::add_trace_context(&mut request);
+        request
     }
 
     /// Returns whether this gRPC status means the server stream should be reconnected to, or not.
@@ -86,7 +113,11 @@ impl GrpcClient {
                 info!("gRPC request interrupted: {}; retrying", status);
                 true
             }
-            Code::Ok | Code::Cancelled | Code::ResourceExhausted => {
+            Code::ResourceExhausted => {
+                info!("gRPC request rate-limited: {}; retrying", status);
+                true
+            }
+            Code::Ok | Code::Cancelled => {
                 error!("Unexpected gRPC status: {}; retrying", status);
                 true
             }
@@ -122,7 +153,7 @@ impl GrpcClient {
             error: "could not convert request to proto".to_string(),
         })?;
         loop {
-            match f(self.client.clone(), Request::new(request_inner.clone())).await {
+            match f(self.client.clone(), Self::request(request_inner.clone())).await {
                 Err(s) if Self::is_retryable(&s) && retry_count < self.max_retries => {
                     let delay = self.retry_delay.saturating_mul(retry_count);
                     retry_count += 1;
@@ -130,6 +161,9 @@ impl GrpcClient {
                     continue;
                 }
                 Err(s) => {
+                    if let Some(error) = protocol_version::incompatible_protocol_version(&s) {
+                        return Err(error);
+                    }
                     return Err(NodeError::GrpcError {
                         error: format!("remote request [{handler}] failed with status: {s:?}"),
                     });
@@ -139,8 +173,9 @@ impl GrpcClient {
         }
     }
 
+    /// Returns the chain information of a successful `result`, or the error of the node.
     #[allow(clippy::result_large_err)]
-    fn try_into_chain_info(
+    pub fn try_into_chain_info(
         result: api::ChainInfoResult,
     ) -> Result<linera_core::data_types::ChainInfoResponse, NodeError> {
         let inner = result.inner.ok_or_else(|| NodeError::GrpcError {
@@ -288,10 +323,14 @@ impl ValidatorNode for GrpcClient {
         // Make the first connection attempt before returning from this method.
         let mut stream = Some(
             client
-                .subscribe(subscription_request.clone())
+                .subscribe(Self::request(subscription_request.clone()))
                 .await
-                .map_err(|status| NodeError::SubscriptionFailed {
-                    status: status.to_string(),
+                .map_err(|status| {
+                    protocol_version::incompatible_protocol_version(&status).unwrap_or_else(|| {
+                        NodeError::SubscriptionFailed {
+                            status: status.to_string(),
+                        }
+                    })
                 })?
                 .into_inner(),
         );
@@ -306,7 +345,10 @@ impl ValidatorNode for GrpcClient {
                 let stream = if let Some(stream) = stream.take() {
                     future::Either::Right(stream)
                 } else {
-                    match client.subscribe(subscription_request.clone()).await {
+                    match client
+                        .subscribe(Self::request(subscription_request.clone()))
+                        .await
+                    {
                         Err(err) => future::Either::Left(stream::iter(iter::once(Err(err)))),
                         Ok(response) => future::Either::Right(response.into_inner()),
                     }
@@ -9,7 +9,7 @@ use linera_base::{
     identifiers::{AccountOwner, BlobId, ChainId, Owner},
 };
 use linera_chain::{
-    data_types::{BlockProposal, LiteValue, ProposalContent},
+    data_types::{BlockProposal, LiteValue, ProposalContent, SignatureScheme},
     types::{
         Certificate, CertificateKind, ConfirmedBlock, ConfirmedBlockCertificate, LiteCertificate,
         Timeout, TimeoutCertificate, ValidatedBlock, ValidatedBlockCertificate,
@@ -44,6 +44,8 @@ pub enum GrpcProtoConversionError {
     InconsistentChainId,
     #[error("Unrecognized certificate type")]
     InvalidCertificateType,
+    #[error("Unrecognized signature scheme")]
+    InvalidSignatureScheme,
 }
 
 impl From<ed25519_dalek::SignatureError> for GrpcProtoConversionError {
@@ -203,6 +205,10 @@ impl TryFrom<BlockProposal> for api::BlockProposal {
                 .validated_block_certificate
                 .map(|cert| bincode::serialize(&cert))
                 .transpose()?,
+            signature_scheme: match block_proposal.signature_scheme {
+                SignatureScheme::Bcs => api::SignatureScheme::Bcs,
+                SignatureScheme::CanonicalJson => api::SignatureScheme::CanonicalJson,
+            } as i32,
         })
     }
 }
@@ -216,6 +222,15 @@ impl TryFrom<api::BlockProposal> for BlockProposal {
             Some(content.block.chain_id.into()) == block_proposal.chain_id,
             GrpcProtoConversionError::InconsistentChainId
         );
+        let signature_scheme = if block_proposal.signature_scheme
+            == api::SignatureScheme::Bcs as i32
+        {
+            SignatureScheme::Bcs
+        } else if block_proposal.signature_scheme == api::SignatureScheme::CanonicalJson as i32 {
+            SignatureScheme::CanonicalJson
+        } else {
+            return Err(GrpcProtoConversionError::InvalidSignatureScheme);
+        };
         Ok(Self {
             content,
             public_key: try_proto_convert(block_proposal.public_key)?,
@@ -225,6 +240,7 @@ impl TryFrom<api::BlockProposal> for BlockProposal {
                 .validated_block_certificate
                 .map(|bytes| bincode::deserialize(&bytes))
                 .transpose()?,
+            signature_scheme,
         })
     }
 }
@@ -567,6 +583,7 @@ impl TryFrom<api::ChainInfoQuery> for ChainInfoQuery {
             request_manager_values: chain_info_query.request_manager_values,
             request_leader_timeout: chain_info_query.request_leader_timeout,
             request_fallback: chain_info_query.request_fallback,
+            request_committee_certificates: chain_info_query.request_committee_certificates,
         })
     }
 }
@@ -596,6 +613,7 @@ impl TryFrom<ChainInfoQuery> for api::ChainInfoQuery {
             request_manager_values: chain_info_query.request_manager_values,
             request_leader_timeout: chain_info_query.request_leader_timeout,
             request_fallback: chain_info_query.request_fallback,
+            request_committee_certificates: chain_info_query.request_committee_certificates,
         })
     }
 }
@@ -1053,6 +1071,7 @@ pub mod tests {
             description: None,
             manager: Box::default(),
             chain_balance: Amount::ZERO,
+            base_fee: Amount::ZERO,
             block_hash: None,
             timestamp: Timestamp::default(),
             next_block_height: BlockHeight::ZERO,
@@ -1063,6 +1082,7 @@ pub mod tests {
             requested_sent_certificate_hashes: vec![],
             count_received_log: 0,
             requested_received_log: vec![],
+            requested_committee_certificate_hashes: vec![],
         });
 
         let chain_info_response_none = ChainInfoResponse {
@@ -1101,6 +1121,7 @@ pub mod tests {
             request_manager_values: false,
             request_leader_timeout: false,
             request_fallback: true,
+            request_committee_certificates: true,
         };
         round_trip_check::<_, api::ChainInfoQuery>(chain_info_query_some);
     }
@@ -1223,6 +1244,7 @@ pub mod tests {
             public_key,
             signature: Signature::new(&Foo("test".into()), &KeyPair::generate()),
             validated_block_certificate: Some(cert),
+            signature_scheme: SignatureScheme::CanonicalJson,
         };
 
         round_trip_check::<_, api::BlockProposal>(block_proposal);
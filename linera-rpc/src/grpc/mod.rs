@@ -5,8 +5,12 @@ mod client;
 mod conversions;
 mod node_provider;
 pub mod pool;
+pub mod protocol_version;
 #[cfg(with_server)]
 mod server;
+#[cfg(with_server)]
+mod tls;
+pub mod trace_context;
 pub mod transport;
 
 pub use client::*;
@@ -14,6 +18,10 @@ pub use conversions::*;
 pub use node_provider::*;
 #[cfg(with_server)]
 pub use server::*;
+#[cfg(with_server)]
+pub use tls::TlsCertificates;
+#[cfg(not(web))]
+use tonic::codec::CompressionEncoding;
 
 pub mod api {
     tonic::include_proto!("rpc.v1");
@@ -39,6 +47,56 @@ pub enum GrpcError {
     #[cfg(with_server)]
     #[error(transparent)]
     Reflection(#[from] tonic_reflection::server::Error),
+
+    #[cfg(with_server)]
+    #[error("failed to read TLS certificate {path:?}: {error}")]
+    ReadTlsCertificate {
+        path: std::path::PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+
+    #[cfg(with_server)]
+    #[error("a TLS certificate requires both `--tls-cert-path` and `--tls-key-path`")]
+    IncompleteTlsIdentity,
+
+    #[cfg(with_server)]
+    #[error(
+        "TLS on the internal network requires `--tls-cert-path`, `--tls-key-path` \
+        and `--tls-client-ca-path`"
+    )]
+    MissingInternalTlsCertificates,
+}
+
+/// The compression applied to the gRPC messages sent by a node.
+///
+/// Compressed messages are always accepted, whatever this setting is, so that nodes configured
+/// differently can still talk to each other. Compression is not supported on the web.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum GrpcCompression {
+    /// Messages are sent uncompressed.
+    #[default]
+    None,
+    /// Messages are compressed with gzip.
+    Gzip,
+    /// Messages are compressed with zstd.
+    Zstd,
+}
+
+#[cfg(not(web))]
+impl GrpcCompression {
+    /// The encodings of the compressed messages accepted from other nodes.
+    pub const ACCEPTED_ENCODINGS: [CompressionEncoding; 2] =
+        [CompressionEncoding::Gzip, CompressionEncoding::Zstd];
+
+    /// Returns the encoding to use for the messages sent, if they are compressed.
+    pub fn encoding(self) -> Option<CompressionEncoding> {
+        match self {
+            GrpcCompression::None => None,
+            GrpcCompression::Gzip => Some(CompressionEncoding::Gzip),
+            GrpcCompression::Zstd => Some(CompressionEncoding::Zstd),
+        }
+    }
 }
 
 const MEBIBYTE: usize = 1024 * 1024;
@@ -6,7 +6,7 @@ use std::str::FromStr as _;
 use linera_base::time::Duration;
 use linera_core::node::{NodeError, ValidatorNodeProvider};
 
-use super::GrpcClient;
+use super::{GrpcClient, GrpcCompression};
 use crate::{
     config::ValidatorPublicNetworkConfig,
     grpc::{pool::GrpcConnectionPool, transport},
@@ -18,6 +18,8 @@ pub struct GrpcNodeProvider {
     pool: GrpcConnectionPool,
     retry_delay: Duration,
     max_retries: u32,
+    #[cfg(not(web))]
+    compression: GrpcCompression,
 }
 
 impl GrpcNodeProvider {
@@ -30,6 +32,8 @@ impl GrpcNodeProvider {
             pool,
             retry_delay,
             max_retries,
+            #[cfg(not(web))]
+            compression: options.compression,
         }
     }
 }
@@ -51,11 +55,9 @@ impl ValidatorNodeProvider for GrpcNodeProvider {
                     error: format!("error creating channel: {}", error),
                 })?;
 
-        Ok(GrpcClient::new(
-            http_address,
-            channel,
-            self.retry_delay,
-            self.max_retries,
-        ))
+        let client = GrpcClient::new(http_address, channel, self.retry_delay, self.max_retries);
+        #[cfg(not(web))]
+        let client = client.with_compression(self.compression);
+        Ok(client)
     }
 }
@@ -10,6 +10,8 @@ use super::{transport, GrpcError};
 #[derive(Clone, Default)]
 pub struct GrpcConnectionPool {
     options: transport::Options,
+    #[cfg(not(web))]
+    tls_config: Option<tonic::transport::ClientTlsConfig>,
     channels: DashMap<String, transport::Channel>,
 }
 
@@ -17,6 +19,8 @@ impl GrpcConnectionPool {
     pub fn new(options: transport::Options) -> Self {
         Self {
             options,
+            #[cfg(not(web))]
+            tls_config: None,
             channels: DashMap::default(),
         }
     }
@@ -31,6 +35,16 @@ impl GrpcConnectionPool {
         self
     }
 
+    /// Uses `tls_config` instead of the default TLS configuration for the new channels.
+    #[cfg(not(web))]
+    pub fn with_tls_config(
+        mut self,
+        tls_config: impl Into<Option<tonic::transport::ClientTlsConfig>>,
+    ) -> Self {
+        self.tls_config = tls_config.into();
+        self
+    }
+
     /// Obtains a channel for the current address. Either clones an existing one (thereby
     /// reusing the connection), or creates one if needed. New channels do not create a
     /// connection immediately.
@@ -38,7 +52,19 @@ impl GrpcConnectionPool {
         Ok(self
             .channels
             .entry(address.clone())
-            .or_try_insert_with(|| transport::create_channel(address, &self.options))?
+            .or_try_insert_with(|| self.create_channel(address))?
             .clone())
     }
+
+    fn create_channel(&self, address: String) -> Result<transport::Channel, transport::Error> {
+        #[cfg(not(web))]
+        if let Some(tls_config) = &self.tls_config {
+            return transport::create_channel_with_tls_config(
+                address,
+                &self.options,
+                tls_config.clone(),
+            );
+        }
+        transport::create_channel(address, &self.options)
+    }
 }
@@ -0,0 +1,164 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Negotiation of the version of the RPC protocol used between nodes.
+//!
+//! Every request carries the range of protocol versions supported by its sender in its
+//! metadata. Servers check that this range overlaps their own, and otherwise reject the request
+//! with a status carrying their range, which clients report as a
+//! [`NodeError::IncompatibleProtocolVersion`]. The highest version supported by both sides is
+//! recorded in the extensions of the request, so that handlers can keep serving the clients of
+//! an adjacent release. Requests without versions come from nodes predating the negotiation, and
+//! are assumed to use [`LEGACY_PROTOCOL_VERSION`].
+
+use linera_core::node::NodeError;
+use tonic::{
+    metadata::{MetadataMap, MetadataValue},
+    Code, Request, Status,
+};
+
+/// The latest version of the RPC protocol supported by this node.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The oldest version of the RPC protocol still supported by this node.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// The version of the RPC protocol used by nodes which don't send their versions.
+pub const LEGACY_PROTOCOL_VERSION: u32 = 1;
+
+/// The metadata key of the latest protocol version supported by a node.
+pub const VERSION_HEADER: &str = "linera-protocol-version";
+/// The metadata key of the oldest protocol version supported by a node.
+pub const MIN_VERSION_HEADER: &str = "linera-min-protocol-version";
+
+/// The version of the RPC protocol agreed on for a request, available in its extensions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NegotiatedProtocolVersion(pub u32);
+
+/// Adds the range of protocol versions supported by this node to the metadata of `request`.
+pub fn add_protocol_version<T>(request: &mut Request<T>) {
+    insert_versions(request.metadata_mut());
+}
+
+/// Checks that the sender of `request` supports a protocol version in common with this node,
+/// and records the negotiated version in the extensions of the request.
+///
+/// This can be used as a [`tonic::service::Interceptor`].
+#[cfg(with_server)]
+pub fn negotiate_protocol_version(mut request: Request<()>) -> Result<Request<()>, Status> {
+    let (min_version, version) = read_versions(request.metadata())?
+        .unwrap_or((LEGACY_PROTOCOL_VERSION, LEGACY_PROTOCOL_VERSION));
+    let negotiated_version = version.min(PROTOCOL_VERSION);
+    if negotiated_version < min_version.max(MIN_PROTOCOL_VERSION) {
+        let mut status = Status::failed_precondition(format!(
+            "incompatible RPC protocol versions: the client supports versions {min_version} to \
+            {version}, but this node supports versions {MIN_PROTOCOL_VERSION} to \
+            {PROTOCOL_VERSION}"
+        ));
+        insert_versions(status.metadata_mut());
+        return Err(status);
+    }
+    request
+        .extensions_mut()
+        .insert(NegotiatedProtocolVersion(negotiated_version));
+    Ok(request)
+}
+
+/// Returns the error to report if a request was rejected with `status` because the server
+/// doesn't support any of the protocol versions of this node.
+pub fn incompatible_protocol_version(status: &Status) -> Option<NodeError> {
+    if status.code() != Code::FailedPrecondition {
+        return None;
+    }
+    let (remote_min_version, remote_version) = read_versions(status.metadata()).ok()??;
+    Some(NodeError::IncompatibleProtocolVersion {
+        min_version: MIN_PROTOCOL_VERSION,
+        version: PROTOCOL_VERSION,
+        remote_min_version,
+        remote_version,
+    })
+}
+
+fn insert_versions(metadata: &mut MetadataMap) {
+    metadata.insert(
+        MIN_VERSION_HEADER,
+        MetadataValue::from(MIN_PROTOCOL_VERSION),
+    );
+    metadata.insert(VERSION_HEADER, MetadataValue::from(PROTOCOL_VERSION));
+}
+
+/// Reads the range of protocol versions supported by a peer, if it sent one.
+fn read_versions(metadata: &MetadataMap) -> Result<Option<(u32, u32)>, Status> {
+    let parse = |header: &str| {
+        metadata
+            .get(header)
+            .map(|value| {
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(|value| value.parse::<u32>().ok())
+                    .ok_or_else(|| Status::invalid_argument(format!("invalid header {header}")))
+            })
+            .transpose()
+    };
+    match (parse(MIN_VERSION_HEADER)?, parse(VERSION_HEADER)?) {
+        (Some(min_version), Some(version)) => Ok(Some((min_version, version))),
+        (None, None) => Ok(None),
+        _ => Err(Status::invalid_argument(format!(
+            "both {MIN_VERSION_HEADER} and {VERSION_HEADER} headers are required"
+        ))),
+    }
+}
+
+#[cfg(all(test, with_server))]
+mod tests {
+    use super::*;
+
+    fn request_with_versions(min_version: u32, version: u32) -> Request<()> {
+        let mut request = Request::new(());
+        let metadata = request.metadata_mut();
+        metadata.insert(MIN_VERSION_HEADER, MetadataValue::from(min_version));
+        metadata.insert(VERSION_HEADER, MetadataValue::from(version));
+        request
+    }
+
+    #[test]
+    fn negotiates_the_highest_common_version() {
+        let request = negotiate_protocol_version(request_with_versions(
+            MIN_PROTOCOL_VERSION,
+            PROTOCOL_VERSION + 1,
+        ))
+        .unwrap();
+        assert_eq!(
+            request.extensions().get::<NegotiatedProtocolVersion>(),
+            Some(&NegotiatedProtocolVersion(PROTOCOL_VERSION))
+        );
+    }
+
+    #[test]
+    fn accepts_legacy_requests() {
+        let request = negotiate_protocol_version(Request::new(())).unwrap();
+        assert_eq!(
+            request.extensions().get::<NegotiatedProtocolVersion>(),
+            Some(&NegotiatedProtocolVersion(LEGACY_PROTOCOL_VERSION))
+        );
+    }
+
+    #[test]
+    fn rejects_incompatible_versions() {
+        let status = negotiate_protocol_version(request_with_versions(
+            PROTOCOL_VERSION + 1,
+            PROTOCOL_VERSION + 2,
+        ))
+        .unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        assert!(matches!(
+            incompatible_protocol_version(&status),
+            Some(NodeError::IncompatibleProtocolVersion {
+                remote_min_version: MIN_PROTOCOL_VERSION,
+                remote_version: PROTOCOL_VERSION,
+                ..
+            })
+        ));
+    }
+}
@@ -25,7 +25,11 @@ use linera_storage::Storage;
 use rand::Rng;
 use tokio::{sync::oneshot, task::JoinSet};
 use tokio_util::sync::CancellationToken;
-use tonic::{Request, Response, Status};
+use tonic::{
+    service::interceptor::InterceptedService,
+    transport::{ClientTlsConfig, Endpoint},
+    Request, Response, Status,
+};
 use tower::{builder::ServiceBuilder, Layer, Service};
 use tracing::{debug, error, info, instrument, trace, warn};
 #[cfg(with_metrics)]
@@ -46,10 +50,15 @@ use super::{
         HandlePendingBlobRequest, LiteCertificate, PendingBlobRequest, PendingBlobResult,
     },
     pool::GrpcConnectionPool,
-    GrpcError, GRPC_MAX_MESSAGE_SIZE,
+    protocol_version,
+    trace_context::{self, TraceContextLayer},
+    GrpcCompression, GrpcError, TlsCertificates, GRPC_MAX_MESSAGE_SIZE,
 };
 use crate::{
-    config::{CrossChainConfig, NotificationConfig, ShardId, ValidatorInternalNetworkConfig},
+    config::{
+        CrossChainConfig, NetworkProtocol, NotificationConfig, ShardId, TlsConfig,
+        ValidatorInternalNetworkConfig,
+    },
     HandleConfirmedCertificateRequest, HandleLiteCertRequest, HandleTimeoutCertificateRequest,
     HandleValidatedCertificateRequest,
 };
@@ -182,14 +191,26 @@ where
         internal_network: ValidatorInternalNetworkConfig,
         cross_chain_config: CrossChainConfig,
         notification_config: NotificationConfig,
+        tls_certificates: &TlsCertificates,
+        compression: GrpcCompression,
         shutdown_signal: CancellationToken,
         join_set: &mut JoinSet<()>,
-    ) -> GrpcServerHandle {
+    ) -> Result<GrpcServerHandle, GrpcError> {
         info!(
             "spawning gRPC server on {}:{} for shard {}",
             host, port, shard_id
         );
 
+        let (server_tls_config, client_tls_config) =
+            if internal_network.protocol == NetworkProtocol::Grpc(TlsConfig::Tls) {
+                (
+                    Some(tls_certificates.internal_server_config()?),
+                    Some(tls_certificates.internal_client_config()?),
+                )
+            } else {
+                (None, None)
+            };
+
         let (cross_chain_sender, cross_chain_receiver) =
             mpsc::channel(cross_chain_config.queue_size);
 
@@ -209,6 +230,8 @@ where
                 Duration::from_millis(cross_chain_config.sender_delay_ms),
                 cross_chain_config.sender_failure_rate,
                 cross_chain_config.max_concurrent_tasks,
+                client_tls_config.clone(),
+                compression,
                 shard_id,
                 cross_chain_receiver,
             )
@@ -222,6 +245,8 @@ where
             Self::forward_notifications(
                 state.nickname().to_string(),
                 internal_network.proxy_address(),
+                client_tls_config,
+                compression,
                 notification_receiver,
             )
         });
@@ -236,9 +261,22 @@ where
             notification_sender,
         };
 
-        let worker_node = ValidatorWorkerServer::new(grpc_server)
+        join_set.spawn_task(grpc_server.clone().retransmit_pending_deliveries(
+            Duration::from_millis(cross_chain_config.retransmission_interval_ms),
+            shutdown_signal.clone(),
+        ));
+
+        let mut worker_node = ValidatorWorkerServer::new(grpc_server)
             .max_encoding_message_size(GRPC_MAX_MESSAGE_SIZE)
             .max_decoding_message_size(GRPC_MAX_MESSAGE_SIZE);
+        for encoding in GrpcCompression::ACCEPTED_ENCODINGS {
+            worker_node = worker_node.accept_compressed(encoding);
+        }
+        if let Some(encoding) = compression.encoding() {
+            worker_node = worker_node.send_compressed(encoding);
+        }
+        let worker_node =
+            InterceptedService::new(worker_node, protocol_version::negotiate_protocol_version);
 
         let handle = join_set.spawn_task(async move {
             let server_address = SocketAddr::from((IpAddr::from_str(&host)?, port));
@@ -251,10 +289,16 @@ where
                 .set_serving::<ValidatorWorkerServer<Self>>()
                 .await;
 
-            tonic::transport::Server::builder()
+            let mut server_builder = tonic::transport::Server::builder();
+            if let Some(server_tls_config) = server_tls_config {
+                server_builder = server_builder.tls_config(server_tls_config)?;
+            }
+
+            server_builder
                 .layer(
                     ServiceBuilder::new()
                         .layer(GrpcPrometheusMetricsMiddlewareLayer)
+                        .layer(TraceContextLayer)
                         .into_inner(),
                 )
                 .add_service(health_service)
@@ -266,23 +310,33 @@ where
             Ok(())
         });
 
-        GrpcServerHandle { handle }
+        Ok(GrpcServerHandle { handle })
     }
 
     /// Continuously waits for receiver to receive a notification which is then sent to
     /// the proxy.
-    #[instrument(skip(receiver))]
+    #[instrument(skip(tls_config, receiver))]
     async fn forward_notifications(
         nickname: String,
         proxy_address: String,
+        tls_config: Option<ClientTlsConfig>,
+        compression: GrpcCompression,
         mut receiver: Receiver<Notification>,
     ) {
-        let channel = tonic::transport::Channel::from_shared(proxy_address.clone())
-            .expect("Proxy URI should be valid")
-            .connect_lazy();
+        let mut endpoint =
+            Endpoint::from_shared(proxy_address.clone()).expect("Proxy URI should be valid");
+        if let Some(tls_config) = tls_config {
+            endpoint = endpoint
+                .tls_config(tls_config)
+                .expect("TLS configuration should be valid");
+        }
+        let channel = endpoint.connect_lazy();
         let mut client = NotifierServiceClient::new(channel)
             .max_encoding_message_size(GRPC_MAX_MESSAGE_SIZE)
             .max_decoding_message_size(GRPC_MAX_MESSAGE_SIZE);
+        if let Some(encoding) = compression.encoding() {
+            client = client.send_compressed(encoding);
+        }
 
         while let Some(notification) = receiver.next().await {
             let notification: api::Notification = match notification.clone().try_into() {
@@ -292,7 +346,8 @@ where
                     continue;
                 }
             };
-            let request = tonic::Request::new(notification.clone());
+            let mut request = tonic::Request::new(notification.clone());
+            protocol_version::add_protocol_version(&mut request);
             if let Err(error) = client.notify(request).await {
                 error!(
                     %error,
@@ -331,6 +386,36 @@ where
         }
     }
 
+    /// Periodically resends the cross-chain requests of the chains of this shard whose outgoing
+    /// messages were not acknowledged yet, starting right away to recover from a restart.
+    ///
+    /// The whole index of these chains is only scanned at startup, to find the chains of this
+    /// shard that were listed by other shards, e.g. before the shards were reconfigured. Then
+    /// only the partition of the index of this shard is read.
+    #[instrument(skip_all, fields(nickname = self.state.nickname(), shard_id = self.shard_id))]
+    async fn retransmit_pending_deliveries(
+        self,
+        interval: Duration,
+        shutdown_signal: CancellationToken,
+    ) {
+        let filter = |chain_id| self.network.get_shard_id(chain_id) == self.shard_id;
+        let mut result = self.state.retransmit_pending_deliveries(filter).await;
+        loop {
+            match result {
+                Ok(actions) => self.handle_network_actions(actions),
+                Err(error) => error!(%error, "Failed to retransmit pending cross-chain requests"),
+            }
+            tokio::select! { biased;
+                _ = shutdown_signal.cancelled() => return,
+                _ = linera_base::time::timer::sleep(interval) => {}
+            }
+            result = self
+                .state
+                .retransmit_partition_pending_deliveries(filter)
+                .await;
+        }
+    }
+
     #[instrument(skip_all, fields(nickname, %this_shard))]
     #[expect(clippy::too_many_arguments)]
     async fn forward_cross_chain_queries(
@@ -341,16 +426,18 @@ where
         cross_chain_sender_delay: Duration,
         cross_chain_sender_failure_rate: f32,
         cross_chain_max_concurrent_tasks: usize,
+        tls_config: Option<ClientTlsConfig>,
+        compression: GrpcCompression,
         this_shard: ShardId,
         receiver: mpsc::Receiver<(linera_core::data_types::CrossChainRequest, ShardId)>,
     ) {
-        let pool = GrpcConnectionPool::default();
+        let pool = GrpcConnectionPool::default().with_tls_config(tls_config);
         let max_concurrent_tasks = Some(cross_chain_max_concurrent_tasks);
 
         receiver
             .for_each_concurrent(max_concurrent_tasks, |(cross_chain_request, shard_id)| {
                 let shard = network.shard(shard_id);
-                let remote_address = shard.http_address();
+                let remote_address = network.shard_address(shard);
 
                 let pool = pool.clone();
                 let nickname = nickname.clone();
@@ -373,11 +460,16 @@ where
 
                         let result = || async {
                             let cross_chain_request = cross_chain_request.clone().try_into()?;
-                            let request = Request::new(cross_chain_request);
+                            let mut request = Request::new(cross_chain_request);
+                            protocol_version::add_protocol_version(&mut request);
+                            trace_context::add_trace_context(&mut request);
                             let mut client =
                                 ValidatorWorkerClient::new(pool.channel(remote_address.clone())?)
                                     .max_encoding_message_size(GRPC_MAX_MESSAGE_SIZE)
                                     .max_decoding_message_size(GRPC_MAX_MESSAGE_SIZE);
+                            if let Some(encoding) = compression.encoding() {
+                                client = client.send_compressed(encoding);
+                            }
                             let response = client.handle_cross_chain_request(request).await?;
                             Ok::<_, anyhow::Error>(response)
                         };
@@ -398,16 +490,18 @@ where
                                     to_shard = shard_id,
                                     "Sent cross-chain query",
                                 );
-                                break;
+                                return;
                             }
                         }
-                        error!(
-                            nickname,
-                            from_shard = this_shard,
-                            to_shard = shard_id,
-                            "Dropping cross-chain query",
-                        );
                     }
+                    // The messages stay in the sender's outbox until they are acknowledged, so
+                    // they will be retransmitted later.
+                    error!(
+                        nickname,
+                        from_shard = this_shard,
+                        to_shard = shard_id,
+                        "Dropping cross-chain query",
+                    );
                 }
             })
             .await;
@@ -0,0 +1,175 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::Path;
+
+use tonic::transport::{Certificate, ClientTlsConfig, Identity, ServerTlsConfig};
+
+use super::GrpcError;
+use crate::config::TlsCertificateConfig;
+
+/// The certificates loaded from a [`TlsCertificateConfig`].
+#[derive(Clone, Debug, Default)]
+pub struct TlsCertificates {
+    identity: Option<Identity>,
+    client_ca: Option<Certificate>,
+}
+
+impl TlsCertificates {
+    /// Reads the certificates referenced by the `config`.
+    pub fn load(config: &TlsCertificateConfig) -> Result<Self, GrpcError> {
+        let identity = match (&config.cert_path, &config.key_path) {
+            (Some(cert_path), Some(key_path)) => Some(Identity::from_pem(
+                read_pem(cert_path)?,
+                read_pem(key_path)?,
+            )),
+            (None, None) => None,
+            _ => return Err(GrpcError::IncompleteTlsIdentity),
+        };
+        let client_ca = config
+            .client_ca_path
+            .as_deref()
+            .map(read_pem)
+            .transpose()?
+            .map(Certificate::from_pem);
+        Ok(TlsCertificates {
+            identity,
+            client_ca,
+        })
+    }
+
+    /// Returns the TLS configuration of a public server, using a self-signed certificate if
+    /// none was configured.
+    pub fn public_server_config(&self) -> ServerTlsConfig {
+        let identity = self
+            .identity
+            .clone()
+            .unwrap_or_else(|| Identity::from_pem(crate::CERT_PEM, crate::KEY_PEM));
+        ServerTlsConfig::new().identity(identity)
+    }
+
+    /// Returns the TLS configuration of a server on the internal network, which only accepts
+    /// clients presenting a certificate signed by the client certificate authority.
+    pub fn internal_server_config(&self) -> Result<ServerTlsConfig, GrpcError> {
+        let (identity, client_ca) = self.internal_certificates()?;
+        Ok(ServerTlsConfig::new()
+            .identity(identity)
+            .client_ca_root(client_ca))
+    }
+
+    /// Returns the TLS configuration of a client on the internal network, which authenticates
+    /// itself to the server and only trusts servers with a certificate signed by the client
+    /// certificate authority.
+    pub fn internal_client_config(&self) -> Result<ClientTlsConfig, GrpcError> {
+        let (identity, client_ca) = self.internal_certificates()?;
+        Ok(ClientTlsConfig::new()
+            .identity(identity)
+            .ca_certificate(client_ca))
+    }
+
+    fn internal_certificates(&self) -> Result<(Identity, Certificate), GrpcError> {
+        match (&self.identity, &self.client_ca) {
+            (Some(identity), Some(client_ca)) => Ok((identity.clone(), client_ca.clone())),
+            _ => Err(GrpcError::MissingInternalTlsCertificates),
+        }
+    }
+}
+
+fn read_pem(path: &Path) -> Result<Vec<u8>, GrpcError> {
+    std::fs::read(path).map_err(|error| GrpcError::ReadTlsCertificate {
+        path: path.to_owned(),
+        error,
+    })
+}
+
+#[cfg(all(test, with_server))]
+mod tests {
+    use rcgen::{
+        BasicConstraints, Certificate as GeneratedCertificate, CertificateParams, DnType, IsCa,
+    };
+    use tonic::transport::{server::TcpIncoming, Certificate, Channel, Identity, Server};
+    use tonic_health::pb::{health_client::HealthClient, HealthCheckRequest};
+
+    use super::TlsCertificates;
+
+    /// A certificate authority signing the certificates of servers and clients.
+    struct CertificateAuthority(GeneratedCertificate);
+
+    impl CertificateAuthority {
+        fn new(name: &str) -> Self {
+            let mut params = CertificateParams::new(Vec::new());
+            params.distinguished_name.push(DnType::CommonName, name);
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            CertificateAuthority(
+                GeneratedCertificate::from_params(params).expect("a certificate authority"),
+            )
+        }
+
+        /// Returns certificates with an identity signed by this authority, trusting the
+        /// `trusted` authority.
+        fn certificates(&self, trusted: &CertificateAuthority) -> TlsCertificates {
+            let mut params = CertificateParams::new(vec!["localhost".to_owned()]);
+            params
+                .distinguished_name
+                .push(DnType::CommonName, "localhost");
+            let certificate = GeneratedCertificate::from_params(params).expect("a certificate");
+            let identity = Identity::from_pem(
+                certificate
+                    .serialize_pem_with_signer(&self.0)
+                    .expect("a signed certificate"),
+                certificate.serialize_private_key_pem(),
+            );
+            let client_ca = Certificate::from_pem(trusted.0.serialize_pem().expect("a PEM"));
+            TlsCertificates {
+                identity: Some(identity),
+                client_ca: Some(client_ca),
+            }
+        }
+    }
+
+    /// Starts a server of the internal network using the `certificates`, and returns its port.
+    async fn serve(certificates: &TlsCertificates) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        let (_, health_service) = tonic_health::server::health_reporter();
+        let server = Server::builder()
+            .tls_config(certificates.internal_server_config().unwrap())
+            .unwrap()
+            .add_service(health_service)
+            .serve_with_incoming(incoming);
+        tokio::spawn(server);
+        port
+    }
+
+    /// Returns whether the server on `port` answers a client using the `certificates`.
+    async fn is_accepted(certificates: &TlsCertificates, port: u16) -> bool {
+        let tls_config = certificates
+            .internal_client_config()
+            .unwrap()
+            .domain_name("localhost");
+        let endpoint = Channel::from_shared(format!("https://127.0.0.1:{port}"))
+            .unwrap()
+            .tls_config(tls_config)
+            .unwrap();
+        let Ok(channel) = endpoint.connect().await else {
+            return false;
+        };
+        HealthClient::new(channel)
+            .check(HealthCheckRequest::default())
+            .await
+            .is_ok()
+    }
+
+    /// Tests that servers of the internal network only accept the clients presenting a
+    /// certificate signed by the configured certificate authority.
+    #[tokio::test]
+    async fn internal_servers_reject_untrusted_clients() {
+        let authority = CertificateAuthority::new("Validator CA");
+        let port = serve(&authority.certificates(&authority)).await;
+
+        assert!(is_accepted(&authority.certificates(&authority), port).await);
+        let untrusted_authority = CertificateAuthority::new("Untrusted CA");
+        assert!(!is_accepted(&untrusted_authority.certificates(&authority), port).await);
+    }
+}
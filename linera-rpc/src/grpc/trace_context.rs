@@ -0,0 +1,158 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Propagation of trace contexts across gRPC requests.
+//!
+//! Clients add the trace context of their current span to the metadata of every request. Servers
+//! wrap their services in a [`TraceContextLayer`], which handles each request in a span whose
+//! parent is the span of the client, so that the spans of the client, the proxy and the workers
+//! handling a request are part of the same trace. Trace contexts are only propagated when the
+//! `opentelemetry` feature of `linera-base` is enabled.
+
+use std::{
+    collections::HashMap,
+    task::{Context, Poll},
+};
+
+use tonic::{
+    codegen::http,
+    metadata::{MetadataKey, MetadataValue},
+    Request,
+};
+use tower::{Layer, Service};
+use tracing::{instrument::Instrumented, Instrument as _};
+
+/// The headers of the W3C trace context format.
+const TRACE_CONTEXT_HEADERS: [&str; 2] = ["traceparent", "tracestate"];
+
+/// Adds the trace context of the current span to the metadata of `request`.
+pub fn add_trace_context<T>(request: &mut Request<T>) {
+    let metadata = request.metadata_mut();
+    for (key, value) in linera_base::tracing::current_trace_context() {
+        if let (Ok(key), Ok(value)) = (
+            MetadataKey::from_bytes(key.as_bytes()),
+            MetadataValue::try_from(value),
+        ) {
+            metadata.insert(key, value);
+        }
+    }
+}
+
+/// A layer handling every request in a span that continues the trace of the client.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TraceContextLayer;
+
+impl<S> Layer<S> for TraceContextLayer {
+    type Service = TraceContextService<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        TraceContextService { service }
+    }
+}
+
+#[derive(Clone)]
+pub struct TraceContextService<S> {
+    service: S,
+}
+
+impl<S, B> Service<http::Request<B>> for TraceContextService<S>
+where
+    S: Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Instrumented<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let span = tracing::info_span!("grpc_request", path = request.uri().path());
+        let trace_context = TRACE_CONTEXT_HEADERS
+            .into_iter()
+            .filter_map(|name| {
+                let value = request.headers().get(name)?.to_str().ok()?;
+                Some((name.to_owned(), value.to_owned()))
+            })
+            .collect::<HashMap<_, _>>();
+        linera_base::tracing::set_remote_parent(&span, &trace_context);
+        self.service.call(request).instrument(span)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::convert::Infallible;
+
+    use futures::future::BoxFuture;
+    use opentelemetry::trace::{TraceContextExt as _, TraceId, TracerProvider as _};
+    use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::TracerProvider};
+    use tracing::{subscriber::DefaultGuard, Span};
+    use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+    use tracing_subscriber::layer::SubscriberExt as _;
+
+    use super::*;
+
+    /// A service returning the ID of the trace of the span in which it handles a request.
+    struct TraceIdService;
+
+    impl<B> Service<http::Request<B>> for TraceIdService {
+        type Response = TraceId;
+        type Error = Infallible;
+        type Future = BoxFuture<'static, Result<TraceId, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: http::Request<B>) -> Self::Future {
+            Box::pin(async { Ok(trace_id(&Span::current())) })
+        }
+    }
+
+    /// Records spans with OpenTelemetry, without exporting them, until the guard is dropped.
+    fn record_spans() -> DefaultGuard {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let tracer = TracerProvider::builder().build().tracer("test");
+        let subscriber =
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+        tracing::subscriber::set_default(subscriber)
+    }
+
+    fn trace_id(span: &Span) -> TraceId {
+        span.context().span().span_context().trace_id()
+    }
+
+    /// Handles `request` as a server would, and returns the ID of the trace it is handled in.
+    async fn handle(request: Request<()>) -> TraceId {
+        let (metadata, _extensions, ()) = request.into_parts();
+        let mut http_request = http::Request::new(());
+        *http_request.headers_mut() = metadata.into_headers();
+        let mut service = TraceContextLayer.layer(TraceIdService);
+        service.call(http_request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_server_continues_trace_of_client() {
+        let _guard = record_spans();
+        let client_span = tracing::info_span!("client");
+        let mut request = Request::new(());
+        client_span.in_scope(|| add_trace_context(&mut request));
+        assert!(request.metadata().contains_key("traceparent"));
+
+        let server_trace_id = handle(request).await;
+        assert_eq!(server_trace_id, trace_id(&client_span));
+    }
+
+    #[tokio::test]
+    async fn test_server_starts_trace_without_trace_context() {
+        let _guard = record_spans();
+        let client_span = tracing::info_span!("client");
+        let request = Request::new(());
+
+        let server_trace_id = handle(request).await;
+        assert_ne!(server_trace_id, TraceId::INVALID);
+        assert_ne!(server_trace_id, trace_id(&client_span));
+    }
+}
@@ -28,13 +28,27 @@ cfg_if::cfg_if! {
         }
     } else {
         pub use tonic::transport::{Channel, Error};
+        use tonic::transport::channel::ClientTlsConfig;
 
         pub fn create_channel(
             address: String,
             options: &Options,
+        ) -> Result<Channel, Error> {
+            create_channel_with_tls_config(
+                address,
+                options,
+                ClientTlsConfig::default().with_webpki_roots(),
+            )
+        }
+
+        /// Creates a channel that uses `tls_config` if the `address` uses TLS.
+        pub fn create_channel_with_tls_config(
+            address: String,
+            options: &Options,
+            tls_config: ClientTlsConfig,
         ) -> Result<Channel, Error> {
             let mut endpoint = tonic::transport::Endpoint::from_shared(address)?
-                .tls_config(tonic::transport::channel::ClientTlsConfig::default().with_webpki_roots())?;
+                .tls_config(tls_config)?;
 
             if let Some(timeout) = options.connect_timeout {
                 endpoint = endpoint.connect_timeout(timeout);
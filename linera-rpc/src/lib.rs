@@ -20,6 +20,8 @@ mod message;
 pub mod simple;
 
 pub mod grpc;
+#[cfg(with_server)]
+pub mod websocket;
 
 pub use client::Client;
 pub use message::RpcMessage;
@@ -6,7 +6,10 @@ use linera_core::node::{NodeError, ValidatorNodeProvider};
 
 #[cfg(with_simple_network)]
 use crate::simple::SimpleNodeProvider;
-use crate::{client::Client, grpc::GrpcNodeProvider};
+use crate::{
+    client::Client,
+    grpc::{GrpcCompression, GrpcNodeProvider},
+};
 
 /// A general node provider which delegates node provision to the underlying
 /// node provider according to the `ValidatorPublicNetworkConfig`.
@@ -52,4 +55,5 @@ pub struct NodeOptions {
     pub recv_timeout: Duration,
     pub retry_delay: Duration,
     pub max_retries: u32,
+    pub compression: GrpcCompression,
 }
@@ -0,0 +1,451 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A transport based on QUIC.
+//!
+//! A single QUIC connection is kept open to each peer, and requests are multiplexed over it:
+//! each [`Transport`] returned by [`connect`] is a new bidirectional stream, and each message
+//! sent through the [`QuicConnectionPool`] uses a new unidirectional stream. Streams are cheap
+//! to open, so requests about different chains don't block each other, and the handshake is
+//! only paid once per peer.
+//!
+//! As with the TCP and UDP transports, peers are not authenticated by the transport: messages
+//! are signed by validators and clients. Servers use a self-signed certificate, which clients
+//! don't verify.
+
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use futures::{future, stream::FuturesUnordered, FutureExt as _, SinkExt, StreamExt};
+use linera_core::JoinSetExt as _;
+use quinn::{
+    crypto::rustls::{QuicClientConfig, QuicServerConfig},
+    ClientConfig, Connection, ConnectionError, Endpoint, RecvStream, SendStream, ServerConfig,
+};
+use rustls::{
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{self, CryptoProvider},
+    pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime},
+    DigitallySignedStruct, SignatureScheme,
+};
+use tokio::{
+    io::Join,
+    net::{lookup_host, ToSocketAddrs},
+    sync::OnceCell,
+    task::JoinSet,
+};
+use tokio_util::{
+    codec::{Framed, FramedRead, FramedWrite},
+    sync::CancellationToken,
+};
+use tracing::{error, warn};
+
+use super::{
+    codec::{self, Codec},
+    transport::{ConnectionPool, MessageHandler, Transport, REAP_TASKS_THRESHOLD},
+};
+use crate::RpcMessage;
+
+/// The server name used in the TLS handshakes, which is not checked by clients.
+const SERVER_NAME: &str = "linera";
+
+/// The connection to a peer, opened by the first call to [`connect`] that needs it.
+type SharedConnection = Arc<OnceCell<Connection>>;
+
+/// The connections opened by [`connect`], reused by later calls for the same peer.
+///
+/// The lock is only held to look up and insert entries: the handshakes happen outside of it,
+/// and concurrent calls for the same peer wait for the same handshake.
+static CONNECTIONS: LazyLock<Mutex<HashMap<SocketAddr, SharedConnection>>> =
+    LazyLock::new(Mutex::default);
+
+/// Opens a new bidirectional stream to the server at `address`, reusing the connection to it
+/// if one is open.
+pub(crate) async fn connect(address: SocketAddr) -> Result<impl Transport, io::Error> {
+    let (shared_connection, connection) = shared_connection(address).await?;
+    match connection.open_bi().await {
+        Ok((send, recv)) => return Ok(framed(send, recv)),
+        Err(error) => {
+            warn!("Reconnecting to {address} after QUIC connection failed: {error}");
+            evict_connection(address, &shared_connection);
+        }
+    }
+
+    let (_, connection) = shared_connection(address).await?;
+    let (send, recv) = connection.open_bi().await?;
+    Ok(framed(send, recv))
+}
+
+/// Returns the connection to the server at `address`, opening it if needed.
+async fn shared_connection(
+    address: SocketAddr,
+) -> Result<(SharedConnection, Connection), io::Error> {
+    let shared_connection = CONNECTIONS
+        .lock()
+        .expect("QUIC connections lock should not be poisoned")
+        .entry(address)
+        .or_default()
+        .clone();
+    let result = shared_connection
+        .get_or_try_init(|| async {
+            let connection = open_connection(address).await?;
+            // Forget the connection once it is closed, e.g. when it times out or the server
+            // goes away, so that the next call reconnects.
+            let closed_connection = connection.clone();
+            let closed_shared_connection = shared_connection.clone();
+            tokio::spawn(async move {
+                closed_connection.closed().await;
+                evict_connection(address, &closed_shared_connection);
+            });
+            Ok::<_, io::Error>(connection)
+        })
+        .await;
+    match result {
+        Ok(connection) => {
+            let connection = connection.clone();
+            Ok((shared_connection, connection))
+        }
+        Err(error) => {
+            evict_connection(address, &shared_connection);
+            Err(error)
+        }
+    }
+}
+
+/// Removes the connection to `address` from [`CONNECTIONS`], unless it was already replaced
+/// by a new one.
+fn evict_connection(address: SocketAddr, shared_connection: &SharedConnection) {
+    let mut connections = CONNECTIONS
+        .lock()
+        .expect("QUIC connections lock should not be poisoned");
+    if connections
+        .get(&address)
+        .is_some_and(|current| Arc::ptr_eq(current, shared_connection))
+    {
+        connections.remove(&address);
+    }
+}
+
+/// Opens a new QUIC connection to the server at `address`.
+async fn open_connection(address: SocketAddr) -> Result<Connection, io::Error> {
+    let local_address = match address {
+        SocketAddr::V4(_) => SocketAddr::from(([0, 0, 0, 0], 0)),
+        SocketAddr::V6(_) => SocketAddr::from(([0u16; 8], 0)),
+    };
+    let mut endpoint = Endpoint::client(local_address)?;
+    endpoint.set_default_client_config(client_config()?);
+    endpoint
+        .connect(address, SERVER_NAME)
+        .map_err(io::Error::other)?
+        .await
+        .map_err(io::Error::from)
+}
+
+/// Frames the messages sent and received on a bidirectional stream.
+fn framed(send: SendStream, recv: RecvStream) -> Framed<Join<RecvStream, SendStream>, Codec> {
+    Framed::new(tokio::io::join(recv, send), Codec)
+}
+
+/// Returns the configuration of QUIC clients, which accept any server certificate.
+fn client_config() -> Result<ClientConfig, io::Error> {
+    let provider = Arc::new(crypto::ring::default_provider());
+    let tls_config = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .map_err(io::Error::other)?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AnyServerCertificate(provider)))
+        .with_no_client_auth();
+    let quic_config = QuicClientConfig::try_from(tls_config).map_err(io::Error::other)?;
+    Ok(ClientConfig::new(Arc::new(quic_config)))
+}
+
+/// Returns the configuration of QUIC servers, with a new self-signed certificate.
+fn server_config() -> Result<ServerConfig, io::Error> {
+    let certificate = rcgen::generate_simple_self_signed(vec![SERVER_NAME.to_owned()])
+        .map_err(io::Error::other)?;
+    let certificate_der = certificate.serialize_der().map_err(io::Error::other)?;
+    let private_key_der = PrivatePkcs8KeyDer::from(certificate.serialize_private_key_der());
+
+    let provider = Arc::new(crypto::ring::default_provider());
+    let tls_config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .map_err(io::Error::other)?
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![CertificateDer::from(certificate_der)],
+            PrivateKeyDer::Pkcs8(private_key_der),
+        )
+        .map_err(io::Error::other)?;
+    let quic_config = QuicServerConfig::try_from(tls_config).map_err(io::Error::other)?;
+    Ok(ServerConfig::with_crypto(Arc::new(quic_config)))
+}
+
+/// A certificate verifier accepting the certificate of any server, but still checking the
+/// signatures of the handshake.
+#[derive(Debug)]
+struct AnyServerCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AnyServerCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(
+            message,
+            certificate,
+            signature,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(
+            message,
+            certificate,
+            signature,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// An implementation of [`ConnectionPool`] based on QUIC.
+pub(crate) struct QuicConnectionPool {
+    connections: HashMap<String, Connection>,
+}
+
+impl QuicConnectionPool {
+    pub(crate) fn new() -> Self {
+        QuicConnectionPool {
+            connections: HashMap::new(),
+        }
+    }
+
+    async fn get_connection(&mut self, address: &str) -> Result<&Connection, io::Error> {
+        let is_open = self
+            .connections
+            .get(address)
+            .is_some_and(|connection| connection.close_reason().is_none());
+        if !is_open {
+            let socket_address = lookup_host(address).await?.next().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("Unknown host {address}"))
+            })?;
+            let connection = open_connection(socket_address).await.inspect_err(|error| {
+                error!("Failed to open connection to {}: {}", address, error);
+            })?;
+            self.connections.insert(address.to_owned(), connection);
+        }
+        Ok(&self.connections[address])
+    }
+
+    async fn send(&mut self, message: RpcMessage, address: &str) -> Result<(), codec::Error> {
+        let connection = self.get_connection(address).await?;
+        let send = connection.open_uni().await.map_err(io::Error::from)?;
+        let mut stream = FramedWrite::new(send, Codec);
+        stream.send(message).await?;
+        stream.into_inner().finish().map_err(io::Error::other)?;
+        Ok(())
+    }
+}
+
+impl ConnectionPool for QuicConnectionPool {
+    fn send_message_to<'a>(
+        &'a mut self,
+        message: RpcMessage,
+        address: &'a str,
+    ) -> future::BoxFuture<'a, Result<(), codec::Error>> {
+        Box::pin(async move {
+            let result = self.send(message, address).await;
+            if result.is_err() {
+                self.connections.remove(address);
+            }
+            result
+        })
+    }
+}
+
+/// Server implementation for QUIC.
+pub struct QuicServer<State> {
+    connection: Connection,
+    handler: State,
+    shutdown_signal: CancellationToken,
+}
+
+impl<State> QuicServer<State>
+where
+    State: MessageHandler + Send + 'static,
+{
+    /// Runs the QUIC server implementation.
+    ///
+    /// Accepts connections and spawns a task with a new [`QuicServer`] instance to serve each
+    /// client.
+    pub async fn run(
+        address: impl ToSocketAddrs,
+        handler: State,
+        shutdown_signal: CancellationToken,
+    ) -> Result<(), io::Error> {
+        let address = lookup_host(address).await?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Couldn't resolve address to bind")
+        })?;
+        let endpoint = Endpoint::server(server_config()?, address)?;
+
+        let connection_shutdown_signal = shutdown_signal.child_token();
+        let mut join_set = JoinSet::new();
+        let mut reap_countdown = REAP_TASKS_THRESHOLD;
+
+        loop {
+            tokio::select! { biased;
+                _ = shutdown_signal.cancelled() => {
+                    join_set.await_all_tasks().await;
+                    endpoint.close(0u32.into(), b"shutdown");
+                    return Ok(());
+                }
+                incoming = endpoint.accept() => match incoming {
+                    Some(incoming) => {
+                        let handler = handler.clone();
+                        let shutdown_signal = connection_shutdown_signal.clone();
+                        join_set.spawn_task(async move {
+                            match incoming.await {
+                                Ok(connection) => {
+                                    QuicServer {
+                                        connection,
+                                        handler,
+                                        shutdown_signal,
+                                    }
+                                    .serve()
+                                    .await
+                                }
+                                Err(error) => warn!("Failed to accept QUIC connection: {error}"),
+                            }
+                        });
+                        reap_countdown -= 1;
+                    }
+                    None => {
+                        join_set.await_all_tasks().await;
+                        return Err(io::Error::other("QUIC endpoint was closed"));
+                    }
+                },
+            }
+
+            if reap_countdown == 0 {
+                join_set.reap_finished_tasks();
+                reap_countdown = REAP_TASKS_THRESHOLD;
+            }
+        }
+    }
+
+    /// Serves the streams opened by a client through a single connection.
+    async fn serve(self) {
+        let mut streams = FuturesUnordered::new();
+
+        loop {
+            tokio::select! { biased;
+                _ = self.shutdown_signal.cancelled() => {
+                    self.connection.close(0u32.into(), b"shutdown");
+                    break;
+                }
+                stream = self.connection.accept_bi() => match stream {
+                    Ok((send, recv)) => {
+                        streams.push(Self::serve_request_stream(
+                            framed(send, recv),
+                            self.handler.clone(),
+                        ).left_future());
+                    }
+                    Err(error) => {
+                        Self::handle_error(error);
+                        break;
+                    }
+                },
+                stream = self.connection.accept_uni() => match stream {
+                    Ok(recv) => {
+                        streams.push(Self::serve_message_stream(
+                            FramedRead::new(recv, Codec),
+                            self.handler.clone(),
+                        ).right_future());
+                    }
+                    Err(error) => {
+                        Self::handle_error(error);
+                        break;
+                    }
+                },
+                Some(()) = streams.next(), if !streams.is_empty() => {}
+            }
+        }
+
+        while streams.next().await.is_some() {}
+    }
+
+    /// Answers the requests received on a bidirectional stream.
+    async fn serve_request_stream(
+        mut stream: Framed<Join<RecvStream, SendStream>, Codec>,
+        mut handler: State,
+    ) {
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(message) => {
+                    if let Some(reply) = handler.handle_message(message).await {
+                        if let Err(error) = stream.send(reply).await {
+                            error!("Failed to send query response: {error}");
+                            return;
+                        }
+                    }
+                }
+                Err(error) => {
+                    error!("Error while reading QUIC stream: {error}");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Handles the messages received on a unidirectional stream, which expect no replies.
+    async fn serve_message_stream(mut stream: FramedRead<RecvStream, Codec>, mut handler: State) {
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(message) => {
+                    handler.handle_message(message).await;
+                }
+                Err(error) => {
+                    error!("Error while reading QUIC stream: {error}");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Handles an error received while accepting streams, ignoring a normal termination of the
+    /// connection.
+    fn handle_error(error: ConnectionError) {
+        if !matches!(
+            error,
+            ConnectionError::ApplicationClosed(_) | ConnectionError::LocallyClosed
+        ) {
+            warn!("QUIC connection failed: {error}");
+        }
+    }
+}
@@ -91,7 +91,7 @@ where
             .await
             .expect("Initialization should not fail");
 
-        while let Some((message, shard_id)) = receiver.next().await {
+        'messages: while let Some((message, shard_id)) = receiver.next().await {
             if cross_chain_sender_failure_rate > 0.0
                 && rand::thread_rng().gen::<f32>() < cross_chain_sender_failure_rate
             {
@@ -128,16 +128,18 @@ where
                             to_shard = shard_id,
                             "Sent cross-chain query",
                         );
-                        break;
+                        continue 'messages;
                     }
                 }
-                error!(
-                    nickname,
-                    from_shard = this_shard,
-                    to_shard = shard_id,
-                    "Dropping cross-chain query",
-                );
             }
+            // The messages stay in the sender's outbox until they are acknowledged, so they will
+            // be retransmitted later.
+            error!(
+                nickname,
+                from_shard = this_shard,
+                to_shard = shard_id,
+                "Dropping cross-chain query",
+            );
         }
     }
 
@@ -171,6 +173,10 @@ where
             server: self,
             cross_chain_sender,
         };
+        join_set.spawn_task(state.clone().retransmit_pending_deliveries(
+            Duration::from_millis(state.server.cross_chain_config.retransmission_interval_ms),
+            shutdown_signal.clone(),
+        ));
         // Launch server for the appropriate protocol.
         protocol.spawn_server(address, state, shutdown_signal, join_set)
     }
@@ -437,8 +443,42 @@ where
 
 impl<S> RunningServerState<S>
 where
-    S: Storage + Send,
+    S: Storage + Clone + Send + Sync + 'static,
 {
+    /// Periodically resends the cross-chain requests of the chains of this shard whose outgoing
+    /// messages were not acknowledged yet, starting right away to recover from a restart.
+    ///
+    /// The whole index of these chains is only scanned at startup, to find the chains of this
+    /// shard that were listed by other shards, e.g. before the shards were reconfigured. Then
+    /// only the partition of the index of this shard is read.
+    async fn retransmit_pending_deliveries(
+        mut self,
+        interval: Duration,
+        shutdown_signal: CancellationToken,
+    ) {
+        let mut is_startup = true;
+        loop {
+            let network = &self.server.network;
+            let shard_id = self.server.shard_id;
+            let filter = |chain_id| network.get_shard_id(chain_id) == shard_id;
+            let state = &self.server.state;
+            let result = if is_startup {
+                is_startup = false;
+                state.retransmit_pending_deliveries(filter).await
+            } else {
+                state.retransmit_partition_pending_deliveries(filter).await
+            };
+            match result {
+                Ok(actions) => self.handle_network_actions(actions),
+                Err(error) => error!(%error, "Failed to retransmit pending cross-chain requests"),
+            }
+            tokio::select! { biased;
+                _ = shutdown_signal.cancelled() => return,
+                _ = linera_base::time::timer::sleep(interval) => {}
+            }
+        }
+    }
+
     fn handle_network_actions(&mut self, actions: NetworkActions) {
         for request in actions.cross_chain_requests {
             let shard_id = self.server.network.get_shard_id(request.target_chain_id());
@@ -22,7 +22,11 @@ use tokio_util::{codec::Framed, sync::CancellationToken, udp::UdpFramed};
 use tracing::{error, warn};
 
 use crate::{
-    simple::{codec, codec::Codec},
+    simple::{
+        codec,
+        codec::Codec,
+        quic::{self, QuicConnectionPool, QuicServer},
+    },
     RpcMessage,
 };
 
@@ -30,13 +34,14 @@ use crate::{
 pub const DEFAULT_MAX_DATAGRAM_SIZE: &str = "65507";
 
 /// Number of tasks to spawn before attempting to reap some finished tasks to prevent memory leaks.
-const REAP_TASKS_THRESHOLD: usize = 100;
+pub(crate) const REAP_TASKS_THRESHOLD: usize = 100;
 
 // Supported transport protocols.
 #[derive(clap::ValueEnum, Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TransportProtocol {
     Udp,
     Tcp,
+    Quic,
 }
 
 impl std::str::FromStr for TransportProtocol {
@@ -58,6 +63,7 @@ impl TransportProtocol {
         match self {
             TransportProtocol::Udp => "udp",
             TransportProtocol::Tcp => "tcp",
+            TransportProtocol::Quic => "quic",
         }
     }
 }
@@ -133,12 +139,14 @@ impl TransportProtocol {
                     .with(move |message| future::ready(Ok((message, address))))
                     .map_ok(|(message, _address)| message)
                     .left_stream()
+                    .left_stream()
             }
             TransportProtocol::Tcp => {
                 let stream = TcpStream::connect(address).await?;
 
-                Framed::new(stream, Codec).right_stream()
+                Framed::new(stream, Codec).right_stream().left_stream()
             }
+            TransportProtocol::Quic => quic::connect(address).await?.right_stream(),
         };
 
         Ok(stream)
@@ -151,6 +159,7 @@ impl TransportProtocol {
         let pool: Box<dyn ConnectionPool> = match self {
             Self::Udp => Box::new(UdpConnectionPool::new().await?),
             Self::Tcp => Box::new(TcpConnectionPool::new().await?),
+            Self::Quic => Box::new(QuicConnectionPool::new()),
         };
         Ok(pool)
     }
@@ -169,6 +178,7 @@ impl TransportProtocol {
         let handle = match self {
             Self::Udp => join_set.spawn_task(UdpServer::run(address, state, shutdown_signal)),
             Self::Tcp => join_set.spawn_task(TcpServer::run(address, state, shutdown_signal)),
+            Self::Quic => join_set.spawn_task(QuicServer::run(address, state, shutdown_signal)),
         };
         ServerHandle { handle }
     }
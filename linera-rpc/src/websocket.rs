@@ -0,0 +1,218 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A WebSocket transport for chain notifications.
+//!
+//! This lets clients that can't use gRPC streaming directly, such as browsers, subscribe to
+//! notifications without going through a gRPC-web proxy. Clients connect to
+//! [`NOTIFICATIONS_PATH`] and send a [`SubscriptionRequest`] as a JSON text message. Each
+//! [`Notification`] about the requested chains is then sent back as a JSON text message.
+//! Sending another request replaces the current subscription.
+//!
+//! Each client has a bounded queue of [`NOTIFICATION_QUEUE_SIZE`] notifications. Clients that
+//! don't keep up with their notifications are disconnected, and can subscribe again.
+
+use std::{future, io, net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        State, WebSocketUpgrade,
+    },
+    response::Response,
+    routing::get,
+    Router,
+};
+use dashmap::DashMap;
+use linera_base::identifiers::ChainId;
+use linera_core::worker::Notification;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// The path where clients connect to receive notifications.
+pub const NOTIFICATIONS_PATH: &str = "/notifications";
+
+/// The maximal number of notifications queued for a client before it is disconnected.
+pub const NOTIFICATION_QUEUE_SIZE: usize = 64;
+
+/// A request to receive the notifications about some chains.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SubscriptionRequest {
+    pub chain_ids: Vec<ChainId>,
+}
+
+/// A server forwarding chain notifications to WebSocket clients.
+#[derive(Clone, Default)]
+pub struct WebSocketNotificationServer {
+    subscribers: Arc<DashMap<ChainId, Vec<Subscriber>>>,
+}
+
+/// The queue of notifications of a client.
+#[derive(Clone)]
+struct Subscriber {
+    sender: mpsc::Sender<Notification>,
+    /// Cancelled when the queue is full, to disconnect the client.
+    lagging: CancellationToken,
+}
+
+impl WebSocketNotificationServer {
+    /// Sends the `notification` to the clients subscribed to its chain.
+    pub fn notify(&self, notification: &Notification) {
+        let chain_id = notification.chain_id;
+        let Some(mut subscribers) = self.subscribers.get_mut(&chain_id) else {
+            return;
+        };
+        subscribers.retain(
+            |subscriber| match subscriber.sender.try_send(notification.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    subscriber.lagging.cancel();
+                    false
+                }
+                Err(TrySendError::Closed(_)) => false,
+            },
+        );
+        drop(subscribers);
+        self.subscribers
+            .remove_if(&chain_id, |_, subscribers| subscribers.is_empty());
+    }
+
+    /// Creates a queue for the notifications about the `chain_ids`.
+    ///
+    /// Returns the receiver of the queue, and a token that is cancelled if the queue is full.
+    fn subscribe(
+        &self,
+        chain_ids: Vec<ChainId>,
+    ) -> (mpsc::Receiver<Notification>, CancellationToken) {
+        let (sender, receiver) = mpsc::channel(NOTIFICATION_QUEUE_SIZE);
+        let subscriber = Subscriber {
+            sender,
+            lagging: CancellationToken::new(),
+        };
+        for chain_id in chain_ids {
+            self.subscribers
+                .entry(chain_id)
+                .or_default()
+                .push(subscriber.clone());
+        }
+        (receiver, subscriber.lagging)
+    }
+
+    /// Returns a [`Router`] accepting WebSocket connections on [`NOTIFICATIONS_PATH`].
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route(NOTIFICATIONS_PATH, get(Self::upgrade))
+            .with_state(self.clone())
+    }
+
+    /// Serves notifications on `address` until the `shutdown_signal` is triggered.
+    pub async fn serve(
+        self,
+        address: SocketAddr,
+        shutdown_signal: CancellationToken,
+    ) -> io::Result<()> {
+        let listener = tokio::net::TcpListener::bind(address).await?;
+        axum::serve(listener, self.router())
+            .with_graceful_shutdown(shutdown_signal.cancelled_owned())
+            .await
+    }
+
+    async fn upgrade(State(server): State<Self>, upgrade: WebSocketUpgrade) -> Response {
+        upgrade.on_upgrade(move |socket| server.handle_connection(socket))
+    }
+
+    /// Handles the subscription requests of a client, and forwards it the notifications it
+    /// subscribed to, until the connection is closed.
+    async fn handle_connection(self, mut socket: WebSocket) {
+        let mut notifications: Option<(mpsc::Receiver<Notification>, CancellationToken)> = None;
+
+        loop {
+            let next_notification = async {
+                match &mut notifications {
+                    Some((receiver, lagging)) => tokio::select! {
+                        notification = receiver.recv() => notification,
+                        () = lagging.cancelled() => None,
+                    },
+                    None => future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                message = socket.recv() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<SubscriptionRequest>(&text) {
+                            Ok(request) => {
+                                notifications = Some(self.subscribe(request.chain_ids));
+                            }
+                            Err(error) => {
+                                warn!(%error, "Invalid WebSocket subscription request");
+                                let _ = socket.send(Message::Close(None)).await;
+                                break;
+                            }
+                        }
+                    }
+                    // Pings are answered automatically, and other messages are ignored.
+                    Some(Ok(Message::Binary(_) | Message::Ping(_) | Message::Pong(_))) => {}
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Err(error)) => {
+                        debug!(%error, "WebSocket connection failed");
+                        break;
+                    }
+                },
+                notification = next_notification => {
+                    let Some(notification) = notification else {
+                        debug!("Disconnecting a WebSocket client lagging behind its notifications");
+                        let _ = socket.send(Message::Close(None)).await;
+                        break;
+                    };
+                    let json = serde_json::to_string(&notification)
+                        .expect("Notifications should be serializable to JSON");
+                    if socket.send(Message::Text(json)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::{crypto::CryptoHash, data_types::BlockHeight, identifiers::ChainId};
+    use linera_core::worker::{Notification, Reason};
+
+    use super::{WebSocketNotificationServer, NOTIFICATION_QUEUE_SIZE};
+
+    fn notification(chain_id: ChainId, height: u64) -> Notification {
+        Notification {
+            chain_id,
+            reason: Reason::NewBlock {
+                height: BlockHeight(height),
+                hash: CryptoHash::test_hash("block"),
+            },
+        }
+    }
+
+    /// Tests that a client whose queue is full is told to disconnect, whichever chain the
+    /// notification was about.
+    #[test]
+    fn lagging_clients_are_unsubscribed() {
+        let server = WebSocketNotificationServer::default();
+        let chain_ids = [ChainId::root(0), ChainId::root(1)];
+        let (mut receiver, lagging) = server.subscribe(chain_ids.to_vec());
+
+        for height in 0..NOTIFICATION_QUEUE_SIZE as u64 {
+            server.notify(&notification(chain_ids[0], height));
+        }
+        assert!(!lagging.is_cancelled());
+        assert_eq!(receiver.try_recv().unwrap(), notification(chain_ids[0], 0));
+
+        server.notify(&notification(chain_ids[0], NOTIFICATION_QUEUE_SIZE as u64));
+        assert!(!lagging.is_cancelled());
+        server.notify(&notification(chain_ids[1], 0));
+        assert!(lagging.is_cancelled());
+        assert!(!server.subscribers.contains_key(&chain_ids[1]));
+    }
+}
@@ -8,8 +8,8 @@ mod utils;
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
 use syn::{
-    parse_macro_input, Fields, ItemEnum,
     __private::{quote::quote, TokenStream2},
+    parse_macro_input, Fields, ItemEnum, Variant,
 };
 
 use crate::utils::{concat, snakify};
@@ -32,64 +32,18 @@ fn generate_mutation_root_code(input: ItemEnum, crate_root: &str) -> TokenStream
     let mutation_root_name = concat(&enum_name, "MutationRoot");
     let mut methods = vec![];
 
-    for variant in input.variants {
-        let variant_name = &variant.ident;
-        let function_name = snakify(variant_name);
-        match variant.fields {
-            Fields::Named(named) => {
-                let mut fields = vec![];
-                let mut field_names = vec![];
-                for field in named.named {
-                    let name = field.ident.expect("named fields always have names");
-                    let ty = field.ty;
-                    fields.push(quote! {#name: #ty});
-                    field_names.push(name);
-                }
-                methods.push(quote! {
-                    async fn #function_name(&self, #(#fields,)*) -> [u8; 0] {
-                        let operation = #enum_name::#variant_name {
-                            #(#field_names,)*
-                        };
-
-                        self.runtime.schedule_operation(&operation);
-
-                        []
-                    }
-                });
-            }
-            Fields::Unnamed(unnamed) => {
-                let mut fields = vec![];
-                let mut field_names = vec![];
-                for (i, field) in unnamed.unnamed.iter().enumerate() {
-                    let name = concat(&syn::parse_str::<Ident>("field").unwrap(), &i.to_string());
-                    let ty = &field.ty;
-                    fields.push(quote! {#name: #ty});
-                    field_names.push(name);
-                }
-                methods.push(quote! {
-                    async fn #function_name(&self, #(#fields,)*) -> [u8; 0] {
-                        let operation = #enum_name::#variant_name(
-                            #(#field_names,)*
-                        );
+    for variant in &input.variants {
+        let function_name = snakify(&variant.ident);
+        let (fields, operation) = variant_constructor(&enum_name, variant);
+        methods.push(quote! {
+            async fn #function_name(&self, #(#fields,)*) -> [u8; 0] {
+                let operation = #operation;
 
-                        self.runtime.schedule_operation(&operation);
+                self.runtime.schedule_operation(&operation);
 
-                        []
-                    }
-                });
-            }
-            Fields::Unit => {
-                methods.push(quote! {
-                    async fn #function_name(&self) -> [u8; 0] {
-                        let operation = #enum_name::#variant_name;
-
-                        self.runtime.schedule_operation(&operation);
-
-                        []
-                    }
-                });
+                []
             }
-        };
+        });
     }
 
     quote! {
@@ -127,11 +81,133 @@ fn generate_mutation_root_code(input: ItemEnum, crate_root: &str) -> TokenStream
     }
 }
 
+/// Returns the parameters needed to build an instance of the enum `variant`, and the
+/// expression building it from these parameters.
+fn variant_constructor(enum_name: &Ident, variant: &Variant) -> (Vec<TokenStream2>, TokenStream2) {
+    let variant_name = &variant.ident;
+    match &variant.fields {
+        Fields::Named(named) => {
+            let mut fields = vec![];
+            let mut field_names = vec![];
+            for field in &named.named {
+                let name = field
+                    .ident
+                    .as_ref()
+                    .expect("named fields always have names");
+                let ty = &field.ty;
+                fields.push(quote! {#name: #ty});
+                field_names.push(name.clone());
+            }
+            let constructor = quote! {
+                #enum_name::#variant_name {
+                    #(#field_names,)*
+                }
+            };
+            (fields, constructor)
+        }
+        Fields::Unnamed(unnamed) => {
+            let mut fields = vec![];
+            let mut field_names = vec![];
+            for (i, field) in unnamed.unnamed.iter().enumerate() {
+                let name = concat(&syn::parse_str::<Ident>("field").unwrap(), &i.to_string());
+                let ty = &field.ty;
+                fields.push(quote! {#name: #ty});
+                field_names.push(name);
+            }
+            let constructor = quote! {
+                #enum_name::#variant_name(
+                    #(#field_names,)*
+                )
+            };
+            (fields, constructor)
+        }
+        Fields::Unit => (vec![], quote! { #enum_name::#variant_name }),
+    }
+}
+
+#[proc_macro_derive(ApplicationClient)]
+pub fn derive_application_client(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as ItemEnum);
+    generate_application_client_code(input, "linera_sdk").into()
+}
+
+#[proc_macro_derive(ApplicationClientInCrate)]
+pub fn derive_application_client_in_crate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as ItemEnum);
+    generate_application_client_code(input, "crate").into()
+}
+
+fn generate_application_client_code(input: ItemEnum, crate_root: &str) -> TokenStream2 {
+    let crate_root = Ident::new(crate_root, Span::call_site());
+    let enum_name = input.ident;
+    let client_name = concat(&enum_name, "Client");
+    let mut methods = vec![];
+
+    for variant in &input.variants {
+        let function_name = snakify(&variant.ident);
+        let (fields, operation) = variant_constructor(&enum_name, variant);
+        let doc = format!(
+            "Calls the application with a [`{enum_name}::{}`] operation.",
+            variant.ident
+        );
+        methods.push(quote! {
+            #[doc = #doc]
+            pub fn #function_name(&mut self, #(#fields,)*) -> Abi::Response {
+                let operation = #operation;
+
+                self.runtime
+                    .call_application(self.authenticated, self.application_id, &operation)
+            }
+        });
+    }
+
+    let doc = format!("A typed client to call applications whose operations are [`{enum_name}`]s.");
+
+    quote! {
+        #[doc = #doc]
+        pub struct #client_name<'runtime, Application, Abi>
+        where
+            Application: #crate_root::Contract,
+        {
+            runtime: &'runtime mut #crate_root::ContractRuntime<Application>,
+            application_id: #crate_root::base::ApplicationId<Abi>,
+            authenticated: bool,
+        }
+
+        impl<'runtime, Application, Abi> #client_name<'runtime, Application, Abi>
+        where
+            Application: #crate_root::Contract,
+            Abi: #crate_root::abi::ContractAbi<Operation = #enum_name> + Send,
+        {
+            /// Creates a client to call the application with the given ID, without forwarding
+            /// the authenticated signer.
+            pub fn new(
+                runtime: &'runtime mut #crate_root::ContractRuntime<Application>,
+                application_id: #crate_root::base::ApplicationId<Abi>,
+            ) -> Self {
+                #client_name {
+                    runtime,
+                    application_id,
+                    authenticated: false,
+                }
+            }
+
+            /// Forwards the authenticated signer to the called application.
+            pub fn with_authentication(mut self) -> Self {
+                self.authenticated = true;
+                self
+            }
+
+            #(#methods)*
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
-    use syn::{parse_quote, ItemEnum, __private::quote::quote};
+    use syn::{__private::quote::quote, parse_quote, ItemEnum};
 
-    use crate::generate_mutation_root_code;
+    use crate::{generate_application_client_code, generate_mutation_root_code};
 
     fn assert_eq_no_whitespace(mut actual: String, mut expected: String) {
         // Intentionally left here for debugging purposes
@@ -211,4 +287,80 @@ pub mod tests {
 
         assert_eq_no_whitespace(output.to_string(), expected.to_string());
     }
+
+    #[test]
+    fn test_derive_application_client() {
+        let operation: ItemEnum = parse_quote! {
+            enum SomeOperation {
+                TupleVariant(String),
+                StructVariant {
+                    a: u32,
+                    b: u64
+                },
+                EmptyVariant
+            }
+        };
+
+        let output = generate_application_client_code(operation, "linera_sdk");
+
+        let expected = quote! {
+            #[doc = "A typed client to call applications whose operations are [`SomeOperation`]s."]
+            pub struct SomeOperationClient<'runtime, Application, Abi>
+            where
+                Application: linera_sdk::Contract,
+            {
+                runtime: &'runtime mut linera_sdk::ContractRuntime<Application>,
+                application_id: linera_sdk::base::ApplicationId<Abi>,
+                authenticated: bool,
+            }
+
+            impl<'runtime, Application, Abi> SomeOperationClient<'runtime, Application, Abi>
+            where
+                Application: linera_sdk::Contract,
+                Abi: linera_sdk::abi::ContractAbi<Operation = SomeOperation> + Send,
+            {
+                /// Creates a client to call the application with the given ID, without forwarding
+                /// the authenticated signer.
+                pub fn new(
+                    runtime: &'runtime mut linera_sdk::ContractRuntime<Application>,
+                    application_id: linera_sdk::base::ApplicationId<Abi>,
+                ) -> Self {
+                    SomeOperationClient {
+                        runtime,
+                        application_id,
+                        authenticated: false,
+                    }
+                }
+
+                /// Forwards the authenticated signer to the called application.
+                pub fn with_authentication(mut self) -> Self {
+                    self.authenticated = true;
+                    self
+                }
+
+                #[doc = "Calls the application with a [`SomeOperation::TupleVariant`] operation."]
+                pub fn tuple_variant(&mut self, field0: String,) -> Abi::Response {
+                    let operation = SomeOperation::TupleVariant(field0,);
+                    self.runtime
+                        .call_application(self.authenticated, self.application_id, &operation)
+                }
+
+                #[doc = "Calls the application with a [`SomeOperation::StructVariant`] operation."]
+                pub fn struct_variant(&mut self, a: u32, b: u64,) -> Abi::Response {
+                    let operation = SomeOperation::StructVariant { a, b, };
+                    self.runtime
+                        .call_application(self.authenticated, self.application_id, &operation)
+                }
+
+                #[doc = "Calls the application with a [`SomeOperation::EmptyVariant`] operation."]
+                pub fn empty_variant(&mut self) -> Abi::Response {
+                    let operation = SomeOperation::EmptyVariant;
+                    self.runtime
+                        .call_application(self.authenticated, self.application_id, &operation)
+                }
+            }
+        };
+
+        assert_eq_no_whitespace(output.to_string(), expected.to_string());
+    }
 }
@@ -11,7 +11,7 @@ use linera_base::{
     data_types::Amount,
     identifiers::{AccountOwner, ChainId},
 };
-use linera_sdk_derive::GraphQLMutationRootInCrate;
+use linera_sdk_derive::{ApplicationClientInCrate, GraphQLMutationRootInCrate};
 use serde::{Deserialize, Serialize};
 
 /// An ABI for applications that implement a fungible token.
@@ -27,8 +27,14 @@ impl ServiceAbi for FungibleTokenAbi {
     type QueryResponse = Response;
 }
 
+/// A typed client to call a fungible token application from another contract.
+pub type FungibleClient<'runtime, Application> =
+    OperationClient<'runtime, Application, FungibleTokenAbi>;
+
 /// An operation
-#[derive(Debug, Deserialize, Serialize, GraphQLMutationRootInCrate)]
+#[derive(
+    Clone, Debug, Deserialize, Serialize, GraphQLMutationRootInCrate, ApplicationClientInCrate,
+)]
 pub enum Operation {
     /// Requests an account balance.
     Balance {
@@ -57,6 +63,35 @@ pub enum Operation {
         /// Target account to claim the amount into
         target_account: Account,
     },
+    /// Allows a `spender` to transfer up to `allowance` tokens from a (locally owned) account,
+    /// replacing any previous allowance.
+    Approve {
+        /// Owner of the account to approve the spending from
+        owner: AccountOwner,
+        /// Owner allowed to spend the tokens
+        spender: AccountOwner,
+        /// Maximum amount that the spender may transfer
+        allowance: Amount,
+    },
+    /// Requests the amount that a `spender` may still transfer from an account.
+    Allowance {
+        /// Owner of the account the allowance applies to
+        owner: AccountOwner,
+        /// Owner allowed to spend the tokens
+        spender: AccountOwner,
+    },
+    /// Transfers tokens from a (possibly remote) account to a (possibly remote) account, on
+    /// behalf of its owner, using the allowance that the owner approved for the `spender`.
+    TransferFrom {
+        /// Source account to transfer the amount from
+        source_account: Account,
+        /// Owner spending the allowance
+        spender: AccountOwner,
+        /// Amount to be transferred
+        amount: Amount,
+        /// Target account to transfer the amount to
+        target_account: Account,
+    },
 }
 
 /// A fungible response
@@ -69,6 +104,8 @@ pub enum FungibleResponse {
     Balance(Amount),
     /// Ticker symbol response
     TickerSymbol(String),
+    /// Allowance response
+    Allowance(Amount),
 }
 
 /// The initial state to instantiate fungible with
@@ -115,6 +152,35 @@ pub struct Account {
     pub owner: AccountOwner,
 }
 
+/// An owner of an account together with a spender allowed to transfer tokens from it.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Eq,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+    SimpleObject,
+    InputObject,
+)]
+#[graphql(input_name = "FungibleOwnerSpender")]
+pub struct OwnerSpender {
+    /// Owner of the account
+    pub owner: AccountOwner,
+    /// Owner allowed to spend the tokens
+    pub spender: AccountOwner,
+}
+
+impl OwnerSpender {
+    /// Creates a new [`OwnerSpender`] pair.
+    pub fn new(owner: AccountOwner, spender: AccountOwner) -> Self {
+        OwnerSpender { owner, spender }
+    }
+}
+
 /// A builder type for constructing the initial state of the application.
 #[derive(Debug, Default)]
 pub struct InitialStateBuilder {
@@ -5,7 +5,7 @@
 
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{Amount, BlockHeight, TimeDelta, Timestamp},
+    data_types::{Amount, ApplicationError, BlockHeight, TimeDelta, Timestamp},
     identifiers::{ApplicationId, BytecodeId, ChainId, MessageId, Owner},
     ownership::{
         ChainOwnership, ChangeApplicationPermissionsError, CloseChainError, TimeoutConfig,
@@ -14,6 +14,16 @@ use linera_base::{
 
 use super::wit::contract_system_api as wit_system_api;
 
+impl From<wit_system_api::ApplicationError> for ApplicationError {
+    fn from(error: wit_system_api::ApplicationError) -> Self {
+        ApplicationError {
+            kind: error.kind,
+            message: error.message,
+            data: error.data,
+        }
+    }
+}
+
 impl From<wit_system_api::Timestamp> for Timestamp {
     fn from(timestamp: wit_system_api::Timestamp) -> Self {
         Timestamp::from(timestamp.inner0)
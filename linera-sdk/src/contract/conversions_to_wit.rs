@@ -6,8 +6,8 @@
 use linera_base::{
     crypto::CryptoHash,
     data_types::{
-        Amount, ApplicationPermissions, BlockHeight, Resources, SendMessageRequest, TimeDelta,
-        Timestamp,
+        Amount, ApplicationError, ApplicationPermissions, BlockHeight, Resources,
+        SendMessageRequest, TimeDelta, Timestamp,
     },
     identifiers::{
         Account, AccountOwner, ApplicationId, BytecodeId, ChainId, ChannelName, Destination,
@@ -16,7 +16,10 @@ use linera_base::{
     ownership::{ChainOwnership, TimeoutConfig},
 };
 
-use super::wit::contract_system_api as wit_system_api;
+use super::wit::{
+    contract_system_api as wit_system_api,
+    exports::linera::app::contract_entrypoints as wit_entrypoints,
+};
 
 impl From<CryptoHash> for wit_system_api::CryptoHash {
     fn from(crypto_hash: CryptoHash) -> Self {
@@ -132,6 +135,7 @@ impl From<SendMessageRequest<Vec<u8>>> for wit_system_api::SendMessageRequest {
             authenticated: message.authenticated,
             is_tracked: message.is_tracked,
             grant: message.grant.into(),
+            expires_at: message.expires_at.map(Into::into),
             message: message.message,
         }
     }
@@ -260,3 +264,13 @@ impl From<ChainOwnership> for wit_system_api::ChainOwnership {
         }
     }
 }
+
+impl From<ApplicationError> for wit_entrypoints::ApplicationError {
+    fn from(error: ApplicationError) -> Self {
+        wit_entrypoints::ApplicationError {
+            kind: error.kind,
+            message: error.message,
+            data: error.data,
+        }
+    }
+}
@@ -12,6 +12,9 @@ mod test_runtime;
 #[doc(hidden)]
 pub mod wit;
 
+/// Re-exports the derive macro generating typed clients for cross-application calls.
+pub use linera_sdk_derive::ApplicationClient;
+
 #[cfg(not(with_testing))]
 pub use self::runtime::ContractRuntime;
 #[cfg(with_testing)]
@@ -38,6 +41,14 @@ macro_rules! contract {
         /// Export the contract interface.
         $crate::export_contract!($contract with_types_in $crate::contract::wit);
 
+        /// The version of the contract's ABI, read by the host to check the calls to the
+        /// contract.
+        #[cfg(target_arch = "wasm32")]
+        #[link_section = "linera:contract-abi-version"]
+        #[used]
+        static CONTRACT_ABI_VERSION: [u8; 4] =
+            <$contract as $crate::abi::ContractAbi>::VERSION.to_le_bytes();
+
         /// Mark the contract type to be exported.
         impl $crate::contract::wit::exports::linera::app::contract_entrypoints::Guest
             for $contract
@@ -55,7 +66,13 @@ macro_rules! contract {
                 )
             }
 
-            fn execute_operation(operation: Vec<u8>) -> Vec<u8> {
+            fn execute_operation(
+                operation: Vec<u8>,
+            ) -> Result<
+                Vec<u8>,
+                $crate::contract::wit::exports::linera::app::contract_entrypoints::ApplicationError,
+            > {
+                use $crate::contract::wit::exports::linera::app::contract_entrypoints as wit;
                 use $crate::util::BlockingWait;
                 $crate::contract::run_async_entrypoint::<$contract, _, _>(
                     unsafe { &mut CONTRACT },
@@ -64,10 +81,14 @@ macro_rules! contract {
                             $crate::bcs::from_bytes(&operation)
                                 .expect("Failed to deserialize operation");
 
-                        let response = contract.execute_operation(operation).blocking_wait();
-
-                        $crate::bcs::to_bytes(&response)
-                            .expect("Failed to serialize contract's `Response`")
+                        contract
+                            .try_execute_operation(operation)
+                            .blocking_wait()
+                            .map(|response| {
+                                $crate::bcs::to_bytes(&response)
+                                    .expect("Failed to serialize contract's `Response`")
+                            })
+                            .map_err(wit::ApplicationError::from)
                     },
                 )
             }
@@ -86,6 +107,27 @@ macro_rules! contract {
                 )
             }
 
+            fn execute_callback(argument: Vec<u8>) {
+                use $crate::util::BlockingWait;
+                $crate::contract::run_async_entrypoint::<$contract, _, _>(
+                    unsafe { &mut CONTRACT },
+                    move |contract| {
+                        let callback: <$contract as $crate::Contract>::Message =
+                            $crate::bcs::from_bytes(&argument)
+                                .expect("Failed to deserialize callback");
+
+                        contract.execute_callback(callback).blocking_wait()
+                    },
+                )
+            }
+
+            fn migrate(old_version: u32) {
+                $crate::contract::run_migration::<$contract>(
+                    unsafe { &mut CONTRACT },
+                    old_version,
+                )
+            }
+
             fn finalize() {
                 use $crate::util::BlockingWait;
 
@@ -120,3 +162,17 @@ where
 
     entrypoint(contract).into()
 }
+
+/// Runs the migration entrypoint of a contract, before the contract is loaded.
+pub fn run_migration<Contract>(contract: &mut Option<Contract>, old_version: u32)
+where
+    Contract: crate::Contract,
+{
+    ContractLogger::install();
+
+    assert!(
+        contract.is_none(),
+        "The state must be migrated before the contract is loaded"
+    );
+    Contract::migrate(&mut ContractRuntime::new(), old_version).blocking_wait();
+}
@@ -6,7 +6,8 @@
 use linera_base::{
     abi::{ContractAbi, ServiceAbi},
     data_types::{
-        Amount, ApplicationPermissions, BlockHeight, Resources, SendMessageRequest, Timestamp,
+        Amount, ApplicationError, ApplicationPermissions, BlockHeight, Resources,
+        SendMessageRequest, Timestamp,
     },
     identifiers::{
         Account, AccountOwner, ApplicationId, BytecodeId, ChainId, ChannelName, Destination,
@@ -262,24 +263,56 @@ where
             &argument,
             &converted_application_ids,
         );
+        // The transaction is reverted if the new application turns out to have another ABI.
+        wit::assert_abi_version(application_id, Abi::VERSION);
         ApplicationId::from(application_id).with_abi::<Abi>()
     }
 
     /// Calls another application.
+    ///
+    /// Panics, aborting the transaction, if the called application returns an error.
     pub fn call_application<A: ContractAbi + Send>(
         &mut self,
         authenticated: bool,
         application: ApplicationId<A>,
         call: &A::Operation,
     ) -> A::Response {
+        self.try_call_application(authenticated, application, call)
+            .unwrap_or_else(|error| panic!("Called application returned an error: {error}"))
+    }
+
+    /// Calls another application, and returns the [`ApplicationError`] it returned, if any.
+    pub fn try_call_application<A: ContractAbi + Send>(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId<A>,
+        call: &A::Operation,
+    ) -> Result<A::Response, ApplicationError> {
         let call_bytes = bcs::to_bytes(call)
             .expect("Failed to serialize `Operation` type for a cross-application call");
 
-        let response_bytes =
-            wit::try_call_application(authenticated, application.forget_abi().into(), &call_bytes);
+        let application_id = application.forget_abi().into();
+        wit::assert_abi_version(application_id, A::VERSION);
+        let response_bytes = wit::try_call_application(authenticated, application_id, &call_bytes)
+            .map_err(ApplicationError::from)?;
 
-        bcs::from_bytes(&response_bytes)
-            .expect("Failed to deserialize `Response` type from cross-application call")
+        Ok(bcs::from_bytes(&response_bytes)
+            .expect("Failed to deserialize `Response` type from cross-application call"))
+    }
+
+    /// Calls another application, with an operation and a response that are opaque blobs of
+    /// bytes.
+    ///
+    /// Unlike [`ContractRuntime::call_application`], this does not check the called
+    /// application's ABI version.
+    pub fn call_raw_application(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId,
+        call: &[u8],
+    ) -> Vec<u8> {
+        wit::try_call_application(authenticated, application.into(), call)
+            .unwrap_or_else(|error| panic!("Called application returned an error: {error:?}"))
     }
 
     /// Adds a new item to an event stream.
@@ -287,13 +320,34 @@ where
         wit::emit(&name.into(), key, value);
     }
 
+    /// Emits an event on the stream `name`, with its payload serialized using BCS and an empty
+    /// key.
+    ///
+    /// The event is recorded in the block, for off-chain observers such as the node service
+    /// and the indexer. Its payload also replaces the one of the latest event emitted on the
+    /// stream, which services can read with `ServiceRuntime::latest_event`, or with
+    /// `ServiceRuntime::read_event` and an empty key.
+    pub fn emit_event(&mut self, name: impl Into<StreamName>, payload: &impl Serialize) {
+        let value = bcs::to_bytes(payload).expect("Failed to serialize event payload");
+        self.emit(name.into(), &[], &value);
+    }
+
     /// Queries an application service as an oracle and returns the response.
     ///
+    /// The query is read-only: it fails if the service schedules any operations. The response is
+    /// recorded in the block, so that validators re-executing it don't query the service again.
+    ///
     /// Should only be used with queries where it is very likely that all validators will compute
     /// the same result, otherwise most block proposals will fail.
     ///
     /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
     /// owner, not a super owner.
+    ///
+    /// The fuel used by the service is charged to the contract, and recorded with the response.
+    ///
+    /// Only available on networks whose validators enable unstable oracles, like
+    /// [`fetch_url`](Self::fetch_url), because validators re-executing the block trust the
+    /// recorded response.
     pub fn query_service<A: ServiceAbi + Send>(
         &mut self,
         application_id: ApplicationId<A>,
@@ -315,6 +369,19 @@ where
         wit::http_post(url, content_type, &payload)
     }
 
+    /// Makes a GET request to the given URL as an oracle and returns the response body.
+    ///
+    /// The response is recorded in the block, so validators confirming the block don't fetch
+    /// the URL again. Should only be used with URLs where it is very likely that all validators
+    /// will receive the same response, otherwise most block proposals will fail. The response
+    /// is charged as bytes read, and can't be larger than what the block may still read.
+    ///
+    /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
+    /// owner, not a super owner. Requires validators to enable the `unstable-oracles` feature.
+    pub fn fetch_url(&mut self, url: &str) -> Vec<u8> {
+        wit::fetch_url(url)
+    }
+
     /// Panics if the current time at block validation is `>= timestamp`. Note that block
     /// validation happens at or after the block timestamp, but isn't necessarily the same.
     ///
@@ -338,6 +405,21 @@ where
     pub fn validation_round(&mut self) -> Option<u32> {
         wit::validation_round()
     }
+
+    /// Schedules a callback of this application, executed with
+    /// [`Contract::execute_callback`] once the chain reaches the `timestamp`.
+    ///
+    /// The callback is executed by the first block of this chain that executes the due
+    /// callbacks at or after the `timestamp`. Clients following the chain propose such a block
+    /// when the time comes, so the application doesn't depend on its users to make progress.
+    ///
+    /// Scheduling a callback is charged like an operation, and an application can only have a
+    /// few callbacks scheduled on a chain at the same time. If the callback fails, it is dropped
+    /// without failing the block.
+    pub fn schedule_callback(&mut self, timestamp: Timestamp, callback: Application::Message) {
+        let argument = bcs::to_bytes(&callback).expect("Failed to serialize callback");
+        wit::schedule_callback(timestamp.into(), &argument);
+    }
 }
 
 /// A helper type that uses the builder pattern to configure how a message is sent, and then
@@ -350,6 +432,7 @@ where
     authenticated: bool,
     is_tracked: bool,
     grant: Resources,
+    expires_at: Option<Timestamp>,
     message: Message,
 }
 
@@ -363,6 +446,7 @@ where
             authenticated: false,
             is_tracked: false,
             grant: Resources::default(),
+            expires_at: None,
             message,
         }
     }
@@ -386,6 +470,13 @@ where
         self
     }
 
+    /// Sets a deadline after which the receiver can no longer accept the message. An expired
+    /// message is rejected instead, and bounces back to the sender if it is tracked.
+    pub fn with_expiry(mut self, expires_at: Timestamp) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
     /// Schedules this `Message` to be sent to the `destination`.
     pub fn send_to(self, destination: impl Into<Destination>) {
         let serialized_message =
@@ -396,6 +487,7 @@ where
             authenticated: self.authenticated,
             is_tracked: self.is_tracked,
             grant: self.grant,
+            expires_at: self.expires_at,
             message: serialized_message,
         };
 
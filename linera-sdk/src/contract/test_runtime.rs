@@ -11,7 +11,8 @@ use std::{
 use linera_base::{
     abi::{ContractAbi, ServiceAbi},
     data_types::{
-        Amount, ApplicationPermissions, BlockHeight, Resources, SendMessageRequest, Timestamp,
+        Amount, ApplicationError, ApplicationPermissions, BlockHeight, Resources,
+        SendMessageRequest, TimeDelta, Timestamp,
     },
     identifiers::{
         Account, AccountOwner, ApplicationId, BytecodeId, ChainId, ChannelName, Destination,
@@ -52,8 +53,9 @@ where
     chain_ownership: Option<ChainOwnership>,
     can_close_chain: Option<bool>,
     can_change_application_permissions: Option<bool>,
-    call_application_handler: Option<CallApplicationHandler>,
+    call_application_handler: Option<TryCallApplicationHandler>,
     send_message_requests: Arc<Mutex<Vec<SendMessageRequest<Application::Message>>>>,
+    scheduled_callbacks: Vec<(Timestamp, Application::Message)>,
     subscribe_requests: Vec<(ChainId, ChannelName)>,
     unsubscribe_requests: Vec<(ChainId, ChannelName)>,
     outgoing_transfers: HashMap<Account, Amount>,
@@ -61,6 +63,8 @@ where
     claim_requests: Vec<ClaimRequest>,
     expected_service_queries: VecDeque<(ApplicationId, String, String)>,
     expected_post_requests: VecDeque<(String, Vec<u8>, Vec<u8>)>,
+    expected_get_requests: VecDeque<(String, Vec<u8>)>,
+    url_blobs: Option<HashMap<String, Vec<u8>>>,
     expected_read_data_blob_requests: VecDeque<(DataBlobHash, Vec<u8>)>,
     expected_assert_data_blob_exists_requests: VecDeque<(DataBlobHash, Option<()>)>,
     expected_open_chain_calls:
@@ -103,6 +107,7 @@ where
             can_change_application_permissions: None,
             call_application_handler: None,
             send_message_requests: Arc::default(),
+            scheduled_callbacks: Vec::new(),
             subscribe_requests: Vec::new(),
             unsubscribe_requests: Vec::new(),
             outgoing_transfers: HashMap::new(),
@@ -110,6 +115,8 @@ where
             claim_requests: Vec::new(),
             expected_service_queries: VecDeque::new(),
             expected_post_requests: VecDeque::new(),
+            expected_get_requests: VecDeque::new(),
+            url_blobs: None,
             expected_read_data_blob_requests: VecDeque::new(),
             expected_assert_data_blob_exists_requests: VecDeque::new(),
             expected_open_chain_calls: VecDeque::new(),
@@ -362,6 +369,17 @@ where
         self
     }
 
+    /// Advances the mocked system time by `delta`, e.g. to test how the contract handles
+    /// deadlines.
+    pub fn advance_system_time(&mut self, delta: TimeDelta) -> &mut Self {
+        let timestamp = self.timestamp.expect(
+            "System time has not been mocked, \
+            please call `MockContractRuntime::set_system_time` first",
+        );
+        self.timestamp = Some(timestamp.saturating_add(delta));
+        self
+    }
+
     /// Retrieves the current system time, i.e. the timestamp of the block in which this is called.
     pub fn system_time(&mut self) -> Timestamp {
         self.timestamp.expect(
@@ -756,28 +774,65 @@ where
     /// Configures the handler for cross-application calls made during the test.
     pub fn with_call_application_handler(
         mut self,
-        handler: impl FnMut(bool, ApplicationId, Vec<u8>) -> Vec<u8> + 'static,
+        mut handler: impl FnMut(bool, ApplicationId, Vec<u8>) -> Vec<u8> + 'static,
     ) -> Self {
-        self.call_application_handler = Some(Box::new(handler));
+        self.call_application_handler = Some(Box::new(move |authenticated, application, call| {
+            Ok(handler(authenticated, application, call))
+        }));
         self
     }
 
     /// Configures the handler for cross-application calls made during the test.
     pub fn set_call_application_handler(
         &mut self,
-        handler: impl FnMut(bool, ApplicationId, Vec<u8>) -> Vec<u8> + 'static,
+        mut handler: impl FnMut(bool, ApplicationId, Vec<u8>) -> Vec<u8> + 'static,
+    ) -> &mut Self {
+        self.call_application_handler = Some(Box::new(move |authenticated, application, call| {
+            Ok(handler(authenticated, application, call))
+        }));
+        self
+    }
+
+    /// Configures the handler for cross-application calls made during the test, which may
+    /// return an [`ApplicationError`] to the caller.
+    pub fn with_try_call_application_handler(
+        mut self,
+        handler: impl FnMut(bool, ApplicationId, Vec<u8>) -> Result<Vec<u8>, ApplicationError> + 'static,
+    ) -> Self {
+        self.call_application_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Configures the handler for cross-application calls made during the test, which may
+    /// return an [`ApplicationError`] to the caller.
+    pub fn set_try_call_application_handler(
+        &mut self,
+        handler: impl FnMut(bool, ApplicationId, Vec<u8>) -> Result<Vec<u8>, ApplicationError> + 'static,
     ) -> &mut Self {
         self.call_application_handler = Some(Box::new(handler));
         self
     }
 
     /// Calls another application.
+    ///
+    /// Panics if the called application returns an error.
     pub fn call_application<A: ContractAbi + Send>(
         &mut self,
         authenticated: bool,
         application: ApplicationId<A>,
         call: &A::Operation,
     ) -> A::Response {
+        self.try_call_application(authenticated, application, call)
+            .unwrap_or_else(|error| panic!("Called application returned an error: {error}"))
+    }
+
+    /// Calls another application, and returns the [`ApplicationError`] it returned, if any.
+    pub fn try_call_application<A: ContractAbi + Send>(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId<A>,
+        call: &A::Operation,
+    ) -> Result<A::Response, ApplicationError> {
         let call_bytes = bcs::to_bytes(call)
             .expect("Failed to serialize `Operation` type for a cross-application call");
 
@@ -785,10 +840,26 @@ where
             "Handler for `call_application` has not been mocked, \
             please call `MockContractRuntime::set_call_application_handler` first",
         );
-        let response_bytes = handler(authenticated, application.forget_abi(), call_bytes);
+        let response_bytes = handler(authenticated, application.forget_abi(), call_bytes)?;
 
-        bcs::from_bytes(&response_bytes)
-            .expect("Failed to deserialize `Response` type from cross-application call")
+        Ok(bcs::from_bytes(&response_bytes)
+            .expect("Failed to deserialize `Response` type from cross-application call"))
+    }
+
+    /// Calls another application, with an operation and a response that are opaque blobs of
+    /// bytes.
+    pub fn call_raw_application(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId,
+        call: &[u8],
+    ) -> Vec<u8> {
+        let handler = self.call_application_handler.as_mut().expect(
+            "Handler for `call_application` has not been mocked, \
+            please call `MockContractRuntime::set_call_application_handler` first",
+        );
+        handler(authenticated, application, call.to_vec())
+            .unwrap_or_else(|error| panic!("Called application returned an error: {error}"))
     }
 
     /// Adds a new item to an event stream.
@@ -796,6 +867,18 @@ where
         self.events.push((name, key.to_vec(), value.to_vec()));
     }
 
+    /// Emits an event on the stream `name`, with its payload serialized using BCS and an empty
+    /// key.
+    pub fn emit_event(&mut self, name: impl Into<StreamName>, payload: &impl Serialize) {
+        let value = bcs::to_bytes(payload).expect("Failed to serialize event payload");
+        self.emit(name.into(), &[], &value);
+    }
+
+    /// Returns the events emitted so far during the test, with their stream names and keys.
+    pub fn emitted_events(&self) -> &[(StreamName, Vec<u8>, Vec<u8>)] {
+        &self.events
+    }
+
     /// Adds an expected `query_service` call`, and the response it should return in the test.
     pub fn add_expected_service_query<A: ServiceAbi + Send>(
         &mut self,
@@ -815,6 +898,49 @@ where
             .push_back((url, payload, response));
     }
 
+    /// Adds an expected `fetch_url` call, and the response it should return in the test.
+    pub fn add_expected_get_request(&mut self, url: String, response: Vec<u8>) {
+        self.expected_get_requests.push_back((url, response));
+    }
+
+    /// Configures the blobs returned when fetching from URLs during the test.
+    ///
+    /// Unlike [`MockContractRuntime::add_expected_get_request`], these can be fetched any
+    /// number of times and in any order.
+    pub fn with_url_blobs(
+        mut self,
+        url_blobs: impl IntoIterator<Item = (String, Vec<u8>)>,
+    ) -> Self {
+        self.url_blobs = Some(url_blobs.into_iter().collect());
+        self
+    }
+
+    /// Configures the blobs returned when fetching from URLs during the test.
+    ///
+    /// Unlike [`MockContractRuntime::add_expected_get_request`], these can be fetched any
+    /// number of times and in any order.
+    pub fn set_url_blobs(
+        &mut self,
+        url_blobs: impl IntoIterator<Item = (String, Vec<u8>)>,
+    ) -> &mut Self {
+        self.url_blobs = Some(url_blobs.into_iter().collect());
+        self
+    }
+
+    /// Configures the `blob` returned when fetching from the `url` during the test.
+    pub fn with_url_blob(mut self, url: impl Into<String>, blob: Vec<u8>) -> Self {
+        self.set_url_blob(url, blob);
+        self
+    }
+
+    /// Configures the `blob` returned when fetching from the `url` during the test.
+    pub fn set_url_blob(&mut self, url: impl Into<String>, blob: Vec<u8>) -> &mut Self {
+        self.url_blobs
+            .get_or_insert_with(HashMap::new)
+            .insert(url.into(), blob);
+        self
+    }
+
     /// Adds an expected `read_data_blob` call, and the response it should return in the test.
     pub fn add_expected_read_data_blob_requests(&mut self, hash: DataBlobHash, response: Vec<u8>) {
         self.expected_read_data_blob_requests
@@ -868,6 +994,34 @@ where
         response
     }
 
+    /// Makes a GET request to the given URL as an oracle and returns the response body.
+    ///
+    /// Should only be used with URLs where it is very likely that all validators will receive
+    /// the same response, otherwise most block proposals will fail.
+    ///
+    /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
+    /// owner, not a super owner.
+    ///
+    /// Expected requests added with [`MockContractRuntime::add_expected_get_request`] are
+    /// checked first, in order, before falling back to the blobs configured with
+    /// [`MockContractRuntime::set_url_blob`].
+    pub fn fetch_url(&mut self, url: &str) -> Vec<u8> {
+        if let Some((expected_url, response)) = self.expected_get_requests.pop_front() {
+            assert_eq!(*url, expected_url);
+            return response;
+        }
+        self.url_blobs
+            .as_ref()
+            .and_then(|url_blobs| url_blobs.get(url).cloned())
+            .unwrap_or_else(|| {
+                panic!(
+                    "Unexpected GET request to {url:?}, please call \
+                    `MockContractRuntime::add_expected_get_request` or \
+                    `MockContractRuntime::set_url_blob` first"
+                )
+            })
+    }
+
     /// Panics if the current time at block validation is `>= timestamp`. Note that block
     /// validation happens at or after the block timestamp, but isn't necessarily the same.
     ///
@@ -898,11 +1052,26 @@ where
     pub fn validation_round(&mut self) -> Option<u32> {
         self.round
     }
+
+    /// Schedules a callback of this application, executed once the chain reaches the
+    /// `timestamp`.
+    pub fn schedule_callback(&mut self, timestamp: Timestamp, callback: Application::Message) {
+        self.scheduled_callbacks.push((timestamp, callback));
+    }
+
+    /// Returns the callbacks scheduled so far during the test, with their scheduled times.
+    pub fn scheduled_callbacks(&self) -> &[(Timestamp, Application::Message)] {
+        &self.scheduled_callbacks
+    }
 }
 
 /// A type alias for the handler for cross-application calls.
 pub type CallApplicationHandler = Box<dyn FnMut(bool, ApplicationId, Vec<u8>) -> Vec<u8>>;
 
+/// A type alias for the handler for cross-application calls that may return an error.
+pub type TryCallApplicationHandler =
+    Box<dyn FnMut(bool, ApplicationId, Vec<u8>) -> Result<Vec<u8>, ApplicationError>>;
+
 /// A helper type that uses the builder pattern to configure how a message is sent, and then
 /// sends the message once it is dropped.
 #[must_use]
@@ -913,6 +1082,7 @@ where
     authenticated: bool,
     is_tracked: bool,
     grant: Resources,
+    expires_at: Option<Timestamp>,
     message: Message,
     send_message_requests: Arc<Mutex<Vec<SendMessageRequest<Message>>>>,
 }
@@ -930,6 +1100,7 @@ where
             authenticated: false,
             is_tracked: false,
             grant: Resources::default(),
+            expires_at: None,
             message,
             send_message_requests,
         }
@@ -954,6 +1125,12 @@ where
         self
     }
 
+    /// Sets a deadline after which the receiver can no longer accept the message.
+    pub fn with_expiry(mut self, expires_at: Timestamp) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
     /// Schedules this `Message` to be sent to the `destination`.
     pub fn send_to(self, destination: impl Into<Destination>) {
         let request = SendMessageRequest {
@@ -961,6 +1138,7 @@ where
             authenticated: self.authenticated,
             is_tracked: self.is_tracked,
             grant: self.grant,
+            expires_at: self.expires_at,
             message: self.message,
         };
 
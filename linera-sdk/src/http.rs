@@ -0,0 +1,73 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Types for services handling plain HTTP requests with JSON bodies.
+//!
+//! The node service forwards the requests to
+//! `/chains/<CHAIN_ID>/applications/<APPLICATION_ID>/<PATH>` to the service as an
+//! [`HttpRequest`]. A service only handling these requests can use [`HttpRequest`] and
+//! [`HttpResponse`] as its [`ServiceAbi`](crate::abi::ServiceAbi) query types, while a service
+//! also handling GraphQL requests can use [`ServiceRequest`] and [`ServiceResponse`].
+
+pub use linera_base::http::{HttpMethod, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+/// A query to a service that handles both GraphQL and plain HTTP requests.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ServiceRequest {
+    /// A plain HTTP request.
+    Http(HttpRequest),
+    /// A GraphQL request.
+    GraphQL(async_graphql::Request),
+}
+
+/// The response of a service that handles both GraphQL and plain HTTP requests.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ServiceResponse {
+    /// The response to a plain HTTP request.
+    Http(HttpResponse),
+    /// The response to a GraphQL request.
+    GraphQL(async_graphql::Response),
+}
+
+impl From<HttpResponse> for ServiceResponse {
+    fn from(response: HttpResponse) -> Self {
+        ServiceResponse::Http(response)
+    }
+}
+
+impl From<async_graphql::Response> for ServiceResponse {
+    fn from(response: async_graphql::Response) -> Self {
+        ServiceResponse::GraphQL(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_requests_are_distinguished_by_their_fields() {
+        let http_request = HttpRequest {
+            method: HttpMethod::Post,
+            path: "/transfer".to_owned(),
+            body: serde_json::json!({ "amount": "1." }),
+        };
+        let bytes = serde_json::to_vec(&http_request).unwrap();
+        let request = serde_json::from_slice::<ServiceRequest>(&bytes).unwrap();
+        assert!(matches!(request, ServiceRequest::Http(request) if request == http_request));
+
+        let graphql_request = async_graphql::Request::new("query { value }");
+        let bytes = serde_json::to_vec(&graphql_request).unwrap();
+        let request = serde_json::from_slice::<ServiceRequest>(&bytes).unwrap();
+        assert!(
+            matches!(request, ServiceRequest::GraphQL(request) if request.query == "query { value }")
+        );
+
+        let bytes = serde_json::to_vec(&ServiceResponse::from(HttpResponse::ok("done"))).unwrap();
+        let response = serde_json::from_slice::<HttpResponse>(&bytes).unwrap();
+        assert_eq!(response, HttpResponse::ok("done"));
+    }
+}
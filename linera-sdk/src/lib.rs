@@ -36,18 +36,23 @@ pub mod contract;
 pub mod ethereum;
 mod extensions;
 pub mod graphql;
+pub mod http;
 mod log;
 pub mod service;
 #[cfg(with_testing)]
 pub mod test;
 pub mod views;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod wasm_size;
 
 use std::fmt::Debug;
 
+#[doc(hidden)]
+pub use ::log as log_crate;
 pub use bcs;
 pub use linera_base::{
     abi,
-    data_types::{Resources, SendMessageRequest},
+    data_types::{ApplicationError, Resources, SendMessageRequest},
     ensure,
 };
 use linera_base::{
@@ -115,6 +120,24 @@ pub trait Contract: WithContractAbi + ContractAbi + Sized {
     /// application's execution.
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response;
 
+    /// Applies an operation, from the current block or from another application calling this
+    /// one, and may return an [`ApplicationError`] instead of a response.
+    ///
+    /// A panic aborts the whole transaction, while an error is returned to the calling
+    /// application, which can handle it if it uses [`ContractRuntime::try_call_application`].
+    /// The changes made before returning the error are kept. An error returned for an operation
+    /// of a block fails the block like a panic.
+    ///
+    /// This is the method called by the host. The default implementation calls
+    /// [`Contract::execute_operation`], which never returns an error. Contracts that override
+    /// it can implement [`Contract::execute_operation`] by panicking if this returns an error.
+    async fn try_execute_operation(
+        &mut self,
+        operation: Self::Operation,
+    ) -> Result<Self::Response, ApplicationError> {
+        Ok(self.execute_operation(operation).await)
+    }
+
     /// Applies a message originating from a cross-chain message.
     ///
     /// Messages are messages sent across chains. These messages are created and received by
@@ -128,6 +151,29 @@ pub trait Contract: WithContractAbi + ContractAbi + Sized {
     /// chain.
     async fn execute_message(&mut self, message: Self::Message);
 
+    /// Executes a callback that the application scheduled with
+    /// [`ContractRuntime::schedule_callback`].
+    ///
+    /// Callbacks are executed by the first block of the chain that executes the due callbacks at
+    /// or after their scheduled time, e.g. to release vested tokens or to close an auction
+    /// without waiting for a user to do so. They are not authenticated by any signer. The default
+    /// implementation does nothing.
+    async fn execute_callback(&mut self, _callback: Self::Message) {}
+
+    /// Migrates the application state after the application's bytecode was upgraded.
+    ///
+    /// This is called once, before the first operation or message is executed with the new
+    /// bytecode on a chain. It is called before the contract is [loaded](Contract::load), so that
+    /// the state can be rewritten before it is read with its new layout, e.g. with a
+    /// [`StateMigration`](views::StateMigration). Until then, queries to the application's
+    /// service on that chain are rejected.
+    ///
+    /// The `old_version` is the version of the bytecode that last used the state: the number of
+    /// times the application had been upgraded at that point, starting from 0 for the bytecode it
+    /// was created with. If the application was upgraded several times since, the migration
+    /// should go through all the intermediate layouts. The default implementation does nothing.
+    async fn migrate(_runtime: &mut ContractRuntime<Self>, _old_version: u32) {}
+
     /// Finishes the execution of the current transaction.
     ///
     /// This is called once at the end of the transaction, to allow all applications that
@@ -69,6 +69,28 @@ impl Log for ServiceLogger {
     fn flush(&self) {}
 }
 
+/// Logs a message with structured fields through the host executing the application.
+///
+/// The level is the name of a [`log::Level`], and the fields are appended to the message as
+/// `key=value` pairs, formatted with [`Debug`]. The host records the ID of the application
+/// with each message, so that the logs of a single application can be selected with a filter
+/// such as `RUST_LOG='linera_execution[application{id=<ID>}]=debug'`.
+///
+/// ```rust
+/// # let amount = 10;
+/// linera_sdk::log!(Info, "Transferred tokens", amount = amount, owner = "Alice");
+/// ```
+#[macro_export]
+macro_rules! log {
+    ($level:ident, $message:expr $(, $key:ident = $value:expr)* $(,)?) => {{
+        let mut message = ::std::string::ToString::to_string(&$message);
+        $(
+            message.push_str(&::std::format!(" {}={:?}", ::std::stringify!($key), $value));
+        )*
+        $crate::log_crate::log!($crate::log_crate::Level::$level, "{message}");
+    }};
+}
+
 /// Logs a panic using the [`log`] API.
 fn log_panic(info: &PanicHookInfo<'_>) {
     log::error!("{info}");
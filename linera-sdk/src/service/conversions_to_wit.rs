@@ -6,7 +6,7 @@
 use linera_base::{
     crypto::CryptoHash,
     data_types::BlockHeight,
-    identifiers::{AccountOwner, ApplicationId, BytecodeId, ChainId, MessageId, Owner},
+    identifiers::{AccountOwner, ApplicationId, BytecodeId, ChainId, MessageId, Owner, StreamName},
 };
 
 use super::wit::service_system_api as wit_system_api;
@@ -98,3 +98,11 @@ impl From<MessageId> for wit_system_api::MessageId {
         }
     }
 }
+
+impl From<StreamName> for wit_system_api::StreamName {
+    fn from(name: StreamName) -> Self {
+        wit_system_api::StreamName {
+            inner0: name.into_bytes(),
+        }
+    }
+}
@@ -38,6 +38,14 @@ macro_rules! service {
         /// Export the service interface.
         $crate::export_service!($service with_types_in $crate::service::wit);
 
+        /// The version of the service's ABI, read by the host to check the queries to the
+        /// service.
+        #[cfg(target_arch = "wasm32")]
+        #[link_section = "linera:service-abi-version"]
+        #[used]
+        static SERVICE_ABI_VERSION: [u8; 4] =
+            <$service as $crate::abi::ServiceAbi>::VERSION.to_le_bytes();
+
         /// Mark the service type to be exported.
         impl $crate::service::wit::exports::linera::app::service_entrypoints::Guest for $service {
             fn handle_query(argument: Vec<u8>) -> Vec<u8> {
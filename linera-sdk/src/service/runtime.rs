@@ -8,9 +8,9 @@ use std::sync::Mutex;
 use linera_base::{
     abi::ServiceAbi,
     data_types::{Amount, BlockHeight, Timestamp},
-    identifiers::{AccountOwner, ApplicationId, ChainId},
+    identifiers::{AccountOwner, ApplicationId, ChainId, StreamName},
 };
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 
 use super::wit::service_system_api as wit;
 use crate::{DataBlobHash, KeyValueStore, Service, ViewStorageContext};
@@ -145,8 +145,9 @@ where
         let query_bytes =
             serde_json::to_vec(&query).expect("Failed to serialize query to another application");
 
-        let response_bytes =
-            wit::try_query_application(application.forget_abi().into(), &query_bytes);
+        let application_id = application.forget_abi().into();
+        wit::assert_abi_version(application_id, A::VERSION);
+        let response_bytes = wit::try_query_application(application_id, &query_bytes);
 
         serde_json::from_slice(&response_bytes)
             .expect("Failed to deserialize query response from application")
@@ -182,4 +183,30 @@ where
     pub fn assert_data_blob_exists(&self, hash: DataBlobHash) {
         wit::assert_data_blob_exists(hash.0.into())
     }
+
+    /// Reads the value of the latest event emitted by an application on this chain, on the
+    /// stream `name` with the given `key`.
+    pub fn read_event<A>(
+        &self,
+        application_id: ApplicationId<A>,
+        name: impl Into<StreamName>,
+        key: &[u8],
+    ) -> Option<Vec<u8>> {
+        let name: StreamName = name.into();
+        wit::read_event(application_id.forget_abi().into(), &name.into(), key)
+    }
+
+    /// Reads the payload of the latest event emitted by an application on this chain with
+    /// `ContractRuntime::emit_event`, on the stream `name`.
+    pub fn latest_event<A, Event>(
+        &self,
+        application_id: ApplicationId<A>,
+        name: impl Into<StreamName>,
+    ) -> Option<Event>
+    where
+        Event: DeserializeOwned,
+    {
+        let value = self.read_event(application_id, name, &[])?;
+        Some(bcs::from_bytes(&value).expect("Failed to deserialize event payload"))
+    }
 }
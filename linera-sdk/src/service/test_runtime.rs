@@ -7,9 +7,9 @@ use std::{collections::HashMap, mem, sync::Mutex};
 
 use linera_base::{
     abi::ServiceAbi,
-    data_types::{Amount, BlockHeight, Timestamp},
+    data_types::{Amount, BlockHeight, TimeDelta, Timestamp},
     hex,
-    identifiers::{AccountOwner, ApplicationId, ChainId},
+    identifiers::{AccountOwner, ApplicationId, ChainId, StreamName},
 };
 use serde::{de::DeserializeOwned, Serialize};
 
@@ -30,6 +30,7 @@ where
     query_application_handler: Mutex<Option<QueryApplicationHandler>>,
     url_blobs: Mutex<Option<HashMap<String, Vec<u8>>>>,
     blobs: Mutex<Option<HashMap<DataBlobHash, Vec<u8>>>>,
+    events: Mutex<HashMap<(ApplicationId, StreamName, Vec<u8>), Vec<u8>>>,
     scheduled_operations: Mutex<Vec<Vec<u8>>>,
     key_value_store: KeyValueStore,
 }
@@ -60,6 +61,7 @@ where
             query_application_handler: Mutex::new(None),
             url_blobs: Mutex::new(None),
             blobs: Mutex::new(None),
+            events: Mutex::new(HashMap::new()),
             scheduled_operations: Mutex::new(vec![]),
             key_value_store: KeyValueStore::mock(),
         }
@@ -177,6 +179,17 @@ where
         self
     }
 
+    /// Advances the mocked system time by `delta`.
+    pub fn advance_system_time(&self, delta: TimeDelta) -> &Self {
+        let mut timestamp = self.timestamp.lock().unwrap();
+        let current = timestamp.expect(
+            "System time has not been mocked, \
+            please call `MockServiceRuntime::set_system_time` first",
+        );
+        *timestamp = Some(current.saturating_add(delta));
+        self
+    }
+
     /// Retrieves the current system time, i.e. the timestamp of the block in which this is called.
     pub fn system_time(&self) -> Timestamp {
         Self::fetch_mocked_value(
@@ -477,6 +490,76 @@ where
             });
     }
 
+    /// Configures the `value` of the latest event emitted by an application on the stream `name`
+    /// with the given `key`, as read during the test.
+    pub fn with_event<A>(
+        self,
+        application_id: ApplicationId<A>,
+        name: impl Into<StreamName>,
+        key: &[u8],
+        value: Vec<u8>,
+    ) -> Self {
+        self.set_event(application_id, name, key, value);
+        self
+    }
+
+    /// Configures the `value` of the latest event emitted by an application on the stream `name`
+    /// with the given `key`, as read during the test.
+    pub fn set_event<A>(
+        &self,
+        application_id: ApplicationId<A>,
+        name: impl Into<StreamName>,
+        key: &[u8],
+        value: Vec<u8>,
+    ) -> &Self {
+        self.events.lock().unwrap().insert(
+            (application_id.forget_abi(), name.into(), key.to_vec()),
+            value,
+        );
+        self
+    }
+
+    /// Configures the payload of the latest event emitted by an application with
+    /// `ContractRuntime::emit_event` on the stream `name`, as read during the test.
+    pub fn set_latest_event<A>(
+        &self,
+        application_id: ApplicationId<A>,
+        name: impl Into<StreamName>,
+        payload: &impl Serialize,
+    ) -> &Self {
+        let value = bcs::to_bytes(payload).expect("Failed to serialize event payload");
+        self.set_event(application_id, name, &[], value)
+    }
+
+    /// Reads the value of the latest event emitted by an application on this chain, on the
+    /// stream `name` with the given `key`.
+    pub fn read_event<A>(
+        &self,
+        application_id: ApplicationId<A>,
+        name: impl Into<StreamName>,
+        key: &[u8],
+    ) -> Option<Vec<u8>> {
+        self.events
+            .lock()
+            .unwrap()
+            .get(&(application_id.forget_abi(), name.into(), key.to_vec()))
+            .cloned()
+    }
+
+    /// Reads the payload of the latest event emitted by an application on this chain with
+    /// `ContractRuntime::emit_event`, on the stream `name`.
+    pub fn latest_event<A, Event>(
+        &self,
+        application_id: ApplicationId<A>,
+        name: impl Into<StreamName>,
+    ) -> Option<Event>
+    where
+        Event: DeserializeOwned,
+    {
+        let value = self.read_event(application_id, name, &[])?;
+        Some(bcs::from_bytes(&value).expect("Failed to deserialize event payload"))
+    }
+
     /// Loads a mocked value from the `slot` cache or panics with a provided `message`.
     fn fetch_mocked_value<T>(slot: &Mutex<Option<T>>, message: &str) -> T
     where
@@ -38,7 +38,8 @@ impl BlockBuilder {
     ///
     /// Initializes the block so that it belongs to the microchain identified by `chain_id` and
     /// owned by `owner`. It becomes the block after the specified `previous_block`, or the genesis
-    /// block if [`None`] is specified.
+    /// block if [`None`] is specified. Its timestamp is the current time of the `validator`, or
+    /// the timestamp of the `previous_block` if that is later.
     ///
     /// # Notes
     ///
@@ -61,6 +62,10 @@ impl BlockBuilder {
                     .expect("Block height limit reached")
             })
             .unwrap_or_default();
+        let timestamp = previous_block
+            .map(|certificate| certificate.inner().block().header.timestamp)
+            .unwrap_or_default()
+            .max(validator.current_time());
 
         BlockBuilder {
             block: ProposedBlock {
@@ -71,13 +76,14 @@ impl BlockBuilder {
                 previous_block_hash,
                 height,
                 authenticated_signer: Some(owner),
-                timestamp: Timestamp::from(0),
+                timestamp,
+                priority_fee: Amount::ZERO,
             },
             validator,
         }
     }
 
-    /// Configures the timestamp of this block.
+    /// Configures the timestamp of this block, instead of the current time of the validator.
     pub fn with_timestamp(&mut self, timestamp: Timestamp) -> &mut Self {
         self.block.timestamp = timestamp;
         self
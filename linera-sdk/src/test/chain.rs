@@ -17,18 +17,20 @@ use linera_base::{
     data_types::{Blob, BlockHeight, Bytecode, CompressedBytecode},
     identifiers::{ApplicationId, BytecodeId, ChainDescription, ChainId, MessageId},
 };
-use linera_chain::{types::ConfirmedBlockCertificate, ChainError, ChainExecutionContext};
+use linera_chain::{
+    data_types::IncomingBundle, types::ConfirmedBlockCertificate, ChainError, ChainExecutionContext,
+};
 use linera_core::{data_types::ChainInfoQuery, worker::WorkerError};
 use linera_execution::{
     system::{SystemExecutionError, SystemOperation, CREATE_APPLICATION_MESSAGE_INDEX},
-    ExecutionError, Query, QueryOutcome, QueryResponse,
+    ApplicationCallPolicy, ExecutionError, Query, QueryOutcome, QueryResponse,
 };
 use linera_storage::Storage as _;
 use serde::Serialize;
 use tokio::{fs, sync::Mutex};
 
 use super::{BlockBuilder, TestValidator};
-use crate::{ContractAbi, ServiceAbi};
+use crate::{wasm_size, ContractAbi, DataBlobHash, ServiceAbi};
 
 /// A reference to a single microchain inside a [`TestValidator`].
 pub struct ActiveChain {
@@ -166,6 +168,16 @@ impl ActiveChain {
     /// Adds a block to this microchain that receives all queued messages in the microchains
     /// inboxes.
     pub async fn handle_received_messages(&self) {
+        let messages = self.pending_message_bundles().await;
+
+        self.add_block(|block| {
+            block.with_incoming_bundles(messages);
+        })
+        .await;
+    }
+
+    /// Returns the message bundles queued in the inboxes of this microchain.
+    pub(crate) async fn pending_message_bundles(&self) -> Vec<IncomingBundle> {
         let chain_id = self.id();
         let (information, _) = self
             .validator
@@ -173,12 +185,7 @@ impl ActiveChain {
             .handle_chain_info_query(ChainInfoQuery::new(chain_id).with_pending_message_bundles())
             .await
             .expect("Failed to query chain's pending messages");
-        let messages = information.info.requested_pending_message_bundles;
-
-        self.add_block(|block| {
-            block.with_incoming_bundles(messages);
-        })
-        .await;
+        information.info.requested_pending_message_bundles
     }
 
     /// Publishes the bytecodes in the crate calling this method to this microchain.
@@ -229,6 +236,23 @@ impl ActiveChain {
         bytecode_id.with_abi()
     }
 
+    /// Publishes a data blob with the given `bytes` on this microchain, and returns its hash,
+    /// e.g. to reference it in the operations of an application.
+    pub async fn publish_data_blob(&self, bytes: impl Into<Box<[u8]>>) -> DataBlobHash {
+        let blob = Blob::new_data(bytes);
+        let blob_hash = blob.id().hash;
+
+        self.add_block_with_blobs(
+            |block| {
+                block.with_system_operation(SystemOperation::PublishDataBlob { blob_hash });
+            },
+            vec![blob],
+        )
+        .await;
+
+        DataBlobHash(blob_hash)
+    }
+
     /// Compiles the crate in the `repository` path.
     async fn build_bytecodes_in(repository: &Path) {
         let output = std::process::Command::new("cargo")
@@ -283,8 +307,10 @@ impl ActiveChain {
             .find_output_directory_of(repository)
             .await
             .expect("Failed to look for output binaries");
-        let contract_path = base_path.join(format!("{}.wasm", contract_binary));
-        let service_path = base_path.join(format!("{}.wasm", service_binary));
+        let contract_path =
+            Self::optimize_bytecode(base_path.join(format!("{}.wasm", contract_binary))).await;
+        let service_path =
+            Self::optimize_bytecode(base_path.join(format!("{}.wasm", service_binary))).await;
 
         let contract = Bytecode::load_from_file(contract_path)
             .await
@@ -298,6 +324,36 @@ impl ActiveChain {
             .expect("Failed to compress bytecodes")
     }
 
+    /// Optimizes the bytecode at `path` for size, as it would be before being published.
+    ///
+    /// Returns the path of the optimized bytecode, stored in an `optimized` sub-directory next to
+    /// the original one, and reused until the original bytecode is rebuilt.
+    async fn optimize_bytecode(path: PathBuf) -> PathBuf {
+        tokio::task::spawn_blocking(move || {
+            let file_name = path
+                .file_name()
+                .expect("Bytecode path should have a file name");
+            let directory = path
+                .parent()
+                .expect("Bytecode path should have a parent directory")
+                .join("optimized");
+            let optimized_path = directory.join(file_name);
+            let modified = |path: &Path| std::fs::metadata(path).and_then(|data| data.modified());
+            let is_up_to_date = matches!(
+                (modified(&path), modified(&optimized_path)),
+                (Ok(original), Ok(optimized)) if optimized >= original
+            );
+            if !is_up_to_date {
+                std::fs::create_dir_all(&directory)
+                    .expect("Failed to create the directory for optimized bytecodes");
+                wasm_size::optimize(&path, &optimized_path).expect("Failed to optimize bytecode");
+            }
+            optimized_path
+        })
+        .await
+        .expect("Failed to optimize bytecode")
+    }
+
     /// Searches for the directory where the built WebAssembly binaries should be.
     ///
     /// Assumes that the binaries will be built and placed inside a
@@ -372,6 +428,7 @@ impl ActiveChain {
                     parameters,
                     instantiation_argument,
                     required_application_ids,
+                    call_policy: ApplicationCallPolicy::default(),
                 });
             })
             .await;
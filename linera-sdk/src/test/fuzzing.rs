@@ -0,0 +1,244 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Property-based testing of applications with random sequences of operations.
+//!
+//! An [`OperationFuzzer`] generates sequences of operations for a [`FuzzedApplication`] with
+//! a [`proptest`] strategy, executes each sequence on new microchains, delivering the messages
+//! they send, and checks the invariants of the application after every block. When an
+//! invariant is violated, the sequence is shrunk before the test fails, so that it reports a
+//! short sequence of operations breaking it.
+//!
+//! The seed of the random sequences is printed when the fuzzer starts and when it fails. A
+//! failure can be reproduced by setting the [`FUZZING_SEED_VARIABLE`] environment variable to
+//! that seed, or with [`OperationFuzzer::with_seed`].
+
+use std::{env, fmt::Write as _, future::Future, ops::Range};
+
+use linera_base::identifiers::ApplicationId;
+use proptest::{
+    arbitrary::any,
+    collection,
+    prelude::RngCore as _,
+    sample::Index,
+    strategy::{Strategy, ValueTree},
+    test_runner::{Config, RngAlgorithm, TestRng, TestRunner},
+};
+
+use super::ActiveChain;
+use crate::ContractAbi;
+
+/// The environment variable setting the seed of the random sequences of operations, unless
+/// [`OperationFuzzer::with_seed`] is used.
+pub const FUZZING_SEED_VARIABLE: &str = "LINERA_FUZZING_SEED";
+
+/// An application whose invariants are checked by an [`OperationFuzzer`].
+pub trait FuzzedApplication {
+    /// The ABI of the application.
+    type Abi: ContractAbi;
+
+    /// A random description of an operation, turned into an operation of the application
+    /// once the microchains exist, e.g. to refer to the accounts of their owners.
+    type Action: std::fmt::Debug;
+
+    /// Returns the strategy generating the actions.
+    fn actions(&self) -> impl Strategy<Value = Self::Action>;
+
+    /// Creates the microchains and the application to execute a sequence of operations on.
+    ///
+    /// This is called for each sequence, which should use a new
+    /// [`TestValidator`](super::TestValidator) so that sequences don't affect each other.
+    fn setup(&self) -> impl Future<Output = (Vec<ActiveChain>, ApplicationId<Self::Abi>)>;
+
+    /// Returns the operation described by `action`, to be executed on the microchain
+    /// `chains[chain_index]`.
+    fn operation(
+        &self,
+        action: &Self::Action,
+        chain_index: usize,
+        chains: &[ActiveChain],
+    ) -> <Self::Abi as ContractAbi>::Operation;
+
+    /// Checks the invariants of the application, e.g. that its total supply of tokens is
+    /// unchanged, and returns a description of the first one that is violated.
+    fn check_invariants(
+        &self,
+        chains: &[ActiveChain],
+        application_id: ApplicationId<Self::Abi>,
+    ) -> impl Future<Output = Result<(), String>>;
+}
+
+/// Checks the invariants of a [`FuzzedApplication`] against random sequences of operations.
+///
+/// Operations that the application rejects are skipped, so the actions don't need to
+/// describe only valid operations.
+#[derive(Clone, Debug)]
+pub struct OperationFuzzer {
+    cases: u32,
+    sequence_length: Range<usize>,
+    max_shrink_iterations: u32,
+    seed: Option<u64>,
+}
+
+impl Default for OperationFuzzer {
+    fn default() -> Self {
+        OperationFuzzer {
+            cases: 32,
+            sequence_length: 1..16,
+            max_shrink_iterations: 256,
+            seed: None,
+        }
+    }
+}
+
+impl OperationFuzzer {
+    /// Sets the number of sequences of operations to execute.
+    pub fn with_cases(mut self, cases: u32) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    /// Sets the range of the number of operations in each sequence.
+    pub fn with_sequence_length(mut self, sequence_length: Range<usize>) -> Self {
+        self.sequence_length = sequence_length;
+        self
+    }
+
+    /// Sets the maximal number of sequences to execute while shrinking a failing one.
+    pub fn with_max_shrink_iterations(mut self, max_shrink_iterations: u32) -> Self {
+        self.max_shrink_iterations = max_shrink_iterations;
+        self
+    }
+
+    /// Sets the seed of the random sequences of operations, e.g. to reproduce a failure.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Executes random sequences of operations of the `application`, and panics with the
+    /// shortest sequence found that violates one of its invariants, if any.
+    pub async fn run<Application: FuzzedApplication>(&self, application: &Application) {
+        let seed = self.seed();
+        eprintln!("Fuzzing operations with seed {seed}");
+        let mut seed_bytes = [0; 32];
+        seed_bytes[..8].copy_from_slice(&seed.to_le_bytes());
+        let mut runner = TestRunner::new_with_rng(
+            Config {
+                cases: self.cases,
+                ..Config::default()
+            },
+            TestRng::from_seed(RngAlgorithm::ChaCha, &seed_bytes),
+        );
+        let strategy = collection::vec(
+            (any::<Index>(), application.actions()),
+            self.sequence_length.clone(),
+        );
+        for _ in 0..self.cases {
+            let mut sequence = strategy
+                .new_tree(&mut runner)
+                .expect("Failed to generate a sequence of operations");
+            let Err(mut failure) = Self::execute(application, &sequence.current()).await else {
+                continue;
+            };
+            let mut iterations = 0;
+            if sequence.simplify() {
+                while iterations < self.max_shrink_iterations {
+                    iterations += 1;
+                    match Self::execute(application, &sequence.current()).await {
+                        Err(error) => {
+                            failure = error;
+                            if !sequence.simplify() {
+                                break;
+                            }
+                        }
+                        Ok(()) => {
+                            if !sequence.complicate() {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            panic!("{failure}\nReproduce this failure with {FUZZING_SEED_VARIABLE}={seed}");
+        }
+    }
+
+    /// Returns the seed set with [`OperationFuzzer::with_seed`] or the
+    /// [`FUZZING_SEED_VARIABLE`], or a random one.
+    fn seed(&self) -> u64 {
+        if let Some(seed) = self.seed {
+            return seed;
+        }
+        match env::var(FUZZING_SEED_VARIABLE) {
+            Ok(seed) => seed.parse().unwrap_or_else(|error| {
+                panic!("Invalid {FUZZING_SEED_VARIABLE} {seed:?}: {error}");
+            }),
+            Err(_) => TestRunner::default().rng().next_u64(),
+        }
+    }
+
+    /// Executes a sequence of actions on new microchains, and returns the description of the
+    /// first invariant violation.
+    async fn execute<Application: FuzzedApplication>(
+        application: &Application,
+        sequence: &[(Index, Application::Action)],
+    ) -> Result<(), String> {
+        let (chains, application_id) = application.setup().await;
+        assert!(
+            !chains.is_empty(),
+            "Operations need at least one microchain"
+        );
+        application
+            .check_invariants(&chains, application_id)
+            .await
+            .map_err(|error| format!("Invariant violated before any operation: {error}"))?;
+        let mut log = String::new();
+        for (index, action) in sequence {
+            let chain_index = index.index(chains.len());
+            let operation = application.operation(action, chain_index, &chains);
+            let result = chains[chain_index]
+                .try_add_block(|block| {
+                    block.with_operation(application_id, &operation);
+                })
+                .await;
+            let outcome = if result.is_err() { " (rejected)" } else { "" };
+            writeln!(log, "  chain {chain_index}: {operation:?}{outcome}")
+                .expect("Writing to a string should not fail");
+            let delivered = Self::deliver_messages(&chains).await;
+            let checked = match delivered {
+                Ok(()) => application.check_invariants(&chains, application_id).await,
+                Err(error) => Err(error),
+            };
+            checked.map_err(|error| {
+                format!("Invariant violated: {error}\nafter the operations:\n{log}")
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Receives the pending messages on each of the `chains`, until no messages are left.
+    async fn deliver_messages(chains: &[ActiveChain]) -> Result<(), String> {
+        loop {
+            let mut delivered = false;
+            for (chain_index, chain) in chains.iter().enumerate() {
+                let bundles = chain.pending_message_bundles().await;
+                if bundles.is_empty() {
+                    continue;
+                }
+                chain
+                    .try_add_block(|block| {
+                        block.with_incoming_bundles(bundles);
+                    })
+                    .await
+                    .map_err(|error| {
+                        format!("chain {chain_index} failed to receive its messages: {error}")
+                    })?;
+                delivered = true;
+            }
+            if !delivered {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -14,22 +14,47 @@
 mod block;
 #[cfg(with_integration_testing)]
 mod chain;
+#[cfg(with_integration_testing)]
+mod fuzzing;
 mod mock_stubs;
 #[cfg(with_integration_testing)]
+mod scenario;
+#[cfg(with_integration_testing)]
 mod validator;
 
 #[cfg(with_integration_testing)]
 pub use {
     linera_chain::data_types::{Medium, MessageAction},
-    linera_execution::QueryOutcome,
+    linera_execution::{system::Recipient, QueryOutcome},
+    proptest,
 };
 
 #[cfg(with_testing)]
 pub use self::mock_stubs::*;
 #[cfg(with_integration_testing)]
-pub use self::{block::BlockBuilder, chain::ActiveChain, validator::TestValidator};
+pub use self::{
+    block::BlockBuilder,
+    chain::ActiveChain,
+    fuzzing::{FuzzedApplication, OperationFuzzer, FUZZING_SEED_VARIABLE},
+    scenario::Scenario,
+    validator::TestValidator,
+};
 use crate::{Contract, ContractRuntime, Service, ServiceRuntime};
 
+/// Prints the logs of the applications executed in the tests, as selected by the `RUST_LOG`
+/// environment variable.
+///
+/// The logs of a single application can be selected with a filter such as
+/// `RUST_LOG='linera_execution[application{id=<ID>}]=debug'`. Only the first call has an
+/// effect, so every test can call this function.
+#[cfg(with_integration_testing)]
+pub fn init_application_logs() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_test_writer()
+        .try_init();
+}
+
 /// Creates a [`ContractRuntime`] to use in tests.
 pub fn test_contract_runtime<Application: Contract>() -> ContractRuntime<Application> {
     ContractRuntime::new()
@@ -0,0 +1,216 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A builder of multi-chain test scenarios.
+//!
+//! A [`Scenario`] names the microchains of a test, so that blocks can be added to them and
+//! messages delivered between them without passing [`ActiveChain`] handles around. It records
+//! every message bundle it delivers, so that tests can check the order in which each chain
+//! received them.
+
+use std::collections::BTreeMap;
+
+use linera_base::identifiers::{ApplicationId, ChainId};
+use linera_chain::{data_types::IncomingBundle, types::ConfirmedBlockCertificate};
+
+use super::{ActiveChain, BlockBuilder, TestValidator};
+
+/// A set of named microchains on a [`TestValidator`], with the log of the message bundles
+/// delivered between them.
+///
+/// ```rust
+/// # use linera_sdk::{base::{Amount, ChainId}, test::*};
+/// # tokio_test::block_on(async {
+/// let mut scenario = Scenario::new().await.with_chains(["alice", "bob"]).await;
+/// let admin = scenario.validator().get_chain(&ChainId::root(0));
+/// scenario.add_chain("admin", admin);
+/// let alice = Recipient::chain(scenario.chain_id("alice"));
+/// let bob = Recipient::chain(scenario.chain_id("bob"));
+///
+/// scenario
+///     .add_block("admin", |block| {
+///         block.with_native_token_transfer(None, alice, Amount::from_tokens(2));
+///     })
+///     .await;
+/// scenario.deliver("alice").await;
+///
+/// scenario
+///     .add_block("alice", |block| {
+///         block.with_native_token_transfer(None, bob, Amount::ONE);
+///     })
+///     .await;
+/// scenario.deliver("bob").await;
+/// scenario
+///     .add_block("admin", |block| {
+///         block.with_native_token_transfer(None, bob, Amount::ONE);
+///     })
+///     .await;
+/// scenario.deliver_all().await;
+///
+/// scenario.assert_received_in_order("bob", ["alice", "admin"]);
+/// # });
+/// ```
+pub struct Scenario {
+    validator: TestValidator,
+    chains: BTreeMap<String, ActiveChain>,
+    order: Vec<String>,
+    deliveries: Vec<(ChainId, IncomingBundle)>,
+}
+
+impl Scenario {
+    /// Creates a new [`Scenario`] on a new [`TestValidator`], without any chains.
+    pub async fn new() -> Self {
+        Scenario::with_validator(TestValidator::new().await)
+    }
+
+    /// Creates a new [`Scenario`] on an existing `validator`, without any chains.
+    pub fn with_validator(validator: TestValidator) -> Self {
+        Scenario {
+            validator,
+            chains: BTreeMap::new(),
+            order: Vec::new(),
+            deliveries: Vec::new(),
+        }
+    }
+
+    /// Creates a new microchain for each of the `names`.
+    pub async fn with_chains(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        for name in names {
+            let chain = self.validator.new_chain().await;
+            self.add_chain(name, chain);
+        }
+        self
+    }
+
+    /// Adds an existing microchain to this scenario, with the given `name`.
+    pub fn add_chain(&mut self, name: impl Into<String>, chain: ActiveChain) {
+        let name = name.into();
+        let previous = self.chains.insert(name.clone(), chain);
+        assert!(previous.is_none(), "Chain {name:?} is already declared");
+        self.order.push(name);
+    }
+
+    /// Returns the [`TestValidator`] running the microchains of this scenario.
+    pub fn validator(&self) -> &TestValidator {
+        &self.validator
+    }
+
+    /// Returns the microchain with the given `name`.
+    pub fn chain(&self, name: &str) -> &ActiveChain {
+        self.chains
+            .get(name)
+            .unwrap_or_else(|| panic!("Unknown chain {name:?}"))
+    }
+
+    /// Returns the microchain with the given `name`, e.g. to change its key pair.
+    pub fn chain_mut(&mut self, name: &str) -> &mut ActiveChain {
+        self.chains
+            .get_mut(name)
+            .unwrap_or_else(|| panic!("Unknown chain {name:?}"))
+    }
+
+    /// Returns the ID of the microchain with the given `name`.
+    pub fn chain_id(&self, name: &str) -> ChainId {
+        self.chain(name).id()
+    }
+
+    /// Returns the name of the microchain with the given ID, if it is part of this scenario.
+    pub fn chain_name(&self, chain_id: ChainId) -> Option<&str> {
+        self.chains
+            .iter()
+            .find(|(_, chain)| chain.id() == chain_id)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Adds a block to the microchain with the given `name`.
+    ///
+    /// The `block_builder` parameter is a closure that should use the [`BlockBuilder`]
+    /// parameter to provide the block's contents.
+    pub async fn add_block(
+        &self,
+        name: &str,
+        block_builder: impl FnOnce(&mut BlockBuilder),
+    ) -> ConfirmedBlockCertificate {
+        self.chain(name).add_block(block_builder).await
+    }
+
+    /// Registers the application on each of the microchains with the given `names`.
+    pub async fn register_application<Abi>(
+        &self,
+        application_id: ApplicationId<Abi>,
+        names: impl IntoIterator<Item = &str>,
+    ) {
+        for name in names {
+            self.chain(name).register_application(application_id).await;
+        }
+    }
+
+    /// Receives all the message bundles waiting in the inboxes of the microchain with the
+    /// given `name`, in a new block, and returns how many there were.
+    ///
+    /// No block is added if there are no messages to receive.
+    pub async fn deliver(&mut self, name: &str) -> usize {
+        let chain = self.chain(name).clone();
+        let bundles = chain.pending_message_bundles().await;
+        if bundles.is_empty() {
+            return 0;
+        }
+        chain
+            .add_block(|block| {
+                block.with_incoming_bundles(bundles.clone());
+            })
+            .await;
+        let count = bundles.len();
+        self.deliveries
+            .extend(bundles.into_iter().map(|bundle| (chain.id(), bundle)));
+        count
+    }
+
+    /// Delivers messages to every microchain, in the order they were declared, until no
+    /// messages are left, including the ones sent while receiving other messages.
+    pub async fn deliver_all(&mut self) {
+        loop {
+            let mut count = 0;
+            for name in self.order.clone() {
+                count += self.deliver(&name).await;
+            }
+            if count == 0 {
+                break;
+            }
+        }
+    }
+
+    /// Returns the message bundles delivered to the microchain with the given `name` by this
+    /// scenario, in the order they were received.
+    pub fn received_bundles(&self, name: &str) -> Vec<&IncomingBundle> {
+        let chain_id = self.chain_id(name);
+        self.deliveries
+            .iter()
+            .filter(|(recipient, _)| *recipient == chain_id)
+            .map(|(_, bundle)| bundle)
+            .collect()
+    }
+
+    /// Asserts that the message bundles delivered to the microchain with the given `name`
+    /// were sent by the microchains with the given names, in that order.
+    pub fn assert_received_in_order<'a>(
+        &self,
+        name: &str,
+        senders: impl IntoIterator<Item = &'a str>,
+    ) {
+        let received = self
+            .received_bundles(name)
+            .into_iter()
+            .map(|bundle| {
+                let sender = bundle.origin.sender;
+                self.chain_name(sender)
+                    .map_or_else(|| sender.to_string(), str::to_owned)
+            })
+            .collect::<Vec<_>>();
+        let expected = senders.into_iter().map(str::to_owned).collect::<Vec<_>>();
+        assert_eq!(
+            received, expected,
+            "Chain {name:?} did not receive message bundles in the expected order"
+        );
+    }
+}
@@ -12,7 +12,7 @@ use dashmap::DashMap;
 use futures::FutureExt as _;
 use linera_base::{
     crypto::KeyPair,
-    data_types::{Amount, ApplicationPermissions, Timestamp},
+    data_types::{Amount, ApplicationPermissions, TimeDelta, Timestamp},
     identifiers::{ApplicationId, BytecodeId, ChainDescription, ChainId, MessageId, Owner},
     ownership::ChainOwnership,
 };
@@ -154,6 +154,45 @@ impl TestValidator {
         &self.clock
     }
 
+    /// Returns the current time of the network.
+    ///
+    /// The time starts at the Unix epoch, and only changes when [`TestValidator::set_time`] or
+    /// [`TestValidator::advance_time`] is called. New blocks use it as their timestamp, unless
+    /// configured otherwise with [`BlockBuilder::with_timestamp`][super::BlockBuilder::with_timestamp].
+    pub fn current_time(&self) -> Timestamp {
+        self.clock.current_time()
+    }
+
+    /// Sets the current time of the network.
+    ///
+    /// The time should not be set to before the timestamp of existing blocks, because a block
+    /// cannot be older than the previous block of its chain.
+    pub fn set_time(&self, time: Timestamp) {
+        self.clock.set(time);
+    }
+
+    /// Advances the current time of the network by `delta`, without waiting.
+    ///
+    /// ```rust
+    /// # use linera_sdk::{base::TimeDelta, test::*};
+    /// # tokio_test::block_on(async {
+    /// let validator = TestValidator::new().await;
+    /// let chain = validator.new_chain().await;
+    /// let start = validator.current_time();
+    ///
+    /// validator.advance_time(TimeDelta::from_secs(60));
+    /// let certificate = chain.add_block(|_| {}).await;
+    ///
+    /// assert_eq!(
+    ///     certificate.inner().block().header.timestamp,
+    ///     start.saturating_add(TimeDelta::from_secs(60))
+    /// );
+    /// # });
+    /// ```
+    pub fn advance_time(&self, delta: TimeDelta) {
+        self.clock.add(delta);
+    }
+
     /// Returns the keys this test validator uses for signing certificates.
     pub fn key_pair(&self) -> &KeyPair {
         &self.key_pair
@@ -0,0 +1,210 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A wrapper around the root view of an application's state, to avoid losing the changes
+//! that were not saved.
+
+use std::ops::{Deref, DerefMut};
+
+use linera_views::views::{RootView, View, ViewError};
+
+use super::ViewStorageContext;
+use crate::util::BlockingWait as _;
+
+/// What a [`ViewGuard`] does when it is dropped while its view has unsaved changes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OnUnsavedDrop {
+    /// Saves the changes. If they can't be saved, panics.
+    #[default]
+    Save,
+    /// Discards the changes, and logs a warning.
+    Warn,
+    /// Panics, e.g. to find the code paths that don't save the state in tests.
+    Panic,
+}
+
+/// A root view that is not dropped with unsaved changes, e.g. the state of a
+/// [`Contract`](crate::Contract).
+///
+/// By default, the changes are saved when the guard is dropped, so the state of a contract
+/// is saved when the contract is dropped at the end of [`Contract::store`](crate::Contract::store).
+///
+/// ```ignore
+/// pub struct CounterContract {
+///     state: ViewGuard<CounterState>,
+///     runtime: ContractRuntime<Self>,
+/// }
+///
+/// impl Contract for CounterContract {
+///     async fn load(runtime: ContractRuntime<Self>) -> Self {
+///         let state = ViewGuard::load(runtime.root_view_storage_context())
+///             .await
+///             .expect("Failed to load state");
+///         CounterContract { state, runtime }
+///     }
+///
+///     async fn store(self) {}
+///
+///     // ...
+/// }
+/// ```
+pub struct ViewGuard<V>
+where
+    V: RootView<ViewStorageContext>,
+{
+    view: V,
+    on_unsaved_drop: OnUnsavedDrop,
+}
+
+impl<V> ViewGuard<V>
+where
+    V: RootView<ViewStorageContext>,
+{
+    /// Guards the `view`, saving its changes if it is dropped before they were saved.
+    pub fn new(view: V) -> Self {
+        ViewGuard {
+            view,
+            on_unsaved_drop: OnUnsavedDrop::default(),
+        }
+    }
+
+    /// Loads the view stored in the `context`, and guards it.
+    pub async fn load(context: ViewStorageContext) -> Result<Self, ViewError> {
+        Ok(Self::new(V::load(context).await?))
+    }
+
+    /// Sets what to do if the guard is dropped while the view has unsaved changes.
+    pub fn with_on_unsaved_drop(mut self, on_unsaved_drop: OnUnsavedDrop) -> Self {
+        self.on_unsaved_drop = on_unsaved_drop;
+        self
+    }
+
+    /// Saves the changes of the view.
+    pub async fn save(&mut self) -> Result<(), ViewError> {
+        self.view.save().await
+    }
+
+    /// Returns whether the view has changes that were not saved.
+    pub async fn has_unsaved_changes(&self) -> bool {
+        self.view.has_pending_changes().await
+    }
+
+    /// Discards the changes of the view that were not saved.
+    pub fn rollback(&mut self) {
+        self.view.rollback();
+    }
+}
+
+impl<V> Deref for ViewGuard<V>
+where
+    V: RootView<ViewStorageContext>,
+{
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        &self.view
+    }
+}
+
+impl<V> DerefMut for ViewGuard<V>
+where
+    V: RootView<ViewStorageContext>,
+{
+    fn deref_mut(&mut self) -> &mut V {
+        &mut self.view
+    }
+}
+
+impl<V> Drop for ViewGuard<V>
+where
+    V: RootView<ViewStorageContext>,
+{
+    fn drop(&mut self) {
+        // Don't make a panic worse, and don't save the changes of a failing transaction.
+        if std::thread::panicking() || !self.view.has_pending_changes().blocking_wait() {
+            return;
+        }
+        let type_name = std::any::type_name::<V>();
+        match self.on_unsaved_drop {
+            OnUnsavedDrop::Save => {
+                if let Err(error) = self.view.save().blocking_wait() {
+                    panic!("Failed to save the unsaved changes of a {type_name}: {error}");
+                }
+            }
+            OnUnsavedDrop::Warn => {
+                log::warn!("A {type_name} was dropped with unsaved changes");
+            }
+            OnUnsavedDrop::Panic => {
+                panic!("A {type_name} was dropped with unsaved changes");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_views::views::{RootView, View};
+
+    use super::{OnUnsavedDrop, ViewGuard};
+    use crate::views::{KeyValueStore, RegisterView, ViewStorageContext};
+
+    #[derive(RootView)]
+    #[view(context = "ViewStorageContext")]
+    struct State {
+        value: RegisterView<u64>,
+    }
+
+    fn context() -> ViewStorageContext {
+        ViewStorageContext::new_unsafe(KeyValueStore::mock().to_mut(), Vec::new(), ())
+    }
+
+    /// Tests that the changes are saved when the guard is dropped.
+    #[tokio::test]
+    async fn changes_are_saved_on_drop() -> anyhow::Result<()> {
+        let context = context();
+        let mut guard = ViewGuard::<State>::load(context.clone()).await?;
+        guard.value.set(3);
+        assert!(guard.has_unsaved_changes().await);
+        drop(guard);
+
+        let state = State::load(context).await?;
+        assert_eq!(*state.value.get(), 3);
+        Ok(())
+    }
+
+    /// Tests that the changes are discarded with a warning if requested.
+    #[tokio::test]
+    async fn changes_are_discarded_on_drop() -> anyhow::Result<()> {
+        let context = context();
+        let mut guard = ViewGuard::<State>::load(context.clone())
+            .await?
+            .with_on_unsaved_drop(OnUnsavedDrop::Warn);
+        guard.value.set(3);
+        drop(guard);
+
+        let state = State::load(context).await?;
+        assert_eq!(*state.value.get(), 0);
+        Ok(())
+    }
+
+    /// Tests that dropping unsaved changes panics if requested, but not after a save.
+    #[tokio::test]
+    async fn unsaved_changes_panic_on_drop() -> anyhow::Result<()> {
+        let context = context();
+        let mut guard = ViewGuard::<State>::load(context.clone())
+            .await?
+            .with_on_unsaved_drop(OnUnsavedDrop::Panic);
+        guard.value.set(3);
+        guard.save().await?;
+        drop(guard);
+
+        let mut guard = ViewGuard::<State>::load(context)
+            .await?
+            .with_on_unsaved_drop(OnUnsavedDrop::Panic);
+        assert_eq!(*guard.value.get(), 3);
+        guard.value.set(4);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || drop(guard)));
+        assert!(result.is_err());
+        Ok(())
+    }
+}
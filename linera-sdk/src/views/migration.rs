@@ -0,0 +1,125 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers to rewrite the state of an application after its bytecode was upgraded.
+
+use linera_views::{
+    batch::Batch,
+    context::Context as _,
+    views::{View, ViewError},
+};
+
+use super::ViewStorageContext;
+
+/// Rewrites the state of an application from an old layout to a new one, e.g. in
+/// [`Contract::migrate`](crate::Contract::migrate).
+///
+/// The state stored with the `Old` layout is loaded into [`StateMigration::old`], to be copied
+/// into [`StateMigration::new`], which starts empty. Nothing is written to storage until
+/// [`StateMigration::save`] replaces the old state with the new one.
+///
+/// ```ignore
+/// async fn migrate(runtime: &mut ContractRuntime<Self>, old_version: u32) {
+///     if old_version == 0 {
+///         let mut migration = StateMigration::<CounterStateV0, CounterState>::load(
+///             runtime.root_view_storage_context(),
+///         )
+///         .await
+///         .expect("Failed to load the old state");
+///         let value = *migration.old.value.get();
+///         migration.new.value.set(u128::from(value));
+///         migration.save().await.expect("Failed to save the migrated state");
+///     }
+/// }
+/// ```
+pub struct StateMigration<Old, New> {
+    /// The state with its old layout, as stored before the migration.
+    pub old: Old,
+    /// The state with its new layout, initially empty.
+    pub new: New,
+}
+
+impl<Old, New> StateMigration<Old, New>
+where
+    Old: View<ViewStorageContext>,
+    New: View<ViewStorageContext>,
+{
+    /// Loads the state stored in the `context` with the `Old` layout.
+    pub async fn load(context: ViewStorageContext) -> Result<Self, ViewError> {
+        let old = Old::load(context.clone()).await?;
+        let new = New::new(context)?;
+        Ok(StateMigration { old, new })
+    }
+
+    /// Deletes the state stored with the `Old` layout, and saves the state with the `New`
+    /// layout in its place.
+    pub async fn save(mut self) -> Result<(), ViewError> {
+        let mut batch = Batch::new();
+        self.old.clear();
+        self.old.flush(&mut batch)?;
+        self.new.flush(&mut batch)?;
+        self.new.context().write_batch(batch).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_views::views::{RootView, View};
+
+    use super::StateMigration;
+    use crate::views::{KeyValueStore, MapView, RegisterView, ViewStorageContext};
+
+    /// The layout of the state before the migration.
+    #[derive(RootView)]
+    #[view(context = "ViewStorageContext")]
+    struct OldState {
+        counters: MapView<String, u64>,
+        total: RegisterView<u64>,
+        label: RegisterView<String>,
+    }
+
+    /// The layout of the state after the migration, without a label.
+    #[derive(RootView)]
+    #[view(context = "ViewStorageContext")]
+    struct NewState {
+        total: RegisterView<u128>,
+        counters: MapView<String, u128>,
+    }
+
+    /// Tests that the old state is replaced by the migrated one, without leaving keys behind.
+    #[tokio::test]
+    async fn state_is_rewritten_with_the_new_layout() -> anyhow::Result<()> {
+        let context =
+            ViewStorageContext::new_unsafe(KeyValueStore::mock().to_mut(), Vec::new(), ());
+        let mut old = OldState::load(context.clone()).await?;
+        old.counters.insert("a", 1)?;
+        old.counters.insert("b", 2)?;
+        old.total.set(3);
+        old.label.set("counters".to_owned());
+        old.save().await?;
+
+        let mut migration = StateMigration::<OldState, NewState>::load(context.clone()).await?;
+        assert_eq!(*migration.old.total.get(), 3);
+        assert!(migration.new.counters.indices().await?.is_empty());
+        migration
+            .new
+            .total
+            .set(u128::from(*migration.old.total.get()));
+        for name in migration.old.counters.indices().await? {
+            let value = migration.old.counters.get(&name).await?.unwrap_or_default();
+            migration.new.counters.insert(&name, u128::from(value))?;
+        }
+        migration.save().await?;
+
+        let new = NewState::load(context.clone()).await?;
+        assert_eq!(*new.total.get(), 3);
+        assert_eq!(new.counters.indices().await?, ["a", "b"]);
+        assert_eq!(new.counters.get("b").await?, Some(2));
+
+        // The label of the old layout was deleted.
+        let old = OldState::load(context).await?;
+        assert!(old.label.get().is_empty());
+        Ok(())
+    }
+}
@@ -5,6 +5,8 @@
 
 mod aliases;
 mod conversions_to_wit;
+mod guard;
+mod migration;
 #[cfg(with_testing)]
 mod mock_key_value_store;
 mod system_api;
@@ -12,7 +14,7 @@ mod system_api;
 pub use linera_views::{
     self,
     common::CustomSerialize,
-    views::{RootView, View, ViewError},
+    views::{GraphQLView, RootView, View, ViewError},
 };
 
 pub use self::{
@@ -21,5 +23,7 @@ pub use self::{
         CustomMapView, CustomSetView, LogView, MapView, QueueView, ReadGuardedView, RegisterView,
         SetView,
     },
+    guard::{OnUnsavedDrop, ViewGuard},
+    migration::StateMigration,
     system_api::{KeyValueStore, ViewStorageContext},
 };
@@ -0,0 +1,494 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Size analysis and optimization of the WebAssembly bytecodes of applications.
+//!
+//! Bytecodes are limited in size by the network, so [`optimize`] runs
+//! [`wasm-opt`](https://github.com/WebAssembly/binaryen) on them when it is installed, and
+//! strips the custom sections (debug information, symbol names, producers) that are not needed
+//! to execute them. The `linera:` custom sections, e.g. the version of a contract's ABI, are
+//! read by the validators and kept. A [`SizeReport`] shows where the bytes of a bytecode come
+//! from, by section, by crate and by function.
+
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use wasmparser::{BinaryReader, BinaryReaderError, Parser, Payload, TypeRef};
+
+/// The number of crates and functions shown when displaying a [`SizeReport`].
+const TOP_ENTRIES: usize = 10;
+
+/// The name given to the functions whose crate can't be determined.
+const UNKNOWN_CRATE: &str = "[unknown]";
+
+/// The prefix of the names of the custom sections read by the validators, which are kept by
+/// [`strip_custom_sections`].
+const LINERA_SECTION_PREFIX: &str = "linera:";
+
+/// Where the bytes of a WebAssembly bytecode come from.
+#[derive(Clone, Debug)]
+pub struct SizeReport {
+    total: usize,
+    sections: Vec<(String, usize)>,
+    functions: Vec<(String, usize)>,
+}
+
+impl SizeReport {
+    /// Analyzes a WebAssembly `bytecode`.
+    ///
+    /// Function names are read from the `name` custom section, which is only present if the
+    /// bytecode wasn't stripped.
+    pub fn analyze(bytecode: &[u8]) -> Result<Self, WasmSizeError> {
+        let mut sections = Vec::new();
+        let mut imported_functions = 0;
+        let mut function_sizes = Vec::new();
+        let mut names = HashMap::new();
+
+        for payload in Parser::new(0).parse_all(bytecode) {
+            let payload = payload?;
+            match &payload {
+                Payload::ImportSection(reader) => {
+                    for import in reader.clone() {
+                        if matches!(import?.ty, TypeRef::Func(_)) {
+                            imported_functions += 1;
+                        }
+                    }
+                }
+                Payload::CodeSectionEntry(body) => function_sizes.push(body.range().len()),
+                Payload::CustomSection(reader) => {
+                    if reader.name() == "name" {
+                        names = function_names(reader.data())?;
+                    }
+                    sections.push((format!("custom `{}`", reader.name()), reader.range().len()));
+                    continue;
+                }
+                _ => {}
+            }
+            if let Some((id, range)) = payload.as_section() {
+                sections.push((section_name(id).to_owned(), range.len()));
+            }
+        }
+
+        let mut functions = function_sizes
+            .into_iter()
+            .enumerate()
+            .map(|(position, size)| {
+                let index = imported_functions + position as u32;
+                let name = names
+                    .remove(&index)
+                    .unwrap_or_else(|| format!("function[{index}]"));
+                (name, size)
+            })
+            .collect::<Vec<_>>();
+        sort_by_size(&mut functions);
+
+        Ok(SizeReport {
+            total: bytecode.len(),
+            sections,
+            functions,
+        })
+    }
+
+    /// Returns the size of the bytecode, in bytes.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns the sections of the bytecode with their sizes, in the order in which they
+    /// appear.
+    pub fn sections(&self) -> &[(String, usize)] {
+        &self.sections
+    }
+
+    /// Returns the demangled names of the functions defined by the bytecode with the sizes of
+    /// their bodies, largest first.
+    pub fn functions(&self) -> &[(String, usize)] {
+        &self.functions
+    }
+
+    /// Returns the crates the functions of the bytecode belong to with the total size of their
+    /// bodies, largest first.
+    pub fn crates(&self) -> Vec<(String, usize)> {
+        let mut sizes = HashMap::<&str, usize>::new();
+        for (function, size) in &self.functions {
+            *sizes.entry(crate_of(function)).or_default() += size;
+        }
+        let mut crates = sizes
+            .into_iter()
+            .map(|(name, size)| (name.to_owned(), size))
+            .collect::<Vec<_>>();
+        sort_by_size(&mut crates);
+        crates
+    }
+
+    fn write_entries(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        title: &str,
+        entries: &[(String, usize)],
+    ) -> fmt::Result {
+        writeln!(f, "{title}:")?;
+        for (name, size) in entries {
+            let percentage = 100.0 * *size as f64 / self.total.max(1) as f64;
+            writeln!(f, "  {size:>10} bytes {percentage:>5.1}%  {name}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total size: {} bytes", self.total)?;
+        self.write_entries(f, "Sections", &self.sections)?;
+        if self.functions.is_empty() {
+            return Ok(());
+        }
+        let crates = self.crates();
+        self.write_entries(
+            f,
+            "Largest crates",
+            &crates[..crates.len().min(TOP_ENTRIES)],
+        )?;
+        self.write_entries(
+            f,
+            "Largest functions",
+            &self.functions[..self.functions.len().min(TOP_ENTRIES)],
+        )
+    }
+}
+
+/// The result of [`optimize`].
+#[derive(Clone, Debug)]
+pub struct Optimized {
+    /// The analysis of the bytecode before it was optimized.
+    pub original: SizeReport,
+    /// The size of the optimized bytecode, in bytes.
+    pub size: usize,
+    /// Whether `wasm-opt` was found and run. Otherwise the custom sections were only stripped.
+    pub used_wasm_opt: bool,
+}
+
+/// Optimizes the bytecode in the `input` file for size and writes it to the `output` file.
+///
+/// Runs `wasm-opt` if it is installed, then removes the custom sections that are not read by
+/// the validators.
+pub fn optimize(input: &Path, output: &Path) -> Result<Optimized, WasmSizeError> {
+    let bytecode = fs::read(input).map_err(|error| WasmSizeError::io(input, error))?;
+    let original = SizeReport::analyze(&bytecode)?;
+
+    let temporary = temporary_path(output);
+    let result = optimize_into(input, bytecode, &temporary);
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    let (size, used_wasm_opt) = result?;
+    fs::rename(&temporary, output).map_err(|error| WasmSizeError::io(output, error))?;
+
+    Ok(Optimized {
+        original,
+        size,
+        used_wasm_opt,
+    })
+}
+
+/// Writes the optimized `bytecode` read from the `input` file to the `output` file, and
+/// returns its size and whether `wasm-opt` was used.
+fn optimize_into(
+    input: &Path,
+    bytecode: Vec<u8>,
+    output: &Path,
+) -> Result<(usize, bool), WasmSizeError> {
+    let wasm_opt = Command::new("wasm-opt")
+        .args([
+            "-Oz",
+            "--strip-debug",
+            "--strip-producers",
+            "--strip-target-features",
+        ])
+        .arg(input)
+        .arg("-o")
+        .arg(output)
+        .output();
+    let (bytecode, used_wasm_opt) = match wasm_opt {
+        Ok(result) if result.status.success() => (
+            fs::read(output).map_err(|error| WasmSizeError::io(output, error))?,
+            true,
+        ),
+        Ok(result) => {
+            return Err(WasmSizeError::WasmOpt {
+                path: input.to_owned(),
+                stderr: String::from_utf8_lossy(&result.stderr).into_owned(),
+            })
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => (bytecode, false),
+        Err(error) => return Err(WasmSizeError::io(Path::new("wasm-opt"), error)),
+    };
+    let stripped = strip_custom_sections(&bytecode)?;
+    fs::write(output, &stripped).map_err(|error| WasmSizeError::io(output, error))?;
+    Ok((stripped.len(), used_wasm_opt))
+}
+
+/// Returns a copy of the `bytecode` without its custom sections, except the `linera:` ones.
+pub fn strip_custom_sections(bytecode: &[u8]) -> Result<Vec<u8>, WasmSizeError> {
+    // Validates the header.
+    Parser::new(0).parse(bytecode, true)?;
+    let header_length = 8;
+    let mut stripped = bytecode[..header_length].to_vec();
+    let mut reader = BinaryReader::new_with_offset(&bytecode[header_length..], header_length);
+    while !reader.eof() {
+        let start = reader.original_position();
+        let id = reader.read_u8()?;
+        let size = reader.read_var_u32()?;
+        let contents = reader.read_bytes(size as usize)?;
+        let keep = id != 0 || {
+            let name = BinaryReader::new(contents).read_string()?;
+            name.starts_with(LINERA_SECTION_PREFIX)
+        };
+        if keep {
+            stripped.extend_from_slice(&bytecode[start..reader.original_position()]);
+        }
+    }
+    Ok(stripped)
+}
+
+/// Reads the function names of a `name` custom section.
+fn function_names(data: &[u8]) -> Result<HashMap<u32, String>, BinaryReaderError> {
+    const FUNCTION_NAMES: u8 = 1;
+    let mut names = HashMap::new();
+    let mut reader = BinaryReader::new(data);
+    while !reader.eof() {
+        let id = reader.read_u8()?;
+        let size = reader.read_var_u32()?;
+        let mut subsection = BinaryReader::new(reader.read_bytes(size as usize)?);
+        if id != FUNCTION_NAMES {
+            continue;
+        }
+        for _ in 0..subsection.read_var_u32()? {
+            let index = subsection.read_var_u32()?;
+            let name = subsection.read_string()?;
+            names.insert(index, format!("{:#}", rustc_demangle::demangle(name)));
+        }
+    }
+    Ok(names)
+}
+
+/// Returns the crate a demangled function belongs to: the first segment of its path.
+fn crate_of(function: &str) -> &str {
+    let path = function.trim_start_matches(['<', '&', '*']);
+    match path.split_once("::") {
+        Some((name, _))
+            if !name.is_empty()
+                && name
+                    .chars()
+                    .all(|character| character.is_alphanumeric() || character == '_') =>
+        {
+            name
+        }
+        _ => UNKNOWN_CRATE,
+    }
+}
+
+/// Returns the name of a standard section.
+fn section_name(id: u8) -> &'static str {
+    match id {
+        0 => "custom",
+        1 => "type",
+        2 => "import",
+        3 => "function",
+        4 => "table",
+        5 => "memory",
+        6 => "global",
+        7 => "export",
+        8 => "start",
+        9 => "element",
+        10 => "code",
+        11 => "data",
+        12 => "data count",
+        13 => "tag",
+        _ => "unknown",
+    }
+}
+
+fn sort_by_size(entries: &mut [(String, usize)]) {
+    entries.sort_by(|(name1, size1), (name2, size2)| size2.cmp(size1).then(name1.cmp(name2)));
+}
+
+/// Returns a path next to `output` that no other optimization writes to.
+fn temporary_path(output: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let mut name = output.file_name().unwrap_or_default().to_owned();
+    name.push(format!(".{}.{counter}.tmp", std::process::id()));
+    output.with_file_name(name)
+}
+
+/// Errors that can occur when analyzing or optimizing a bytecode.
+#[derive(Debug, thiserror::Error)]
+pub enum WasmSizeError {
+    /// A file could not be read or written.
+    #[error("Failed to access {}: {error}", path.display())]
+    Io {
+        /// The file.
+        path: PathBuf,
+        /// The underlying error.
+        #[source]
+        error: io::Error,
+    },
+
+    /// The bytecode is not valid WebAssembly.
+    #[error("Invalid WebAssembly bytecode: {0}")]
+    Invalid(#[from] BinaryReaderError),
+
+    /// `wasm-opt` failed.
+    #[error("`wasm-opt` failed to optimize {}: {stderr}", path.display())]
+    WasmOpt {
+        /// The bytecode being optimized.
+        path: PathBuf,
+        /// The error output of `wasm-opt`.
+        stderr: String,
+    },
+}
+
+impl WasmSizeError {
+    fn io(path: &Path, error: io::Error) -> Self {
+        WasmSizeError::Io {
+            path: path.to_owned(),
+            error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use wasmparser::{Parser, Payload};
+
+    use super::{optimize, strip_custom_sections, SizeReport};
+
+    /// Encodes a section whose contents are shorter than 128 bytes.
+    fn section(id: u8, contents: &[u8]) -> Vec<u8> {
+        let mut section = vec![id, contents.len() as u8];
+        section.extend_from_slice(contents);
+        section
+    }
+
+    /// Encodes a custom section.
+    fn custom_section(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut contents = vec![name.len() as u8];
+        contents.extend_from_slice(name.as_bytes());
+        contents.extend_from_slice(data);
+        section(0, &contents)
+    }
+
+    /// Returns a module importing one function and defining two, with a `name` section naming
+    /// the last one (with index 2) with a mangled Rust symbol, and a `producers` section.
+    fn module() -> Vec<u8> {
+        let symbol = "_ZN5serde3foo17h0123456789abcdefE";
+        let mut function_names = vec![1, 2, symbol.len() as u8];
+        function_names.extend_from_slice(symbol.as_bytes());
+        let mut name_section = vec![1, function_names.len() as u8];
+        name_section.extend_from_slice(&function_names);
+
+        let mut module = b"\0asm\x01\0\0\0".to_vec();
+        // One type: `() -> ()`.
+        module.extend(section(1, &[1, 0x60, 0, 0]));
+        // One imported function: `env.f`.
+        module.extend(section(2, &[1, 3, b'e', b'n', b'v', 1, b'f', 0, 0]));
+        module.extend(section(3, &[2, 0, 0]));
+        // Two functions: an empty one, and one with a `nop`.
+        module.extend(section(10, &[2, 2, 0, 0x0b, 3, 0, 0x01, 0x0b]));
+        module.extend(custom_section("name", &name_section));
+        module.extend(custom_section("producers", &[0]));
+        module
+    }
+
+    /// Tests that sections, functions and crates are reported with their sizes.
+    #[test]
+    fn analyzes_sizes() {
+        let bytecode = module();
+        let report = SizeReport::analyze(&bytecode).unwrap();
+
+        assert_eq!(report.total(), bytecode.len());
+        let sections = report
+            .sections()
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sections,
+            [
+                "type",
+                "import",
+                "function",
+                "code",
+                "custom `name`",
+                "custom `producers`"
+            ]
+        );
+        assert_eq!(
+            report.functions(),
+            [("serde::foo".to_owned(), 3), ("function[1]".to_owned(), 2)]
+        );
+        assert_eq!(
+            report.crates(),
+            [("serde".to_owned(), 3), ("[unknown]".to_owned(), 2)]
+        );
+    }
+
+    /// Tests that stripping removes the custom sections only.
+    #[test]
+    fn strips_custom_sections() {
+        let bytecode = module();
+        let stripped = strip_custom_sections(&bytecode).unwrap();
+
+        let report = SizeReport::analyze(&stripped).unwrap();
+        assert!(report
+            .sections()
+            .iter()
+            .all(|(name, _)| !name.starts_with("custom")));
+        assert_eq!(report.functions().len(), 2);
+        assert!(bytecode.starts_with(&stripped));
+        assert!(strip_custom_sections(b"not wasm").is_err());
+    }
+
+    /// Returns the data of the custom section `name` of the `bytecode`, if any.
+    fn custom_section_data(bytecode: &[u8], name: &str) -> Option<Vec<u8>> {
+        Parser::new(0)
+            .parse_all(bytecode)
+            .map_while(Result::ok)
+            .find_map(|payload| match payload {
+                Payload::CustomSection(section) if section.name() == name => {
+                    Some(section.data().to_vec())
+                }
+                _ => None,
+            })
+    }
+
+    /// Tests that optimizing a contract keeps the version of its ABI.
+    #[test]
+    fn optimizing_keeps_the_contract_abi_version() {
+        let version = 42_u32.to_le_bytes();
+        let mut bytecode = module();
+        bytecode.extend(custom_section("linera:contract-abi-version", &version));
+
+        let directory = tempfile::tempdir().unwrap();
+        let input = directory.path().join("contract.wasm");
+        let output = directory.path().join("contract_optimized.wasm");
+        fs::write(&input, &bytecode).unwrap();
+        optimize(&input, &output).unwrap();
+        let optimized = fs::read(&output).unwrap();
+
+        assert_eq!(
+            custom_section_data(&optimized, "linera:contract-abi-version"),
+            Some(version.to_vec())
+        );
+        assert_eq!(custom_section_data(&optimized, "name"), None);
+        assert_eq!(custom_section_data(&optimized, "producers"), None);
+    }
+}
@@ -184,6 +184,7 @@ mod from {
                 grant,
                 refund_grant_to,
                 kind,
+                expires_at,
                 index,
                 message,
             } = val;
@@ -192,6 +193,7 @@ mod from {
                 grant,
                 refund_grant_to,
                 kind,
+                expires_at,
                 index: index as u32,
                 message,
             }
@@ -206,6 +208,7 @@ mod from {
                 grant,
                 refund_grant_to,
                 kind,
+                expires_at,
                 message,
             } = val;
             OutgoingMessage {
@@ -214,6 +217,7 @@ mod from {
                 grant,
                 refund_grant_to,
                 kind,
+                expires_at,
                 message,
             }
         }
@@ -228,6 +232,7 @@ mod from {
                 height,
                 timestamp,
                 authenticated_signer,
+                priority_fee,
                 previous_block_hash,
                 state_hash,
                 bundles_hash,
@@ -250,6 +255,7 @@ mod from {
                 height,
                 timestamp,
                 authenticated_signer,
+                priority_fee,
                 previous_block_hash,
                 state_hash,
                 bundles_hash,
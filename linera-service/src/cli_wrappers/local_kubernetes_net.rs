@@ -1,7 +1,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use anyhow::{anyhow, bail, ensure, Result};
 use async_trait::async_trait;
@@ -156,6 +156,43 @@ impl LineraNetConfig for LocalKubernetesNetConfig {
     }
 }
 
+impl LocalKubernetesNetConfig {
+    /// Generates the configuration of the validators and the genesis configuration, and
+    /// writes them to `output_dir` together with the Helm values of each validator and
+    /// the initial wallet, instead of deploying the network.
+    pub async fn generate_manifests(self, output_dir: &Path) -> Result<()> {
+        ensure!(
+            self.num_initial_validators > 0,
+            "There should be at least one initial validator"
+        );
+
+        let mut net = LocalKubernetesNet::new(
+            self.network,
+            self.testing_prng_seed,
+            self.binaries,
+            self.no_build,
+            self.docker_image_name,
+            KubectlInstance::new(Vec::new()),
+            Vec::new(),
+            self.num_initial_validators,
+            self.num_shards,
+        )?;
+
+        let client = net.make_client().await;
+        net.generate_initial_validator_config().await?;
+        client
+            .create_genesis_config(
+                self.num_other_initial_chains,
+                self.initial_amount,
+                self.policy,
+            )
+            .await?;
+        net.write_manifests(output_dir)?;
+        fs_err::copy(client.wallet_path(), output_dir.join("wallet.json"))?;
+        Ok(())
+    }
+}
+
 #[cfg(with_testing)]
 #[async_trait]
 impl LineraNetConfig for SharedLocalKubernetesNetTestingConfig {
@@ -404,6 +441,49 @@ impl LocalKubernetesNet {
         Ok(())
     }
 
+    /// Writes the server configurations and the genesis configuration to the directory
+    /// `working` of `output_dir`, and the Helm values of the validator `i` of the chart
+    /// `kubernetes/linera-validator` to `values_{i}.yaml`.
+    fn write_manifests(&self, output_dir: &Path) -> Result<()> {
+        let working_dir = output_dir.join("working");
+        fs_err::create_dir_all(&working_dir)?;
+        fs_err::copy(
+            self.tmp_dir.path().join("genesis.json"),
+            working_dir.join("genesis.json"),
+        )?;
+
+        for i in 0..self.num_initial_validators {
+            let server_config_filename = format!("server_{i}.json");
+            fs_err::copy(
+                self.tmp_dir.path().join(&server_config_filename),
+                working_dir.join(&server_config_filename),
+            )?;
+
+            let docker_image_name = &self.docker_image_name;
+            let proxy_port = 19100 + i;
+            let num_shards = self.num_shards;
+            let values = format!(
+                r#"# Values for the chart linera-validator of the validator {i}.
+
+lineraImage: {docker_image_name}
+lineraImagePullPolicy: IfNotPresent
+logLevel: "info"
+proxyPort: {proxy_port}
+metricsPort: 21100
+numShards: {num_shards}
+environment: "kind"
+writeToGrafanaCloud: false
+
+validator:
+  serverConfig: working/{server_config_filename}
+  genesisConfig: working/genesis.json
+"#
+            );
+            fs_err::write(output_dir.join(format!("values_{i}.yaml")), values)?;
+        }
+        Ok(())
+    }
+
     async fn run(&mut self) -> Result<()> {
         let github_root = get_github_root().await?;
         // Build Docker image
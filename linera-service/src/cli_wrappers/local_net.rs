@@ -77,7 +77,11 @@ async fn make_testing_config(database: Database) -> Result<StorageConfig> {
             #[cfg(feature = "dynamodb")]
             {
                 let use_localstack = true;
-                Ok(StorageConfig::DynamoDb { use_localstack })
+                Ok(StorageConfig::DynamoDb {
+                    use_localstack,
+                    regions: Vec::new(),
+                    options: Default::default(),
+                })
             }
             #[cfg(not(feature = "dynamodb"))]
             panic!("Database::DynamoDb is selected without the feature aws");
@@ -491,7 +495,7 @@ impl LocalNet {
                 let nickname = format!("validator proxy {validator}");
                 Self::ensure_grpc_server_has_started(&nickname, port, "https").await?;
             }
-            Network::Tcp | Network::Udp => {
+            Network::Tcp | Network::Udp | Network::Quic => {
                 info!("Letting validator proxy {validator} start");
                 linera_base::time::timer::sleep(Duration::from_secs(2)).await;
             }
@@ -603,7 +607,7 @@ impl LocalNet {
                 let nickname = format!("validator server {validator}:{shard}");
                 Self::ensure_grpc_server_has_started(&nickname, port, "https").await?;
             }
-            Network::Tcp | Network::Udp => {
+            Network::Tcp | Network::Udp | Network::Quic => {
                 info!("Letting validator server {validator}:{shard} start");
                 linera_base::time::timer::sleep(Duration::from_secs(2)).await;
             }
@@ -647,6 +651,7 @@ impl LocalNet {
             recv_timeout: Duration::from_secs(1),
             retry_delay: Duration::ZERO,
             max_retries: 0,
+            compression: linera_rpc::grpc::GrpcCompression::None,
         });
 
         Ok(node_provider.make_node(&self.validator_address(validator))?)
@@ -66,6 +66,7 @@ pub enum Network {
     Grpcs,
     Tcp,
     Udp,
+    Quic,
 }
 
 /// Network protocol in use outside and inside a Linera net.
@@ -84,6 +85,7 @@ impl Network {
             Network::Grpcs => "{ Grpc = \"Tls\" }",
             Network::Tcp => "{ Simple = \"Tcp\" }",
             Network::Udp => "{ Simple = \"Udp\" }",
+            Network::Quic => "{ Simple = \"Quic\" }",
         }
     }
 
@@ -93,6 +95,7 @@ impl Network {
             Network::Grpcs => "grpcs",
             Network::Tcp => "tcp",
             Network::Udp => "udp",
+            Network::Quic => "quic",
         }
     }
 
@@ -102,13 +105,14 @@ impl Network {
             Network::Grpcs => Network::Grpc,
             Network::Tcp => Network::Tcp,
             Network::Udp => Network::Udp,
+            Network::Quic => Network::Quic,
         }
     }
 
     pub fn localhost(&self) -> &'static str {
         match self {
             Network::Grpc | Network::Grpcs => "localhost",
-            Network::Tcp | Network::Udp => "127.0.0.1",
+            Network::Tcp | Network::Udp | Network::Quic => "127.0.0.1",
         }
     }
 
@@ -118,6 +122,7 @@ impl Network {
             Network::Grpc | Network::Grpcs => "grpc",
             Network::Tcp => "tcp",
             Network::Udp => "udp",
+            Network::Quic => "quic",
         }
     }
 }
@@ -242,6 +242,7 @@ impl ClientWrapper {
             byte_read,
             byte_written,
             byte_stored,
+            byte_stored_per_day,
             operation,
             operation_byte,
             message,
@@ -254,6 +255,14 @@ impl ClientWrapper {
             maximum_block_proposal_size,
             maximum_bytes_read_per_block,
             maximum_bytes_written_per_block,
+            maximum_wasm_memory_size,
+            maximum_wasm_table_elements,
+            maximum_bytes_stored_per_application,
+            storage_rent_grace_period,
+            fuel_schedule,
+            base_fee_change_denominator,
+            maximum_scheduled_callbacks_per_application,
+            maximum_callbacks_per_operation,
         } = policy;
         let mut command = self.command().await?;
         command
@@ -270,6 +279,10 @@ impl ClientWrapper {
             .args(["--byte-read-price", &byte_read.to_string()])
             .args(["--byte-written-price", &byte_written.to_string()])
             .args(["--byte-stored-price", &byte_stored.to_string()])
+            .args([
+                "--byte-stored-per-day-price",
+                &byte_stored_per_day.to_string(),
+            ])
             .args(["--message-byte-price", &message_byte.to_string()])
             .args(["--write-operation-price", &write_operation.to_string()])
             .args(["--operation-price", &operation.to_string()])
@@ -303,6 +316,48 @@ impl ClientWrapper {
             .args([
                 "--maximum-bytes-written-per-block",
                 &maximum_bytes_written_per_block.to_string(),
+            ])
+            .args([
+                "--maximum-wasm-memory-size",
+                &maximum_wasm_memory_size.to_string(),
+            ])
+            .args([
+                "--maximum-wasm-table-elements",
+                &maximum_wasm_table_elements.to_string(),
+            ])
+            .args([
+                "--maximum-bytes-stored-per-application",
+                &maximum_bytes_stored_per_application.to_string(),
+            ])
+            .args([
+                "--storage-rent-grace-period-ms",
+                &(storage_rent_grace_period.as_micros() / 1_000).to_string(),
+            ])
+            .args(["--instruction-fuel", &fuel_schedule.instruction.to_string()])
+            .args([
+                "--structural-instruction-fuel",
+                &fuel_schedule.structural_instruction.to_string(),
+            ])
+            .args([
+                "--memory-access-fuel",
+                &fuel_schedule.memory_access.to_string(),
+            ])
+            .args(["--call-fuel", &fuel_schedule.call.to_string()])
+            .args([
+                "--memory-grow-page-fuel",
+                &fuel_schedule.memory_grow_page.to_string(),
+            ])
+            .args([
+                "--base-fee-change-denominator",
+                &base_fee_change_denominator.to_string(),
+            ])
+            .args([
+                "--maximum-scheduled-callbacks-per-application",
+                &maximum_scheduled_callbacks_per_application.to_string(),
+            ])
+            .args([
+                "--maximum-callbacks-per-operation",
+                &maximum_callbacks_per_operation.to_string(),
             ]);
         if let Some(seed) = self.testing_prng_seed {
             command.arg("--testing-prng-seed").arg(seed.to_string());
@@ -947,6 +1002,74 @@ impl ClientWrapper {
         Ok(chain_id)
     }
 
+    /// Runs `linera multi-owner new-key`.
+    pub async fn multi_owner_new_key(&self) -> Result<Owner> {
+        let stdout = self
+            .command()
+            .await?
+            .args(["multi-owner", "new-key"])
+            .spawn_and_wait_for_stdout()
+            .await?;
+        Ok(Owner::from_str(stdout.trim())?)
+    }
+
+    /// Runs `linera multi-owner open`.
+    pub async fn multi_owner_open(
+        &self,
+        from: ChainId,
+        owners: Vec<Owner>,
+        balance: Amount,
+    ) -> Result<ChainId> {
+        let mut command = self.command().await?;
+        command
+            .args(["multi-owner", "open"])
+            .args(["--from", &from.to_string()])
+            .args(["--initial-balance", &balance.to_string()]);
+        if !owners.is_empty() {
+            command
+                .arg("--owners")
+                .args(owners.iter().map(Owner::to_string));
+        }
+        let stdout = command.spawn_and_wait_for_stdout().await?;
+        Ok(ChainId::from_str(stdout.trim())?)
+    }
+
+    /// Runs `linera multi-owner add-owner`.
+    pub async fn multi_owner_add_owner(&self, chain_id: ChainId, owner: Owner) -> Result<()> {
+        self.command()
+            .await?
+            .args(["multi-owner", "add-owner"])
+            .arg(owner.to_string())
+            .args(["--chain-id", &chain_id.to_string()])
+            .spawn_and_wait_for_stdout()
+            .await?;
+        Ok(())
+    }
+
+    /// Runs `linera multi-owner remove-owner`.
+    pub async fn multi_owner_remove_owner(&self, chain_id: ChainId, owner: Owner) -> Result<()> {
+        self.command()
+            .await?
+            .args(["multi-owner", "remove-owner"])
+            .arg(owner.to_string())
+            .args(["--chain-id", &chain_id.to_string()])
+            .spawn_and_wait_for_stdout()
+            .await?;
+        Ok(())
+    }
+
+    /// Runs `linera multi-owner accept`.
+    pub async fn multi_owner_accept(&self, chain_id: ChainId) -> Result<Owner> {
+        let stdout = self
+            .command()
+            .await?
+            .args(["multi-owner", "accept"])
+            .arg(chain_id.to_string())
+            .spawn_and_wait_for_stdout()
+            .await?;
+        Ok(Owner::from_str(stdout.trim())?)
+    }
+
     pub async fn build_application(
         &self,
         path: &Path,
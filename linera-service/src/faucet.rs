@@ -1,50 +1,78 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{net::SocketAddr, num::NonZeroU16, sync::Arc};
+use std::{
+    net::{IpAddr, SocketAddr},
+    num::NonZeroU16,
+    sync::Arc,
+};
 
-use async_graphql::{EmptySubscription, Error, Schema, SimpleObject};
+use async_graphql::{Context, EmptySubscription, Error, Schema, SimpleObject};
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
-use axum::{Extension, Router};
+use axum::{extract::ConnectInfo, http::HeaderMap, Extension, Router};
 use futures::lock::Mutex;
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{Amount, ApplicationPermissions, Timestamp},
+    data_types::{Amount, ApplicationPermissions, TimeDelta, Timestamp},
     identifiers::{ChainId, MessageId, Owner},
     ownership::ChainOwnership,
 };
 use linera_client::{
     chain_listener::{ChainListener, ChainListenerConfig, ClientContext},
+    client_options::FaucetLimitConfig,
     config::GenesisConfig,
 };
 use linera_core::data_types::ClientOutcome;
 use linera_execution::committee::ValidatorName;
 use linera_storage::{Clock as _, Storage};
+use linera_views::views::View as _;
 use serde::Deserialize;
 use tower_http::cors::CorsLayer;
-use tracing::info;
+use tracing::{error, info};
 
+use self::{
+    limits::{ClaimLimiter, ClientAddress, Grant},
+    state::{Budget, FaucetStateView},
+};
 use crate::util;
 
+mod limits;
+mod state;
+
 #[cfg(test)]
 #[path = "unit_tests/faucet.rs"]
 mod tests;
 
+/// The maximal number of grants returned by a single query.
+const MAX_GRANTS_PER_QUERY: usize = 1000;
+
+/// The persistent state of the faucet of a client context.
+type StateView<C> = FaucetStateView<<<C as ClientContext>::Storage as Storage>::ServiceContext>;
+
 /// The root GraphQL query type.
-pub struct QueryRoot<C> {
+pub struct QueryRoot<C: ClientContext> {
     context: Arc<Mutex<C>>,
     genesis_config: Arc<GenesisConfig>,
     chain_id: ChainId,
+    state: Arc<Mutex<StateView<C>>>,
+    budget_per_period: Option<Amount>,
+    budget_period: TimeDelta,
 }
 
 /// The root GraphQL mutation type.
-pub struct MutationRoot<C> {
+pub struct MutationRoot<C: ClientContext> {
     chain_id: ChainId,
     context: Arc<Mutex<C>>,
     amount: Amount,
     end_timestamp: Timestamp,
     start_timestamp: Timestamp,
     start_balance: Amount,
+    limiter: Arc<ClaimLimiter>,
+    state: Arc<Mutex<StateView<C>>>,
+    /// The tokens being granted by the claims in progress.
+    pending: Arc<std::sync::Mutex<Amount>>,
+    budget_per_period: Option<Amount>,
+    budget_period: TimeDelta,
 }
 
 /// The result of a successful `claim` mutation.
@@ -92,6 +120,46 @@ where
             })
             .collect())
     }
+
+    /// Returns the grants issued by the faucet, oldest first, starting at index `offset`. At
+    /// most 1000 grants are returned at once.
+    async fn grants(
+        &self,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Grant>, Error> {
+        let start = offset.unwrap_or(0);
+        let limit = limit
+            .unwrap_or(MAX_GRANTS_PER_QUERY)
+            .min(MAX_GRANTS_PER_QUERY);
+        let state = self.state.lock().await;
+        Ok(state
+            .grants
+            .read(start..start.saturating_add(limit))
+            .await?)
+    }
+
+    /// Returns the number of grants issued by the faucet.
+    async fn grant_count(&self) -> usize {
+        self.state.lock().await.grants.count()
+    }
+
+    /// Returns the chains created by the faucet for the given owner.
+    async fn claimed_chains(&self, owner: Owner) -> Result<Vec<ChainId>, Error> {
+        let state = self.state.lock().await;
+        Ok(state.claimed_chains.get(&owner).await?.unwrap_or_default())
+    }
+
+    /// Returns the budget of the faucet in the current period, if it has one.
+    async fn budget(&self) -> Result<Option<Budget>, Error> {
+        let Some(total) = self.budget_per_period else {
+            return Ok(None);
+        };
+        let client = self.context.lock().await.make_chain_client(self.chain_id)?;
+        let now = client.storage_client().clock().current_time();
+        let state = self.state.lock().await;
+        Ok(Some(state.budget(total, self.budget_period, now)))
+    }
 }
 
 #[async_graphql::Object(cache_control(no_cache))]
@@ -100,8 +168,18 @@ where
     C: ClientContext,
 {
     /// Creates a new chain with the given authentication key, and transfers tokens to it.
-    async fn claim(&self, owner: Owner) -> Result<ClaimOutcome, Error> {
-        self.do_claim(owner).await
+    ///
+    /// The `verification_token`, e.g. the response to a captcha, is required by faucets
+    /// that verify the claims.
+    async fn claim(
+        &self,
+        ctx: &Context<'_>,
+        owner: Owner,
+        verification_token: Option<String>,
+    ) -> Result<ClaimOutcome, Error> {
+        let ip = ctx.data_opt::<ClientAddress>().map(|address| address.0);
+        self.do_claim(owner, ip, verification_token.as_deref())
+            .await
     }
 }
 
@@ -109,9 +187,31 @@ impl<C> MutationRoot<C>
 where
     C: ClientContext,
 {
-    async fn do_claim(&self, owner: Owner) -> Result<ClaimOutcome, Error> {
+    async fn do_claim(
+        &self,
+        owner: Owner,
+        ip: Option<IpAddr>,
+        verification_token: Option<&str>,
+    ) -> Result<ClaimOutcome, Error> {
         let client = self.context.lock().await.make_chain_client(self.chain_id)?;
+        let now = client.storage_client().clock().current_time();
+        let reservation = self.limiter.reserve(owner, ip, now)?;
+        self.limiter.verify(owner, ip, verification_token).await?;
+
+        // The budget and the unlocked balance are checked one claim at a time, and the amount
+        // is counted as pending until the claim completes. The state is not locked while the
+        // chain is being created, so that claims can be processed concurrently.
+        let mut state = self.state.lock().await;
+        let period_start = state::period_start(self.budget_period, now);
+        if let Some(budget) = self.budget_per_period {
+            if state.spent_since(period_start).saturating_add(self.amount) > budget {
+                return Err(Error::new(
+                    "The faucet's budget for this period is exhausted; try again later.",
+                ));
+            }
+        }
 
+        let in_progress = *self.pending.lock().unwrap();
         if self.start_timestamp < self.end_timestamp {
             let local_time = client.storage_client().clock().current_time();
             if local_time < self.end_timestamp {
@@ -121,7 +221,9 @@ where
                     .as_micros();
                 let remaining_duration = self.end_timestamp.delta_since(local_time).as_micros();
                 let balance = client.local_balance().await?;
-                let Ok(remaining_balance) = balance.try_sub(self.amount) else {
+                let Ok(remaining_balance) =
+                    balance.try_sub(in_progress.saturating_add(self.amount))
+                else {
                     return Err(Error::new("The faucet is empty."));
                 };
                 // The tokens unlock linearly, e.g. if 1/3 of the time is left, then 1/3 of the
@@ -136,22 +238,46 @@ where
             }
         }
 
+        // The amount is persisted as spent before the tokens are granted, so that the budget
+        // can't be exceeded even if the faucet stops during the claim.
+        state.reserve_budget(self.amount, period_start).await?;
+        let pending = PendingAmount::new(&self.pending, self.amount);
+        drop(state);
+
         let ownership = ChainOwnership::single(owner);
         let result = client
             .open_chain(ownership, ApplicationPermissions::default(), self.amount)
             .await;
-        self.context.lock().await.update_wallet(&client).await?;
-        let (message_id, certificate) = match result? {
-            ClientOutcome::Committed(result) => result,
-            ClientOutcome::WaitForTimeout(timeout) => {
-                return Err(Error::new(format!(
-                    "This faucet is using a multi-owner chain and is not the leader right now. \
-                    try again at {}",
-                    timeout.timestamp,
-                )));
+        drop(pending);
+        let update_result = self.context.lock().await.update_wallet(&client).await;
+        let outcome = match result {
+            Ok(ClientOutcome::Committed(outcome)) => Ok(outcome),
+            Ok(ClientOutcome::WaitForTimeout(timeout)) => Err(Error::new(format!(
+                "This faucet is using a multi-owner chain and is not the leader right now. \
+                try again at {}",
+                timeout.timestamp,
+            ))),
+            Err(error) => Err(error.into()),
+        };
+        let (message_id, certificate) = match outcome {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                let mut state = self.state.lock().await;
+                if let Err(error) = state.release_budget(self.amount, period_start).await {
+                    error!(%error, "Failed to release the budget of a failed faucet claim");
+                }
+                return Err(error);
             }
         };
+        update_result?;
         let chain_id = ChainId::child(message_id);
+        let grant = reservation.commit(chain_id, self.amount);
+        if let Err(error) = self.state.lock().await.record(grant).await {
+            error!(%error, %chain_id, "Failed to record a faucet grant");
+            return Err(Error::new(format!(
+                "Chain {chain_id} was created, but the faucet failed to record the grant: {error}"
+            )));
+        }
         Ok(ClaimOutcome {
             message_id,
             chain_id,
@@ -160,7 +286,29 @@ where
     }
 }
 
-impl<C> MutationRoot<C> {
+/// An amount being granted by a claim in progress, which is not deducted from the local
+/// balance of the faucet's chain yet.
+struct PendingAmount<'a> {
+    pending: &'a std::sync::Mutex<Amount>,
+    amount: Amount,
+}
+
+impl<'a> PendingAmount<'a> {
+    fn new(pending: &'a std::sync::Mutex<Amount>, amount: Amount) -> Self {
+        let mut total = pending.lock().unwrap();
+        *total = total.saturating_add(amount);
+        PendingAmount { pending, amount }
+    }
+}
+
+impl Drop for PendingAmount<'_> {
+    fn drop(&mut self) {
+        let mut total = self.pending.lock().unwrap();
+        *total = total.saturating_sub(self.amount);
+    }
+}
+
+impl<C: ClientContext> MutationRoot<C> {
     /// Multiplies a `u128` with a `u64` and returns the result as a 192-bit number.
     fn multiply(a: u128, b: u64) -> [u64; 3] {
         let lower = u128::from(u64::MAX);
@@ -187,6 +335,12 @@ where
     end_timestamp: Timestamp,
     start_timestamp: Timestamp,
     start_balance: Amount,
+    limiter: Arc<ClaimLimiter>,
+    state: Arc<Mutex<StateView<C>>>,
+    pending: Arc<std::sync::Mutex<Amount>>,
+    budget_per_period: Option<Amount>,
+    budget_period: TimeDelta,
+    trust_forwarded_for: bool,
 }
 
 impl<C> Clone for FaucetService<C>
@@ -205,6 +359,12 @@ where
             end_timestamp: self.end_timestamp,
             start_timestamp: self.start_timestamp,
             start_balance: self.start_balance,
+            limiter: Arc::clone(&self.limiter),
+            state: Arc::clone(&self.state),
+            pending: Arc::clone(&self.pending),
+            budget_per_period: self.budget_per_period,
+            budget_period: self.budget_period,
+            trust_forwarded_for: self.trust_forwarded_for,
         }
     }
 }
@@ -222,12 +382,30 @@ where
         amount: Amount,
         end_timestamp: Timestamp,
         genesis_config: Arc<GenesisConfig>,
+        limits: FaucetLimitConfig,
         config: ChainListenerConfig,
         storage: C::Storage,
     ) -> anyhow::Result<Self> {
+        let trust_forwarded_for = limits.trust_forwarded_for;
+        let budget_per_period = limits.budget_per_period;
+        let budget_period = TimeDelta::from_duration(limits.budget_period);
+        let mut limiter = ClaimLimiter::new(limits)?;
         let client = context.make_chain_client(chain_id)?;
         let context = Arc::new(Mutex::new(context));
         let start_timestamp = client.storage_client().clock().current_time();
+        let state_context = storage
+            .service_context(&format!("faucet-{chain_id}"))
+            .await?;
+        let mut state = FaucetStateView::load(state_context).await?;
+        // Faucets used to only record their grants in the grants file.
+        state
+            .import(limiter.read_grants_file()?, budget_period)
+            .await?;
+        // The recent grants still count toward the limits after a restart.
+        let since = start_timestamp.saturating_sub(limiter.window());
+        for grant in state.grants_since(since).await?.iter().rev() {
+            limiter.count(grant);
+        }
         client.process_inbox().await?;
         let start_balance = client.local_balance().await?;
         Ok(Self {
@@ -241,6 +419,12 @@ where
             end_timestamp,
             start_timestamp,
             start_balance,
+            limiter: Arc::new(limiter),
+            state: Arc::new(Mutex::new(state)),
+            pending: Arc::default(),
+            budget_per_period,
+            budget_period,
+            trust_forwarded_for,
         })
     }
 
@@ -252,11 +436,19 @@ where
             end_timestamp: self.end_timestamp,
             start_timestamp: self.start_timestamp,
             start_balance: self.start_balance,
+            limiter: Arc::clone(&self.limiter),
+            state: Arc::clone(&self.state),
+            pending: Arc::clone(&self.pending),
+            budget_per_period: self.budget_per_period,
+            budget_period: self.budget_period,
         };
         let query_root = QueryRoot {
             genesis_config: Arc::clone(&self.genesis_config),
             context: Arc::clone(&self.context),
             chain_id: self.chain_id,
+            state: Arc::clone(&self.state),
+            budget_per_period: self.budget_per_period,
+            budget_period: self.budget_period,
         };
         Schema::build(query_root, mutation_root, EmptySubscription).finish()
     }
@@ -281,7 +473,7 @@ where
 
         axum::serve(
             tokio::net::TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], port))).await?,
-            app,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
         )
         .await?;
 
@@ -289,8 +481,31 @@ where
     }
 
     /// Executes a GraphQL query and generates a response for our `Schema`.
-    async fn index_handler(service: Extension<Self>, request: GraphQLRequest) -> GraphQLResponse {
+    async fn index_handler(
+        service: Extension<Self>,
+        ConnectInfo(address): ConnectInfo<SocketAddr>,
+        headers: HeaderMap,
+        request: GraphQLRequest,
+    ) -> GraphQLResponse {
+        let ip = service.0.client_ip(address, &headers);
         let schema = service.0.schema();
-        schema.execute(request.into_inner()).await.into()
+        let request = request.into_inner().data(ClientAddress(ip));
+        schema.execute(request).await.into()
+    }
+
+    /// Returns the IP address of the client. If the `X-Forwarded-For` header is trusted, this
+    /// is the last address in it, which was added by the reverse proxy.
+    fn client_ip(&self, address: SocketAddr, headers: &HeaderMap) -> IpAddr {
+        if self.trust_forwarded_for {
+            let forwarded_ip = headers
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.rsplit(',').next())
+                .and_then(|ip| ip.trim().parse().ok());
+            if let Some(ip) = forwarded_ip {
+                return ip;
+            }
+        }
+        address.ip()
     }
 }
@@ -0,0 +1,322 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Protection of the faucet against being drained by a few clients.
+//!
+//! Claims are counted per IP address and per owner over a sliding window, and can be submitted
+//! to an external verification service, e.g. checking a captcha, before being granted. Grants
+//! can also be appended to a file, e.g. to be analyzed by other tools.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    io::{BufRead as _, Write as _},
+    net::IpAddr,
+    sync::Mutex,
+};
+
+use anyhow::Context as _;
+use async_graphql::{Error, SimpleObject};
+use linera_base::{
+    data_types::{Amount, TimeDelta, Timestamp},
+    identifiers::{ChainId, Owner},
+    time::Duration,
+};
+use linera_client::client_options::FaucetLimitConfig;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+/// The number of tracked keys above which those without recent claims are forgotten.
+const MIN_PRUNED_KEYS: usize = 10_000;
+
+/// The maximal time to wait for the verification webhook.
+const VERIFICATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The address of the client that sent a request, available in the GraphQL context.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientAddress(pub IpAddr);
+
+/// A grant of tokens by the faucet.
+#[derive(Clone, Debug, Serialize, Deserialize, SimpleObject)]
+pub struct Grant {
+    pub timestamp: Timestamp,
+    pub owner: Owner,
+    /// The address of the client, which is not disclosed through GraphQL.
+    #[graphql(skip)]
+    pub ip: Option<IpAddr>,
+    pub chain_id: ChainId,
+    pub amount: Amount,
+}
+
+/// The request sent to the verification webhook.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VerificationRequest<'a> {
+    owner: Owner,
+    ip: Option<IpAddr>,
+    verification_token: Option<&'a str>,
+}
+
+/// Enforces the [`FaucetLimitConfig`] of a faucet.
+pub struct ClaimLimiter {
+    config: FaucetLimitConfig,
+    window: TimeDelta,
+    per_ip: Mutex<ClaimCounter<IpAddr>>,
+    per_owner: Mutex<ClaimCounter<Owner>>,
+    grants_file: Option<Mutex<fs_err::File>>,
+    http_client: reqwest::Client,
+}
+
+impl ClaimLimiter {
+    /// Creates a limiter with the given configuration.
+    pub fn new(config: FaucetLimitConfig) -> anyhow::Result<Self> {
+        let mut limiter = ClaimLimiter {
+            window: TimeDelta::from_duration(config.claim_window),
+            per_ip: Mutex::default(),
+            per_owner: Mutex::default(),
+            grants_file: None,
+            http_client: reqwest::Client::new(),
+            config,
+        };
+        if let Some(path) = &limiter.config.grants_path {
+            let file = fs_err::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            limiter.grants_file = Some(Mutex::new(file));
+        }
+        Ok(limiter)
+    }
+
+    /// Reserves a claim for `owner` from the client at `ip`, failing if either of them
+    /// exceeded its limit. The claim counts toward the limits unless the returned reservation
+    /// is dropped without being committed.
+    pub fn reserve(
+        &self,
+        owner: Owner,
+        ip: Option<IpAddr>,
+        now: Timestamp,
+    ) -> Result<Reservation<'_>, Error> {
+        let since = now.saturating_sub(self.window);
+        let mut per_ip = match self.config.max_claims_per_ip {
+            Some(max_claims) => {
+                let ip = ip.ok_or_else(|| Error::new("The address of the client is unknown."))?;
+                let mut per_ip = self.per_ip.lock().unwrap();
+                if per_ip.count_since(&ip, since) >= max_claims.get() as usize {
+                    return Err(Error::new(
+                        "Too many claims from this address; try again later.",
+                    ));
+                }
+                Some((ip, per_ip))
+            }
+            None => None,
+        };
+        let mut per_owner = match self.config.max_claims_per_owner {
+            Some(max_claims) => {
+                let mut per_owner = self.per_owner.lock().unwrap();
+                if per_owner.count_since(&owner, since) >= max_claims.get() as usize {
+                    return Err(Error::new(
+                        "Too many claims for this owner; try again later.",
+                    ));
+                }
+                Some(per_owner)
+            }
+            None => None,
+        };
+        // Only count the claim once both limits are known to allow it: dropping the
+        // reservation releases it from both counters.
+        if let Some((ip, per_ip)) = &mut per_ip {
+            per_ip.add(*ip, now);
+        }
+        if let Some(per_owner) = &mut per_owner {
+            per_owner.add(owner, now);
+        }
+        Ok(Reservation {
+            limiter: self,
+            owner,
+            ip,
+            timestamp: now,
+            committed: false,
+        })
+    }
+
+    /// Asks the verification webhook, if any, whether the claim should be granted.
+    pub async fn verify(
+        &self,
+        owner: Owner,
+        ip: Option<IpAddr>,
+        verification_token: Option<&str>,
+    ) -> Result<(), Error> {
+        let Some(url) = &self.config.verification_webhook else {
+            return Ok(());
+        };
+        let request = VerificationRequest {
+            owner,
+            ip,
+            verification_token,
+        };
+        let response = self
+            .http_client
+            .post(url)
+            .json(&request)
+            .timeout(VERIFICATION_TIMEOUT)
+            .send()
+            .await
+            .map_err(|error| {
+                error!(%error, "Failed to reach the faucet verification webhook");
+                Error::new("The claim could not be verified; try again later.")
+            })?;
+        if !response.status().is_success() {
+            return Err(Error::new("The claim was rejected by the verification."));
+        }
+        Ok(())
+    }
+
+    /// Returns the grants recorded in the grants file, if any, e.g. to import the grants of a
+    /// faucet that only recorded them there.
+    pub fn read_grants_file(&self) -> anyhow::Result<Vec<Grant>> {
+        let Some(path) = &self.config.grants_path else {
+            return Ok(Vec::new());
+        };
+        let reader = std::io::BufReader::new(fs_err::File::open(path)?);
+        let mut grants = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let grant = serde_json::from_str(&line?).with_context(|| {
+                format!("invalid grant on line {} of {}", index + 1, path.display())
+            })?;
+            grants.push(grant);
+        }
+        Ok(grants)
+    }
+
+    /// Returns the duration of the window in which claims are counted.
+    pub fn window(&self) -> TimeDelta {
+        self.window
+    }
+
+    /// Counts a past grant toward the limits, e.g. after a restart.
+    pub fn count(&mut self, grant: &Grant) {
+        if let Some(ip) = grant.ip {
+            self.per_ip.get_mut().unwrap().add(ip, grant.timestamp);
+        }
+        self.per_owner
+            .get_mut()
+            .unwrap()
+            .add(grant.owner, grant.timestamp);
+    }
+
+    /// Removes a reserved claim that wasn't granted.
+    fn release(&self, owner: Owner, ip: Option<IpAddr>, timestamp: Timestamp) {
+        if let (Some(ip), Some(_)) = (ip, self.config.max_claims_per_ip) {
+            self.per_ip.lock().unwrap().remove(&ip, timestamp);
+        }
+        if self.config.max_claims_per_owner.is_some() {
+            self.per_owner.lock().unwrap().remove(&owner, timestamp);
+        }
+    }
+
+    /// Appends the grant to the grants file, if any.
+    fn record(&self, grant: &Grant) -> anyhow::Result<()> {
+        let Some(file) = &self.grants_file else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_vec(grant)?;
+        line.push(b'\n');
+        let mut file = file.lock().unwrap();
+        file.write_all(&line)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// A claim counting toward the limits of its owner and client.
+pub struct Reservation<'a> {
+    limiter: &'a ClaimLimiter,
+    owner: Owner,
+    ip: Option<IpAddr>,
+    timestamp: Timestamp,
+    committed: bool,
+}
+
+impl Reservation<'_> {
+    /// Records that the claim was granted, creating `chain_id` with `amount` tokens.
+    pub fn commit(mut self, chain_id: ChainId, amount: Amount) -> Grant {
+        self.committed = true;
+        let grant = Grant {
+            timestamp: self.timestamp,
+            owner: self.owner,
+            ip: self.ip,
+            chain_id,
+            amount,
+        };
+        if let Err(error) = self.limiter.record(&grant) {
+            error!(%error, ?grant, "Failed to record a faucet grant");
+        }
+        grant
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.limiter.release(self.owner, self.ip, self.timestamp);
+        }
+    }
+}
+
+/// The timestamps of the recent claims of each key.
+struct ClaimCounter<K> {
+    claims: HashMap<K, VecDeque<Timestamp>>,
+    /// The number of keys above which those without recent claims are removed.
+    prune_at: usize,
+}
+
+impl<K> Default for ClaimCounter<K> {
+    fn default() -> Self {
+        ClaimCounter {
+            claims: HashMap::new(),
+            prune_at: MIN_PRUNED_KEYS,
+        }
+    }
+}
+
+impl<K: Hash + Eq> ClaimCounter<K> {
+    /// Returns the number of claims of `key` since the given time, forgetting older ones.
+    fn count_since(&mut self, key: &K, since: Timestamp) -> usize {
+        if self.claims.len() >= self.prune_at {
+            self.claims.retain(|_, timestamps| {
+                Self::forget_before(timestamps, since);
+                !timestamps.is_empty()
+            });
+            self.prune_at = (2 * self.claims.len()).max(MIN_PRUNED_KEYS);
+        }
+        let Some(timestamps) = self.claims.get_mut(key) else {
+            return 0;
+        };
+        Self::forget_before(timestamps, since);
+        timestamps.len()
+    }
+
+    fn add(&mut self, key: K, timestamp: Timestamp) {
+        self.claims.entry(key).or_default().push_back(timestamp);
+    }
+
+    fn remove(&mut self, key: &K, timestamp: Timestamp) {
+        if let Some(timestamps) = self.claims.get_mut(key) {
+            if let Some(index) = timestamps.iter().rposition(|t| *t == timestamp) {
+                timestamps.remove(index);
+            }
+            if timestamps.is_empty() {
+                self.claims.remove(key);
+            }
+        }
+    }
+
+    /// Removes the timestamps before `since`. Timestamps are added in increasing order,
+    /// except for reservations made concurrently, so this only looks at the oldest ones.
+    fn forget_before(timestamps: &mut VecDeque<Timestamp>, since: Timestamp) {
+        while timestamps.front().is_some_and(|t| *t < since) {
+            timestamps.pop_front();
+        }
+    }
+}
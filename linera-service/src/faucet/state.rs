@@ -0,0 +1,192 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The persistent accounting of the faucet.
+//!
+//! Every grant is recorded in a [`FaucetStateView`], stored next to the chains of the wallet,
+//! together with the chains claimed by each owner and the tokens granted in the current budget
+//! period. The history of the faucet and its remaining budget thus survive restarts.
+
+use async_graphql::SimpleObject;
+use linera_base::{
+    data_types::{Amount, TimeDelta, Timestamp},
+    identifiers::{ChainId, Owner},
+};
+use linera_views::{
+    context::Context,
+    log_view::LogView,
+    map_view::MapView,
+    register_view::RegisterView,
+    views::{RootView, View as _, ViewError},
+};
+use serde::{Deserialize, Serialize};
+
+use super::limits::Grant;
+
+/// The number of grants read at once when scanning the most recent ones.
+const GRANTS_BATCH_SIZE: usize = 100;
+
+/// The persistent state of a faucet.
+#[derive(RootView)]
+pub struct FaucetStateView<C> {
+    /// All grants, in the order in which they were issued.
+    pub grants: LogView<C, Grant>,
+    /// The chains created for each owner.
+    pub claimed_chains: MapView<C, Owner, Vec<ChainId>>,
+    /// The tokens granted in the current budget period.
+    pub budget_period: RegisterView<C, BudgetPeriod>,
+}
+
+/// The tokens granted in a budget period.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct BudgetPeriod {
+    /// The start of the period.
+    pub start: Timestamp,
+    /// The tokens granted since then.
+    pub spent: Amount,
+}
+
+/// The budget of the faucet in the current period.
+#[derive(Debug, SimpleObject)]
+pub struct Budget {
+    /// The start of the current period.
+    pub period_start: Timestamp,
+    /// The end of the current period, when the budget is renewed.
+    pub period_end: Timestamp,
+    /// The tokens that can be granted per period.
+    pub total: Amount,
+    /// The tokens granted in the current period.
+    pub spent: Amount,
+    /// The tokens that can still be granted in the current period.
+    pub remaining: Amount,
+}
+
+impl<C> FaucetStateView<C>
+where
+    C: Context + Clone + Send + Sync + 'static,
+{
+    /// Returns the tokens granted in the period starting at `period_start`.
+    pub fn spent_since(&self, period_start: Timestamp) -> Amount {
+        let period = self.budget_period.get();
+        if period.start == period_start {
+            period.spent
+        } else {
+            Amount::ZERO
+        }
+    }
+
+    /// Returns the budget of the period of the given duration containing `now`.
+    pub fn budget(&self, total: Amount, period: TimeDelta, now: Timestamp) -> Budget {
+        let period_start = period_start(period, now);
+        let spent = self.spent_since(period_start);
+        Budget {
+            period_start,
+            period_end: period_start.saturating_add(period),
+            total,
+            spent,
+            remaining: total.saturating_sub(spent),
+        }
+    }
+
+    /// Counts `amount` as spent in the period starting at `period_start`, and saves the state.
+    ///
+    /// This is done before the tokens are granted, so that they count toward the budget even
+    /// if the faucet stops before recording the grant.
+    pub async fn reserve_budget(
+        &mut self,
+        amount: Amount,
+        period_start: Timestamp,
+    ) -> Result<(), ViewError> {
+        self.add_spent(amount, period_start);
+        self.save_or_reload().await
+    }
+
+    /// Releases `amount` reserved in the period starting at `period_start` for tokens that
+    /// were not granted, and saves the state.
+    pub async fn release_budget(
+        &mut self,
+        amount: Amount,
+        period_start: Timestamp,
+    ) -> Result<(), ViewError> {
+        let period = self.budget_period.get_mut();
+        if period.start == period_start {
+            period.spent = period.spent.saturating_sub(amount);
+        }
+        self.save_or_reload().await
+    }
+
+    /// Records a grant whose amount was already reserved in the budget, and saves the state.
+    pub async fn record(&mut self, grant: Grant) -> Result<(), ViewError> {
+        self.push_grant(grant).await?;
+        self.save_or_reload().await
+    }
+
+    /// Records grants made before the faucet kept its state in the storage, in the order in
+    /// which they were issued, counting them toward the budget of their periods. Nothing is
+    /// imported if the state already has grants, so this is only done once.
+    pub async fn import(&mut self, grants: Vec<Grant>, period: TimeDelta) -> Result<(), ViewError> {
+        if self.grants.count() > 0 || grants.is_empty() {
+            return Ok(());
+        }
+        for grant in grants {
+            self.add_spent(grant.amount, period_start(period, grant.timestamp));
+            self.push_grant(grant).await?;
+        }
+        self.save_or_reload().await
+    }
+
+    fn add_spent(&mut self, amount: Amount, period_start: Timestamp) {
+        let spent = self.spent_since(period_start).saturating_add(amount);
+        self.budget_period.set(BudgetPeriod {
+            start: period_start,
+            spent,
+        });
+    }
+
+    async fn push_grant(&mut self, grant: Grant) -> Result<(), ViewError> {
+        let mut chains = self
+            .claimed_chains
+            .get(&grant.owner)
+            .await?
+            .unwrap_or_default();
+        chains.push(grant.chain_id);
+        self.claimed_chains.insert(&grant.owner, chains)?;
+        self.grants.push(grant);
+        Ok(())
+    }
+
+    /// Saves the state. If that fails, the view may no longer match the storage, so it is
+    /// loaded again.
+    async fn save_or_reload(&mut self) -> Result<(), ViewError> {
+        if let Err(error) = self.save().await {
+            *self = Self::load(self.context().clone()).await?;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Returns the grants issued at or after `since`, most recent first.
+    pub async fn grants_since(&self, since: Timestamp) -> Result<Vec<Grant>, ViewError> {
+        let mut grants = Vec::new();
+        let mut end = self.grants.count();
+        while end > 0 {
+            let start = end.saturating_sub(GRANTS_BATCH_SIZE);
+            let batch = self.grants.read(start..end).await?;
+            for grant in batch.into_iter().rev() {
+                if grant.timestamp < since {
+                    return Ok(grants);
+                }
+                grants.push(grant);
+            }
+            end = start;
+        }
+        Ok(grants)
+    }
+}
+
+/// Returns the start of the period of the given duration containing `now`. Periods are aligned
+/// to the Unix epoch.
+pub fn period_start(period: TimeDelta, now: Timestamp) -> Timestamp {
+    let period = period.as_micros().max(1);
+    Timestamp::from(now.micros() - now.micros() % period)
+}
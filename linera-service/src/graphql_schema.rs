@@ -0,0 +1,561 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Export of GraphQL schemas for web frontends.
+//!
+//! The schemas of applications are only known by their services, so they are obtained by
+//! running the standard introspection query. An [`IntrospectedSchema`] can then be printed as
+//! SDL by `async-graphql`, or as TypeScript type definitions for the frontends querying the
+//! schema.
+
+use std::fmt::Write as _;
+
+use anyhow::Context as _;
+use async_graphql::{
+    dynamic, parser::types::DocumentOperations, ObjectType, Response, Schema, SubscriptionType,
+};
+use convert_case::{Case, Casing};
+use futures::stream;
+use serde::Deserialize;
+
+/// The introspection query, returning everything needed to print a schema.
+pub const INTROSPECTION_QUERY: &str = r#"
+query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types { ...FullType }
+  }
+}
+
+fragment FullType on __Type {
+  kind
+  name
+  description
+  fields(includeDeprecated: true) {
+    name
+    description
+    args { ...InputValue }
+    type { ...TypeRef }
+    isDeprecated
+    deprecationReason
+  }
+  inputFields { ...InputValue }
+  interfaces { ...TypeRef }
+  enumValues(includeDeprecated: true) {
+    name
+    description
+    isDeprecated
+    deprecationReason
+  }
+  possibleTypes { ...TypeRef }
+}
+
+fragment InputValue on __InputValue {
+  name
+  description
+  type { ...TypeRef }
+  defaultValue
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType {
+          kind
+          name
+          ofType {
+            kind
+            name
+            ofType {
+              kind
+              name
+            }
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
+/// The scalars that every GraphQL schema has.
+const BUILT_IN_SCALARS: [&str; 5] = ["Boolean", "Float", "ID", "Int", "String"];
+
+/// The response to the [`INTROSPECTION_QUERY`].
+#[derive(Clone, Debug, Deserialize)]
+pub struct IntrospectedSchema {
+    #[serde(rename = "__schema")]
+    schema: SchemaDescription,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SchemaDescription {
+    query_type: Option<NamedType>,
+    mutation_type: Option<NamedType>,
+    subscription_type: Option<NamedType>,
+    types: Vec<FullType>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct NamedType {
+    name: String,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+    List,
+    NonNull,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FullType {
+    kind: TypeKind,
+    name: String,
+    description: Option<String>,
+    fields: Option<Vec<Field>>,
+    input_fields: Option<Vec<InputValue>>,
+    interfaces: Option<Vec<TypeRef>>,
+    enum_values: Option<Vec<EnumValue>>,
+    possible_types: Option<Vec<TypeRef>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Field {
+    name: String,
+    description: Option<String>,
+    args: Vec<InputValue>,
+    #[serde(rename = "type")]
+    type_ref: TypeRef,
+    is_deprecated: bool,
+    deprecation_reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InputValue {
+    name: String,
+    description: Option<String>,
+    #[serde(rename = "type")]
+    type_ref: TypeRef,
+    default_value: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EnumValue {
+    name: String,
+    description: Option<String>,
+    is_deprecated: bool,
+    deprecation_reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TypeRef {
+    kind: TypeKind,
+    name: Option<String>,
+    of_type: Option<Box<TypeRef>>,
+}
+
+impl IntrospectedSchema {
+    /// Runs the introspection query on a schema served by this process.
+    pub async fn of<Query, Mutation, Subscription>(
+        schema: &Schema<Query, Mutation, Subscription>,
+    ) -> anyhow::Result<Self>
+    where
+        Query: ObjectType + 'static,
+        Mutation: ObjectType + 'static,
+        Subscription: SubscriptionType + 'static,
+    {
+        Self::from_response(schema.execute(INTROSPECTION_QUERY).await)
+    }
+
+    /// Reads the response to the [`INTROSPECTION_QUERY`].
+    pub fn from_response(response: Response) -> anyhow::Result<Self> {
+        if let Some(error) = response.errors.first() {
+            anyhow::bail!("Introspection query failed: {error}");
+        }
+        Ok(serde_json::from_value(response.data.into_json()?)?)
+    }
+
+    /// Returns the types defined by the schema, without the introspection types and the
+    /// built-in scalars.
+    fn defined_types(&self) -> impl Iterator<Item = &FullType> {
+        self.schema.types.iter().filter(|ty| {
+            !ty.name.starts_with("__") && !BUILT_IN_SCALARS.contains(&ty.name.as_str())
+        })
+    }
+
+    /// Prints the schema in the GraphQL schema definition language.
+    pub fn to_sdl(&self) -> anyhow::Result<String> {
+        Ok(self.to_dynamic_schema()?.sdl())
+    }
+
+    /// Returns a schema with the same types, whose fields don't resolve to anything, so that it
+    /// can be printed by `async-graphql`.
+    fn to_dynamic_schema(&self) -> anyhow::Result<dynamic::Schema> {
+        let query = &self
+            .schema
+            .query_type
+            .as_ref()
+            .context("The introspected schema has no query type")?
+            .name;
+        let mutation = self
+            .schema
+            .mutation_type
+            .as_ref()
+            .map(|ty| ty.name.as_str());
+        let subscription = self
+            .schema
+            .subscription_type
+            .as_ref()
+            .map(|ty| ty.name.as_str());
+        let mut builder = dynamic::Schema::build(query, mutation, subscription);
+        for ty in self.defined_types() {
+            builder = builder.register(ty.to_dynamic(subscription == Some(ty.name.as_str()))?);
+        }
+        builder
+            .finish()
+            .map_err(|error| anyhow::anyhow!("Invalid introspected schema: {error}"))
+    }
+
+    /// Prints TypeScript definitions of the types of the schema.
+    ///
+    /// Nullable fields of input objects and arguments are optional. The arguments of each
+    /// field are described by an interface named after the type and the field, e.g.
+    /// `QueryChainArgs` for the arguments of `Query.chain`. Custom scalars are left as `any`,
+    /// since their JSON representation is not part of the schema.
+    pub fn to_typescript(&self) -> anyhow::Result<String> {
+        let mut typescript = String::from("// Generated by `linera service codegen`.\n");
+        for ty in self.defined_types() {
+            typescript.push('\n');
+            write_typescript_description(&mut typescript, &ty.description, "");
+            let name = &ty.name;
+            match ty.kind {
+                TypeKind::Scalar => writeln!(typescript, "export type {name} = any;")?,
+                TypeKind::Object | TypeKind::Interface => {
+                    writeln!(typescript, "export interface {name} {{")?;
+                    for field in ty.fields.iter().flatten() {
+                        write_typescript_description(&mut typescript, &field.description, "  ");
+                        writeln!(
+                            typescript,
+                            "  {}: {};",
+                            field.name,
+                            field.type_ref.to_typescript()?
+                        )?;
+                    }
+                    typescript.push_str("}\n");
+                    for field in ty.fields.iter().flatten() {
+                        if field.args.is_empty() {
+                            continue;
+                        }
+                        let args_name = format!("{name}{}Args", field.name.to_case(Case::Pascal));
+                        writeln!(typescript, "\nexport interface {args_name} {{")?;
+                        write_typescript_input_values(&mut typescript, &field.args)?;
+                        typescript.push_str("}\n");
+                    }
+                }
+                TypeKind::Union => {
+                    let members = ty
+                        .possible_types
+                        .iter()
+                        .flatten()
+                        .map(TypeRef::name)
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+                    writeln!(typescript, "export type {name} = {};", members.join(" | "))?;
+                }
+                TypeKind::Enum => {
+                    let values = ty
+                        .enum_values
+                        .iter()
+                        .flatten()
+                        .map(|value| format!("{:?}", value.name))
+                        .collect::<Vec<_>>();
+                    writeln!(typescript, "export type {name} = {};", values.join(" | "))?;
+                }
+                TypeKind::InputObject => {
+                    writeln!(typescript, "export interface {name} {{")?;
+                    write_typescript_input_values(
+                        &mut typescript,
+                        ty.input_fields.iter().flatten(),
+                    )?;
+                    typescript.push_str("}\n");
+                }
+                TypeKind::List | TypeKind::NonNull => {}
+            }
+        }
+        Ok(typescript)
+    }
+}
+
+/// Sets the description of an element of a [`dynamic::Schema`], if there is one.
+macro_rules! with_description {
+    ($element:expr, $description:expr) => {
+        match $description {
+            Some(description) => $element.description(description),
+            None => $element,
+        }
+    };
+}
+
+/// Marks an element of a [`dynamic::Schema`] as deprecated, if it is.
+macro_rules! with_deprecation {
+    ($element:expr, $is_deprecated:expr, $reason:expr) => {
+        if $is_deprecated {
+            $element.deprecation($reason.as_deref())
+        } else {
+            $element
+        }
+    };
+}
+
+impl FullType {
+    /// Returns the type to register in a [`dynamic::Schema`]. Fields don't resolve to anything.
+    fn to_dynamic(&self, is_subscription: bool) -> anyhow::Result<dynamic::Type> {
+        let name = &self.name;
+        let description = self.description.as_ref();
+        let fields = self.fields.iter().flatten();
+        let ty = match self.kind {
+            TypeKind::Scalar => with_description!(dynamic::Scalar::new(name), description).into(),
+            TypeKind::Object if is_subscription => {
+                let mut subscription = dynamic::Subscription::new(name);
+                for field in fields {
+                    let mut dynamic_field = dynamic::SubscriptionField::new(
+                        &field.name,
+                        field.type_ref.to_dynamic()?,
+                        |_| {
+                            dynamic::SubscriptionFieldFuture::new(async {
+                                Ok(stream::empty::<async_graphql::Result<dynamic::FieldValue>>())
+                            })
+                        },
+                    );
+                    for argument in &field.args {
+                        dynamic_field = dynamic_field.argument(argument.to_dynamic()?);
+                    }
+                    let dynamic_field =
+                        with_description!(dynamic_field, field.description.as_ref());
+                    subscription = subscription.field(with_deprecation!(
+                        dynamic_field,
+                        field.is_deprecated,
+                        field.deprecation_reason
+                    ));
+                }
+                with_description!(subscription, description).into()
+            }
+            TypeKind::Object => {
+                let mut object = dynamic::Object::new(name);
+                for interface in self.interfaces.iter().flatten() {
+                    object = object.implement(interface.name()?);
+                }
+                for field in fields {
+                    let mut dynamic_field =
+                        dynamic::Field::new(&field.name, field.type_ref.to_dynamic()?, |_| {
+                            dynamic::FieldFuture::new(async { Ok(None::<dynamic::FieldValue>) })
+                        });
+                    for argument in &field.args {
+                        dynamic_field = dynamic_field.argument(argument.to_dynamic()?);
+                    }
+                    let dynamic_field =
+                        with_description!(dynamic_field, field.description.as_ref());
+                    object = object.field(with_deprecation!(
+                        dynamic_field,
+                        field.is_deprecated,
+                        field.deprecation_reason
+                    ));
+                }
+                with_description!(object, description).into()
+            }
+            TypeKind::Interface => {
+                let mut interface = dynamic::Interface::new(name);
+                for field in fields {
+                    let mut dynamic_field =
+                        dynamic::InterfaceField::new(&field.name, field.type_ref.to_dynamic()?);
+                    for argument in &field.args {
+                        dynamic_field = dynamic_field.argument(argument.to_dynamic()?);
+                    }
+                    let dynamic_field =
+                        with_description!(dynamic_field, field.description.as_ref());
+                    interface = interface.field(with_deprecation!(
+                        dynamic_field,
+                        field.is_deprecated,
+                        field.deprecation_reason
+                    ));
+                }
+                with_description!(interface, description).into()
+            }
+            TypeKind::Union => {
+                let mut union = dynamic::Union::new(name);
+                for member in self.possible_types.iter().flatten() {
+                    union = union.possible_type(member.name()?);
+                }
+                with_description!(union, description).into()
+            }
+            TypeKind::Enum => {
+                let mut enum_type = dynamic::Enum::new(name);
+                for value in self.enum_values.iter().flatten() {
+                    let item = dynamic::EnumItem::new(&value.name);
+                    let item = with_description!(item, value.description.as_ref());
+                    enum_type = enum_type.item(with_deprecation!(
+                        item,
+                        value.is_deprecated,
+                        value.deprecation_reason
+                    ));
+                }
+                with_description!(enum_type, description).into()
+            }
+            TypeKind::InputObject => {
+                let mut input_object = dynamic::InputObject::new(name);
+                for field in self.input_fields.iter().flatten() {
+                    input_object = input_object.field(field.to_dynamic()?);
+                }
+                with_description!(input_object, description).into()
+            }
+            TypeKind::List | TypeKind::NonNull => {
+                anyhow::bail!("The introspected type {name} is a {:?} type", self.kind)
+            }
+        };
+        Ok(ty)
+    }
+}
+
+impl InputValue {
+    /// Returns the argument or input field to add to a [`dynamic::Schema`].
+    fn to_dynamic(&self) -> anyhow::Result<dynamic::InputValue> {
+        let mut value = dynamic::InputValue::new(&self.name, self.type_ref.to_dynamic()?);
+        if let Some(default_value) = &self.default_value {
+            value = value.default_value(parse_value(default_value)?);
+        }
+        Ok(with_description!(value, self.description.as_ref()))
+    }
+}
+
+impl TypeRef {
+    /// Returns the type wrapped in this list or non-null type.
+    ///
+    /// The [`INTROSPECTION_QUERY`] only describes a limited number of nested wrapping types, so
+    /// this fails for types that are nested deeper, as well as for invalid introspection results.
+    fn of_type(&self) -> anyhow::Result<&TypeRef> {
+        self.of_type.as_deref().with_context(|| {
+            format!(
+                "The introspection result doesn't describe the type wrapped in a {:?} type: \
+                it may be nested too deeply",
+                self.kind
+            )
+        })
+    }
+
+    fn name(&self) -> anyhow::Result<&str> {
+        self.name.as_deref().with_context(|| {
+            format!(
+                "The introspection result has a {:?} type without a name",
+                self.kind
+            )
+        })
+    }
+
+    fn to_dynamic(&self) -> anyhow::Result<dynamic::TypeRef> {
+        Ok(match self.kind {
+            TypeKind::NonNull => dynamic::TypeRef::NonNull(Box::new(self.of_type()?.to_dynamic()?)),
+            TypeKind::List => dynamic::TypeRef::List(Box::new(self.of_type()?.to_dynamic()?)),
+            _ => dynamic::TypeRef::named(self.name()?),
+        })
+    }
+
+    fn to_typescript(&self) -> anyhow::Result<String> {
+        Ok(match self.kind {
+            TypeKind::NonNull => self.of_type()?.to_non_null_typescript()?,
+            _ => format!("{} | null", self.to_non_null_typescript()?),
+        })
+    }
+
+    fn to_non_null_typescript(&self) -> anyhow::Result<String> {
+        Ok(match self.kind {
+            TypeKind::NonNull => self.of_type()?.to_non_null_typescript()?,
+            TypeKind::List => format!("Array<{}>", self.of_type()?.to_typescript()?),
+            _ => match self.name()? {
+                "Boolean" => "boolean".to_owned(),
+                "Float" | "Int" => "number".to_owned(),
+                "ID" | "String" => "string".to_owned(),
+                name => name.to_owned(),
+            },
+        })
+    }
+}
+
+/// Parses a value written in GraphQL, as the default values of arguments are introspected.
+fn parse_value(value: &str) -> anyhow::Result<async_graphql::Value> {
+    // Only variable definitions have a default value on their own, in a query.
+    let query = format!("query($value: Boolean = {value}) {{ __typename }}");
+    let document = async_graphql::parser::parse_query(query)?;
+    let DocumentOperations::Single(operation) = document.operations else {
+        anyhow::bail!("Failed to parse the default value {value}");
+    };
+    let default_value = operation
+        .node
+        .variable_definitions
+        .into_iter()
+        .next()
+        .and_then(|definition| definition.node.default_value)
+        .with_context(|| format!("Failed to parse the default value {value}"))?;
+    Ok(default_value.node)
+}
+
+fn write_typescript_description(
+    typescript: &mut String,
+    description: &Option<String>,
+    indent: &str,
+) {
+    if let Some(description) = description {
+        writeln!(typescript, "{indent}/**").unwrap();
+        for line in description.lines() {
+            writeln!(typescript, "{indent} * {}", line.replace("*/", "*\\/")).unwrap();
+        }
+        writeln!(typescript, "{indent} */").unwrap();
+    }
+}
+
+fn write_typescript_input_values<'a>(
+    typescript: &mut String,
+    values: impl IntoIterator<Item = &'a InputValue>,
+) -> anyhow::Result<()> {
+    for value in values {
+        write_typescript_description(typescript, &value.description, "  ");
+        let optional = if value.type_ref.kind == TypeKind::NonNull {
+            ""
+        } else {
+            "?"
+        };
+        writeln!(
+            typescript,
+            "  {}{optional}: {};",
+            value.name,
+            value.type_ref.to_typescript()?
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "unit_tests/graphql_schema.rs"]
+mod tests;
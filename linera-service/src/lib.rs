@@ -8,9 +8,12 @@
 
 pub mod cli_wrappers;
 pub mod faucet;
+pub mod graphql_schema;
 pub mod node_service;
+mod node_service_grpc;
 pub mod project;
 #[cfg(with_metrics)]
 pub mod prometheus_server;
 pub mod util;
 pub mod wallet;
+pub mod webhooks;
@@ -0,0 +1,449 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The interactive console started by `linera console`.
+
+use std::{path::PathBuf, time::Duration};
+
+use async_trait::async_trait;
+use clap::{CommandFactory as _, Parser as _};
+use colored::Colorize as _;
+use linera_base::{
+    data_types::BlockHeight,
+    identifiers::{ChainId, UserApplicationId},
+};
+use linera_client::{
+    chain_listener::ClientContext as _,
+    client_context::ClientContext,
+    client_options::{ClientCommand, ClientOptions},
+    storage::Runnable,
+};
+use linera_storage::Storage;
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+    Editor, Helper,
+};
+use tracing::debug;
+
+/// The commands handled by the console itself rather than by `linera`.
+const BUILTINS: [&str; 3] = ["use", "exit", "quit"];
+
+/// The name of the file in the configuration directory keeping the history of the console.
+const HISTORY_FILE: &str = "console_history";
+
+/// A line typed in the console, parsed as a `linera` command.
+#[derive(clap::Parser)]
+#[command(name = "linera", no_binary_name = true)]
+struct ConsoleLine {
+    #[command(subcommand)]
+    command: ClientCommand,
+}
+
+/// An interactive console reading `linera` commands, with a persistent current chain.
+pub struct Console {
+    /// The options the commands are run with.
+    options: ClientOptions,
+    editor: Editor<ConsoleHelper, DefaultHistory>,
+    history_path: Option<PathBuf>,
+    /// The chain used by commands that don't specify one.
+    current_chain: Option<ChainId>,
+    /// The next block height of the current chain, as of the last refresh.
+    next_block_height: Option<BlockHeight>,
+}
+
+impl Console {
+    /// Creates a console running commands with the given global options.
+    pub fn new(options: ClientOptions) -> anyhow::Result<Self> {
+        let mut editor = Editor::new()?;
+        editor.set_helper(Some(ConsoleHelper {
+            command: ConsoleLine::command(),
+            chain_ids: Vec::new(),
+            application_ids: Vec::new(),
+        }));
+        let history_path = ClientOptions::config_dir()
+            .ok()
+            .map(|dir| dir.join(HISTORY_FILE));
+        if let Some(path) = &history_path {
+            if let Err(error) = editor.load_history(path) {
+                debug!(%error, "No console history loaded");
+            }
+        }
+        Ok(Console {
+            options,
+            editor,
+            history_path,
+            current_chain: None,
+            next_block_height: None,
+        })
+    }
+
+    /// Reads lines until one of them is a `linera` command, and returns the options to run it
+    /// with. Returns `None` when the user leaves the console.
+    pub async fn read_command(&mut self) -> anyhow::Result<Option<ClientOptions>> {
+        loop {
+            let prompt = match self.current_chain {
+                Some(chain_id) => format!("linera {chain_id:.8}> "),
+                None => "linera> ".to_owned(),
+            };
+            let line = match self.editor.readline(&prompt) {
+                Ok(line) => line,
+                // Ctrl-C discards the current line.
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => return Ok(None),
+                Err(error) => return Err(error.into()),
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            self.editor.add_history_entry(line)?;
+            let Some(words) = shlex::split(line) else {
+                eprintln!("{}", "Unbalanced quotes".red());
+                continue;
+            };
+            // Lines with only a comment have no words.
+            let Some(first_word) = words.first() else {
+                continue;
+            };
+            match first_word.as_str() {
+                "exit" | "quit" => return Ok(None),
+                "use" => {
+                    self.use_chain(&words[1..]).await;
+                    continue;
+                }
+                _ => {}
+            }
+            match ConsoleLine::try_parse_from(words) {
+                Ok(ConsoleLine {
+                    command: ClientCommand::Console,
+                }) => eprintln!("{}", "Already running the console".red()),
+                Ok(ConsoleLine { command }) => {
+                    return Ok(Some(ClientOptions {
+                        command,
+                        ..self.options.clone()
+                    }))
+                }
+                // This also prints the help, if requested.
+                Err(error) => error.print()?,
+            }
+        }
+    }
+
+    /// Prints the outcome of a command.
+    pub fn report(&self, result: anyhow::Result<i32>, elapsed: Duration) {
+        let millis = elapsed.as_millis();
+        match result {
+            Ok(0) => eprintln!("{}", format!("Done in {millis} ms").green()),
+            Ok(code) => eprintln!(
+                "{}",
+                format!("Exited with code {code} after {millis} ms").yellow()
+            ),
+            Err(error) => eprintln!("{}", format!("Error after {millis} ms: {error:#}").red()),
+        }
+    }
+
+    /// Reloads the chains and applications to complete, and reports the new blocks of the
+    /// current chain.
+    pub async fn refresh(&mut self) {
+        let job = RefreshJob(self.options.clone());
+        let result = self.options.run_with_storage(job).await;
+        let snapshot = match result
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result)
+        {
+            Ok(snapshot) => snapshot,
+            Err(error) => {
+                debug!(%error, "Failed to refresh the console");
+                return;
+            }
+        };
+        if let (Some(chain_id), Some(old_height), Some(new_height)) = (
+            snapshot.current_chain,
+            self.next_block_height,
+            snapshot.next_block_height,
+        ) {
+            if snapshot.current_chain == self.current_chain && new_height > old_height {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "Chain {chain_id:.8} has {} new blocks, up to height {}",
+                        new_height.0 - old_height.0,
+                        new_height.0 - 1
+                    )
+                    .cyan()
+                );
+            }
+        }
+        self.current_chain = snapshot.current_chain;
+        self.next_block_height = snapshot.next_block_height;
+        let helper = self.editor.helper_mut().expect("The console has a helper");
+        helper.chain_ids = snapshot.chain_ids;
+        helper.application_ids = snapshot.application_ids;
+    }
+
+    /// Handles `use [CHAIN_ID]`: prints or sets the current chain.
+    async fn use_chain(&mut self, args: &[String]) {
+        let chain_id = match args {
+            [] => {
+                match self.current_chain {
+                    Some(chain_id) => println!("{chain_id}"),
+                    None => eprintln!("{}", "No chain selected".red()),
+                }
+                return;
+            }
+            [chain_id] => match chain_id.parse::<ChainId>() {
+                Ok(chain_id) => chain_id,
+                Err(error) => {
+                    eprintln!("{}", format!("Invalid chain ID: {error}").red());
+                    return;
+                }
+            },
+            _ => {
+                eprintln!("{}", "Usage: use [CHAIN_ID]".red());
+                return;
+            }
+        };
+        let helper = self.editor.helper().expect("The console has a helper");
+        if !helper.chain_ids.contains(&chain_id) {
+            eprintln!("{}", format!("Chain {chain_id} is not in the wallet").red());
+            return;
+        }
+        self.options.default_chain = Some(chain_id);
+        self.refresh().await;
+    }
+}
+
+impl Drop for Console {
+    fn drop(&mut self) {
+        if let Some(path) = &self.history_path {
+            if let Err(error) = self.editor.save_history(path) {
+                debug!(%error, "Failed to save the console history");
+            }
+        }
+    }
+}
+
+/// Completes the command names, options, chain IDs and application IDs typed in the console.
+struct ConsoleHelper {
+    command: clap::Command,
+    chain_ids: Vec<ChainId>,
+    application_ids: Vec<UserApplicationId>,
+}
+
+impl ConsoleHelper {
+    /// Returns the completions of `word`, following the given words of the line.
+    fn candidates(&self, previous: &[&str], word: &str) -> Vec<String> {
+        let mut candidates = Vec::new();
+        match previous.first() {
+            None => {
+                candidates.extend(BUILTINS.iter().map(|name| name.to_string()));
+                candidates.extend(Self::subcommands(&self.command));
+            }
+            Some(&"use") => {
+                candidates.extend(self.chain_ids.iter().map(ChainId::to_string));
+            }
+            Some(_) => {
+                let mut command = &self.command;
+                for name in previous {
+                    if let Some(subcommand) = command.find_subcommand(name) {
+                        command = subcommand;
+                    }
+                }
+                if word.starts_with('-') {
+                    candidates.extend(
+                        command
+                            .get_arguments()
+                            .filter(|argument| !argument.is_hide_set())
+                            .filter_map(|argument| argument.get_long())
+                            .map(|long| format!("--{long}")),
+                    );
+                } else {
+                    candidates.extend(Self::subcommands(command));
+                    candidates.extend(self.chain_ids.iter().map(ChainId::to_string));
+                    candidates.extend(
+                        self.application_ids
+                            .iter()
+                            .map(UserApplicationId::to_string),
+                    );
+                }
+            }
+        }
+        candidates.retain(|candidate| candidate.starts_with(word));
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+
+    /// Returns the names of the visible subcommands of `command`.
+    fn subcommands(command: &clap::Command) -> impl Iterator<Item = String> + '_ {
+        command
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .map(|subcommand| subcommand.get_name().to_owned())
+    }
+}
+
+impl Completer for ConsoleHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _context: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |index| index + 1);
+        let previous = line[..start].split_whitespace().collect::<Vec<_>>();
+        let pairs = self
+            .candidates(&previous, &line[start..])
+            .into_iter()
+            .map(|candidate| Pair {
+                display: candidate.clone(),
+                replacement: candidate,
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ConsoleHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ConsoleHelper {}
+
+impl Validator for ConsoleHelper {}
+
+impl Helper for ConsoleHelper {}
+
+/// What the console knows about the wallet and the current chain.
+#[derive(Default)]
+struct Snapshot {
+    chain_ids: Vec<ChainId>,
+    current_chain: Option<ChainId>,
+    next_block_height: Option<BlockHeight>,
+    application_ids: Vec<UserApplicationId>,
+}
+
+/// Reads a [`Snapshot`] from the wallet and the storage.
+struct RefreshJob(ClientOptions);
+
+#[async_trait]
+impl Runnable for RefreshJob {
+    type Output = anyhow::Result<Snapshot>;
+
+    async fn run<S>(self, storage: S) -> Self::Output
+    where
+        S: Storage + Clone + Send + Sync + 'static,
+    {
+        let RefreshJob(options) = self;
+        let wallet = options.wallet().await?;
+        let context = ClientContext::new(storage, options.clone(), wallet);
+        let mut snapshot = Snapshot {
+            chain_ids: context.wallet().chain_ids(),
+            current_chain: options
+                .default_chain
+                .or_else(|| context.wallet().default_chain()),
+            ..Snapshot::default()
+        };
+        if let Some(chain_id) = snapshot.current_chain {
+            let chain = context
+                .make_chain_client(chain_id)?
+                .chain_state_view()
+                .await?;
+            snapshot.next_block_height = Some(chain.tip_state.get().next_block_height);
+            snapshot.application_ids = chain
+                .execution_state
+                .list_applications()
+                .await?
+                .into_iter()
+                .map(|(application_id, _)| application_id)
+                .collect();
+        }
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory as _;
+    use linera_base::{
+        crypto::CryptoHash,
+        data_types::BlockHeight,
+        identifiers::{BytecodeId, ChainId, MessageId, UserApplicationId},
+    };
+
+    use super::{ConsoleHelper, ConsoleLine};
+
+    fn helper() -> ConsoleHelper {
+        let application_id = UserApplicationId {
+            bytecode_id: BytecodeId::new(
+                CryptoHash::test_hash("contract"),
+                CryptoHash::test_hash("service"),
+            ),
+            creation: MessageId {
+                chain_id: ChainId::root(0),
+                height: BlockHeight::ZERO,
+                index: 0,
+            },
+        };
+        ConsoleHelper {
+            command: ConsoleLine::command(),
+            chain_ids: vec![ChainId::root(1), ChainId::root(0)],
+            application_ids: vec![application_id],
+        }
+    }
+
+    #[test]
+    fn test_complete_commands() {
+        let helper = helper();
+        assert_eq!(
+            helper.candidates(&[], "transfer"),
+            ["transfer", "transfer-batch"]
+        );
+        // Built-in commands are completed, but not hidden ones.
+        assert_eq!(helper.candidates(&[], "e"), ["exit", "export-certificates"]);
+        assert_eq!(helper.candidates(&[], "us"), ["use"]);
+        // Subcommands are completed after their parent command.
+        assert_eq!(helper.candidates(&["wallet"], "sh"), ["show"]);
+        assert!(helper.candidates(&[], "nonexistent").is_empty());
+    }
+
+    #[test]
+    fn test_complete_options() {
+        let helper = helper();
+        let candidates = helper.candidates(&["transfer"], "--");
+        assert!(candidates.contains(&"--from".to_owned()));
+        assert!(candidates.contains(&"--to".to_owned()));
+        assert_eq!(helper.candidates(&["transfer"], "--fr"), ["--from"]);
+        // Options are those of the innermost subcommand.
+        assert_eq!(helper.candidates(&["wallet", "show"], "--sh"), ["--short"]);
+    }
+
+    #[test]
+    fn test_complete_ids() {
+        let helper = helper();
+        let chain0 = ChainId::root(0).to_string();
+        let chain1 = ChainId::root(1).to_string();
+        let mut chain_ids = vec![chain0.clone(), chain1.clone()];
+        chain_ids.sort();
+        // `use` only takes chain IDs.
+        assert_eq!(helper.candidates(&["use"], ""), chain_ids);
+        assert_eq!(helper.candidates(&["use"], &chain1[..8]), [chain1.clone()]);
+        // Other commands also take application IDs.
+        let application_id = helper.application_ids[0].to_string();
+        let candidates = helper.candidates(&["sync"], "");
+        assert!(candidates.contains(&chain0));
+        assert!(candidates.contains(&application_id));
+        assert_eq!(
+            helper.candidates(&["query-balance"], &application_id[..8]),
+            [application_id]
+        );
+    }
+}
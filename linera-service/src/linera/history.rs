@@ -0,0 +1,383 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The chain history printed by `linera query-history`.
+
+use std::fmt;
+
+use linera_base::{
+    data_types::BlockHeight,
+    identifiers::{Destination, GenericApplicationId, Owner, UserApplicationId},
+};
+use linera_chain::{
+    block::Block,
+    data_types::{MessageAction, Origin},
+};
+use linera_execution::{
+    system::{Recipient, SystemMessage, SystemOperation},
+    Message, Operation,
+};
+
+/// An operation executed by a block, or a message it received or sent.
+pub enum HistoryEntry<'a> {
+    /// An operation of the block.
+    Operation(&'a Operation),
+    /// A message received from another chain, and whether it was executed.
+    IncomingMessage {
+        origin: &'a Origin,
+        height: BlockHeight,
+        action: MessageAction,
+        message: &'a Message,
+    },
+    /// A message sent by the block.
+    OutgoingMessage {
+        destination: &'a Destination,
+        message: &'a Message,
+    },
+}
+
+impl<'a> HistoryEntry<'a> {
+    /// Returns the entries of a block, in the order in which they were executed: incoming
+    /// messages first, then operations, then the messages sent. If `application_id` is given,
+    /// only the entries of that application are returned.
+    pub fn of_block(block: &'a Block, application_id: Option<UserApplicationId>) -> Vec<Self> {
+        let body = &block.body;
+        let incoming = body.incoming_bundles.iter().flat_map(|bundle| {
+            bundle
+                .bundle
+                .messages
+                .iter()
+                .map(move |posted| HistoryEntry::IncomingMessage {
+                    origin: &bundle.origin,
+                    height: bundle.bundle.height,
+                    action: bundle.action,
+                    message: &posted.message,
+                })
+        });
+        let operations = body.operations.iter().map(HistoryEntry::Operation);
+        let outgoing =
+            body.messages
+                .iter()
+                .flatten()
+                .map(|outgoing| HistoryEntry::OutgoingMessage {
+                    destination: &outgoing.destination,
+                    message: &outgoing.message,
+                });
+        incoming
+            .chain(operations)
+            .chain(outgoing)
+            .filter(|entry| {
+                application_id.map_or(true, |application_id| {
+                    entry.application_id() == GenericApplicationId::User(application_id)
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the application the operation or message belongs to.
+    fn application_id(&self) -> GenericApplicationId {
+        match self {
+            HistoryEntry::Operation(operation) => operation.application_id(),
+            HistoryEntry::IncomingMessage { message, .. }
+            | HistoryEntry::OutgoingMessage { message, .. } => message.application_id(),
+        }
+    }
+}
+
+impl fmt::Display for HistoryEntry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryEntry::Operation(operation) => {
+                write!(f, "operation: {}", OperationSummary(operation))
+            }
+            HistoryEntry::IncomingMessage {
+                origin,
+                height,
+                action,
+                message,
+            } => {
+                write!(
+                    f,
+                    "message from {origin} at height {height}: {}",
+                    MessageSummary(message)
+                )?;
+                if *action == MessageAction::Reject {
+                    write!(f, " (rejected)")?;
+                }
+                Ok(())
+            }
+            HistoryEntry::OutgoingMessage {
+                destination,
+                message,
+            } => {
+                let destination = match destination {
+                    Destination::Recipient(chain_id) => chain_id.to_string(),
+                    Destination::Subscribers(name) => format!("subscribers of {name:?}"),
+                };
+                write!(f, "message to {destination}: {}", MessageSummary(message))
+            }
+        }
+    }
+}
+
+/// A short description of an operation.
+struct OperationSummary<'a>(&'a Operation);
+
+impl fmt::Display for OperationSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Operation::System(SystemOperation::Transfer {
+                owner,
+                recipient,
+                amount,
+            }) => write!(
+                f,
+                "transfer {amount} from {} to {}",
+                OwnerSummary(owner.as_ref()),
+                RecipientSummary(recipient)
+            ),
+            Operation::System(SystemOperation::Claim {
+                owner,
+                target_id,
+                recipient,
+                amount,
+            }) => write!(
+                f,
+                "claim {amount} from {owner} on chain {target_id} for {}",
+                RecipientSummary(recipient)
+            ),
+            Operation::System(SystemOperation::OpenChain(config)) => {
+                write!(f, "open a chain with {}", config.balance)
+            }
+            Operation::System(SystemOperation::CloseChain) => write!(f, "close the chain"),
+            Operation::System(operation) => write!(f, "{operation:?}"),
+            Operation::User {
+                application_id,
+                bytes,
+            } => write!(f, "{} bytes for application {application_id}", bytes.len()),
+        }
+    }
+}
+
+/// A short description of a message.
+struct MessageSummary<'a>(&'a Message);
+
+impl fmt::Display for MessageSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Message::System(SystemMessage::Credit { target, amount, .. }) => match target {
+                Some(owner) => write!(f, "credit {amount} to {owner}"),
+                None => write!(f, "credit {amount} to the chain"),
+            },
+            Message::System(SystemMessage::Withdraw {
+                owner,
+                amount,
+                recipient,
+            }) => write!(
+                f,
+                "withdraw {amount} from {owner} to {}",
+                RecipientSummary(recipient)
+            ),
+            Message::System(SystemMessage::OpenChain(config)) => {
+                write!(f, "open a chain with {}", config.balance)
+            }
+            Message::System(message) => write!(f, "{message:?}"),
+            Message::User {
+                application_id,
+                bytes,
+            } => write!(f, "{} bytes for application {application_id}", bytes.len()),
+        }
+    }
+}
+
+/// The owner of a transferred amount: an account, or the chain itself.
+struct OwnerSummary<'a>(Option<&'a Owner>);
+
+impl fmt::Display for OwnerSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(owner) => write!(f, "{owner}"),
+            None => write!(f, "the chain"),
+        }
+    }
+}
+
+/// The recipient of a transfer.
+struct RecipientSummary<'a>(&'a Recipient);
+
+impl fmt::Display for RecipientSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Recipient::Burn => write!(f, "nobody (burnt)"),
+            Recipient::Account(account) => write!(f, "{account}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::{
+        crypto::CryptoHash,
+        data_types::{Amount, BlockHeight, Timestamp},
+        identifiers::{Account, BytecodeId, ChainId, Destination, MessageId, UserApplicationId},
+    };
+    use linera_chain::{
+        block::Block,
+        data_types::{
+            BlockExecutionOutcome, IncomingBundle, MessageAction, MessageBundle, Origin,
+            OutgoingMessage,
+        },
+        test::{make_first_block, BlockTestExt as _, MessageTestExt as _},
+    };
+    use linera_execution::{system::SystemMessage, Message, MessageKind, Operation};
+
+    use super::HistoryEntry;
+
+    fn application_id() -> UserApplicationId {
+        UserApplicationId {
+            bytecode_id: BytecodeId::new(
+                CryptoHash::test_hash("contract"),
+                CryptoHash::test_hash("service"),
+            ),
+            creation: MessageId {
+                chain_id: ChainId::root(0),
+                height: BlockHeight::ZERO,
+                index: 0,
+            },
+        }
+    }
+
+    fn user_message(bytes: Vec<u8>) -> Message {
+        Message::User {
+            application_id: application_id(),
+            bytes,
+        }
+    }
+
+    fn credit(amount: Amount) -> Message {
+        Message::System(SystemMessage::Credit {
+            target: None,
+            amount,
+            source: None,
+        })
+    }
+
+    fn outgoing(recipient: ChainId, message: Message) -> OutgoingMessage {
+        OutgoingMessage {
+            destination: Destination::Recipient(recipient),
+            authenticated_signer: None,
+            grant: Amount::ZERO,
+            refund_grant_to: None,
+            kind: MessageKind::Simple,
+            expires_at: None,
+            message,
+        }
+    }
+
+    /// Returns a block receiving a credit and a user message, executing a transfer and a
+    /// user operation, and sending a credit and a user message.
+    fn make_block() -> Block {
+        let chain_id = ChainId::root(1);
+        let bundle = IncomingBundle {
+            origin: Origin::chain(ChainId::root(2)),
+            bundle: MessageBundle {
+                certificate_hash: CryptoHash::test_hash("certificate"),
+                height: BlockHeight(3),
+                transaction_index: 0,
+                timestamp: Timestamp::from(0),
+                messages: vec![
+                    credit(Amount::ONE).to_posted(0, MessageKind::Tracked),
+                    user_message(vec![1]).to_posted(1, MessageKind::Simple),
+                ],
+            },
+            action: MessageAction::Reject,
+        };
+        let proposed_block = make_first_block(chain_id)
+            .with_incoming_bundle(bundle)
+            .with_simple_transfer(ChainId::root(2), Amount::ONE)
+            .with_operation(Operation::User {
+                application_id: application_id(),
+                bytes: vec![2, 3],
+            });
+        let outcome = BlockExecutionOutcome {
+            messages: vec![
+                vec![],
+                vec![outgoing(ChainId::root(2), credit(Amount::ONE))],
+                vec![outgoing(ChainId::root(3), user_message(vec![4, 5, 6]))],
+            ],
+            state_hash: CryptoHash::test_hash("state"),
+            oracle_responses: vec![vec![]; 3],
+            events: vec![vec![]; 3],
+        };
+        Block::new(proposed_block, outcome)
+    }
+
+    #[test]
+    fn test_entries_of_block() {
+        let block = make_block();
+        let origin = Origin::chain(ChainId::root(2));
+        let application_id = application_id();
+        let entries = HistoryEntry::of_block(&block, None)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        // Incoming messages come first, then operations, then the messages sent.
+        assert_eq!(
+            entries,
+            [
+                format!(
+                    "message from {origin} at height 3: credit {} to the chain (rejected)",
+                    Amount::ONE
+                ),
+                format!(
+                    "message from {origin} at height 3: 1 bytes for application \
+                    {application_id} (rejected)"
+                ),
+                format!(
+                    "operation: transfer {} from the chain to {}",
+                    Amount::ONE,
+                    Account::chain(ChainId::root(2))
+                ),
+                format!("operation: 2 bytes for application {application_id}"),
+                format!(
+                    "message to {}: credit {} to the chain",
+                    ChainId::root(2),
+                    Amount::ONE
+                ),
+                format!(
+                    "message to {}: 3 bytes for application {application_id}",
+                    ChainId::root(3)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entries_of_application() {
+        let block = make_block();
+        let entries = HistoryEntry::of_block(&block, Some(application_id()));
+        assert_eq!(entries.len(), 3);
+        assert!(matches!(
+            entries[0],
+            HistoryEntry::IncomingMessage {
+                message: Message::User { .. },
+                ..
+            }
+        ));
+        assert!(matches!(
+            entries[1],
+            HistoryEntry::Operation(Operation::User { .. })
+        ));
+        assert!(matches!(
+            entries[2],
+            HistoryEntry::OutgoingMessage {
+                message: Message::User { .. },
+                ..
+            }
+        ));
+        // Other applications have no entries in the block.
+        let mut other_application_id = application_id();
+        other_application_id.creation.index = 1;
+        assert!(HistoryEntry::of_block(&block, Some(other_application_id)).is_empty());
+    }
+}
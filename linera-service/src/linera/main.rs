@@ -6,7 +6,14 @@
 #![deny(clippy::large_futures)]
 
 use std::{
-    borrow::Cow, collections::HashMap, env, path::PathBuf, process, sync::Arc, time::Instant,
+    borrow::Cow,
+    collections::HashMap,
+    env,
+    io::{self, Write as _},
+    path::PathBuf,
+    process,
+    sync::Arc,
+    time::Instant,
 };
 
 use anyhow::{anyhow, bail, ensure, Context};
@@ -16,23 +23,29 @@ use colored::Colorize;
 use futures::{lock::Mutex, FutureExt as _, StreamExt};
 use linera_base::{
     crypto::{CryptoHash, CryptoRng},
-    data_types::{ApplicationPermissions, Timestamp},
+    data_types::{Amount, ApplicationPermissions, BlockHeight, TimeDelta, Timestamp},
     identifiers::{AccountOwner, ChainDescription, ChainId, MessageId, Owner},
     ownership::ChainOwnership,
 };
+use linera_chain::data_types::{BlockProposal, ProposalContent};
 use linera_client::{
     chain_listener::ClientContext as _,
     client_context::ClientContext,
     client_options::{
-        ClientCommand, ClientOptions, DatabaseToolCommand, NetCommand, ProjectCommand,
-        WalletCommand,
+        ChainCommand, ClientCommand, ClientOptions, DatabaseToolCommand, MultiOwnerCommand,
+        NetCommand, ProfileCommand, ProjectCommand, ServiceCommand, WalletCommand,
     },
     config::{CommitteeConfig, GenesisConfig},
+    inspect::ChainStateEntry,
+    payouts::{self, Payout},
     persistent::{self, Persist},
+    profile::Profile,
     storage::Runnable,
-    wallet::{UserChain, Wallet},
+    wallet::{ExportedChain, UserChain, Wallet},
 };
 use linera_core::{
+    audit::{self, AuditError, AuditReport, CertificateArchive},
+    client::ChainClient,
     data_types::{ChainInfoQuery, ClientOutcome},
     node::ValidatorNodeProvider,
     remote_node::RemoteNode,
@@ -40,12 +53,14 @@ use linera_core::{
     JoinSetExt as _,
 };
 use linera_execution::{
-    committee::{Committee, ValidatorName, ValidatorState},
-    Message, ResourceControlPolicy, SystemMessage,
+    committee::{Committee, Epoch, ValidatorKeyRotation, ValidatorName, ValidatorState},
+    system::{Recipient, SystemOperation},
+    ApplicationCallPolicy, FuelSchedule, Message, Operation, ResourceControlPolicy, SystemMessage,
 };
 use linera_service::{
     cli_wrappers,
     faucet::FaucetService,
+    graphql_schema::IntrospectedSchema,
     node_service::NodeService,
     project::{self, Project},
     util, wallet,
@@ -56,18 +71,32 @@ use serde_json::Value;
 use tokio::task::JoinSet;
 use tracing::{debug, error, info, warn, Instrument as _};
 
+mod console;
+mod history;
 mod net_up_utils;
+mod watch;
 
 #[cfg(feature = "benchmark")]
 use {
     linera_base::hashed::Hashed,
     linera_chain::types::ConfirmedBlock,
+    linera_client::benchmark::{BenchmarkOperation, OperationMix},
     linera_core::data_types::ChainInfoResponse,
     linera_rpc::{HandleConfirmedCertificateRequest, RpcMessage},
     std::collections::HashSet,
 };
 
-use crate::persistent::PersistExt as _;
+use crate::{
+    console::Console, history::HistoryEntry, persistent::PersistExt as _, watch::ChainActivity,
+};
+
+/// The number of certificates read at once by `linera query-history`.
+const HISTORY_BATCH_SIZE: usize = 100;
+
+/// The error when a command needs a validator address, and neither the command line nor the
+/// profile has one.
+const MISSING_VALIDATOR_ADDRESS: &str =
+    "Missing validator address: pass one, or use a profile with a validator";
 
 #[cfg(feature = "benchmark")]
 fn deserialize_response(response: RpcMessage) -> Option<ChainInfoResponse> {
@@ -99,6 +128,35 @@ fn read_json(string: Option<String>, path: Option<PathBuf>) -> anyhow::Result<Ve
     Ok(serde_json::to_vec(&value)?)
 }
 
+/// Returns a description of the block proposal, for the signer to review it.
+fn describe_proposal(content: &ProposalContent) -> String {
+    let block = &content.block;
+    let mut description = format!(
+        "Chain: {}\nHeight: {}\nRound: {}\nEpoch: {}\nTimestamp: {}\nPriority fee: {}\n",
+        block.chain_id,
+        block.height,
+        content.round,
+        block.epoch,
+        block.timestamp,
+        block.priority_fee,
+    );
+    if let Some(signer) = &block.authenticated_signer {
+        description.push_str(&format!("Signer: {signer}\n"));
+    }
+    for bundle in &block.incoming_bundles {
+        for message in &bundle.bundle.messages {
+            description.push_str(&format!(
+                "{:?} message from {}: {:?}\n",
+                bundle.action, bundle.origin, message.message
+            ));
+        }
+    }
+    for operation in &block.operations {
+        description.push_str(&format!("Operation: {operation:?}\n"));
+    }
+    description
+}
+
 #[async_trait]
 impl Runnable for Job {
     type Output = anyhow::Result<()>;
@@ -118,6 +176,7 @@ impl Runnable for Job {
                 sender,
                 recipient,
                 amount,
+                offline,
             } => {
                 let chain_client = context.make_chain_client(sender.chain_id)?;
                 let owner = match sender.owner {
@@ -127,6 +186,41 @@ impl Runnable for Job {
                     }
                     None => None,
                 };
+                if let Some(path) = offline {
+                    // The block must be signed by the owner of the account, or by an owner of
+                    // the chain for a transfer from the chain's balance.
+                    let signer = match owner {
+                        Some(owner) => owner,
+                        None => {
+                            let ownership = chain_client.prepare_chain().await?.manager.ownership;
+                            let mut owners = ownership.all_owners();
+                            match (owners.next(), owners.next()) {
+                                (Some(owner), None) => *owner,
+                                _ => bail!(
+                                    "Chain {} does not have a single owner; \
+                                     please specify the sending account's owner",
+                                    sender.chain_id
+                                ),
+                            }
+                        }
+                    };
+                    let operation = Operation::System(SystemOperation::Transfer {
+                        owner,
+                        recipient: Recipient::Account(recipient),
+                        amount,
+                    });
+                    let content = chain_client
+                        .prepare_unsigned_proposal(vec![operation], signer)
+                        .await
+                        .context("Failed to prepare the transfer")?;
+                    fs_err::write(&path, serde_json::to_vec_pretty(&content)?)?;
+                    info!(
+                        "Unsigned block proposal for {} written to {}",
+                        signer,
+                        path.display()
+                    );
+                    return Ok(());
+                }
                 info!(
                     "Starting transfer of {} native tokens from {} to {}",
                     amount, sender, recipient
@@ -148,6 +242,116 @@ impl Runnable for Job {
                 debug!("{:?}", certificate);
             }
 
+            TransferBatch {
+                sender,
+                file,
+                max_transfers_per_block,
+            } => {
+                let chain_client = context.make_chain_client(sender.chain_id)?;
+                let owner = match sender.owner {
+                    Some(AccountOwner::User(owner)) => Some(owner),
+                    Some(AccountOwner::Application(_)) => {
+                        bail!("Can't transfer from an application account")
+                    }
+                    None => None,
+                };
+                let payouts = Payout::read(&file)?;
+                ensure!(!payouts.is_empty(), "No transfers in {}", file.display());
+                let total = payouts
+                    .iter()
+                    .try_fold(Amount::ZERO, |total, payout| total.try_add(payout.amount))?;
+                let balance = match sender.owner {
+                    Some(owner) => chain_client.query_owner_balance(owner).await?,
+                    None => chain_client.query_balance().await?,
+                };
+                ensure!(
+                    total <= balance,
+                    "The transfers add up to {total} but the balance of {sender} is {balance}"
+                );
+                let committee = chain_client.local_committee().await?;
+                let policy = committee.policy();
+                let max_size = policy
+                    .maximum_block_proposal_size
+                    .min(policy.maximum_executed_block_size);
+                let batches = payouts::batches(&payouts, owner, max_transfers_per_block, max_size);
+                info!(
+                    "Starting {} transfers of {} native tokens in total from {} in {} blocks",
+                    payouts.len(),
+                    total,
+                    sender,
+                    batches.len()
+                );
+                let time_start = Instant::now();
+                let mut failed = None;
+                for batch in batches {
+                    let outcome = if failed.is_some() {
+                        "skipped".to_owned()
+                    } else {
+                        let operations = payouts[batch.clone()]
+                            .iter()
+                            .map(|payout| payout.operation(owner))
+                            .collect::<Vec<_>>();
+                        let start_height = chain_client.next_block_height();
+                        let result =
+                            context
+                                .apply_client_command(&chain_client, |chain_client| {
+                                    let operations = operations.clone();
+                                    let chain_client = chain_client.clone();
+                                    async move {
+                                        chain_client.execute_operations(operations, vec![]).await
+                                    }
+                                })
+                                .await;
+                        match result {
+                            Ok(certificate) => certificate.hash().to_string(),
+                            Err(error) => {
+                                // The block may have been committed anyway, e.g. after a
+                                // timeout, so the chain is checked before reporting a failure.
+                                match Self::find_committed_block(
+                                    &chain_client,
+                                    start_height,
+                                    &operations,
+                                )
+                                .await
+                                {
+                                    Ok(Some(hash)) => {
+                                        warn!(%error, "Transfers {batch:?} were committed anyway");
+                                        hash.to_string()
+                                    }
+                                    Ok(None) => {
+                                        error!(%error, "Failed to send transfers {batch:?}");
+                                        failed = Some(batch.start);
+                                        "failed".to_owned()
+                                    }
+                                    Err(sync_error) => {
+                                        error!(
+                                            %error,
+                                            %sync_error,
+                                            "Failed to send transfers {batch:?} and to check \
+                                            whether they were committed"
+                                        );
+                                        failed = Some(batch.start);
+                                        "unknown".to_owned()
+                                    }
+                                }
+                            }
+                        }
+                    };
+                    for payout in &payouts[batch] {
+                        println!("{},{},{}", payout.recipient, payout.amount, outcome);
+                    }
+                }
+                let time_total = time_start.elapsed();
+                if let Some(index) = failed {
+                    bail!(
+                        "Sent {index} of {} transfers in {} ms",
+                        payouts.len(),
+                        time_total.as_millis()
+                    );
+                }
+                info!("Transfers confirmed after {} ms", time_total.as_millis());
+            }
+
             OpenChain {
                 chain_id,
                 owner,
@@ -380,6 +584,53 @@ impl Runnable for Job {
                 );
             }
 
+            QueryHistory {
+                chain_id,
+                application,
+                from_height,
+            } => {
+                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                // Chains that are not in the wallet are synchronized through the default chain.
+                let client_chain_id = if context.wallet().get(chain_id).is_some() {
+                    chain_id
+                } else {
+                    context.default_chain()
+                };
+                let chain_client = context.make_chain_client(client_chain_id)?;
+                info!("Synchronizing the history of chain {}", chain_id);
+                if let Err(error) = chain_client
+                    .synchronize_chain_from_validators(chain_id)
+                    .await
+                {
+                    warn!(%error, "Failed to synchronize the chain; showing the local history");
+                }
+                let hashes = storage
+                    .load_chain(chain_id)
+                    .await?
+                    .confirmed_log
+                    .read(usize::try_from(from_height)?..)
+                    .await?;
+                for hashes in hashes.chunks(HISTORY_BATCH_SIZE) {
+                    let certificates = storage.read_certificates(hashes.iter().copied()).await?;
+                    for certificate in certificates {
+                        let block = certificate.block();
+                        let entries = HistoryEntry::of_block(block, application);
+                        if entries.is_empty() && application.is_some() {
+                            continue;
+                        }
+                        println!(
+                            "Block {} ({}) at {}",
+                            block.header.height,
+                            certificate.hash(),
+                            block.header.timestamp
+                        );
+                        for entry in entries {
+                            println!("    {entry}");
+                        }
+                    }
+                }
+            }
+
             QueryValidator {
                 address,
                 chain_id,
@@ -387,6 +638,7 @@ impl Runnable for Job {
             } => {
                 use linera_core::node::ValidatorNode as _;
 
+                let address = address.context(MISSING_VALIDATOR_ADDRESS)?;
                 let node = context.make_node_provider().make_node(&address)?;
                 match node.get_version_info().await {
                     Ok(version_info)
@@ -525,6 +777,7 @@ impl Runnable for Job {
                     chains.push(context.default_chain());
                 }
 
+                let address = address.context(MISSING_VALIDATOR_ADDRESS)?;
                 let validator = context.make_node_provider().make_node(&address)?;
 
                 for chain_id in chains {
@@ -536,6 +789,7 @@ impl Runnable for Job {
 
             command @ (SetValidator { .. }
             | RemoveValidator { .. }
+            | RotateValidatorKey { .. }
             | ResourceControlPolicy { .. }) => {
                 use linera_core::node::ValidatorNode as _;
 
@@ -577,6 +831,16 @@ impl Runnable for Job {
                         ),
                     }
                 }
+                let rotation = match &command {
+                    RotateValidatorKey { rotation_path } => {
+                        let rotation: ValidatorKeyRotation = util::read_json(rotation_path)?;
+                        rotation
+                            .check()
+                            .context("The key rotation is not signed by the old and the new key")?;
+                        Some(rotation)
+                    }
+                    _ => None,
+                };
                 let chain_client =
                     context.make_chain_client(context.wallet.genesis_admin_chain())?;
                 let n = context
@@ -587,10 +851,19 @@ impl Runnable for Job {
                     .map(|c| c.block().messages().len())
                     .sum::<usize>();
                 info!("Subscribed {} chains to new committees", n);
+                if let Some(rotation) = &rotation {
+                    let committee = chain_client.local_committee().await?;
+                    ensure!(
+                        !committee.validators().contains_key(&rotation.new_name),
+                        "The new key {} of the rotation is already in the committee",
+                        rotation.new_name
+                    );
+                }
                 let maybe_certificate = context
                     .apply_client_command(&chain_client, |chain_client| {
                         let chain_client = chain_client.clone();
                         let command = command.clone();
+                        let rotation = rotation.clone();
                         async move {
                             // Create the new committee.
                             let mut committee = chain_client.local_committee().await.unwrap();
@@ -617,6 +890,15 @@ impl Runnable for Job {
                                         return Ok(ClientOutcome::Committed(None));
                                     }
                                 }
+                                RotateValidatorKey { .. } => {
+                                    let rotation =
+                                        rotation.expect("the key rotation should have been read");
+                                    let Some(state) = validators.remove(&rotation.old_name) else {
+                                        warn!("Skipping key rotation of nonexistent validator");
+                                        return Ok(ClientOutcome::Committed(None));
+                                    };
+                                    validators.insert(rotation.new_name, state);
+                                }
                                 ResourceControlPolicy {
                                     block,
                                     fuel_unit,
@@ -625,6 +907,7 @@ impl Runnable for Job {
                                     byte_read,
                                     byte_written,
                                     byte_stored,
+                                    byte_stored_per_day,
                                     operation,
                                     operation_byte,
                                     message,
@@ -637,6 +920,18 @@ impl Runnable for Job {
                                     maximum_block_proposal_size,
                                     maximum_bytes_read_per_block,
                                     maximum_bytes_written_per_block,
+                                    maximum_wasm_memory_size,
+                                    maximum_wasm_table_elements,
+                                    maximum_bytes_stored_per_application,
+                                    storage_rent_grace_period,
+                                    instruction_fuel,
+                                    structural_instruction_fuel,
+                                    memory_access_fuel,
+                                    call_fuel,
+                                    memory_grow_page_fuel,
+                                    base_fee_change_denominator,
+                                    maximum_scheduled_callbacks_per_application,
+                                    maximum_callbacks_per_operation,
                                 } => {
                                     if let Some(block) = block {
                                         policy.block = block;
@@ -659,6 +954,9 @@ impl Runnable for Job {
                                     if let Some(byte_stored) = byte_stored {
                                         policy.byte_stored = byte_stored;
                                     }
+                                    if let Some(byte_stored_per_day) = byte_stored_per_day {
+                                        policy.byte_stored_per_day = byte_stored_per_day;
+                                    }
                                     if let Some(operation) = operation {
                                         policy.operation = operation;
                                     }
@@ -707,6 +1005,65 @@ impl Runnable for Job {
                                         policy.maximum_bytes_written_per_block =
                                             maximum_bytes_written_per_block;
                                     }
+                                    if let Some(maximum_wasm_memory_size) = maximum_wasm_memory_size
+                                    {
+                                        policy.maximum_wasm_memory_size = maximum_wasm_memory_size;
+                                    }
+                                    if let Some(maximum_wasm_table_elements) =
+                                        maximum_wasm_table_elements
+                                    {
+                                        policy.maximum_wasm_table_elements =
+                                            maximum_wasm_table_elements;
+                                    }
+                                    if let Some(maximum_bytes_stored_per_application) =
+                                        maximum_bytes_stored_per_application
+                                    {
+                                        policy.maximum_bytes_stored_per_application =
+                                            maximum_bytes_stored_per_application;
+                                    }
+                                    if let Some(storage_rent_grace_period) =
+                                        storage_rent_grace_period
+                                    {
+                                        policy.storage_rent_grace_period =
+                                            storage_rent_grace_period;
+                                    }
+                                    let fuel_schedule = &mut policy.fuel_schedule;
+                                    if let Some(instruction_fuel) = instruction_fuel {
+                                        fuel_schedule.instruction = instruction_fuel;
+                                    }
+                                    if let Some(structural_instruction_fuel) =
+                                        structural_instruction_fuel
+                                    {
+                                        fuel_schedule.structural_instruction =
+                                            structural_instruction_fuel;
+                                    }
+                                    if let Some(memory_access_fuel) = memory_access_fuel {
+                                        fuel_schedule.memory_access = memory_access_fuel;
+                                    }
+                                    if let Some(call_fuel) = call_fuel {
+                                        fuel_schedule.call = call_fuel;
+                                    }
+                                    if let Some(memory_grow_page_fuel) = memory_grow_page_fuel {
+                                        fuel_schedule.memory_grow_page = memory_grow_page_fuel;
+                                    }
+                                    if let Some(base_fee_change_denominator) =
+                                        base_fee_change_denominator
+                                    {
+                                        policy.base_fee_change_denominator =
+                                            base_fee_change_denominator;
+                                    }
+                                    if let Some(maximum_scheduled_callbacks_per_application) =
+                                        maximum_scheduled_callbacks_per_application
+                                    {
+                                        policy.maximum_scheduled_callbacks_per_application =
+                                            maximum_scheduled_callbacks_per_application;
+                                    }
+                                    if let Some(maximum_callbacks_per_operation) =
+                                        maximum_callbacks_per_operation
+                                    {
+                                        policy.maximum_callbacks_per_operation =
+                                            maximum_callbacks_per_operation;
+                                    }
                                     info!("{policy}");
                                     if committee.policy() == &policy {
                                         return Ok(ClientOutcome::Committed(None));
@@ -764,7 +1121,21 @@ impl Runnable for Job {
                 tokens_per_chain,
                 transactions_per_block,
                 fungible_application_id,
+                operation_mix,
+                blocks_per_chain,
             } => {
+                let operation_mix = operation_mix.unwrap_or_else(|| {
+                    OperationMix::single(match fungible_application_id {
+                        Some(_) => BenchmarkOperation::FungibleTransfer,
+                        None => BenchmarkOperation::NativeTransfer,
+                    })
+                });
+                ensure!(
+                    fungible_application_id.is_some()
+                        || !operation_mix.contains(BenchmarkOperation::FungibleTransfer),
+                    "Fungible transfers require a --fungible-application-id"
+                );
+
                 // Below all block proposals are supposed to succeed without retries, we
                 // must make sure that all incoming payments have been accepted on-chain
                 // and that no validator is missing user certificates.
@@ -780,12 +1151,29 @@ impl Runnable for Job {
                         .await?;
                 }
 
+                if let Some(blocks_per_chain) = blocks_per_chain {
+                    let report = context
+                        .run_benchmark_load(
+                            &key_pairs,
+                            blocks_per_chain,
+                            transactions_per_block,
+                            &operation_mix,
+                            fungible_application_id,
+                            max_in_flight,
+                        )
+                        .await?;
+                    context.save_wallet().await?;
+                    println!("{report}");
+                    return Ok(());
+                }
+
                 // For this command, we create proposals and gather certificates without using
                 // the client library. We update the wallet storage at the end using a local node.
                 info!("Starting benchmark phase 1 (block proposals)");
                 let proposals = context.make_benchmark_block_proposals(
                     &key_pairs,
                     transactions_per_block,
+                    &operation_mix,
                     fungible_application_id,
                 );
                 let num_proposal = proposals.len();
@@ -857,35 +1245,148 @@ impl Runnable for Job {
                 context.save_wallet().await?;
             }
 
-            Watch { chain_id, raw } => {
+            Watch {
+                mut chain_ids,
+                raw,
+                json,
+            } => {
+                if chain_ids.is_empty() {
+                    chain_ids.push(context.default_chain());
+                }
                 let mut join_set = JoinSet::new();
-                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
-                let chain_client = context.make_chain_client(chain_id)?;
-                info!("Watching for notifications for chain {:?}", chain_id);
-                let (listener, _listen_handle, mut notifications) = chain_client.listen().await?;
-                join_set.spawn_task(listener);
+                let mut chain_clients = HashMap::new();
+                let mut listen_handles = Vec::new();
+                let mut streams = Vec::new();
+                for chain_id in chain_ids {
+                    let chain_client = context.make_chain_client(chain_id)?;
+                    info!("Watching for notifications for chain {:?}", chain_id);
+                    let (listener, listen_handle, notifications) = chain_client.listen().await?;
+                    join_set.spawn_task(listener);
+                    listen_handles.push(listen_handle);
+                    streams.push(notifications);
+                    chain_clients.insert(chain_id, chain_client);
+                }
+                let mut notifications = futures::stream::select_all(streams);
                 while let Some(notification) = notifications.next().await {
-                    if let Reason::NewBlock { .. } = notification.reason {
-                        context.update_and_save_wallet(&chain_client).await?;
-                    }
                     if raw {
                         println!("{}", serde_json::to_string(&notification)?);
                     }
+                    let chain_client = &chain_clients[&notification.chain_id];
+                    let block;
+                    let activity = match &notification.reason {
+                        Reason::NewBlock { hash, .. } => {
+                            context.update_and_save_wallet(chain_client).await?;
+                            block = chain_client.read_hashed_confirmed_block(*hash).await?;
+                            ChainActivity::new_block(&block)
+                        }
+                        Reason::NewIncomingBundle { origin, height } => {
+                            ChainActivity::NewIncomingBundle {
+                                chain_id: notification.chain_id,
+                                origin,
+                                height: *height,
+                            }
+                        }
+                        Reason::NewRound { .. } => continue,
+                    };
+                    if json {
+                        println!("{}", serde_json::to_string(&activity)?);
+                    } else if !raw {
+                        println!("{activity}");
+                    }
                 }
                 info!("Notification stream ended.");
             }
 
-            Service { config, port } => {
-                let default_chain = context.wallet().default_chain();
-                let service = NodeService::new(config, port, default_chain, storage, context).await;
+            Service {
+                command: None,
+                config,
+                port,
+                webhooks,
+                storage_stats,
+            } => {
+                let default_chain = context
+                    .default_chain
+                    .or_else(|| context.wallet().default_chain());
+                let genesis_config_hash = context.wallet().genesis_config().hash();
+                let service = NodeService::new(config, port, default_chain, storage, context)
+                    .await
+                    .with_webhooks(webhooks)
+                    .with_genesis_config_hash(genesis_config_hash)
+                    .with_storage_stats(storage_stats);
                 service.run().await?;
             }
 
+            Service {
+                command: Some(command),
+                config,
+                port,
+                webhooks: _,
+                storage_stats: _,
+            } => {
+                let (options, output_dir) = match command {
+                    ServiceCommand::Schema { options } => (options, None),
+                    ServiceCommand::Codegen {
+                        options,
+                        output_dir,
+                    } => (options, Some(output_dir)),
+                };
+                let chain_id = options.chain_id.unwrap_or_else(|| context.default_chain());
+                let application_ids = match options.application_id {
+                    Some(application_id) => vec![application_id],
+                    None => context
+                        .make_chain_client(chain_id)?
+                        .chain_state_view()
+                        .await?
+                        .execution_state
+                        .list_applications()
+                        .await?
+                        .into_iter()
+                        .map(|(application_id, _)| application_id)
+                        .collect(),
+                };
+                let default_chain = context
+                    .default_chain
+                    .or_else(|| context.wallet().default_chain());
+                let service = NodeService::new(config, port, default_chain, storage, context).await;
+                let mut schemas = Vec::new();
+                if options.application_id.is_none() {
+                    let schema = IntrospectedSchema::of(&service.schema()).await?;
+                    schemas.push(("node_service".to_owned(), schema));
+                }
+                for application_id in application_ids {
+                    match service.application_schema(application_id, chain_id).await {
+                        Ok(schema) => schemas.push((application_id.to_string(), schema)),
+                        // Not all applications have a GraphQL service.
+                        Err(error) if options.application_id.is_none() => {
+                            warn!("Skipping the schema of application {application_id}: {error}")
+                        }
+                        Err(error) => return Err(error),
+                    }
+                }
+                match output_dir {
+                    None => {
+                        for (name, schema) in schemas {
+                            println!("# {name}\n\n{}", schema.to_sdl()?);
+                        }
+                    }
+                    Some(output_dir) => {
+                        fs_err::create_dir_all(&output_dir)?;
+                        for (name, schema) in schemas {
+                            let path = output_dir.join(format!("{name}.graphql"));
+                            fs_err::write(path, schema.to_sdl()?)?;
+                            let path = output_dir.join(format!("{name}.ts"));
+                            fs_err::write(path, schema.to_typescript()?)?;
+                        }
+                    }
+                }
+            }
+
             Faucet {
                 chain_id,
                 port,
                 amount,
                 limit_rate_until,
+                limits,
                 config,
             } => {
                 let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
@@ -905,6 +1406,7 @@ impl Runnable for Job {
                     amount,
                     end_timestamp,
                     genesis_config,
+                    limits,
                     config,
                     storage,
                 )
@@ -965,6 +1467,7 @@ impl Runnable for Job {
                 json_argument,
                 json_argument_path,
                 required_application_ids,
+                call_policy_config,
             } => {
                 let start_time = Instant::now();
                 let creator = creator.unwrap_or_else(|| context.default_chain());
@@ -972,6 +1475,7 @@ impl Runnable for Job {
                 let chain_client = context.make_chain_client(creator)?;
                 let parameters = read_json(json_parameters, json_parameters_path)?;
                 let argument = read_json(json_argument, json_argument_path)?;
+                let call_policy = ApplicationCallPolicy::from(call_policy_config);
 
                 info!("Synchronizing");
                 let chain_client = chain_client;
@@ -983,13 +1487,15 @@ impl Runnable for Job {
                         let argument = argument.clone();
                         let chain_client = chain_client.clone();
                         let required_application_ids = required_application_ids.clone();
+                        let call_policy = call_policy.clone();
                         async move {
                             chain_client
-                                .create_application_untyped(
+                                .create_application_with_call_policy_untyped(
                                     bytecode_id,
                                     parameters,
                                     argument,
                                     required_application_ids.unwrap_or_default(),
+                                    call_policy,
                                 )
                                 .await
                         }
@@ -1080,6 +1586,65 @@ impl Runnable for Job {
                 debug!("{:?}", certificate);
             }
 
+            UpgradeApplication {
+                application_id,
+                bytecode_id,
+                creator_chain_id,
+            } => {
+                let start_time = Instant::now();
+                let creator_chain_id = creator_chain_id.unwrap_or_else(|| context.default_chain());
+                info!("Upgrading application on chain {}", creator_chain_id);
+                let chain_client = context.make_chain_client(creator_chain_id)?;
+                let certificate = context
+                    .apply_client_command(&chain_client, |chain_client| {
+                        let chain_client = chain_client.clone();
+                        async move {
+                            chain_client
+                                .upgrade_application(application_id, bytecode_id)
+                                .await
+                        }
+                    })
+                    .await
+                    .context("Failed to upgrade application")?;
+                info!(
+                    "Application upgraded in {} ms",
+                    start_time.elapsed().as_millis()
+                );
+                debug!("{:?}", certificate);
+            }
+
+            SetApplicationCallPolicy {
+                application_id,
+                creator_chain_id,
+                call_policy_config,
+            } => {
+                let start_time = Instant::now();
+                let creator_chain_id = creator_chain_id.unwrap_or_else(|| context.default_chain());
+                info!(
+                    "Setting the call policy of an application on chain {}",
+                    creator_chain_id
+                );
+                let chain_client = context.make_chain_client(creator_chain_id)?;
+                let policy = ApplicationCallPolicy::from(call_policy_config);
+                let certificate = context
+                    .apply_client_command(&chain_client, |chain_client| {
+                        let chain_client = chain_client.clone();
+                        let policy = policy.clone();
+                        async move {
+                            chain_client
+                                .set_application_call_policy(application_id, policy)
+                                .await
+                        }
+                    })
+                    .await
+                    .context("Failed to set the application call policy")?;
+                info!(
+                    "Application call policy set in {} ms",
+                    start_time.elapsed().as_millis()
+                );
+                debug!("{:?}", certificate);
+            }
+
             Assign { owner, message_id } => {
                 let start_time = Instant::now();
                 let chain_id = ChainId::child(message_id);
@@ -1097,6 +1662,158 @@ impl Runnable for Job {
                 );
             }
 
+            MultiOwner(multi_owner_command) => match multi_owner_command {
+                MultiOwnerCommand::Open {
+                    chain_id,
+                    ownership_config,
+                    application_permissions_config,
+                    balance,
+                } => {
+                    let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                    let chain_client = context.make_chain_client(chain_id)?;
+                    info!(
+                        "Opening a new multi-owner chain from existing chain {}",
+                        chain_id
+                    );
+                    let time_start = Instant::now();
+                    let key_pair = context.wallet.generate_key_pair();
+                    let ownership = ChainOwnership::try_from(ownership_config)?
+                        .with_regular_owner(key_pair.public().into(), 100);
+                    let application_permissions =
+                        ApplicationPermissions::from(application_permissions_config);
+                    let (message_id, certificate) = context
+                        .apply_client_command(&chain_client, |chain_client| {
+                            let ownership = ownership.clone();
+                            let application_permissions = application_permissions.clone();
+                            let chain_client = chain_client.clone();
+                            async move {
+                                chain_client
+                                    .open_chain(ownership, application_permissions, balance)
+                                    .await
+                            }
+                        })
+                        .await
+                        .context("Failed to open chain")?;
+                    let id = ChainId::child(message_id);
+                    let timestamp = certificate.block().header.timestamp;
+                    context
+                        .update_wallet_for_new_chain(id, Some(key_pair), timestamp)
+                        .await?;
+                    info!(
+                        "Opening a new multi-owner chain confirmed after {} ms",
+                        time_start.elapsed().as_millis()
+                    );
+                    debug!("{:?}", certificate);
+                    println!("{}", id);
+                    info!("The other owners can now run `linera multi-owner accept {id}`");
+                }
+
+                MultiOwnerCommand::AddOwner {
+                    owner,
+                    chain_id,
+                    weight,
+                } => {
+                    let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                    let time_start = Instant::now();
+                    let mut ownership = Self::current_ownership(chain_id, &context).await?;
+                    ensure!(
+                        !ownership.verify_owner(&owner),
+                        "{owner} is already an owner of chain {chain_id}"
+                    );
+                    ownership.owners.insert(owner, weight);
+                    context.set_ownership(chain_id, ownership).await?;
+                    info!(
+                        "Owner added in {} ms. They can now run \
+                        `linera multi-owner accept {chain_id}`",
+                        time_start.elapsed().as_millis()
+                    );
+                }
+
+                MultiOwnerCommand::RemoveOwner { owner, chain_id } => {
+                    let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                    let time_start = Instant::now();
+                    let mut ownership = Self::current_ownership(chain_id, &context).await?;
+                    let was_super_owner = ownership.super_owners.remove(&owner);
+                    let was_owner = ownership.owners.remove(&owner).is_some();
+                    ensure!(
+                        was_super_owner || was_owner,
+                        "{owner} is not an owner of chain {chain_id}"
+                    );
+                    ensure!(
+                        ownership.all_owners().next().is_some(),
+                        "Cannot remove the last owner of chain {chain_id}"
+                    );
+                    context.set_ownership(chain_id, ownership).await?;
+                    info!("Owner removed in {} ms", time_start.elapsed().as_millis());
+                }
+
+                MultiOwnerCommand::Accept { chain_id } => {
+                    let time_start = Instant::now();
+                    if let Some(chain) = context.wallet.get(chain_id) {
+                        ensure!(
+                            chain.key_pair.is_none(),
+                            "The wallet already has a key for chain {chain_id}"
+                        );
+                    }
+                    // The wallet may have no chain yet, so the chain is synchronized by a
+                    // temporary client, with the validators of the admin chain.
+                    let admin_id = context.wallet.genesis_admin_chain();
+                    let client = Arc::new(context.client.clone_with(
+                        context.make_node_provider(),
+                        "Temporary client for accepting a chain",
+                        vec![chain_id],
+                        false,
+                    ));
+                    let admin_client = client.create_chain_client(
+                        admin_id,
+                        vec![],
+                        admin_id,
+                        None,
+                        Timestamp::from(0),
+                        BlockHeight::ZERO,
+                        None,
+                    );
+                    info!("Synchronizing chain {chain_id}");
+                    admin_client
+                        .synchronize_chain_from_validators(chain_id)
+                        .await
+                        .context("Failed to synchronize the chain")?;
+                    let (owner, timestamp) = {
+                        let chain = storage.load_chain(chain_id).await?;
+                        let system = &chain.execution_state.system;
+                        let ownership = system.ownership.get();
+                        let owner = context
+                            .wallet
+                            .unassigned_key_pairs
+                            .keys()
+                            .find(|owner| ownership.verify_owner(owner))
+                            .copied()
+                            .with_context(|| {
+                                format!(
+                                    "None of the keys of the wallet owns chain {chain_id}. \
+                                    Create one with `linera multi-owner new-key` and ask an \
+                                    owner of the chain to add it."
+                                )
+                            })?;
+                        (owner, *system.timestamp.get())
+                    };
+                    context
+                        .wallet_mut()
+                        .mutate(|w| w.assign_new_chain_to_owner(owner, chain_id, timestamp))
+                        .await?
+                        .context("could not assign the chain")?;
+                    println!("{}", owner);
+                    info!(
+                        "Chain {chain_id} accepted in {} ms",
+                        time_start.elapsed().as_millis()
+                    );
+                }
+
+                MultiOwnerCommand::NewKey => {
+                    unreachable!("creating a key does not require storage")
+                }
+            },
+
             Project(project_command) => match project_command {
                 ProjectCommand::PublishAndCreate {
                     path,
@@ -1107,6 +1824,7 @@ impl Runnable for Job {
                     json_argument,
                     json_argument_path,
                     required_application_ids,
+                    no_optimize,
                 } => {
                     let start_time = Instant::now();
                     let publisher = publisher.unwrap_or_else(|| context.default_chain());
@@ -1118,7 +1836,7 @@ impl Runnable for Job {
                     let project_path = path.unwrap_or_else(|| env::current_dir().unwrap());
 
                     let project = project::Project::from_existing_project(project_path)?;
-                    let (contract_path, service_path) = project.build(name)?;
+                    let (contract_path, service_path) = project.build(name, !no_optimize)?;
 
                     let bytecode_id = context
                         .publish_bytecode(&chain_client, contract_path, service_path)
@@ -1153,6 +1871,42 @@ impl Runnable for Job {
                 _ => unreachable!("other project commands do not require storage"),
             },
 
+            Submit { proposal } => {
+                let start_time = Instant::now();
+                let proposal: BlockProposal =
+                    serde_json::from_str(&fs_err::read_to_string(&proposal)?)?;
+                let chain_client = context.make_chain_client(proposal.content.block.chain_id)?;
+                info!(
+                    "Submitting block proposal for chain {}",
+                    chain_client.chain_id()
+                );
+                let certificate = chain_client
+                    .submit_signed_proposal(proposal)
+                    .await
+                    .context("Failed to submit the block proposal")?;
+                context.update_and_save_wallet(&chain_client).await?;
+                println!("{}", certificate.hash());
+                info!(
+                    "Block proposal submitted in {} ms",
+                    start_time.elapsed().as_millis()
+                );
+            }
+
+            ExportCertificates { chain_ids, output } => {
+                let chain_ids = if chain_ids.is_empty() {
+                    context.wallet().chain_ids()
+                } else {
+                    chain_ids
+                };
+                let archive = audit::export_certificates(&storage, chain_ids).await?;
+                info!(
+                    "Exporting {} certificate(s) and {} blob(s)",
+                    archive.certificates.len(),
+                    archive.blobs.len()
+                );
+                fs_err::write(output, serde_json::to_vec(&archive)?)?;
+            }
+
             RetryPendingBlock { chain_id } => {
                 let start_time = Instant::now();
                 let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
@@ -1223,9 +1977,14 @@ impl Runnable for Job {
 
             CreateGenesisConfig { .. }
             | Keygen
+            | Sign { .. }
+            | Audit { .. }
             | Net(_)
+            | Profile(_)
             | Storage { .. }
             | Wallet(_)
+            | Chain(_)
+            | Console
             | ExtractScriptFromMarkdown { .. }
             | HelpMarkdown => {
                 unreachable!()
@@ -1235,7 +1994,53 @@ impl Runnable for Job {
     }
 }
 
+/// Verifies a certificate archive against a network's genesis configuration.
+struct AuditJob(GenesisConfig, CertificateArchive);
+
+#[async_trait]
+impl Runnable for AuditJob {
+    type Output = Result<AuditReport, AuditError>;
+
+    async fn run<S>(self, storage: S) -> Self::Output
+    where
+        S: Storage + Clone + Send + Sync + 'static,
+    {
+        let AuditJob(genesis_config, archive) = self;
+        let committees = [(Epoch::ZERO, genesis_config.create_committee())].into();
+        audit::audit_certificates(storage, genesis_config.admin_id, committees, archive).await
+    }
+}
+
 impl Job {
+    /// Synchronizes the chain of `chain_client` from the validators, and returns the hash of
+    /// the block at or above `from_height` executing exactly the given operations, if any.
+    async fn find_committed_block<P, S>(
+        chain_client: &ChainClient<P, S>,
+        from_height: BlockHeight,
+        operations: &[Operation],
+    ) -> anyhow::Result<Option<CryptoHash>>
+    where
+        P: ValidatorNodeProvider + Sync + 'static,
+        S: Storage + Clone + Send + Sync + 'static,
+    {
+        chain_client
+            .synchronize_chain_from_validators(chain_client.chain_id())
+            .await?;
+        let hashes = chain_client
+            .chain_state_view()
+            .await?
+            .confirmed_log
+            .read(usize::try_from(from_height)?..)
+            .await?;
+        for hash in hashes {
+            let block = chain_client.read_hashed_confirmed_block(hash).await?;
+            if block.inner().block().body.operations == operations {
+                return Ok(Some(hash));
+            }
+        }
+        Ok(None)
+    }
+
     async fn assign_new_chain_to_key<S>(
         chain_id: ChainId,
         message_id: MessageId,
@@ -1311,6 +2116,20 @@ impl Job {
         Ok(())
     }
 
+    /// Synchronizes a chain of the wallet and returns its current owners.
+    async fn current_ownership<S>(
+        chain_id: ChainId,
+        context: &ClientContext<S, impl Persist<Target = Wallet>>,
+    ) -> anyhow::Result<ChainOwnership>
+    where
+        S: Storage + Clone + Send + Sync + 'static,
+    {
+        let chain_client = context.make_chain_client(chain_id)?;
+        chain_client.synchronize_from_validators().await?;
+        let chain = chain_client.chain_state_view().await?;
+        Ok(chain.execution_state.system.ownership.get().clone())
+    }
+
     /// Prints a warning message to explain that the wallet has been initialized using data from
     /// untrusted nodes, and gives instructions to verify that we are connected to the right
     /// network.
@@ -1413,6 +2232,7 @@ fn main() -> anyhow::Result<()> {
 fn log_file_name_for(command: &ClientCommand) -> Cow<'static, str> {
     match command {
         ClientCommand::Transfer { .. }
+        | ClientCommand::TransferBatch { .. }
         | ClientCommand::OpenChain { .. }
         | ClientCommand::OpenMultiOwnerChain { .. }
         | ClientCommand::ChangeOwnership { .. }
@@ -1423,11 +2243,13 @@ fn log_file_name_for(command: &ClientCommand) -> Cow<'static, str> {
         | ClientCommand::SyncBalance { .. }
         | ClientCommand::Sync { .. }
         | ClientCommand::ProcessInbox { .. }
+        | ClientCommand::QueryHistory { .. }
         | ClientCommand::QueryValidator { .. }
         | ClientCommand::QueryValidators { .. }
         | ClientCommand::SyncValidator { .. }
         | ClientCommand::SetValidator { .. }
         | ClientCommand::RemoveValidator { .. }
+        | ClientCommand::RotateValidatorKey { .. }
         | ClientCommand::ResourceControlPolicy { .. }
         | ClientCommand::FinalizeCommittee
         | ClientCommand::CreateGenesisConfig { .. }
@@ -1437,15 +2259,25 @@ fn log_file_name_for(command: &ClientCommand) -> Cow<'static, str> {
         | ClientCommand::CreateApplication { .. }
         | ClientCommand::PublishAndCreate { .. }
         | ClientCommand::RequestApplication { .. }
+        | ClientCommand::UpgradeApplication { .. }
+        | ClientCommand::SetApplicationCallPolicy { .. }
         | ClientCommand::Keygen { .. }
         | ClientCommand::Assign { .. }
         | ClientCommand::Wallet { .. }
-        | ClientCommand::RetryPendingBlock { .. } => "client".into(),
+        | ClientCommand::Chain { .. }
+        | ClientCommand::MultiOwner { .. }
+        | ClientCommand::RetryPendingBlock { .. }
+        | ClientCommand::Sign { .. }
+        | ClientCommand::Submit { .. }
+        | ClientCommand::ExportCertificates { .. } => "client".into(),
+        ClientCommand::Audit { .. } => "audit".into(),
         #[cfg(feature = "benchmark")]
         ClientCommand::Benchmark { .. } => "benchmark".into(),
         ClientCommand::Net { .. } => "net".into(),
         ClientCommand::Project { .. } => "project".into(),
+        ClientCommand::Profile { .. } => "profile".into(),
         ClientCommand::Watch { .. } => "watch".into(),
+        ClientCommand::Console => "console".into(),
         ClientCommand::Storage { .. } => "storage".into(),
         ClientCommand::Service { port, .. } => format!("service-{port}").into(),
         ClientCommand::Faucet { .. } => "faucet".into(),
@@ -1462,6 +2294,18 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
             Ok(0)
         }
 
+        ClientCommand::Console => {
+            let mut console = Console::new(options.clone())?;
+            console.refresh().await;
+            while let Some(options) = console.read_command().await? {
+                let start_time = Instant::now();
+                let result = Box::pin(run(&options)).await;
+                console.report(result, start_time.elapsed());
+                console.refresh().await;
+            }
+            Ok(0)
+        }
+
         ClientCommand::ExtractScriptFromMarkdown {
             path,
             pause_after_linera_service,
@@ -1494,6 +2338,7 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
             byte_read_price,
             byte_written_price,
             byte_stored_price,
+            byte_stored_per_day_price,
             operation_price,
             operation_byte_price,
             message_price,
@@ -1506,6 +2351,18 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
             maximum_block_proposal_size,
             maximum_bytes_read_per_block,
             maximum_bytes_written_per_block,
+            maximum_wasm_memory_size,
+            maximum_wasm_table_elements,
+            maximum_bytes_stored_per_application,
+            storage_rent_grace_period,
+            instruction_fuel,
+            structural_instruction_fuel,
+            memory_access_fuel,
+            call_fuel,
+            memory_grow_page_fuel,
+            base_fee_change_denominator,
+            maximum_scheduled_callbacks_per_application,
+            maximum_callbacks_per_operation,
             testing_prng_seed,
             network_name,
         } => {
@@ -1521,6 +2378,11 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
             let maximum_published_blobs = maximum_published_blobs.unwrap_or(u64::MAX);
             let maximum_bytecode_size = maximum_bytecode_size.unwrap_or(u64::MAX);
             let maximum_block_proposal_size = maximum_block_proposal_size.unwrap_or(u64::MAX);
+            let maximum_wasm_memory_size = maximum_wasm_memory_size.unwrap_or(u64::MAX);
+            let maximum_wasm_table_elements = maximum_wasm_table_elements.unwrap_or(u64::MAX);
+            let maximum_bytes_stored_per_application =
+                maximum_bytes_stored_per_application.unwrap_or(u64::MAX);
+            let storage_rent_grace_period = storage_rent_grace_period.unwrap_or(TimeDelta::ZERO);
             let policy = ResourceControlPolicy {
                 block: *block_price,
                 fuel_unit: *fuel_unit_price,
@@ -1529,6 +2391,7 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
                 byte_read: *byte_read_price,
                 byte_written: *byte_written_price,
                 byte_stored: *byte_stored_price,
+                byte_stored_per_day: *byte_stored_per_day_price,
                 operation_byte: *operation_byte_price,
                 operation: *operation_price,
                 message_byte: *message_byte_price,
@@ -1541,6 +2404,21 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
                 maximum_block_proposal_size,
                 maximum_bytes_read_per_block,
                 maximum_bytes_written_per_block,
+                maximum_wasm_memory_size,
+                maximum_wasm_table_elements,
+                maximum_bytes_stored_per_application,
+                storage_rent_grace_period,
+                fuel_schedule: FuelSchedule {
+                    instruction: *instruction_fuel,
+                    structural_instruction: *structural_instruction_fuel,
+                    memory_access: *memory_access_fuel,
+                    call: *call_fuel,
+                    memory_grow_page: *memory_grow_page_fuel,
+                },
+                base_fee_change_denominator: *base_fee_change_denominator,
+                maximum_scheduled_callbacks_per_application:
+                    *maximum_scheduled_callbacks_per_application,
+                maximum_callbacks_per_operation: *maximum_callbacks_per_operation,
             };
             let timestamp = start_timestamp
                 .map(|st| {
@@ -1615,6 +2493,72 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
             }
         },
 
+        ClientCommand::Chain(chain_command) => match chain_command {
+            ChainCommand::Export {
+                chain_id,
+                output,
+                plaintext,
+                forget,
+            } => {
+                let mut wallet = options.wallet().await?;
+                let passphrase = if *plaintext {
+                    None
+                } else {
+                    Some(ClientOptions::new_export_passphrase()?)
+                };
+                wallet
+                    .export_chain(*chain_id)?
+                    .write(output, passphrase.as_ref())?;
+                if *forget {
+                    wallet.mutate(|w| w.forget_chain(chain_id)).await??;
+                    info!(
+                        "Chain {chain_id} exported to {} and forgotten",
+                        output.display()
+                    );
+                } else {
+                    info!("Chain {chain_id} exported to {}", output.display());
+                }
+                Ok(0)
+            }
+            ChainCommand::Import { path } => {
+                let exported =
+                    ExportedChain::read(path, || Ok(ClientOptions::export_passphrase()?))?;
+                let chain_id = options
+                    .wallet()
+                    .await?
+                    .mutate(|w| w.import_chain(exported))
+                    .await??;
+                info!("Chain {chain_id} imported");
+                Ok(0)
+            }
+        },
+
+        ClientCommand::Profile(profile_command) => match profile_command {
+            ProfileCommand::Create {
+                name,
+                wallet,
+                storage,
+                faucet,
+                validator,
+            } => {
+                let profile = Profile {
+                    wallet: wallet.clone(),
+                    storage: storage.clone(),
+                    faucet: faucet.clone(),
+                    validator: validator.clone(),
+                };
+                profile.create(&ClientOptions::config_dir()?, name)?;
+                info!("Profile {name} created");
+                Ok(0)
+            }
+            ProfileCommand::List => {
+                for name in Profile::list(&ClientOptions::config_dir()?)? {
+                    println!("{name}");
+                }
+                Ok(0)
+            }
+        },
+
         ClientCommand::Keygen => {
             let start_time = Instant::now();
             let mut wallet = options.wallet().await?;
@@ -1628,6 +2572,84 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
             Ok(0)
         }
 
+        ClientCommand::MultiOwner(MultiOwnerCommand::NewKey) => {
+            let mut wallet = options.wallet().await?;
+            let key_pair = wallet.generate_key_pair();
+            let owner = Owner::from(key_pair.public());
+            wallet
+                .mutate(|w| w.add_unassigned_key_pair(key_pair))
+                .await?;
+            println!("{}", owner);
+            info!(
+                "Send this owner to whoever opens the chain with `linera multi-owner open \
+                --owners {owner}`, or to an owner of an existing chain, who can add it with \
+                `linera multi-owner add-owner {owner}`. Then run `linera multi-owner accept` \
+                with the ID of the chain."
+            );
+            Ok(0)
+        }
+
+        ClientCommand::Sign {
+            proposal,
+            output,
+            yes,
+        } => {
+            let wallet = options.wallet().await?;
+            let content: ProposalContent =
+                serde_json::from_str(&fs_err::read_to_string(proposal)?)?;
+            let signer = content
+                .block
+                .authenticated_signer
+                .context("The block proposal has no signer")?;
+            let key_pair = wallet
+                .key_pair_for_owner(&signer)
+                .with_context(|| format!("The wallet has no key for {signer}"))?;
+            ensure!(
+                content.outcome.is_none(),
+                "Only new block proposals can be signed offline"
+            );
+            print!("{}", describe_proposal(&content));
+            if !yes {
+                print!("Sign this block as {signer}? [y/N] ");
+                io::stdout().flush()?;
+                let mut answer = String::new();
+                io::stdin().read_line(&mut answer)?;
+                ensure!(
+                    matches!(answer.trim(), "y" | "Y" | "yes"),
+                    "The block proposal was not signed"
+                );
+            }
+            let proposal = BlockProposal::new_initial(content.round, content.block, &key_pair);
+            fs_err::write(output, serde_json::to_vec_pretty(&proposal)?)?;
+            Ok(0)
+        }
+
+        ClientCommand::Audit {
+            certificates,
+            genesis_config_path,
+        } => {
+            let genesis_config: GenesisConfig = util::read_json(genesis_config_path)?;
+            let archive: CertificateArchive =
+                serde_json::from_str(&fs_err::read_to_string(certificates)?)?;
+            info!(
+                "Auditing {} certificate(s) of network {}",
+                archive.certificates.len(),
+                genesis_config.network_name
+            );
+            let report = options
+                .run_with_memory_storage(&genesis_config, AuditJob(genesis_config.clone(), archive))
+                .await??;
+            for (chain_id, next_block_height) in &report.next_block_heights {
+                let state_hash = report
+                    .state_hashes
+                    .get(chain_id)
+                    .map_or_else(|| "-".to_string(), CryptoHash::to_string);
+                println!("{chain_id}\t{next_block_height}\t{state_hash}");
+            }
+            info!("{} certificate(s) verified", report.certificate_count);
+            Ok(0)
+        }
+
         ClientCommand::Net(net_command) => match net_command {
             #[cfg(feature = "kubernetes")]
             NetCommand::Up {
@@ -1642,8 +2664,12 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
                 binaries,
                 no_build,
                 docker_image_name,
+                kubernetes_manifests,
                 path: _,
                 storage: _,
+                // Conflicts with `--kubernetes`, so it is always the default.
+                storage_backend: _,
+                storage_file: _,
                 external_protocol: _,
                 with_faucet_chain,
                 faucet_port,
@@ -1659,6 +2685,7 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
                     binaries,
                     *no_build,
                     docker_image_name.clone(),
+                    kubernetes_manifests,
                     policy_config.into_policy(),
                     *with_faucet_chain,
                     *faucet_port,
@@ -1679,6 +2706,8 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
                 policy_config,
                 path,
                 storage,
+                storage_backend,
+                storage_file,
                 external_protocol,
                 with_faucet_chain,
                 faucet_port,
@@ -1695,6 +2724,8 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
                     policy_config.into_policy(),
                     path,
                     storage,
+                    *storage_backend,
+                    storage_file,
                     external_protocol.clone(),
                     *with_faucet_chain,
                     *faucet_port,
@@ -1784,6 +2815,67 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
                     info!("Blob IDs listed in {} ms", start_time.elapsed().as_millis());
                     println!("The list of blob IDs is {:?}", blob_ids);
                 }
+                DatabaseToolCommand::ListChainIds { .. } => {
+                    let chain_ids = Box::pin(full_storage_config.list_chain_ids()).await?;
+                    info!(
+                        "Chain IDs listed in {} ms",
+                        start_time.elapsed().as_millis()
+                    );
+                    println!("The list of chain IDs is {:?}", chain_ids);
+                }
+                DatabaseToolCommand::BackfillChainIndices { .. } => {
+                    let chain_ids = Box::pin(full_storage_config.backfill_chain_indices()).await?;
+                    info!(
+                        "{} chains registered in {} ms",
+                        chain_ids.len(),
+                        start_time.elapsed().as_millis()
+                    );
+                    println!("The list of registered chain IDs is {:?}", chain_ids);
+                }
+                DatabaseToolCommand::DeleteChain { chain_id, .. } => {
+                    Box::pin(full_storage_config.delete_chain_namespace(*chain_id)).await?;
+                    info!(
+                        "State of chain {chain_id} deleted in {} ms",
+                        start_time.elapsed().as_millis()
+                    );
+                }
+                DatabaseToolCommand::ExportChain {
+                    chain_id, output, ..
+                } => {
+                    let entries =
+                        Box::pin(full_storage_config.read_chain_state_entries(*chain_id, &[]))
+                            .await?;
+                    let entries = entries
+                        .iter()
+                        .map(|(key, value)| (hex::encode(key), hex::encode(value)))
+                        .collect::<Vec<_>>();
+                    fs_err::write(output, serde_json::to_vec_pretty(&entries)?)?;
+                    info!(
+                        "{} entries of chain {chain_id} exported in {} ms",
+                        entries.len(),
+                        start_time.elapsed().as_millis()
+                    );
+                }
+                DatabaseToolCommand::Inspect {
+                    chain_id, prefix, ..
+                } => {
+                    let prefix = match prefix {
+                        Some(prefix) => hex::decode(prefix.trim_start_matches("0x"))
+                            .context("Invalid key prefix: expected hexadecimal bytes")?,
+                        None => Vec::new(),
+                    };
+                    let entries =
+                        Box::pin(full_storage_config.read_chain_state_entries(*chain_id, &prefix))
+                            .await?;
+                    info!(
+                        "{} entries of chain {chain_id} read in {} ms",
+                        entries.len(),
+                        start_time.elapsed().as_millis()
+                    );
+                    for (key, value) in entries {
+                        println!("{}", ChainStateEntry::decode(&key, &value));
+                    }
+                }
             }
             Ok(0)
         }
@@ -1853,6 +2945,20 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
                 Ok(0)
             }
 
+            WalletCommand::Encrypt => {
+                let mut wallet = options.wallet().await?;
+                let was_encrypted = wallet.is_encrypted();
+                wallet
+                    .encrypt(ClientOptions::new_wallet_passphrase()?)
+                    .await?;
+                if was_encrypted {
+                    info!("Wallet passphrase changed");
+                } else {
+                    info!("Wallet encrypted");
+                }
+                Ok(0)
+            }
+
             WalletCommand::Init {
                 genesis_config_path,
                 faucet,
@@ -1,12 +1,17 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{num::NonZeroU16, str::FromStr};
+use std::{num::NonZeroU16, path::PathBuf, str::FromStr};
 
 use colored::Colorize as _;
 use linera_base::{data_types::Amount, identifiers::ChainId, time::Duration};
-use linera_client::storage::{StorageConfig, StorageConfigNamespace};
+use linera_client::{
+    client_options::NetStorageBackend,
+    storage::{StorageConfig, StorageConfigNamespace},
+};
 use linera_execution::ResourceControlPolicy;
+#[cfg(feature = "kubernetes")]
+use linera_service::cli_wrappers::local_kubernetes_net::LocalKubernetesNetConfig;
 use linera_service::{
     cli_wrappers::{
         local_net::{Database, LocalNetConfig, PathProvider, StorageConfigBuilder},
@@ -22,11 +27,6 @@ use linera_storage_service::{
 };
 use tokio_util::sync::CancellationToken;
 use tracing::info;
-#[cfg(feature = "kubernetes")]
-use {
-    linera_service::cli_wrappers::local_kubernetes_net::LocalKubernetesNetConfig,
-    std::path::PathBuf,
-};
 
 struct StorageConfigProvider {
     /// The StorageConfig and the namespace
@@ -36,13 +36,36 @@ struct StorageConfigProvider {
 }
 
 impl StorageConfigProvider {
-    pub async fn new(storage: &Option<String>) -> anyhow::Result<StorageConfigProvider> {
+    pub async fn new(
+        storage: &Option<String>,
+        storage_backend: NetStorageBackend,
+        storage_file: &Option<PathBuf>,
+    ) -> anyhow::Result<StorageConfigProvider> {
+        if storage_file.is_some() && storage_backend != NetStorageBackend::Service {
+            anyhow::bail!("`--storage-file` is only supported by the storage service");
+        }
         match storage {
+            None if storage_backend != NetStorageBackend::Service => {
+                let storage_config = Self::local_storage_config(storage_backend)?;
+                let namespace = "table_default".to_string();
+                let storage = StorageConfigNamespace {
+                    storage_config,
+                    namespace,
+                };
+                Ok(StorageConfigProvider {
+                    storage,
+                    #[cfg(feature = "storage-service")]
+                    _service_guard: None,
+                })
+            }
             #[cfg(feature = "storage-service")]
             None => {
                 let service_endpoint = linera_base::port::get_free_endpoint().await?;
                 let binary = get_service_storage_binary().await?.display().to_string();
-                let service = StorageService::new(&service_endpoint, binary);
+                let mut service = StorageService::new(&service_endpoint, binary);
+                if let Some(storage_file) = storage_file {
+                    service = service.with_file(storage_file.clone());
+                }
                 let _service_guard = service.run().await?;
                 let _service_guard = Some(_service_guard);
                 let storage_config = StorageConfig::Service {
@@ -78,6 +101,37 @@ impl StorageConfigProvider {
         }
     }
 
+    /// Returns the configuration of a database running locally with its default settings.
+    fn local_storage_config(storage_backend: NetStorageBackend) -> anyhow::Result<StorageConfig> {
+        match storage_backend {
+            NetStorageBackend::Service => {
+                anyhow::bail!("The storage service is started by `linera net up`")
+            }
+            NetStorageBackend::ScyllaDb => {
+                #[cfg(feature = "scylladb")]
+                {
+                    let uri = "localhost:9042".to_string();
+                    Ok(StorageConfig::ScyllaDb { uri })
+                }
+                #[cfg(not(feature = "scylladb"))]
+                anyhow::bail!("The ScyllaDB backend requires the feature scylladb")
+            }
+            NetStorageBackend::DynamoDb => {
+                #[cfg(feature = "dynamodb")]
+                {
+                    let use_localstack = true;
+                    Ok(StorageConfig::DynamoDb {
+                        use_localstack,
+                        regions: Vec::new(),
+                        options: Default::default(),
+                    })
+                }
+                #[cfg(not(feature = "dynamodb"))]
+                anyhow::bail!("The DynamoDB backend requires the feature dynamodb")
+            }
+        }
+    }
+
     pub fn storage_config(&self) -> StorageConfig {
         self.storage.storage_config.clone()
     }
@@ -113,6 +167,7 @@ pub async fn handle_net_up_kubernetes(
     binaries: &Option<Option<PathBuf>>,
     no_build: bool,
     docker_image_name: String,
+    kubernetes_manifests: &Option<PathBuf>,
     policy: ResourceControlPolicy,
     with_faucet_chain: Option<u32>,
     faucet_port: NonZeroU16,
@@ -140,6 +195,16 @@ pub async fn handle_net_up_kubernetes(
         docker_image_name,
         policy,
     };
+    if let Some(output_dir) = kubernetes_manifests {
+        config.generate_manifests(output_dir).await?;
+        eprintln!(
+            "The configuration of the validators was written to {}. To deploy the validator \
+            $N, copy the directory `working` into `kubernetes/linera-validator` and run \
+            `helm install linera-core kubernetes/linera-validator --values values_$N.yaml`.",
+            output_dir.display()
+        );
+        return Ok(());
+    }
     let (mut net, client) = config.instantiate().await?;
     let faucet_service = create_wallets_and_faucets(
         extra_wallets,
@@ -164,6 +229,8 @@ pub async fn handle_net_up_service(
     policy: ResourceControlPolicy,
     path: &Option<String>,
     storage: &Option<String>,
+    storage_backend: NetStorageBackend,
+    storage_file: &Option<PathBuf>,
     external_protocol: String,
     with_faucet_chain: Option<u32>,
     faucet_port: NonZeroU16,
@@ -179,7 +246,7 @@ pub async fn handle_net_up_service(
     let shutdown_notifier = CancellationToken::new();
     tokio::spawn(listen_for_shutdown_signals(shutdown_notifier.clone()));
 
-    let storage = StorageConfigProvider::new(storage).await?;
+    let storage = StorageConfigProvider::new(storage, storage_backend, storage_file).await?;
     let storage_config = storage.storage_config();
     let namespace = storage.namespace();
     let database = storage.database()?;
@@ -0,0 +1,100 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The chain activity printed by `linera watch`.
+
+use std::fmt;
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{BlockHeight, Timestamp},
+    hashed::Hashed,
+    identifiers::ChainId,
+};
+use linera_chain::{
+    data_types::{IncomingBundle, Origin},
+    types::ConfirmedBlock,
+};
+use linera_execution::Operation;
+use serde::Serialize;
+
+/// A new block or incoming message bundle on a watched chain.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ChainActivity<'a> {
+    /// A block was added to the chain.
+    NewBlock {
+        chain_id: ChainId,
+        height: BlockHeight,
+        hash: CryptoHash,
+        timestamp: Timestamp,
+        incoming_bundles: &'a [IncomingBundle],
+        operations: &'a [Operation],
+    },
+    /// A message bundle arrived in the inbox of the chain, and waits to be processed.
+    NewIncomingBundle {
+        chain_id: ChainId,
+        origin: &'a Origin,
+        height: BlockHeight,
+    },
+}
+
+impl<'a> ChainActivity<'a> {
+    /// Returns the activity of a new confirmed block.
+    pub fn new_block(block: &'a Hashed<ConfirmedBlock>) -> Self {
+        let header = &block.inner().block().header;
+        let body = &block.inner().block().body;
+        ChainActivity::NewBlock {
+            chain_id: header.chain_id,
+            height: header.height,
+            hash: block.hash(),
+            timestamp: header.timestamp,
+            incoming_bundles: &body.incoming_bundles,
+            operations: &body.operations,
+        }
+    }
+}
+
+impl fmt::Display for ChainActivity<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainActivity::NewBlock {
+                chain_id,
+                height,
+                hash,
+                timestamp,
+                incoming_bundles,
+                operations,
+            } => {
+                write!(
+                    f,
+                    "{chain_id}: new block {height} ({hash}) at {timestamp}, with {} incoming \
+                    bundles and {} operations",
+                    incoming_bundles.len(),
+                    operations.len()
+                )?;
+                for bundle in *incoming_bundles {
+                    for message in &bundle.bundle.messages {
+                        write!(
+                            f,
+                            "\n  message from {} at height {}: {:?}",
+                            bundle.origin, bundle.bundle.height, message.message
+                        )?;
+                    }
+                }
+                for operation in *operations {
+                    write!(f, "\n  operation: {operation:?}")?;
+                }
+                Ok(())
+            }
+            ChainActivity::NewIncomingBundle {
+                chain_id,
+                origin,
+                height,
+            } => write!(
+                f,
+                "{chain_id}: new incoming bundle from {origin} at height {height}"
+            ),
+        }
+    }
+}
@@ -4,6 +4,7 @@
 use std::{borrow::Cow, iter, net::SocketAddr, num::NonZeroU16, sync::Arc};
 
 use async_graphql::{
+    connection::{self, Connection, CursorType, Edge},
     futures_util::Stream,
     parser::types::{DocumentOperations, ExecutableDocument, OperationType},
     resolver_utils::ContainerType,
@@ -11,42 +12,69 @@ use async_graphql::{
     Subscription,
 };
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
-use axum::{extract::Path, http::StatusCode, response, response::IntoResponse, Extension, Router};
-use futures::{lock::Mutex, Future};
+use axum::{
+    body::Bytes,
+    extract::{Path, Query as UrlQuery},
+    http::{Method, StatusCode},
+    response,
+    response::IntoResponse,
+    Extension, Json, Router,
+};
+use futures::{lock::Mutex, stream, Future, StreamExt as _};
 use linera_base::{
     crypto::{CryptoError, CryptoHash},
-    data_types::{Amount, ApplicationPermissions, Bytecode, TimeDelta, UserApplicationDescription},
+    data_types::{
+        Amount, ApplicationPermissions, BlockHeight, Bytecode, TimeDelta,
+        UserApplicationDescription,
+    },
     ensure,
     hashed::Hashed,
-    identifiers::{ApplicationId, BytecodeId, ChainId, Owner, UserApplicationId},
+    http::{HttpMethod, HttpRequest, HttpResponse},
+    identifiers::{
+        AccountOwner, ApplicationId, BytecodeId, ChainId, GenericApplicationId, Owner,
+        UserApplicationId,
+    },
     ownership::{ChainOwnership, TimeoutConfig},
     BcsHexParseError,
 };
 use linera_chain::{
+    data_types::EventRecord,
     types::{ConfirmedBlock, GenericCertificate},
-    ChainStateView,
+    ChainError, ChainStateView,
+};
+use linera_client::{
+    chain_listener::{ChainListener, ChainListenerConfig, ClientContext},
+    client_options::WebhookConfig,
 };
-use linera_client::chain_listener::{ChainListener, ChainListenerConfig, ClientContext};
 use linera_core::{
     client::{ChainClient, ChainClientError},
     data_types::ClientOutcome,
-    worker::Notification,
+    local_node::LocalNodeError,
+    worker::{Notification, Reason, WorkerError},
 };
 use linera_execution::{
     committee::{Committee, Epoch},
     system::{AdminOperation, Recipient, SystemChannel},
-    Operation, Query, QueryOutcome, QueryResponse, SystemOperation,
+    ExecutionError, Operation, Query, QueryOutcome, QueryResponse, SystemOperation,
 };
+use linera_rpc::grpc::api::validator_node_server::ValidatorNodeServer;
 use linera_sdk::base::BlobContent;
 use linera_storage::Storage;
+use linera_views::views::{StorageStats, View as _, ViewError};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error as ThisError;
 use tokio::sync::OwnedRwLockReadGuard;
+use tonic::service::Routes;
 use tower_http::cors::CorsLayer;
-use tracing::{debug, error, info, instrument, trace};
+use tracing::{debug, error, info, instrument, trace, warn};
 
-use crate::util;
+use crate::{
+    graphql_schema::{IntrospectedSchema, INTROSPECTION_QUERY},
+    node_service_grpc::NodeServiceGrpc,
+    util,
+    webhooks::WebhookNotifier,
+};
 
 #[derive(SimpleObject, Serialize, Deserialize, Clone)]
 pub struct Chains {
@@ -54,11 +82,163 @@ pub struct Chains {
     pub default: Option<ChainId>,
 }
 
+/// The query parameters of the REST endpoint returning a balance.
+#[derive(Deserialize)]
+struct BalanceQuery {
+    /// The account owner whose balance is requested, instead of the chain's.
+    owner: Option<AccountOwner>,
+}
+
+/// The response of the REST endpoint returning a balance.
+#[derive(Serialize, Deserialize)]
+pub struct BalanceResponse {
+    pub balance: Amount,
+}
+
+/// The response of the REST endpoint executing an operation.
+#[derive(Serialize, Deserialize)]
+pub struct OperationResponse {
+    /// The hash of the block containing the operation.
+    pub hash: CryptoHash,
+}
+
+/// The response of the REST endpoint returning a block.
+#[derive(Serialize)]
+pub struct BlockResponse {
+    pub hash: CryptoHash,
+    pub block: ConfirmedBlock,
+}
+
+/// An event emitted by an application in a block.
+#[derive(SimpleObject, Serialize, Deserialize, Clone)]
+pub struct ChainEvent {
+    /// The height of the block.
+    pub height: BlockHeight,
+    /// The hash of the block.
+    pub block_hash: CryptoHash,
+    /// The event.
+    pub event: EventRecord,
+}
+
+impl ChainEvent {
+    /// Returns the events of a confirmed block, optionally only those emitted by the given
+    /// application.
+    fn from_block(
+        block: Hashed<ConfirmedBlock>,
+        application_id: Option<UserApplicationId>,
+    ) -> impl Iterator<Item = ChainEvent> {
+        let block_hash = block.hash();
+        let block = block.into_inner().into_block();
+        let height = block.header.height;
+        block
+            .body
+            .events
+            .into_iter()
+            .flatten()
+            .filter(move |event| {
+                application_id.map_or(true, |application_id| {
+                    event.stream_id.application_id == GenericApplicationId::User(application_id)
+                })
+            })
+            .map(move |event| ChainEvent {
+                height,
+                block_hash,
+                event,
+            })
+    }
+}
+
+/// An operation of a confirmed block.
+#[derive(SimpleObject, Clone)]
+pub struct OperationEntry {
+    /// The height of the block.
+    pub height: BlockHeight,
+    /// The hash of the block.
+    pub block_hash: CryptoHash,
+    /// The index of the operation in the block.
+    pub index: u32,
+    /// The operation.
+    pub operation: Operation,
+}
+
+/// The storage used by a view of a chain's state.
+#[derive(SimpleObject, Clone)]
+pub struct ViewStorageStats {
+    /// The name of the view in the chain's state, e.g. `execution_state.system`.
+    pub view: String,
+    /// The application, if the view is the state of an application.
+    pub application_id: Option<UserApplicationId>,
+    /// The storage used by the view.
+    pub stats: StorageStats,
+}
+
+/// The number of items in a page of a paginated query, if `first` is not specified.
+const DEFAULT_PAGE_SIZE: usize = 10;
+
+/// The maximum number of items in a page of a paginated query.
+const MAX_PAGE_SIZE: usize = 100;
+
+fn page_size(first: Option<usize>) -> usize {
+    first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)
+}
+
+/// The maximum number of blocks read to fill a page of operations. This bounds the work of a
+/// query on chains with many blocks without operations, e.g. blocks that only receive messages.
+const MAX_SCANNED_BLOCKS: usize = 100;
+
+/// The position of an operation in a chain, used as a pagination cursor. A cursor without an
+/// index is the end of a block, formatted as `<height>`.
+#[derive(Clone, Copy)]
+struct OperationCursor {
+    /// The height of the block.
+    height: usize,
+    /// The index of the operation in the block.
+    index: Option<usize>,
+}
+
+impl CursorType for OperationCursor {
+    type Error = String;
+
+    fn decode_cursor(cursor: &str) -> Result<Self, Self::Error> {
+        let invalid = || format!("Invalid operation cursor: {cursor}");
+        let (height, index) = match cursor.split_once(':') {
+            Some((height, index)) => (height, Some(index.parse().map_err(|_| invalid())?)),
+            None => (cursor, None),
+        };
+        Ok(OperationCursor {
+            height: height.parse().map_err(|_| invalid())?,
+            index,
+        })
+    }
+
+    fn encode_cursor(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl std::fmt::Display for OperationCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "{}:{}", self.height, index),
+            None => write!(f, "{}", self.height),
+        }
+    }
+}
+
+/// The additional fields of a page of operations.
+#[derive(SimpleObject)]
+pub struct OperationPage {
+    /// The cursor to continue from if there is a next page. This is the end cursor, unless
+    /// the page is not full because the maximum number of blocks were read.
+    pub resume_after: Option<String>,
+}
+
 /// Our root GraphQL query type.
 pub struct QueryRoot<C> {
     context: Arc<Mutex<C>>,
     port: NonZeroU16,
     default_chain: Option<ChainId>,
+    storage_stats: bool,
 }
 
 /// Our root GraphQL subscription type.
@@ -83,6 +263,8 @@ enum NodeServiceError {
     BcsError(#[from] bcs::Error),
     #[error(transparent)]
     JsonError(#[from] serde_json::Error),
+    #[error("invalid JSON request body: {0}")]
+    InvalidRequestBody(serde_json::Error),
     #[error("missing GraphQL operation")]
     MissingOperation,
     #[error("unsupported query type: subscription")]
@@ -99,6 +281,40 @@ enum NodeServiceError {
     InvalidChainId(CryptoError),
     #[error("unexpected application operations added during non-mutation query")]
     UnexpectedOperationsFromQuery,
+    #[error("application service exceeded its query limits: {error}")]
+    ServiceQueryLimitExceeded { error: String },
+    #[error(transparent)]
+    ViewError(#[from] ViewError),
+    #[error("chain {chain_id} has no block at height {height}")]
+    BlockNotFound {
+        chain_id: ChainId,
+        height: BlockHeight,
+    },
+}
+
+impl NodeServiceError {
+    /// Converts the error of a failed application query, reporting separately the queries
+    /// that were aborted for exceeding their resource limits.
+    fn from_query_error(error: ChainClientError) -> Self {
+        if let ChainClientError::LocalNodeError(LocalNodeError::WorkerError(
+            WorkerError::ChainError(chain_error),
+        )) = &error
+        {
+            if let ChainError::ExecutionError(execution_error, _) = &**chain_error {
+                if matches!(
+                    **execution_error,
+                    ExecutionError::ServiceQueryFuelLimitExceeded(_)
+                        | ExecutionError::ServiceQueryTimedOut(_)
+                        | ExecutionError::ServiceQueryResponseTooLarge { .. }
+                ) {
+                    return NodeServiceError::ServiceQueryLimitExceeded {
+                        error: execution_error.to_string(),
+                    };
+                }
+            }
+        }
+        error.into()
+    }
 }
 
 impl From<ServerError> for NodeServiceError {
@@ -123,9 +339,18 @@ impl IntoResponse for NodeServiceError {
             NodeServiceError::JsonError(e) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, vec![e.to_string()])
             }
+            NodeServiceError::ViewError(e) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, vec![e.to_string()])
+            }
+            NodeServiceError::InvalidRequestBody(_) => {
+                (StatusCode::BAD_REQUEST, vec![self.to_string()])
+            }
             NodeServiceError::UnexpectedOperationsFromQuery => {
                 (StatusCode::INTERNAL_SERVER_ERROR, vec![self.to_string()])
             }
+            NodeServiceError::ServiceQueryLimitExceeded { .. } => {
+                (StatusCode::UNPROCESSABLE_ENTITY, vec![self.to_string()])
+            }
             NodeServiceError::MissingOperation
             | NodeServiceError::HeterogeneousOperations
             | NodeServiceError::UnsupportedQueryType => {
@@ -143,6 +368,9 @@ impl IntoResponse for NodeServiceError {
                 StatusCode::BAD_REQUEST,
                 vec!["invalid chain ID".to_string()],
             ),
+            NodeServiceError::BlockNotFound { .. } => {
+                (StatusCode::NOT_FOUND, vec![self.to_string()])
+            }
         };
         let tuple = (tuple.0, json!({"error": tuple.1}).to_string());
         tuple.into_response()
@@ -162,6 +390,36 @@ where
         let client = self.context.lock().await.make_chain_client(chain_id)?;
         Ok(client.subscribe().await?)
     }
+
+    /// Subscribes to the events emitted by applications in the new blocks of the specified
+    /// chain, optionally only by the given application.
+    async fn events(
+        &self,
+        chain_id: ChainId,
+        application_id: Option<UserApplicationId>,
+    ) -> Result<impl Stream<Item = ChainEvent>, Error> {
+        let client = self.context.lock().await.make_chain_client(chain_id)?;
+        let notifications = client.subscribe().await?;
+        Ok(notifications
+            .filter_map(move |notification| {
+                let client = client.clone();
+                async move {
+                    let Reason::NewBlock { hash, .. } = notification.reason else {
+                        return None;
+                    };
+                    let block = match client.read_hashed_confirmed_block(hash).await {
+                        Ok(block) => block,
+                        Err(error) => {
+                            warn!(%error, %hash, "Failed to read the events of a new block");
+                            return None;
+                        }
+                    };
+                    let events = ChainEvent::from_block(block, application_id).collect::<Vec<_>>();
+                    Some(stream::iter(events))
+                }
+            })
+            .flatten())
+    }
 }
 
 impl<C> MutationRoot<C>
@@ -735,6 +993,302 @@ where
         }
     }
 
+    /// Returns the events emitted by applications in the confirmed blocks of a chain, in the
+    /// order they were emitted, starting with the block at height `from`, optionally only by
+    /// the given application. At most `limit` events are returned, up to 100, and at most 100
+    /// blocks are read: fewer events are returned if these blocks don't contain enough of them.
+    async fn events(
+        &self,
+        chain_id: ChainId,
+        application_id: Option<UserApplicationId>,
+        from: Option<BlockHeight>,
+        limit: Option<u32>,
+    ) -> Result<Vec<ChainEvent>, Error> {
+        let client = self.context.lock().await.make_chain_client(chain_id)?;
+        let limit = usize::try_from(limit.unwrap_or(100))?.min(MAX_PAGE_SIZE);
+        let start = usize::try_from(from.unwrap_or_default().0)?;
+        let hashes = {
+            let view = client.chain_state_view().await?;
+            let end = start.saturating_add(MAX_SCANNED_BLOCKS);
+            view.confirmed_log.read(start..end).await?
+        };
+        let mut events = Vec::new();
+        for hash in hashes {
+            if events.len() >= limit {
+                break;
+            }
+            let block = client.read_hashed_confirmed_block(hash).await?;
+            events.extend(ChainEvent::from_block(block, application_id));
+        }
+        events.truncate(limit);
+        Ok(events)
+    }
+
+    /// Returns the storage used by each view of a chain's state, and by the state of each
+    /// application, from the largest to the smallest. This reads the whole state of the chain,
+    /// so it is only available if the node service was started with `--storage-stats`.
+    async fn storage_stats(&self, chain_id: ChainId) -> Result<Vec<ViewStorageStats>, Error> {
+        if !self.storage_stats {
+            return Err(Error::new(
+                "Storage statistics are disabled: start the node service with `--storage-stats`",
+            ));
+        }
+        let client = self.context.lock().await.make_chain_client(chain_id)?;
+        let view = client.chain_state_view().await?;
+        let views = [
+            (
+                "execution_state.system",
+                view.execution_state.system.storage_stats().await?,
+            ),
+            (
+                "execution_state_hash",
+                view.execution_state_hash.storage_stats().await?,
+            ),
+            ("tip_state", view.tip_state.storage_stats().await?),
+            ("manager", view.manager.storage_stats().await?),
+            (
+                "pending_validated_blobs",
+                view.pending_validated_blobs.storage_stats().await?,
+            ),
+            (
+                "pending_proposed_blobs",
+                view.pending_proposed_blobs.storage_stats().await?,
+            ),
+            ("confirmed_log", view.confirmed_log.storage_stats().await?),
+            ("received_log", view.received_log.storage_stats().await?),
+            (
+                "received_certificate_trackers",
+                view.received_certificate_trackers.storage_stats().await?,
+            ),
+            ("inboxes", view.inboxes.storage_stats().await?),
+            (
+                "unskippable_bundles",
+                view.unskippable_bundles.storage_stats().await?,
+            ),
+            (
+                "removed_unskippable_bundles",
+                view.removed_unskippable_bundles.storage_stats().await?,
+            ),
+            ("outboxes", view.outboxes.storage_stats().await?),
+            (
+                "outbox_counters",
+                view.outbox_counters.storage_stats().await?,
+            ),
+            ("channels", view.channels.storage_stats().await?),
+            (
+                "committee_certificates",
+                view.committee_certificates.storage_stats().await?,
+            ),
+            ("base_fee", view.base_fee.storage_stats().await?),
+        ];
+        let mut stats = views
+            .into_iter()
+            .map(|(name, stats)| ViewStorageStats {
+                view: name.to_owned(),
+                application_id: None,
+                stats,
+            })
+            .collect::<Vec<_>>();
+        let users = &view.execution_state.users;
+        for application_id in users.indices().await? {
+            let Some(state) = users.try_load_entry(&application_id).await? else {
+                continue;
+            };
+            stats.push(ViewStorageStats {
+                view: "execution_state.users".to_owned(),
+                application_id: Some(application_id),
+                stats: state.storage_stats().await?,
+            });
+        }
+        stats.sort_by(|stats1, stats2| stats2.stats.total_size.cmp(&stats1.stats.total_size));
+        Ok(stats)
+    }
+
+    /// Returns the applications registered on a chain, a page at a time. The cursor of each
+    /// application is its ID.
+    async fn applications_connection(
+        &self,
+        chain_id: ChainId,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, ApplicationOverview>, Error> {
+        let client = self.context.lock().await.make_chain_client(chain_id)?;
+        let port = self.port;
+        connection::query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<String>, _, first, _| async move {
+                let after = after
+                    .map(|after| after.parse::<UserApplicationId>())
+                    .transpose()?;
+                let limit = page_size(first);
+                let view = client.chain_state_view().await?;
+                let known_applications = &view.execution_state.system.registry.known_applications;
+                if let Some(after) = &after {
+                    ensure!(
+                        known_applications.contains_key(after).await?,
+                        Error::new(format!("Unknown application cursor: {after}"))
+                    );
+                }
+                let mut ids = known_applications
+                    .indices_after(after.as_ref(), Some(limit.saturating_add(1)))
+                    .await?;
+                let has_next_page = ids.len() > limit;
+                ids.truncate(limit);
+                let mut connection = Connection::new(after.is_some(), has_next_page);
+                for id in ids {
+                    let description = known_applications
+                        .get(&id)
+                        .await?
+                        .ok_or_else(|| Error::new(format!("Missing application {id}")))?;
+                    let overview = ApplicationOverview::new(id, description, port, chain_id);
+                    connection.edges.push(Edge::new(id.to_string(), overview));
+                }
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Returns the confirmed blocks of a chain in increasing order of height, a page at a
+    /// time. The cursor of each block is its height.
+    async fn blocks_connection(
+        &self,
+        chain_id: ChainId,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<usize, Hashed<ConfirmedBlock>>, Error> {
+        let client = self.context.lock().await.make_chain_client(chain_id)?;
+        connection::query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<usize>, _, first, _| async move {
+                let start = after.map_or(0, |after| after.saturating_add(1));
+                let (hashes, count) = {
+                    let view = client.chain_state_view().await?;
+                    let count = view.confirmed_log.count();
+                    if let Some(after) = after {
+                        ensure!(
+                            after < count,
+                            Error::new(format!("Unknown block cursor: {after}"))
+                        );
+                    }
+                    let end = count.min(start.saturating_add(page_size(first)));
+                    (view.confirmed_log.read(start..end).await?, count)
+                };
+                let has_next_page = start + hashes.len() < count;
+                let mut connection = Connection::new(start > 0, has_next_page);
+                for (height, hash) in (start..).zip(hashes) {
+                    let block = client.read_hashed_confirmed_block(hash).await?;
+                    connection.edges.push(Edge::new(height, block));
+                }
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Returns the operations of the confirmed blocks of a chain in the order they were
+    /// executed, a page at a time. The cursor of each operation is formatted as
+    /// `<height>:<index>`.
+    ///
+    /// At most 100 blocks are read per page, so a page can have fewer operations than
+    /// requested even though there are more: `resumeAfter` is then the cursor of the end of
+    /// the last block that was read, formatted as `<height>`.
+    async fn operations_connection(
+        &self,
+        chain_id: ChainId,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<OperationCursor, OperationEntry, OperationPage>, Error> {
+        let client = self.context.lock().await.make_chain_client(chain_id)?;
+        connection::query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<OperationCursor>, _, first, _| async move {
+                let limit = page_size(first);
+                let (hashes, start, count) = {
+                    let view = client.chain_state_view().await?;
+                    let count = view.confirmed_log.count();
+                    let start = match after {
+                        Some(after) => {
+                            ensure!(
+                                after.height < count,
+                                Error::new(format!("Unknown operation cursor: {after}"))
+                            );
+                            // The block of an operation cursor is read again, to continue
+                            // after that operation.
+                            after.height + usize::from(after.index.is_none())
+                        }
+                        None => 0,
+                    };
+                    let end = count.min(start.saturating_add(MAX_SCANNED_BLOCKS));
+                    (view.confirmed_log.read(start..end).await?, start, count)
+                };
+                let mut has_next_page = start + hashes.len() < count;
+                let mut resume_after = after;
+                let mut edges = Vec::new();
+                'blocks: for (height, hash) in (start..).zip(hashes) {
+                    let block = client.read_hashed_confirmed_block(hash).await?;
+                    let header = &block.inner().block().header;
+                    let operations = &block.inner().block().body.operations;
+                    let first_index = match after {
+                        Some(
+                            after @ OperationCursor {
+                                index: Some(index), ..
+                            },
+                        ) if after.height == height => {
+                            ensure!(
+                                index < operations.len(),
+                                Error::new(format!("Unknown operation cursor: {after}"))
+                            );
+                            index + 1
+                        }
+                        _ => 0,
+                    };
+                    for (index, operation) in operations.iter().enumerate().skip(first_index) {
+                        if edges.len() == limit {
+                            has_next_page = true;
+                            break 'blocks;
+                        }
+                        let entry = OperationEntry {
+                            height: header.height,
+                            block_hash: hash,
+                            index: u32::try_from(index)?,
+                            operation: operation.clone(),
+                        };
+                        let position = OperationCursor {
+                            height,
+                            index: Some(index),
+                        };
+                        resume_after = Some(position);
+                        edges.push(Edge::new(position, entry));
+                    }
+                    resume_after = Some(OperationCursor {
+                        height,
+                        index: None,
+                    });
+                }
+                let page = OperationPage {
+                    resume_after: has_next_page
+                        .then(|| resume_after.map(|cursor| cursor.encode_cursor()))
+                        .flatten(),
+                };
+                let mut connection =
+                    Connection::with_additional_fields(after.is_some(), has_next_page, page);
+                connection.edges = edges;
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+
     /// Returns the version information on this node service.
     async fn version(&self) -> linera_version::VersionInfo {
         linera_version::VersionInfo::default()
@@ -869,6 +1423,9 @@ where
     default_chain: Option<ChainId>,
     storage: C::Storage,
     context: Arc<Mutex<C>>,
+    webhooks: WebhookConfig,
+    genesis_config_hash: Option<CryptoHash>,
+    storage_stats: bool,
 }
 
 impl<C> Clone for NodeService<C>
@@ -882,6 +1439,9 @@ where
             default_chain: self.default_chain,
             storage: self.storage.clone(),
             context: Arc::clone(&self.context),
+            webhooks: self.webhooks.clone(),
+            genesis_config_hash: self.genesis_config_hash,
+            storage_stats: self.storage_stats,
         }
     }
 }
@@ -904,15 +1464,38 @@ where
             default_chain,
             storage,
             context: Arc::new(Mutex::new(context)),
+            webhooks: WebhookConfig::default(),
+            genesis_config_hash: None,
+            storage_stats: false,
         }
     }
 
+    /// Configures the webhooks notified of the events of the wallet's chains.
+    pub fn with_webhooks(mut self, webhooks: WebhookConfig) -> Self {
+        self.webhooks = webhooks;
+        self
+    }
+
+    /// Configures the hash of the genesis configuration returned to gRPC-Web clients.
+    pub fn with_genesis_config_hash(mut self, genesis_config_hash: CryptoHash) -> Self {
+        self.genesis_config_hash = Some(genesis_config_hash);
+        self
+    }
+
+    /// Configures whether the `storageStats` query is answered. It reads the whole state of a
+    /// chain, so it should only be enabled for the operator of the node service.
+    pub fn with_storage_stats(mut self, storage_stats: bool) -> Self {
+        self.storage_stats = storage_stats;
+        self
+    }
+
     pub fn schema(&self) -> Schema<QueryRoot<C>, MutationRoot<C>, SubscriptionRoot<C>> {
         Schema::build(
             QueryRoot {
                 context: Arc::clone(&self.context),
                 port: self.port,
                 default_chain: self.default_chain,
+                storage_stats: self.storage_stats,
             },
             MutationRoot {
                 context: Arc::clone(&self.context),
@@ -924,31 +1507,76 @@ where
         .finish()
     }
 
-    /// Runs the node service.
-    #[instrument(name = "node_service", level = "info", skip(self), fields(port = ?self.port))]
-    pub async fn run(self) -> Result<(), anyhow::Error> {
-        let port = self.port.get();
+    /// Returns the GraphQL schema of an application, obtained by running the introspection
+    /// query on the given chain.
+    pub async fn application_schema(
+        &self,
+        application_id: UserApplicationId,
+        chain_id: ChainId,
+    ) -> anyhow::Result<IntrospectedSchema> {
+        let request = Request::new(INTROSPECTION_QUERY);
+        let response = self
+            .user_application_query(application_id, &request, chain_id)
+            .await?;
+        IntrospectedSchema::from_response(response)
+    }
+
+    /// Returns the router serving the GraphQL and REST endpoints of the node service, and the
+    /// read-only requests of the validator gRPC API to gRPC-Web clients.
+    fn router(&self) -> Router {
+        let grpc_web = Routes::new(tonic_web::enable(ValidatorNodeServer::new(
+            NodeServiceGrpc::new(self.storage.clone(), self.genesis_config_hash),
+        )))
+        .into_axum_router();
         let index_handler = axum::routing::get(util::graphiql).post(Self::index_handler);
         let application_handler =
             axum::routing::get(util::graphiql).post(Self::application_handler);
 
-        let app = Router::new()
+        Router::new()
             .route("/", index_handler)
             .route(
                 "/chains/:chain_id/applications/:application_id",
                 application_handler,
             )
+            .route(
+                "/chains/:chain_id/applications/:application_id/*path",
+                axum::routing::get(Self::application_http_handler)
+                    .post(Self::application_http_handler),
+            )
+            .route(
+                "/api/v1/chains/:chain_id/balance",
+                axum::routing::get(Self::balance_handler),
+            )
+            .route(
+                "/api/v1/chains/:chain_id/operations",
+                axum::routing::post(Self::operation_handler),
+            )
+            .route(
+                "/api/v1/chains/:chain_id/blocks/:height",
+                axum::routing::get(Self::block_handler),
+            )
             .route("/ready", axum::routing::get(|| async { "ready!" }))
             .route_service("/ws", GraphQLSubscription::new(self.schema()))
+            .merge(grpc_web)
             .layer(Extension(self.clone()))
             // TODO(#551): Provide application authentication.
-            .layer(CorsLayer::permissive());
+            .layer(CorsLayer::permissive())
+    }
+
+    /// Runs the node service.
+    #[instrument(name = "node_service", level = "info", skip(self), fields(port = ?self.port))]
+    pub async fn run(self) -> Result<(), anyhow::Error> {
+        let port = self.port.get();
+        let app = self.router();
 
         info!("GraphiQL IDE: http://localhost:{}", port);
 
         ChainListener::new(self.config)
             .run(Arc::clone(&self.context), self.storage.clone())
             .await;
+        if let Some(notifier) = WebhookNotifier::new(self.webhooks)? {
+            notifier.run(Arc::clone(&self.context)).await?;
+        }
         let serve_fut = axum::serve(
             tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).await?,
             app,
@@ -1004,7 +1632,46 @@ where
             return Err(NodeServiceError::ApplicationServiceError { errors });
         }
         trace!("Operations: {operations:?}");
+        let hash = self.execute_user_operations(chain_id, operations).await?;
+        Ok(async_graphql::Response::new(hash.to_value()))
+    }
+
+    /// Handles plain HTTP requests for user applications.
+    ///
+    /// The operations scheduled by the application while handling a `POST` request are
+    /// executed in a new block.
+    async fn user_application_http_request(
+        &self,
+        application_id: UserApplicationId,
+        request: &HttpRequest,
+        chain_id: ChainId,
+    ) -> Result<HttpResponse, NodeServiceError> {
+        debug!("HTTP request: {:?}", &request);
+        let QueryOutcome {
+            response,
+            operations,
+        } = self
+            .query_user_application(application_id, request, chain_id)
+            .await?;
+        let response = serde_json::from_slice::<HttpResponse>(&response)?;
+        if !operations.is_empty() {
+            ensure!(
+                request.method == HttpMethod::Post,
+                NodeServiceError::UnexpectedOperationsFromQuery
+            );
+            trace!("Operations: {operations:?}");
+            self.execute_user_operations(chain_id, operations).await?;
+        }
+        Ok(response)
+    }
 
+    /// Executes the operations scheduled by a user application in a new block, and returns
+    /// the hash of the block.
+    async fn execute_user_operations(
+        &self,
+        chain_id: ChainId,
+        operations: Vec<Operation>,
+    ) -> Result<CryptoHash, NodeServiceError> {
         let client = self
             .context
             .lock()
@@ -1026,14 +1693,14 @@ where
             })?;
             util::wait_for_next_round(&mut stream, timeout).await;
         };
-        Ok(async_graphql::Response::new(hash.to_value()))
+        Ok(hash)
     }
 
     /// Queries a user application, returning the raw [`QueryOutcome`].
     async fn query_user_application(
         &self,
         application_id: UserApplicationId,
-        request: &Request,
+        request: &impl Serialize,
         chain_id: ChainId,
     ) -> Result<QueryOutcome<Vec<u8>>, NodeServiceError> {
         let bytes = serde_json::to_vec(&request)?;
@@ -1052,7 +1719,10 @@ where
         let QueryOutcome {
             response,
             operations,
-        } = client.query_application(query).await?;
+        } = client
+            .query_application(query)
+            .await
+            .map_err(NodeServiceError::from_query_error)?;
         match response {
             QueryResponse::System(_) => {
                 unreachable!("cannot get a system response for a user query")
@@ -1064,6 +1734,82 @@ where
         }
     }
 
+    /// Returns a client for the chain `chain_id`, if it is in the wallet.
+    async fn chain_client(
+        &self,
+        chain_id: &str,
+    ) -> Result<ChainClient<C::ValidatorNodeProvider, C::Storage>, NodeServiceError> {
+        let chain_id: ChainId = chain_id.parse().map_err(NodeServiceError::InvalidChainId)?;
+        self.context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .map_err(|_| NodeServiceError::UnknownChainId {
+                chain_id: chain_id.to_string(),
+            })
+    }
+
+    /// Returns the balance of a chain, or of an account if an `owner` is specified, after
+    /// staging the execution of its incoming messages.
+    async fn balance_handler(
+        Path(chain_id): Path<String>,
+        UrlQuery(query): UrlQuery<BalanceQuery>,
+        service: Extension<Self>,
+    ) -> Result<Json<BalanceResponse>, NodeServiceError> {
+        let client = service.0.chain_client(&chain_id).await?;
+        let balance = match query.owner {
+            Some(owner) => client.query_owner_balance(owner).await?,
+            None => client.query_balance().await?,
+        };
+        Ok(Json(BalanceResponse { balance }))
+    }
+
+    /// Executes an operation in a new block, and returns the hash of the block.
+    async fn operation_handler(
+        Path(chain_id): Path<String>,
+        service: Extension<Self>,
+        Json(operation): Json<Operation>,
+    ) -> Result<Json<OperationResponse>, NodeServiceError> {
+        let client = service.0.chain_client(&chain_id).await?;
+        let hash = loop {
+            let timeout = match client.execute_operation(operation.clone()).await? {
+                ClientOutcome::Committed(certificate) => break certificate.hash(),
+                ClientOutcome::WaitForTimeout(timeout) => timeout,
+            };
+            let mut stream = client.subscribe().await.map_err(|_| {
+                ChainClientError::InternalError("Could not subscribe to the local node.")
+            })?;
+            util::wait_for_next_round(&mut stream, timeout).await;
+        };
+        Ok(Json(OperationResponse { hash }))
+    }
+
+    /// Returns the confirmed block of a chain at the given height.
+    async fn block_handler(
+        Path((chain_id, height)): Path<(String, u64)>,
+        service: Extension<Self>,
+    ) -> Result<Json<BlockResponse>, NodeServiceError> {
+        let client = service.0.chain_client(&chain_id).await?;
+        let height = BlockHeight(height);
+        let index = height.try_into().map_err(ChainClientError::from)?;
+        let hash = client
+            .chain_state_view()
+            .await
+            .map_err(ChainClientError::from)?
+            .confirmed_log
+            .get(index)
+            .await?
+            .ok_or(NodeServiceError::BlockNotFound {
+                chain_id: client.chain_id(),
+                height,
+            })?;
+        let block = client.read_hashed_confirmed_block(hash).await?;
+        Ok(Json(BlockResponse {
+            hash,
+            block: block.into_inner(),
+        }))
+    }
+
     /// Executes a GraphQL query and generates a response for our `Schema`.
     async fn index_handler(service: Extension<Self>, request: GraphQLRequest) -> GraphQLResponse {
         service
@@ -1108,4 +1854,42 @@ where
 
         Ok(response.into())
     }
+
+    /// Forwards a plain HTTP request, with its JSON body if any, to the service of an
+    /// application, and replies with the application's response.
+    async fn application_http_handler(
+        method: Method,
+        Path((chain_id, application_id, path)): Path<(String, String, String)>,
+        service: Extension<Self>,
+        body: Bytes,
+    ) -> Result<(StatusCode, Json<serde_json::Value>), NodeServiceError> {
+        let chain_id: ChainId = chain_id.parse().map_err(NodeServiceError::InvalidChainId)?;
+        let application_id: UserApplicationId = application_id.parse()?;
+        let method = if method == Method::POST {
+            HttpMethod::Post
+        } else {
+            HttpMethod::Get
+        };
+        let body = if body.is_empty() {
+            serde_json::Value::Null
+        } else {
+            serde_json::from_slice(&body).map_err(NodeServiceError::InvalidRequestBody)?
+        };
+        let request = HttpRequest {
+            method,
+            path: format!("/{path}"),
+            body,
+        };
+        let response = service
+            .0
+            .user_application_http_request(application_id, &request, chain_id)
+            .await?;
+        let status =
+            StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        Ok((status, Json(response.body)))
+    }
 }
+
+#[cfg(test)]
+#[path = "unit_tests/node_service.rs"]
+mod tests;
@@ -0,0 +1,264 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The read-only part of the validator gRPC API, served by the node service over gRPC-Web.
+//!
+//! This lets browser-based clients use the same gRPC-Web client code against their node
+//! service as against a validator proxy, to read chain information, blobs and certificates
+//! from the node's local storage. Requests that would change a chain are not supported: they
+//! must be sent to the validators, or made with the GraphQL API of the node service.
+
+use std::num::NonZeroUsize;
+
+use async_trait::async_trait;
+use futures::stream;
+use linera_core::{node::NodeError, worker::WorkerState};
+use linera_rpc::grpc::{
+    api::{
+        self, validator_node_server::ValidatorNode, BlobContent, BlobId, BlobIds, BlockProposal,
+        Certificate, CertificatesBatchRequest, CertificatesBatchResponse, ChainInfoQuery,
+        ChainInfoResult, CryptoHash, HandlePendingBlobRequest, LiteCertificate, Notification,
+        PendingBlobRequest, PendingBlobResult, SubscriptionRequest, VersionInfo,
+    },
+    GRPC_CHUNKED_MESSAGE_FILL_LIMIT,
+};
+use linera_storage::Storage;
+use linera_views::views::ViewError;
+use prost::Message as _;
+use tonic::{Request, Response, Status};
+
+/// The number of chain workers kept in memory to answer chain information queries.
+const CHAIN_WORKER_LIMIT: usize = 100;
+
+/// Serves the read-only requests of the [`ValidatorNode`] gRPC service from the local storage.
+pub struct NodeServiceGrpc<S>
+where
+    S: Storage,
+{
+    storage: S,
+    worker: WorkerState<S>,
+    genesis_config_hash: Option<linera_base::crypto::CryptoHash>,
+}
+
+impl<S> NodeServiceGrpc<S>
+where
+    S: Storage + Clone + Send + Sync + 'static,
+{
+    /// Creates a service reading from the `storage` of a node of the network with the given
+    /// genesis configuration, if known.
+    pub fn new(storage: S, genesis_config_hash: Option<linera_base::crypto::CryptoHash>) -> Self {
+        let worker = WorkerState::new(
+            "Node service gRPC-Web".to_string(),
+            None,
+            storage.clone(),
+            NonZeroUsize::new(CHAIN_WORKER_LIMIT).expect("Chain worker limit should not be 0"),
+        );
+        NodeServiceGrpc {
+            storage,
+            worker,
+            genesis_config_hash,
+        }
+    }
+
+    /// Returns the status of requests that the node service doesn't answer.
+    fn read_only() -> Status {
+        Status::unimplemented(
+            "The node service only answers read requests; send this request to a validator",
+        )
+    }
+
+    /// Returns the appropriate gRPC status for the given [`ViewError`].
+    fn error_to_status(error: ViewError) -> Status {
+        match error {
+            ViewError::NotFound(_) | ViewError::BlobsNotFound(_) => {
+                Status::not_found(error.to_string())
+            }
+            _ => Status::internal(error.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> ValidatorNode for NodeServiceGrpc<S>
+where
+    S: Storage + Clone + Send + Sync + 'static,
+{
+    type SubscribeStream = stream::Empty<Result<Notification, Status>>;
+
+    async fn handle_block_proposal(
+        &self,
+        _request: Request<BlockProposal>,
+    ) -> Result<Response<ChainInfoResult>, Status> {
+        Err(Self::read_only())
+    }
+
+    async fn handle_lite_certificate(
+        &self,
+        _request: Request<LiteCertificate>,
+    ) -> Result<Response<ChainInfoResult>, Status> {
+        Err(Self::read_only())
+    }
+
+    async fn handle_confirmed_certificate(
+        &self,
+        _request: Request<api::HandleConfirmedCertificateRequest>,
+    ) -> Result<Response<ChainInfoResult>, Status> {
+        Err(Self::read_only())
+    }
+
+    async fn handle_validated_certificate(
+        &self,
+        _request: Request<api::HandleValidatedCertificateRequest>,
+    ) -> Result<Response<ChainInfoResult>, Status> {
+        Err(Self::read_only())
+    }
+
+    async fn handle_timeout_certificate(
+        &self,
+        _request: Request<api::HandleTimeoutCertificateRequest>,
+    ) -> Result<Response<ChainInfoResult>, Status> {
+        Err(Self::read_only())
+    }
+
+    async fn handle_chain_info_query(
+        &self,
+        request: Request<ChainInfoQuery>,
+    ) -> Result<Response<ChainInfoResult>, Status> {
+        let query = request.into_inner().try_into()?;
+        let result = match self.worker.handle_chain_info_query(query).await {
+            Ok((response, _actions)) => response.try_into()?,
+            Err(error) => NodeError::from(error).try_into()?,
+        };
+        Ok(Response::new(result))
+    }
+
+    async fn subscribe(
+        &self,
+        _request: Request<SubscriptionRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        Err(Status::unimplemented(
+            "Subscribe to notifications with the GraphQL API of the node service",
+        ))
+    }
+
+    async fn get_version_info(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<VersionInfo>, Status> {
+        Ok(Response::new(linera_version::VersionInfo::default().into()))
+    }
+
+    async fn get_genesis_config_hash(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<CryptoHash>, Status> {
+        let hash = self
+            .genesis_config_hash
+            .ok_or_else(|| Status::unavailable("The genesis configuration is not known"))?;
+        Ok(Response::new(hash.into()))
+    }
+
+    async fn upload_blob(
+        &self,
+        _request: Request<BlobContent>,
+    ) -> Result<Response<BlobId>, Status> {
+        Err(Self::read_only())
+    }
+
+    async fn download_blob(
+        &self,
+        request: Request<BlobId>,
+    ) -> Result<Response<BlobContent>, Status> {
+        let blob_id = request.into_inner().try_into()?;
+        let blob = self
+            .storage
+            .read_blob(blob_id)
+            .await
+            .map_err(Self::error_to_status)?;
+        Ok(Response::new(blob.into_content().try_into()?))
+    }
+
+    async fn download_pending_blob(
+        &self,
+        _request: Request<PendingBlobRequest>,
+    ) -> Result<Response<PendingBlobResult>, Status> {
+        Err(Self::read_only())
+    }
+
+    async fn handle_pending_blob(
+        &self,
+        _request: Request<HandlePendingBlobRequest>,
+    ) -> Result<Response<ChainInfoResult>, Status> {
+        Err(Self::read_only())
+    }
+
+    async fn download_certificate(
+        &self,
+        request: Request<CryptoHash>,
+    ) -> Result<Response<Certificate>, Status> {
+        let hash = request.into_inner().try_into()?;
+        let certificate: linera_chain::types::Certificate = self
+            .storage
+            .read_certificate(hash)
+            .await
+            .map_err(Self::error_to_status)?
+            .into();
+        Ok(Response::new(certificate.try_into()?))
+    }
+
+    async fn download_certificates(
+        &self,
+        request: Request<CertificatesBatchRequest>,
+    ) -> Result<Response<CertificatesBatchResponse>, Status> {
+        let hashes = request
+            .into_inner()
+            .hashes
+            .into_iter()
+            .map(linera_base::crypto::CryptoHash::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let certificates = self
+            .storage
+            .read_certificates(hashes)
+            .await
+            .map_err(Self::error_to_status)?;
+        // Like validators, only return as many certificates as fit in a gRPC message. Clients
+        // request the remaining ones again.
+        let mut size = 0;
+        let mut batch = Vec::new();
+        for certificate in certificates {
+            let certificate = linera_chain::types::Certificate::from(certificate);
+            size += Certificate::try_from(certificate.clone())?.encoded_len();
+            if size > GRPC_CHUNKED_MESSAGE_FILL_LIMIT {
+                break;
+            }
+            batch.push(certificate);
+        }
+        Ok(Response::new(CertificatesBatchResponse::try_from(batch)?))
+    }
+
+    async fn blob_last_used_by(
+        &self,
+        request: Request<BlobId>,
+    ) -> Result<Response<CryptoHash>, Status> {
+        let blob_id = request.into_inner().try_into()?;
+        let blob_state = self
+            .storage
+            .read_blob_state(blob_id)
+            .await
+            .map_err(Self::error_to_status)?;
+        Ok(Response::new(blob_state.last_used_by.into()))
+    }
+
+    async fn missing_blob_ids(
+        &self,
+        request: Request<BlobIds>,
+    ) -> Result<Response<BlobIds>, Status> {
+        let blob_ids: Vec<linera_base::identifiers::BlobId> = request.into_inner().try_into()?;
+        let missing_blob_ids = self
+            .storage
+            .missing_blobs(&blob_ids)
+            .await
+            .map_err(Self::error_to_status)?;
+        Ok(Response::new(missing_blob_ids.try_into()?))
+    }
+}
@@ -12,7 +12,8 @@ use cargo_toml::Manifest;
 use convert_case::{Case, Casing};
 use current_platform::CURRENT_PLATFORM;
 use fs_err::File;
-use tracing::debug;
+use linera_sdk::wasm_size;
+use tracing::{debug, info, warn};
 
 pub struct Project {
     root: PathBuf,
@@ -266,7 +267,15 @@ impl Project {
         (linera_sdk_dep, linera_sdk_dev_dep)
     }
 
-    pub fn build(&self, name: Option<String>) -> Result<(PathBuf, PathBuf), anyhow::Error> {
+    /// Builds the contract and service bytecodes of the project, and returns their paths.
+    ///
+    /// Unless `optimize` is false, the bytecodes are then optimized for size, and a breakdown
+    /// of their original sizes is logged.
+    pub fn build(
+        &self,
+        name: Option<String>,
+        optimize: bool,
+    ) -> Result<(PathBuf, PathBuf), anyhow::Error> {
         let name = match name {
             Some(name) => name,
             None => self.project_package_name()?.replace('-', "_"),
@@ -284,12 +293,40 @@ impl Project {
         let build_path = self
             .workspace_root()?
             .join("target/wasm32-unknown-unknown/release");
+        let contract_path = build_path.join(contract_name).with_extension("wasm");
+        let service_path = build_path.join(service_name).with_extension("wasm");
+        if !optimize {
+            return Ok((contract_path, service_path));
+        }
+        let optimized_path = build_path.join("optimized");
+        fs_err::create_dir_all(&optimized_path)?;
         Ok((
-            build_path.join(contract_name).with_extension("wasm"),
-            build_path.join(service_name).with_extension("wasm"),
+            Self::optimize(&contract_path, &optimized_path)?,
+            Self::optimize(&service_path, &optimized_path)?,
         ))
     }
 
+    /// Optimizes the bytecode at `path` into the `output_directory`, and returns the path of the
+    /// optimized bytecode.
+    fn optimize(path: &Path, output_directory: &Path) -> Result<PathBuf> {
+        let output_path = output_directory.join(path.file_name().context("invalid bytecode path")?);
+        let optimized = wasm_size::optimize(path, &output_path)?;
+        info!("Size of {}:\n{}", path.display(), optimized.original);
+        if !optimized.used_wasm_opt {
+            warn!(
+                "`wasm-opt` was not found; only stripping custom sections. Install Binaryen to \
+                make bytecodes smaller"
+            );
+        }
+        info!(
+            "Optimized {} from {} to {} bytes",
+            path.display(),
+            optimized.original.total(),
+            optimized.size
+        );
+        Ok(output_path)
+    }
+
     fn project_package_name(&self) -> Result<String> {
         let manifest = Manifest::from_path(self.cargo_toml_path())?;
         let name = manifest
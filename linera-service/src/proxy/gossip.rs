@@ -0,0 +1,509 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Gossiping of confirmed certificates between validators.
+//!
+//! Clients only need to submit a confirmed certificate to a quorum of validators. To keep the
+//! other validators up to date when the client disconnects early, the proxy forwards each
+//! new confirmed certificate that a client submitted successfully to the other validators of
+//! the chain's committee. Forwarded requests are marked with [`GOSSIP_HEADER`], and are not
+//! forwarded again, and neither are the certificates that the validator already had, so each
+//! certificate is gossiped at most once by each validator that received it first from a
+//! client. Validators that already have the certificate, e.g. because the client submitted it
+//! to them too, are only asked for their chain's next block height.
+
+use std::{str::FromStr as _, sync::Arc};
+
+use anyhow::{ensure, Context as _, Result};
+use async_trait::async_trait;
+use futures::future;
+use linera_base::{
+    data_types::{BlobContent, BlockHeight},
+    identifiers::ChainId,
+};
+use linera_chain::types::ConfirmedBlockCertificate;
+use linera_core::{data_types::ChainInfoQuery, node::NodeError};
+use linera_execution::committee::ValidatorName;
+use linera_rpc::{
+    config::ValidatorPublicNetworkConfig,
+    grpc::{
+        api::{self, validator_node_client::ValidatorNodeClient},
+        pool::GrpcConnectionPool,
+        protocol_version, GrpcClient, GrpcCompression, GRPC_MAX_MESSAGE_SIZE,
+    },
+    HandleConfirmedCertificateRequest,
+};
+use linera_storage::Storage;
+use tokio::sync::Semaphore;
+use tonic::{metadata::MetadataValue, transport::Channel, Request};
+use tracing::{warn, Instrument as _};
+
+/// The metadata key marking the requests forwarded by another validator.
+pub const GOSSIP_HEADER: &str = "linera-gossip";
+
+/// The maximum number of validators that certificates are being sent to at the same time.
+const MAX_CONCURRENT_SENDS: usize = 64;
+
+/// The maximum number of certificates waiting to be gossiped. New certificates are not gossiped
+/// while this many are pending.
+const MAX_PENDING_GOSSIPS: usize = 1024;
+
+/// The maximum number of earlier blocks sent to a validator that is missing them. Validators
+/// further behind must synchronize the chain themselves.
+const MAX_MISSING_BLOCKS: u64 = 100;
+
+/// Returns whether the `request` was forwarded by another validator.
+pub fn is_gossip<R>(request: &Request<R>) -> bool {
+    request.metadata().contains_key(GOSSIP_HEADER)
+}
+
+/// A validator that certificates are gossiped to.
+#[async_trait]
+pub trait GossipPeer: Send + Sync {
+    /// Sends a confirmed certificate, marked as gossip.
+    async fn handle_confirmed_certificate(
+        &self,
+        certificate: ConfirmedBlockCertificate,
+    ) -> Result<(), NodeError>;
+
+    /// Returns the height of the next block that the validator expects on the chain.
+    async fn next_block_height(&self, chain_id: ChainId) -> Result<BlockHeight, NodeError>;
+
+    /// Uploads a blob that the validator is missing.
+    async fn upload_blob(&self, content: BlobContent) -> Result<(), NodeError>;
+}
+
+/// Connects to the validators that certificates are gossiped to.
+pub trait GossipPeers: Send + Sync {
+    type Peer: GossipPeer;
+
+    /// Returns the validator at `address`.
+    fn connect(&self, address: &str) -> Result<Self::Peer>;
+}
+
+/// Connects to the other validators with gRPC.
+pub struct GrpcGossipPeers {
+    connection_pool: GrpcConnectionPool,
+    compression: GrpcCompression,
+}
+
+impl GrpcGossipPeers {
+    pub fn new(connection_pool: GrpcConnectionPool, compression: GrpcCompression) -> Self {
+        GrpcGossipPeers {
+            connection_pool,
+            compression,
+        }
+    }
+}
+
+impl GossipPeers for GrpcGossipPeers {
+    type Peer = GrpcGossipPeer;
+
+    fn connect(&self, address: &str) -> Result<GrpcGossipPeer> {
+        let network = ValidatorPublicNetworkConfig::from_str(address)?;
+        let channel = self.connection_pool.channel(network.http_address())?;
+        let mut client = ValidatorNodeClient::new(channel)
+            .max_encoding_message_size(GRPC_MAX_MESSAGE_SIZE)
+            .max_decoding_message_size(GRPC_MAX_MESSAGE_SIZE);
+        for encoding in GrpcCompression::ACCEPTED_ENCODINGS {
+            client = client.accept_compressed(encoding);
+        }
+        if let Some(encoding) = self.compression.encoding() {
+            client = client.send_compressed(encoding);
+        }
+        Ok(GrpcGossipPeer(client))
+    }
+}
+
+/// Another validator, reached with gRPC.
+pub struct GrpcGossipPeer(ValidatorNodeClient<Channel>);
+
+impl GrpcGossipPeer {
+    fn request<R>(message: R) -> Request<R> {
+        let mut request = Request::new(message);
+        protocol_version::add_protocol_version(&mut request);
+        request
+    }
+}
+
+#[async_trait]
+impl GossipPeer for GrpcGossipPeer {
+    async fn handle_confirmed_certificate(
+        &self,
+        certificate: ConfirmedBlockCertificate,
+    ) -> Result<(), NodeError> {
+        // The other validators don't need to wait for the cross-chain messages: the client
+        // already got its answer from this one.
+        let request = HandleConfirmedCertificateRequest {
+            certificate,
+            wait_for_outgoing_messages: false,
+        };
+        let mut request = Self::request(api::HandleConfirmedCertificateRequest::try_from(request)?);
+        request
+            .metadata_mut()
+            .insert(GOSSIP_HEADER, MetadataValue::from_static("1"));
+        let response = self.0.clone().handle_confirmed_certificate(request).await?;
+        GrpcClient::try_into_chain_info(response.into_inner())?;
+        Ok(())
+    }
+
+    async fn next_block_height(&self, chain_id: ChainId) -> Result<BlockHeight, NodeError> {
+        let query = api::ChainInfoQuery::try_from(ChainInfoQuery::new(chain_id))?;
+        let response = self
+            .0
+            .clone()
+            .handle_chain_info_query(Self::request(query))
+            .await?;
+        let response = GrpcClient::try_into_chain_info(response.into_inner())?;
+        Ok(response.info.next_block_height)
+    }
+
+    async fn upload_blob(&self, content: BlobContent) -> Result<(), NodeError> {
+        let content = api::BlobContent::try_from(content)?;
+        self.0.clone().upload_blob(Self::request(content)).await?;
+        Ok(())
+    }
+}
+
+/// Forwards confirmed certificates to the other validators of the committee.
+pub struct CertificateGossip<S, P = GrpcGossipPeers> {
+    /// The name of this validator, which is not sent its own certificates.
+    name: ValidatorName,
+    storage: S,
+    peers: P,
+    /// Bounds the number of validators that certificates are being sent to.
+    send_permits: Semaphore,
+    /// Bounds the number of certificates waiting to be gossiped.
+    pending_permits: Arc<Semaphore>,
+}
+
+impl<S, P> CertificateGossip<S, P>
+where
+    S: Storage + Clone + Send + Sync + 'static,
+    P: GossipPeers + 'static,
+{
+    pub fn new(name: ValidatorName, storage: S, peers: P) -> Self {
+        CertificateGossip {
+            name,
+            storage,
+            peers,
+            send_permits: Semaphore::new(MAX_CONCURRENT_SENDS),
+            pending_permits: Arc::new(Semaphore::new(MAX_PENDING_GOSSIPS)),
+        }
+    }
+
+    /// Returns the certificate of the `request` if it must be gossiped, i.e. if it was
+    /// submitted by a client and this validator didn't have it yet. This must be called
+    /// before the request is handled.
+    pub async fn certificate_to_gossip(
+        &self,
+        request: &Request<api::HandleConfirmedCertificateRequest>,
+    ) -> Result<Option<ConfirmedBlockCertificate>> {
+        if is_gossip(request) {
+            return Ok(None);
+        }
+        let request = HandleConfirmedCertificateRequest::try_from(request.get_ref().clone())?;
+        if self
+            .storage
+            .contains_certificate(request.certificate.hash())
+            .await?
+        {
+            return Ok(None);
+        }
+        Ok(Some(request.certificate))
+    }
+
+    /// Forwards the `certificate` to the other validators of the chain's committee in the
+    /// background, unless too many certificates are already waiting to be gossiped.
+    pub fn spawn(self: &Arc<Self>, certificate: ConfirmedBlockCertificate) {
+        let Ok(permit) = self.pending_permits.clone().try_acquire_owned() else {
+            warn!(
+                hash = %certificate.hash(),
+                "Too many certificates waiting to be gossiped; not gossiping this one"
+            );
+            return;
+        };
+        let gossip = self.clone();
+        tokio::spawn(
+            async move {
+                if let Err(error) = gossip.forward(&certificate).await {
+                    warn!(%error, "Failed to gossip a confirmed certificate");
+                }
+                drop(permit);
+            }
+            .in_current_span(),
+        );
+    }
+
+    async fn forward(&self, certificate: &ConfirmedBlockCertificate) -> Result<()> {
+        let addresses = self.peer_addresses(certificate.inner().chain_id()).await?;
+        let results = future::join_all(
+            addresses
+                .iter()
+                .map(|address| self.send(address, certificate)),
+        )
+        .await;
+        for (address, result) in addresses.iter().zip(results) {
+            if let Err(error) = result {
+                warn!(%address, %error, "Failed to gossip a confirmed certificate");
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the network addresses of the other validators of the chain's committee.
+    async fn peer_addresses(&self, chain_id: ChainId) -> Result<Vec<String>> {
+        let chain = self.storage.load_chain(chain_id).await?;
+        let Some((_, committee)) = chain.execution_state.system.current_committee() else {
+            return Ok(Vec::new());
+        };
+        Ok(committee
+            .validators()
+            .iter()
+            .filter(|(name, _)| **name != self.name)
+            .map(|(_, validator)| validator.network_address.clone())
+            .collect())
+    }
+
+    /// Sends the `certificate` to the validator at `address`, unless it already has it,
+    /// preceded by the earlier blocks of the chain that the validator is missing, as clients do
+    /// when they synchronize a validator.
+    async fn send(&self, address: &str, certificate: &ConfirmedBlockCertificate) -> Result<()> {
+        let _permit = self.send_permits.acquire().await?;
+        let peer = self.peers.connect(address)?;
+        let chain_id = certificate.inner().chain_id();
+        let height = certificate.inner().height();
+        let next_height = peer.next_block_height(chain_id).await?;
+        if next_height > height {
+            return Ok(());
+        }
+        if next_height < height {
+            let missing_blocks = height.0 - next_height.0;
+            ensure!(
+                missing_blocks <= MAX_MISSING_BLOCKS,
+                "The validator is missing {missing_blocks} blocks of chain {chain_id}"
+            );
+            let hashes = {
+                let chain = self.storage.load_chain(chain_id).await?;
+                chain
+                    .confirmed_log
+                    .read(next_height.try_into()?..height.try_into()?)
+                    .await?
+            };
+            for earlier_certificate in self.storage.read_certificates(hashes).await? {
+                self.send_with_blobs(&peer, &earlier_certificate).await?;
+            }
+        }
+        self.send_with_blobs(&peer, certificate).await
+    }
+
+    /// Sends the `certificate` to the `peer`, uploading the blobs it is missing if needed.
+    async fn send_with_blobs(
+        &self,
+        peer: &P::Peer,
+        certificate: &ConfirmedBlockCertificate,
+    ) -> Result<()> {
+        let blob_ids = match peer.handle_confirmed_certificate(certificate.clone()).await {
+            Err(NodeError::BlobsNotFound(blob_ids)) => blob_ids,
+            result => return Ok(result?),
+        };
+        let required_blob_ids = certificate.inner().required_blob_ids();
+        ensure!(
+            blob_ids
+                .iter()
+                .all(|blob_id| required_blob_ids.contains(blob_id)),
+            "The validator requested blobs that the certificate doesn't use"
+        );
+        // The certificate is confirmed, so the blobs must be in storage.
+        let maybe_blobs = self.storage.read_blobs(&blob_ids).await?;
+        for (blob_id, maybe_blob) in blob_ids.iter().zip(maybe_blobs) {
+            let blob = maybe_blob.with_context(|| format!("Missing blob {blob_id}"))?;
+            peer.upload_blob(blob.into_content()).await?;
+        }
+        Ok(peer
+            .handle_confirmed_certificate(certificate.clone())
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        sync::{Arc, Mutex},
+    };
+
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use linera_base::{
+        crypto::{CryptoHash, PublicKey},
+        data_types::{Blob, BlobContent, BlockHeight, Round},
+        hashed::Hashed,
+        identifiers::{BlobId, ChainId},
+    };
+    use linera_chain::{
+        data_types::BlockExecutionOutcome,
+        test::{make_first_block, BlockTestExt as _},
+        types::{ConfirmedBlock, ConfirmedBlockCertificate},
+    };
+    use linera_core::node::NodeError;
+    use linera_execution::{
+        committee::{Committee, Epoch, ValidatorName, ValidatorState},
+        system::SystemOperation,
+        ResourceControlPolicy,
+    };
+    use linera_rpc::{grpc::api, HandleConfirmedCertificateRequest};
+    use linera_storage::{DbStorage, Storage as _, TestClock};
+    use linera_views::{memory::MemoryStore, views::RootView as _};
+    use tonic::{metadata::MetadataValue, Request};
+
+    use super::{is_gossip, CertificateGossip, GossipPeer, GossipPeers, GOSSIP_HEADER};
+
+    /// Validators that record the certificates they receive, and reject them until they
+    /// have their blobs.
+    #[derive(Clone, Default)]
+    struct MockPeers {
+        delivered: Arc<Mutex<Vec<(String, CryptoHash)>>>,
+        uploaded: Arc<Mutex<BTreeSet<BlobId>>>,
+    }
+
+    struct MockPeer {
+        address: String,
+        peers: MockPeers,
+    }
+
+    impl GossipPeers for MockPeers {
+        type Peer = MockPeer;
+
+        fn connect(&self, address: &str) -> Result<MockPeer> {
+            Ok(MockPeer {
+                address: address.to_string(),
+                peers: self.clone(),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl GossipPeer for MockPeer {
+        async fn handle_confirmed_certificate(
+            &self,
+            certificate: ConfirmedBlockCertificate,
+        ) -> Result<(), NodeError> {
+            let uploaded = self.peers.uploaded.lock().unwrap().clone();
+            let missing_blob_ids = certificate
+                .inner()
+                .required_blob_ids()
+                .into_iter()
+                .filter(|blob_id| !uploaded.contains(blob_id))
+                .collect::<Vec<_>>();
+            if !missing_blob_ids.is_empty() {
+                return Err(NodeError::BlobsNotFound(missing_blob_ids));
+            }
+            self.peers
+                .delivered
+                .lock()
+                .unwrap()
+                .push((self.address.clone(), certificate.hash()));
+            Ok(())
+        }
+
+        /// The tests use a single chain, so its next block height is the number of delivered
+        /// certificates.
+        async fn next_block_height(&self, _chain_id: ChainId) -> Result<BlockHeight, NodeError> {
+            let delivered = self.peers.delivered.lock().unwrap();
+            let count = delivered
+                .iter()
+                .filter(|(address, _)| *address == self.address)
+                .count();
+            Ok(BlockHeight(count as u64))
+        }
+
+        async fn upload_blob(&self, content: BlobContent) -> Result<(), NodeError> {
+            let blob_id = Blob::new(content).id();
+            self.peers.uploaded.lock().unwrap().insert(blob_id);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn gossiped_requests_are_recognized() {
+        let mut request = Request::new(());
+        assert!(!is_gossip(&request));
+        request
+            .metadata_mut()
+            .insert(GOSSIP_HEADER, MetadataValue::from_static("1"));
+        assert!(is_gossip(&request));
+    }
+
+    /// Tests that a new certificate is delivered to the other validators with its blobs, is
+    /// not sent again to validators that have it, and is not gossiped again once this validator
+    /// has it.
+    #[tokio::test]
+    async fn certificates_are_delivered_and_not_gossiped_again() -> Result<()> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let chain_id = ChainId::root(0);
+        let name = ValidatorName(PublicKey::test_key(0));
+        let validators = [
+            (name, "local"),
+            (ValidatorName(PublicKey::test_key(1)), "peer"),
+        ]
+        .into_iter()
+        .map(|(name, address)| {
+            let state = ValidatorState {
+                network_address: address.to_string(),
+                votes: 1,
+            };
+            (name, state)
+        })
+        .collect();
+        let committee = Committee::new(validators, ResourceControlPolicy::default());
+        let mut chain = storage.load_chain(chain_id).await?;
+        chain.execution_state.system.epoch.set(Some(Epoch::ZERO));
+        chain
+            .execution_state
+            .system
+            .committees
+            .set(BTreeMap::from([(Epoch::ZERO, committee)]));
+        chain.save().await?;
+
+        let blob = Blob::new_data(b"blob".to_vec());
+        storage.write_blob(&blob).await?;
+        let block = make_first_block(chain_id).with_operation(SystemOperation::PublishDataBlob {
+            blob_hash: blob.id().hash,
+        });
+        let value = Hashed::new(ConfirmedBlock::new(
+            BlockExecutionOutcome::default().with(block),
+        ));
+        let certificate = ConfirmedBlockCertificate::new(value, Round::Fast, vec![]);
+        let request = Request::new(api::HandleConfirmedCertificateRequest::try_from(
+            HandleConfirmedCertificateRequest {
+                certificate: certificate.clone(),
+                wait_for_outgoing_messages: true,
+            },
+        )?);
+
+        let peers = MockPeers::default();
+        let gossip = CertificateGossip::new(name, storage.clone(), peers.clone());
+        let gossiped_certificate = gossip
+            .certificate_to_gossip(&request)
+            .await?
+            .expect("a new certificate should be gossiped");
+        gossip.forward(&gossiped_certificate).await?;
+        assert_eq!(
+            *peers.delivered.lock().unwrap(),
+            vec![("peer".to_string(), certificate.hash())]
+        );
+        assert!(peers.uploaded.lock().unwrap().contains(&blob.id()));
+
+        // The other validator already has the certificate, so it is not sent again.
+        gossip.forward(&gossiped_certificate).await?;
+        assert_eq!(peers.delivered.lock().unwrap().len(), 1);
+
+        // Once handled by this validator, the certificate is not gossiped again.
+        storage
+            .write_blobs_and_certificate(&[blob], &certificate)
+            .await?;
+        assert!(gossip.certificate_to_gossip(&request).await?.is_none());
+        Ok(())
+    }
+}
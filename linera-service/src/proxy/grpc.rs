@@ -11,6 +11,7 @@ use std::{
     fmt::Debug,
     marker::PhantomData,
     net::SocketAddr,
+    pin::Pin,
     sync::Arc,
     task::{Context, Poll},
     time::Duration,
@@ -18,13 +19,16 @@ use std::{
 
 use anyhow::Result;
 use async_trait::async_trait;
-use futures::{future::BoxFuture, FutureExt as _};
+use futures::{future::BoxFuture, FutureExt as _, Stream};
+use http::{HeaderName, Method};
 use linera_base::identifiers::ChainId;
 use linera_client::config::GenesisConfig;
 use linera_core::{notifier::ChannelNotifier, JoinSetExt as _};
+use linera_execution::committee::ValidatorName;
 use linera_rpc::{
     config::{
-        ShardConfig, TlsConfig, ValidatorInternalNetworkConfig, ValidatorPublicNetworkConfig,
+        NetworkProtocol, ShardConfig, TlsConfig, ValidatorInternalNetworkConfig,
+        ValidatorPublicNetworkConfig,
     },
     grpc::{
         api::{
@@ -38,9 +42,12 @@ use linera_rpc::{
             PendingBlobResult, SubscriptionRequest, VersionInfo,
         },
         pool::GrpcConnectionPool,
-        GrpcProtoConversionError, GrpcProxyable, GRPC_CHUNKED_MESSAGE_FILL_LIMIT,
-        GRPC_MAX_MESSAGE_SIZE,
+        protocol_version::{self, negotiate_protocol_version},
+        trace_context::{self, TraceContextLayer},
+        GrpcCompression, GrpcProtoConversionError, GrpcProxyable, TlsCertificates,
+        GRPC_CHUNKED_MESSAGE_FILL_LIMIT, GRPC_MAX_MESSAGE_SIZE,
     },
+    websocket::WebSocketNotificationServer,
 };
 use linera_sdk::{base::Blob, views::ViewError};
 use linera_storage::Storage;
@@ -49,19 +56,26 @@ use tokio::{select, task::JoinSet};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tokio_util::sync::CancellationToken;
 use tonic::{
-    transport::{Channel, Identity, Server, ServerTlsConfig},
+    service::interceptor::InterceptedService,
+    transport::{Channel, Server},
     Request, Response, Status,
 };
+use tonic_web::GrpcWebLayer;
 use tower::{builder::ServiceBuilder, Layer, Service};
-use tracing::{debug, info, instrument, Instrument as _, Level};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::{debug, error, info, instrument, warn, Instrument as _, Level};
 #[cfg(with_metrics)]
 use {
     linera_base::prometheus_util::{
-        bucket_latencies, register_histogram_vec, register_int_counter_vec,
+        bucket_latencies, register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
     },
-    prometheus::{HistogramVec, IntCounterVec},
+    prometheus::{HistogramVec, IntCounterVec, IntGaugeVec},
 };
 
+use super::{
+    gossip::{CertificateGossip, GrpcGossipPeers},
+    rate_limit::{LimitedStream, RateLimitConfig, RateLimitInterceptor, RateLimits},
+};
 #[cfg(with_metrics)]
 use crate::prometheus_server;
 
@@ -97,6 +111,48 @@ static PROXY_REQUEST_ERROR: LazyLock<IntCounterVec> = LazyLock::new(|| {
     )
 });
 
+#[cfg(with_metrics)]
+static PROXY_OPEN_STREAMS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec(
+        "proxy_open_streams",
+        "Number of open gRPC notification streams",
+        &[],
+    )
+});
+
+/// How long browsers may cache the response to a CORS preflight request.
+const GRPC_WEB_CORS_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The request headers that browsers are allowed to send with gRPC-Web requests.
+const GRPC_WEB_ALLOWED_HEADERS: [&str; 6] = [
+    "content-type",
+    "grpc-timeout",
+    "x-grpc-web",
+    "x-user-agent",
+    protocol_version::VERSION_HEADER,
+    protocol_version::MIN_VERSION_HEADER,
+];
+
+/// The response headers and trailers that browsers expose to gRPC-Web clients.
+const GRPC_WEB_EXPOSED_HEADERS: [&str; 5] = [
+    "grpc-status",
+    "grpc-message",
+    "grpc-status-details-bin",
+    protocol_version::VERSION_HEADER,
+    protocol_version::MIN_VERSION_HEADER,
+];
+
+/// Returns the CORS configuration allowing browser-based clients from any origin to make
+/// gRPC-Web requests, including the protocol version negotiation.
+fn grpc_web_cors() -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::mirror_request())
+        .allow_methods([Method::POST])
+        .allow_headers(GRPC_WEB_ALLOWED_HEADERS.map(HeaderName::from_static))
+        .expose_headers(GRPC_WEB_EXPOSED_HEADERS.map(HeaderName::from_static))
+        .max_age(GRPC_WEB_CORS_MAX_AGE)
+}
+
 #[derive(Clone)]
 pub struct PrometheusMetricsMiddlewareLayer;
 
@@ -145,6 +201,37 @@ where
     }
 }
 
+/// A notification stream, counted as open in the metrics until it is dropped.
+pub struct CountedStream<S> {
+    stream: S,
+}
+
+impl<S> CountedStream<S> {
+    fn new(stream: S) -> Self {
+        #[cfg(with_metrics)]
+        PROXY_OPEN_STREAMS.with_label_values(&[]).inc();
+        CountedStream { stream }
+    }
+}
+
+impl<S: Stream + Unpin> Stream for CountedStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.stream).poll_next(cx)
+    }
+}
+
+impl<S> Drop for CountedStream<S> {
+    fn drop(&mut self) {
+        #[cfg(with_metrics)]
+        PROXY_OPEN_STREAMS.with_label_values(&[]).dec();
+    }
+}
+
+/// The type of [`negotiate_protocol_version`], used as an interceptor.
+type NegotiateProtocolVersion = fn(Request<()>) -> Result<Request<()>, Status>;
+
 #[derive(Clone)]
 pub struct GrpcProxy<S>(Arc<GrpcProxyInner<S>>);
 
@@ -154,7 +241,14 @@ struct GrpcProxyInner<S> {
     genesis_config: GenesisConfig,
     worker_connection_pool: GrpcConnectionPool,
     notifier: ChannelNotifier<Result<Notification, Status>>,
+    websocket_port: Option<u16>,
+    websocket_server: WebSocketNotificationServer,
     tls: TlsConfig,
+    tls_certificates: TlsCertificates,
+    compression: GrpcCompression,
+    rate_limits: Arc<RateLimits>,
+    max_request_size: usize,
+    certificate_gossip: Option<Arc<CertificateGossip<S>>>,
     storage: S,
 }
 
@@ -162,36 +256,87 @@ impl<S> GrpcProxy<S>
 where
     S: Storage + Clone + Send + Sync + 'static,
 {
+    #[expect(clippy::too_many_arguments)]
     pub fn new(
         public_config: ValidatorPublicNetworkConfig,
         internal_config: ValidatorInternalNetworkConfig,
         genesis_config: GenesisConfig,
         connect_timeout: Duration,
         timeout: Duration,
+        websocket_port: Option<u16>,
         tls: TlsConfig,
+        tls_certificates: TlsCertificates,
+        compression: GrpcCompression,
+        rate_limit_config: RateLimitConfig,
+        name: ValidatorName,
+        certificate_gossip: bool,
         storage: S,
-    ) -> Self {
-        Self(Arc::new(GrpcProxyInner {
+    ) -> Result<Self> {
+        let internal_tls_config = if Self::uses_internal_tls(&internal_config) {
+            Some(tls_certificates.internal_client_config()?)
+        } else {
+            None
+        };
+        let certificate_gossip = certificate_gossip.then(|| {
+            let connection_pool = GrpcConnectionPool::default()
+                .with_connect_timeout(connect_timeout)
+                .with_timeout(timeout);
+            let peers = GrpcGossipPeers::new(connection_pool, compression);
+            Arc::new(CertificateGossip::new(name, storage.clone(), peers))
+        });
+        Ok(Self(Arc::new(GrpcProxyInner {
             public_config,
             internal_config,
             genesis_config,
             worker_connection_pool: GrpcConnectionPool::default()
                 .with_connect_timeout(connect_timeout)
-                .with_timeout(timeout),
+                .with_timeout(timeout)
+                .with_tls_config(internal_tls_config),
             notifier: ChannelNotifier::default(),
+            websocket_port,
+            websocket_server: WebSocketNotificationServer::default(),
             tls,
+            tls_certificates,
+            compression,
+            rate_limits: Arc::new(RateLimits::new(&rate_limit_config)),
+            max_request_size: rate_limit_config.max_request_size,
+            certificate_gossip,
             storage,
-        }))
+        })))
+    }
+
+    fn uses_internal_tls(internal_config: &ValidatorInternalNetworkConfig) -> bool {
+        internal_config.protocol == NetworkProtocol::Grpc(TlsConfig::Tls)
     }
 
-    fn as_validator_node(&self) -> ValidatorNodeServer<Self> {
-        ValidatorNodeServer::new(self.clone())
+    /// Returns the public service, which rejects the requests exceeding the rate limits
+    /// before decoding them.
+    fn as_validator_node(
+        &self,
+    ) -> InterceptedService<ValidatorNodeServer<Self>, RateLimitInterceptor> {
+        let mut server = ValidatorNodeServer::new(self.clone())
             .max_encoding_message_size(GRPC_MAX_MESSAGE_SIZE)
-            .max_decoding_message_size(GRPC_MAX_MESSAGE_SIZE)
+            .max_decoding_message_size(self.0.max_request_size);
+        for encoding in GrpcCompression::ACCEPTED_ENCODINGS {
+            server = server.accept_compressed(encoding);
+        }
+        if let Some(encoding) = self.0.compression.encoding() {
+            server = server.send_compressed(encoding);
+        }
+        InterceptedService::new(server, RateLimitInterceptor(self.0.rate_limits.clone()))
     }
 
-    fn as_notifier_service(&self) -> NotifierServiceServer<Self> {
-        NotifierServiceServer::new(self.clone())
+    fn as_notifier_service(
+        &self,
+    ) -> InterceptedService<NotifierServiceServer<Self>, NegotiateProtocolVersion> {
+        let mut server = NotifierServiceServer::new(self.clone());
+        for encoding in GrpcCompression::ACCEPTED_ENCODINGS {
+            server = server.accept_compressed(encoding);
+        }
+        InterceptedService::new(
+            server,
+            negotiate_protocol_version as NegotiateProtocolVersion,
+        )
     }
 
     fn public_address(&self) -> SocketAddr {
@@ -206,6 +351,11 @@ where
         SocketAddr::from(([0, 0, 0, 0], self.0.internal_config.port))
     }
 
+    fn websocket_address(&self) -> Option<SocketAddr> {
+        let port = self.0.websocket_port?;
+        Some(SocketAddr::from(([0, 0, 0, 0], port)))
+    }
+
     fn shard_for(&self, proxyable: &impl GrpcProxyable) -> Option<ShardConfig> {
         Some(
             self.0
@@ -219,11 +369,17 @@ where
         &self,
         shard: &ShardConfig,
     ) -> Result<ValidatorWorkerClient<Channel>> {
-        let address = shard.http_address();
+        let address = self.0.internal_config.shard_address(shard);
         let channel = self.0.worker_connection_pool.channel(address)?;
-        let client = ValidatorWorkerClient::new(channel)
+        let mut client = ValidatorWorkerClient::new(channel)
             .max_encoding_message_size(GRPC_MAX_MESSAGE_SIZE)
             .max_decoding_message_size(GRPC_MAX_MESSAGE_SIZE);
+        for encoding in GrpcCompression::ACCEPTED_ENCODINGS {
+            client = client.accept_compressed(encoding);
+        }
+        if let Some(encoding) = self.0.compression.encoding() {
+            client = client.send_compressed(encoding);
+        }
 
         Ok(client)
     }
@@ -247,12 +403,30 @@ where
         #[cfg(with_metrics)]
         prometheus_server::start_metrics(self.metrics_address(), shutdown_signal.clone());
 
+        if let Some(websocket_address) = self.websocket_address() {
+            info!(%websocket_address, "Serving notifications over WebSocket");
+            let websocket_server = self.0.websocket_server.clone();
+            let shutdown_signal = shutdown_signal.clone();
+            tokio::spawn(
+                async move {
+                    if let Err(error) = websocket_server
+                        .serve(websocket_address, shutdown_signal)
+                        .await
+                    {
+                        error!(%error, "WebSocket notification server failed");
+                    }
+                }
+                .in_current_span(),
+            );
+        }
+
         let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
         health_reporter
             .set_serving::<ValidatorNodeServer<GrpcProxy<S>>>()
             .await;
         let internal_server = join_set.spawn_task(
-            Server::builder()
+            self.internal_server()?
+                .layer(TraceContextLayer)
                 .add_service(self.as_notifier_service())
                 .serve(self.internal_address())
                 .in_current_span(),
@@ -260,17 +434,22 @@ where
         let reflection_service = tonic_reflection::server::Builder::configure()
             .register_encoded_file_descriptor_set(linera_rpc::FILE_DESCRIPTOR_SET)
             .build_v1()?;
+        // gRPC-Web requests from browsers are translated on every public service, so that they
+        // can reach the validator without a separate gRPC-Web proxy.
         let public_server = join_set.spawn_task(
             self.public_server()?
                 .layer(
                     ServiceBuilder::new()
                         .layer(PrometheusMetricsMiddlewareLayer)
+                        .layer(TraceContextLayer)
+                        .layer(grpc_web_cors())
+                        .layer(GrpcWebLayer::new())
                         .into_inner(),
                 )
                 .accept_http1(true)
                 .add_service(health_service)
-                .add_service(tonic_web::enable(self.as_validator_node()))
-                .add_service(tonic_web::enable(reflection_service))
+                .add_service(self.as_validator_node())
+                .add_service(reflection_service)
                 .serve_with_shutdown(self.public_address(), shutdown_signal.cancelled_owned())
                 .in_current_span(),
         );
@@ -283,35 +462,51 @@ where
     }
 
     /// Pre-configures the public server with no services attached.
-    /// If a certificate and key are defined, creates a TLS server.
+    /// If TLS is enabled, uses the configured certificate, or a self-signed one.
     fn public_server(&self) -> Result<Server> {
         match self.0.tls {
             TlsConfig::Tls => {
-                use linera_rpc::{CERT_PEM, KEY_PEM};
-                let identity = Identity::from_pem(CERT_PEM, KEY_PEM);
-                let tls_config = ServerTlsConfig::new().identity(identity);
+                let tls_config = self.0.tls_certificates.public_server_config();
                 Ok(Server::builder().tls_config(tls_config)?)
             }
             TlsConfig::ClearText => Ok(Server::builder()),
         }
     }
 
+    /// Pre-configures the internal server with no services attached.
+    /// If TLS is enabled on the internal network, only accepts clients authenticated with
+    /// the client certificate authority.
+    fn internal_server(&self) -> Result<Server> {
+        if Self::uses_internal_tls(&self.0.internal_config) {
+            let tls_config = self.0.tls_certificates.internal_server_config()?;
+            Ok(Server::builder().tls_config(tls_config)?)
+        } else {
+            Ok(Server::builder())
+        }
+    }
+
     async fn worker_client<R>(
         &self,
         request: Request<R>,
-    ) -> Result<(ValidatorWorkerClient<Channel>, R), Status>
+    ) -> Result<(ValidatorWorkerClient<Channel>, Request<R>), Status>
     where
         R: Debug + GrpcProxyable,
     {
         debug!("proxying request from {:?}", request.remote_addr());
         let inner = request.into_inner();
+        if let Some(chain_id) = inner.chain_id() {
+            self.0.rate_limits.check_chain(chain_id)?;
+        }
         let shard = self
             .shard_for(&inner)
             .ok_or_else(|| Status::not_found("could not find shard for message"))?;
         let client = self
             .worker_client_for_shard(&shard)
             .map_err(|_| Status::internal("could not connect to shard"))?;
-        Ok((client, inner))
+        let mut request = Request::new(inner);
+        protocol_version::add_protocol_version(&mut request);
+        trace_context::add_trace_context(&mut request);
+        Ok((client, request))
     }
 
     fn log_and_return_proxy_request_outcome(
@@ -338,12 +533,13 @@ where
     /// Returns the appropriate gRPC status for the given [`ViewError`].
     fn error_to_status(err: ViewError) -> Status {
         let mut status = match &err {
-            ViewError::TooLargeValue | ViewError::BcsError(_) => {
+            ViewError::TooLargeValue | ViewError::BcsError(_) | ViewError::CodecError(_) => {
                 Status::invalid_argument(err.to_string())
             }
             ViewError::StoreError { .. }
             | ViewError::TokioJoinError(_)
             | ViewError::TryLockError(_)
+            | ViewError::LockOrderConflict { .. }
             | ViewError::InconsistentEntries
             | ViewError::PostLoadValuesError
             | ViewError::IoError(_) => Status::internal(err.to_string()),
@@ -354,6 +550,7 @@ where
             | ViewError::BlobsNotFound(_)
             | ViewError::CannotAcquireCollectionEntry
             | ViewError::MissingEntries => Status::not_found(err.to_string()),
+            ViewError::WriteConflict(_) => Status::aborted(err.to_string()),
         };
         status.set_source(Arc::new(err));
         status
@@ -365,7 +562,8 @@ impl<S> ValidatorNode for GrpcProxy<S>
 where
     S: Storage + Clone + Send + Sync + 'static,
 {
-    type SubscribeStream = UnboundedReceiverStream<Result<Notification, Status>>;
+    type SubscribeStream =
+        LimitedStream<CountedStream<UnboundedReceiverStream<Result<Notification, Status>>>>;
 
     #[instrument(skip_all, err(Display))]
     async fn handle_block_proposal(
@@ -396,11 +594,27 @@ where
         &self,
         request: Request<api::HandleConfirmedCertificateRequest>,
     ) -> Result<Response<ChainInfoResult>, Status> {
+        // Only the new certificates submitted by clients are gossiped, so that they are not
+        // forwarded back and forth between validators.
+        let mut gossiped_certificate = None;
+        if let Some(certificate_gossip) = &self.0.certificate_gossip {
+            match certificate_gossip.certificate_to_gossip(&request).await {
+                Ok(certificate) => gossiped_certificate = certificate,
+                Err(error) => warn!(%error, "Failed to check whether to gossip a certificate"),
+            }
+        }
         let (mut client, inner) = self.worker_client(request).await?;
-        Self::log_and_return_proxy_request_outcome(
-            client.handle_confirmed_certificate(inner).await,
-            "handle_confirmed_certificate",
-        )
+        let result = client.handle_confirmed_certificate(inner).await;
+        if let (Some(certificate_gossip), Some(certificate), Ok(response)) =
+            (&self.0.certificate_gossip, gossiped_certificate, &result)
+        {
+            if let Some(api::chain_info_result::Inner::ChainInfoResponse(_)) =
+                response.get_ref().inner
+            {
+                certificate_gossip.spawn(certificate);
+            }
+        }
+        Self::log_and_return_proxy_request_outcome(result, "handle_confirmed_certificate")
     }
 
     #[instrument(skip_all, err(Display))]
@@ -444,6 +658,10 @@ where
         &self,
         request: Request<SubscriptionRequest>,
     ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let permit = self
+            .0
+            .rate_limits
+            .open_subscription(request.remote_addr())?;
         let subscription_request = request.into_inner();
         let chain_ids = subscription_request
             .chain_ids
@@ -456,7 +674,10 @@ where
             .0
             .notifier
             .subscribe_with_ack(chain_ids, Ok(Notification::default()));
-        Ok(Response::new(UnboundedReceiverStream::new(rx)))
+        Ok(Response::new(LimitedStream::new(
+            CountedStream::new(UnboundedReceiverStream::new(rx)),
+            permit,
+        )))
     }
 
     #[instrument(skip_all, err(Display))]
@@ -653,6 +874,13 @@ where
             .clone()
             .ok_or_else(|| Status::invalid_argument("Missing field: chain_id."))?
             .try_into()?;
+        if self.0.websocket_port.is_some() {
+            let websocket_notification: Option<linera_core::worker::Notification> =
+                notification.clone().try_into()?;
+            if let Some(websocket_notification) = websocket_notification {
+                self.0.websocket_server.notify(&websocket_notification);
+            }
+        }
         self.0.notifier.notify_chain(&chain_id, &Ok(notification));
         Ok(Response::new(()))
     }
@@ -759,3 +987,255 @@ mod proto_message_cap {
         );
     }
 }
+
+#[cfg(test)]
+mod grpc_web_cors {
+    use std::{
+        convert::Infallible,
+        task::{Context, Poll},
+    };
+
+    use futures::future::Ready;
+    use http::{header, Method, Request, Response};
+    use linera_rpc::grpc::protocol_version::{MIN_VERSION_HEADER, VERSION_HEADER};
+    use tower::{Layer, Service};
+
+    /// A service that must not be reached, since the CORS layer answers preflight requests.
+    struct Unreachable;
+
+    impl Service<Request<()>> for Unreachable {
+        type Response = Response<()>;
+        type Error = Infallible;
+        type Future = Ready<Result<Response<()>, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: Request<()>) -> Self::Future {
+            panic!("preflight requests should be answered by the CORS layer");
+        }
+    }
+
+    #[tokio::test]
+    async fn allows_protocol_version_headers() {
+        let origin = "https://wallet.example";
+        let mut service = super::grpc_web_cors().layer(Unreachable);
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/rpc.v1.ValidatorNode/HandleChainInfoQuery")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .header(
+                header::ACCESS_CONTROL_REQUEST_HEADERS,
+                format!("content-type,x-grpc-web,{VERSION_HEADER},{MIN_VERSION_HEADER}"),
+            )
+            .body(())
+            .unwrap();
+
+        let response = service.call(request).await.unwrap();
+        let headers = response.headers();
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], origin);
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "POST");
+        let allowed_headers = headers[header::ACCESS_CONTROL_ALLOW_HEADERS]
+            .to_str()
+            .unwrap();
+        for name in ["x-grpc-web", VERSION_HEADER, MIN_VERSION_HEADER] {
+            assert!(allowed_headers.contains(name), "{name} is not allowed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::{SinkExt as _, StreamExt as _};
+    use linera_base::{
+        crypto::{CryptoHash, PublicKey},
+        data_types::{BlockHeight, Timestamp},
+        identifiers::ChainId,
+    };
+    use linera_client::config::{CommitteeConfig, GenesisConfig};
+    use linera_core::{
+        node::ValidatorNode as _,
+        worker::{Notification, Reason},
+    };
+    use linera_execution::{committee::ValidatorName, ResourceControlPolicy};
+    use linera_rpc::{
+        config::{
+            NetworkProtocol, TlsConfig, ValidatorInternalNetworkConfig,
+            ValidatorPublicNetworkConfig,
+        },
+        grpc::{
+            api,
+            api::{
+                notifier_service_server::NotifierService as _,
+                validator_node_client::ValidatorNodeClient,
+            },
+            transport::{create_channel, Options},
+            GrpcClient, GrpcCompression,
+        },
+        websocket::{SubscriptionRequest, NOTIFICATIONS_PATH},
+    };
+    use linera_storage::{DbStorage, TestClock};
+    use linera_views::memory::MemoryStore;
+    use tonic::{
+        transport::{server::TcpIncoming, Server},
+        Request,
+    };
+
+    use super::GrpcProxy;
+    use crate::proxy::rate_limit::RateLimitConfig;
+
+    /// Returns a proxy without shards, sending messages with the given `compression`.
+    async fn make_proxy(
+        compression: GrpcCompression,
+    ) -> anyhow::Result<GrpcProxy<DbStorage<MemoryStore, TestClock>>> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let name = ValidatorName(PublicKey::test_key(0));
+        let protocol = NetworkProtocol::Grpc(TlsConfig::ClearText);
+        let public_config = ValidatorPublicNetworkConfig {
+            protocol: protocol.clone(),
+            host: "127.0.0.1".to_string(),
+            port: 0,
+        };
+        let internal_config = ValidatorInternalNetworkConfig {
+            name,
+            protocol,
+            shards: Vec::new(),
+            host: "127.0.0.1".to_string(),
+            port: 0,
+            metrics_host: "127.0.0.1".to_string(),
+            metrics_port: 0,
+        };
+        let genesis_config = GenesisConfig::new(
+            CommitteeConfig {
+                validators: Vec::new(),
+            },
+            ChainId::root(0),
+            Timestamp::from(0),
+            ResourceControlPolicy::default(),
+            "test".to_string(),
+        );
+        let rate_limit_config = RateLimitConfig {
+            max_requests_per_second_per_ip: None,
+            max_requests_per_second_per_chain: None,
+            max_subscriptions_per_ip: None,
+            max_request_size: usize::MAX,
+        };
+        GrpcProxy::new(
+            public_config,
+            internal_config,
+            genesis_config,
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+            Some(0),
+            TlsConfig::ClearText,
+            Default::default(),
+            compression,
+            rate_limit_config,
+            name,
+            /* certificate_gossip */ false,
+            storage,
+        )
+    }
+
+    /// Tests that a WebSocket client subscribed to a chain receives the notifications sent to
+    /// the proxy about that chain.
+    #[tokio::test]
+    async fn notifications_are_forwarded_to_subscribers() -> anyhow::Result<()> {
+        let proxy = make_proxy(GrpcCompression::None).await?;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let router = proxy.0.websocket_server.router();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        let (mut websocket, _) =
+            async_tungstenite::tokio::connect_async(format!("ws://{address}{NOTIFICATIONS_PATH}"))
+                .await?;
+        let request = SubscriptionRequest {
+            chain_ids: vec![ChainId::root(1)],
+        };
+        websocket
+            .send(serde_json::to_string(&request)?.into())
+            .await?;
+
+        let notification = Notification {
+            chain_id: ChainId::root(1),
+            reason: Reason::NewBlock {
+                height: BlockHeight::ZERO,
+                hash: CryptoHash::test_hash("block"),
+            },
+        };
+        // The subscription is handled concurrently, so the notification is sent until it arrives.
+        let message = loop {
+            proxy
+                .notify(Request::new(api::Notification::try_from(
+                    notification.clone(),
+                )?))
+                .await?;
+            let next_message = tokio::time::timeout(Duration::from_millis(100), websocket.next());
+            if let Ok(message) = next_message.await {
+                break message.expect("the WebSocket connection should stay open")?;
+            }
+        };
+        let received: Notification = serde_json::from_str(message.to_text()?)?;
+        assert_eq!(received, notification);
+        Ok(())
+    }
+
+    /// Tests that clients and proxies exchange messages whatever compression each of them uses,
+    /// and that the proxy compresses its responses as configured.
+    #[tokio::test]
+    async fn compressed_messages_are_exchanged() -> anyhow::Result<()> {
+        let compressions = [
+            GrpcCompression::None,
+            GrpcCompression::Gzip,
+            GrpcCompression::Zstd,
+        ];
+        for proxy_compression in compressions {
+            let proxy = make_proxy(proxy_compression).await?;
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+            let address = format!("http://{}", listener.local_addr()?);
+            let incoming = TcpIncoming::from_listener(listener, true, None)
+                .map_err(|error| anyhow::anyhow!(error))?;
+            tokio::spawn(
+                Server::builder()
+                    .add_service(proxy.as_validator_node())
+                    .serve_with_incoming(incoming),
+            );
+            let options = Options {
+                connect_timeout: Some(Duration::from_secs(1)),
+                timeout: Some(Duration::from_secs(1)),
+            };
+
+            for client_compression in compressions {
+                let channel = create_channel(address.clone(), &options)?;
+                GrpcClient::new(address.clone(), channel, Duration::from_millis(100), 0)
+                    .with_compression(client_compression)
+                    .get_version_info()
+                    .await?;
+            }
+
+            let channel = create_channel(address.clone(), &options)?;
+            let mut client = ValidatorNodeClient::new(channel);
+            for encoding in GrpcCompression::ACCEPTED_ENCODINGS {
+                client = client.accept_compressed(encoding);
+            }
+            let response = client.get_version_info(Request::new(())).await?;
+            let encoding = response
+                .metadata()
+                .get("grpc-encoding")
+                .map(|encoding| encoding.to_str())
+                .transpose()?;
+            let expected_encoding = match proxy_compression {
+                GrpcCompression::None => None,
+                GrpcCompression::Gzip => Some("gzip"),
+                GrpcCompression::Zstd => Some("zstd"),
+            };
+            assert_eq!(encoding, expected_encoding);
+        }
+        Ok(())
+    }
+}
@@ -13,11 +13,13 @@ use linera_client::{
     storage::{run_with_storage, Runnable, StorageConfigNamespace},
 };
 use linera_core::{node::NodeError, JoinSetExt as _};
+use linera_execution::{ExecutionRuntimeConfig, NativeApplicationRegistry};
 use linera_rpc::{
     config::{
-        NetworkProtocol, ShardConfig, ValidatorInternalNetworkPreConfig,
+        NetworkProtocol, ShardConfig, TlsCertificateConfig, ValidatorInternalNetworkPreConfig,
         ValidatorPublicNetworkPreConfig,
     },
+    grpc::{GrpcCompression, TlsCertificates},
     simple::{MessageHandler, TransportProtocol},
     RpcMessage,
 };
@@ -31,8 +33,11 @@ use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info, instrument};
 
+mod gossip;
 mod grpc;
+mod rate_limit;
 use grpc::GrpcProxy;
+use rate_limit::RateLimitConfig;
 
 /// Options for running the proxy.
 #[derive(clap::Parser, Debug, Clone)]
@@ -76,6 +81,35 @@ pub struct ProxyOptions {
     /// Path to the file describing the initial user chains (aka genesis state)
     #[arg(long = "genesis")]
     genesis_config_path: PathBuf,
+
+    /// The port on which to also serve chain notifications over WebSocket, for clients
+    /// that can't use gRPC streaming directly. Only supported by the gRPC proxy.
+    #[arg(long)]
+    websocket_port: Option<u16>,
+
+    /// The port on which to serve the Prometheus metrics of the proxy, at `/metrics`.
+    /// Overrides the metrics port of the server configuration.
+    #[arg(long)]
+    metrics_port: Option<u16>,
+
+    /// Certificates used to secure the gRPC connections.
+    #[command(flatten)]
+    tls_certificate_config: TlsCertificateConfig,
+
+    /// The compression of the gRPC messages sent by the proxy. Only supported by the gRPC
+    /// proxy.
+    #[arg(long, default_value = "none")]
+    grpc_compression: GrpcCompression,
+
+    /// Limits protecting the workers from being flooded. Only supported by the gRPC proxy.
+    #[command(flatten)]
+    rate_limit_config: RateLimitConfig,
+
+    /// Forward the confirmed certificates submitted by clients to the other validators of
+    /// the committee, so that they receive them even if the clients disconnect after
+    /// reaching a quorum. Only supported by the gRPC proxy.
+    #[arg(long)]
+    certificate_gossip: bool,
 }
 
 /// A Linera Proxy, either gRPC or over 'Simple Transport', meaning TCP or UDP.
@@ -94,16 +128,29 @@ struct ProxyContext {
     genesis_config: GenesisConfig,
     send_timeout: Duration,
     recv_timeout: Duration,
+    websocket_port: Option<u16>,
+    tls_certificates: TlsCertificates,
+    grpc_compression: GrpcCompression,
+    rate_limit_config: RateLimitConfig,
+    certificate_gossip: bool,
 }
 
 impl ProxyContext {
     pub fn from_options(options: &ProxyOptions) -> Result<Self> {
-        let config = util::read_json(&options.config_path)?;
+        let mut config: ValidatorServerConfig = util::read_json(&options.config_path)?;
+        if let Some(metrics_port) = options.metrics_port {
+            config.internal_network.metrics_port = metrics_port;
+        }
         let genesis_config = util::read_json(&options.genesis_config_path)?;
         Ok(Self {
             config,
             send_timeout: options.send_timeout,
             recv_timeout: options.recv_timeout,
+            websocket_port: options.websocket_port,
+            tls_certificates: TlsCertificates::load(&options.tls_certificate_config)?,
+            grpc_compression: options.grpc_compression,
+            rate_limit_config: options.rate_limit_config.clone(),
+            certificate_gossip: options.certificate_gossip,
             genesis_config,
         })
     }
@@ -143,28 +190,52 @@ where
                     context.genesis_config,
                     context.send_timeout,
                     context.recv_timeout,
+                    context.websocket_port,
                     tls,
+                    context.tls_certificates,
+                    context.grpc_compression,
+                    context.rate_limit_config,
+                    context.config.validator.name,
+                    context.certificate_gossip,
                     storage,
-                ))
+                )?)
             }
             (
                 NetworkProtocol::Simple(internal_transport),
                 NetworkProtocol::Simple(public_transport),
-            ) => Self::Simple(Box::new(SimpleProxy {
-                internal_config: context
-                    .config
-                    .internal_network
-                    .clone_with_protocol(internal_transport),
-                public_config: context
-                    .config
-                    .validator
-                    .network
-                    .clone_with_protocol(public_transport),
-                genesis_config: context.genesis_config,
-                send_timeout: context.send_timeout,
-                recv_timeout: context.recv_timeout,
-                storage,
-            })),
+            ) => {
+                ensure!(
+                    context.websocket_port.is_none(),
+                    "WebSocket notifications are only supported by the gRPC proxy"
+                );
+                ensure!(
+                    context.grpc_compression == GrpcCompression::None,
+                    "Compression is only supported by the gRPC proxy"
+                );
+                ensure!(
+                    !context.rate_limit_config.is_enabled(),
+                    "Rate limits are only supported by the gRPC proxy"
+                );
+                ensure!(
+                    !context.certificate_gossip,
+                    "Certificate gossip is only supported by the gRPC proxy"
+                );
+                Self::Simple(Box::new(SimpleProxy {
+                    internal_config: context
+                        .config
+                        .internal_network
+                        .clone_with_protocol(internal_transport),
+                    public_config: context
+                        .config
+                        .validator
+                        .network
+                        .clone_with_protocol(public_transport),
+                    genesis_config: context.genesis_config,
+                    send_timeout: context.send_timeout,
+                    recv_timeout: context.recv_timeout,
+                    storage,
+                }))
+            }
             _ => {
                 bail!(
                     "network protocol mismatch: cannot have {} and {} ",
@@ -392,6 +463,8 @@ impl ProxyOptions {
             full_storage_config,
             &genesis_config,
             None,
+            ExecutionRuntimeConfig::default(),
+            NativeApplicationRegistry::default(),
             ProxyContext::from_options(self)?,
         )
         .boxed()
@@ -0,0 +1,323 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Limits on the requests accepted by the proxy, protecting the workers from being flooded.
+//!
+//! Requests are rate-limited per IP address as soon as their headers are received, before
+//! their body is decoded, and per chain before being forwarded to a worker. The number of
+//! notification streams open by each IP address is capped as well. Each limit is disabled
+//! unless configured.
+
+#[cfg(with_metrics)]
+use std::sync::LazyLock;
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    net::{IpAddr, SocketAddr},
+    num::{NonZeroU32, NonZeroUsize},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use futures::Stream;
+use linera_base::identifiers::ChainId;
+use linera_rpc::grpc::{protocol_version, GRPC_MAX_MESSAGE_SIZE};
+use tonic::{service::Interceptor, Request, Status};
+#[cfg(with_metrics)]
+use {linera_base::prometheus_util::register_int_counter_vec, prometheus::IntCounterVec};
+
+#[cfg(with_metrics)]
+static PROXY_REJECTED_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "proxy_rejected_requests",
+        "Requests rejected by the proxy because a limit was exceeded",
+        &["limit"],
+    )
+});
+
+/// The number of rate-limited keys above which the idle ones are forgotten.
+const MIN_PRUNED_KEYS: usize = 10_000;
+
+/// Configuration of the limits on the requests accepted by the proxy.
+#[derive(Clone, Debug, clap::Args)]
+pub struct RateLimitConfig {
+    /// The maximal number of requests per second accepted from each IP address
+    #[arg(long)]
+    pub max_requests_per_second_per_ip: Option<NonZeroU32>,
+
+    /// The maximal number of requests per second forwarded to the workers about each chain
+    #[arg(long)]
+    pub max_requests_per_second_per_chain: Option<NonZeroU32>,
+
+    /// The maximal number of notification streams each IP address can open at the same time
+    #[arg(long)]
+    pub max_subscriptions_per_ip: Option<NonZeroUsize>,
+
+    /// The maximal size of a request in bytes. Larger requests are rejected without being
+    /// decoded.
+    #[arg(long, default_value_t = GRPC_MAX_MESSAGE_SIZE)]
+    pub max_request_size: usize,
+}
+
+impl RateLimitConfig {
+    /// Returns `true` if any of the request and stream limits is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.max_requests_per_second_per_ip.is_some()
+            || self.max_requests_per_second_per_chain.is_some()
+            || self.max_subscriptions_per_ip.is_some()
+    }
+}
+
+/// The limits enforced by the proxy.
+pub struct RateLimits {
+    per_ip: Option<RateLimiter<IpAddr>>,
+    per_chain: Option<RateLimiter<ChainId>>,
+    subscriptions: Option<SubscriptionLimiter>,
+}
+
+impl RateLimits {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        RateLimits {
+            per_ip: config.max_requests_per_second_per_ip.map(RateLimiter::new),
+            per_chain: config
+                .max_requests_per_second_per_chain
+                .map(RateLimiter::new),
+            subscriptions: config
+                .max_subscriptions_per_ip
+                .map(SubscriptionLimiter::new),
+        }
+    }
+
+    /// Checks that the client at `address` didn't exceed its request rate. Requests with an
+    /// unknown address are not limited.
+    pub fn check_ip(&self, address: Option<SocketAddr>) -> Result<(), Status> {
+        let (Some(limiter), Some(address)) = (&self.per_ip, address) else {
+            return Ok(());
+        };
+        if limiter.try_acquire(address.ip(), Instant::now()) {
+            Ok(())
+        } else {
+            Err(Self::reject("ip", "too many requests from this address"))
+        }
+    }
+
+    /// Checks that the request rate about the chain `chain_id` wasn't exceeded.
+    pub fn check_chain(&self, chain_id: ChainId) -> Result<(), Status> {
+        let Some(limiter) = &self.per_chain else {
+            return Ok(());
+        };
+        if limiter.try_acquire(chain_id, Instant::now()) {
+            Ok(())
+        } else {
+            Err(Self::reject("chain", "too many requests about this chain"))
+        }
+    }
+
+    /// Reserves a notification stream for the client at `address`. The stream counts toward
+    /// the client's limit until the returned permit is dropped.
+    pub fn open_subscription(
+        &self,
+        address: Option<SocketAddr>,
+    ) -> Result<Option<SubscriptionPermit>, Status> {
+        let (Some(limiter), Some(address)) = (&self.subscriptions, address) else {
+            return Ok(None);
+        };
+        match limiter.try_acquire(address.ip()) {
+            Some(permit) => Ok(Some(permit)),
+            None => Err(Self::reject(
+                "subscriptions",
+                "too many notification streams from this address",
+            )),
+        }
+    }
+
+    #[cfg_attr(not(with_metrics), expect(unused_variables))]
+    fn reject(limit: &str, message: &str) -> Status {
+        #[cfg(with_metrics)]
+        PROXY_REJECTED_REQUESTS.with_label_values(&[limit]).inc();
+        Status::resource_exhausted(message)
+    }
+}
+
+/// An [`Interceptor`] rejecting the requests of clients that exceeded their request rate,
+/// and negotiating the protocol version of the others.
+#[derive(Clone)]
+pub struct RateLimitInterceptor(pub Arc<RateLimits>);
+
+impl Interceptor for RateLimitInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        self.0.check_ip(request.remote_addr())?;
+        protocol_version::negotiate_protocol_version(request)
+    }
+}
+
+/// A token bucket rate limiter, with one bucket for each key.
+struct RateLimiter<K> {
+    /// The number of tokens added to each bucket per second, which is also its capacity.
+    rate: f64,
+    buckets: Mutex<Buckets<K>>,
+}
+
+struct Buckets<K> {
+    buckets: HashMap<K, Bucket>,
+    /// The number of buckets above which the full ones are removed.
+    prune_at: usize,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, rate: f64, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(rate);
+        self.updated_at = now;
+    }
+}
+
+impl<K: Hash + Eq> RateLimiter<K> {
+    fn new(rate: NonZeroU32) -> Self {
+        RateLimiter {
+            rate: f64::from(rate.get()),
+            buckets: Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                prune_at: MIN_PRUNED_KEYS,
+            }),
+        }
+    }
+
+    /// Takes a token from the bucket of `key`, returning `false` if it is empty.
+    fn try_acquire(&self, key: K, now: Instant) -> bool {
+        let rate = self.rate;
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.buckets.len() >= buckets.prune_at {
+            // Full buckets behave exactly like new ones, so they can be forgotten.
+            buckets.buckets.retain(|_, bucket| {
+                bucket.refill(rate, now);
+                bucket.tokens < rate
+            });
+            buckets.prune_at = (2 * buckets.buckets.len()).max(MIN_PRUNED_KEYS);
+        }
+        let bucket = buckets.buckets.entry(key).or_insert(Bucket {
+            tokens: rate,
+            updated_at: now,
+        });
+        bucket.refill(rate, now);
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+}
+
+/// Counts the notification streams open by each IP address.
+#[derive(Clone)]
+struct SubscriptionLimiter {
+    max_subscriptions: usize,
+    open_subscriptions: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+impl SubscriptionLimiter {
+    fn new(max_subscriptions: NonZeroUsize) -> Self {
+        SubscriptionLimiter {
+            max_subscriptions: max_subscriptions.get(),
+            open_subscriptions: Arc::default(),
+        }
+    }
+
+    fn try_acquire(&self, ip: IpAddr) -> Option<SubscriptionPermit> {
+        let mut open_subscriptions = self.open_subscriptions.lock().unwrap();
+        let count = open_subscriptions.entry(ip).or_default();
+        if *count >= self.max_subscriptions {
+            return None;
+        }
+        *count += 1;
+        Some(SubscriptionPermit {
+            limiter: self.clone(),
+            ip,
+        })
+    }
+}
+
+/// A notification stream counting toward the limit of an IP address, until dropped.
+pub struct SubscriptionPermit {
+    limiter: SubscriptionLimiter,
+    ip: IpAddr,
+}
+
+impl Drop for SubscriptionPermit {
+    fn drop(&mut self) {
+        let mut open_subscriptions = self.limiter.open_subscriptions.lock().unwrap();
+        if let Some(count) = open_subscriptions.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                open_subscriptions.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// A stream holding a [`SubscriptionPermit`] for as long as it is open.
+pub struct LimitedStream<S> {
+    stream: S,
+    _permit: Option<SubscriptionPermit>,
+}
+
+impl<S> LimitedStream<S> {
+    pub fn new(stream: S, permit: Option<SubscriptionPermit>) -> Self {
+        LimitedStream {
+            stream,
+            _permit: permit,
+        }
+    }
+}
+
+impl<S: Stream + Unpin> Stream for LimitedStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.stream).poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        num::{NonZeroU32, NonZeroUsize},
+        time::{Duration, Instant},
+    };
+
+    use super::{RateLimiter, SubscriptionLimiter};
+
+    #[test]
+    fn rate_limiter_refills_over_time() {
+        let limiter = RateLimiter::new(NonZeroU32::new(2).unwrap());
+        let start = Instant::now();
+        assert!(limiter.try_acquire(0, start));
+        assert!(limiter.try_acquire(0, start));
+        assert!(!limiter.try_acquire(0, start));
+        // Other keys have their own buckets.
+        assert!(limiter.try_acquire(1, start));
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.try_acquire(0, later));
+        assert!(!limiter.try_acquire(0, later));
+    }
+
+    #[test]
+    fn subscription_permits_are_released_when_dropped() {
+        let limiter = SubscriptionLimiter::new(NonZeroUsize::new(1).unwrap());
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let permit = limiter.try_acquire(ip);
+        assert!(permit.is_some());
+        assert!(limiter.try_acquire(ip).is_none());
+        drop(permit);
+        assert!(limiter.try_acquire(ip).is_some());
+        assert!(limiter.open_subscriptions.lock().unwrap().is_empty());
+    }
+}
@@ -11,21 +11,27 @@ use std::{
     time::Duration,
 };
 
-use anyhow::{bail, Context};
+use anyhow::Context;
 use async_trait::async_trait;
 use futures::{stream::FuturesUnordered, FutureExt as _, StreamExt, TryFutureExt as _};
-use linera_base::crypto::{CryptoRng, KeyPair};
+use linera_base::{
+    crypto::{CryptoRng, KeyPair},
+    data_types::{TimeDelta, Timestamp},
+};
 use linera_client::{
     config::{CommitteeConfig, GenesisConfig, ValidatorConfig, ValidatorServerConfig},
     persistent::{self, Persist},
     storage::{full_initialize_storage, run_with_storage, Runnable, StorageConfigNamespace},
 };
 use linera_core::{worker::WorkerState, JoinSetExt as _};
-use linera_execution::{committee::ValidatorName, WasmRuntime, WithWasmDefault};
+use linera_execution::{
+    committee::{ValidatorKeyRotation, ValidatorName},
+    ExecutionRuntimeConfig, NativeApplicationRegistry, WasmRuntime, WithWasmDefault,
+};
 use linera_rpc::{
     config::{
-        CrossChainConfig, NetworkProtocol, NotificationConfig, ShardConfig, ShardId, TlsConfig,
-        ValidatorInternalNetworkConfig, ValidatorPublicNetworkConfig,
+        CrossChainConfig, NetworkProtocol, NotificationConfig, ShardConfig, ShardId,
+        TlsCertificateConfig, ValidatorInternalNetworkConfig, ValidatorPublicNetworkConfig,
     },
     grpc, simple,
 };
@@ -43,9 +49,15 @@ struct ServerContext {
     server_config: ValidatorServerConfig,
     cross_chain_config: CrossChainConfig,
     notification_config: NotificationConfig,
+    tls_certificates: grpc::TlsCertificates,
+    grpc_compression: grpc::GrpcCompression,
     shard: Option<usize>,
     grace_period: Duration,
     max_loaded_chains: NonZeroUsize,
+    /// The port of a single metrics endpoint for all the shards run by this process,
+    /// replacing those of the shard configurations.
+    #[cfg_attr(not(with_metrics), expect(dead_code))]
+    metrics_port: Option<u16>,
 }
 
 impl ServerContext {
@@ -61,12 +73,19 @@ impl ServerContext {
         let shard = self.server_config.internal_network.shard(shard_id);
         info!("Shard booted on {}", shard.host);
         info!("Public key: {}", self.server_config.key.public());
+        let partition = u32::try_from(shard_id).expect("Shard IDs should fit in 32 bits");
         let state = WorkerState::new(
             format!("Shard {} @ {}:{}", shard_id, local_ip_addr, shard.port),
             Some(self.server_config.key.copy()),
-            storage,
+            storage.with_pending_deliveries_partition(partition),
             self.max_loaded_chains,
         )
+        .with_previous_key_pair(
+            self.server_config.previous_key.as_ref().map(KeyPair::copy),
+            self.server_config
+                .previous_key_valid_until
+                .unwrap_or(Timestamp::from(u64::MAX)),
+        )
         .with_allow_inactive_chains(false)
         .with_allow_messages_from_deprecated_epochs(false)
         .with_grace_period(self.grace_period);
@@ -97,7 +116,7 @@ impl ServerContext {
             let listen_address = listen_address.to_owned();
 
             #[cfg(with_metrics)]
-            if let Some(port) = shard.metrics_port {
+            if let Some(port) = self.shard_metrics_port(&shard) {
                 Self::start_metrics(&listen_address, port, shutdown_signal.clone());
             }
 
@@ -131,7 +150,7 @@ impl ServerContext {
         listen_address: &str,
         states: Vec<(WorkerState<S>, ShardId, ShardConfig)>,
         shutdown_signal: CancellationToken,
-    ) -> JoinSet<()>
+    ) -> anyhow::Result<JoinSet<()>>
     where
         S: Storage + Clone + Send + Sync + 'static,
     {
@@ -140,7 +159,7 @@ impl ServerContext {
 
         for (state, shard_id, shard) in states {
             #[cfg(with_metrics)]
-            if let Some(port) = shard.metrics_port {
+            if let Some(port) = self.shard_metrics_port(&shard) {
                 Self::start_metrics(listen_address, port, shutdown_signal.clone());
             }
 
@@ -152,9 +171,11 @@ impl ServerContext {
                 self.server_config.internal_network.clone(),
                 self.cross_chain_config.clone(),
                 self.notification_config.clone(),
+                &self.tls_certificates,
+                self.grpc_compression,
                 shutdown_signal.clone(),
                 &mut join_set,
-            );
+            )?;
 
             handles.push(
                 server_handle
@@ -168,7 +189,17 @@ impl ServerContext {
 
         join_set.spawn_task(handles.collect::<()>());
 
-        join_set
+        Ok(join_set)
+    }
+
+    /// Returns the port of the metrics endpoint of a shard, unless a single endpoint is
+    /// served for all the shards.
+    #[cfg(with_metrics)]
+    fn shard_metrics_port(&self, shard: &ShardConfig) -> Option<u16> {
+        match self.metrics_port {
+            Some(_) => None,
+            None => shard.metrics_port,
+        }
     }
 
     #[cfg(with_metrics)]
@@ -195,6 +226,20 @@ impl Runnable for ServerContext {
 
         tokio::spawn(util::listen_for_shutdown_signals(shutdown_notifier.clone()));
 
+        #[cfg(with_metrics)]
+        if let Some(port) = self.metrics_port {
+            Self::start_metrics(&listen_address, port, shutdown_notifier.clone());
+        }
+
+        // Recover from an interruption between writing a certificate and saving its chain.
+        let network = &self.server_config.internal_network;
+        storage
+            .replay_write_ahead_log(|chain_id| {
+                self.shard
+                    .map_or(true, |shard| network.get_shard_id(chain_id) == shard)
+            })
+            .await?;
+
         // Run the server
         let states = match self.shard {
             Some(shard) => {
@@ -214,10 +259,9 @@ impl Runnable for ServerContext {
             NetworkProtocol::Simple(protocol) => {
                 self.spawn_simple(&listen_address, states, protocol, shutdown_notifier)
             }
-            NetworkProtocol::Grpc(tls_config) => match tls_config {
-                TlsConfig::ClearText => self.spawn_grpc(&listen_address, states, shutdown_notifier),
-                TlsConfig::Tls => bail!("TLS not supported between proxy and shards."),
-            },
+            NetworkProtocol::Grpc(_) => {
+                self.spawn_grpc(&listen_address, states, shutdown_notifier)?
+            }
         };
 
         join_set.await_all_tasks().await;
@@ -302,6 +346,8 @@ fn make_server_config<R: CryptoRng>(
         ValidatorServerConfig {
             validator,
             key,
+            previous_key: None,
+            previous_key_valid_until: None,
             internal_network,
         },
     )?)
@@ -328,6 +374,14 @@ enum ServerCommand {
         #[command(flatten)]
         notification_config: NotificationConfig,
 
+        /// Certificates used to secure the internal gRPC network
+        #[command(flatten)]
+        tls_certificate_config: TlsCertificateConfig,
+
+        /// The compression of the gRPC messages sent by the server
+        #[arg(long, default_value = "none")]
+        grpc_compression: grpc::GrpcCompression,
+
         /// Path to the file describing the initial user chains (aka genesis state)
         #[arg(long = "genesis")]
         genesis_config_path: PathBuf,
@@ -360,6 +414,26 @@ enum ServerCommand {
         /// The maximal number of entries in the storage cache.
         #[arg(long, default_value = "1000")]
         cache_size: usize,
+
+        /// A directory where compiled WebAssembly modules are persisted, so that applications
+        /// don't need to be compiled again after a restart. The modules are loaded as machine
+        /// code without further checks, so the directory must only be writable by this server.
+        #[arg(long)]
+        wasm_module_cache_dir: Option<PathBuf>,
+
+        /// The maximal size of the compiled WebAssembly modules kept in
+        /// `--wasm-module-cache-dir`, in mebibytes.
+        #[arg(long, default_value = "2048")]
+        wasm_module_cache_size_mib: u64,
+
+        /// The port on which to serve the Prometheus metrics of all the shards run by this
+        /// process, at `/metrics`. Overrides the metrics ports of the shard configurations.
+        ///
+        /// Chain heights are only reported as their maximum, `linera_max_chain_block_height`,
+        /// and not per chain, so that the number of series does not grow with the number of
+        /// chains.
+        #[arg(long)]
+        metrics_port: Option<u16>,
     },
 
     /// Act as a trusted third-party and generate all server configurations
@@ -401,6 +475,17 @@ enum ServerCommand {
         /// The maximal number of entries in the storage cache.
         #[arg(long, default_value = "1000")]
         cache_size: usize,
+
+        /// A directory where compiled WebAssembly modules are persisted, so that applications
+        /// don't need to be compiled again after a restart. The modules are loaded as machine
+        /// code without further checks, so the directory must only be writable by this server.
+        #[arg(long)]
+        wasm_module_cache_dir: Option<PathBuf>,
+
+        /// The maximal size of the compiled WebAssembly modules kept in
+        /// `--wasm-module-cache-dir`, in mebibytes.
+        #[arg(long, default_value = "2048")]
+        wasm_module_cache_size_mib: u64,
     },
 
     /// Replaces the configurations of the shards by following the given template.
@@ -436,6 +521,41 @@ enum ServerCommand {
         #[arg(long)]
         metrics_port: Option<String>,
     },
+
+    /// Replaces the key of this Linera validator by a new one, and writes the key rotation
+    /// to be applied to the committee by the administrators with `linera
+    /// rotate-validator-key`. Until then, and for the chains still using previous
+    /// committees, the validator keeps signing with its old key after a restart, during the
+    /// grace period.
+    #[command(name = "rotate-key")]
+    RotateKey {
+        /// Path to the file containing the server configuration of this Linera validator.
+        #[arg(long = "server")]
+        server_config_path: PathBuf,
+
+        /// Path where to write the key rotation, signed by the old and the new key.
+        #[arg(long = "rotation")]
+        rotation_path: PathBuf,
+
+        /// The number of hours during which the old key remains valid, for the chains whose
+        /// committee doesn't include the new key yet.
+        #[arg(long = "grace-period-hours", default_value = "168")]
+        grace_period_hours: u64,
+
+        /// Force this command to generate keys using a PRNG and a given seed. USE FOR
+        /// TESTING ONLY.
+        #[arg(long)]
+        testing_prng_seed: Option<u64>,
+    },
+
+    /// Discards the key used before the last key rotation before the end of its grace period,
+    /// once no chain uses a committee including it anymore.
+    #[command(name = "end-key-rotation")]
+    EndKeyRotation {
+        /// Path to the file containing the server configuration of this Linera validator.
+        #[arg(long = "server")]
+        server_config_path: PathBuf,
+    },
 }
 
 fn main() {
@@ -483,10 +603,39 @@ fn log_file_name_for(command: &ServerCommand) -> Cow<'static, str> {
         }
         ServerCommand::Generate { .. }
         | ServerCommand::Initialize { .. }
-        | ServerCommand::EditShards { .. } => "server".into(),
+        | ServerCommand::EditShards { .. }
+        | ServerCommand::RotateKey { .. }
+        | ServerCommand::EndKeyRotation { .. } => "server".into(),
     }
 }
 
+/// Persists compiled WebAssembly modules in `directory`, and loads the modules compiled by
+/// previous runs.
+#[cfg(any(feature = "wasmer", feature = "wasmtime"))]
+async fn enable_wasm_module_cache(directory: PathBuf, max_size_mib: u64) {
+    linera_execution::enable_module_disk_cache(directory, max_size_mib << 20)
+        .expect("Failed to create the WebAssembly module cache directory");
+    let loaded_modules = linera_execution::warm_up_module_caches().await;
+    info!("Loaded {loaded_modules} compiled WebAssembly modules from the disk cache");
+}
+
+#[cfg(not(any(feature = "wasmer", feature = "wasmtime")))]
+async fn enable_wasm_module_cache(_directory: PathBuf, _max_size_mib: u64) {
+    tracing::warn!("No WebAssembly runtime is enabled, ignoring `--wasm-module-cache-dir`");
+}
+
+/// Returns the native implementations that this validator runs instead of some application
+/// bytecodes.
+///
+/// Operators building their own server binary register their implementations here. Each one
+/// must first be tested against the bytecode it replaces with `check_native_equivalence`, from
+/// `linera_execution::test_utils`, otherwise this validator won't agree with the others on the
+/// outcome of blocks. The test only holds for the fuel schedule of the policy it is run with, so
+/// it must be repeated before each change of the committee's fuel schedule.
+fn native_applications() -> NativeApplicationRegistry {
+    NativeApplicationRegistry::default()
+}
+
 async fn run(options: ServerOptions) {
     match options.command {
         ServerCommand::Run {
@@ -494,6 +643,8 @@ async fn run(options: ServerOptions) {
             storage_config,
             cross_chain_config,
             notification_config,
+            tls_certificate_config,
+            grpc_compression,
             genesis_config_path,
             shard,
             grace_period,
@@ -502,9 +653,21 @@ async fn run(options: ServerOptions) {
             max_concurrent_queries,
             max_stream_queries,
             cache_size,
+            wasm_module_cache_dir,
+            wasm_module_cache_size_mib,
+            metrics_port,
         } => {
             linera_version::VERSION_INFO.log();
 
+            let native_applications = native_applications();
+            if !native_applications.is_empty() {
+                info!("Running native implementations of some application bytecodes");
+            }
+
+            if let Some(directory) = wasm_module_cache_dir {
+                enable_wasm_module_cache(directory, wasm_module_cache_size_mib).await;
+            }
+
             let genesis_config: GenesisConfig =
                 util::read_json(&genesis_config_path).expect("Failed to read initial chain config");
             let server_config: ValidatorServerConfig =
@@ -517,13 +680,19 @@ async fn run(options: ServerOptions) {
                 panic!("Multiple shards not supported with RocksDB");
             }
 
+            let tls_certificates = grpc::TlsCertificates::load(&tls_certificate_config)
+                .expect("Failed to load the TLS certificates");
+
             let job = ServerContext {
                 server_config,
                 cross_chain_config,
                 notification_config,
+                tls_certificates,
+                grpc_compression,
                 shard,
                 grace_period,
                 max_loaded_chains,
+                metrics_port,
             };
             let wasm_runtime = wasm_runtime.with_wasm_default();
             let common_config = CommonStoreConfig {
@@ -535,11 +704,18 @@ async fn run(options: ServerOptions) {
                 .add_common_config(common_config)
                 .await
                 .unwrap();
-            run_with_storage(full_storage_config, &genesis_config, wasm_runtime, job)
-                .boxed()
-                .await
-                .unwrap()
-                .unwrap();
+            run_with_storage(
+                full_storage_config,
+                &genesis_config,
+                wasm_runtime,
+                ExecutionRuntimeConfig::default(),
+                native_applications,
+                job,
+            )
+            .boxed()
+            .await
+            .unwrap()
+            .unwrap();
         }
 
         ServerCommand::Generate {
@@ -621,6 +797,63 @@ async fn run(options: ServerOptions) {
                 .await
                 .expect("Failed to write updated server config");
         }
+
+        ServerCommand::RotateKey {
+            server_config_path,
+            rotation_path,
+            grace_period_hours,
+            testing_prng_seed,
+        } => {
+            let mut server_config =
+                persistent::File::<ValidatorServerConfig>::read(&server_config_path)
+                    .expect("Failed to read server config");
+            let now = Timestamp::now();
+            assert!(
+                server_config.previous_key.is_none()
+                    || server_config
+                        .previous_key_valid_until
+                        .is_some_and(|valid_until| valid_until <= now),
+                "The previous key rotation is not over; run `end-key-rotation` once no chain \
+                uses a committee including the previous key anymore"
+            );
+            let mut rng = Box::<dyn CryptoRng>::from(testing_prng_seed);
+            let new_key = KeyPair::generate_from(&mut rng);
+            let new_name = ValidatorName(new_key.public());
+            let rotation = ValidatorKeyRotation::new(&server_config.key, &new_key);
+            // Write the rotation first: it can only be produced with the old key.
+            let mut rotation_file = persistent::File::new(&rotation_path, rotation)
+                .expect("Unable to open key rotation file");
+            Persist::persist(&mut rotation_file)
+                .await
+                .expect("Unable to write key rotation file");
+            info!("Wrote key rotation {}", rotation_path.display());
+            let old_key = std::mem::replace(&mut server_config.key, new_key);
+            server_config.previous_key = Some(old_key);
+            let grace_period = TimeDelta::from_secs(grace_period_hours.saturating_mul(3600));
+            server_config.previous_key_valid_until = Some(now.saturating_add(grace_period));
+            server_config.validator.name = new_name;
+            server_config.internal_network.name = new_name;
+            Persist::persist(&mut server_config)
+                .await
+                .expect("Failed to write updated server config");
+            info!("Restart the validator to start using the new key");
+            println!("{new_name}");
+        }
+
+        ServerCommand::EndKeyRotation { server_config_path } => {
+            let mut server_config =
+                persistent::File::<ValidatorServerConfig>::read(&server_config_path)
+                    .expect("Failed to read server config");
+            server_config.previous_key_valid_until = None;
+            if server_config.previous_key.take().is_none() {
+                info!("No key rotation in progress");
+                return;
+            }
+            Persist::persist(&mut server_config)
+                .await
+                .expect("Failed to write updated server config");
+            info!("Discarded the previous key; restart the validator to stop using it");
+        }
     }
 }
 
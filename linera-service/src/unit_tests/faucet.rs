@@ -3,24 +3,33 @@
 
 #![allow(clippy::large_futures)]
 
-use std::sync::Arc;
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    num::NonZeroU32,
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use futures::lock::Mutex;
 use linera_base::{
     crypto::{KeyPair, PublicKey},
-    data_types::{Amount, Timestamp},
+    data_types::{Amount, TimeDelta, Timestamp},
     identifiers::ChainId,
+    time::Duration,
 };
-use linera_client::{chain_listener, wallet::Wallet};
+use linera_client::{chain_listener, client_options::FaucetLimitConfig, wallet::Wallet};
 use linera_core::{
     client::ChainClient,
     test_utils::{FaultType, MemoryStorageBuilder, NodeProvider, StorageBuilder as _, TestBuilder},
 };
-use linera_storage::{DbStorage, TestClock};
-use linera_views::memory::MemoryStore;
+use linera_storage::{DbStorage, Storage as _, TestClock};
+use linera_views::{memory::MemoryStore, views::View as _};
 
-use super::MutationRoot;
+use super::{
+    limits::{ClaimLimiter, Grant},
+    state::FaucetStateView,
+    MutationRoot, StateView,
+};
 
 struct ClientContext {
     client: ChainClient<TestProvider, TestStorage>,
@@ -66,6 +75,11 @@ impl chain_listener::ClientContext for ClientContext {
     }
 }
 
+async fn load_state(storage: &TestStorage) -> StateView<ClientContext> {
+    let context = storage.service_context("faucet").await.unwrap();
+    FaucetStateView::load(context).await.unwrap()
+}
+
 #[tokio::test]
 async fn test_faucet_rate_limiting() {
     let storage_builder = MemoryStorageBuilder::default();
@@ -77,6 +91,7 @@ async fn test_faucet_rate_limiting() {
         .await
         .unwrap();
     let chain_id = client.chain_id();
+    let state = load_state(client.storage_client()).await;
     let context = ClientContext {
         client,
         update_calls: 0,
@@ -89,28 +104,260 @@ async fn test_faucet_rate_limiting() {
         end_timestamp: Timestamp::from(6000),
         start_timestamp: Timestamp::from(0),
         start_balance: Amount::from_tokens(6),
+        limiter: Arc::new(ClaimLimiter::new(limit_config(None, None)).unwrap()),
+        state: Arc::new(Mutex::new(state)),
+        pending: Arc::default(),
+        budget_per_period: None,
+        budget_period: TimeDelta::from_secs(1),
     };
     // The faucet is releasing one token every 1000 microseconds. So at 1000 one claim should
     // succeed. At 3000, two more should have been unlocked.
     clock.set(Timestamp::from(999));
-    assert!(root.do_claim(PublicKey::test_key(0).into()).await.is_err());
+    assert!(root
+        .do_claim(PublicKey::test_key(0).into(), None, None)
+        .await
+        .is_err());
     clock.set(Timestamp::from(1000));
-    assert!(root.do_claim(PublicKey::test_key(1).into()).await.is_ok());
-    assert!(root.do_claim(PublicKey::test_key(2).into()).await.is_err());
+    assert!(root
+        .do_claim(PublicKey::test_key(1).into(), None, None)
+        .await
+        .is_ok());
+    assert!(root
+        .do_claim(PublicKey::test_key(2).into(), None, None)
+        .await
+        .is_err());
     clock.set(Timestamp::from(3000));
-    assert!(root.do_claim(PublicKey::test_key(3).into()).await.is_ok());
-    assert!(root.do_claim(PublicKey::test_key(4).into()).await.is_ok());
-    assert!(root.do_claim(PublicKey::test_key(5).into()).await.is_err());
+    assert!(root
+        .do_claim(PublicKey::test_key(3).into(), None, None)
+        .await
+        .is_ok());
+    assert!(root
+        .do_claim(PublicKey::test_key(4).into(), None, None)
+        .await
+        .is_ok());
+    assert!(root
+        .do_claim(PublicKey::test_key(5).into(), None, None)
+        .await
+        .is_err());
     // If a validator is offline, it will create a pending block and then fail.
     clock.set(Timestamp::from(6000));
     builder.set_fault_type([0, 1], FaultType::Offline).await;
-    assert!(root.do_claim(PublicKey::test_key(6).into()).await.is_err());
+    assert!(root
+        .do_claim(PublicKey::test_key(6).into(), None, None)
+        .await
+        .is_err());
     assert_eq!(context.lock().await.update_calls, 4); // Also called in the last error case.
 }
 
+#[tokio::test]
+async fn test_faucet_budget() {
+    let storage_builder = MemoryStorageBuilder::default();
+    let clock = storage_builder.clock().clone();
+    clock.set(Timestamp::from(0));
+    let mut builder = TestBuilder::new(storage_builder, 4, 1).await.unwrap();
+    let client = builder
+        .add_root_chain(1, Amount::from_tokens(6))
+        .await
+        .unwrap();
+    let chain_id = client.chain_id();
+    let storage = client.storage_client().clone();
+    let state = load_state(&storage).await;
+    let context = ClientContext {
+        client,
+        update_calls: 0,
+    };
+    let root = MutationRoot {
+        chain_id,
+        context: Arc::new(Mutex::new(context)),
+        amount: Amount::from_tokens(1),
+        end_timestamp: Timestamp::from(0),
+        start_timestamp: Timestamp::from(0),
+        start_balance: Amount::from_tokens(6),
+        limiter: Arc::new(ClaimLimiter::new(limit_config(None, None)).unwrap()),
+        state: Arc::new(Mutex::new(state)),
+        pending: Arc::default(),
+        budget_per_period: Some(Amount::from_tokens(2)),
+        budget_period: TimeDelta::from_micros(1000),
+    };
+    let owner = PublicKey::test_key(0).into();
+    // Two tokens can be granted in each period of 1000 microseconds.
+    assert!(root.do_claim(owner, None, None).await.is_ok());
+    assert!(root.do_claim(owner, None, None).await.is_ok());
+    assert!(root.do_claim(owner, None, None).await.is_err());
+    clock.set(Timestamp::from(1500));
+    assert!(root.do_claim(owner, None, None).await.is_ok());
+    {
+        let state = root.state.lock().await;
+        let budget = state.budget(
+            Amount::from_tokens(2),
+            TimeDelta::from_micros(1000),
+            Timestamp::from(1500),
+        );
+        assert_eq!(budget.period_start, Timestamp::from(1000));
+        assert_eq!(budget.period_end, Timestamp::from(2000));
+        assert_eq!(budget.remaining, Amount::from_tokens(1));
+    }
+
+    // The grants and the spent budget are persisted.
+    drop(root);
+    let state = load_state(&storage).await;
+    assert_eq!(state.grants.count(), 3);
+    let recent_grants = state.grants_since(Timestamp::from(1000)).await.unwrap();
+    assert_eq!(recent_grants.len(), 1);
+    assert_eq!(state.spent_since(Timestamp::from(1000)), Amount::ONE);
+    let chains = state.claimed_chains.get(&owner).await.unwrap().unwrap();
+    assert_eq!(chains.len(), 3);
+    let grants = state.grants.read(..).await.unwrap();
+    assert_eq!(
+        grants
+            .iter()
+            .map(|grant| grant.chain_id)
+            .collect::<Vec<_>>(),
+        chains
+    );
+}
+
+fn limit_config(
+    max_claims_per_ip: Option<u32>,
+    max_claims_per_owner: Option<u32>,
+) -> FaucetLimitConfig {
+    FaucetLimitConfig {
+        max_claims_per_ip: max_claims_per_ip.and_then(NonZeroU32::new),
+        max_claims_per_owner: max_claims_per_owner.and_then(NonZeroU32::new),
+        claim_window: Duration::from_secs(1),
+        trust_forwarded_for: false,
+        verification_webhook: None,
+        grants_path: None,
+        budget_per_period: None,
+        budget_period: Duration::from_secs(1),
+    }
+}
+
+#[test]
+fn test_claim_limits() {
+    let limiter = ClaimLimiter::new(limit_config(Some(2), Some(1))).unwrap();
+    let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let owner0 = PublicKey::test_key(0).into();
+    let owner1 = PublicKey::test_key(1).into();
+    let owner2 = PublicKey::test_key(2).into();
+    let start = Timestamp::from(0);
+
+    limiter
+        .reserve(owner0, Some(ip), start)
+        .unwrap()
+        .commit(ChainId::root(0), Amount::ONE);
+    // Each owner can only claim once per window.
+    assert!(limiter.reserve(owner0, Some(ip), start).is_err());
+    // Claims that are not granted don't count toward the limits.
+    drop(limiter.reserve(owner1, Some(ip), start).unwrap());
+    limiter
+        .reserve(owner1, Some(ip), start)
+        .unwrap()
+        .commit(ChainId::root(1), Amount::ONE);
+    // Each address can claim twice per window.
+    assert!(limiter.reserve(owner2, Some(ip), start).is_err());
+    // Clients with an unknown address are rejected.
+    assert!(limiter.reserve(owner2, None, start).is_err());
+
+    let later = start.saturating_add_micros(1_000_001);
+    assert!(limiter.reserve(owner2, Some(ip), later).is_ok());
+}
+
+#[test]
+fn test_rejected_claims_release_nothing() {
+    let limiter = ClaimLimiter::new(limit_config(Some(2), Some(1))).unwrap();
+    let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let owner0 = PublicKey::test_key(0).into();
+    let owner1 = PublicKey::test_key(1).into();
+    let start = Timestamp::from(0);
+
+    limiter
+        .reserve(owner0, Some(ip), start)
+        .unwrap()
+        .commit(ChainId::root(0), Amount::ONE);
+    // Rejecting a claim of the same owner from another address doesn't release the owner's
+    // granted claim.
+    let other_ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    assert!(limiter.reserve(owner0, Some(other_ip), start).is_err());
+    assert!(limiter.reserve(owner0, Some(ip), start).is_err());
+    limiter
+        .reserve(owner1, Some(other_ip), start)
+        .unwrap()
+        .commit(ChainId::root(1), Amount::ONE);
+    // The rejected claim doesn't count toward the limit of the other address.
+    assert!(limiter
+        .reserve(PublicKey::test_key(2).into(), Some(other_ip), start)
+        .is_ok());
+}
+
+#[test]
+fn test_grants_are_recorded() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = tmp_dir.path().join("grants.jsonl");
+    let config = FaucetLimitConfig {
+        grants_path: Some(path.clone()),
+        ..limit_config(None, Some(1))
+    };
+    let owner = PublicKey::test_key(0).into();
+    let limiter = ClaimLimiter::new(config.clone()).unwrap();
+    let grant = limiter
+        .reserve(owner, None, Timestamp::from(0))
+        .unwrap()
+        .commit(ChainId::root(0), Amount::ONE);
+    drop(limiter);
+
+    // The grant is appended to the grants file.
+    let contents = fs_err::read_to_string(&path).unwrap();
+    let recorded: Grant = serde_json::from_str(contents.trim_end()).unwrap();
+    assert_eq!(recorded.chain_id, ChainId::root(0));
+
+    // Once counted again after a restart, the grant still counts toward the limits.
+    let mut limiter = ClaimLimiter::new(config).unwrap();
+    limiter.count(&grant);
+    assert!(limiter.reserve(owner, None, Timestamp::from(0)).is_err());
+}
+
+#[tokio::test]
+async fn test_grants_file_is_imported() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = tmp_dir.path().join("grants.jsonl");
+    let config = FaucetLimitConfig {
+        grants_path: Some(path),
+        ..limit_config(None, None)
+    };
+    let owner = PublicKey::test_key(0).into();
+    let limiter = ClaimLimiter::new(config).unwrap();
+    for (index, micros) in [500, 1500, 1700].into_iter().enumerate() {
+        limiter
+            .reserve(owner, None, Timestamp::from(micros))
+            .unwrap()
+            .commit(ChainId::root(index as u32), Amount::ONE);
+    }
+
+    let storage = MemoryStorageBuilder::default().build().await.unwrap();
+    let mut state = load_state(&storage).await;
+    let period = TimeDelta::from_micros(1000);
+    let grants = limiter.read_grants_file().unwrap();
+    state.import(grants.clone(), period).await.unwrap();
+    // The grants are only imported once.
+    state.import(grants, period).await.unwrap();
+
+    let state = load_state(&storage).await;
+    assert_eq!(state.grants.count(), 3);
+    assert_eq!(
+        state.spent_since(Timestamp::from(1000)),
+        Amount::from_tokens(2)
+    );
+    let chains = state.claimed_chains.get(&owner).await.unwrap().unwrap();
+    assert_eq!(
+        chains,
+        [ChainId::root(0), ChainId::root(1), ChainId::root(2)]
+    );
+}
+
 #[test]
 fn test_multiply() {
-    let mul = MutationRoot::<()>::multiply;
+    let mul = MutationRoot::<ClientContext>::multiply;
     assert_eq!(mul((1 << 127) + (1 << 63), 1 << 63), [1 << 62, 1 << 62, 0]);
     assert_eq!(mul(u128::MAX, u64::MAX), [u64::MAX - 1, u64::MAX, 1]);
 }
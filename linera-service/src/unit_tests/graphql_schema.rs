@@ -0,0 +1,106 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use async_graphql::{EmptyMutation, EmptySubscription, Enum, InputObject, Object, Schema};
+use serde_json::json;
+
+use super::IntrospectedSchema;
+
+struct Query;
+
+#[derive(Clone, Copy, Enum, Eq, PartialEq)]
+enum Color {
+    Red,
+    Green,
+}
+
+#[derive(InputObject)]
+struct Filter {
+    color: Color,
+    limit: Option<i32>,
+}
+
+#[Object]
+impl Query {
+    /// Greets someone.
+    async fn greeting(&self, name: String, times: Option<i32>) -> Vec<String> {
+        vec![name; times.unwrap_or(1) as usize]
+    }
+
+    async fn favorite_color(&self, filter: Filter) -> Option<Color> {
+        Some(filter.color)
+    }
+}
+
+async fn introspect() -> IntrospectedSchema {
+    let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+    IntrospectedSchema::of(&schema).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_introspected_schema_to_sdl() {
+    let sdl = introspect().await.to_sdl().unwrap();
+    let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+    assert_eq!(sdl, schema.sdl());
+}
+
+/// Tests that types nested deeper than the introspection query describes are reported as errors.
+#[test]
+fn test_deeply_nested_type_is_an_error() {
+    let mut type_ref = json!({ "kind": "NON_NULL", "name": null, "ofType": null });
+    for _ in 0..7 {
+        type_ref = json!({ "kind": "LIST", "name": null, "ofType": type_ref });
+    }
+    let response = json!({
+        "__schema": {
+            "queryType": { "name": "Query" },
+            "mutationType": null,
+            "subscriptionType": null,
+            "types": [{
+                "kind": "OBJECT",
+                "name": "Query",
+                "description": null,
+                "fields": [{
+                    "name": "matrix",
+                    "description": null,
+                    "args": [],
+                    "type": type_ref,
+                    "isDeprecated": false,
+                    "deprecationReason": null,
+                }],
+                "inputFields": null,
+                "interfaces": [],
+                "enumValues": null,
+                "possibleTypes": null,
+            }],
+        }
+    });
+    let schema = serde_json::from_value::<IntrospectedSchema>(response).unwrap();
+    let error = schema.to_sdl().unwrap_err();
+    assert!(error.to_string().contains("nested too deeply"));
+    assert!(schema.to_typescript().is_err());
+}
+
+#[tokio::test]
+async fn test_introspected_schema_to_typescript() {
+    let typescript = introspect().await.to_typescript().unwrap();
+
+    assert!(typescript.contains(
+        "export interface Query {\n  \
+        /**\n   \
+        * Greets someone.\n   \
+        */\n  \
+        greeting: Array<string>;\n  \
+        favoriteColor: Color | null;\n\
+        }\n"
+    ));
+    assert!(typescript.contains(
+        "export interface QueryGreetingArgs {\n  name: string;\n  times?: number | null;\n}\n"
+    ));
+    assert!(
+        typescript.contains("export interface QueryFavoriteColorArgs {\n  filter: Filter;\n}\n")
+    );
+    assert!(typescript.contains("export type Color = \"RED\" | \"GREEN\";\n"));
+    assert!(typescript
+        .contains("export interface Filter {\n  color: Color;\n  limit?: number | null;\n}\n"));
+}
@@ -0,0 +1,449 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::large_futures)]
+
+use std::num::NonZeroU16;
+
+use async_trait::async_trait;
+use futures::{poll, task::Poll, StreamExt as _};
+use linera_base::{
+    crypto::{CryptoHash, KeyPair},
+    data_types::{Amount, BlockHeight, Bytecode, Timestamp},
+    http::{HttpMethod, HttpRequest, HttpResponse},
+    identifiers::{BytecodeId, ChainId},
+};
+use linera_client::{
+    chain_listener::{self, ChainListenerConfig},
+    wallet::Wallet,
+};
+use linera_core::{
+    client::{ChainClient, ChainClientError},
+    test_utils::{MemoryStorageBuilder, NodeProvider, TestBuilder},
+};
+use linera_execution::{
+    system::Recipient,
+    test_utils::{ExpectedCall, MockApplication},
+    wasm_test, NativeApplicationRegistry, Operation, SystemOperation, WasmRuntime,
+};
+use linera_rpc::grpc::api;
+use linera_storage::{DbStorage, TestClock};
+use linera_views::memory::MemoryStore;
+use prost::Message as _;
+use serde_json::json;
+
+use super::{BalanceResponse, NodeService, OperationResponse};
+
+struct ClientContext {
+    client: ChainClient<TestProvider, TestStorage>,
+}
+
+type TestStorage = DbStorage<MemoryStore, TestClock>;
+type TestProvider = NodeProvider<TestStorage>;
+
+#[async_trait]
+impl chain_listener::ClientContext for ClientContext {
+    type ValidatorNodeProvider = TestProvider;
+    type Storage = TestStorage;
+
+    fn wallet(&self) -> &Wallet {
+        unimplemented!()
+    }
+
+    fn make_chain_client(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<ChainClient<TestProvider, TestStorage>, linera_client::Error> {
+        if chain_id != self.client.chain_id() {
+            return Err(ChainClientError::InternalError("unknown chain").into());
+        }
+        Ok(self.client.clone())
+    }
+
+    async fn update_wallet_for_new_chain(
+        &mut self,
+        _: ChainId,
+        _: Option<KeyPair>,
+        _: Timestamp,
+    ) -> Result<(), linera_client::Error> {
+        Ok(())
+    }
+
+    async fn update_wallet(
+        &mut self,
+        _: &ChainClient<TestProvider, TestStorage>,
+    ) -> Result<(), linera_client::Error> {
+        Ok(())
+    }
+}
+
+async fn make_service(
+    client: &ChainClient<TestProvider, TestStorage>,
+) -> NodeService<ClientContext> {
+    NodeService::new(
+        ChainListenerConfig::default(),
+        NonZeroU16::new(8080).unwrap(),
+        Some(client.chain_id()),
+        client.storage_client(),
+        ClientContext {
+            client: client.clone(),
+        },
+    )
+    .await
+}
+
+async fn publish_bytecode(
+    client: &ChainClient<TestProvider, TestStorage>,
+    name: &str,
+) -> anyhow::Result<BytecodeId> {
+    let (contract_path, service_path) = wasm_test::get_example_bytecode_paths(name)?;
+    let (bytecode_id, _) = client
+        .publish_bytecode(
+            Bytecode::load_from_file(contract_path).await?,
+            Bytecode::load_from_file(service_path).await?,
+        )
+        .await?
+        .unwrap();
+    Ok(bytecode_id)
+}
+
+#[tokio::test]
+async fn test_events_subscription() -> anyhow::Result<()> {
+    let storage_builder = MemoryStorageBuilder::with_wasm_runtime(WasmRuntime::default());
+    let mut builder = TestBuilder::new(storage_builder, 4, 1).await?;
+    let client = builder.add_root_chain(0, Amount::from_tokens(3)).await?;
+    let chain_id = client.chain_id();
+    let schema = make_service(&client).await.schema();
+
+    let counter_bytecode_id = publish_bytecode(&client, "counter").await?;
+    let meta_counter_bytecode_id = publish_bytecode(&client, "meta_counter").await?;
+    let (counter_id, _) = client
+        .create_application_untyped(
+            counter_bytecode_id,
+            serde_json::to_vec(&())?,
+            serde_json::to_vec(&10_u64)?,
+            vec![],
+        )
+        .await?
+        .unwrap();
+
+    let query = |filter: String| {
+        format!(
+            "subscription {{ events(chainId: \"{chain_id}\"{filter}) \
+             {{ height blockHash event {{ key value }} }} }}"
+        )
+    };
+    let mut all_events = schema.execute_stream(query(String::new()));
+    let mut counter_events =
+        schema.execute_stream(query(format!(", applicationId: \"{counter_id}\"")));
+    // Polling the streams once subscribes them to the chain's notifications.
+    assert!(matches!(poll!(all_events.next()), Poll::Pending));
+    assert!(matches!(poll!(counter_events.next()), Poll::Pending));
+
+    // Instantiating the meta-counter emits an event.
+    let (_, certificate) = client
+        .create_application_untyped(
+            meta_counter_bytecode_id,
+            serde_json::to_vec(&counter_id)?,
+            serde_json::to_vec(&())?,
+            vec![counter_id],
+        )
+        .await?
+        .unwrap();
+
+    let response = all_events.next().await.unwrap();
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json()?,
+        json!({
+            "events": {
+                "height": certificate.block().header.height,
+                "blockHash": certificate.hash(),
+                "event": {
+                    "key": b"updates".to_vec(),
+                    "value": b"instantiated".to_vec(),
+                },
+            }
+        })
+    );
+    // The counter itself emitted no events.
+    assert!(matches!(poll!(counter_events.next()), Poll::Pending));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rest_endpoints() -> anyhow::Result<()> {
+    let mut builder = TestBuilder::new(MemoryStorageBuilder::default(), 4, 1).await?;
+    let client = builder.add_root_chain(0, Amount::from_tokens(3)).await?;
+    let chain_id = client.chain_id();
+    let router = make_service(&client).await.router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/api/v1/chains", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    let http = reqwest::Client::new();
+
+    let response = http.get(format!("{url}/{chain_id}/balance")).send().await?;
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let BalanceResponse { balance } = response.json().await?;
+    assert_eq!(balance, Amount::from_tokens(3));
+
+    let operation = Operation::System(SystemOperation::Transfer {
+        owner: None,
+        recipient: Recipient::root(1),
+        amount: Amount::ONE,
+    });
+    let response = http
+        .post(format!("{url}/{chain_id}/operations"))
+        .json(&operation)
+        .send()
+        .await?;
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let OperationResponse { hash } = response.json().await?;
+    assert_eq!(client.query_balance().await?, Amount::from_tokens(2));
+
+    let response = http
+        .get(format!("{url}/{chain_id}/blocks/0"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let block = response.json::<serde_json::Value>().await?;
+    assert_eq!(block["hash"], json!(hash));
+    assert_eq!(block["block"]["header"]["chain_id"], json!(chain_id));
+
+    let response = http
+        .get(format!("{url}/{chain_id}/blocks/1"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    let response = http.get(format!("{url}/invalid/balance")).send().await?;
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    let response = http
+        .get(format!("{url}/{}/balance", ChainId::root(1)))
+        .send()
+        .await?;
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_grpc_web_requests() -> anyhow::Result<()> {
+    let mut builder = TestBuilder::new(MemoryStorageBuilder::default(), 4, 1).await?;
+    let client = builder.add_root_chain(0, Amount::from_tokens(3)).await?;
+    let genesis_config_hash = CryptoHash::test_hash("genesis");
+    let router = make_service(&client)
+        .await
+        .with_genesis_config_hash(genesis_config_hash)
+        .router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/rpc.v1.ValidatorNode", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    let http = reqwest::Client::new();
+
+    // Sends a gRPC-Web request with an empty message and returns the message of the response.
+    let call = |method: &'static str| {
+        let request = http
+            .post(format!("{url}/{method}"))
+            .header("content-type", "application/grpc-web+proto")
+            .header("x-grpc-web", "1")
+            .body(vec![0; 5]);
+        async move {
+            let response = request.send().await?;
+            assert_eq!(response.status(), reqwest::StatusCode::OK);
+            let body = response.bytes().await?;
+            let length = u32::from_be_bytes(body[1..5].try_into()?) as usize;
+            anyhow::Ok(body[5..5 + length].to_vec())
+        }
+    };
+
+    let version_info = api::VersionInfo::decode(&call("GetVersionInfo").await?[..])?;
+    assert_eq!(
+        linera_version::VersionInfo::from(version_info),
+        linera_version::VersionInfo::default()
+    );
+    let hash = api::CryptoHash::decode(&call("GetGenesisConfigHash").await?[..])?;
+    assert_eq!(CryptoHash::try_from(hash)?, genesis_config_hash);
+
+    // Requests changing chains are not answered by the node service.
+    let response = http
+        .post(format!("{url}/HandleBlockProposal"))
+        .header("content-type", "application/grpc-web+proto")
+        .header("x-grpc-web", "1")
+        .body(vec![0; 5])
+        .send()
+        .await?;
+    let status = response
+        .headers()
+        .get("grpc-status")
+        .map(|status| status.to_str().map(str::to_owned))
+        .transpose()?;
+    assert_eq!(status.as_deref(), Some("12"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_application_http_requests() -> anyhow::Result<()> {
+    let storage_builder = MemoryStorageBuilder::with_wasm_runtime(WasmRuntime::default());
+    let mut builder = TestBuilder::new(storage_builder, 4, 1).await?;
+    let creator = builder.add_root_chain(0, Amount::from_tokens(3)).await?;
+    let chain_id = creator.chain_id();
+    let bytecode_id = publish_bytecode(&creator, "counter").await?;
+    let (application_id, _) = creator
+        .create_application_untyped(
+            bytecode_id,
+            serde_json::to_vec(&())?,
+            serde_json::to_vec(&10_u64)?,
+            vec![],
+        )
+        .await?
+        .unwrap();
+
+    // The node service runs with a local node whose counter service is mocked, to handle
+    // plain HTTP requests.
+    let service = MockApplication::default();
+    let mut native_applications = NativeApplicationRegistry::default();
+    native_applications.register_service(bytecode_id.service_blob_hash, service.clone());
+    let storage = builder
+        .make_storage()
+        .await?
+        .with_native_applications(native_applications);
+    let client = builder.make_client_with_storage(
+        storage,
+        chain_id,
+        KeyPair::generate(),
+        None,
+        BlockHeight::ZERO,
+    );
+    client.synchronize_from_validators().await?;
+    let router = make_service(&client).await.router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!(
+        "http://{}/chains/{chain_id}/applications/{application_id}",
+        listener.local_addr()?
+    );
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    let http = reqwest::Client::new();
+
+    service.expect_call(ExpectedCall::handle_query(|_, _, query| {
+        let request = serde_json::from_slice::<HttpRequest>(&query).unwrap();
+        assert_eq!(
+            request,
+            HttpRequest {
+                method: HttpMethod::Post,
+                path: "/greetings/new".to_owned(),
+                body: json!({ "name": "Alice" }),
+            }
+        );
+        let response = HttpResponse::ok(json!({ "greeting": "Hello, Alice!" }));
+        Ok(serde_json::to_vec(&response).unwrap())
+    }));
+    let response = http
+        .post(format!("{url}/greetings/new"))
+        .json(&json!({ "name": "Alice" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert_eq!(
+        response.json::<serde_json::Value>().await?,
+        json!({ "greeting": "Hello, Alice!" })
+    );
+
+    // The status of the application's response is forwarded, and requests without a body
+    // have a `null` one.
+    service.expect_call(ExpectedCall::handle_query(|_, _, query| {
+        let request = serde_json::from_slice::<HttpRequest>(&query).unwrap();
+        assert_eq!(request.method, HttpMethod::Get);
+        assert_eq!(request.body, serde_json::Value::Null);
+        Ok(serde_json::to_vec(&HttpResponse::not_found(&request.path)).unwrap())
+    }));
+    let response = http.get(format!("{url}/unknown")).send().await?;
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.json::<serde_json::Value>().await?,
+        json!("no handler for \"/unknown\"")
+    );
+    service.assert_no_more_expected_calls();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_paginated_blocks_and_operations() -> anyhow::Result<()> {
+    let mut builder = TestBuilder::new(MemoryStorageBuilder::default(), 4, 1).await?;
+    let client = builder.add_root_chain(0, Amount::from_tokens(3)).await?;
+    let chain_id = client.chain_id();
+    let schema = make_service(&client).await.schema();
+
+    // A transfer, then more blocks without operations than are read for a page, then
+    // another transfer.
+    client
+        .transfer(None, Amount::ONE, Recipient::root(1))
+        .await?
+        .unwrap();
+    for _ in 0..100 {
+        client.execute_operations(vec![], vec![]).await?.unwrap();
+    }
+    client
+        .transfer(None, Amount::ONE, Recipient::root(1))
+        .await?
+        .unwrap();
+
+    let query = |field: &str, arguments: &str, selection: &str| {
+        let query = format!(
+            "query {{ {field}(chainId: \"{chain_id}\"{arguments}) \
+             {{ edges {{ cursor }} pageInfo {{ hasNextPage }} {selection} }} }}"
+        );
+        let schema = schema.clone();
+        async move { schema.execute(query).await }
+    };
+
+    let response = query("blocksConnection", ", first: 2", "").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json()?,
+        json!({ "blocksConnection": {
+            "edges": [{ "cursor": "0" }, { "cursor": "1" }],
+            "pageInfo": { "hasNextPage": true },
+        }})
+    );
+    let response = query("blocksConnection", ", after: \"100\"", "").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json()?,
+        json!({ "blocksConnection": {
+            "edges": [{ "cursor": "101" }],
+            "pageInfo": { "hasNextPage": false },
+        }})
+    );
+
+    // The first page stops after reading 100 blocks, with a single operation.
+    let response = query("operationsConnection", "", "resumeAfter").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json()?,
+        json!({ "operationsConnection": {
+            "edges": [{ "cursor": "0:0" }],
+            "pageInfo": { "hasNextPage": true },
+            "resumeAfter": "99",
+        }})
+    );
+    let response = query("operationsConnection", ", after: \"99\"", "resumeAfter").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json()?,
+        json!({ "operationsConnection": {
+            "edges": [{ "cursor": "101:0" }],
+            "pageInfo": { "hasNextPage": false },
+            "resumeAfter": null,
+        }})
+    );
+
+    // Cursors that don't refer to a block or an operation of the chain are rejected.
+    for (field, after) in [
+        ("blocksConnection", "102"),
+        ("operationsConnection", "102"),
+        ("operationsConnection", "0:1"),
+    ] {
+        let response = query(field, &format!(", after: \"{after}\""), "").await;
+        assert_eq!(response.errors.len(), 1, "{field} after {after}");
+    }
+    Ok(())
+}
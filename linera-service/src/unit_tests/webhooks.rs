@@ -0,0 +1,143 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::BlockHeight,
+    identifiers::{
+        ApplicationId, BytecodeId, ChainId, GenericApplicationId, MessageId, StreamId, StreamName,
+    },
+};
+use linera_chain::data_types::{EventRecord, Origin};
+use linera_client::client_options::{Webhook, WebhookEvent};
+use linera_core::worker::{Notification, Reason};
+use serde_json::json;
+
+use super::{payloads, sign, WebhookPayload};
+
+fn application_id(name: &str) -> ApplicationId {
+    ApplicationId {
+        bytecode_id: BytecodeId::new(
+            CryptoHash::test_hash(format!("{name} contract")),
+            CryptoHash::test_hash(format!("{name} service")),
+        ),
+        creation: MessageId {
+            chain_id: ChainId::root(0),
+            height: BlockHeight::ZERO,
+            index: 0,
+        },
+    }
+}
+
+fn event(application_id: ApplicationId) -> EventRecord {
+    EventRecord {
+        stream_id: StreamId {
+            application_id: GenericApplicationId::User(application_id),
+            stream_name: StreamName(b"stream".to_vec()),
+        },
+        key: b"key".to_vec(),
+        value: b"value".to_vec(),
+    }
+}
+
+#[test]
+fn test_parse_webhooks() {
+    let application_id = application_id("app");
+    assert_eq!(
+        "new-block=https://example.com/hook?a=b"
+            .parse::<Webhook>()
+            .unwrap(),
+        Webhook {
+            event: WebhookEvent::NewBlock,
+            url: "https://example.com/hook?a=b".to_owned(),
+        }
+    );
+    let event = WebhookEvent::ApplicationEvent(application_id);
+    assert_eq!(event.to_string().parse::<WebhookEvent>().unwrap(), event);
+    assert!("new-round=https://example.com".parse::<Webhook>().is_err());
+    assert!("https://example.com".parse::<Webhook>().is_err());
+}
+
+#[test]
+fn test_payloads() {
+    let chain_id = ChainId::root(1);
+    let hash = CryptoHash::test_hash("block");
+    let height = BlockHeight(3);
+    let new_block = Notification {
+        chain_id,
+        reason: Reason::NewBlock { height, hash },
+    };
+    let app1 = application_id("app1");
+    let app2 = application_id("app2");
+    let events = [event(app1), event(app2), event(app1)];
+
+    assert_eq!(
+        payloads(WebhookEvent::NewBlock, &new_block, &events),
+        vec![WebhookPayload::NewBlock {
+            chain_id,
+            height,
+            hash
+        }]
+    );
+    assert!(payloads(WebhookEvent::NewIncomingBundle, &new_block, &events).is_empty());
+    assert_eq!(
+        payloads(WebhookEvent::ApplicationEvent(app1), &new_block, &events),
+        vec![
+            WebhookPayload::ApplicationEvent {
+                chain_id,
+                height,
+                block_hash: hash,
+                event: event(app1),
+            };
+            2
+        ]
+    );
+
+    let new_bundle = Notification {
+        chain_id,
+        reason: Reason::NewIncomingBundle {
+            origin: Origin::chain(ChainId::root(2)),
+            height,
+        },
+    };
+    assert!(payloads(WebhookEvent::NewBlock, &new_bundle, &[]).is_empty());
+    assert!(payloads(WebhookEvent::ApplicationEvent(app1), &new_bundle, &[]).is_empty());
+    assert_eq!(
+        payloads(WebhookEvent::NewIncomingBundle, &new_bundle, &[]),
+        vec![WebhookPayload::NewIncomingBundle {
+            chain_id,
+            origin: Origin::chain(ChainId::root(2)),
+            height,
+        }]
+    );
+}
+
+#[test]
+fn test_payload_json() {
+    let chain_id = ChainId::root(1);
+    let hash = CryptoHash::test_hash("block");
+    let payload = WebhookPayload::ApplicationEvent {
+        chain_id,
+        height: BlockHeight(3),
+        block_hash: hash,
+        event: event(application_id("app")),
+    };
+    let json = serde_json::to_value(&payload).unwrap();
+    assert_eq!(json["type"], "application-event");
+    assert_eq!(json["chainId"], json!(chain_id));
+    assert_eq!(json["blockHash"], json!(hash));
+    assert_eq!(json["height"], json!(BlockHeight(3)));
+}
+
+#[test]
+fn test_sign() {
+    // The HMAC-SHA256 of `1700000000.{"type":"new-block"}`.
+    let body = br#"{"type":"new-block"}"#;
+    let signature = sign("secret", "1700000000", body);
+    assert_eq!(
+        signature,
+        "9795b50d61bd912d0e3c920ecbe4e2bea66872fa0b2a8e67cd8487f6fe0eb22b"
+    );
+    // A request cannot be replayed with a new timestamp.
+    assert_ne!(sign("secret", "1700000060", body), signature);
+}
@@ -0,0 +1,346 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Notification of the events of the wallet's chains to HTTP endpoints.
+//!
+//! Each [`Webhook`] is posted a JSON [`WebhookPayload`] for every event of its kind on the
+//! chains of the wallet, so that backends can react to them without keeping GraphQL
+//! subscriptions open. Requests are signed with the configured secret: the
+//! [`SIGNATURE_HEADER`] contains the hex-encoded HMAC-SHA256 of the [`TIMESTAMP_HEADER`], a dot
+//! and the request body. Receivers should reject requests with an old timestamp, so that
+//! recorded requests cannot be replayed.
+//!
+//! The payloads of each webhook are queued and posted in order, independently from the other
+//! webhooks. Payloads are dropped if too many are queued for a webhook that doesn't respond.
+
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context as _;
+use futures::{lock::Mutex, StreamExt as _};
+use hmac::{Hmac, Mac as _};
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::BlockHeight,
+    identifiers::{ChainId, GenericApplicationId},
+    time::Duration,
+};
+use linera_chain::data_types::{EventRecord, Origin};
+use linera_client::{
+    chain_listener::ClientContext,
+    client_options::{Webhook, WebhookConfig, WebhookEvent},
+};
+use linera_core::{
+    client::ChainClient,
+    node::ValidatorNodeProvider,
+    worker::{Notification, Reason},
+};
+use linera_storage::Storage;
+use serde::Serialize;
+use sha2::Sha256;
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+
+/// The header containing the signature of a webhook request.
+pub const SIGNATURE_HEADER: &str = "X-Linera-Signature";
+
+/// The header containing the time at which a webhook request was signed, in seconds since the
+/// Unix epoch.
+pub const TIMESTAMP_HEADER: &str = "X-Linera-Timestamp";
+
+/// The maximal number of payloads queued for a webhook. Further payloads are dropped.
+const QUEUE_CAPACITY: usize = 1000;
+
+/// The interval at which the wallet is checked for new chains whose events to notify.
+const NEW_CHAINS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The number of times a notification is sent before giving up.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// The delay before resending a notification, multiplied by the number of failed attempts.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The maximal time to wait for a webhook to respond.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The body of a webhook request.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "kebab-case",
+    rename_all_fields = "camelCase"
+)]
+pub enum WebhookPayload {
+    /// A new block was added to the chain.
+    NewBlock {
+        chain_id: ChainId,
+        height: BlockHeight,
+        hash: CryptoHash,
+    },
+    /// The chain received new messages from another chain.
+    NewIncomingBundle {
+        chain_id: ChainId,
+        origin: Origin,
+        height: BlockHeight,
+    },
+    /// An application emitted an event in a new block of the chain.
+    ApplicationEvent {
+        chain_id: ChainId,
+        height: BlockHeight,
+        block_hash: CryptoHash,
+        event: EventRecord,
+    },
+}
+
+/// Posts the events of the chains of the wallet to the configured webhooks.
+pub struct WebhookNotifier {
+    webhooks: Vec<Webhook>,
+    secret: String,
+    http_client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    /// Creates a notifier with the given configuration, or returns `None` if no webhooks are
+    /// configured.
+    pub fn new(config: WebhookConfig) -> anyhow::Result<Option<Self>> {
+        if config.webhooks.is_empty() {
+            return Ok(None);
+        }
+        let secret = config
+            .webhook_secret
+            .context("a webhook secret is required to sign the notifications")?;
+        Ok(Some(WebhookNotifier {
+            webhooks: config.webhooks,
+            secret,
+            http_client: reqwest::Client::new(),
+        }))
+    }
+
+    /// Starts notifying the webhooks of the events of the chains in the wallet, including the
+    /// chains added to the wallet later.
+    pub async fn run<C>(self, context: Arc<Mutex<C>>) -> anyhow::Result<()>
+    where
+        C: ClientContext,
+    {
+        let endpoints = self
+            .webhooks
+            .into_iter()
+            .map(|webhook| {
+                let (queue, payloads) = mpsc::channel(QUEUE_CAPACITY);
+                let delivery = Delivery {
+                    url: webhook.url.clone(),
+                    secret: self.secret.clone(),
+                    http_client: self.http_client.clone(),
+                };
+                tokio::spawn(delivery.run(payloads));
+                Endpoint { webhook, queue }
+            })
+            .collect();
+        let notifier = Arc::new(ChainNotifier { endpoints });
+        let mut subscribed = HashSet::new();
+        notifier
+            .subscribe_new_chains(&context, &mut subscribed)
+            .await?;
+        tokio::spawn(async move {
+            loop {
+                linera_base::time::timer::sleep(NEW_CHAINS_INTERVAL).await;
+                if let Err(error) = notifier
+                    .subscribe_new_chains(&context, &mut subscribed)
+                    .await
+                {
+                    error!(%error, "Failed to notify webhooks of the events of new chains");
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// A webhook, with the queue of the payloads to post to it.
+struct Endpoint {
+    webhook: Webhook,
+    queue: mpsc::Sender<WebhookPayload>,
+}
+
+/// Queues the payloads of the notifications of the chains for the interested webhooks.
+struct ChainNotifier {
+    endpoints: Vec<Endpoint>,
+}
+
+impl ChainNotifier {
+    /// Subscribes to the notifications of the chains of the wallet that are not in
+    /// `subscribed` yet, and adds them to it.
+    async fn subscribe_new_chains<C>(
+        self: &Arc<Self>,
+        context: &Mutex<C>,
+        subscribed: &mut HashSet<ChainId>,
+    ) -> anyhow::Result<()>
+    where
+        C: ClientContext,
+    {
+        let context = context.lock().await;
+        for chain_id in context.wallet().chain_ids() {
+            if subscribed.contains(&chain_id) {
+                continue;
+            }
+            let client = context.make_chain_client(chain_id)?;
+            let mut notifications = client.subscribe().await?;
+            subscribed.insert(chain_id);
+            let notifier = Arc::clone(self);
+            tokio::spawn(async move {
+                while let Some(notification) = notifications.next().await {
+                    notifier.notify(&client, notification).await;
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Queues the payloads of a notification for the interested webhooks.
+    async fn notify<P, S>(&self, client: &ChainClient<P, S>, notification: Notification)
+    where
+        P: ValidatorNodeProvider + Sync + 'static,
+        S: Storage + Clone + Send + Sync + 'static,
+    {
+        let mut events = Vec::new();
+        if let Reason::NewBlock { hash, .. } = notification.reason {
+            if self
+                .endpoints
+                .iter()
+                .any(|endpoint| matches!(endpoint.webhook.event, WebhookEvent::ApplicationEvent(_)))
+            {
+                match client.read_hashed_confirmed_block(hash).await {
+                    Ok(block) => {
+                        events = block.into_inner().into_block().body.events;
+                    }
+                    Err(error) => warn!(%error, %hash, "Failed to read the events of a new block"),
+                }
+            }
+        }
+        let events = events.into_iter().flatten().collect::<Vec<_>>();
+        for endpoint in &self.endpoints {
+            for payload in payloads(endpoint.webhook.event, &notification, &events) {
+                match endpoint.queue.try_send(payload) {
+                    Ok(()) => {}
+                    Err(mpsc::error::TrySendError::Full(payload)) => error!(
+                        url = endpoint.webhook.url,
+                        ?payload,
+                        "Dropping webhook notification: too many are queued"
+                    ),
+                    Err(mpsc::error::TrySendError::Closed(payload)) => error!(
+                        url = endpoint.webhook.url,
+                        ?payload,
+                        "Dropping webhook notification: the webhook is no longer notified"
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// Posts the payloads queued for a webhook.
+struct Delivery {
+    url: String,
+    secret: String,
+    http_client: reqwest::Client,
+}
+
+impl Delivery {
+    /// Posts the queued payloads in order, until the queue is closed.
+    async fn run(self, mut payloads: mpsc::Receiver<WebhookPayload>) {
+        while let Some(payload) = payloads.recv().await {
+            self.send(&payload).await;
+        }
+    }
+
+    /// Posts a signed payload to the webhook, retrying a few times if it fails.
+    async fn send(&self, payload: &WebhookPayload) {
+        let url = &self.url;
+        let body = serde_json::to_vec(payload).expect("Webhook payloads should serialize");
+        for attempt in 1..=MAX_ATTEMPTS {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+                .to_string();
+            let signature = sign(&self.secret, &timestamp, &body);
+            let result = self
+                .http_client
+                .post(url)
+                .header("Content-Type", "application/json")
+                .header(TIMESTAMP_HEADER, &timestamp)
+                .header(SIGNATURE_HEADER, &signature)
+                .body(body.clone())
+                .timeout(REQUEST_TIMEOUT)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            match result {
+                Ok(_) => return,
+                Err(error) => warn!(%error, url, attempt, "Failed to notify webhook"),
+            }
+            if attempt < MAX_ATTEMPTS {
+                linera_base::time::timer::sleep(RETRY_DELAY * attempt).await;
+            }
+        }
+        error!(url, ?payload, "Dropping webhook notification");
+    }
+}
+
+/// Returns the payloads to post to a webhook interested in `event`, given a notification and
+/// the events emitted in the new block, if any.
+fn payloads(
+    event: WebhookEvent,
+    notification: &Notification,
+    events: &[EventRecord],
+) -> Vec<WebhookPayload> {
+    let chain_id = notification.chain_id;
+    match (event, &notification.reason) {
+        (WebhookEvent::NewBlock, Reason::NewBlock { height, hash }) => {
+            vec![WebhookPayload::NewBlock {
+                chain_id,
+                height: *height,
+                hash: *hash,
+            }]
+        }
+        (WebhookEvent::NewIncomingBundle, Reason::NewIncomingBundle { origin, height }) => {
+            vec![WebhookPayload::NewIncomingBundle {
+                chain_id,
+                origin: origin.clone(),
+                height: *height,
+            }]
+        }
+        (WebhookEvent::ApplicationEvent(application_id), Reason::NewBlock { height, hash }) => {
+            events
+                .iter()
+                .filter(|event| {
+                    event.stream_id.application_id == GenericApplicationId::User(application_id)
+                })
+                .map(|event| WebhookPayload::ApplicationEvent {
+                    chain_id,
+                    height: *height,
+                    block_hash: *hash,
+                    event: event.clone(),
+                })
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the hex-encoded HMAC-SHA256 of `timestamp`, a dot and `body`, keyed with `secret`.
+pub fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+#[path = "unit_tests/webhooks.rs"]
+mod tests;
@@ -578,6 +578,80 @@ async fn test_wasm_end_to_end_counter_publish_create(config: impl LineraNetConfi
     Ok(())
 }
 
+/// Tests that the node service forwards the prompts of the LLM application to a remote
+/// inference server, which is how inference runs on a GPU.
+#[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_test_service_grpc"))]
+#[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
+#[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
+#[test_log::test(tokio::test)]
+async fn test_wasm_end_to_end_llm_remote_inference(config: impl LineraNetConfig) -> Result<()> {
+    use std::collections::HashMap;
+
+    use axum::{extract::Query, routing::get, Router};
+    use linera_base::abi::{ContractAbi, ServiceAbi};
+
+    /// The ABI of the LLM example, which only the service is used through here.
+    struct LlmAbi;
+
+    impl ContractAbi for LlmAbi {
+        type Operation = Value;
+        type Response = ();
+    }
+
+    impl ServiceAbi for LlmAbi {
+        type Query = Value;
+        type QueryResponse = Value;
+    }
+
+    let _guard = INTEGRATION_TEST_GUARD.lock().await;
+    tracing::info!("Starting test {}", test_name!());
+
+    // A stand-in for `llm_inference_server`, which needs a model.
+    let inference_server = Router::new().route(
+        "/complete",
+        get(
+            |Query(parameters): Query<HashMap<String, String>>| async move {
+                format!("Completion of {:?}", parameters["prompt"])
+            },
+        ),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let endpoint = format!("http://{}/complete", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, inference_server).await });
+
+    let (mut net, client) = config.instantiate().await?;
+
+    let chain = client.load_wallet()?.default_chain().unwrap();
+    let (contract, service) = client.build_example("llm").await?;
+    let application_id = client
+        .publish_and_create::<LlmAbi, _, ()>(
+            contract,
+            service,
+            &json!({ "Remote": { "endpoint": endpoint } }),
+            &(),
+            &[],
+            None,
+        )
+        .await?;
+    let port = get_node_port().await;
+    let mut node_service = client.run_node_service(port, ProcessInbox::Skip).await?;
+
+    let application = node_service
+        .make_application(&chain, &application_id)
+        .await?;
+    let completion: String = application
+        .query_json(r#"prompt(prompt: "Once upon a time")"#)
+        .await?;
+    assert_eq!(completion, r#"Completion of "Once upon a time""#);
+
+    node_service.ensure_is_running()?;
+
+    net.ensure_is_running().await?;
+    net.terminate().await?;
+
+    Ok(())
+}
+
 #[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_test_service_grpc"))]
 #[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
 #[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
@@ -1303,6 +1377,7 @@ async fn test_wasm_end_to_end_crowd_funding(config: impl LineraNetConfig) -> Res
         owner: account_owner1,
         deadline,
         target,
+        milestones: vec![],
     };
     let (contract_crowd, service_crowd) = client1.build_example("crowd-funding").await?;
     let application_id_crowd = client1
@@ -1591,6 +1666,7 @@ async fn test_wasm_end_to_end_matching_engine(config: impl LineraNetConfig) -> R
                 amount: Amount::from_tokens(3),
                 nature: OrderNature::Bid,
                 price: Price { price },
+                expiry: None,
             })
             .await;
     }
@@ -1602,6 +1678,7 @@ async fn test_wasm_end_to_end_matching_engine(config: impl LineraNetConfig) -> R
                 amount: Amount::from_tokens(4),
                 nature: OrderNature::Ask,
                 price: Price { price },
+                expiry: None,
             })
             .await;
     }
@@ -2660,6 +2737,66 @@ async fn test_end_to_end_open_multi_owner_chain(config: impl LineraNetConfig) ->
     Ok(())
 }
 
+#[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_test_service_grpc"))]
+#[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
+#[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
+#[cfg_attr(feature = "kubernetes", test_case(SharedLocalKubernetesNetTestingConfig::new(Network::Grpc, BuildArg::Build) ; "kubernetes_grpc"))]
+#[cfg_attr(feature = "remote-net", test_case(RemoteNetTestingConfig::new(None) ; "remote_net_grpc"))]
+#[test_log::test(tokio::test)]
+async fn test_end_to_end_multi_owner_commands(config: impl LineraNetConfig) -> Result<()> {
+    let _guard = INTEGRATION_TEST_GUARD.lock().await;
+    tracing::info!("Starting test {}", test_name!());
+
+    let (mut net, client1) = config.instantiate().await?;
+    let chain1 = client1.load_wallet()?.default_chain().unwrap();
+
+    // The other wallets have no chain yet.
+    let client2 = net.make_client().await;
+    client2.wallet_init(&[], FaucetOption::None).await?;
+    let client3 = net.make_client().await;
+    client3.wallet_init(&[], FaucetOption::None).await?;
+    let owner2 = client2.multi_owner_new_key().await?;
+    let owner3 = client3.multi_owner_new_key().await?;
+
+    // Accepting fails until the key is an owner of the chain.
+    let chain2 = client1
+        .multi_owner_open(chain1, vec![owner2], Amount::from_tokens(6))
+        .await?;
+    assert!(client3.multi_owner_accept(chain2).await.is_err());
+    assert_eq!(client2.multi_owner_accept(chain2).await?, owner2);
+    assert!(client2.multi_owner_accept(chain2).await.is_err());
+
+    client1.multi_owner_add_owner(chain2, owner3).await?;
+    assert_eq!(client3.multi_owner_accept(chain2).await?, owner3);
+    assert!(client1.multi_owner_add_owner(chain2, owner3).await.is_err());
+
+    // All three wallets can use the chain.
+    client2.transfer(Amount::ONE, chain2, chain1).await?;
+    client3.sync(chain2).await?;
+    client3.transfer(Amount::ONE, chain2, chain1).await?;
+    client1.sync(chain2).await?;
+    client1.transfer(Amount::ONE, chain2, chain1).await?;
+    client1.sync(chain1).await?;
+    client1.process_inbox(chain1).await?;
+
+    // Once removed, an owner can't propose blocks anymore.
+    client1.multi_owner_remove_owner(chain2, owner3).await?;
+    client3.sync(chain2).await?;
+    assert!(client3.transfer(Amount::ONE, chain2, chain1).await.is_err());
+    assert!(client1
+        .multi_owner_remove_owner(chain2, owner3)
+        .await
+        .is_err());
+
+    let account2 = Account::chain(chain2);
+    assert!(client1.query_balance(account2).await? <= Amount::from_tokens(3));
+
+    net.ensure_is_running().await?;
+    net.terminate().await?;
+
+    Ok(())
+}
+
 #[cfg_attr(feature = "storage-service", test_case(LocalNetConfig::new_test(Database::Service, Network::Grpc) ; "storage_test_service_grpc"))]
 #[cfg_attr(feature = "scylladb", test_case(LocalNetConfig::new_test(Database::ScyllaDb, Network::Grpc) ; "scylladb_grpc"))]
 #[cfg_attr(feature = "dynamodb", test_case(LocalNetConfig::new_test(Database::DynamoDb, Network::Grpc) ; "aws_grpc"))]
@@ -3,7 +3,10 @@
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use linera_storage_service::client::ServiceStoreClient;
-use linera_views::{store::TestKeyValueStore as _, test_utils::performance};
+use linera_views::{
+    store::TestKeyValueStore as _,
+    test_utils::performance::{self, Workload},
+};
 use tokio::runtime::Runtime;
 
 fn bench_storage_service(criterion: &mut Criterion) {
@@ -12,7 +15,7 @@ fn bench_storage_service(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ServiceStoreClient::new_test_store().await.unwrap();
-                performance::contains_key(store, iterations, black_box).await
+                performance::contains_key(store, Workload::default(), iterations, black_box).await
             })
     });
 
@@ -21,7 +24,7 @@ fn bench_storage_service(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ServiceStoreClient::new_test_store().await.unwrap();
-                performance::contains_keys(store, iterations, black_box).await
+                performance::contains_keys(store, Workload::default(), iterations, black_box).await
             })
     });
 
@@ -30,7 +33,8 @@ fn bench_storage_service(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ServiceStoreClient::new_test_store().await.unwrap();
-                performance::find_keys_by_prefix(store, iterations, black_box).await
+                performance::find_keys_by_prefix(store, Workload::default(), iterations, black_box)
+                    .await
             })
     });
 
@@ -41,7 +45,13 @@ fn bench_storage_service(criterion: &mut Criterion) {
                 .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
                 .iter_custom(|iterations| async move {
                     let store = ServiceStoreClient::new_test_store().await.unwrap();
-                    performance::find_key_values_by_prefix(store, iterations, black_box).await
+                    performance::find_key_values_by_prefix(
+                        store,
+                        Workload::default(),
+                        iterations,
+                        black_box,
+                    )
+                    .await
                 })
         },
     );
@@ -51,7 +61,8 @@ fn bench_storage_service(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ServiceStoreClient::new_test_store().await.unwrap();
-                performance::read_value_bytes(store, iterations, black_box).await
+                performance::read_value_bytes(store, Workload::default(), iterations, black_box)
+                    .await
             })
     });
 
@@ -60,7 +71,13 @@ fn bench_storage_service(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ServiceStoreClient::new_test_store().await.unwrap();
-                performance::read_multi_values_bytes(store, iterations, black_box).await
+                performance::read_multi_values_bytes(
+                    store,
+                    Workload::default(),
+                    iterations,
+                    black_box,
+                )
+                .await
             })
     });
 
@@ -69,7 +86,7 @@ fn bench_storage_service(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ServiceStoreClient::new_test_store().await.unwrap();
-                performance::write_batch(store, iterations).await
+                performance::write_batch(store, Workload::default(), iterations).await
             })
     });
 }
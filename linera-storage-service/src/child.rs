@@ -1,6 +1,8 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::path::PathBuf;
+
 use anyhow::{bail, Result};
 use linera_base::{command::CommandExt, time::Duration};
 use tokio::process::{Child, Command};
@@ -11,6 +13,7 @@ use crate::client::{storage_service_check_absence, storage_service_check_validit
 pub struct StorageService {
     endpoint: String,
     binary: String,
+    file: Option<PathBuf>,
 }
 
 /// A storage service running as a child process.
@@ -27,12 +30,22 @@ impl StorageService {
         Self {
             endpoint: endpoint.to_string(),
             binary,
+            file: None,
         }
     }
 
+    /// Saves the data of the service to `file`, so that it survives restarts.
+    pub fn with_file(mut self, file: PathBuf) -> Self {
+        self.file = Some(file);
+        self
+    }
+
     async fn command(&self) -> Command {
         let mut command = Command::new(&self.binary);
         command.args(["memory", "--endpoint", &self.endpoint]);
+        if let Some(file) = &self.file {
+            command.arg("--path").arg(file);
+        }
         command.kill_on_drop(true);
         command
     }
@@ -9,6 +9,7 @@ use async_lock::RwLock;
 use linera_storage_service::common::{KeyTag, MAX_PAYLOAD_SIZE};
 use linera_views::{
     batch::Batch,
+    file_backed_memory::FileBackedMemoryStore,
     memory::MemoryStore,
     store::{CommonStoreConfig, ReadableKeyValueStore, WritableKeyValueStore},
 };
@@ -40,6 +41,8 @@ pub mod key_value_store {
 
 enum ServiceStoreServerInternal {
     Memory(MemoryStore),
+    /// The memory key value store, with the writes saved to files
+    FileBackedMemory(FileBackedMemoryStore),
     /// The RocksDb key value store
     #[cfg(with_rocksdb)]
     RocksDb(RocksDbStore),
@@ -70,6 +73,14 @@ impl ServiceStoreServer {
                 .read_value_bytes(key)
                 .await
                 .map_err(|e| Status::unknown(format!("Memory error {:?} at read_value_bytes", e))),
+            ServiceStoreServerInternal::FileBackedMemory(store) => {
+                store.read_value_bytes(key).await.map_err(|e| {
+                    Status::unknown(format!(
+                        "File-backed memory error {:?} at read_value_bytes",
+                        e
+                    ))
+                })
+            }
             #[cfg(with_rocksdb)]
             ServiceStoreServerInternal::RocksDb(store) => store
                 .read_value_bytes(key)
@@ -84,6 +95,11 @@ impl ServiceStoreServer {
                 .contains_key(key)
                 .await
                 .map_err(|e| Status::unknown(format!("Memory error {:?} at contains_key", e))),
+            ServiceStoreServerInternal::FileBackedMemory(store) => {
+                store.contains_key(key).await.map_err(|e| {
+                    Status::unknown(format!("File-backed memory error {:?} at contains_key", e))
+                })
+            }
             #[cfg(with_rocksdb)]
             ServiceStoreServerInternal::RocksDb(store) => store
                 .contains_key(key)
@@ -98,6 +114,11 @@ impl ServiceStoreServer {
                 .contains_keys(keys)
                 .await
                 .map_err(|e| Status::unknown(format!("Memory error {:?} at contains_keys", e))),
+            ServiceStoreServerInternal::FileBackedMemory(store) => {
+                store.contains_keys(keys).await.map_err(|e| {
+                    Status::unknown(format!("File-backed memory error {:?} at contains_keys", e))
+                })
+            }
             #[cfg(with_rocksdb)]
             ServiceStoreServerInternal::RocksDb(store) => store
                 .contains_keys(keys)
@@ -116,6 +137,14 @@ impl ServiceStoreServer {
                     Status::unknown(format!("Memory error {:?} at read_multi_values_bytes", e))
                 })
             }
+            ServiceStoreServerInternal::FileBackedMemory(store) => {
+                store.read_multi_values_bytes(keys).await.map_err(|e| {
+                    Status::unknown(format!(
+                        "File-backed memory error {:?} at read_multi_values_bytes",
+                        e
+                    ))
+                })
+            }
             #[cfg(with_rocksdb)]
             ServiceStoreServerInternal::RocksDb(store) => {
                 store.read_multi_values_bytes(keys).await.map_err(|e| {
@@ -132,6 +161,14 @@ impl ServiceStoreServer {
                     Status::unknown(format!("Memory error {:?} at find_keys_by_prefix", e))
                 })
             }
+            ServiceStoreServerInternal::FileBackedMemory(store) => {
+                store.find_keys_by_prefix(key_prefix).await.map_err(|e| {
+                    Status::unknown(format!(
+                        "File-backed memory error {:?} at find_keys_by_prefix",
+                        e
+                    ))
+                })
+            }
             #[cfg(with_rocksdb)]
             ServiceStoreServerInternal::RocksDb(store) => {
                 store.find_keys_by_prefix(key_prefix).await.map_err(|e| {
@@ -152,6 +189,15 @@ impl ServiceStoreServer {
                 .map_err(|e| {
                     Status::unknown(format!("Memory error {:?} at find_key_values_by_prefix", e))
                 }),
+            ServiceStoreServerInternal::FileBackedMemory(store) => store
+                .find_key_values_by_prefix(key_prefix)
+                .await
+                .map_err(|e| {
+                    Status::unknown(format!(
+                        "File-backed memory error {:?} at find_key_values_by_prefix",
+                        e
+                    ))
+                }),
             #[cfg(with_rocksdb)]
             ServiceStoreServerInternal::RocksDb(store) => store
                 .find_key_values_by_prefix(key_prefix)
@@ -171,6 +217,11 @@ impl ServiceStoreServer {
                 .write_batch(batch)
                 .await
                 .map_err(|e| Status::unknown(format!("Memory error {:?} at write_batch", e))),
+            ServiceStoreServerInternal::FileBackedMemory(store) => {
+                store.write_batch(batch).await.map_err(|e| {
+                    Status::unknown(format!("File-backed memory error {:?} at write_batch", e))
+                })
+            }
             #[cfg(with_rocksdb)]
             ServiceStoreServerInternal::RocksDb(store) => store
                 .write_batch(batch)
@@ -245,6 +296,10 @@ enum ServiceStoreServerOptions {
     Memory {
         #[arg(long = "endpoint")]
         endpoint: String,
+        /// If set, the data is loaded from this file on startup, and the writes are saved
+        /// to it and to a log next to it, so that the data survives restarts.
+        #[arg(long = "path")]
+        path: Option<String>,
     },
 
     #[cfg(with_rocksdb)]
@@ -582,12 +637,30 @@ async fn main() {
     let namespace = "linera_storage_service";
     let root_key = &[];
     let (store, endpoint) = match options {
-        ServiceStoreServerOptions::Memory { endpoint } => {
+        ServiceStoreServerOptions::Memory {
+            endpoint,
+            path: None,
+        } => {
             let store =
                 MemoryStore::new(common_config.max_stream_queries, namespace, root_key).unwrap();
             let store = ServiceStoreServerInternal::Memory(store);
             (store, endpoint)
         }
+        ServiceStoreServerOptions::Memory {
+            endpoint,
+            path: Some(path),
+        } => {
+            let store = FileBackedMemoryStore::new(
+                path.into(),
+                common_config.max_stream_queries,
+                namespace,
+                root_key,
+            )
+            .await
+            .expect("store");
+            let store = ServiceStoreServerInternal::FileBackedMemory(store);
+            (store, endpoint)
+        }
         #[cfg(with_rocksdb)]
         ServiceStoreServerOptions::RocksDb { path, endpoint } => {
             let path_buf = path.into();
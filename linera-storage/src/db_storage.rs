@@ -3,32 +3,33 @@
 
 #[cfg(with_metrics)]
 use std::sync::LazyLock;
-use std::{fmt::Debug, sync::Arc};
+use std::{collections::BTreeSet, fmt::Debug, sync::Arc};
 
 use async_trait::async_trait;
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use linera_base::{
     crypto::CryptoHash,
     data_types::{Blob, TimeDelta, Timestamp},
     hashed::Hashed,
-    identifiers::{BlobId, ChainId, UserApplicationId},
+    identifiers::{BlobId, ChainId, Destination, UserApplicationId},
 };
 use linera_chain::{
     types::{ConfirmedBlock, ConfirmedBlockCertificate, LiteCertificate},
     ChainStateView,
 };
 use linera_execution::{
-    committee::Epoch, BlobState, ExecutionRuntimeConfig, UserContractCode, UserServiceCode,
-    WasmRuntime,
+    committee::Epoch, BlobState, ExecutionRuntimeConfig, NativeApplicationRegistry,
+    UserContractCode, UserServiceCode, WasmRuntime,
 };
 use linera_views::{
     backends::dual::{DualStoreRootKeyAssignment, StoreInUse},
     batch::Batch,
     context::ViewContext,
-    store::{KeyIterable as _, KeyValueStore},
-    views::{View, ViewError},
+    store::{KeyIterable as _, KeyValueIterable as _, KeyValueStore},
+    views::{RootView, View, ViewError},
 };
 use serde::{Deserialize, Serialize};
+use tracing::instrument;
 #[cfg(with_testing)]
 use {
     futures::channel::oneshot::{self, Receiver},
@@ -173,6 +174,16 @@ pub static WRITE_CERTIFICATE_COUNTER: LazyLock<IntCounterVec> = LazyLock::new(||
     )
 });
 
+/// The metric counting how often a certificate is removed when replaying the write-ahead log.
+#[cfg(with_metrics)]
+static REVERTED_CERTIFICATE_COUNTER: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "reverted_certificate",
+        "The metric counting how often a certificate is removed when replaying the write-ahead log",
+        &[],
+    )
+});
+
 /// The latency to load a chain state.
 #[cfg(with_metrics)]
 #[doc(hidden)]
@@ -190,8 +201,20 @@ trait BatchExt {
 
     fn add_blob_state(&mut self, blob_id: BlobId, blob_state: &BlobState) -> Result<(), ViewError>;
 
+    fn delete_blob_state(&mut self, blob_id: BlobId) -> Result<(), ViewError>;
+
     fn add_certificate(&mut self, certificate: &ConfirmedBlockCertificate)
         -> Result<(), ViewError>;
+
+    fn delete_certificate(&mut self, hash: CryptoHash) -> Result<(), ViewError>;
+
+    fn add_write_ahead_log_entry(
+        &mut self,
+        chain_id: ChainId,
+        entry: &WriteAheadLogEntry,
+    ) -> Result<(), ViewError>;
+
+    fn delete_write_ahead_log_entry(&mut self, chain_id: ChainId) -> Result<(), ViewError>;
 }
 
 impl BatchExt for Batch {
@@ -209,6 +232,12 @@ impl BatchExt for Batch {
         Ok(())
     }
 
+    fn delete_blob_state(&mut self, blob_id: BlobId) -> Result<(), ViewError> {
+        let blob_state_key = bcs::to_bytes(&BaseKey::BlobState(blob_id))?;
+        self.delete_key(blob_state_key);
+        Ok(())
+    }
+
     fn add_certificate(
         &mut self,
         certificate: &ConfirmedBlockCertificate,
@@ -222,6 +251,30 @@ impl BatchExt for Batch {
         self.put_key_value(value_key.to_vec(), certificate.value())?;
         Ok(())
     }
+
+    fn delete_certificate(&mut self, hash: CryptoHash) -> Result<(), ViewError> {
+        let cert_key = bcs::to_bytes(&BaseKey::Certificate(hash))?;
+        let value_key = bcs::to_bytes(&BaseKey::ConfirmedBlock(hash))?;
+        self.delete_key(cert_key);
+        self.delete_key(value_key);
+        Ok(())
+    }
+
+    fn add_write_ahead_log_entry(
+        &mut self,
+        chain_id: ChainId,
+        entry: &WriteAheadLogEntry,
+    ) -> Result<(), ViewError> {
+        let log_key = bcs::to_bytes(&BaseKey::WriteAheadLog(chain_id))?;
+        self.put_key_value(log_key, entry)?;
+        Ok(())
+    }
+
+    fn delete_write_ahead_log_entry(&mut self, chain_id: ChainId) -> Result<(), ViewError> {
+        let log_key = bcs::to_bytes(&BaseKey::WriteAheadLog(chain_id))?;
+        self.delete_key(log_key);
+        Ok(())
+    }
 }
 
 /// Main implementation of the [`Storage`] trait.
@@ -233,6 +286,14 @@ pub struct DbStorage<Store, Clock> {
     user_contracts: Arc<DashMap<UserApplicationId, UserContractCode>>,
     user_services: Arc<DashMap<UserApplicationId, UserServiceCode>>,
     execution_runtime_config: ExecutionRuntimeConfig,
+    native_applications: NativeApplicationRegistry,
+    /// The chains whose namespace is known to be registered in the store.
+    registered_chains: Arc<DashSet<ChainId>>,
+    /// Whether each chain is known to be listed in the index of chains with pending deliveries.
+    pending_delivery_markers: Arc<DashMap<ChainId, bool>>,
+    /// The partition of the index of chains with pending deliveries in which the chains saved
+    /// by this instance are listed.
+    pending_deliveries_partition: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -242,19 +303,400 @@ enum BaseKey {
     ConfirmedBlock(CryptoHash),
     Blob(BlobId),
     BlobState(BlobId),
+    /// The [`WriteAheadLogEntry`] of a chain whose state changes may not have been saved yet.
+    WriteAheadLog(ChainId),
+    /// The marker of a chain whose state was stored under [`BaseKey::ChainState`].
+    ChainNamespace(ChainId),
+    /// The marker of a chain that may have outgoing messages that were not acknowledged yet, in
+    /// the partition of the index read by the shard handling the chain.
+    PendingDeliveries(u32, ChainId),
+    /// The state of a service run on top of the storage, e.g. a faucet.
+    ServiceState(String),
+    /// The marker of a chain whose state is being deleted by [`delete_chain_namespace`].
+    DeletedChain(ChainId),
 }
 
+/// The record of the last certificate written for a chain, until the chain state is saved.
+#[derive(Debug, Serialize, Deserialize)]
+struct WriteAheadLogEntry {
+    /// The hash of the certificate.
+    certificate_hash: CryptoHash,
+    /// The blob states that were updated in the same batch as the certificate, with their
+    /// previous values, so that they can be restored if the certificate is removed.
+    previous_blob_states: Vec<(BlobId, Option<BlobState>)>,
+}
+
+const INDEX_CONFIRMED_BLOCK: u8 = 2;
 const INDEX_BLOB: u8 = 3;
+const INDEX_WRITE_AHEAD_LOG: u8 = 5;
+const INDEX_CHAIN_NAMESPACE: u8 = 6;
+const INDEX_PENDING_DELIVERIES: u8 = 7;
+const INDEX_SERVICE_STATE: u8 = 8;
+const INDEX_DELETED_CHAIN: u8 = 9;
 const BLOB_LENGTH: usize = std::mem::size_of::<BlobId>();
+/// The number of confirmed blocks read at once by [`DbStorage::backfill_chain_indices`].
+const BACKFILL_BLOCKS_PER_READ: usize = 100;
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+
     use linera_base::{
         crypto::CryptoHash,
-        identifiers::{BlobId, BlobType},
+        data_types::{Blob, BlockHeight, Round},
+        hashed::Hashed,
+        identifiers::{BlobId, BlobType, ChainId},
+    };
+    use linera_chain::{
+        data_types::BlockExecutionOutcome,
+        test::{make_first_block, BlockTestExt as _},
+        types::{ConfirmedBlock, ConfirmedBlockCertificate},
+    };
+    use linera_execution::{committee::Epoch, system::SystemOperation, BlobState};
+    use linera_views::{
+        batch::Batch,
+        memory::MemoryStore,
+        store::{KeyIterable as _, ReadableKeyValueStore as _, WritableKeyValueStore as _},
+        views::RootView as _,
     };
 
-    use crate::db_storage::{BaseKey, INDEX_BLOB};
+    use crate::{
+        db_storage::{
+            chain_namespace, complete_chain_deletions, delete_chain_namespace, list_chain_ids,
+            BaseKey, DbStorage, INDEX_BLOB, INDEX_CHAIN_NAMESPACE, INDEX_CONFIRMED_BLOCK,
+            INDEX_DELETED_CHAIN, INDEX_PENDING_DELIVERIES, INDEX_SERVICE_STATE,
+            INDEX_WRITE_AHEAD_LOG,
+        },
+        Storage as _, TestClock,
+    };
+
+    /// Returns a certificate for the first block of `chain_id` in `epoch`, publishing the blobs.
+    fn make_certificate(
+        chain_id: ChainId,
+        epoch: Epoch,
+        blobs: &[Blob],
+    ) -> ConfirmedBlockCertificate {
+        let mut block = make_first_block(chain_id);
+        block.epoch = epoch;
+        for blob in blobs {
+            block = block.with_operation(SystemOperation::PublishDataBlob {
+                blob_hash: blob.id().hash,
+            });
+        }
+        let executed_block = BlockExecutionOutcome::default().with(block);
+        let value = Hashed::new(ConfirmedBlock::new(executed_block));
+        ConfirmedBlockCertificate::new(value, Round::Fast, vec![])
+    }
+
+    /// Tests that a certificate whose chain state was not saved before a crash is removed when
+    /// the write-ahead log is replayed, together with the blob states it wrote.
+    #[tokio::test]
+    async fn test_replay_write_ahead_log_reverts_unsaved_certificate() -> anyhow::Result<()> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let chain_id = ChainId::root(0);
+        let new_blob = Blob::new_data(b"new".to_vec());
+        let known_blob = Blob::new_data(b"known".to_vec());
+        let known_blob_state = BlobState {
+            last_used_by: CryptoHash::test_hash("another certificate"),
+            chain_id: ChainId::root(1),
+            block_height: BlockHeight::ZERO,
+            epoch: Epoch::ZERO,
+        };
+        storage
+            .write_blob_state(known_blob.id(), &known_blob_state)
+            .await?;
+        let blobs = [new_blob.clone(), known_blob.clone()];
+        let certificate = make_certificate(chain_id, Epoch(1), &blobs);
+
+        // The node crashes after writing the certificate, before saving the chain state.
+        storage
+            .write_blobs_and_certificate(&blobs, &certificate)
+            .await?;
+        assert!(storage.contains_certificate(certificate.hash()).await?);
+        assert_eq!(
+            storage.read_blob_state(known_blob.id()).await?.last_used_by,
+            certificate.hash()
+        );
+
+        storage.replay_write_ahead_log(|_| true).await?;
+        assert!(!storage.contains_certificate(certificate.hash()).await?);
+        assert!(!storage.contains_blob_state(new_blob.id()).await?);
+        assert_eq!(
+            storage.read_blob_state(known_blob.id()).await?,
+            known_blob_state
+        );
+        // The log entry was removed.
+        storage.replay_write_ahead_log(|_| true).await?;
+        Ok(())
+    }
+
+    /// Tests that a certificate whose chain state was saved is kept when the write-ahead log
+    /// is replayed.
+    #[tokio::test]
+    async fn test_replay_write_ahead_log_keeps_saved_certificate() -> anyhow::Result<()> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let chain_id = ChainId::root(0);
+        let blob = Blob::new_data(b"blob".to_vec());
+        let certificate = make_certificate(chain_id, Epoch::ZERO, &[blob.clone()]);
+        storage
+            .write_blobs_and_certificate(&[blob.clone()], &certificate)
+            .await?;
+        let mut chain = storage.load_chain(chain_id).await?;
+        chain.confirmed_log.push(certificate.hash());
+        chain.save().await?;
+
+        // The node crashes before clearing the write-ahead log.
+        storage.replay_write_ahead_log(|_| true).await?;
+        assert!(storage.contains_certificate(certificate.hash()).await?);
+        assert_eq!(
+            storage.read_blob_state(blob.id()).await?.last_used_by,
+            certificate.hash()
+        );
+        Ok(())
+    }
+
+    /// Tests that the chains are listed with pending deliveries exactly while their saved state
+    /// has outgoing messages that were not acknowledged.
+    #[tokio::test]
+    async fn test_read_pending_deliveries() -> anyhow::Result<()> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let chain_ids = [ChainId::root(0), ChainId::root(1)];
+        for chain_id in chain_ids {
+            let mut chain = storage.load_chain(chain_id).await?;
+            chain.outbox_counters.get_mut().insert(BlockHeight::ZERO, 1);
+            storage.save_chain(&mut chain).await?;
+        }
+        let pending = storage.read_pending_deliveries(|_| true).await?;
+        assert_eq!(BTreeSet::from_iter(pending), BTreeSet::from(chain_ids));
+        assert_eq!(
+            storage
+                .read_pending_deliveries(|chain_id| chain_id == chain_ids[1])
+                .await?,
+            [chain_ids[1]]
+        );
+
+        // The index is updated when the messages are acknowledged.
+        let mut chain = storage.load_chain(chain_ids[0]).await?;
+        chain.outbox_counters.get_mut().clear();
+        storage.save_chain(&mut chain).await?;
+        assert_eq!(
+            storage.read_pending_deliveries(|_| true).await?,
+            [chain_ids[1]]
+        );
+
+        // Chains that are only loaded are not listed.
+        storage.load_chain(ChainId::root(2)).await?;
+        assert_eq!(
+            storage.read_pending_deliveries(|_| true).await?,
+            [chain_ids[1]]
+        );
+        Ok(())
+    }
+
+    /// Tests that the chains are listed in the partition of the index of the storage that saved
+    /// them, and moved to the partition of the storage that scans the whole index.
+    #[tokio::test]
+    async fn test_pending_deliveries_partitions() -> anyhow::Result<()> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let shards = [0, 1].map(|shard| storage.clone().with_pending_deliveries_partition(shard));
+        let chain_ids = [ChainId::root(0), ChainId::root(1)];
+        for (shard, chain_id) in shards.iter().zip(chain_ids) {
+            let mut chain = shard.load_chain(chain_id).await?;
+            chain.outbox_counters.get_mut().insert(BlockHeight::ZERO, 1);
+            shard.save_chain(&mut chain).await?;
+        }
+        for (shard, chain_id) in shards.iter().zip(chain_ids) {
+            assert_eq!(
+                shard.read_partition_pending_deliveries(|_| true).await?,
+                [chain_id]
+            );
+        }
+        assert!(shards[0]
+            .read_partition_pending_deliveries(|chain_id| chain_id == chain_ids[1])
+            .await?
+            .is_empty());
+
+        // The second shard now handles both chains, e.g. after the shards were reconfigured.
+        let pending = shards[1].read_pending_deliveries(|_| true).await?;
+        assert_eq!(BTreeSet::from_iter(pending), BTreeSet::from(chain_ids));
+        assert!(shards[0]
+            .read_partition_pending_deliveries(|_| true)
+            .await?
+            .is_empty());
+        let pending = shards[1]
+            .read_partition_pending_deliveries(|_| true)
+            .await?;
+        assert_eq!(BTreeSet::from_iter(pending), BTreeSet::from(chain_ids));
+
+        // Deleting a chain removes it from every partition.
+        delete_chain_namespace(&*storage.store, chain_ids[0]).await?;
+        assert_eq!(
+            shards[1].read_pending_deliveries(|_| true).await?,
+            [chain_ids[1]]
+        );
+        Ok(())
+    }
+
+    /// Tests that chains are listed once their state is saved, and no longer once it is deleted.
+    #[tokio::test]
+    async fn test_list_and_delete_chains() -> anyhow::Result<()> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let chain_id = ChainId::root(0);
+
+        // Loading a chain doesn't write anything.
+        storage.load_chain(chain_id).await?;
+        assert!(list_chain_ids(&*storage.store).await?.is_empty());
+
+        let mut chain = storage.load_chain(chain_id).await?;
+        chain.outbox_counters.get_mut().insert(BlockHeight::ZERO, 1);
+        storage.save_chain(&mut chain).await?;
+        assert_eq!(list_chain_ids(&*storage.store).await?, [chain_id]);
+        assert_eq!(storage.read_pending_deliveries(|_| true).await?, [chain_id]);
+
+        delete_chain_namespace(&*storage.store, chain_id).await?;
+        assert!(list_chain_ids(&*storage.store).await?.is_empty());
+        assert!(storage.read_pending_deliveries(|_| true).await?.is_empty());
+        assert!(!storage.has_chain_state(chain_id).await?);
+        Ok(())
+    }
+
+    /// Tests that a deletion interrupted after the chain was removed from the indices is
+    /// completed later.
+    #[tokio::test]
+    async fn test_complete_interrupted_chain_deletion() -> anyhow::Result<()> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let chain_id = ChainId::root(0);
+        let mut chain = storage.load_chain(chain_id).await?;
+        chain.outbox_counters.get_mut().insert(BlockHeight::ZERO, 1);
+        storage.save_chain(&mut chain).await?;
+
+        // Only the first batch of the deletion is written.
+        let mut batch = Batch::new();
+        batch.delete_key(bcs::to_bytes(&BaseKey::ChainNamespace(chain_id))?);
+        batch.delete_key(bcs::to_bytes(&BaseKey::PendingDeliveries(0, chain_id))?);
+        batch.put_key_value_bytes(bcs::to_bytes(&BaseKey::DeletedChain(chain_id))?, Vec::new());
+        storage.store.write_batch(batch).await?;
+        assert!(list_chain_ids(&*storage.store).await?.is_empty());
+        assert!(storage.has_chain_state(chain_id).await?);
+
+        complete_chain_deletions(&*storage.store).await?;
+        assert!(!storage.has_chain_state(chain_id).await?);
+        let store = chain_namespace(&*storage.store, chain_id)?;
+        assert!(store
+            .find_keys_by_prefix(&[])
+            .await?
+            .iterator()
+            .next()
+            .is_none());
+        let keys = storage
+            .store
+            .find_keys_by_prefix(&[INDEX_DELETED_CHAIN])
+            .await?;
+        assert!(keys.iterator().next().is_none());
+        Ok(())
+    }
+
+    /// Tests that the chains of a storage written before they were indexed are found among the
+    /// root chains and the chains of the confirmed blocks.
+    #[tokio::test]
+    async fn test_backfill_chain_indices() -> anyhow::Result<()> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let root_chain_id = ChainId::root(0);
+        let other_chain_id = ChainId::root(5);
+        let certificate = make_certificate(other_chain_id, Epoch::ZERO, &[]);
+        storage
+            .write_blobs_and_certificate(&[], &certificate)
+            .await?;
+
+        // The states are saved without updating the indices.
+        let mut chain = storage.load_chain(root_chain_id).await?;
+        chain.confirmed_log.push(CryptoHash::test_hash("block"));
+        chain.save().await?;
+        let mut chain = storage.load_chain(other_chain_id).await?;
+        chain.confirmed_log.push(certificate.hash());
+        chain.outbox_counters.get_mut().insert(BlockHeight::ZERO, 1);
+        chain.save().await?;
+        assert!(list_chain_ids(&*storage.store).await?.is_empty());
+        assert!(storage.read_pending_deliveries(|_| true).await?.is_empty());
+
+        let registered = storage.backfill_chain_indices().await?;
+        let expected = BTreeSet::from([root_chain_id, other_chain_id]);
+        assert_eq!(BTreeSet::from_iter(registered), expected);
+        let listed = list_chain_ids(&*storage.store).await?;
+        assert_eq!(BTreeSet::from_iter(listed), expected);
+        assert_eq!(
+            storage.read_pending_deliveries(|_| true).await?,
+            [other_chain_id]
+        );
+        Ok(())
+    }
+
+    /// Tests that the native implementations registered in the storage are used instead of the
+    /// application bytecodes, and behave like the WebAssembly modules loaded from the blobs.
+    ///
+    /// The "counter" example compiled directly stands in for a native implementation here.
+    #[cfg(with_wasm_runtime)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_native_applications_replace_wasm_bytecode() -> anyhow::Result<()> {
+        use linera_base::{
+            data_types::{Bytecode, UserApplicationDescription},
+            identifiers::{BytecodeId, MessageId},
+        };
+        use linera_execution::{
+            test_utils::{check_native_equivalence, ApplicationCode},
+            wasm_test, NativeApplicationRegistry, ResourceControlPolicy, WasmContractModule,
+            WasmRuntime, WasmServiceModule,
+        };
+
+        let wasm_runtime = WasmRuntime::default();
+        let (contract_path, service_path) = wasm_test::get_example_bytecode_paths("counter")?;
+        let contract_bytecode = Bytecode::load_from_file(contract_path).await?;
+        let service_bytecode = Bytecode::load_from_file(service_path).await?;
+        let contract_blob = Blob::new_contract_bytecode(contract_bytecode.clone().compress());
+        let service_blob = Blob::new_service_bytecode(service_bytecode.clone().compress());
+        let bytecode_id = BytecodeId::new(contract_blob.id().hash, service_blob.id().hash);
+        let description = UserApplicationDescription {
+            bytecode_id,
+            creation: MessageId {
+                chain_id: ChainId::root(0),
+                height: BlockHeight::ZERO,
+                index: 0,
+            },
+            parameters: b"null".to_vec(),
+            required_application_ids: vec![],
+        };
+
+        let wasm_storage =
+            DbStorage::<MemoryStore, TestClock>::make_test_storage(Some(wasm_runtime)).await;
+        wasm_storage
+            .write_blobs(&[contract_blob, service_blob])
+            .await?;
+
+        // The native storage doesn't have the bytecode blobs, so it can only use the registry.
+        let mut native_applications = NativeApplicationRegistry::default();
+        native_applications.register_application(
+            bytecode_id,
+            WasmContractModule::new(contract_bytecode, wasm_runtime).await?,
+            WasmServiceModule::new(service_bytecode, wasm_runtime).await?,
+        );
+        let native_storage =
+            DbStorage::<MemoryStore, TestClock>::make_test_storage(Some(wasm_runtime))
+                .await
+                .with_native_applications(native_applications);
+
+        let reference = ApplicationCode {
+            contract: wasm_storage.load_contract(&description).await?,
+            service: wasm_storage.load_service(&description).await?,
+        };
+        let native = ApplicationCode {
+            contract: native_storage.load_contract(&description).await?,
+            service: native_storage.load_service(&description).await?,
+        };
+        let operations = [bcs::to_bytes(&5_u64)?, bcs::to_bytes(&37_u64)?];
+        let queries = [br#"{"query":"query { value }"}"#.to_vec()];
+        let policy = ResourceControlPolicy::default();
+        check_native_equivalence(reference, native, &policy, &operations, &queries).await
+    }
 
     #[test]
     fn test_base_key_serialization() {
@@ -264,6 +706,55 @@ mod tests {
         let base_key = BaseKey::Blob(blob_id);
         let key = bcs::to_bytes(&base_key).expect("a key");
         assert_eq!(key[0], INDEX_BLOB);
+        let base_key = BaseKey::ConfirmedBlock(hash);
+        let key = bcs::to_bytes(&base_key).expect("a key");
+        assert_eq!(key[0], INDEX_CONFIRMED_BLOCK);
+        assert_eq!(bcs::from_bytes::<CryptoHash>(&key[1..]).unwrap(), hash);
+    }
+
+    #[test]
+    fn test_write_ahead_log_key_serialization() {
+        let chain_id = ChainId::root(0);
+        let base_key = BaseKey::WriteAheadLog(chain_id);
+        let key = bcs::to_bytes(&base_key).expect("a key");
+        assert_eq!(key[0], INDEX_WRITE_AHEAD_LOG);
+        assert_eq!(bcs::from_bytes::<ChainId>(&key[1..]).unwrap(), chain_id);
+    }
+
+    #[test]
+    fn test_chain_namespace_key_serialization() {
+        let chain_id = ChainId::root(0);
+        let base_key = BaseKey::ChainNamespace(chain_id);
+        let key = bcs::to_bytes(&base_key).expect("a key");
+        assert_eq!(key[0], INDEX_CHAIN_NAMESPACE);
+        assert_eq!(bcs::from_bytes::<ChainId>(&key[1..]).unwrap(), chain_id);
+    }
+
+    #[test]
+    fn test_pending_deliveries_key_serialization() {
+        let chain_id = ChainId::root(0);
+        let base_key = BaseKey::PendingDeliveries(3, chain_id);
+        let key = bcs::to_bytes(&base_key).expect("a key");
+        assert_eq!(key[0], INDEX_PENDING_DELIVERIES);
+        assert_eq!(key[1..5], 3u32.to_le_bytes());
+        assert_eq!(bcs::from_bytes::<ChainId>(&key[5..]).unwrap(), chain_id);
+    }
+
+    #[test]
+    fn test_service_state_key_serialization() {
+        let base_key = BaseKey::ServiceState("faucet".to_owned());
+        let key = bcs::to_bytes(&base_key).expect("a key");
+        assert_eq!(key[0], INDEX_SERVICE_STATE);
+        assert_eq!(bcs::from_bytes::<String>(&key[1..]).unwrap(), "faucet");
+    }
+
+    #[test]
+    fn test_deleted_chain_key_serialization() {
+        let chain_id = ChainId::root(0);
+        let base_key = BaseKey::DeletedChain(chain_id);
+        let key = bcs::to_bytes(&base_key).expect("a key");
+        assert_eq!(key[0], INDEX_DELETED_CHAIN);
+        assert_eq!(bcs::from_bytes::<ChainId>(&key[1..]).unwrap(), chain_id);
     }
 }
 
@@ -281,6 +772,117 @@ pub async fn list_all_blob_ids<S: KeyValueStore>(store: &S) -> Result<Vec<BlobId
     Ok(blob_ids)
 }
 
+/// Returns the store holding the state of a chain, isolated from the other chains and from
+/// the certificates and blobs.
+fn chain_namespace<S: KeyValueStore>(store: &S, chain_id: ChainId) -> Result<S, ViewError> {
+    let root_key = bcs::to_bytes(&BaseKey::ChainState(chain_id))?;
+    Ok(store.clone_with_root_key(&root_key)?)
+}
+
+/// Lists the chains that have a namespace in the storage.
+pub async fn list_chain_ids<S: KeyValueStore>(store: &S) -> Result<Vec<ChainId>, ViewError> {
+    let prefix = &[INDEX_CHAIN_NAMESPACE];
+    let keys = store.find_keys_by_prefix(prefix).await?;
+    let mut chain_ids = Vec::new();
+    for key in keys.iterator() {
+        chain_ids.push(bcs::from_bytes(key?)?);
+    }
+    Ok(chain_ids)
+}
+
+/// Deletes the whole state of a chain.
+///
+/// A batch cannot span the chain's namespace and the rest of the storage, since the backends
+/// may keep them apart. So the chain is first removed from the indices in a single batch,
+/// which also records that its state is being deleted. From then on, the chain is deleted for
+/// the readers of the indices. Its state is then deleted in a single batch, and the record is
+/// removed. If the process stops in between, the deletion is completed by the next call to
+/// [`complete_chain_deletions`], which this function starts with.
+///
+/// This must not be used while a validator or a client may be loading the chain.
+pub async fn delete_chain_namespace<S: KeyValueStore>(
+    store: &S,
+    chain_id: ChainId,
+) -> Result<(), ViewError> {
+    complete_chain_deletions(store).await?;
+    let deleted_chain_key = bcs::to_bytes(&BaseKey::DeletedChain(chain_id))?;
+    let mut batch = Batch::new();
+    batch.delete_key(bcs::to_bytes(&BaseKey::ChainNamespace(chain_id))?);
+    for partition in pending_deliveries_partitions(store, chain_id).await? {
+        batch.delete_key(bcs::to_bytes(&BaseKey::PendingDeliveries(
+            partition, chain_id,
+        ))?);
+    }
+    batch.put_key_value_bytes(deleted_chain_key.clone(), Vec::new());
+    store.write_batch(batch).await?;
+    delete_chain_state(store, chain_id, deleted_chain_key).await
+}
+
+/// Returns the partitions of the index of chains with pending deliveries that list the given
+/// chain.
+async fn pending_deliveries_partitions<S: KeyValueStore>(
+    store: &S,
+    chain_id: ChainId,
+) -> Result<Vec<u32>, ViewError> {
+    let prefix = &[INDEX_PENDING_DELIVERIES];
+    let keys = store.find_keys_by_prefix(prefix).await?;
+    let mut partitions = Vec::new();
+    for key in keys.iterator() {
+        let (partition, listed_chain_id) = bcs::from_bytes::<(u32, ChainId)>(key?)?;
+        if listed_chain_id == chain_id {
+            partitions.push(partition);
+        }
+    }
+    Ok(partitions)
+}
+
+/// Completes the deletions of chain states that were interrupted, after the chains were removed
+/// from the indices.
+pub async fn complete_chain_deletions<S: KeyValueStore>(store: &S) -> Result<(), ViewError> {
+    let prefix = &[INDEX_DELETED_CHAIN];
+    let keys = store.find_keys_by_prefix(prefix).await?;
+    let mut chain_ids = Vec::new();
+    for key in keys.iterator() {
+        chain_ids.push(bcs::from_bytes::<ChainId>(key?)?);
+    }
+    for chain_id in chain_ids {
+        let deleted_chain_key = bcs::to_bytes(&BaseKey::DeletedChain(chain_id))?;
+        delete_chain_state(store, chain_id, deleted_chain_key).await?;
+    }
+    Ok(())
+}
+
+/// Deletes the state of a chain that was removed from the indices, then the record of its
+/// deletion.
+async fn delete_chain_state<S: KeyValueStore>(
+    store: &S,
+    chain_id: ChainId,
+    deleted_chain_key: Vec<u8>,
+) -> Result<(), ViewError> {
+    let mut batch = Batch::new();
+    batch.delete_key_prefix(Vec::new());
+    chain_namespace(store, chain_id)?.write_batch(batch).await?;
+    let mut batch = Batch::new();
+    batch.delete_key(deleted_chain_key);
+    store.write_batch(batch).await?;
+    Ok(())
+}
+
+/// Reads the raw entries of the state of a chain whose keys start with `key_prefix`.
+pub async fn read_chain_state_entries<S: KeyValueStore>(
+    store: &S,
+    chain_id: ChainId,
+    key_prefix: &[u8],
+) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewError> {
+    let store = chain_namespace(store, chain_id)?;
+    let key_values = store.find_key_values_by_prefix(key_prefix).await?;
+    let mut entries = Vec::new();
+    for entry in key_values.into_iterator_owned() {
+        entries.push(entry?);
+    }
+    Ok(entries)
+}
+
 /// An implementation of [`DualStoreRootKeyAssignment`] that stores the
 /// chain states into the first store.
 pub struct ChainStatesFirstAssignment;
@@ -288,7 +890,7 @@ pub struct ChainStatesFirstAssignment;
 impl DualStoreRootKeyAssignment for ChainStatesFirstAssignment {
     fn assigned_store(root_key: &[u8]) -> Result<StoreInUse, bcs::Error> {
         let store = match bcs::from_bytes(root_key)? {
-            BaseKey::ChainState(_) => StoreInUse::First,
+            BaseKey::ChainState(_) | BaseKey::ServiceState(_) => StoreInUse::First,
             _ => StoreInUse::Second,
         };
         Ok(store)
@@ -416,12 +1018,14 @@ where
     Store::Error: Send + Sync,
 {
     type Context = ViewContext<ChainRuntimeContext<Self>, Store>;
+    type ServiceContext = ViewContext<(), Store>;
     type Clock = C;
 
     fn clock(&self) -> &C {
         &self.clock
     }
 
+    #[instrument(level = "trace", skip_all, fields(%chain_id))]
     async fn load_chain(
         &self,
         chain_id: ChainId,
@@ -435,12 +1039,17 @@ where
             user_contracts: self.user_contracts.clone(),
             user_services: self.user_services.clone(),
         };
-        let root_key = bcs::to_bytes(&BaseKey::ChainState(chain_id))?;
-        let store = self.store.clone_with_root_key(&root_key)?;
+        let store = chain_namespace(&*self.store, chain_id)?;
         let context = ViewContext::create_root_context(store, runtime_context).await?;
         ChainStateView::load(context).await
     }
 
+    async fn service_context(&self, name: &str) -> Result<Self::ServiceContext, ViewError> {
+        let root_key = bcs::to_bytes(&BaseKey::ServiceState(name.to_owned()))?;
+        let store = self.store.clone_with_root_key(&root_key)?;
+        Ok(ViewContext::create_root_context(store, ()).await?)
+    }
+
     async fn contains_blob(&self, blob_id: BlobId) -> Result<bool, ViewError> {
         let blob_key = bcs::to_bytes(&BaseKey::Blob(blob_id))?;
         let test = self.store.contains_key(&blob_key).await?;
@@ -475,6 +1084,7 @@ where
         Ok(test)
     }
 
+    #[instrument(level = "trace", skip_all, fields(%hash))]
     async fn read_hashed_confirmed_block(
         &self,
         hash: CryptoHash,
@@ -489,6 +1099,7 @@ where
         Ok(value.with_hash_unchecked(hash))
     }
 
+    #[instrument(level = "trace", skip_all, fields(%blob_id))]
     async fn read_blob(&self, blob_id: BlobId) -> Result<Blob, ViewError> {
         let blob_key = bcs::to_bytes(&BaseKey::Blob(blob_id))?;
         let maybe_blob_bytes = self.store.read_value::<Vec<u8>>(&blob_key).await?;
@@ -498,6 +1109,7 @@ where
         Ok(Blob::new_with_id_unchecked(blob_id, blob_bytes))
     }
 
+    #[instrument(level = "trace", skip_all, fields(count = blob_ids.len()))]
     async fn read_blobs(&self, blob_ids: &[BlobId]) -> Result<Vec<Option<Blob>>, ViewError> {
         if blob_ids.is_empty() {
             return Ok(Vec::new());
@@ -573,6 +1185,7 @@ where
         Ok(values)
     }
 
+    #[instrument(level = "trace", skip_all, fields(blob_id = %blob.id()))]
     async fn write_blob(&self, blob: &Blob) -> Result<(), ViewError> {
         let mut batch = Batch::new();
         batch.add_blob(blob)?;
@@ -672,6 +1285,7 @@ where
         Ok(blob_states)
     }
 
+    #[instrument(level = "trace", skip_all, fields(count = blobs.len()))]
     async fn write_blobs(&self, blobs: &[Blob]) -> Result<(), ViewError> {
         if blobs.is_empty() {
             return Ok(());
@@ -683,19 +1297,183 @@ where
         self.write_batch(batch).await
     }
 
+    #[instrument(level = "trace", skip_all, fields(hash = %certificate.hash()))]
     async fn write_blobs_and_certificate(
         &self,
         blobs: &[Blob],
         certificate: &ConfirmedBlockCertificate,
     ) -> Result<(), ViewError> {
+        let header = &certificate.block().header;
+        let certificate_hash = certificate.hash();
+        let blob_state = BlobState {
+            last_used_by: certificate_hash,
+            chain_id: header.chain_id,
+            block_height: header.height,
+            epoch: header.epoch,
+        };
+        let blob_state_keys = blobs
+            .iter()
+            .map(|blob| bcs::to_bytes(&BaseKey::BlobState(blob.id())))
+            .collect::<Result<_, _>>()?;
+        let maybe_blob_states = self
+            .store
+            .read_multi_values::<BlobState>(blob_state_keys)
+            .await?;
         let mut batch = Batch::new();
-        for blob in blobs {
+        let mut previous_blob_states = Vec::new();
+        for (blob, maybe_blob_state) in blobs.iter().zip(maybe_blob_states) {
             batch.add_blob(blob)?;
+            // As in `maybe_write_blob_states`, only a more recent epoch replaces a blob state.
+            if maybe_blob_state
+                .as_ref()
+                .map_or(true, |current| current.epoch < blob_state.epoch)
+            {
+                batch.add_blob_state(blob.id(), &blob_state)?;
+                previous_blob_states.push((blob.id(), maybe_blob_state));
+            }
         }
         batch.add_certificate(certificate)?;
+        let entry = WriteAheadLogEntry {
+            certificate_hash,
+            previous_blob_states,
+        };
+        batch.add_write_ahead_log_entry(header.chain_id, &entry)?;
         self.write_batch(batch).await
     }
 
+    async fn clear_write_ahead_log(&self, chain_id: ChainId) -> Result<(), ViewError> {
+        let mut batch = Batch::new();
+        batch.delete_write_ahead_log_entry(chain_id)?;
+        self.write_batch(batch).await
+    }
+
+    async fn replay_write_ahead_log<F>(&self, filter: F) -> Result<(), ViewError>
+    where
+        F: Fn(ChainId) -> bool + Send + Sync,
+    {
+        let prefix = &[INDEX_WRITE_AHEAD_LOG];
+        let key_values = self.store.find_key_values_by_prefix(prefix).await?;
+        let mut entries = Vec::new();
+        for entry in key_values.into_iterator_owned() {
+            let (key, value) = entry?;
+            let chain_id = bcs::from_bytes::<ChainId>(&key)?;
+            if filter(chain_id) {
+                entries.push((chain_id, bcs::from_bytes::<WriteAheadLogEntry>(&value)?));
+            }
+        }
+        for (chain_id, entry) in entries {
+            let hash = entry.certificate_hash;
+            let height = self
+                .read_hashed_confirmed_block(hash)
+                .await?
+                .inner()
+                .height();
+            let chain = self.load_chain(chain_id).await?;
+            let mut batch = Batch::new();
+            if chain.confirmed_log.get(height.try_into()?).await? != Some(hash) {
+                // The chain state was not saved: forget the certificate so that it is
+                // processed again.
+                #[cfg(with_metrics)]
+                REVERTED_CERTIFICATE_COUNTER.with_label_values(&[]).inc();
+                batch.delete_certificate(hash)?;
+                // Restore the blob states that still refer to the removed certificate.
+                let blob_state_keys = entry
+                    .previous_blob_states
+                    .iter()
+                    .map(|(blob_id, _)| bcs::to_bytes(&BaseKey::BlobState(*blob_id)))
+                    .collect::<Result<_, _>>()?;
+                let maybe_blob_states = self
+                    .store
+                    .read_multi_values::<BlobState>(blob_state_keys)
+                    .await?;
+                for ((blob_id, previous_blob_state), maybe_blob_state) in entry
+                    .previous_blob_states
+                    .into_iter()
+                    .zip(maybe_blob_states)
+                {
+                    if maybe_blob_state.map(|blob_state| blob_state.last_used_by) != Some(hash) {
+                        continue;
+                    }
+                    match previous_blob_state {
+                        Some(blob_state) => batch.add_blob_state(blob_id, &blob_state)?,
+                        None => batch.delete_blob_state(blob_id)?,
+                    }
+                }
+            }
+            batch.delete_write_ahead_log_entry(chain_id)?;
+            self.write_batch(batch).await?;
+        }
+        Ok(())
+    }
+
+    fn with_pending_deliveries_partition(mut self, partition: u32) -> Self {
+        self.pending_deliveries_partition = partition;
+        self
+    }
+
+    async fn read_pending_deliveries<F>(&self, filter: F) -> Result<Vec<ChainId>, ViewError>
+    where
+        F: Fn(ChainId) -> bool + Send + Sync,
+    {
+        let prefix = &[INDEX_PENDING_DELIVERIES];
+        let keys = self.store.find_keys_by_prefix(prefix).await?;
+        let mut chain_ids = BTreeSet::new();
+        let mut batch = Batch::new();
+        for key in keys.iterator() {
+            let (partition, chain_id) = bcs::from_bytes::<(u32, ChainId)>(key?)?;
+            if !filter(chain_id) {
+                continue;
+            }
+            if partition != self.pending_deliveries_partition {
+                let key = BaseKey::PendingDeliveries(partition, chain_id);
+                batch.delete_key(bcs::to_bytes(&key)?);
+                let key = BaseKey::PendingDeliveries(self.pending_deliveries_partition, chain_id);
+                batch.put_key_value_bytes(bcs::to_bytes(&key)?, Vec::new());
+            }
+            chain_ids.insert(chain_id);
+        }
+        if !batch.is_empty() {
+            self.write_batch(batch).await?;
+        }
+        Ok(chain_ids.into_iter().collect())
+    }
+
+    async fn read_partition_pending_deliveries<F>(
+        &self,
+        filter: F,
+    ) -> Result<Vec<ChainId>, ViewError>
+    where
+        F: Fn(ChainId) -> bool + Send + Sync,
+    {
+        let mut prefix = vec![INDEX_PENDING_DELIVERIES];
+        prefix.extend(bcs::to_bytes(&self.pending_deliveries_partition)?);
+        let keys = self.store.find_keys_by_prefix(&prefix).await?;
+        let mut chain_ids = Vec::new();
+        for key in keys.iterator() {
+            let chain_id = bcs::from_bytes(key?)?;
+            if filter(chain_id) {
+                chain_ids.push(chain_id);
+            }
+        }
+        Ok(chain_ids)
+    }
+
+    async fn save_chain(&self, chain: &mut ChainStateView<Self::Context>) -> Result<(), ViewError> {
+        let chain_id = chain.chain_id();
+        let has_pending_deliveries = !chain.outbox_counters.get().is_empty();
+        // The markers are written before a state with pending deliveries and removed after a
+        // state without any, so that they are never missing if the process stops in between.
+        self.register_chain_namespace(chain_id).await?;
+        if has_pending_deliveries {
+            self.set_pending_delivery_marker(chain_id, true).await?;
+        }
+        chain.save().await?;
+        if !has_pending_deliveries {
+            self.set_pending_delivery_marker(chain_id, false).await?;
+        }
+        Ok(())
+    }
+
     async fn contains_certificate(&self, hash: CryptoHash) -> Result<bool, ViewError> {
         let keys = Self::get_keys_for_certificates(&[hash])?;
         let results = self.store.contains_keys(keys).await?;
@@ -704,6 +1482,7 @@ where
         Ok(results[0] && results[1])
     }
 
+    #[instrument(level = "trace", skip_all, fields(%hash))]
     async fn read_certificate(
         &self,
         hash: CryptoHash,
@@ -718,6 +1497,7 @@ where
         Self::deserialize_certificate(&values, hash)
     }
 
+    #[instrument(level = "trace", skip_all)]
     async fn read_certificates<I: IntoIterator<Item = CryptoHash> + Send>(
         &self,
         hashes: I,
@@ -744,6 +1524,10 @@ where
     fn wasm_runtime(&self) -> Option<WasmRuntime> {
         self.wasm_runtime
     }
+
+    fn native_applications(&self) -> &NativeApplicationRegistry {
+        &self.native_applications
+    }
 }
 
 impl<Store, C> DbStorage<Store, C>
@@ -786,6 +1570,48 @@ where
         Ok(())
     }
 
+    /// Records that the given chain has a namespace, so that it can be listed later.
+    async fn register_chain_namespace(&self, chain_id: ChainId) -> Result<(), ViewError> {
+        if self.registered_chains.contains(&chain_id) {
+            return Ok(());
+        }
+        let key = bcs::to_bytes(&BaseKey::ChainNamespace(chain_id))?;
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(key, Vec::new());
+        self.write_batch(batch).await?;
+        self.registered_chains.insert(chain_id);
+        Ok(())
+    }
+
+    /// Adds the chain to, or removes it from, the index of chains with pending deliveries.
+    async fn set_pending_delivery_marker(
+        &self,
+        chain_id: ChainId,
+        has_pending_deliveries: bool,
+    ) -> Result<(), ViewError> {
+        if self
+            .pending_delivery_markers
+            .get(&chain_id)
+            .is_some_and(|marker| *marker == has_pending_deliveries)
+        {
+            return Ok(());
+        }
+        let key = bcs::to_bytes(&BaseKey::PendingDeliveries(
+            self.pending_deliveries_partition,
+            chain_id,
+        ))?;
+        let mut batch = Batch::new();
+        if has_pending_deliveries {
+            batch.put_key_value_bytes(key, Vec::new());
+        } else {
+            batch.delete_key(key);
+        }
+        self.write_batch(batch).await?;
+        self.pending_delivery_markers
+            .insert(chain_id, has_pending_deliveries);
+        Ok(())
+    }
+
     fn create(store: Store, wasm_runtime: Option<WasmRuntime>, clock: C) -> Self {
         Self {
             store: Arc::new(store),
@@ -794,8 +1620,107 @@ where
             user_contracts: Arc::new(DashMap::new()),
             user_services: Arc::new(DashMap::new()),
             execution_runtime_config: ExecutionRuntimeConfig::default(),
+            native_applications: NativeApplicationRegistry::default(),
+            registered_chains: Arc::new(DashSet::new()),
+            pending_delivery_markers: Arc::new(DashMap::new()),
+            pending_deliveries_partition: 0,
         }
     }
+
+    /// Configures how applications are executed on the chains loaded from this storage.
+    pub fn with_execution_runtime_config(
+        mut self,
+        execution_runtime_config: ExecutionRuntimeConfig,
+    ) -> Self {
+        self.execution_runtime_config = execution_runtime_config;
+        self
+    }
+
+    /// Configures the native implementations to use instead of some application bytecodes.
+    ///
+    /// Native implementations must behave exactly like the bytecodes they replace, including
+    /// the fuel they consume, otherwise this validator won't agree with the others on the
+    /// outcome of blocks.
+    pub fn with_native_applications(
+        mut self,
+        native_applications: NativeApplicationRegistry,
+    ) -> Self {
+        self.native_applications = native_applications;
+        self
+    }
+}
+
+impl<Store, C> DbStorage<Store, C>
+where
+    Store: KeyValueStore + Clone + Send + Sync + 'static,
+    C: Clock + Clone + Send + Sync + 'static,
+    Store::Error: Send + Sync,
+{
+    /// Registers the chains of a storage written before the chains were indexed when saved, so
+    /// that they are listed by [`list_chain_ids`] and retransmit their pending deliveries.
+    /// Returns the chains that were registered.
+    ///
+    /// The chains are looked for among the root chains, the chains of the confirmed blocks and
+    /// the recipients of their messages. This must not be used while a validator or a client
+    /// may be using the storage.
+    pub async fn backfill_chain_indices(&self) -> Result<Vec<ChainId>, ViewError> {
+        // The chains whose deletion was interrupted must not be registered again.
+        complete_chain_deletions(&*self.store).await?;
+        let mut chain_ids = BTreeSet::new();
+        for index in 0.. {
+            let chain_id = ChainId::root(index);
+            if !self.has_chain_state(chain_id).await? {
+                break;
+            }
+            chain_ids.insert(chain_id);
+        }
+        let keys = self
+            .store
+            .find_keys_by_prefix(&[INDEX_CONFIRMED_BLOCK])
+            .await?;
+        let mut hashes = Vec::new();
+        for key in keys.iterator() {
+            hashes.push(bcs::from_bytes::<CryptoHash>(key?)?);
+        }
+        for hashes in hashes.chunks(BACKFILL_BLOCKS_PER_READ) {
+            let keys = hashes
+                .iter()
+                .map(|hash| bcs::to_bytes(&BaseKey::ConfirmedBlock(*hash)))
+                .collect::<Result<_, _>>()?;
+            for value in self.store.read_multi_values_bytes(keys).await? {
+                let Some(bytes) = value else {
+                    continue;
+                };
+                let block = bcs::from_bytes::<ConfirmedBlock>(&bytes)?.into_block();
+                chain_ids.insert(block.header.chain_id);
+                for message in block.messages().iter().flatten() {
+                    if let Destination::Recipient(recipient) = message.destination {
+                        chain_ids.insert(recipient);
+                    }
+                }
+            }
+        }
+        let mut registered = Vec::new();
+        for chain_id in chain_ids {
+            if !self.has_chain_state(chain_id).await? {
+                continue;
+            }
+            self.register_chain_namespace(chain_id).await?;
+            let chain = self.load_chain(chain_id).await?;
+            if !chain.outbox_counters.get().is_empty() {
+                self.set_pending_delivery_marker(chain_id, true).await?;
+            }
+            registered.push(chain_id);
+        }
+        Ok(registered)
+    }
+
+    /// Returns whether anything was ever saved in the namespace of the given chain.
+    async fn has_chain_state(&self, chain_id: ChainId) -> Result<bool, ViewError> {
+        let store = chain_namespace(&*self.store, chain_id)?;
+        let keys = store.find_keys_by_prefix_after(&[], None, Some(1)).await?;
+        Ok(!keys.is_empty())
+    }
 }
 
 impl<Store> DbStorage<Store, WallClock>
@@ -29,21 +29,28 @@ use linera_execution::{
     committee::{Committee, Epoch},
     system::SystemChannel,
     BlobState, ChannelSubscription, ExecutionError, ExecutionRuntimeConfig,
-    ExecutionRuntimeContext, UserContractCode, UserServiceCode, WasmRuntime,
+    ExecutionRuntimeContext, NativeApplicationRegistry, UserContractCode, UserServiceCode,
+    WasmRuntime,
 };
 use linera_views::{
     context::Context,
-    views::{CryptoHashView, RootView, ViewError},
+    views::{CryptoHashView, ViewError},
 };
 #[cfg(with_wasm_runtime)]
 use {
-    linera_base::{data_types::CompressedBytecode, identifiers::BlobType},
+    linera_base::{
+        data_types::{BytecodeChunks, CompressedBytecode},
+        identifiers::BlobType,
+    },
     linera_execution::{WasmContractModule, WasmServiceModule},
 };
 
 #[cfg(with_testing)]
 pub use crate::db_storage::TestClock;
-pub use crate::db_storage::{list_all_blob_ids, ChainStatesFirstAssignment, DbStorage, WallClock};
+pub use crate::db_storage::{
+    complete_chain_deletions, delete_chain_namespace, list_all_blob_ids, list_chain_ids,
+    read_chain_state_entries, ChainStatesFirstAssignment, DbStorage, WallClock,
+};
 #[cfg(with_metrics)]
 pub use crate::db_storage::{
     READ_CERTIFICATE_COUNTER, READ_HASHED_CONFIRMED_BLOCK_COUNTER, WRITE_CERTIFICATE_COUNTER,
@@ -56,6 +63,9 @@ pub trait Storage: Sized {
     /// The low-level storage implementation in use.
     type Context: Context<Extra = ChainRuntimeContext<Self>> + Clone + Send + Sync + 'static;
 
+    /// The context of the views holding the state of services run on top of the storage.
+    type ServiceContext: Context<Extra = ()> + Clone + Send + Sync + 'static;
+
     /// The clock type being used.
     type Clock: Clock;
 
@@ -75,6 +85,17 @@ pub trait Storage: Sized {
     /// [`create_chain`][`Self::create_chain`].
     async fn load_chain(&self, id: ChainId) -> Result<ChainStateView<Self::Context>, ViewError>;
 
+    /// Saves the state of a chain loaded with [`load_chain`][`Self::load_chain`], and updates
+    /// the indices of the chains in the storage accordingly.
+    async fn save_chain(&self, chain: &mut ChainStateView<Self::Context>) -> Result<(), ViewError>;
+
+    /// Returns the context of a root view holding the state of the service with the given
+    /// name, e.g. a faucet, stored apart from the chains.
+    ///
+    /// As with [`load_chain`][`Self::load_chain`], a single view should be loaded from this
+    /// context at any given moment.
+    async fn service_context(&self, name: &str) -> Result<Self::ServiceContext, ViewError>;
+
     /// Tests the existence of a blob with the given blob ID.
     async fn contains_blob(&self, blob_id: BlobId) -> Result<bool, ViewError>;
 
@@ -112,13 +133,60 @@ pub trait Storage: Sized {
     /// Writes the given blob.
     async fn write_blob(&self, blob: &Blob) -> Result<(), ViewError>;
 
-    /// Writes blobs and certificate
+    /// Writes blobs and certificate, and records the certificate as the last user of the
+    /// blobs in their states. This is done together with a write-ahead log entry for the
+    /// certificate's chain, which must be removed with
+    /// [`clear_write_ahead_log`][`Self::clear_write_ahead_log`] once the chain state has been
+    /// saved.
     async fn write_blobs_and_certificate(
         &self,
         blobs: &[Blob],
         certificate: &ConfirmedBlockCertificate,
     ) -> Result<(), ViewError>;
 
+    /// Removes the write-ahead log entry of the given chain, after its state changes have been
+    /// saved.
+    async fn clear_write_ahead_log(&self, chain_id: ChainId) -> Result<(), ViewError>;
+
+    /// Recovers from a crash between writing a certificate and saving the state of its chain,
+    /// for the chains selected by `filter`.
+    ///
+    /// For each remaining write-ahead log entry, the certificate is kept if the chain state
+    /// includes it. Otherwise, it is removed and the blob states that refer to it are restored,
+    /// so that the block is executed again the next time the certificate is received. This must
+    /// be called before any worker loads these chains.
+    async fn replay_write_ahead_log<F>(&self, filter: F) -> Result<(), ViewError>
+    where
+        F: Fn(ChainId) -> bool + Send + Sync;
+
+    /// Returns this storage, listing the chains it saves with pending deliveries in the given
+    /// partition of their index, e.g. the number of the shard of a validator handling them.
+    fn with_pending_deliveries_partition(self, partition: u32) -> Self;
+
+    /// Returns the chains selected by `filter` that have outgoing cross-chain messages that were
+    /// not acknowledged yet, so that they can be retransmitted, e.g. after a restart.
+    ///
+    /// This is read from an index that [`save_chain`][`Self::save_chain`] keeps up to date. If a
+    /// process stopped while saving a chain, the index may still list that chain although all
+    /// its messages were acknowledged, but it never misses a chain.
+    ///
+    /// This scans all the partitions of the index, and moves the selected chains to the
+    /// partition of this storage, e.g. after the chains were assigned to other shards. They are
+    /// then found by [`read_partition_pending_deliveries`][`Self::read_partition_pending_deliveries`].
+    async fn read_pending_deliveries<F>(&self, filter: F) -> Result<Vec<ChainId>, ViewError>
+    where
+        F: Fn(ChainId) -> bool + Send + Sync;
+
+    /// Returns the chains selected by `filter` that have pending deliveries, like
+    /// [`read_pending_deliveries`][`Self::read_pending_deliveries`], but only reading the
+    /// partition of the index of this storage.
+    async fn read_partition_pending_deliveries<F>(
+        &self,
+        filter: F,
+    ) -> Result<Vec<ChainId>, ViewError>
+    where
+        F: Fn(ChainId) -> bool + Send + Sync;
+
     /// Writes the given blob state.
     async fn write_blob_state(
         &self,
@@ -243,18 +311,52 @@ pub trait Storage: Sized {
                 let mut channel = admin_chain.channels.try_load_entry_mut(&full_name).await?;
                 channel.subscribers.insert(&id)?;
             } // Make channel go out of scope, so we can call save.
-            admin_chain.save().await?;
+            self.save_chain(&mut admin_chain).await?;
         }
 
         let state_hash = chain.execution_state.crypto_hash().await?;
         chain.execution_state_hash.set(Some(state_hash));
-        chain.save().await?;
+        self.save_chain(&mut chain).await?;
         Ok(())
     }
 
     /// Selects the WebAssembly runtime to use for applications (if any).
     fn wasm_runtime(&self) -> Option<WasmRuntime>;
 
+    /// Returns the native implementations to use instead of some application bytecodes.
+    fn native_applications(&self) -> &NativeApplicationRegistry;
+
+    /// Reads the compressed bytecode of a bytecode blob, reassembling it from its chunks if
+    /// it was too large to be published in a single blob.
+    #[cfg(with_wasm_runtime)]
+    async fn read_compressed_bytecode(
+        &self,
+        blob_id: BlobId,
+    ) -> Result<CompressedBytecode, ExecutionError> {
+        let blob = self.read_blob(blob_id).await?;
+        let Some(bytecode_chunks) = BytecodeChunks::from_blob_bytes(blob.bytes()) else {
+            return Ok(CompressedBytecode {
+                compressed_bytes: blob.into_bytes().to_vec(),
+            });
+        };
+        let chunk_blob_ids = bytecode_chunks.chunk_blob_ids().collect::<Vec<_>>();
+        let mut chunks = Vec::new();
+        let mut missing_blobs = Vec::new();
+        for (chunk_blob_id, chunk) in chunk_blob_ids
+            .iter()
+            .zip(self.read_blobs(&chunk_blob_ids).await?)
+        {
+            match chunk {
+                Some(chunk) => chunks.push(chunk.into_content()),
+                None => missing_blobs.push(*chunk_blob_id),
+            }
+        }
+        if !missing_blobs.is_empty() {
+            return Err(ExecutionError::BlobsNotFound(missing_blobs));
+        }
+        Ok(bytecode_chunks.reassemble(blob_id.blob_type, chunks)?)
+    }
+
     /// Creates a [`UserContractCode`] instance using the bytecode in storage referenced
     /// by the `application_description`.
     #[cfg(with_wasm_runtime)]
@@ -262,17 +364,18 @@ pub trait Storage: Sized {
         &self,
         application_description: &UserApplicationDescription,
     ) -> Result<UserContractCode, ExecutionError> {
+        let bytecode_id = &application_description.bytecode_id;
+        if let Some(contract) = self.native_applications().contract(bytecode_id) {
+            return Ok(contract);
+        }
         let Some(wasm_runtime) = self.wasm_runtime() else {
             panic!("A Wasm runtime is required to load user applications.");
         };
-        let contract_bytecode_blob_id = BlobId::new(
-            application_description.bytecode_id.contract_blob_hash,
-            BlobType::ContractBytecode,
-        );
-        let contract_blob = self.read_blob(contract_bytecode_blob_id).await?;
-        let compressed_contract_bytecode = CompressedBytecode {
-            compressed_bytes: contract_blob.into_bytes().to_vec(),
-        };
+        let contract_bytecode_blob_id =
+            BlobId::new(bytecode_id.contract_blob_hash, BlobType::ContractBytecode);
+        let compressed_contract_bytecode = self
+            .read_compressed_bytecode(contract_bytecode_blob_id)
+            .await?;
         let contract_bytecode =
             linera_base::task::Blocking::<linera_base::task::NoInput, _>::spawn(
                 move |_| async move { compressed_contract_bytecode.decompress() },
@@ -289,8 +392,14 @@ pub trait Storage: Sized {
     #[allow(clippy::diverging_sub_expression)]
     async fn load_contract(
         &self,
-        _application_description: &UserApplicationDescription,
+        application_description: &UserApplicationDescription,
     ) -> Result<UserContractCode, ExecutionError> {
+        if let Some(contract) = self
+            .native_applications()
+            .contract(&application_description.bytecode_id)
+        {
+            return Ok(contract);
+        }
         panic!(
             "A Wasm runtime is required to load user applications. \
             Please enable the `wasmer` or the `wasmtime` feature flags \
@@ -305,17 +414,18 @@ pub trait Storage: Sized {
         &self,
         application_description: &UserApplicationDescription,
     ) -> Result<UserServiceCode, ExecutionError> {
+        let bytecode_id = &application_description.bytecode_id;
+        if let Some(service) = self.native_applications().service(bytecode_id) {
+            return Ok(service);
+        }
         let Some(wasm_runtime) = self.wasm_runtime() else {
             panic!("A Wasm runtime is required to load user applications.");
         };
-        let service_bytecode_blob_id = BlobId::new(
-            application_description.bytecode_id.service_blob_hash,
-            BlobType::ServiceBytecode,
-        );
-        let service_blob = self.read_blob(service_bytecode_blob_id).await?;
-        let compressed_service_bytecode = CompressedBytecode {
-            compressed_bytes: service_blob.into_bytes().to_vec(),
-        };
+        let service_bytecode_blob_id =
+            BlobId::new(bytecode_id.service_blob_hash, BlobType::ServiceBytecode);
+        let compressed_service_bytecode = self
+            .read_compressed_bytecode(service_bytecode_blob_id)
+            .await?;
         let service_bytecode = linera_base::task::Blocking::<linera_base::task::NoInput, _>::spawn(
             move |_| async move { compressed_service_bytecode.decompress() },
         )
@@ -331,8 +441,14 @@ pub trait Storage: Sized {
     #[allow(clippy::diverging_sub_expression)]
     async fn load_service(
         &self,
-        _application_description: &UserApplicationDescription,
+        application_description: &UserApplicationDescription,
     ) -> Result<UserServiceCode, ExecutionError> {
+        if let Some(service) = self
+            .native_applications()
+            .service(&application_description.bytecode_id)
+        {
+            return Ok(service);
+        }
         panic!(
             "A Wasm runtime is required to load user applications. \
             Please enable the `wasmer` or the `wasmtime` feature flags \
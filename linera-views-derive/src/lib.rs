@@ -405,6 +405,172 @@ fn generate_clonable_view_code(input: ItemStruct) -> TokenStream2 {
     }
 }
 
+/// The kind of a field of a view exposed by `GraphQLView`, with the types needed to query
+/// it.
+enum GraphQLField {
+    /// A register, queried for its value.
+    Register(Type),
+    /// A log, queried for a page of its entries.
+    Log(Type),
+    /// A map, queried for a page of its entries in key order, starting after a given key.
+    Map(Type, Type),
+    /// Any other view, exposed through its own GraphQL object.
+    Other,
+}
+
+impl GraphQLField {
+    fn from_type(ty: &Type) -> Self {
+        let Type::Path(type_path) = ty else {
+            return GraphQLField::Other;
+        };
+        let Some(segment) = type_path.path.segments.last() else {
+            return GraphQLField::Other;
+        };
+        let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+            return GraphQLField::Other;
+        };
+        // The `linera-sdk` aliases omit the context, so the relevant types are the last ones.
+        let mut types = arguments
+            .args
+            .iter()
+            .rev()
+            .filter_map(|argument| match argument {
+                syn::GenericArgument::Type(ty) => Some(ty.clone()),
+                _ => None,
+            });
+        match segment.ident.to_string().as_str() {
+            "RegisterView" | "HashedRegisterView" => match types.next() {
+                Some(value) => GraphQLField::Register(value),
+                None => GraphQLField::Other,
+            },
+            "LogView" | "HashedLogView" => match types.next() {
+                Some(value) => GraphQLField::Log(value),
+                None => GraphQLField::Other,
+            },
+            "MapView" | "HashedMapView" | "CustomMapView" | "HashedCustomMapView" => {
+                match (types.next(), types.next()) {
+                    (Some(value), Some(index)) => GraphQLField::Map(index, value),
+                    _ => GraphQLField::Other,
+                }
+            }
+            _ => GraphQLField::Other,
+        }
+    }
+}
+
+/// Returns whether a field is marked with `#[graphql(skip)]`.
+fn is_graphql_skipped(field: &syn::Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("graphql"))
+        .any(|attribute| match attribute.parse_args::<syn::Ident>() {
+            Ok(ident) => ident == "skip",
+            Err(_) => panic!(
+                "Invalid `graphql` attribute syntax. \
+                Expected syntax: `#[graphql(skip)]`",
+            ),
+        })
+}
+
+/// Converts a field name to the camel case used by default for GraphQL fields.
+fn graphql_name(name: &str) -> String {
+    let mut parts = name.split('_').filter(|part| !part.is_empty());
+    let mut result = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            result.extend(first.to_uppercase());
+            result.push_str(chars.as_str());
+        }
+    }
+    result
+}
+
+fn generate_graphql_view_code(input: ItemStruct) -> TokenStream2 {
+    let struct_name = input.ident;
+    let (impl_generics, type_generics, maybe_where_clause) = input.generics.split_for_impl();
+    let template_vect = get_seq_parameter(input.generics.clone());
+
+    let (context, context_constraints) = context_and_constraints(&input.attrs, &template_vect);
+
+    let mut where_clause = maybe_where_clause
+        .cloned()
+        .unwrap_or_else(empty_where_clause);
+    where_clause
+        .predicates
+        .extend(context_constraints.predicates);
+    where_clause.predicates.push(parse_quote! {
+        linera_views::views::ViewError:
+            From<<#context as linera_views::context::Context>::Error>
+    });
+
+    let field_quotes = input
+        .fields
+        .iter()
+        .filter(|field| !is_graphql_skipped(field))
+        .map(|field| {
+            let name = field.ident.clone().expect("GraphQLView requires named fields");
+            let graphql_name = graphql_name(&name.to_string());
+            let method = format_ident!("graphql_{}", name);
+            match GraphQLField::from_type(&field.ty) {
+                GraphQLField::Register(value) => quote! {
+                    #[graphql(name = #graphql_name)]
+                    async fn #method(&self) -> &#value {
+                        self.#name.get()
+                    }
+                },
+                GraphQLField::Log(value) => quote! {
+                    #[graphql(name = #graphql_name)]
+                    async fn #method(
+                        &self,
+                        offset: Option<usize>,
+                        count: Option<usize>,
+                    ) -> Result<Vec<#value>, async_graphql::Error> {
+                        let range = linera_views::graphql::page_range(self.#name.count(), offset, count);
+                        Ok(self.#name.read(range).await?)
+                    }
+                },
+                GraphQLField::Map(index, value) => quote! {
+                    #[graphql(name = #graphql_name)]
+                    async fn #method(
+                        &self,
+                        after: Option<#index>,
+                        count: Option<usize>,
+                    ) -> Result<Vec<linera_views::graphql::Entry<#index, Option<#value>>>, async_graphql::Error> {
+                        let indices = self.#name.indices_after(after.as_ref(), count).await?;
+                        let mut entries = Vec::with_capacity(indices.len());
+                        for index in indices {
+                            entries.push(linera_views::graphql::Entry {
+                                value: self.#name.get(&index).await?,
+                                key: index,
+                            });
+                        }
+                        Ok(entries)
+                    }
+                },
+                GraphQLField::Other => {
+                    let ty = &field.ty;
+                    quote! {
+                        #[graphql(name = #graphql_name)]
+                        async fn #method(&self) -> &#ty {
+                            &self.#name
+                        }
+                    }
+                }
+            }
+        });
+
+    quote! {
+        #[async_graphql::Object(cache_control(no_cache))]
+        impl #impl_generics #struct_name #type_generics
+        #where_clause
+        {
+            #(#field_quotes)*
+        }
+    }
+}
+
 #[proc_macro_derive(View, attributes(view))]
 pub fn derive_view(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as ItemStruct);
@@ -462,6 +628,12 @@ pub fn derive_clonable_view(input: TokenStream) -> TokenStream {
     generate_clonable_view_code(input).into()
 }
 
+#[proc_macro_derive(GraphQLView, attributes(view, graphql))]
+pub fn derive_graphql_view(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as ItemStruct);
+    generate_graphql_view_code(input).into()
+}
+
 #[cfg(test)]
 pub mod tests {
 
@@ -541,6 +713,13 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_graphql_name() {
+        assert_eq!(graphql_name("value"), "value");
+        assert_eq!(graphql_name("total_supply"), "totalSupply");
+        assert_eq!(graphql_name("a_b_c"), "aBC");
+    }
+
     #[derive(Clone)]
     pub struct SpecificContextInfo {
         name: String,
@@ -0,0 +1,117 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    fmt::Debug,
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+#[cfg(with_dynamodb)]
+use linera_views::dynamo_db::DynamoDbStore;
+#[cfg(with_rocksdb)]
+use linera_views::rocks_db::RocksDbStore;
+#[cfg(with_scylladb)]
+use linera_views::scylla_db::ScyllaDbStore;
+use linera_views::{
+    context::ViewContext,
+    map_view::MapView,
+    memory::MemoryStore,
+    random::{make_deterministic_rng, DeterministicRng},
+    store::{KeyValueStore, TestKeyValueStore as _},
+    views::{CryptoHashRootView, RootView, View},
+};
+use rand::Rng;
+use tokio::runtime::Runtime;
+
+/// The number of entries inserted in each iteration.
+const N_ENTRIES: usize = 1000;
+
+fn generate_entries(n_entries: usize, rng: &mut DeterministicRng) -> Vec<(u64, u64)> {
+    (0..n_entries).map(|_| (rng.gen(), rng.gen())).collect()
+}
+
+#[derive(CryptoHashRootView)]
+pub struct MapStateView<C> {
+    pub map: MapView<C, u64, u64>,
+}
+
+/// Inserts the entries one at a time, or all at once with `extend`, and saves the view.
+pub async fn performance_map_view<S: KeyValueStore + Clone + Sync + 'static>(
+    store: S,
+    iterations: u64,
+    use_extend: bool,
+) -> Duration
+where
+    S::Error: Debug + Send + Sync + 'static,
+{
+    let context = ViewContext::<(), S>::create_root_context(store, ())
+        .await
+        .unwrap();
+    let mut total_time = Duration::ZERO;
+    let mut rng = make_deterministic_rng();
+    for _ in 0..iterations {
+        let entries = generate_entries(N_ENTRIES, &mut rng);
+        let mut view = MapStateView::load(context.clone()).await.unwrap();
+        let measurement = Instant::now();
+        if use_extend {
+            view.map.extend(entries).unwrap();
+        } else {
+            for (index, value) in entries {
+                view.map.insert(&index, value).unwrap();
+            }
+        }
+        view.save().await.unwrap();
+        total_time += measurement.elapsed();
+        view.clear();
+        view.save().await.unwrap();
+    }
+
+    total_time
+}
+
+fn bench_map_view(criterion: &mut Criterion) {
+    for (name, use_extend) in [("insert", false), ("extend", true)] {
+        criterion.bench_function(&format!("memory_map_view_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| async move {
+                    let store = MemoryStore::new_test_store().await.unwrap();
+                    performance_map_view(store, iterations, use_extend).await
+                })
+        });
+
+        #[cfg(with_rocksdb)]
+        criterion.bench_function(&format!("rocksdb_map_view_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| async move {
+                    let store = RocksDbStore::new_test_store().await.unwrap();
+                    performance_map_view(store, iterations, use_extend).await
+                })
+        });
+
+        #[cfg(with_dynamodb)]
+        criterion.bench_function(&format!("dynamodb_map_view_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| async move {
+                    let store = DynamoDbStore::new_test_store().await.unwrap();
+                    performance_map_view(store, iterations, use_extend).await
+                })
+        });
+
+        #[cfg(with_scylladb)]
+        criterion.bench_function(&format!("scylladb_map_view_{name}"), |bencher| {
+            bencher
+                .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                .iter_custom(|iterations| async move {
+                    let store = ScyllaDbStore::new_test_store().await.unwrap();
+                    performance_map_view(store, iterations, use_extend).await
+                })
+        });
+    }
+}
+
+criterion_group!(benches, bench_map_view);
+criterion_main!(benches);
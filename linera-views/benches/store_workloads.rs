@@ -0,0 +1,96 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::Debug;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+#[cfg(with_dynamodb)]
+use linera_views::dynamo_db::DynamoDbStore;
+#[cfg(with_rocksdb)]
+use linera_views::rocks_db::RocksDbStore;
+#[cfg(with_scylladb)]
+use linera_views::scylla_db::ScyllaDbStore;
+use linera_views::{
+    memory::MemoryStore,
+    store::TestKeyValueStore,
+    test_utils::performance::{self, Workload},
+};
+use tokio::runtime::Runtime;
+
+/// The lengths of the values in the benchmarked workloads.
+const LEN_VALUES: [usize; 3] = [100, 1000, 10000];
+
+/// Benchmarks the reads, prefix scans and batch writes of a store, for values of increasing
+/// lengths.
+fn bench_workloads<S: TestKeyValueStore + 'static>(criterion: &mut Criterion, backend: &str)
+where
+    S::Error: Debug,
+{
+    let mut group = criterion.benchmark_group(format!("store_{backend}_workloads"));
+    for len_value in LEN_VALUES {
+        let workload = Workload {
+            len_value,
+            ..Workload::default()
+        };
+
+        group.bench_with_input(
+            BenchmarkId::new("read_multi_values_bytes", len_value),
+            &workload,
+            |bencher, &workload| {
+                bencher
+                    .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                    .iter_custom(|iterations| async move {
+                        let store = S::new_test_store().await.unwrap();
+                        performance::read_multi_values_bytes(store, workload, iterations, black_box)
+                            .await
+                    })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("find_key_values_by_prefix", len_value),
+            &workload,
+            |bencher, &workload| {
+                bencher
+                    .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                    .iter_custom(|iterations| async move {
+                        let store = S::new_test_store().await.unwrap();
+                        performance::find_key_values_by_prefix(
+                            store, workload, iterations, black_box,
+                        )
+                        .await
+                    })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("write_batch", len_value),
+            &workload,
+            |bencher, &workload| {
+                bencher
+                    .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+                    .iter_custom(|iterations| async move {
+                        let store = S::new_test_store().await.unwrap();
+                        performance::write_batch(store, workload, iterations).await
+                    })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_store_workloads(criterion: &mut Criterion) {
+    bench_workloads::<MemoryStore>(criterion, "memory");
+
+    #[cfg(with_rocksdb)]
+    bench_workloads::<RocksDbStore>(criterion, "rocksdb");
+
+    #[cfg(with_dynamodb)]
+    bench_workloads::<DynamoDbStore>(criterion, "dynamodb");
+
+    #[cfg(with_scylladb)]
+    bench_workloads::<ScyllaDbStore>(criterion, "scylladb");
+}
+
+criterion_group!(benches, bench_store_workloads);
+criterion_main!(benches);
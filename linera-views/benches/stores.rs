@@ -8,7 +8,11 @@ use linera_views::dynamo_db::DynamoDbStore;
 use linera_views::rocks_db::RocksDbStore;
 #[cfg(with_scylladb)]
 use linera_views::scylla_db::ScyllaDbStore;
-use linera_views::{memory::MemoryStore, store::TestKeyValueStore as _, test_utils::performance};
+use linera_views::{
+    memory::MemoryStore,
+    store::TestKeyValueStore as _,
+    test_utils::performance::{self, Workload},
+};
 use tokio::runtime::Runtime;
 
 fn bench_contains_key(criterion: &mut Criterion) {
@@ -17,7 +21,7 @@ fn bench_contains_key(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = MemoryStore::new_test_store().await.unwrap();
-                performance::contains_key(store, iterations, black_box).await
+                performance::contains_key(store, Workload::default(), iterations, black_box).await
             })
     });
 
@@ -27,7 +31,7 @@ fn bench_contains_key(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = RocksDbStore::new_test_store().await.unwrap();
-                performance::contains_key(store, iterations, black_box).await
+                performance::contains_key(store, Workload::default(), iterations, black_box).await
             })
     });
 
@@ -37,7 +41,7 @@ fn bench_contains_key(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = DynamoDbStore::new_test_store().await.unwrap();
-                performance::contains_key(store, iterations, black_box).await
+                performance::contains_key(store, Workload::default(), iterations, black_box).await
             })
     });
 
@@ -47,7 +51,7 @@ fn bench_contains_key(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ScyllaDbStore::new_test_store().await.unwrap();
-                performance::contains_key(store, iterations, black_box).await
+                performance::contains_key(store, Workload::default(), iterations, black_box).await
             })
     });
 }
@@ -58,7 +62,7 @@ fn bench_contains_keys(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = MemoryStore::new_test_store().await.unwrap();
-                performance::contains_keys(store, iterations, black_box).await
+                performance::contains_keys(store, Workload::default(), iterations, black_box).await
             })
     });
 
@@ -68,7 +72,7 @@ fn bench_contains_keys(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = RocksDbStore::new_test_store().await.unwrap();
-                performance::contains_keys(store, iterations, black_box).await
+                performance::contains_keys(store, Workload::default(), iterations, black_box).await
             })
     });
 
@@ -78,7 +82,7 @@ fn bench_contains_keys(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = DynamoDbStore::new_test_store().await.unwrap();
-                performance::contains_keys(store, iterations, black_box).await
+                performance::contains_keys(store, Workload::default(), iterations, black_box).await
             })
     });
 
@@ -88,7 +92,7 @@ fn bench_contains_keys(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ScyllaDbStore::new_test_store().await.unwrap();
-                performance::contains_keys(store, iterations, black_box).await
+                performance::contains_keys(store, Workload::default(), iterations, black_box).await
             })
     });
 }
@@ -99,7 +103,8 @@ fn bench_find_keys_by_prefix(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = MemoryStore::new_test_store().await.unwrap();
-                performance::find_keys_by_prefix(store, iterations, black_box).await
+                performance::find_keys_by_prefix(store, Workload::default(), iterations, black_box)
+                    .await
             })
     });
 
@@ -109,7 +114,8 @@ fn bench_find_keys_by_prefix(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = RocksDbStore::new_test_store().await.unwrap();
-                performance::find_keys_by_prefix(store, iterations, black_box).await
+                performance::find_keys_by_prefix(store, Workload::default(), iterations, black_box)
+                    .await
             })
     });
 
@@ -119,7 +125,8 @@ fn bench_find_keys_by_prefix(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = DynamoDbStore::new_test_store().await.unwrap();
-                performance::find_keys_by_prefix(store, iterations, black_box).await
+                performance::find_keys_by_prefix(store, Workload::default(), iterations, black_box)
+                    .await
             })
     });
 
@@ -129,7 +136,8 @@ fn bench_find_keys_by_prefix(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ScyllaDbStore::new_test_store().await.unwrap();
-                performance::find_keys_by_prefix(store, iterations, black_box).await
+                performance::find_keys_by_prefix(store, Workload::default(), iterations, black_box)
+                    .await
             })
     });
 }
@@ -140,7 +148,13 @@ fn bench_find_key_values_by_prefix(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = MemoryStore::new_test_store().await.unwrap();
-                performance::find_key_values_by_prefix(store, iterations, black_box).await
+                performance::find_key_values_by_prefix(
+                    store,
+                    Workload::default(),
+                    iterations,
+                    black_box,
+                )
+                .await
             })
     });
 
@@ -150,7 +164,13 @@ fn bench_find_key_values_by_prefix(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = RocksDbStore::new_test_store().await.unwrap();
-                performance::find_key_values_by_prefix(store, iterations, black_box).await
+                performance::find_key_values_by_prefix(
+                    store,
+                    Workload::default(),
+                    iterations,
+                    black_box,
+                )
+                .await
             })
     });
 
@@ -160,7 +180,13 @@ fn bench_find_key_values_by_prefix(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = DynamoDbStore::new_test_store().await.unwrap();
-                performance::find_key_values_by_prefix(store, iterations, black_box).await
+                performance::find_key_values_by_prefix(
+                    store,
+                    Workload::default(),
+                    iterations,
+                    black_box,
+                )
+                .await
             })
     });
 
@@ -170,7 +196,13 @@ fn bench_find_key_values_by_prefix(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ScyllaDbStore::new_test_store().await.unwrap();
-                performance::find_key_values_by_prefix(store, iterations, black_box).await
+                performance::find_key_values_by_prefix(
+                    store,
+                    Workload::default(),
+                    iterations,
+                    black_box,
+                )
+                .await
             })
     });
 }
@@ -181,7 +213,8 @@ fn bench_read_value_bytes(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = MemoryStore::new_test_store().await.unwrap();
-                performance::read_value_bytes(store, iterations, black_box).await
+                performance::read_value_bytes(store, Workload::default(), iterations, black_box)
+                    .await
             })
     });
 
@@ -191,7 +224,8 @@ fn bench_read_value_bytes(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = RocksDbStore::new_test_store().await.unwrap();
-                performance::read_value_bytes(store, iterations, black_box).await
+                performance::read_value_bytes(store, Workload::default(), iterations, black_box)
+                    .await
             })
     });
 
@@ -201,7 +235,8 @@ fn bench_read_value_bytes(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = DynamoDbStore::new_test_store().await.unwrap();
-                performance::read_value_bytes(store, iterations, black_box).await
+                performance::read_value_bytes(store, Workload::default(), iterations, black_box)
+                    .await
             })
     });
 
@@ -211,7 +246,8 @@ fn bench_read_value_bytes(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ScyllaDbStore::new_test_store().await.unwrap();
-                performance::read_value_bytes(store, iterations, black_box).await
+                performance::read_value_bytes(store, Workload::default(), iterations, black_box)
+                    .await
             })
     });
 }
@@ -222,7 +258,13 @@ fn bench_read_multi_values_bytes(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = MemoryStore::new_test_store().await.unwrap();
-                performance::read_multi_values_bytes(store, iterations, black_box).await
+                performance::read_multi_values_bytes(
+                    store,
+                    Workload::default(),
+                    iterations,
+                    black_box,
+                )
+                .await
             })
     });
 
@@ -232,7 +274,13 @@ fn bench_read_multi_values_bytes(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = RocksDbStore::new_test_store().await.unwrap();
-                performance::read_multi_values_bytes(store, iterations, black_box).await
+                performance::read_multi_values_bytes(
+                    store,
+                    Workload::default(),
+                    iterations,
+                    black_box,
+                )
+                .await
             })
     });
 
@@ -242,7 +290,13 @@ fn bench_read_multi_values_bytes(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = DynamoDbStore::new_test_store().await.unwrap();
-                performance::read_multi_values_bytes(store, iterations, black_box).await
+                performance::read_multi_values_bytes(
+                    store,
+                    Workload::default(),
+                    iterations,
+                    black_box,
+                )
+                .await
             })
     });
 
@@ -252,7 +306,13 @@ fn bench_read_multi_values_bytes(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ScyllaDbStore::new_test_store().await.unwrap();
-                performance::read_multi_values_bytes(store, iterations, black_box).await
+                performance::read_multi_values_bytes(
+                    store,
+                    Workload::default(),
+                    iterations,
+                    black_box,
+                )
+                .await
             })
     });
 }
@@ -263,7 +323,7 @@ fn bench_write_batch(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = MemoryStore::new_test_store().await.unwrap();
-                performance::write_batch(store, iterations).await
+                performance::write_batch(store, Workload::default(), iterations).await
             })
     });
 
@@ -273,7 +333,7 @@ fn bench_write_batch(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = RocksDbStore::new_test_store().await.unwrap();
-                performance::write_batch(store, iterations).await
+                performance::write_batch(store, Workload::default(), iterations).await
             })
     });
 
@@ -283,7 +343,7 @@ fn bench_write_batch(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = DynamoDbStore::new_test_store().await.unwrap();
-                performance::write_batch(store, iterations).await
+                performance::write_batch(store, Workload::default(), iterations).await
             })
     });
 
@@ -293,7 +353,7 @@ fn bench_write_batch(criterion: &mut Criterion) {
             .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
             .iter_custom(|iterations| async move {
                 let store = ScyllaDbStore::new_test_store().await.unwrap();
-                performance::write_batch(store, iterations).await
+                performance::write_batch(store, Workload::default(), iterations).await
             })
     });
 }
@@ -188,6 +188,48 @@ where
         };
         Ok(result)
     }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let result = match self.store_in_use {
+            StoreInUse::First => self
+                .first_store
+                .find_keys_by_prefix_after(key_prefix, start_after, limit)
+                .await
+                .map_err(DualStoreError::First)?,
+            StoreInUse::Second => self
+                .second_store
+                .find_keys_by_prefix_after(key_prefix, start_after, limit)
+                .await
+                .map_err(DualStoreError::Second)?,
+        };
+        Ok(result)
+    }
+
+    async fn find_key_values_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        let result = match self.store_in_use {
+            StoreInUse::First => self
+                .first_store
+                .find_key_values_by_prefix_after(key_prefix, start_after, limit)
+                .await
+                .map_err(DualStoreError::First)?,
+            StoreInUse::Second => self
+                .second_store
+                .find_key_values_by_prefix_after(key_prefix, start_after, limit)
+                .await
+                .map_err(DualStoreError::Second)?,
+        };
+        Ok(result)
+    }
 }
 
 impl<S1, S2, A> WritableKeyValueStore for DualStore<S1, S2, A>
@@ -214,6 +256,13 @@ where
         Ok(())
     }
 
+    fn supports_conditional_writes(&self) -> bool {
+        match self.store_in_use {
+            StoreInUse::First => self.first_store.supports_conditional_writes(),
+            StoreInUse::Second => self.second_store.supports_conditional_writes(),
+        }
+    }
+
     async fn clear_journal(&self) -> Result<(), Self::Error> {
         match self.store_in_use {
             StoreInUse::First => self
@@ -370,6 +419,14 @@ where
     E2: KeyValueStoreError,
 {
     const BACKEND: &'static str = "dual_store";
+
+    fn write_conflict(&self) -> Option<&[u8]> {
+        match self {
+            DualStoreError::BcsError(_) => None,
+            DualStoreError::First(error) => error.write_conflict(),
+            DualStoreError::Second(error) => error.write_conflict(),
+        }
+    }
 }
 
 /// A set of keys returned by [`DualStore::find_keys_by_prefix`].
@@ -2,12 +2,34 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Implements [`crate::store::KeyValueStore`] for the DynamoDB database.
+//!
+//! The store can use DynamoDB [global
+//! tables](https://docs.aws.amazon.com/amazondynamodb/latest/developerguide/GlobalTables.html)
+//! by configuring the regions of their replicas, with
+//! [`DynamoDbStoreConfig::with_regions`]. The writes are always sent to the preferred region:
+//! moving them to another region is an operator action, done by reordering the regions. The
+//! reads fail over to the next regions while the preferred one is unavailable, see
+//! [`DynamoDbStoreConfig::with_failover`]. The tables are only created in the first region, so
+//! the replicas have to be added when setting up the global tables.
+//!
+//! The requests throttled by DynamoDB are retried with the adaptive retry mode of the AWS
+//! SDK, see [`DynamoDbStoreConfig::with_retries`]. The transactions canceled because one of
+//! their items was throttled are retried by the store, with the same settings.
 
-use std::{collections::HashMap, env, sync::Arc};
+#[cfg(with_metrics)]
+use std::sync::LazyLock;
+use std::{
+    collections::HashMap,
+    env,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use async_lock::{Semaphore, SemaphoreGuard};
 use async_trait::async_trait;
 use aws_sdk_dynamodb::{
+    config::Region,
     error::SdkError,
     operation::{
         batch_write_item::BatchWriteItemError,
@@ -20,14 +42,19 @@ use aws_sdk_dynamodb::{
     },
     primitives::Blob,
     types::{
-        AttributeDefinition, AttributeValue, Delete, KeySchemaElement, KeyType,
-        ProvisionedThroughput, Put, ScalarAttributeType, TransactWriteItem,
+        AttributeDefinition, AttributeValue, BillingMode, ConditionCheck, ConsumedCapacity, Delete,
+        KeySchemaElement, KeyType, ProvisionedThroughput, Put, ReturnConsumedCapacity,
+        ScalarAttributeType, TransactWriteItem,
     },
     Client,
 };
-use aws_smithy_types::error::operation::BuildError;
+use aws_smithy_types::{error::operation::BuildError, retry::RetryConfig};
 use futures::future::{join_all, FutureExt as _};
-use linera_base::ensure;
+#[cfg(with_metrics)]
+use linera_base::prometheus_util::{bucket_interval, register_histogram_vec};
+use linera_base::{ensure, time::timer::sleep};
+#[cfg(with_metrics)]
+use prometheus::HistogramVec;
 use thiserror::Error;
 
 #[cfg(with_metrics)]
@@ -35,7 +62,7 @@ use crate::metering::MeteredStore;
 #[cfg(with_testing)]
 use crate::store::TestKeyValueStore;
 use crate::{
-    batch::SimpleUnorderedBatch,
+    batch::{SimpleUnorderedBatch, WriteCondition},
     common::get_uleb128_size,
     journaling::{DirectWritableKeyValueStore, JournalConsistencyError, JournalingKeyValueStore},
     lru_caching::{LruCachingConfig, LruCachingStore},
@@ -204,6 +231,27 @@ fn build_key_value(
     .into()
 }
 
+/// Builds the condition expression of a transaction item, with its attribute values, that
+/// holds if the item is absent or has one of the allowed values.
+fn build_condition(
+    allowed_values: Vec<Option<Vec<u8>>>,
+) -> (String, Option<HashMap<String, AttributeValue>>) {
+    let mut clauses = Vec::new();
+    let mut values = HashMap::new();
+    for value in allowed_values {
+        match value {
+            None => clauses.push(format!("attribute_not_exists({KEY_ATTRIBUTE})")),
+            Some(value) => {
+                let name = format!(":value{}", values.len());
+                clauses.push(format!("{VALUE_ATTRIBUTE} = {name}"));
+                values.insert(name, AttributeValue::B(Blob::new(value)));
+            }
+        }
+    }
+    let values = (!values.is_empty()).then_some(values);
+    (clauses.join(" OR "), values)
+}
+
 /// Checks that a key is of the correct size
 fn check_key_size(key: &[u8]) -> Result<(), DynamoDbStoreInternalError> {
     ensure!(!key.is_empty(), DynamoDbStoreInternalError::ZeroLengthKey);
@@ -279,24 +327,30 @@ fn extract_key_value_owned(
 struct TransactionBuilder {
     root_key: Vec<u8>,
     transacts: Vec<TransactWriteItem>,
+    /// The keys of the conditions, by the index of the item that checks them.
+    condition_keys: HashMap<usize, Vec<u8>>,
 }
 
 impl TransactionBuilder {
     fn new(root_key: &[u8]) -> Self {
         let root_key = root_key.to_vec();
         let transacts = Vec::new();
+        let condition_keys = HashMap::new();
         Self {
             root_key,
             transacts,
+            condition_keys,
         }
     }
 
     fn insert_delete_request(
         &mut self,
         key: Vec<u8>,
+        condition: Option<Vec<Option<Vec<u8>>>>,
         store: &DynamoDbStoreInternal,
     ) -> Result<(), DynamoDbStoreInternalError> {
-        let transact = store.build_delete_transact(&self.root_key, key)?;
+        self.record_condition(&key, &condition);
+        let transact = store.build_delete_transact(&self.root_key, key, condition)?;
         self.transacts.push(transact);
         Ok(())
     }
@@ -305,18 +359,229 @@ impl TransactionBuilder {
         &mut self,
         key: Vec<u8>,
         value: Vec<u8>,
+        condition: Option<Vec<Option<Vec<u8>>>>,
         store: &DynamoDbStoreInternal,
     ) -> Result<(), DynamoDbStoreInternalError> {
-        let transact = store.build_put_transact(&self.root_key, key, value)?;
+        self.record_condition(&key, &condition);
+        let transact = store.build_put_transact(&self.root_key, key, value, condition)?;
         self.transacts.push(transact);
         Ok(())
     }
+
+    fn insert_condition_check(
+        &mut self,
+        condition: WriteCondition,
+        store: &DynamoDbStoreInternal,
+    ) -> Result<(), DynamoDbStoreInternalError> {
+        self.condition_keys
+            .insert(self.transacts.len(), condition.key.clone());
+        let transact = store.build_condition_check_transact(&self.root_key, condition)?;
+        self.transacts.push(transact);
+        Ok(())
+    }
+
+    fn record_condition(&mut self, key: &[u8], condition: &Option<Vec<Option<Vec<u8>>>>) {
+        if condition.is_some() {
+            self.condition_keys
+                .insert(self.transacts.len(), key.to_vec());
+        }
+    }
+
+    /// Returns the key of the condition that canceled the transaction, if any.
+    fn failed_condition(&self, error: &SdkError<TransactWriteItemsError>) -> Option<Vec<u8>> {
+        let TransactWriteItemsError::TransactionCanceledException(exception) =
+            error.as_service_error()?
+        else {
+            return None;
+        };
+        let (index, _) = exception
+            .cancellation_reasons()
+            .iter()
+            .enumerate()
+            .find(|(_, reason)| reason.code() == Some("ConditionalCheckFailed"))?;
+        self.condition_keys.get(&index).cloned()
+    }
+}
+
+/// The capacity units consumed by the DynamoDB requests.
+#[cfg(with_metrics)]
+static CONSUMED_CAPACITY_UNITS: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec(
+        "dynamo_db_consumed_capacity_units",
+        "The capacity units consumed by the DynamoDB requests",
+        &["operation"],
+        bucket_interval(0.5, 1000.0),
+    )
+});
+
+/// Records the capacity units consumed by a request.
+#[cfg_attr(not(with_metrics), expect(unused_variables))]
+fn record_consumed_capacity<'a>(
+    operation: &str,
+    consumed_capacities: impl IntoIterator<Item = &'a ConsumedCapacity>,
+) {
+    #[cfg(with_metrics)]
+    {
+        let capacity_units = consumed_capacities
+            .into_iter()
+            .filter_map(|consumed_capacity| consumed_capacity.capacity_units)
+            .sum::<f64>();
+        CONSUMED_CAPACITY_UNITS
+            .with_label_values(&[operation])
+            .observe(capacity_units);
+    }
+}
+
+/// Returns whether an error indicates that a region is unavailable, in which case a read can
+/// be sent to another region. Other server errors, such as internal errors, are retried in the
+/// same region by the AWS SDK instead.
+fn is_region_failure<E>(error: &SdkError<E>) -> bool {
+    match error {
+        SdkError::DispatchFailure(_) | SdkError::TimeoutError(_) => true,
+        SdkError::ServiceError(error) => error.raw().status().as_u16() == 503,
+        _ => false,
+    }
+}
+
+/// Returns whether a transaction was canceled because one of its items was throttled, in
+/// which case it can be retried. The AWS SDK does not retry these cancellations.
+fn is_throttled_transaction(error: &SdkError<TransactWriteItemsError>) -> bool {
+    let Some(TransactWriteItemsError::TransactionCanceledException(exception)) =
+        error.as_service_error()
+    else {
+        return false;
+    };
+    exception.cancellation_reasons().iter().any(|reason| {
+        matches!(
+            reason.code(),
+            Some("ThrottlingError" | "ProvisionedThroughputExceeded")
+        )
+    })
+}
+
+/// The consistency of a kind of reads from DynamoDB.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadConsistency {
+    /// Eventually consistent reads, which are the default in DynamoDB.
+    #[default]
+    Eventual,
+    /// Strongly consistent reads. With global tables, they only observe the writes made in
+    /// the same region.
+    Strong,
+}
+
+impl ReadConsistency {
+    fn is_strong(self) -> bool {
+        self == ReadConsistency::Strong
+    }
+}
+
+/// The consistency of the reads from DynamoDB, for each kind of operation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DynamoDbConsistency {
+    /// The consistency of the reads of single keys.
+    pub get_item: ReadConsistency,
+    /// The consistency of the searches by key prefix.
+    pub query: ReadConsistency,
+}
+
+/// The retries of the requests that fail because of throttling or transient errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DynamoDbRetryConfig {
+    /// The maximum number of attempts of a request, including the first one.
+    pub max_attempts: u32,
+    /// The initial delay before retrying a request.
+    pub initial_backoff: Duration,
+    /// The maximum delay between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for DynamoDbRetryConfig {
+    fn default() -> Self {
+        DynamoDbRetryConfig {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(20),
+        }
+    }
+}
+
+impl DynamoDbRetryConfig {
+    /// Returns the adaptive retry configuration of the AWS SDK, which also limits the rate of
+    /// the requests after they were throttled.
+    fn to_retry_config(self) -> RetryConfig {
+        RetryConfig::adaptive()
+            .with_max_attempts(self.max_attempts)
+            .with_initial_backoff(self.initial_backoff)
+            .with_max_backoff(self.max_backoff)
+    }
+
+    /// Returns the delay before the given retry of a request, starting from 0, which doubles
+    /// with each retry up to the maximum delay.
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(1 << retry.min(31))
+            .min(self.max_backoff)
+    }
+}
+
+/// The failover of the reads to the other regions of the global tables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DynamoDbFailover {
+    /// Whether the reads are sent to the next region when a region is unavailable.
+    pub reads: bool,
+    /// How long the reads keep using the region they failed over to, before trying the
+    /// preferred region again.
+    pub cooldown: Duration,
+}
+
+impl Default for DynamoDbFailover {
+    fn default() -> Self {
+        DynamoDbFailover {
+            reads: true,
+            cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+/// The region the reads failed over to, and when.
+#[derive(Clone, Copy, Debug)]
+struct FailedOver {
+    region: usize,
+    since: Instant,
+}
+
+/// The capacity mode of the tables created by the store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DynamoDbCapacity {
+    /// On-demand capacity, where the requests are billed individually.
+    OnDemand,
+    /// Provisioned capacity, with the given number of capacity units per second.
+    Provisioned {
+        /// The read capacity units.
+        read_capacity_units: i64,
+        /// The write capacity units.
+        write_capacity_units: i64,
+    },
+}
+
+impl Default for DynamoDbCapacity {
+    fn default() -> Self {
+        DynamoDbCapacity::Provisioned {
+            read_capacity_units: 10,
+            write_capacity_units: 10,
+        }
+    }
 }
 
 /// A DynamoDB client.
 #[derive(Clone, Debug)]
 pub struct DynamoDbStoreInternal {
-    client: Client,
+    clients: Arc<[Client]>,
+    failover: DynamoDbFailover,
+    failed_over: Arc<Mutex<Option<FailedOver>>>,
+    consistency: DynamoDbConsistency,
+    retries: DynamoDbRetryConfig,
     namespace: String,
     semaphore: Option<Arc<Semaphore>>,
     max_stream_queries: usize,
@@ -328,10 +593,50 @@ pub struct DynamoDbStoreInternal {
 pub struct DynamoDbStoreInternalConfig {
     /// The AWS configuration
     config: aws_sdk_dynamodb::Config,
+    /// The regions of the replicas of the global tables, by order of preference
+    regions: Vec<String>,
+    /// The failover of the reads to the other regions
+    failover: DynamoDbFailover,
+    /// The consistency of the reads
+    consistency: DynamoDbConsistency,
+    /// The retries of the throttled requests
+    retries: DynamoDbRetryConfig,
+    /// The capacity mode of the created tables
+    capacity: DynamoDbCapacity,
     /// The common configuration of the key value store
     common_config: CommonStoreInternalConfig,
 }
 
+impl DynamoDbStoreInternalConfig {
+    /// Creates a client for the given region, or the region of the AWS configuration.
+    fn client(&self, region: Option<&String>) -> Client {
+        let mut builder = self
+            .config
+            .to_builder()
+            .retry_config(self.retries.to_retry_config());
+        if let Some(region) = region {
+            builder = builder.region(Region::new(region.clone()));
+        }
+        Client::from_conf(builder.build())
+    }
+
+    /// Creates a client for each region, by order of preference.
+    fn clients(&self) -> Vec<Client> {
+        if self.regions.is_empty() {
+            return vec![self.client(None)];
+        }
+        self.regions
+            .iter()
+            .map(|region| self.client(Some(region)))
+            .collect()
+    }
+
+    /// Creates a client for the preferred region, which is used to manage the tables.
+    fn preferred_client(&self) -> Client {
+        self.client(self.regions.first())
+    }
+}
+
 impl AdminKeyValueStore for DynamoDbStoreInternal {
     type Config = DynamoDbStoreInternalConfig;
 
@@ -345,7 +650,11 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
         root_key: &[u8],
     ) -> Result<Self, DynamoDbStoreInternalError> {
         Self::check_namespace(namespace)?;
-        let client = Client::from_conf(config.config.clone());
+        let clients = config.clients().into();
+        let failover = config.failover;
+        let failed_over = Arc::default();
+        let consistency = config.consistency;
+        let retries = config.retries;
         let semaphore = config
             .common_config
             .max_concurrent_queries
@@ -354,7 +663,11 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
         let namespace = namespace.to_string();
         let root_key = root_key.to_vec();
         Ok(Self {
-            client,
+            clients,
+            failover,
+            failed_over,
+            consistency,
+            retries,
             namespace,
             semaphore,
             max_stream_queries,
@@ -363,13 +676,21 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
     }
 
     fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, DynamoDbStoreInternalError> {
-        let client = self.client.clone();
+        let clients = self.clients.clone();
+        let failover = self.failover;
+        let failed_over = self.failed_over.clone();
+        let consistency = self.consistency;
+        let retries = self.retries;
         let namespace = self.namespace.clone();
         let semaphore = self.semaphore.clone();
         let max_stream_queries = self.max_stream_queries;
         let root_key = root_key.to_vec();
         Ok(Self {
-            client,
+            clients,
+            failover,
+            failed_over,
+            consistency,
+            retries,
             namespace,
             semaphore,
             max_stream_queries,
@@ -378,7 +699,7 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
     }
 
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, DynamoDbStoreInternalError> {
-        let client = Client::from_conf(config.config.clone());
+        let client = config.preferred_client();
         let mut namespaces = Vec::new();
         let mut start_table = None;
         loop {
@@ -401,7 +722,7 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
     }
 
     async fn delete_all(config: &Self::Config) -> Result<(), DynamoDbStoreInternalError> {
-        let client = Client::from_conf(config.config.clone());
+        let client = config.preferred_client();
         let tables = Self::list_all(config).await?;
         for table in tables {
             client
@@ -419,7 +740,7 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
         namespace: &str,
     ) -> Result<bool, DynamoDbStoreInternalError> {
         Self::check_namespace(namespace)?;
-        let client = Client::from_conf(config.config.clone());
+        let client = config.preferred_client();
         let key_db = build_key(EMPTY_ROOT_KEY, DB_KEY.to_vec());
         let response = client
             .get_item()
@@ -453,8 +774,8 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
         namespace: &str,
     ) -> Result<(), DynamoDbStoreInternalError> {
         Self::check_namespace(namespace)?;
-        let client = Client::from_conf(config.config.clone());
-        let _result = client
+        let client = config.preferred_client();
+        let request = client
             .create_table()
             .table_name(namespace)
             .attribute_definitions(
@@ -480,16 +801,22 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
                     .attribute_name(KEY_ATTRIBUTE)
                     .key_type(KeyType::Range)
                     .build()?,
-            )
-            .provisioned_throughput(
-                ProvisionedThroughput::builder()
-                    .read_capacity_units(10)
-                    .write_capacity_units(10)
-                    .build()?,
-            )
-            .send()
-            .boxed()
-            .await?;
+            );
+        let request = match config.capacity {
+            DynamoDbCapacity::OnDemand => request.billing_mode(BillingMode::PayPerRequest),
+            DynamoDbCapacity::Provisioned {
+                read_capacity_units,
+                write_capacity_units,
+            } => request
+                .billing_mode(BillingMode::Provisioned)
+                .provisioned_throughput(
+                    ProvisionedThroughput::builder()
+                        .read_capacity_units(read_capacity_units)
+                        .write_capacity_units(write_capacity_units)
+                        .build()?,
+                ),
+        };
+        request.send().boxed().await?;
         Ok(())
     }
 
@@ -498,7 +825,7 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
         namespace: &str,
     ) -> Result<(), DynamoDbStoreInternalError> {
         Self::check_namespace(namespace)?;
-        let client = Client::from_conf(config.config.clone());
+        let client = config.preferred_client();
         client
             .delete_table()
             .table_name(namespace)
@@ -535,11 +862,15 @@ impl DynamoDbStoreInternal {
         &self,
         root_key: &[u8],
         key: Vec<u8>,
+        condition: Option<Vec<Option<Vec<u8>>>>,
     ) -> Result<TransactWriteItem, DynamoDbStoreInternalError> {
         check_key_size(&key)?;
+        let (expression, values) = condition.map(build_condition).unzip();
         let request = Delete::builder()
             .table_name(&self.namespace)
             .set_key(Some(build_key(root_key, key)))
+            .set_condition_expression(expression)
+            .set_expression_attribute_values(values.flatten())
             .build()?;
         Ok(TransactWriteItem::builder().delete(request).build())
     }
@@ -549,19 +880,94 @@ impl DynamoDbStoreInternal {
         root_key: &[u8],
         key: Vec<u8>,
         value: Vec<u8>,
+        condition: Option<Vec<Option<Vec<u8>>>>,
     ) -> Result<TransactWriteItem, DynamoDbStoreInternalError> {
         check_key_size(&key)?;
         ensure!(
             value.len() <= RAW_MAX_VALUE_SIZE,
             DynamoDbStoreInternalError::ValueLengthTooLarge
         );
+        let (expression, values) = condition.map(build_condition).unzip();
         let request = Put::builder()
             .table_name(&self.namespace)
             .set_item(Some(build_key_value(root_key, key, value)))
+            .set_condition_expression(expression)
+            .set_expression_attribute_values(values.flatten())
             .build()?;
         Ok(TransactWriteItem::builder().put(request).build())
     }
 
+    fn build_condition_check_transact(
+        &self,
+        root_key: &[u8],
+        condition: WriteCondition,
+    ) -> Result<TransactWriteItem, DynamoDbStoreInternalError> {
+        check_key_size(&condition.key)?;
+        let (expression, values) = build_condition(condition.allowed_values);
+        let request = ConditionCheck::builder()
+            .table_name(&self.namespace)
+            .set_key(Some(build_key(root_key, condition.key)))
+            .condition_expression(expression)
+            .set_expression_attribute_values(values)
+            .build()?;
+        Ok(TransactWriteItem::builder()
+            .condition_check(request)
+            .build())
+    }
+
+    /// Returns the region to send the reads to: the region they failed over to during the
+    /// cooldown, and the preferred region otherwise.
+    fn read_region(&self) -> usize {
+        let mut failed_over = self.failed_over.lock().unwrap();
+        match *failed_over {
+            Some(FailedOver { region, since }) if since.elapsed() < self.failover.cooldown => {
+                region
+            }
+            _ => {
+                *failed_over = None;
+                0
+            }
+        }
+    }
+
+    /// Runs a read with the client of the current read region. If the region is unavailable,
+    /// the read is sent to the next regions, and the first one that succeeds is used by the
+    /// reads until the cooldown of the failover elapses.
+    async fn with_read_failover<T, E, F, Fut>(&self, request: F) -> Result<T, SdkError<E>>
+    where
+        F: Fn(&Client) -> Fut,
+        Fut: Future<Output = Result<T, SdkError<E>>>,
+    {
+        if !self.failover.reads {
+            return request(&self.clients[0]).await;
+        }
+        let first_index = self.read_region();
+        let mut index = first_index;
+        loop {
+            let result = request(&self.clients[index]).await;
+            let next_index = (index + 1) % self.clients.len();
+            match result {
+                Err(error) if next_index != first_index && is_region_failure(&error) => {
+                    tracing::warn!(
+                        "DynamoDB region {:?} is unavailable, failing over to {:?}",
+                        self.clients[index].config().region(),
+                        self.clients[next_index].config().region()
+                    );
+                    index = next_index;
+                }
+                result => {
+                    if result.is_ok() && index != first_index {
+                        *self.failed_over.lock().unwrap() = Some(FailedOver {
+                            region: index,
+                            since: Instant::now(),
+                        });
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+
     /// Obtains the semaphore lock on the database if needed.
     async fn acquire(&self) -> Option<SemaphoreGuard<'_>> {
         match &self.semaphore {
@@ -576,23 +982,33 @@ impl DynamoDbStoreInternal {
         root_key: &[u8],
         key_prefix: &[u8],
         start_key_map: Option<HashMap<String, AttributeValue>>,
+        limit: Option<usize>,
     ) -> Result<QueryOutput, DynamoDbStoreInternalError> {
         let _guard = self.acquire().await;
         let big_root = extend_root_key(root_key);
         let response = self
-            .client
-            .query()
-            .table_name(&self.namespace)
-            .projection_expression(attribute_str)
-            .key_condition_expression(format!(
-                "{PARTITION_ATTRIBUTE} = :partition and begins_with({KEY_ATTRIBUTE}, :prefix)"
-            ))
-            .expression_attribute_values(":partition", AttributeValue::B(Blob::new(big_root)))
-            .expression_attribute_values(":prefix", AttributeValue::B(Blob::new(key_prefix)))
-            .set_exclusive_start_key(start_key_map)
-            .send()
-            .boxed()
+            .with_read_failover(|client| {
+                client
+                    .query()
+                    .table_name(&self.namespace)
+                    .projection_expression(attribute_str)
+                    .key_condition_expression(format!(
+                        "{PARTITION_ATTRIBUTE} = :partition and begins_with({KEY_ATTRIBUTE}, :prefix)"
+                    ))
+                    .expression_attribute_values(
+                        ":partition",
+                        AttributeValue::B(Blob::new(big_root.clone())),
+                    )
+                    .expression_attribute_values(":prefix", AttributeValue::B(Blob::new(key_prefix)))
+                    .set_exclusive_start_key(start_key_map.clone())
+                    .set_limit(limit.map(|limit| limit.try_into().unwrap_or(i32::MAX)))
+                    .consistent_read(self.consistency.query.is_strong())
+                    .return_consumed_capacity(ReturnConsumedCapacity::Total)
+                    .send()
+                    .boxed()
+            })
             .await?;
+        record_consumed_capacity("query", response.consumed_capacity());
         Ok(response)
     }
 
@@ -602,13 +1018,18 @@ impl DynamoDbStoreInternal {
     ) -> Result<Option<Vec<u8>>, DynamoDbStoreInternalError> {
         let _guard = self.acquire().await;
         let response = self
-            .client
-            .get_item()
-            .table_name(&self.namespace)
-            .set_key(Some(key_db))
-            .send()
-            .boxed()
+            .with_read_failover(|client| {
+                client
+                    .get_item()
+                    .table_name(&self.namespace)
+                    .set_key(Some(key_db.clone()))
+                    .consistent_read(self.consistency.get_item.is_strong())
+                    .return_consumed_capacity(ReturnConsumedCapacity::Total)
+                    .send()
+                    .boxed()
+            })
             .await?;
+        record_consumed_capacity("get_item", response.consumed_capacity());
 
         match response.item {
             Some(mut item) => {
@@ -625,14 +1046,19 @@ impl DynamoDbStoreInternal {
     ) -> Result<bool, DynamoDbStoreInternalError> {
         let _guard = self.acquire().await;
         let response = self
-            .client
-            .get_item()
-            .table_name(&self.namespace)
-            .set_key(Some(key_db))
-            .projection_expression(PARTITION_ATTRIBUTE)
-            .send()
-            .boxed()
+            .with_read_failover(|client| {
+                client
+                    .get_item()
+                    .table_name(&self.namespace)
+                    .set_key(Some(key_db.clone()))
+                    .projection_expression(PARTITION_ATTRIBUTE)
+                    .consistent_read(self.consistency.get_item.is_strong())
+                    .return_consumed_capacity(ReturnConsumedCapacity::Total)
+                    .send()
+                    .boxed()
+            })
             .await?;
+        record_consumed_capacity("get_item", response.consumed_capacity());
 
         Ok(response.item.is_some())
     }
@@ -642,23 +1068,32 @@ impl DynamoDbStoreInternal {
         attribute: &str,
         root_key: &[u8],
         key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        mut limit: Option<usize>,
     ) -> Result<QueryResponses, DynamoDbStoreInternalError> {
         check_key_size(key_prefix)?;
         let mut responses = Vec::new();
-        let mut start_key = None;
+        let mut start_key = start_after.map(|start_after| {
+            let mut key = key_prefix.to_vec();
+            key.extend(start_after);
+            build_key(root_key, key)
+        });
         loop {
             let response = self
-                .get_query_output(attribute, root_key, key_prefix, start_key)
+                .get_query_output(attribute, root_key, key_prefix, start_key, limit)
                 .await?;
             let last_evaluated = response.last_evaluated_key.clone();
+            if let Some(limit) = &mut limit {
+                *limit = limit.saturating_sub(response.count.try_into().unwrap_or(0));
+            }
             responses.push(response);
             match last_evaluated {
-                None => {
-                    break;
-                }
-                Some(value) => {
+                Some(value) if limit != Some(0) => {
                     start_key = Some(value);
                 }
+                _ => {
+                    break;
+                }
             }
         }
         Ok(QueryResponses {
@@ -904,7 +1339,7 @@ impl ReadableKeyValueStore for DynamoDbStoreInternal {
         key_prefix: &[u8],
     ) -> Result<DynamoDbKeys, DynamoDbStoreInternalError> {
         let result_queries = self
-            .get_list_responses(KEY_ATTRIBUTE, &self.root_key, key_prefix)
+            .get_list_responses(KEY_ATTRIBUTE, &self.root_key, key_prefix, None, None)
             .await?;
         Ok(DynamoDbKeys { result_queries })
     }
@@ -914,10 +1349,56 @@ impl ReadableKeyValueStore for DynamoDbStoreInternal {
         key_prefix: &[u8],
     ) -> Result<DynamoDbKeyValues, DynamoDbStoreInternalError> {
         let result_queries = self
-            .get_list_responses(KEY_VALUE_ATTRIBUTE, &self.root_key, key_prefix)
+            .get_list_responses(KEY_VALUE_ATTRIBUTE, &self.root_key, key_prefix, None, None)
             .await?;
         Ok(DynamoDbKeyValues { result_queries })
     }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, DynamoDbStoreInternalError> {
+        // DynamoDB does not accept a limit of zero.
+        if limit == Some(0) {
+            return Ok(Vec::new());
+        }
+        let result_queries = self
+            .get_list_responses(
+                KEY_ATTRIBUTE,
+                &self.root_key,
+                key_prefix,
+                start_after,
+                limit,
+            )
+            .await?;
+        let keys = DynamoDbKeys { result_queries };
+        keys.iterator().map(|key| key.map(<[u8]>::to_vec)).collect()
+    }
+
+    async fn find_key_values_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, DynamoDbStoreInternalError> {
+        // DynamoDB does not accept a limit of zero.
+        if limit == Some(0) {
+            return Ok(Vec::new());
+        }
+        let result_queries = self
+            .get_list_responses(
+                KEY_VALUE_ATTRIBUTE,
+                &self.root_key,
+                key_prefix,
+                start_after,
+                limit,
+            )
+            .await?;
+        let key_values = DynamoDbKeyValues { result_queries };
+        key_values.into_iterator_owned().collect()
+    }
 }
 
 #[async_trait]
@@ -930,21 +1411,71 @@ impl DirectWritableKeyValueStore for DynamoDbStoreInternal {
     type Batch = SimpleUnorderedBatch;
 
     async fn write_batch(&self, batch: Self::Batch) -> Result<(), DynamoDbStoreInternalError> {
+        self.write_batch_with_conditions(batch, Vec::new()).await
+    }
+
+    fn supports_conditional_writes(&self) -> bool {
+        true
+    }
+
+    /// Writes the batch in one `TransactWriteItems` request. A condition is attached to the
+    /// write of its key, or checked with a `ConditionCheck` item if the key is not written,
+    /// since a transaction can only have one item per key.
+    async fn write_batch_with_conditions(
+        &self,
+        batch: Self::Batch,
+        conditions: Vec<WriteCondition>,
+    ) -> Result<(), DynamoDbStoreInternalError> {
+        let mut conditions = conditions
+            .into_iter()
+            .map(|condition| (condition.key, condition.allowed_values))
+            .collect::<HashMap<_, _>>();
+        if let Some((key, _)) = conditions.iter().find(|(_, values)| values.is_empty()) {
+            return Err(DynamoDbStoreInternalError::WriteConflict(key.clone()));
+        }
         let mut builder = TransactionBuilder::new(&self.root_key);
         for key in batch.deletions {
-            builder.insert_delete_request(key, self)?;
+            let condition = conditions.remove(&key);
+            builder.insert_delete_request(key, condition, self)?;
         }
         for (key, value) in batch.insertions {
-            builder.insert_put_request(key, value, self)?;
+            let condition = conditions.remove(&key);
+            builder.insert_put_request(key, value, condition, self)?;
+        }
+        for (key, allowed_values) in conditions {
+            let condition = WriteCondition {
+                key,
+                allowed_values,
+            };
+            builder.insert_condition_check(condition, self)?;
         }
         if !builder.transacts.is_empty() {
             let _guard = self.acquire().await;
-            self.client
-                .transact_write_items()
-                .set_transact_items(Some(builder.transacts))
-                .send()
-                .boxed()
-                .await?;
+            let mut retry = 0;
+            let response = loop {
+                // The writes are not failed over, since the replicas of the other regions may
+                // not have received the previous writes yet.
+                let response = self.clients[0]
+                    .transact_write_items()
+                    .set_transact_items(Some(builder.transacts.clone()))
+                    .return_consumed_capacity(ReturnConsumedCapacity::Total)
+                    .send()
+                    .await;
+                match response {
+                    Ok(response) => break response,
+                    Err(error) => {
+                        if let Some(key) = builder.failed_condition(&error) {
+                            return Err(DynamoDbStoreInternalError::WriteConflict(key));
+                        }
+                        retry += 1;
+                        if retry >= self.retries.max_attempts || !is_throttled_transaction(&error) {
+                            return Err(error.into());
+                        }
+                        sleep(self.retries.backoff(retry - 1)).await;
+                    }
+                }
+            };
+            record_consumed_capacity("transact_write_items", response.consumed_capacity());
         }
         Ok(())
     }
@@ -1056,6 +1587,10 @@ pub enum DynamoDbStoreInternalError {
     /// An error occurred while building an object
     #[error(transparent)]
     Build(#[from] Box<BuildError>),
+
+    /// The value of a key doesn't satisfy a condition of the batch
+    #[error("The value of key {0:?} doesn't satisfy a condition of the batch")]
+    WriteConflict(Vec<u8>),
 }
 
 impl<InnerError> From<SdkError<InnerError>> for DynamoDbStoreInternalError
@@ -1112,6 +1647,13 @@ impl DynamoDbStoreInternalError {
 
 impl KeyValueStoreError for DynamoDbStoreInternalError {
     const BACKEND: &'static str = "dynamo_db";
+
+    fn write_conflict(&self) -> Option<&[u8]> {
+        match self {
+            DynamoDbStoreInternalError::WriteConflict(key) => Some(key),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(with_testing)]
@@ -1125,6 +1667,11 @@ impl TestKeyValueStore for JournalingKeyValueStore<DynamoDbStoreInternal> {
         let config = get_config_internal(use_localstack).await?;
         Ok(DynamoDbStoreInternalConfig {
             config,
+            regions: Vec::new(),
+            failover: DynamoDbFailover::default(),
+            consistency: DynamoDbConsistency::default(),
+            retries: DynamoDbRetryConfig::default(),
+            capacity: DynamoDbCapacity::default(),
             common_config,
         })
     }
@@ -1164,6 +1711,11 @@ impl DynamoDbStoreConfig {
     ) -> DynamoDbStoreConfig {
         let inner_config = DynamoDbStoreInternalConfig {
             config,
+            regions: Vec::new(),
+            failover: DynamoDbFailover::default(),
+            consistency: DynamoDbConsistency::default(),
+            retries: DynamoDbRetryConfig::default(),
+            capacity: DynamoDbCapacity::default(),
             common_config: common_config.reduced(),
         };
         DynamoDbStoreConfig {
@@ -1171,14 +1723,117 @@ impl DynamoDbStoreConfig {
             cache_size: common_config.cache_size,
         }
     }
+
+    /// Sets the regions of the replicas of the global tables, by order of preference.
+    ///
+    /// The writes are sent to the first region, and the reads to the first one that is
+    /// available. If no region is given, the region of the AWS configuration is used.
+    pub fn with_regions(mut self, regions: Vec<String>) -> Self {
+        self.inner_config.regions = regions;
+        self
+    }
+
+    /// Sets the failover of the reads to the next regions, when a region cannot be reached or
+    /// is unavailable.
+    pub fn with_failover(mut self, failover: DynamoDbFailover) -> Self {
+        self.inner_config.failover = failover;
+        self
+    }
+
+    /// Sets the consistency of the reads, for each kind of operation.
+    pub fn with_consistency(mut self, consistency: DynamoDbConsistency) -> Self {
+        self.inner_config.consistency = consistency;
+        self
+    }
+
+    /// Sets the retries of the requests that are throttled by DynamoDB, or that fail because
+    /// of transient errors.
+    pub fn with_retries(mut self, retries: DynamoDbRetryConfig) -> Self {
+        self.inner_config.retries = retries;
+        self
+    }
+
+    /// Sets the capacity mode of the tables created by the store.
+    pub fn with_capacity(mut self, capacity: DynamoDbCapacity) -> Self {
+        self.inner_config.capacity = capacity;
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use aws_sdk_dynamodb::{
+        config::http::HttpResponse,
+        error::{ErrorMetadata, SdkError},
+        operation::{get_item::GetItemError, transact_write_items::TransactWriteItemsError},
+        types::{error::TransactionCanceledException, CancellationReason},
+    };
+    use aws_smithy_types::body::SdkBody;
     use bcs::serialized_size;
 
+    use super::{is_region_failure, is_throttled_transaction, DynamoDbRetryConfig};
     use crate::common::get_uleb128_size;
 
+    #[test]
+    fn test_region_failures() {
+        let timeout = SdkError::<GetItemError>::timeout_error("request timed out");
+        assert!(is_region_failure(&timeout));
+        let construction = SdkError::<GetItemError>::construction_failure("invalid request");
+        assert!(!is_region_failure(&construction));
+        let service_error = |status: u16| {
+            let response = HttpResponse::new(status.try_into().unwrap(), SdkBody::empty());
+            SdkError::service_error(GetItemError::generic(ErrorMetadata::default()), response)
+        };
+        assert!(is_region_failure(&service_error(503)));
+        assert!(!is_region_failure(&service_error(500)));
+        assert!(!is_region_failure(&service_error(400)));
+    }
+
+    #[test]
+    fn test_throttled_transactions() {
+        let canceled = |codes: &[&str]| {
+            let reasons = codes
+                .iter()
+                .map(|code| CancellationReason::builder().code(*code).build())
+                .collect();
+            let exception = TransactionCanceledException::builder()
+                .set_cancellation_reasons(Some(reasons))
+                .build();
+            let response = HttpResponse::new(400.try_into().unwrap(), SdkBody::empty());
+            SdkError::service_error(
+                TransactWriteItemsError::TransactionCanceledException(exception),
+                response,
+            )
+        };
+        assert!(is_throttled_transaction(&canceled(&[
+            "None",
+            "ThrottlingError"
+        ])));
+        assert!(is_throttled_transaction(&canceled(&[
+            "ProvisionedThroughputExceeded"
+        ])));
+        assert!(!is_throttled_transaction(&canceled(&[
+            "ConditionalCheckFailed"
+        ])));
+        assert!(!is_throttled_transaction(&canceled(&[])));
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let retries = DynamoDbRetryConfig {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        };
+        assert_eq!(retries.backoff(0), Duration::from_millis(100));
+        assert_eq!(retries.backoff(1), Duration::from_millis(200));
+        assert_eq!(retries.backoff(3), Duration::from_millis(800));
+        assert_eq!(retries.backoff(4), Duration::from_secs(1));
+        assert_eq!(retries.backoff(100), Duration::from_secs(1));
+    }
+
     #[test]
     fn test_serialization_len() {
         for n in [0, 10, 127, 128, 129, 16383, 16384, 20000] {
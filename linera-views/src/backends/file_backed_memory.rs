@@ -0,0 +1,266 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements [`crate::store::KeyValueStore`] in memory, with the data saved to files so that
+//! it survives restarts. This is meant for development networks, which do not need a database.
+//!
+//! The data is saved in a snapshot file, and the batches written since the snapshot was taken
+//! are appended to a log file. When the log grows larger than the snapshot, it is compacted
+//! into a new snapshot.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write as _},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use thiserror::Error;
+
+use crate::{
+    batch::{Batch, WriteOperation},
+    memory::{MemoryStore, MemoryStoreError},
+    store::{KeyValueStoreError, ReadableKeyValueStore, WithError, WritableKeyValueStore},
+};
+
+/// The minimal size of the log, in bytes, before it is compacted into a new snapshot.
+const MIN_COMPACTION_SIZE: u64 = 1 << 20;
+
+/// A [`MemoryStore`] whose data is loaded from files when it is created, and saved to them
+/// after every write.
+#[derive(Clone)]
+pub struct FileBackedMemoryStore {
+    /// The store holding the data.
+    store: MemoryStore,
+    /// The files where the data is saved.
+    files: Arc<Mutex<StoreFiles>>,
+    /// Serializes the writes, so that the log has the batches in the order they are applied.
+    write_lock: Arc<async_lock::Mutex<()>>,
+}
+
+/// The snapshot and the log of a [`FileBackedMemoryStore`].
+struct StoreFiles {
+    /// The file with all the data of the last snapshot.
+    snapshot_path: PathBuf,
+    /// The size of the last snapshot.
+    snapshot_size: u64,
+    /// The log of the batches written since the last snapshot, opened for appending.
+    log: File,
+    /// The size of the log.
+    log_size: u64,
+}
+
+impl StoreFiles {
+    /// Opens the files at `path`, and returns them with the content of the snapshot and of
+    /// the log.
+    fn open(path: PathBuf) -> io::Result<(Self, Vec<u8>, Vec<u8>)> {
+        let snapshot = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error),
+        };
+        let log_path = path.with_extension("log");
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)?;
+        let log_content = fs::read(&log_path)?;
+        let files = StoreFiles {
+            snapshot_path: path,
+            snapshot_size: snapshot.len() as u64,
+            log,
+            log_size: log_content.len() as u64,
+        };
+        Ok((files, snapshot, log_content))
+    }
+
+    /// Appends a record to the log, and returns whether the log should now be compacted.
+    fn append(&mut self, record: &[u8]) -> io::Result<bool> {
+        let length = u32::try_from(record.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record too large"))?;
+        let mut bytes = length.to_le_bytes().to_vec();
+        bytes.extend_from_slice(record);
+        self.log.write_all(&bytes)?;
+        self.log_size += bytes.len() as u64;
+        Ok(self.log_size > self.snapshot_size.max(MIN_COMPACTION_SIZE))
+    }
+
+    /// Replaces the snapshot and empties the log. The snapshot is written to a temporary file
+    /// first, so that the previous data is not lost if the process is interrupted. If it is
+    /// interrupted before the log is emptied, replaying the log on top of the new snapshot
+    /// leads to the same data.
+    fn compact(&mut self, snapshot: &[u8]) -> io::Result<()> {
+        let temporary_path = self.snapshot_path.with_extension("tmp");
+        fs::write(&temporary_path, snapshot)?;
+        fs::rename(&temporary_path, &self.snapshot_path)?;
+        self.snapshot_size = snapshot.len() as u64;
+        self.log.set_len(0)?;
+        self.log_size = 0;
+        Ok(())
+    }
+}
+
+/// Splits the content of a log into its records. A record that is incomplete, because the
+/// process was interrupted while writing it, is ignored.
+fn log_records(mut log: &[u8]) -> Vec<&[u8]> {
+    let mut records = Vec::new();
+    while let Some((length, rest)) = log.split_first_chunk::<4>() {
+        let length = u32::from_le_bytes(*length) as usize;
+        if rest.len() < length {
+            break;
+        }
+        let (record, rest) = rest.split_at(length);
+        records.push(record);
+        log = rest;
+    }
+    records
+}
+
+impl FileBackedMemoryStore {
+    /// Creates a `FileBackedMemoryStore` from a number of queries and a namespace, with the
+    /// data saved at `path`, and the batches written since the last snapshot in the file with
+    /// the `log` extension. The data of the files is loaded if they already exist.
+    pub async fn new(
+        path: PathBuf,
+        max_stream_queries: usize,
+        namespace: &str,
+        root_key: &[u8],
+    ) -> Result<Self, FileBackedMemoryStoreError> {
+        let store = MemoryStore::new(max_stream_queries, namespace, root_key)?;
+        let (files, snapshot, log) =
+            tokio::task::spawn_blocking(move || StoreFiles::open(path)).await??;
+        if !snapshot.is_empty() {
+            let key_values = bcs::from_bytes::<Vec<(Vec<u8>, Vec<u8>)>>(&snapshot)?;
+            let mut batch = Batch::new();
+            for (key, value) in key_values {
+                batch.put_key_value_bytes(key, value);
+            }
+            store.write_batch(batch).await?;
+        }
+        for record in log_records(&log) {
+            let operations = bcs::from_bytes::<Vec<WriteOperation>>(record)?;
+            let batch = Batch {
+                operations,
+                ..Batch::default()
+            };
+            store.write_batch(batch).await?;
+        }
+        let store = Self {
+            store,
+            files: Arc::new(Mutex::new(files)),
+            write_lock: Arc::default(),
+        };
+        // The log may end with an incomplete record, so new records can't be appended to it.
+        store.compact().await?;
+        Ok(store)
+    }
+
+    /// Saves all the data of the store in a new snapshot, and empties the log.
+    async fn compact(&self) -> Result<(), FileBackedMemoryStoreError> {
+        let key_values = self.store.find_key_values_by_prefix(&[]).await?;
+        let snapshot = bcs::to_bytes(&key_values)?;
+        let files = self.files.clone();
+        tokio::task::spawn_blocking(move || files.lock().unwrap().compact(&snapshot)).await??;
+        Ok(())
+    }
+}
+
+impl WithError for FileBackedMemoryStore {
+    type Error = FileBackedMemoryStoreError;
+}
+
+impl ReadableKeyValueStore for FileBackedMemoryStore {
+    const MAX_KEY_SIZE: usize = MemoryStore::MAX_KEY_SIZE;
+    type Keys = Vec<Vec<u8>>;
+    type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+
+    fn max_stream_queries(&self) -> usize {
+        self.store.max_stream_queries()
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self.store.read_value_bytes(key).await?)
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        Ok(self.store.contains_key(key).await?)
+    }
+
+    async fn contains_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<bool>, Self::Error> {
+        Ok(self.store.contains_keys(keys).await?)
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        Ok(self.store.read_multi_values_bytes(keys).await?)
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Self::Keys, Self::Error> {
+        Ok(self.store.find_keys_by_prefix(key_prefix).await?)
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, Self::Error> {
+        Ok(self.store.find_key_values_by_prefix(key_prefix).await?)
+    }
+}
+
+impl WritableKeyValueStore for FileBackedMemoryStore {
+    const MAX_VALUE_SIZE: usize = MemoryStore::MAX_VALUE_SIZE;
+
+    async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        let _guard = self.write_lock.lock().await;
+        let record = bcs::to_bytes(&batch.operations)?;
+        self.store.write_batch(batch).await?;
+        let files = self.files.clone();
+        let needs_compaction =
+            tokio::task::spawn_blocking(move || files.lock().unwrap().append(&record)).await??;
+        if needs_compaction {
+            self.compact().await?;
+        }
+        Ok(())
+    }
+
+    async fn clear_journal(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn supports_conditional_writes(&self) -> bool {
+        self.store.supports_conditional_writes()
+    }
+}
+
+/// The error type for [`FileBackedMemoryStore`].
+#[derive(Error, Debug)]
+pub enum FileBackedMemoryStoreError {
+    /// An error from the memory store.
+    #[error(transparent)]
+    MemoryStoreError(#[from] MemoryStoreError),
+
+    /// Serialization error with BCS.
+    #[error(transparent)]
+    BcsError(#[from] bcs::Error),
+
+    /// The files of the store could not be read or written.
+    #[error("Failed to access the files of the store: {0}")]
+    IoError(#[from] io::Error),
+
+    /// The task accessing the files of the store failed.
+    #[error(transparent)]
+    JoinError(#[from] tokio::task::JoinError),
+}
+
+impl KeyValueStoreError for FileBackedMemoryStoreError {
+    const BACKEND: &'static str = "file_backed_memory";
+
+    fn write_conflict(&self) -> Option<&[u8]> {
+        match self {
+            FileBackedMemoryStoreError::MemoryStoreError(error) => error.write_conflict(),
+            _ => None,
+        }
+    }
+}
@@ -20,12 +20,13 @@
 //! transaction to mark the block as processed.
 
 use async_trait::async_trait;
+use linera_base::ensure;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use static_assertions as sa;
 use thiserror::Error;
 
 use crate::{
-    batch::{Batch, BatchValueWriter, DeletePrefixExpander, SimplifiedBatch},
+    batch::{Batch, BatchValueWriter, DeletePrefixExpander, SimplifiedBatch, WriteCondition},
     store::{
         AdminKeyValueStore, KeyIterable, ReadableKeyValueStore, WithError, WritableKeyValueStore,
     },
@@ -44,6 +45,10 @@ pub enum JournalConsistencyError {
     /// The journal block could not be retrieved, it could be missing or corrupted
     #[error("the journal block could not be retrieved, it could be missing or corrupted")]
     FailureToRetrieveJournalBlock,
+
+    /// The batch has conditions, but it can't be written in a single transaction
+    #[error("the batch has conditions, but it can't be written in a single transaction")]
+    ConditionsNotSupported,
 }
 
 #[repr(u8)]
@@ -78,6 +83,26 @@ pub trait DirectWritableKeyValueStore: WithError {
 
     /// Writes the batch to the database.
     async fn write_batch(&self, batch: Self::Batch) -> Result<(), Self::Error>;
+
+    /// Returns whether [`Self::write_batch_with_conditions`] checks the conditions in the
+    /// same transaction as the writes.
+    fn supports_conditional_writes(&self) -> bool {
+        false
+    }
+
+    /// Writes the batch to the database in a single transaction, if the values in the
+    /// database satisfy the `conditions`.
+    async fn write_batch_with_conditions(
+        &self,
+        batch: Self::Batch,
+        conditions: Vec<WriteCondition>,
+    ) -> Result<(), Self::Error> {
+        assert!(
+            conditions.is_empty(),
+            "the store doesn't support conditional writes"
+        );
+        self.write_batch(batch).await
+    }
 }
 
 /// Low-level, asynchronous direct read/write key-value operations with simplified batch
@@ -170,6 +195,28 @@ where
     ) -> Result<Self::KeyValues, Self::Error> {
         self.store.find_key_values_by_prefix(key_prefix).await
     }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.store
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
+
+    async fn find_key_values_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        self.store
+            .find_key_values_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
 }
 
 impl<K> AdminKeyValueStore for JournalingKeyValueStore<K>
@@ -225,8 +272,21 @@ where
     /// The size constant do not change
     const MAX_VALUE_SIZE: usize = K::MAX_VALUE_SIZE;
 
-    async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+    async fn write_batch(&self, mut batch: Batch) -> Result<(), Self::Error> {
+        let conditions = std::mem::take(&mut batch.conditions);
         let batch = K::Batch::from_batch(self, batch).await?;
+        if !conditions.is_empty() {
+            // The conditions can only be checked if the batch is written in one transaction.
+            ensure!(
+                self.store.supports_conditional_writes()
+                    && Self::is_conditional_fastpath_feasible(&batch, &conditions),
+                JournalConsistencyError::ConditionsNotSupported
+            );
+            return self
+                .store
+                .write_batch_with_conditions(batch, conditions)
+                .await;
+        }
         if Self::is_fastpath_feasible(&batch) {
             self.store.write_batch(batch).await
         } else {
@@ -235,6 +295,10 @@ where
         }
     }
 
+    fn supports_conditional_writes(&self) -> bool {
+        self.store.supports_conditional_writes()
+    }
+
     async fn clear_journal(&self) -> Result<(), Self::Error> {
         let key = get_journaling_key(KeyTag::Journal as u8, 0)?;
         let value = self.read_value::<JournalHeader>(&key).await?;
@@ -398,6 +462,19 @@ where
     fn is_fastpath_feasible(batch: &K::Batch) -> bool {
         batch.len() <= K::MAX_BATCH_SIZE && batch.num_bytes() <= K::MAX_BATCH_TOTAL_SIZE
     }
+
+    /// Returns whether the batch and the checks of its conditions fit in one transaction.
+    fn is_conditional_fastpath_feasible(batch: &K::Batch, conditions: &[WriteCondition]) -> bool {
+        let condition_bytes = conditions
+            .iter()
+            .map(|condition| {
+                let values = condition.allowed_values.iter().flatten();
+                condition.key.len() + values.map(Vec::len).sum::<usize>()
+            })
+            .sum::<usize>();
+        batch.len() + conditions.len() <= K::MAX_BATCH_SIZE
+            && batch.num_bytes() + condition_bytes <= K::MAX_BATCH_TOTAL_SIZE
+    }
 }
 
 impl<K> JournalingKeyValueStore<K> {
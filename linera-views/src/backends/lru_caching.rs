@@ -236,6 +236,28 @@ where
     ) -> Result<Self::KeyValues, Self::Error> {
         self.store.find_key_values_by_prefix(key_prefix).await
     }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.store
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
+
+    async fn find_key_values_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        self.store
+            .find_key_values_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
 }
 
 impl<K> WritableKeyValueStore for LruCachingStore<K>
@@ -249,29 +271,24 @@ where
         let Some(lru_read_values) = &self.lru_read_values else {
             return self.store.write_batch(batch).await;
         };
-
-        {
-            let mut lru_read_values = lru_read_values.lock().unwrap();
-            for operation in &batch.operations {
-                match operation {
-                    WriteOperation::Put { key, value } => {
-                        lru_read_values.insert(key.to_vec(), Some(value.to_vec()));
-                    }
-                    WriteOperation::Delete { key } => {
-                        lru_read_values.insert(key.to_vec(), None);
-                    }
-                    WriteOperation::DeletePrefix { key_prefix } => {
-                        lru_read_values.delete_prefix(key_prefix);
-                    }
-                }
-            }
+        if !batch.conditions.is_empty() {
+            // The batch may not be written, so the cache is only updated afterwards.
+            let operations = batch.operations.clone();
+            self.store.write_batch(batch).await?;
+            Self::update_cache(lru_read_values, &operations);
+            return Ok(());
         }
+        Self::update_cache(lru_read_values, &batch.operations);
         self.store.write_batch(batch).await
     }
 
     async fn clear_journal(&self) -> Result<(), Self::Error> {
         self.store.clear_journal().await
     }
+
+    fn supports_conditional_writes(&self) -> bool {
+        self.store.supports_conditional_writes()
+    }
 }
 
 /// The configuration type for the `LruCachingStore`.
@@ -362,6 +379,29 @@ impl<K> LruCachingStore<K> {
         }
     }
 
+    /// Returns the underlying store.
+    pub fn inner(&self) -> &K {
+        &self.store
+    }
+
+    /// Applies the write `operations` to the cached values.
+    fn update_cache(lru_read_values: &Mutex<LruPrefixCache>, operations: &[WriteOperation]) {
+        let mut lru_read_values = lru_read_values.lock().unwrap();
+        for operation in operations {
+            match operation {
+                WriteOperation::Put { key, value } => {
+                    lru_read_values.insert(key.to_vec(), Some(value.to_vec()));
+                }
+                WriteOperation::Delete { key } => {
+                    lru_read_values.insert(key.to_vec(), None);
+                }
+                WriteOperation::DeletePrefix { key_prefix } => {
+                    lru_read_values.delete_prefix(key_prefix);
+                }
+            }
+        }
+    }
+
     /// Gets the `cache_size`
     pub fn cache_size(&self) -> usize {
         match &self.lru_read_values {
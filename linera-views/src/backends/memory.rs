@@ -227,6 +227,14 @@ impl WritableKeyValueStore for MemoryStore {
             .map
             .write()
             .expect("MemoryStore lock should not be poisoned");
+        for condition in &batch.conditions {
+            if !condition
+                .allowed_values
+                .contains(&map.get(&condition.key).cloned())
+            {
+                return Err(MemoryStoreError::WriteConflict(condition.key.clone()));
+            }
+        }
         for ent in batch.operations {
             match ent {
                 WriteOperation::Put { key, value } => {
@@ -252,6 +260,10 @@ impl WritableKeyValueStore for MemoryStore {
     async fn clear_journal(&self) -> Result<(), MemoryStoreError> {
         Ok(())
     }
+
+    fn supports_conditional_writes(&self) -> bool {
+        true
+    }
 }
 
 impl MemoryStore {
@@ -400,8 +412,19 @@ pub enum MemoryStoreError {
     /// The namespace does not exist
     #[error("The namespace does not exist")]
     NamespaceNotFound,
+
+    /// The value of a key doesn't satisfy a condition of the batch
+    #[error("The value of key {0:?} doesn't satisfy a condition of the batch")]
+    WriteConflict(Vec<u8>),
 }
 
 impl KeyValueStoreError for MemoryStoreError {
     const BACKEND: &'static str = "memory";
+
+    fn write_conflict(&self) -> Option<&[u8]> {
+        match self {
+            MemoryStoreError::WriteConflict(key) => Some(key),
+            _ => None,
+        }
+    }
 }
@@ -270,6 +270,13 @@ pub struct MeteredStore<K> {
     store: K,
 }
 
+impl<K> MeteredStore<K> {
+    /// Returns the underlying store.
+    pub fn inner(&self) -> &K {
+        &self.store
+    }
+}
+
 impl<K> WithError for MeteredStore<K>
 where
     K: WithError,
@@ -407,6 +414,66 @@ where
             .observe(key_values_size as f64);
         Ok(result)
     }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let _latency = self.counter.find_keys_by_prefix_latency.measure_latency();
+        self.counter
+            .find_keys_by_prefix_prefix_size
+            .with_label_values(&[])
+            .observe(key_prefix.len() as f64);
+        let result = self
+            .store
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await?;
+        let keys_size = result.iter().map(Vec::len).sum::<usize>();
+        self.counter
+            .find_keys_by_prefix_num_keys
+            .with_label_values(&[])
+            .observe(result.len() as f64);
+        self.counter
+            .find_keys_by_prefix_keys_size
+            .with_label_values(&[])
+            .observe(keys_size as f64);
+        Ok(result)
+    }
+
+    async fn find_key_values_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        let _latency = self
+            .counter
+            .find_key_values_by_prefix_latency
+            .measure_latency();
+        self.counter
+            .find_key_values_by_prefix_prefix_size
+            .with_label_values(&[])
+            .observe(key_prefix.len() as f64);
+        let result = self
+            .store
+            .find_key_values_by_prefix_after(key_prefix, start_after, limit)
+            .await?;
+        let key_values_size = result
+            .iter()
+            .map(|(key, value)| key.len() + value.len())
+            .sum::<usize>();
+        self.counter
+            .find_key_values_by_prefix_num_keys
+            .with_label_values(&[])
+            .observe(result.len() as f64);
+        self.counter
+            .find_key_values_by_prefix_key_values_size
+            .with_label_values(&[])
+            .observe(key_values_size as f64);
+        Ok(result)
+    }
 }
 
 impl<K> WritableKeyValueStore for MeteredStore<K>
@@ -428,6 +495,10 @@ where
         let _metric = self.counter.clear_journal_latency.measure_latency();
         self.store.clear_journal().await
     }
+
+    fn supports_conditional_writes(&self) -> bool {
+        self.store.supports_conditional_writes()
+    }
 }
 
 impl<K> AdminKeyValueStore for MeteredStore<K>
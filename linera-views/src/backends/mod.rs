@@ -10,6 +10,9 @@ pub mod value_splitting;
 
 pub mod memory;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod file_backed_memory;
+
 pub mod lru_caching;
 
 pub mod dual;
@@ -2,6 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Implements [`crate::store::KeyValueStore`] for the RocksDB database.
+//!
+//! The searches by key prefix iterate over a snapshot of the database, so that they are not
+//! affected by the batches written at the same time. Several reads can also be made consistent
+//! with each other by using a [`RocksDbSnapshot`].
 
 use std::{
     ffi::OsString,
@@ -44,6 +48,19 @@ const MAX_KEY_SIZE: usize = 8388208;
 /// The RocksDB client that we use.
 type DB = rocksdb::DBWithThreadMode<rocksdb::MultiThreaded>;
 
+/// A snapshot of the RocksDB database.
+type DbSnapshot<'a> = rocksdb::SnapshotWithThreadMode<'a, DB>;
+
+self_cell::self_cell!(
+    /// A snapshot of the RocksDB database, together with the database that it refers to.
+    struct OwnedSnapshot {
+        owner: Arc<DB>,
+
+        #[covariant]
+        dependent: DbSnapshot,
+    }
+);
+
 /// The choice of the spawning mode.
 /// `SpawnBlocking` always works and is the safest.
 /// `BlockInPlace` can only be used in multi-threaded environment.
@@ -96,10 +113,46 @@ fn check_key_size(key: &[u8]) -> Result<(), RocksDbStoreInternalError> {
 #[derive(Clone)]
 struct RocksDbStoreExecutor {
     db: Arc<DB>,
+    /// The snapshot used by all the reads, if any.
+    snapshot: Option<Arc<OwnedSnapshot>>,
     root_key: Vec<u8>,
 }
 
 impl RocksDbStoreExecutor {
+    /// Returns the options of the reads, which use the snapshot of the executor if there is
+    /// one.
+    fn read_options(&self) -> rocksdb::ReadOptions {
+        let mut options = rocksdb::ReadOptions::default();
+        if let Some(snapshot) = &self.snapshot {
+            options.set_snapshot(snapshot.borrow_dependent());
+        }
+        options
+    }
+
+    /// Runs `f` with the snapshot of the executor if there is one, or with a new snapshot of
+    /// the database.
+    fn with_snapshot<T>(&self, f: impl FnOnce(&DbSnapshot<'_>) -> T) -> T {
+        match &self.snapshot {
+            Some(snapshot) => f(snapshot.borrow_dependent()),
+            None => f(&self.db.snapshot()),
+        }
+    }
+
+    fn read_value_bytes_internal(
+        &self,
+        full_key: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, RocksDbStoreInternalError> {
+        Ok(self.db.get_opt(&full_key, &self.read_options())?)
+    }
+
+    fn contains_key_internal(&self, full_key: Vec<u8>) -> Result<bool, RocksDbStoreInternalError> {
+        let options = self.read_options();
+        if !self.db.key_may_exist_opt(&full_key, &options) {
+            return Ok(false);
+        }
+        Ok(self.db.get_opt(&full_key, &options)?.is_some())
+    }
+
     pub fn contains_keys_internal(
         &self,
         keys: Vec<Vec<u8>>,
@@ -108,16 +161,17 @@ impl RocksDbStoreExecutor {
         let mut results = vec![false; size];
         let mut indices = Vec::new();
         let mut keys_red = Vec::new();
+        let options = self.read_options();
         for (i, key) in keys.into_iter().enumerate() {
             check_key_size(&key)?;
             let mut full_key = self.root_key.to_vec();
             full_key.extend(key);
-            if self.db.key_may_exist(&full_key) {
+            if self.db.key_may_exist_opt(&full_key, &options) {
                 indices.push(i);
                 keys_red.push(full_key);
             }
         }
-        let values_red = self.db.multi_get(keys_red);
+        let values_red = self.db.multi_get_opt(keys_red, &options);
         for (index, value) in indices.into_iter().zip(values_red) {
             results[index] = value?.is_some();
         }
@@ -139,61 +193,96 @@ impl RocksDbStoreExecutor {
                 full_key
             })
             .collect::<Vec<_>>();
-        let entries = self.db.multi_get(&full_keys);
+        let entries = self.db.multi_get_opt(&full_keys, &self.read_options());
         Ok(entries.into_iter().collect::<Result<_, _>>()?)
     }
 
+    /// Creates an iterator over `snapshot` positioned on the first key matching `prefix` that
+    /// comes strictly after `start_after`, or on the first key matching `prefix` if there is
+    /// none.
+    fn seek_after<'a>(
+        snapshot: &'a DbSnapshot<'_>,
+        prefix: &[u8],
+        start_after: Option<Vec<u8>>,
+    ) -> rocksdb::DBRawIteratorWithThreadMode<'a, DB> {
+        let mut iter = snapshot.raw_iterator();
+        match start_after {
+            None => iter.seek(prefix),
+            Some(start_after) => {
+                let mut start_key = prefix.to_vec();
+                start_key.extend(start_after);
+                iter.seek(&start_key);
+                if iter.key() == Some(&start_key[..]) {
+                    iter.next();
+                }
+            }
+        }
+        iter
+    }
+
     fn find_keys_by_prefix_internal(
         &self,
-        key_prefix: Vec<u8>,
+        (key_prefix, start_after, limit): (Vec<u8>, Option<Vec<u8>>, Option<usize>),
     ) -> Result<Vec<Vec<u8>>, RocksDbStoreInternalError> {
         check_key_size(&key_prefix)?;
         let mut prefix = self.root_key.clone();
         prefix.extend(key_prefix);
         let len = prefix.len();
-        let mut iter = self.db.raw_iterator();
-        let mut keys = Vec::new();
-        iter.seek(&prefix);
-        let mut next_key = iter.key();
-        while let Some(key) = next_key {
-            if !key.starts_with(&prefix) {
-                break;
+        self.with_snapshot(|snapshot| {
+            let mut iter = Self::seek_after(snapshot, &prefix, start_after);
+            let mut keys = Vec::new();
+            let mut next_key = iter.key();
+            while let Some(key) = next_key {
+                if !key.starts_with(&prefix) || limit.is_some_and(|limit| keys.len() >= limit) {
+                    break;
+                }
+                keys.push(key[len..].to_vec());
+                iter.next();
+                next_key = iter.key();
             }
-            keys.push(key[len..].to_vec());
-            iter.next();
-            next_key = iter.key();
-        }
-        Ok(keys)
+            Ok(keys)
+        })
     }
 
     #[allow(clippy::type_complexity)]
     fn find_key_values_by_prefix_internal(
         &self,
-        key_prefix: Vec<u8>,
+        (key_prefix, start_after, limit): (Vec<u8>, Option<Vec<u8>>, Option<usize>),
     ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, RocksDbStoreInternalError> {
         check_key_size(&key_prefix)?;
         let mut prefix = self.root_key.clone();
         prefix.extend(key_prefix);
         let len = prefix.len();
-        let mut iter = self.db.raw_iterator();
-        let mut key_values = Vec::new();
-        iter.seek(&prefix);
-        let mut next_key = iter.key();
-        while let Some(key) = next_key {
-            if !key.starts_with(&prefix) {
-                break;
-            }
-            if let Some(value) = iter.value() {
-                let key_value = (key[len..].to_vec(), value.to_vec());
-                key_values.push(key_value);
+        self.with_snapshot(|snapshot| {
+            let mut iter = Self::seek_after(snapshot, &prefix, start_after);
+            let mut key_values = Vec::new();
+            let mut next_key = iter.key();
+            while let Some(key) = next_key {
+                if !key.starts_with(&prefix) || limit.is_some_and(|limit| key_values.len() >= limit)
+                {
+                    break;
+                }
+                if let Some(value) = iter.value() {
+                    let key_value = (key[len..].to_vec(), value.to_vec());
+                    key_values.push(key_value);
+                }
+                iter.next();
+                next_key = iter.key();
             }
-            iter.next();
-            next_key = iter.key();
-        }
-        Ok(key_values)
+            Ok(key_values)
+        })
     }
 
     fn write_batch_internal(&self, mut batch: Batch) -> Result<(), RocksDbStoreInternalError> {
+        for condition in &batch.conditions {
+            let mut full_key = self.root_key.clone();
+            full_key.extend(&condition.key);
+            if !condition.allowed_values.contains(&self.db.get(&full_key)?) {
+                return Err(RocksDbStoreInternalError::WriteConflict(
+                    condition.key.clone(),
+                ));
+            }
+        }
         // NOTE: The delete_range functionality of RocksDB needs to have an upper bound in order to work.
         // Thus in order to have the system working, we need to handle the unlikely case of having to
         // delete a key starting with [255, ...., 255]
@@ -203,7 +292,9 @@ impl RocksDbStoreExecutor {
             let op = batch.operations.get(i).unwrap();
             if let WriteOperation::DeletePrefix { key_prefix } = op {
                 if get_upper_bound(key_prefix) == Bound::Unbounded {
-                    for short_key in self.find_keys_by_prefix_internal(key_prefix.to_vec())? {
+                    for short_key in
+                        self.find_keys_by_prefix_internal((key_prefix.to_vec(), None, None))?
+                    {
                         let mut full_key = self.root_key.clone();
                         full_key.extend(key_prefix);
                         full_key.extend(short_key);
@@ -251,6 +342,9 @@ impl RocksDbStoreExecutor {
 #[derive(Clone)]
 pub struct RocksDbStoreInternal {
     executor: RocksDbStoreExecutor,
+    /// The lock held to write a batch, exclusively if the batch has conditions. RocksDB locks
+    /// the database for the process that opened it, so this excludes all the other writers.
+    write_lock: Arc<tokio::sync::RwLock<()>>,
     _path_with_guard: PathWithGuard,
     max_stream_queries: usize,
     spawn_mode: RocksDbSpawnMode,
@@ -294,15 +388,29 @@ impl RocksDbStoreInternal {
         let root_key = root_key.to_vec();
         let executor = RocksDbStoreExecutor {
             db: Arc::new(db),
+            snapshot: None,
             root_key,
         };
         Ok(RocksDbStoreInternal {
             executor,
+            write_lock: Arc::default(),
             _path_with_guard: path_with_guard,
             max_stream_queries,
             spawn_mode,
         })
     }
+
+    /// Returns a read-only view of the store as it is now. The writes made to the store
+    /// afterwards are not visible in the snapshot.
+    pub fn snapshot(&self) -> RocksDbSnapshot {
+        let mut store = self.clone();
+        if store.executor.snapshot.is_none() {
+            let db = store.executor.db.clone();
+            let snapshot = OwnedSnapshot::new(db, |db| db.snapshot());
+            store.executor.snapshot = Some(Arc::new(snapshot));
+        }
+        RocksDbSnapshot { store }
+    }
 }
 
 impl WithError for RocksDbStoreInternal {
@@ -323,29 +431,21 @@ impl ReadableKeyValueStore for RocksDbStoreInternal {
         key: &[u8],
     ) -> Result<Option<Vec<u8>>, RocksDbStoreInternalError> {
         check_key_size(key)?;
-        let db = self.executor.db.clone();
+        let executor = self.executor.clone();
         let mut full_key = self.executor.root_key.to_vec();
         full_key.extend(key);
         self.spawn_mode
-            .spawn(move |x| Ok(db.get(&x)?), full_key)
+            .spawn(move |x| executor.read_value_bytes_internal(x), full_key)
             .await
     }
 
     async fn contains_key(&self, key: &[u8]) -> Result<bool, RocksDbStoreInternalError> {
         check_key_size(key)?;
-        let db = self.executor.db.clone();
+        let executor = self.executor.clone();
         let mut full_key = self.executor.root_key.to_vec();
         full_key.extend(key);
         self.spawn_mode
-            .spawn(
-                move |x| {
-                    if !db.key_may_exist(&x) {
-                        return Ok(false);
-                    }
-                    Ok(db.get(&x)?.is_some())
-                },
-                full_key,
-            )
+            .spawn(move |x| executor.contains_key_internal(x), full_key)
             .await
     }
 
@@ -373,35 +473,140 @@ impl ReadableKeyValueStore for RocksDbStoreInternal {
         &self,
         key_prefix: &[u8],
     ) -> Result<Self::Keys, RocksDbStoreInternalError> {
+        self.find_keys_by_prefix_after(key_prefix, None, None).await
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, RocksDbStoreInternalError> {
+        self.find_key_values_by_prefix_after(key_prefix, None, None)
+            .await
+    }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, RocksDbStoreInternalError> {
         let executor = self.executor.clone();
-        let key_prefix = key_prefix.to_vec();
+        let input = (key_prefix.to_vec(), start_after.map(<[u8]>::to_vec), limit);
         self.spawn_mode
-            .spawn(
-                move |x| executor.find_keys_by_prefix_internal(x),
-                key_prefix,
-            )
+            .spawn(move |x| executor.find_keys_by_prefix_internal(x), input)
             .await
     }
 
-    async fn find_key_values_by_prefix(
+    async fn find_key_values_by_prefix_after(
         &self,
         key_prefix: &[u8],
-    ) -> Result<Self::KeyValues, RocksDbStoreInternalError> {
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, RocksDbStoreInternalError> {
         let executor = self.executor.clone();
-        let key_prefix = key_prefix.to_vec();
+        let input = (key_prefix.to_vec(), start_after.map(<[u8]>::to_vec), limit);
         self.spawn_mode
             .spawn(
                 move |x| executor.find_key_values_by_prefix_internal(x),
-                key_prefix,
+                input,
             )
             .await
     }
 }
 
+/// A read-only view of a [`RocksDbStoreInternal`] at the time the snapshot was taken. All the
+/// reads of the snapshot are consistent with each other.
+#[derive(Clone)]
+pub struct RocksDbSnapshot {
+    store: RocksDbStoreInternal,
+}
+
+impl WithError for RocksDbSnapshot {
+    type Error = RocksDbStoreInternalError;
+}
+
+impl ReadableKeyValueStore for RocksDbSnapshot {
+    const MAX_KEY_SIZE: usize = MAX_KEY_SIZE;
+    type Keys = Vec<Vec<u8>>;
+    type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+
+    fn max_stream_queries(&self) -> usize {
+        self.store.max_stream_queries()
+    }
+
+    async fn read_value_bytes(
+        &self,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, RocksDbStoreInternalError> {
+        self.store.read_value_bytes(key).await
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, RocksDbStoreInternalError> {
+        self.store.contains_key(key).await
+    }
+
+    async fn contains_keys(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<bool>, RocksDbStoreInternalError> {
+        self.store.contains_keys(keys).await
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, RocksDbStoreInternalError> {
+        self.store.read_multi_values_bytes(keys).await
+    }
+
+    async fn find_keys_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::Keys, RocksDbStoreInternalError> {
+        self.store.find_keys_by_prefix(key_prefix).await
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, RocksDbStoreInternalError> {
+        self.store.find_key_values_by_prefix(key_prefix).await
+    }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, RocksDbStoreInternalError> {
+        self.store
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
+
+    async fn find_key_values_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, RocksDbStoreInternalError> {
+        self.store
+            .find_key_values_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
+}
+
 impl WritableKeyValueStore for RocksDbStoreInternal {
     const MAX_VALUE_SIZE: usize = MAX_VALUE_SIZE;
 
     async fn write_batch(&self, batch: Batch) -> Result<(), RocksDbStoreInternalError> {
+        let _shared_guard;
+        let _exclusive_guard;
+        if batch.conditions.is_empty() {
+            _shared_guard = self.write_lock.read().await;
+        } else {
+            _exclusive_guard = self.write_lock.write().await;
+        }
         let executor = self.executor.clone();
         self.spawn_mode
             .spawn(move |x| executor.write_batch_internal(x), batch)
@@ -411,6 +616,10 @@ impl WritableKeyValueStore for RocksDbStoreInternal {
     async fn clear_journal(&self) -> Result<(), RocksDbStoreInternalError> {
         Ok(())
     }
+
+    fn supports_conditional_writes(&self) -> bool {
+        true
+    }
 }
 
 impl AdminKeyValueStore for RocksDbStoreInternal {
@@ -556,6 +765,10 @@ pub enum RocksDbStoreInternalError {
     /// BCS serialization error.
     #[error(transparent)]
     BcsError(#[from] bcs::Error),
+
+    /// The value of a key doesn't satisfy a condition of the batch
+    #[error("The value of key {0:?} doesn't satisfy a condition of the batch")]
+    WriteConflict(Vec<u8>),
 }
 
 /// A path and the guard for the temporary directory if needed
@@ -588,6 +801,13 @@ impl PathWithGuard {
 
 impl KeyValueStoreError for RocksDbStoreInternalError {
     const BACKEND: &'static str = "rocks_db";
+
+    fn write_conflict(&self) -> Option<&[u8]> {
+        match self {
+            RocksDbStoreInternalError::WriteConflict(key) => Some(key),
+            _ => None,
+        }
+    }
 }
 
 /// The `RocksDbStore` composed type with metrics
@@ -606,6 +826,63 @@ pub type RocksDbStoreError = ValueSplittingError<RocksDbStoreInternalError>;
 /// The composed config type for the `RocksDbStore`
 pub type RocksDbStoreConfig = LruCachingConfig<RocksDbStoreInternalConfig>;
 
+/// The composed snapshot type of the `RocksDbStore`, which bypasses the LRU cache
+pub type RocksDbStoreSnapshot = ValueSplittingStore<RocksDbSnapshot>;
+
+impl RocksDbStore {
+    /// Returns a read-only view of the store as it is now.
+    pub fn snapshot(&self) -> RocksDbStoreSnapshot {
+        self.read_snapshot()
+    }
+}
+
+/// A store built on top of a [`RocksDbStoreInternal`], from which snapshots can be taken.
+trait RocksDbSnapshotSource {
+    /// The read-only view of the store.
+    type Snapshot;
+
+    /// Returns a read-only view of the store as it is now.
+    fn read_snapshot(&self) -> Self::Snapshot;
+}
+
+impl RocksDbSnapshotSource for RocksDbStoreInternal {
+    type Snapshot = RocksDbSnapshot;
+
+    fn read_snapshot(&self) -> RocksDbSnapshot {
+        self.snapshot()
+    }
+}
+
+#[cfg(with_metrics)]
+impl<K: RocksDbSnapshotSource> RocksDbSnapshotSource for MeteredStore<K> {
+    type Snapshot = K::Snapshot;
+
+    fn read_snapshot(&self) -> K::Snapshot {
+        self.inner().read_snapshot()
+    }
+}
+
+/// The snapshots bypass the LRU cache, which holds the latest values.
+impl<K: RocksDbSnapshotSource> RocksDbSnapshotSource for LruCachingStore<K> {
+    type Snapshot = K::Snapshot;
+
+    fn read_snapshot(&self) -> K::Snapshot {
+        self.inner().read_snapshot()
+    }
+}
+
+impl<K> RocksDbSnapshotSource for ValueSplittingStore<K>
+where
+    K: RocksDbSnapshotSource + WithError,
+    K::Snapshot: WithError,
+{
+    type Snapshot = ValueSplittingStore<K::Snapshot>;
+
+    fn read_snapshot(&self) -> Self::Snapshot {
+        ValueSplittingStore::new(self.inner().read_snapshot())
+    }
+}
+
 impl RocksDbStoreConfig {
     /// Creates a new `RocksDbStoreConfig` from the input.
     pub fn new(
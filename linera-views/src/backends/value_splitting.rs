@@ -38,6 +38,10 @@ pub enum ValueSplittingError<E> {
     /// no count of size u32 is available in the value
     #[error("no count of size u32 is available in the value")]
     NoCountAvailable,
+
+    /// the value of a condition doesn't fit in a single segment
+    #[error("the value of a condition doesn't fit in a single segment")]
+    TooLargeConditionValue,
 }
 
 impl<E: KeyValueStoreError> From<bcs::Error> for ValueSplittingError<E> {
@@ -49,6 +53,17 @@ impl<E: KeyValueStoreError> From<bcs::Error> for ValueSplittingError<E> {
 
 impl<E: KeyValueStoreError + 'static> KeyValueStoreError for ValueSplittingError<E> {
     const BACKEND: &'static str = "value splitting";
+
+    fn write_conflict(&self) -> Option<&[u8]> {
+        match self {
+            ValueSplittingError::InnerStoreError(error) => {
+                // The conditions are on the first segments of the values.
+                let key = error.write_conflict()?;
+                key.get(..key.len().checked_sub(4)?)
+            }
+            _ => None,
+        }
+    }
 }
 
 /// A key-value store with no size limit for values.
@@ -204,32 +219,77 @@ where
         key_prefix: &[u8],
     ) -> Result<Self::KeyValues, Self::Error> {
         let small_key_values = self.store.find_key_values_by_prefix(key_prefix).await?;
-        let mut small_kv_iterator = small_key_values.into_iterator_owned();
-        let mut key_values = Vec::new();
-        while let Some(result) = small_kv_iterator.next() {
-            let (mut big_key, value) = result?;
-            if Self::read_index_from_key(&big_key)? != 0 {
-                continue; // Leftover segment from an earlier value.
+        Self::join_segments(small_key_values.into_iterator_owned())
+    }
+
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        // The first segment of a key comes after all the segments of the smaller keys, so
+        // we can resume the search of the inner store from it.
+        let mut start_after = start_after
+            .map(|key| Self::get_segment_key(key, 0))
+            .transpose()?;
+        let mut keys = Vec::new();
+        loop {
+            // The inner store also returns the other segments, so the keys of a page may not
+            // fill the limit.
+            let page_limit = limit.map(|limit| limit - keys.len());
+            let mut big_keys = self
+                .store
+                .find_keys_by_prefix_after(key_prefix, start_after.as_deref(), page_limit)
+                .await?;
+            let is_last_page = page_limit.map_or(true, |page_limit| big_keys.len() < page_limit);
+            for big_key in &big_keys {
+                if Self::read_index_from_key(big_key)? == 0 {
+                    keys.push(big_key[0..big_key.len() - 4].to_vec());
+                }
             }
-            big_key.truncate(big_key.len() - 4);
-            let key = big_key;
-            let count = Self::read_count_from_value(&value)?;
-            let mut big_value = value[4..].to_vec();
-            for idx in 1..count {
-                let (big_key, value) = small_kv_iterator
-                    .next()
-                    .ok_or(ValueSplittingError::MissingSegment)??;
-                ensure!(
-                    Self::read_index_from_key(&big_key)? == idx
-                        && big_key.starts_with(&key)
-                        && big_key.len() == key.len() + 4,
-                    ValueSplittingError::MissingSegment
-                );
-                big_value.extend(value);
+            if is_last_page || limit.is_some_and(|limit| keys.len() >= limit) {
+                return Ok(keys);
             }
-            key_values.push((key, big_value));
+            start_after = big_keys.pop();
         }
-        Ok(key_values)
+    }
+
+    async fn find_key_values_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        if limit.is_none() {
+            let start_after = start_after
+                .map(|key| Self::get_segment_key(key, 0))
+                .transpose()?;
+            let small_key_values = self
+                .store
+                .find_key_values_by_prefix_after(key_prefix, start_after.as_deref(), None)
+                .await?;
+            return Self::join_segments(small_key_values.into_iter().map(Ok));
+        }
+        // A page may end in the middle of the segments of a value, so we read the values of
+        // the keys of the page separately.
+        let keys = self
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await?;
+        let full_keys = keys
+            .iter()
+            .map(|key| {
+                let mut full_key = key_prefix.to_vec();
+                full_key.extend(key);
+                full_key
+            })
+            .collect();
+        let values = self.read_multi_values_bytes(full_keys).await?;
+        Ok(keys
+            .into_iter()
+            .zip(values)
+            .filter_map(|(key, value)| Some((key, value?)))
+            .collect())
     }
 }
 
@@ -242,6 +302,24 @@ where
 
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
         let mut batch_new = Batch::new();
+        for condition in batch.conditions {
+            let big_key = Self::get_segment_key(&condition.key, 0)?;
+            let allowed_values = condition
+                .allowed_values
+                .into_iter()
+                .map(|value| match value {
+                    None => Ok(None),
+                    Some(value) => {
+                        ensure!(
+                            value.len() <= K::MAX_VALUE_SIZE - 4,
+                            ValueSplittingError::TooLargeConditionValue
+                        );
+                        Ok(Some(Self::get_initial_count_first_chunk(1, &value)?))
+                    }
+                })
+                .collect::<Result<_, Self::Error>>()?;
+            batch_new.add_condition(big_key, allowed_values);
+        }
         for operation in batch.operations {
             match operation {
                 WriteOperation::Delete { key } => {
@@ -276,6 +354,10 @@ where
     async fn clear_journal(&self) -> Result<(), Self::Error> {
         Ok(self.store.clear_journal().await?)
     }
+
+    fn supports_conditional_writes(&self) -> bool {
+        self.store.supports_conditional_writes()
+    }
 }
 
 impl<K> AdminKeyValueStore for ValueSplittingStore<K>
@@ -344,6 +426,11 @@ where
         ValueSplittingStore { store }
     }
 
+    /// Returns the underlying store.
+    pub fn inner(&self) -> &K {
+        &self.store
+    }
+
     fn get_segment_key(key: &[u8], index: u32) -> Result<Vec<u8>, ValueSplittingError<K::Error>> {
         let mut big_key_segment = key.to_vec();
         let mut bytes = bcs::to_bytes(&index)?;
@@ -373,6 +460,37 @@ where
         Ok(bcs::from_bytes::<u32>(&bytes)?)
     }
 
+    /// Joins the segments of the values found by a search of the inner store.
+    fn join_segments(
+        mut small_kv_iterator: impl Iterator<Item = Result<(Vec<u8>, Vec<u8>), K::Error>>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ValueSplittingError<K::Error>> {
+        let mut key_values = Vec::new();
+        while let Some(result) = small_kv_iterator.next() {
+            let (mut big_key, value) = result?;
+            if Self::read_index_from_key(&big_key)? != 0 {
+                continue; // Leftover segment from an earlier value.
+            }
+            big_key.truncate(big_key.len() - 4);
+            let key = big_key;
+            let count = Self::read_count_from_value(&value)?;
+            let mut big_value = value[4..].to_vec();
+            for idx in 1..count {
+                let (big_key, value) = small_kv_iterator
+                    .next()
+                    .ok_or(ValueSplittingError::MissingSegment)??;
+                ensure!(
+                    Self::read_index_from_key(&big_key)? == idx
+                        && big_key.starts_with(&key)
+                        && big_key.len() == key.len() + 4,
+                    ValueSplittingError::MissingSegment
+                );
+                big_value.extend(value);
+            }
+            key_values.push((key, big_value));
+        }
+        Ok(key_values)
+    }
+
     fn read_index_from_key(key: &[u8]) -> Result<u32, ValueSplittingError<K::Error>> {
         let len = key.len();
         if len < 4 {
@@ -461,6 +579,10 @@ impl WritableKeyValueStore for LimitedTestMemoryStore {
     async fn clear_journal(&self) -> Result<(), MemoryStoreError> {
         self.store.clear_journal().await
     }
+
+    fn supports_conditional_writes(&self) -> bool {
+        self.store.supports_conditional_writes()
+    }
 }
 
 #[cfg(with_testing)]
@@ -38,7 +38,7 @@ use crate::{
 /// * Deletion of a specific key.
 /// * Deletion of all keys matching a specific prefix.
 /// * Insertion or replacement of a key with a value.
-#[derive(Clone, Debug, Eq, PartialEq, WitType, WitLoad, WitStore)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, WitType, WitLoad, WitStore)]
 pub enum WriteOperation {
     /// Delete the given key.
     Delete {
@@ -64,6 +64,18 @@ pub enum WriteOperation {
 pub struct Batch {
     /// The write operations.
     pub operations: Vec<WriteOperation>,
+    /// The conditions on the values in storage for the batch to be written. They are checked
+    /// by the store, in the same transaction as the write operations.
+    pub conditions: Vec<WriteCondition>,
+}
+
+/// A condition on the value of a key in storage, for a [`Batch`] to be written.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WriteCondition {
+    /// The key whose value is checked.
+    pub key: Vec<u8>,
+    /// The values that the key may have, where `None` means that the key is absent.
+    pub allowed_values: Vec<Option<Vec<u8>>>,
 }
 
 /// A batch of deletions and insertions that operate on disjoint keys, thus can be
@@ -202,7 +214,7 @@ impl Batch {
 
     /// Whether the batch is empty or not
     pub fn is_empty(&self) -> bool {
-        self.operations.is_empty()
+        self.operations.is_empty() && self.conditions.is_empty()
     }
 
     /// Returns the number of operations in this [`Batch`].
@@ -299,6 +311,15 @@ impl Batch {
         }
     }
 
+    /// Adds a condition to the batch: it is only written if the value of the `key` in storage
+    /// is one of the `allowed_values`, where `None` means that the key is absent.
+    pub fn add_condition(&mut self, key: Vec<u8>, allowed_values: Vec<Option<Vec<u8>>>) {
+        self.conditions.push(WriteCondition {
+            key,
+            allowed_values,
+        });
+    }
+
     /// Checks the size of the values of the batch.
     pub fn check_value_size(&self, max_value_size: usize) -> bool {
         for operation in &self.operations {
@@ -0,0 +1,153 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A tool to measure the key-value store operations of a storage backend, with key-values of
+//! configurable sizes.
+//!
+//! The stores are created with the test configuration of each backend, so the DynamoDB and
+//! ScyllaDB backends expect a local instance to be running.
+
+use std::{fmt::Debug, hint::black_box, time::Duration};
+
+use clap::Parser as _;
+#[cfg(with_dynamodb)]
+use linera_views::dynamo_db::DynamoDbStore;
+#[cfg(with_rocksdb)]
+use linera_views::rocks_db::RocksDbStore;
+#[cfg(with_scylladb)]
+use linera_views::scylla_db::ScyllaDbStore;
+use linera_views::{
+    memory::MemoryStore,
+    store::TestKeyValueStore,
+    test_utils::performance::{self, Workload},
+};
+
+/// The storage backends that can be benchmarked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum Backend {
+    Memory,
+    #[cfg(with_rocksdb)]
+    RocksDb,
+    #[cfg(with_dynamodb)]
+    DynamoDb,
+    #[cfg(with_scylladb)]
+    ScyllaDb,
+}
+
+/// The store operations that can be benchmarked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum Operation {
+    ContainsKey,
+    ContainsKeys,
+    FindKeysByPrefix,
+    FindKeyValuesByPrefix,
+    ReadValueBytes,
+    ReadMultiValuesBytes,
+    WriteBatch,
+}
+
+#[derive(clap::Parser)]
+#[command(
+    name = "linera-views-bench",
+    about = "Benchmarks the key-value store operations of a storage backend"
+)]
+struct BenchOptions {
+    /// The storage backend to benchmark.
+    #[arg(long, value_enum, default_value = "memory")]
+    backend: Backend,
+
+    /// The operations to benchmark, separated by commas. All of them are benchmarked by
+    /// default.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    operations: Vec<Operation>,
+
+    /// The number of times each operation is measured.
+    #[arg(long, default_value = "10")]
+    iterations: u32,
+
+    /// The number of key-values written to the store before each measurement.
+    #[arg(long, default_value = "200")]
+    num_entries: usize,
+
+    /// The number of keys inserted before checking for the presence of all of them.
+    #[arg(long, default_value = "70")]
+    num_insert: usize,
+
+    /// The length of the keys.
+    #[arg(long, default_value = "10")]
+    len_key: usize,
+
+    /// The length of the values.
+    #[arg(long, default_value = "10000")]
+    len_value: usize,
+}
+
+impl BenchOptions {
+    fn operations(&self) -> Vec<Operation> {
+        if self.operations.is_empty() {
+            <Operation as clap::ValueEnum>::value_variants().to_vec()
+        } else {
+            self.operations.clone()
+        }
+    }
+
+    fn workload(&self) -> Workload {
+        Workload {
+            num_entries: self.num_entries,
+            num_insert: self.num_insert,
+            len_key: self.len_key,
+            len_value: self.len_value,
+        }
+    }
+}
+
+/// Measures the selected operations on a new test store for each of them, and prints the
+/// average time of an iteration.
+async fn run<S: TestKeyValueStore>(options: &BenchOptions) -> anyhow::Result<()>
+where
+    S::Error: Debug + Send + Sync + 'static,
+{
+    let workload = options.workload();
+    let iterations = u64::from(options.iterations);
+    for operation in options.operations() {
+        let store = S::new_test_store().await?;
+        let total_time: Duration = match operation {
+            Operation::ContainsKey => {
+                performance::contains_key(store, workload, iterations, black_box).await
+            }
+            Operation::ContainsKeys => {
+                performance::contains_keys(store, workload, iterations, black_box).await
+            }
+            Operation::FindKeysByPrefix => {
+                performance::find_keys_by_prefix(store, workload, iterations, black_box).await
+            }
+            Operation::FindKeyValuesByPrefix => {
+                performance::find_key_values_by_prefix(store, workload, iterations, black_box).await
+            }
+            Operation::ReadValueBytes => {
+                performance::read_value_bytes(store, workload, iterations, black_box).await
+            }
+            Operation::ReadMultiValuesBytes => {
+                performance::read_multi_values_bytes(store, workload, iterations, black_box).await
+            }
+            Operation::WriteBatch => performance::write_batch(store, workload, iterations).await,
+        };
+        let average_time = total_time / options.iterations.max(1);
+        println!("{operation:?}: {average_time:?} per iteration");
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let options = BenchOptions::parse();
+    match options.backend {
+        Backend::Memory => run::<MemoryStore>(&options).await,
+        #[cfg(with_rocksdb)]
+        Backend::RocksDb => run::<RocksDbStore>(&options).await,
+        #[cfg(with_dynamodb)]
+        Backend::DynamoDb => run::<DynamoDbStore>(&options).await,
+        #[cfg(with_scylladb)]
+        Backend::ScyllaDb => run::<ScyllaDbStore>(&options).await,
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Encodings of the values stored by views.
+//!
+//! Views store their values with [`Bcs`] by default. The views storing values, such as
+//! [`CodecRegisterView`](crate::register_view::CodecRegisterView),
+//! [`CodecLogView`](crate::log_view::CodecLogView),
+//! [`CodecQueueView`](crate::queue_view::CodecQueueView) and
+//! [`CodecMapView`](crate::map_view::CodecMapView), can instead be given another
+//! [`ValueCodec`], e.g. to store data in the native encoding of an external system.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::views::{Hasher, ViewError};
+
+/// An encoding of the values of type [`ValueCodec::Value`] as bytes in storage.
+pub trait ValueCodec {
+    /// The type of the encoded values.
+    type Value;
+
+    /// Encodes a value.
+    fn encode(value: &Self::Value) -> Result<Vec<u8>, ViewError>;
+
+    /// Decodes a value from the bytes returned by [`ValueCodec::encode`].
+    fn decode(bytes: &[u8]) -> Result<Self::Value, ViewError>;
+}
+
+/// The BCS encoding of the values of type `T`, used by default.
+pub struct Bcs<T>(PhantomData<fn() -> T>);
+
+impl<T> fmt::Debug for Bcs<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("Bcs")
+    }
+}
+
+impl<T> ValueCodec for Bcs<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Value = T;
+
+    fn encode(value: &T) -> Result<Vec<u8>, ViewError> {
+        Ok(bcs::to_bytes(value)?)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, ViewError> {
+        Ok(bcs::from_bytes(bytes)?)
+    }
+}
+
+/// Includes the `values` encoded with `K` in the hash, as a sequence. With [`Bcs`], this is the
+/// same as including the BCS bytes of the vector of values.
+pub(crate) fn update_with_encoded_values<K: ValueCodec>(
+    hasher: &mut impl Hasher,
+    values: &[K::Value],
+) -> Result<(), ViewError> {
+    // The length is a ULEB128 integer, as in BCS.
+    let mut length = values.len();
+    loop {
+        let byte = (length & 0x7f) as u8;
+        length >>= 7;
+        if length == 0 {
+            hasher.update_with_bytes(&[byte])?;
+            break;
+        }
+        hasher.update_with_bytes(&[byte | 0x80])?;
+    }
+    for value in values {
+        hasher.update_with_bytes(&K::encode(value)?)?;
+    }
+    Ok(())
+}
@@ -4,7 +4,7 @@
 //! This provides some common code for the linera-views.
 
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet, VecDeque},
     ops::{
         Bound,
         Bound::{Excluded, Included, Unbounded},
@@ -13,7 +13,7 @@ use std::{
 
 use serde::de::DeserializeOwned;
 
-use crate::views::ViewError;
+use crate::{context::Context, views::ViewError};
 
 #[doc(hidden)]
 pub type HasherOutputSize = <sha3::Sha3_256 as sha3::digest::OutputSizeUser>::OutputSize;
@@ -186,6 +186,65 @@ where
     }
 }
 
+/// Returns at most `limit` keys that come strictly after `start_after`, merging the pending
+/// `updates` of a view with the keys stored under `base`. The stored keys are read one page at a
+/// time, and those for which `is_deleted` returns `true` are skipped.
+pub(crate) async fn find_keys_after<C, T>(
+    context: &C,
+    base: &[u8],
+    updates: &BTreeMap<Vec<u8>, Update<T>>,
+    is_deleted: impl Fn(&[u8]) -> bool,
+    start_after: Option<&[u8]>,
+    limit: Option<usize>,
+) -> Result<Vec<Vec<u8>>, ViewError>
+where
+    C: Context,
+    ViewError: From<C::Error>,
+{
+    let lower_bound = match start_after {
+        Some(start_after) => Excluded(start_after.to_vec()),
+        None => Unbounded,
+    };
+    let mut updates = updates
+        .range::<Vec<u8>, _>((lower_bound, Unbounded))
+        .peekable();
+    let mut cursor = start_after.map(<[u8]>::to_vec);
+    let mut stored_keys = VecDeque::new();
+    // Every key has the empty prefix, so nothing needs to be read if it is deleted.
+    let mut storage_exhausted = is_deleted(&[]);
+    let mut keys = Vec::new();
+    while limit.map_or(true, |limit| keys.len() < limit) {
+        if stored_keys.is_empty() && !storage_exhausted {
+            let page_limit = limit.map(|limit| limit - keys.len());
+            let page = context
+                .find_keys_by_prefix_after(base, cursor.as_deref(), page_limit)
+                .await?;
+            storage_exhausted = page_limit.map_or(true, |page_limit| page.len() < page_limit);
+            cursor = page.last().cloned().or(cursor);
+            stored_keys.extend(page.into_iter().filter(|key| !is_deleted(key)));
+            continue;
+        }
+        match (stored_keys.front(), updates.peek()) {
+            (Some(stored_key), Some((key, _))) if *key <= stored_key => {
+                if *key == stored_key {
+                    stored_keys.pop_front();
+                }
+                if let Some((key, Update::Set(_))) = updates.next() {
+                    keys.push(key.clone());
+                }
+            }
+            (Some(_), _) => keys.extend(stored_keys.pop_front()),
+            (None, Some(_)) => {
+                if let Some((key, Update::Set(_))) = updates.next() {
+                    keys.push(key.clone());
+                }
+            }
+            (None, None) => break,
+        }
+    }
+    Ok(keys)
+}
+
 pub(crate) fn contains_prefix_of(prefixes: &BTreeSet<Vec<u8>>, key: &[u8]) -> bool {
     let iter = prefixes.iter();
     let mut suffix_closed_set = SuffixClosedSetIterator::new(0, iter);
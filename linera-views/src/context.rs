@@ -9,7 +9,7 @@ use crate::{
     common::from_bytes_option,
     memory::MemoryStore,
     store::{KeyIterable, KeyValueIterable, KeyValueStoreError, RestrictedKeyValueStore},
-    views::MIN_VIEW_TAG,
+    views::{View, ViewError, MIN_VIEW_TAG},
 };
 
 /// The context in which a view is operated. Typically, this includes the client to
@@ -62,9 +62,37 @@ pub trait Context: Clone {
         key_prefix: &[u8],
     ) -> Result<Self::KeyValues, Self::Error>;
 
+    /// Finds at most `limit` keys matching the `key_prefix` that come strictly after
+    /// `start_after`. Neither `start_after` nor the returned keys include the `key_prefix`.
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error>;
+
+    /// Finds at most `limit` `(key,value)` pairs matching the `key_prefix` whose keys come
+    /// strictly after `start_after`. Neither `start_after` nor the returned keys include the
+    /// `key_prefix`.
+    async fn find_key_values_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error>;
+
     /// Applies the operations from the `batch`, persisting the changes.
+    ///
+    /// If the values in storage don't satisfy the conditions of the `batch`, nothing is
+    /// written and the error converts to [`ViewError::WriteConflict`].
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error>;
 
+    /// Returns whether batches with conditions can be written, see
+    /// [`crate::store::LocalWritableKeyValueStore::supports_conditional_writes`].
+    fn supports_conditional_writes(&self) -> bool {
+        false
+    }
+
     /// Getter for the user-provided data.
     fn extra(&self) -> &Self::Extra;
 
@@ -257,10 +285,36 @@ where
         self.store.find_key_values_by_prefix(key_prefix).await
     }
 
+    async fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.store
+            .find_keys_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
+
+    async fn find_key_values_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        self.store
+            .find_key_values_by_prefix_after(key_prefix, start_after, limit)
+            .await
+    }
+
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
         self.store.write_batch(batch).await
     }
 
+    fn supports_conditional_writes(&self) -> bool {
+        self.store.supports_conditional_writes()
+    }
+
     fn clone_with_base_key(&self, base_key: Vec<u8>) -> Self {
         Self {
             store: self.store.clone(),
@@ -270,6 +324,66 @@ where
     }
 }
 
+/// Collects the staged changes of several independent root views, e.g. the state of a chain
+/// and the state of one of its applications, to commit them to storage all at once.
+///
+/// The views must be stored in the database of the `context`. Their changes are written in
+/// a single [`Batch`], which the database applies atomically: either as one backend
+/// transaction, or, for databases limiting the size of transactions, through the
+/// [journaling](crate::journaling) layer.
+///
+/// The flushed views consider their changes saved before the transaction is committed. If
+/// [`TransactionalContext::commit`] fails, or if the transaction is dropped without being
+/// committed, the flushed views no longer match the storage: they must be dropped and loaded
+/// again before being used.
+pub struct TransactionalContext<C> {
+    context: C,
+    batch: Batch,
+}
+
+impl<C: Context> TransactionalContext<C> {
+    /// Creates an empty transaction, to be committed with the `context`.
+    pub fn new(context: C) -> Self {
+        TransactionalContext {
+            context,
+            batch: Batch::new(),
+        }
+    }
+
+    /// Adds the staged changes of a `view` to the transaction.
+    ///
+    /// The view behaves as if its changes were saved, even before the transaction is
+    /// committed, so it must be loaded again if the transaction is not committed
+    /// successfully. It must use the same type of context, so that its changes are written to
+    /// the same kind of database.
+    pub fn flush<V: View<C>>(&mut self, view: &mut V) -> Result<(), ViewError> {
+        view.flush(&mut self.batch)?;
+        Ok(())
+    }
+
+    /// Adds the operations of a `batch` to the transaction.
+    pub fn add_batch(&mut self, batch: Batch) {
+        self.batch.operations.extend(batch.operations);
+        self.batch.conditions.extend(batch.conditions);
+    }
+
+    /// Returns whether the transaction has no changes to commit.
+    pub fn is_empty(&self) -> bool {
+        self.batch.is_empty()
+    }
+
+    /// Writes all the changes of the transaction to the database, in a single batch.
+    ///
+    /// If this fails, the flushed views consider their changes saved although they may not
+    /// have been written: these views must be loaded again from storage.
+    pub async fn commit(self) -> Result<(), ViewError> {
+        if !self.batch.is_empty() {
+            self.context.write_batch(self.batch).await?;
+        }
+        Ok(())
+    }
+}
+
 /// An implementation of [`crate::context::Context`] that stores all values in memory.
 pub type MemoryContext<E> = ViewContext<E, MemoryStore>;
 
@@ -1,7 +1,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::borrow::Cow;
+use std::{borrow::Cow, ops::Range};
 
 // TODO(#1858): come up with a better name-mangling scheme
 /// Mangle a GraphQL type into something that can be interpolated into a GraphQL type name
@@ -35,7 +35,9 @@ pub struct Entry<
     K: async_graphql::OutputType + Send + Sync,
     V: async_graphql::OutputType + Send + Sync,
 > {
+    /// The key of the item.
     pub key: K,
+    /// The value of the item.
     pub value: V,
 }
 
@@ -55,11 +57,13 @@ impl<K: async_graphql::OutputType, V: async_graphql::OutputType> async_graphql::
 
 /// A struct to use to filter map values via GraphQL.
 pub struct MapFilters<K: async_graphql::InputType> {
+    /// The keys to select, or `None` to select all of them.
     pub keys: Option<Vec<K>>,
 }
 
 /// The inputs given when inspecting a map value via GraphQL.
 pub struct MapInput<K: async_graphql::InputType> {
+    /// The filters to apply to the map, if any.
     pub filters: Option<MapFilters<K>>,
 }
 
@@ -220,3 +224,11 @@ pub(crate) fn missing_key_error(key: &impl std::fmt::Debug) -> async_graphql::Er
         extensions: None,
     }
 }
+
+/// Returns the range of the items of a collection of `len` items to return in a GraphQL page
+/// that skips the first `offset` items and contains at most `count` items.
+pub fn page_range(len: usize, offset: Option<usize>, count: Option<usize>) -> Range<usize> {
+    let start = offset.unwrap_or(0).min(len);
+    let end = count.map_or(len, |count| start.saturating_add(count).min(len));
+    start..end
+}
@@ -71,6 +71,9 @@ pub mod context;
 /// Common definitions used for views and backends.
 pub mod common;
 
+/// The encodings of the values stored by views.
+pub mod codec;
+
 /// Elementary data-structures implementing the [`views::View`] trait.
 pub mod views;
 
@@ -81,8 +84,8 @@ pub mod backends;
 #[cfg(with_metrics)]
 pub mod metrics;
 
-/// GraphQL implementations.
-mod graphql;
+/// GraphQL implementations, and helpers for the `GraphQLView` derive macro.
+pub mod graphql;
 
 /// Functions for random generation
 #[cfg(with_testing)]
@@ -94,6 +97,8 @@ pub mod test_utils;
 
 #[cfg(with_dynamodb)]
 pub use backends::dynamo_db;
+#[cfg(not(target_arch = "wasm32"))]
+pub use backends::file_backed_memory;
 #[cfg(with_indexeddb)]
 pub use backends::indexed_db;
 #[cfg(with_metrics)]
@@ -55,10 +55,19 @@ impl Default for CommonStoreConfig {
 pub trait KeyValueStoreError: std::error::Error + Debug + From<bcs::Error> {
     /// The name of the backend.
     const BACKEND: &'static str;
+
+    /// Returns the key whose value didn't satisfy a condition of a batch, if the batch was
+    /// not written because of that.
+    fn write_conflict(&self) -> Option<&[u8]> {
+        None
+    }
 }
 
 impl<E: KeyValueStoreError> From<E> for ViewError {
     fn from(error: E) -> Self {
+        if let Some(key) = error.write_conflict() {
+            return Self::WriteConflict(key.to_vec());
+        }
         Self::StoreError {
             backend: E::BACKEND.to_string(),
             error: error.to_string(),
@@ -111,6 +120,71 @@ pub trait LocalReadableKeyValueStore: WithError {
         key_prefix: &[u8],
     ) -> Result<Self::KeyValues, Self::Error>;
 
+    /// Finds the keys matching the prefix that come strictly after `start_after`, in
+    /// lexicographic order, and returns at most `limit` of them. Neither `start_after` nor the
+    /// returned keys include the prefix.
+    ///
+    /// This is used to resume an iteration from the last key of the previous page. The default
+    /// implementation filters the result of `find_keys_by_prefix`, so backends that can
+    /// seek to `start_after` should override it.
+    fn find_keys_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> impl Future<Output = Result<Vec<Vec<u8>>, Self::Error>>
+    where
+        Self: Sync,
+    {
+        async move {
+            let keys = self.find_keys_by_prefix(key_prefix).await?;
+            let mut page = Vec::new();
+            for key in keys.iterator() {
+                if limit.is_some_and(|limit| page.len() >= limit) {
+                    break;
+                }
+                let key = key?;
+                if start_after.is_some_and(|start_after| key <= start_after) {
+                    continue;
+                }
+                page.push(key.to_vec());
+            }
+            Ok(page)
+        }
+    }
+
+    /// Finds the `(key,value)` pairs matching the prefix whose keys come strictly after
+    /// `start_after`, in lexicographic order, and returns at most `limit` of them. Neither
+    /// `start_after` nor the returned keys include the prefix.
+    ///
+    /// The default implementation filters the result of `find_key_values_by_prefix`, so
+    /// backends that can seek to `start_after` should override it.
+    fn find_key_values_by_prefix_after(
+        &self,
+        key_prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> impl Future<Output = Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error>>
+    where
+        Self: Sync,
+    {
+        async move {
+            let key_values = self.find_key_values_by_prefix(key_prefix).await?;
+            let mut page = Vec::new();
+            for key_value in key_values.into_iterator_owned() {
+                if limit.is_some_and(|limit| page.len() >= limit) {
+                    break;
+                }
+                let (key, value) = key_value?;
+                if start_after.is_some_and(|start_after| key.as_slice() <= start_after) {
+                    continue;
+                }
+                page.push((key, value));
+            }
+            Ok(page)
+        }
+    }
+
     // We can't use `async fn` here in the below implementations due to
     // https://github.com/rust-lang/impl-trait-utils/issues/17, but once that bug is fixed
     // we can revert them to `async fn` syntax, which is neater.
@@ -151,8 +225,19 @@ pub trait LocalWritableKeyValueStore: WithError {
     const MAX_VALUE_SIZE: usize;
 
     /// Writes the `batch` in the database.
+    ///
+    /// If the values in the database don't satisfy the conditions of the `batch`, nothing is
+    /// written and the error is a [write conflict](KeyValueStoreError::write_conflict). The
+    /// batch must not have conditions if the store doesn't
+    /// [support them](Self::supports_conditional_writes).
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error>;
 
+    /// Returns whether the conditions of a batch are checked atomically with its writes,
+    /// including against the writes of other processes.
+    fn supports_conditional_writes(&self) -> bool {
+        false
+    }
+
     /// Clears any journal entry that may remain.
     /// The journal is located at the `root_key`.
     async fn clear_journal(&self) -> Result<(), Self::Error>;
@@ -241,6 +241,86 @@ pub async fn run_reads<S: LocalRestrictedKeyValueStore>(
     }
 }
 
+/// This test inserts a collection of key/values and checks that reading them by pages with
+/// `find_keys_by_prefix_after` / `find_key_values_by_prefix_after` returns the same entries as
+/// `find_keys_by_prefix` / `find_key_values_by_prefix`.
+pub async fn run_paginated_reads<S: LocalRestrictedKeyValueStore + Sync>(
+    store: S,
+    key_values: Vec<(Vec<u8>, Vec<u8>)>,
+) {
+    let mut batch = Batch::new();
+    for (key, value) in &key_values {
+        batch.put_key_value_bytes(key.clone(), value.clone());
+    }
+    store.write_batch(batch).await.unwrap();
+    for key_prefix in key_values
+        .iter()
+        .flat_map(|(key, _)| (0..key.len()).map(|u| &key[..=u]))
+    {
+        let keys_by_prefix = store.find_keys_by_prefix(key_prefix).await.unwrap();
+        let keys = keys_by_prefix
+            .iterator()
+            .map(|key| key.unwrap().to_vec())
+            .collect::<Vec<_>>();
+        let key_values_by_prefix = store.find_key_values_by_prefix(key_prefix).await.unwrap();
+        let key_values = key_values_by_prefix
+            .into_iterator_owned()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        for page_size in [1, 3] {
+            let mut keys_paginated = Vec::new();
+            let mut key_values_paginated = Vec::new();
+            loop {
+                let page = store
+                    .find_keys_by_prefix_after(
+                        key_prefix,
+                        keys_paginated.last().map(Vec::as_slice),
+                        Some(page_size),
+                    )
+                    .await
+                    .unwrap();
+                let key_values_page = store
+                    .find_key_values_by_prefix_after(
+                        key_prefix,
+                        keys_paginated.last().map(Vec::as_slice),
+                        Some(page_size),
+                    )
+                    .await
+                    .unwrap();
+                assert!(page.len() <= page_size);
+                assert_eq!(
+                    page,
+                    key_values_page
+                        .iter()
+                        .map(|(key, _)| key.clone())
+                        .collect::<Vec<_>>()
+                );
+                let is_last_page = page.len() < page_size;
+                keys_paginated.extend(page);
+                key_values_paginated.extend(key_values_page);
+                if is_last_page {
+                    break;
+                }
+            }
+            assert_eq!(keys, keys_paginated);
+            assert_eq!(key_values, key_values_paginated);
+        }
+        // Without a limit, the search resumes after the given key up to the end.
+        if let Some(first_key) = keys.first() {
+            let remaining_keys = store
+                .find_keys_by_prefix_after(key_prefix, Some(first_key), None)
+                .await
+                .unwrap();
+            assert_eq!(remaining_keys, keys[1..]);
+            let remaining_key_values = store
+                .find_key_values_by_prefix_after(key_prefix, Some(first_key), None)
+                .await
+                .unwrap();
+            assert_eq!(remaining_key_values, key_values[1..]);
+        }
+    }
+}
+
 fn get_random_key_values1(num_entries: usize, len_value: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
     let key_prefix = vec![0];
     let mut rng = make_deterministic_rng();
@@ -12,7 +12,7 @@ use crate::{
     test_utils::{add_prefix, get_random_key_values2},
 };
 
-// We generate about 2000 keys of length 11 with a key of length 10000
+// By default, we generate about 200 keys of length 11 with values of length 10000.
 // The keys are of the form 0,x_1, ..., x_n with 0 <= x_i < 4 and n=10.
 
 /// A value to use for the keys
@@ -21,17 +21,38 @@ const PREFIX: &[u8] = &[0];
 /// A value to use for the keys
 const PREFIX_SEARCH: &[u8] = &[0, 0];
 
-/// The number of keys
-const NUM_ENTRIES: usize = 200;
-
-/// The number of inserted keys
-const NUM_INSERT: usize = 70;
+/// The sizes of the key-values used by the benchmarks.
+#[derive(Clone, Copy, Debug)]
+pub struct Workload {
+    /// The number of keys.
+    pub num_entries: usize,
+    /// The number of keys inserted before checking for the presence of all of them.
+    pub num_insert: usize,
+    /// The length of the keys.
+    pub len_key: usize,
+    /// The length of the values.
+    pub len_value: usize,
+}
 
-/// The length of the keys
-const LEN_KEY: usize = 10;
+impl Default for Workload {
+    fn default() -> Self {
+        Workload {
+            num_entries: 200,
+            num_insert: 70,
+            len_key: 10,
+            len_value: 10000,
+        }
+    }
+}
 
-/// The length of the values
-const LEN_VALUE: usize = 10000;
+impl Workload {
+    fn key_values(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        add_prefix(
+            PREFIX,
+            get_random_key_values2(self.num_entries, self.len_key, self.len_value),
+        )
+    }
+}
 
 async fn clear_store<S: LocalKeyValueStore>(store: &S) {
     let mut batch = Batch::new();
@@ -40,19 +61,21 @@ async fn clear_store<S: LocalKeyValueStore>(store: &S) {
 }
 
 /// Benchmarks the `contains_key` operation.
-pub async fn contains_key<S: LocalKeyValueStore, F>(store: S, iterations: u64, f: F) -> Duration
+pub async fn contains_key<S: LocalKeyValueStore, F>(
+    store: S,
+    workload: Workload,
+    iterations: u64,
+    f: F,
+) -> Duration
 where
     S::Error: Debug,
     F: Fn(bool) -> bool,
 {
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
-        let key_values = add_prefix(
-            PREFIX,
-            get_random_key_values2(NUM_ENTRIES, LEN_KEY, LEN_VALUE),
-        );
+        let key_values = workload.key_values();
         let mut batch = Batch::new();
-        for key_value in &key_values[..NUM_INSERT] {
+        for key_value in &key_values[..workload.num_insert.min(key_values.len())] {
             batch.put_key_value_bytes(key_value.0.clone(), key_value.1.clone());
         }
         store.write_batch(batch).await.unwrap();
@@ -70,19 +93,21 @@ where
 }
 
 /// Benchmarks the `contains_keys` operation.
-pub async fn contains_keys<S: LocalKeyValueStore, F>(store: S, iterations: u64, f: F) -> Duration
+pub async fn contains_keys<S: LocalKeyValueStore, F>(
+    store: S,
+    workload: Workload,
+    iterations: u64,
+    f: F,
+) -> Duration
 where
     S::Error: Debug,
     F: Fn(Vec<bool>) -> Vec<bool>,
 {
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
-        let key_values = add_prefix(
-            PREFIX,
-            get_random_key_values2(NUM_ENTRIES, LEN_KEY, LEN_VALUE),
-        );
+        let key_values = workload.key_values();
         let mut batch = Batch::new();
-        for key_value in &key_values[..NUM_INSERT] {
+        for key_value in &key_values[..workload.num_insert.min(key_values.len())] {
             batch.put_key_value_bytes(key_value.0.clone(), key_value.1.clone());
         }
         store.write_batch(batch).await.unwrap();
@@ -104,6 +129,7 @@ where
 /// Benchmarks the `find_keys_by_prefix` operation.
 pub async fn find_keys_by_prefix<S: LocalKeyValueStore, F>(
     store: S,
+    workload: Workload,
     iterations: u64,
     f: F,
 ) -> Duration
@@ -113,10 +139,7 @@ where
 {
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
-        let key_values = add_prefix(
-            PREFIX,
-            get_random_key_values2(NUM_ENTRIES, LEN_KEY, LEN_VALUE),
-        );
+        let key_values = workload.key_values();
         let mut batch = Batch::new();
         for key_value in &key_values {
             batch.put_key_value_bytes(key_value.0.clone(), key_value.1.clone());
@@ -133,9 +156,10 @@ where
     total_time
 }
 
-/// Benchmarks the `find_keys_by_prefix` operation.
+/// Benchmarks the `find_key_values_by_prefix` operation.
 pub async fn find_key_values_by_prefix<S: LocalKeyValueStore, F>(
     store: S,
+    workload: Workload,
     iterations: u64,
     f: F,
 ) -> Duration
@@ -145,10 +169,7 @@ where
 {
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
-        let key_values = add_prefix(
-            PREFIX,
-            get_random_key_values2(NUM_ENTRIES, LEN_KEY, LEN_VALUE),
-        );
+        let key_values = workload.key_values();
         let mut batch = Batch::new();
         for key_value in &key_values {
             batch.put_key_value_bytes(key_value.0.clone(), key_value.1.clone());
@@ -169,17 +190,19 @@ where
 }
 
 /// Benchmarks the `read_value_bytes` operation.
-pub async fn read_value_bytes<S: LocalKeyValueStore, F>(store: S, iterations: u64, f: F) -> Duration
+pub async fn read_value_bytes<S: LocalKeyValueStore, F>(
+    store: S,
+    workload: Workload,
+    iterations: u64,
+    f: F,
+) -> Duration
 where
     S::Error: Debug,
     F: Fn(Option<Vec<u8>>) -> Option<Vec<u8>>,
 {
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
-        let key_values = add_prefix(
-            PREFIX,
-            get_random_key_values2(NUM_ENTRIES, LEN_KEY, LEN_VALUE),
-        );
+        let key_values = workload.key_values();
         let mut batch = Batch::new();
         for key_value in &key_values {
             batch.put_key_value_bytes(key_value.0.clone(), key_value.1.clone());
@@ -201,6 +224,7 @@ where
 /// Benchmarks the `read_multi_values_bytes` operation.
 pub async fn read_multi_values_bytes<S: LocalKeyValueStore, F>(
     store: S,
+    workload: Workload,
     iterations: u64,
     f: F,
 ) -> Duration
@@ -210,10 +234,7 @@ where
 {
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
-        let key_values = add_prefix(
-            PREFIX,
-            get_random_key_values2(NUM_ENTRIES, LEN_KEY, LEN_VALUE),
-        );
+        let key_values = workload.key_values();
         let mut batch = Batch::new();
         for key_value in &key_values {
             batch.put_key_value_bytes(key_value.0.clone(), key_value.1.clone());
@@ -235,16 +256,17 @@ where
 }
 
 /// Benchmarks the `write_batch` operation.
-pub async fn write_batch<S: LocalKeyValueStore>(store: S, iterations: u64) -> Duration
+pub async fn write_batch<S: LocalKeyValueStore>(
+    store: S,
+    workload: Workload,
+    iterations: u64,
+) -> Duration
 where
     S::Error: Debug,
 {
     let mut total_time = Duration::ZERO;
     for _ in 0..iterations {
-        let key_values = add_prefix(
-            PREFIX,
-            get_random_key_values2(NUM_ENTRIES, LEN_KEY, LEN_VALUE),
-        );
+        let key_values = workload.key_values();
         let mut batch = Batch::new();
         for key_value in &key_values {
             batch.put_key_value_bytes(key_value.0.clone(), key_value.1.clone());
@@ -9,6 +9,7 @@ use std::{
     io::Write,
     marker::PhantomData,
     mem,
+    sync::Mutex,
 };
 
 use async_lock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
@@ -22,7 +23,7 @@ use {
 
 use crate::{
     batch::Batch,
-    common::{CustomSerialize, HasherOutput, Update},
+    common::{find_keys_after, CustomSerialize, HasherOutput, Update},
     context::Context,
     hashable_wrapper::WrappedHashableContainerView,
     store::KeyIterable,
@@ -47,6 +48,8 @@ pub struct ByteCollectionView<C, W> {
     context: C,
     delete_storage_first: bool,
     updates: RwLock<BTreeMap<Vec<u8>, Update<W>>>,
+    /// The hashes of the entries that were not modified since they were last hashed.
+    cached_hashes: Mutex<BTreeMap<Vec<u8>, Vec<u8>>>,
 }
 
 /// A read-only accessor for a particular subview in a [`CollectionView`].
@@ -103,6 +106,7 @@ where
             context,
             delete_storage_first: false,
             updates: RwLock::new(BTreeMap::new()),
+            cached_hashes: Mutex::new(BTreeMap::new()),
         })
     }
 
@@ -112,6 +116,10 @@ where
 
     fn rollback(&mut self) {
         self.delete_storage_first = false;
+        let cached_hashes = self.cached_hashes.get_mut().unwrap();
+        for short_key in self.updates.get_mut().keys() {
+            cached_hashes.remove(short_key);
+        }
         self.updates.get_mut().clear();
     }
 
@@ -158,6 +166,7 @@ where
     fn clear(&mut self) {
         self.delete_storage_first = true;
         self.updates.get_mut().clear();
+        self.cached_hashes.get_mut().unwrap().clear();
     }
 }
 
@@ -185,6 +194,7 @@ where
             context: self.context.clone(),
             delete_storage_first: self.delete_storage_first,
             updates: RwLock::new(cloned_updates),
+            cached_hashes: Mutex::new(self.cached_hashes.get_mut().unwrap().clone()),
         })
     }
 }
@@ -208,6 +218,11 @@ where
         batch.put_key_value_bytes(key, vec![]);
     }
 
+    /// Forgets the cached hash of an entry that may be modified.
+    fn invalidate_hash(&mut self, short_key: &[u8]) {
+        self.cached_hashes.get_mut().unwrap().remove(short_key);
+    }
+
     /// Loads a subview for the data at the given index in the collection. If an entry
     /// is absent then a default entry is added to the collection. The resulting view
     /// can be modified.
@@ -342,6 +357,7 @@ where
             .base_tag_index(KeyTag::Subview as u8, short_key);
         let context = self.context.clone_with_base_key(key);
         let view = W::new(context)?;
+        self.invalidate_hash(short_key);
         self.updates
             .get_mut()
             .insert(short_key.to_vec(), Update::Set(view));
@@ -398,6 +414,7 @@ where
     /// # })
     /// ```
     pub fn remove_entry(&mut self, short_key: Vec<u8>) {
+        self.invalidate_hash(&short_key);
         if self.delete_storage_first {
             // Optimization: No need to mark `short_key` for deletion as we are going to remove all the keys at once.
             self.updates.get_mut().remove(&short_key);
@@ -412,6 +429,7 @@ where
     }
 
     async fn do_load_entry_mut(&mut self, short_key: &[u8]) -> Result<&mut W, ViewError> {
+        self.invalidate_hash(short_key);
         match self.updates.get_mut().entry(short_key.to_vec()) {
             btree_map::Entry::Occupied(entry) => {
                 let entry = entry.into_mut();
@@ -586,6 +604,41 @@ where
         Ok(keys)
     }
 
+    /// Returns at most `limit` keys in the collection that come strictly after
+    /// `start_after`. The order is lexicographic.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::{create_test_memory_context, MemoryContext};
+    /// # use linera_views::collection_view::ByteCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: ByteCollectionView<_, RegisterView<_, String>> =
+    ///     ByteCollectionView::load(context).await.unwrap();
+    /// view.load_entry_mut(&[0, 1]).await.unwrap();
+    /// view.load_entry_mut(&[0, 2]).await.unwrap();
+    /// view.load_entry_mut(&[0, 3]).await.unwrap();
+    /// let keys = view.keys_after(Some(&[0, 1]), Some(1)).await.unwrap();
+    /// assert_eq!(keys, vec![vec![0, 2]]);
+    /// # })
+    /// ```
+    pub async fn keys_after(
+        &self,
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, ViewError> {
+        let updates = self.updates.read().await;
+        find_keys_after(
+            &self.context,
+            &self.get_index_key(&[]),
+            &updates,
+            |_key| self.delete_storage_first,
+            start_after,
+            limit,
+        )
+        .await
+    }
+
     /// Returns the number of entries in the collection.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -629,8 +682,13 @@ where
         let count = keys.len() as u32;
         hasher.update_with_bcs_bytes(&count)?;
         let updates = self.updates.get_mut();
+        let cached_hashes = self.cached_hashes.get_mut().unwrap();
         for key in keys {
             hasher.update_with_bytes(&key)?;
+            if let Some(hash) = cached_hashes.get(&key) {
+                hasher.write_all(hash)?;
+                continue;
+            }
             let hash = match updates.get_mut(&key) {
                 Some(entry) => {
                     let Update::Set(view) = entry else {
@@ -646,6 +704,7 @@ where
                 }
             };
             hasher.write_all(hash.as_ref())?;
+            cached_hashes.insert(key, hash.as_ref().to_vec());
         }
         Ok(hasher.finalize())
     }
@@ -660,6 +719,11 @@ where
         let updates = self.updates.read().await;
         for key in keys {
             hasher.update_with_bytes(&key)?;
+            let cached_hash = self.cached_hashes.lock().unwrap().get(&key).cloned();
+            if let Some(hash) = cached_hash {
+                hasher.write_all(&hash)?;
+                continue;
+            }
             let hash = match updates.get(&key) {
                 Some(entry) => {
                     let Update::Set(view) = entry else {
@@ -675,6 +739,10 @@ where
                 }
             };
             hasher.write_all(hash.as_ref())?;
+            self.cached_hashes
+                .lock()
+                .unwrap()
+                .insert(key, hash.as_ref().to_vec());
         }
         Ok(hasher.finalize())
     }
@@ -941,6 +1009,37 @@ where
         Ok(indices)
     }
 
+    /// Returns at most `limit` indices in the collection that come strictly after
+    /// `start_after`, in the order determined by the serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::{create_test_memory_context, MemoryContext};
+    /// # use linera_views::collection_view::CollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: CollectionView<_, u64, RegisterView<_, String>> =
+    ///     CollectionView::load(context).await.unwrap();
+    /// view.load_entry_mut(&23).await.unwrap();
+    /// view.load_entry_mut(&25).await.unwrap();
+    /// let indices = view.indices_after(Some(&23), None).await.unwrap();
+    /// assert_eq!(indices, vec![25]);
+    /// # })
+    /// ```
+    pub async fn indices_after(
+        &self,
+        start_after: Option<&I>,
+        limit: Option<usize>,
+    ) -> Result<Vec<I>, ViewError> {
+        let start_after = start_after.map(C::derive_short_key).transpose()?;
+        self.collection
+            .keys_after(start_after.as_deref(), limit)
+            .await?
+            .iter()
+            .map(|key| Ok(C::deserialize_value(key)?))
+            .collect()
+    }
+
     /// Returns the number of entries in the collection.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -1320,6 +1419,38 @@ where
         Ok(indices)
     }
 
+    /// Returns at most `limit` indices in the collection that come strictly after
+    /// `start_after`, in the order determined by the custom serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::{create_test_memory_context, MemoryContext};
+    /// # use linera_views::collection_view::CustomCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: CustomCollectionView<_, u128, RegisterView<_, String>> =
+    ///     CustomCollectionView::load(context).await.unwrap();
+    /// view.load_entry_mut(&23).await.unwrap();
+    /// view.load_entry_mut(&25).await.unwrap();
+    /// view.load_entry_mut(&27).await.unwrap();
+    /// let indices = view.indices_after(None, Some(2)).await.unwrap();
+    /// assert_eq!(indices, vec![23, 25]);
+    /// # })
+    /// ```
+    pub async fn indices_after(
+        &self,
+        start_after: Option<&I>,
+        limit: Option<usize>,
+    ) -> Result<Vec<I>, ViewError> {
+        let start_after = start_after.map(I::to_custom_bytes).transpose()?;
+        self.collection
+            .keys_after(start_after.as_deref(), limit)
+            .await?
+            .iter()
+            .map(|key| I::from_custom_bytes(key))
+            .collect()
+    }
+
     /// Returns the number of entries in the collection.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -1512,8 +1643,12 @@ mod graphql {
         MapInput<K>: async_graphql::InputType,
         MapFilters<K>: async_graphql::InputType,
     {
-        async fn keys(&self) -> Result<Vec<K>, async_graphql::Error> {
-            Ok(self.indices().await?)
+        async fn keys(
+            &self,
+            first: Option<usize>,
+            after: Option<K>,
+        ) -> Result<Vec<K>, async_graphql::Error> {
+            Ok(self.indices_after(after.as_ref(), first).await?)
         }
 
         async fn entry(
@@ -1530,6 +1665,8 @@ mod graphql {
         async fn entries(
             &self,
             input: Option<MapInput<K>>,
+            first: Option<usize>,
+            after: Option<K>,
         ) -> Result<Vec<Entry<K, ReadGuardedView<V>>>, async_graphql::Error> {
             let keys = if let Some(keys) = input
                 .and_then(|input| input.filters)
@@ -1537,7 +1674,7 @@ mod graphql {
             {
                 keys
             } else {
-                self.indices().await?
+                self.indices_after(after.as_ref(), first).await?
             };
 
             let mut values = vec![];
@@ -1579,8 +1716,12 @@ mod graphql {
         MapInput<K>: async_graphql::InputType,
         MapFilters<K>: async_graphql::InputType,
     {
-        async fn keys(&self) -> Result<Vec<K>, async_graphql::Error> {
-            Ok(self.indices().await?)
+        async fn keys(
+            &self,
+            first: Option<usize>,
+            after: Option<K>,
+        ) -> Result<Vec<K>, async_graphql::Error> {
+            Ok(self.indices_after(after.as_ref(), first).await?)
         }
 
         async fn entry(
@@ -1597,6 +1738,8 @@ mod graphql {
         async fn entries(
             &self,
             input: Option<MapInput<K>>,
+            first: Option<usize>,
+            after: Option<K>,
         ) -> Result<Vec<Entry<K, ReadGuardedView<V>>>, async_graphql::Error> {
             let keys = if let Some(keys) = input
                 .and_then(|input| input.filters)
@@ -1604,7 +1747,7 @@ mod graphql {
             {
                 keys
             } else {
-                self.indices().await?
+                self.indices_after(after.as_ref(), first).await?
             };
 
             let mut values = vec![];
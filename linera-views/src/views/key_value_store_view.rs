@@ -15,7 +15,7 @@ use {
 };
 
 use crate::{
-    batch::{Batch, WriteOperation},
+    batch::{Batch, SimpleUnorderedBatch, UnorderedBatch, WriteOperation},
     common::{
         from_bytes_option, from_bytes_option_or_default, get_interval, get_upper_bound,
         DeletionSet, HasherOutput, SuffixClosedSetIterator, Update,
@@ -23,7 +23,7 @@ use crate::{
     context::Context,
     map_view::ByteMapView,
     store::{KeyIterable, KeyValueIterable},
-    views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
+    views::{ClonableView, HashableView, Hasher, StorageStats, View, ViewError, MIN_VIEW_TAG},
 };
 
 #[cfg(with_metrics)]
@@ -349,6 +349,22 @@ where
         self.sizes.clear();
         *self.hash.get_mut().unwrap() = None;
     }
+
+    /// The size of the entries is maintained by the view, so only their keys are read.
+    async fn storage_stats(&self) -> Result<StorageStats, ViewError> {
+        let key_prefix = self.context.base_tag(KeyTag::Index as u8);
+        let keys = self.context.find_keys_by_prefix(&key_prefix).await?;
+        let mut key_count = 0;
+        for key in keys.iterator() {
+            key?;
+            key_count += 1;
+        }
+        let size = self.stored_total_size;
+        Ok(StorageStats {
+            key_count,
+            total_size: u64::from(size.key) + u64::from(size.value),
+        })
+    }
 }
 
 impl<C> ClonableView<C> for KeyValueStoreView<C>
@@ -396,6 +412,53 @@ where
         self.total_size
     }
 
+    /// Returns the total size that the view would have after writing `batch`, without
+    /// writing it.
+    pub async fn total_size_after(&self, batch: &Batch) -> Result<SizeData, ViewError> {
+        let UnorderedBatch {
+            key_prefix_deletions,
+            simple_unordered_batch,
+        } = batch.clone().simplify();
+        let mut total_size = self.total_size;
+        for key_prefix in &key_prefix_deletions {
+            for (key, value) in self.sizes.key_values_by_prefix(key_prefix.clone()).await? {
+                total_size.sub_assign(SizeData {
+                    key: key.len() as u32,
+                    value,
+                });
+            }
+        }
+        let SimpleUnorderedBatch {
+            deletions,
+            insertions,
+        } = simple_unordered_batch;
+        let keys = deletions
+            .iter()
+            .chain(insertions.iter().map(|(key, _)| key));
+        for key in keys {
+            // The sizes of the keys under a deleted prefix were already subtracted.
+            if key_prefix_deletions
+                .iter()
+                .any(|key_prefix| key.starts_with(key_prefix))
+            {
+                continue;
+            }
+            if let Some(value) = self.sizes.get(key).await? {
+                total_size.sub_assign(SizeData {
+                    key: key.len() as u32,
+                    value,
+                });
+            }
+        }
+        for (key, value) in &insertions {
+            total_size.add_assign(SizeData {
+                key: key.len() as u32,
+                value: value.len() as u32,
+            })?;
+        }
+        Ok(total_size)
+    }
+
     /// Applies the function f over all indices. If the function f returns
     /// false, then the loop ends prematurely.
     /// ```rust
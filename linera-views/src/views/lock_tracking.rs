@@ -0,0 +1,195 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tracking of the entries of reentrant collection views locked by each task.
+//!
+//! Entries are locked without waiting, so loading entries in different orders from
+//! concurrent tasks fails instead of hanging. With the `deadlock-detection` feature, the
+//! order in which tasks lock entries is recorded in a graph shared by all the collections: an
+//! edge from an entry to another means that a task locked the second one while holding the
+//! first one. If a task fails to lock an entry while it holds another entry that was locked
+//! after the requested one, the graph has a cycle: the tasks lock the entries in
+//! inconsistent orders, and would deadlock if they waited for each other. The error then
+//! reports both entries. Without the feature, nothing is tracked.
+
+#[cfg(with_deadlock_detection)]
+pub(crate) use tracking::{HeldLock, LockTracker};
+#[cfg(not(with_deadlock_detection))]
+pub(crate) use untracked::{HeldLock, LockTracker};
+
+#[cfg(with_deadlock_detection)]
+mod tracking {
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        },
+    };
+
+    use tokio::task;
+
+    use crate::views::ViewError;
+
+    /// An entry of a collection: the ID of the collection's tracker, and the entry's key.
+    type Entry = (u64, Vec<u8>);
+
+    /// The entries locked by the tasks, and the order in which they were locked.
+    struct LockGraph {
+        next_lock_id: u64,
+        /// The entries currently locked, by registration ID, with the task that locked them.
+        held: BTreeMap<u64, (task::Id, Entry)>,
+        /// For each entry, the entries that a task locked while holding it.
+        locked_after: BTreeMap<Entry, BTreeSet<Entry>>,
+    }
+
+    impl LockGraph {
+        /// Returns the entries currently held by `task`, other than `entry`.
+        fn held_by(&self, task: task::Id, entry: &Entry) -> Vec<Entry> {
+            self.held
+                .values()
+                .filter(|(holder, held)| *holder == task && held != entry)
+                .map(|(_, held)| held.clone())
+                .collect()
+        }
+
+        /// Returns whether `target` was locked after `source`, directly or transitively.
+        fn is_locked_after(&self, source: &Entry, target: &Entry) -> bool {
+            let mut visited = BTreeSet::new();
+            let mut pending = vec![source];
+            while let Some(entry) = pending.pop() {
+                let Some(successors) = self.locked_after.get(entry) else {
+                    continue;
+                };
+                for successor in successors {
+                    if successor == target {
+                        return true;
+                    }
+                    if visited.insert(successor) {
+                        pending.push(successor);
+                    }
+                }
+            }
+            false
+        }
+    }
+
+    static LOCK_GRAPH: Mutex<LockGraph> = Mutex::new(LockGraph {
+        next_lock_id: 0,
+        held: BTreeMap::new(),
+        locked_after: BTreeMap::new(),
+    });
+
+    static NEXT_TRACKER_ID: AtomicU64 = AtomicU64::new(0);
+
+    /// Records the entries of a collection locked by each task in the shared lock graph.
+    #[derive(Debug)]
+    pub(crate) struct LockTracker {
+        id: u64,
+    }
+
+    /// An entry locked by a task, until this is dropped.
+    #[derive(Debug)]
+    pub(crate) struct HeldLock {
+        lock_id: Option<u64>,
+    }
+
+    impl Default for LockTracker {
+        fn default() -> Self {
+            LockTracker {
+                id: NEXT_TRACKER_ID.fetch_add(1, Ordering::Relaxed),
+            }
+        }
+    }
+
+    impl LockTracker {
+        /// Records that the current task locked the entry `key`, after the entries it holds.
+        pub(crate) fn register(&self, key: &[u8]) -> HeldLock {
+            let Some(task) = task::try_id() else {
+                return HeldLock { lock_id: None };
+            };
+            let entry = (self.id, key.to_vec());
+            let mut graph = LOCK_GRAPH.lock().unwrap();
+            for held in graph.held_by(task, &entry) {
+                graph
+                    .locked_after
+                    .entry(held)
+                    .or_default()
+                    .insert(entry.clone());
+            }
+            let lock_id = graph.next_lock_id;
+            graph.next_lock_id += 1;
+            graph.held.insert(lock_id, (task, entry));
+            HeldLock {
+                lock_id: Some(lock_id),
+            }
+        }
+
+        /// Returns the error for a failure to lock the entry `key`.
+        ///
+        /// If the current task holds an entry that was locked after this one, the two entries
+        /// are locked in inconsistent orders, and both keys are reported.
+        pub(crate) fn conflict(&self, key: &[u8]) -> ViewError {
+            let Some(task) = task::try_id() else {
+                return ViewError::TryLockError(key.to_vec());
+            };
+            let entry = (self.id, key.to_vec());
+            let graph = LOCK_GRAPH.lock().unwrap();
+            match graph
+                .held_by(task, &entry)
+                .into_iter()
+                .find(|held| graph.is_locked_after(&entry, held))
+            {
+                Some((_, held)) => ViewError::LockOrderConflict {
+                    requested: key.to_vec(),
+                    held,
+                },
+                None => ViewError::TryLockError(key.to_vec()),
+            }
+        }
+    }
+
+    impl Drop for LockTracker {
+        fn drop(&mut self) {
+            // The entries of a dropped collection can no longer be locked.
+            let mut graph = LOCK_GRAPH.lock().unwrap();
+            graph.locked_after.retain(|(tracker, _), successors| {
+                successors.retain(|(successor_tracker, _)| *successor_tracker != self.id);
+                *tracker != self.id && !successors.is_empty()
+            });
+        }
+    }
+
+    impl Drop for HeldLock {
+        fn drop(&mut self) {
+            if let Some(lock_id) = self.lock_id {
+                LOCK_GRAPH.lock().unwrap().held.remove(&lock_id);
+            }
+        }
+    }
+}
+
+#[cfg(not(with_deadlock_detection))]
+mod untracked {
+    use crate::views::ViewError;
+
+    /// Does not track the locked entries.
+    #[derive(Debug, Default)]
+    pub(crate) struct LockTracker;
+
+    /// A locked entry, not tracked.
+    #[derive(Debug)]
+    pub(crate) struct HeldLock;
+
+    impl LockTracker {
+        /// Does nothing.
+        pub(crate) fn register(&self, _key: &[u8]) -> HeldLock {
+            HeldLock
+        }
+
+        /// Returns the error for a failure to lock the entry `key`.
+        pub(crate) fn conflict(&self, key: &[u8]) -> ViewError {
+            ViewError::TryLockError(key.to_vec())
+        }
+    }
+}
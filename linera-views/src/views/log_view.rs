@@ -6,7 +6,6 @@ use std::ops::{Bound, Range, RangeBounds};
 use std::sync::LazyLock;
 
 use async_trait::async_trait;
-use serde::{de::DeserializeOwned, Serialize};
 #[cfg(with_metrics)]
 use {
     linera_base::prometheus_util::{bucket_latencies, register_histogram_vec, MeasureLatency},
@@ -15,6 +14,7 @@ use {
 
 use crate::{
     batch::Batch,
+    codec::{update_with_encoded_values, Bcs, ValueCodec},
     common::{from_bytes_option_or_default, HasherOutput},
     context::Context,
     hashable_wrapper::WrappedHashableContainerView,
@@ -41,21 +41,25 @@ enum KeyTag {
     Index,
 }
 
-/// A view that supports logging values of type `T`.
+/// A view that supports logging values, stored with the codec `K`.
 #[derive(Debug)]
-pub struct LogView<C, T> {
+pub struct CodecLogView<C, K: ValueCodec> {
     context: C,
     delete_storage_first: bool,
     stored_count: usize,
-    new_values: Vec<T>,
+    new_values: Vec<K::Value>,
 }
 
+/// A view that supports logging values of type `T`, stored with BCS.
+pub type LogView<C, T> = CodecLogView<C, Bcs<T>>;
+
 #[async_trait]
-impl<C, T> View<C> for LogView<C, T>
+impl<C, K> View<C> for CodecLogView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    T: Send + Sync + Serialize,
+    K: ValueCodec,
+    K::Value: Send + Sync,
 {
     const NUM_INIT_KEYS: usize = 1;
 
@@ -109,7 +113,7 @@ where
                 let key = self
                     .context
                     .derive_tag_key(KeyTag::Index as u8, &self.stored_count)?;
-                batch.put_key_value(key, value)?;
+                batch.put_key_value_bytes(key, K::encode(value)?);
                 self.stored_count += 1;
             }
             let key = self.context.base_tag(KeyTag::Count as u8);
@@ -126,14 +130,15 @@ where
     }
 }
 
-impl<C, T> ClonableView<C> for LogView<C, T>
+impl<C, K> ClonableView<C> for CodecLogView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    T: Clone + Send + Sync + Serialize,
+    K: ValueCodec,
+    K::Value: Clone + Send + Sync,
 {
     fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
-        Ok(LogView {
+        Ok(CodecLogView {
             context: self.context.clone(),
             delete_storage_first: self.delete_storage_first,
             stored_count: self.stored_count,
@@ -142,9 +147,10 @@ where
     }
 }
 
-impl<C, T> LogView<C, T>
+impl<C, K> CodecLogView<C, K>
 where
     C: Context,
+    K: ValueCodec,
 {
     /// Pushes a value to the end of the log.
     /// ```rust
@@ -157,7 +163,7 @@ where
     /// log.push(34);
     /// # })
     /// ```
-    pub fn push(&mut self, value: T) {
+    pub fn push(&mut self, value: K::Value) {
         self.new_values.push(value);
     }
 
@@ -188,11 +194,12 @@ where
     }
 }
 
-impl<C, T> LogView<C, T>
+impl<C, K> CodecLogView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    T: Clone + DeserializeOwned + Serialize + Send,
+    K: ValueCodec,
+    K::Value: Clone + Send,
 {
     /// Reads the logged value with the given index (including staged ones).
     /// ```rust
@@ -206,12 +213,15 @@ where
     /// assert_eq!(log.get(0).await.unwrap(), Some(34));
     /// # })
     /// ```
-    pub async fn get(&self, index: usize) -> Result<Option<T>, ViewError> {
+    pub async fn get(&self, index: usize) -> Result<Option<K::Value>, ViewError> {
         let value = if self.delete_storage_first {
             self.new_values.get(index).cloned()
         } else if index < self.stored_count {
             let key = self.context.derive_tag_key(KeyTag::Index as u8, &index)?;
-            self.context.read_value(&key).await?
+            match self.context.read_value_bytes(&key).await? {
+                Some(bytes) => Some(K::decode(&bytes)?),
+                None => None,
+            }
         } else {
             self.new_values.get(index - self.stored_count).cloned()
         };
@@ -234,7 +244,7 @@ where
     /// );
     /// # })
     /// ```
-    pub async fn multi_get(&self, indices: Vec<usize>) -> Result<Vec<Option<T>>, ViewError> {
+    pub async fn multi_get(&self, indices: Vec<usize>) -> Result<Vec<Option<K::Value>>, ViewError> {
         let mut result = Vec::new();
         if self.delete_storage_first {
             for index in indices {
@@ -253,15 +263,17 @@ where
                     result.push(self.new_values.get(index - self.stored_count).cloned());
                 }
             }
-            let values = self.context.read_multi_values(keys).await?;
+            let values = self.context.read_multi_values_bytes(keys).await?;
             for (pos, value) in positions.into_iter().zip(values) {
-                *result.get_mut(pos).unwrap() = value;
+                if let Some(bytes) = value {
+                    *result.get_mut(pos).unwrap() = Some(K::decode(&bytes)?);
+                }
             }
         }
         Ok(result)
     }
 
-    async fn read_context(&self, range: Range<usize>) -> Result<Vec<T>, ViewError> {
+    async fn read_context(&self, range: Range<usize>) -> Result<Vec<K::Value>, ViewError> {
         let count = range.len();
         let mut keys = Vec::with_capacity(count);
         for index in range {
@@ -269,12 +281,12 @@ where
             keys.push(key);
         }
         let mut values = Vec::with_capacity(count);
-        for entry in self.context.read_multi_values(keys).await? {
+        for entry in self.context.read_multi_values_bytes(keys).await? {
             match entry {
                 None => {
                     return Err(ViewError::MissingEntries);
                 }
-                Some(value) => values.push(value),
+                Some(bytes) => values.push(K::decode(&bytes)?),
             }
         }
         Ok(values)
@@ -294,7 +306,7 @@ where
     /// assert_eq!(log.read(0..2).await.unwrap(), vec![34, 42]);
     /// # })
     /// ```
-    pub async fn read<R>(&self, range: R) -> Result<Vec<T>, ViewError>
+    pub async fn read<R>(&self, range: R) -> Result<Vec<K::Value>, ViewError>
     where
         R: RangeBounds<usize>,
     {
@@ -339,11 +351,12 @@ where
 }
 
 #[async_trait]
-impl<C, T> HashableView<C> for LogView<C, T>
+impl<C, K> HashableView<C> for CodecLogView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    T: Send + Sync + Clone + Serialize + DeserializeOwned,
+    K: ValueCodec,
+    K::Value: Send + Sync + Clone,
 {
     type Hasher = sha3::Sha3_256;
 
@@ -356,7 +369,7 @@ where
         let _hash_latency = LOG_VIEW_HASH_RUNTIME.measure_latency();
         let elements = self.read(..).await?;
         let mut hasher = sha3::Sha3_256::default();
-        hasher.update_with_bcs_bytes(&elements)?;
+        update_with_encoded_values::<K>(&mut hasher, &elements)?;
         Ok(hasher.finalize())
     }
 }
@@ -364,37 +377,47 @@ where
 /// Type wrapping `LogView` while memoizing the hash.
 pub type HashedLogView<C, T> = WrappedHashableContainerView<C, LogView<C, T>, HasherOutput>;
 
+/// Type wrapping `CodecLogView` while memoizing the hash.
+pub type HashedCodecLogView<C, K> =
+    WrappedHashableContainerView<C, CodecLogView<C, K>, HasherOutput>;
+
 mod graphql {
     use std::borrow::Cow;
 
-    use super::LogView;
+    use super::CodecLogView;
     use crate::{
+        codec::ValueCodec,
         context::Context,
         graphql::{hash_name, mangle},
     };
 
-    impl<C: Send + Sync, T: async_graphql::OutputType> async_graphql::TypeName for LogView<C, T> {
+    impl<C, K> async_graphql::TypeName for CodecLogView<C, K>
+    where
+        C: Send + Sync,
+        K: ValueCodec,
+        K::Value: async_graphql::OutputType,
+    {
         fn type_name() -> Cow<'static, str> {
             format!(
                 "LogView_{}_{:08x}",
-                mangle(T::type_name()),
-                hash_name::<T>()
+                mangle(K::Value::type_name()),
+                hash_name::<K::Value>()
             )
             .into()
         }
     }
 
     #[async_graphql::Object(cache_control(no_cache), name_type)]
-    impl<C: Context, T: async_graphql::OutputType> LogView<C, T>
+    impl<C: Context, K: ValueCodec> CodecLogView<C, K>
     where
         C: Send + Sync,
-        T: serde::ser::Serialize + serde::de::DeserializeOwned + Clone + Send + Sync,
+        K::Value: async_graphql::OutputType + Clone + Send + Sync,
     {
         async fn entries(
             &self,
             start: Option<usize>,
             end: Option<usize>,
-        ) -> async_graphql::Result<Vec<T>> {
+        ) -> async_graphql::Result<Vec<K::Value>> {
             Ok(self
                 .read(start.unwrap_or_default()..end.unwrap_or_else(|| self.count()))
                 .await?)
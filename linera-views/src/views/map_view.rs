@@ -12,6 +12,9 @@
 //! * The [`CustomMapView`][class3] whose keys are a serializable type `K` and the value a serializable type `V`.
 //!   The ordering is via the order of the custom serialized keys.
 //!
+//! The values are stored with BCS. The [`CodecByteMapView`], [`CodecMapView`] and
+//! [`CodecCustomMapView`] variants store them with another [`ValueCodec`] instead.
+//!
 //! [class1]: map_view::ByteMapView
 //! [class2]: map_view::MapView
 //! [class3]: map_view::CustomMapView
@@ -48,8 +51,9 @@ use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
     batch::Batch,
+    codec::{Bcs, ValueCodec},
     common::{
-        from_bytes_option, get_interval, CustomSerialize, DeletionSet, HasherOutput,
+        find_keys_after, get_interval, CustomSerialize, DeletionSet, HasherOutput,
         SuffixClosedSetIterator, Update,
     },
     context::Context,
@@ -58,12 +62,22 @@ use crate::{
     views::{ClonableView, HashableView, Hasher, View, ViewError},
 };
 
-/// A view that supports inserting and removing values indexed by `Vec<u8>`.
+/// A view that supports inserting and removing values indexed by `Vec<u8>`, stored with the
+/// codec `K`.
 #[derive(Debug)]
-pub struct ByteMapView<C, V> {
+pub struct CodecByteMapView<C, K: ValueCodec> {
     context: C,
     deletion_set: DeletionSet,
-    updates: BTreeMap<Vec<u8>, Update<V>>,
+    updates: BTreeMap<Vec<u8>, Update<K::Value>>,
+}
+
+/// A view that supports inserting and removing values of type `V` indexed by `Vec<u8>`,
+/// stored with BCS.
+pub type ByteMapView<C, V> = CodecByteMapView<C, Bcs<V>>;
+
+/// Decodes the value stored under a key, if any.
+fn decode_option<K: ValueCodec>(bytes: Option<Vec<u8>>) -> Result<Option<K::Value>, ViewError> {
+    bytes.map(|bytes| K::decode(&bytes)).transpose()
 }
 
 /// Whether we have a value or its serialization.
@@ -76,36 +90,34 @@ enum ValueOrBytes<'a, T> {
 
 impl<'a, T> ValueOrBytes<'a, T>
 where
-    T: Clone + DeserializeOwned,
+    T: Clone,
 {
-    /// Convert to a Cow.
-    fn to_value(&self) -> Result<Cow<'a, T>, ViewError> {
+    /// Convert to a Cow, decoding the serialization with `K`.
+    fn to_value<K: ValueCodec<Value = T>>(&self) -> Result<Cow<'a, T>, ViewError> {
         match self {
             ValueOrBytes::Value(value) => Ok(Cow::Borrowed(value)),
-            ValueOrBytes::Bytes(bytes) => Ok(Cow::Owned(bcs::from_bytes(bytes)?)),
+            ValueOrBytes::Bytes(bytes) => Ok(Cow::Owned(K::decode(bytes)?)),
         }
     }
 }
 
-impl<'a, T> ValueOrBytes<'a, T>
-where
-    T: Serialize,
-{
-    /// Convert to bytes.
-    pub fn into_bytes(self) -> Result<Vec<u8>, ViewError> {
+impl<T> ValueOrBytes<'_, T> {
+    /// Convert to bytes, encoding the value with `K`.
+    pub fn into_bytes<K: ValueCodec<Value = T>>(self) -> Result<Vec<u8>, ViewError> {
         match self {
-            ValueOrBytes::Value(value) => Ok(bcs::to_bytes(value)?),
+            ValueOrBytes::Value(value) => K::encode(value),
             ValueOrBytes::Bytes(bytes) => Ok(bytes),
         }
     }
 }
 
 #[async_trait]
-impl<C, V> View<C> for ByteMapView<C, V>
+impl<C, K> View<C> for CodecByteMapView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    V: Send + Sync + Serialize,
+    K: ValueCodec,
+    K::Value: Send + Sync,
 {
     const NUM_INIT_KEYS: usize = 0;
 
@@ -146,7 +158,7 @@ where
             for (index, update) in mem::take(&mut self.updates) {
                 if let Update::Set(value) = update {
                     let key = self.context.base_index(&index);
-                    batch.put_key_value(key, &value)?;
+                    batch.put_key_value_bytes(key, K::encode(&value)?);
                     delete_view = false;
                 }
             }
@@ -159,7 +171,7 @@ where
                 let key = self.context.base_index(&index);
                 match update {
                     Update::Removed => batch.delete_key(key),
-                    Update::Set(value) => batch.put_key_value(key, &value)?,
+                    Update::Set(value) => batch.put_key_value_bytes(key, K::encode(&value)?),
                 }
             }
         }
@@ -173,14 +185,15 @@ where
     }
 }
 
-impl<C, V> ClonableView<C> for ByteMapView<C, V>
+impl<C, K> ClonableView<C> for CodecByteMapView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    V: Clone + Send + Sync + Serialize,
+    K: ValueCodec,
+    K::Value: Clone + Send + Sync,
 {
     fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
-        Ok(ByteMapView {
+        Ok(CodecByteMapView {
             context: self.context.clone(),
             updates: self.updates.clone(),
             deletion_set: self.deletion_set.clone(),
@@ -188,10 +201,11 @@ where
     }
 }
 
-impl<C, V> ByteMapView<C, V>
+impl<C, K> CodecByteMapView<C, K>
 where
     C: Context,
     ViewError: From<C::Error>,
+    K: ValueCodec,
 {
     /// Inserts or resets the value of a key of the map.
     /// ```rust
@@ -205,10 +219,32 @@ where
     /// assert_eq!(map.keys().await.unwrap(), vec![vec![0, 1]]);
     /// # })
     /// ```
-    pub fn insert(&mut self, short_key: Vec<u8>, value: V) {
+    pub fn insert(&mut self, short_key: Vec<u8>, value: K::Value) {
         self.updates.insert(short_key, Update::Set(value));
     }
 
+    /// Inserts or resets the values of many keys of the map at once. The entries are sorted,
+    /// then merged with the pending updates in a single pass, instead of being inserted one
+    /// at a time.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.extend([(vec![0, 1], "Hello"), (vec![0, 2], "World")]);
+    /// assert_eq!(map.keys().await.unwrap(), vec![vec![0, 1], vec![0, 2]]);
+    /// # })
+    /// ```
+    pub fn extend(&mut self, entries: impl IntoIterator<Item = (Vec<u8>, K::Value)>) {
+        let mut updates = entries
+            .into_iter()
+            .map(|(short_key, value)| (short_key, Update::Set(value)))
+            .collect::<BTreeMap<_, _>>();
+        self.updates.append(&mut updates);
+    }
+
     /// Removes a value. If absent then nothing is done.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -290,11 +326,12 @@ where
     }
 }
 
-impl<C, V> ByteMapView<C, V>
+impl<C, K> CodecByteMapView<C, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
-    V: Clone + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + 'static,
 {
     /// Reads the value at the given position, if any.
     /// ```rust
@@ -308,7 +345,7 @@ where
     /// assert_eq!(map.get(&[0, 1]).await.unwrap(), Some(String::from("Hello")));
     /// # })
     /// ```
-    pub async fn get(&self, short_key: &[u8]) -> Result<Option<V>, ViewError> {
+    pub async fn get(&self, short_key: &[u8]) -> Result<Option<K::Value>, ViewError> {
         if let Some(update) = self.updates.get(short_key) {
             let value = match update {
                 Update::Removed => None,
@@ -320,7 +357,7 @@ where
             return Ok(None);
         }
         let key = self.context.base_index(short_key);
-        Ok(self.context.read_value(&key).await?)
+        decode_option::<K>(self.context.read_value_bytes(&key).await?)
     }
 
     /// Reads the values at the given positions, if any.
@@ -336,7 +373,10 @@ where
     /// assert_eq!(values, vec![Some(String::from("Hello")), None]);
     /// # })
     /// ```
-    pub async fn multi_get(&self, short_keys: Vec<Vec<u8>>) -> Result<Vec<Option<V>>, ViewError> {
+    pub async fn multi_get(
+        &self,
+        short_keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<K::Value>>, ViewError> {
         let size = short_keys.len();
         let mut results = vec![None; size];
         let mut missed_indices = Vec::new();
@@ -354,7 +394,7 @@ where
         }
         let values = self.context.read_multi_values_bytes(vector_query).await?;
         for (i, value) in missed_indices.into_iter().zip(values) {
-            results[i] = from_bytes_option(&value)?;
+            results[i] = decode_option::<K>(value)?;
         }
         Ok(results)
     }
@@ -374,14 +414,14 @@ where
     /// assert_eq!(map.get(&[0, 1]).await.unwrap(), Some(String::from("Hola")));
     /// # })
     /// ```
-    pub async fn get_mut(&mut self, short_key: &[u8]) -> Result<Option<&mut V>, ViewError> {
+    pub async fn get_mut(&mut self, short_key: &[u8]) -> Result<Option<&mut K::Value>, ViewError> {
         let update = match self.updates.entry(short_key.to_vec()) {
             Entry::Vacant(e) => {
                 if self.deletion_set.contains_prefix_of(short_key) {
                     None
                 } else {
                     let key = self.context.base_index(short_key);
-                    let value = self.context.read_value(&key).await?;
+                    let value = decode_option::<K>(self.context.read_value_bytes(&key).await?)?;
                     value.map(|value| e.insert(Update::Set(value)))
                 }
             }
@@ -394,11 +434,12 @@ where
     }
 }
 
-impl<C, V> ByteMapView<C, V>
+impl<C, K> CodecByteMapView<C, K>
 where
     C: Context,
     ViewError: From<C::Error>,
-    V: Clone + Serialize + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + 'static,
 {
     /// Applies the function f on each index (aka key) which has the assigned prefix.
     /// Keys are visited in the lexicographic order. The shortened key is send to the
@@ -581,6 +622,41 @@ where
         Ok(keys)
     }
 
+    /// Returns at most `limit` keys of the map that come strictly after `start_after`, in
+    /// lexicographic order. Only the keys of that page are read from storage, so that
+    /// paginating through the map doesn't re-scan it from the beginning.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// map.insert(vec![2, 2], String::from("Hallo"));
+    /// assert_eq!(
+    ///     map.keys_after(Some(&[0, 1]), Some(1)).await.unwrap(),
+    ///     vec![vec![1, 2]]
+    /// );
+    /// # })
+    /// ```
+    pub async fn keys_after(
+        &self,
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, ViewError> {
+        find_keys_after(
+            &self.context,
+            &self.context.base_key(),
+            &self.updates,
+            |key| self.deletion_set.contains_prefix_of(key),
+            start_after,
+            limit,
+        )
+        .await
+    }
+
     /// Returns the number of keys of the map
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -621,7 +697,7 @@ where
         prefix: Vec<u8>,
     ) -> Result<(), ViewError>
     where
-        F: FnMut(&[u8], ValueOrBytes<'a, V>) -> Result<bool, ViewError> + Send,
+        F: FnMut(&[u8], ValueOrBytes<'a, K::Value>) -> Result<bool, ViewError> + Send,
     {
         let prefix_len = prefix.len();
         let mut updates = self.updates.range(get_interval(prefix.clone()));
@@ -712,11 +788,11 @@ where
         prefix: Vec<u8>,
     ) -> Result<(), ViewError>
     where
-        F: FnMut(&[u8], Cow<'a, V>) -> Result<bool, ViewError> + Send,
+        F: FnMut(&[u8], Cow<'a, K::Value>) -> Result<bool, ViewError> + Send,
     {
         self.for_each_key_value_or_bytes_while(
             |key, value| {
-                let value = value.to_value()?;
+                let value = value.to_value::<K>()?;
                 f(key, value)
             },
             prefix,
@@ -735,7 +811,7 @@ where
         prefix: Vec<u8>,
     ) -> Result<(), ViewError>
     where
-        F: FnMut(&[u8], ValueOrBytes<'a, V>) -> Result<(), ViewError> + Send,
+        F: FnMut(&[u8], ValueOrBytes<'a, K::Value>) -> Result<(), ViewError> + Send,
     {
         self.for_each_key_value_or_bytes_while(
             |key, value| {
@@ -778,7 +854,7 @@ where
         prefix: Vec<u8>,
     ) -> Result<(), ViewError>
     where
-        F: FnMut(&[u8], Cow<'a, V>) -> Result<(), ViewError> + Send,
+        F: FnMut(&[u8], Cow<'a, K::Value>) -> Result<(), ViewError> + Send,
     {
         self.for_each_key_value_while(
             |key, value| {
@@ -791,11 +867,12 @@ where
     }
 }
 
-impl<C, V> ByteMapView<C, V>
+impl<C, K> CodecByteMapView<C, K>
 where
     C: Context,
     ViewError: From<C::Error>,
-    V: Clone + Send + Serialize + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + Send + 'static,
 {
     /// Returns the list of keys and values of the map matching a prefix
     /// in lexicographic order.
@@ -817,7 +894,7 @@ where
     pub async fn key_values_by_prefix(
         &self,
         prefix: Vec<u8>,
-    ) -> Result<Vec<(Vec<u8>, V)>, ViewError> {
+    ) -> Result<Vec<(Vec<u8>, K::Value)>, ViewError> {
         let mut key_values = Vec::new();
         let prefix_copy = prefix.clone();
         self.for_each_key_value(
@@ -849,16 +926,17 @@ where
     /// );
     /// # })
     /// ```
-    pub async fn key_values(&self) -> Result<Vec<(Vec<u8>, V)>, ViewError> {
+    pub async fn key_values(&self) -> Result<Vec<(Vec<u8>, K::Value)>, ViewError> {
         self.key_values_by_prefix(Vec::new()).await
     }
 }
 
-impl<C, V> ByteMapView<C, V>
+impl<C, K> CodecByteMapView<C, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
-    V: Default + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Default + 'static,
 {
     /// Obtains a mutable reference to a value at a given position.
     /// Default value if the index is missing.
@@ -877,14 +955,18 @@ where
     /// assert_eq!(map.get(&[0, 1]).await.unwrap(), Some(String::from("Hola")));
     /// # })
     /// ```
-    pub async fn get_mut_or_default(&mut self, short_key: &[u8]) -> Result<&mut V, ViewError> {
+    pub async fn get_mut_or_default(
+        &mut self,
+        short_key: &[u8],
+    ) -> Result<&mut K::Value, ViewError> {
         let update = match self.updates.entry(short_key.to_vec()) {
             Entry::Vacant(e) if self.deletion_set.contains_prefix_of(short_key) => {
-                e.insert(Update::Set(V::default()))
+                e.insert(Update::Set(K::Value::default()))
             }
             Entry::Vacant(e) => {
                 let key = self.context.base_index(short_key);
-                let value = self.context.read_value(&key).await?.unwrap_or_default();
+                let value = decode_option::<K>(self.context.read_value_bytes(&key).await?)?
+                    .unwrap_or_default();
                 e.insert(Update::Set(value))
             }
             Entry::Occupied(entry) => {
@@ -892,7 +974,7 @@ where
                 match entry {
                     Update::Set(_) => &mut *entry,
                     Update::Removed => {
-                        *entry = Update::Set(V::default());
+                        *entry = Update::Set(K::Value::default());
                         &mut *entry
                     }
                 }
@@ -906,11 +988,12 @@ where
 }
 
 #[async_trait]
-impl<C, V> HashableView<C> for ByteMapView<C, V>
+impl<C, K> HashableView<C> for CodecByteMapView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + Send + Sync + 'static,
 {
     type Hasher = sha3::Sha3_256;
 
@@ -928,7 +1011,7 @@ where
             |index, value| {
                 count += 1;
                 hasher.update_with_bytes(index)?;
-                let bytes = value.into_bytes()?;
+                let bytes = value.into_bytes::<K>()?;
                 hasher.update_with_bytes(&bytes)?;
                 Ok(())
             },
@@ -941,34 +1024,38 @@ where
 }
 
 /// A `View` that has a type for keys. The ordering of the entries
-/// is determined by the serialization of the context.
+/// is determined by the serialization of the context. The values are stored with the codec `K`.
 #[derive(Debug)]
-pub struct MapView<C, I, V> {
-    map: ByteMapView<C, V>,
+pub struct CodecMapView<C, I, K: ValueCodec> {
+    map: CodecByteMapView<C, K>,
     _phantom: PhantomData<I>,
 }
 
+/// A `View` that has a type for keys, whose values of type `V` are stored with BCS.
+pub type MapView<C, I, V> = CodecMapView<C, I, Bcs<V>>;
+
 #[async_trait]
-impl<C, I, V> View<C> for MapView<C, I, V>
+impl<C, I, K> View<C> for CodecMapView<C, I, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
     I: Sync,
-    V: Send + Sync + Serialize,
+    K: ValueCodec,
+    K::Value: Send + Sync,
 {
-    const NUM_INIT_KEYS: usize = ByteMapView::<C, V>::NUM_INIT_KEYS;
+    const NUM_INIT_KEYS: usize = CodecByteMapView::<C, K>::NUM_INIT_KEYS;
 
     fn context(&self) -> &C {
         self.map.context()
     }
 
     fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
-        ByteMapView::<C, V>::pre_load(context)
+        CodecByteMapView::<C, K>::pre_load(context)
     }
 
     fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
-        let map = ByteMapView::post_load(context, values)?;
-        Ok(MapView {
+        let map = CodecByteMapView::post_load(context, values)?;
+        Ok(CodecMapView {
             map,
             _phantom: PhantomData,
         })
@@ -995,26 +1082,28 @@ where
     }
 }
 
-impl<C, I, V> ClonableView<C> for MapView<C, I, V>
+impl<C, I, K> ClonableView<C> for CodecMapView<C, I, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
     I: Sync,
-    V: Clone + Send + Sync + Serialize,
+    K: ValueCodec,
+    K::Value: Clone + Send + Sync,
 {
     fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
-        Ok(MapView {
+        Ok(CodecMapView {
             map: self.map.clone_unchecked()?,
             _phantom: PhantomData,
         })
     }
 }
 
-impl<C, I, V> MapView<C, I, V>
+impl<C, I, K> CodecMapView<C, I, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
     I: Serialize,
+    K: ValueCodec,
 {
     /// Inserts or resets a value at an index.
     /// ```rust
@@ -1031,7 +1120,7 @@ where
     /// );
     /// # })
     /// ```
-    pub fn insert<Q>(&mut self, index: &Q, value: V) -> Result<(), ViewError>
+    pub fn insert<Q>(&mut self, index: &Q, value: K::Value) -> Result<(), ViewError>
     where
         I: Borrow<Q>,
         Q: Serialize + ?Sized,
@@ -1041,6 +1130,67 @@ where
         Ok(())
     }
 
+    /// Inserts or resets the values at many indices at once. If an index cannot be
+    /// serialized, the map is left unchanged.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::MapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map: MapView<_, u32, _> = MapView::load(context).await.unwrap();
+    /// map.insert_batch([
+    ///     (&(24 as u32), String::from("Hello")),
+    ///     (&(37 as u32), String::from("World")),
+    /// ])
+    /// .unwrap();
+    /// assert_eq!(
+    ///     map.get(&(37 as u32)).await.unwrap(),
+    ///     Some(String::from("World"))
+    /// );
+    /// # })
+    /// ```
+    pub fn insert_batch<'b, Q>(
+        &mut self,
+        entries: impl IntoIterator<Item = (&'b Q, K::Value)>,
+    ) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized + 'b,
+    {
+        let entries = entries
+            .into_iter()
+            .map(|(index, value)| Ok::<_, ViewError>((C::derive_short_key(index)?, value)))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.map.extend(entries);
+        Ok(())
+    }
+
+    /// Inserts or resets the values at the indices of `entries`. If an index cannot be
+    /// serialized, the map is left unchanged.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::MapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map: MapView<_, u32, _> = MapView::load(context).await.unwrap();
+    /// map.extend((0..10).map(|i| (i, i * i))).unwrap();
+    /// assert_eq!(map.get(&(7 as u32)).await.unwrap(), Some(49));
+    /// # })
+    /// ```
+    pub fn extend(
+        &mut self,
+        entries: impl IntoIterator<Item = (I, K::Value)>,
+    ) -> Result<(), ViewError> {
+        let entries = entries
+            .into_iter()
+            .map(|(index, value)| Ok::<_, ViewError>((C::derive_short_key(&index)?, value)))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.map.extend(entries);
+        Ok(())
+    }
+
     /// Removes a value. If absent then the operation does nothing.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -1091,12 +1241,13 @@ where
     }
 }
 
-impl<C, I, V> MapView<C, I, V>
+impl<C, I, K> CodecMapView<C, I, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
     I: Serialize,
-    V: Clone + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + 'static,
 {
     /// Reads the value at the given position, if any.
     /// ```rust
@@ -1114,7 +1265,7 @@ where
     /// assert_eq!(map.get(&(34 as u32)).await.unwrap(), None);
     /// # })
     /// ```
-    pub async fn get<Q>(&self, index: &Q) -> Result<Option<V>, ViewError>
+    pub async fn get<Q>(&self, index: &Q) -> Result<Option<K::Value>, ViewError>
     where
         I: Borrow<Q>,
         Q: Serialize + ?Sized,
@@ -1141,7 +1292,7 @@ where
     /// );
     /// # })
     /// ```
-    pub async fn get_mut<Q>(&mut self, index: &Q) -> Result<Option<&mut V>, ViewError>
+    pub async fn get_mut<Q>(&mut self, index: &Q) -> Result<Option<&mut K::Value>, ViewError>
     where
         I: Borrow<Q>,
         Q: Serialize + ?Sized,
@@ -1151,12 +1302,13 @@ where
     }
 }
 
-impl<C, I, V> MapView<C, I, V>
+impl<C, I, K> CodecMapView<C, I, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
     I: Send + DeserializeOwned,
-    V: Clone + Sync + Serialize + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + Sync + 'static,
 {
     /// Returns the list of indices in the map. The order is determined by serialization.
     /// ```rust
@@ -1180,6 +1332,41 @@ where
         Ok(indices)
     }
 
+    /// Returns at most `limit` indices in the map that come strictly after `start_after`.
+    /// The order is determined by serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::MapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map: MapView<_, u32, String> = MapView::load(context).await.unwrap();
+    /// map.insert(&(34 as u32), String::from("Hello"));
+    /// map.insert(&(37 as u32), String::from("Bonjour"));
+    /// map.insert(&(38 as u32), String::from("Hallo"));
+    /// assert_eq!(
+    ///     map.indices_after(Some(&34), Some(1)).await.unwrap(),
+    ///     vec![37 as u32]
+    /// );
+    /// # })
+    /// ```
+    pub async fn indices_after(
+        &self,
+        start_after: Option<&I>,
+        limit: Option<usize>,
+    ) -> Result<Vec<I>, ViewError>
+    where
+        I: Serialize,
+    {
+        let start_after = start_after.map(C::derive_short_key).transpose()?;
+        self.map
+            .keys_after(start_after.as_deref(), limit)
+            .await?
+            .iter()
+            .map(|key| Ok(C::deserialize_value(key)?))
+            .collect()
+    }
+
     /// Applies a function f on each index. Indices are visited in an order
     /// determined by the serialization. If the function returns false, then
     /// the loop ends prematurely.
@@ -1282,7 +1469,7 @@ where
     /// ```
     pub async fn for_each_index_value_while<'a, F>(&'a self, mut f: F) -> Result<(), ViewError>
     where
-        F: FnMut(I, Cow<'a, V>) -> Result<bool, ViewError> + Send,
+        F: FnMut(I, Cow<'a, K::Value>) -> Result<bool, ViewError> + Send,
     {
         let prefix = Vec::new();
         self.map
@@ -1319,7 +1506,7 @@ where
     /// ```
     pub async fn for_each_index_value<'a, F>(&'a self, mut f: F) -> Result<(), ViewError>
     where
-        F: FnMut(I, Cow<'a, V>) -> Result<(), ViewError> + Send,
+        F: FnMut(I, Cow<'a, K::Value>) -> Result<(), ViewError> + Send,
     {
         let prefix = Vec::new();
         self.map
@@ -1335,12 +1522,13 @@ where
     }
 }
 
-impl<C, I, V> MapView<C, I, V>
+impl<C, I, K> CodecMapView<C, I, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
     I: Send + DeserializeOwned,
-    V: Clone + Sync + Send + Serialize + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + Sync + Send + 'static,
 {
     /// Obtains all the `(index,value)` pairs.
     /// ```rust
@@ -1358,7 +1546,7 @@ where
     /// );
     /// # })
     /// ```
-    pub async fn index_values(&self) -> Result<Vec<(I, V)>, ViewError> {
+    pub async fn index_values(&self) -> Result<Vec<(I, K::Value)>, ViewError> {
         let mut key_values = Vec::new();
         self.for_each_index_value(|index, value| {
             let value = value.into_owned();
@@ -1387,12 +1575,13 @@ where
     }
 }
 
-impl<C, I, V> MapView<C, I, V>
+impl<C, I, K> CodecMapView<C, I, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
     I: Serialize,
-    V: Default + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Default + 'static,
 {
     /// Obtains a mutable reference to a value at a given position.
     /// Default value if the index is missing.
@@ -1407,7 +1596,7 @@ where
     /// assert_eq!(*value, 0 as u128);
     /// # })
     /// ```
-    pub async fn get_mut_or_default<Q>(&mut self, index: &Q) -> Result<&mut V, ViewError>
+    pub async fn get_mut_or_default<Q>(&mut self, index: &Q) -> Result<&mut K::Value, ViewError>
     where
         I: Borrow<Q>,
         Q: Sync + Send + Serialize + ?Sized,
@@ -1418,12 +1607,13 @@ where
 }
 
 #[async_trait]
-impl<C, I, V> HashableView<C> for MapView<C, I, V>
+impl<C, I, K> HashableView<C> for CodecMapView<C, I, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
     I: Send + Sync + Serialize + DeserializeOwned,
-    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + Send + Sync + 'static,
 {
     type Hasher = sha3::Sha3_256;
 
@@ -1436,34 +1626,39 @@ where
     }
 }
 
-/// A Custom MapView that uses the custom serialization
+/// A Custom MapView that uses the custom serialization, and stores the values with the codec `K`.
 #[derive(Debug)]
-pub struct CustomMapView<C, I, V> {
-    map: ByteMapView<C, V>,
+pub struct CodecCustomMapView<C, I, K: ValueCodec> {
+    map: CodecByteMapView<C, K>,
     _phantom: PhantomData<I>,
 }
 
+/// A Custom MapView that uses the custom serialization, whose values of type `V` are stored with
+/// BCS.
+pub type CustomMapView<C, I, V> = CodecCustomMapView<C, I, Bcs<V>>;
+
 #[async_trait]
-impl<C, I, V> View<C> for CustomMapView<C, I, V>
+impl<C, I, K> View<C> for CodecCustomMapView<C, I, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
     I: Send + Sync + CustomSerialize,
-    V: Clone + Send + Sync + Serialize,
+    K: ValueCodec,
+    K::Value: Clone + Send + Sync,
 {
-    const NUM_INIT_KEYS: usize = ByteMapView::<C, V>::NUM_INIT_KEYS;
+    const NUM_INIT_KEYS: usize = CodecByteMapView::<C, K>::NUM_INIT_KEYS;
 
     fn context(&self) -> &C {
         self.map.context()
     }
 
     fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
-        ByteMapView::<C, V>::pre_load(context)
+        CodecByteMapView::<C, K>::pre_load(context)
     }
 
     fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
-        let map = ByteMapView::post_load(context, values)?;
-        Ok(CustomMapView {
+        let map = CodecByteMapView::post_load(context, values)?;
+        Ok(CodecCustomMapView {
             map,
             _phantom: PhantomData,
         })
@@ -1490,26 +1685,28 @@ where
     }
 }
 
-impl<C, I, V> ClonableView<C> for CustomMapView<C, I, V>
+impl<C, I, K> ClonableView<C> for CodecCustomMapView<C, I, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
     I: Send + Sync + CustomSerialize,
-    V: Clone + Send + Sync + Serialize,
+    K: ValueCodec,
+    K::Value: Clone + Send + Sync,
 {
     fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
-        Ok(CustomMapView {
+        Ok(CodecCustomMapView {
             map: self.map.clone_unchecked()?,
             _phantom: PhantomData,
         })
     }
 }
 
-impl<C, I, V> CustomMapView<C, I, V>
+impl<C, I, K> CodecCustomMapView<C, I, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
     I: CustomSerialize,
+    K: ValueCodec,
 {
     /// Insert or resets a value.
     /// ```rust
@@ -1526,7 +1723,7 @@ where
     /// );
     /// # })
     /// ```
-    pub fn insert<Q>(&mut self, index: &Q, value: V) -> Result<(), ViewError>
+    pub fn insert<Q>(&mut self, index: &Q, value: K::Value) -> Result<(), ViewError>
     where
         I: Borrow<Q>,
         Q: Serialize + CustomSerialize,
@@ -1536,6 +1733,67 @@ where
         Ok(())
     }
 
+    /// Inserts or resets the values at many indices at once. If an index cannot be
+    /// serialized, the map is left unchanged.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::CustomMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map: CustomMapView<_, u128, _> = CustomMapView::load(context).await.unwrap();
+    /// map.insert_batch([
+    ///     (&(24 as u128), String::from("Hello")),
+    ///     (&(37 as u128), String::from("World")),
+    /// ])
+    /// .unwrap();
+    /// assert_eq!(
+    ///     map.get(&(37 as u128)).await.unwrap(),
+    ///     Some(String::from("World"))
+    /// );
+    /// # })
+    /// ```
+    pub fn insert_batch<'b, Q>(
+        &mut self,
+        entries: impl IntoIterator<Item = (&'b Q, K::Value)>,
+    ) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + CustomSerialize + 'b,
+    {
+        let entries = entries
+            .into_iter()
+            .map(|(index, value)| Ok::<_, ViewError>((index.to_custom_bytes()?, value)))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.map.extend(entries);
+        Ok(())
+    }
+
+    /// Inserts or resets the values at the indices of `entries`. If an index cannot be
+    /// serialized, the map is left unchanged.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::CustomMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map: CustomMapView<_, u128, _> = CustomMapView::load(context).await.unwrap();
+    /// map.extend((0..10).map(|i| (i, i * i))).unwrap();
+    /// assert_eq!(map.get(&(7 as u128)).await.unwrap(), Some(49));
+    /// # })
+    /// ```
+    pub fn extend(
+        &mut self,
+        entries: impl IntoIterator<Item = (I, K::Value)>,
+    ) -> Result<(), ViewError> {
+        let entries = entries
+            .into_iter()
+            .map(|(index, value)| Ok::<_, ViewError>((index.to_custom_bytes()?, value)))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.map.extend(entries);
+        Ok(())
+    }
+
     /// Removes a value. If absent then this does not do anything.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -1586,12 +1844,13 @@ where
     }
 }
 
-impl<C, I, V> CustomMapView<C, I, V>
+impl<C, I, K> CodecCustomMapView<C, I, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
     I: CustomSerialize,
-    V: Clone + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + 'static,
 {
     /// Reads the value at the given position, if any.
     /// ```rust
@@ -1609,7 +1868,7 @@ where
     /// );
     /// # })
     /// ```
-    pub async fn get<Q>(&self, index: &Q) -> Result<Option<V>, ViewError>
+    pub async fn get<Q>(&self, index: &Q) -> Result<Option<K::Value>, ViewError>
     where
         I: Borrow<Q>,
         Q: CustomSerialize,
@@ -1635,7 +1894,7 @@ where
     /// );
     /// # })
     /// ```
-    pub async fn get_mut<Q>(&mut self, index: &Q) -> Result<Option<&mut V>, ViewError>
+    pub async fn get_mut<Q>(&mut self, index: &Q) -> Result<Option<&mut K::Value>, ViewError>
     where
         I: Borrow<Q>,
         Q: CustomSerialize,
@@ -1645,12 +1904,13 @@ where
     }
 }
 
-impl<C, I, V> CustomMapView<C, I, V>
+impl<C, I, K> CodecCustomMapView<C, I, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
     I: Send + CustomSerialize,
-    V: Clone + Serialize + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + 'static,
 {
     /// Returns the list of indices in the map. The order is determined
     /// by the custom serialization.
@@ -1676,6 +1936,37 @@ where
         Ok(indices)
     }
 
+    /// Returns at most `limit` indices in the map that come strictly after `start_after`.
+    /// The order is determined by the custom serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::CustomMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map: CustomMapView<_, u128, String> = CustomMapView::load(context).await.unwrap();
+    /// map.insert(&(34 as u128), String::from("Hello"));
+    /// map.insert(&(37 as u128), String::from("Bonjour"));
+    /// assert_eq!(
+    ///     map.indices_after(Some(&34), None).await.unwrap(),
+    ///     vec![37 as u128]
+    /// );
+    /// # })
+    /// ```
+    pub async fn indices_after(
+        &self,
+        start_after: Option<&I>,
+        limit: Option<usize>,
+    ) -> Result<Vec<I>, ViewError> {
+        let start_after = start_after.map(I::to_custom_bytes).transpose()?;
+        self.map
+            .keys_after(start_after.as_deref(), limit)
+            .await?
+            .iter()
+            .map(|key| I::from_custom_bytes(key))
+            .collect()
+    }
+
     /// Applies a function f on each index. Indices are visited in an order
     /// determined by the custom serialization. If the function returns false,
     /// then the loop ends prematurely.
@@ -1779,7 +2070,7 @@ where
     /// ```
     pub async fn for_each_index_value_while<'a, F>(&'a self, mut f: F) -> Result<(), ViewError>
     where
-        F: FnMut(I, Cow<'a, V>) -> Result<bool, ViewError> + Send,
+        F: FnMut(I, Cow<'a, K::Value>) -> Result<bool, ViewError> + Send,
     {
         let prefix = Vec::new();
         self.map
@@ -1817,7 +2108,7 @@ where
     /// ```
     pub async fn for_each_index_value<'a, F>(&'a self, mut f: F) -> Result<(), ViewError>
     where
-        F: FnMut(I, Cow<'a, V>) -> Result<(), ViewError> + Send,
+        F: FnMut(I, Cow<'a, K::Value>) -> Result<(), ViewError> + Send,
     {
         let prefix = Vec::new();
         self.map
@@ -1833,12 +2124,13 @@ where
     }
 }
 
-impl<C, I, V> CustomMapView<C, I, V>
+impl<C, I, K> CodecCustomMapView<C, I, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
     I: Send + CustomSerialize,
-    V: Clone + Sync + Send + Serialize + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + Sync + Send + 'static,
 {
     /// Obtains all the `(index,value)` pairs.
     /// ```rust
@@ -1856,7 +2148,7 @@ where
     /// );
     /// # })
     /// ```
-    pub async fn index_values(&self) -> Result<Vec<(I, V)>, ViewError> {
+    pub async fn index_values(&self) -> Result<Vec<(I, K::Value)>, ViewError> {
         let mut key_values = Vec::new();
         self.for_each_index_value(|index, value| {
             let value = value.into_owned();
@@ -1885,12 +2177,13 @@ where
     }
 }
 
-impl<C, I, V> CustomMapView<C, I, V>
+impl<C, I, K> CodecCustomMapView<C, I, K>
 where
     C: Context + Sync,
     ViewError: From<C::Error>,
     I: CustomSerialize,
-    V: Default + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Default + 'static,
 {
     /// Obtains a mutable reference to a value at a given position.
     /// Default value if the index is missing.
@@ -1907,7 +2200,7 @@ where
     /// );
     /// # })
     /// ```
-    pub async fn get_mut_or_default<Q>(&mut self, index: &Q) -> Result<&mut V, ViewError>
+    pub async fn get_mut_or_default<Q>(&mut self, index: &Q) -> Result<&mut K::Value, ViewError>
     where
         I: Borrow<Q>,
         Q: Send + CustomSerialize,
@@ -1918,12 +2211,13 @@ where
 }
 
 #[async_trait]
-impl<C, I, V> HashableView<C> for CustomMapView<C, I, V>
+impl<C, I, K> HashableView<C> for CodecCustomMapView<C, I, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
     I: Send + Sync + CustomSerialize,
-    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+    K: ValueCodec,
+    K::Value: Clone + Send + Sync + 'static,
 {
     type Hasher = sha3::Sha3_256;
 
@@ -1946,53 +2240,65 @@ pub type HashedMapView<C, I, V> = WrappedHashableContainerView<C, MapView<C, I,
 pub type HashedCustomMapView<C, I, V> =
     WrappedHashableContainerView<C, CustomMapView<C, I, V>, HasherOutput>;
 
+/// Type wrapping `CodecByteMapView` while memoizing the hash.
+pub type HashedCodecByteMapView<C, K> =
+    WrappedHashableContainerView<C, CodecByteMapView<C, K>, HasherOutput>;
+
+/// Type wrapping `CodecMapView` while memoizing the hash.
+pub type HashedCodecMapView<C, I, K> =
+    WrappedHashableContainerView<C, CodecMapView<C, I, K>, HasherOutput>;
+
+/// Type wrapping `CodecCustomMapView` while memoizing the hash.
+pub type HashedCodecCustomMapView<C, I, K> =
+    WrappedHashableContainerView<C, CodecCustomMapView<C, I, K>, HasherOutput>;
+
 mod graphql {
     use std::borrow::Cow;
 
-    use super::{ByteMapView, CustomMapView, MapView};
+    use super::{CodecByteMapView, CodecCustomMapView, CodecMapView};
     use crate::{
+        codec::ValueCodec,
         context::Context,
         graphql::{hash_name, mangle, Entry, MapInput},
     };
 
-    impl<C: Send + Sync, V: async_graphql::OutputType> async_graphql::TypeName for ByteMapView<C, V> {
+    impl<C, K> async_graphql::TypeName for CodecByteMapView<C, K>
+    where
+        C: Send + Sync,
+        K: ValueCodec,
+        K::Value: async_graphql::OutputType,
+    {
         fn type_name() -> Cow<'static, str> {
             format!(
                 "ByteMapView_{}_{:08x}",
-                mangle(V::type_name()),
-                hash_name::<V>()
+                mangle(K::Value::type_name()),
+                hash_name::<K::Value>()
             )
             .into()
         }
     }
 
     #[async_graphql::Object(cache_control(no_cache), name_type)]
-    impl<C, V> ByteMapView<C, V>
+    impl<C, K> CodecByteMapView<C, K>
     where
         C: Context + Send + Sync,
-        V: async_graphql::OutputType
-            + serde::ser::Serialize
-            + serde::de::DeserializeOwned
-            + Clone
-            + Send
-            + Sync
-            + 'static,
+        K: ValueCodec,
+        K::Value: async_graphql::OutputType + Clone + Send + Sync + 'static,
     {
         #[graphql(derived(name = "keys"))]
-        async fn keys_(&self, count: Option<usize>) -> Result<Vec<Vec<u8>>, async_graphql::Error> {
-            let keys = self.keys().await?;
-            let it = keys.iter().cloned();
-            Ok(if let Some(count) = count {
-                it.take(count).collect()
-            } else {
-                it.collect()
-            })
+        async fn keys_(
+            &self,
+            count: Option<usize>,
+            first: Option<usize>,
+            after: Option<Vec<u8>>,
+        ) -> Result<Vec<Vec<u8>>, async_graphql::Error> {
+            Ok(self.keys_after(after.as_deref(), first.or(count)).await?)
         }
 
         async fn entry(
             &self,
             key: Vec<u8>,
-        ) -> Result<Entry<Vec<u8>, Option<V>>, async_graphql::Error> {
+        ) -> Result<Entry<Vec<u8>, Option<K::Value>>, async_graphql::Error> {
             Ok(Entry {
                 value: self.get(&key).await?,
                 key,
@@ -2002,14 +2308,16 @@ mod graphql {
         async fn entries(
             &self,
             input: Option<MapInput<Vec<u8>>>,
-        ) -> Result<Vec<Entry<Vec<u8>, Option<V>>>, async_graphql::Error> {
+            first: Option<usize>,
+            after: Option<Vec<u8>>,
+        ) -> Result<Vec<Entry<Vec<u8>, Option<K::Value>>>, async_graphql::Error> {
             let keys = input
                 .and_then(|input| input.filters)
                 .and_then(|filters| filters.keys);
             let keys = if let Some(keys) = keys {
                 keys
             } else {
-                self.keys().await?
+                self.keys_after(after.as_deref(), first).await?
             };
 
             let mut entries = vec![];
@@ -2024,24 +2332,29 @@ mod graphql {
         }
     }
 
-    impl<C: Send + Sync, I: async_graphql::OutputType, V: async_graphql::OutputType>
-        async_graphql::TypeName for MapView<C, I, V>
+    impl<C, I, K> async_graphql::TypeName for CodecMapView<C, I, K>
+    where
+        C: Send + Sync,
+        I: async_graphql::OutputType,
+        K: ValueCodec,
+        K::Value: async_graphql::OutputType,
     {
         fn type_name() -> Cow<'static, str> {
             format!(
                 "MapView_{}_{}_{:08x}",
                 mangle(I::type_name()),
-                mangle(V::type_name()),
-                hash_name::<(I, V)>(),
+                mangle(K::Value::type_name()),
+                hash_name::<(I, K::Value)>(),
             )
             .into()
         }
     }
 
     #[async_graphql::Object(cache_control(no_cache), name_type)]
-    impl<C, I, V> MapView<C, I, V>
+    impl<C, I, K> CodecMapView<C, I, K>
     where
         C: Context + Send + Sync,
+        K: ValueCodec,
         I: async_graphql::OutputType
             + async_graphql::InputType
             + serde::ser::Serialize
@@ -2051,25 +2364,18 @@ mod graphql {
             + Send
             + Sync
             + 'static,
-        V: async_graphql::OutputType
-            + serde::ser::Serialize
-            + serde::de::DeserializeOwned
-            + Clone
-            + Send
-            + Sync
-            + 'static,
+        K::Value: async_graphql::OutputType + Clone + Send + Sync + 'static,
     {
-        async fn keys(&self, count: Option<usize>) -> Result<Vec<I>, async_graphql::Error> {
-            let indices = self.indices().await?;
-            let it = indices.iter().cloned();
-            Ok(if let Some(count) = count {
-                it.take(count).collect()
-            } else {
-                it.collect()
-            })
+        async fn keys(
+            &self,
+            count: Option<usize>,
+            first: Option<usize>,
+            after: Option<I>,
+        ) -> Result<Vec<I>, async_graphql::Error> {
+            Ok(self.indices_after(after.as_ref(), first.or(count)).await?)
         }
 
-        async fn entry(&self, key: I) -> Result<Entry<I, Option<V>>, async_graphql::Error> {
+        async fn entry(&self, key: I) -> Result<Entry<I, Option<K::Value>>, async_graphql::Error> {
             Ok(Entry {
                 value: self.get(&key).await?,
                 key,
@@ -2079,14 +2385,16 @@ mod graphql {
         async fn entries(
             &self,
             input: Option<MapInput<I>>,
-        ) -> Result<Vec<Entry<I, Option<V>>>, async_graphql::Error> {
+            first: Option<usize>,
+            after: Option<I>,
+        ) -> Result<Vec<Entry<I, Option<K::Value>>>, async_graphql::Error> {
             let keys = input
                 .and_then(|input| input.filters)
                 .and_then(|filters| filters.keys);
             let keys = if let Some(keys) = keys {
                 keys
             } else {
-                self.indices().await?
+                self.indices_after(after.as_ref(), first).await?
             };
 
             let mut values = vec![];
@@ -2101,18 +2409,23 @@ mod graphql {
         }
     }
 
-    impl<C: Send + Sync, I: async_graphql::OutputType, V: async_graphql::OutputType>
-        async_graphql::TypeName for CustomMapView<C, I, V>
+    impl<C, I, K> async_graphql::TypeName for CodecCustomMapView<C, I, K>
+    where
+        C: Send + Sync,
+        I: async_graphql::OutputType,
+        K: ValueCodec,
+        K::Value: async_graphql::OutputType,
     {
         fn type_name() -> Cow<'static, str> {
-            format!("CustomMapView_{}_{}", I::type_name(), V::type_name()).into()
+            format!("CustomMapView_{}_{}", I::type_name(), K::Value::type_name()).into()
         }
     }
 
     #[async_graphql::Object(cache_control(no_cache), name_type)]
-    impl<C, I, V> CustomMapView<C, I, V>
+    impl<C, I, K> CodecCustomMapView<C, I, K>
     where
         C: Context + Send + Sync,
+        K: ValueCodec,
         I: async_graphql::OutputType
             + async_graphql::InputType
             + crate::common::CustomSerialize
@@ -2121,25 +2434,18 @@ mod graphql {
             + Send
             + Sync
             + 'static,
-        V: async_graphql::OutputType
-            + serde::ser::Serialize
-            + serde::de::DeserializeOwned
-            + Clone
-            + Send
-            + Sync
-            + 'static,
+        K::Value: async_graphql::OutputType + Clone + Send + Sync + 'static,
     {
-        async fn keys(&self, count: Option<usize>) -> Result<Vec<I>, async_graphql::Error> {
-            let indices = self.indices().await?;
-            let it = indices.iter().cloned();
-            Ok(if let Some(count) = count {
-                it.take(count).collect()
-            } else {
-                it.collect()
-            })
+        async fn keys(
+            &self,
+            count: Option<usize>,
+            first: Option<usize>,
+            after: Option<I>,
+        ) -> Result<Vec<I>, async_graphql::Error> {
+            Ok(self.indices_after(after.as_ref(), first.or(count)).await?)
         }
 
-        async fn entry(&self, key: I) -> Result<Entry<I, Option<V>>, async_graphql::Error> {
+        async fn entry(&self, key: I) -> Result<Entry<I, Option<K::Value>>, async_graphql::Error> {
             Ok(Entry {
                 value: self.get(&key).await?,
                 key,
@@ -2149,14 +2455,16 @@ mod graphql {
         async fn entries(
             &self,
             input: Option<MapInput<I>>,
-        ) -> Result<Vec<Entry<I, Option<V>>>, async_graphql::Error> {
+            first: Option<usize>,
+            after: Option<I>,
+        ) -> Result<Vec<Entry<I, Option<K::Value>>>, async_graphql::Error> {
             let keys = input
                 .and_then(|input| input.filters)
                 .and_then(|filters| filters.keys);
             let keys = if let Some(keys) = keys {
                 keys
             } else {
-                self.indices().await?
+                self.indices_after(after.as_ref(), first).await?
             };
 
             let mut values = vec![];
@@ -6,12 +6,12 @@ use std::{fmt::Debug, io::Write};
 use async_trait::async_trait;
 use linera_base::{crypto::CryptoHash, data_types::ArithmeticError, identifiers::BlobId};
 pub use linera_views_derive::{
-    ClonableView, CryptoHashRootView, CryptoHashView, HashableView, RootView, View,
+    ClonableView, CryptoHashRootView, CryptoHashView, GraphQLView, HashableView, RootView, View,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{batch::Batch, common::HasherOutput};
+use crate::{batch::Batch, common::HasherOutput, context::Context, store::KeyValueIterable as _};
 
 #[cfg(test)]
 #[path = "unit_tests/views.rs"]
@@ -20,6 +20,9 @@ mod tests;
 /// The `RegisterView` implements a register for a single value.
 pub mod register_view;
 
+/// Tracking of the locked entries of reentrant collection views, to detect deadlocks.
+mod lock_tracking;
+
 /// The `LogView` implements a log list that can be pushed.
 pub mod log_view;
 
@@ -93,6 +96,47 @@ pub trait View<C>: Sized {
         view.clear();
         Ok(view)
     }
+
+    /// Returns the number of keys used by the view in storage, and their approximate size.
+    /// Changes that were not flushed and saved are not taken into account.
+    ///
+    /// By default, all the keys and values of the view are read from storage, which takes
+    /// time proportional to its size: this is meant for diagnostic tools run by the operator of
+    /// the storage, not for requests from untrusted users.
+    async fn storage_stats(&self) -> Result<StorageStats, ViewError>
+    where
+        C: Context + Send + Sync,
+        ViewError: From<C::Error>,
+    {
+        let context = self.context();
+        let prefix = context.base_key();
+        let key_values = context.find_key_values_by_prefix(&prefix).await?;
+        let mut stats = StorageStats::default();
+        for entry in key_values.iterator() {
+            let (key, value) = entry?;
+            stats.add_entry(prefix.len() + key.len(), value.len());
+        }
+        Ok(stats)
+    }
+}
+
+/// The storage used by a view.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject,
+)]
+pub struct StorageStats {
+    /// The number of keys.
+    pub key_count: u64,
+    /// The total size of the keys and values, in bytes.
+    pub total_size: u64,
+}
+
+impl StorageStats {
+    /// Accounts for a key and its value, given their sizes.
+    pub fn add_entry(&mut self, key_size: usize, value_size: usize) {
+        self.key_count += 1;
+        self.total_size += (key_size + value_size) as u64;
+    }
 }
 
 /// Main error type for the crate.
@@ -118,6 +162,20 @@ pub enum ViewError {
     #[error("Failed to lock collection entry: {0:?}")]
     TryLockError(Vec<u8>),
 
+    /// An entry is locked by another task while the current task holds an entry that was
+    /// locked after it, so the tasks lock the entries in inconsistent orders.
+    #[error(
+        "Failed to lock collection entry {requested:?}, locked by another task while this task \
+        holds entry {held:?}, which was locked after it"
+    )]
+    LockOrderConflict {
+        /// The entry that could not be locked.
+        requested: Vec<u8>,
+        /// An entry locked by the current task after the requested one was locked, possibly in
+        /// another collection.
+        held: Vec<u8>,
+    },
+
     /// Tokio errors can happen while joining.
     #[error("Panic in sub-task: {0}")]
     TokioJoinError(#[from] tokio::task::JoinError),
@@ -158,6 +216,20 @@ pub enum ViewError {
     /// Some blobs were not found.
     #[error("Blobs not found: {0:?}")]
     BlobsNotFound(Vec<BlobId>),
+
+    /// A value could not be encoded or decoded by a custom codec.
+    #[error("Failed to encode or decode a value: {0}")]
+    CodecError(String),
+
+    /// A batch was not written because the value of a key in storage doesn't satisfy one of
+    /// its conditions, e.g. because another writer changed it.
+    #[error("The value of key {0:?} in storage doesn't satisfy the condition of the batch")]
+    WriteConflict(Vec<u8>),
+
+    /// A condition was set on a view whose store can't check it atomically with the writes
+    /// of the batch.
+    #[error("The store doesn't support conditional writes")]
+    ConditionalWritesNotSupported,
 }
 
 impl ViewError {
@@ -9,7 +9,6 @@ use std::{
 };
 
 use async_trait::async_trait;
-use serde::{de::DeserializeOwned, Serialize};
 #[cfg(with_metrics)]
 use {
     linera_base::prometheus_util::{bucket_latencies, register_histogram_vec, MeasureLatency},
@@ -18,6 +17,7 @@ use {
 
 use crate::{
     batch::Batch,
+    codec::{update_with_encoded_values, Bcs, ValueCodec},
     common::{from_bytes_option_or_default, HasherOutput},
     context::Context,
     hashable_wrapper::WrappedHashableContainerView,
@@ -44,22 +44,26 @@ enum KeyTag {
     Index,
 }
 
-/// A view that supports a FIFO queue for values of type `T`.
+/// A view that supports a FIFO queue for values stored with the codec `K`.
 #[derive(Debug)]
-pub struct QueueView<C, T> {
+pub struct CodecQueueView<C, K: ValueCodec> {
     context: C,
     stored_indices: Range<usize>,
     front_delete_count: usize,
     delete_storage_first: bool,
-    new_back_values: VecDeque<T>,
+    new_back_values: VecDeque<K::Value>,
 }
 
+/// A view that supports a FIFO queue for values of type `T`, stored with BCS.
+pub type QueueView<C, T> = CodecQueueView<C, Bcs<T>>;
+
 #[async_trait]
-impl<C, T> View<C> for QueueView<C, T>
+impl<C, K> View<C> for CodecQueueView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    T: Send + Sync + Serialize,
+    K: ValueCodec,
+    K::Value: Send + Sync,
 {
     const NUM_INIT_KEYS: usize = 1;
 
@@ -129,7 +133,7 @@ where
                 let key = self
                     .context
                     .derive_tag_key(KeyTag::Index as u8, &self.stored_indices.end)?;
-                batch.put_key_value(key, value)?;
+                batch.put_key_value_bytes(key, K::encode(value)?);
                 self.stored_indices.end += 1;
             }
             self.new_back_values.clear();
@@ -149,14 +153,15 @@ where
     }
 }
 
-impl<C, T> ClonableView<C> for QueueView<C, T>
+impl<C, K> ClonableView<C> for CodecQueueView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    T: Clone + Send + Sync + Serialize,
+    K: ValueCodec,
+    K::Value: Clone + Send + Sync,
 {
     fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
-        Ok(QueueView {
+        Ok(CodecQueueView {
             context: self.context.clone(),
             stored_indices: self.stored_indices.clone(),
             front_delete_count: self.front_delete_count,
@@ -166,7 +171,7 @@ where
     }
 }
 
-impl<C, T> QueueView<C, T> {
+impl<C, K: ValueCodec> CodecQueueView<C, K> {
     fn stored_count(&self) -> usize {
         if self.delete_storage_first {
             0
@@ -176,15 +181,19 @@ impl<C, T> QueueView<C, T> {
     }
 }
 
-impl<'a, C, T> QueueView<C, T>
+impl<'a, C, K> CodecQueueView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    T: Send + Sync + Clone + Serialize + DeserializeOwned,
+    K: ValueCodec,
+    K::Value: Send + Sync + Clone,
 {
-    async fn get(&self, index: usize) -> Result<Option<T>, ViewError> {
+    async fn get(&self, index: usize) -> Result<Option<K::Value>, ViewError> {
         let key = self.context.derive_tag_key(KeyTag::Index as u8, &index)?;
-        Ok(self.context.read_value(&key).await?)
+        match self.context.read_value_bytes(&key).await? {
+            Some(bytes) => Ok(Some(K::decode(&bytes)?)),
+            None => Ok(None),
+        }
     }
 
     /// Reads the front value, if any.
@@ -200,7 +209,7 @@ where
     /// assert_eq!(queue.front().await.unwrap(), Some(34));
     /// # })
     /// ```
-    pub async fn front(&self) -> Result<Option<T>, ViewError> {
+    pub async fn front(&self) -> Result<Option<K::Value>, ViewError> {
         let stored_remainder = self.stored_count();
         let value = if stored_remainder > 0 {
             self.get(self.stored_indices.end - stored_remainder).await?
@@ -223,7 +232,7 @@ where
     /// assert_eq!(queue.back().await.unwrap(), Some(42));
     /// # })
     /// ```
-    pub async fn back(&self) -> Result<Option<T>, ViewError> {
+    pub async fn back(&self) -> Result<Option<K::Value>, ViewError> {
         Ok(match self.new_back_values.back() {
             Some(value) => Some(value.clone()),
             None if self.stored_count() > 0 => self.get(self.stored_indices.end - 1).await?,
@@ -265,7 +274,7 @@ where
     /// assert_eq!(queue.elements().await.unwrap(), vec![34, 37]);
     /// # })
     /// ```
-    pub fn push_back(&mut self, value: T) {
+    pub fn push_back(&mut self, value: K::Value) {
         self.new_back_values.push_back(value);
     }
 
@@ -290,7 +299,7 @@ where
         self.context.extra()
     }
 
-    async fn read_context(&self, range: Range<usize>) -> Result<Vec<T>, ViewError> {
+    async fn read_context(&self, range: Range<usize>) -> Result<Vec<K::Value>, ViewError> {
         let count = range.len();
         let mut keys = Vec::with_capacity(count);
         for index in range {
@@ -298,12 +307,12 @@ where
             keys.push(key)
         }
         let mut values = Vec::with_capacity(count);
-        for entry in self.context.read_multi_values(keys).await? {
+        for entry in self.context.read_multi_values_bytes(keys).await? {
             match entry {
                 None => {
                     return Err(ViewError::MissingEntries);
                 }
-                Some(value) => values.push(value),
+                Some(bytes) => values.push(K::decode(&bytes)?),
             }
         }
         Ok(values)
@@ -322,7 +331,7 @@ where
     /// assert_eq!(queue.read_front(1).await.unwrap(), vec![34]);
     /// # })
     /// ```
-    pub async fn read_front(&self, mut count: usize) -> Result<Vec<T>, ViewError> {
+    pub async fn read_front(&self, mut count: usize) -> Result<Vec<K::Value>, ViewError> {
         if count > self.count() {
             count = self.count();
         }
@@ -362,7 +371,7 @@ where
     /// assert_eq!(queue.read_back(1).await.unwrap(), vec![42]);
     /// # })
     /// ```
-    pub async fn read_back(&self, mut count: usize) -> Result<Vec<T>, ViewError> {
+    pub async fn read_back(&self, mut count: usize) -> Result<Vec<K::Value>, ViewError> {
         if count > self.count() {
             count = self.count();
         }
@@ -398,7 +407,7 @@ where
     /// assert_eq!(queue.elements().await.unwrap(), vec![34, 37]);
     /// # })
     /// ```
-    pub async fn elements(&self) -> Result<Vec<T>, ViewError> {
+    pub async fn elements(&self) -> Result<Vec<K::Value>, ViewError> {
         let count = self.count();
         self.read_front(count).await
     }
@@ -437,18 +446,19 @@ where
     /// assert_eq!(queue.elements().await.unwrap(), vec![42]);
     /// # })
     /// ```
-    pub async fn iter_mut(&'a mut self) -> Result<IterMut<'a, T>, ViewError> {
+    pub async fn iter_mut(&'a mut self) -> Result<IterMut<'a, K::Value>, ViewError> {
         self.load_all().await?;
         Ok(self.new_back_values.iter_mut())
     }
 }
 
 #[async_trait]
-impl<C, T> HashableView<C> for QueueView<C, T>
+impl<C, K> HashableView<C> for CodecQueueView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    T: Send + Sync + Clone + Serialize + DeserializeOwned,
+    K: ValueCodec,
+    K::Value: Send + Sync + Clone,
 {
     type Hasher = sha3::Sha3_256;
 
@@ -461,7 +471,7 @@ where
         let _hash_latency = QUEUE_VIEW_HASH_RUNTIME.measure_latency();
         let elements = self.elements().await?;
         let mut hasher = sha3::Sha3_256::default();
-        hasher.update_with_bcs_bytes(&elements)?;
+        update_with_encoded_values::<K>(&mut hasher, &elements)?;
         Ok(hasher.finalize())
     }
 }
@@ -469,33 +479,43 @@ where
 /// Type wrapping `QueueView` while memoizing the hash.
 pub type HashedQueueView<C, T> = WrappedHashableContainerView<C, QueueView<C, T>, HasherOutput>;
 
+/// Type wrapping `CodecQueueView` while memoizing the hash.
+pub type HashedCodecQueueView<C, K> =
+    WrappedHashableContainerView<C, CodecQueueView<C, K>, HasherOutput>;
+
 mod graphql {
     use std::borrow::Cow;
 
-    use super::QueueView;
+    use super::CodecQueueView;
     use crate::{
+        codec::ValueCodec,
         context::Context,
         graphql::{hash_name, mangle},
     };
 
-    impl<C: Send + Sync, T: async_graphql::OutputType> async_graphql::TypeName for QueueView<C, T> {
+    impl<C, K> async_graphql::TypeName for CodecQueueView<C, K>
+    where
+        C: Send + Sync,
+        K: ValueCodec,
+        K::Value: async_graphql::OutputType,
+    {
         fn type_name() -> Cow<'static, str> {
             format!(
                 "QueueView_{}_{:08x}",
-                mangle(T::type_name()),
-                hash_name::<T>()
+                mangle(K::Value::type_name()),
+                hash_name::<K::Value>()
             )
             .into()
         }
     }
 
     #[async_graphql::Object(cache_control(no_cache), name_type)]
-    impl<C: Context, T: async_graphql::OutputType> QueueView<C, T>
+    impl<C: Context, K: ValueCodec> CodecQueueView<C, K>
     where
         C: Send + Sync,
-        T: serde::ser::Serialize + serde::de::DeserializeOwned + Clone + Send + Sync,
+        K::Value: async_graphql::OutputType + Clone + Send + Sync,
     {
-        async fn entries(&self, count: Option<usize>) -> async_graphql::Result<Vec<T>> {
+        async fn entries(&self, count: Option<usize>) -> async_graphql::Result<Vec<K::Value>> {
             Ok(self
                 .read_front(count.unwrap_or_else(|| self.count()))
                 .await?)
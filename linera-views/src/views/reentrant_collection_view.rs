@@ -14,6 +14,7 @@ use std::{
 
 use async_lock::{RwLock, RwLockReadGuardArc, RwLockWriteGuardArc};
 use async_trait::async_trait;
+use futures::future;
 use serde::{de::DeserializeOwned, Serialize};
 #[cfg(with_metrics)]
 use {
@@ -23,11 +24,14 @@ use {
 
 use crate::{
     batch::Batch,
-    common::{CustomSerialize, HasherOutput, Update},
+    common::{find_keys_after, CustomSerialize, HasherOutput, Update},
     context::Context,
     hashable_wrapper::WrappedHashableContainerView,
     store::KeyIterable,
-    views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
+    views::{
+        lock_tracking::{HeldLock, LockTracker},
+        ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG,
+    },
 };
 
 #[cfg(with_metrics)]
@@ -43,7 +47,7 @@ static REENTRANT_COLLECTION_VIEW_HASH_RUNTIME: LazyLock<HistogramVec> = LazyLock
 
 /// A read-only accessor for a particular subview in a [`ReentrantCollectionView`].
 #[derive(Debug)]
-pub struct ReadGuardedView<T>(RwLockReadGuardArc<T>);
+pub struct ReadGuardedView<T>(RwLockReadGuardArc<T>, HeldLock);
 
 impl<T> std::ops::Deref for ReadGuardedView<T> {
     type Target = T;
@@ -54,7 +58,7 @@ impl<T> std::ops::Deref for ReadGuardedView<T> {
 
 /// A read-write accessor for a particular subview in a [`ReentrantCollectionView`].
 #[derive(Debug)]
-pub struct WriteGuardedView<T>(RwLockWriteGuardArc<T>);
+pub struct WriteGuardedView<T>(RwLockWriteGuardArc<T>, HeldLock);
 
 impl<T> std::ops::Deref for WriteGuardedView<T> {
     type Target = T;
@@ -81,6 +85,10 @@ pub struct ReentrantByteCollectionView<C, W> {
     updates: BTreeMap<Vec<u8>, Update<Arc<RwLock<W>>>>,
     /// Entries cached in memory that have the exact same state as in the persistent storage.
     cached_entries: Mutex<BTreeMap<Vec<u8>, Arc<RwLock<W>>>>,
+    /// The hashes of the entries that were not locked for writing since they were last hashed.
+    cached_hashes: Mutex<BTreeMap<Vec<u8>, Vec<u8>>>,
+    /// The entries currently locked, if deadlock detection is enabled.
+    lock_tracker: LockTracker,
 }
 
 /// We need to find new base keys in order to implement the collection_view.
@@ -121,6 +129,8 @@ where
             delete_storage_first: false,
             updates: BTreeMap::new(),
             cached_entries: Mutex::new(BTreeMap::new()),
+            cached_hashes: Mutex::new(BTreeMap::new()),
+            lock_tracker: LockTracker::default(),
         })
     }
 
@@ -130,6 +140,10 @@ where
 
     fn rollback(&mut self) {
         self.delete_storage_first = false;
+        let cached_hashes = self.cached_hashes.get_mut().unwrap();
+        for short_key in self.updates.keys() {
+            cached_hashes.remove(short_key);
+        }
         self.updates.clear();
     }
 
@@ -182,6 +196,7 @@ where
         self.delete_storage_first = true;
         self.updates.clear();
         self.cached_entries.get_mut().unwrap().clear();
+        self.cached_hashes.get_mut().unwrap().clear();
     }
 }
 
@@ -215,6 +230,8 @@ where
             delete_storage_first: self.delete_storage_first,
             updates: cloned_updates,
             cached_entries: Mutex::new(BTreeMap::new()),
+            cached_hashes: Mutex::new(self.cached_hashes.get_mut().unwrap().clone()),
+            lock_tracker: LockTracker::default(),
         })
     }
 }
@@ -234,6 +251,40 @@ impl<C: Context, W> ReentrantByteCollectionView<C, W> {
     }
 }
 
+impl<C, W> ReentrantByteCollectionView<C, W> {
+    /// Locks the entry `short_key` for reading, without waiting.
+    fn read_entry(
+        &self,
+        short_key: &[u8],
+        view: Arc<RwLock<W>>,
+    ) -> Result<ReadGuardedView<W>, ViewError> {
+        let guard = view
+            .try_read_arc()
+            .ok_or_else(|| self.lock_tracker.conflict(short_key))?;
+        Ok(ReadGuardedView(
+            guard,
+            self.lock_tracker.register(short_key),
+        ))
+    }
+
+    /// Locks the entry `short_key` for writing, without waiting. Its cached hash is
+    /// forgotten, since the entry may be modified.
+    fn write_entry(
+        &self,
+        short_key: &[u8],
+        view: Arc<RwLock<W>>,
+    ) -> Result<WriteGuardedView<W>, ViewError> {
+        let guard = view
+            .try_write_arc()
+            .ok_or_else(|| self.lock_tracker.conflict(short_key))?;
+        self.cached_hashes.lock().unwrap().remove(short_key);
+        Ok(WriteGuardedView(
+            guard,
+            self.lock_tracker.register(short_key),
+        ))
+    }
+}
+
 impl<C, W> ReentrantByteCollectionView<C, W>
 where
     C: Context + Send,
@@ -338,12 +389,8 @@ where
         &mut self,
         short_key: &[u8],
     ) -> Result<WriteGuardedView<W>, ViewError> {
-        Ok(WriteGuardedView(
-            self.try_load_view_mut(short_key)
-                .await?
-                .try_write_arc()
-                .ok_or_else(|| ViewError::TryLockError(short_key.to_vec()))?,
-        ))
+        let view = self.try_load_view_mut(short_key).await?;
+        self.write_entry(short_key, view)
     }
 
     /// Loads a subview at the given index in the collection and gives read-only access to the data.
@@ -371,10 +418,7 @@ where
     ) -> Result<Option<ReadGuardedView<W>>, ViewError> {
         match self.try_load_view(short_key).await? {
             None => Ok(None),
-            Some(view) => Ok(Some(ReadGuardedView(
-                view.try_read_arc()
-                    .ok_or_else(|| ViewError::TryLockError(short_key.to_vec()))?,
-            ))),
+            Some(view) => Ok(Some(self.read_entry(short_key, view)?)),
         }
     }
 
@@ -429,6 +473,7 @@ where
     /// ```
     pub fn remove_entry(&mut self, short_key: Vec<u8>) {
         self.cached_entries.get_mut().unwrap().remove(&short_key);
+        self.cached_hashes.get_mut().unwrap().remove(&short_key);
         if self.delete_storage_first {
             // Optimization: No need to mark `short_key` for deletion as we are going to remove all the keys at once.
             self.updates.remove(&short_key);
@@ -468,6 +513,7 @@ where
         let view = Update::Set(view);
         self.updates.insert(short_key.to_vec(), view);
         self.cached_entries.get_mut().unwrap().remove(short_key);
+        self.cached_hashes.get_mut().unwrap().remove(short_key);
         Ok(())
     }
 
@@ -509,10 +555,51 @@ where
     pub async fn try_load_entries_mut(
         &mut self,
         short_keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<WriteGuardedView<W>>, ViewError> {
+        self.do_load_entries_mut(short_keys, 1).await
+    }
+
+    /// Loads multiple entries for writing at once, like [`Self::try_load_entries_mut`], but
+    /// reads the subviews that are not in memory with up to `max_stream_queries` concurrent
+    /// queries to the storage.
+    /// The entries in `short_keys` have to be all distinct.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::{create_test_memory_context, MemoryContext};
+    /// # use linera_views::reentrant_collection_view::ReentrantByteCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: ReentrantByteCollectionView<_, RegisterView<_, String>> =
+    ///     ReentrantByteCollectionView::load(context).await.unwrap();
+    /// {
+    ///     let mut subview = view.try_load_entry_mut(&[0, 1]).await.unwrap();
+    ///     *subview.get_mut() = "Bonjour".to_string();
+    /// }
+    /// let short_keys = vec![vec![0, 1], vec![2, 3], vec![4, 5]];
+    /// let subviews = view.try_load_entries_parallel(short_keys).await.unwrap();
+    /// assert_eq!(subviews.len(), 3);
+    /// assert_eq!(*subviews[0].get(), "Bonjour".to_string());
+    /// assert_eq!(*subviews[2].get(), String::default());
+    /// # })
+    /// ```
+    pub async fn try_load_entries_parallel(
+        &mut self,
+        short_keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<WriteGuardedView<W>>, ViewError> {
+        let max_concurrency = self.context.max_stream_queries();
+        self.do_load_entries_mut(short_keys, max_concurrency).await
+    }
+
+    /// Loads multiple entries for writing, reading the subviews that are not in memory in up
+    /// to `max_concurrency` concurrent batches.
+    async fn do_load_entries_mut(
+        &mut self,
+        short_keys: Vec<Vec<u8>>,
+        max_concurrency: usize,
     ) -> Result<Vec<WriteGuardedView<W>>, ViewError> {
         let cached_entries = self.cached_entries.get_mut().unwrap();
-        let mut short_keys_to_load = Vec::new();
-        let mut keys = Vec::new();
+        let mut entries_to_load = Vec::new();
         for short_key in &short_keys {
             let key = self
                 .context
@@ -536,25 +623,32 @@ where
                     } else if let Some(view) = cached_entries.remove(short_key) {
                         entry.insert(Update::Set(view));
                     } else {
-                        keys.extend(W::pre_load(&context)?);
-                        short_keys_to_load.push(short_key.to_vec());
+                        entries_to_load.push((short_key.to_vec(), context));
                     }
                 }
             }
         }
-        let values = self.context.read_multi_values_bytes(keys).await?;
-        for (loaded_values, short_key) in values
-            .chunks_exact(W::NUM_INIT_KEYS)
-            .zip(short_keys_to_load)
+        let batch_size = entries_to_load
+            .len()
+            .div_ceil(max_concurrency.max(1))
+            .max(1);
+        let mut batches = Vec::new();
+        for entries in entries_to_load.chunks(batch_size) {
+            let mut keys = Vec::with_capacity(entries.len() * W::NUM_INIT_KEYS);
+            for (_, context) in entries {
+                keys.extend(W::pre_load(context)?);
+            }
+            batches.push(self.context.read_multi_values_bytes(keys));
+        }
+        let values = future::try_join_all(batches).await?;
+        for (loaded_values, (short_key, context)) in values
+            .iter()
+            .flat_map(|values| values.chunks_exact(W::NUM_INIT_KEYS))
+            .zip(entries_to_load)
         {
-            let key = self
-                .context
-                .base_tag_index(KeyTag::Subview as u8, &short_key);
-            let context = self.context.clone_with_base_key(key);
             let view = W::post_load(context, loaded_values)?;
             let wrapped_view = Arc::new(RwLock::new(view));
-            self.updates
-                .insert(short_key.to_vec(), Update::Set(wrapped_view));
+            self.updates.insert(short_key, Update::Set(wrapped_view));
         }
 
         short_keys
@@ -563,11 +657,7 @@ where
                 let Some(Update::Set(view)) = self.updates.get(&short_key) else {
                     unreachable!()
                 };
-                Ok(WriteGuardedView(
-                    view.clone()
-                        .try_write_arc()
-                        .ok_or_else(|| ViewError::TryLockError(short_key))?,
-                ))
+                self.write_entry(&short_key, view.clone())
             })
             .collect()
     }
@@ -651,10 +741,7 @@ where
         results
             .into_iter()
             .map(|maybe_view| match maybe_view {
-                Some((short_key, view)) => Ok(Some(ReadGuardedView(
-                    view.try_read_arc()
-                        .ok_or_else(|| ViewError::TryLockError(short_key))?,
-                ))),
+                Some((short_key, view)) => Ok(Some(self.read_entry(&short_key, view)?)),
                 None => Ok(None),
             })
             .collect()
@@ -727,10 +814,7 @@ where
                 } else {
                     unreachable!("All entries should have been loaded into memory");
                 };
-                let guard = ReadGuardedView(
-                    view.try_read_arc()
-                        .ok_or_else(|| ViewError::TryLockError(short_key.clone()))?,
-                );
+                let guard = self.read_entry(&short_key, view)?;
                 Ok((short_key, guard))
             })
             .collect()
@@ -798,11 +882,7 @@ where
                 let Some(Update::Set(view)) = self.updates.get(&short_key) else {
                     unreachable!("All entries should have been loaded into `updates`")
                 };
-                let guard = WriteGuardedView(
-                    view.clone()
-                        .try_write_arc()
-                        .ok_or_else(|| ViewError::TryLockError(short_key.clone()))?,
-                );
+                let guard = self.write_entry(&short_key, view.clone())?;
                 Ok((short_key, guard))
             })
             .collect()
@@ -841,6 +921,39 @@ where
         Ok(keys)
     }
 
+    /// Returns at most `limit` indices in the collection that come strictly after
+    /// `start_after`, in lexicographic order.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::{create_test_memory_context, MemoryContext};
+    /// # use linera_views::reentrant_collection_view::ReentrantByteCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: ReentrantByteCollectionView<_, RegisterView<_, String>> =
+    ///     ReentrantByteCollectionView::load(context).await.unwrap();
+    /// view.try_load_entry_mut(&[0, 1]).await.unwrap();
+    /// view.try_load_entry_mut(&[0, 2]).await.unwrap();
+    /// let keys = view.keys_after(Some(&[0, 1]), None).await.unwrap();
+    /// assert_eq!(keys, vec![vec![0, 2]]);
+    /// # })
+    /// ```
+    pub async fn keys_after(
+        &self,
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, ViewError> {
+        find_keys_after(
+            &self.context,
+            &self.get_index_key(&[]),
+            &self.updates,
+            |_key| self.delete_storage_first,
+            start_after,
+            limit,
+        )
+        .await
+    }
+
     /// Returns the number of indices of the collection.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -986,8 +1099,13 @@ where
         let count = keys.len() as u32;
         hasher.update_with_bcs_bytes(&count)?;
         let cached_entries = self.cached_entries.get_mut().unwrap();
+        let cached_hashes = self.cached_hashes.get_mut().unwrap();
         for key in keys {
             hasher.update_with_bytes(&key)?;
+            if let Some(hash) = cached_hashes.get(&key) {
+                hasher.write_all(hash)?;
+                continue;
+            }
             let hash = if let Some(entry) = self.updates.get_mut(&key) {
                 let Update::Set(view) = entry else {
                     unreachable!();
@@ -1008,6 +1126,7 @@ where
                 view.hash_mut().await?
             };
             hasher.write_all(hash.as_ref())?;
+            cached_hashes.insert(key, hash.as_ref().to_vec());
         }
         Ok(hasher.finalize())
     }
@@ -1028,6 +1147,11 @@ where
         }
         for (key, cached_entry) in keys.into_iter().zip(cached_entries_result) {
             hasher.update_with_bytes(&key)?;
+            let cached_hash = self.cached_hashes.lock().unwrap().get(&key).cloned();
+            if let Some(hash) = cached_hash {
+                hasher.write_all(&hash)?;
+                continue;
+            }
             let hash = if let Some(entry) = self.updates.get(&key) {
                 let Update::Set(view) = entry else {
                     unreachable!();
@@ -1048,6 +1172,10 @@ where
                 view.hash().await?
             };
             hasher.write_all(hash.as_ref())?;
+            self.cached_hashes
+                .lock()
+                .unwrap()
+                .insert(key, hash.as_ref().to_vec());
         }
         Ok(hasher.finalize())
     }
@@ -1319,6 +1447,39 @@ where
         self.collection.try_load_entries_mut(short_keys).await
     }
 
+    /// Loads multiple entries for writing at once, reading the subviews that are not in memory
+    /// with concurrent queries to the storage.
+    /// The entries in indices have to be all distinct.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::{create_test_memory_context, MemoryContext};
+    /// # use linera_views::reentrant_collection_view::ReentrantCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: ReentrantCollectionView<_, u64, RegisterView<_, String>> =
+    ///     ReentrantCollectionView::load(context).await.unwrap();
+    /// let indices = vec![23, 42, 57];
+    /// let subviews = view.try_load_entries_parallel(&indices).await.unwrap();
+    /// assert_eq!(subviews.len(), 3);
+    /// assert_eq!(*subviews[1].get(), String::default());
+    /// # })
+    /// ```
+    pub async fn try_load_entries_parallel<'a, Q>(
+        &'a mut self,
+        indices: impl IntoIterator<Item = &'a Q>,
+    ) -> Result<Vec<WriteGuardedView<W>>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + 'a,
+    {
+        let short_keys = indices
+            .into_iter()
+            .map(|index| C::derive_short_key(index))
+            .collect::<Result<_, _>>()?;
+        self.collection.try_load_entries_parallel(short_keys).await
+    }
+
     /// Load multiple entries for reading at once.
     /// The entries in indices have to be all distinct.
     /// ```rust
@@ -1460,6 +1621,37 @@ where
         Ok(indices)
     }
 
+    /// Returns at most `limit` indices in the collection that come strictly after
+    /// `start_after`, in an order determined by serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::{create_test_memory_context, MemoryContext};
+    /// # use linera_views::reentrant_collection_view::ReentrantCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: ReentrantCollectionView<_, u64, RegisterView<_, String>> =
+    ///     ReentrantCollectionView::load(context).await.unwrap();
+    /// view.try_load_entry_mut(&23).await.unwrap();
+    /// view.try_load_entry_mut(&25).await.unwrap();
+    /// let indices = view.indices_after(Some(&23), None).await.unwrap();
+    /// assert_eq!(indices, vec![25]);
+    /// # })
+    /// ```
+    pub async fn indices_after(
+        &self,
+        start_after: Option<&I>,
+        limit: Option<usize>,
+    ) -> Result<Vec<I>, ViewError> {
+        let start_after = start_after.map(C::derive_short_key).transpose()?;
+        self.collection
+            .keys_after(start_after.as_deref(), limit)
+            .await?
+            .iter()
+            .map(|key| Ok(C::deserialize_value(key)?))
+            .collect()
+    }
+
     /// Returns the number of indices in the collection.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -1839,6 +2031,39 @@ where
         self.collection.try_load_entries_mut(short_keys).await
     }
 
+    /// Loads multiple entries for writing at once, reading the subviews that are not in memory
+    /// with concurrent queries to the storage.
+    /// The entries in indices have to be all distinct.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::{create_test_memory_context, MemoryContext};
+    /// # use linera_views::reentrant_collection_view::ReentrantCustomCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: ReentrantCustomCollectionView<_, u128, RegisterView<_, String>> =
+    ///     ReentrantCustomCollectionView::load(context).await.unwrap();
+    /// let indices = vec![23, 42, 57];
+    /// let subviews = view.try_load_entries_parallel(indices).await.unwrap();
+    /// assert_eq!(subviews.len(), 3);
+    /// assert_eq!(*subviews[1].get(), String::default());
+    /// # })
+    /// ```
+    pub async fn try_load_entries_parallel<Q>(
+        &mut self,
+        indices: impl IntoIterator<Item = Q>,
+    ) -> Result<Vec<WriteGuardedView<W>>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+    {
+        let short_keys = indices
+            .into_iter()
+            .map(|index| index.to_custom_bytes())
+            .collect::<Result<_, _>>()?;
+        self.collection.try_load_entries_parallel(short_keys).await
+    }
+
     /// Load multiple entries for reading at once.
     /// The entries in indices have to be all distinct.
     /// ```rust
@@ -1978,6 +2203,37 @@ where
         Ok(indices)
     }
 
+    /// Returns at most `limit` indices in the collection that come strictly after
+    /// `start_after`. The order is determined by the custom serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::{create_test_memory_context, MemoryContext};
+    /// # use linera_views::reentrant_collection_view::ReentrantCustomCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: ReentrantCustomCollectionView<_, u128, RegisterView<_, String>> =
+    ///     ReentrantCustomCollectionView::load(context).await.unwrap();
+    /// view.try_load_entry_mut(&23).await.unwrap();
+    /// view.try_load_entry_mut(&25).await.unwrap();
+    /// let indices = view.indices_after(None, Some(1)).await.unwrap();
+    /// assert_eq!(indices, vec![23]);
+    /// # })
+    /// ```
+    pub async fn indices_after(
+        &self,
+        start_after: Option<&I>,
+        limit: Option<usize>,
+    ) -> Result<Vec<I>, ViewError> {
+        let start_after = start_after.map(I::to_custom_bytes).transpose()?;
+        self.collection
+            .keys_after(start_after.as_deref(), limit)
+            .await?
+            .iter()
+            .map(|key| I::from_custom_bytes(key))
+            .collect()
+    }
+
     /// Returns the number of entries in the collection.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -2160,8 +2416,12 @@ mod graphql {
         MapInput<K>: async_graphql::InputType,
         MapFilters<K>: async_graphql::InputType,
     {
-        async fn keys(&self) -> Result<Vec<K>, async_graphql::Error> {
-            Ok(self.indices().await?)
+        async fn keys(
+            &self,
+            first: Option<usize>,
+            after: Option<K>,
+        ) -> Result<Vec<K>, async_graphql::Error> {
+            Ok(self.indices_after(after.as_ref(), first).await?)
         }
 
         async fn entry(
@@ -2178,6 +2438,8 @@ mod graphql {
         async fn entries(
             &self,
             input: Option<MapInput<K>>,
+            first: Option<usize>,
+            after: Option<K>,
         ) -> Result<Vec<Entry<K, ReadGuardedView<V>>>, async_graphql::Error> {
             let keys = if let Some(keys) = input
                 .and_then(|input| input.filters)
@@ -2185,7 +2447,7 @@ mod graphql {
             {
                 keys
             } else {
-                self.indices().await?
+                self.indices_after(after.as_ref(), first).await?
             };
 
             let mut values = vec![];
@@ -2229,8 +2491,12 @@ mod graphql {
         MapInput<K>: async_graphql::InputType,
         MapFilters<K>: async_graphql::InputType,
     {
-        async fn keys(&self) -> Result<Vec<K>, async_graphql::Error> {
-            Ok(self.indices().await?)
+        async fn keys(
+            &self,
+            first: Option<usize>,
+            after: Option<K>,
+        ) -> Result<Vec<K>, async_graphql::Error> {
+            Ok(self.indices_after(after.as_ref(), first).await?)
         }
 
         async fn entry(
@@ -2247,6 +2513,8 @@ mod graphql {
         async fn entries(
             &self,
             input: Option<MapInput<K>>,
+            first: Option<usize>,
+            after: Option<K>,
         ) -> Result<Vec<Entry<K, ReadGuardedView<V>>>, async_graphql::Error> {
             let keys = if let Some(keys) = input
                 .and_then(|input| input.filters)
@@ -2254,7 +2522,7 @@ mod graphql {
             {
                 keys
             } else {
-                self.indices().await?
+                self.indices_after(after.as_ref(), first).await?
             };
 
             let mut values = vec![];
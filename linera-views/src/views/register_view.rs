@@ -5,7 +5,7 @@
 use std::sync::LazyLock;
 
 use async_trait::async_trait;
-use serde::{de::DeserializeOwned, Serialize};
+use linera_base::ensure;
 #[cfg(with_metrics)]
 use {
     linera_base::prometheus_util::{bucket_latencies, register_histogram_vec, MeasureLatency},
@@ -14,7 +14,8 @@ use {
 
 use crate::{
     batch::Batch,
-    common::{from_bytes_option_or_default, HasherOutput},
+    codec::{Bcs, ValueCodec},
+    common::HasherOutput,
     context::Context,
     hashable_wrapper::WrappedHashableContainerView,
     views::{ClonableView, HashableView, Hasher, View, ViewError},
@@ -31,21 +32,40 @@ static REGISTER_VIEW_HASH_RUNTIME: LazyLock<HistogramVec> = LazyLock::new(|| {
     )
 });
 
-/// A view that supports modifying a single value of type `T`.
+/// A view that supports modifying a single value, stored with the codec `K`.
 #[derive(Debug)]
-pub struct RegisterView<C, T> {
+pub struct CodecRegisterView<C, K: ValueCodec> {
     delete_storage_first: bool,
     context: C,
-    stored_value: Box<T>,
-    update: Option<Box<T>>,
+    stored_value: Box<K::Value>,
+    update: Option<Box<K::Value>>,
+    /// The values that the register may have in storage for the pending changes to be
+    /// written, if they were made with [`CodecRegisterView::compare_and_set`].
+    condition: Option<Vec<Option<Vec<u8>>>>,
+}
+
+/// A view that supports modifying a single value of type `T`, stored with BCS.
+pub type RegisterView<C, T> = CodecRegisterView<C, Bcs<T>>;
+
+/// Decodes the value stored under a key, or returns the default value if there is none.
+fn decode_or_default<K>(bytes: &Option<Vec<u8>>) -> Result<K::Value, ViewError>
+where
+    K: ValueCodec,
+    K::Value: Default,
+{
+    match bytes {
+        Some(bytes) => K::decode(bytes),
+        None => Ok(K::Value::default()),
+    }
 }
 
 #[async_trait]
-impl<C, T> View<C> for RegisterView<C, T>
+impl<C, K> View<C> for CodecRegisterView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    T: Default + Send + Sync + Serialize + DeserializeOwned,
+    K: ValueCodec,
+    K::Value: Default + Send + Sync,
 {
     const NUM_INIT_KEYS: usize = 1;
 
@@ -58,14 +78,14 @@ where
     }
 
     fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
-        let value =
-            from_bytes_option_or_default(values.first().ok_or(ViewError::PostLoadValuesError)?)?;
+        let value = decode_or_default::<K>(values.first().ok_or(ViewError::PostLoadValuesError)?)?;
         let stored_value = Box::new(value);
         Ok(Self {
             delete_storage_first: false,
             context,
             stored_value,
             update: None,
+            condition: None,
         })
     }
 
@@ -78,6 +98,7 @@ where
     fn rollback(&mut self) {
         self.delete_storage_first = false;
         self.update = None;
+        self.condition = None;
     }
 
     async fn has_pending_changes(&self) -> bool {
@@ -88,6 +109,9 @@ where
     }
 
     fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        if let Some(allowed_values) = self.condition.take() {
+            batch.add_condition(self.context.base_key(), allowed_values);
+        }
         let mut delete_view = false;
         if self.delete_storage_first {
             batch.delete_key(self.context.base_key());
@@ -95,7 +119,7 @@ where
             delete_view = true;
         } else if let Some(value) = self.update.take() {
             let key = self.context.base_key();
-            batch.put_key_value(key, &value)?;
+            batch.put_key_value_bytes(key, K::encode(&value)?);
             self.stored_value = value;
         }
         self.delete_storage_first = false;
@@ -109,25 +133,28 @@ where
     }
 }
 
-impl<C, T> ClonableView<C> for RegisterView<C, T>
+impl<C, K> ClonableView<C> for CodecRegisterView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    T: Clone + Default + Send + Sync + Serialize + DeserializeOwned,
+    K: ValueCodec,
+    K::Value: Clone + Default + Send + Sync,
 {
     fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
-        Ok(RegisterView {
+        Ok(CodecRegisterView {
             delete_storage_first: self.delete_storage_first,
             context: self.context.clone(),
             stored_value: self.stored_value.clone(),
             update: self.update.clone(),
+            condition: self.condition.clone(),
         })
     }
 }
 
-impl<C, T> RegisterView<C, T>
+impl<C, K> CodecRegisterView<C, K>
 where
     C: Context,
+    K: ValueCodec,
 {
     /// Access the current value in the register.
     /// ```rust
@@ -141,7 +168,7 @@ where
     /// assert_eq!(*value, 0);
     /// # })
     /// ```
-    pub fn get(&self) -> &T {
+    pub fn get(&self) -> &K::Value {
         match &self.update {
             None => &self.stored_value,
             Some(value) => value,
@@ -161,7 +188,7 @@ where
     /// assert_eq!(*value, 5);
     /// # })
     /// ```
-    pub fn set(&mut self, value: T) {
+    pub fn set(&mut self, value: K::Value) {
         self.delete_storage_first = false;
         self.update = Some(Box::new(value));
     }
@@ -172,10 +199,51 @@ where
     }
 }
 
-impl<C, T> RegisterView<C, T>
+impl<C, K> CodecRegisterView<C, K>
 where
     C: Context,
-    T: Clone + Serialize,
+    K: ValueCodec,
+    K::Value: Default + PartialEq,
+{
+    /// Sets the value in the register to `new`, on the condition that the value persisted in
+    /// storage is still `expected` when the changes are written. Otherwise, writing the batch
+    /// fails with [`ViewError::WriteConflict`] and none of its changes are written, so the
+    /// views must be loaded again before retrying.
+    ///
+    /// The condition is checked by the store, atomically with the writes of the batch: the
+    /// memory store and RocksDB check it in the process that has exclusive access to the data,
+    /// and DynamoDB checks it in the write transaction. The other stores fail with
+    /// [`ViewError::ConditionalWritesNotSupported`].
+    ///
+    /// If the register already has a pending condition, that condition is kept, and `expected`
+    /// is compared with the current value of the register instead.
+    pub fn compare_and_set(&mut self, expected: &K::Value, new: K::Value) -> Result<(), ViewError> {
+        ensure!(
+            self.context.supports_conditional_writes(),
+            ViewError::ConditionalWritesNotSupported
+        );
+        if self.condition.is_some() {
+            if self.get() != expected {
+                return Err(ViewError::WriteConflict(self.context.base_key()));
+            }
+        } else {
+            let mut allowed_values = vec![Some(K::encode(expected)?)];
+            // A register that was never written has the default value.
+            if *expected == K::Value::default() {
+                allowed_values.push(None);
+            }
+            self.condition = Some(allowed_values);
+        }
+        self.set(new);
+        Ok(())
+    }
+}
+
+impl<C, K> CodecRegisterView<C, K>
+where
+    C: Context,
+    K: ValueCodec,
+    K::Value: Clone,
 {
     /// Obtains a mutable reference to the value in the register.
     /// ```rust
@@ -189,7 +257,7 @@ where
     /// assert_eq!(*value, 0);
     /// # })
     /// ```
-    pub fn get_mut(&mut self) -> &mut T {
+    pub fn get_mut(&mut self) -> &mut K::Value {
         self.delete_storage_first = false;
         match &mut self.update {
             Some(value) => value,
@@ -204,17 +272,18 @@ where
         #[cfg(with_metrics)]
         let _hash_latency = REGISTER_VIEW_HASH_RUNTIME.measure_latency();
         let mut hasher = sha3::Sha3_256::default();
-        hasher.update_with_bcs_bytes(self.get())?;
+        hasher.update_with_bytes(&K::encode(self.get())?)?;
         Ok(hasher.finalize())
     }
 }
 
 #[async_trait]
-impl<C, T> HashableView<C> for RegisterView<C, T>
+impl<C, K> HashableView<C> for CodecRegisterView<C, K>
 where
     C: Context + Send + Sync,
     ViewError: From<C::Error>,
-    T: Clone + Default + Send + Sync + Serialize + DeserializeOwned,
+    K: ValueCodec,
+    K::Value: Clone + Default + Send + Sync,
 {
     type Hasher = sha3::Sha3_256;
 
@@ -231,23 +300,28 @@ where
 pub type HashedRegisterView<C, T> =
     WrappedHashableContainerView<C, RegisterView<C, T>, HasherOutput>;
 
+/// Type wrapping `CodecRegisterView` while memoizing the hash.
+pub type HashedCodecRegisterView<C, K> =
+    WrappedHashableContainerView<C, CodecRegisterView<C, K>, HasherOutput>;
+
 mod graphql {
     use std::borrow::Cow;
 
-    use super::RegisterView;
-    use crate::context::Context;
+    use super::CodecRegisterView;
+    use crate::{codec::ValueCodec, context::Context};
 
-    impl<C, T> async_graphql::OutputType for RegisterView<C, T>
+    impl<C, K> async_graphql::OutputType for CodecRegisterView<C, K>
     where
         C: Context + Send + Sync,
-        T: async_graphql::OutputType + Send + Sync,
+        K: ValueCodec,
+        K::Value: async_graphql::OutputType + Send + Sync,
     {
         fn type_name() -> Cow<'static, str> {
-            T::type_name()
+            K::Value::type_name()
         }
 
         fn create_type_info(registry: &mut async_graphql::registry::Registry) -> String {
-            T::create_type_info(registry)
+            K::Value::create_type_info(registry)
         }
 
         async fn resolve(
@@ -9,15 +9,18 @@ use test_case::test_case;
 
 #[cfg(with_dynamodb)]
 use crate::dynamo_db::DynamoDbStore;
+#[cfg(any(with_rocksdb, with_scylladb, with_dynamodb))]
+use crate::random::generate_test_namespace;
 #[cfg(with_rocksdb)]
 use crate::rocks_db::RocksDbStore;
 #[cfg(with_scylladb)]
 use crate::scylla_db::ScyllaDbStore;
 #[cfg(any(with_scylladb, with_dynamodb, with_rocksdb))]
-use crate::store::TestKeyValueStore;
+use crate::store::{AdminKeyValueStore, TestKeyValueStore};
 use crate::{
     batch::Batch,
-    context::{create_test_memory_context, Context, MemoryContext},
+    context::{create_test_memory_context, Context, MemoryContext, ViewContext},
+    key_value_store_view::ViewContainer,
     queue_view::QueueView,
     reentrant_collection_view::ReentrantCollectionView,
     register_view::{HashedRegisterView, RegisterView},
@@ -27,8 +30,6 @@ use crate::{
     },
     views::{HashableView, View, ViewError},
 };
-#[cfg(any(with_rocksdb, with_scylladb, with_dynamodb))]
-use crate::{context::ViewContext, random::generate_test_namespace, store::AdminKeyValueStore};
 
 #[tokio::test]
 async fn test_queue_operations_with_memory_context() -> Result<(), anyhow::Error> {
@@ -487,6 +488,63 @@ async fn test_reentrant_collection_view_has_pending_changes_after_try_load_entri
     Ok(())
 }
 
+/// Checks that failing to lock an entry reports a lock order conflict only if the current task
+/// holds an entry, possibly of another collection, that was locked after the requested one.
+#[cfg(with_deadlock_detection)]
+#[tokio::test]
+async fn test_reentrant_collection_view_reports_lock_order_conflicts() -> anyhow::Result<()> {
+    let context = create_test_memory_context();
+    let mut first =
+        ReentrantCollectionView::<_, u8, RegisterView<_, String>>::load(context.clone()).await?;
+    populate_reentrant_collection_view(&mut first, [(1, "first".to_owned())]).await?;
+    let mut second =
+        ReentrantCollectionView::<_, u8, RegisterView<_, String>>::load(context.clone()).await?;
+    populate_reentrant_collection_view(
+        &mut second,
+        [(1, "first".to_owned()), (2, "second".to_owned())],
+    )
+    .await?;
+
+    // A task locks the first entry of `second` while holding the first entry of `first`.
+    let (mut first, second) = tokio::spawn(async move {
+        {
+            let _first_entry = first.try_load_entry(&1).await?;
+            let _second_entry = second.try_load_entry(&1).await?;
+        }
+        anyhow::Ok((first, second))
+    })
+    .await??;
+
+    let _locked = first.try_load_entry_mut(&1).await?;
+    let first = std::sync::Arc::new(first);
+    let second = std::sync::Arc::new(second);
+
+    // Locking the entries in the opposite order is a conflict.
+    let error = tokio::spawn({
+        let (first, second) = (first.clone(), second.clone());
+        async move {
+            let _second_entry = second.try_load_entry(&1).await?;
+            first.try_load_entry(&1).await.map(|_| ())
+        }
+    })
+    .await?
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        ViewError::LockOrderConflict { requested, held } if requested == [1] && held == [1]
+    ));
+
+    // An entry that was never locked after the requested one is not a conflict.
+    let error = tokio::spawn(async move {
+        let _second_entry = second.try_load_entry(&2).await?;
+        first.try_load_entry(&1).await.map(|_| ())
+    })
+    .await?
+    .unwrap_err();
+    assert!(matches!(error, ViewError::TryLockError(key) if key == [1]));
+    Ok(())
+}
+
 /// Checks if a cleared [`TestView`] has no pending changes after flushing.
 #[test_case(PhantomData::<TestCollectionView<_>>; "with CollectionView")]
 #[test_case(PhantomData::<TestLogView<_>>; "with LogView")]
@@ -510,6 +568,128 @@ async fn test_flushing_cleared_view<V: TestView>(_view_type: PhantomData<V>) ->
     Ok(())
 }
 
+/// Tests that [`RegisterView::compare_and_set`] writes the new value if the persisted value
+/// is the expected one.
+#[tokio::test]
+async fn test_compare_and_set() -> anyhow::Result<()> {
+    let context = create_test_memory_context();
+    let mut register = RegisterView::<_, u32>::load(context.clone()).await?;
+
+    register.compare_and_set(&0, 5)?;
+    assert_eq!(*register.get(), 5);
+    save_view(&context, &mut register).await?;
+
+    register.compare_and_set(&5, 6)?;
+    save_view(&context, &mut register).await?;
+
+    let register = RegisterView::<_, u32>::load(context).await?;
+    assert_eq!(*register.get(), 6);
+    Ok(())
+}
+
+/// Tests that [`RegisterView::compare_and_set`] can be called several times before the
+/// changes are saved, comparing with the pending value.
+#[tokio::test]
+async fn test_repeated_compare_and_set() -> anyhow::Result<()> {
+    let context = create_test_memory_context();
+    let mut register = RegisterView::<_, u32>::load(context.clone()).await?;
+
+    register.compare_and_set(&0, 1)?;
+    register.compare_and_set(&1, 2)?;
+    assert!(matches!(
+        register.compare_and_set(&0, 3),
+        Err(ViewError::WriteConflict(_))
+    ));
+    assert_eq!(*register.get(), 2);
+    save_view(&context, &mut register).await?;
+
+    let register = RegisterView::<_, u32>::load(context).await?;
+    assert_eq!(*register.get(), 2);
+    Ok(())
+}
+
+/// Tests that the changes of [`RegisterView::compare_and_set`] are not written if the value
+/// was changed in storage after the register was loaded.
+#[tokio::test]
+async fn test_compare_and_set_conflict() -> anyhow::Result<()> {
+    let context = create_test_memory_context();
+    let mut register = RegisterView::<_, u32>::load(context.clone()).await?;
+    let mut other = RegisterView::<_, u32>::load(context.clone()).await?;
+
+    other.set(3);
+    save_view(&context, &mut other).await?;
+
+    register.compare_and_set(&0, 5)?;
+    let mut batch = Batch::new();
+    register.flush(&mut batch)?;
+    assert!(matches!(
+        context.write_batch(batch).await.map_err(ViewError::from),
+        Err(ViewError::WriteConflict(key)) if key == context.base_key()
+    ));
+
+    let mut register = RegisterView::<_, u32>::load(context.clone()).await?;
+    assert_eq!(*register.get(), 3);
+
+    // Rolling back discards the condition along with the changes.
+    register.compare_and_set(&0, 5)?;
+    register.rollback();
+    register.set(4);
+    save_view(&context, &mut register).await?;
+
+    let register = RegisterView::<_, u32>::load(context).await?;
+    assert_eq!(*register.get(), 4);
+    Ok(())
+}
+
+/// Tests that only one of two concurrent writers comparing with the same value succeeds.
+#[tokio::test]
+async fn test_compare_and_set_with_concurrent_writers() -> anyhow::Result<()> {
+    let context = create_test_memory_context();
+    let mut first = RegisterView::<_, u32>::load(context.clone()).await?;
+    let mut second = RegisterView::<_, u32>::load(context.clone()).await?;
+
+    first.compare_and_set(&0, 1)?;
+    second.compare_and_set(&0, 2)?;
+    let mut first_batch = Batch::new();
+    first.flush(&mut first_batch)?;
+    let mut second_batch = Batch::new();
+    second.flush(&mut second_batch)?;
+
+    let (first_result, second_result) = tokio::join!(
+        context.write_batch(first_batch),
+        context.write_batch(second_batch)
+    );
+    let results = (
+        first_result.map_err(ViewError::from),
+        second_result.map_err(ViewError::from),
+    );
+    let expected_value = match results {
+        (Ok(()), Err(ViewError::WriteConflict(_))) => 1,
+        (Err(ViewError::WriteConflict(_)), Ok(())) => 2,
+        results => panic!("Exactly one writer should succeed: {results:?}"),
+    };
+
+    let register = RegisterView::<_, u32>::load(context).await?;
+    assert_eq!(*register.get(), expected_value);
+    Ok(())
+}
+
+/// Tests that [`RegisterView::compare_and_set`] is rejected if the store doesn't support
+/// conditional writes.
+#[tokio::test]
+async fn test_compare_and_set_without_conditional_writes() -> anyhow::Result<()> {
+    let store = ViewContainer::new(create_test_memory_context()).await?;
+    let context = ViewContext::create_root_context(store, ()).await?;
+    let mut register = RegisterView::<_, u32>::load(context).await?;
+
+    assert!(matches!(
+        register.compare_and_set(&0, 5),
+        Err(ViewError::ConditionalWritesNotSupported)
+    ));
+    assert_eq!(*register.get(), 0);
+    Ok(())
+}
+
 /// Saves a [`View`] into the [`MemoryContext<()>`] storage simulation.
 async fn save_view<C>(context: &C, view: &mut impl View<C>) -> anyhow::Result<()>
 where
@@ -5,14 +5,15 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use anyhow::Result;
 use linera_views::{
+    batch::Batch,
     bucket_queue_view::HashedBucketQueueView,
-    collection_view::HashedCollectionView,
+    collection_view::{CollectionView, HashedCollectionView},
     context::{create_test_memory_context, Context},
     key_value_store_view::{KeyValueStoreView, SizeData},
     map_view::HashedByteMapView,
     queue_view::HashedQueueView,
     random::make_deterministic_rng,
-    reentrant_collection_view::HashedReentrantCollectionView,
+    reentrant_collection_view::{HashedReentrantCollectionView, ReentrantCollectionView},
     register_view::RegisterView,
     views::{CryptoHashRootView, CryptoHashView, RootView, View, ViewError},
 };
@@ -119,6 +120,21 @@ async fn classic_collection_view_check() -> Result<()> {
             // Checking the keys
             let key_values = view.key_values().await;
             assert_eq!(key_values, new_map);
+            // Checking the pages of indices
+            let page_size = rng.gen_range(1..5);
+            let mut indices = Vec::new();
+            loop {
+                let page = view
+                    .v
+                    .indices_after(indices.last(), Some(page_size))
+                    .await?;
+                let is_last_page = page.len() < page_size;
+                indices.extend(page);
+                if is_last_page {
+                    break;
+                }
+            }
+            assert_eq!(indices, new_map.keys().copied().collect::<Vec<_>>());
         }
         if save {
             if map != new_map {
@@ -132,6 +148,99 @@ async fn classic_collection_view_check() -> Result<()> {
     Ok(())
 }
 
+#[derive(CryptoHashRootView)]
+struct UnhashedCollectionStateView<C> {
+    pub v: CollectionView<C, u8, RegisterView<C, u32>>,
+}
+
+#[tokio::test]
+async fn collection_view_cached_hashes_check() -> Result<()> {
+    let context = create_test_memory_context();
+    let mut rng = make_deterministic_rng();
+    let nmax: u8 = 25;
+    let mut view = UnhashedCollectionStateView::load(context.clone()).await?;
+    for _ in 0..50 {
+        let count_oper = rng.gen_range(0..10);
+        for _ in 0..count_oper {
+            let pos = rng.gen_range(0..nmax);
+            match rng.gen_range(0..4) {
+                0 => view.v.remove_entry(&pos)?,
+                1 => *view.v.load_entry_mut(&pos).await?.get_mut() = rng.gen(),
+                2 => view.v.reset_entry_to_default(&pos)?,
+                _ => view.clear(),
+            }
+            // The hashes of the entries are cached, and must be invalidated by the next changes.
+            if rng.gen::<bool>() {
+                view.crypto_hash().await?;
+            } else {
+                view.crypto_hash_mut().await?;
+            }
+        }
+        if rng.gen::<bool>() {
+            view.save().await?;
+        } else {
+            view.rollback();
+        }
+        let reloaded_view = UnhashedCollectionStateView::load(context.clone()).await?;
+        assert_eq!(
+            view.crypto_hash().await?,
+            reloaded_view.crypto_hash().await?
+        );
+    }
+    Ok(())
+}
+
+#[derive(CryptoHashRootView)]
+struct UnhashedReentrantCollectionStateView<C> {
+    pub v: ReentrantCollectionView<C, u8, RegisterView<C, u32>>,
+}
+
+#[tokio::test]
+async fn reentrant_collection_view_cached_hashes_check() -> Result<()> {
+    let context = create_test_memory_context();
+    let mut rng = make_deterministic_rng();
+    let nmax: u8 = 25;
+    let mut view = UnhashedReentrantCollectionStateView::load(context.clone()).await?;
+    for _ in 0..50 {
+        let count_oper = rng.gen_range(0..10);
+        for _ in 0..count_oper {
+            let pos = rng.gen_range(0..nmax);
+            match rng.gen_range(0..5) {
+                0 => view.v.remove_entry(&pos)?,
+                1 => *view.v.try_load_entry_mut(&pos).await?.get_mut() = rng.gen(),
+                2 => {
+                    let other_pos = rng.gen_range(0..nmax);
+                    if other_pos != pos {
+                        let mut entries = view.v.try_load_entries_mut(&[pos, other_pos]).await?;
+                        for entry in &mut entries {
+                            *entry.get_mut() = rng.gen();
+                        }
+                    }
+                }
+                3 => view.v.try_reset_entry_to_default(&pos)?,
+                _ => view.clear(),
+            }
+            // The hashes of the entries are cached, and must be invalidated by the next changes.
+            if rng.gen::<bool>() {
+                view.crypto_hash().await?;
+            } else {
+                view.crypto_hash_mut().await?;
+            }
+        }
+        if rng.gen::<bool>() {
+            view.save().await?;
+        } else {
+            view.rollback();
+        }
+        let reloaded_view = UnhashedReentrantCollectionStateView::load(context.clone()).await?;
+        assert_eq!(
+            view.crypto_hash().await?,
+            reloaded_view.crypto_hash().await?
+        );
+    }
+    Ok(())
+}
+
 #[derive(CryptoHashRootView)]
 pub struct KeyValueStateView<C> {
     pub store: KeyValueStoreView<C>,
@@ -249,6 +358,38 @@ async fn key_value_store_view_mutability() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn key_value_store_view_total_size_after() -> Result<()> {
+    let context = create_test_memory_context();
+    let mut rng = make_deterministic_rng();
+    for _ in 0..40 {
+        let mut view = KeyValueStateView::load(context.clone()).await?;
+        let mut batch = Batch::new();
+        for _ in 0..rng.gen_range(0..15) {
+            let len = rng.gen_range(1..4);
+            let key = (&mut rng)
+                .sample_iter(Uniform::from(0..4))
+                .take(len)
+                .collect::<Vec<_>>();
+            match rng.gen_range(0..4) {
+                0 => batch.delete_key(key),
+                1 => batch.delete_key_prefix(key),
+                _ => {
+                    let value = vec![0; rng.gen_range(0..10)];
+                    batch.put_key_value_bytes(key, value);
+                }
+            }
+        }
+        let total_size_after = view.store.total_size_after(&batch).await?;
+        view.store.write_batch(batch).await?;
+        assert_eq!(total_size_after, view.store.total_size());
+        if rng.gen::<bool>() {
+            view.save().await?;
+        }
+    }
+    Ok(())
+}
+
 #[derive(CryptoHashRootView)]
 pub struct ByteMapStateView<C> {
     pub map: HashedByteMapView<C, u8>,
@@ -370,6 +511,21 @@ async fn run_map_view_mutability<R: RngCore + Clone>(rng: &mut R) -> Result<()>
                 let part_key_values = view.map.key_values_by_prefix(vec![u]).await?;
                 assert_eq!(part_state_vec, part_key_values);
             }
+            let page_size = rng.gen_range(1..5);
+            let mut keys = Vec::new();
+            loop {
+                let start_after = keys.last().cloned();
+                let page = view
+                    .map
+                    .keys_after(start_after.as_deref(), Some(page_size))
+                    .await?;
+                let is_last_page = page.len() < page_size;
+                keys.extend(page);
+                if is_last_page {
+                    break;
+                }
+            }
+            assert_eq!(keys, new_state_map.keys().cloned().collect::<Vec<_>>());
             let keys_vec = all_keys.iter().cloned().collect::<Vec<_>>();
             let values = view.map.multi_get(keys_vec.clone()).await?;
             for i in 0..keys_vec.len() {
@@ -662,7 +818,11 @@ async fn reentrant_collection_view_check() -> Result<()> {
                 }
                 // Only if all indices are distinct can the query be acceptable
                 if set_indices.len() == n_ins {
-                    let mut subviews = view.v.try_load_entries_mut(&indices).await?;
+                    let mut subviews = if rng.gen::<bool>() {
+                        view.v.try_load_entries_mut(&indices).await?
+                    } else {
+                        view.v.try_load_entries_parallel(&indices).await?
+                    };
                     for i in 0..n_ins {
                         let index = indices[i];
                         let value = values[i];
@@ -1,16 +1,18 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(not(target_arch = "wasm32"))]
+use linera_views::file_backed_memory::FileBackedMemoryStore;
 use linera_views::{
     batch::Batch,
     context::{create_test_memory_context, Context as _},
     key_value_store_view::ViewContainer,
     memory::MemoryStore,
-    random::make_deterministic_rng,
-    store::TestKeyValueStore as _,
+    random::{generate_test_namespace, make_deterministic_rng},
+    store::{ReadableKeyValueStore as _, TestKeyValueStore as _, WritableKeyValueStore as _},
     test_utils::{
-        big_read_multi_values, get_random_test_scenarios, run_big_write_read, run_reads,
-        run_writes_from_blank, run_writes_from_state,
+        big_read_multi_values, get_random_test_scenarios, run_big_write_read, run_paginated_reads,
+        run_reads, run_writes_from_blank, run_writes_from_state,
     },
     value_splitting::create_value_splitting_memory_store,
 };
@@ -83,6 +85,50 @@ async fn test_reads_dynamo_db() {
     }
 }
 
+#[cfg(with_dynamodb)]
+#[tokio::test]
+async fn test_reads_dynamo_db_strong_consistency() {
+    use linera_views::{
+        dynamo_db::{DynamoDbConsistency, DynamoDbStore, ReadConsistency},
+        store::AdminKeyValueStore as _,
+    };
+    let consistency = DynamoDbConsistency {
+        get_item: ReadConsistency::Strong,
+        query: ReadConsistency::Strong,
+    };
+    let config = DynamoDbStore::new_test_config()
+        .await
+        .unwrap()
+        .with_consistency(consistency);
+    for scenario in get_random_test_scenarios() {
+        let namespace = generate_test_namespace();
+        let store = DynamoDbStore::recreate_and_connect(&config, &namespace, &[])
+            .await
+            .unwrap();
+        run_reads(store, scenario).await;
+    }
+}
+
+#[cfg(with_dynamodb)]
+#[tokio::test]
+async fn test_reads_dynamo_db_on_demand() {
+    use linera_views::{
+        dynamo_db::{DynamoDbCapacity, DynamoDbStore},
+        store::AdminKeyValueStore as _,
+    };
+    let config = DynamoDbStore::new_test_config()
+        .await
+        .unwrap()
+        .with_capacity(DynamoDbCapacity::OnDemand);
+    for scenario in get_random_test_scenarios() {
+        let namespace = generate_test_namespace();
+        let store = DynamoDbStore::recreate_and_connect(&config, &namespace, &[])
+            .await
+            .unwrap();
+        run_reads(store, scenario).await;
+    }
+}
+
 #[cfg(with_scylladb)]
 #[tokio::test]
 async fn test_reads_scylla_db() {
@@ -103,6 +149,89 @@ async fn test_reads_indexed_db() {
     }
 }
 
+#[tokio::test]
+async fn test_paginated_reads_test_memory() {
+    for scenario in get_random_test_scenarios() {
+        let key_value_store = create_value_splitting_memory_store();
+        run_paginated_reads(key_value_store, scenario).await;
+    }
+}
+
+#[tokio::test]
+async fn test_paginated_reads_memory() {
+    for scenario in get_random_test_scenarios() {
+        let store = MemoryStore::new_test_store().await.unwrap();
+        run_paginated_reads(store, scenario).await;
+    }
+}
+
+#[cfg(with_rocksdb)]
+#[tokio::test]
+async fn test_paginated_reads_rocks_db() {
+    for scenario in get_random_test_scenarios() {
+        let store = linera_views::rocks_db::RocksDbStore::new_test_store()
+            .await
+            .unwrap();
+        run_paginated_reads(store, scenario).await;
+    }
+}
+
+#[cfg(with_rocksdb)]
+#[tokio::test]
+async fn test_rocks_db_snapshot() {
+    let store = linera_views::rocks_db::RocksDbStore::new_test_store()
+        .await
+        .unwrap();
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(vec![0, 1], vec![1]);
+    batch.put_key_value_bytes(vec![0, 2], vec![2]);
+    store.write_batch(batch).await.unwrap();
+    let snapshot = store.snapshot();
+
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(vec![0, 1], vec![3]);
+    batch.delete_key(vec![0, 2]);
+    batch.put_key_value_bytes(vec![0, 3], vec![4]);
+    store.write_batch(batch).await.unwrap();
+
+    assert_eq!(
+        snapshot.read_value_bytes(&[0, 1]).await.unwrap(),
+        Some(vec![1])
+    );
+    assert!(snapshot.contains_key(&[0, 2]).await.unwrap());
+    assert!(!snapshot.contains_key(&[0, 3]).await.unwrap());
+    assert_eq!(
+        snapshot.find_key_values_by_prefix(&[0]).await.unwrap(),
+        vec![(vec![1], vec![1]), (vec![2], vec![2])]
+    );
+    assert_eq!(
+        store.find_key_values_by_prefix(&[0]).await.unwrap(),
+        vec![(vec![1], vec![3]), (vec![3], vec![4])]
+    );
+}
+
+#[cfg(with_dynamodb)]
+#[tokio::test]
+async fn test_paginated_reads_dynamo_db() {
+    for scenario in get_random_test_scenarios() {
+        let store = linera_views::dynamo_db::DynamoDbStore::new_test_store()
+            .await
+            .unwrap();
+        run_paginated_reads(store, scenario).await;
+    }
+}
+
+#[cfg(with_scylladb)]
+#[tokio::test]
+async fn test_paginated_reads_scylla_db() {
+    for scenario in get_random_test_scenarios() {
+        let store = linera_views::scylla_db::ScyllaDbStore::new_test_store()
+            .await
+            .unwrap();
+        run_paginated_reads(store, scenario).await;
+    }
+}
+
 #[tokio::test]
 async fn test_reads_key_value_store_view_memory() {
     for scenario in get_random_test_scenarios() {
@@ -124,6 +253,52 @@ async fn test_specific_reads_memory() {
     run_reads(store, key_values).await;
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_file_backed_memory_store_reload() {
+    use std::io::Write as _;
+
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("store.bcs");
+    let key_values = linera_views::test_utils::get_random_key_values2(100, 8, 40);
+    let store = FileBackedMemoryStore::new(path.clone(), 10, &generate_test_namespace(), &[])
+        .await
+        .unwrap();
+    run_writes_from_blank(&store).await;
+    let mut batch = Batch::new();
+    for (key, value) in &key_values {
+        batch.put_key_value_bytes(key.clone(), value.clone());
+    }
+    store.write_batch(batch).await.unwrap();
+    let mut batch = Batch::new();
+    batch.delete_key_prefix(vec![0]);
+    store.write_batch(batch).await.unwrap();
+    let expected = store.find_key_values_by_prefix(&[]).await.unwrap();
+    // The last batch is still in the log, and a record interrupted while it was written is
+    // ignored.
+    let log_path = path.with_extension("log");
+    assert!(std::fs::metadata(&log_path).unwrap().len() > 0);
+    let mut log = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&log_path)
+        .unwrap();
+    log.write_all(&[10, 0, 0, 0, 1, 2]).unwrap();
+    // A store in another namespace only has the data of the files.
+    let reloaded_store =
+        FileBackedMemoryStore::new(path.clone(), 10, &generate_test_namespace(), &[])
+            .await
+            .unwrap();
+    let key_values = reloaded_store.find_key_values_by_prefix(&[]).await.unwrap();
+    assert_eq!(key_values, expected);
+    // The log was compacted into the snapshot when the store was loaded.
+    assert_eq!(std::fs::metadata(&log_path).unwrap().len(), 0);
+    let reloaded_store = FileBackedMemoryStore::new(path, 10, &generate_test_namespace(), &[])
+        .await
+        .unwrap();
+    let key_values = reloaded_store.find_key_values_by_prefix(&[]).await.unwrap();
+    assert_eq!(key_values, expected);
+}
+
 #[tokio::test]
 async fn test_test_memory_writes_from_blank() {
     let key_value_store = create_value_splitting_memory_store();
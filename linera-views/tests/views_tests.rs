@@ -18,24 +18,30 @@ use linera_views::{
         Batch, WriteOperation,
         WriteOperation::{Delete, DeletePrefix, Put},
     },
+    codec::ValueCodec,
     collection_view::HashedCollectionView,
-    context::{create_test_memory_context, Context, MemoryContext, ViewContext},
+    context::{
+        create_test_memory_context, Context, MemoryContext, TransactionalContext, ViewContext,
+    },
     key_value_store_view::{KeyValueStoreView, ViewContainer},
-    log_view::HashedLogView,
+    log_view::{CodecLogView, HashedLogView, LogView},
     lru_caching::{LruCachingMemoryStore, LruCachingStore},
-    map_view::{ByteMapView, HashedMapView},
+    map_view::{ByteMapView, CodecMapView, HashedMapView, MapView},
     memory::MemoryStore,
-    queue_view::HashedQueueView,
+    queue_view::{CodecQueueView, HashedQueueView, QueueView},
     random::make_deterministic_rng,
     reentrant_collection_view::HashedReentrantCollectionView,
-    register_view::HashedRegisterView,
+    register_view::{CodecRegisterView, HashedRegisterView, RegisterView},
     set_view::HashedSetView,
-    store::TestKeyValueStore as _,
+    store::{KeyValueIterable as _, TestKeyValueStore as _},
     test_utils::{
         get_random_byte_vector, get_random_key_value_operations, get_random_key_values,
         span_random_reordering_put_delete,
     },
-    views::{CryptoHashRootView, HashableView, Hasher, RootView, View, ViewError},
+    views::{
+        CryptoHashRootView, GraphQLView, HashableView, Hasher, RootView, StorageStats, View,
+        ViewError,
+    },
 };
 use rand::{Rng, RngCore};
 
@@ -652,6 +658,265 @@ async fn test_byte_map_view() -> Result<()> {
     Ok(())
 }
 
+#[derive(RootView, GraphQLView)]
+pub struct GraphQLStateView<C> {
+    pub total_supply: RegisterView<C, u64>,
+    pub history: LogView<C, u32>,
+    pub balances: MapView<C, String, u64>,
+    #[graphql(skip)]
+    pub hidden: RegisterView<C, u64>,
+}
+
+#[tokio::test]
+async fn test_graphql_view() -> Result<()> {
+    let context = create_test_memory_context();
+    let mut view = GraphQLStateView::load(context).await?;
+    view.total_supply.set(100);
+    for entry in [1, 2, 3, 4] {
+        view.history.push(entry);
+    }
+    view.balances.insert("alice", 60)?;
+    view.balances.insert("bob", 40)?;
+    view.hidden.set(7);
+    view.save().await?;
+
+    let schema = async_graphql::Schema::new(
+        view,
+        async_graphql::EmptyMutation,
+        async_graphql::EmptySubscription,
+    );
+    let response = schema
+        .execute("{ totalSupply history(offset: 1, count: 2) balances(count: 1) { key value } }")
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data,
+        async_graphql::value!({
+            "totalSupply": 100,
+            "history": [2, 3],
+            "balances": [{ "key": "bob", "value": 40 }],
+        })
+    );
+
+    // Map entries are paged in the order of their serialized keys.
+    let response = schema
+        .execute(r#"{ balances(after: "bob") { key value } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data,
+        async_graphql::value!({
+            "balances": [{ "key": "alice", "value": 60 }],
+        })
+    );
+
+    let response = schema.execute("{ hidden }").await;
+    assert!(!response.errors.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transactional_context() -> Result<()> {
+    let context = create_test_memory_context();
+    let chain_context = context.clone_with_base_key(vec![0]);
+    let application_context = context.clone_with_base_key(vec![1]);
+    let mut chain = ByteMapStateView::load(chain_context.clone()).await?;
+    let mut application = GraphQLStateView::load(application_context.clone()).await?;
+    chain.map.insert(vec![0, 1], 5);
+    application.total_supply.set(100);
+    application.balances.insert("alice", 100)?;
+
+    let mut transaction = TransactionalContext::new(context.clone());
+    assert!(transaction.is_empty());
+    transaction.flush(&mut chain)?;
+    transaction.flush(&mut application)?;
+    assert!(!transaction.is_empty());
+
+    // Nothing is written before the transaction is committed.
+    let loaded = GraphQLStateView::load(application_context.clone()).await?;
+    assert_eq!(*loaded.total_supply.get(), 0);
+    assert!(!chain.has_pending_changes().await);
+
+    transaction.commit().await?;
+    let chain = ByteMapStateView::load(chain_context).await?;
+    assert_eq!(chain.map.get(&[0, 1]).await?, Some(5));
+    let application = GraphQLStateView::load(application_context).await?;
+    assert_eq!(*application.total_supply.get(), 100);
+    assert_eq!(application.balances.get("alice").await?, Some(100));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_storage_stats() -> Result<()> {
+    let context = create_test_memory_context();
+    let mut view = GraphQLStateView::load(context.clone_with_base_key(vec![0])).await?;
+    view.total_supply.set(100);
+    view.balances.insert("alice", 100)?;
+    view.balances.insert("bob", 5)?;
+
+    // Changes are only counted once they are saved.
+    assert_eq!(view.storage_stats().await?, StorageStats::default());
+    view.save().await?;
+    let total_supply = view.total_supply.storage_stats().await?;
+    let balances = view.balances.storage_stats().await?;
+    assert_eq!(total_supply.key_count, 1);
+    assert_eq!(balances.key_count, 2);
+    assert_eq!(view.history.storage_stats().await?, StorageStats::default());
+    assert_eq!(
+        view.storage_stats().await?,
+        StorageStats {
+            key_count: 3,
+            total_size: total_supply.total_size + balances.total_size,
+        }
+    );
+
+    let store_context = context.clone_with_base_key(vec![1]);
+    let mut store = KeyValueStoreView::load(store_context.clone()).await?;
+    store.insert(vec![0], vec![1, 2, 3]).await?;
+    store.insert(vec![1, 2], vec![4]).await?;
+    let mut batch = Batch::new();
+    store.flush(&mut batch)?;
+    store_context.write_batch(batch).await?;
+    assert_eq!(
+        store.storage_stats().await?,
+        StorageStats {
+            key_count: 2,
+            total_size: 7,
+        }
+    );
+
+    // The storage used by a log is counted when requested, without any key of its own.
+    let log_context = context.clone_with_base_key(vec![2]);
+    let mut log = LogView::<_, String>::load(log_context.clone()).await?;
+    log.push("hello".to_string());
+    log.push("world!".to_string());
+    let mut batch = Batch::new();
+    log.flush(&mut batch)?;
+    log_context.write_batch(batch).await?;
+    let prefix = log_context.base_key();
+    let mut scanned = StorageStats::default();
+    let key_values = log_context.find_key_values_by_prefix(&prefix).await?;
+    for entry in key_values.iterator() {
+        let (key, value) = entry?;
+        scanned.add_entry(prefix.len() + key.len(), value.len());
+    }
+    assert_eq!(scanned.key_count, 3);
+    assert_eq!(log.storage_stats().await?, scanned);
+    Ok(())
+}
+
+/// Stores a `u64` as 8 big-endian bytes.
+struct BigEndian;
+
+impl ValueCodec for BigEndian {
+    type Value = u64;
+
+    fn encode(value: &u64) -> Result<Vec<u8>, ViewError> {
+        Ok(value.to_be_bytes().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<u64, ViewError> {
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| ViewError::CodecError(format!("expected 8 bytes, got {}", bytes.len())))?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+}
+
+#[tokio::test]
+async fn test_register_view_with_custom_codec() -> Result<()> {
+    let context = create_test_memory_context();
+    let mut register = CodecRegisterView::<_, BigEndian>::load(context.clone()).await?;
+    assert_eq!(*register.get(), 0);
+    register.set(0x0102);
+    let mut batch = Batch::new();
+    register.flush(&mut batch)?;
+    context.write_batch(batch).await?;
+
+    let bytes = context.read_value_bytes(&context.base_key()).await?;
+    assert_eq!(bytes, Some(vec![0, 0, 0, 0, 0, 0, 1, 2]));
+    let register = CodecRegisterView::<_, BigEndian>::load(context.clone()).await?;
+    assert_eq!(*register.get(), 0x0102);
+
+    // The same value stored with the default codec has a different encoding.
+    let register = RegisterView::<_, u64>::load(context.clone()).await?;
+    assert_eq!(*register.get(), 0x0201_0000_0000_0000);
+
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(context.base_key(), vec![1, 2]);
+    context.write_batch(batch).await?;
+    assert!(matches!(
+        CodecRegisterView::<_, BigEndian>::load(context).await,
+        Err(ViewError::CodecError(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_log_and_queue_views_with_custom_codec() -> Result<()> {
+    let context = create_test_memory_context();
+    let mut log = CodecLogView::<_, BigEndian>::load(context.clone()).await?;
+    log.push(0x0102);
+    let mut batch = Batch::new();
+    log.flush(&mut batch)?;
+    context.write_batch(batch).await?;
+
+    let log = CodecLogView::<_, BigEndian>::load(context.clone()).await?;
+    assert_eq!(log.read(..).await?, vec![0x0102]);
+    let log = LogView::<_, u64>::load(context).await?;
+    assert_eq!(log.get(0).await?, Some(0x0201_0000_0000_0000));
+
+    let context = create_test_memory_context();
+    let mut queue = CodecQueueView::<_, BigEndian>::load(context.clone()).await?;
+    queue.push_back(0x0102);
+    let mut batch = Batch::new();
+    queue.flush(&mut batch)?;
+    context.write_batch(batch).await?;
+
+    let queue = CodecQueueView::<_, BigEndian>::load(context.clone()).await?;
+    assert_eq!(queue.elements().await?, vec![0x0102]);
+    let queue = QueueView::<_, u64>::load(context).await?;
+    assert_eq!(queue.front().await?, Some(0x0201_0000_0000_0000));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_map_view_with_custom_codec() -> Result<()> {
+    let context = create_test_memory_context();
+    let mut map = CodecMapView::<_, u8, BigEndian>::load(context.clone()).await?;
+    map.insert(&1_u8, 0x0102)?;
+    let mut batch = Batch::new();
+    map.flush(&mut batch)?;
+    context.write_batch(batch).await?;
+
+    let map = CodecMapView::<_, u8, BigEndian>::load(context.clone()).await?;
+    assert_eq!(map.get(&1_u8).await?, Some(0x0102));
+    assert_eq!(map.index_values().await?, vec![(1, 0x0102)]);
+    let map = MapView::<_, u8, u64>::load(context).await?;
+    assert_eq!(map.get(&1_u8).await?, Some(0x0201_0000_0000_0000));
+    Ok(())
+}
+
+/// Tests that the hashes of the views storing their values with the default codec are still
+/// computed from the BCS serialization of the values, including for long sequences.
+#[tokio::test]
+async fn test_hashes_with_default_codec() -> Result<()> {
+    let values = (0..200).collect::<Vec<u64>>();
+    let mut hasher = sha3::Sha3_256::default();
+    hasher.update_with_bcs_bytes(&values)?;
+    let expected_hash = hasher.finalize();
+
+    let mut log = LogView::<_, u64>::load(create_test_memory_context()).await?;
+    let mut queue = QueueView::<_, u64>::load(create_test_memory_context()).await?;
+    for value in &values {
+        log.push(*value);
+        queue.push_back(*value);
+    }
+    assert_eq!(log.hash().await?, expected_hash);
+    assert_eq!(queue.hash().await?, expected_hash);
+    Ok(())
+}
+
 #[cfg(test)]
 async fn test_views_in_lru_memory_param(config: &TestConfig) -> Result<()> {
     tracing::warn!("Testing config {:?} with lru memory", config);
@@ -0,0 +1,261 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `wasm-bindgen` bindings to run a Linera client in the browser.
+//!
+//! The client keeps its chains in memory, and talks to the validators directly, so that web
+//! frontends can sign and submit blocks without a local `linera service`.
+
+use std::{future::Future, str::FromStr, sync::Arc};
+
+use clap::Parser as _;
+use futures::lock::Mutex;
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::Amount,
+    identifiers::{Account, ApplicationId, ChainId},
+};
+use linera_chain::types::ConfirmedBlockCertificate;
+use linera_client::{
+    chain_listener::{self, ChainListener, ChainListenerConfig},
+    client_context::ClientContext,
+    client_options::ClientOptions,
+    persistent, util,
+    wallet::Wallet,
+};
+use linera_core::{
+    client::{ChainClient, ChainClientError},
+    data_types::ClientOutcome,
+};
+use linera_execution::{Query, QueryOutcome, QueryResponse};
+use linera_rpc::node_provider::NodeProvider;
+use linera_storage::{DbStorage, WallClock};
+use linera_views::memory::{MemoryStore, MemoryStoreConfig};
+use wasm_bindgen::prelude::*;
+
+type WebStorage = DbStorage<MemoryStore, WallClock>;
+
+type WebClientContext = ClientContext<WebStorage, persistent::Memory<Wallet>>;
+
+type WebChainClient = ChainClient<NodeProvider, WebStorage>;
+
+const ROOT_KEY: &[u8] = &[0];
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &str = r#"
+/** A chain ID, as a hexadecimal string. */
+export type ChainId = string;
+/** The hash of a block, as a hexadecimal string. */
+export type BlockHash = string;
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Promise<string>")]
+    pub type StringPromise;
+
+    #[wasm_bindgen(typescript_type = "Promise<ChainId>")]
+    pub type ChainIdPromise;
+
+    #[wasm_bindgen(typescript_type = "Promise<BlockHash>")]
+    pub type BlockHashPromise;
+}
+
+#[wasm_bindgen(start)]
+pub fn main() {
+    console_error_panic_hook::set_once();
+}
+
+fn js_error(error: impl std::fmt::Display) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+/// Runs `future` as a JavaScript promise, with the type `P` in the TypeScript bindings.
+fn promise<P: JsCast>(future: impl Future<Output = Result<String, JsError>> + 'static) -> P {
+    wasm_bindgen_futures::future_to_promise(async move {
+        future.await.map(JsValue::from).map_err(JsValue::from)
+    })
+    .unchecked_into()
+}
+
+/// A Linera client running in the browser.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct Client {
+    context: Arc<Mutex<WebClientContext>>,
+}
+
+#[wasm_bindgen]
+impl Client {
+    /// Creates a client from the JSON of a wallet, as written by `linera wallet init`, and
+    /// starts listening to the notifications of the wallet's chains.
+    #[wasm_bindgen(js_name = create)]
+    pub async fn new(wallet: String) -> Result<Client, JsError> {
+        let wallet = serde_json::from_str::<Wallet>(&wallet).map_err(js_error)?;
+        // The client options are only used for their default values, so any command will do.
+        let options = ClientOptions::try_parse_from(["linera", "keygen"]).map_err(js_error)?;
+        let store_config = MemoryStoreConfig::new(options.max_stream_queries);
+        let mut storage = WebStorage::new(store_config, "linera", ROOT_KEY, options.wasm_runtime)
+            .await
+            .map_err(js_error)?;
+        wallet
+            .genesis_config()
+            .initialize_storage(&mut storage)
+            .await
+            .map_err(js_error)?;
+        let context = ClientContext::new(storage.clone(), options, persistent::Memory::new(wallet));
+        let context = Arc::new(Mutex::new(context));
+        ChainListener::new(ChainListenerConfig::default())
+            .run(context.clone(), storage)
+            .await;
+        Ok(Client { context })
+    }
+
+    /// Returns the JSON of the wallet, including the chains and keys added since the client
+    /// was created.
+    pub fn wallet(&self) -> StringPromise {
+        let this = self.clone();
+        promise(async move {
+            serde_json::to_string(this.context.lock().await.wallet()).map_err(js_error)
+        })
+    }
+
+    /// Returns the ID of the default chain of the wallet.
+    #[wasm_bindgen(js_name = defaultChain)]
+    pub fn default_chain(&self) -> ChainIdPromise {
+        let this = self.clone();
+        promise(async move { Ok(this.context.lock().await.default_chain().to_string()) })
+    }
+
+    /// Returns the owner that signs the blocks of the default chain.
+    pub fn identity(&self) -> StringPromise {
+        let this = self.clone();
+        promise(async move {
+            let client = this.default_chain_client().await?;
+            Ok(client.identity().await.map_err(js_error)?.to_string())
+        })
+    }
+
+    /// Returns the balance of the default chain.
+    pub fn balance(&self) -> StringPromise {
+        let this = self.clone();
+        promise(async move {
+            let client = this.default_chain_client().await?;
+            Ok(client.query_balance().await.map_err(js_error)?.to_string())
+        })
+    }
+
+    /// Transfers `amount` native tokens from the default chain to the `recipient` account,
+    /// formatted as `<chain ID>` or `<owner>@<chain ID>`, and returns the hash of the block.
+    pub fn transfer(&self, recipient: String, amount: String) -> BlockHashPromise {
+        let this = self.clone();
+        promise(async move {
+            let recipient = Account::from_str(&recipient).map_err(js_error)?;
+            let amount = Amount::from_str(&amount).map_err(js_error)?;
+            let client = this.default_chain_client().await?;
+            let hash = this
+                .execute(&client, || {
+                    client.transfer_to_account(None, amount, recipient)
+                })
+                .await?;
+            Ok(hash.to_string())
+        })
+    }
+
+    /// Returns the application with the given ID, to query it on the default chain.
+    pub fn application(&self, id: &str) -> Result<Application, JsError> {
+        Ok(Application {
+            client: self.clone(),
+            id: ApplicationId::from_str(id).map_err(js_error)?,
+        })
+    }
+}
+
+impl Client {
+    async fn default_chain_client(&self) -> Result<WebChainClient, JsError> {
+        let context = self.context.lock().await;
+        let chain_id: ChainId = context.default_chain();
+        chain_listener::ClientContext::make_chain_client(&*context, chain_id).map_err(js_error)
+    }
+
+    /// Proposes a block with `propose`, retrying in the next round until it is committed, and
+    /// saves the new state of the chain in the wallet.
+    async fn execute<F, Fut>(
+        &self,
+        client: &WebChainClient,
+        propose: F,
+    ) -> Result<CryptoHash, JsError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<ClientOutcome<ConfirmedBlockCertificate>, ChainClientError>>,
+    {
+        let hash = loop {
+            let timeout = match propose().await.map_err(js_error)? {
+                ClientOutcome::Committed(certificate) => break certificate.hash(),
+                ClientOutcome::WaitForTimeout(timeout) => timeout,
+            };
+            let mut stream = client.subscribe().await.map_err(js_error)?;
+            util::wait_for_next_round(&mut stream, timeout).await;
+        };
+        let mut context = self.context.lock().await;
+        chain_listener::ClientContext::update_wallet(&mut *context, client)
+            .await
+            .map_err(js_error)?;
+        Ok(hash)
+    }
+
+    /// Queries a user application on the default chain, and executes the operations that it
+    /// scheduled in a new block.
+    async fn query_application(
+        &self,
+        application_id: ApplicationId,
+        request: &str,
+    ) -> Result<String, JsError> {
+        let request = serde_json::from_str::<async_graphql::Request>(request).map_err(js_error)?;
+        let query = Query::user_without_abi(application_id, &request).map_err(js_error)?;
+        let client = self.default_chain_client().await?;
+        let QueryOutcome {
+            response,
+            operations,
+        } = client.query_application(query).await.map_err(js_error)?;
+        let QueryResponse::User(response) = response else {
+            unreachable!("cannot get a system response for a user query");
+        };
+        if operations.is_empty() {
+            return String::from_utf8(response).map_err(js_error);
+        }
+        let graphql_response =
+            serde_json::from_slice::<async_graphql::Response>(&response).map_err(js_error)?;
+        if let Some(error) = graphql_response.errors.first() {
+            return Err(js_error(error));
+        }
+        let hash = self
+            .execute(&client, || {
+                client.execute_operations(operations.clone(), vec![])
+            })
+            .await?;
+        let response = async_graphql::Response::new(async_graphql::Value::String(hash.to_string()));
+        serde_json::to_string(&response).map_err(js_error)
+    }
+}
+
+/// An application, queried on the default chain of a [`Client`].
+#[wasm_bindgen]
+pub struct Application {
+    client: Client,
+    id: ApplicationId,
+}
+
+#[wasm_bindgen]
+impl Application {
+    /// Sends a GraphQL request, as JSON, to the application's service, and returns the JSON
+    /// response.
+    ///
+    /// If the request is a mutation, the operations it scheduled are signed and submitted in a
+    /// new block, and the data of the response is the hash of that block instead.
+    pub fn query(&self, request: String) -> StringPromise {
+        let client = self.client.clone();
+        let id = self.id;
+        promise(async move { client.query_application(id, &request).await })
+    }
+}